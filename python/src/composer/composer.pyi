@@ -5,7 +5,8 @@ This file provides type annotations for the compiled Rust extension,
 enabling static type checking with tools like mypy, pyright, and pylance.
 """
 
-from typing import Any
+from collections.abc import Iterator
+from typing import Any, Literal, overload
 
 # Core Classes
 
@@ -46,6 +47,10 @@ class Chord:
     def borrowed(self) -> BorrowedScale | None: ...
     @property
     def is_rest(self) -> bool: ...
+    def compatible_alterations(self) -> list[str]: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> Chord: ...
     def __str__(self) -> str: ...
     def __repr__(self) -> str: ...
     def __eq__(self, other: object) -> bool: ...
@@ -76,6 +81,14 @@ class ScaleFingerprint:
     def chromatic_notes(self) -> list[int]: ...
     @property
     def scale_degrees(self) -> list[int]: ...
+    def rotate(self, n: int) -> ScaleFingerprint: ...
+    def intersection(self, other: ScaleFingerprint) -> ScaleFingerprint | None: ...
+    def difference(self, other: ScaleFingerprint) -> ScaleFingerprint | None: ...
+    def contains_chord(self, chord: Chord) -> bool: ...
+    def distance(self, other: ScaleFingerprint) -> int: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> ScaleFingerprint: ...
     def __str__(self) -> str: ...
     def __eq__(self, other: object) -> bool: ...
 
@@ -102,6 +115,16 @@ class PyChordGraphic:
     def adds(self) -> list[str]: ...
     @property
     def omits(self) -> list[str]: ...
+    @property
+    def accidental(self) -> str: ...
+    @property
+    def borrowed_from(self) -> str | None: ...
+    @property
+    def applied_to(self) -> int | None: ...
+    @property
+    def root_offset(self) -> int: ...
+    def is_diatonic(self) -> bool: ...
+    def full_symbol(self) -> str: ...
     def __str__(self) -> str: ...
     def __repr__(self) -> str: ...
 
@@ -122,38 +145,264 @@ class AiEngine:
 
     def __init__(self) -> None: ...
     def initialize(self, training_data: list[Any]) -> bool: ...
+    def initialize_from_tokens(
+        self,
+        library: TokenLibrary,
+        token_patterns: list[tuple[list[str], str, str | None]],
+    ) -> None: ...
     def is_initialized(self) -> bool: ...
+    def add_training_pattern(
+        self,
+        pattern: list[Chord],
+        source_id: str,
+        key_tonic: str | None,
+        weight: float = 1.0,
+    ) -> None: ...
+    def add_patterns_bulk(
+        self, patterns: list[tuple[list[Chord], str, str | None]], weight: float = 1.0
+    ) -> dict[str, Any]: ...
+    @staticmethod
+    def augment_patterns(
+        patterns: list[tuple[list[Chord], str, str | None]],
+        transpose: bool = True,
+        invert: bool = True,
+        vary_extensions: bool = True,
+        time_stretch: list[float] | None = None,
+    ) -> list[tuple[list[Chord], str, str | None]]: ...
+    def add_timeline_patterns(
+        self,
+        patterns: list[tuple[Timeline, str, str | None]],
+        weight_by_duration: bool = False,
+    ) -> dict[str, Any]: ...
+    def get_chord_suggestions(
+        self,
+        pattern: list[Chord],
+        context: SuggestionContext | None = None,
+        config: SuggestionConfig | None = None,
+        **overrides: Any,
+    ) -> list[ChordSuggestion]: ...
+    def continue_progression(
+        self,
+        seed: list[Chord],
+        n: int,
+        context: SuggestionContext | None = None,
+        config: SuggestionConfig | None = None,
+        temperature: float = 1.0,
+        random_seed: int = 0,
+        target_cadence: bool = True,
+    ) -> list[ChordSuggestion]: ...
+    def assess_difficulty(
+        self,
+        progression: list[Chord],
+        tempo_bpm: float | None = None,
+        time_signature: tuple[int, int] | None = None,
+    ) -> DifficultyAssessment: ...
+    def assess_timeline_difficulty(
+        self,
+        timeline: Timeline,
+        tempo_bpm: float | None = None,
+        time_signature: tuple[int, int] | None = None,
+    ) -> DifficultyAssessment: ...
+    def assess_melody_difficulty(
+        self,
+        progression: list[Chord],
+        melody: list[Note],
+        tempo_bpm: float | None = None,
+        time_signature: tuple[int, int] | None = None,
+    ) -> DifficultyAssessment: ...
+    def analyze_melody(self, melody: list[Note]) -> dict[str, Any]: ...
+    def assess_instrument_difficulty(
+        self,
+        progression: list[Chord],
+        instrument: str,
+        tempo_bpm: float | None = None,
+        time_signature: tuple[int, int] | None = None,
+    ) -> DifficultyAssessment: ...
+    def assess_difficulty_by_instrument(
+        self,
+        progression: list[Chord],
+        tempo_bpm: float | None = None,
+        time_signature: tuple[int, int] | None = None,
+    ) -> dict[str, DifficultyAssessment]: ...
+    def extract_difficulty_features(
+        self,
+        progression: list[Chord],
+        tempo_bpm: float | None = None,
+        time_signature: tuple[int, int] | None = None,
+    ) -> list[float]: ...
+    def set_difficulty_model(self, model_json: str | None = None) -> None: ...
+    def set_skill_thresholds(
+        self, bands: list[tuple[str, str, float]] | None = None
+    ) -> None: ...
+    def skill_thresholds(self) -> list[tuple[str, str, float]]: ...
+    def calibrate_difficulty(
+        self, corpus_assessments: list[DifficultyAssessment] | None = None
+    ) -> None: ...
+    def fit_difficulty_model(self, examples: list[tuple[list[Chord], float]]) -> str: ...
+    def set_polynomial_model(self, model_json: str | None = None) -> None: ...
+    def polynomial_model(self) -> str: ...
+    def similar_chords(
+        self, chord: Chord, k: int = 5
+    ) -> list[tuple[Chord, float, int]]: ...
+    def chord_similarity(self, a: Chord, b: Chord) -> float: ...
+    def nearest_chords(
+        self, chord: Chord, k: int = 5
+    ) -> list[tuple[Chord, float, int]]: ...
+    def get_substitution_suggestions(
+        self, chord: Chord, scale: ScaleFingerprint
+    ) -> list[tuple[str, Chord, str, int]]: ...
+    def restyle_progression(
+        self, progression: list[Chord], from_genre: str, to_genre: str
+    ) -> list[tuple[list[Chord], int, float]]: ...
+    def similar_progressions(
+        self, progression: list[Chord], k: int = 5
+    ) -> list[tuple[str, str | None, list[Chord], float]]: ...
+    def more_like_source(
+        self, source_id: str, k: int = 5
+    ) -> list[tuple[str, str | None, list[Chord], float]]: ...
+    def estimate_valence(
+        self,
+        progression: list[Chord],
+        scale: ScaleFingerprint | None = None,
+        tempo_bpm: float | None = None,
+    ) -> dict[str, Any]: ...
+    def analyze_form(self, timeline: Timeline) -> dict[str, Any]: ...
+    def compute_novelty(self, progression: list[Chord]) -> dict[str, Any]: ...
+    def analyze_loop(self, progression: list[Chord]) -> dict[str, Any]: ...
+    def suggest_turnarounds(self, progression: list[Chord]) -> list[dict[str, Any]]: ...
+    def render_accompaniment(
+        self,
+        timeline: Timeline,
+        style: str = "Block",
+        density: str = "Moderate",
+        scale: ScaleFingerprint | None = None,
+        tonic: int = 0,
+    ) -> Timeline: ...
+    def suggest_groove(
+        self,
+        timeline: Timeline,
+        density: str = "Moderate",
+        time_signature: tuple[int, int] | None = None,
+    ) -> dict[str, Any]: ...
+    def analyze_harmonic_rhythm(
+        self, chords: list[tuple[Chord, float]]
+    ) -> dict[str, Any]: ...
+    def analyze_timeline_progression(self, timeline: Timeline) -> Any: ...
+    def generate_practice_plan(
+        self, progression: list[Chord], player_level: str
+    ) -> dict[str, Any]: ...
+    def simplify_progression(
+        self, progression: list[Chord], target_level: str
+    ) -> dict[str, Any]: ...
+    def enrich_progression(
+        self, progression: list[Chord], target_level: str
+    ) -> dict[str, Any]: ...
     def get_memory_usage(self) -> int: ...
     def get_total_requests(self) -> int: ...
     def get_average_response_time(self) -> float: ...
     def get_cache_hit_rate(self) -> float: ...
     def get_total_patterns(self) -> int: ...
     def get_uptime_seconds(self) -> int: ...
+    def get_metrics(self) -> dict[str, Any]:
+        """Performance metrics, including ``budget_violations`` by operation."""
+        ...
+    def set_performance_budget(
+        self,
+        chord_lookup_max_ms: int | None = None,
+        asset_loading_max_ms: int | None = None,
+        mode: Literal["warn", "fail"] | None = None,
+    ) -> None:
+        """Set the time limits for suggestion and asset loading calls.
+
+        Arguments left as None keep their current values. In "warn" mode (the
+        default) slow calls return their results and are counted in the
+        ``budget_violations`` of ``get_metrics()``; in "fail" mode they raise.
+        """
+        ...
+    def get_performance_budget(self) -> dict[str, Any]:
+        """Time limits in milliseconds and the budget ``mode``."""
+        ...
+    def compact_trie(self) -> dict[str, int]:
+        """Compact the pattern trie.
+
+        Returns ``nodes``, ``bytes_before``, ``bytes_after`` and ``bytes_saved``.
+        """
+        ...
     def shutdown(self) -> None: ...
 
 class SuggestionContext:
-    """Context for chord suggestions."""
+    """Context for chord suggestions.
 
-    def __init__(self) -> None: ...
+    Omitted arguments take the Rust defaults: no scale, position 0.5,
+    valence 0.0, complexity 0.5, no genre weights, a repetition window of 4,
+    no recent chords and no key tonic.
+    """
+
+    def __init__(
+        self,
+        scale_fingerprint: ScaleFingerprint | None = None,
+        position_in_progression: float | None = None,
+        target_valence: float | None = None,
+        complexity_preference: float | None = None,
+        genre_weights: dict[str, float] | None = None,
+        avoid_repetition_within: int | None = None,
+        recent_chords: list[Chord] | None = None,
+        key_tonic: str | None = None,
+    ) -> None: ...
+    @property
+    def scale_fingerprint(self) -> ScaleFingerprint | None: ...
+    @scale_fingerprint.setter
+    def scale_fingerprint(self, value: ScaleFingerprint | None) -> None: ...
+    @property
+    def position_in_progression(self) -> float: ...
+    @position_in_progression.setter
+    def position_in_progression(self, value: float) -> None: ...
+    @property
+    def target_valence(self) -> float: ...
+    @target_valence.setter
+    def target_valence(self, value: float) -> None: ...
     @property
-    def position(self) -> float: ...
-    @position.setter
-    def position(self, value: float) -> None: ...
+    def complexity_preference(self) -> float: ...
+    @complexity_preference.setter
+    def complexity_preference(self, value: float) -> None: ...
     @property
-    def valence(self) -> float: ...
-    @valence.setter
-    def valence(self, value: float) -> None: ...
+    def genre_weights(self) -> dict[str, float]: ...
+    @genre_weights.setter
+    def genre_weights(self, value: dict[str, float]) -> None: ...
     @property
-    def complexity(self) -> float: ...
-    @complexity.setter
-    def complexity(self, value: float) -> None: ...
+    def avoid_repetition_within(self) -> int: ...
+    @avoid_repetition_within.setter
+    def avoid_repetition_within(self, value: int) -> None: ...
+    @property
+    def recent_chords(self) -> list[Chord]: ...
+    @recent_chords.setter
+    def recent_chords(self, value: list[Chord]) -> None: ...
+    @property
+    def key_tonic(self) -> str | None: ...
+    @key_tonic.setter
+    def key_tonic(self, value: str | None) -> None: ...
     def add_recent_chord(self, chord: Chord) -> None: ...
     def set_genre_weight(self, genre: str, weight: float) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __ne__(self, other: object) -> bool: ...
 
 class SuggestionConfig:
     """Configuration for chord suggestions."""
 
-    def __init__(self) -> None: ...
+    def __init__(
+        self,
+        max_suggestions: int | None = None,
+        min_confidence: float | None = None,
+        search_depth: int | None = None,
+        use_probabilistic: bool = False,
+        temperature: float = 1.0,
+        enable_context_weighting: bool = True,
+        match_mode: Literal["relative", "absolute", "both"] = "relative",
+        diversity_threshold: float | None = None,
+        suggest_inversions: bool = False,
+        chromaticism: float = 0.0,
+        novelty_weight: float = 0.0,
+    ) -> None: ...
     @property
     def max_suggestions(self) -> int: ...
     @max_suggestions.setter
@@ -163,9 +412,41 @@ class SuggestionConfig:
     @min_confidence.setter
     def min_confidence(self, value: float) -> None: ...
     @property
+    def search_depth(self) -> int: ...
+    @search_depth.setter
+    def search_depth(self, value: int) -> None: ...
+    @property
+    def use_probabilistic(self) -> bool: ...
+    @use_probabilistic.setter
+    def use_probabilistic(self, value: bool) -> None: ...
+    @property
     def temperature(self) -> float: ...
     @temperature.setter
     def temperature(self, value: float) -> None: ...
+    @property
+    def enable_context_weighting(self) -> bool: ...
+    @enable_context_weighting.setter
+    def enable_context_weighting(self, value: bool) -> None: ...
+    @property
+    def match_mode(self) -> Literal["relative", "absolute", "both"]: ...
+    @match_mode.setter
+    def match_mode(self, value: Literal["relative", "absolute", "both"]) -> None: ...
+    @property
+    def diversity_threshold(self) -> float: ...
+    @diversity_threshold.setter
+    def diversity_threshold(self, value: float) -> None: ...
+    @property
+    def suggest_inversions(self) -> bool: ...
+    @suggest_inversions.setter
+    def suggest_inversions(self, value: bool) -> None: ...
+    @property
+    def chromaticism(self) -> float: ...
+    @chromaticism.setter
+    def chromaticism(self, value: float) -> None: ...
+    @property
+    def novelty_weight(self) -> float: ...
+    @novelty_weight.setter
+    def novelty_weight(self, value: float) -> None: ...
 
 class ChordSuggestion:
     """A chord suggestion with confidence score."""
@@ -176,6 +457,12 @@ class ChordSuggestion:
     def confidence(self) -> float: ...
     @property
     def reasoning(self) -> str: ...
+    @property
+    def branch(self) -> str | None: ...
+    @property
+    def recommended_inversion(self) -> int | None: ...
+    @property
+    def suggested_duration(self) -> float | None: ...
 
 class DifficultyAssessment:
     """Assessment of chord progression difficulty."""
@@ -185,6 +472,14 @@ class DifficultyAssessment:
     @property
     def skill_level(self) -> str: ...
     @property
+    def skill_band(self) -> str: ...
+    @property
+    def score_interval(self) -> tuple[float, float]: ...
+    @property
+    def evidence(self) -> float: ...
+    @property
+    def insufficient_data(self) -> bool: ...
+    @property
     def confidence(self) -> float: ...
     @property
     def harmonic_complexity(self) -> float: ...
@@ -198,6 +493,18 @@ class DifficultyAssessment:
     def unique_chords(self) -> int: ...
     @property
     def extended_harmonies(self) -> int: ...
+    @property
+    def time_signature_changes(self) -> int: ...
+    @property
+    def instrument(self) -> str | None: ...
+    @property
+    def percentile(self) -> int | None: ...
+    @property
+    def explanation(self) -> dict[str, Any]: ...
+    @property
+    def per_chord_breakdown(self) -> list[dict[str, Any]]: ...
+    @property
+    def melody(self) -> dict[str, Any] | None: ...
 
 class BassHarmonization:
     """Bass harmonization result."""
@@ -231,23 +538,68 @@ class Note:
 class TokenLibrary:
     """Token library for ML tokenization."""
 
-    def __init__(self) -> None: ...
+    def __init__(self, schema_version: int = 2) -> None: ...
+    @property
+    def schema_version(self) -> int: ...
+    def upgrade(self) -> int: ...
+    @overload
+    def export_vocab(self, binary: Literal[False] = False) -> str: ...
+    @overload
+    def export_vocab(self, binary: Literal[True]) -> bytes: ...
+    @staticmethod
+    def import_vocab(data: str | bytes) -> TokenLibrary: ...
     def add_token(self, name: str, data: bytes) -> None: ...
     def resolve_token(self, name: str) -> bytes | None: ...
     def size(self) -> int: ...
 
+class TokenEvent:
+    """A note, chord, note cluster or rest placed at a beat on a timeline."""
+
+    def __init__(
+        self,
+        beat: float = 0.0,
+        note: Note | None = None,
+        chord: Chord | None = None,
+        cluster: list[tuple[int, int, float]] | None = None,
+    ) -> None: ...
+    @property
+    def beat(self) -> float: ...
+    @property
+    def kind(self) -> str: ...
+    @property
+    def note(self) -> Note | None: ...
+    @property
+    def chord(self) -> Chord | None: ...
+    @property
+    def cluster(self) -> list[tuple[int, int, float]] | None: ...
+
 class Timeline:
     """Timeline for musical events."""
 
-    def __init__(self) -> None: ...
+    def __init__(
+        self,
+        events: list[TokenEvent] | None = None,
+        total_duration: float | None = None,
+        meter_changes: list[tuple[float, tuple[int, int]]] | None = None,
+        key_changes: list[tuple[float, str]] | None = None,
+    ) -> None: ...
     @property
-    def beats(self) -> float: ...
+    def total_duration(self) -> float: ...
     @property
-    def events(self) -> list[Any]: ...
-    def add_chord(self, beat: float, chord: Chord, duration: float) -> None: ...
-    def add_note(self, beat: float, note: Note, duration: float) -> None: ...
-    def add_rest(self, beat: float, duration: float) -> None: ...
-    def __str__(self) -> str: ...
+    def meter_changes(self) -> list[tuple[float, tuple[int, int]]]: ...
+    @property
+    def key_changes(self) -> list[tuple[float, str]]: ...
+    @property
+    def event_count(self) -> int: ...
+    def __len__(self) -> int: ...
+    @overload
+    def __getitem__(self, index: int) -> TokenEvent: ...
+    @overload
+    def __getitem__(self, index: slice) -> list[TokenEvent]: ...
+    def __iter__(self) -> Iterator[TokenEvent]: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> Timeline: ...
 
 class TrieNode:
     """Trie node for pattern storage."""
@@ -261,6 +613,7 @@ class TrieNode:
     def ids(self) -> list[str]: ...
     def add_pattern(self, pattern: list[bytes], pattern_id: str) -> None: ...
     def search_pattern(self, pattern: list[bytes]) -> list[str]: ...
+    def __iter__(self) -> Iterator[tuple[list[bytes], list[int]]]: ...
     def __str__(self) -> str: ...
 
 # Theory Functions
@@ -285,14 +638,75 @@ def is_valid_tri_sub(chord: Chord, scale_type: str) -> bool:
     """Validate tritone substitution eligibility."""
     ...
 
+def get_substitutions(
+    chord: Chord, scale: ScaleFingerprint
+) -> list[tuple[str, Chord, str]]:
+    """Enumerate valid substitutions for a chord in a key as (kind, chord, placement)."""
+    ...
+
+def negative_harmony_mirror(
+    chord: Chord, scale: ScaleFingerprint, axis: tuple[int, int] = (0, 7)
+) -> Chord:
+    """Reflect a chord about an axis (negative harmony)."""
+    ...
+
+def modal_interchange_options(
+    chord: Chord, from_scale: ScaleFingerprint, to_scale: str
+) -> list[Chord]:
+    """Chords borrowed from another scale that can stand in for a chord."""
+    ...
+
+def progression_reharmonize(
+    progression: list[Chord],
+    scale: ScaleFingerprint,
+    strategy: str,
+    axis: tuple[int, int] = (0, 7),
+    borrowed: str = "minor",
+) -> list[Chord]:
+    """Reharmonize a progression with one strategy."""
+    ...
+
+def piano_voicing(
+    chord: Chord, scale: ScaleFingerprint, tonic: int = 0, style: str = "close"
+) -> list[int]:
+    """Voice a chord for piano as ascending MIDI notes."""
+    ...
+
+def guitar_fingerings(
+    chord: Chord,
+    scale: ScaleFingerprint,
+    tonic: int = 0,
+    max_fret_span: int = 4,
+    max_fret: int = 12,
+    tuning: list[int] | None = None,
+) -> list[list[int | None]]:
+    """Enumerate playable guitar fingerings of a chord."""
+    ...
+
 def is_isotonal(chord1: Chord, chord2: Chord, scale: ScaleFingerprint) -> bool:
-    """Check if two chords are harmonically equivalent (isotonal)."""
+    """Check if two chords sound the same pitch classes (isotonal)."""
+    ...
+
+def validate_modification(chord: Chord, modification: str) -> list[dict[str, Any]]:
+    """Reasons a modification cannot be made to a chord; empty when allowed."""
     ...
 
-def analyze_harmonic_function(chord: Chord, scale_type: str) -> str:
+def analyze_harmonic_function(chord: Chord, scale_type: str) -> str | None:
     """Analyze harmonic function of a chord."""
     ...
 
+def analyze_harmonic_functions(
+    progression: list[Chord], scale: ScaleFingerprint
+) -> dict[str, Any]:
+    """Label the harmonic function of every chord and check functional syntax."""
+    ...
+
+def check_counterpoint(
+    lines: list[list[tuple[int | None, float]]], beats_per_measure: float = 4.0
+) -> list[tuple[str, float, tuple[int, int], tuple[int, int]]]:
+    """Check lines against species counterpoint rules."""
+    ...
+
 def chord_letter_to_lower_case(note_string: str) -> str:
     """Convert chord letter to lowercase while preserving accidentals."""
     ...
@@ -329,6 +743,18 @@ def py_parse_duration_token(token: str) -> float:
     """Parse duration token back to float value."""
     ...
 
+def py_tokenize_metric_position(
+    beat: float, time_signature: tuple[int, int] = (4, 4)
+) -> str:
+    """Tokenize the metric position of an onset beat."""
+    ...
+
+def py_timeline_metric_tokens(
+    timeline: Timeline, time_signature: tuple[int, int] = (4, 4)
+) -> list[str]:
+    """Metric position tokens of every event in a timeline."""
+    ...
+
 def py_tokenize_chord_as_raw(chord: Chord) -> str:
     """Tokenize chord as raw cluster."""
     ...
@@ -341,6 +767,22 @@ def py_detokenize_midi_like(tokens: list[str], scale: ScaleFingerprint) -> Timel
     """Detokenize MIDI-like tokens."""
     ...
 
+def py_tokenize_polyphonic(timeline: Timeline, scale: ScaleFingerprint) -> list[str]:
+    """Tokenize a timeline with note clusters and per-note durations."""
+    ...
+
+def py_detokenize_polyphonic(tokens: str | list[str]) -> Timeline:
+    """Detokenize polyphonic tokens into a timeline."""
+    ...
+
+def py_tokenize_timeline(timeline: Timeline) -> list[str]:
+    """Tokenize a whole timeline with bar lines, keys, meters and exact chords."""
+    ...
+
+def py_detokenize_tokens(tokens: str | list[str]) -> Timeline:
+    """Detokenize a token sequence from py_tokenize_timeline into a timeline."""
+    ...
+
 # Hash and Compression Functions
 
 def py_fast_hash(data: str) -> int:
@@ -369,10 +811,24 @@ def py_deserialize_trie(data: dict[str, Any]) -> TrieNode:
     """Deserialize trie from dictionary."""
     ...
 
+def py_serialize_trie_to_file(trie: TrieNode, file: Any, chunk_size: int = 65536) -> int:
+    """Write a serialized trie to a binary file object in chunks; returns bytes written."""
+    ...
+
+def py_deserialize_trie_from_file(file: Any, include_key_tonic: bool = False) -> TrieNode:
+    """Read a serialized trie from a binary file object in chunks."""
+    ...
+
 def py_validate_binary_format(data: bytes) -> bool:
     """Validate binary format."""
     ...
 
+def py_validate_chord_binary(
+    data: bytes | str, mode: Literal["strict", "lenient"] = "strict"
+) -> dict[str, Any]:
+    """Report the decoded value and status of every field in a 5-byte chord."""
+    ...
+
 def py_reduce_chord_vocab(chords: list[bytes], target_size: int) -> list[bytes]:
     """Reduce chord vocabulary to target size."""
     ...
@@ -403,6 +859,23 @@ def py_validate_chord_cluster_token(token: str) -> bool:
     """Validate chord cluster token format."""
     ...
 
+def py_validate_metric_token(token: str) -> bool:
+    """Validate metric position token format."""
+    ...
+
+def py_validate_token_stream(
+    tokens: str | list[str],
+) -> tuple[int, str, str] | None:
+    """Check a token stream against the token grammar.
+
+    Returns ``None`` if valid, else ``(index, token, reason)`` of the first violation.
+    """
+    ...
+
+def py_upgrade_token_stream(tokens: str | list[str], from_version: int) -> list[str]:
+    """Rewrite a token stream from an older vocabulary version in the current one."""
+    ...
+
 # Configuration Functions
 
 def get_configuration_constants() -> dict[str, str]:
@@ -413,6 +886,21 @@ def get_version() -> str:
     """Get the library version."""
     ...
 
+def enable_tracing(
+    level: Literal["error", "warn", "info", "debug", "trace"] = "debug",
+) -> None:
+    """Print spans for trie search, suggestion scoring and analysis to stderr.
+
+    Each span is printed when it closes, with its fields and the time spent in it.
+    The spans are at "debug" level.
+
+    Raises:
+        ValueError: If ``level`` is not a tracing level.
+        RuntimeError: If a subscriber is already installed or the module was built
+            without the ``tracing`` feature.
+    """
+    ...
+
 def get_serialization_constants() -> dict[str, Any]:
     """Get serialization-related constants."""
     ...
@@ -422,12 +910,28 @@ def get_serialization_constants() -> dict[str, Any]:
 class ComposerError(Exception):
     """Base exception for composer errors."""
 
-    @property
-    def message(self) -> str: ...
-    @property
-    def error_type(self) -> str: ...
+    code: str
+    """Stable error code, one of the ``ERROR_*`` configuration constants."""
+    domain: Literal["theory", "serialization", "ai"]
+    """Crate that raised the error."""
+    details: dict[str, Any]
+    """Fields of the error, such as ``{"root": 9}`` for an invalid chord root."""
+
+class ChordTheoryError(ComposerError, ValueError):
+    """Invalid chord or music theory argument."""
+
+class SerializationError(ComposerError, RuntimeError):
+    """Binary, hex or token data could not be encoded or decoded."""
+
+class AiError(ComposerError, ValueError):
+    """Error raised by the AI engine."""
+
+class EngineError(AiError, RuntimeError):
+    """Critical AI engine error, such as an uninitialized engine."""
 
 # Module Constants
 
 constants: dict[str, Any]
+DIFFICULTY_FEATURE_NAMES: list[str]
+DIFFICULTY_FEATURE_VERSION: int
 __version__: str
@@ -0,0 +1,190 @@
+"""Export difficulty models trained in Python to the Rust engine.
+
+The engine describes every progression with a fixed feature vector whose
+columns are listed in ``DIFFICULTY_FEATURE_NAMES``. Train a regressor on those
+features (``AiEngine.extract_difficulty_features`` builds the rows), export it
+to ONNX, and convert the graph with :func:`onnx_to_difficulty_model`. The
+resulting JSON is loaded with ``AiEngine.set_difficulty_model`` and evaluated
+natively, without an ONNX runtime.
+
+Supported ONNX operators form a feed-forward chain: ``Gemm``, ``MatMul``
+followed by ``Add``, ``Relu``, ``Sigmoid`` and ``Tanh``. A leading ``Sub``/
+``Div`` pair with constant operands becomes the input standardization and a
+trailing ``Mul`` by a scalar becomes the output scale.
+
+Converting requires the optional ``onnx`` and ``numpy`` packages
+(``pip install composer[onnx]``).
+"""
+
+from __future__ import annotations
+
+import json
+from collections.abc import Iterable, Sequence
+from typing import Any
+
+from .composer import DIFFICULTY_FEATURE_NAMES, DIFFICULTY_FEATURE_VERSION
+
+__all__ = [
+    "difficulty_feature_matrix",
+    "dense_difficulty_model",
+    "onnx_to_difficulty_model",
+]
+
+_ACTIVATIONS = {"Relu", "Sigmoid", "Tanh"}
+
+
+def difficulty_feature_matrix(
+    engine: Any,
+    progressions: Iterable[Sequence[Any]],
+    tempo_bpm: float | None = None,
+    time_signature: tuple[int, int] | None = None,
+) -> list[list[float]]:
+    """Extract one feature row per progression, in DIFFICULTY_FEATURE_NAMES order."""
+    return [
+        engine.extract_difficulty_features(list(progression), tempo_bpm, time_signature)
+        for progression in progressions
+    ]
+
+
+def dense_difficulty_model(
+    layers: list[dict[str, Any]],
+    input_mean: Sequence[float] | None = None,
+    input_scale: Sequence[float] | None = None,
+    output_scale: float = 1.0,
+    accuracy: float | None = None,
+) -> str:
+    """Serialize layer descriptions to the JSON format read by the engine.
+
+    Each layer is ``{"op": "Gemm", "weight": [[...]], "bias": [...]}`` with the
+    weight shaped ``[out, in]``, or ``{"op": "Relu" | "Sigmoid" | "Tanh"}``.
+    ``accuracy`` is the validation accuracy (0.0-1.0) the engine uses to size
+    score intervals.
+    """
+    model = {
+        "feature_version": DIFFICULTY_FEATURE_VERSION,
+        "feature_names": list(DIFFICULTY_FEATURE_NAMES),
+        "input_mean": [float(x) for x in input_mean or []],
+        "input_scale": [float(x) for x in input_scale or []],
+        "layers": layers,
+        "output_scale": float(output_scale),
+    }
+    if accuracy is not None:
+        model["accuracy"] = float(accuracy)
+    return json.dumps(model)
+
+
+def onnx_to_difficulty_model(
+    model: Any,
+    input_mean: Sequence[float] | None = None,
+    input_scale: Sequence[float] | None = None,
+    output_scale: float | None = None,
+    accuracy: float | None = None,
+) -> str:
+    """Convert an ONNX feed-forward regressor to the engine's JSON format.
+
+    ``model`` is an ``onnx.ModelProto`` or a path to a ``.onnx`` file. The graph
+    must take a single ``[batch, 12]`` input and produce a single value.
+    Explicit ``input_mean``/``input_scale``/``output_scale`` arguments override
+    any normalization found in the graph.
+    """
+    import numpy as np
+    import onnx
+    from onnx import numpy_helper
+
+    if not isinstance(model, onnx.ModelProto):
+        model = onnx.load(str(model))
+    graph = model.graph
+
+    constants = {
+        init.name: numpy_helper.to_array(init).astype(np.float64)
+        for init in graph.initializer
+    }
+    for node in graph.node:
+        if node.op_type == "Constant":
+            constants[node.output[0]] = numpy_helper.to_array(
+                node.attribute[0].t
+            ).astype(np.float64)
+
+    def constant(name: str) -> Any:
+        if name not in constants:
+            raise ValueError(f"ONNX tensor {name!r} must be a constant initializer")
+        return constants[name]
+
+    def attribute(node: Any, name: str, default: Any) -> Any:
+        for attr in node.attribute:
+            if attr.name == name:
+                return onnx.helper.get_attribute_value(attr)
+        return default
+
+    layers: list[dict[str, Any]] = []
+    mean = None
+    scale = None
+    out_scale = 1.0
+    pending_matmul = None
+
+    for node in graph.node:
+        op = node.op_type
+        if op == "Constant":
+            continue
+        if op == "MatMul":
+            pending_matmul = constant(node.input[1]).T
+            continue
+        if pending_matmul is not None:
+            if op == "Add":
+                bias = constant(node.input[1]).reshape(-1)
+            else:
+                bias = np.zeros(pending_matmul.shape[0])
+            layers.append(_gemm(pending_matmul, bias))
+            pending_matmul = None
+            if op == "Add":
+                continue
+
+        if op == "Gemm":
+            weight = constant(node.input[1])
+            if attribute(node, "transA", 0):
+                raise ValueError("Gemm with transA=1 is not supported")
+            if not attribute(node, "transB", 0):
+                weight = weight.T
+            alpha = attribute(node, "alpha", 1.0)
+            beta = attribute(node, "beta", 1.0)
+            if len(node.input) > 2 and node.input[2]:
+                bias = constant(node.input[2]).reshape(-1) * beta
+            else:
+                bias = np.zeros(weight.shape[0])
+            layers.append(_gemm(weight * alpha, bias))
+        elif op in _ACTIVATIONS:
+            layers.append({"op": op})
+        elif op == "Sub" and not layers and mean is None:
+            mean = constant(node.input[1]).reshape(-1)
+        elif op == "Div" and not layers and scale is None:
+            scale = constant(node.input[1]).reshape(-1)
+        elif op == "Mul" and layers:
+            out_scale *= float(constant(node.input[1]).reshape(-1)[0])
+        elif op in {"Flatten", "Identity"}:
+            continue
+        else:
+            raise ValueError(f"Unsupported ONNX operator for difficulty export: {op}")
+
+    if pending_matmul is not None:
+        layers.append(_gemm(pending_matmul, np.zeros(pending_matmul.shape[0])))
+
+    return dense_difficulty_model(
+        layers,
+        input_mean=input_mean if input_mean is not None else _to_list(mean),
+        input_scale=input_scale if input_scale is not None else _to_list(scale),
+        output_scale=output_scale if output_scale is not None else out_scale,
+        accuracy=accuracy,
+    )
+
+
+def _gemm(weight: Any, bias: Any) -> dict[str, Any]:
+    return {"op": "Gemm", "weight": weight.tolist(), "bias": bias.tolist()}
+
+
+def _to_list(values: Any) -> list[float] | None:
+    if values is None:
+        return None
+    features = len(DIFFICULTY_FEATURE_NAMES)
+    if values.size == 1:
+        return [float(values[0])] * features
+    return [float(x) for x in values]
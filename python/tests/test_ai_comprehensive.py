@@ -2,6 +2,8 @@
 
 from __future__ import annotations
 
+import json
+
 import pytest
 
 
@@ -166,6 +168,184 @@ class TestChordSuggestions:
             # Expected: AI engine requires complex training data not suitable for unit tests
             assert "not initialized" in str(e) or "Engine not initialized" in str(e)
 
+    def test_suggestion_context_fields(self, composer_module) -> None:
+        """Every SuggestionContext field is a keyword argument with Rust defaults."""
+        context = composer_module.SuggestionContext()
+        assert context.scale_fingerprint is None
+        assert context.position_in_progression == 0.5
+        assert context.target_valence == 0.0
+        assert context.complexity_preference == 0.5
+        assert context.genre_weights == {}
+        assert context.avoid_repetition_within == 4
+        assert context.recent_chords == []
+
+        major = composer_module.ScaleFingerprint.major()
+        recent = [composer_module.Chord(1, 5), composer_module.Chord(4, 5)]
+        context = composer_module.SuggestionContext(
+            scale_fingerprint=major,
+            position_in_progression=0.75,
+            target_valence=-0.5,
+            complexity_preference=0.2,
+            genre_weights={"jazz": 0.8},
+            avoid_repetition_within=2,
+            recent_chords=recent,
+        )
+        assert context.scale_fingerprint == major
+        assert context.position_in_progression == 0.75
+        assert context.target_valence == -0.5
+        assert context.complexity_preference == 0.2
+        assert context.genre_weights == {"jazz": 0.8}
+        assert context.avoid_repetition_within == 2
+        assert context.recent_chords == recent
+        assert context.key_tonic is None
+        assert "scale_fingerprint=ScaleFingerprint" in repr(context)
+
+        # Only the overridden field differs from the defaults
+        partial = composer_module.SuggestionContext(target_valence=0.3)
+        partial.target_valence = 0.0
+        assert partial == composer_module.SuggestionContext()
+
+    def test_chord_suggestions_match_mode(self, composer_module) -> None:
+        """Absolute matching only uses patterns stored in the context's key."""
+        chord = composer_module.Chord
+        engine = composer_module.AiEngine()
+        engine.initialize(
+            [
+                ([chord(1, 5), chord(4, 5)], "in_c", "C"),
+                ([chord(1, 5), chord(5, 5)], "in_g", "G"),
+            ]
+        )
+        context = composer_module.SuggestionContext(key_tonic="C")
+        config = composer_module.SuggestionConfig(min_confidence=0.0, match_mode="absolute")
+        assert config.match_mode == "absolute"
+
+        suggestions = engine.get_chord_suggestions([chord(1, 5)], context, config)
+        assert [s.chord.root for s in suggestions] == [4]
+
+        config.match_mode = "relative"
+        suggestions = engine.get_chord_suggestions([chord(1, 5)], context, config)
+        assert len(suggestions) == 2
+        with pytest.raises(ValueError):
+            config.match_mode = "pitch"
+
+    def test_chord_suggestions_diversity(self, composer_module) -> None:
+        """Diversity selection trades near-duplicate suggestions for other chords."""
+        chord = composer_module.Chord
+        engine = composer_module.AiEngine()
+        variants = [chord(5, 7, inversion=inversion) for inversion in range(3)]
+        engine.initialize(
+            [
+                ([chord(1, 5), variant], f"dominant_{i}_{j}", None)
+                for i, variant in enumerate(variants)
+                for j in range(3)
+            ]
+            + [([chord(1, 5), chord(4, 5)], "plagal", None)]
+        )
+        context = composer_module.SuggestionContext()
+        config = composer_module.SuggestionConfig(
+            max_suggestions=2, min_confidence=0.0, diversity_threshold=1.0
+        )
+        assert config.diversity_threshold == 1.0
+
+        suggestions = engine.get_chord_suggestions([chord(1, 5)], context, config)
+        assert [s.chord.root for s in suggestions] == [5, 5]
+
+        config.diversity_threshold = 0.5
+        suggestions = engine.get_chord_suggestions([chord(1, 5)], context, config)
+        assert sorted(s.chord.root for s in suggestions) == [4, 5]
+
+    def test_continue_progression(self, composer_module) -> None:
+        """Continuations follow the corpus and close on a cadence."""
+        chord = composer_module.Chord
+        engine = composer_module.AiEngine()
+        engine.initialize(
+            [
+                ([chord(1, 5), chord(4, 5), chord(5, 5), chord(1, 5)], "plagal", None),
+                ([chord(1, 5), chord(6, 5), chord(2, 5), chord(5, 5)], "circle", None),
+            ]
+        )
+        config = composer_module.SuggestionConfig(min_confidence=0.0)
+
+        continuation = engine.continue_progression(
+            [chord(1, 5), chord(4, 5)], 2, config=config, random_seed=42
+        )
+        assert [s.chord.root for s in continuation] == [5, 1]
+
+        def sampled():
+            continuation = engine.continue_progression(
+                [chord(1, 5)], 3, config=config, temperature=1.5, random_seed=7
+            )
+            return [s.chord.root for s in continuation]
+
+        assert sampled() == sampled()
+        with pytest.raises(Exception):
+            engine.continue_progression([], 2)
+
+    def test_chord_suggestions_recommended_inversion(self, composer_module) -> None:
+        """Suggestions can carry the inversion with the smoothest bass motion."""
+        chord = composer_module.Chord
+        engine = composer_module.AiEngine()
+        engine.initialize([([chord(1, 5), chord(5, 5)], "authentic", None)])
+        context = composer_module.SuggestionContext()
+        config = composer_module.SuggestionConfig(min_confidence=0.0)
+
+        suggestions = engine.get_chord_suggestions([chord(1, 5)], context, config)
+        assert suggestions[0].recommended_inversion is None
+
+        config.suggest_inversions = True
+        suggestions = engine.get_chord_suggestions([chord(1, 5)], context, config)
+        assert suggestions[0].recommended_inversion == 1
+
+    def test_harmonize_by_sd_chromaticism(self, composer_module) -> None:
+        """Chromaticism adds applied and borrowed chords to harmonizations."""
+        chord = composer_module.Chord
+        engine = composer_module.AiEngine()
+        engine.initialize([([chord(1, 5), chord(5, 5)], "cadence", None)])
+        bits = (1 << 1) | (1 << 3)
+
+        diatonic = engine.get_harmonize_by_sd_solutions(bits, "major", 100)
+        assert all(s.chord.applied == 0 for s in diatonic)
+
+        config = composer_module.SuggestionConfig(chromaticism=1.0)
+        assert config.chromaticism == 1.0
+        chromatic = engine.get_harmonize_by_sd_solutions(bits, "major", 100, config)
+        assert any(s.chord.applied != 0 for s in chromatic)
+        assert len(chromatic) > len(diatonic)
+
+    def test_chord_suggestions_with_keyword_overrides(self, composer_module) -> None:
+        """Test context-aware suggestions with defaults and keyword overrides."""
+        chord = composer_module.Chord
+        engine = composer_module.AiEngine()
+        engine.initialize(
+            [
+                ([chord(1, 5), chord(4, 5), chord(5, 5), chord(1, 5)], "pop_1", None),
+                ([chord(1, 5), chord(6, 5), chord(4, 5), chord(5, 5)], "pop_2", None),
+            ]
+        )
+
+        # Context and config are optional
+        assert isinstance(engine.get_chord_suggestions([chord(1, 5)]), list)
+
+        context = composer_module.SuggestionContext(recent_chords=[chord(4, 5)])
+        suggestions = engine.get_chord_suggestions(
+            [chord(1, 5)],
+            context,
+            target_valence=0.5,
+            genre_weights={"pop": 1.0},
+            avoid_repetition_within=1,
+            max_suggestions=1,
+            min_confidence=0.0,
+        )
+        assert len(suggestions) <= 1
+
+        # Overrides apply to a copy of the passed context
+        assert context.target_valence == 0.0
+        assert context.avoid_repetition_within == 4
+        assert len(context.recent_chords) == 1
+
+        with pytest.raises(TypeError):
+            engine.get_chord_suggestions([chord(1, 5)], not_a_field=1)
+
 
 class TestDifficultyAssessment:
     """Test difficulty assessment functionality."""
@@ -474,3 +654,994 @@ class TestAiEngineEfficiency:
 
         # Should not fail with memory issues
         assert len(engines) == 10
+
+
+class TestDifficultyModelExport:
+    """Test the exported difficulty features and custom difficulty models."""
+
+    @staticmethod
+    def _engine(composer_module):
+        engine = composer_module.AiEngine()
+        cadence = [composer_module.Chord(1, 5), composer_module.Chord(5, 7)]
+        engine.initialize([(cadence, "cadence", None)])
+        return engine
+
+    def test_feature_extraction(self, composer_module) -> None:
+        """Features follow DIFFICULTY_FEATURE_NAMES order."""
+        from composer.difficulty_export import difficulty_feature_matrix
+
+        engine = self._engine(composer_module)
+        progression = [composer_module.Chord(1, 5), composer_module.Chord(5, 7)]
+
+        names = composer_module.DIFFICULTY_FEATURE_NAMES
+        assert names[0] == "harmonic_complexity"
+        features = engine.extract_difficulty_features(progression, 120.0, (4, 4))
+        assert len(features) == len(names)
+        assert features[names.index("unique_chords")] == 2.0
+
+        rows = difficulty_feature_matrix(engine, [progression, progression])
+        assert rows == [features, features]
+
+    def test_dense_model_replaces_builtin(self, composer_module) -> None:
+        """A dense model's prediction becomes the overall score."""
+        from composer.difficulty_export import dense_difficulty_model
+
+        engine = self._engine(composer_module)
+        progression = [composer_module.Chord(1, 5), composer_module.Chord(5, 7)]
+        builtin = engine.assess_difficulty(progression).overall_score
+
+        names = composer_module.DIFFICULTY_FEATURE_NAMES
+        weight = [0.0] * len(names)
+        weight[names.index("unique_chords")] = 1.5
+        model_json = dense_difficulty_model(
+            [{"op": "Gemm", "weight": [weight], "bias": [1.0]}]
+        )
+        engine.set_difficulty_model(model_json)
+        assert engine.assess_difficulty(progression).overall_score == pytest.approx(4.0)
+
+        with pytest.raises(ValueError):
+            engine.set_difficulty_model('{"layers": []}')
+
+        engine.set_difficulty_model(None)
+        assert engine.assess_difficulty(progression).overall_score == builtin
+
+    def test_onnx_conversion(self, composer_module) -> None:
+        """An ONNX Gemm/Sigmoid graph converts to an equivalent dense model."""
+        pytest.importorskip("onnx")
+        np = pytest.importorskip("numpy")
+        from onnx import TensorProto, helper, numpy_helper
+
+        from composer.difficulty_export import onnx_to_difficulty_model
+
+        features = len(composer_module.DIFFICULTY_FEATURE_NAMES)
+        weight = np.full((1, features), 0.1, dtype=np.float32)
+        graph = helper.make_graph(
+            [
+                helper.make_node("Gemm", ["x", "w", "b"], ["h"], transB=1),
+                helper.make_node("Sigmoid", ["h"], ["s"]),
+                helper.make_node("Mul", ["s", "scale"], ["y"]),
+            ],
+            "difficulty",
+            [helper.make_tensor_value_info("x", TensorProto.FLOAT, [None, features])],
+            [helper.make_tensor_value_info("y", TensorProto.FLOAT, [None, 1])],
+            initializer=[
+                numpy_helper.from_array(weight, "w"),
+                numpy_helper.from_array(np.zeros(1, dtype=np.float32), "b"),
+                numpy_helper.from_array(np.array([10.0], dtype=np.float32), "scale"),
+            ],
+        )
+        model_json = onnx_to_difficulty_model(helper.make_model(graph))
+
+        engine = self._engine(composer_module)
+        progression = [composer_module.Chord(1, 5), composer_module.Chord(5, 7)]
+        x = np.array(engine.extract_difficulty_features(progression))
+        expected = 10.0 / (1.0 + np.exp(-(weight.astype(np.float64) @ x)[0]))
+
+        engine.set_difficulty_model(model_json)
+        score = engine.assess_difficulty(progression).overall_score
+        assert score == pytest.approx(min(expected, 10.0), rel=1e-5)
+
+
+class TestSimilarChords:
+    """Test embedding-based chord similarity search."""
+
+    def test_similar_chords(self, composer_module) -> None:
+        """Neighbors come from the trained vocabulary, best first."""
+        engine = composer_module.AiEngine()
+        chords = [composer_module.Chord(root, 5) for root in (1, 6, 4, 5)]
+        engine.initialize([(chords, "pop", None)])
+
+        similar = engine.similar_chords(composer_module.Chord(1, 5), k=2)
+        assert len(similar) == 2
+        chord, similarity, frequency = similar[0]
+        assert chord.root == 6
+        assert 0.0 < similarity <= 1.0
+        assert frequency == 1
+        assert similar[0][1] >= similar[1][1]
+
+    def test_nearest_chords_by_usage(self, composer_module) -> None:
+        """Chords in the same contexts are neighbors."""
+        engine = composer_module.AiEngine()
+        engine.initialize(
+            [
+                ([composer_module.Chord(r, 5) for r in (1, 2, 5)], "ii", None),
+                ([composer_module.Chord(r, 5) for r in (1, 4, 5)], "IV", None),
+                ([composer_module.Chord(r, 5) for r in (1, 6, 3)], "vi", None),
+            ]
+        )
+
+        ii = composer_module.Chord(2, 5)
+        nearest = engine.nearest_chords(ii, k=1)
+        assert nearest[0][0].root == 4
+        assert engine.chord_similarity(ii, composer_module.Chord(4, 5)) > engine.chord_similarity(
+            ii, composer_module.Chord(6, 5)
+        )
+
+
+class TestSimilarProgressions:
+    """Test embedding-based progression similarity search."""
+
+    def test_similar_progressions(self, composer_module) -> None:
+        """Each stored source is reported once, best first."""
+        engine = composer_module.AiEngine()
+
+        def chords(roots):
+            return [composer_module.Chord(root, 5) for root in roots]
+
+        engine.initialize(
+            [
+                (chords([1, 5, 6, 4]), "axis", "C"),
+                (chords([6, 4, 1, 5]), "rotated", None),
+                (chords([2, 5, 1]), "cadence", None),
+            ]
+        )
+
+        similar = engine.similar_progressions(chords([1, 5, 6, 4]), k=2)
+        assert [entry[0] for entry in similar] == ["axis", "rotated"]
+        source_id, key_tonic, progression, similarity = similar[0]
+        assert key_tonic == "C"
+        assert [chord.root for chord in progression] == [1, 5, 6, 4]
+        assert similarity == pytest.approx(1.0)
+
+        more = engine.more_like_source("axis", k=5)
+        assert [entry[0] for entry in more] == ["rotated", "cadence"]
+        with pytest.raises(Exception):
+            engine.more_like_source("unknown")
+
+
+class TestSubstitutionSuggestions:
+    """Test theory-based substitution suggestions."""
+
+    def test_get_substitutions(self, composer_module) -> None:
+        """V7 in major offers a tritone substitute and the backdoor dominant."""
+        major = composer_module.ScaleFingerprint.major()
+        substitutions = composer_module.get_substitutions(
+            composer_module.Chord(5, 7), major
+        )
+        kinds = [kind for kind, _, _ in substitutions]
+        assert "tritone" in kinds
+        assert "backdoor_dominant" in kinds
+        placements = {kind: placement for kind, _, placement in substitutions}
+        assert placements["secondary_dominant"] == "precede"
+        assert placements["relative"] == "replace"
+
+    def test_get_substitution_suggestions(self, composer_module) -> None:
+        """Substitutes used in the corpus rank first."""
+        engine = composer_module.AiEngine()
+        chords = [composer_module.Chord(root, 5) for root in (1, 6, 4)]
+        engine.initialize([(chords, "pop", None)])
+
+        suggestions = engine.get_substitution_suggestions(
+            composer_module.Chord(1, 5), composer_module.ScaleFingerprint.major()
+        )
+        kind, chord, placement, frequency = suggestions[0]
+        assert kind == "relative"
+        assert chord.root == 6
+        assert placement == "replace"
+        assert frequency == 1
+
+    def test_restyle_progression(self, composer_module) -> None:
+        """Pop triads become jazz ii-V chains and back."""
+        engine = composer_module.AiEngine()
+        engine.initialize([])
+        pop = [composer_module.Chord(root, 5) for root in (1, 6, 5, 1)]
+
+        alternatives = engine.restyle_progression(pop, "pop", "Jazz")
+        assert [intensity for _, intensity, _ in alternatives] == [1, 2, 3, 4]
+        confidences = [confidence for _, _, confidence in alternatives]
+        assert confidences == sorted(confidences, reverse=True)
+        ii_v, _, _ = alternatives[1]
+        assert [chord.root for chord in ii_v] == [1, 6, 2, 5, 1]
+
+        back = engine.restyle_progression(ii_v, "jazz", "pop")
+        assert [chord.chord_type for chord in back[0][0]] == [5] * 5
+
+        with pytest.raises(ValueError):
+            engine.restyle_progression(pop, "pop", "polka")
+
+
+class TestDifficultyExplanation:
+    """Test structured difficulty explanations."""
+
+    def test_explanation_structure(self, composer_module) -> None:
+        """Factors are ranked and compared against same-level references."""
+        engine = composer_module.AiEngine()
+        engine.initialize([])
+        progression = [
+            composer_module.Chord(2, 9),
+            composer_module.Chord(5, 13),
+            composer_module.Chord(1, 9),
+        ]
+        assessment = engine.assess_difficulty(progression)
+        explanation = assessment.explanation
+
+        shares = [factor["share"] for factor in explanation["top_factors"]]
+        assert len(shares) == 4
+        assert shares == sorted(shares, reverse=True)
+
+        reference = explanation["reference"]
+        assert reference["skill_level"] == assessment.skill_level
+        assert reference["references"]
+        assert {"feature", "value", "reference_mean", "difference"} <= set(
+            reference["features"][0]
+        )
+
+
+class TestMinimumEvidence:
+    """Test flagging of progressions too short to score reliably."""
+
+    def test_short_progressions_flagged(self, composer_module) -> None:
+        """One chord is flagged and less confident than four."""
+        engine = composer_module.AiEngine()
+        engine.initialize([])
+        tonic = composer_module.Chord(1, 5)
+
+        single = engine.assess_difficulty([tonic])
+        vamp = engine.assess_difficulty([tonic] * 4)
+        assert single.insufficient_data
+        assert single.evidence == pytest.approx(0.25)
+        assert not vamp.insufficient_data
+        assert single.confidence < vamp.confidence
+
+
+class TestConfidenceIntervals:
+    """Test bounds reported next to point estimates."""
+
+    def test_score_and_key_intervals(self, composer_module) -> None:
+        """Intervals contain the estimate and follow reported model accuracy."""
+        from composer.difficulty_export import dense_difficulty_model
+
+        engine = composer_module.AiEngine()
+        engine.initialize([])
+        progression = [composer_module.Chord(root, 5) for root in (1, 4, 5, 1)]
+
+        assessment = engine.assess_difficulty(progression)
+        lower, upper = assessment.score_interval
+        assert lower <= assessment.overall_score <= upper
+
+        names = composer_module.DIFFICULTY_FEATURE_NAMES
+        model_json = dense_difficulty_model(
+            [{"op": "Gemm", "weight": [[0.0] * len(names)], "bias": [5.0]}],
+            accuracy=0.95,
+        )
+        engine.set_difficulty_model(model_json)
+        assert engine.assess_difficulty(progression).score_interval == pytest.approx(
+            (4.5, 5.5)
+        )
+
+        key = engine.analyze_progression(progression).key_centers[0]
+        low, high = key["confidence_interval"]
+        assert low <= key["confidence"] <= high
+
+
+class TestSkillThresholds:
+    """Test calibrated skill bands."""
+
+    def test_custom_thresholds(self, composer_module) -> None:
+        """Institutions can replace the default bands."""
+        engine = composer_module.AiEngine()
+        engine.initialize([])
+        progression = [composer_module.Chord(1, 5), composer_module.Chord(5, 5)]
+
+        names = [name for name, _, _ in engine.skill_thresholds()]
+        assert "Early Intermediate" in names
+        assert "Late Intermediate" in names
+
+        engine.set_skill_thresholds(
+            [("Grade 1", "Beginner", 0.0), ("Grade 8", "Expert", 0.1)]
+        )
+        assessment = engine.assess_difficulty(progression)
+        assert assessment.skill_band == "Grade 8"
+        assert assessment.skill_level == "Expert"
+
+        with pytest.raises(Exception):
+            engine.set_skill_thresholds([("A", "Expert", 0.0), ("B", "Beginner", 1.0)])
+
+        engine.set_skill_thresholds(None)
+        assert engine.skill_thresholds()[0][0] == "Beginner"
+
+
+class TestFormAnalysis:
+    """Test phrase and form segmentation."""
+
+    def test_analyze_form(self, composer_module) -> None:
+        """Cadences split phrases and repeated phrases share a label."""
+        engine = composer_module.AiEngine()
+        engine.initialize([])
+        roots = (1, 4, 5, 1, 6, 2, 4, 1, 1, 4, 5, 1)
+        events = [
+            composer_module.TokenEvent(
+                beat=2.0 * i, chord=composer_module.Chord(root, 5)
+            )
+            for i, root in enumerate(roots)
+        ]
+        timeline = composer_module.Timeline(events, total_duration=2.0 * len(roots))
+
+        form = engine.analyze_form(timeline)
+        assert form["form"] == "ABA"
+        assert form["phrases"][0]["chord_range"] == (0, 4)
+        assert form["phrases"][0]["cadence"] == "Authentic"
+        assert form["phrases"][1]["cadence"] == "Plagal"
+        assert [section["label"] for section in form["sections"]] == ["A", "B", "A"]
+        assert form["sections"][1]["start_beat"] == 8.0
+
+
+class TestLoops:
+    """Test loop analysis and turnaround suggestions."""
+
+    def test_analyze_loop(self, composer_module) -> None:
+        """A dominant at the end leads smoothly back to the tonic."""
+        chord = composer_module.Chord
+        engine = composer_module.AiEngine()
+        engine.initialize([])
+
+        smooth = engine.analyze_loop([chord(1, 5), chord(6, 5), chord(4, 5), chord(5, 5)])
+        assert smooth["loops_smoothly"]
+        assert smooth["wrap_quality"] == 0.5
+        assert smooth["wrap_count"] == 0
+
+        stalled = engine.analyze_loop([chord(1, 5), chord(4, 5), chord(5, 5), chord(1, 5)])
+        assert not stalled["loops_smoothly"]
+        assert stalled["internal_quality"] > stalled["wrap_quality"]
+        with pytest.raises(Exception):
+            engine.analyze_loop([chord(1, 5)])
+
+    def test_compute_novelty(self, composer_module) -> None:
+        """Moves the corpus rarely makes are novel."""
+        chord = composer_module.Chord
+        engine = composer_module.AiEngine()
+        engine.initialize(
+            [([chord(1, 5), chord(5, 5)], f"cadence-{i}", None) for i in range(9)]
+            + [([chord(1, 5), chord(4, 5)], "plagal", None)]
+        )
+
+        common = engine.compute_novelty([chord(1, 5), chord(5, 5)])
+        assert not common["novel"]
+        assert abs(common["transitions"][0]["probability"] - 0.9) < 1e-9
+
+        rare = engine.compute_novelty([chord(1, 5), chord(4, 5), chord(6, 5)])
+        assert rare["novel"]
+        assert [t["novel"] for t in rare["transitions"]] == [True, True]
+        assert rare["transitions"][1]["count"] == 0
+        assert engine.compute_novelty([chord(1, 5)])["transitions"] == []
+
+        config = composer_module.SuggestionConfig(novelty_weight=0.5)
+        assert config.novelty_weight == 0.5
+
+    def test_suggest_turnarounds(self, composer_module) -> None:
+        """Turnarounds are ranked by how well they lead back to the start."""
+        chord = composer_module.Chord
+        engine = composer_module.AiEngine()
+        engine.initialize([])
+        progression = [chord(1, 5), chord(6, 5), chord(2, 5), chord(4, 5)]
+
+        turnarounds = engine.suggest_turnarounds(progression)
+        assert turnarounds[0]["chord"].root == 5
+        assert turnarounds[0]["wrap_quality"] == 0.5
+        assert len(turnarounds) == 13
+
+
+class TestPerChordBreakdown:
+    """Test per-chord difficulty contributions."""
+
+    def test_per_chord_breakdown(self, composer_module) -> None:
+        """Each chord reports complexity, voice-leading cost and novelty."""
+        Chord = composer_module.Chord
+        engine = composer_module.AiEngine()
+        engine.initialize([])
+        progression = [Chord(1, 5), Chord(5, 5), Chord(2, 9), Chord(1, 5)]
+
+        breakdown = engine.assess_difficulty(progression).per_chord_breakdown
+        assert [entry["index"] for entry in breakdown] == [0, 1, 2, 3]
+        assert breakdown[0]["voice_leading_cost"] == 0.0
+        assert breakdown[0]["novelty"] == 0.0
+        # Nothing was trained, so every move is novel
+        assert all(entry["novelty"] == 1.0 for entry in breakdown[1:])
+        assert breakdown[2]["complexity"] > breakdown[1]["complexity"]
+
+
+class TestPracticePlan:
+    """Test practice plans generated from difficulty assessment."""
+
+    def test_generate_practice_plan(self, composer_module) -> None:
+        """Hard segments come first with simpler chords for the player's level."""
+        Chord = composer_module.Chord
+        engine = composer_module.AiEngine()
+        engine.initialize([])
+        progression = [Chord(1, 5), Chord(4, 5), Chord(5, 5), Chord(1, 5)]
+        progression += [Chord(2, 9), Chord(5, 13), Chord(1, 11)]
+
+        plan = engine.generate_practice_plan(progression, "Beginner")
+        assert plan["player_level"] == "Beginner"
+        hardest = plan["segments"][0]
+        assert hardest["chord_range"] == (4, 7)
+        assert hardest["above_level"]
+        assert [s["index"] for s in hardest["substitutions"]] == [4, 5, 6]
+        assert plan["simplified_progression"][5] == Chord(5, 5)
+        assert plan["estimated_minutes"] == pytest.approx(
+            sum(s["estimated_minutes"] for s in plan["segments"])
+        )
+
+        expert = engine.generate_practice_plan(progression, "Expert")
+        assert all(not s["substitutions"] for s in expert["segments"])
+        with pytest.raises(ValueError):
+            engine.generate_practice_plan(progression, "Virtuoso")
+
+
+class TestProgressionRewrite:
+    """Test simplification and enrichment toward a target level."""
+
+    def test_enrich_then_simplify(self, composer_module) -> None:
+        """Rewrites move the score in the requested direction."""
+        Chord = composer_module.Chord
+        engine = composer_module.AiEngine()
+        engine.initialize([])
+        progression = [Chord(1, 5), Chord(6, 5), Chord(2, 5), Chord(5, 5)]
+
+        richer = engine.enrich_progression(progression, "Expert")
+        assert richer["changed"]
+        assert richer["score"] > richer["original_score"]
+        assert [chord.root for chord in richer["progression"]] == [1, 6, 2, 5]
+
+        plainer = engine.simplify_progression(richer["progression"], "Beginner")
+        assert plainer["score"] < richer["score"]
+
+        unchanged = engine.enrich_progression(progression, "Beginner")
+        assert unchanged["changed"] == []
+        assert unchanged["reached_target"]
+        with pytest.raises(ValueError):
+            engine.simplify_progression(progression, "Novice")
+
+
+class TestMelodicAnalysis:
+    """Test melody analysis and melody-aware difficulty assessment."""
+
+    def test_analyze_melody(self, composer_module) -> None:
+        """Range, intervals and contour are measured from the notes."""
+        Note = composer_module.Note
+        engine = composer_module.AiEngine()
+        engine.initialize([])
+
+        scale = engine.analyze_melody([Note(degree, 5) for degree in (0, 2, 4, 5, 7)])
+        assert scale["note_count"] == 5
+        assert scale["range"] == 7
+        assert scale["contour_entropy"] == 0.0
+        assert len(scale["interval_histogram"]) == 13
+
+        leaps = engine.analyze_melody(
+            [Note(degree, 5) for degree in (0, 9, 2, 2, 11, 0)]
+        )
+        assert leaps["complexity"] > scale["complexity"]
+        with pytest.raises(ValueError):
+            engine.analyze_melody([Note(0, 5, is_rest=True)])
+
+    def test_assess_melody_difficulty(self, composer_module) -> None:
+        """The melody replaces the chord-root estimate of melodic complexity."""
+        Chord = composer_module.Chord
+        Note = composer_module.Note
+        engine = composer_module.AiEngine()
+        engine.initialize([])
+        progression = [Chord(1, 5), Chord(4, 5), Chord(5, 5), Chord(1, 5)]
+        melody = [Note(degree, 5) for degree in (0, 4, 7, 11, 9, 5, 4, 2, 0)]
+
+        assessment = engine.assess_melody_difficulty(progression, melody)
+        assert assessment.melody is not None
+        assert assessment.melodic_complexity == assessment.melody["complexity"]
+        assert engine.assess_difficulty(progression).melody is None
+
+
+class TestTimelineDifficulty:
+    """Test meter-aware difficulty assessment of timelines."""
+
+    def test_assess_timeline_difficulty(self, composer_module) -> None:
+        """Meter changes are counted and raise rhythmic complexity."""
+        engine = composer_module.AiEngine()
+        engine.initialize([])
+        events = [
+            composer_module.TokenEvent(beat=beat, chord=composer_module.Chord(root, 5))
+            for root, beat in ((1, 0.0), (6, 4.0), (4, 8.0), (5, 12.5))
+        ]
+        plain = composer_module.Timeline(events, total_duration=16.0)
+        changing = composer_module.Timeline(
+            events, total_duration=16.0, meter_changes=[(8.0, (5, 4))]
+        )
+        assert changing.meter_changes == [(8.0, (5, 4))]
+
+        before = engine.assess_timeline_difficulty(plain)
+        after = engine.assess_timeline_difficulty(changing)
+        assert before.time_signature_changes == 0
+        assert after.time_signature_changes == 1
+        assert after.rhythmic_complexity > before.rhythmic_complexity
+
+
+class TestAccompaniment:
+    """Test accompaniment rendering from chord timelines."""
+
+    def test_render_accompaniment(self, composer_module) -> None:
+        """Chords are expanded into note clusters on the chosen pulse."""
+        engine = composer_module.AiEngine()
+        engine.initialize([])
+        events = [
+            composer_module.TokenEvent(beat=beat, chord=composer_module.Chord(root, 5))
+            for root, beat in ((1, 0.0), (5, 2.0))
+        ]
+        timeline = composer_module.Timeline(
+            events, total_duration=4.0, meter_changes=[(0.0, (2, 4))]
+        )
+
+        block = engine.render_accompaniment(timeline)
+        assert len(block) == 8
+        assert block.total_duration == 4.0
+        assert block.meter_changes == [(0.0, (2, 4))]
+        assert all(event.kind == "cluster" for event in block)
+        # The bass under the first chord is held for both of its beats
+        assert block[0].cluster[0] == (0, 3, 2.0)
+
+        alberti = engine.render_accompaniment(timeline, style="Alberti", density="Dense")
+        assert len(alberti) == 16
+        assert [event.cluster[0][0] for event in alberti[:4]] == [0, 7, 4, 7]
+
+        with pytest.raises(ValueError):
+            engine.render_accompaniment(timeline, style="Waltz")
+
+
+class TestGroove:
+    """Test drum groove suggestion from chord timelines."""
+
+    def test_suggest_groove(self, composer_module) -> None:
+        """Kicks follow the chord changes and snares take the backbeats."""
+        engine = composer_module.AiEngine()
+        engine.initialize([])
+        events = [
+            composer_module.TokenEvent(beat=beat, chord=composer_module.Chord(root, 5))
+            for root, beat in ((1, 0.0), (4, 2.0), (5, 3.5))
+        ]
+        timeline = composer_module.Timeline(events, total_duration=8.0)
+
+        groove = engine.suggest_groove(timeline, density="Sparse")
+        kicks = [(beat, accent) for beat, voice, accent in groove["hits"] if voice == "Kick"]
+        snares = [beat for beat, voice, _ in groove["hits"] if voice == "Snare"]
+        assert kicks == [(0.0, True), (2.0, True), (3.5, True), (6.0, False)]
+        assert snares == [1.0, 3.0, 5.0, 7.0]
+        assert groove["timeline"].total_duration == 8.0
+        assert all(event.kind == "cluster" for event in groove["timeline"])
+        assert groove["tokens"]
+
+        waltz = engine.suggest_groove(timeline, time_signature=(3, 4))
+        assert [beat for beat, voice, _ in waltz["hits"] if voice == "Snare"][:2] == [1.0, 4.0]
+
+        with pytest.raises(ValueError):
+            engine.suggest_groove(timeline, density="Frantic")
+
+
+class TestHarmonicRhythm:
+    """Test harmonic rhythm analysis from chord durations."""
+
+    def test_analyze_harmonic_rhythm(self, composer_module) -> None:
+        """Repeating cycles and cadential lengthening are recognised."""
+        Chord = composer_module.Chord
+        engine = composer_module.AiEngine()
+        engine.initialize([])
+
+        cycle = [(Chord(1, 5), 2.0), (Chord(4, 5), 1.0), (Chord(5, 5), 1.0)] * 2
+        rhythm = engine.analyze_harmonic_rhythm(cycle)
+        assert rhythm["rhythm_patterns"] == ["Repeating 2+1+1"]
+        assert rhythm["trend"] == "Steady"
+        assert 0.0 < rhythm["regularity"] < 1.0
+
+        cadence = [(Chord(2, 5), 1.0), (Chord(5, 5), 1.0), (Chord(1, 5), 4.0)]
+        rhythm = engine.analyze_harmonic_rhythm(cadence)
+        assert rhythm["rhythm_patterns"] == ["Cadential lengthening"]
+
+        with pytest.raises(Exception):
+            engine.analyze_harmonic_rhythm([(Chord(1, 5), -1.0)])
+
+    def test_analyze_timeline_progression(self, composer_module) -> None:
+        """Timeline analysis takes the harmonic rhythm from event timing."""
+        engine = composer_module.AiEngine()
+        engine.initialize([])
+        events = [
+            composer_module.TokenEvent(beat=beat, chord=composer_module.Chord(root, 5))
+            for root, beat in ((1, 0.0), (4, 4.0), (5, 8.0), (1, 12.0))
+        ]
+        timeline = composer_module.Timeline(events, total_duration=16.0)
+
+        analysis = engine.analyze_timeline_progression(timeline)
+        assert analysis.harmonic_rhythm["rhythm_patterns"] == ["Regular"]
+        assert analysis.harmonic_rhythm["regularity"] == pytest.approx(1.0)
+
+
+class TestValenceEstimation:
+    """Test emotional valence and arousal estimation."""
+
+    def test_estimate_valence(self, composer_module, major_scale, minor_scale) -> None:
+        """Mode sets valence and tempo raises arousal."""
+        engine = composer_module.AiEngine()
+        engine.initialize([])
+        progression = [
+            composer_module.Chord(1, 5),
+            composer_module.Chord(4, 5),
+            composer_module.Chord(5, 7),
+        ]
+
+        major = engine.estimate_valence(progression, major_scale)
+        minor = engine.estimate_valence(progression, minor_scale)
+        assert major["valence"] > minor["valence"]
+        assert len(major["chords"]) == 3
+        assert major["chords"][0]["valence"] == pytest.approx(0.6)
+        assert major["chords"][2]["arousal"] > major["chords"][0]["arousal"]
+
+        fast = engine.estimate_valence(progression, tempo_bpm=170.0)
+        assert fast["arousal"] > major["arousal"]
+
+        with pytest.raises(Exception):
+            engine.estimate_valence([])
+
+
+class TestHarmonicFunction:
+    """Test harmonic function labeling."""
+
+    def test_analyze_harmonic_function(self, composer_module) -> None:
+        """Single chords are labeled in the key, applied chords in their target's."""
+        Chord = composer_module.Chord
+        assert composer_module.analyze_harmonic_function(Chord(4, 5), "major") == (
+            "Subdominant"
+        )
+        assert composer_module.analyze_harmonic_function(Chord(7, 5), "major") == (
+            "Dominant"
+        )
+        # ♭VII in minor is subdominant
+        assert composer_module.analyze_harmonic_function(Chord(7, 5), "minor") == (
+            "Subdominant"
+        )
+        with pytest.raises(ValueError):
+            composer_module.analyze_harmonic_function(Chord(1, 5), "nonexistent")
+
+    def test_analyze_harmonic_functions(self, composer_module) -> None:
+        """Function sequences report unresolved applied chords and retrogressions."""
+        Chord = composer_module.Chord
+        progression = [
+            Chord(1, 5),
+            Chord(5, 7, applied=2),
+            Chord(2, 7),
+            Chord(5, 7),
+            Chord(4, 5),
+        ]
+        analysis = composer_module.analyze_harmonic_functions(
+            progression, composer_module.ScaleFingerprint.major()
+        )
+        assert analysis["functions"][0] == ("Tonic", 1)
+        assert analysis["functions"][1] == ("Dominant", 2)
+        assert analysis["functions"][2] == ("Predominant", 1)
+        assert analysis["violations"] == [("retrogression", 4)]
+
+    def test_check_counterpoint(self, composer_module) -> None:
+        """Parallel fifths and unresolved suspensions are located by beat and note."""
+        upper = [(72, 4.0), (74, 4.0)]
+        lower = [(65, 4.0), (67, 4.0)]
+        assert composer_module.check_counterpoint([upper, lower]) == [
+            ("parallel_fifths", 4.0, (0, 1), (1, 1))
+        ]
+
+        # C5 tied over the bass moving to G3 must resolve down by step
+        suspension = [(69, 2.0), (72, 4.0), (74, 2.0)]
+        bass = [(53, 4.0), (55, 4.0)]
+        violations = composer_module.check_counterpoint([suspension, bass])
+        assert violations == [("unresolved_suspension", 4.0, (0, 1), (1, 1))]
+        with pytest.raises(ValueError):
+            composer_module.check_counterpoint([upper])
+
+
+class TestReharmonization:
+    """Test negative harmony and modal interchange transforms."""
+
+    def test_negative_harmony_mirror(self, composer_module) -> None:
+        """V7 mirrors to iiø7 of the parallel minor; mirroring twice round-trips."""
+        Chord = composer_module.Chord
+        major = composer_module.ScaleFingerprint.major()
+        mirrored = composer_module.negative_harmony_mirror(Chord(5, 7), major)
+        assert (mirrored.root, mirrored.chord_type) == (2, 7)
+        assert mirrored.borrowed is not None
+
+        twice = composer_module.negative_harmony_mirror(mirrored, major, (0, 7))
+        assert (twice.root, twice.borrowed) == (5, None)
+
+    def test_modal_interchange_options(self, composer_module) -> None:
+        """IV borrows iv first, then chords sharing its function."""
+        major = composer_module.ScaleFingerprint.major()
+        options = composer_module.modal_interchange_options(
+            composer_module.Chord(4, 5), major, "minor"
+        )
+        assert [option.root for option in options] == [4, 2, 6, 7]
+        with pytest.raises(ValueError):
+            composer_module.modal_interchange_options(
+                composer_module.Chord(4, 5), major, "nonexistent"
+            )
+
+    def test_progression_reharmonize(self, composer_module) -> None:
+        """Secondary dominants are inserted before each tonicizable chord."""
+        Chord = composer_module.Chord
+        major = composer_module.ScaleFingerprint.major()
+        progression = [Chord(1, 5), Chord(2, 7), Chord(5, 7), Chord(1, 5)]
+
+        reharmonized = composer_module.progression_reharmonize(
+            progression, major, "secondary_dominants"
+        )
+        assert len(reharmonized) == 6
+        assert (reharmonized[1].root, reharmonized[1].applied) == (5, 2)
+
+        negative = composer_module.progression_reharmonize(
+            progression, major, "negative_harmony"
+        )
+        assert negative[2].root == 2
+        with pytest.raises(ValueError):
+            composer_module.progression_reharmonize(progression, major, "unknown")
+
+
+class TestBulkPatterns:
+    """Test bulk training pattern loading."""
+
+    def test_add_patterns_bulk(self, composer_module) -> None:
+        """Valid patterns load; rejects are reported by position."""
+        Chord = composer_module.Chord
+        engine = composer_module.AiEngine()
+        report = engine.add_patterns_bulk(
+            [
+                ([Chord(1, 5), Chord(4, 5)], "first", None),
+                ([], "empty", None),
+                ([Chord(1, 5), Chord(5, 5)], "second", "C"),
+            ]
+        )
+        assert report["added"] == 2
+        index, source_id, error = report["rejected"][0]
+        assert (index, source_id) == (1, "empty")
+        assert "empty" in error
+        assert engine.get_metrics()["total_patterns"] == 2
+
+    def test_weighted_patterns(self, composer_module) -> None:
+        """Weighted patterns load alongside unweighted ones; bad weights are rejected."""
+        Chord = composer_module.Chord
+        engine = composer_module.AiEngine()
+        report = engine.add_patterns_bulk(
+            [([Chord(1, 5), Chord(4, 5)], "canon", "C")], weight=3.0
+        )
+        assert report["added"] == 1
+        engine.add_training_pattern([Chord(1, 5), Chord(5, 5)], "user", None)
+        engine.add_training_pattern([Chord(1, 5), Chord(6, 5)], "user2", None, weight=0.5)
+        assert engine.get_metrics()["total_patterns"] == 3
+
+        report = engine.add_patterns_bulk([([Chord(2, 5)], "zero", None)], weight=0.0)
+        assert report["added"] == 0
+        assert "weight" in report["rejected"][0][2]
+        with pytest.raises(Exception):
+            engine.add_training_pattern([Chord(1, 5)], "negative", None, weight=-1.0)
+
+    def test_augment_patterns(self, composer_module) -> None:
+        """Augmented corpora cover every key and voicing of each pattern."""
+        Chord = composer_module.Chord
+        corpus = [([Chord(1, 5), Chord(5, 7)], "song", "C")]
+        keys = composer_module.AiEngine.augment_patterns(
+            corpus, invert=False, vary_extensions=False, time_stretch=[]
+        )
+        assert [key for _, _, key in keys][:3] == ["C", "Db", "D"]
+        assert keys[3][1] == "song [Eb]"
+
+        augmented = composer_module.AiEngine.augment_patterns(corpus, transpose=False)
+        sources = [source for _, source, _ in augmented]
+        assert sources[0] == "song"
+        assert "song [inv1]" in sources
+        with pytest.raises(Exception):
+            composer_module.AiEngine.augment_patterns(corpus, time_stretch=[0.0])
+
+    def test_add_timeline_patterns(self, composer_module) -> None:
+        """Duration weighting keeps passing chords from dominating."""
+
+        def timeline(*chords):
+            events = [
+                composer_module.TokenEvent(
+                    beat=beat, chord=composer_module.Chord(root, 5)
+                )
+                for root, beat in chords
+            ]
+            return composer_module.Timeline(events, total_duration=4.0)
+
+        patterns = [
+            (timeline((1, 0.0), (2, 3.5)), "a", None),
+            (timeline((1, 0.0), (2, 3.5)), "b", None),
+            (timeline((1, 0.0), (4, 2.0)), "c", None),
+        ]
+        engine = composer_module.AiEngine()
+        report = engine.add_timeline_patterns(patterns, weight_by_duration=True)
+        assert report == {"added": 3, "rejected": []}
+        assert engine.get_metrics()["total_patterns"] == 3
+
+        config = composer_module.SuggestionConfig(min_confidence=0.0)
+        suggestions = engine.get_chord_suggestions(
+            [composer_module.Chord(1, 5)], None, config
+        )
+        durations = {s.chord.root: s.suggested_duration for s in suggestions}
+        assert durations == {2: 0.5, 4: 2.0}
+
+
+class TestVoicings:
+    """Test instrument voicings."""
+
+    def test_piano_voicing(self, composer_module) -> None:
+        """Drop voicings lower one voice of the close voicing by an octave."""
+        major = composer_module.ScaleFingerprint.major()
+        g7 = composer_module.Chord(5, 7)
+        assert composer_module.piano_voicing(g7, major) == [67, 71, 74, 77]
+        assert composer_module.piano_voicing(g7, major, style="drop2") == [
+            62,
+            67,
+            71,
+            77,
+        ]
+        with pytest.raises(Exception):
+            composer_module.piano_voicing(composer_module.Chord(1, 5), major, style="drop2")
+        with pytest.raises(ValueError):
+            composer_module.piano_voicing(g7, major, style="spread")
+
+    def test_guitar_fingerings(self, composer_module) -> None:
+        """The open C shape comes first in standard tuning."""
+        major = composer_module.ScaleFingerprint.major()
+        fingerings = composer_module.guitar_fingerings(composer_module.Chord(1, 5), major)
+        assert fingerings[0] == [None, 3, 2, 0, 1, 0]
+
+
+class TestTokenInitialization:
+    """Test engine initialization from token streams."""
+
+    def test_initialize_from_tokens(self, composer_module) -> None:
+        """Token patterns resolve through a token library; unknown tokens fail."""
+        library = composer_module.TokenLibrary()
+        for token, root in (("I", 1), ("IV", 4), ("V", 5)):
+            binary = composer_module.serialize_chord_to_binary(
+                composer_module.Chord(root, 5)
+            )
+            library.add_chord_token(token, binary)
+
+        engine = composer_module.AiEngine()
+        engine.initialize_from_tokens(
+            library, [(["I", "IV", "V"], "1", None), (["I", "V"], "2", "G")]
+        )
+        assert engine.is_initialized()
+        assert engine.get_metrics()["total_patterns"] == 2
+
+        with pytest.raises(Exception):
+            composer_module.AiEngine().initialize_from_tokens(
+                library, [(["I", "vi"], "3", None)]
+            )
+
+
+class TestDifficultyCalibration:
+    """Test percentile calibration against a corpus."""
+
+    def test_calibrate_difficulty(self, composer_module) -> None:
+        """Assessments report their percentile once calibrated."""
+        Chord = composer_module.Chord
+        engine = composer_module.AiEngine()
+        engine.initialize([])
+        simple = [Chord(1, 5), Chord(5, 5)]
+        complex_ = [Chord(2, 9), Chord(5, 13), Chord(1, 11)]
+        corpus = [engine.assess_difficulty(song) for song in (simple, complex_)]
+        assert corpus[0].percentile is None
+
+        engine.calibrate_difficulty(corpus)
+        assert engine.assess_difficulty(simple).percentile == 0
+        assert engine.assess_difficulty(complex_).percentile == 50
+        with pytest.raises(Exception):
+            engine.calibrate_difficulty([])
+
+        engine.calibrate_difficulty(None)
+        assert engine.assess_difficulty(simple).percentile is None
+
+
+class TestFittedDifficultyModel:
+    """Test fitting the polynomial difficulty model to labeled progressions."""
+
+    def test_fit_difficulty_model(self, composer_module) -> None:
+        """A fitted model is installed and can be restored from JSON."""
+        Chord = composer_module.Chord
+        engine = composer_module.AiEngine()
+        engine.initialize([])
+        examples = [
+            ([Chord(1, 5), Chord(4, 5)], 1.0),
+            ([Chord(1, 5), Chord(5, 5), Chord(1, 5)], 1.5),
+            ([Chord(2, 7), Chord(5, 7), Chord(1, 5)], 4.0),
+            ([Chord(6, 5), Chord(4, 7), Chord(5, 5), Chord(3, 5)], 5.0),
+            ([Chord(7, 7), Chord(3, 7), Chord(6, 7), Chord(2, 7)], 7.5),
+            ([Chord(2, 5), Chord(6, 5), Chord(7, 7), Chord(4, 5), Chord(1, 7)], 6.0),
+        ]
+        default = json.loads(engine.polynomial_model())
+
+        model_json = engine.fit_difficulty_model(examples)
+        fitted = json.loads(model_json)
+        assert 0.0 <= fitted["accuracy"] <= 1.0
+        assert json.loads(engine.polynomial_model()) == fitted
+
+        engine.set_polynomial_model(None)
+        assert json.loads(engine.polynomial_model()) == default
+        engine.set_polynomial_model(model_json)
+        installed = json.loads(engine.polynomial_model())
+        assert installed["coefficients"] == pytest.approx(fitted["coefficients"])
+
+        with pytest.raises(Exception):
+            engine.fit_difficulty_model(examples[:2])
+        with pytest.raises(Exception):
+            engine.set_polynomial_model("{")
+
+
+class TestInstrumentDifficulty:
+    """Test instrument-specific difficulty assessment."""
+
+    def test_assess_difficulty_by_instrument(self, composer_module) -> None:
+        """Each instrument gets its own assessment; unknown instruments fail."""
+        engine = composer_module.AiEngine()
+        engine.initialize([])
+        progression = [
+            composer_module.Chord(4, 5),
+            composer_module.Chord(2, 5),
+            composer_module.Chord(5, 5),
+        ]
+
+        levels = engine.assess_difficulty_by_instrument(progression)
+        assert set(levels) == {"piano", "guitar", "ukulele", "voice"}
+        for instrument, assessment in levels.items():
+            assert assessment.instrument == instrument
+            assert 0.0 <= assessment.overall_score <= 10.0
+        assert (
+            levels["guitar"].technical_complexity > levels["piano"].technical_complexity
+        )
+
+        guitar = engine.assess_instrument_difficulty(progression, "Guitar")
+        assert guitar.instrument == "guitar"
+        assert engine.assess_difficulty(progression).instrument is None
+        with pytest.raises(ValueError):
+            engine.assess_instrument_difficulty(progression, "banjo")
+
+
+class TestPerformanceBudget:
+    """Test configurable performance budgets."""
+
+    def test_performance_budget(self, composer_module) -> None:
+        """Budgets update field by field and violations start at zero."""
+        engine = composer_module.AiEngine()
+        assert engine.get_performance_budget()["mode"] == "warn"
+
+        engine.set_performance_budget(chord_lookup_max_ms=25, mode="fail")
+        budget = engine.get_performance_budget()
+        assert budget["chord_lookup_max_ms"] == 25
+        assert budget["mode"] == "fail"
+        assert budget["asset_loading_max_ms"] == 30000
+        assert engine.get_metrics()["budget_violations"] == {}
+        with pytest.raises(ValueError):
+            engine.set_performance_budget(mode="ignore")
+
+    def test_compact_trie(self, composer_module) -> None:
+        """Compaction reports node counts and never grows storage."""
+        engine = composer_module.AiEngine()
+        stats = engine.compact_trie()
+        assert stats["nodes"] >= 1
+        assert stats["bytes_after"] <= stats["bytes_before"]
+        assert stats["bytes_saved"] == stats["bytes_before"] - stats["bytes_after"]
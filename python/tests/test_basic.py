@@ -39,3 +39,18 @@ def test_version() -> None:
             assert len(version) > 0
     except ImportError:
         pytest.skip("Composer module not built yet")
+
+
+def test_enable_tracing() -> None:
+    """Test that a tracing subscriber can be installed once."""
+    try:
+        import composer
+
+        with pytest.raises(ValueError):
+            composer.enable_tracing("verbose")
+
+        composer.enable_tracing("error")
+        with pytest.raises(RuntimeError):
+            composer.enable_tracing()
+    except ImportError:
+        pytest.skip("Composer module not built yet")
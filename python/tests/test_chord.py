@@ -153,6 +153,20 @@ class TestChordProperties:
         except Exception as e:
             pytest.skip(f"Chord string representation test failed: {e}")
 
+    def test_is_isotonal(self) -> None:
+        """Chords with the same pitch classes are isotonal."""
+        try:
+            import composer
+
+            major = composer.ScaleFingerprint.major()
+            tonic = composer.Chord(1, 5)
+            first_inversion = composer.Chord(1, 5, inversion=1)
+            assert composer.is_isotonal(tonic, first_inversion, major)
+            assert not composer.is_isotonal(tonic, composer.Chord(6, 5), major)
+
+        except ImportError:
+            pytest.skip("Composer module not built yet")
+
 
 class TestChordSerialization:
     """Test chord serialization functionality."""
@@ -263,6 +277,50 @@ class TestChordValidation:
         except Exception as e:
             pytest.skip(f"Invalid chord type test failed: {e}")
 
+    def test_validate_modification(self) -> None:
+        """Conflicting modifications are reported before construction."""
+        try:
+            import composer
+
+            chord = composer.Chord(5, 7)
+            chord.add_suspension(4)
+            chord.add_alteration("b5")
+
+            assert composer.validate_modification(chord, "sus2") == [
+                {"reason": "Incompatible", "existing": "sus4"}
+            ]
+            assert composer.validate_modification(chord, "b7") == [
+                {"reason": "InvalidValue", "existing": None}
+            ]
+            assert composer.validate_modification(chord, "add9") == []
+            assert chord.compatible_alterations() == ["b5", "b9", "#9", "#11", "b13"]
+            with pytest.raises(ValueError):
+                composer.validate_modification(chord, "susx")
+
+        except ImportError:
+            pytest.skip("Composer module not built yet")
+
+    def test_error_codes(self) -> None:
+        """Errors carry stable codes and structured details."""
+        try:
+            import composer
+
+            with pytest.raises(composer.ChordTheoryError) as raised:
+                composer.Chord(9, 5)
+
+            error = raised.value
+            assert isinstance(error, composer.ComposerError)
+            assert isinstance(error, ValueError)
+            assert error.code == "invalid-chord-root"
+            assert error.domain == "theory"
+            assert error.details == {"root": 9}
+
+            constants = composer.get_configuration_constants()
+            assert constants["ERROR_INVALID_CHORD_ROOT"] == error.code
+
+        except ImportError:
+            pytest.skip("Composer module not built yet")
+
 
 @pytest.mark.parametrize(
     "root,chord_type,expected_complexity",
@@ -0,0 +1,115 @@
+"""Tests for pickle support of the Rust wrapper classes."""
+
+from __future__ import annotations
+
+import copy
+import pickle
+
+import pytest
+
+
+def roundtrip(obj: object) -> object:
+    """Pickle and unpickle an object."""
+    return pickle.loads(pickle.dumps(obj))
+
+
+@pytest.fixture
+def trained_engine(composer_module) -> object:
+    """AI engine trained on two short pop progressions."""
+    chord = composer_module.Chord
+    engine = composer_module.AiEngine(max_memory_mb=64)
+    engine.initialize(
+        [
+            ([chord(1, 5), chord(4, 5), chord(5, 5), chord(1, 5)], "pop_1", None),
+            ([chord(1, 5), chord(6, 5), chord(4, 5), chord(5, 5)], "pop_2", None),
+        ]
+    )
+    return engine
+
+
+class TestCorePickling:
+    """Pickling of core theory types."""
+
+    def test_chord_roundtrip(self, composer_module) -> None:
+        """Chords keep every field across pickling."""
+        chord = composer_module.Chord(5, 7, inversion=1, applied=2)
+        chord.add_alteration("b9")
+
+        restored = roundtrip(chord)
+        assert restored.root == 5
+        assert restored.chord_type == 7
+        assert restored.inversion == 1
+        assert restored.applied == 2
+        assert restored.alterations == ["b9"]
+        assert copy.deepcopy(chord).alterations == ["b9"]
+
+    def test_scale_and_note_roundtrip(self, composer_module) -> None:
+        """Scale fingerprints, borrowed scales and notes survive pickling."""
+        dorian = composer_module.ScaleFingerprint.dorian()
+        assert roundtrip(dorian).scale_degrees == dorian.scale_degrees
+
+        borrowed = roundtrip(composer_module.BorrowedScale("harmonic_minor"))
+        assert borrowed.scale_name == "harmonic_minor"
+
+        note = roundtrip(composer_module.Note(3, 4))
+        assert (note.scale_degree, note.octave) == (3, 4)
+
+
+class TestSerializationPickling:
+    """Pickling of types backed by binary formats."""
+
+    def test_trie_and_token_library_roundtrip(self, composer_module) -> None:
+        """Tries and token libraries use their binary formats as state."""
+        pattern = [[0x10, 0, 0, 0, 0], [0x50, 0x08, 0, 0, 0]]
+        trie = composer_module.TrieNode()
+        trie.add_pattern(pattern, 7)
+        assert roundtrip(trie).search_patterns(pattern) == [7]
+
+        library = composer_module.TokenLibrary()
+        library.add_chord_token("C", bytes(5))
+        assert len(roundtrip(library)) == 1
+
+        assert roundtrip(composer_module.Timeline()).event_count == 0
+
+
+class TestAiPickling:
+    """Pickling of the AI engine and its results."""
+
+    def test_engine_roundtrip(self, composer_module, trained_engine) -> None:
+        """Trained engines keep their patterns; untrained engines stay untrained."""
+        chord = composer_module.Chord
+        assert not roundtrip(composer_module.AiEngine()).is_initialized()
+
+        restored = roundtrip(trained_engine)
+        assert restored.is_initialized()
+
+        pattern = [chord(1, 5)]
+        original = trained_engine.get_chord_suggestions(pattern, min_confidence=0.0)
+        again = restored.get_chord_suggestions(pattern, min_confidence=0.0)
+        assert [s.chord.root for s in original] == [s.chord.root for s in again]
+
+    def test_results_and_settings_roundtrip(
+        self, composer_module, trained_engine
+    ) -> None:
+        """Suggestion settings and analysis results can cross process boundaries."""
+        chord = composer_module.Chord
+        context = roundtrip(
+            composer_module.SuggestionContext(
+                target_valence=0.7, recent_chords=[chord(4, 5)]
+            )
+        )
+        assert context.target_valence == pytest.approx(0.7)
+        assert len(context.recent_chords) == 1
+        config = roundtrip(composer_module.SuggestionConfig(max_suggestions=3))
+        assert config.max_suggestions == 3
+
+        progression = [chord(1, 5), chord(5, 7)]
+        assessment = trained_engine.assess_difficulty(progression)
+        restored = roundtrip(assessment)
+        assert restored.overall_score == pytest.approx(assessment.overall_score)
+
+        analysis = trained_engine.analyze_progression(progression)
+        assert roundtrip(analysis).improvements == analysis.improvements
+
+        harmonization = roundtrip(trained_engine.harmonize_bass_line(progression))
+        assert harmonization.style == "Root"
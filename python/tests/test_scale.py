@@ -652,3 +652,30 @@ class TestScaleTransposition:
             pytest.skip("Composer module not built yet")
         except Exception as e:
             pytest.skip(f"Scale relative modes test failed: {e}")
+
+
+class TestScaleArithmetic:
+    """Test rotation, set operations and comparison of scales."""
+
+    def test_rotate_gives_modes(self, composer_module) -> None:
+        """Rotating a scale by n notes gives its modes."""
+        major = composer_module.ScaleFingerprint.major()
+        assert major.rotate(1) == composer_module.ScaleFingerprint.dorian()
+        assert major.rotate(4) == composer_module.ScaleFingerprint.mixolydian()
+        assert major.rotate(7) == major
+
+    def test_set_operations(self, composer_module) -> None:
+        """Intersection and difference return None when no notes remain."""
+        major = composer_module.ScaleFingerprint.major()
+        minor = composer_module.ScaleFingerprint.minor()
+        assert major.intersection(minor).chromatic_notes == [0, 2, 5, 7]
+        assert major.difference(minor).chromatic_notes == [4, 9, 11]
+        assert major.difference(composer_module.ScaleFingerprint.chromatic()) is None
+        assert major.distance(minor) == 6
+        assert major.distance(major) == 0
+
+    def test_contains_chord(self, composer_module) -> None:
+        """Diatonic chords are contained, chromatic ones are not."""
+        major = composer_module.ScaleFingerprint.major()
+        assert major.contains_chord(composer_module.Chord(5, 7))
+        assert not major.contains_chord(composer_module.Chord(5, 5, applied=5))
@@ -2,6 +2,10 @@
 
 from __future__ import annotations
 
+import io
+import json
+import pickle
+
 import pytest
 
 
@@ -497,3 +501,264 @@ class TestBatchOperations:
             pytest.skip("Composer module not built yet")
         except Exception as e:
             pytest.skip(f"Batch deserialization test failed: {e}")
+
+
+class TestSequenceProtocols:
+    """Test Python sequence and iterator protocols on timelines and tries."""
+
+    def test_timeline_sequence(self, composer_module) -> None:
+        """Timelines support len(), indexing, slicing and iteration."""
+        tokens = ["NOTE-60-ON", "DELAY-1", "CHORD-1-ON", "DELAY-1", "NOTE-62-ON"]
+        timeline = composer_module.py_detokenize_midi_like(
+            tokens, composer_module.ScaleFingerprint.major()
+        )
+
+        assert len(timeline) == 3
+        assert [event.kind for event in timeline] == ["note", "chord", "note"]
+        assert [event.beat for event in timeline[1:]] == [1.0, 2.0]
+        assert timeline[-1].note.octave == timeline[0].note.octave
+        assert timeline[1].chord.root == 1
+        assert timeline[0].chord is None
+
+        with pytest.raises(IndexError):
+            timeline[3]
+
+    def test_trie_iteration(self, composer_module) -> None:
+        """Iterating a trie yields each stored pattern with its ids."""
+        tonic = bytes([0x10, 0, 0, 0, 0])
+        dominant = bytes([0x50, 0x08, 0, 0, 0])
+        trie = composer_module.TrieNode()
+        trie.add_pattern([list(tonic), list(dominant)], 1)
+        trie.add_pattern([list(tonic)], 2)
+
+        assert list(trie) == [([tonic], [2]), ([tonic, dominant], [1])]
+        assert list(composer_module.TrieNode()) == []
+
+    def test_trie_file_streaming(self, composer_module) -> None:
+        """Tries stream to and from binary files in chunks."""
+        tonic = [0x10, 0, 0, 0, 0]
+        dominant = [0x50, 0x08, 0, 0, 0]
+        trie = composer_module.TrieNode()
+        for pattern_id in range(100):
+            trie.add_pattern([tonic, dominant], pattern_id)
+
+        file = io.BytesIO()
+        written = composer_module.py_serialize_trie_to_file(trie, file, chunk_size=16)
+        assert written == len(file.getvalue())
+
+        file.seek(0)
+        restored = composer_module.py_deserialize_trie_from_file(file)
+        assert list(restored) == list(trie)
+
+        with pytest.raises(Exception):
+            composer_module.py_deserialize_trie_from_file(io.BytesIO(file.getvalue()[:-1]))
+
+
+class TestJson:
+    """Test human-readable JSON for chords, timelines and scales."""
+
+    def test_chord_json_round_trip(self, composer_module) -> None:
+        """Chords round-trip and hand-written JSON may leave fields out."""
+        chord = composer_module.Chord.from_json('{"root": 5, "chord_type": 7}')
+        assert chord == composer_module.Chord.seventh(5)
+        assert composer_module.Chord.from_json(chord.to_json()) == chord
+        assert json.loads(chord.to_json())["chord_type"] == 7
+
+        with pytest.raises(Exception):
+            composer_module.Chord.from_json('{"root": 9}')
+
+    def test_timeline_and_scale_json(self, composer_module) -> None:
+        """Timelines and scale fingerprints round-trip through JSON."""
+        timeline = composer_module.Timeline.from_json(
+            '{"events": [{"beat": 0.0, "event_type": "Rest"}], "total_duration": 1.0}'
+        )
+        assert len(timeline) == 1
+        restored = composer_module.Timeline.from_json(timeline.to_json())
+        assert restored.to_json() == timeline.to_json()
+
+        major = composer_module.ScaleFingerprint.major()
+        assert json.loads(major.to_json()) == [1, 0, 1, 0, 1, 1, 0, 1, 0, 1, 0, 1]
+        assert composer_module.ScaleFingerprint.from_json(major.to_json()) == major
+
+
+class TestTokenStreamValidation:
+    """Test whole-stream grammar validation."""
+
+    def test_validate_token_stream(self, composer_module) -> None:
+        """Valid streams pass; violations report the first offending token."""
+        validate = composer_module.py_validate_token_stream
+        assert validate("M_DOWN O_4 R_0 D_18 <CHORD>R_0-R_4-R_7</CHORD> D_30") is None
+        assert validate(["R_0", "O_4", "D_18", "NOTE-REST", "D_c"]) is None
+
+        assert validate(["O_4", "D_18"]) == (
+            1,
+            "D_18",
+            "duration does not follow a note, rest or chord",
+        )
+        assert validate("R_0 D_18")[2] == "raw note has no octave"
+        assert validate("<CHORD> R_0-R_4")[:2] == (0, "<CHORD>")
+        assert validate("<NOTES> <CHORD>")[0] == 1
+
+
+class TestChordBinaryValidation:
+    """Test field-by-field reports for 5-byte chord payloads."""
+
+    def test_valid_chord_report(self, composer_module) -> None:
+        """Serialized chords validate and decode back to the same chord."""
+        chord = composer_module.Chord(5, 7)
+        report = composer_module.py_validate_chord_binary(
+            composer_module.chord_to_hex(chord)
+        )
+        assert report["valid"]
+        assert report["chord"] == chord
+        assert report["chord_error"] is None
+        assert {field["status"] for field in report["fields"]} == {"valid"}
+        root = next(field for field in report["fields"] if field["name"] == "root")
+        assert root["value"] == "5"
+
+    def test_reserved_bits_by_mode(self, composer_module) -> None:
+        """Reserved bits are errors when strict and warnings when lenient."""
+        data = bytearray(composer_module.serialize_chord_to_binary(composer_module.Chord(1, 5)))
+        data[0] |= 0x80
+        strict = composer_module.py_validate_chord_binary(bytes(data))
+        assert not strict["valid"]
+        assert strict["fields"][0]["status"] == "invalid"
+
+        lenient = composer_module.py_validate_chord_binary(bytes(data), "lenient")
+        assert lenient["valid"]
+        assert lenient["fields"][0]["status"] == "warning"
+        assert lenient["chord"] == composer_module.Chord(1, 5)
+
+        with pytest.raises(ValueError):
+            composer_module.py_validate_chord_binary(bytes(data), "loose")
+        with pytest.raises(Exception):
+            composer_module.py_validate_chord_binary(b"\x00")
+
+
+class TestPolyphonicTokenization:
+    """Test tokenization of note clusters with per-note durations."""
+
+    def test_polyphonic_round_trip(self, composer_module) -> None:
+        """Clusters keep each note's duration through tokens and back."""
+        TokenEvent = composer_module.TokenEvent
+        held = [(0, 3, 4.0), (4, 4, 2.0), (7, 4, 2.0)]
+        events = [
+            TokenEvent(0.0, cluster=held),
+            TokenEvent(2.0, cluster=[(5, 4, 2.0), (9, 4, 2.0)]),
+        ]
+        assert events[0].kind == "cluster"
+        assert events[0].cluster == held
+        timeline = composer_module.Timeline(events, total_duration=4.0)
+
+        tokens = composer_module.py_tokenize_polyphonic(
+            timeline, composer_module.ScaleFingerprint.major()
+        )
+        assert composer_module.py_validate_token_stream(tokens) is None
+        assert tokens[:4] == ["<NOTES>", "R_0", "O_3", "D_60"]
+
+        restored = composer_module.py_detokenize_polyphonic(" ".join(tokens))
+        assert [event.cluster for event in restored] == [event.cluster for event in events]
+        assert restored.total_duration == 4.0
+
+        with pytest.raises(ValueError):
+            TokenEvent(0.0, chord=composer_module.Chord(1, 5), cluster=held)
+        with pytest.raises(Exception):
+            composer_module.py_detokenize_polyphonic("<NOTES> R_0 O_4 </NOTES> D_18")
+
+    def test_timeline_round_trip(self, composer_module) -> None:
+        """Whole timelines keep chords, keys and meters through one token sequence."""
+        TokenEvent = composer_module.TokenEvent
+        chords = [composer_module.Chord(1, 5), composer_module.Chord(5, 7)]
+        events = [TokenEvent(0.0, chord=chords[0]), TokenEvent(3.0, chord=chords[1])]
+        timeline = composer_module.Timeline(
+            events,
+            total_duration=6.0,
+            meter_changes=[(0.0, (3, 4))],
+            key_changes=[(0.0, "G")],
+        )
+        assert timeline.key_changes == [(0.0, "G")]
+
+        tokens = composer_module.py_tokenize_timeline(timeline)
+        assert tokens[:3] == ["BAR", "K_7_MAJ", "TS_3_4"]
+        assert tokens.count("BAR") == 2
+
+        restored = composer_module.py_detokenize_tokens(tokens)
+        assert [event.chord for event in restored] == chords
+        assert restored.key_changes == [(0.0, "G")]
+        assert restored.meter_changes == [(0.0, (3, 4))]
+
+
+class TestTokenVocabularyUpgrade:
+    """Test upgrading tokens from older vocabulary versions."""
+
+    def test_upgrade_token_stream(self, composer_module) -> None:
+        """Decimal version 1 numbers become hex; other tokens are unchanged."""
+        upgrade = composer_module.py_upgrade_token_stream
+        assert upgrade("O_4 R_10 D_48 M_DOWN", 1) == ["O_4", "R_a", "D_30", "M_DOWN"]
+        assert upgrade(["R_0-R_4-R_11"], 1) == ["R_0-R_4-R_b"]
+        assert upgrade(["D_48"], 2) == ["D_48"]
+        with pytest.raises(Exception):
+            upgrade(["D_48"], 0)
+
+    def test_token_library_upgrade(self, composer_module) -> None:
+        """Old libraries are upgraded in place and when unpickled."""
+        binary = composer_module.serialize_chord_to_binary(composer_module.Chord(5, 5))
+        library = composer_module.TokenLibrary(schema_version=1)
+        library.add_chord_token("R_7-R_11-R_2", binary)
+        assert library.schema_version == 1
+
+        restored = pickle.loads(pickle.dumps(library))
+        assert restored.schema_version == 2
+        assert restored.resolve_chord_token("R_7-R_b-R_2") == binary
+
+        assert library.upgrade() == 1
+        assert library.resolve_chord_token("R_7-R_b-R_2") == binary
+        assert composer_module.TokenLibrary().schema_version == 2
+
+    def test_token_vocabulary_round_trip(self, composer_module) -> None:
+        """Exported vocabularies keep fixed ids and restore the library's chords."""
+        binary = composer_module.serialize_chord_to_binary(composer_module.Chord(1, 5))
+        library = composer_module.TokenLibrary()
+        library.add_chord_token("R_0-R_4-R_7", binary)
+
+        vocabulary = json.loads(library.export_vocab())
+        assert vocabulary["tokens"][:4] == ["<PAD>", "<UNK>", "<BOS>", "<EOS>"]
+        assert vocabulary["tokens"][-1] == "R_0-R_4-R_7"
+
+        for data in (library.export_vocab(), library.export_vocab(binary=True)):
+            restored = composer_module.TokenLibrary.import_vocab(data)
+            assert restored.resolve_chord_token("R_0-R_4-R_7") == binary
+        with pytest.raises(Exception):
+            composer_module.TokenLibrary.import_vocab(b"CVOC")
+
+
+class TestMetricTokens:
+    """Test beat-strength tokens for metric placement."""
+
+    def test_tokenize_metric_position(self, composer_module) -> None:
+        """Onsets map to downbeat, mid-bar, beat, anticipation or off-beat tokens."""
+        tokenize = composer_module.py_tokenize_metric_position
+        assert [tokenize(beat) for beat in (0.0, 1.0, 2.0, 2.5, 3.5)] == [
+            "M_DOWN",
+            "M_BEAT",
+            "M_MID",
+            "M_OFF",
+            "M_ANT",
+        ]
+        assert tokenize(1.5, (6, 8)) == "M_MID"
+        assert composer_module.py_validate_metric_token("M_ANT")
+        assert not composer_module.py_validate_metric_token("M_UP")
+        with pytest.raises(Exception):
+            tokenize(1.0, (4, 3))
+
+    def test_timeline_metric_tokens(self, composer_module) -> None:
+        """Every timeline event gets the token of its onset."""
+        tokens = ["CHORD-1-ON", "DELAY-3.5", "CHORD-5-ON", "DELAY-0.5", "CHORD-1-ON"]
+        timeline = composer_module.py_detokenize_midi_like(
+            tokens, composer_module.ScaleFingerprint.major()
+        )
+        assert composer_module.py_timeline_metric_tokens(timeline) == [
+            "M_DOWN",
+            "M_ANT",
+            "M_DOWN",
+        ]
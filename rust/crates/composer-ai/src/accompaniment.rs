@@ -0,0 +1,363 @@
+//! Accompaniment patterns rendered from chord timelines
+//!
+//! Expands the chords of a timeline into note-level accompaniment: block
+//! chords, Alberti bass, arpeggios or strumming, on a pulse set by the
+//! density. Each chord is voiced above middle C as by [`piano_voicing`], with
+//! its bass an octave below the lowest voice, and the notes are written as
+//! [`TokenEventType::Cluster`] events carrying their own durations, so the
+//! result can be tokenized with `tokenize_polyphonic` or exported as MIDI.
+
+use crate::error::{AiError, AiResult};
+use composer_core::{piano_voicing, Chord, PianoVoicingStyle, ScaleFingerprint};
+use composer_serialization::{
+    ClusterNote, Timeline, TokenEvent, TokenEventType, OCTAVE_RANGE_MAX, OCTAVE_RANGE_MIN,
+};
+use serde::{Deserialize, Serialize};
+
+/// Pulses shorter than this (in quarter notes) are not rendered
+const MIN_PULSE_LENGTH: f64 = 1e-6;
+
+/// Upper voices sounded by an up-strum
+const UP_STRUM_VOICES: usize = 3;
+
+/// Note-level patterns chords are rendered as
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum AccompanimentStyle {
+    /// Voicing struck on every pulse over a bass held through the chord
+    #[default]
+    Block,
+    /// Voicing an octave lower, broken into a low-high-middle-high figure
+    Alberti,
+    /// Bass, then the voicing one note per pulse, rising and falling
+    Arpeggio,
+    /// Down-strums of bass and voicing on even pulses, up-strums of the
+    /// upper voices on odd pulses
+    Strum,
+}
+
+/// How many notes per beat an accompaniment moves in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum AccompanimentDensity {
+    /// A pulse every quarter note
+    Sparse,
+    /// A pulse every eighth note
+    #[default]
+    Moderate,
+    /// A pulse every sixteenth note
+    Dense,
+}
+
+impl AccompanimentDensity {
+    /// Pulse length in quarter notes
+    pub fn pulse(&self) -> f64 {
+        match self {
+            AccompanimentDensity::Sparse => 1.0,
+            AccompanimentDensity::Moderate => 0.5,
+            AccompanimentDensity::Dense => 0.25,
+        }
+    }
+}
+
+/// Options for [`render_accompaniment`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccompanimentOptions {
+    pub style: AccompanimentStyle,
+    pub density: AccompanimentDensity,
+    /// Scale the chords' degrees are read in
+    pub scale: ScaleFingerprint,
+    /// Tonic pitch class of the key (0 = C)
+    pub tonic: u8,
+}
+
+impl Default for AccompanimentOptions {
+    fn default() -> Self {
+        Self {
+            style: AccompanimentStyle::default(),
+            density: AccompanimentDensity::default(),
+            scale: ScaleFingerprint::major_scale(),
+            tonic: 0,
+        }
+    }
+}
+
+/// Cluster note of a MIDI note, moved by octaves into the tokenizable range
+fn cluster_note(note: u8, duration: f64) -> ClusterNote {
+    let lowest = (OCTAVE_RANGE_MIN + 1) * 12;
+    let highest = (OCTAVE_RANGE_MAX + 2) * 12 - 1;
+    let mut note = note;
+    while note < lowest {
+        note += 12;
+    }
+    while note > highest {
+        note -= 12;
+    }
+    ClusterNote {
+        pitch_class: note % 12,
+        octave: note / 12 - 1,
+        duration,
+    }
+}
+
+/// MIDI notes struck on pulse `step` of a chord voiced as `voicing` over `bass`
+fn pulse_notes(style: AccompanimentStyle, step: usize, voicing: &[u8], bass: u8) -> Vec<u8> {
+    let top = voicing.len() - 1;
+    match style {
+        AccompanimentStyle::Block => voicing.to_vec(),
+        AccompanimentStyle::Alberti => {
+            let figure = [0, top, top / 2, top];
+            vec![voicing[figure[step % figure.len()]] - 12]
+        },
+        AccompanimentStyle::Arpeggio => {
+            let cycle: Vec<u8> = std::iter::once(bass)
+                .chain(voicing.iter().copied())
+                .chain(voicing[..top].iter().rev().copied())
+                .collect();
+            vec![cycle[step % cycle.len()]]
+        },
+        AccompanimentStyle::Strum if step % 2 == 0 => std::iter::once(bass)
+            .chain(voicing.iter().copied())
+            .collect(),
+        AccompanimentStyle::Strum => {
+            voicing[voicing.len().saturating_sub(UP_STRUM_VOICES)..].to_vec()
+        },
+    }
+}
+
+/// Expand the chords of a timeline into an accompaniment timeline.
+///
+/// Each chord sounds until the next chord event or the end of the timeline;
+/// rest chords silence the accompaniment. Pulses start on each chord's onset,
+/// so figures restart with every chord, and the last pulse of a chord is cut
+/// short where the chord ends. Notes, note clusters and rests of the input
+/// are not copied. The result keeps the timeline's length and meter changes.
+///
+/// Fails with `AiError::SuggestionFailed` for a chord that cannot be voiced.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::{render_accompaniment, AccompanimentOptions, AccompanimentStyle};
+/// use composer_core::Chord;
+/// use composer_serialization::{Timeline, TokenEvent, TokenEventType};
+///
+/// let timeline = Timeline {
+///     events: vec![TokenEvent { beat: 0.0, event_type: TokenEventType::Chord(Chord::triad(1)?) }],
+///     total_duration: 2.0,
+///     ..Default::default()
+/// };
+/// let options = AccompanimentOptions {
+///     style: AccompanimentStyle::Alberti,
+///     ..Default::default()
+/// };
+///
+/// // C E G voiced an octave down and broken as C G E G on eighth notes
+/// let accompaniment = render_accompaniment(&timeline, &options)?;
+/// let notes: Vec<u8> = accompaniment
+///     .events
+///     .iter()
+///     .filter_map(|event| match &event.event_type {
+///         TokenEventType::Cluster(notes) => Some(notes[0].pitch_class),
+///         _ => None,
+///     })
+///     .collect();
+/// assert_eq!(notes, vec![0, 7, 4, 7]);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`composer_core::piano_voicing`] - How chords are voiced
+/// - [`composer_serialization::tokenize_polyphonic`] - Tokenize the result
+pub fn render_accompaniment(
+    timeline: &Timeline,
+    options: &AccompanimentOptions,
+) -> AiResult<Timeline> {
+    let mut chords: Vec<(f64, &Chord)> = timeline
+        .events
+        .iter()
+        .filter_map(|event| match &event.event_type {
+            TokenEventType::Chord(chord) => Some((event.beat, chord)),
+            _ => None,
+        })
+        .collect();
+    chords.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let pulse = options.density.pulse();
+    let mut events = Vec::new();
+    for (index, &(beat, chord)) in chords.iter().enumerate() {
+        let end = chords
+            .get(index + 1)
+            .map_or(timeline.total_duration, |next| next.0);
+        if chord.is_rest || end - beat < MIN_PULSE_LENGTH {
+            continue;
+        }
+
+        let voicing = piano_voicing(
+            chord,
+            &options.scale,
+            options.tonic,
+            PianoVoicingStyle::Close,
+        )
+        .map_err(|e| AiError::SuggestionFailed {
+            reason: format!("Cannot voice the chord at beat {}: {}", beat, e),
+        })?;
+        let bass = voicing[0] - 12;
+
+        let mut step = 0;
+        let mut onset = beat;
+        while end - onset >= MIN_PULSE_LENGTH {
+            let length = pulse.min(end - onset);
+            let mut notes: Vec<ClusterNote> =
+                if step == 0 && options.style == AccompanimentStyle::Block {
+                    vec![cluster_note(bass, end - beat)]
+                } else {
+                    Vec::new()
+                };
+            notes.extend(
+                pulse_notes(options.style, step, &voicing, bass)
+                    .into_iter()
+                    .map(|note| cluster_note(note, length)),
+            );
+            events.push(TokenEvent {
+                beat: onset,
+                event_type: TokenEventType::Cluster(notes),
+            });
+
+            step += 1;
+            onset = beat + step as f64 * pulse;
+        }
+    }
+
+    Ok(Timeline {
+        events,
+        total_duration: timeline.total_duration,
+        meter_changes: timeline.meter_changes.clone(),
+        key_changes: timeline.key_changes.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord_timeline(chords: &[(f64, Chord)], total_duration: f64) -> Timeline {
+        Timeline {
+            events: chords
+                .iter()
+                .map(|(beat, chord)| TokenEvent {
+                    beat: *beat,
+                    event_type: TokenEventType::Chord(chord.clone()),
+                })
+                .collect(),
+            total_duration,
+            ..Default::default()
+        }
+    }
+
+    /// Onset and MIDI notes of a rendered cluster
+    type Pulse = (f64, Vec<u8>);
+
+    fn rendered(timeline: &Timeline) -> Vec<Pulse> {
+        timeline
+            .events
+            .iter()
+            .map(|event| match &event.event_type {
+                TokenEventType::Cluster(notes) => (
+                    event.beat,
+                    notes
+                        .iter()
+                        .map(|note| (note.octave + 1) * 12 + note.pitch_class)
+                        .collect(),
+                ),
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_block_and_strum() {
+        let timeline = chord_timeline(
+            &[
+                (0.0, Chord::triad(1).unwrap()),
+                (1.5, Chord::seventh(5).unwrap()),
+            ],
+            2.0,
+        );
+        let block = render_accompaniment(&timeline, &AccompanimentOptions::default()).unwrap();
+        assert_eq!(
+            rendered(&block),
+            vec![
+                (0.0, vec![48, 60, 64, 67]),
+                (0.5, vec![60, 64, 67]),
+                (1.0, vec![60, 64, 67]),
+                (1.5, vec![55, 67, 71, 74, 77]),
+            ]
+        );
+        let TokenEventType::Cluster(notes) = &block.events[0].event_type else {
+            unreachable!()
+        };
+        assert_eq!(notes[0].duration, 1.5);
+        assert_eq!(notes[1].duration, 0.5);
+
+        let strum = render_accompaniment(
+            &timeline,
+            &AccompanimentOptions {
+                style: AccompanimentStyle::Strum,
+                density: AccompanimentDensity::Sparse,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            rendered(&strum),
+            vec![
+                (0.0, vec![48, 60, 64, 67]),
+                (1.0, vec![60, 64, 67]),
+                (1.5, vec![55, 67, 71, 74, 77]),
+            ]
+        );
+        let TokenEventType::Cluster(notes) = &strum.events[1].event_type else {
+            unreachable!()
+        };
+        assert_eq!(notes[0].duration, 0.5);
+    }
+
+    #[test]
+    fn test_arpeggio_restarts_per_chord() {
+        let mut timeline = chord_timeline(
+            &[
+                (0.0, Chord::triad(1).unwrap()),
+                (3.0, Chord::rest()),
+                (4.0, Chord::triad(4).unwrap()),
+            ],
+            5.0,
+        );
+        timeline.events.push(TokenEvent {
+            beat: 0.5,
+            event_type: TokenEventType::Rest,
+        });
+        let options = AccompanimentOptions {
+            style: AccompanimentStyle::Arpeggio,
+            density: AccompanimentDensity::Moderate,
+            tonic: 2,
+            ..Default::default()
+        };
+        let notes: Vec<Vec<u8>> = rendered(&render_accompaniment(&timeline, &options).unwrap())
+            .into_iter()
+            .map(|(_, notes)| notes)
+            .collect();
+        // D major over D3, then G major over G3; nothing under the rest chord
+        assert_eq!(notes.concat(), vec![50, 62, 66, 69, 66, 62, 55, 67]);
+    }
+
+    #[test]
+    fn test_unvoiceable_chord() {
+        let mut chord = Chord::triad(1).unwrap();
+        chord.root = 0;
+        let timeline = chord_timeline(&[(0.0, chord)], 1.0);
+        assert!(matches!(
+            render_accompaniment(&timeline, &AccompanimentOptions::default()),
+            Err(AiError::SuggestionFailed { .. })
+        ));
+    }
+}
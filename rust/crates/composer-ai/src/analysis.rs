@@ -4,17 +4,27 @@
 //! and advanced musical pattern recognition using polynomial regression and
 //! machine learning techniques.
 
+use crate::difficulty_model::{
+    DifficultyFeatures, SharedDifficultyModel, DIFFICULTY_FEATURE_COUNT, DIFFICULTY_FEATURE_NAMES,
+};
 use crate::error::{AiError, AiResult};
+use crate::form::timed_chords;
+use crate::instrument::{self, DifficultyProfile};
+use crate::melody::{interval_difficulty, MelodicAnalysis};
+use crate::meter::MeterContext;
 use crate::trie::ChordProgressionTrie;
-use composer_config::MUSICAL;
-use composer_core::Chord;
-use rayon::prelude::*;
+use composer_config::{AnalysisParameters, MUSICAL};
+use composer_core::{
+    BorrowedScale, Chord, ChordTheoryResult, Key, Mode, ScaleFingerprint, ScaleType,
+};
+use composer_serialization::Timeline;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::sync::Arc;
 
 /// Difficulty assessment result
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DifficultyAssessment {
     /// Overall difficulty score (0.0-10.0)
     pub overall_score: f64,
@@ -37,12 +47,229 @@ pub struct DifficultyAssessment {
     /// Confidence in assessment (0.0-1.0)
     pub confidence: f64,
 
+    /// Range expected to contain the overall score, widening with model error
+    /// and for progressions below [`MIN_DIFFICULTY_EVIDENCE_CHORDS`]
+    #[serde(default)]
+    pub score_interval: ConfidenceInterval,
+
+    /// Share of the minimum evidence the progression provides (0.0-1.0);
+    /// see [`MIN_DIFFICULTY_EVIDENCE_CHORDS`]
+    #[serde(default = "full_evidence")]
+    pub evidence: f64,
+
+    /// Set when the progression is too short for a stable score; scores of
+    /// such inputs are indicative only and should not be shown as absolute
+    #[serde(default)]
+    pub insufficient_data: bool,
+
     /// Suggested skill level
     pub skill_level: SkillLevel,
+
+    /// Name of the calibrated [`SkillBand`] the overall score falls in
+    #[serde(default)]
+    pub skill_band: String,
+
+    /// Structured account of why the progression received this score
+    #[serde(default)]
+    pub explanation: DifficultyExplanation,
+
+    /// Instrument the technical complexity was measured for; `None` for the
+    /// instrument-agnostic assessment
+    #[serde(default)]
+    pub instrument: Option<DifficultyProfile>,
+
+    /// Share of the calibration corpus scoring below this progression (0-99);
+    /// `None` until the analyzer is calibrated
+    #[serde(default)]
+    pub percentile: Option<u8>,
+
+    /// What each chord of the progression adds to its difficulty, in order
+    #[serde(default)]
+    pub per_chord_breakdown: Vec<ChordDifficultyContribution>,
+
+    /// Melody the melodic complexity was measured from; `None` when it was
+    /// estimated from chord roots
+    #[serde(default)]
+    pub melody: Option<MelodicAnalysis>,
+}
+
+/// One chord's contribution to the difficulty of its progression
+///
+/// Lets education apps point at the bars that make a song hard rather than
+/// only reporting an aggregate score.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChordDifficultyContribution {
+    /// Position of the chord in the progression
+    pub index: usize,
+
+    /// Complexity of the chord on its own (1.0 for a root-position triad)
+    pub complexity: f64,
+
+    /// Voice-leading cost of moving from the previous chord; 0.0 for the first chord
+    pub voice_leading_cost: f64,
+
+    /// How rare the move from the previous chord is in the pattern database,
+    /// from 0.0 (common) to 1.0 (never seen); 0.0 for the first chord
+    pub novelty: f64,
+}
+
+/// z-value of the two-sided 95% intervals reported by the analyzer
+const INTERVAL_Z: f64 = 1.96;
+
+/// Occurrences in the pattern database from which a chord transition counts
+/// as common
+const COMMON_TRANSITION_COUNT: u32 = 5;
+
+/// Range expected to contain the true value of an estimate
+///
+/// Returned next to point estimates so that interfaces can show how much an
+/// analysis should be trusted instead of a bare number.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConfidenceInterval {
+    /// Lower bound
+    pub lower: f64,
+
+    /// Upper bound
+    pub upper: f64,
+}
+
+impl ConfidenceInterval {
+    /// Interval of `half_width` around `center`, clamped to `min..=max`
+    pub fn around(center: f64, half_width: f64, min: f64, max: f64) -> Self {
+        Self {
+            lower: (center - half_width).clamp(min, max),
+            upper: (center + half_width).clamp(min, max),
+        }
+    }
+
+    /// 95% Wilson score interval of a proportion observed `successes` times in
+    /// `trials`; the full 0.0-1.0 range when there are no trials
+    pub fn wilson(successes: usize, trials: usize) -> Self {
+        if trials == 0 {
+            return Self {
+                lower: 0.0,
+                upper: 1.0,
+            };
+        }
+        let n = trials as f64;
+        let p = successes.min(trials) as f64 / n;
+        let z2 = INTERVAL_Z * INTERVAL_Z;
+        let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+        let half_width =
+            INTERVAL_Z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / (1.0 + z2 / n);
+        Self::around(center, half_width, 0.0, 1.0)
+    }
+
+    /// Distance between the bounds
+    pub fn width(&self) -> f64 {
+        self.upper - self.lower
+    }
+
+    /// Whether `value` lies within the bounds
+    pub fn contains(&self, value: f64) -> bool {
+        (self.lower..=self.upper).contains(&value)
+    }
+}
+
+/// Chords an assessment needs before its score counts as fully supported
+///
+/// Shorter progressions are looped up to this length before scoring, so that
+/// length-dependent factors such as chord-to-chord movement are measured over
+/// a comparable span, and are reported with reduced confidence and
+/// [`DifficultyAssessment::insufficient_data`] set.
+pub const MIN_DIFFICULTY_EVIDENCE_CHORDS: usize = 4;
+
+/// Progression looped up to [`MIN_DIFFICULTY_EVIDENCE_CHORDS`] chords
+fn evidence_span(progression: &[Chord]) -> Cow<'_, [Chord]> {
+    if progression.is_empty() || progression.len() >= MIN_DIFFICULTY_EVIDENCE_CHORDS {
+        return Cow::Borrowed(progression);
+    }
+    Cow::Owned(
+        progression
+            .iter()
+            .cycle()
+            .take(MIN_DIFFICULTY_EVIDENCE_CHORDS)
+            .cloned()
+            .collect(),
+    )
+}
+
+/// Serde default for assessments stored before evidence was tracked
+fn full_evidence() -> f64 {
+    1.0
+}
+
+/// Structured reasons behind a [`DifficultyAssessment`]
+///
+/// Meant for rendering "why is this Advanced?" in education apps: every entry
+/// is a named, numeric quantity rather than prose, so it can be localized or
+/// visualized freely. Feature names are those of [`DIFFICULTY_FEATURE_NAMES`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DifficultyExplanation {
+    /// Sub-scores ranked by their weighted contribution, largest first
+    pub top_factors: Vec<FactorContribution>,
+
+    /// How the progression compares to reference progressions at its level
+    pub reference: ReferenceComparison,
+}
+
+/// Weighted contribution of one complexity sub-score to the overall score
+///
+/// Contributions follow the weights of the built-in [`PolynomialModel`], which
+/// stays a good guide to relative importance when a custom model is attached.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FactorContribution {
+    /// Feature name (e.g. "harmonic_complexity")
+    pub feature: String,
+
+    /// Sub-score (0.0-10.0)
+    pub value: f64,
+
+    /// Model weight of the sub-score
+    pub weight: f64,
+
+    /// Share of the weighted model input (0.0-1.0)
+    pub share: f64,
+}
+
+/// Comparison with the built-in reference progressions at one skill level
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReferenceComparison {
+    /// Level of the references; the assessed level unless none scored there,
+    /// in which case the nearest level with references
+    pub skill_level: SkillLevel,
+
+    /// Names of the reference progressions (e.g. "ii7-V7-I")
+    pub references: Vec<String>,
+
+    /// Mean overall score of the references
+    pub reference_score: f64,
+
+    /// Overall score minus the reference mean
+    pub score_difference: f64,
+
+    /// Every feature against its reference mean, largest relative difference first
+    pub features: Vec<FeatureComparison>,
+}
+
+/// One difficulty feature compared against the reference mean
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeatureComparison {
+    /// Feature name from [`DIFFICULTY_FEATURE_NAMES`]
+    pub feature: String,
+
+    /// Value for the assessed progression
+    pub value: f64,
+
+    /// Mean value over the references
+    pub reference_mean: f64,
+
+    /// Value minus the reference mean
+    pub difference: f64,
 }
 
 /// Detailed complexity factors
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ComplexityFactors {
     /// Number of unique chords used
     pub unique_chords: usize,
@@ -70,16 +297,242 @@ pub struct ComplexityFactors {
 }
 
 /// Skill level classification
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum SkillLevel {
+    #[default]
     Beginner,
     Intermediate,
     Advanced,
     Expert,
 }
 
+impl SkillLevel {
+    /// Position in the Beginner..Expert ordering
+    pub(crate) fn rank(&self) -> usize {
+        match self {
+            SkillLevel::Beginner => 0,
+            SkillLevel::Intermediate => 1,
+            SkillLevel::Advanced => 2,
+            SkillLevel::Expert => 3,
+        }
+    }
+}
+
+/// Named range of overall difficulty scores within a [`SkillThresholds`] scale
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkillBand {
+    /// Display name (e.g. "Early Intermediate" or an institution's grade)
+    pub name: String,
+
+    /// Broad skill level the band belongs to
+    pub level: SkillLevel,
+
+    /// Lowest overall score in the band; the band extends to the next one
+    pub min_score: f64,
+}
+
+impl SkillBand {
+    /// Create a band starting at `min_score`
+    pub fn new(name: impl Into<String>, level: SkillLevel, min_score: f64) -> Self {
+        Self {
+            name: name.into(),
+            level,
+            min_score,
+        }
+    }
+}
+
+/// Calibrated mapping from overall difficulty scores to skill bands
+///
+/// The default scale keeps the 2.5/5.0/7.5 cutoffs between the broad
+/// [`SkillLevel`]s and splits Intermediate into early and late halves.
+/// Institutions can supply their own bands, e.g. one per grade of an exam
+/// syllabus, as long as band minimums increase and broad levels never go down.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::{SkillBand, SkillLevel, SkillThresholds};
+///
+/// let grades = SkillThresholds::new(vec![
+///     SkillBand::new("Grade 1", SkillLevel::Beginner, 0.0),
+///     SkillBand::new("Grade 2", SkillLevel::Beginner, 1.5),
+///     SkillBand::new("Grade 3", SkillLevel::Intermediate, 3.0),
+///     SkillBand::new("Diploma", SkillLevel::Advanced, 6.0),
+/// ])?;
+///
+/// assert_eq!(grades.classify(2.0).name, "Grade 2");
+/// assert_eq!(grades.classify(9.0).level, SkillLevel::Advanced);
+/// # Ok::<(), composer_ai::AiError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Vec<SkillBand>", into = "Vec<SkillBand>")]
+pub struct SkillThresholds {
+    bands: Vec<SkillBand>,
+}
+
+impl Default for SkillThresholds {
+    fn default() -> Self {
+        Self {
+            bands: vec![
+                SkillBand::new("Beginner", SkillLevel::Beginner, 0.0),
+                SkillBand::new("Early Intermediate", SkillLevel::Intermediate, 2.5),
+                SkillBand::new("Late Intermediate", SkillLevel::Intermediate, 3.75),
+                SkillBand::new("Advanced", SkillLevel::Advanced, 5.0),
+                SkillBand::new("Expert", SkillLevel::Expert, 7.5),
+            ],
+        }
+    }
+}
+
+impl SkillThresholds {
+    /// Validate and build a scale from bands ordered by `min_score`
+    ///
+    /// Scores below the first band's minimum fall into the first band.
+    pub fn new(bands: Vec<SkillBand>) -> AiResult<Self> {
+        if bands.is_empty() {
+            return Err(AiError::InvalidModel {
+                reason: "Skill thresholds need at least one band".to_string(),
+            });
+        }
+        if let Some(band) = bands.iter().find(|band| !band.min_score.is_finite()) {
+            return Err(AiError::InvalidModel {
+                reason: format!("Skill band '{}' has a non-finite minimum", band.name),
+            });
+        }
+        for pair in bands.windows(2) {
+            if pair[1].min_score <= pair[0].min_score {
+                return Err(AiError::InvalidModel {
+                    reason: format!(
+                        "Skill band '{}' must start above '{}'",
+                        pair[1].name, pair[0].name
+                    ),
+                });
+            }
+            if pair[1].level.rank() < pair[0].level.rank() {
+                return Err(AiError::InvalidModel {
+                    reason: format!(
+                        "Skill band '{}' is at a lower level than '{}'",
+                        pair[1].name, pair[0].name
+                    ),
+                });
+            }
+        }
+        Ok(Self { bands })
+    }
+
+    /// Bands in increasing score order
+    pub fn bands(&self) -> &[SkillBand] {
+        &self.bands
+    }
+
+    /// Band containing an overall score
+    pub fn classify(&self, score: f64) -> &SkillBand {
+        self.bands
+            .iter()
+            .rev()
+            .find(|band| score >= band.min_score)
+            .unwrap_or(&self.bands[0])
+    }
+}
+
+impl TryFrom<Vec<SkillBand>> for SkillThresholds {
+    type Error = AiError;
+
+    fn try_from(bands: Vec<SkillBand>) -> AiResult<Self> {
+        Self::new(bands)
+    }
+}
+
+impl From<SkillThresholds> for Vec<SkillBand> {
+    fn from(thresholds: SkillThresholds) -> Self {
+        thresholds.bands
+    }
+}
+
+/// Empirical distribution of overall scores across a corpus
+///
+/// Turns an absolute score into a percentile, so "difficulty 6.2" reads as
+/// "harder than 78% of songs in your library". Percentiles are capped at
+/// `difficulty_percentile_max` (99 by default): nothing is harder than the
+/// whole corpus.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::DifficultyCalibration;
+///
+/// let calibration = DifficultyCalibration::from_scores(vec![2.0, 3.0, 5.0, 8.0])?;
+/// assert_eq!(calibration.percentile(4.0), 50);
+/// assert_eq!(calibration.percentile(9.5), 99);
+/// # Ok::<(), composer_ai::AiError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Vec<f64>", into = "Vec<f64>")]
+pub struct DifficultyCalibration {
+    scores: Vec<f64>,
+}
+
+impl DifficultyCalibration {
+    /// Build a calibration from corpus overall scores in any order
+    pub fn from_scores(mut scores: Vec<f64>) -> AiResult<Self> {
+        if scores.is_empty() {
+            return Err(AiError::InvalidModel {
+                reason: "Calibration corpus is empty".to_string(),
+            });
+        }
+        if let Some(score) = scores.iter().find(|score| !score.is_finite()) {
+            return Err(AiError::InvalidModel {
+                reason: format!("Calibration score is not finite: {}", score),
+            });
+        }
+        scores.sort_by(f64::total_cmp);
+        Ok(Self { scores })
+    }
+
+    /// Build a calibration from assessments of the corpus songs
+    pub fn from_assessments(assessments: &[DifficultyAssessment]) -> AiResult<Self> {
+        Self::from_scores(assessments.iter().map(|a| a.overall_score).collect())
+    }
+
+    /// Corpus scores in increasing order
+    pub fn scores(&self) -> &[f64] {
+        &self.scores
+    }
+
+    /// Percentage of corpus scores strictly below `score`, capped at the
+    /// default `difficulty_percentile_max`
+    pub fn percentile(&self, score: f64) -> u8 {
+        self.percentile_capped(
+            score,
+            AnalysisParameters::default().difficulty_percentile_max,
+        )
+    }
+
+    /// Percentage of corpus scores strictly below `score`, capped at `max`
+    pub fn percentile_capped(&self, score: f64, max: u8) -> u8 {
+        let below = self.scores.partition_point(|&corpus| corpus < score);
+        let percent = (below * 100 / self.scores.len()) as u8;
+        percent.min(max)
+    }
+}
+
+impl TryFrom<Vec<f64>> for DifficultyCalibration {
+    type Error = AiError;
+
+    fn try_from(scores: Vec<f64>) -> AiResult<Self> {
+        Self::from_scores(scores)
+    }
+}
+
+impl From<DifficultyCalibration> for Vec<f64> {
+    fn from(calibration: DifficultyCalibration) -> Self {
+        calibration.scores
+    }
+}
+
 /// Chord progression analysis result
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ProgressionAnalysis {
     /// Detected key centers
     pub key_centers: Vec<KeyCenter>,
@@ -98,36 +551,24 @@ pub struct ProgressionAnalysis {
 }
 
 /// Key center detection result
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeyCenter {
-    /// The key root note
-    pub root: u8,
-
-    /// Major or minor mode
-    pub mode: Mode,
+    /// The key, reading the progression's scale degrees against C major
+    pub key: Key,
 
     /// Confidence in detection (0.0-1.0)
     pub confidence: f64,
 
+    /// 95% interval for the confidence, narrowing as the range grows
+    #[serde(default)]
+    pub confidence_interval: ConfidenceInterval,
+
     /// Range where this key is active
     pub chord_range: (usize, usize),
 }
 
-/// Musical mode
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Mode {
-    Major,
-    Minor,
-    Dorian,
-    Phrygian,
-    Lydian,
-    Mixolydian,
-    Aeolian,
-    Locrian,
-}
-
 /// Common progression pattern
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommonPattern {
     /// Pattern name (e.g., "I-V-vi-IV")
     pub name: String,
@@ -142,8 +583,11 @@ pub struct CommonPattern {
     pub chords: Vec<Chord>,
 }
 
+/// Chord and its duration in beats
+pub type ChordDuration = (Chord, f64);
+
 /// Harmonic rhythm analysis
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct HarmonicRhythm {
     /// Average chord duration in ticks
     pub avg_chord_duration: f64,
@@ -151,8 +595,126 @@ pub struct HarmonicRhythm {
     /// Rhythm regularity score (0.0-1.0)
     pub regularity: f64,
 
-    /// Detected patterns in chord timing
+    /// Detected patterns in chord timing: "Regular", "Repeating 2+1+1" (a
+    /// repeating cycle of durations in beats), "Accelerating", "Decelerating",
+    /// "Cadential lengthening" or "Irregular"
     pub rhythm_patterns: Vec<String>,
+
+    /// Variance of chord durations in ticks²
+    #[serde(default)]
+    pub duration_variance: f64,
+
+    /// Whether chords change faster or slower over the progression
+    #[serde(default)]
+    pub trend: RhythmTrend,
+}
+
+/// Direction in which the pace of chord changes moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RhythmTrend {
+    /// No consistent change in chord durations
+    #[default]
+    Steady,
+    /// Chords get shorter: changes come faster
+    Accelerating,
+    /// Chords get longer: changes come slower
+    Decelerating,
+}
+
+/// Relative change in chord duration across a progression that counts as a trend
+const RHYTHM_TREND_THRESHOLD: f64 = 0.25;
+
+/// Harmonic rhythm of successive chord durations in beats
+fn harmonic_rhythm_of(durations: &[f64]) -> HarmonicRhythm {
+    let ticks_per_beat = MUSICAL.ticks_per_beat as f64;
+    let ticks: Vec<f64> = durations
+        .iter()
+        .map(|beats| beats * ticks_per_beat)
+        .collect();
+    let count = ticks.len().max(1) as f64;
+    let mean = ticks.iter().sum::<f64>() / count;
+    let variance = ticks.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / count;
+    let variation = if mean > 0.0 {
+        variance.sqrt() / mean
+    } else {
+        0.0
+    };
+
+    let mut patterns = Vec::new();
+    let grid: Vec<i64> = ticks.iter().map(|t| t.round() as i64).collect();
+    if grid.windows(2).all(|pair| pair[0] == pair[1]) {
+        patterns.push("Regular".to_string());
+    } else if let Some(period) =
+        (2..=grid.len() / 2).find(|&p| (p..grid.len()).all(|i| grid[i] == grid[i - p]))
+    {
+        let cycle: Vec<String> = durations[..period]
+            .iter()
+            .map(|beats| format!("{}", (beats * 100.0).round() / 100.0))
+            .collect();
+        patterns.push(format!("Repeating {}", cycle.join("+")));
+    }
+
+    // A final chord held at least twice as long as the others closes a phrase
+    let cadential = match ticks.split_last() {
+        Some((last, before)) if before.len() >= 2 => {
+            *last >= 2.0 * before.iter().sum::<f64>() / before.len() as f64
+        },
+        _ => false,
+    };
+
+    // Repeating cycles have no trend, and a cadential final chord is not a slowdown
+    let trend = if patterns.is_empty() {
+        duration_trend(&ticks[..ticks.len() - usize::from(cadential)])
+    } else {
+        RhythmTrend::Steady
+    };
+    match trend {
+        RhythmTrend::Accelerating => patterns.push("Accelerating".to_string()),
+        RhythmTrend::Decelerating => patterns.push("Decelerating".to_string()),
+        RhythmTrend::Steady => {},
+    }
+    if cadential {
+        patterns.push("Cadential lengthening".to_string());
+    }
+    if patterns.is_empty() {
+        patterns.push("Irregular".to_string());
+    }
+
+    HarmonicRhythm {
+        avg_chord_duration: mean,
+        regularity: 1.0 / (1.0 + variation),
+        rhythm_patterns: patterns,
+        duration_variance: variance,
+        trend,
+    }
+}
+
+/// Trend of durations from their least-squares slope, relative to the mean
+fn duration_trend(durations: &[f64]) -> RhythmTrend {
+    let count = durations.len() as f64;
+    let mean = durations.iter().sum::<f64>() / count.max(1.0);
+    if durations.len() < 3 || mean <= 0.0 {
+        return RhythmTrend::Steady;
+    }
+
+    let center = (count - 1.0) / 2.0;
+    let spread: f64 = (0..durations.len())
+        .map(|i| (i as f64 - center).powi(2))
+        .sum();
+    let slope = durations
+        .iter()
+        .enumerate()
+        .map(|(i, d)| (i as f64 - center) * (d - mean))
+        .sum::<f64>()
+        / spread;
+    let change = slope * (count - 1.0) / mean;
+    if change < -RHYTHM_TREND_THRESHOLD {
+        RhythmTrend::Accelerating
+    } else if change > RHYTHM_TREND_THRESHOLD {
+        RhythmTrend::Decelerating
+    } else {
+        RhythmTrend::Steady
+    }
 }
 
 /// Main musical analysis engine
@@ -164,11 +726,28 @@ pub struct MusicalAnalyzer {
     analysis_cache: Arc<dashmap::DashMap<String, DifficultyAssessment>>,
 
     /// Statistical model coefficients for difficulty assessment
-    difficulty_model: PolynomialModel,
+    difficulty_model: parking_lot::RwLock<PolynomialModel>,
+
+    /// Externally trained model that replaces the polynomial model when set
+    custom_model: parking_lot::RwLock<Option<SharedDifficultyModel>>,
+
+    /// Score cutoffs for skill levels and bands
+    skill_thresholds: parking_lot::RwLock<SkillThresholds>,
+
+    /// Corpus score distribution for percentiles
+    calibration: parking_lot::RwLock<Option<DifficultyCalibration>>,
+
+    /// Runtime analysis parameters, such as the percentile cap
+    parameters: parking_lot::RwLock<AnalysisParameters>,
+
+    /// Scored reference progressions by pattern total, tempo, meter and
+    /// instrument profile; cleared whenever the model, thresholds or
+    /// parameters change
+    reference_scores: ReferenceScores,
 }
 
 /// Polynomial regression model for difficulty assessment
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PolynomialModel {
     /// Cubic polynomial coefficients [a, b, c, d] for ax³ + bx² + cx + d
     pub coefficients: [f64; 4],
@@ -180,14 +759,24 @@ pub struct PolynomialModel {
     pub accuracy: f64,
 }
 
+/// Sub-scores weighted by a [`PolynomialModel`] and their default weights
+const POLYNOMIAL_FEATURES: [(&str, f64); 4] = [
+    ("harmonic_complexity", 0.35),
+    ("rhythmic_complexity", 0.25),
+    ("technical_complexity", 0.25),
+    ("melodic_complexity", 0.15),
+];
+
+/// Fewest labeled examples [`PolynomialModel::fit`] accepts
+pub const MIN_FIT_EXAMPLES: usize = 5;
+
 impl Default for PolynomialModel {
     fn default() -> Self {
         // Default model based on specification
-        let mut feature_weights = HashMap::new();
-        feature_weights.insert("harmonic_complexity".to_string(), 0.35);
-        feature_weights.insert("rhythmic_complexity".to_string(), 0.25);
-        feature_weights.insert("technical_complexity".to_string(), 0.25);
-        feature_weights.insert("melodic_complexity".to_string(), 0.15);
+        let feature_weights = POLYNOMIAL_FEATURES
+            .iter()
+            .map(|(name, weight)| (name.to_string(), *weight))
+            .collect();
 
         Self {
             coefficients: [0.1, 0.2, 0.8, 0.2], // More conservative coefficients to prevent saturation
@@ -197,13 +786,354 @@ impl Default for PolynomialModel {
     }
 }
 
+impl PolynomialModel {
+    /// Fit the model to labeled examples by least squares.
+    ///
+    /// Each example pairs the features of a progression (its sub-scores and
+    /// [`ComplexityFactors`], e.g. from [`DifficultyFeatures::from_assessment`])
+    /// with a target overall score on the 0.0-10.0 scale, such as a teacher's
+    /// grade mapped onto it. Feature weights come from a linear regression of the
+    /// targets on the four sub-scores, with negative weights dropped and the rest
+    /// normalized to sum to one; the cubic coefficients are then fitted to the
+    /// weighted input. `accuracy` is set to the R² on the examples.
+    ///
+    /// Fails with `AiError::InvalidModel` on fewer than [`MIN_FIT_EXAMPLES`]
+    /// examples, non-finite values, targets outside 0.0-10.0, or examples too
+    /// alike to determine the coefficients.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{ComplexityFactors, DifficultyFeatures, PolynomialModel};
+    ///
+    /// let factors = ComplexityFactors::default();
+    /// let examples: Vec<(DifficultyFeatures, f64)> = (0..8)
+    ///     .map(|i| {
+    ///         let harmonic = i as f64;
+    ///         let technical = (i % 3) as f64;
+    ///         let features = DifficultyFeatures::new(harmonic, 2.0, technical, 3.0, &factors);
+    ///         (features, 1.0 + harmonic)
+    ///     })
+    ///     .collect();
+    ///
+    /// let model = PolynomialModel::fit(&examples)?;
+    /// assert!(model.accuracy > 0.99);
+    /// assert!((model.predict(&examples[3].0) - 4.0).abs() < 0.1);
+    /// # Ok::<(), composer_ai::AiError>(())
+    /// ```
+    pub fn fit(examples: &[(DifficultyFeatures, f64)]) -> AiResult<Self> {
+        let invalid = |reason: String| Err(AiError::InvalidModel { reason });
+        if examples.len() < MIN_FIT_EXAMPLES {
+            return invalid(format!(
+                "Fitting needs at least {} examples, got {}",
+                MIN_FIT_EXAMPLES,
+                examples.len()
+            ));
+        }
+        for (index, (features, target)) in examples.iter().enumerate() {
+            if !target.is_finite() || !(0.0..=10.0).contains(target) {
+                return invalid(format!(
+                    "Example {} has target {} outside 0-10",
+                    index, target
+                ));
+            }
+            if features.values().iter().any(|value| !value.is_finite()) {
+                return invalid(format!("Example {} has non-finite features", index));
+            }
+        }
+
+        let sub_scores = |features: &DifficultyFeatures| {
+            let values = features.values();
+            [values[0], values[1], values[2], values[3]]
+        };
+        let targets: Vec<f64> = examples.iter().map(|(_, target)| *target).collect();
+
+        // Feature weights from a linear fit of the targets on the centered
+        // sub-scores; the ridge term gives constant sub-scores zero weight
+        let count = examples.len() as f64;
+        let mut means = [0.0; 4];
+        for (features, _) in examples {
+            for (mean, score) in means.iter_mut().zip(sub_scores(features)) {
+                *mean += score / count;
+            }
+        }
+        let target_mean = targets.iter().sum::<f64>() / count;
+        let linear_rows: Vec<Vec<f64>> = examples
+            .iter()
+            .map(|(features, _)| {
+                sub_scores(features)
+                    .iter()
+                    .zip(&means)
+                    .map(|(score, mean)| score - mean)
+                    .collect()
+            })
+            .collect();
+        let centered_targets: Vec<f64> = targets.iter().map(|t| t - target_mean).collect();
+        let slopes = solve_least_squares(&linear_rows, &centered_targets, 1e-6)?;
+        let positive: Vec<f64> = slopes.iter().map(|slope| slope.max(0.0)).collect();
+        let total: f64 = positive.iter().sum();
+        let weights: Vec<f64> = if total > f64::EPSILON {
+            positive.iter().map(|slope| slope / total).collect()
+        } else {
+            POLYNOMIAL_FEATURES
+                .iter()
+                .map(|(_, weight)| *weight)
+                .collect()
+        };
+
+        // Cubic coefficients on the weighted, normalized input
+        let weighted_input = |features: &DifficultyFeatures| -> f64 {
+            sub_scores(features)
+                .iter()
+                .zip(&weights)
+                .map(|(score, weight)| score * weight)
+                .sum::<f64>()
+                / 10.0
+        };
+        let cubic_rows: Vec<Vec<f64>> = examples
+            .iter()
+            .map(|(features, _)| {
+                let x = weighted_input(features);
+                vec![x.powi(3), x.powi(2), x, 1.0]
+            })
+            .collect();
+        let scaled_targets: Vec<f64> = targets.iter().map(|target| target / 10.0).collect();
+        let cubic = solve_least_squares(&cubic_rows, &scaled_targets, 0.0)?;
+
+        let mut model = Self {
+            coefficients: [cubic[0], cubic[1], cubic[2], cubic[3]],
+            feature_weights: POLYNOMIAL_FEATURES
+                .iter()
+                .zip(&weights)
+                .map(|((name, _), weight)| (name.to_string(), *weight))
+                .collect(),
+            accuracy: 0.0,
+        };
+
+        let total_variance: f64 = centered_targets.iter().map(|t| t.powi(2)).sum();
+        let residual: f64 = examples
+            .iter()
+            .map(|(features, target)| (model.predict(features) - target).powi(2))
+            .sum();
+        model.accuracy = if total_variance > f64::EPSILON {
+            (1.0 - residual / total_variance).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        model.validate()?;
+        Ok(model)
+    }
+
+    /// Overall score (0.0-10.0) for a feature vector
+    pub fn predict(&self, features: &DifficultyFeatures) -> f64 {
+        let values = features.values();
+        let weighted_input: f64 = POLYNOMIAL_FEATURES
+            .iter()
+            .zip(values)
+            .map(|((name, default), value)| {
+                value * self.feature_weights.get(*name).copied().unwrap_or(*default)
+            })
+            .sum();
+
+        // Apply cubic polynomial: ax³ + bx² + cx + d
+        let x = weighted_input / 10.0; // Normalize input to max complexity scale
+        let [a, b, c, d] = self.coefficients;
+        let polynomial_result = a * x.powi(3) + b * x.powi(2) + c * x + d;
+
+        // Scale back to 0-10 range and clamp
+        (polynomial_result * 10.0).clamp(0.0, 10.0)
+    }
+
+    /// Check that coefficients and weights are finite and accuracy is in 0.0-1.0
+    pub fn validate(&self) -> AiResult<()> {
+        let invalid = |reason: String| Err(AiError::InvalidModel { reason });
+        if self.coefficients.iter().any(|c| !c.is_finite()) {
+            return invalid(format!("Non-finite coefficients {:?}", self.coefficients));
+        }
+        for (name, weight) in &self.feature_weights {
+            if !POLYNOMIAL_FEATURES.iter().any(|(known, _)| known == name) {
+                return invalid(format!("Unknown feature weight '{}'", name));
+            }
+            if !weight.is_finite() {
+                return invalid(format!("Feature weight '{}' is {}", name, weight));
+            }
+        }
+        if !(0.0..=1.0).contains(&self.accuracy) {
+            return invalid(format!("Accuracy {} is outside 0-1", self.accuracy));
+        }
+        Ok(())
+    }
+
+    /// Parse and validate a model from JSON
+    pub fn from_json(json: &str) -> AiResult<Self> {
+        let model: Self = serde_json::from_str(json).map_err(|e| AiError::InvalidModel {
+            reason: format!("Failed to parse polynomial model: {}", e),
+        })?;
+        model.validate()?;
+        Ok(model)
+    }
+
+    /// Serialize the model to JSON
+    pub fn to_json(&self) -> AiResult<String> {
+        serde_json::to_string(self).map_err(|e| AiError::InvalidModel {
+            reason: format!("Failed to serialize polynomial model: {}", e),
+        })
+    }
+}
+
+/// Least-squares solution of `rows · x ≈ targets` via the normal equations,
+/// with `ridge` added to the diagonal
+fn solve_least_squares(rows: &[Vec<f64>], targets: &[f64], ridge: f64) -> AiResult<Vec<f64>> {
+    let width = rows.first().map_or(0, Vec::len);
+    // Augmented normal equations [AᵀA + λI | Aᵀy]
+    let mut system = vec![vec![0.0; width + 1]; width];
+    for (i, equation) in system.iter_mut().enumerate() {
+        equation[i] = ridge;
+    }
+    for (row, target) in rows.iter().zip(targets) {
+        for (i, equation) in system.iter_mut().enumerate() {
+            for (j, value) in row.iter().enumerate() {
+                equation[j] += row[i] * value;
+            }
+            equation[width] += row[i] * target;
+        }
+    }
+
+    // Gaussian elimination with partial pivoting
+    for column in 0..width {
+        let pivot = (column..width)
+            .max_by(|&a, &b| system[a][column].abs().total_cmp(&system[b][column].abs()))
+            .unwrap_or(column);
+        if system[pivot][column].abs() < 1e-12 {
+            return Err(AiError::InvalidModel {
+                reason: "Examples are too alike to determine the model".to_string(),
+            });
+        }
+        system.swap(column, pivot);
+        let (upper, lower) = system.split_at_mut(column + 1);
+        let pivot_row = &upper[column];
+        for equation in lower {
+            let factor = equation[column] / pivot_row[column];
+            for (value, pivot_value) in equation.iter_mut().zip(pivot_row).skip(column) {
+                *value -= factor * pivot_value;
+            }
+        }
+    }
+
+    let mut solution = vec![0.0; width];
+    for row in (0..width).rev() {
+        let known: f64 = (row + 1..width).map(|k| system[row][k] * solution[k]).sum();
+        solution[row] = (system[row][width] - known) / system[row][row];
+    }
+    Ok(solution)
+}
+
+/// Reference progression name and chords
+type ReferenceProgression = (&'static str, Vec<Chord>);
+
+/// Reference progression name, features, overall score and skill level
+type ScoredReference = (&'static str, DifficultyFeatures, f64, SkillLevel);
+
+/// Scored reference progressions by scoring conditions
+type ReferenceScores = dashmap::DashMap<String, Arc<Vec<ScoredReference>>>;
+
+/// Named reference progressions spanning the skill levels
+///
+/// Their levels are not fixed: [`DifficultyExplanation`] scores them with the
+/// active model, so the comparison stays consistent when the model changes.
+fn reference_progressions() -> AiResult<Vec<ReferenceProgression>> {
+    let build = || -> ChordTheoryResult<Vec<ReferenceProgression>> {
+        let triads = |roots: &[u8]| -> ChordTheoryResult<Vec<Chord>> {
+            roots.iter().map(|&root| Chord::triad(root)).collect()
+        };
+        let minor = BorrowedScale::ScaleType(ScaleType::Minor);
+        Ok(vec![
+            ("I-IV-V-I", triads(&[1, 4, 5, 1])?),
+            ("I-V-vi-IV", triads(&[1, 5, 6, 4])?),
+            ("I-vi-IV-V", triads(&[1, 6, 4, 5])?),
+            (
+                "ii7-V7-I",
+                vec![Chord::seventh(2)?, Chord::seventh(5)?, Chord::triad(1)?],
+            ),
+            (
+                "I-V6-vi-IV",
+                vec![
+                    Chord::triad(1)?,
+                    Chord::triad(5)?.with_inversion(1)?,
+                    Chord::triad(6)?,
+                    Chord::triad(4)?,
+                ],
+            ),
+            (
+                "I-vi7-ii7-V7",
+                vec![
+                    Chord::triad(1)?,
+                    Chord::seventh(6)?,
+                    Chord::seventh(2)?,
+                    Chord::seventh(5)?,
+                ],
+            ),
+            (
+                "I-iv-I-V7",
+                vec![
+                    Chord::triad(1)?,
+                    Chord::triad(4)?.with_borrowed_scale(minor.clone())?,
+                    Chord::triad(1)?,
+                    Chord::seventh(5)?,
+                ],
+            ),
+            (
+                "V7/V-V7-I",
+                vec![
+                    Chord::seventh(5)?.with_applied(5)?,
+                    Chord::seventh(5)?,
+                    Chord::triad(1)?,
+                ],
+            ),
+            (
+                "ii9-V13-I9",
+                vec![Chord::new(2, 9)?, Chord::new(5, 13)?, Chord::new(1, 9)?],
+            ),
+            (
+                "iii7-V7/ii-ii9-V7b9-I9",
+                vec![
+                    Chord::seventh(3)?,
+                    Chord::seventh(5)?.with_applied(2)?,
+                    Chord::new(2, 9)?,
+                    Chord::seventh(5)?.with_alteration("b9")?,
+                    Chord::new(1, 9)?,
+                ],
+            ),
+            (
+                "ii11-V13b9#11-bVI9-I13",
+                vec![
+                    Chord::new(2, 11)?.with_inversion(1)?,
+                    Chord::new(5, 13)?
+                        .with_alteration("b9")?
+                        .with_alteration("#11")?,
+                    Chord::new(6, 9)?.with_borrowed_scale(minor)?,
+                    Chord::new(1, 13)?.with_inversion(2)?,
+                ],
+            ),
+        ])
+    };
+    build().map_err(|e| AiError::AnalysisFailed {
+        reason: format!("Invalid reference progression: {}", e),
+    })
+}
+
 impl MusicalAnalyzer {
     /// Create a new musical analyzer
     pub fn new(trie: Arc<ChordProgressionTrie>) -> Self {
         Self {
             trie,
             analysis_cache: Arc::new(dashmap::DashMap::new()),
-            difficulty_model: PolynomialModel::default(),
+            difficulty_model: parking_lot::RwLock::new(PolynomialModel::default()),
+            custom_model: parking_lot::RwLock::new(None),
+            skill_thresholds: parking_lot::RwLock::new(SkillThresholds::default()),
+            calibration: parking_lot::RwLock::new(None),
+            parameters: parking_lot::RwLock::new(AnalysisParameters::default()),
+            reference_scores: dashmap::DashMap::new(),
         }
     }
 
@@ -213,6 +1143,76 @@ impl MusicalAnalyzer {
         progression: &[Chord],
         tempo_bpm: Option<f64>,
         time_signature: Option<(u8, u8)>,
+    ) -> AiResult<DifficultyAssessment> {
+        self.assess_difficulty_with_profile(progression, tempo_bpm, time_signature, None)
+    }
+
+    /// Assess the difficulty of a chord progression on one instrument.
+    ///
+    /// Harmonic, rhythmic and melodic complexity are as in
+    /// [`Self::assess_difficulty`]; technical complexity is measured from
+    /// voicings on the instrument (see [`DifficultyProfile`]), and the overall
+    /// score, skill level and reference comparison follow from it.
+    pub fn assess_instrument_difficulty(
+        &self,
+        progression: &[Chord],
+        tempo_bpm: Option<f64>,
+        time_signature: Option<(u8, u8)>,
+        profile: DifficultyProfile,
+    ) -> AiResult<DifficultyAssessment> {
+        self.assess_difficulty_with_profile(progression, tempo_bpm, time_signature, Some(profile))
+    }
+
+    /// Assess the difficulty of the chords of a timeline.
+    ///
+    /// Unlike [`Self::assess_difficulty`], rhythmic complexity reflects when
+    /// chords change: the timeline's meter changes are counted in
+    /// [`ComplexityFactors::time_signature_changes`], and time in irregular
+    /// meters (see [`crate::meter_irregularity`]) and chord changes off the beat
+    /// add to the score. `time_signature` applies before the first meter
+    /// change, defaulting to 4/4. When the timeline has note events, melodic
+    /// complexity is measured from them (see [`Self::analyze_timeline_melody`]).
+    pub fn assess_timeline_difficulty(
+        &self,
+        timeline: &Timeline,
+        tempo_bpm: Option<f64>,
+        time_signature: Option<(u8, u8)>,
+    ) -> AiResult<DifficultyAssessment> {
+        let chords = timed_chords(timeline);
+        let progression: Vec<Chord> = chords.iter().map(|timed| timed.chord.clone()).collect();
+        let onsets = chords.iter().map(|timed| timed.start).collect();
+        let meter = MeterContext::from_timeline(timeline, onsets, time_signature);
+        let melody = self.analyze_timeline_melody(timeline).ok();
+        self.assess_difficulty_in_meter(&progression, tempo_bpm, &meter, None, melody)
+    }
+
+    fn assess_difficulty_with_profile(
+        &self,
+        progression: &[Chord],
+        tempo_bpm: Option<f64>,
+        time_signature: Option<(u8, u8)>,
+        profile: Option<DifficultyProfile>,
+    ) -> AiResult<DifficultyAssessment> {
+        let meter = MeterContext::from_time_signature(time_signature);
+        self.assess_difficulty_in_meter(progression, tempo_bpm, &meter, profile, None)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "assess_difficulty",
+            level = "debug",
+            skip_all,
+            fields(chords = progression.len(), tempo_bpm = tempo_bpm, profile = profile.is_some())
+        )
+    )]
+    pub(crate) fn assess_difficulty_in_meter(
+        &self,
+        progression: &[Chord],
+        tempo_bpm: Option<f64>,
+        meter: &MeterContext,
+        profile: Option<DifficultyProfile>,
+        melody: Option<MelodicAnalysis>,
     ) -> AiResult<DifficultyAssessment> {
         if progression.is_empty() {
             return Err(AiError::AnalysisFailed {
@@ -220,34 +1220,68 @@ impl MusicalAnalyzer {
             });
         }
 
-        // Check cache
-        let cache_key = self.generate_difficulty_cache_key(progression, tempo_bpm, time_signature);
-        if let Some(cached) = self.analysis_cache.get(&cache_key) {
+        // Check cache; keys describe chords only, so melody assessments are not cached
+        let cache_key = melody
+            .is_none()
+            .then(|| self.generate_difficulty_cache_key(progression, tempo_bpm, meter, profile));
+        if let Some(cached) = cache_key
+            .as_ref()
+            .and_then(|key| self.analysis_cache.get(key))
+        {
             return Ok(cached.clone());
         }
 
+        let evidence = (progression.len() as f64 / MIN_DIFFICULTY_EVIDENCE_CHORDS as f64).min(1.0);
+        let insufficient_data = progression.len() < MIN_DIFFICULTY_EVIDENCE_CHORDS;
+        let per_chord_breakdown = self.per_chord_breakdown(progression)?;
+        let progression = &*evidence_span(progression);
+
         // Calculate complexity factors
-        let factors = self.calculate_complexity_factors(progression, tempo_bpm, time_signature)?;
+        let factors = self.calculate_complexity_factors(progression, tempo_bpm, meter)?;
 
         // Calculate individual complexity scores
         let harmonic_complexity = self.calculate_harmonic_complexity(progression, &factors)?;
-        let rhythmic_complexity = self.calculate_rhythmic_complexity(&factors, tempo_bpm);
-        let technical_complexity = self.calculate_technical_complexity(progression, &factors);
-        let melodic_complexity = self.calculate_melodic_complexity(progression);
+        let rhythmic_complexity = self.calculate_rhythmic_complexity(&factors, tempo_bpm, meter);
+        let technical_complexity = self.technical_complexity_for(progression, &factors, profile);
+        let melodic_complexity = match &melody {
+            Some(melody) => melody.complexity,
+            None => self.calculate_melodic_complexity(progression),
+        };
 
-        // Apply polynomial regression model
-        let overall_score = self.apply_difficulty_model(
+        let features = DifficultyFeatures::new(
             harmonic_complexity,
             rhythmic_complexity,
             technical_complexity,
             melodic_complexity,
+            &factors,
         );
-
-        // Determine skill level
-        let skill_level = self.classify_skill_level(overall_score);
+        let overall_score = self.predict_overall_score(&features)?;
+
+        // Determine skill level and calibrated band
+        let band = self.skill_thresholds.read().classify(overall_score).clone();
+        let skill_level = band.level;
+        let max_percentile = self.parameters.read().difficulty_percentile_max;
+        let percentile = self
+            .calibration
+            .read()
+            .as_ref()
+            .map(|calibration| calibration.percentile_capped(overall_score, max_percentile));
 
         // Calculate confidence based on model accuracy and data quality
-        let confidence = self.calculate_analysis_confidence(progression, &factors);
+        let confidence = self.calculate_analysis_confidence(progression, evidence, &factors);
+        let score_interval = self.score_interval(overall_score, evidence);
+
+        let explanation = DifficultyExplanation {
+            top_factors: self.rank_factor_contributions(&features),
+            reference: self.compare_to_references(
+                &features,
+                overall_score,
+                skill_level,
+                tempo_bpm,
+                meter,
+                profile,
+            )?,
+        };
 
         let assessment = DifficultyAssessment {
             overall_score,
@@ -257,16 +1291,59 @@ impl MusicalAnalyzer {
             melodic_complexity,
             factors,
             confidence,
+            score_interval,
+            evidence,
+            insufficient_data,
             skill_level,
+            skill_band: band.name,
+            explanation,
+            instrument: profile,
+            percentile,
+            per_chord_breakdown,
+            melody,
         };
 
         // Cache the result
-        self.analysis_cache.insert(cache_key, assessment.clone());
+        if let Some(cache_key) = cache_key {
+            self.analysis_cache.insert(cache_key, assessment.clone());
+        }
 
         Ok(assessment)
     }
 
+    /// Extract the model input features for a progression without scoring it.
+    ///
+    /// Produces the same values that [`Self::assess_difficulty`] feeds into the
+    /// difficulty model, in [`crate::DIFFICULTY_FEATURE_NAMES`] order.
+    pub fn extract_difficulty_features(
+        &self,
+        progression: &[Chord],
+        tempo_bpm: Option<f64>,
+        time_signature: Option<(u8, u8)>,
+    ) -> AiResult<DifficultyFeatures> {
+        if progression.is_empty() {
+            return Err(AiError::AnalysisFailed {
+                reason: "Cannot analyze empty progression".to_string(),
+            });
+        }
+
+        let progression = &*evidence_span(progression);
+        let meter = MeterContext::from_time_signature(time_signature);
+        let factors = self.calculate_complexity_factors(progression, tempo_bpm, &meter)?;
+        Ok(DifficultyFeatures::new(
+            self.calculate_harmonic_complexity(progression, &factors)?,
+            self.calculate_rhythmic_complexity(&factors, tempo_bpm, &meter),
+            self.calculate_technical_complexity(progression, &factors),
+            self.calculate_melodic_complexity(progression),
+            &factors,
+        ))
+    }
+
     /// Analyze chord progression patterns and structure
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(chords = progression.len()))
+    )]
     pub fn analyze_progression(&self, progression: &[Chord]) -> AiResult<ProgressionAnalysis> {
         if progression.is_empty() {
             return Err(AiError::AnalysisFailed {
@@ -280,8 +1357,8 @@ impl MusicalAnalyzer {
         // Find common patterns
         let common_patterns = self.find_common_patterns(progression)?;
 
-        // Analyze harmonic rhythm
-        let harmonic_rhythm = self.analyze_harmonic_rhythm(progression);
+        // Untimed chords count one beat each; see analyze_timeline_progression
+        let harmonic_rhythm = self.untimed_harmonic_rhythm(progression);
 
         // Assess voice leading quality
         let voice_leading_quality = self.assess_voice_leading_quality(progression);
@@ -299,12 +1376,87 @@ impl MusicalAnalyzer {
         })
     }
 
+    /// Analyze how often chords change from their durations in beats.
+    ///
+    /// Consecutive repeats of the same chord count as one harmonic change and
+    /// chords of zero length are ignored. Reports the average duration and its
+    /// variance in ticks, a regularity score, whether chords speed up or slow
+    /// down, and detected duration patterns (see [`HarmonicRhythm`]).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{ChordProgressionTrie, MusicalAnalyzer, RhythmTrend};
+    /// use composer_core::Chord;
+    /// use std::sync::Arc;
+    ///
+    /// let analyzer = MusicalAnalyzer::new(Arc::new(ChordProgressionTrie::new()));
+    /// let chords: Vec<(Chord, f64)> = [(1, 4.0), (4, 2.0), (5, 2.0), (1, 4.0), (4, 2.0), (5, 2.0)]
+    ///     .into_iter()
+    ///     .map(|(root, beats)| Ok((Chord::triad(root)?, beats)))
+    ///     .collect::<Result<_, composer_core::ChordTheoryError>>()?;
+    ///
+    /// let rhythm = analyzer.analyze_harmonic_rhythm(&chords)?;
+    /// assert_eq!(rhythm.rhythm_patterns, ["Repeating 4+2+2"]);
+    /// assert_eq!(rhythm.trend, RhythmTrend::Steady);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`Self::analyze_timeline_progression`] - Analyze a timeline, timing included
+    pub fn analyze_harmonic_rhythm(&self, chords: &[ChordDuration]) -> AiResult<HarmonicRhythm> {
+        let mut changes: Vec<ChordDuration> = Vec::new();
+        for (chord, beats) in chords {
+            if !beats.is_finite() || *beats < 0.0 {
+                return Err(AiError::AnalysisFailed {
+                    reason: format!("Invalid chord duration {}", beats),
+                });
+            }
+            if *beats == 0.0 {
+                continue;
+            }
+            match changes.last_mut() {
+                Some((previous, duration)) if previous == chord => *duration += beats,
+                _ => changes.push((chord.clone(), *beats)),
+            }
+        }
+        if changes.is_empty() {
+            return Err(AiError::AnalysisFailed {
+                reason: "Cannot analyze harmonic rhythm without timed chords".to_string(),
+            });
+        }
+
+        let durations: Vec<f64> = changes.iter().map(|(_, beats)| *beats).collect();
+        Ok(harmonic_rhythm_of(&durations))
+    }
+
+    /// Analyze the chords of a timeline, with harmonic rhythm from their timing
+    ///
+    /// Like [`Self::analyze_progression`] on the timeline's chords, except that
+    /// [`ProgressionAnalysis::harmonic_rhythm`] comes from
+    /// [`Self::analyze_harmonic_rhythm`] on the chord durations.
+    pub fn analyze_timeline_progression(
+        &self,
+        timeline: &Timeline,
+    ) -> AiResult<ProgressionAnalysis> {
+        let chords: Vec<ChordDuration> = timed_chords(timeline)
+            .into_iter()
+            .map(|timed| (timed.chord, timed.end - timed.start))
+            .collect();
+        let progression: Vec<Chord> = chords.iter().map(|(chord, _)| chord.clone()).collect();
+
+        let mut analysis = self.analyze_progression(&progression)?;
+        analysis.harmonic_rhythm = self.analyze_harmonic_rhythm(&chords)?;
+        Ok(analysis)
+    }
+
     /// Calculate detailed complexity factors
     fn calculate_complexity_factors(
         &self,
         progression: &[Chord],
         _tempo_bpm: Option<f64>,
-        _time_signature: Option<(u8, u8)>,
+        meter: &MeterContext,
     ) -> AiResult<ComplexityFactors> {
         // Count unique chords
         // Count unique chords by serializing them
@@ -317,11 +1469,9 @@ impl MusicalAnalyzer {
         let unique_chords = seen_chords.len();
 
         // Calculate average chord complexity
-        let avg_chord_complexity = progression
-            .par_iter()
-            .map(|chord| self.calculate_single_chord_complexity(chord))
-            .sum::<f64>()
-            / progression.len() as f64;
+        let avg_chord_complexity = crate::parallel::map_sum(progression, |chord| {
+            self.calculate_single_chord_complexity(chord)
+        }) / progression.len() as f64;
 
         // Detect key changes (simplified)
         let key_changes = self.count_key_changes(progression);
@@ -329,8 +1479,8 @@ impl MusicalAnalyzer {
         // Count uncommon progressions
         let uncommon_progressions = self.count_uncommon_progressions(progression)?;
 
-        // Time signature changes (0 if not provided)
-        let time_signature_changes = 0;
+        // Changes to a different time signature
+        let time_signature_changes = meter.signature_changes();
 
         // Count extended harmonies
         let extended_harmonies = progression
@@ -396,12 +1546,17 @@ impl MusicalAnalyzer {
         &self,
         factors: &ComplexityFactors,
         tempo_bpm: Option<f64>,
+        meter: &MeterContext,
     ) -> f64 {
         let mut complexity = 2.0; // Base complexity
 
         // Time signature changes
         complexity += factors.time_signature_changes as f64 * 1.0;
 
+        // Irregular meters (5/4, 7/8) and chord changes off the beat
+        complexity += meter.irregularity() * 1.5;
+        complexity += meter.syncopation() * 3.0;
+
         // Tempo variations
         complexity += factors.tempo_variations * 1.5;
 
@@ -415,6 +1570,19 @@ impl MusicalAnalyzer {
         complexity.min(10.0)
     }
 
+    /// Technical complexity on an instrument, or instrument-agnostic for `None`
+    fn technical_complexity_for(
+        &self,
+        progression: &[Chord],
+        factors: &ComplexityFactors,
+        profile: Option<DifficultyProfile>,
+    ) -> f64 {
+        match profile {
+            Some(profile) => instrument::technical_complexity(profile, progression),
+            None => self.calculate_technical_complexity(progression, factors),
+        }
+    }
+
     /// Calculate technical complexity score
     fn calculate_technical_complexity(
         &self,
@@ -451,20 +1619,7 @@ impl MusicalAnalyzer {
         for window in progression.windows(2) {
             let interval = self.calculate_root_interval(&window[0], &window[1]);
 
-            // Interval difficulty assessment
-            let interval_difficulty = match interval {
-                0 => 0.0,      // Unison (no movement)
-                1 => 0.2,      // Minor second (chromatic)
-                2 => 0.1,      // Major second (step)
-                3 => 0.2,      // Minor third
-                4 => 0.3,      // Major third
-                5 => 0.4,      // Perfect fourth
-                6 => 0.5,      // Tritone (complex)
-                7 => 0.4,      // Perfect fifth
-                8..=11 => 0.6, // Large intervals (6th, 7th, etc.)
-                _ => 0.7,      // Very large intervals
-            };
-            interval_complexity += interval_difficulty;
+            interval_complexity += interval_difficulty(interval);
         }
         complexity += interval_complexity;
 
@@ -493,72 +1648,172 @@ impl MusicalAnalyzer {
         complexity.min(10.0)
     }
 
-    /// Apply polynomial regression model for final difficulty score
-    fn apply_difficulty_model(
-        &self,
-        harmonic: f64,
-        rhythmic: f64,
-        technical: f64,
-        melodic: f64,
-    ) -> f64 {
-        // Weighted combination of complexity factors
-        let weighted_input = harmonic
-            * self
-                .difficulty_model
-                .feature_weights
-                .get("harmonic_complexity")
-                .unwrap_or(&0.35)
-            + rhythmic
-                * self
-                    .difficulty_model
-                    .feature_weights
-                    .get("rhythmic_complexity")
-                    .unwrap_or(&0.25)
-            + technical
-                * self
-                    .difficulty_model
-                    .feature_weights
-                    .get("technical_complexity")
-                    .unwrap_or(&0.25)
-            + melodic
-                * self
+    /// Overall score from the custom model if one is attached, else polynomial regression
+    fn predict_overall_score(&self, features: &DifficultyFeatures) -> AiResult<f64> {
+        let custom_model = self.custom_model.read().clone();
+        match custom_model {
+            Some(model) => {
+                let score = model.predict(features)?;
+                if !score.is_finite() {
+                    return Err(AiError::AnalysisFailed {
+                        reason: format!("{} returned {}", model.name(), score),
+                    });
+                }
+                Ok(score.clamp(0.0, 10.0))
+            },
+            None => Ok(self.difficulty_model.read().predict(features)),
+        }
+    }
+
+    /// Sub-scores ordered by their share of the weighted model input
+    fn rank_factor_contributions(&self, features: &DifficultyFeatures) -> Vec<FactorContribution> {
+        let mut contributions: Vec<FactorContribution> = DIFFICULTY_FEATURE_NAMES[..4]
+            .iter()
+            .zip(features.values())
+            .map(|(&feature, &value)| FactorContribution {
+                feature: feature.to_string(),
+                value,
+                weight: self
                     .difficulty_model
+                    .read()
                     .feature_weights
-                    .get("melodic_complexity")
-                    .unwrap_or(&0.15);
+                    .get(feature)
+                    .copied()
+                    .unwrap_or(0.0),
+                share: 0.0,
+            })
+            .collect();
+
+        let total: f64 = contributions.iter().map(|c| c.value * c.weight).sum();
+        if total > 0.0 {
+            for contribution in &mut contributions {
+                contribution.share = contribution.value * contribution.weight / total;
+            }
+        }
+        contributions.sort_by(|a, b| b.share.total_cmp(&a.share));
+        contributions
+    }
 
-        // Apply cubic polynomial: ax³ + bx² + cx + d
-        let x = weighted_input / 10.0; // Normalize input to max complexity scale
-        let [a, b, c, d] = self.difficulty_model.coefficients;
+    /// Reference progressions scored with the active model, computed once per
+    /// tempo, meter and instrument profile until patterns are added
+    fn scored_references(
+        &self,
+        tempo_bpm: Option<f64>,
+        meter: &MeterContext,
+        profile: Option<DifficultyProfile>,
+    ) -> AiResult<Arc<Vec<ScoredReference>>> {
+        // References are untimed but share the assessed meters
+        let meter = &meter.untimed();
+        // Uncommon transitions are counted against the stored patterns
+        let key = format!(
+            "{}_{:?}_{}_{}",
+            self.trie.total_patterns(),
+            tempo_bpm,
+            meter.cache_key(),
+            profile.map_or("any", |profile| profile.name())
+        );
+        if let Some(scored) = self.reference_scores.get(&key) {
+            return Ok(Arc::clone(&scored));
+        }
 
-        let polynomial_result = a * x.powi(3) + b * x.powi(2) + c * x + d;
+        let mut scored = Vec::new();
+        for (name, progression) in reference_progressions()? {
+            let progression = &*evidence_span(&progression);
+            let factors = self.calculate_complexity_factors(progression, tempo_bpm, meter)?;
+            let reference_features = DifficultyFeatures::new(
+                self.calculate_harmonic_complexity(progression, &factors)?,
+                self.calculate_rhythmic_complexity(&factors, tempo_bpm, meter),
+                self.technical_complexity_for(progression, &factors, profile),
+                self.calculate_melodic_complexity(progression),
+                &factors,
+            );
+            let score = self.predict_overall_score(&reference_features)?;
+            scored.push((
+                name,
+                reference_features,
+                score,
+                self.classify_skill_level(score),
+            ));
+        }
 
-        // Scale back to 0-10 range and clamp
-        (polynomial_result * 10.0).clamp(0.0, 10.0) // Scale to 0-10 range
+        let scored = Arc::new(scored);
+        self.reference_scores.insert(key, Arc::clone(&scored));
+        Ok(scored)
+    }
+
+    /// Compare features against the reference progressions scored at `skill_level`
+    fn compare_to_references(
+        &self,
+        features: &DifficultyFeatures,
+        overall_score: f64,
+        skill_level: SkillLevel,
+        tempo_bpm: Option<f64>,
+        meter: &MeterContext,
+        profile: Option<DifficultyProfile>,
+    ) -> AiResult<ReferenceComparison> {
+        let scored = self.scored_references(tempo_bpm, meter, profile)?;
+
+        // Nearest level that has references, preferring the assessed one
+        let level = scored
+            .iter()
+            .map(|(_, _, _, level)| *level)
+            .min_by_key(|level| (level.rank().abs_diff(skill_level.rank()), level.rank()))
+            .unwrap_or(skill_level);
+        let references: Vec<_> = scored
+            .iter()
+            .filter(|(_, _, _, reference_level)| *reference_level == level)
+            .collect();
+
+        let count = references.len().max(1) as f64;
+        let reference_score = references.iter().map(|(_, _, score, _)| score).sum::<f64>() / count;
+        let mut means = [0.0; DIFFICULTY_FEATURE_COUNT];
+        for (_, reference_features, _, _) in &references {
+            for (mean, value) in means.iter_mut().zip(reference_features.values()) {
+                *mean += value / count;
+            }
+        }
+
+        let mut comparisons: Vec<FeatureComparison> = features
+            .iter()
+            .zip(means)
+            .map(|((feature, value), reference_mean)| FeatureComparison {
+                feature: feature.to_string(),
+                value,
+                reference_mean,
+                difference: value - reference_mean,
+            })
+            .collect();
+        let relative = |c: &FeatureComparison| c.difference.abs() / (c.reference_mean.abs() + 1.0);
+        comparisons.sort_by(|a, b| relative(b).total_cmp(&relative(a)));
+
+        Ok(ReferenceComparison {
+            skill_level: level,
+            references: references
+                .iter()
+                .map(|(name, _, _, _)| name.to_string())
+                .collect(),
+            reference_score,
+            score_difference: overall_score - reference_score,
+            features: comparisons,
+        })
     }
 
     /// Classify skill level based on difficulty score
     fn classify_skill_level(&self, score: f64) -> SkillLevel {
-        match score {
-            s if s < 2.5 => SkillLevel::Beginner,
-            s if s < 5.0 => SkillLevel::Intermediate,
-            s if s < 7.5 => SkillLevel::Advanced,
-            _ => SkillLevel::Expert,
-        }
+        self.skill_thresholds.read().classify(score).level
     }
 
     /// Calculate confidence in analysis
     fn calculate_analysis_confidence(
         &self,
         progression: &[Chord],
+        evidence: f64,
         factors: &ComplexityFactors,
     ) -> f64 {
-        let mut confidence = self.difficulty_model.accuracy;
+        let mut confidence = self.difficulty_model.read().accuracy;
 
-        // Reduce confidence for very short progressions
-        if progression.len() < 4 {
-            confidence *= 0.8;
-        }
+        // Reduce confidence in proportion to missing evidence
+        confidence *= 0.5 + 0.5 * evidence;
 
         // Reduce confidence if we have many uncommon progressions
         if factors.uncommon_progressions > progression.len() / 2 {
@@ -568,6 +1823,23 @@ impl MusicalAnalyzer {
         confidence.clamp(0.0, 1.0)
     }
 
+    /// Interval around an overall score
+    ///
+    /// The half-width is the model error, `(1 - accuracy)` of the 0-10 scale,
+    /// and grows with the square root of missing evidence so that a single
+    /// chord gets twice the spread of a four-chord progression.
+    fn score_interval(&self, overall_score: f64, evidence: f64) -> ConfidenceInterval {
+        let accuracy = self
+            .custom_model
+            .read()
+            .as_ref()
+            .and_then(|model| model.accuracy())
+            .unwrap_or(self.difficulty_model.read().accuracy);
+        let model_error = (1.0 - accuracy.clamp(0.0, 1.0)) * 10.0;
+        let half_width = model_error / evidence.max(f64::EPSILON).sqrt();
+        ConfidenceInterval::around(overall_score, half_width, 0.0, 10.0)
+    }
+
     /// Helper methods for complexity calculation
     pub fn calculate_single_chord_complexity(&self, chord: &Chord) -> f64 {
         let mut complexity = 1.0;
@@ -652,11 +1924,8 @@ impl MusicalAnalyzer {
 
         // Check each 2-chord progression
         for window in progression.windows(2) {
-            let pattern = vec![Some(window[0].clone()), Some(window[1].clone())];
-            let results = self.trie.search_with_wildcard(&pattern, 1)?;
-
             // If we find very few results, consider it uncommon
-            if results.is_empty() || results[0].count < 5 {
+            if self.transition_count(&window[0], &window[1])? < COMMON_TRANSITION_COUNT {
                 uncommon_count += 1;
             }
         }
@@ -664,41 +1933,83 @@ impl MusicalAnalyzer {
         Ok(uncommon_count)
     }
 
+    /// Occurrences of the move from `from` to `to` in the pattern database
+    pub(crate) fn transition_count(&self, from: &Chord, to: &Chord) -> AiResult<u32> {
+        self.sequence_count(&[from.clone(), to.clone()])
+    }
+
+    /// Occurrences of `chords`, in order, in the pattern database
+    pub(crate) fn sequence_count(&self, chords: &[Chord]) -> AiResult<u32> {
+        let pattern: Vec<_> = chords.iter().cloned().map(Some).collect();
+        let results = self.trie.search_with_wildcard(&pattern, 1)?;
+        Ok(results.first().map_or(0, |result| result.count))
+    }
+
+    /// Each chord's complexity, voice-leading cost and novelty
+    fn per_chord_breakdown(
+        &self,
+        progression: &[Chord],
+    ) -> AiResult<Vec<ChordDifficultyContribution>> {
+        progression
+            .iter()
+            .enumerate()
+            .map(|(index, chord)| {
+                let (voice_leading_cost, novelty) = match index.checked_sub(1) {
+                    Some(previous) => {
+                        let previous = &progression[previous];
+                        let count = self.transition_count(previous, chord)?;
+                        (
+                            self.voice_leading_cost(previous, chord),
+                            1.0 - count.min(COMMON_TRANSITION_COUNT) as f64
+                                / COMMON_TRANSITION_COUNT as f64,
+                        )
+                    },
+                    None => (0.0, 0.0),
+                };
+                Ok(ChordDifficultyContribution {
+                    index,
+                    complexity: self.calculate_single_chord_complexity(chord),
+                    voice_leading_cost,
+                    novelty,
+                })
+            })
+            .collect()
+    }
+
     fn is_extended_harmony(&self, chord: &Chord) -> bool {
         // Check for 7ths, 9ths, 11ths, 13ths, etc.
         chord.chord_type >= 7 || !chord.adds.is_empty() || !chord.alterations.is_empty()
     }
 
     fn calculate_voice_leading_complexity(&self, progression: &[Chord]) -> f64 {
-        let mut complexity = 0.0;
+        let complexity: f64 = progression
+            .windows(2)
+            .map(|window| self.voice_leading_cost(&window[0], &window[1]))
+            .sum();
 
-        for window in progression.windows(2) {
-            let interval = self.calculate_root_interval(&window[0], &window[1]);
-
-            // Voice leading quality assessment per specification
-            match interval {
-                0 => complexity += 0.1,     // Static (minimal complexity)
-                1 => complexity += 0.4,     // Chromatic (moderate complexity)
-                2 => complexity += 0.2,     // Step-wise (smooth)
-                3..=4 => complexity += 0.3, // Skip intervals (moderate)
-                5..=6 => complexity += 0.6, // Larger intervals (more complex)
-                _ => complexity += 0.8,     // Very large intervals (complex)
-            }
+        complexity / (progression.len() as f64).max(1.0)
+    }
 
-            // Additional complexity for chord type changes
-            let type_change_complexity = if window[0].chord_type != window[1].chord_type {
-                0.2 // Different chord types add voice leading complexity
-            } else {
-                0.0
-            };
-            complexity += type_change_complexity;
+    /// Voice-leading cost of moving from one chord to the next
+    fn voice_leading_cost(&self, from: &Chord, to: &Chord) -> f64 {
+        // Voice leading quality assessment per specification
+        let mut cost = match self.calculate_root_interval(from, to) {
+            0 => 0.1,     // Static (minimal complexity)
+            1 => 0.4,     // Chromatic (moderate complexity)
+            2 => 0.2,     // Step-wise (smooth)
+            3..=4 => 0.3, // Skip intervals (moderate)
+            5..=6 => 0.6, // Larger intervals (more complex)
+            _ => 0.8,     // Very large intervals (complex)
+        };
 
-            // Inversion changes add complexity
-            let inversion_change = (window[1].inversion as i8 - window[0].inversion as i8).abs();
-            complexity += inversion_change as f64 * 0.1;
+        // Additional complexity for chord type changes
+        if from.chord_type != to.chord_type {
+            cost += 0.2; // Different chord types add voice leading complexity
         }
 
-        complexity / (progression.len() as f64).max(1.0)
+        // Inversion changes add complexity
+        let inversion_change = (to.inversion as i8 - from.inversion as i8).abs();
+        cost + inversion_change as f64 * 0.1
     }
 
     fn calculate_chord_span_complexity(&self, progression: &[Chord]) -> f64 {
@@ -725,18 +2036,21 @@ impl MusicalAnalyzer {
         let mut key_centers = Vec::new();
 
         // Find most common root as likely key center
+        let reference = ScaleFingerprint::major_scale();
         let mut root_counts = HashMap::new();
         for chord in progression {
-            *root_counts.entry(chord.root).or_insert(0) += 1;
+            if let Some(root) = reference.scale_degree_to_chromatic(chord.root) {
+                *root_counts.entry(root).or_insert(0) += 1;
+            }
         }
 
         if let Some((&most_common_root, &count)) =
             root_counts.iter().max_by_key(|(_, &count)| count)
         {
             key_centers.push(KeyCenter {
-                root: most_common_root,
-                mode: Mode::Major, // Simplified
+                key: Key::from_chromatic(most_common_root, Mode::Major), // Simplified
                 confidence: count as f64 / progression.len() as f64,
+                confidence_interval: ConfidenceInterval::wilson(count, progression.len()),
                 chord_range: (0, progression.len()),
             });
         }
@@ -767,12 +2081,9 @@ impl MusicalAnalyzer {
         chords.len() == 4 // Placeholder logic
     }
 
-    fn analyze_harmonic_rhythm(&self, _progression: &[Chord]) -> HarmonicRhythm {
-        HarmonicRhythm {
-            avg_chord_duration: MUSICAL.ticks_per_beat as f64, // Simplified
-            regularity: 0.8,                                   // Simplified
-            rhythm_patterns: vec!["Regular".to_string()],      // Simplified
-        }
+    /// Harmonic rhythm of an untimed progression, one beat per chord
+    fn untimed_harmonic_rhythm(&self, progression: &[Chord]) -> HarmonicRhythm {
+        harmonic_rhythm_of(&vec![1.0; progression.len()])
     }
 
     fn assess_voice_leading_quality(&self, progression: &[Chord]) -> f64 {
@@ -806,7 +2117,8 @@ impl MusicalAnalyzer {
         &self,
         progression: &[Chord],
         tempo_bpm: Option<f64>,
-        time_signature: Option<(u8, u8)>,
+        meter: &MeterContext,
+        profile: Option<DifficultyProfile>,
     ) -> String {
         // Create a unique key that includes the actual chord content
         let mut chord_descriptors = Vec::new();
@@ -826,16 +2138,92 @@ impl MusicalAnalyzer {
         }
 
         format!(
-            "diff_[{}]_{:.1}_{:?}",
+            "diff_[{}]_{:.1}_{}_{}",
             chord_descriptors.join(","),
             tempo_bpm.unwrap_or(120.0),
-            time_signature.unwrap_or((4, 4))
+            meter.cache_key(),
+            profile.map_or("any", |profile| profile.name())
         )
     }
 
+    /// Polynomial model used for assessments when no custom model is attached
+    pub fn difficulty_model(&self) -> PolynomialModel {
+        self.difficulty_model.read().clone()
+    }
+
+    /// Install a polynomial model, e.g. one from [`PolynomialModel::fit`]
+    pub fn set_polynomial_model(&self, model: PolynomialModel) -> AiResult<()> {
+        model.validate()?;
+        *self.difficulty_model.write() = model;
+        self.analysis_cache.clear();
+        self.reference_scores.clear();
+        Ok(())
+    }
+
+    /// Replace the polynomial model with an external model, or restore it with `None`
+    pub fn set_difficulty_model(&self, model: Option<SharedDifficultyModel>) {
+        *self.custom_model.write() = model;
+        self.analysis_cache.clear();
+        self.reference_scores.clear();
+    }
+
+    /// Replace the score cutoffs used to assign skill levels and bands
+    pub fn set_skill_thresholds(&self, thresholds: SkillThresholds) {
+        *self.skill_thresholds.write() = thresholds;
+        self.analysis_cache.clear();
+        self.reference_scores.clear();
+    }
+
+    /// Score cutoffs used to assign skill levels and bands
+    pub fn skill_thresholds(&self) -> SkillThresholds {
+        self.skill_thresholds.read().clone()
+    }
+
+    /// Calibrate percentiles against assessments of a corpus.
+    ///
+    /// Later assessments report where they sit in the corpus through
+    /// [`DifficultyAssessment::percentile`]. Assess the corpus the same way
+    /// (model, instrument profile) as the songs to be placed in it.
+    pub fn calibrate(&self, corpus_assessments: &[DifficultyAssessment]) -> AiResult<()> {
+        let calibration = DifficultyCalibration::from_assessments(corpus_assessments)?;
+        self.set_calibration(Some(calibration));
+        Ok(())
+    }
+
+    /// Replace the percentile calibration, or drop it with `None`
+    pub fn set_calibration(&self, calibration: Option<DifficultyCalibration>) {
+        *self.calibration.write() = calibration;
+        self.analysis_cache.clear();
+    }
+
+    /// Corpus score distribution used for percentiles, if calibrated
+    pub fn calibration(&self) -> Option<DifficultyCalibration> {
+        self.calibration.read().clone()
+    }
+
+    /// Replace the runtime analysis parameters.
+    ///
+    /// Clears the analysis cache, since cached assessments no longer apply.
+    pub fn set_analysis_parameters(&self, parameters: AnalysisParameters) {
+        *self.parameters.write() = parameters;
+        self.analysis_cache.clear();
+        self.reference_scores.clear();
+    }
+
+    /// Runtime analysis parameters
+    pub fn analysis_parameters(&self) -> AnalysisParameters {
+        self.parameters.read().clone()
+    }
+
+    /// Currently attached external difficulty model, if any
+    pub fn custom_difficulty_model(&self) -> Option<SharedDifficultyModel> {
+        self.custom_model.read().clone()
+    }
+
     /// Clear analysis cache
     pub fn clear_cache(&self) {
         self.analysis_cache.clear();
+        self.reference_scores.clear();
     }
 
     /// Get cache statistics
@@ -847,8 +2235,62 @@ impl MusicalAnalyzer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::difficulty_model::{DenseDifficultyModel, DenseLayer, DIFFICULTY_FEATURE_VERSION};
     use composer_core::Chord;
 
+    #[test]
+    fn test_difficulty_explanation() {
+        let analyzer = MusicalAnalyzer::new(Arc::new(ChordProgressionTrie::new()));
+        let progression = vec![
+            Chord::new(2, 9).unwrap(),
+            Chord::new(5, 13).unwrap(),
+            Chord::new(1, 9).unwrap(),
+        ];
+        let assessment = analyzer
+            .assess_difficulty(&progression, Some(120.0), Some((4, 4)))
+            .unwrap();
+        let explanation = &assessment.explanation;
+
+        assert_eq!(explanation.top_factors.len(), 4);
+        let shares: Vec<f64> = explanation.top_factors.iter().map(|f| f.share).collect();
+        assert!(shares.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert!((shares.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert_eq!(explanation.top_factors[0].feature, "harmonic_complexity");
+
+        let reference = &explanation.reference;
+        assert_eq!(reference.skill_level, assessment.skill_level);
+        assert!(reference.references.contains(&"ii9-V13-I9".to_string()));
+        assert_eq!(reference.features.len(), DIFFICULTY_FEATURE_COUNT);
+        assert!(
+            (reference.score_difference - (assessment.overall_score - reference.reference_score))
+                .abs()
+                < 1e-9
+        );
+    }
+
+    #[test]
+    fn test_reference_comparison_uses_nearest_level() {
+        let analyzer = MusicalAnalyzer::new(Arc::new(ChordProgressionTrie::new()));
+        let features = analyzer
+            .extract_difficulty_features(&[Chord::new(1, 5).unwrap()], None, None)
+            .unwrap();
+
+        // The default model scores no reference as Beginner
+        let comparison = analyzer
+            .compare_to_references(
+                &features,
+                1.0,
+                SkillLevel::Beginner,
+                None,
+                &MeterContext::default(),
+                None,
+            )
+            .unwrap();
+        assert_eq!(comparison.skill_level, SkillLevel::Intermediate);
+        assert!(comparison.references.contains(&"I-V-vi-IV".to_string()));
+        assert!(comparison.score_difference < 0.0);
+    }
+
     #[test]
     fn test_analyzer_creation() {
         let trie = Arc::new(ChordProgressionTrie::new());
@@ -897,6 +2339,328 @@ mod tests {
         assert_eq!(analyzer.classify_skill_level(9.0), SkillLevel::Expert);
     }
 
+    #[test]
+    fn test_confidence_intervals() {
+        let wilson = ConfidenceInterval::wilson(3, 4);
+        assert!((wilson.lower - 0.3006).abs() < 1e-3);
+        assert!((wilson.upper - 0.9544).abs() < 1e-3);
+        assert_eq!(ConfidenceInterval::wilson(0, 0).width(), 1.0);
+        assert!(ConfidenceInterval::wilson(30, 40).width() < wilson.width());
+
+        let analyzer = MusicalAnalyzer::new(Arc::new(ChordProgressionTrie::new()));
+        let progression: Vec<Chord> = [1, 4, 5, 1]
+            .iter()
+            .map(|&root| Chord::new(root, 5).unwrap())
+            .collect();
+        let full = analyzer
+            .assess_difficulty(&progression, None, None)
+            .unwrap();
+        let single = analyzer
+            .assess_difficulty(&progression[..1], None, None)
+            .unwrap();
+        assert!(full.score_interval.contains(full.overall_score));
+        // Model error of the default 0.85 accuracy, doubled for a single chord
+        assert!((full.score_interval.width() - 3.0).abs() < 1e-9);
+        assert!(single.score_interval.width() > full.score_interval.width());
+
+        // Externally reported accuracy narrows the interval
+        let model = DenseDifficultyModel {
+            feature_version: DIFFICULTY_FEATURE_VERSION,
+            feature_names: DIFFICULTY_FEATURE_NAMES
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            input_mean: vec![],
+            input_scale: vec![],
+            layers: vec![DenseLayer::Gemm {
+                weight: vec![vec![0.0; DIFFICULTY_FEATURE_COUNT]],
+                bias: vec![5.0],
+            }],
+            output_scale: 1.0,
+            accuracy: Some(0.95),
+        };
+        analyzer.set_difficulty_model(Some(Arc::new(model)));
+        let custom = analyzer
+            .assess_difficulty(&progression, None, None)
+            .unwrap();
+        assert_eq!(
+            custom.score_interval,
+            ConfidenceInterval::around(5.0, 0.5, 0.0, 10.0)
+        );
+
+        let analysis = analyzer.analyze_progression(&progression).unwrap();
+        let key = &analysis.key_centers[0];
+        assert!(key.confidence_interval.contains(key.confidence));
+        assert_eq!(key.key, "C".parse().unwrap());
+    }
+
+    #[test]
+    fn test_minimum_evidence() {
+        let trie = Arc::new(ChordProgressionTrie::new());
+        let analyzer = MusicalAnalyzer::new(trie);
+        let tonic = Chord::new(1, 5).unwrap();
+
+        let single = analyzer
+            .assess_difficulty(std::slice::from_ref(&tonic), None, None)
+            .unwrap();
+        assert!(single.insufficient_data);
+        assert_eq!(single.evidence, 0.25);
+
+        // A lone chord scores like the vamp it stands for
+        let vamp = analyzer
+            .assess_difficulty(&vec![tonic.clone(); 4], None, None)
+            .unwrap();
+        assert!(!vamp.insufficient_data);
+        assert_eq!(vamp.evidence, 1.0);
+        assert_eq!(single.overall_score, vamp.overall_score);
+        assert!(single.confidence < vamp.confidence);
+
+        let pair = analyzer
+            .assess_difficulty(&[tonic, Chord::new(5, 5).unwrap()], None, None)
+            .unwrap();
+        assert!(single.confidence < pair.confidence);
+        assert!(pair.confidence < vamp.confidence);
+    }
+
+    #[test]
+    fn test_skill_thresholds() {
+        let defaults = SkillThresholds::default();
+        assert_eq!(defaults.classify(3.0).name, "Early Intermediate");
+        assert_eq!(defaults.classify(4.0).name, "Late Intermediate");
+        assert_eq!(defaults.classify(-1.0).name, "Beginner");
+
+        let band = |name: &str, level, min| SkillBand::new(name, level, min);
+        assert!(SkillThresholds::new(vec![]).is_err());
+        assert!(SkillThresholds::new(vec![
+            band("a", SkillLevel::Beginner, 0.0),
+            band("b", SkillLevel::Advanced, 0.0),
+        ])
+        .is_err());
+        assert!(SkillThresholds::new(vec![
+            band("a", SkillLevel::Advanced, 0.0),
+            band("b", SkillLevel::Beginner, 5.0),
+        ])
+        .is_err());
+        assert!(serde_json::from_str::<SkillThresholds>(r#"[]"#).is_err());
+
+        // Calibrated thresholds drive assessments
+        let trie = Arc::new(ChordProgressionTrie::new());
+        let analyzer = MusicalAnalyzer::new(trie);
+        let progression = vec![Chord::new(1, 5).unwrap(), Chord::new(5, 5).unwrap()];
+        let before = analyzer
+            .assess_difficulty(&progression, None, None)
+            .unwrap();
+        let strict = SkillThresholds::new(vec![
+            band("Novice", SkillLevel::Beginner, 0.0),
+            band("Graduate", SkillLevel::Expert, before.overall_score / 2.0),
+        ])
+        .unwrap();
+        analyzer.set_skill_thresholds(strict.clone());
+        let after = analyzer
+            .assess_difficulty(&progression, None, None)
+            .unwrap();
+        assert_eq!(after.skill_band, "Graduate");
+        assert_eq!(after.skill_level, SkillLevel::Expert);
+
+        let json = serde_json::to_string(&strict).unwrap();
+        assert_eq!(
+            serde_json::from_str::<SkillThresholds>(&json).unwrap(),
+            strict
+        );
+    }
+
+    #[test]
+    fn test_reference_scores_follow_thresholds() {
+        let analyzer = MusicalAnalyzer::new(Arc::new(ChordProgressionTrie::new()));
+        let progression = [Chord::new(1, 5).unwrap(), Chord::new(4, 5).unwrap()];
+        analyzer
+            .assess_difficulty(&progression, None, None)
+            .unwrap();
+        analyzer
+            .assess_difficulty(&progression[..1], None, None)
+            .unwrap();
+        assert_eq!(analyzer.reference_scores.len(), 1);
+
+        let everyone_expert =
+            SkillThresholds::new(vec![SkillBand::new("Graduate", SkillLevel::Expert, 0.0)])
+                .unwrap();
+        analyzer.set_skill_thresholds(everyone_expert);
+        assert!(analyzer.reference_scores.is_empty());
+        let reference = analyzer
+            .assess_difficulty(&progression, None, None)
+            .unwrap()
+            .explanation
+            .reference;
+        assert_eq!(reference.skill_level, SkillLevel::Expert);
+        assert_eq!(reference.references.len(), 11);
+    }
+
+    #[test]
+    fn test_percentile_calibration() {
+        assert!(DifficultyCalibration::from_scores(vec![]).is_err());
+        assert!(DifficultyCalibration::from_scores(vec![1.0, f64::NAN]).is_err());
+        let calibration = DifficultyCalibration::from_scores(vec![8.0, 2.0, 5.0, 3.0]).unwrap();
+        assert_eq!(calibration.scores(), [2.0, 3.0, 5.0, 8.0]);
+        assert_eq!(calibration.percentile(1.0), 0);
+        assert_eq!(calibration.percentile(3.0), 25);
+        assert_eq!(calibration.percentile(6.2), 75);
+        assert_eq!(calibration.percentile(10.0), 99);
+
+        let json = serde_json::to_string(&calibration).unwrap();
+        assert_eq!(
+            serde_json::from_str::<DifficultyCalibration>(&json).unwrap(),
+            calibration
+        );
+
+        let trie = Arc::new(ChordProgressionTrie::new());
+        let analyzer = MusicalAnalyzer::new(trie);
+        let simple = vec![Chord::new(1, 5).unwrap(), Chord::new(5, 5).unwrap()];
+        let complex = vec![
+            Chord::new(2, 9).unwrap(),
+            Chord::new(5, 13).unwrap(),
+            Chord::new(1, 11).unwrap(),
+        ];
+        let corpus = [
+            analyzer.assess_difficulty(&simple, None, None).unwrap(),
+            analyzer.assess_difficulty(&complex, None, None).unwrap(),
+        ];
+        assert_eq!(corpus[0].percentile, None);
+
+        analyzer.calibrate(&corpus).unwrap();
+        let simple_assessment = analyzer.assess_difficulty(&simple, None, None).unwrap();
+        let complex_assessment = analyzer.assess_difficulty(&complex, None, None).unwrap();
+        assert_eq!(simple_assessment.percentile, Some(0));
+        assert_eq!(complex_assessment.percentile, Some(50));
+        assert!(analyzer.calibrate(&[]).is_err());
+
+        analyzer.set_analysis_parameters(AnalysisParameters {
+            difficulty_percentile_max: 40,
+            ..AnalysisParameters::default()
+        });
+        let capped = analyzer.assess_difficulty(&complex, None, None).unwrap();
+        assert_eq!(capped.percentile, Some(40));
+
+        analyzer.set_calibration(None);
+        let uncalibrated = analyzer.assess_difficulty(&simple, None, None).unwrap();
+        assert_eq!(uncalibrated.percentile, None);
+    }
+
+    #[test]
+    fn test_harmonic_rhythm_from_durations() {
+        let analyzer = MusicalAnalyzer::new(Arc::new(ChordProgressionTrie::new()));
+        let timed = |pairs: &[(u8, f64)]| -> Vec<ChordDuration> {
+            pairs
+                .iter()
+                .map(|&(root, beats)| (Chord::new(root, 5).unwrap(), beats))
+                .collect()
+        };
+        let ticks = MUSICAL.ticks_per_beat as f64;
+
+        let regular = analyzer
+            .analyze_harmonic_rhythm(&timed(&[(1, 4.0), (4, 4.0), (5, 4.0), (1, 4.0)]))
+            .unwrap();
+        assert_eq!(regular.avg_chord_duration, 4.0 * ticks);
+        assert_eq!(regular.regularity, 1.0);
+        assert_eq!(regular.duration_variance, 0.0);
+        assert_eq!(regular.rhythm_patterns, ["Regular"]);
+
+        // Repeated chords merge into one change: 4, 2, 1, 1 beats
+        let accelerating = analyzer
+            .analyze_harmonic_rhythm(&timed(&[(1, 2.0), (1, 2.0), (4, 2.0), (5, 1.0), (1, 1.0)]))
+            .unwrap();
+        assert_eq!(accelerating.avg_chord_duration, 2.0 * ticks);
+        assert_eq!(accelerating.trend, RhythmTrend::Accelerating);
+        assert!(accelerating.regularity < 1.0);
+        assert_eq!(accelerating.rhythm_patterns, ["Accelerating"]);
+
+        let cadence = analyzer
+            .analyze_harmonic_rhythm(&timed(&[(1, 1.0), (4, 1.0), (5, 1.0), (6, 1.0), (1, 4.0)]))
+            .unwrap();
+        assert_eq!(cadence.rhythm_patterns, ["Cadential lengthening"]);
+        assert_eq!(cadence.trend, RhythmTrend::Steady);
+
+        assert!(analyzer.analyze_harmonic_rhythm(&[]).is_err());
+        assert!(analyzer
+            .analyze_harmonic_rhythm(&timed(&[(1, -1.0)]))
+            .is_err());
+
+        let untimed = analyzer
+            .analyze_progression(&[Chord::new(1, 5).unwrap(), Chord::new(5, 5).unwrap()])
+            .unwrap();
+        assert_eq!(untimed.harmonic_rhythm.avg_chord_duration, ticks);
+        assert_eq!(untimed.harmonic_rhythm.rhythm_patterns, ["Regular"]);
+    }
+
+    #[test]
+    fn test_fit_polynomial_model() {
+        let truth = PolynomialModel {
+            coefficients: [0.0, 0.0, 0.9, 0.05],
+            feature_weights: [
+                ("harmonic_complexity", 0.5),
+                ("rhythmic_complexity", 0.1),
+                ("technical_complexity", 0.4),
+                ("melodic_complexity", 0.0),
+            ]
+            .iter()
+            .map(|(name, weight)| (name.to_string(), *weight))
+            .collect(),
+            accuracy: 1.0,
+        };
+        let factors = ComplexityFactors::default();
+        let examples: Vec<(DifficultyFeatures, f64)> = (0..20)
+            .map(|i| {
+                let features = DifficultyFeatures::new(
+                    (i % 7) as f64 * 1.4,
+                    (i % 3) as f64 * 2.0,
+                    (i % 5) as f64 * 1.8,
+                    2.0,
+                    &factors,
+                );
+                (features, truth.predict(&features))
+            })
+            .collect();
+
+        let model = PolynomialModel::fit(&examples).unwrap();
+        assert!(model.accuracy > 0.999);
+        for (features, target) in &examples {
+            assert!((model.predict(features) - target).abs() < 1e-3);
+        }
+        assert!(model.feature_weights["melodic_complexity"].abs() < 1e-6);
+        assert_eq!(
+            PolynomialModel::from_json(&truth.to_json().unwrap()).unwrap(),
+            truth
+        );
+
+        assert!(PolynomialModel::fit(&examples[..4]).is_err());
+        let mut out_of_range = examples.clone();
+        out_of_range[0].1 = 11.0;
+        assert!(PolynomialModel::fit(&out_of_range).is_err());
+        let identical = vec![examples[0]; 6];
+        assert!(PolynomialModel::fit(&identical).is_err());
+        assert!(PolynomialModel::from_json(
+            r#"{"coefficients":[0,0,0,0],"feature_weights":{"tempo":1.0},"accuracy":0.5}"#
+        )
+        .is_err());
+
+        let trie = Arc::new(ChordProgressionTrie::new());
+        let analyzer = MusicalAnalyzer::new(trie);
+        let progression = vec![Chord::new(1, 5).unwrap(), Chord::new(5, 7).unwrap()];
+        let before = analyzer
+            .assess_difficulty(&progression, None, None)
+            .unwrap();
+        let constant = PolynomialModel {
+            coefficients: [0.0, 0.0, 0.0, 0.9],
+            ..PolynomialModel::default()
+        };
+        analyzer.set_polynomial_model(constant.clone()).unwrap();
+        let after = analyzer
+            .assess_difficulty(&progression, None, None)
+            .unwrap();
+        assert_ne!(before.overall_score, after.overall_score);
+        assert!((after.overall_score - 9.0).abs() < 1e-9);
+        assert_eq!(analyzer.difficulty_model(), constant);
+    }
+
     #[test]
     fn test_chord_complexity_calculation() {
         let trie = Arc::new(ChordProgressionTrie::new());
@@ -934,6 +2698,38 @@ mod tests {
         assert!(jumpy_complexity >= smooth_complexity);
     }
 
+    #[test]
+    fn test_per_chord_breakdown() {
+        let trie = Arc::new(ChordProgressionTrie::new());
+        let common = [Chord::new(1, 5).unwrap(), Chord::new(5, 5).unwrap()];
+        for i in 0..COMMON_TRANSITION_COUNT {
+            trie.add_pattern(&common, format!("common-{}", i), None)
+                .unwrap();
+        }
+        let analyzer = MusicalAnalyzer::new(trie);
+
+        let progression = [
+            Chord::new(1, 5).unwrap(),
+            Chord::new(5, 5).unwrap(),
+            Chord::new(2, 9).unwrap(),
+        ];
+        let assessment = analyzer
+            .assess_difficulty(&progression, None, None)
+            .unwrap();
+        let breakdown = &assessment.per_chord_breakdown;
+        // One entry per chord, even below the evidence minimum
+        assert_eq!(breakdown.len(), 3);
+        assert_eq!(breakdown[0].voice_leading_cost, 0.0);
+        assert_eq!(breakdown[0].novelty, 0.0);
+        assert_eq!(breakdown[1].novelty, 0.0);
+        assert_eq!(breakdown[2].novelty, 1.0);
+        assert!(breakdown[2].complexity > breakdown[1].complexity);
+        assert_eq!(
+            breakdown[2].voice_leading_cost,
+            analyzer.voice_leading_cost(&progression[1], &progression[2])
+        );
+    }
+
     #[test]
     fn test_extended_harmony_detection() {
         let trie = Arc::new(ChordProgressionTrie::new());
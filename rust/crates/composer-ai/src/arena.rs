@@ -0,0 +1,605 @@
+//! Arena storage for pattern trees
+//!
+//! Every node of a tree lives in one `Vec` and links to its children by `u32`
+//! index: a node points at its first child, and each child at its next sibling
+//! in key order. Source references are stored once per tree and listed on the
+//! nodes by index, so a node costs a fixed few dozen bytes and allocates only
+//! when more than four sources pass through it. [`TrieArena::compact`] releases
+//! spare capacity and lays the nodes out depth-first, so a search reads them
+//! front to back.
+//!
+//! Nodes and references are held in fixed-size chunks behind `Arc`s, so
+//! cloning an arena copies one pointer per chunk. The clones share their
+//! chunks until one of them writes, which copies only the chunk written to.
+
+use crate::error::{AiError, AiResult};
+use crate::trie::Reference;
+use ahash::AHashMap;
+use composer_serialization::{fast_hash, fold_hash, ChordBinary};
+use smallvec::SmallVec;
+use std::mem::size_of;
+use std::sync::Arc;
+
+/// Index of a node in its [`TrieArena`]
+pub type NodeId = u32;
+
+/// Index of a source reference in its [`TrieArena`]
+pub type ReferenceId = u32;
+
+/// Id of the root node. It also marks a missing child or sibling, since the
+/// root is never either.
+pub const ROOT_NODE: NodeId = 0;
+
+/// Reference ids by hash of the reference, for interning
+type ReferenceIndex = AHashMap<u32, SmallVec<[ReferenceId; 1]>>;
+
+/// Deepest tree accepted from the binary format, one level per pattern chord
+const MAX_BINARY_DEPTH: usize = 20;
+
+/// Items per storage chunk, the unit copied when a shared arena is written
+const CHUNK_LEN: usize = 256;
+
+/// Append-only storage in chunks shared copy-on-write between clones
+#[derive(Debug, Clone)]
+struct Chunked<T> {
+    chunks: Vec<Arc<Vec<T>>>,
+    len: usize,
+}
+
+impl<T: Clone> Chunked<T> {
+    fn new() -> Self {
+        Self {
+            chunks: Vec::new(),
+            len: 0,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, index: usize) -> &T {
+        &self.chunks[index / CHUNK_LEN][index % CHUNK_LEN]
+    }
+
+    /// Item at `index`, copying its chunk first if it is shared
+    fn get_mut(&mut self, index: usize) -> &mut T {
+        &mut Arc::make_mut(&mut self.chunks[index / CHUNK_LEN])[index % CHUNK_LEN]
+    }
+
+    fn push(&mut self, value: T) {
+        if self.len % CHUNK_LEN == 0 {
+            self.chunks.push(Arc::new(Vec::with_capacity(CHUNK_LEN)));
+        }
+        if let Some(chunk) = self.chunks.last_mut() {
+            Arc::make_mut(chunk).push(value);
+        }
+        self.len += 1;
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.chunks.iter().flat_map(|chunk| chunk.iter())
+    }
+
+    /// Items in order, copying every shared chunk
+    fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> + '_ {
+        self.chunks
+            .iter_mut()
+            .flat_map(|chunk| Arc::make_mut(chunk).iter_mut())
+    }
+
+    /// Allocated bytes, counting shared chunks in full
+    fn allocated_bytes(&self) -> usize {
+        self.chunks.capacity() * size_of::<Arc<Vec<T>>>()
+            + self
+                .chunks
+                .iter()
+                .map(|chunk| size_of::<Vec<T>>() + chunk.capacity() * size_of::<T>())
+                .sum::<usize>()
+    }
+
+    fn shrink_to_fit(&mut self) {
+        if let Some(chunk) = self.chunks.last_mut() {
+            Arc::make_mut(chunk).shrink_to_fit();
+        }
+        self.chunks.shrink_to_fit();
+    }
+
+    /// Items in chunks stored at the same address in `other`
+    #[cfg(test)]
+    fn shared_len(&self, other: &Self) -> usize {
+        self.chunks
+            .iter()
+            .zip(&other.chunks)
+            .filter(|(a, b)| Arc::ptr_eq(a, b))
+            .map(|(chunk, _)| chunk.len())
+            .sum()
+    }
+}
+
+/// Trie node for chord progression storage
+#[derive(Debug, Clone, Default)]
+pub struct TrieNode {
+    /// Serialized chord leading to this node from its parent (zeroes at the root)
+    pub key: ChordBinary,
+
+    /// Occurrence frequency at this node
+    pub count: u32,
+
+    /// Occurrence frequency weighted by chord duration and pattern weight;
+    /// equals `count` for patterns added without either
+    pub duration_weight: f64,
+
+    /// Total duration in beats of the occurrences added with timing
+    pub beats: f64,
+
+    /// Occurrences added with timing
+    pub timed_count: u32,
+
+    /// Popularity ranking among siblings (1-based, calculated)
+    pub rank: u32,
+
+    /// Sources through this node, as indices into the arena's references
+    pub references: SmallVec<[ReferenceId; 4]>,
+
+    /// First child in key order, or [`ROOT_NODE`] for none
+    first_child: NodeId,
+
+    /// Next sibling in key order, or [`ROOT_NODE`] for none
+    next_sibling: NodeId,
+}
+
+/// Pattern tree whose nodes and references are stored in shared chunks.
+///
+/// `clone` is cheap: the clone shares storage with the original, and each
+/// side copies a chunk of 256 nodes or references on first writing to it.
+#[derive(Debug, Clone)]
+pub struct TrieArena {
+    /// Nodes, the root first
+    nodes: Chunked<TrieNode>,
+
+    /// Distinct source references listed on the nodes
+    references: Chunked<Reference>,
+
+    /// Reference ids by hash of the reference, for interning; copied whole
+    /// on the first new reference after a clone
+    reference_index: Arc<ReferenceIndex>,
+
+    /// Whether to include key tonics in references
+    include_key_tonic: bool,
+}
+
+/// Children of a node in key order, from [`TrieArena::children`]
+#[derive(Debug, Clone)]
+pub struct Children<'a> {
+    arena: &'a TrieArena,
+    next: NodeId,
+}
+
+impl<'a> Iterator for Children<'a> {
+    type Item = (NodeId, &'a TrieNode);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == ROOT_NODE {
+            return None;
+        }
+        let id = self.next;
+        let node = self.arena.node(id);
+        self.next = node.next_sibling;
+        Some((id, node))
+    }
+}
+
+impl Default for TrieArena {
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+impl TrieArena {
+    /// Create a tree holding only the root
+    pub fn new(include_key_tonic: bool) -> Self {
+        let mut nodes = Chunked::new();
+        nodes.push(TrieNode::default());
+        Self {
+            nodes,
+            references: Chunked::new(),
+            reference_index: Arc::new(AHashMap::new()),
+            include_key_tonic,
+        }
+    }
+
+    /// Whether to include key tonics in references
+    pub fn include_key_tonic(&self) -> bool {
+        self.include_key_tonic
+    }
+
+    /// Number of nodes, the root included
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Root node
+    pub fn root(&self) -> &TrieNode {
+        self.node(ROOT_NODE)
+    }
+
+    /// Node by id
+    pub fn node(&self, id: NodeId) -> &TrieNode {
+        self.nodes.get(id as usize)
+    }
+
+    pub(crate) fn node_mut(&mut self, id: NodeId) -> &mut TrieNode {
+        self.nodes.get_mut(id as usize)
+    }
+
+    /// Number of nodes whose storage is shared with `other`, a clone of this
+    /// arena or of one it was cloned from
+    #[cfg(test)]
+    pub fn shared_nodes(&self, other: &TrieArena) -> usize {
+        self.nodes.shared_len(&other.nodes)
+    }
+
+    /// Children of a node in key order
+    pub fn children(&self, id: NodeId) -> Children<'_> {
+        Children {
+            arena: self,
+            next: self.node(id).first_child,
+        }
+    }
+
+    /// Child of a node under `key`
+    pub fn child(&self, id: NodeId, key: &ChordBinary) -> Option<NodeId> {
+        for (child_id, child) in self.children(id) {
+            match child.key.cmp(key) {
+                std::cmp::Ordering::Less => continue,
+                std::cmp::Ordering::Equal => return Some(child_id),
+                std::cmp::Ordering::Greater => return None,
+            }
+        }
+        None
+    }
+
+    /// Node reached from the root by following `keys`
+    pub fn path(&self, keys: &[ChordBinary]) -> Option<NodeId> {
+        keys.iter()
+            .try_fold(ROOT_NODE, |node, key| self.child(node, key))
+    }
+
+    /// Sources through a node
+    pub fn references(&self, id: NodeId) -> impl Iterator<Item = &Reference> + '_ {
+        self.node(id)
+            .references
+            .iter()
+            .map(|&reference| self.references.get(reference as usize))
+    }
+
+    /// Every stored source reference, in [`ReferenceId`] order
+    pub fn reference_table(&self) -> impl Iterator<Item = &Reference> + '_ {
+        self.references.iter()
+    }
+
+    /// Whether a reference with `source_id` passes through a node
+    pub fn has_reference(&self, id: NodeId, source_id: &str) -> bool {
+        self.references(id).any(|r| r.id == source_id)
+    }
+
+    /// Child of `parent` under `key`, created if missing
+    pub(crate) fn child_or_insert(&mut self, parent: NodeId, key: ChordBinary) -> AiResult<NodeId> {
+        let mut previous = ROOT_NODE;
+        let mut next = self.node(parent).first_child;
+        while next != ROOT_NODE {
+            let sibling = self.node(next);
+            if sibling.key == key {
+                return Ok(next);
+            }
+            if sibling.key > key {
+                break;
+            }
+            previous = next;
+            next = sibling.next_sibling;
+        }
+
+        let id = NodeId::try_from(self.nodes.len()).map_err(|_| AiError::CacheFull {
+            cache_name: "trie arena".to_string(),
+        })?;
+        self.nodes.push(TrieNode {
+            key,
+            next_sibling: next,
+            ..TrieNode::default()
+        });
+        if previous == ROOT_NODE {
+            self.node_mut(parent).first_child = id;
+        } else {
+            self.node_mut(previous).next_sibling = id;
+        }
+        Ok(id)
+    }
+
+    /// List `reference` on a node unless its source id is listed already
+    pub(crate) fn add_reference(&mut self, id: NodeId, reference: &Reference) -> AiResult<()> {
+        if !self.has_reference(id, &reference.id) {
+            let reference = self.intern(reference)?;
+            self.node_mut(id).references.push(reference);
+        }
+        Ok(())
+    }
+
+    /// Id of a stored reference equal to `reference`, storing it if new
+    fn intern(&mut self, reference: &Reference) -> AiResult<ReferenceId> {
+        let key = reference
+            .key_tonic
+            .map(|key| key.to_string())
+            .unwrap_or_default();
+        let hash = fold_hash(fast_hash(&reference.id), &key);
+        if let Some(ids) = self.reference_index.get(&hash) {
+            if let Some(&id) = ids
+                .iter()
+                .find(|&&id| self.references.get(id as usize) == reference)
+            {
+                return Ok(id);
+            }
+        }
+
+        let id = ReferenceId::try_from(self.references.len()).map_err(|_| AiError::CacheFull {
+            cache_name: "trie references".to_string(),
+        })?;
+        self.references.push(reference.clone());
+        Arc::make_mut(&mut self.reference_index)
+            .entry(hash)
+            .or_default()
+            .push(id);
+        Ok(id)
+    }
+
+    /// Get memory usage in bytes
+    pub fn memory_usage(&self) -> u64 {
+        let nodes = self.nodes.allocated_bytes();
+        let spilled: usize = self
+            .nodes
+            .iter()
+            .filter(|node| node.references.spilled())
+            .map(|node| node.references.capacity() * size_of::<ReferenceId>())
+            .sum();
+        let references = self.references.allocated_bytes()
+            + self
+                .references
+                .iter()
+                .map(|r| r.id.capacity())
+                .sum::<usize>();
+        let index = self.reference_index.capacity()
+            * (size_of::<u32>() + size_of::<SmallVec<[ReferenceId; 1]>>());
+
+        (size_of::<Self>() + nodes + spilled + references + index) as u64
+    }
+
+    /// Release spare capacity and store the nodes depth-first with siblings
+    /// in key order.
+    ///
+    /// Node ids change; contents, ranks and search results do not. The
+    /// nodes are rewritten, so none stay shared with clones.
+    pub fn compact(&mut self) {
+        fn copy(old: &Chunked<TrieNode>, id: NodeId, new: &mut Chunked<TrieNode>) -> NodeId {
+            let new_id = new.len() as NodeId;
+            let mut node = old.get(id as usize).clone();
+            node.first_child = ROOT_NODE;
+            node.next_sibling = ROOT_NODE;
+            node.references.shrink_to_fit();
+            new.push(node);
+
+            let mut previous = ROOT_NODE;
+            let mut child = old.get(id as usize).first_child;
+            while child != ROOT_NODE {
+                let copied = copy(old, child, new);
+                if previous == ROOT_NODE {
+                    new.get_mut(new_id as usize).first_child = copied;
+                } else {
+                    new.get_mut(previous as usize).next_sibling = copied;
+                }
+                previous = copied;
+                child = old.get(child as usize).next_sibling;
+            }
+            new_id
+        }
+
+        let mut nodes = Chunked::new();
+        copy(&self.nodes, ROOT_NODE, &mut nodes);
+        nodes.shrink_to_fit();
+        self.nodes = nodes;
+
+        for reference in self.references.iter_mut() {
+            reference.id.shrink_to_fit();
+        }
+        self.references.shrink_to_fit();
+        let reference_index = Arc::make_mut(&mut self.reference_index);
+        for ids in reference_index.values_mut() {
+            ids.shrink_to_fit();
+        }
+        reference_index.shrink_to_fit();
+    }
+
+    /// Build a tree from the `composer_serialization` trie format.
+    ///
+    /// Source ids become their decimal string form; ranks are left for
+    /// [`TrieArena::calculate_ranks`]. The binary format stores plain counts,
+    /// so duration weights are restored as the counts.
+    pub fn from_binary_node(binary: &composer_serialization::TrieNode) -> AiResult<Self> {
+        Self::convert_binary_node(binary, &|source| {
+            Ok(Reference {
+                id: source.to_string(),
+                key_tonic: None,
+            })
+        })
+    }
+
+    /// Build a tree from the `composer_serialization` trie format whose ids
+    /// index `references`, as read with its reference table.
+    ///
+    /// Source ids and keys are restored from the table; ranks and duration
+    /// weights are restored as by [`TrieArena::from_binary_node`].
+    pub fn from_referenced_binary_node(
+        binary: &composer_serialization::TrieNode,
+        references: &[composer_serialization::TrieReference],
+    ) -> AiResult<Self> {
+        Self::convert_binary_node(binary, &|source| {
+            let reference =
+                references
+                    .get(source as usize)
+                    .ok_or_else(|| AiError::DataCorruption {
+                        details: format!("Reference {} is not in the reference table", source),
+                    })?;
+            Ok(Reference::new(reference.id.clone(), reference.key))
+        })
+    }
+
+    fn convert_binary_node(
+        binary: &composer_serialization::TrieNode,
+        reference: &dyn Fn(u32) -> AiResult<Reference>,
+    ) -> AiResult<Self> {
+        fn convert(
+            arena: &mut TrieArena,
+            id: NodeId,
+            binary: &composer_serialization::TrieNode,
+            depth: usize,
+            reference: &dyn Fn(u32) -> AiResult<Reference>,
+        ) -> AiResult<()> {
+            if depth > MAX_BINARY_DEPTH {
+                return Err(AiError::DataCorruption {
+                    details: format!("Trie depth exceeds maximum pattern length at {}", depth),
+                });
+            }
+
+            let node = arena.node_mut(id);
+            node.count = binary.node_count;
+            node.duration_weight = f64::from(binary.node_count);
+            for &source in &binary.id_list {
+                let reference = arena.intern(&reference(source)?)?;
+                arena.node_mut(id).references.push(reference);
+            }
+
+            for (key, child) in &binary.children {
+                let key: ChordBinary =
+                    key.as_slice()
+                        .try_into()
+                        .map_err(|_| AiError::DataCorruption {
+                            details: format!("Chord key must be 5 bytes, got {}", key.len()),
+                        })?;
+                let child_id = arena.child_or_insert(id, key)?;
+                convert(arena, child_id, child, depth + 1, reference)?;
+            }
+            Ok(())
+        }
+
+        let mut arena = Self::new(false);
+        convert(&mut arena, ROOT_NODE, binary, 0, reference)?;
+        Ok(arena)
+    }
+
+    /// Rank the children of every node by duration-weighted count
+    pub fn calculate_ranks(&mut self) {
+        let mut ranked: Vec<(NodeId, f64)> = Vec::new();
+        for id in 0..self.nodes.len() as NodeId {
+            ranked.clear();
+            ranked.extend(
+                self.children(id)
+                    .map(|(child, node)| (child, node.duration_weight)),
+            );
+            ranked.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+            for (rank, (child, _)) in ranked.iter().enumerate() {
+                self.node_mut(*child).rank = (rank + 1) as u32;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference(id: &str) -> Reference {
+        Reference {
+            id: id.to_string(),
+            key_tonic: None,
+        }
+    }
+
+    #[test]
+    fn test_children_stay_in_key_order() {
+        let mut arena = TrieArena::new(false);
+        for key in [
+            [3, 0, 0, 0, 0],
+            [1, 0, 0, 0, 0],
+            [2, 0, 0, 0, 0],
+            [1, 0, 0, 0, 0],
+        ] {
+            arena.child_or_insert(ROOT_NODE, key).unwrap();
+        }
+        let keys: Vec<u8> = arena.children(ROOT_NODE).map(|(_, n)| n.key[0]).collect();
+        assert_eq!(keys, [1, 2, 3]);
+        assert_eq!(arena.node_count(), 4);
+        assert_eq!(arena.child(ROOT_NODE, &[2, 0, 0, 0, 0]), Some(3));
+        assert_eq!(arena.child(ROOT_NODE, &[4, 0, 0, 0, 0]), None);
+    }
+
+    #[test]
+    fn test_references_are_interned() {
+        let mut arena = TrieArena::new(false);
+        let first = arena.child_or_insert(ROOT_NODE, [1; 5]).unwrap();
+        let second = arena.child_or_insert(first, [2; 5]).unwrap();
+        for id in [ROOT_NODE, first, second] {
+            arena.add_reference(id, &reference("song")).unwrap();
+            arena.add_reference(id, &reference("song")).unwrap();
+        }
+        assert_eq!(arena.references.len(), 1);
+        assert_eq!(arena.node(second).references.len(), 1);
+        assert!(arena.has_reference(second, "song"));
+        assert!(!arena.has_reference(second, "other"));
+    }
+
+    #[test]
+    fn test_compact_keeps_contents() {
+        let mut arena = TrieArena::new(false);
+        for pattern in [[[5; 5], [1; 5]], [[1; 5], [4; 5]], [[1; 5], [5; 5]]] {
+            let mut node = ROOT_NODE;
+            for key in pattern {
+                node = arena.child_or_insert(node, key).unwrap();
+                arena.node_mut(node).count += 1;
+                arena.add_reference(node, &reference("song")).unwrap();
+            }
+        }
+        let before = arena.memory_usage();
+        let tonic = arena.path(&[[1; 5], [5; 5]]).unwrap();
+
+        arena.compact();
+        assert!(arena.memory_usage() <= before);
+        let compacted = arena.path(&[[1; 5], [5; 5]]).unwrap();
+        assert_ne!(compacted, tonic);
+        assert_eq!(arena.node(compacted).count, 1);
+        // Depth-first: the first child directly follows its parent
+        assert_eq!(arena.path(&[[1; 5]]), Some(1));
+        assert_eq!(arena.path(&[[1; 5], [4; 5]]), Some(2));
+        assert_eq!(arena.node_count(), 6);
+    }
+
+    #[test]
+    fn test_clones_copy_written_chunks_only() {
+        let mut base = TrieArena::new(false);
+        let mut parent = ROOT_NODE;
+        for depth in 0..(CHUNK_LEN * 3) as u32 {
+            parent = base.child_or_insert(parent, [depth as u8; 5]).unwrap();
+            base.add_reference(parent, &reference("base")).unwrap();
+        }
+        let mut fork = base.clone();
+        assert_eq!(fork.shared_nodes(&base), base.node_count());
+
+        // Writing under the root copies its chunk and the new node's chunk
+        let added = fork.child_or_insert(ROOT_NODE, [255; 5]).unwrap();
+        fork.add_reference(added, &reference("session")).unwrap();
+        assert_eq!(fork.shared_nodes(&base), CHUNK_LEN * 2);
+        assert!(fork.path(&[[255; 5]]).is_some());
+        assert!(base.path(&[[255; 5]]).is_none());
+        assert!(base.path(&[[0; 5], [1; 5]]).is_some());
+        assert_eq!(base.references.len(), 1);
+        assert_eq!(fork.references.len(), 2);
+    }
+}
@@ -0,0 +1,342 @@
+//! Training corpus augmentation
+//!
+//! A corpus rarely covers every key, voicing, chord color and tempo a model
+//! will be asked about. [`augment_patterns`] multiplies a set of
+//! [`TrainingPattern`]s with musically equivalent variants before they are fed
+//! to a trie or a model. Chords are scale degrees, so transposing a pattern
+//! changes its key and leaves the chords alone; inversions, extensions and
+//! time stretches rewrite the chords themselves.
+
+use crate::engine::TrainingPattern;
+use crate::error::{AiError, AiResult};
+use composer_core::Chord;
+#[cfg(doc)]
+use composer_core::Key;
+use serde::{Deserialize, Serialize};
+
+/// Chords of a pattern variant and the tags naming its transformations
+type Variant = (Vec<Chord>, Vec<String>);
+
+/// Variants generated for each training pattern
+///
+/// Enabled transformations compound: every voicing is also written with every
+/// extension variant, at every tempo, in every key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AugmentationConfig {
+    /// Copy patterns with a key into all 12 keys
+    pub transpose: bool,
+
+    /// Add copies with every chord in each other inversion it has
+    pub invert: bool,
+
+    /// Add copies with triads extended to sevenths, and with seventh and
+    /// extended chords reduced to triads
+    pub vary_extensions: bool,
+
+    /// Factors applied to chord beats and durations; patterns without timing
+    /// are not stretched
+    pub time_stretch: Vec<f64>,
+}
+
+impl Default for AugmentationConfig {
+    fn default() -> Self {
+        Self {
+            transpose: true,
+            invert: true,
+            vary_extensions: true,
+            time_stretch: vec![0.5, 2.0],
+        }
+    }
+}
+
+/// Augment a corpus with transposed, inverted, re-extended and time-stretched
+/// variants of each pattern
+///
+/// Each pattern is followed by its variants, with duplicates dropped. A
+/// variant's source identifier is the original's with the transformations
+/// appended in brackets, e.g. `song [inv1 x0.5 Eb]`, so variants stay
+/// traceable. Keys are transposed keeping their mode and spelled as in
+/// [`Key::transpose`]; patterns without a key are not transposed.
+/// Fails with `AiError::InvalidPattern` for a stretch factor that is not a
+/// positive number.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::{augment_patterns, AugmentationConfig};
+/// use composer_core::Chord;
+///
+/// let corpus = vec![(vec![Chord::triad(1)?, Chord::seventh(5)?], "song".to_string(), Some("C".parse()?))];
+///
+/// let keys_only = AugmentationConfig { invert: false, vary_extensions: false, time_stretch: vec![], ..Default::default() };
+/// let augmented = augment_patterns(corpus.clone(), &keys_only)?;
+/// assert_eq!(augmented.len(), 12);
+/// assert_eq!(augmented[3].1, "song [Eb]");
+/// assert_eq!(augmented[3].2, Some("Eb".parse()?));
+///
+/// // Inversions and extension variants compound with the keys
+/// assert!(augment_patterns(corpus, &AugmentationConfig::default())?.len() > 12);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`crate::AiEngine::initialize`] - Train on the augmented corpus
+/// - [`crate::NGramModel::add_patterns_bulk`] - Train an n-gram model on it
+pub fn augment_patterns(
+    patterns: impl IntoIterator<Item = TrainingPattern>,
+    config: &AugmentationConfig,
+) -> AiResult<Vec<TrainingPattern>> {
+    if let Some(factor) = config
+        .time_stretch
+        .iter()
+        .find(|factor| !factor.is_finite() || **factor <= 0.0)
+    {
+        return Err(AiError::InvalidPattern {
+            reason: format!("Time stretch factor {} must be positive", factor),
+        });
+    }
+
+    let mut augmented = Vec::new();
+    for (chords, source_id, key_tonic) in patterns {
+        let mut variants: Vec<Variant> = vec![(chords.clone(), Vec::new())];
+        if config.invert {
+            for inversion in 1..=3 {
+                let inverted: Vec<Chord> = chords
+                    .iter()
+                    .map(|chord| invert(chord, inversion))
+                    .collect();
+                variants.push((inverted, vec![format!("inv{}", inversion)]));
+            }
+        }
+        if config.vary_extensions {
+            variants = variants
+                .into_iter()
+                .flat_map(|(chords, tags)| {
+                    let extended = chords.iter().map(extend).collect();
+                    let reduced = chords.iter().map(reduce).collect();
+                    let tagged = |tag: &str| [tags.clone(), vec![tag.to_string()]].concat();
+                    let (extended_tags, reduced_tags) = (tagged("ext"), tagged("red"));
+                    [
+                        (chords, tags),
+                        (extended, extended_tags),
+                        (reduced, reduced_tags),
+                    ]
+                })
+                .collect();
+        }
+        if !config.time_stretch.is_empty() {
+            variants = variants
+                .into_iter()
+                .flat_map(|(chords, tags)| {
+                    let mut stretched: Vec<_> = config
+                        .time_stretch
+                        .iter()
+                        .map(|&factor| {
+                            (
+                                chords.iter().map(|chord| stretch(chord, factor)).collect(),
+                                [tags.clone(), vec![format!("x{}", factor)]].concat(),
+                            )
+                        })
+                        .collect();
+                    stretched.insert(0, (chords, tags));
+                    stretched
+                })
+                .collect();
+        }
+
+        // Drop variants a transformation left unchanged, keeping the first
+        let mut distinct: Vec<Variant> = Vec::new();
+        for (chords, tags) in variants {
+            if !distinct.iter().any(|(seen, _)| *seen == chords) {
+                distinct.push((chords, tags));
+            }
+        }
+
+        let keys = match key_tonic {
+            Some(key) if config.transpose => (0..12)
+                .map(|step| {
+                    let transposed = if step == 0 { key } else { key.transpose(step) };
+                    let tag = (step > 0).then(|| transposed.tonic.to_string());
+                    (Some(transposed), tag)
+                })
+                .collect(),
+            _ => vec![(key_tonic, None)],
+        };
+
+        for (key, key_tag) in &keys {
+            for (chords, tags) in &distinct {
+                let tags: Vec<&str> = tags
+                    .iter()
+                    .map(String::as_str)
+                    .chain(key_tag.as_deref())
+                    .collect();
+                let source = if tags.is_empty() {
+                    source_id.clone()
+                } else {
+                    format!("{} [{}]", source_id, tags.join(" "))
+                };
+                augmented.push((chords.clone(), source, *key));
+            }
+        }
+    }
+    Ok(augmented)
+}
+
+/// The chord in `inversion`, if it has that many; rests and chords with fewer
+/// tones are unchanged
+fn invert(chord: &Chord, inversion: u8) -> Chord {
+    let tones = if chord.is_triad() { 3 } else { 4 };
+    if chord.is_rest || inversion >= tones {
+        return chord.clone();
+    }
+    chord
+        .clone()
+        .with_inversion(inversion)
+        .unwrap_or_else(|_| chord.clone())
+}
+
+/// Triads as seventh chords; other chords are unchanged
+fn extend(chord: &Chord) -> Chord {
+    if chord.is_rest || !chord.is_triad() {
+        return chord.clone();
+    }
+    let mut extended = chord.clone();
+    extended.chord_type = 7;
+    extended
+}
+
+/// Seventh and extended chords as triads, dropping a third inversion; other
+/// chords are unchanged
+fn reduce(chord: &Chord) -> Chord {
+    if chord.is_rest || chord.is_triad() || chord.chord_type < 7 {
+        return chord.clone();
+    }
+    let mut reduced = chord.clone();
+    reduced.chord_type = 5;
+    if reduced.inversion > 2 {
+        reduced.inversion = 0;
+    }
+    reduced
+}
+
+/// The chord with its beat and duration scaled by `factor`
+fn stretch(chord: &Chord, factor: f64) -> Chord {
+    let mut stretched = chord.clone();
+    stretched.beat = chord.beat.map(|beat| beat * factor);
+    stretched.duration = chord.duration.map(|duration| duration * factor);
+    stretched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(key: Option<&str>) -> TrainingPattern {
+        (
+            vec![
+                Chord::triad(2).unwrap(),
+                Chord::seventh(5).unwrap(),
+                Chord::triad(1).unwrap(),
+            ],
+            "tune".to_string(),
+            key.map(|key| key.parse().unwrap()),
+        )
+    }
+
+    #[test]
+    fn test_transposes_key_tonics() {
+        let config = AugmentationConfig {
+            invert: false,
+            vary_extensions: false,
+            time_stretch: Vec::new(),
+            ..Default::default()
+        };
+        let augmented = augment_patterns(vec![pattern(Some("Bbm"))], &config).unwrap();
+        let keys: Vec<_> = augmented.iter().map(|p| p.2.unwrap().to_string()).collect();
+        assert_eq!(keys.len(), 12);
+        assert_eq!(&keys[..4], ["Bbm", "Bm", "Cm", "C#m"]);
+        assert_eq!(augmented[0].1, "tune");
+        assert_eq!(augmented[2].1, "tune [C]");
+        assert!(augmented.iter().all(|p| p.0 == pattern(None).0));
+
+        // Modes are kept, and each key is spelled with the simpler signature
+        let augmented = augment_patterns(vec![pattern(Some("D dorian"))], &config).unwrap();
+        assert_eq!(augmented[1].2, Some("Eb dorian".parse().unwrap()));
+
+        // Without a key the pattern is kept as is
+        let augmented = augment_patterns(vec![pattern(None)], &config).unwrap();
+        assert_eq!(augmented, vec![pattern(None)]);
+    }
+
+    #[test]
+    fn test_voicing_and_extension_variants() {
+        let config = AugmentationConfig {
+            transpose: false,
+            time_stretch: Vec::new(),
+            ..Default::default()
+        };
+        let augmented = augment_patterns(vec![pattern(None)], &config).unwrap();
+        let find = |source: &str| {
+            augmented
+                .iter()
+                .find(|p| p.1 == source)
+                .map(|p| p.0.clone())
+                .unwrap_or_else(|| panic!("no variant {}", source))
+        };
+
+        assert_eq!(find("tune"), pattern(None).0);
+        let second = find("tune [inv2]");
+        assert_eq!(
+            second.iter().map(|c| c.inversion).collect::<Vec<_>>(),
+            [2, 2, 2]
+        );
+        // Only the seventh chord has a third inversion
+        let third = find("tune [inv3]");
+        assert_eq!(
+            third.iter().map(|c| c.inversion).collect::<Vec<_>>(),
+            [0, 3, 0]
+        );
+        assert!(find("tune [ext]").iter().all(Chord::is_seventh));
+        assert!(find("tune [red]").iter().all(Chord::is_triad));
+        // Reducing a third inversion drops back to root position, duplicating "red"
+        assert!(augmented.iter().all(|p| p.1 != "tune [inv3 red]"));
+        let third_inversion = Chord::seventh(5).unwrap().with_inversion(3).unwrap();
+        assert_eq!(reduce(&third_inversion).inversion, 0);
+
+        let sources: std::collections::HashSet<_> = augmented.iter().map(|p| &p.1).collect();
+        assert_eq!(sources.len(), augmented.len());
+    }
+
+    #[test]
+    fn test_time_stretch() {
+        let timed = (
+            vec![
+                Chord::triad(1).unwrap().with_timing(0.0, 2.0),
+                Chord::triad(5).unwrap().with_timing(2.0, 2.0),
+            ],
+            "timed".to_string(),
+            None,
+        );
+        let config = AugmentationConfig {
+            transpose: false,
+            invert: false,
+            vary_extensions: false,
+            time_stretch: vec![0.5, 1.5],
+        };
+        let augmented = augment_patterns(vec![timed, pattern(None)], &config).unwrap();
+        // Untimed patterns gain no stretched copies
+        assert_eq!(augmented.len(), 4);
+        assert_eq!(augmented[2].1, "timed [x1.5]");
+        assert_eq!(augmented[2].0[1].beat, Some(3.0));
+        assert_eq!(augmented[2].0[1].duration, Some(3.0));
+
+        for factor in [0.0, f64::NAN] {
+            let config = AugmentationConfig {
+                time_stretch: vec![factor],
+                ..Default::default()
+            };
+            assert!(augment_patterns(vec![pattern(None)], &config).is_err());
+        }
+    }
+}
@@ -0,0 +1,287 @@
+//! Walking bass lines
+//!
+//! Walks a progression one note per beat as MIDI notes in the range of a
+//! four-string bass. Each chord's root falls on its downbeat, placed as close
+//! as possible to the previous root. The beats between move toward the next
+//! root through chord tones, falling back to scale tones and then chromatic
+//! passing tones where no chord tone is close to the planned path. The last
+//! beat of each chord approaches the next root chromatically from below when
+//! the line is rising and diatonically from above when it is falling.
+
+use crate::engine::BassHarmonizationOptions;
+use crate::error::{AiError, AiResult};
+use composer_config::MUSICAL;
+use composer_core::{chord_pitch_classes, Chord, ScaleFingerprint};
+use serde::{Deserialize, Serialize};
+
+/// Lowest note of a line (E1, the open low string)
+const BASS_LOWEST: u8 = 28;
+
+/// Highest note of a line (G3)
+const BASS_HIGHEST: u8 = 55;
+
+/// Root placement ties are broken toward this note (F#2)
+const BASS_CENTER: u8 = 42;
+
+/// The first root is the lowest one from this note up (A1)
+const FIRST_ROOT_FLOOR: u8 = 33;
+
+/// A passing note may lie this many semitones from the planned path
+const PATH_REACH: f64 = 2.0;
+
+/// Roots closer than this to the next one walk up to the fifth and back
+const MIN_WALK_SPAN: i16 = 3;
+
+/// Every pitch class, for chromatic passing tones
+const CHROMATIC: [u8; 12] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+
+/// Complexity from which the beat before an approach note is split into a
+/// skip, the note repeated on the offbeat
+const SKIP_COMPLEXITY: f64 = 0.7;
+
+/// Eighth-note feel of split beats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum SwingFeel {
+    /// Even eighths
+    #[default]
+    Straight,
+    /// Triplet swing, the first eighth twice as long as the second
+    Triplet,
+    /// Hard swing, the first eighth dotted
+    Dotted,
+}
+
+impl SwingFeel {
+    /// Length in ticks of the first eighth of a beat of `ticks_per_beat`
+    pub fn first_eighth(&self, ticks_per_beat: u32) -> u32 {
+        match self {
+            SwingFeel::Straight => ticks_per_beat / 2,
+            SwingFeel::Triplet => ticks_per_beat * 2 / 3,
+            SwingFeel::Dotted => ticks_per_beat * 3 / 4,
+        }
+    }
+}
+
+/// MIDI notes of a bass line and their lengths in ticks
+pub(crate) type BassLine = (Vec<u8>, Vec<u32>);
+
+/// Pitch classes available to a line, and the key they are read in
+struct LineContext {
+    /// Pitch classes of the key
+    scale_tones: Vec<u8>,
+}
+
+impl LineContext {
+    /// Note in range with one of `pitch_classes` closest to `ideal`, skipping
+    /// `exclude`; ties go to the note closer to the middle of the range
+    fn nearest(pitch_classes: &[u8], ideal: f64, exclude: &[u8]) -> Option<u8> {
+        (BASS_LOWEST..=BASS_HIGHEST)
+            .filter(|note| pitch_classes.contains(&(note % 12)) && !exclude.contains(note))
+            .min_by(|a, b| {
+                let distance = |note: u8| (note as f64 - ideal).abs();
+                distance(*a)
+                    .total_cmp(&distance(*b))
+                    .then_with(|| a.abs_diff(BASS_CENTER).cmp(&b.abs_diff(BASS_CENTER)))
+            })
+    }
+
+    /// Approach note into `target`, rising or falling into it
+    fn approach(&self, target: u8, rising: bool) -> u8 {
+        let from_above = (1..=2)
+            .map(|step| target + step)
+            .find(|note| *note <= BASS_HIGHEST && self.scale_tones.contains(&(note % 12)));
+        match from_above {
+            Some(note) if !rising || target <= BASS_LOWEST => note,
+            _ if target > BASS_LOWEST => target - 1,
+            _ => target + 1,
+        }
+    }
+
+    /// Notes of one chord's bar, from its root toward `target`
+    fn walk(&self, chord_tones: &[u8], root: u8, target: u8, beats: usize) -> Vec<u8> {
+        if beats == 1 {
+            return vec![root];
+        }
+
+        // Toward a nearby target, arch up to the fifth (or down to the fourth) first
+        let span = target as i16 - root as i16;
+        let waypoint = if span.abs() < MIN_WALK_SPAN {
+            if root + 7 <= BASS_HIGHEST {
+                root + 7
+            } else {
+                root - 5
+            }
+        } else {
+            target
+        };
+        let rising = if waypoint == target {
+            span > 0
+        } else {
+            waypoint < target
+        };
+        let approach = self.approach(target, rising);
+
+        let middle = beats - 2;
+        let mut notes = vec![root];
+        for step in 1..=middle {
+            let previous = notes[notes.len() - 1];
+            let ideal = if waypoint == target {
+                root as f64 + (approach as f64 - root as f64) * step as f64 / (middle + 1) as f64
+            } else {
+                root as f64 + (waypoint as f64 - root as f64) * step as f64 / middle as f64
+            };
+            let exclude = [previous, approach, target];
+            let note = [chord_tones, &self.scale_tones, &CHROMATIC]
+                .iter()
+                .find_map(|pitch_classes| {
+                    LineContext::nearest(pitch_classes, ideal, &exclude)
+                        .filter(|note| (*note as f64 - ideal).abs() <= PATH_REACH)
+                })
+                .unwrap_or(previous);
+            notes.push(note);
+        }
+        notes.push(approach);
+        notes
+    }
+}
+
+/// Walk a progression as MIDI notes with one bar of
+/// `options.beats_per_chord` beats per chord
+///
+/// Rest chords are skipped. The last chord walks back toward the first root,
+/// as in a repeated chorus. Fails with `AiError::SuggestionFailed` for a chord
+/// whose tones cannot be determined.
+pub(crate) fn walking_bass_line(
+    progression: &[Chord],
+    options: &BassHarmonizationOptions,
+) -> AiResult<BassLine> {
+    let scale = options
+        .scale_fingerprint
+        .unwrap_or_else(ScaleFingerprint::major_scale);
+    let transpose = |pitch_class: u8| (pitch_class + options.tonic) % 12;
+    let context = LineContext {
+        scale_tones: scale.chromatic_notes().into_iter().map(transpose).collect(),
+    };
+
+    let chord_tones: Vec<Vec<u8>> = progression
+        .iter()
+        .filter(|chord| !chord.is_rest)
+        .map(|chord| {
+            chord_pitch_classes(chord, &scale)
+                .map(|tones| tones.into_iter().map(transpose).collect())
+                .map_err(|e| AiError::SuggestionFailed {
+                    reason: format!("Cannot walk chord {:?}: {}", chord, e),
+                })
+        })
+        .collect::<AiResult<_>>()?;
+
+    // Plan every root first so each bar knows where the next one starts
+    let mut roots: Vec<u8> = Vec::with_capacity(chord_tones.len());
+    for tones in &chord_tones {
+        let root = match roots.last() {
+            Some(&previous) => LineContext::nearest(&tones[..1], previous as f64, &[]),
+            None => LineContext::nearest(&tones[..1], FIRST_ROOT_FLOOR as f64 + 5.5, &[]),
+        };
+        roots.push(root.unwrap_or(BASS_CENTER));
+    }
+
+    let ticks_per_beat = MUSICAL.ticks_per_beat as u32;
+    let first_eighth = options.swing.first_eighth(ticks_per_beat);
+    let beats = options.beats_per_chord.max(1) as usize;
+    let mut notes = Vec::new();
+    let mut rhythm = Vec::new();
+    for (index, tones) in chord_tones.iter().enumerate() {
+        let target = roots.get(index + 1).or(roots.first()).copied();
+        let bar = context.walk(tones, roots[index], target.unwrap_or(roots[index]), beats);
+        let skip = options.complexity >= SKIP_COMPLEXITY && beats >= 3;
+        for (beat, &note) in bar.iter().enumerate() {
+            if skip && beat == beats - 2 {
+                notes.extend([note, note]);
+                rhythm.extend([first_eighth, ticks_per_beat - first_eighth]);
+            } else {
+                notes.push(note);
+                rhythm.push(ticks_per_beat);
+            }
+        }
+    }
+    Ok((notes, rhythm))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn walk(progression: &[Chord], options: &BassHarmonizationOptions) -> BassLine {
+        walking_bass_line(progression, options).unwrap()
+    }
+
+    #[test]
+    fn test_walking_line() {
+        let progression = [
+            Chord::new(2, 7).unwrap(),
+            Chord::new(5, 7).unwrap(),
+            Chord::new(1, 7).unwrap(),
+        ];
+        let (notes, rhythm) = walk(&progression, &BassHarmonizationOptions::default());
+        assert_eq!(notes.len(), 12);
+        assert_eq!(rhythm, vec![MUSICAL.ticks_per_beat as u32; 12]);
+        assert!(notes
+            .iter()
+            .all(|note| (BASS_LOWEST..=BASS_HIGHEST).contains(note)));
+
+        // Roots on the downbeats: D, G, C
+        assert_eq!([notes[0] % 12, notes[4] % 12, notes[8] % 12], [2, 7, 0]);
+        // Every approach note is a step from the root that follows
+        for bar in 0..3 {
+            let next_root = notes[(bar * 4 + 4) % 12];
+            assert!(notes[bar * 4 + 3].abs_diff(next_root) <= 2);
+        }
+        // Chord tones first: F over Dm7, B over Cmaj7
+        assert_eq!(notes[1], 41);
+        assert_eq!(notes[9], 47);
+        assert!(notes.windows(2).all(|pair| pair[0] != pair[1]));
+    }
+
+    #[test]
+    fn test_approach_direction() {
+        let context = LineContext {
+            scale_tones: ScaleFingerprint::major_scale().chromatic_notes(),
+        };
+        // Rising into C3 from below, chromatically
+        assert_eq!(context.approach(48, true), 47);
+        // Falling into C3 from the scale step above
+        assert_eq!(context.approach(48, false), 50);
+        // Falling into E2 from F2, a half step that is in the key
+        assert_eq!(context.approach(40, false), 41);
+        // No room below the open string
+        assert_eq!(context.approach(BASS_LOWEST, true), 29);
+
+        // A repeated chord arches up to its fifth and falls back
+        let bar = context.walk(&[0, 4, 7], 36, 36, 4);
+        assert_eq!(bar, vec![36, 40, 43, 38]);
+    }
+
+    #[test]
+    fn test_swung_skip_and_key() {
+        let options = BassHarmonizationOptions {
+            complexity: 0.9,
+            swing: SwingFeel::Triplet,
+            tonic: 7,
+            beats_per_chord: 3,
+            ..Default::default()
+        };
+        let (notes, rhythm) = walk(
+            &[Chord::triad(1).unwrap(), Chord::triad(5).unwrap()],
+            &options,
+        );
+        // In G, the first root is G1 and the second chord is D
+        assert_eq!(notes[0], 43);
+        assert_eq!(notes[4] % 12, 2);
+        assert_eq!(notes[1], notes[2]);
+        assert_eq!(rhythm, vec![24, 16, 8, 24, 24, 16, 8, 24]);
+        assert!(walking_bass_line(&[Chord::rest()], &options)
+            .unwrap()
+            .0
+            .is_empty());
+    }
+}
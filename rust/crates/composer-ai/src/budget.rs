@@ -0,0 +1,98 @@
+//! Performance budgets for suggestion and asset loading calls
+//!
+//! A call that runs past its time limit is a budget violation. By default the
+//! call still returns its results and the violation is counted in
+//! [`crate::EngineMetrics::budget_violations`]; [`BudgetMode::Fail`] turns
+//! violations into [`AiError::PerformanceDegradation`] errors instead.
+
+use crate::error::{AiError, AiResult};
+use composer_config::PerformanceThresholds;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// What happens when a call exceeds its time limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BudgetMode {
+    /// Return the results and count the violation
+    #[default]
+    Warn,
+    /// Fail with [`AiError::PerformanceDegradation`]
+    Fail,
+}
+
+/// Time limits for engine calls, in milliseconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PerformanceBudget {
+    /// Limit for one suggestion query
+    pub chord_lookup_max_ms: u64,
+
+    /// Limit for loading a trie asset
+    pub asset_loading_max_ms: u64,
+
+    /// Limit for a suggestion call made from a UI thread, such as a browser's
+    /// main thread; checked by the bindings that run on one
+    #[serde(default = "default_ui_response_max_ms")]
+    pub ui_response_max_ms: u64,
+
+    /// What happens when a limit is exceeded
+    pub mode: BudgetMode,
+}
+
+impl Default for PerformanceBudget {
+    fn default() -> Self {
+        Self::from(&PerformanceThresholds::default())
+    }
+}
+
+fn default_ui_response_max_ms() -> u64 {
+    PerformanceThresholds::default().ui_response_max_ms as u64
+}
+
+impl From<&PerformanceThresholds> for PerformanceBudget {
+    /// Take the limits from configured thresholds, in [`BudgetMode::Warn`]
+    fn from(thresholds: &PerformanceThresholds) -> Self {
+        Self {
+            chord_lookup_max_ms: thresholds.chord_lookup_max_ms as u64,
+            asset_loading_max_ms: thresholds.asset_loading_max_ms as u64,
+            ui_response_max_ms: thresholds.ui_response_max_ms as u64,
+            mode: BudgetMode::default(),
+        }
+    }
+}
+
+impl PerformanceBudget {
+    /// Check a call that took `elapsed_ms` against `limit_ms`.
+    ///
+    /// Returns whether the limit was exceeded, or the error in
+    /// [`BudgetMode::Fail`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{BudgetMode, PerformanceBudget};
+    ///
+    /// let mut budget = PerformanceBudget::default();
+    /// assert!(!budget.check("chord_suggestion", 0.5, 1)?);
+    /// assert!(budget.check("chord_suggestion", 5.0, 1)?);
+    ///
+    /// budget.mode = BudgetMode::Fail;
+    /// assert!(budget.check("chord_suggestion", 5.0, 1).is_err());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn check(&self, operation: &str, elapsed_ms: f64, limit_ms: u64) -> AiResult<bool> {
+        if elapsed_ms <= limit_ms as f64 {
+            return Ok(false);
+        }
+        match self.mode {
+            BudgetMode::Warn => Ok(true),
+            BudgetMode::Fail => Err(AiError::PerformanceDegradation {
+                operation: operation.to_string(),
+                ms: elapsed_ms as u64,
+                limit_ms,
+            }),
+        }
+    }
+}
+
+/// Budget violation counts keyed by operation name
+pub type BudgetViolations = HashMap<String, u64>;
@@ -0,0 +1,534 @@
+//! Model/version compatibility negotiation for serialized AI assets
+//!
+//! Serialized tries and difficulty models are wrapped in a small versioned header
+//! identifying the asset kind and the model version that produced it. Before an asset
+//! is loaded, its version is negotiated against the version the engine is configured
+//! to use (`AiEngineConfig::model_versions`): compatible assets load as-is, older
+//! assets are upgraded through explicitly registered migrations, and everything else
+//! is rejected.
+
+use crate::error::{AiError, AiResult};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt;
+
+/// Magic bytes identifying a versioned AI asset
+pub const ASSET_MAGIC: [u8; 4] = *b"CMAI";
+
+/// Version of the asset header layout itself
+pub const ASSET_HEADER_VERSION: u8 = 1;
+
+/// Size of the asset header in bytes
+pub const ASSET_HEADER_LEN: usize = 12;
+
+/// Semantic model version (`vMAJOR.MINOR.PATCH`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ModelVersion {
+    pub major: u16,
+    pub minor: u16,
+    pub patch: u16,
+}
+
+impl ModelVersion {
+    /// Create a new model version
+    pub const fn new(major: u16, minor: u16, patch: u16) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parse a version string such as `"v2.35.2"` or `"2.35"`
+    pub fn parse(version: &str) -> AiResult<Self> {
+        let invalid = || AiError::InvalidModelVersion {
+            version: version.to_string(),
+        };
+
+        let trimmed = version.trim();
+        let digits = trimmed
+            .strip_prefix('v')
+            .or_else(|| trimmed.strip_prefix('V'))
+            .unwrap_or(trimmed);
+
+        let mut parts = [0u16; 3];
+        let mut count = 0;
+        for part in digits.split('.') {
+            if count == parts.len() {
+                return Err(invalid());
+            }
+            parts[count] = part.parse().map_err(|_| invalid())?;
+            count += 1;
+        }
+
+        if count < 2 {
+            return Err(invalid());
+        }
+
+        Ok(Self::new(parts[0], parts[1], parts[2]))
+    }
+
+    /// Version of the running engine, from `APPLICATION.version`
+    pub fn current() -> AiResult<Self> {
+        Self::parse(composer_config::APPLICATION.version)
+    }
+}
+
+impl fmt::Display for ModelVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Kind of serialized asset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AssetKind {
+    /// Chord progression trie (`composer_serialization::serialize_trie` payload)
+    ChordTrie,
+    /// Difficulty assessment model
+    DifficultyModel,
+}
+
+impl AssetKind {
+    /// Key of this asset's entry in `AiEngineConfig::model_versions`
+    pub fn model_key(&self) -> &'static str {
+        match self {
+            AssetKind::ChordTrie => "chord_suggestion",
+            AssetKind::DifficultyModel => "difficulty_assessment",
+        }
+    }
+
+    fn tag(&self) -> u8 {
+        match self {
+            AssetKind::ChordTrie => 1,
+            AssetKind::DifficultyModel => 2,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            1 => Some(AssetKind::ChordTrie),
+            2 => Some(AssetKind::DifficultyModel),
+            _ => None,
+        }
+    }
+}
+
+/// Header prefixed to every versioned asset
+///
+/// Layout (12 bytes, big-endian): magic (4), header version (1), asset kind (1),
+/// major (2), minor (2), patch (2).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetHeader {
+    pub kind: AssetKind,
+    pub model_version: ModelVersion,
+}
+
+impl AssetHeader {
+    /// Create a new asset header
+    pub fn new(kind: AssetKind, model_version: ModelVersion) -> Self {
+        Self {
+            kind,
+            model_version,
+        }
+    }
+
+    /// Encode the header to bytes
+    pub fn encode(&self) -> [u8; ASSET_HEADER_LEN] {
+        let mut bytes = [0u8; ASSET_HEADER_LEN];
+        bytes[0..4].copy_from_slice(&ASSET_MAGIC);
+        bytes[4] = ASSET_HEADER_VERSION;
+        bytes[5] = self.kind.tag();
+        bytes[6..8].copy_from_slice(&self.model_version.major.to_be_bytes());
+        bytes[8..10].copy_from_slice(&self.model_version.minor.to_be_bytes());
+        bytes[10..12].copy_from_slice(&self.model_version.patch.to_be_bytes());
+        bytes
+    }
+
+    /// Check whether the bytes start with a versioned asset header
+    pub fn is_present(bytes: &[u8]) -> bool {
+        bytes.len() >= ASSET_HEADER_LEN && bytes[0..4] == ASSET_MAGIC
+    }
+
+    /// Decode the header at the start of a versioned asset
+    pub fn decode(bytes: &[u8]) -> AiResult<Self> {
+        if !Self::is_present(bytes) {
+            return Err(AiError::DataCorruption {
+                details: "Missing versioned asset header".to_string(),
+            });
+        }
+
+        if bytes[4] != ASSET_HEADER_VERSION {
+            return Err(AiError::InvalidModelVersion {
+                version: format!("asset header version {}", bytes[4]),
+            });
+        }
+
+        let kind = AssetKind::from_tag(bytes[5]).ok_or_else(|| AiError::DataCorruption {
+            details: format!("Unknown asset kind tag {}", bytes[5]),
+        })?;
+
+        let read_u16 = |offset: usize| u16::from_be_bytes([bytes[offset], bytes[offset + 1]]);
+        let model_version = ModelVersion::new(read_u16(6), read_u16(8), read_u16(10));
+
+        Ok(Self::new(kind, model_version))
+    }
+}
+
+/// A versioned asset split into its header and payload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionedAsset<'a> {
+    pub header: AssetHeader,
+    pub payload: Cow<'a, [u8]>,
+}
+
+impl<'a> VersionedAsset<'a> {
+    /// Split a versioned asset into header and payload without copying
+    pub fn decode(bytes: &'a [u8]) -> AiResult<Self> {
+        let header = AssetHeader::decode(bytes)?;
+        Ok(Self {
+            header,
+            payload: Cow::Borrowed(&bytes[ASSET_HEADER_LEN..]),
+        })
+    }
+
+    /// Encode the asset back to bytes
+    pub fn encode(&self) -> Vec<u8> {
+        encode_asset(self.header.kind, self.header.model_version, &self.payload)
+    }
+}
+
+/// Wrap a serialized payload in a versioned asset header
+pub fn encode_asset(kind: AssetKind, model_version: ModelVersion, payload: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(ASSET_HEADER_LEN + payload.len());
+    bytes.extend_from_slice(&AssetHeader::new(kind, model_version).encode());
+    bytes.extend_from_slice(payload);
+    bytes
+}
+
+/// Payload upgrade function used by a migration step
+pub type MigrationFn = fn(&[u8]) -> AiResult<Vec<u8>>;
+
+/// A single explicit upgrade of an asset payload between two model versions
+#[derive(Debug, Clone, Copy)]
+pub struct AssetMigration {
+    /// Asset kind this migration applies to
+    pub kind: AssetKind,
+    /// Oldest version accepted as input (only major/minor are matched)
+    pub from: ModelVersion,
+    /// Version the migrated payload conforms to
+    pub to: ModelVersion,
+    /// Payload transformation
+    pub apply: MigrationFn,
+}
+
+/// Outcome of negotiating an asset version against the engine's required version
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Compatibility {
+    /// Versions are identical
+    Exact,
+    /// Same major and minor version; patch releases never change the payload layout
+    Compatible,
+    /// Asset is older and can be upgraded through the listed versions
+    RequiresMigration { path: Vec<ModelVersion> },
+    /// Asset cannot be loaded by this engine
+    Incompatible { reason: String },
+}
+
+impl Compatibility {
+    /// Check whether an asset with this status can be loaded
+    pub fn is_loadable(&self) -> bool {
+        !matches!(self, Compatibility::Incompatible { .. })
+    }
+}
+
+/// Ordered set of explicit asset migrations
+#[derive(Debug, Clone, Default)]
+pub struct MigrationRegistry {
+    migrations: Vec<AssetMigration>,
+}
+
+impl MigrationRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a migration step
+    pub fn register(&mut self, migration: AssetMigration) -> AiResult<()> {
+        if migration.to <= migration.from {
+            return Err(AiError::InvalidModelVersion {
+                version: format!(
+                    "migration must upgrade ({} -> {})",
+                    migration.from, migration.to
+                ),
+            });
+        }
+        self.migrations.push(migration);
+        Ok(())
+    }
+
+    /// Number of registered migrations
+    pub fn len(&self) -> usize {
+        self.migrations.len()
+    }
+
+    /// Check if no migrations are registered
+    pub fn is_empty(&self) -> bool {
+        self.migrations.is_empty()
+    }
+
+    /// Negotiate an asset version against the required engine version
+    pub fn negotiate(
+        &self,
+        kind: AssetKind,
+        asset: ModelVersion,
+        required: ModelVersion,
+    ) -> Compatibility {
+        if asset == required {
+            return Compatibility::Exact;
+        }
+
+        if asset.major == required.major && asset.minor == required.minor {
+            return Compatibility::Compatible;
+        }
+
+        if asset > required {
+            return Compatibility::Incompatible {
+                reason: format!(
+                    "asset was produced by newer model {} (engine uses {})",
+                    asset, required
+                ),
+            };
+        }
+
+        match self.find_path(kind, asset, required) {
+            Some(steps) => Compatibility::RequiresMigration {
+                path: steps.iter().map(|step| step.to).collect(),
+            },
+            None => Compatibility::Incompatible {
+                reason: format!("no migration registered from {} to {}", asset, required),
+            },
+        }
+    }
+
+    /// Apply the migration path from `asset` to `required` to a payload
+    pub fn migrate<'a>(
+        &self,
+        kind: AssetKind,
+        asset: ModelVersion,
+        required: ModelVersion,
+        payload: &'a [u8],
+    ) -> AiResult<Cow<'a, [u8]>> {
+        match self.negotiate(kind, asset, required) {
+            Compatibility::Exact | Compatibility::Compatible => Ok(Cow::Borrowed(payload)),
+            Compatibility::RequiresMigration { .. } => {
+                let steps = self.find_path(kind, asset, required).unwrap_or_default();
+                let mut current = payload.to_vec();
+                for step in steps {
+                    current = (step.apply)(&current)?;
+                }
+                Ok(Cow::Owned(current))
+            },
+            Compatibility::Incompatible { reason } => Err(AiError::InvalidModelVersion {
+                version: format!("{} {}: {}", kind.model_key(), asset, reason),
+            }),
+        }
+    }
+
+    /// Find a chain of migrations from `asset` up to the minor version of `required`
+    fn find_path(
+        &self,
+        kind: AssetKind,
+        asset: ModelVersion,
+        required: ModelVersion,
+    ) -> Option<Vec<AssetMigration>> {
+        let same_minor =
+            |a: ModelVersion, b: ModelVersion| a.major == b.major && a.minor == b.minor;
+
+        let mut current = asset;
+        let mut path = Vec::new();
+
+        while !same_minor(current, required) {
+            // Prefer the step that gets closest to the target without overshooting it
+            let step = self
+                .migrations
+                .iter()
+                .filter(|m| m.kind == kind && same_minor(m.from, current))
+                .filter(|m| m.to <= required || same_minor(m.to, required))
+                .max_by_key(|m| m.to)?;
+
+            current = step.to;
+            path.push(*step);
+        }
+
+        Some(path)
+    }
+}
+
+/// Compatibility status of a single asset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetCompatibility {
+    /// Asset kind, if the header could be read
+    pub kind: Option<AssetKind>,
+    /// Version recorded in the asset header
+    pub asset_version: Option<ModelVersion>,
+    /// Version the engine is configured to use for this asset kind
+    pub required_version: Option<ModelVersion>,
+    /// Negotiation outcome
+    pub status: Compatibility,
+}
+
+/// Compatibility report covering a set of assets
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompatibilityReport {
+    /// Version of the engine that produced the report
+    pub engine_version: String,
+    /// Per-asset results, in input order
+    pub assets: Vec<AssetCompatibility>,
+}
+
+impl CompatibilityReport {
+    /// Check whether every asset in the report can be loaded
+    pub fn all_loadable(&self) -> bool {
+        self.assets.iter().all(|asset| asset.status.is_loadable())
+    }
+
+    /// Assets that would be rejected
+    pub fn incompatible(&self) -> Vec<&AssetCompatibility> {
+        self.assets
+            .iter()
+            .filter(|asset| !asset.status.is_loadable())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn append_marker(payload: &[u8]) -> AiResult<Vec<u8>> {
+        let mut upgraded = payload.to_vec();
+        upgraded.push(0xFF);
+        Ok(upgraded)
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        let version = ModelVersion::parse("v2.35.2").unwrap();
+        assert_eq!(version, ModelVersion::new(2, 35, 2));
+        assert_eq!(version.to_string(), "v2.35.2");
+        assert_eq!(
+            ModelVersion::parse("3.1").unwrap(),
+            ModelVersion::new(3, 1, 0)
+        );
+
+        assert!(ModelVersion::parse("latest").is_err());
+        assert!(ModelVersion::parse("v2").is_err());
+        assert!(ModelVersion::parse("1.2.3.4").is_err());
+        assert_eq!(
+            ModelVersion::current().unwrap().to_string(),
+            format!("v{}", composer_config::APPLICATION.version)
+        );
+    }
+
+    #[test]
+    fn test_header_round_trip() {
+        let payload = [1u8, 2, 3];
+        let bytes = encode_asset(
+            AssetKind::DifficultyModel,
+            ModelVersion::new(2, 35, 2),
+            &payload,
+        );
+
+        let asset = VersionedAsset::decode(&bytes).unwrap();
+        assert_eq!(asset.header.kind, AssetKind::DifficultyModel);
+        assert_eq!(asset.header.model_version, ModelVersion::new(2, 35, 2));
+        assert_eq!(asset.payload.as_ref(), &payload);
+        assert_eq!(asset.encode(), bytes);
+
+        assert!(AssetHeader::decode(&payload).is_err());
+
+        let mut unknown_kind = bytes.clone();
+        unknown_kind[5] = 99;
+        assert!(AssetHeader::decode(&unknown_kind).is_err());
+    }
+
+    #[test]
+    fn test_negotiation_rules() {
+        let registry = MigrationRegistry::new();
+        let required = ModelVersion::new(2, 35, 2);
+        let kind = AssetKind::ChordTrie;
+
+        assert_eq!(
+            registry.negotiate(kind, required, required),
+            Compatibility::Exact
+        );
+        assert_eq!(
+            registry.negotiate(kind, ModelVersion::new(2, 35, 0), required),
+            Compatibility::Compatible
+        );
+        assert!(!registry
+            .negotiate(kind, ModelVersion::new(2, 36, 0), required)
+            .is_loadable());
+        assert!(!registry
+            .negotiate(kind, ModelVersion::new(3, 0, 0), required)
+            .is_loadable());
+        assert!(!registry
+            .negotiate(kind, ModelVersion::new(2, 30, 0), required)
+            .is_loadable());
+    }
+
+    #[test]
+    fn test_chained_migration() {
+        let mut registry = MigrationRegistry::new();
+        registry
+            .register(AssetMigration {
+                kind: AssetKind::ChordTrie,
+                from: ModelVersion::new(2, 30, 0),
+                to: ModelVersion::new(2, 33, 0),
+                apply: append_marker,
+            })
+            .unwrap();
+        registry
+            .register(AssetMigration {
+                kind: AssetKind::ChordTrie,
+                from: ModelVersion::new(2, 33, 0),
+                to: ModelVersion::new(2, 35, 0),
+                apply: append_marker,
+            })
+            .unwrap();
+
+        let required = ModelVersion::new(2, 35, 2);
+        let asset = ModelVersion::new(2, 30, 4);
+
+        assert_eq!(
+            registry.negotiate(AssetKind::ChordTrie, asset, required),
+            Compatibility::RequiresMigration {
+                path: vec![ModelVersion::new(2, 33, 0), ModelVersion::new(2, 35, 0)],
+            }
+        );
+        let migrated = registry
+            .migrate(AssetKind::ChordTrie, asset, required, &[7])
+            .unwrap();
+        assert_eq!(migrated.as_ref(), &[7, 0xFF, 0xFF]);
+
+        // Migrations are kind-specific
+        assert!(registry
+            .migrate(AssetKind::DifficultyModel, asset, required, &[7])
+            .is_err());
+    }
+
+    #[test]
+    fn test_register_rejects_downgrade() {
+        let mut registry = MigrationRegistry::new();
+        let result = registry.register(AssetMigration {
+            kind: AssetKind::ChordTrie,
+            from: ModelVersion::new(2, 35, 0),
+            to: ModelVersion::new(2, 30, 0),
+            apply: append_marker,
+        });
+        assert!(result.is_err());
+        assert!(registry.is_empty());
+    }
+}
@@ -0,0 +1,376 @@
+//! Multi-step progression continuation
+//!
+//! [`continue_progression`] extends a progression chord by chord. Each step
+//! asks for suggestions after the last few chords, samples one of them at the
+//! configured temperature and feeds it back as context, so clients need not
+//! loop over single-step suggestions themselves. The context's position is
+//! advanced and its recent chords refreshed at every step, and the last two
+//! chords can be steered toward an authentic cadence.
+
+use crate::error::{AiError, AiResult};
+use crate::suggestions::{ChordSuggestion, SuggestionConfig, SuggestionContext};
+use composer_core::Chord;
+use serde::{Deserialize, Serialize};
+
+/// Score multiplier for candidates completing the target cadence
+const CADENCE_WEIGHT: f64 = 4.0;
+
+/// Chords kept in the context's recent history
+const RECENT_CHORDS: usize = 8;
+
+/// Settings for [`continue_progression`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContinuationConfig {
+    /// Settings for each step's suggestions; `search_depth` caps how many
+    /// trailing chords are matched
+    pub suggestion: SuggestionConfig,
+
+    /// Context for every step; its position and recent chords are replaced as
+    /// the progression grows
+    pub context: SuggestionContext,
+
+    /// Sampling temperature over suggestion scores (0.0-2.0). At 1.0 chords are
+    /// drawn in proportion to their score; 0.0 always takes the best one.
+    pub temperature: f64,
+
+    /// Seed of the sampling stream, so a continuation can be reproduced
+    pub seed: u64,
+
+    /// Favour a dominant (V or vii) for the second-to-last chord and the tonic
+    /// for the last
+    pub target_cadence: bool,
+}
+
+impl Default for ContinuationConfig {
+    fn default() -> Self {
+        Self {
+            suggestion: SuggestionConfig::default(),
+            context: SuggestionContext::default(),
+            temperature: 1.0,
+            seed: 0,
+            target_cadence: true,
+        }
+    }
+}
+
+/// Extend `seed` by up to `n` chords drawn from `suggest`
+///
+/// `suggest` is called like [`crate::AiEngine::get_chord_suggestions`] with the
+/// last `search_depth` chords of the progression, shortening the context while
+/// nothing is found. When repetition avoidance filters out every candidate the
+/// step is retried without it. The continuation stops early when no context
+/// yields a suggestion; each returned suggestion is the chord chosen at its
+/// step.
+///
+/// # Errors
+///
+/// `AiError::InvalidPattern` for an empty seed or a negative or non-finite
+/// temperature, and any error returned by `suggest`.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::{continue_progression, ChordSuggestion, ContinuationConfig};
+/// use composer_core::Chord;
+///
+/// // A stand-in suggester that answers I with V and anything else with I
+/// let (tonic, dominant) = (Chord::triad(1)?, Chord::triad(5)?);
+/// let suggest = |pattern: &[Chord], _: &_, _: &_| {
+///     let next = if pattern.last() == Some(&tonic) { &dominant } else { &tonic };
+///     Ok(vec![ChordSuggestion {
+///         chord: next.clone(),
+///         weighted_score: 1.0,
+///         ..Default::default()
+///     }])
+/// };
+/// let seed = [tonic.clone()];
+/// let continuation = continue_progression(&seed, 2, &ContinuationConfig::default(), suggest)?;
+/// let roots: Vec<u8> = continuation.iter().map(|s| s.chord.root).collect();
+/// assert_eq!(roots, [5, 1]);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`crate::AiEngine::continue_progression`] - Continuation from the engine's corpus
+pub fn continue_progression<F>(
+    seed: &[Chord],
+    n: usize,
+    config: &ContinuationConfig,
+    mut suggest: F,
+) -> AiResult<Vec<ChordSuggestion>>
+where
+    F: FnMut(&[Chord], &SuggestionContext, &SuggestionConfig) -> AiResult<Vec<ChordSuggestion>>,
+{
+    if seed.is_empty() {
+        return Err(AiError::InvalidPattern {
+            reason: "Seed progression cannot be empty".to_string(),
+        });
+    }
+    if !config.temperature.is_finite() || config.temperature < 0.0 {
+        return Err(AiError::InvalidPattern {
+            reason: format!("Invalid temperature: {}", config.temperature),
+        });
+    }
+
+    let total = seed.len() + n;
+    let depth = config.suggestion.search_depth.max(1);
+    let mut progression = seed.to_vec();
+    let mut context = config.context.clone();
+    let mut state = config.seed;
+    let mut continuation = Vec::with_capacity(n);
+
+    for step in 0..n {
+        let index = progression.len();
+        context.position_in_progression = index as f64 / (total - 1).max(1) as f64;
+        context.recent_chords = progression[index.saturating_sub(RECENT_CHORDS)..]
+            .iter()
+            .cloned()
+            .collect();
+
+        let mut candidates =
+            suggest_with_backoff(&progression, depth, &context, config, &mut suggest)?;
+        if candidates.is_empty() && context.avoid_repetition_within > 0 {
+            let relaxed = SuggestionContext {
+                avoid_repetition_within: 0,
+                ..context.clone()
+            };
+            candidates = suggest_with_backoff(&progression, depth, &relaxed, config, &mut suggest)?;
+        }
+
+        let goal: &[u8] = match (config.target_cadence, n - step) {
+            (true, 1) => &[1],
+            (true, 2) => &[5, 7],
+            _ => &[],
+        };
+        let Some(choice) = sample(candidates, goal, config.temperature, &mut state) else {
+            break;
+        };
+        progression.push(choice.chord.clone());
+        continuation.push(choice);
+    }
+
+    Ok(continuation)
+}
+
+/// Suggestions after the longest trailing context, up to `depth` chords, that
+/// has any
+fn suggest_with_backoff<F>(
+    progression: &[Chord],
+    depth: usize,
+    context: &SuggestionContext,
+    config: &ContinuationConfig,
+    suggest: &mut F,
+) -> AiResult<Vec<ChordSuggestion>>
+where
+    F: FnMut(&[Chord], &SuggestionContext, &SuggestionConfig) -> AiResult<Vec<ChordSuggestion>>,
+{
+    for length in (1..=depth.min(progression.len())).rev() {
+        let suggestions = suggest(
+            &progression[progression.len() - length..],
+            context,
+            &config.suggestion,
+        )?;
+        if !suggestions.is_empty() {
+            return Ok(suggestions);
+        }
+    }
+    Ok(Vec::new())
+}
+
+/// Draw one candidate with probability proportional to `score^(1/temperature)`,
+/// scores of chords rooted on a `goal` degree multiplied by [`CADENCE_WEIGHT`]
+fn sample(
+    candidates: Vec<ChordSuggestion>,
+    goal: &[u8],
+    temperature: f64,
+    state: &mut u64,
+) -> Option<ChordSuggestion> {
+    let scores: Vec<f64> = candidates
+        .iter()
+        .map(|s| {
+            let on_goal = s.chord.applied == 0 && goal.contains(&s.chord.root);
+            s.weighted_score.max(0.0) * if on_goal { CADENCE_WEIGHT } else { 1.0 }
+        })
+        .collect();
+    let best = (0..scores.len())
+        .rev()
+        .max_by(|&a, &b| scores[a].total_cmp(&scores[b]))?;
+
+    let index = if temperature <= 0.0 {
+        best
+    } else {
+        // Normalise by the best score first so small temperatures can't underflow
+        let top = scores[best];
+        let weights: Vec<f64> = scores
+            .iter()
+            .map(|&score| {
+                if top > 0.0 {
+                    (score / top).powf(1.0 / temperature)
+                } else {
+                    1.0
+                }
+            })
+            .collect();
+        let mut draw = unit(state) * weights.iter().sum::<f64>();
+        weights
+            .iter()
+            .position(|&weight| {
+                draw -= weight;
+                draw < 0.0
+            })
+            .unwrap_or(best)
+    };
+    candidates.into_iter().nth(index)
+}
+
+/// Uniform value in [0, 1) from a SplitMix64 stream
+fn unit(state: &mut u64) -> f64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    ((z ^ (z >> 31)) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suggestion(root: u8, score: f64) -> ChordSuggestion {
+        ChordSuggestion {
+            chord: Chord::triad(root).unwrap(),
+            weighted_score: score,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_context_tracking() {
+        let mut seen = Vec::new();
+        let config = ContinuationConfig {
+            temperature: 0.0,
+            target_cadence: false,
+            ..Default::default()
+        };
+        let continuation = continue_progression(
+            &[Chord::triad(1).unwrap()],
+            3,
+            &config,
+            |pattern: &[Chord], context: &SuggestionContext, _: &SuggestionConfig| {
+                seen.push((pattern.len(), context.position_in_progression));
+                let last = pattern.last().unwrap().root;
+                Ok(vec![suggestion(last % 7 + 1, 1.0)])
+            },
+        )
+        .unwrap();
+
+        let roots: Vec<u8> = continuation.iter().map(|s| s.chord.root).collect();
+        assert_eq!(roots, [2, 3, 4]);
+        let positions: Vec<f64> = seen.iter().map(|&(_, position)| position).collect();
+        assert_eq!(positions, [1.0 / 3.0, 2.0 / 3.0, 1.0]);
+        let lengths: Vec<usize> = seen.iter().map(|&(length, _)| length).collect();
+        assert_eq!(lengths, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_backoff_and_relaxed_repetition() {
+        let config = ContinuationConfig {
+            temperature: 0.0,
+            target_cadence: false,
+            ..Default::default()
+        };
+        // Only single-chord contexts match, and only without repetition avoidance
+        let continuation = continue_progression(
+            &[Chord::triad(1).unwrap(), Chord::triad(5).unwrap()],
+            2,
+            &config,
+            |pattern: &[Chord], context: &SuggestionContext, _: &SuggestionConfig| {
+                Ok(
+                    if pattern.len() == 1 && context.avoid_repetition_within == 0 {
+                        vec![suggestion(1, 1.0)]
+                    } else {
+                        Vec::new()
+                    },
+                )
+            },
+        )
+        .unwrap();
+        assert_eq!(continuation.len(), 2);
+
+        let stalled = continue_progression(
+            &[Chord::triad(1).unwrap()],
+            4,
+            &config,
+            |_: &[Chord], _: &_, _: &_| Ok(Vec::new()),
+        )
+        .unwrap();
+        assert!(stalled.is_empty());
+    }
+
+    #[test]
+    fn test_cadence_targeting() {
+        let candidates = |_: &[Chord], _: &SuggestionContext, _: &SuggestionConfig| {
+            Ok(vec![
+                suggestion(4, 1.0),
+                suggestion(5, 0.5),
+                suggestion(1, 0.3),
+            ])
+        };
+        let greedy = ContinuationConfig {
+            temperature: 0.0,
+            ..Default::default()
+        };
+        let seed = [Chord::triad(1).unwrap()];
+        let roots = |config: &ContinuationConfig| -> Vec<u8> {
+            continue_progression(&seed, 4, config, candidates)
+                .unwrap()
+                .iter()
+                .map(|s| s.chord.root)
+                .collect()
+        };
+
+        assert_eq!(roots(&greedy), [4, 4, 5, 1]);
+        let free = ContinuationConfig {
+            target_cadence: false,
+            ..greedy.clone()
+        };
+        assert_eq!(roots(&free), [4, 4, 4, 4]);
+    }
+
+    #[test]
+    fn test_temperature_sampling() {
+        let candidates = |_: &[Chord], _: &SuggestionContext, _: &SuggestionConfig| {
+            Ok(vec![
+                suggestion(4, 1.0),
+                suggestion(5, 0.8),
+                suggestion(6, 0.6),
+            ])
+        };
+        let seed = [Chord::triad(1).unwrap()];
+        let sampled = |seed_value: u64| -> Vec<u8> {
+            let config = ContinuationConfig {
+                temperature: 1.5,
+                seed: seed_value,
+                target_cadence: false,
+                ..Default::default()
+            };
+            continue_progression(&seed, 16, &config, candidates)
+                .unwrap()
+                .iter()
+                .map(|s| s.chord.root)
+                .collect()
+        };
+
+        // Reproducible for a seed, and varied at a high temperature
+        assert_eq!(sampled(7), sampled(7));
+        assert!(sampled(7).iter().any(|&root| root != 4));
+
+        let invalid = ContinuationConfig {
+            temperature: -1.0,
+            ..Default::default()
+        };
+        assert!(continue_progression(&seed, 1, &invalid, candidates).is_err());
+        assert!(continue_progression(&[], 1, &ContinuationConfig::default(), candidates).is_err());
+    }
+}
@@ -0,0 +1,551 @@
+//! Exportable difficulty features and pluggable difficulty models
+//!
+//! The built-in assessment reduces a progression to a fixed set of numeric
+//! features (the four complexity sub-scores plus the raw [`ComplexityFactors`]) and
+//! maps them to an overall score with a [`PolynomialModel`]. This module publishes
+//! that feature vector in a documented, stable order so models can be trained on
+//! it offline (e.g. in Python), and provides an inference path for deploying such
+//! models back inside the engine.
+//!
+//! [`DenseDifficultyModel`] evaluates the subset of ONNX operators used by small
+//! regression networks (`Gemm`, `Relu`, `Sigmoid`, `Tanh`) from a JSON description,
+//! so no inference runtime has to be linked. Larger models can implement
+//! [`DifficultyModel`] directly on top of a runtime of their choice.
+//!
+//! [`PolynomialModel`]: crate::PolynomialModel
+
+use crate::analysis::{ComplexityFactors, DifficultyAssessment};
+use crate::error::{AiError, AiResult};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Number of features in a [`DifficultyFeatures`] vector
+pub const DIFFICULTY_FEATURE_COUNT: usize = 12;
+
+/// Feature names in the order they appear in a [`DifficultyFeatures`] vector.
+///
+/// The order is part of the public contract: models trained on exported features
+/// expect their input columns in exactly this order. New features are only ever
+/// appended, together with a bump of [`DIFFICULTY_FEATURE_VERSION`].
+pub const DIFFICULTY_FEATURE_NAMES: [&str; DIFFICULTY_FEATURE_COUNT] = [
+    "harmonic_complexity",
+    "rhythmic_complexity",
+    "technical_complexity",
+    "melodic_complexity",
+    "unique_chords",
+    "avg_chord_complexity",
+    "key_changes",
+    "uncommon_progressions",
+    "time_signature_changes",
+    "extended_harmonies",
+    "voice_leading_complexity",
+    "tempo_variations",
+];
+
+/// Version of the feature layout described by [`DIFFICULTY_FEATURE_NAMES`]
+pub const DIFFICULTY_FEATURE_VERSION: u32 = 1;
+
+/// Fixed-order numeric features describing one progression.
+///
+/// The first four entries are the harmonic, rhythmic, technical and melodic
+/// sub-scores (0.0-10.0); the remaining eight are the [`ComplexityFactors`] fields,
+/// with counts converted to `f64`. See [`DIFFICULTY_FEATURE_NAMES`] for the exact
+/// order.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::{AiEngine, AiEngineConfig, DifficultyFeatures, DIFFICULTY_FEATURE_NAMES};
+/// use composer_core::Chord;
+///
+/// let engine = AiEngine::new(AiEngineConfig::default());
+/// engine.initialize(vec![])?;
+///
+/// let progression = vec![Chord::new(1, 5)?, Chord::new(5, 7)?, Chord::new(1, 5)?];
+/// let assessment = engine.assess_difficulty(&progression, Some(120.0), Some((4, 4)))?;
+/// let features = DifficultyFeatures::from_assessment(&assessment);
+///
+/// assert_eq!(features.values().len(), DIFFICULTY_FEATURE_NAMES.len());
+/// assert_eq!(features.get("unique_chords"), Some(2.0));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`crate::AiEngine::extract_difficulty_features`] - Extract features directly
+/// - [`DifficultyModel`] - Consume features to predict a difficulty score
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct DifficultyFeatures {
+    values: [f64; DIFFICULTY_FEATURE_COUNT],
+}
+
+impl DifficultyFeatures {
+    /// Build a feature vector from sub-scores and complexity factors
+    pub fn new(
+        harmonic_complexity: f64,
+        rhythmic_complexity: f64,
+        technical_complexity: f64,
+        melodic_complexity: f64,
+        factors: &ComplexityFactors,
+    ) -> Self {
+        Self {
+            values: [
+                harmonic_complexity,
+                rhythmic_complexity,
+                technical_complexity,
+                melodic_complexity,
+                factors.unique_chords as f64,
+                factors.avg_chord_complexity,
+                factors.key_changes as f64,
+                factors.uncommon_progressions as f64,
+                factors.time_signature_changes as f64,
+                factors.extended_harmonies as f64,
+                factors.voice_leading_complexity,
+                factors.tempo_variations,
+            ],
+        }
+    }
+
+    /// Build a feature vector from a completed assessment
+    pub fn from_assessment(assessment: &DifficultyAssessment) -> Self {
+        Self::new(
+            assessment.harmonic_complexity,
+            assessment.rhythmic_complexity,
+            assessment.technical_complexity,
+            assessment.melodic_complexity,
+            &assessment.factors,
+        )
+    }
+
+    /// Wrap raw values that are already in [`DIFFICULTY_FEATURE_NAMES`] order
+    pub fn from_values(values: [f64; DIFFICULTY_FEATURE_COUNT]) -> Self {
+        Self { values }
+    }
+
+    /// Feature values in [`DIFFICULTY_FEATURE_NAMES`] order
+    pub fn values(&self) -> &[f64; DIFFICULTY_FEATURE_COUNT] {
+        &self.values
+    }
+
+    /// Feature values as `f32`, the input type expected by most ONNX models
+    pub fn to_f32(&self) -> [f32; DIFFICULTY_FEATURE_COUNT] {
+        self.values.map(|value| value as f32)
+    }
+
+    /// Look up a feature by name
+    pub fn get(&self, name: &str) -> Option<f64> {
+        DIFFICULTY_FEATURE_NAMES
+            .iter()
+            .position(|&feature| feature == name)
+            .map(|index| self.values[index])
+    }
+
+    /// Iterate over `(name, value)` pairs in feature order
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, f64)> + '_ {
+        DIFFICULTY_FEATURE_NAMES
+            .iter()
+            .copied()
+            .zip(self.values.iter().copied())
+    }
+}
+
+/// Model that maps difficulty features to an overall difficulty score.
+///
+/// The returned score is clamped to `0.0..=10.0` by the analyzer; a non-finite
+/// score or an error fails the assessment. Implementations must be thread-safe.
+///
+/// Closures of the form `Fn(&DifficultyFeatures) -> AiResult<f64>` implement this
+/// trait.
+///
+/// # Related Functions
+///
+/// - [`crate::AiEngine::set_difficulty_model`] - Replace the built-in model
+/// - [`DenseDifficultyModel`] - Ready-made implementation for exported networks
+pub trait DifficultyModel: Send + Sync {
+    /// Predict the overall difficulty (0.0-10.0) for one progression
+    fn predict(&self, features: &DifficultyFeatures) -> AiResult<f64>;
+
+    /// Short model name used in error messages
+    fn name(&self) -> &str {
+        "difficulty model"
+    }
+
+    /// Validation accuracy (0.0-1.0) used to size score intervals; `None`
+    /// falls back to the accuracy of the built-in [`crate::PolynomialModel`]
+    fn accuracy(&self) -> Option<f64> {
+        None
+    }
+}
+
+impl<F> DifficultyModel for F
+where
+    F: Fn(&DifficultyFeatures) -> AiResult<f64> + Send + Sync,
+{
+    fn predict(&self, features: &DifficultyFeatures) -> AiResult<f64> {
+        self(features)
+    }
+}
+
+/// Shared handle to a difficulty model
+pub type SharedDifficultyModel = Arc<dyn DifficultyModel>;
+
+/// One layer of a [`DenseDifficultyModel`], named after the ONNX operator it mirrors
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op")]
+pub enum DenseLayer {
+    /// ONNX `Gemm` with `transB = 1`: `y = W·x + b`, `weight` shaped `[out, in]`
+    /// like a PyTorch `nn.Linear`
+    Gemm {
+        weight: Vec<Vec<f64>>,
+        #[serde(default)]
+        bias: Vec<f64>,
+    },
+    /// ONNX `Relu`
+    Relu,
+    /// ONNX `Sigmoid`
+    Sigmoid,
+    /// ONNX `Tanh`
+    Tanh,
+}
+
+/// Feed-forward difficulty model evaluated without an inference runtime.
+///
+/// The JSON form is produced by `composer.difficulty_export` on the Python side
+/// from an ONNX graph made of `Gemm`/`MatMul`+`Add` and activation nodes:
+///
+/// ```json
+/// {
+///   "feature_version": 1,
+///   "feature_names": ["harmonic_complexity", "...", "tempo_variations"],
+///   "input_mean": [0.0, "..."],
+///   "input_scale": [1.0, "..."],
+///   "layers": [
+///     {"op": "Gemm", "weight": [[0.1, "..."]], "bias": [0.0]},
+///     {"op": "Sigmoid"}
+///   ],
+///   "output_scale": 10.0
+/// }
+/// ```
+///
+/// Inputs are standardized as `(x - input_mean) / input_scale`, the layers are
+/// applied in order, and the single output is multiplied by `output_scale`.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::{DenseDifficultyModel, DifficultyFeatures, DifficultyModel};
+///
+/// // Average of the four sub-scores
+/// let json = r#"{
+///     "feature_names": [
+///         "harmonic_complexity", "rhythmic_complexity", "technical_complexity",
+///         "melodic_complexity", "unique_chords", "avg_chord_complexity", "key_changes",
+///         "uncommon_progressions", "time_signature_changes", "extended_harmonies",
+///         "voice_leading_complexity", "tempo_variations"
+///     ],
+///     "layers": [{
+///         "op": "Gemm",
+///         "weight": [[0.25, 0.25, 0.25, 0.25, 0, 0, 0, 0, 0, 0, 0, 0]],
+///         "bias": [0.0]
+///     }]
+/// }"#;
+/// let model = DenseDifficultyModel::from_json(json)?;
+///
+/// let mut values = [0.0; 12];
+/// values[..4].copy_from_slice(&[2.0, 4.0, 6.0, 8.0]);
+/// let score = model.predict(&DifficultyFeatures::from_values(values))?;
+/// assert!((score - 5.0).abs() < 1e-9);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DenseDifficultyModel {
+    /// Feature layout version the model was trained on
+    #[serde(default = "default_feature_version")]
+    pub feature_version: u32,
+
+    /// Input feature names; must equal [`DIFFICULTY_FEATURE_NAMES`]
+    pub feature_names: Vec<String>,
+
+    /// Per-feature mean subtracted before the first layer (empty for none)
+    #[serde(default)]
+    pub input_mean: Vec<f64>,
+
+    /// Per-feature divisor applied after centering (empty for none)
+    #[serde(default)]
+    pub input_scale: Vec<f64>,
+
+    /// Layers applied in order
+    pub layers: Vec<DenseLayer>,
+
+    /// Multiplier applied to the model output
+    #[serde(default = "default_output_scale")]
+    pub output_scale: f64,
+
+    /// Validation accuracy (0.0-1.0) reported through [`DifficultyModel::accuracy`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accuracy: Option<f64>,
+}
+
+fn default_feature_version() -> u32 {
+    DIFFICULTY_FEATURE_VERSION
+}
+
+fn default_output_scale() -> f64 {
+    1.0
+}
+
+impl DenseDifficultyModel {
+    /// Parse and validate a model from its JSON description
+    pub fn from_json(json: &str) -> AiResult<Self> {
+        let model: Self = serde_json::from_str(json).map_err(|e| AiError::InvalidModel {
+            reason: format!("Failed to parse difficulty model: {}", e),
+        })?;
+        model.validate()?;
+        Ok(model)
+    }
+
+    /// Serialize the model to its JSON description
+    pub fn to_json(&self) -> AiResult<String> {
+        serde_json::to_string(self).map_err(|e| AiError::InvalidModel {
+            reason: format!("Failed to serialize difficulty model: {}", e),
+        })
+    }
+
+    /// Check that the feature layout matches and all layer shapes line up
+    pub fn validate(&self) -> AiResult<()> {
+        let invalid = |reason: String| Err(AiError::InvalidModel { reason });
+
+        if self.feature_version != DIFFICULTY_FEATURE_VERSION {
+            return invalid(format!(
+                "Model uses feature version {}, expected {}",
+                self.feature_version, DIFFICULTY_FEATURE_VERSION
+            ));
+        }
+        if self.feature_names != DIFFICULTY_FEATURE_NAMES {
+            return invalid(format!(
+                "Model feature names {:?} do not match the exported feature order",
+                self.feature_names
+            ));
+        }
+        for (name, values) in [
+            ("input_mean", &self.input_mean),
+            ("input_scale", &self.input_scale),
+        ] {
+            if !values.is_empty() && values.len() != DIFFICULTY_FEATURE_COUNT {
+                return invalid(format!(
+                    "{} has {} entries, expected {}",
+                    name,
+                    values.len(),
+                    DIFFICULTY_FEATURE_COUNT
+                ));
+            }
+        }
+        if self
+            .input_scale
+            .iter()
+            .any(|&scale| scale == 0.0 || !scale.is_finite())
+        {
+            return invalid("input_scale must be finite and non-zero".to_string());
+        }
+
+        let mut width = DIFFICULTY_FEATURE_COUNT;
+        for (index, layer) in self.layers.iter().enumerate() {
+            if let DenseLayer::Gemm { weight, bias } = layer {
+                if weight.is_empty() || weight.iter().any(|row| row.len() != width) {
+                    return invalid(format!(
+                        "Layer {} weight must be shaped [out, {}]",
+                        index, width
+                    ));
+                }
+                if !bias.is_empty() && bias.len() != weight.len() {
+                    return invalid(format!(
+                        "Layer {} bias has {} entries, expected {}",
+                        index,
+                        bias.len(),
+                        weight.len()
+                    ));
+                }
+                width = weight.len();
+            }
+        }
+        if width != 1 {
+            return invalid(format!("Model produces {} outputs, expected 1", width));
+        }
+        if let Some(accuracy) = self.accuracy {
+            if !(0.0..=1.0).contains(&accuracy) {
+                return invalid(format!("accuracy {} must be within 0.0-1.0", accuracy));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl DifficultyModel for DenseDifficultyModel {
+    fn predict(&self, features: &DifficultyFeatures) -> AiResult<f64> {
+        let mut activations: Vec<f64> = features
+            .values()
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| {
+                let centered = value - self.input_mean.get(i).copied().unwrap_or(0.0);
+                centered / self.input_scale.get(i).copied().unwrap_or(1.0)
+            })
+            .collect();
+
+        for layer in &self.layers {
+            match layer {
+                DenseLayer::Gemm { weight, bias } => {
+                    activations = weight
+                        .iter()
+                        .enumerate()
+                        .map(|(row_index, row)| {
+                            let dot: f64 = row.iter().zip(&activations).map(|(w, x)| w * x).sum();
+                            dot + bias.get(row_index).copied().unwrap_or(0.0)
+                        })
+                        .collect();
+                },
+                DenseLayer::Relu => activations.iter_mut().for_each(|x| *x = x.max(0.0)),
+                DenseLayer::Sigmoid => activations
+                    .iter_mut()
+                    .for_each(|x| *x = 1.0 / (1.0 + (-*x).exp())),
+                DenseLayer::Tanh => activations.iter_mut().for_each(|x| *x = x.tanh()),
+            }
+        }
+
+        activations
+            .first()
+            .map(|output| output * self.output_scale)
+            .ok_or_else(|| AiError::InvalidModel {
+                reason: "Model produced no output".to_string(),
+            })
+    }
+
+    fn name(&self) -> &str {
+        "dense difficulty model"
+    }
+
+    fn accuracy(&self) -> Option<f64> {
+        self.accuracy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feature_names() -> Vec<String> {
+        DIFFICULTY_FEATURE_NAMES
+            .iter()
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_feature_order_matches_factors() {
+        let factors = ComplexityFactors {
+            unique_chords: 3,
+            avg_chord_complexity: 2.5,
+            key_changes: 1,
+            uncommon_progressions: 2,
+            time_signature_changes: 0,
+            extended_harmonies: 4,
+            voice_leading_complexity: 1.5,
+            tempo_variations: 0.5,
+        };
+        let features = DifficultyFeatures::new(1.0, 2.0, 3.0, 4.0, &factors);
+
+        assert_eq!(
+            features.values(),
+            &[1.0, 2.0, 3.0, 4.0, 3.0, 2.5, 1.0, 2.0, 0.0, 4.0, 1.5, 0.5]
+        );
+        assert_eq!(features.get("extended_harmonies"), Some(4.0));
+        assert_eq!(features.get("missing"), None);
+        assert_eq!(features.iter().count(), DIFFICULTY_FEATURE_COUNT);
+    }
+
+    #[test]
+    fn test_dense_model_forward_pass() {
+        let mut first = vec![vec![0.0; DIFFICULTY_FEATURE_COUNT]; 2];
+        first[0][0] = 1.0;
+        first[1][0] = -1.0;
+        let model = DenseDifficultyModel {
+            feature_version: DIFFICULTY_FEATURE_VERSION,
+            feature_names: feature_names(),
+            input_mean: vec![2.0; DIFFICULTY_FEATURE_COUNT],
+            input_scale: vec![2.0; DIFFICULTY_FEATURE_COUNT],
+            layers: vec![
+                DenseLayer::Gemm {
+                    weight: first,
+                    bias: vec![0.0, 0.5],
+                },
+                DenseLayer::Relu,
+                DenseLayer::Gemm {
+                    weight: vec![vec![1.0, 1.0]],
+                    bias: vec![],
+                },
+            ],
+            output_scale: 10.0,
+            accuracy: None,
+        };
+        model.validate().unwrap();
+
+        let mut values = [0.0; DIFFICULTY_FEATURE_COUNT];
+        values[0] = 6.0;
+        // (6 - 2) / 2 = 2 -> [2, -1.5] -> relu [2, 0] -> 2 -> x10
+        let score = model
+            .predict(&DifficultyFeatures::from_values(values))
+            .unwrap();
+        assert!((score - 20.0).abs() < 1e-9);
+
+        let round_trip = DenseDifficultyModel::from_json(&model.to_json().unwrap()).unwrap();
+        assert_eq!(round_trip, model);
+    }
+
+    #[test]
+    fn test_dense_model_validation() {
+        let base = DenseDifficultyModel {
+            feature_version: DIFFICULTY_FEATURE_VERSION,
+            feature_names: feature_names(),
+            input_mean: vec![],
+            input_scale: vec![],
+            layers: vec![DenseLayer::Gemm {
+                weight: vec![vec![0.0; DIFFICULTY_FEATURE_COUNT]],
+                bias: vec![0.0],
+            }],
+            output_scale: 1.0,
+            accuracy: Some(0.9),
+        };
+        assert!(base.validate().is_ok());
+
+        let mut overconfident = base.clone();
+        overconfident.accuracy = Some(1.5);
+        assert!(overconfident.validate().is_err());
+
+        let mut reordered = base.clone();
+        reordered.feature_names.swap(0, 1);
+        assert!(reordered.validate().is_err());
+
+        let mut wrong_width = base.clone();
+        wrong_width.layers = vec![DenseLayer::Gemm {
+            weight: vec![vec![0.0; 3]],
+            bias: vec![],
+        }];
+        assert!(wrong_width.validate().is_err());
+
+        let mut two_outputs = base.clone();
+        two_outputs.layers = vec![DenseLayer::Gemm {
+            weight: vec![vec![0.0; DIFFICULTY_FEATURE_COUNT]; 2],
+            bias: vec![],
+        }];
+        assert!(two_outputs.validate().is_err());
+
+        let mut future = base;
+        future.feature_version = DIFFICULTY_FEATURE_VERSION + 1;
+        assert!(matches!(
+            future.validate(),
+            Err(AiError::InvalidModel { .. })
+        ));
+
+        assert!(DenseDifficultyModel::from_json("{").is_err());
+    }
+}
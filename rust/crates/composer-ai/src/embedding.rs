@@ -0,0 +1,1188 @@
+//! Chord and progression embeddings with approximate nearest-neighbor search
+//!
+//! The trie answers "what followed this pattern in the corpus", which says nothing
+//! about chords that never appeared in that position. Embeddings place every chord
+//! in a fixed vector space built from its pitch content alone, so harmonically
+//! close chords (shared tones, same bass, similar extensions) are close in the
+//! space regardless of corpus statistics. Progressions embed by pooling their
+//! chords and hashing their chord-to-chord motion, which powers "more like this"
+//! searches over stored sources. [`EmbeddingIndex`] hashes embeddings with random
+//! hyperplanes (SimHash) for sub-linear lookup and re-ranks the candidates by
+//! exact cosine similarity.
+
+use crate::engine::TrainingPattern;
+use crate::error::{AiError, AiResult};
+use crate::trie::ChordProgressionTrie;
+use ahash::{AHashMap, AHashSet};
+use composer_core::{get_stable_scale_degrees, Chord, Key, ScaleFingerprint};
+use composer_serialization::{deserialize_chord, serialize_chord, ChordBinary};
+use serde::{Deserialize, Serialize};
+
+/// Dimension of [`ChordEmbedding`]
+///
+/// Twelve pitch-class weights relative to the tonic, then chord extension,
+/// inversion, applied and borrowed indicators.
+pub const CHORD_EMBEDDING_DIM: usize = 16;
+
+/// Fixed-length, unit-norm embedding of a single chord
+pub type ChordEmbedding = [f32; CHORD_EMBEDDING_DIM];
+
+/// Feature-hashing buckets for the motion block of a [`ProgressionEmbedding`]
+const SEQUENCE_BUCKETS: usize = 32;
+
+/// Dimension of [`ProgressionEmbedding`]
+///
+/// A pooled block of [`CHORD_EMBEDDING_DIM`] values followed by the hashed
+/// chord-motion block.
+pub const PROGRESSION_EMBEDDING_DIM: usize = CHORD_EMBEDDING_DIM + SEQUENCE_BUCKETS;
+
+/// Fixed-length, unit-norm embedding of a chord progression
+pub type ProgressionEmbedding = [f32; PROGRESSION_EMBEDDING_DIM];
+
+/// Number of independent hash tables in an [`EmbeddingIndex`]
+const LSH_TABLES: usize = 4;
+
+/// Hyperplanes (hash bits) per table
+const LSH_BITS: usize = 8;
+
+/// Seed for the hyperplane generator, fixed so indexes are reproducible
+const LSH_SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Semitone offsets of the major scale degrees
+const MAJOR_OFFSETS: [i32; 7] = [0, 2, 4, 5, 7, 9, 11];
+
+/// Weight added to the bass pitch class so inversions stay distinguishable
+const BASS_WEIGHT: f32 = 0.5;
+
+/// Embed a chord by its pitch content in a major-key context.
+///
+/// Chord tones come from [`get_stable_scale_degrees`], so alterations, applied
+/// targets and suspensions all move the embedding. Rests embed as the zero vector.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::{cosine_similarity, embed_chord};
+/// use composer_core::Chord;
+///
+/// let tonic = embed_chord(&Chord::new(1, 5)?)?;
+/// let relative_minor = embed_chord(&Chord::new(6, 5)?)?;
+/// let tritone_away = embed_chord(&Chord::new(4, 5)?.with_alteration("#11")?)?;
+///
+/// // vi shares two tones with I
+/// assert!(cosine_similarity(&tonic, &relative_minor) > cosine_similarity(&tonic, &tritone_away));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn embed_chord(chord: &Chord) -> AiResult<ChordEmbedding> {
+    let mut embedding = [0.0f32; CHORD_EMBEDDING_DIM];
+    if chord.is_rest {
+        return Ok(embedding);
+    }
+
+    let degrees =
+        get_stable_scale_degrees(chord, &ScaleFingerprint::major_scale()).map_err(|e| {
+            AiError::InvalidPattern {
+                reason: format!("Cannot embed chord {}: {}", chord, e),
+            }
+        })?;
+    for (index, degree) in degrees.iter().enumerate() {
+        let Some(pitch_class) = degree_pitch_class(degree) else {
+            continue;
+        };
+        embedding[pitch_class] = 1.0;
+        if index == 0 {
+            embedding[pitch_class] += BASS_WEIGHT;
+        }
+    }
+
+    embedding[12] = (chord.chord_type.saturating_sub(5)) as f32 / 8.0;
+    embedding[13] = chord.inversion as f32 / 3.0;
+    embedding[14] = if chord.applied != 0 { 0.5 } else { 0.0 };
+    embedding[15] = if chord.borrowed.is_some() { 0.5 } else { 0.0 };
+
+    normalize(&mut embedding);
+    Ok(embedding)
+}
+
+/// Cosine similarity of two embeddings, 0.0 when either is the zero vector
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+    let dot: f64 = a.iter().zip(b).map(|(x, y)| *x as f64 * *y as f64).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| (*x as f64).powi(2)).sum::<f64>().sqrt();
+    let denominator = norm(a) * norm(b);
+    if denominator == 0.0 {
+        0.0
+    } else {
+        dot / denominator
+    }
+}
+
+/// Pitch class above the tonic of a scale degree string such as `"b3"` or `"#11"`
+fn degree_pitch_class(degree: &str) -> Option<usize> {
+    let digits = degree.trim_start_matches(['b', '#']);
+    let accidental = degree[..degree.len() - digits.len()]
+        .chars()
+        .map(|c| if c == 'b' { -1 } else { 1 })
+        .sum::<i32>();
+    let number: usize = digits.parse().ok().filter(|&n| n > 0)?;
+    Some((MAJOR_OFFSETS[(number - 1) % 7] + accidental).rem_euclid(12) as usize)
+}
+
+/// Scale a vector to unit length in place; zero vectors are left unchanged
+fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|x| *x /= norm);
+    }
+}
+
+/// Bucket hash to positions of the embeddings in that bucket
+type HashTable = AHashMap<u32, Vec<usize>>;
+
+/// Item found by [`EmbeddingIndex::nearest`] with its cosine similarity
+pub type Neighbor<'a, T> = (&'a T, f64);
+
+/// Approximate nearest-neighbor index over fixed-dimension embeddings
+///
+/// Each of the index's hash tables buckets embeddings by the signs of their
+/// projections onto random hyperplanes, after centering on the mean embedding.
+/// A query probes its own bucket and every bucket one bit away in each table,
+/// then ranks the union by exact cosine similarity. When the probes find fewer
+/// candidates than requested, the index falls back to an exact scan, so small
+/// vocabularies always get exact answers.
+#[derive(Debug, Clone)]
+pub struct EmbeddingIndex<T> {
+    dimension: usize,
+    embeddings: Vec<Vec<f32>>,
+    items: Vec<T>,
+    mean: Vec<f32>,
+    hyperplanes: Vec<Vec<f32>>,
+    tables: Vec<HashTable>,
+}
+
+impl<T> EmbeddingIndex<T> {
+    /// Build an index over `(embedding, item)` pairs of length `dimension`
+    pub fn build(
+        dimension: usize,
+        entries: impl IntoIterator<Item = (Vec<f32>, T)>,
+    ) -> AiResult<Self> {
+        let (embeddings, items): (Vec<_>, Vec<_>) = entries.into_iter().unzip();
+        if let Some(bad) = embeddings.iter().find(|e| e.len() != dimension) {
+            return Err(AiError::InvalidPattern {
+                reason: format!(
+                    "Embedding has {} dimensions, index expects {}",
+                    bad.len(),
+                    dimension
+                ),
+            });
+        }
+
+        let mut mean = vec![0.0f32; dimension];
+        for embedding in &embeddings {
+            mean.iter_mut().zip(embedding).for_each(|(m, x)| *m += x);
+        }
+        if !embeddings.is_empty() {
+            mean.iter_mut().for_each(|m| *m /= embeddings.len() as f32);
+        }
+
+        let mut state = LSH_SEED;
+        let hyperplanes = (0..LSH_TABLES * LSH_BITS)
+            .map(|_| (0..dimension).map(|_| gaussian(&mut state)).collect())
+            .collect();
+
+        let mut index = Self {
+            dimension,
+            embeddings,
+            items,
+            mean,
+            hyperplanes,
+            tables: vec![AHashMap::new(); LSH_TABLES],
+        };
+        for position in 0..index.embeddings.len() {
+            for table in 0..LSH_TABLES {
+                let hash = index.hash(table, &index.embeddings[position]);
+                index.tables[table].entry(hash).or_default().push(position);
+            }
+        }
+        Ok(index)
+    }
+
+    /// Number of indexed items
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Check if the index holds no items
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Embedding dimension accepted by [`Self::nearest`]
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    /// Up to `k` items most similar to `query`, best first, with cosine similarity
+    ///
+    /// Items for which `exclude` returns true are skipped, which lets callers drop
+    /// the query itself.
+    pub fn nearest(
+        &self,
+        query: &[f32],
+        k: usize,
+        exclude: impl Fn(&T) -> bool,
+    ) -> AiResult<Vec<Neighbor<'_, T>>> {
+        if query.len() != self.dimension {
+            return Err(AiError::InvalidPattern {
+                reason: format!(
+                    "Query has {} dimensions, index expects {}",
+                    query.len(),
+                    self.dimension
+                ),
+            });
+        }
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut candidates = vec![false; self.items.len()];
+        for (table, buckets) in self.tables.iter().enumerate() {
+            let hash = self.hash(table, query);
+            for probe in std::iter::once(hash).chain((0..LSH_BITS).map(|bit| hash ^ (1 << bit))) {
+                for &position in buckets.get(&probe).into_iter().flatten() {
+                    candidates[position] = true;
+                }
+            }
+        }
+
+        let rank = |positions: &mut dyn Iterator<Item = usize>| {
+            let mut scored: Vec<(usize, f64)> = positions
+                .filter(|&position| !exclude(&self.items[position]))
+                .map(|position| {
+                    (
+                        position,
+                        cosine_similarity(query, &self.embeddings[position]),
+                    )
+                })
+                .collect();
+            scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+            scored.truncate(k);
+            scored
+        };
+
+        let mut ranked = rank(&mut (0..self.items.len()).filter(|&p| candidates[p]));
+        if ranked.len() < k {
+            ranked = rank(&mut (0..self.items.len()));
+        }
+        Ok(ranked
+            .into_iter()
+            .map(|(position, similarity)| (&self.items[position], similarity))
+            .collect())
+    }
+
+    /// Bucket of `embedding` in hash table `table`
+    fn hash(&self, table: usize, embedding: &[f32]) -> u32 {
+        let planes = &self.hyperplanes[table * LSH_BITS..(table + 1) * LSH_BITS];
+        planes.iter().enumerate().fold(0, |hash, (bit, plane)| {
+            let projection: f32 = plane
+                .iter()
+                .zip(embedding.iter().zip(&self.mean))
+                .map(|(p, (x, m))| p * (x - m))
+                .sum();
+            if projection >= 0.0 {
+                hash | (1 << bit)
+            } else {
+                hash
+            }
+        })
+    }
+}
+
+/// How [`embed_progression`] summarizes a progression
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ProgressionEmbeddingMode {
+    /// Mean of the chord embeddings: same chords in any order embed alike
+    Pooled,
+    /// Hashed chord-to-chord motion: same root movement in any key embeds alike
+    SequenceHashed,
+    /// Both blocks with equal weight
+    #[default]
+    Combined,
+}
+
+/// Embed a whole progression.
+///
+/// The pooled block averages [`embed_chord`] over the non-rest chords. The
+/// sequence block feature-hashes every transition (root motion with both chord
+/// types, and the exact chord pair) and every pair of consecutive root motions,
+/// so it captures order that pooling discards. Each block is normalized before
+/// they are combined according to `mode`; a progression without chords embeds
+/// as the zero vector.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::{cosine_similarity, embed_progression, ProgressionEmbeddingMode};
+/// use composer_core::Chord;
+///
+/// let chords = |roots: &[u8]| -> Vec<Chord> {
+///     roots.iter().map(|&root| Chord::new(root, 5).unwrap()).collect()
+/// };
+/// let pop = embed_progression(&chords(&[1, 5, 6, 4]), ProgressionEmbeddingMode::Combined)?;
+/// let rotated = embed_progression(&chords(&[6, 4, 1, 5]), ProgressionEmbeddingMode::Combined)?;
+/// let blues = embed_progression(&chords(&[1, 4, 1, 5]), ProgressionEmbeddingMode::Combined)?;
+///
+/// assert!(cosine_similarity(&pop, &rotated) > cosine_similarity(&pop, &blues));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn embed_progression(
+    progression: &[Chord],
+    mode: ProgressionEmbeddingMode,
+) -> AiResult<ProgressionEmbedding> {
+    let mut embedding = [0.0f32; PROGRESSION_EMBEDDING_DIM];
+    let chords: Vec<&Chord> = progression.iter().filter(|c| !c.is_rest).collect();
+    let (pooled, sequence) = embedding.split_at_mut(CHORD_EMBEDDING_DIM);
+
+    if mode != ProgressionEmbeddingMode::SequenceHashed {
+        for chord in &chords {
+            let chord_embedding = embed_chord(chord)?;
+            pooled
+                .iter_mut()
+                .zip(chord_embedding)
+                .for_each(|(p, x)| *p += x);
+        }
+        normalize(pooled);
+    }
+
+    if mode != ProgressionEmbeddingMode::Pooled {
+        let motion = |a: &Chord, b: &Chord| (b.root as i32 - a.root as i32).rem_euclid(7);
+        for pair in chords.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            let interval = motion(a, b);
+            add_hashed_feature(
+                sequence,
+                format!("motion:{}:{}:{}", interval, a.chord_type, b.chord_type).as_bytes(),
+                1.0,
+            );
+            if let (Ok(from), Ok(to)) = (serialize_chord(a), serialize_chord(b)) {
+                add_hashed_feature(sequence, &[&b"pair:"[..], &from, &to].concat(), 0.5);
+            }
+        }
+        for triple in chords.windows(3) {
+            let feature = format!(
+                "motion2:{}:{}",
+                motion(triple[0], triple[1]),
+                motion(triple[1], triple[2])
+            );
+            add_hashed_feature(sequence, feature.as_bytes(), 0.5);
+        }
+        normalize(sequence);
+    }
+
+    if mode == ProgressionEmbeddingMode::Combined {
+        normalize(&mut embedding);
+    }
+    Ok(embedding)
+}
+
+/// Add a signed hashed feature to `buckets`
+fn add_hashed_feature(buckets: &mut [f32], feature: &[u8], weight: f32) {
+    // FNV-1a, then a multiplicative mix so bucket and sign bits are independent
+    let hash = feature
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325u64, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        });
+    let mixed = hash.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    let bucket = (mixed >> 32) as usize % buckets.len();
+    let sign = if mixed & 1 == 0 { 1.0 } else { -1.0 };
+    buckets[bucket] += sign * weight;
+}
+
+/// Approximately normal sample from a xorshift stream (sum of four uniforms)
+fn gaussian(state: &mut u64) -> f32 {
+    let mut sum = 0.0;
+    for _ in 0..4 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        sum += (*state >> 11) as f64 / (1u64 << 53) as f64;
+    }
+    (sum - 2.0) as f32
+}
+
+/// Chord found by [`ChordIndex::similar_chords`] or
+/// [`ChordEmbeddingTable::nearest_chords`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimilarChord {
+    /// The similar chord
+    pub chord: Chord,
+
+    /// Cosine similarity to the query (-1.0 to 1.0, 1.0 = identical embeddings)
+    pub similarity: f64,
+
+    /// Occurrences of the chord in the indexed corpus
+    pub frequency: u64,
+}
+
+/// Vocabulary entry stored in a [`ChordIndex`]
+#[derive(Debug, Clone)]
+struct VocabularyChord {
+    key: ChordBinary,
+    chord: Chord,
+    frequency: u64,
+}
+
+/// Nearest-neighbor index over the chord vocabulary of a corpus
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::ChordIndex;
+/// use composer_core::Chord;
+///
+/// let vocabulary = vec![
+///     (Chord::new(1, 5)?, 10),
+///     (Chord::new(6, 5)?, 4),
+///     (Chord::new(3, 5)?, 2),
+///     (Chord::new(5, 7)?, 8),
+/// ];
+/// let index = ChordIndex::build(vocabulary)?;
+///
+/// let similar = index.similar_chords(&Chord::new(1, 7)?, 2)?;
+/// assert_eq!(similar.len(), 2);
+/// assert_eq!(similar[0].chord, Chord::new(1, 5)?);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`crate::AiEngine::similar_chords`] - Search the engine's trained vocabulary
+/// - [`embed_chord`] - The embedding the index is built on
+#[derive(Debug, Clone)]
+pub struct ChordIndex {
+    index: EmbeddingIndex<VocabularyChord>,
+}
+
+impl ChordIndex {
+    /// Build an index over `(chord, corpus frequency)` pairs.
+    ///
+    /// Rests are skipped and duplicate chords merged, summing their frequencies.
+    pub fn build(vocabulary: impl IntoIterator<Item = (Chord, u64)>) -> AiResult<Self> {
+        let mut merged: AHashMap<ChordBinary, VocabularyChord> = AHashMap::new();
+        for (chord, frequency) in vocabulary {
+            if chord.is_rest {
+                continue;
+            }
+            let key = serialize_chord(&chord).map_err(|e| AiError::InvalidPattern {
+                reason: format!("Cannot index chord {}: {}", chord, e),
+            })?;
+            merged
+                .entry(key)
+                .or_insert_with(|| VocabularyChord {
+                    key,
+                    chord,
+                    frequency: 0,
+                })
+                .frequency += frequency;
+        }
+
+        let mut entries: Vec<VocabularyChord> = merged.into_values().collect();
+        entries.sort_unstable_by_key(|entry| entry.key);
+        let entries = entries
+            .into_iter()
+            .map(|entry| Ok((embed_chord(&entry.chord)?.to_vec(), entry)))
+            .collect::<AiResult<Vec<_>>>()?;
+
+        Ok(Self {
+            index: EmbeddingIndex::build(CHORD_EMBEDDING_DIM, entries)?,
+        })
+    }
+
+    /// Build an index from serialized chords, as returned by
+    /// [`crate::ChordProgressionTrie::chord_vocabulary`]
+    pub fn from_binary_vocabulary(vocabulary: &[(ChordBinary, u64)]) -> AiResult<Self> {
+        let chords = vocabulary
+            .iter()
+            .map(|(key, frequency)| {
+                deserialize_chord(key)
+                    .map(|chord| (chord, *frequency))
+                    .map_err(|e| AiError::DataCorruption {
+                        details: format!("Invalid chord key in vocabulary: {}", e),
+                    })
+            })
+            .collect::<AiResult<Vec<_>>>()?;
+        Self::build(chords)
+    }
+
+    /// Number of distinct chords in the index
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Check if the index holds no chords
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Up to `k` vocabulary chords closest to `chord`, excluding `chord` itself.
+    ///
+    /// Ties in similarity are broken by corpus frequency, then by serialized chord.
+    pub fn similar_chords(&self, chord: &Chord, k: usize) -> AiResult<Vec<SimilarChord>> {
+        let query = embed_chord(chord)?;
+        let query_key = serialize_chord(chord).ok();
+
+        // Over-fetch so frequency tie-breaks can reorder equal similarities
+        let mut similar: Vec<SimilarChord> = self
+            .index
+            .nearest(&query, k.saturating_mul(2), |entry| {
+                Some(entry.key) == query_key
+            })?
+            .into_iter()
+            .map(|(entry, similarity)| SimilarChord {
+                chord: entry.chord.clone(),
+                similarity,
+                frequency: entry.frequency,
+            })
+            .collect();
+        similar.sort_by(|a, b| {
+            b.similarity
+                .total_cmp(&a.similarity)
+                .then(b.frequency.cmp(&a.frequency))
+        });
+        similar.truncate(k);
+        Ok(similar)
+    }
+}
+
+/// Where the vectors of a [`ChordEmbeddingTable`] came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EmbeddingSource {
+    /// Derived from which chords precede and follow each other in a corpus
+    Cooccurrence,
+    /// Supplied by the caller, typically learned offline
+    Learned,
+}
+
+/// Chord stored in a [`ChordEmbeddingTable`] with its unit-norm vector
+#[derive(Debug, Clone)]
+struct TableEntry {
+    key: ChordBinary,
+    chord: Chord,
+    vector: Vec<f32>,
+    frequency: u64,
+}
+
+/// Embedding vectors for a chord vocabulary, by usage rather than pitch content
+///
+/// [`embed_chord`] places chords by the notes they contain, so a ii and a IV
+/// are close because they share two tones. A table instead places chords by how
+/// they are used: built from a trie, each chord's vector records which chords
+/// precede and follow it, weighted by positive pointwise mutual information, so
+/// chords that appear in the same contexts end up close. Tables learned offline
+/// can be supplied directly. Chords missing from the table are compared by pitch
+/// content instead.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::{ChordEmbeddingTable, ChordProgressionTrie};
+/// use composer_core::Chord;
+///
+/// let trie = ChordProgressionTrie::new();
+/// for (pattern, id) in [([1, 2, 5], "ii"), ([1, 4, 5], "IV"), ([1, 6, 3], "vi")] {
+///     let chords = pattern.iter().map(|r| Chord::triad(*r)).collect::<Result<Vec<_>, _>>()?;
+///     trie.add_pattern(&chords, id.to_string(), None)?;
+/// }
+/// let table = ChordEmbeddingTable::from_trie(&trie)?;
+///
+/// // ii and IV both lead from I to V
+/// let ii = Chord::triad(2)?;
+/// assert_eq!(table.nearest_chords(&ii, 1)?[0].chord, Chord::triad(4)?);
+/// assert!(table.chord_similarity(&ii, &Chord::triad(4)?)? > table.chord_similarity(&ii, &Chord::triad(6)?)?);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`crate::AiEngine::nearest_chords`] - Search the engine's table
+/// - [`ChordIndex`] - Nearest neighbors by pitch content
+#[derive(Debug, Clone)]
+pub struct ChordEmbeddingTable {
+    source: EmbeddingSource,
+    dimension: usize,
+    entries: Vec<TableEntry>,
+    positions: AHashMap<ChordBinary, usize>,
+}
+
+/// Dimension of the vectors of a co-occurrence table
+const COOCCURRENCE_DIM: usize = 64;
+
+/// Chord, raw vector and corpus frequency of a table entry before indexing
+type TableRow = (Chord, Vec<f32>, u64);
+
+impl ChordEmbeddingTable {
+    /// Table over the chords of a trie, from the chords around each one.
+    ///
+    /// Chords that never sit next to another chord have no usage to embed and
+    /// are left out.
+    pub fn from_trie(trie: &ChordProgressionTrie) -> AiResult<Self> {
+        let transitions = trie.chord_transitions();
+        let mut outgoing: AHashMap<ChordBinary, u64> = AHashMap::new();
+        let mut incoming: AHashMap<ChordBinary, u64> = AHashMap::new();
+        for (from, to, count) in &transitions {
+            *outgoing.entry(*from).or_insert(0) += count;
+            *incoming.entry(*to).or_insert(0) += count;
+        }
+        let total: u64 = transitions.iter().map(|(_, _, count)| count).sum();
+
+        // The same PMI weights "to follows from" in both chords' vectors
+        let mut vectors: AHashMap<ChordBinary, Vec<f32>> = AHashMap::new();
+        for (from, to, count) in &transitions {
+            let pmi = (*count as f64 * total as f64 / (outgoing[from] * incoming[to]) as f64).ln();
+            if pmi <= 0.0 {
+                continue;
+            }
+            let feature = |direction: &[u8], chord: &ChordBinary| [direction, chord].concat();
+            add_hashed_feature(
+                vectors
+                    .entry(*from)
+                    .or_insert_with(|| vec![0.0; COOCCURRENCE_DIM]),
+                &feature(b"next:", to),
+                pmi as f32,
+            );
+            add_hashed_feature(
+                vectors
+                    .entry(*to)
+                    .or_insert_with(|| vec![0.0; COOCCURRENCE_DIM]),
+                &feature(b"previous:", from),
+                pmi as f32,
+            );
+        }
+
+        let frequencies: AHashMap<ChordBinary, u64> = trie.chord_vocabulary().into_iter().collect();
+        let mut entries = Vec::with_capacity(vectors.len());
+        for (key, vector) in vectors {
+            let chord = deserialize_chord(&key).map_err(|e| AiError::DataCorruption {
+                details: format!("Cannot decode stored chord: {}", e),
+            })?;
+            let frequency = frequencies.get(&key).copied().unwrap_or(0);
+            entries.push((chord, vector, frequency));
+        }
+        Self::build(EmbeddingSource::Cooccurrence, COOCCURRENCE_DIM, entries)
+    }
+
+    /// Table of vectors supplied by the caller, e.g. learned offline.
+    ///
+    /// Every vector must have the same non-zero dimension and finite values;
+    /// vectors are normalized to unit length and zero vectors are left out.
+    /// Rests are skipped and a chord listed twice keeps its last vector.
+    pub fn from_vectors(vectors: impl IntoIterator<Item = (Chord, Vec<f32>)>) -> AiResult<Self> {
+        let mut dimension = None;
+        let mut entries = Vec::new();
+        for (chord, vector) in vectors {
+            if chord.is_rest {
+                continue;
+            }
+            let expected = *dimension.get_or_insert(vector.len());
+            if vector.is_empty() || vector.len() != expected {
+                return Err(AiError::InvalidModel {
+                    reason: format!(
+                        "Embedding of {} has dimension {}, expected {}",
+                        chord,
+                        vector.len(),
+                        expected
+                    ),
+                });
+            }
+            if vector.iter().any(|x| !x.is_finite()) {
+                return Err(AiError::InvalidModel {
+                    reason: format!("Embedding of {} has a non-finite value", chord),
+                });
+            }
+            entries.push((chord, vector, 0));
+        }
+        Self::build(EmbeddingSource::Learned, dimension.unwrap_or(0), entries)
+    }
+
+    fn build(source: EmbeddingSource, dimension: usize, vectors: Vec<TableRow>) -> AiResult<Self> {
+        let mut table = Self {
+            source,
+            dimension,
+            entries: Vec::with_capacity(vectors.len()),
+            positions: AHashMap::new(),
+        };
+        for (chord, mut vector, frequency) in vectors {
+            normalize(&mut vector);
+            if vector.iter().all(|x| *x == 0.0) {
+                continue;
+            }
+            let key = serialize_chord(&chord).map_err(|e| AiError::InvalidPattern {
+                reason: format!("Cannot index chord {}: {}", chord, e),
+            })?;
+            let entry = TableEntry {
+                key,
+                chord,
+                vector,
+                frequency,
+            };
+            match table.positions.get(&key) {
+                Some(&position) => table.entries[position] = entry,
+                None => {
+                    table.positions.insert(key, table.entries.len());
+                    table.entries.push(entry);
+                },
+            }
+        }
+        Ok(table)
+    }
+
+    /// Where the vectors came from
+    pub fn source(&self) -> EmbeddingSource {
+        self.source
+    }
+
+    /// Number of chords with a vector
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the table has no vectors
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Length of each vector
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    /// Unit-norm vector of `chord`, if the table has one
+    pub fn embedding(&self, chord: &Chord) -> Option<&[f32]> {
+        let key = serialize_chord(chord).ok()?;
+        self.positions
+            .get(&key)
+            .map(|&position| self.entries[position].vector.as_slice())
+    }
+
+    /// Cosine similarity of two chords (-1.0 to 1.0).
+    ///
+    /// Uses the table's vectors when it has both chords and their pitch content
+    /// ([`embed_chord`]) otherwise.
+    pub fn chord_similarity(&self, a: &Chord, b: &Chord) -> AiResult<f64> {
+        match (self.embedding(a), self.embedding(b)) {
+            (Some(a), Some(b)) => Ok(cosine_similarity(a, b)),
+            _ => Ok(cosine_similarity(&embed_chord(a)?, &embed_chord(b)?)),
+        }
+    }
+
+    /// Up to `k` chords of the table closest to `chord`, excluding `chord` itself.
+    ///
+    /// A chord missing from the table is compared with the others by pitch
+    /// content. Ties in similarity are broken by corpus frequency, then by
+    /// serialized chord.
+    pub fn nearest_chords(&self, chord: &Chord, k: usize) -> AiResult<Vec<SimilarChord>> {
+        let query_key = serialize_chord(chord).ok();
+        let query = match self.embedding(chord) {
+            Some(vector) => vector.to_vec(),
+            None => embed_chord(chord)?.to_vec(),
+        };
+        let in_table = self.embedding(chord).is_some();
+
+        let mut nearest: Vec<(&TableEntry, f64)> = self
+            .entries
+            .iter()
+            .filter(|entry| Some(entry.key) != query_key)
+            .filter_map(|entry| {
+                let similarity = if in_table {
+                    cosine_similarity(&query, &entry.vector)
+                } else {
+                    cosine_similarity(&query, &embed_chord(&entry.chord).ok()?)
+                };
+                Some((entry, similarity))
+            })
+            .collect();
+        nearest.sort_by(|(a, a_similarity), (b, b_similarity)| {
+            b_similarity
+                .total_cmp(a_similarity)
+                .then(b.frequency.cmp(&a.frequency))
+                .then(a.key.cmp(&b.key))
+        });
+
+        Ok(nearest
+            .into_iter()
+            .take(k)
+            .map(|(entry, similarity)| SimilarChord {
+                chord: entry.chord.clone(),
+                similarity,
+                frequency: entry.frequency,
+            })
+            .collect())
+    }
+}
+
+/// Stored progression found by [`ProgressionIndex`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimilarProgression {
+    /// Source identifier the progression was stored under
+    pub source_id: String,
+
+    /// Key recorded with the source, if any
+    pub key_tonic: Option<Key>,
+
+    /// The stored progression
+    pub progression: Vec<Chord>,
+
+    /// Cosine similarity to the query (-1.0 to 1.0)
+    pub similarity: f64,
+}
+
+/// Progression entry stored in a [`ProgressionIndex`]
+#[derive(Debug, Clone)]
+struct IndexedProgression {
+    source_id: String,
+    key_tonic: Option<Key>,
+    progression: Vec<Chord>,
+}
+
+/// Nearest-neighbor index over stored progressions, for "more like this" search
+///
+/// Results are per source: when a source stored several progressions, only its
+/// best match is reported.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::{ProgressionEmbeddingMode, ProgressionIndex};
+/// use composer_core::Chord;
+///
+/// let chords = |roots: &[u8]| -> Vec<Chord> {
+///     roots.iter().map(|&root| Chord::new(root, 5).unwrap()).collect()
+/// };
+/// let catalog = vec![
+///     (chords(&[1, 5, 6, 4]), "axis".to_string(), Some("C".parse()?)),
+///     (chords(&[6, 4, 1, 5]), "axis-rotated".to_string(), None),
+///     (chords(&[1, 4, 1, 5]), "blues".to_string(), None),
+/// ];
+/// let index = ProgressionIndex::build(catalog, ProgressionEmbeddingMode::Combined)?;
+///
+/// let similar = index.more_like_source("axis", 1)?;
+/// assert_eq!(similar[0].source_id, "axis-rotated");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`crate::AiEngine::similar_progressions`] - Search the engine's stored patterns
+/// - [`crate::AiEngine::more_like_source`] - Recommend sources similar to a source
+/// - [`embed_progression`] - The embedding the index is built on
+#[derive(Debug, Clone)]
+pub struct ProgressionIndex {
+    index: EmbeddingIndex<IndexedProgression>,
+    mode: ProgressionEmbeddingMode,
+}
+
+impl ProgressionIndex {
+    /// Build an index over `(progression, source id, key)` patterns.
+    ///
+    /// Progressions without chords are skipped.
+    pub fn build(
+        patterns: impl IntoIterator<Item = TrainingPattern>,
+        mode: ProgressionEmbeddingMode,
+    ) -> AiResult<Self> {
+        let mut entries = Vec::new();
+        for (progression, source_id, key_tonic) in patterns {
+            if progression.iter().all(|chord| chord.is_rest) {
+                continue;
+            }
+            let embedding = embed_progression(&progression, mode)?.to_vec();
+            entries.push((
+                embedding,
+                IndexedProgression {
+                    source_id,
+                    key_tonic,
+                    progression,
+                },
+            ));
+        }
+
+        Ok(Self {
+            index: EmbeddingIndex::build(PROGRESSION_EMBEDDING_DIM, entries)?,
+            mode,
+        })
+    }
+
+    /// Build an index over the complete progressions stored in a trie
+    pub fn from_trie(
+        trie: &ChordProgressionTrie,
+        mode: ProgressionEmbeddingMode,
+    ) -> AiResult<Self> {
+        let patterns = trie
+            .stored_progressions()
+            .into_iter()
+            .map(|(reference, keys)| {
+                let progression = keys
+                    .iter()
+                    .map(deserialize_chord)
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| AiError::DataCorruption {
+                        details: format!("Invalid chord key in stored pattern: {}", e),
+                    })?;
+                Ok((progression, reference.id, reference.key_tonic))
+            })
+            .collect::<AiResult<Vec<_>>>()?;
+        Self::build(patterns, mode)
+    }
+
+    /// Number of indexed progressions
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Check if the index holds no progressions
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Up to `k` sources whose progressions are most similar to `progression`
+    pub fn similar_progressions(
+        &self,
+        progression: &[Chord],
+        k: usize,
+    ) -> AiResult<Vec<SimilarProgression>> {
+        let query = embed_progression(progression, self.mode)?;
+        self.best_per_source(&query, k, |_| false)
+    }
+
+    /// Up to `k` other sources most similar to the progressions of `source_id`
+    ///
+    /// The query is the mean embedding of every progression the source stored.
+    pub fn more_like_source(&self, source_id: &str, k: usize) -> AiResult<Vec<SimilarProgression>> {
+        let mut query = [0.0f32; PROGRESSION_EMBEDDING_DIM];
+        let mut found = false;
+        let stored = self.index.items.iter().zip(&self.index.embeddings);
+        for (_, embedding) in stored.filter(|(entry, _)| entry.source_id == source_id) {
+            query.iter_mut().zip(embedding).for_each(|(q, x)| *q += x);
+            found = true;
+        }
+        if !found {
+            return Err(AiError::InvalidPattern {
+                reason: format!("Unknown source: {}", source_id),
+            });
+        }
+        normalize(&mut query);
+        self.best_per_source(&query, k, |entry| entry.source_id == source_id)
+    }
+
+    /// Nearest entries collapsed to the best one per source
+    fn best_per_source(
+        &self,
+        query: &[f32],
+        k: usize,
+        exclude: impl Fn(&IndexedProgression) -> bool,
+    ) -> AiResult<Vec<SimilarProgression>> {
+        let mut fetch = k.max(1);
+        loop {
+            let neighbors = self.index.nearest(query, fetch, &exclude)?;
+            let exhausted = neighbors.len() < fetch;
+
+            let mut seen = AHashSet::new();
+            let results: Vec<SimilarProgression> = neighbors
+                .into_iter()
+                .filter(|(entry, _)| seen.insert(entry.source_id.as_str()))
+                .take(k)
+                .map(|(entry, similarity)| SimilarProgression {
+                    source_id: entry.source_id.clone(),
+                    key_tonic: entry.key_tonic,
+                    progression: entry.progression.clone(),
+                    similarity,
+                })
+                .collect();
+
+            if results.len() >= k || exhausted {
+                return Ok(results);
+            }
+            fetch *= 2;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embed_chord() {
+        let tonic = embed_chord(&Chord::new(1, 5).unwrap()).unwrap();
+        let norm: f32 = tonic.iter().map(|x| x * x).sum();
+        assert!((norm - 1.0).abs() < 1e-5);
+        // C, E and G with the bass doubled
+        assert!(tonic[0] > tonic[4] && tonic[4] > 0.0 && tonic[7] > 0.0);
+        assert_eq!(tonic[1], 0.0);
+
+        let first_inversion = embed_chord(&Chord::new(1, 5).unwrap().with_inversion(1).unwrap());
+        assert!(first_inversion.unwrap()[4] > tonic[4]);
+
+        assert_eq!(
+            embed_chord(&Chord::rest()).unwrap(),
+            [0.0; CHORD_EMBEDDING_DIM]
+        );
+        assert_eq!(degree_pitch_class("b3"), Some(3));
+        assert_eq!(degree_pitch_class("#11"), Some(6));
+        assert_eq!(degree_pitch_class("x"), None);
+    }
+
+    #[test]
+    fn test_similar_chords_orders_by_shared_tones() {
+        let vocabulary: Vec<(Chord, u64)> = (1..=7)
+            .flat_map(|root| {
+                [
+                    (Chord::new(root, 5).unwrap(), 1),
+                    (Chord::new(root, 7).unwrap(), 1),
+                ]
+            })
+            .collect();
+        let index = ChordIndex::build(vocabulary).unwrap();
+        assert_eq!(index.len(), 14);
+
+        let similar = index.similar_chords(&Chord::new(1, 5).unwrap(), 3).unwrap();
+        assert_eq!(similar.len(), 3);
+        assert!(similar.iter().all(|s| s.chord != Chord::new(1, 5).unwrap()));
+        // I7 keeps every triad tone, so it must rank first
+        assert_eq!(similar[0].chord, Chord::new(1, 7).unwrap());
+        assert!(similar
+            .windows(2)
+            .all(|w| w[0].similarity >= w[1].similarity));
+    }
+
+    #[test]
+    fn test_embed_progression_modes() {
+        let chords = |roots: &[u8]| -> Vec<Chord> {
+            roots
+                .iter()
+                .map(|&root| Chord::new(root, 5).unwrap())
+                .collect()
+        };
+        let forward = chords(&[1, 4, 5, 1]);
+        let reversed = chords(&[1, 5, 4, 1]);
+
+        // Pooling ignores order, sequence hashing does not
+        let pooled = |p: &[Chord]| embed_progression(p, ProgressionEmbeddingMode::Pooled).unwrap();
+        assert!((cosine_similarity(&pooled(&forward), &pooled(&reversed)) - 1.0).abs() < 1e-6);
+        let hashed =
+            |p: &[Chord]| embed_progression(p, ProgressionEmbeddingMode::SequenceHashed).unwrap();
+        assert!(cosine_similarity(&hashed(&forward), &hashed(&reversed)) < 0.9);
+        assert!(pooled(&forward)[CHORD_EMBEDDING_DIM..]
+            .iter()
+            .all(|&x| x == 0.0));
+
+        // Root motion is transposition invariant: I-IV-V and II-V-VI share motion features
+        let transposed = chords(&[2, 5, 6, 2]);
+        assert!(
+            cosine_similarity(&hashed(&forward), &hashed(&transposed))
+                > cosine_similarity(&hashed(&forward), &hashed(&reversed))
+        );
+
+        let combined = embed_progression(&forward, ProgressionEmbeddingMode::Combined).unwrap();
+        let norm: f32 = combined.iter().map(|x| x * x).sum();
+        assert!((norm - 1.0).abs() < 1e-5);
+        assert_eq!(
+            embed_progression(&[Chord::rest()], ProgressionEmbeddingMode::Combined).unwrap(),
+            [0.0; PROGRESSION_EMBEDDING_DIM]
+        );
+    }
+
+    #[test]
+    fn test_progression_index_best_per_source() {
+        let chords = |roots: &[u8]| -> Vec<Chord> {
+            roots
+                .iter()
+                .map(|&root| Chord::new(root, 5).unwrap())
+                .collect()
+        };
+        let catalog = vec![
+            (chords(&[1, 5, 6, 4]), "a".to_string(), None),
+            (chords(&[1, 5, 6, 4, 1]), "a".to_string(), None),
+            (
+                chords(&[6, 4, 1, 5]),
+                "b".to_string(),
+                Some("G".parse().unwrap()),
+            ),
+            (chords(&[2, 5, 1]), "c".to_string(), None),
+        ];
+        let index = ProgressionIndex::build(catalog, ProgressionEmbeddingMode::Combined).unwrap();
+        assert_eq!(index.len(), 4);
+
+        let similar = index
+            .similar_progressions(&chords(&[1, 5, 6, 4]), 3)
+            .unwrap();
+        let sources: Vec<&str> = similar.iter().map(|s| s.source_id.as_str()).collect();
+        assert_eq!(sources, ["a", "b", "c"]);
+        assert!((similar[0].similarity - 1.0).abs() < 1e-6);
+
+        let more = index.more_like_source("a", 5).unwrap();
+        assert_eq!(more.len(), 2);
+        assert_eq!(more[0].source_id, "b");
+        assert_eq!(more[0].key_tonic, Some("G".parse().unwrap()));
+        assert!(index.more_like_source("missing", 1).is_err());
+    }
+
+    #[test]
+    fn test_embedding_index_nearest() {
+        let mut state = 7u64;
+        let points: Vec<_> = (0..500usize)
+            .map(|i| ((0..8).map(|_| gaussian(&mut state)).collect(), i))
+            .collect();
+        let index = EmbeddingIndex::build(8, points.clone()).unwrap();
+
+        for (query, _) in points.iter().take(20) {
+            let found = index.nearest(query, 5, |_| false).unwrap();
+            assert_eq!(found.len(), 5);
+            // The query itself is always its own nearest neighbor
+            assert!((found[0].1 - 1.0).abs() < 1e-9);
+        }
+
+        assert!(index.nearest(&[0.0; 3], 1, |_| false).is_err());
+        assert!(EmbeddingIndex::build(2, vec![(vec![1.0], ())]).is_err());
+    }
+
+    #[test]
+    fn test_learned_embedding_table() {
+        let triad = |root| Chord::triad(root).unwrap();
+        let table = ChordEmbeddingTable::from_vectors(vec![
+            (triad(1), vec![2.0, 0.0]),
+            (triad(4), vec![1.0, 1.0]),
+            (triad(5), vec![0.0, 3.0]),
+            (triad(6), vec![0.0, 0.0]),
+            (Chord::rest(), vec![1.0]),
+        ])
+        .unwrap();
+        assert_eq!(table.source(), EmbeddingSource::Learned);
+        assert_eq!((table.len(), table.dimension()), (3, 2));
+        assert_eq!(table.embedding(&triad(1)), Some(&[1.0, 0.0][..]));
+        assert!(table.embedding(&triad(6)).is_none());
+
+        let similarity = table.chord_similarity(&triad(1), &triad(4)).unwrap();
+        assert!((similarity - std::f64::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+        assert_eq!(table.chord_similarity(&triad(1), &triad(5)).unwrap(), 0.0);
+        // Chords outside the table compare by pitch content
+        let pitch = cosine_similarity(
+            &embed_chord(&triad(1)).unwrap(),
+            &embed_chord(&triad(6)).unwrap(),
+        );
+        assert_eq!(table.chord_similarity(&triad(1), &triad(6)).unwrap(), pitch);
+
+        let nearest = table.nearest_chords(&triad(1), 5).unwrap();
+        assert_eq!(nearest.len(), 2);
+        assert_eq!(nearest[0].chord, triad(4));
+        // vi shares no tones with V
+        let from_outside = table.nearest_chords(&triad(6), 3).unwrap();
+        assert_eq!(from_outside[2].chord, triad(5));
+
+        assert!(ChordEmbeddingTable::from_vectors(vec![
+            (triad(1), vec![1.0]),
+            (triad(2), vec![1.0, 0.0]),
+        ])
+        .is_err());
+        assert!(ChordEmbeddingTable::from_vectors(vec![(triad(1), vec![f32::NAN])]).is_err());
+    }
+}
@@ -4,20 +4,69 @@
 //! bass line harmonization, and advanced musical analysis using machine learning models
 //! and statistical analysis.
 
-use crate::analysis::{DifficultyAssessment, MusicalAnalyzer, ProgressionAnalysis};
+use crate::accompaniment::{render_accompaniment, AccompanimentOptions};
+use crate::analysis::{
+    ChordDuration, DifficultyAssessment, DifficultyCalibration, HarmonicRhythm, MusicalAnalyzer,
+    PolynomialModel, ProgressionAnalysis, SkillLevel, SkillThresholds,
+};
+use crate::arena::TrieArena;
+use crate::bass::{walking_bass_line, SwingFeel};
+use crate::budget::{BudgetViolations, PerformanceBudget};
+use crate::compatibility::{
+    AssetCompatibility, AssetHeader, AssetKind, AssetMigration, Compatibility, CompatibilityReport,
+    MigrationRegistry, ModelVersion, VersionedAsset,
+};
+use crate::continuation::{continue_progression, ContinuationConfig};
+use crate::difficulty_model::{DifficultyFeatures, SharedDifficultyModel};
+use crate::embedding::{
+    ChordEmbeddingTable, ChordIndex, EmbeddingSource, ProgressionEmbeddingMode, ProgressionIndex,
+    SimilarChord, SimilarProgression,
+};
 use crate::error::{AiError, AiResult};
+use crate::form::{timed_chords, FormAnalysis};
+use crate::genre::{restyle_progression, Genre, RestyledProgression};
+use crate::groove::{suggest_groove, Groove, GrooveOptions};
+use crate::instrument::DifficultyProfile;
+use crate::melody::MelodicAnalysis;
+use crate::neural::SharedNeuralScorer;
+use crate::ngram::SharedSuggestionBackend;
+use crate::novelty::{compute_novelty, NoveltyReport};
+use crate::practice::{PracticePlan, ProgressionRewrite};
+use crate::session::{FeedbackKind, Session, SessionOutcome, SessionQuery, SessionRecorder};
 use crate::suggestions::{
-    ChordProgressionSuggester, ChordSuggestion, SuggestionConfig, SuggestionContext,
+    ChordProgressionSuggester, ChordSuggestion, ScaleFallback, SubstitutionSuggestion,
+    SuggestionConfig, SuggestionContext,
+};
+use crate::trie::{
+    BulkAddReport, ChordProgressionTrie, CompactionStats, CorpusStatistics, DuplicatePolicy,
+    TimedPattern, TrieStatistics, WeightedPattern,
 };
-use crate::trie::ChordProgressionTrie;
-use composer_config::MEMORY;
-use composer_core::Chord;
+use crate::turnaround::{LoopAnalysis, Turnaround};
+use crate::valence::{estimate_valence, ValenceEstimate};
+use composer_config::ComposerConfig;
+use composer_core::{get_substitutions, Chord, Key, Mode, PitchClass, ScaleFingerprint};
+use composer_serialization::{deserialize_chord, ChordBinary, Note, Timeline, TokenLibrary};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Instant;
 
+/// Training pattern: chord progression, source identifier, and optional key
+pub type TrainingPattern = (Vec<Chord>, String, Option<Key>);
+
+/// Tokenized training pattern: chord tokens, source identifier, and optional key
+pub type TokenPattern = (Vec<String>, String, Option<Key>);
+
+/// Timeline training pattern: timeline, source identifier, and optional key
+pub type TimelinePattern = (Timeline, String, Option<Key>);
+
+/// Difficulty assessment of a progression on one instrument
+pub type InstrumentAssessment = (DifficultyProfile, DifficultyAssessment);
+
+/// Labeled difficulty example: chord progression and target overall score (0.0-10.0)
+pub type LabeledProgression = (Vec<Chord>, f64);
+
 /// Main AI engine configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AiEngineConfig {
@@ -38,29 +87,78 @@ pub struct AiEngineConfig {
     pub enable_difficulty_assessment: bool,
     pub enable_bass_harmonization: bool,
     pub enable_pattern_analysis: bool,
+
+    /// Handling of training patterns identical to ones already loaded
+    #[serde(default)]
+    pub duplicate_policy: DuplicatePolicy,
+
+    /// When magic chord solutions fall back from the requested scale's
+    /// patterns to the whole corpus
+    #[serde(default)]
+    pub scale_fallback: ScaleFallback,
+
+    /// Time limits for suggestion and asset loading calls, and whether
+    /// exceeding them fails the call
+    #[serde(default)]
+    pub performance_budget: PerformanceBudget,
+
+    /// Tunable thresholds read by the suggester and analyzer
+    #[serde(default)]
+    pub runtime: ComposerConfig,
 }
 
 impl Default for AiEngineConfig {
     fn default() -> Self {
+        Self::from_runtime(ComposerConfig::default())
+    }
+}
+
+impl AiEngineConfig {
+    /// Default configuration with limits taken from a runtime configuration.
+    ///
+    /// The memory limit, cache size and performance budget come from its
+    /// `memory` and `performance` sections; the whole configuration is kept in
+    /// [`AiEngineConfig::runtime`] for the suggester and analyzer.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig};
+    /// use composer_config::ComposerConfig;
+    ///
+    /// let runtime = ComposerConfig::builder()
+    ///     .with_toml_str("[memory]\nmemory_usage_max_mb = 64\n\n[quality]\nconfidence_threshold = 0.5\n")
+    ///     .build()?;
+    /// let engine = AiEngine::new(AiEngineConfig::from_runtime(runtime));
+    ///
+    /// assert_eq!(engine.get_config().max_memory_mb, 64);
+    /// assert_eq!(engine.get_config().runtime.quality.confidence_threshold, 0.5);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_runtime(runtime: ComposerConfig) -> Self {
         let mut model_versions = HashMap::new();
         model_versions.insert("chord_suggestion".to_string(), "v2.35.2".to_string());
         model_versions.insert("difficulty_assessment".to_string(), "v2.35.2".to_string());
 
         Self {
-            max_memory_mb: MEMORY.memory_usage_max_mb,
+            max_memory_mb: runtime.memory.memory_usage_max_mb,
             enable_performance_monitoring: true,
-            max_cache_entries: 10000,
+            max_cache_entries: runtime.memory.cache_size_max_entries as usize,
             model_versions,
             enable_advanced_suggestions: true,
             enable_difficulty_assessment: true,
             enable_bass_harmonization: true,
             enable_pattern_analysis: true,
+            duplicate_policy: DuplicatePolicy::default(),
+            scale_fallback: ScaleFallback::default(),
+            performance_budget: PerformanceBudget::from(&runtime.performance),
+            runtime,
         }
     }
 }
 
 /// Engine performance metrics
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EngineMetrics {
     /// Total requests processed
     pub total_requests: u64,
@@ -79,6 +177,10 @@ pub struct EngineMetrics {
 
     /// Engine uptime in seconds
     pub uptime_seconds: u64,
+
+    /// Calls that exceeded their performance budget, by operation
+    #[serde(default)]
+    pub budget_violations: BudgetViolations,
 }
 
 /// Bass line harmonization options
@@ -95,6 +197,26 @@ pub struct BassHarmonizationOptions {
 
     /// Preferred rhythm pattern
     pub rhythm_pattern: Option<String>,
+
+    /// Scale the chords' degrees are read in (walking style; major if unset)
+    #[serde(default)]
+    pub scale_fingerprint: Option<ScaleFingerprint>,
+
+    /// Tonic pitch class of the key (walking style; 0 = C)
+    #[serde(default)]
+    pub tonic: u8,
+
+    /// Beats walked over each chord (walking style)
+    #[serde(default = "default_beats_per_chord")]
+    pub beats_per_chord: u8,
+
+    /// Eighth-note feel of skip notes (walking style)
+    #[serde(default)]
+    pub swing: SwingFeel,
+}
+
+fn default_beats_per_chord() -> u8 {
+    4
 }
 
 /// Bass line style options
@@ -115,14 +237,19 @@ impl Default for BassHarmonizationOptions {
             complexity: 0.5,
             enable_walking: false,
             rhythm_pattern: None,
+            scale_fingerprint: None,
+            tonic: 0,
+            beats_per_chord: default_beats_per_chord(),
+            swing: SwingFeel::default(),
         }
     }
 }
 
 /// Bass line harmonization result
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct BassHarmonization {
-    /// Generated bass notes
+    /// Generated bass notes: MIDI notes for the walking style, scale degrees
+    /// for the others
     pub bass_notes: Vec<u8>,
 
     /// Rhythm pattern (in MIDI ticks)
@@ -166,9 +293,9 @@ pub struct BassHarmonization {
 /// // Prepare training patterns (chord progressions)
 /// let training_patterns = vec![
 ///     (vec![Chord::triad(1)?, Chord::triad(6)?, Chord::triad(4)?, Chord::triad(5)?],
-///      "pop-progression-1".to_string(), Some("C".to_string())),
+///      "pop-progression-1".to_string(), Some("C".parse()?)),
 ///     (vec![Chord::seventh(2)?, Chord::seventh(5)?, Chord::triad(1)?],
-///      "jazz-ii-V-I".to_string(), Some("C".to_string())),
+///      "jazz-ii-V-I".to_string(), Some("C".parse()?)),
 /// ];
 ///
 /// // Initialize with training data
@@ -181,7 +308,7 @@ pub struct BassHarmonization {
 ///
 /// ```rust
 /// use composer_ai::{AiEngine, AiEngineConfig};
-/// use composer_core::Chord;
+/// use composer_core::{Chord, Mode};
 ///
 /// let engine = AiEngine::new(AiEngineConfig::default());
 /// // ... initialize with patterns ...
@@ -191,7 +318,7 @@ pub struct BassHarmonization {
 /// // Get chord progression suggestions
 /// let context = vec![Chord::triad(1)?, Chord::triad(6)?];
 /// let following = vec![Chord::triad(5)?];
-/// let suggestions = engine.get_magic_chord_solutions(&context, &following, "major", 5)?;
+/// let suggestions = engine.get_magic_chord_solutions(&context, &following, Mode::Major, 5)?;
 ///
 /// // Assess difficulty of a progression
 /// let progression = vec![Chord::seventh(2)?, Chord::seventh(5)?, Chord::new(1, 9)?];
@@ -199,7 +326,7 @@ pub struct BassHarmonization {
 /// println!("Difficulty: {:.1}/10", assessment.overall_score);
 ///
 /// // Get bass harmonization
-/// let bass_solutions = engine.get_magic_bass_solutions("C", "major", 3)?;
+/// let bass_solutions = engine.get_magic_bass_solutions("C".parse()?, Mode::Major, 3)?;
 /// # Ok::<(), Box<dyn std::error::Error>>(())
 /// ```
 ///
@@ -246,8 +373,32 @@ pub struct AiEngine {
 
     /// Initialization status
     initialized: Arc<RwLock<bool>>,
+
+    /// Opt-in session recorder
+    recorder: Arc<SessionRecorder>,
+
+    /// Registered asset migrations
+    migrations: Arc<RwLock<MigrationRegistry>>,
+
+    /// Nearest-neighbor index over the trie vocabulary, built on first use
+    chord_index: ChordIndexCache,
+
+    /// Nearest-neighbor index over the stored progressions, built on first use
+    progression_index: ProgressionIndexCache,
+
+    /// Chord embeddings, installed or derived from the trie on first use
+    embedding_table: EmbeddingTableCache,
 }
 
+/// Lazily built chord index, dropped whenever the trie changes
+type ChordIndexCache = Arc<RwLock<Option<Arc<ChordIndex>>>>;
+
+/// Lazily built progression index, dropped whenever the trie changes
+type ProgressionIndexCache = Arc<RwLock<Option<Arc<ProgressionIndex>>>>;
+
+/// Chord embedding table; a derived one is dropped whenever the trie changes
+type EmbeddingTableCache = Arc<RwLock<Option<Arc<ChordEmbeddingTable>>>>;
+
 impl AiEngine {
     /// Creates a new AI engine instance with the specified configuration.
     ///
@@ -289,9 +440,15 @@ impl AiEngine {
     ///
     /// [`initialize`]: AiEngine::initialize
     pub fn new(config: AiEngineConfig) -> Self {
-        let trie = Arc::new(ChordProgressionTrie::new());
+        let trie = Arc::new(ChordProgressionTrie::with_duplicate_policy(
+            config.duplicate_policy,
+        ));
         let suggester = Arc::new(ChordProgressionSuggester::new(trie.clone()));
+        suggester.set_scale_fallback(config.scale_fallback);
+        suggester.set_performance_budget(config.performance_budget);
+        suggester.set_quality_thresholds(config.runtime.quality.clone());
         let analyzer = Arc::new(MusicalAnalyzer::new(trie.clone()));
+        analyzer.set_analysis_parameters(config.runtime.analysis.clone());
 
         let metrics = EngineMetrics {
             total_requests: 0,
@@ -300,6 +457,7 @@ impl AiEngine {
             cache_hit_rate: 0.0,
             total_patterns: 0,
             uptime_seconds: 0,
+            budget_violations: BudgetViolations::new(),
         };
 
         Self {
@@ -310,6 +468,11 @@ impl AiEngine {
             metrics: Arc::new(RwLock::new(metrics)),
             start_time: Instant::now(),
             initialized: Arc::new(RwLock::new(false)),
+            recorder: Arc::new(SessionRecorder::new()),
+            migrations: Arc::new(RwLock::new(MigrationRegistry::new())),
+            chord_index: Arc::new(RwLock::new(None)),
+            progression_index: Arc::new(RwLock::new(None)),
+            embedding_table: Arc::new(RwLock::new(None)),
         }
     }
 
@@ -324,7 +487,7 @@ impl AiEngine {
     /// * `training_patterns` - Vector of tuples containing:
     ///   - `Vec<Chord>`: The chord progression pattern
     ///   - `String`: Source identifier (e.g., "song-title", "exercise-1")
-    ///   - `Option<String>`: Optional key/tonic (e.g., "C", "Bb", "F#m")
+    ///   - `Option<Key>`: Optional key (e.g., `"C".parse()?`, `"F#m".parse()?`)
     ///
     /// # Returns
     ///
@@ -345,12 +508,12 @@ impl AiEngine {
     ///     (vec![
     ///         Chord::triad(1)?, Chord::triad(6)?,
     ///         Chord::triad(4)?, Chord::triad(5)?
-    ///     ], "pop-progression".to_string(), Some("C".to_string())),
+    ///     ], "pop-progression".to_string(), Some("C".parse()?)),
     ///     
     ///     // ii-V-I progression (jazz fundamental)
     ///     (vec![
     ///         Chord::seventh(2)?, Chord::seventh(5)?, Chord::triad(1)?
-    ///     ], "jazz-ii-V-I".to_string(), Some("C".to_string())),
+    ///     ], "jazz-ii-V-I".to_string(), Some("C".parse()?)),
     ///     
     ///     // Circle of fifths sequence
     ///     (vec![
@@ -379,7 +542,7 @@ impl AiEngine {
     ///         Chord::seventh(6)?,
     ///         Chord::seventh(2)?.with_alteration("b5")?,
     ///         Chord::seventh(5)?.with_alteration("b9")?
-    ///     ], "jazz-complex-1".to_string(), Some("C".to_string())),
+    ///     ], "jazz-complex-1".to_string(), Some("C".parse()?)),
     /// ];
     ///
     /// engine.initialize(jazz_patterns)?;
@@ -405,6 +568,7 @@ impl AiEngine {
     ///
     /// - `AiError::EngineAlreadyInitialized` - Engine is already initialized
     /// - `AiError::InvalidPatternData` - Invalid chord data in patterns
+    /// - `AiError::InvalidPattern` - A duplicate pattern under `DuplicatePolicy::Reject`
     /// - `AiError::MemoryExhausted` - Patterns exceed memory limits
     /// - `AiError::TrieOperationFailed` - Internal trie construction failed
     ///
@@ -419,10 +583,7 @@ impl AiEngine {
     /// - [`AiEngine::is_initialized`] - Check initialization status
     /// - [`AiEngine::get_metrics`] - Monitor pattern storage statistics
     /// - [`AiEngine::shutdown`] - Clean shutdown and resource cleanup
-    pub fn initialize(
-        &self,
-        training_patterns: Vec<(Vec<Chord>, String, Option<String>)>,
-    ) -> AiResult<()> {
+    pub fn initialize(&self, training_patterns: Vec<TrainingPattern>) -> AiResult<()> {
         if *self.initialized.read() {
             return Err(AiError::EngineNotInitialized);
         }
@@ -441,6 +602,303 @@ impl AiEngine {
         Ok(())
     }
 
+    /// Initializes the engine from a tokenized corpus.
+    ///
+    /// Each pattern is a sequence of chord tokens resolved through `library`
+    /// straight to the chord binaries the trie stores, so no `Chord` values are
+    /// built per pattern. Patterns are consumed one at a time from the iterator
+    /// and every distinct token is resolved and validated once, keeping peak
+    /// memory close to the size of the trie itself on large training runs.
+    ///
+    /// Fails with `AiError::InvalidPattern` on a token missing from the library,
+    /// a library entry that is not a valid chord binary, or a pattern of the
+    /// wrong length; patterns loaded before the failure are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig};
+    /// use composer_core::Chord;
+    /// use composer_serialization::{serialize_chord, TokenLibrary};
+    ///
+    /// let mut library = TokenLibrary::new();
+    /// library.add_chord_token("I".to_string(), serialize_chord(&Chord::triad(1)?)?.to_vec());
+    /// library.add_chord_token("V".to_string(), serialize_chord(&Chord::triad(5)?)?.to_vec());
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// engine.initialize_from_tokens(
+    ///     &library,
+    ///     vec![(vec!["I".to_string(), "V".to_string()], "1".to_string(), None)],
+    /// )?;
+    /// assert!(engine.is_initialized());
+    /// assert_eq!(engine.get_metrics().total_patterns, 1);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`AiEngine::initialize`] - Initialize from in-memory chord patterns
+    /// - [`AiEngine::initialize_from_binary`] - Initialize from a serialized trie
+    pub fn initialize_from_tokens<I>(&self, library: &TokenLibrary, patterns: I) -> AiResult<()>
+    where
+        I: IntoIterator<Item = TokenPattern>,
+    {
+        if *self.initialized.read() {
+            return Err(AiError::EngineNotInitialized);
+        }
+
+        let mut resolved: HashMap<String, ChordBinary> = HashMap::new();
+        let mut pattern: Vec<ChordBinary> = Vec::new();
+        for (tokens, source_id, key_tonic) in patterns {
+            pattern.clear();
+            for token in tokens {
+                let binary = match resolved.get(&token) {
+                    Some(binary) => *binary,
+                    None => {
+                        let binary = Self::resolve_chord_token(library, &token)?;
+                        resolved.insert(token, binary);
+                        binary
+                    },
+                };
+                pattern.push(binary);
+            }
+            self.trie
+                .add_binary_pattern(&pattern, source_id, key_tonic)?;
+        }
+
+        self.trie.calculate_all_ranks();
+        *self.initialized.write() = true;
+
+        Ok(())
+    }
+
+    /// Resolve a chord token to a validated chord binary
+    fn resolve_chord_token(library: &TokenLibrary, token: &str) -> AiResult<ChordBinary> {
+        let bytes = library
+            .resolve_chord_token(token)
+            .map_err(|e| AiError::InvalidPattern {
+                reason: e.to_string(),
+            })?;
+        let binary =
+            ChordBinary::try_from(bytes.as_slice()).map_err(|_| AiError::InvalidPattern {
+                reason: format!(
+                    "Token {} maps to {} bytes, expected a 5-byte chord binary",
+                    token,
+                    bytes.len()
+                ),
+            })?;
+        deserialize_chord(&binary).map_err(|e| AiError::InvalidPattern {
+            reason: format!("Token {} is not a valid chord binary: {}", token, e),
+        })?;
+        Ok(binary)
+    }
+
+    /// Initializes the engine from a pre-serialized chord trie.
+    ///
+    /// Loads a trie produced offline (for example with
+    /// [`AiEngine::export_trie_asset`] or `composer_serialization::serialize_trie`)
+    /// in one call instead of replaying every training pattern. Assets carrying a
+    /// versioned header are negotiated with [`AiEngine::negotiate_asset`] first; bare
+    /// `serialize_trie` output is accepted as-is, with its numeric ids as the
+    /// source ids. Either may be compressed with
+    /// `composer_serialization::compress`, and is decompressed within the
+    /// trie memory limit.
+    ///
+    /// Loading should finish within the `asset_loading_max_ms` of the engine's
+    /// [`PerformanceBudget`]. If it does not, the violation is counted in
+    /// [`EngineMetrics::budget_violations`]; in [`crate::BudgetMode::Fail`] the
+    /// engine is also left uninitialized and `AiError::PerformanceDegradation` is
+    /// returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig};
+    /// use composer_core::Chord;
+    ///
+    /// let trainer = AiEngine::new(AiEngineConfig::default());
+    /// trainer.initialize(vec![(
+    ///     vec![Chord::triad(1)?, Chord::triad(4)?, Chord::triad(5)?],
+    ///     "1".to_string(),
+    ///     None,
+    /// )])?;
+    /// let asset = trainer.export_trie_asset()?;
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// engine.initialize_from_binary(&asset)?;
+    /// assert!(engine.is_initialized());
+    /// assert_eq!(engine.get_metrics().total_patterns, 1);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`AiEngine::initialize`] - Initialize from in-memory training patterns
+    /// - [`AiEngine::export_trie_asset`] - Produce an asset for this method
+    pub fn initialize_from_binary(&self, bytes: &[u8]) -> AiResult<()> {
+        if *self.initialized.read() {
+            return Err(AiError::EngineNotInitialized);
+        }
+
+        let start_time = Instant::now();
+
+        // Whole assets may be compressed for transfer; compressed payloads are
+        // handled by deserialize_trie
+        let limit = composer_serialization::DeserializationLimits::default().max_decompressed_bytes;
+        let bytes = composer_serialization::decompress(bytes, limit).map_err(|e| {
+            AiError::DataCorruption {
+                details: format!("Asset decompression failed: {}", e),
+            }
+        })?;
+        let bytes = bytes.as_ref();
+
+        let negotiated;
+        let payload = if AssetHeader::is_present(bytes) {
+            negotiated = self.negotiate_asset(bytes)?;
+            if negotiated.header.kind != AssetKind::ChordTrie {
+                return Err(AiError::DataCorruption {
+                    details: format!(
+                        "Expected a chord trie asset, found {:?}",
+                        negotiated.header.kind
+                    ),
+                });
+            }
+            negotiated.payload.as_ref()
+        } else {
+            bytes
+        };
+
+        let limits = composer_serialization::DeserializationLimits::unlimited();
+        let (binary, references) =
+            composer_serialization::deserialize_trie_with_references(payload, false, &limits)
+                .map_err(|e| AiError::DataCorruption {
+                    details: format!("Trie deserialization failed: {}", e),
+                })?;
+
+        self.check_asset_loading_budget(start_time)?;
+
+        let root = match references {
+            Some(references) => TrieArena::from_referenced_binary_node(&binary, &references)?,
+            None => TrieArena::from_binary_node(&binary)?,
+        };
+        drop(binary);
+
+        self.check_asset_loading_budget(start_time)?;
+
+        self.trie.replace_root(root);
+        *self.initialized.write() = true;
+
+        Ok(())
+    }
+
+    /// Serialize the engine's pattern trie as a versioned chord trie asset
+    ///
+    /// Source ids and keys are stored in the trie's reference table. Chord
+    /// durations, pattern weights and scale branches are not: an engine loading
+    /// the asset weights every occurrence alike and has no scale branches to
+    /// search.
+    pub fn export_trie_asset(&self) -> AiResult<Vec<u8>> {
+        let (binary, references) = self.trie.to_referenced_binary_node();
+        let payload = composer_serialization::serialize_trie_with_references(&binary, &references)
+            .map_err(|e| AiError::DataCorruption {
+                details: format!("Trie serialization failed: {}", e),
+            })?;
+        let version = self.required_model_version(AssetKind::ChordTrie)?;
+        Ok(crate::compatibility::encode_asset(
+            AssetKind::ChordTrie,
+            version,
+            &payload,
+        ))
+    }
+
+    /// Serialize the engine's difficulty model as a versioned difficulty model asset
+    pub fn export_difficulty_model_asset(&self) -> AiResult<Vec<u8>> {
+        let payload = serde_json::to_vec(&self.analyzer.difficulty_model()).map_err(|e| {
+            AiError::DataCorruption {
+                details: format!("Difficulty model serialization failed: {}", e),
+            }
+        })?;
+        let version = self.required_model_version(AssetKind::DifficultyModel)?;
+        Ok(crate::compatibility::encode_asset(
+            AssetKind::DifficultyModel,
+            version,
+            &payload,
+        ))
+    }
+
+    /// Install the polynomial difficulty model from a difficulty model asset
+    ///
+    /// Accepts the output of [`AiEngine::export_difficulty_model_asset`]; older
+    /// versions are negotiated with [`AiEngine::negotiate_asset`] first.
+    pub fn load_difficulty_model_asset(&self, bytes: &[u8]) -> AiResult<()> {
+        let negotiated = self.negotiate_asset(bytes)?;
+        if negotiated.header.kind != AssetKind::DifficultyModel {
+            return Err(AiError::DataCorruption {
+                details: format!(
+                    "Expected a difficulty model asset, found {:?}",
+                    negotiated.header.kind
+                ),
+            });
+        }
+        let model: PolynomialModel =
+            serde_json::from_slice(&negotiated.payload).map_err(|e| AiError::DataCorruption {
+                details: format!("Difficulty model deserialization failed: {}", e),
+            })?;
+        self.analyzer.set_polynomial_model(model)
+    }
+
+    /// Count asset loading over its time limit, failing in `BudgetMode::Fail`
+    fn check_asset_loading_budget(&self, start_time: Instant) -> AiResult<()> {
+        let elapsed_ms = start_time.elapsed().as_secs_f64() * 1000.0;
+        let limit_ms = self.config.read().performance_budget.asset_loading_max_ms;
+        self.check_budget("asset_loading", elapsed_ms, limit_ms)
+    }
+
+    /// Check a suggestion call made from a UI thread against the budget's
+    /// `ui_response_max_ms`.
+    ///
+    /// Bindings running on a browser's main thread time their suggestion calls,
+    /// thread pool included, and report them here. Calls over the limit are
+    /// counted as `ui_response` in [`EngineMetrics::budget_violations`]; in
+    /// [`crate::BudgetMode::Fail`] they fail.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig, BudgetMode, PerformanceBudget};
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// engine.check_ui_response_budget(40.0)?;
+    /// assert_eq!(engine.get_metrics().budget_violations["ui_response"], 1);
+    ///
+    /// engine.set_performance_budget(PerformanceBudget {
+    ///     mode: BudgetMode::Fail,
+    ///     ..PerformanceBudget::default()
+    /// });
+    /// assert!(engine.check_ui_response_budget(40.0).is_err());
+    /// # Ok::<(), composer_ai::AiError>(())
+    /// ```
+    pub fn check_ui_response_budget(&self, elapsed_ms: f64) -> AiResult<()> {
+        let limit_ms = self.config.read().performance_budget.ui_response_max_ms;
+        self.check_budget("ui_response", elapsed_ms, limit_ms)
+    }
+
+    /// Count a call over `limit_ms` under `operation`, failing in
+    /// [`crate::BudgetMode::Fail`]
+    fn check_budget(&self, operation: &str, elapsed_ms: f64, limit_ms: u64) -> AiResult<()> {
+        let budget = self.config.read().performance_budget;
+        let result = budget.check(operation, elapsed_ms, limit_ms);
+        if !matches!(result, Ok(false)) {
+            *self
+                .metrics
+                .write()
+                .budget_violations
+                .entry(operation.to_string())
+                .or_insert(0) += 1;
+        }
+        result.map(|_| ())
+    }
+
     /// Check if engine is initialized
     pub fn is_initialized(&self) -> bool {
         *self.initialized.read()
@@ -465,9 +923,32 @@ impl AiEngine {
 
         let start_time = Instant::now();
 
-        let suggestions = self
+        let result = self
             .suggester
-            .get_chord_suggestions(pattern, context, config)?;
+            .get_chord_suggestions(pattern, context, config)
+            .and_then(|suggestions| {
+                if suggestions.is_empty() && config.similarity_backoff {
+                    self.suggester.similar_context_suggestions(
+                        pattern,
+                        context,
+                        config,
+                        self.embedding_table()?.as_ref(),
+                    )
+                } else {
+                    Ok(suggestions)
+                }
+            });
+
+        self.record_interaction(
+            || SessionQuery::ChordSuggestions {
+                pattern: pattern.to_vec(),
+                context: Box::new(context.clone()),
+                config: config.clone(),
+            },
+            &result,
+            start_time,
+        );
+        let suggestions = result?;
 
         self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
 
@@ -475,11 +956,17 @@ impl AiEngine {
     }
 
     /// Get magic chord solutions using statistical algorithm from test specification
+    ///
+    /// Patterns added with a key in the scale branch of `mode` (see
+    /// [`ChordProgressionTrie::scale_branch`]) are searched first, then the
+    /// whole corpus according to the configured [`ScaleFallback`]; each
+    /// suggestion's `pattern_info.branch` names the scale branch it came from,
+    /// or is `None` for the whole corpus.
     pub fn get_magic_chord_solutions(
         &self,
         previous_chords: &[Chord],
         following_chords: &[Chord],
-        scale: &str,
+        mode: Mode,
         limit: usize,
     ) -> AiResult<Vec<ChordSuggestion>> {
         if !self.is_initialized() {
@@ -494,23 +981,40 @@ impl AiEngine {
 
         let start_time = Instant::now();
 
-        let suggestions = self.suggester.get_magic_chord_solutions(
+        let result = self.suggester.get_magic_chord_solutions(
             previous_chords,
             following_chords,
-            scale,
+            mode,
             limit,
-        )?;
+        );
+
+        self.record_interaction(
+            || SessionQuery::MagicChordSolutions {
+                previous_chords: previous_chords.to_vec(),
+                following_chords: following_chords.to_vec(),
+                scale: mode.name().to_string(),
+                limit,
+            },
+            &result,
+            start_time,
+        );
+        let suggestions = result?;
 
         self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
 
         Ok(suggestions)
     }
 
-    /// Get bass harmonization solutions from test specification
+    /// Get chords heard over a bass note in the training corpus
+    ///
+    /// `bass_note` is a pitch class above the tonic ("C" is the tonic) and
+    /// `mode` places scale degrees on pitch classes; patterns stored in its
+    /// scale branch come first, then the whole corpus according to the
+    /// configured [`ScaleFallback`].
     pub fn get_magic_bass_solutions(
         &self,
-        bass_note: &str,
-        scale: &str,
+        bass_note: PitchClass,
+        mode: Mode,
         limit: usize,
     ) -> AiResult<Vec<ChordSuggestion>> {
         if !self.is_initialized() {
@@ -525,9 +1029,20 @@ impl AiEngine {
 
         let start_time = Instant::now();
 
-        let suggestions = self
+        let result = self
             .suggester
-            .get_magic_bass_solutions(bass_note, scale, limit)?;
+            .get_magic_bass_solutions(bass_note, mode, limit);
+
+        self.record_interaction(
+            || SessionQuery::MagicBassSolutions {
+                bass_note: bass_note.to_string(),
+                scale: mode.name().to_string(),
+                limit,
+            },
+            &result,
+            start_time,
+        );
+        let suggestions = result?;
 
         self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
 
@@ -538,8 +1053,48 @@ impl AiEngine {
     pub fn get_harmonize_by_sd_solutions(
         &self,
         scale_degree_bits: u32,
-        scale: &str,
+        mode: Mode,
+        limit: usize,
+    ) -> AiResult<Vec<ChordSuggestion>> {
+        self.get_harmonize_by_sd_solutions_with_config(
+            scale_degree_bits,
+            mode,
+            limit,
+            &SuggestionConfig::default(),
+        )
+    }
+
+    /// Get scale degree harmonization solutions, with chromatic candidates
+    /// enabled by [`SuggestionConfig::chromaticism`]
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig, SuggestionConfig};
+    /// use composer_core::{Chord, Mode};
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// engine.initialize(vec![(vec![Chord::triad(1)?, Chord::triad(5)?], "cadence".to_string(), None)])?;
+    ///
+    /// // Scale degree 6 alone, harmonized with chromatic chords allowed
+    /// let config = SuggestionConfig {
+    ///     chromaticism: 1.0,
+    ///     ..Default::default()
+    /// };
+    /// let solutions = engine.get_harmonize_by_sd_solutions_with_config(1 << 5, Mode::Major, 20, &config)?;
+    /// assert!(solutions.iter().any(|s| s.chord.is_applied()));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`AiEngine::get_harmonize_by_sd_solutions`] - Diatonic candidates only
+    pub fn get_harmonize_by_sd_solutions_with_config(
+        &self,
+        scale_degree_bits: u32,
+        mode: Mode,
         limit: usize,
+        config: &SuggestionConfig,
     ) -> AiResult<Vec<ChordSuggestion>> {
         if !self.is_initialized() {
             return Err(AiError::EngineNotInitialized);
@@ -553,21 +1108,172 @@ impl AiEngine {
 
         let start_time = Instant::now();
 
-        let suggestions =
-            self.suggester
-                .get_harmonize_by_sd_solutions(scale_degree_bits, scale, limit)?;
+        let result = self.suggester.get_harmonize_by_sd_solutions_with_config(
+            scale_degree_bits,
+            mode,
+            limit,
+            config,
+        );
+
+        self.record_interaction(
+            || SessionQuery::HarmonizeBySdSolutions {
+                scale_degree_bits,
+                scale: mode.name().to_string(),
+                limit,
+                config: config.clone(),
+            },
+            &result,
+            start_time,
+        );
+        let suggestions = result?;
 
         self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
 
         Ok(suggestions)
     }
 
+    /// Generate the next `n` chords after `seed`
+    ///
+    /// Each chord is sampled from [`Self::get_chord_suggestions`] for the
+    /// progression so far, with the context's position and recent chords
+    /// updated at every step; see [`continue_progression`]. The result may be
+    /// shorter than `n` when the corpus runs out of continuations.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig, ContinuationConfig, SuggestionConfig};
+    /// use composer_core::Chord;
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// let chords = |roots: &[u8]| roots.iter().map(|&r| Chord::triad(r)).collect::<Result<Vec<_>, _>>();
+    /// engine.initialize(vec![
+    ///     (chords(&[1, 4, 5, 1])?, "plagal".to_string(), None),
+    ///     (chords(&[1, 6, 2, 5, 1])?, "circle".to_string(), None),
+    /// ])?;
+    ///
+    /// let config = ContinuationConfig {
+    ///     suggestion: SuggestionConfig {
+    ///         min_confidence: 0.0,
+    ///         ..Default::default()
+    ///     },
+    ///     seed: 42,
+    ///     ..Default::default()
+    /// };
+    /// let continuation = engine.continue_progression(&chords(&[1, 4])?, 2, &config)?;
+    /// let roots: Vec<u8> = continuation.iter().map(|s| s.chord.root).collect();
+    /// assert_eq!(roots, [5, 1]);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`AiEngine::get_chord_suggestions`] - A single step of suggestions
+    pub fn continue_progression(
+        &self,
+        seed: &[Chord],
+        n: usize,
+        config: &ContinuationConfig,
+    ) -> AiResult<Vec<ChordSuggestion>> {
+        continue_progression(seed, n, config, |pattern, context, suggestion| {
+            self.get_chord_suggestions(pattern, context, suggestion)
+        })
+    }
+
     /// Assess difficulty of a chord progression
+    ///
+    /// The assessment's [`crate::DifficultyExplanation`] ranks the contributing
+    /// sub-scores and compares every feature with reference progressions scored
+    /// at the same skill level.
     pub fn assess_difficulty(
         &self,
         progression: &[Chord],
         tempo_bpm: Option<f64>,
         time_signature: Option<(u8, u8)>,
+    ) -> AiResult<DifficultyAssessment> {
+        self.assess_difficulty_for(progression, tempo_bpm, time_signature, None)
+    }
+
+    /// Assess difficulty of a chord progression on one instrument
+    ///
+    /// Technical complexity is measured from voicings on the instrument:
+    /// barres, fret spans and position shifts on guitar and ukulele, hand span,
+    /// black-key density and bass jumps on piano, leaps and chromatic tones for
+    /// voice. The skill level therefore differs between instruments for the same
+    /// progression.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig, DifficultyProfile};
+    /// use composer_core::Chord;
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// engine.initialize(vec![(vec![Chord::triad(1)?, Chord::triad(5)?], "demo".to_string(), None)])?;
+    ///
+    /// let progression = vec![Chord::triad(4)?, Chord::triad(1)?];
+    /// let assessment = engine.assess_instrument_difficulty(
+    ///     &progression,
+    ///     None,
+    ///     None,
+    ///     DifficultyProfile::Guitar,
+    /// )?;
+    /// assert_eq!(assessment.instrument, Some(DifficultyProfile::Guitar));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`Self::assess_difficulty`] - Instrument-agnostic assessment
+    /// - [`Self::assess_difficulty_by_instrument`] - Every profile at once
+    pub fn assess_instrument_difficulty(
+        &self,
+        progression: &[Chord],
+        tempo_bpm: Option<f64>,
+        time_signature: Option<(u8, u8)>,
+        profile: DifficultyProfile,
+    ) -> AiResult<DifficultyAssessment> {
+        self.assess_difficulty_for(progression, tempo_bpm, time_signature, Some(profile))
+    }
+
+    /// Assess difficulty of the chords of a timeline
+    ///
+    /// Rhythmic complexity follows the timeline's meter changes and chord
+    /// onsets: changes of time signature, time spent in irregular meters such
+    /// as 5/4 or 7/8, and chord changes off the beat all raise it.
+    /// `time_signature` applies before the first meter change (4/4 if `None`).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig};
+    /// use composer_core::Chord;
+    /// use composer_serialization::{MeterChange, Timeline, TokenEvent, TokenEventType};
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// engine.initialize(vec![(vec![Chord::triad(1)?, Chord::triad(5)?], "demo".to_string(), None)])?;
+    ///
+    /// let events = [(1, 0.0), (4, 3.5), (5, 7.0), (1, 10.5)]
+    ///     .into_iter()
+    ///     .map(|(root, beat)| Ok(TokenEvent { beat, event_type: TokenEventType::Chord(Chord::triad(root)?) }))
+    ///     .collect::<Result<Vec<_>, composer_core::ChordTheoryError>>()?;
+    /// let mut timeline = Timeline { events, total_duration: 14.0, ..Default::default() };
+    /// let plain = engine.assess_timeline_difficulty(&timeline, None, Some((4, 4)))?;
+    ///
+    /// timeline.meter_changes.push(MeterChange { beat: 0.0, time_signature: (7, 8) });
+    /// let irregular = engine.assess_timeline_difficulty(&timeline, None, Some((4, 4)))?;
+    /// assert!(irregular.rhythmic_complexity > plain.rhythmic_complexity);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`Self::assess_difficulty`] - Assess an untimed progression in one meter
+    pub fn assess_timeline_difficulty(
+        &self,
+        timeline: &Timeline,
+        tempo_bpm: Option<f64>,
+        time_signature: Option<(u8, u8)>,
     ) -> AiResult<DifficultyAssessment> {
         if !self.is_initialized() {
             return Err(AiError::EngineNotInitialized);
@@ -581,17 +1287,181 @@ impl AiEngine {
 
         let start_time = Instant::now();
 
-        let assessment = self
-            .analyzer
-            .assess_difficulty(progression, tempo_bpm, time_signature)?;
+        let assessment =
+            self.analyzer
+                .assess_timeline_difficulty(timeline, tempo_bpm, time_signature)?;
 
         self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
 
         Ok(assessment)
     }
 
-    /// Analyze chord progression patterns
-    pub fn analyze_progression(&self, progression: &[Chord]) -> AiResult<ProgressionAnalysis> {
+    /// Assess difficulty of a chord progression accompanying a melody
+    ///
+    /// Melodic complexity is measured from the melody's range, intervals,
+    /// contour and rhythm instead of estimated from chord roots; see
+    /// [`MusicalAnalyzer::analyze_melody`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig};
+    /// use composer_core::Chord;
+    /// use composer_serialization::Note;
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// engine.initialize(vec![(vec![Chord::triad(1)?, Chord::triad(5)?], "demo".to_string(), None)])?;
+    ///
+    /// let progression = [Chord::triad(1)?, Chord::triad(4)?, Chord::triad(5)?, Chord::triad(1)?];
+    /// let melody: Vec<Note> = [0, 2, 4, 5, 7, 5, 4, 2, 0]
+    ///     .into_iter()
+    ///     .map(|scale_degree| Note { scale_degree, octave: 5, is_rest: false })
+    ///     .collect();
+    /// let assessment = engine.assess_melody_difficulty(&progression, &melody, None, None)?;
+    /// let analysis = assessment.melody.as_ref().expect("melody analysis");
+    /// assert_eq!(analysis.range, 7);
+    /// assert_eq!(assessment.melodic_complexity, analysis.complexity);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`Self::analyze_melody`] - The melody analysis on its own
+    /// - [`Self::assess_timeline_difficulty`] - Uses the note events of a timeline
+    pub fn assess_melody_difficulty(
+        &self,
+        progression: &[Chord],
+        melody: &[Note],
+        tempo_bpm: Option<f64>,
+        time_signature: Option<(u8, u8)>,
+    ) -> AiResult<DifficultyAssessment> {
+        if !self.is_initialized() {
+            return Err(AiError::EngineNotInitialized);
+        }
+
+        if !self.config.read().enable_difficulty_assessment {
+            return Err(AiError::AnalysisFailed {
+                reason: "Difficulty assessment is disabled".to_string(),
+            });
+        }
+
+        let start_time = Instant::now();
+
+        let assessment = self.analyzer.assess_melody_difficulty(
+            progression,
+            melody,
+            tempo_bpm,
+            time_signature,
+        )?;
+
+        self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
+
+        Ok(assessment)
+    }
+
+    /// Measure range, tessitura, intervals, contour and rhythm of a melody
+    ///
+    /// Notes last one beat each. See [`MusicalAnalyzer::analyze_melody`].
+    pub fn analyze_melody(&self, melody: &[Note]) -> AiResult<MelodicAnalysis> {
+        if !self.is_initialized() {
+            return Err(AiError::EngineNotInitialized);
+        }
+
+        let start_time = Instant::now();
+
+        let analysis = self.analyzer.analyze_melody(melody)?;
+
+        self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
+
+        Ok(analysis)
+    }
+
+    /// Assess difficulty of a chord progression on every instrument
+    ///
+    /// Returns one assessment per [`DifficultyProfile::ALL`] entry, in that order.
+    pub fn assess_difficulty_by_instrument(
+        &self,
+        progression: &[Chord],
+        tempo_bpm: Option<f64>,
+        time_signature: Option<(u8, u8)>,
+    ) -> AiResult<Vec<InstrumentAssessment>> {
+        DifficultyProfile::ALL
+            .into_iter()
+            .map(|profile| {
+                self.assess_instrument_difficulty(progression, tempo_bpm, time_signature, profile)
+                    .map(|assessment| (profile, assessment))
+            })
+            .collect()
+    }
+
+    fn assess_difficulty_for(
+        &self,
+        progression: &[Chord],
+        tempo_bpm: Option<f64>,
+        time_signature: Option<(u8, u8)>,
+        profile: Option<DifficultyProfile>,
+    ) -> AiResult<DifficultyAssessment> {
+        if !self.is_initialized() {
+            return Err(AiError::EngineNotInitialized);
+        }
+
+        if !self.config.read().enable_difficulty_assessment {
+            return Err(AiError::AnalysisFailed {
+                reason: "Difficulty assessment is disabled".to_string(),
+            });
+        }
+
+        let start_time = Instant::now();
+
+        let result = match profile {
+            Some(profile) => self.analyzer.assess_instrument_difficulty(
+                progression,
+                tempo_bpm,
+                time_signature,
+                profile,
+            ),
+            None => self
+                .analyzer
+                .assess_difficulty(progression, tempo_bpm, time_signature),
+        };
+
+        self.record_interaction(
+            || SessionQuery::AssessDifficulty {
+                progression: progression.to_vec(),
+                tempo_bpm,
+                time_signature,
+                instrument: profile,
+            },
+            &result,
+            start_time,
+        );
+        let assessment = result?;
+
+        self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
+
+        Ok(assessment)
+    }
+
+    /// Extract the difficulty model features for a progression.
+    ///
+    /// Returns the vector in [`crate::DIFFICULTY_FEATURE_NAMES`] order, for building
+    /// training sets for externally trained difficulty models.
+    pub fn extract_difficulty_features(
+        &self,
+        progression: &[Chord],
+        tempo_bpm: Option<f64>,
+        time_signature: Option<(u8, u8)>,
+    ) -> AiResult<DifficultyFeatures> {
+        if !self.is_initialized() {
+            return Err(AiError::EngineNotInitialized);
+        }
+
+        self.analyzer
+            .extract_difficulty_features(progression, tempo_bpm, time_signature)
+    }
+
+    /// Analyze chord progression patterns
+    pub fn analyze_progression(&self, progression: &[Chord]) -> AiResult<ProgressionAnalysis> {
         if !self.is_initialized() {
             return Err(AiError::EngineNotInitialized);
         }
@@ -611,212 +1481,1278 @@ impl AiEngine {
         Ok(analysis)
     }
 
-    /// Generate bass line harmonization
-    pub fn harmonize_bass_line(
+    /// Analyze harmonic rhythm from chords and their durations in beats
+    ///
+    /// See [`MusicalAnalyzer::analyze_harmonic_rhythm`].
+    pub fn analyze_harmonic_rhythm(&self, chords: &[ChordDuration]) -> AiResult<HarmonicRhythm> {
+        if !self.is_initialized() {
+            return Err(AiError::EngineNotInitialized);
+        }
+
+        let start_time = Instant::now();
+
+        let rhythm = self.analyzer.analyze_harmonic_rhythm(chords)?;
+
+        self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
+
+        Ok(rhythm)
+    }
+
+    /// Analyze the chords of a timeline, with harmonic rhythm from their timing
+    ///
+    /// See [`MusicalAnalyzer::analyze_timeline_progression`].
+    pub fn analyze_timeline_progression(
         &self,
-        progression: &[Chord],
-        options: &BassHarmonizationOptions,
-    ) -> AiResult<BassHarmonization> {
+        timeline: &Timeline,
+    ) -> AiResult<ProgressionAnalysis> {
         if !self.is_initialized() {
             return Err(AiError::EngineNotInitialized);
         }
 
-        if !self.config.read().enable_bass_harmonization {
-            return Err(AiError::SuggestionFailed {
-                reason: "Bass harmonization is disabled".to_string(),
+        if !self.config.read().enable_pattern_analysis {
+            return Err(AiError::AnalysisFailed {
+                reason: "Pattern analysis is disabled".to_string(),
             });
         }
 
         let start_time = Instant::now();
 
-        let harmonization = self.generate_bass_harmonization(progression, options)?;
+        let analysis = self.analyzer.analyze_timeline_progression(timeline)?;
 
         self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
 
-        Ok(harmonization)
+        Ok(analysis)
     }
 
-    /// Generate bass harmonization (internal implementation)
-    fn generate_bass_harmonization(
+    /// Estimate per-chord and overall valence and arousal of a progression
+    ///
+    /// Uses the major scale when no scale is given. See [`crate::estimate_valence`].
+    pub fn estimate_valence(
         &self,
         progression: &[Chord],
-        options: &BassHarmonizationOptions,
-    ) -> AiResult<BassHarmonization> {
-        let mut bass_notes = Vec::new();
-        let mut rhythm = Vec::new();
+        scale_fingerprint: Option<&ScaleFingerprint>,
+        tempo_bpm: Option<f64>,
+    ) -> AiResult<ValenceEstimate> {
+        if !self.is_initialized() {
+            return Err(AiError::EngineNotInitialized);
+        }
 
-        match options.style {
-            BassStyle::Root => {
-                // Simple root note bass line
-                for chord in progression {
-                    bass_notes.push(chord.root);
-                    rhythm.push(composer_config::MUSICAL.ticks_per_beat as u32);
-                    // Whole note
-                }
-            },
-            BassStyle::Alternating => {
-                // Alternating bass pattern
-                for (i, chord) in progression.iter().enumerate() {
-                    if i % 2 == 0 {
-                        bass_notes.push(chord.root);
-                    } else {
-                        // Alternate with fifth (scale degree 5 relative to root)
-                        let fifth_degree = if chord.root + 4 > 7 {
-                            chord.root + 4 - 7
-                        } else {
-                            chord.root + 4
-                        };
-                        bass_notes.push(fifth_degree);
-                    }
-                    rhythm.push(composer_config::MUSICAL.ticks_per_beat as u32 / 2);
-                    // Half notes
-                }
-            },
-            BassStyle::Walking => {
-                // Walking bass line with passing tones
-                for (i, chord) in progression.iter().enumerate() {
-                    bass_notes.push(chord.root);
-                    rhythm.push(composer_config::MUSICAL.ticks_per_beat as u32 / 4); // Quarter notes
-
-                    // Add passing tones between chords
-                    if i < progression.len() - 1 {
-                        let next_chord = &progression[i + 1];
-                        let passing_tone = self.calculate_passing_tone(chord.root, next_chord.root);
-                        bass_notes.push(passing_tone);
-                        rhythm.push(composer_config::MUSICAL.ticks_per_beat as u32 / 4);
-                    }
-                }
-            },
-            BassStyle::Arpeggiated => {
-                // Arpeggiated bass patterns
-                for chord in progression {
-                    bass_notes.push(chord.root);
-                    // Third (scale degree 3 relative to root)
-                    let third_degree = if chord.root + 2 > 7 {
-                        chord.root + 2 - 7
-                    } else {
-                        chord.root + 2
-                    };
-                    bass_notes.push(third_degree);
-                    // Fifth (scale degree 5 relative to root)
-                    let fifth_degree = if chord.root + 4 > 7 {
-                        chord.root + 4 - 7
-                    } else {
-                        chord.root + 4
-                    };
-                    bass_notes.push(fifth_degree);
+        let start_time = Instant::now();
 
-                    rhythm.push(composer_config::MUSICAL.ticks_per_beat as u32 / 3);
-                    rhythm.push(composer_config::MUSICAL.ticks_per_beat as u32 / 3);
-                    rhythm.push(composer_config::MUSICAL.ticks_per_beat as u32 / 3);
-                }
-            },
-            BassStyle::Rhythmic => {
-                // Rhythmic bass patterns
-                for chord in progression {
-                    bass_notes.push(chord.root);
-                    bass_notes.push(chord.root); // Repeat for rhythm
+        let major = ScaleFingerprint::major_scale();
+        let estimate =
+            estimate_valence(progression, scale_fingerprint.unwrap_or(&major), tempo_bpm)?;
 
-                    rhythm.push(composer_config::MUSICAL.ticks_per_beat as u32 / 2);
-                    rhythm.push(composer_config::MUSICAL.ticks_per_beat as u32 / 4);
-                }
-            },
+        self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
+
+        Ok(estimate)
+    }
+
+    /// Measure how statistically unusual a progression is in the training
+    /// corpus, per transition and overall
+    ///
+    /// Transitions rarer than the configured
+    /// [`AnalysisParameters::novelty_threshold`](composer_config::AnalysisParameters::novelty_threshold)
+    /// are marked novel. See [`crate::compute_novelty`].
+    pub fn compute_novelty(&self, progression: &[Chord]) -> AiResult<NoveltyReport> {
+        if !self.is_initialized() {
+            return Err(AiError::EngineNotInitialized);
+        }
+
+        let start_time = Instant::now();
+
+        let threshold = self.analyzer.analysis_parameters().novelty_threshold;
+        let report = compute_novelty(&self.trie, progression, threshold)?;
+
+        self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
+
+        Ok(report)
+    }
+
+    /// Segment a timeline into labeled phrases and sections
+    ///
+    /// See [`MusicalAnalyzer::analyze_form`] for how boundaries, repetition
+    /// labels and section roles are determined.
+    pub fn analyze_form(&self, timeline: &Timeline) -> AiResult<FormAnalysis> {
+        if !self.is_initialized() {
+            return Err(AiError::EngineNotInitialized);
+        }
+
+        if !self.config.read().enable_pattern_analysis {
+            return Err(AiError::AnalysisFailed {
+                reason: "Pattern analysis is disabled".to_string(),
+            });
+        }
+
+        let start_time = Instant::now();
+
+        let form = self.analyzer.analyze_form(timeline)?;
+
+        self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
+
+        Ok(form)
+    }
+
+    /// Rate how smoothly a progression loops from its last chord to its first
+    ///
+    /// See [`MusicalAnalyzer::analyze_loop`].
+    pub fn analyze_loop(&self, progression: &[Chord]) -> AiResult<LoopAnalysis> {
+        self.analyze_turnaround(|analyzer| analyzer.analyze_loop(progression))
+    }
+
+    /// Propose last chords that make a progression loop seamlessly, best first
+    ///
+    /// See [`MusicalAnalyzer::suggest_turnarounds`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig};
+    /// use composer_core::Chord;
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// engine.initialize(vec![])?;
+    ///
+    /// let progression = vec![Chord::triad(1)?, Chord::triad(4)?, Chord::triad(5)?, Chord::triad(1)?];
+    /// assert!(!engine.analyze_loop(&progression)?.loops_smoothly);
+    ///
+    /// let turnarounds = engine.suggest_turnarounds(&progression)?;
+    /// let mut looped = progression.clone();
+    /// looped[3] = turnarounds[0].chord.clone();
+    /// assert!(engine.analyze_loop(&looped)?.loops_smoothly);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn suggest_turnarounds(&self, progression: &[Chord]) -> AiResult<Vec<Turnaround>> {
+        self.analyze_turnaround(|analyzer| analyzer.suggest_turnarounds(progression))
+    }
+
+    fn analyze_turnaround<T>(
+        &self,
+        analyze: impl FnOnce(&MusicalAnalyzer) -> AiResult<T>,
+    ) -> AiResult<T> {
+        if !self.is_initialized() {
+            return Err(AiError::EngineNotInitialized);
+        }
+
+        if !self.config.read().enable_pattern_analysis {
+            return Err(AiError::AnalysisFailed {
+                reason: "Pattern analysis is disabled".to_string(),
+            });
+        }
+
+        let start_time = Instant::now();
+
+        let result = analyze(&self.analyzer)?;
+
+        self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
+
+        Ok(result)
+    }
+
+    /// Plan practice of a progression for a player at `player_level`
+    ///
+    /// Returns four-chord segments ranked hardest first, simpler substitutes
+    /// for the chords of segments above the player's level and an estimate of
+    /// the practice time. See [`MusicalAnalyzer::generate_practice_plan`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig, SkillLevel};
+    /// use composer_core::Chord;
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// engine.initialize(vec![(vec![Chord::triad(1)?, Chord::triad(5)?], "demo".to_string(), None)])?;
+    ///
+    /// let progression = vec![Chord::new(2, 7)?, Chord::new(5, 13)?, Chord::new(1, 7)?];
+    /// let plan = engine.generate_practice_plan(&progression, SkillLevel::Intermediate)?;
+    /// assert_eq!(plan.segments[0].chord_range, (0, 3));
+    /// assert!(plan.estimated_minutes > 0.0);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn generate_practice_plan(
+        &self,
+        progression: &[Chord],
+        player_level: SkillLevel,
+    ) -> AiResult<PracticePlan> {
+        if !self.is_initialized() {
+            return Err(AiError::EngineNotInitialized);
+        }
+
+        if !self.config.read().enable_difficulty_assessment {
+            return Err(AiError::AnalysisFailed {
+                reason: "Difficulty assessment is disabled".to_string(),
+            });
+        }
+
+        let start_time = Instant::now();
+
+        let plan = self
+            .analyzer
+            .generate_practice_plan(progression, player_level)?;
+
+        self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
+
+        Ok(plan)
+    }
+
+    /// Simplify a progression toward `target_level` without changing chord functions
+    ///
+    /// See [`MusicalAnalyzer::simplify_progression`].
+    pub fn simplify_progression(
+        &self,
+        progression: &[Chord],
+        target_level: SkillLevel,
+    ) -> AiResult<ProgressionRewrite> {
+        self.rewrite_progression(|analyzer| {
+            analyzer.simplify_progression(progression, target_level)
+        })
+    }
+
+    /// Enrich a progression toward `target_level` without changing chord functions
+    ///
+    /// See [`MusicalAnalyzer::enrich_progression`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig, SkillLevel};
+    /// use composer_core::Chord;
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// engine.initialize(vec![(vec![Chord::triad(1)?, Chord::triad(5)?], "demo".to_string(), None)])?;
+    ///
+    /// let progression = vec![Chord::triad(2)?, Chord::triad(5)?, Chord::triad(1)?];
+    /// let richer = engine.enrich_progression(&progression, SkillLevel::Expert)?;
+    /// let plainer = engine.simplify_progression(&richer.progression, SkillLevel::Beginner)?;
+    /// assert!(plainer.score < richer.score);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn enrich_progression(
+        &self,
+        progression: &[Chord],
+        target_level: SkillLevel,
+    ) -> AiResult<ProgressionRewrite> {
+        self.rewrite_progression(|analyzer| analyzer.enrich_progression(progression, target_level))
+    }
+
+    fn rewrite_progression(
+        &self,
+        rewrite: impl FnOnce(&MusicalAnalyzer) -> AiResult<ProgressionRewrite>,
+    ) -> AiResult<ProgressionRewrite> {
+        if !self.is_initialized() {
+            return Err(AiError::EngineNotInitialized);
+        }
+
+        if !self.config.read().enable_difficulty_assessment {
+            return Err(AiError::AnalysisFailed {
+                reason: "Difficulty assessment is disabled".to_string(),
+            });
+        }
+
+        let start_time = Instant::now();
+
+        let rewrite = rewrite(&self.analyzer)?;
+
+        self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
+
+        Ok(rewrite)
+    }
+
+    /// Generate bass line harmonization
+    pub fn harmonize_bass_line(
+        &self,
+        progression: &[Chord],
+        options: &BassHarmonizationOptions,
+    ) -> AiResult<BassHarmonization> {
+        if !self.is_initialized() {
+            return Err(AiError::EngineNotInitialized);
+        }
+
+        if !self.config.read().enable_bass_harmonization {
+            return Err(AiError::SuggestionFailed {
+                reason: "Bass harmonization is disabled".to_string(),
+            });
+        }
+
+        let start_time = Instant::now();
+
+        let result = self.generate_bass_harmonization(progression, options);
+
+        self.record_interaction(
+            || SessionQuery::HarmonizeBassLine {
+                progression: progression.to_vec(),
+                options: options.clone(),
+            },
+            &result,
+            start_time,
+        );
+        let harmonization = result?;
+
+        self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
+
+        Ok(harmonization)
+    }
+
+    /// Expand the chords of a timeline into an accompaniment timeline
+    ///
+    /// Renders block chords, Alberti bass, arpeggios or strumming at the
+    /// chosen density as note clusters; see [`render_accompaniment`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AccompanimentDensity, AccompanimentOptions, AccompanimentStyle, AiEngine, AiEngineConfig};
+    /// use composer_core::Chord;
+    /// use composer_serialization::{Timeline, TokenEvent, TokenEventType};
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// engine.initialize(vec![])?;
+    ///
+    /// let events = [(1, 0.0), (5, 2.0)]
+    ///     .into_iter()
+    ///     .map(|(root, beat)| {
+    ///         Ok(TokenEvent { beat, event_type: TokenEventType::Chord(Chord::triad(root)?) })
+    ///     })
+    ///     .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+    /// let timeline = Timeline { events, total_duration: 4.0, ..Default::default() };
+    /// let options = AccompanimentOptions {
+    ///     style: AccompanimentStyle::Arpeggio,
+    ///     density: AccompanimentDensity::Dense,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// // Sixteenth notes through four beats
+    /// let accompaniment = engine.render_accompaniment(&timeline, &options)?;
+    /// assert_eq!(accompaniment.events.len(), 16);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`AiEngine::harmonize_bass_line`] - Bass line of an untimed progression
+    pub fn render_accompaniment(
+        &self,
+        timeline: &Timeline,
+        options: &AccompanimentOptions,
+    ) -> AiResult<Timeline> {
+        if !self.is_initialized() {
+            return Err(AiError::EngineNotInitialized);
+        }
+
+        let start_time = Instant::now();
+
+        let accompaniment = render_accompaniment(timeline, options)?;
+
+        self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
+
+        Ok(accompaniment)
+    }
+
+    /// Suggest a kick, snare and hi-hat groove for the chords of a timeline
+    ///
+    /// Kicks are accented on chord changes and snares fall on the backbeats of
+    /// the timeline's meter; see [`suggest_groove`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig, DrumVoice, GrooveOptions};
+    /// use composer_core::Chord;
+    /// use composer_serialization::{Timeline, TokenEvent, TokenEventType};
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// engine.initialize(vec![])?;
+    ///
+    /// let events = vec![TokenEvent { beat: 0.0, event_type: TokenEventType::Chord(Chord::triad(1)?) }];
+    /// let timeline = Timeline { events, total_duration: 4.0, ..Default::default() };
+    ///
+    /// // Eighth-note hi-hats, snares on 2 and 4
+    /// let groove = engine.suggest_groove(&timeline, &GrooveOptions::default())?;
+    /// assert_eq!(groove.voice(DrumVoice::HiHat).count(), 8);
+    /// assert_eq!(groove.voice(DrumVoice::Snare).count(), 2);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`AiEngine::render_accompaniment`] - Accompaniment for the same chords
+    pub fn suggest_groove(&self, timeline: &Timeline, options: &GrooveOptions) -> AiResult<Groove> {
+        if !self.is_initialized() {
+            return Err(AiError::EngineNotInitialized);
+        }
+
+        let start_time = Instant::now();
+
+        let groove = suggest_groove(timeline, options)?;
+
+        self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
+
+        Ok(groove)
+    }
+
+    /// Rewrite a progression from one genre's idiom into another's
+    ///
+    /// Returns alternatives at each intensity of the target genre's
+    /// vocabulary, most confident first; see [`restyle_progression`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig, Genre};
+    /// use composer_core::Chord;
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// engine.initialize(vec![])?;
+    ///
+    /// let jazz = vec![Chord::seventh(2)?, Chord::new(5, 9)?, Chord::seventh(1)?];
+    /// let alternatives = engine.restyle_progression(&jazz, Genre::Jazz, Genre::Pop)?;
+    /// assert_eq!(alternatives[0].progression, vec![Chord::triad(2)?, Chord::triad(5)?, Chord::triad(1)?]);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`AiEngine::get_substitution_suggestions`] - Substitutions learned from patterns
+    pub fn restyle_progression(
+        &self,
+        progression: &[Chord],
+        from: Genre,
+        to: Genre,
+    ) -> AiResult<Vec<RestyledProgression>> {
+        if !self.is_initialized() {
+            return Err(AiError::EngineNotInitialized);
+        }
+
+        let start_time = Instant::now();
+
+        let alternatives = restyle_progression(progression, from, to)?;
+
+        self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
+
+        Ok(alternatives)
+    }
+
+    /// Generate bass harmonization (internal implementation)
+    fn generate_bass_harmonization(
+        &self,
+        progression: &[Chord],
+        options: &BassHarmonizationOptions,
+    ) -> AiResult<BassHarmonization> {
+        let mut bass_notes = Vec::new();
+        let mut rhythm = Vec::new();
+
+        match options.style {
+            BassStyle::Root => {
+                // Simple root note bass line
+                for chord in progression {
+                    bass_notes.push(chord.root);
+                    rhythm.push(composer_config::MUSICAL.ticks_per_beat as u32);
+                    // Whole note
+                }
+            },
+            BassStyle::Alternating => {
+                // Alternating bass pattern
+                for (i, chord) in progression.iter().enumerate() {
+                    if i % 2 == 0 {
+                        bass_notes.push(chord.root);
+                    } else {
+                        // Alternate with fifth (scale degree 5 relative to root)
+                        let fifth_degree = if chord.root + 4 > 7 {
+                            chord.root + 4 - 7
+                        } else {
+                            chord.root + 4
+                        };
+                        bass_notes.push(fifth_degree);
+                    }
+                    rhythm.push(composer_config::MUSICAL.ticks_per_beat as u32 / 2);
+                    // Half notes
+                }
+            },
+            BassStyle::Walking => {
+                // Octave-placed line through chord tones with approach notes
+                (bass_notes, rhythm) = walking_bass_line(progression, options)?;
+            },
+            BassStyle::Arpeggiated => {
+                // Arpeggiated bass patterns
+                for chord in progression {
+                    bass_notes.push(chord.root);
+                    // Third (scale degree 3 relative to root)
+                    let third_degree = if chord.root + 2 > 7 {
+                        chord.root + 2 - 7
+                    } else {
+                        chord.root + 2
+                    };
+                    bass_notes.push(third_degree);
+                    // Fifth (scale degree 5 relative to root)
+                    let fifth_degree = if chord.root + 4 > 7 {
+                        chord.root + 4 - 7
+                    } else {
+                        chord.root + 4
+                    };
+                    bass_notes.push(fifth_degree);
+
+                    rhythm.push(composer_config::MUSICAL.ticks_per_beat as u32 / 3);
+                    rhythm.push(composer_config::MUSICAL.ticks_per_beat as u32 / 3);
+                    rhythm.push(composer_config::MUSICAL.ticks_per_beat as u32 / 3);
+                }
+            },
+            BassStyle::Rhythmic => {
+                // Rhythmic bass patterns
+                for chord in progression {
+                    bass_notes.push(chord.root);
+                    bass_notes.push(chord.root); // Repeat for rhythm
+
+                    rhythm.push(composer_config::MUSICAL.ticks_per_beat as u32 / 2);
+                    rhythm.push(composer_config::MUSICAL.ticks_per_beat as u32 / 4);
+                }
+            },
+        }
+
+        let confidence = self.calculate_bass_harmonization_confidence(progression, options);
+
+        Ok(BassHarmonization {
+            bass_notes,
+            rhythm,
+            confidence,
+            style: options.style,
+        })
+    }
+
+    /// Calculate confidence in bass harmonization
+    fn calculate_bass_harmonization_confidence(
+        &self,
+        progression: &[Chord],
+        options: &BassHarmonizationOptions,
+    ) -> f64 {
+        let mut confidence: f64 = 0.7; // Base confidence
+
+        // Simpler styles have higher confidence
+        match options.style {
+            BassStyle::Root => confidence += 0.2,
+            BassStyle::Alternating => confidence += 0.1,
+            BassStyle::Walking => confidence -= 0.1,
+            BassStyle::Arpeggiated => confidence += 0.0,
+            BassStyle::Rhythmic => confidence += 0.05,
+        }
+
+        // Shorter progressions are easier to harmonize
+        if progression.len() <= 4 {
+            confidence += 0.1;
+        }
+
+        confidence.clamp(0.0, 1.0)
+    }
+
+    /// Add training pattern to the engine
+    pub fn add_training_pattern(
+        &self,
+        pattern: &[Chord],
+        source_id: String,
+        key_tonic: Option<Key>,
+    ) -> AiResult<()> {
+        self.trie.add_pattern(pattern, source_id, key_tonic)?;
+        self.trie.calculate_all_ranks();
+        self.invalidate_indexes();
+        Ok(())
+    }
+
+    /// Add a training pattern counting `weight` times as much as one added with
+    /// [`Self::add_training_pattern`].
+    ///
+    /// See [`ChordProgressionTrie::add_weighted_pattern`].
+    pub fn add_weighted_training_pattern(
+        &self,
+        pattern: &[Chord],
+        source_id: String,
+        key_tonic: Option<Key>,
+        weight: f64,
+    ) -> AiResult<()> {
+        self.trie
+            .add_weighted_pattern(pattern, source_id, key_tonic, weight)?;
+        self.trie.calculate_all_ranks();
+        self.invalidate_indexes();
+        Ok(())
+    }
+
+    /// Adds many training patterns in one pass.
+    ///
+    /// Unlike calling [`Self::add_training_pattern`] in a loop, ranks are
+    /// recalculated and the similarity indexes invalidated once for the whole
+    /// batch. Invalid patterns do not abort the load: they are skipped and
+    /// reported with their input position and the validation error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig};
+    /// use composer_core::Chord;
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// let report = engine.add_patterns_bulk(vec![
+    ///     (vec![Chord::triad(1)?, Chord::triad(4)?], "1".to_string(), None),
+    ///     (Vec::new(), "2".to_string(), None),
+    /// ]);
+    ///
+    /// assert_eq!(report.added, 1);
+    /// assert_eq!(report.rejected[0].index, 1);
+    /// assert_eq!(engine.get_metrics().total_patterns, 1);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn add_patterns_bulk<I>(&self, patterns: I) -> BulkAddReport
+    where
+        I: IntoIterator<Item = TrainingPattern>,
+    {
+        let report = self.trie.add_patterns_bulk(patterns);
+        if report.added > 0 {
+            self.invalidate_indexes();
+        }
+        report
+    }
+
+    /// Adds many weighted training patterns in one pass.
+    ///
+    /// Each pattern counts `weight` times as much as an unweighted one, so a
+    /// canonical corpus can outweigh user-submitted data in ranks and
+    /// suggestion scores. Loading and reporting are as for
+    /// [`Self::add_patterns_bulk`]; patterns with a weight that is not positive
+    /// and finite are rejected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig};
+    /// use composer_core::Chord;
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// let cadence = vec![Chord::triad(5)?, Chord::triad(1)?];
+    /// let report = engine.add_weighted_patterns_bulk(vec![
+    ///     ((cadence.clone(), "canon".to_string(), None), 4.0),
+    ///     ((cadence, "user".to_string(), None), -1.0),
+    /// ]);
+    ///
+    /// assert_eq!(report.added, 1);
+    /// assert_eq!(report.rejected[0].index, 1);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`Self::add_weighted_training_pattern`] - Add one weighted pattern
+    pub fn add_weighted_patterns_bulk<I>(&self, patterns: I) -> BulkAddReport
+    where
+        I: IntoIterator<Item = WeightedPattern>,
+    {
+        let report = self.trie.add_weighted_patterns_bulk(patterns);
+        if report.added > 0 {
+            self.invalidate_indexes();
+        }
+        report
+    }
+
+    /// Add patterns read from timelines, optionally weighted by chord duration.
+    ///
+    /// Each timeline contributes its chords in beat order, rests and notes
+    /// skipped, each chord lasting until the next event (the last until the
+    /// timeline's `total_duration`). With `weight_by_duration`, a chord counts in
+    /// proportion to how long it sounds, so passing chords don't dominate the
+    /// pattern statistics (see [`ChordProgressionTrie::add_timed_patterns_bulk`]),
+    /// and chord durations are kept for suggestions'
+    /// [`ChordSuggestion::suggested_duration`]; otherwise every chord counts
+    /// once, as with [`Self::add_patterns_bulk`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig};
+    /// use composer_core::Chord;
+    /// use composer_serialization::{Timeline, TokenEvent, TokenEventType};
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// let events = [(1, 0.0), (2, 3.5), (5, 4.0)]
+    ///     .into_iter()
+    ///     .map(|(root, beat)| {
+    ///         Ok(TokenEvent { beat, event_type: TokenEventType::Chord(Chord::triad(root)?) })
+    ///     })
+    ///     .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+    /// let timeline = Timeline { events, total_duration: 8.0, ..Default::default() };
+    ///
+    /// let report = engine.add_timeline_patterns(vec![(timeline, "song".to_string(), None)], true);
+    /// assert!(report.is_complete());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`Self::add_patterns_bulk`] - Add untimed chord patterns
+    pub fn add_timeline_patterns<I>(&self, patterns: I, weight_by_duration: bool) -> BulkAddReport
+    where
+        I: IntoIterator<Item = TimelinePattern>,
+    {
+        let timed = patterns
+            .into_iter()
+            .map(|(timeline, source_id, key_tonic)| {
+                let pattern: TimedPattern = timed_chords(&timeline)
+                    .into_iter()
+                    .map(|timed| (timed.chord, timed.end - timed.start))
+                    .unzip();
+                (pattern, source_id, key_tonic)
+            });
+
+        let report = if weight_by_duration {
+            self.trie.add_timed_patterns_bulk(timed)
+        } else {
+            self.trie.add_patterns_bulk(
+                timed.map(|((chords, _), source_id, key_tonic)| (chords, source_id, key_tonic)),
+            )
+        };
+        if report.added > 0 {
+            self.invalidate_indexes();
+        }
+        report
+    }
+
+    /// Get engine metrics
+    pub fn get_metrics(&self) -> EngineMetrics {
+        let mut metrics = self.metrics.read().clone();
+        metrics.uptime_seconds = self.start_time.elapsed().as_secs();
+        metrics.memory_usage_bytes = self.trie.memory_usage();
+        metrics.total_patterns = self.trie.total_patterns();
+        for (operation, count) in self.suggester.budget_violations() {
+            *metrics.budget_violations.entry(operation).or_insert(0) += count;
+        }
+        metrics
+    }
+
+    /// Set the time limits for suggestion and asset loading calls, and whether
+    /// exceeding them fails the call.
+    ///
+    /// By default slow calls still return their results and are only counted in
+    /// [`EngineMetrics::budget_violations`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig, BudgetMode, PerformanceBudget};
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// engine.set_performance_budget(PerformanceBudget {
+    ///     chord_lookup_max_ms: 20,
+    ///     mode: BudgetMode::Fail,
+    ///     ..PerformanceBudget::default()
+    /// });
+    /// assert_eq!(engine.get_config().performance_budget.chord_lookup_max_ms, 20);
+    /// ```
+    pub fn set_performance_budget(&self, budget: PerformanceBudget) {
+        self.suggester.set_performance_budget(budget);
+        self.config.write().performance_budget = budget;
+    }
+
+    /// Update engine configuration
+    pub fn update_config(&self, new_config: AiEngineConfig) -> AiResult<()> {
+        // Validate memory limits
+        if new_config.max_memory_mb > 1000 {
+            return Err(AiError::MemoryExhausted {
+                limit_mb: new_config.max_memory_mb,
+            });
+        }
+
+        // Validate model versions
+        for version in new_config.model_versions.values() {
+            ModelVersion::parse(version)?;
+        }
+
+        self.suggester.set_scale_fallback(new_config.scale_fallback);
+        self.suggester
+            .set_performance_budget(new_config.performance_budget);
+        self.suggester
+            .set_quality_thresholds(new_config.runtime.quality.clone());
+        self.analyzer
+            .set_analysis_parameters(new_config.runtime.analysis.clone());
+        *self.config.write() = new_config;
+        Ok(())
+    }
+
+    /// Structural statistics of the pattern trie
+    pub fn trie_statistics(&self) -> TrieStatistics {
+        self.trie.statistics()
+    }
+
+    /// Release spare capacity in the pattern trie after loading a corpus
+    ///
+    /// See [`ChordProgressionTrie::compact`]; returns the trie's memory use
+    /// before and after.
+    pub fn compact_trie(&self) -> CompactionStats {
+        self.trie.compact()
+    }
+
+    /// Chord histogram, progression frequencies and key coverage of the trained corpus
+    ///
+    /// See [`ChordProgressionTrie::corpus_statistics`].
+    pub fn corpus_statistics(&self, progression_length: usize) -> AiResult<CorpusStatistics> {
+        self.trie.corpus_statistics(progression_length)
+    }
+
+    /// Finds the chords of the trained vocabulary closest to `chord` in embedding space.
+    ///
+    /// Unlike [`Self::get_chord_suggestions`], which ranks what followed a pattern
+    /// in the corpus, this compares pitch content only (see [`crate::embed_chord`]),
+    /// so it finds plausible substitutes even for chords the corpus never used in
+    /// the current position. The query chord itself is excluded. The index over
+    /// the vocabulary is built on the first call and rebuilt after the patterns
+    /// change.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig};
+    /// use composer_core::Chord;
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// engine.initialize(vec![(
+    ///     vec![Chord::new(1, 5)?, Chord::new(6, 5)?, Chord::new(4, 5)?, Chord::new(5, 7)?],
+    ///     "pop".to_string(),
+    ///     None,
+    /// )])?;
+    ///
+    /// let similar = engine.similar_chords(&Chord::new(1, 5)?, 2)?;
+    /// assert_eq!(similar.len(), 2);
+    /// assert_eq!(similar[0].chord, Chord::new(6, 5)?); // shares two tones with I
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`crate::ChordIndex`] - Build an index over an arbitrary vocabulary
+    /// - [`Self::get_chord_suggestions`] - Corpus-driven next-chord suggestions
+    pub fn similar_chords(&self, chord: &Chord, k: usize) -> AiResult<Vec<SimilarChord>> {
+        if !self.is_initialized() {
+            return Err(AiError::EngineNotInitialized);
+        }
+
+        let start_time = Instant::now();
+        let index = self.chord_index()?;
+        let similar = index.similar_chords(chord, k)?;
+        self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
+        Ok(similar)
+    }
+
+    /// Cached chord index, building it from the trie vocabulary when missing
+    fn chord_index(&self) -> AiResult<Arc<ChordIndex>> {
+        if let Some(index) = self.chord_index.read().as_ref() {
+            return Ok(index.clone());
+        }
+
+        let index = Arc::new(ChordIndex::from_binary_vocabulary(
+            &self.trie.chord_vocabulary(),
+        )?);
+        *self.chord_index.write() = Some(index.clone());
+        Ok(index)
+    }
+
+    /// Similarity of two chords by usage in the trained corpus (-1.0 to 1.0).
+    ///
+    /// Compares the chords' vectors in the engine's [`ChordEmbeddingTable`]:
+    /// the one installed with [`Self::set_chord_embeddings`], or else one derived
+    /// from which chords precede and follow each other in the trie. Chords
+    /// missing from the table are compared by pitch content.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig};
+    /// use composer_core::Chord;
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// let chords = |roots: &[u8]| roots.iter().map(|r| Chord::triad(*r)).collect::<Result<Vec<_>, _>>();
+    /// engine.initialize(vec![
+    ///     (chords(&[1, 2, 5])?, "ii".to_string(), None),
+    ///     (chords(&[1, 4, 5])?, "IV".to_string(), None),
+    ///     (chords(&[1, 6, 3])?, "vi".to_string(), None),
+    /// ])?;
+    ///
+    /// let ii = Chord::triad(2)?;
+    /// assert!(engine.chord_similarity(&ii, &Chord::triad(4)?)? > engine.chord_similarity(&ii, &Chord::triad(6)?)?);
+    /// assert_eq!(engine.nearest_chords(&ii, 1)?[0].chord, Chord::triad(4)?);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`Self::nearest_chords`] - The closest chords of the table
+    /// - [`Self::similar_chords`] - Similarity by pitch content alone
+    pub fn chord_similarity(&self, a: &Chord, b: &Chord) -> AiResult<f64> {
+        if !self.is_initialized() {
+            return Err(AiError::EngineNotInitialized);
+        }
+
+        let start_time = Instant::now();
+        let similarity = self.embedding_table()?.chord_similarity(a, b)?;
+        self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
+        Ok(similarity)
+    }
+
+    /// Up to `k` chords closest to `chord` by usage in the trained corpus.
+    ///
+    /// Searches the table described in [`Self::chord_similarity`]. When a
+    /// pattern has no match, [`Self::get_chord_suggestions`] uses these
+    /// neighbors to suggest from similar patterns instead.
+    pub fn nearest_chords(&self, chord: &Chord, k: usize) -> AiResult<Vec<SimilarChord>> {
+        if !self.is_initialized() {
+            return Err(AiError::EngineNotInitialized);
+        }
+
+        let start_time = Instant::now();
+        let nearest = self.embedding_table()?.nearest_chords(chord, k)?;
+        self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
+        Ok(nearest)
+    }
+
+    /// Install chord embeddings learned offline, or return to embeddings
+    /// derived from the trie with `None`.
+    ///
+    /// An installed table is kept when patterns are added.
+    pub fn set_chord_embeddings(&self, table: Option<ChordEmbeddingTable>) {
+        *self.embedding_table.write() = table.map(Arc::new);
+        self.suggester.clear_cache();
+    }
+
+    /// Current embedding table, deriving it from the trie when missing
+    fn embedding_table(&self) -> AiResult<Arc<ChordEmbeddingTable>> {
+        if let Some(table) = self.embedding_table.read().as_ref() {
+            return Ok(table.clone());
+        }
+
+        let table = Arc::new(ChordEmbeddingTable::from_trie(&self.trie)?);
+        *self.embedding_table.write() = Some(table.clone());
+        Ok(table)
+    }
+
+    /// Finds the stored sources whose progressions are closest to `progression`.
+    ///
+    /// Progressions are compared with [`crate::embed_progression`] in
+    /// [`ProgressionEmbeddingMode::Combined`] mode, so both the chords used and
+    /// the root motion between them count. Each source is reported once, with
+    /// its best-matching progression.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig};
+    /// use composer_core::Chord;
+    ///
+    /// let chords = |roots: &[u8]| -> Vec<Chord> {
+    ///     roots.iter().map(|&root| Chord::new(root, 5).unwrap()).collect()
+    /// };
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// engine.initialize(vec![
+    ///     (chords(&[1, 5, 6, 4]), "axis".to_string(), None),
+    ///     (chords(&[2, 5, 1]), "cadence".to_string(), None),
+    /// ])?;
+    ///
+    /// let similar = engine.similar_progressions(&chords(&[1, 5, 6, 4]), 1)?;
+    /// assert_eq!(similar[0].source_id, "axis");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`Self::more_like_source`] - Query by a stored source instead of chords
+    /// - [`crate::ProgressionIndex`] - Build an index over an arbitrary catalog
+    pub fn similar_progressions(
+        &self,
+        progression: &[Chord],
+        k: usize,
+    ) -> AiResult<Vec<SimilarProgression>> {
+        if !self.is_initialized() {
+            return Err(AiError::EngineNotInitialized);
+        }
+
+        let start_time = Instant::now();
+        let similar = self
+            .progression_index()?
+            .similar_progressions(progression, k)?;
+        self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
+        Ok(similar)
+    }
+
+    /// Recommends other stored sources similar to `source_id` ("more like this").
+    ///
+    /// The query combines every progression the source contributed, and the
+    /// source itself is never part of the result. Fails with
+    /// [`AiError::InvalidPattern`] when no pattern was stored under `source_id`.
+    pub fn more_like_source(&self, source_id: &str, k: usize) -> AiResult<Vec<SimilarProgression>> {
+        if !self.is_initialized() {
+            return Err(AiError::EngineNotInitialized);
+        }
+
+        let start_time = Instant::now();
+        let similar = self.progression_index()?.more_like_source(source_id, k)?;
+        self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
+        Ok(similar)
+    }
+
+    /// Suggests reharmonizations of `chord` in the key of `scale_fingerprint`.
+    ///
+    /// Candidates come from [`composer_core::get_substitutions`] (tritone
+    /// substitute, relative and parallel swaps, backdoor dominant, secondary
+    /// dominant and diminished passing chords), so every suggestion is valid in
+    /// the key. They are ranked by how often the substitute chord occurs in the
+    /// training corpus, keeping theory order between equally common ones. Tritone
+    /// substitutes are not distinguished in stored patterns and always report a
+    /// frequency of zero.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig};
+    /// use composer_core::{Chord, ScaleFingerprint, SubstitutionKind};
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// engine.initialize(vec![(
+    ///     vec![Chord::new(1, 5)?, Chord::new(6, 5)?, Chord::new(4, 5)?],
+    ///     "pop".to_string(),
+    ///     None,
+    /// )])?;
+    ///
+    /// let suggestions =
+    ///     engine.get_substitution_suggestions(&Chord::new(1, 5)?, &ScaleFingerprint::major_scale())?;
+    /// // vi is the relative minor of I and the only substitute the corpus uses
+    /// assert_eq!(suggestions[0].substitution.kind, SubstitutionKind::Relative);
+    /// assert_eq!(suggestions[0].frequency, 1);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`Self::similar_chords`] - Substitutes by pitch content rather than theory
+    /// - [`composer_core::is_valid_tri_sub`] - Tritone substitution eligibility
+    pub fn get_substitution_suggestions(
+        &self,
+        chord: &Chord,
+        scale_fingerprint: &ScaleFingerprint,
+    ) -> AiResult<Vec<SubstitutionSuggestion>> {
+        if !self.is_initialized() {
+            return Err(AiError::EngineNotInitialized);
+        }
+
+        let start_time = Instant::now();
+        let substitutions =
+            get_substitutions(chord, scale_fingerprint).map_err(|e| AiError::InvalidPattern {
+                reason: format!("Invalid chord for substitution: {}", e),
+            })?;
+
+        let vocabulary: HashMap<_, _> = self.trie.chord_vocabulary().into_iter().collect();
+        let mut suggestions: Vec<SubstitutionSuggestion> = substitutions
+            .into_iter()
+            .map(|substitution| {
+                let frequency = if substitution.chord.substitutions.is_empty() {
+                    composer_serialization::serialize_chord(&substitution.chord)
+                        .ok()
+                        .and_then(|key| vocabulary.get(&key).copied())
+                        .unwrap_or(0)
+                } else {
+                    0
+                };
+                SubstitutionSuggestion {
+                    substitution,
+                    frequency,
+                }
+            })
+            .collect();
+        suggestions.sort_by_key(|s| std::cmp::Reverse(s.frequency));
+
+        self.update_metrics_for_request(start_time.elapsed().as_millis() as f64);
+        Ok(suggestions)
+    }
+
+    /// Cached progression index, building it from the trie when missing
+    fn progression_index(&self) -> AiResult<Arc<ProgressionIndex>> {
+        if let Some(index) = self.progression_index.read().as_ref() {
+            return Ok(index.clone());
+        }
+
+        let index = Arc::new(ProgressionIndex::from_trie(
+            &self.trie,
+            ProgressionEmbeddingMode::Combined,
+        )?);
+        *self.progression_index.write() = Some(index.clone());
+        Ok(index)
+    }
+
+    /// Drop the embedding indexes so they are rebuilt from the current trie
+    fn invalidate_indexes(&self) {
+        *self.chord_index.write() = None;
+        *self.progression_index.write() = None;
+        let mut table = self.embedding_table.write();
+        if table
+            .as_ref()
+            .is_some_and(|table| table.source() == EmbeddingSource::Cooccurrence)
+        {
+            *table = None;
+        }
+    }
+
+    /// Get current configuration
+    pub fn get_config(&self) -> AiEngineConfig {
+        self.config.read().clone()
+    }
+
+    /// Attach an external neural scorer, or detach it with `None`.
+    ///
+    /// The scorer is consulted by [`Self::get_chord_suggestions`] whenever
+    /// [`SuggestionConfig::neural_weight`] is positive. See [`crate::NeuralScorer`].
+    pub fn set_neural_scorer(&self, scorer: Option<SharedNeuralScorer>) {
+        self.suggester.set_neural_scorer(scorer);
+    }
+
+    /// Attach a second suggestion backend, or detach it with `None`.
+    ///
+    /// Whenever [`SuggestionConfig::backend_weight`] is positive,
+    /// [`Self::get_chord_suggestions`] adds the backend's candidates to the
+    /// trie's and blends its probabilities into the frequency score. See
+    /// [`crate::SuggestionBackend`] and [`crate::NGramModel`].
+    pub fn set_suggestion_backend(&self, backend: Option<SharedSuggestionBackend>) {
+        self.suggester.set_backend(backend);
+    }
+
+    /// Replace the built-in difficulty model, or restore it with `None`.
+    ///
+    /// The model receives [`DifficultyFeatures`] and its prediction becomes the
+    /// overall score of [`Self::assess_difficulty`]. See [`crate::DifficultyModel`].
+    pub fn set_difficulty_model(&self, model: Option<SharedDifficultyModel>) {
+        self.analyzer.set_difficulty_model(model);
+    }
+
+    /// Fit the polynomial difficulty model to labeled progressions and install it
+    ///
+    /// Each example pairs a progression with a target overall score on the
+    /// 0.0-10.0 scale, such as a teacher's grade. Features are extracted as by
+    /// [`AiEngine::extract_difficulty_features`] with no tempo or time signature.
+    /// A model attached with [`AiEngine::set_difficulty_model`] still takes
+    /// precedence. See [`PolynomialModel::fit`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig};
+    /// use composer_core::Chord;
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// engine.initialize(vec![(vec![Chord::triad(1)?, Chord::triad(5)?], "pop".to_string(), None)])?;
+    ///
+    /// let examples = vec![
+    ///     (vec![Chord::triad(1)?, Chord::triad(4)?], 1.0),
+    ///     (vec![Chord::triad(1)?, Chord::triad(5)?, Chord::triad(1)?], 1.5),
+    ///     (vec![Chord::seventh(2)?, Chord::seventh(5)?, Chord::triad(1)?], 4.0),
+    ///     (vec![Chord::triad(6)?, Chord::seventh(4)?, Chord::triad(5)?, Chord::triad(3)?], 5.0),
+    ///     (vec![Chord::seventh(7)?, Chord::seventh(3)?, Chord::seventh(6)?, Chord::seventh(2)?], 7.5),
+    ///     (vec![Chord::triad(2)?, Chord::triad(6)?, Chord::seventh(7)?, Chord::triad(4)?, Chord::seventh(1)?], 6.0),
+    /// ];
+    /// let model = engine.fit_difficulty_model(&examples)?;
+    /// assert_eq!(engine.polynomial_model(), model);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`AiEngine::set_polynomial_model`] - Install a previously fitted model
+    /// - [`AiEngine::export_difficulty_model_asset`] - Persist the installed model
+    pub fn fit_difficulty_model(
+        &self,
+        examples: &[LabeledProgression],
+    ) -> AiResult<PolynomialModel> {
+        if !self.is_initialized() {
+            return Err(AiError::EngineNotInitialized);
         }
 
-        let confidence = self.calculate_bass_harmonization_confidence(progression, options);
-
-        Ok(BassHarmonization {
-            bass_notes,
-            rhythm,
-            confidence,
-            style: options.style,
-        })
+        let labeled = examples
+            .iter()
+            .map(|(progression, target)| {
+                self.analyzer
+                    .extract_difficulty_features(progression, None, None)
+                    .map(|features| (features, *target))
+            })
+            .collect::<AiResult<Vec<_>>>()?;
+        let model = PolynomialModel::fit(&labeled)?;
+        self.analyzer.set_polynomial_model(model.clone())?;
+        Ok(model)
     }
 
-    /// Calculate passing tone for walking bass
-    fn calculate_passing_tone(&self, from_root: u8, to_root: u8) -> u8 {
-        let interval = (to_root as i8 - from_root as i8).abs();
-
-        if interval == 1 {
-            // Chromatic passing tone
-            (from_root + 1) % 12
-        } else if interval >= 3 {
-            // Scale-wise passing tone (simplified)
-            (from_root + 2) % 12
-        } else {
-            // Default to chromatic
-            (from_root + 1) % 12
-        }
+    /// Install a polynomial difficulty model, e.g. one loaded from JSON
+    pub fn set_polynomial_model(&self, model: PolynomialModel) -> AiResult<()> {
+        self.analyzer.set_polynomial_model(model)
     }
 
-    /// Calculate confidence in bass harmonization
-    fn calculate_bass_harmonization_confidence(
-        &self,
-        progression: &[Chord],
-        options: &BassHarmonizationOptions,
-    ) -> f64 {
-        let mut confidence: f64 = 0.7; // Base confidence
-
-        // Simpler styles have higher confidence
-        match options.style {
-            BassStyle::Root => confidence += 0.2,
-            BassStyle::Alternating => confidence += 0.1,
-            BassStyle::Walking => confidence -= 0.1,
-            BassStyle::Arpeggiated => confidence += 0.0,
-            BassStyle::Rhythmic => confidence += 0.05,
-        }
+    /// Polynomial difficulty model currently installed
+    pub fn polynomial_model(&self) -> PolynomialModel {
+        self.analyzer.difficulty_model()
+    }
 
-        // Shorter progressions are easier to harmonize
-        if progression.len() <= 4 {
-            confidence += 0.1;
-        }
+    /// Calibrate the score cutoffs of skill levels and bands
+    ///
+    /// Affects [`DifficultyAssessment::skill_level`] and
+    /// [`DifficultyAssessment::skill_band`] of later assessments, including
+    /// the level used to pick reference progressions. See [`crate::SkillThresholds`].
+    pub fn set_skill_thresholds(&self, thresholds: SkillThresholds) {
+        self.analyzer.set_skill_thresholds(thresholds);
+    }
 
-        confidence.clamp(0.0, 1.0)
+    /// Score cutoffs currently used for skill levels and bands
+    pub fn skill_thresholds(&self) -> SkillThresholds {
+        self.analyzer.skill_thresholds()
     }
 
-    /// Add training pattern to the engine
-    pub fn add_training_pattern(
+    /// Calibrate difficulty percentiles against assessments of a corpus
+    ///
+    /// Later assessments report in [`DifficultyAssessment::percentile`] the
+    /// share of the corpus they are harder than. See [`crate::DifficultyCalibration`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig};
+    /// use composer_core::Chord;
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// engine.initialize(vec![])?;
+    ///
+    /// let library = [
+    ///     vec![Chord::triad(1)?, Chord::triad(5)?],
+    ///     vec![Chord::seventh(2)?, Chord::seventh(5)?, Chord::seventh(1)?],
+    /// ];
+    /// let corpus = library
+    ///     .iter()
+    ///     .map(|song| engine.assess_difficulty(song, None, None))
+    ///     .collect::<Result<Vec<_>, _>>()?;
+    /// engine.calibrate_difficulty(&corpus)?;
+    ///
+    /// let assessment = engine.assess_difficulty(&library[1], None, None)?;
+    /// assert_eq!(assessment.percentile, Some(50));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn calibrate_difficulty(
         &self,
-        pattern: &[Chord],
-        source_id: String,
-        key_tonic: Option<String>,
+        corpus_assessments: &[DifficultyAssessment],
     ) -> AiResult<()> {
-        self.trie.add_pattern(pattern, source_id, key_tonic)?;
-        self.trie.calculate_all_ranks();
-        Ok(())
-    }
-
-    /// Get engine metrics
-    pub fn get_metrics(&self) -> EngineMetrics {
-        let mut metrics = self.metrics.read().clone();
-        metrics.uptime_seconds = self.start_time.elapsed().as_secs();
-        metrics.memory_usage_bytes = self.trie.memory_usage();
-        metrics.total_patterns = self.trie.total_patterns();
-        metrics
+        self.analyzer.calibrate(corpus_assessments)
     }
 
-    /// Update engine configuration
-    pub fn update_config(&self, new_config: AiEngineConfig) -> AiResult<()> {
-        // Validate memory limits
-        if new_config.max_memory_mb > 1000 {
-            return Err(AiError::MemoryExhausted {
-                limit_mb: new_config.max_memory_mb,
-            });
-        }
-
-        *self.config.write() = new_config;
-        Ok(())
+    /// Replace the difficulty percentile calibration, or drop it with `None`
+    pub fn set_difficulty_calibration(&self, calibration: Option<DifficultyCalibration>) {
+        self.analyzer.set_calibration(calibration);
     }
 
-    /// Get current configuration
-    pub fn get_config(&self) -> AiEngineConfig {
-        self.config.read().clone()
+    /// Corpus score distribution currently used for percentiles
+    pub fn difficulty_calibration(&self) -> Option<DifficultyCalibration> {
+        self.analyzer.calibration()
     }
 
     /// Clear all caches
     pub fn clear_caches(&self) {
         self.suggester.clear_cache();
         self.analyzer.clear_cache();
+        self.invalidate_indexes();
     }
 
     /// Validate memory usage against limits
@@ -831,6 +2767,239 @@ impl AiEngine {
         Ok(())
     }
 
+    /// Model version the engine requires for an asset kind.
+    ///
+    /// Read from `AiEngineConfig::model_versions`; kinds without an entry default to
+    /// the running engine version.
+    pub fn required_model_version(&self, kind: AssetKind) -> AiResult<ModelVersion> {
+        match self.config.read().model_versions.get(kind.model_key()) {
+            Some(version) => ModelVersion::parse(version),
+            None => ModelVersion::current(),
+        }
+    }
+
+    /// Register an explicit migration for upgrading older assets
+    pub fn register_migration(&self, migration: AssetMigration) -> AiResult<()> {
+        self.migrations.write().register(migration)
+    }
+
+    /// Check a single serialized asset against the engine's model versions
+    pub fn check_asset_compatibility(&self, asset: &[u8]) -> AssetCompatibility {
+        let header = match AssetHeader::decode(asset) {
+            Ok(header) => header,
+            Err(e) => {
+                return AssetCompatibility {
+                    kind: None,
+                    asset_version: None,
+                    required_version: None,
+                    status: Compatibility::Incompatible {
+                        reason: e.to_string(),
+                    },
+                }
+            },
+        };
+
+        let (required_version, status) = match self.required_model_version(header.kind) {
+            Ok(required) => (
+                Some(required),
+                self.migrations
+                    .read()
+                    .negotiate(header.kind, header.model_version, required),
+            ),
+            Err(e) => (
+                None,
+                Compatibility::Incompatible {
+                    reason: e.to_string(),
+                },
+            ),
+        };
+
+        AssetCompatibility {
+            kind: Some(header.kind),
+            asset_version: Some(header.model_version),
+            required_version,
+            status,
+        }
+    }
+
+    /// Builds a compatibility report for a set of serialized assets.
+    ///
+    /// Each asset's header is read and its model version negotiated against the
+    /// version configured in `AiEngineConfig::model_versions`. Nothing is loaded, so
+    /// this is safe to call before deciding whether to ship or fetch new assets.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{encode_asset, AiEngine, AiEngineConfig, AssetKind, ModelVersion};
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    ///
+    /// let current = encode_asset(AssetKind::ChordTrie, ModelVersion::new(2, 35, 2), &[]);
+    /// let future = encode_asset(AssetKind::DifficultyModel, ModelVersion::new(3, 0, 0), &[]);
+    ///
+    /// let report = engine.compatibility_report(&[&current, &future]);
+    /// assert!(report.assets[0].status.is_loadable());
+    /// assert!(!report.assets[1].status.is_loadable());
+    /// assert!(!report.all_loadable());
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`AiEngine::negotiate_asset`] - Migrate or reject an asset before loading it
+    /// - [`AiEngine::register_migration`] - Allow older assets to be upgraded
+    pub fn compatibility_report(&self, assets: &[&[u8]]) -> CompatibilityReport {
+        CompatibilityReport {
+            engine_version: composer_config::APPLICATION.version.to_string(),
+            assets: assets
+                .iter()
+                .map(|asset| self.check_asset_compatibility(asset))
+                .collect(),
+        }
+    }
+
+    /// Negotiates a serialized asset's version, returning a payload the engine can load.
+    ///
+    /// Assets whose version matches the engine's required version (ignoring patch
+    /// releases) are returned unchanged; the returned header carries the engine's
+    /// required version. Older assets are upgraded through the
+    /// registered migrations; assets with no migration path, newer assets, and assets
+    /// from a different major version are rejected with
+    /// [`AiError::InvalidModelVersion`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{
+    ///     encode_asset, AiEngine, AiEngineConfig, AiResult, AssetKind, AssetMigration,
+    ///     ModelVersion,
+    /// };
+    ///
+    /// fn upgrade(payload: &[u8]) -> AiResult<Vec<u8>> {
+    ///     Ok(payload.to_vec())
+    /// }
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// let old = encode_asset(AssetKind::ChordTrie, ModelVersion::new(2, 34, 0), &[1, 2, 3]);
+    ///
+    /// // Rejected until a migration is registered
+    /// assert!(engine.negotiate_asset(&old).is_err());
+    ///
+    /// engine.register_migration(AssetMigration {
+    ///     kind: AssetKind::ChordTrie,
+    ///     from: ModelVersion::new(2, 34, 0),
+    ///     to: ModelVersion::new(2, 35, 0),
+    ///     apply: upgrade,
+    /// })?;
+    ///
+    /// let negotiated = engine.negotiate_asset(&old)?;
+    /// assert_eq!(negotiated.header.model_version, ModelVersion::new(2, 35, 2));
+    /// assert_eq!(negotiated.payload.as_ref(), &[1, 2, 3]);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`AiEngine::compatibility_report`] - Inspect assets without loading them
+    /// - [`encode_asset`](crate::encode_asset) - Produce a versioned asset
+    pub fn negotiate_asset<'a>(&self, asset: &'a [u8]) -> AiResult<VersionedAsset<'a>> {
+        let header = AssetHeader::decode(asset)?;
+        let required = self.required_model_version(header.kind)?;
+        let payload = self.migrations.read().migrate(
+            header.kind,
+            header.model_version,
+            required,
+            &asset[crate::compatibility::ASSET_HEADER_LEN..],
+        )?;
+
+        Ok(VersionedAsset {
+            header: AssetHeader::new(header.kind, required),
+            payload,
+        })
+    }
+
+    /// Starts recording engine interactions into a new session.
+    ///
+    /// Recording is opt-in: until this is called, queries are not captured. Every
+    /// subsequent suggestion, difficulty, and bass harmonization query is stored with
+    /// its arguments and outcome until [`AiEngine::stop_session_recording`] is called.
+    /// Starting again discards the session in progress.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{AiEngine, AiEngineConfig, FeedbackKind};
+    /// use composer_core::Chord;
+    ///
+    /// let engine = AiEngine::new(AiEngineConfig::default());
+    /// engine.initialize(vec![])?;
+    ///
+    /// engine.start_session_recording();
+    /// engine.assess_difficulty(&[Chord::triad(1)?, Chord::seventh(5)?], None, None)?;
+    ///
+    /// let id = engine.last_interaction_id().unwrap();
+    /// engine.record_feedback(id, FeedbackKind::Rating { score: 0.8 })?;
+    ///
+    /// let session = engine.stop_session_recording().unwrap();
+    /// assert_eq!(session.interactions.len(), 1);
+    /// assert_eq!(session.feedback.len(), 1);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`AiEngine::stop_session_recording`] - Finish and retrieve the session
+    /// - [`AiEngine::record_feedback`] - Attach user feedback to an interaction
+    /// - [`Session::save`] - Write a session file
+    pub fn start_session_recording(&self) {
+        self.recorder.start(self.get_config());
+    }
+
+    /// Stop recording and return the recorded session, if one was active
+    pub fn stop_session_recording(&self) -> Option<Session> {
+        self.recorder.stop()
+    }
+
+    /// Check if a session is being recorded
+    pub fn is_recording_session(&self) -> bool {
+        self.recorder.is_recording()
+    }
+
+    /// Copy of the session recorded so far, without stopping the recording
+    pub fn session_snapshot(&self) -> Option<Session> {
+        self.recorder.snapshot()
+    }
+
+    /// Id of the most recently recorded interaction
+    pub fn last_interaction_id(&self) -> Option<u64> {
+        self.recorder.last_interaction_id()
+    }
+
+    /// Attach user feedback to a recorded interaction
+    pub fn record_feedback(&self, interaction_id: u64, feedback: FeedbackKind) -> AiResult<()> {
+        self.recorder.record_feedback(interaction_id, feedback)
+    }
+
+    /// Record a query and its result if a session is being recorded
+    fn record_interaction<T, Q>(&self, query: Q, result: &AiResult<T>, start_time: Instant)
+    where
+        T: Clone + Into<SessionOutcome>,
+        Q: FnOnce() -> SessionQuery,
+    {
+        if !self.recorder.is_recording() {
+            return;
+        }
+
+        let outcome = match result {
+            Ok(value) => value.clone().into(),
+            Err(e) => SessionOutcome::Error(e.to_string()),
+        };
+        let elapsed_ms = start_time.elapsed().as_secs_f64() * 1000.0;
+
+        self.recorder
+            .record_interaction(query(), outcome, elapsed_ms);
+    }
+
     /// Update performance metrics after a request
     fn update_metrics_for_request(&self, response_time_ms: f64) {
         let mut metrics = self.metrics.write();
@@ -867,6 +3036,217 @@ mod tests {
     use super::*;
     use composer_core::Chord;
 
+    #[test]
+    fn test_model_versions_drive_negotiation() {
+        let mut config = AiEngineConfig::default();
+        config
+            .model_versions
+            .insert("difficulty_assessment".to_string(), "v2.20.0".to_string());
+        let engine = AiEngine::new(config);
+
+        assert_eq!(
+            engine
+                .required_model_version(AssetKind::DifficultyModel)
+                .unwrap(),
+            ModelVersion::new(2, 20, 0)
+        );
+
+        let asset =
+            crate::encode_asset(AssetKind::DifficultyModel, ModelVersion::new(2, 35, 2), &[]);
+        let report = engine.compatibility_report(&[&asset, &[0u8; 3]]);
+        assert!(!report.all_loadable());
+        assert_eq!(report.incompatible().len(), 2);
+        assert!(matches!(
+            engine.negotiate_asset(&asset),
+            Err(AiError::InvalidModelVersion { .. })
+        ));
+
+        let mut bad_config = AiEngineConfig::default();
+        bad_config
+            .model_versions
+            .insert("chord_suggestion".to_string(), "latest".to_string());
+        assert!(engine.update_config(bad_config).is_err());
+    }
+
+    #[test]
+    fn test_initialize_from_binary() {
+        let trainer = AiEngine::new(AiEngineConfig::default());
+        trainer
+            .initialize(vec![
+                (
+                    vec![Chord::new(1, 5).unwrap(), Chord::new(5, 7).unwrap()],
+                    "1".to_string(),
+                    None,
+                ),
+                (
+                    vec![Chord::new(1, 5).unwrap(), Chord::new(4, 5).unwrap()],
+                    "2".to_string(),
+                    None,
+                ),
+            ])
+            .unwrap();
+
+        let asset = trainer.export_trie_asset().unwrap();
+        let engine = AiEngine::new(AiEngineConfig::default());
+        engine.initialize_from_binary(&asset).unwrap();
+        assert!(engine.is_initialized());
+        assert_eq!(engine.get_metrics().total_patterns, 2);
+        assert!(engine.initialize_from_binary(&asset).is_err());
+
+        // The bare trie is accepted too
+        let bare = &asset[crate::compatibility::ASSET_HEADER_LEN..];
+        let engine = AiEngine::new(AiEngineConfig::default());
+        engine.initialize_from_binary(bare).unwrap();
+        assert_eq!(engine.get_metrics().total_patterns, 2);
+
+        // Compressed assets are unpacked first
+        use composer_serialization::{compress, Compression};
+        for packed in [
+            compress(&asset, Compression::Zstd).unwrap(),
+            compress(bare, Compression::Lz4).unwrap(),
+        ] {
+            let engine = AiEngine::new(AiEngineConfig::default());
+            engine.initialize_from_binary(&packed).unwrap();
+            assert_eq!(engine.get_metrics().total_patterns, 2);
+        }
+
+        // Wrong asset kind and truncated payloads are rejected
+        let mut wrong_kind = asset.clone();
+        wrong_kind[5] = 2;
+        let engine = AiEngine::new(AiEngineConfig::default());
+        assert!(engine.initialize_from_binary(&wrong_kind).is_err());
+        assert!(engine
+            .initialize_from_binary(&asset[..asset.len() - 3])
+            .is_err());
+        assert!(!engine.is_initialized());
+    }
+
+    #[test]
+    fn test_add_timeline_patterns() {
+        use composer_serialization::{TokenEvent, TokenEventType};
+
+        let chord_at = |root: u8, beat: f64| TokenEvent {
+            beat,
+            event_type: TokenEventType::Chord(Chord::triad(root).unwrap()),
+        };
+        let timeline = |events: Vec<TokenEvent>, total_duration: f64| Timeline {
+            events,
+            total_duration,
+            meter_changes: Vec::new(),
+            key_changes: Vec::new(),
+        };
+        // I with a passing ii twice, I-IV held once, and a final chord of no length
+        let patterns = || {
+            vec![
+                (
+                    timeline(vec![chord_at(1, 0.0), chord_at(2, 3.5)], 4.0),
+                    "a".to_string(),
+                    None,
+                ),
+                (
+                    timeline(vec![chord_at(1, 0.0), chord_at(2, 3.5)], 4.0),
+                    "b".to_string(),
+                    None,
+                ),
+                (
+                    timeline(vec![chord_at(1, 0.0), chord_at(4, 2.0)], 4.0),
+                    "c".to_string(),
+                    None,
+                ),
+                (
+                    timeline(vec![chord_at(1, 0.0), chord_at(5, 2.0)], 2.0),
+                    "d".to_string(),
+                    None,
+                ),
+            ]
+        };
+        let top_after_tonic = |engine: &AiEngine| {
+            let results = engine
+                .trie
+                .search_with_wildcard(&[Some(Chord::triad(1).unwrap()), None], 10)
+                .unwrap();
+            let top = results.iter().find(|result| result.rank == 1).unwrap();
+            deserialize_chord(&top.serialized_chord).unwrap().root
+        };
+
+        let counted = AiEngine::new(AiEngineConfig::default());
+        assert_eq!(counted.add_timeline_patterns(patterns(), false).added, 4);
+        assert_eq!(top_after_tonic(&counted), 2);
+
+        let weighted = AiEngine::new(AiEngineConfig::default());
+        let report = weighted.add_timeline_patterns(patterns(), true);
+        assert_eq!(report.added, 3);
+        assert_eq!(report.rejected[0].source_id, "d");
+        assert_eq!(top_after_tonic(&weighted), 4);
+    }
+
+    #[test]
+    fn test_initialize_from_tokens() {
+        let mut library = TokenLibrary::new();
+        for (token, root) in [("I", 1), ("IV", 4), ("V", 5)] {
+            let binary = composer_serialization::serialize_chord(&Chord::new(root, 5).unwrap());
+            library.add_chord_token(token.to_string(), binary.unwrap().to_vec());
+        }
+        let tokens = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+
+        let engine = AiEngine::new(AiEngineConfig::default());
+        engine
+            .initialize_from_tokens(
+                &library,
+                vec![
+                    (tokens(&["I", "IV", "V", "I"]), "1".to_string(), None),
+                    (
+                        tokens(&["I", "V"]),
+                        "2".to_string(),
+                        Some("G".parse().unwrap()),
+                    ),
+                ],
+            )
+            .unwrap();
+        assert!(engine.is_initialized());
+        assert_eq!(engine.get_metrics().total_patterns, 2);
+
+        // Token-trained and chord-trained engines store the same patterns
+        let chord_engine = AiEngine::new(AiEngineConfig::default());
+        chord_engine
+            .initialize(vec![
+                (
+                    [1, 4, 5, 1]
+                        .iter()
+                        .map(|&root| Chord::new(root, 5).unwrap())
+                        .collect(),
+                    "1".to_string(),
+                    None,
+                ),
+                (
+                    vec![Chord::new(1, 5).unwrap(), Chord::new(5, 5).unwrap()],
+                    "2".to_string(),
+                    Some("G".parse().unwrap()),
+                ),
+            ])
+            .unwrap();
+        assert_eq!(
+            engine.export_trie_asset().unwrap(),
+            chord_engine.export_trie_asset().unwrap()
+        );
+        assert!(engine.initialize_from_tokens(&library, Vec::new()).is_err());
+
+        // Unknown tokens and malformed library entries are rejected
+        let engine = AiEngine::new(AiEngineConfig::default());
+        let result = engine.initialize_from_tokens(
+            &library,
+            vec![(tokens(&["I", "vi"]), "3".to_string(), None)],
+        );
+        assert!(matches!(result, Err(AiError::InvalidPattern { .. })));
+        assert!(!engine.is_initialized());
+
+        library.add_chord_token("bad".to_string(), vec![0xff; 3]);
+        let engine = AiEngine::new(AiEngineConfig::default());
+        assert!(engine
+            .initialize_from_tokens(&library, vec![(tokens(&["bad"]), "4".to_string(), None)])
+            .is_err());
+    }
+
     #[test]
     fn test_engine_creation() {
         let config = AiEngineConfig::default();
@@ -882,7 +3262,7 @@ mod tests {
         let training_patterns = vec![(
             vec![Chord::new(1, 5).unwrap(), Chord::new(5, 7).unwrap()],
             "song1".to_string(),
-            Some("C".to_string()),
+            Some("C".parse().unwrap()),
         )];
 
         let result = engine.initialize(training_patterns);
@@ -926,6 +3306,7 @@ mod tests {
                 complexity: 0.5,
                 enable_walking: false,
                 rhythm_pattern: None,
+                ..Default::default()
             };
 
             let result = engine.harmonize_bass_line(&progression, &options);
@@ -956,6 +3337,317 @@ mod tests {
         assert!(assessment.overall_score >= 0.0 && assessment.overall_score <= 10.0);
     }
 
+    #[test]
+    fn test_timeline_difficulty() {
+        use composer_serialization::{MeterChange, TokenEvent, TokenEventType};
+
+        let engine = AiEngine::new(AiEngineConfig::default());
+        engine.initialize(vec![]).unwrap();
+        let progression: Vec<Chord> = [1, 6, 4, 5]
+            .iter()
+            .map(|&root| Chord::triad(root).unwrap())
+            .collect();
+
+        let common = engine
+            .assess_difficulty(&progression, None, Some((4, 4)))
+            .unwrap();
+        let irregular = engine
+            .assess_difficulty(&progression, None, Some((7, 8)))
+            .unwrap();
+        assert_eq!(irregular.factors.time_signature_changes, 0);
+        assert!(irregular.rhythmic_complexity > common.rhythmic_complexity);
+
+        // One chord per 4/4 bar, then a change to 5/4 with a pushed chord
+        let events = progression
+            .iter()
+            .zip([0.0, 4.0, 8.0, 12.5])
+            .map(|(chord, beat)| TokenEvent {
+                beat,
+                event_type: TokenEventType::Chord(chord.clone()),
+            })
+            .collect();
+        let mut timeline = Timeline {
+            events,
+            total_duration: 16.0,
+            meter_changes: Vec::new(),
+            key_changes: Vec::new(),
+        };
+        let plain = engine
+            .assess_timeline_difficulty(&timeline, None, None)
+            .unwrap();
+        assert_eq!(plain.factors.time_signature_changes, 0);
+
+        timeline.meter_changes.push(MeterChange {
+            beat: 8.0,
+            time_signature: (5, 4),
+        });
+        let changing = engine
+            .assess_timeline_difficulty(&timeline, None, None)
+            .unwrap();
+        assert_eq!(changing.factors.time_signature_changes, 1);
+        assert!(changing.rhythmic_complexity > plain.rhythmic_complexity);
+        assert_eq!(changing.harmonic_complexity, common.harmonic_complexity);
+    }
+
+    #[test]
+    fn test_instrument_difficulty() {
+        let engine = AiEngine::new(AiEngineConfig::default());
+        engine.initialize(vec![]).unwrap();
+        let progression = vec![
+            Chord::triad(4).unwrap(),
+            Chord::triad(2).unwrap(),
+            Chord::triad(5).unwrap(),
+        ];
+
+        let agnostic = engine.assess_difficulty(&progression, None, None).unwrap();
+        assert_eq!(agnostic.instrument, None);
+
+        let by_instrument = engine
+            .assess_difficulty_by_instrument(&progression, None, None)
+            .unwrap();
+        assert_eq!(by_instrument.len(), DifficultyProfile::ALL.len());
+        for (profile, assessment) in &by_instrument {
+            assert_eq!(assessment.instrument, Some(*profile));
+            assert_eq!(assessment.harmonic_complexity, agnostic.harmonic_complexity);
+            assert!((0.0..=10.0).contains(&assessment.overall_score));
+        }
+
+        // F major and B minor need barres on guitar but not on piano
+        let (_, piano) = &by_instrument[0];
+        let (_, guitar) = &by_instrument[1];
+        assert!(guitar.technical_complexity > piano.technical_complexity);
+
+        engine.start_session_recording();
+        engine
+            .assess_instrument_difficulty(&progression, None, None, DifficultyProfile::Voice)
+            .unwrap();
+        let session = engine.stop_session_recording().unwrap();
+        let recorded = match &session.interactions[0].query {
+            SessionQuery::AssessDifficulty { instrument, .. } => *instrument,
+            _ => None,
+        };
+        assert_eq!(recorded, Some(DifficultyProfile::Voice));
+    }
+
+    #[test]
+    fn test_difficulty_model_asset_round_trip() {
+        let engine = AiEngine::new(AiEngineConfig::default());
+        engine.initialize(vec![]).unwrap();
+        let fitted = PolynomialModel {
+            coefficients: [0.0, 0.1, 0.7, 0.05],
+            ..PolynomialModel::default()
+        };
+        engine.set_polynomial_model(fitted.clone()).unwrap();
+        let asset = engine.export_difficulty_model_asset().unwrap();
+
+        let restored = AiEngine::new(AiEngineConfig::default());
+        restored.load_difficulty_model_asset(&asset).unwrap();
+        assert_eq!(restored.polynomial_model(), fitted);
+
+        let trie_asset = engine.export_trie_asset().unwrap();
+        assert!(restored.load_difficulty_model_asset(&trie_asset).is_err());
+        assert!(engine.fit_difficulty_model(&[]).is_err());
+    }
+
+    #[test]
+    fn test_custom_difficulty_model() {
+        let engine = AiEngine::new(AiEngineConfig::default());
+        engine.initialize(vec![]).unwrap();
+        let progression = vec![Chord::new(1, 5).unwrap(), Chord::new(5, 7).unwrap()];
+
+        let builtin = engine
+            .assess_difficulty(&progression, Some(120.0), Some((4, 4)))
+            .unwrap();
+        let features = engine
+            .extract_difficulty_features(&progression, Some(120.0), Some((4, 4)))
+            .unwrap();
+        assert_eq!(features, DifficultyFeatures::from_assessment(&builtin));
+
+        let model =
+            |features: &DifficultyFeatures| Ok(features.get("unique_chords").unwrap() * 6.0);
+        engine.set_difficulty_model(Some(Arc::new(model)));
+        let custom = engine
+            .assess_difficulty(&progression, Some(120.0), Some((4, 4)))
+            .unwrap();
+        assert_eq!(custom.overall_score, 10.0);
+        assert_eq!(custom.harmonic_complexity, builtin.harmonic_complexity);
+
+        let failing = |_: &DifficultyFeatures| Ok(f64::NAN);
+        engine.set_difficulty_model(Some(Arc::new(failing)));
+        assert!(engine.assess_difficulty(&progression, None, None).is_err());
+
+        engine.set_difficulty_model(None);
+        let restored = engine
+            .assess_difficulty(&progression, Some(120.0), Some((4, 4)))
+            .unwrap();
+        assert_eq!(restored.overall_score, builtin.overall_score);
+    }
+
+    #[test]
+    fn test_similar_chords_tracks_vocabulary() {
+        let engine = AiEngine::new(AiEngineConfig::default());
+        let query = Chord::new(1, 5).unwrap();
+        assert!(engine.similar_chords(&query, 3).is_err());
+
+        engine
+            .initialize(vec![(
+                vec![query.clone(), Chord::new(4, 5).unwrap()],
+                "1".to_string(),
+                None,
+            )])
+            .unwrap();
+        let similar = engine.similar_chords(&query, 3).unwrap();
+        assert_eq!(similar.len(), 1);
+        assert_eq!(similar[0].chord.root, 4);
+        assert_eq!(similar[0].frequency, 1);
+
+        // New patterns invalidate the cached index
+        engine
+            .add_training_pattern(
+                &[Chord::new(6, 5).unwrap(), Chord::new(4, 5).unwrap()],
+                "2".to_string(),
+                None,
+            )
+            .unwrap();
+        let similar = engine.similar_chords(&query, 3).unwrap();
+        assert_eq!(similar.len(), 2);
+        assert_eq!(similar[0].chord.root, 6);
+        assert_eq!(similar[1].frequency, 2);
+    }
+
+    #[test]
+    fn test_suggestions_back_off_to_similar_chords() {
+        let engine = AiEngine::new(AiEngineConfig::default());
+        let triads = |roots: &[u8]| -> Vec<Chord> {
+            roots.iter().map(|r| Chord::triad(*r).unwrap()).collect()
+        };
+        engine
+            .initialize(vec![
+                (triads(&[1, 2, 5]), "ii".to_string(), None),
+                (triads(&[6, 4, 5]), "IV".to_string(), None),
+            ])
+            .unwrap();
+
+        // I-IV never occurs, but IV is used like ii, which leads to V after I
+        let config = SuggestionConfig {
+            min_confidence: 0.0,
+            ..Default::default()
+        };
+        let context = SuggestionContext::default();
+        let suggestions = engine
+            .get_chord_suggestions(&triads(&[1, 4]), &context, &config)
+            .unwrap();
+        assert_eq!(suggestions[0].chord, Chord::triad(5).unwrap());
+        assert!(suggestions[0].reasoning.contains("in place of"));
+
+        let exact_only = SuggestionConfig {
+            similarity_backoff: false,
+            ..config
+        };
+        assert!(engine
+            .get_chord_suggestions(&triads(&[1, 3]), &context, &exact_only)
+            .unwrap()
+            .is_empty());
+
+        // An installed table survives new patterns
+        let table = ChordEmbeddingTable::from_vectors(vec![
+            (Chord::triad(2).unwrap(), vec![1.0, 0.0]),
+            (Chord::triad(4).unwrap(), vec![0.0, 1.0]),
+        ])
+        .unwrap();
+        engine.set_chord_embeddings(Some(table));
+        engine
+            .add_training_pattern(&triads(&[2, 4]), "2".to_string(), None)
+            .unwrap();
+        let similarity = engine
+            .chord_similarity(&Chord::triad(2).unwrap(), &Chord::triad(4).unwrap())
+            .unwrap();
+        assert_eq!(similarity, 0.0);
+    }
+
+    #[test]
+    fn test_more_like_source() {
+        let chords = |roots: &[u8]| -> Vec<Chord> {
+            roots
+                .iter()
+                .map(|&root| Chord::new(root, 5).unwrap())
+                .collect()
+        };
+        let engine = AiEngine::new(AiEngineConfig::default());
+        assert!(engine.more_like_source("axis", 1).is_err());
+
+        engine
+            .initialize(vec![
+                (chords(&[1, 5, 6, 4]), "axis".to_string(), None),
+                (chords(&[6, 4, 1, 5]), "rotated".to_string(), None),
+                (chords(&[2, 5, 1]), "cadence".to_string(), None),
+            ])
+            .unwrap();
+        let similar = engine.more_like_source("axis", 5).unwrap();
+        let sources: Vec<&str> = similar.iter().map(|s| s.source_id.as_str()).collect();
+        assert_eq!(sources, ["rotated", "cadence"]);
+        assert_eq!(similar[0].progression, chords(&[6, 4, 1, 5]));
+        assert!(engine.more_like_source("unknown", 1).is_err());
+
+        // New patterns invalidate the cached index
+        engine
+            .add_training_pattern(&chords(&[1, 5, 6, 4, 1]), "axis-live".to_string(), None)
+            .unwrap();
+        let similar = engine
+            .similar_progressions(&chords(&[1, 5, 6, 4]), 1)
+            .unwrap();
+        assert_eq!(similar[0].source_id, "axis");
+        assert_eq!(
+            engine.more_like_source("axis", 1).unwrap()[0].source_id,
+            "axis-live"
+        );
+    }
+
+    #[test]
+    fn test_substitution_suggestions_ranked_by_corpus() {
+        let engine = AiEngine::new(AiEngineConfig::default());
+        let major = ScaleFingerprint::major_scale();
+        let dominant = Chord::new(5, 7).unwrap();
+        assert!(engine
+            .get_substitution_suggestions(&dominant, &major)
+            .is_err());
+
+        // The corpus uses V7/V twice and iii7 once
+        let secondary = Chord::new(5, 7).unwrap().with_applied(5).unwrap();
+        engine
+            .initialize(vec![
+                (
+                    vec![secondary.clone(), dominant.clone()],
+                    "1".to_string(),
+                    None,
+                ),
+                (
+                    vec![secondary, Chord::new(3, 7).unwrap()],
+                    "2".to_string(),
+                    None,
+                ),
+            ])
+            .unwrap();
+
+        let suggestions = engine
+            .get_substitution_suggestions(&dominant, &major)
+            .unwrap();
+        let ranked: Vec<_> = suggestions
+            .iter()
+            .map(|s| (s.substitution.kind, s.frequency))
+            .collect();
+        assert_eq!(
+            ranked[0],
+            (composer_core::SubstitutionKind::SecondaryDominant, 2)
+        );
+        assert_eq!(ranked[1], (composer_core::SubstitutionKind::Relative, 1));
+        assert_eq!(ranked[2], (composer_core::SubstitutionKind::Tritone, 0));
+        assert!(engine
+            .get_substitution_suggestions(&Chord::rest(), &major)
+            .unwrap()
+            .is_empty());
+    }
+
     #[test]
     fn test_metrics_tracking() {
         let config = AiEngineConfig::default();
@@ -991,6 +3683,84 @@ mod tests {
         assert!(!updated_config.enable_advanced_suggestions);
     }
 
+    #[test]
+    fn test_runtime_config_reaches_suggester() {
+        let engine = AiEngine::new(AiEngineConfig::default());
+        engine.initialize(vec![]).unwrap();
+        assert!(!engine
+            .get_harmonize_by_sd_solutions(1, Mode::Major, 20)
+            .unwrap()
+            .is_empty());
+
+        let mut config = engine.get_config();
+        config.runtime.quality.confidence_threshold = 1.0;
+        engine.update_config(config).unwrap();
+        assert_eq!(
+            engine.suggester.quality_thresholds().confidence_threshold,
+            1.0
+        );
+        assert!(engine
+            .get_harmonize_by_sd_solutions(1, Mode::Major, 20)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_novelty_uses_configured_threshold() {
+        let engine = AiEngine::new(AiEngineConfig::default());
+        let (tonic, dominant) = (Chord::triad(1).unwrap(), Chord::triad(5).unwrap());
+        let progression = [tonic.clone(), dominant.clone()];
+        assert!(matches!(
+            engine.compute_novelty(&progression),
+            Err(AiError::EngineNotInitialized)
+        ));
+
+        engine
+            .initialize(vec![
+                (progression.to_vec(), "authentic".to_string(), None),
+                (
+                    vec![tonic.clone(), Chord::triad(4).unwrap()],
+                    "plagal".to_string(),
+                    None,
+                ),
+            ])
+            .unwrap();
+        let report = engine.compute_novelty(&progression).unwrap();
+        assert_eq!(report.transitions[0].probability, 0.5);
+        assert!(!report.novel);
+
+        let mut config = engine.get_config();
+        config.runtime.analysis.novelty_threshold = 0.6;
+        engine.update_config(config).unwrap();
+        assert!(engine.compute_novelty(&progression).unwrap().novel);
+    }
+
+    #[test]
+    fn test_session_recording_is_opt_in() {
+        let engine = AiEngine::new(AiEngineConfig::default());
+        engine.initialize(vec![]).unwrap();
+
+        let progression = vec![Chord::new(1, 5).unwrap(), Chord::new(5, 7).unwrap()];
+        engine.assess_difficulty(&progression, None, None).unwrap();
+        assert!(!engine.is_recording_session());
+        assert!(engine.stop_session_recording().is_none());
+
+        engine.start_session_recording();
+        engine.assess_difficulty(&progression, None, None).unwrap();
+        let _ = engine.get_magic_bass_solutions("C".parse().unwrap(), Mode::Major, 3);
+
+        let session = engine.stop_session_recording().unwrap();
+        assert_eq!(session.interactions.len(), 2);
+        assert!(matches!(
+            session.interactions[0].outcome,
+            SessionOutcome::Difficulty(_)
+        ));
+        assert_eq!(
+            session.interactions[1].query.operation(),
+            "get_magic_bass_solutions"
+        );
+    }
+
     #[test]
     fn test_memory_validation() {
         let config = AiEngineConfig::default();
@@ -1015,8 +3785,11 @@ mod tests {
         engine.initialize(vec![]).unwrap();
 
         let pattern = vec![Chord::new(1, 5).unwrap(), Chord::new(5, 7).unwrap()];
-        let result =
-            engine.add_training_pattern(&pattern, "test_song".to_string(), Some("C".to_string()));
+        let result = engine.add_training_pattern(
+            &pattern,
+            "test_song".to_string(),
+            Some("C".parse().unwrap()),
+        );
         assert!(result.is_ok());
 
         let metrics = engine.get_metrics();
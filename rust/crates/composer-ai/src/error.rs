@@ -1,20 +1,31 @@
 //! Error types for AI-powered features
 
+use composer_core::ChordTheoryError;
+use composer_serialization::SerializationError;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 /// Error types for AI operations
-#[derive(Debug, Error, Clone, PartialEq)]
+///
+/// Serializes as `{"code": ..., "details": {...}}`, where `code` is the stable
+/// code from [`AiError::code`] and `details` holds the variant's fields.
+#[derive(Debug, Error, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "code", content = "details")]
 pub enum AiError {
     #[error("Engine not initialized")]
+    #[serde(rename = "engine-not-initialized")]
     EngineNotInitialized,
 
     #[error("Data corruption detected: {details}")]
+    #[serde(rename = "data-corruption")]
     DataCorruption { details: String },
 
     #[error("Memory exhausted: {limit_mb}MB limit exceeded")]
+    #[serde(rename = "memory-exhausted")]
     MemoryExhausted { limit_mb: u32 },
 
     #[error("Performance degradation: {operation} took {ms}ms (limit: {limit_ms}ms)")]
+    #[serde(rename = "response-timeout")]
     PerformanceDegradation {
         operation: String,
         ms: u64,
@@ -22,42 +33,83 @@ pub enum AiError {
     },
 
     #[error("Invalid pattern: {reason}")]
+    #[serde(rename = "invalid-pattern")]
     InvalidPattern { reason: String },
 
     #[error("Suggestion generation failed: {reason}")]
+    #[serde(rename = "suggestion-failed")]
     SuggestionFailed { reason: String },
 
     #[error("Model not found: {model_name}")]
+    #[serde(rename = "model-not-found")]
     ModelNotFound { model_name: String },
 
     #[error("Invalid model version: {version}")]
+    #[serde(rename = "invalid-model-version")]
     InvalidModelVersion { version: String },
 
+    #[error("Invalid model: {reason}")]
+    #[serde(rename = "invalid-model")]
+    InvalidModel { reason: String },
+
     #[error("Cache full: {cache_name} at capacity")]
+    #[serde(rename = "cache-full")]
     CacheFull { cache_name: String },
 
     #[error("Analysis failed: {reason}")]
+    #[serde(rename = "analysis-failed")]
     AnalysisFailed { reason: String },
+
+    #[error("Session recording error: {reason}")]
+    #[serde(rename = "session-failed")]
+    SessionError { reason: String },
+
+    #[error("Thread pool error: {reason}")]
+    #[serde(rename = "thread-pool-failed")]
+    ThreadPool { reason: String },
 }
 
 /// Result type for AI operations
 pub type AiResult<T> = Result<T, AiError>;
 
 impl AiError {
+    /// Stable machine-readable code for this error, one of the `ERROR_*`
+    /// constants in `composer_config`
+    pub fn code(&self) -> &'static str {
+        match self {
+            AiError::EngineNotInitialized => composer_config::ERROR_ENGINE_NOT_INITIALIZED,
+            AiError::DataCorruption { .. } => composer_config::ERROR_DATA_CORRUPTION,
+            AiError::MemoryExhausted { .. } => composer_config::ERROR_MEMORY_EXHAUSTED,
+            AiError::PerformanceDegradation { .. } => composer_config::ERROR_RESPONSE_TIMEOUT,
+            AiError::InvalidPattern { .. } => composer_config::ERROR_INVALID_PATTERN,
+            AiError::SuggestionFailed { .. } => composer_config::ERROR_SUGGESTION_FAILED,
+            AiError::ModelNotFound { .. } => composer_config::ERROR_MODEL_NOT_FOUND,
+            AiError::InvalidModelVersion { .. } => composer_config::ERROR_INVALID_MODEL_VERSION,
+            AiError::InvalidModel { .. } => composer_config::ERROR_INVALID_MODEL,
+            AiError::CacheFull { .. } => composer_config::ERROR_CACHE_FULL,
+            AiError::AnalysisFailed { .. } => composer_config::ERROR_ANALYSIS_FAILED,
+            AiError::SessionError { .. } => composer_config::ERROR_SESSION_FAILED,
+            AiError::ThreadPool { .. } => composer_config::ERROR_THREAD_POOL_FAILED,
+        }
+    }
+
     /// Check if this error is recoverable
     pub fn is_recoverable(&self) -> bool {
         match self {
             AiError::EngineNotInitialized
             | AiError::InvalidPattern { .. }
             | AiError::ModelNotFound { .. }
-            | AiError::InvalidModelVersion { .. } => false,
+            | AiError::InvalidModelVersion { .. }
+            | AiError::InvalidModel { .. } => false,
 
             AiError::DataCorruption { .. }
             | AiError::MemoryExhausted { .. }
             | AiError::PerformanceDegradation { .. }
             | AiError::SuggestionFailed { .. }
             | AiError::CacheFull { .. }
-            | AiError::AnalysisFailed { .. } => true,
+            | AiError::AnalysisFailed { .. }
+            | AiError::SessionError { .. }
+            | AiError::ThreadPool { .. } => true,
         }
     }
 
@@ -72,8 +124,77 @@ impl AiError {
             AiError::SuggestionFailed { .. } => Severity::Medium,
             AiError::ModelNotFound { .. } => Severity::High,
             AiError::InvalidModelVersion { .. } => Severity::High,
+            AiError::InvalidModel { .. } => Severity::High,
             AiError::CacheFull { .. } => Severity::Low,
             AiError::AnalysisFailed { .. } => Severity::Medium,
+            AiError::SessionError { .. } => Severity::Low,
+            AiError::ThreadPool { .. } => Severity::Low,
+        }
+    }
+}
+
+/// Any error raised by the Composer crates, as reported to the language bindings
+///
+/// Serializes as `{"domain": ..., "code": ..., "details": {...}}` so callers can
+/// dispatch on the stable `code` instead of parsing messages.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::ComposerError;
+/// use composer_core::Chord;
+///
+/// let error = ComposerError::from(Chord::new(9, 5).unwrap_err());
+/// assert_eq!(error.code(), composer_config::ERROR_INVALID_CHORD_ROOT);
+/// assert_eq!(error.domain(), "theory");
+/// assert_eq!(error.details()["root"], 9);
+/// ```
+#[derive(Debug, Error, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "domain", rename_all = "snake_case")]
+pub enum ComposerError {
+    #[error(transparent)]
+    Theory(#[from] ChordTheoryError),
+
+    #[error(transparent)]
+    Serialization(#[from] SerializationError),
+
+    #[error(transparent)]
+    Ai(#[from] AiError),
+}
+
+impl ComposerError {
+    /// Stable machine-readable code, one of the `ERROR_*` constants in `composer_config`
+    pub fn code(&self) -> &'static str {
+        match self {
+            ComposerError::Theory(e) => e.code(),
+            ComposerError::Serialization(e) => e.code(),
+            ComposerError::Ai(e) => e.code(),
+        }
+    }
+
+    /// Crate the error came from: `"theory"`, `"serialization"` or `"ai"`
+    pub fn domain(&self) -> &'static str {
+        match self {
+            ComposerError::Theory(_) => "theory",
+            ComposerError::Serialization(_) => "serialization",
+            ComposerError::Ai(_) => "ai",
+        }
+    }
+
+    /// Fields of the error variant as a JSON object, empty for errors without fields
+    pub fn details(&self) -> serde_json::Value {
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|mut value| value.get_mut("details").map(serde_json::Value::take))
+            .unwrap_or_else(|| serde_json::Value::Object(Default::default()))
+    }
+
+    /// Check if this error is recoverable
+    pub fn is_recoverable(&self) -> bool {
+        match self {
+            ComposerError::Theory(e) => e.is_recoverable(),
+            ComposerError::Serialization(e) => e.is_recoverable(),
+            ComposerError::Ai(e) => e.is_recoverable(),
         }
     }
 }
@@ -86,3 +207,58 @@ pub enum Severity {
     High,
     Critical,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialized_code_matches_code() {
+        let errors: Vec<ComposerError> = vec![
+            ChordTheoryError::InvalidChordRoot { root: 9 }.into(),
+            ChordTheoryError::IncompatibleAlterations {
+                alterations: vec!["b5".to_string(), "#5".to_string()],
+            }
+            .into(),
+            SerializationError::UnexpectedEof.into(),
+            SerializationError::Timeout {
+                elapsed_ms: 20,
+                limit_ms: 10,
+            }
+            .into(),
+            AiError::EngineNotInitialized.into(),
+            AiError::PerformanceDegradation {
+                operation: "suggest".to_string(),
+                ms: 80,
+                limit_ms: 50,
+            }
+            .into(),
+        ];
+
+        for error in errors {
+            let json = serde_json::to_value(&error).unwrap();
+            assert_eq!(json["code"], error.code());
+            assert_eq!(json["domain"], error.domain());
+            assert!(composer_config::ERROR_CODES
+                .iter()
+                .any(|(_, code)| *code == error.code()));
+            assert!(error.details().is_object());
+
+            let back: ComposerError = serde_json::from_value(json).unwrap();
+            assert_eq!(back, error);
+        }
+    }
+
+    #[test]
+    fn test_error_details() {
+        let error = ComposerError::from(AiError::CacheFull {
+            cache_name: "suggestions".to_string(),
+        });
+        assert_eq!(error.code(), "cache-full");
+        assert_eq!(error.details()["cache_name"], "suggestions");
+        assert_eq!(
+            ComposerError::from(AiError::EngineNotInitialized).details(),
+            serde_json::json!({})
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! High-level entry point for common tasks
+//!
+//! [`Composer`] wraps an [`AiEngine`] and the key progressions are written in,
+//! so analyzing a progression, suggesting what comes next and exporting the
+//! learned patterns each take one call with sensible defaults. Everything else
+//! is on the engine itself, through [`Composer::engine`].
+
+use crate::analysis::{DifficultyAssessment, ProgressionAnalysis};
+use crate::engine::{AiEngine, AiEngineConfig, TrainingPattern};
+use crate::error::AiResult;
+use crate::suggestions::{ChordSuggestion, SuggestionConfig, SuggestionContext};
+use composer_core::{Chord, ScaleFingerprint};
+use serde::{Deserialize, Serialize};
+
+/// Difficulty and harmonic analysis of one progression
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgressionReport {
+    pub difficulty: DifficultyAssessment,
+    pub analysis: ProgressionAnalysis,
+}
+
+/// An initialized engine and a key, for one-call analysis and suggestions
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::prelude::*;
+///
+/// let composer = Composer::from_patterns(vec![(
+///     vec![Chord::new(2, 7)?, Chord::new(5, 7)?, Chord::triad(1)?],
+///     "ii-V-I".to_string(),
+///     None,
+/// )])?;
+///
+/// let suggestions = composer.suggest(&[Chord::new(2, 7)?], 3)?;
+/// assert_eq!(suggestions[0].chord, Chord::new(5, 7)?);
+///
+/// let report = composer.analyze(&[Chord::new(2, 7)?, Chord::new(5, 7)?, Chord::triad(1)?])?;
+/// assert!(report.difficulty.overall_score > 0.0);
+///
+/// // The learned patterns load back into a new instance
+/// let restored = Composer::from_trie_asset(&composer.export()?)?;
+/// assert_eq!(restored.engine().get_metrics().total_patterns, 1);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct Composer {
+    engine: AiEngine,
+    scale: ScaleFingerprint,
+}
+
+impl Default for Composer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Composer {
+    /// An instance with no learned patterns, in a major key
+    pub fn new() -> Self {
+        let engine = AiEngine::new(AiEngineConfig::default());
+        // Initializing a fresh engine with no patterns cannot fail
+        let _ = engine.initialize(Vec::new());
+        Self::with_engine(engine)
+    }
+
+    /// An instance that has learned `patterns`, in a major key
+    pub fn from_patterns(patterns: Vec<TrainingPattern>) -> AiResult<Self> {
+        let engine = AiEngine::new(AiEngineConfig::default());
+        engine.initialize(patterns)?;
+        Ok(Self::with_engine(engine))
+    }
+
+    /// An instance loaded from a trie asset written by [`Composer::export`]
+    /// or `composer-cli ingest`, in a major key
+    pub fn from_trie_asset(bytes: &[u8]) -> AiResult<Self> {
+        let engine = AiEngine::new(AiEngineConfig::default());
+        engine.initialize_from_binary(bytes)?;
+        Ok(Self::with_engine(engine))
+    }
+
+    /// Wrap an already initialized engine, in a major key
+    pub fn with_engine(engine: AiEngine) -> Self {
+        Self {
+            engine,
+            scale: ScaleFingerprint::major_scale(),
+        }
+    }
+
+    /// Read progressions in `scale` instead
+    pub fn with_scale(mut self, scale: ScaleFingerprint) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// The wrapped engine, for everything beyond the common tasks
+    pub fn engine(&self) -> &AiEngine {
+        &self.engine
+    }
+
+    /// Scale progressions are read in
+    pub fn scale(&self) -> &ScaleFingerprint {
+        &self.scale
+    }
+
+    /// Assess the difficulty of a progression and analyze its harmony
+    ///
+    /// See [`AiEngine::assess_difficulty`] and [`AiEngine::analyze_progression`].
+    pub fn analyze(&self, progression: &[Chord]) -> AiResult<ProgressionReport> {
+        Ok(ProgressionReport {
+            difficulty: self.engine.assess_difficulty(progression, None, None)?,
+            analysis: self.engine.analyze_progression(progression)?,
+        })
+    }
+
+    /// Up to `count` chords to follow a progression, best first
+    ///
+    /// Suggestions come from the learned patterns, so an instance without any
+    /// returns none. See [`AiEngine::get_chord_suggestions`] for finer control.
+    pub fn suggest(&self, progression: &[Chord], count: usize) -> AiResult<Vec<ChordSuggestion>> {
+        let context = SuggestionContext {
+            scale_fingerprint: Some(self.scale),
+            position_in_progression: 1.0,
+            ..Default::default()
+        };
+        let config = SuggestionConfig {
+            max_suggestions: count,
+            ..Default::default()
+        };
+        let mut suggestions = self
+            .engine
+            .get_chord_suggestions(progression, &context, &config)?;
+        suggestions.truncate(count);
+        Ok(suggestions)
+    }
+
+    /// Learn one more progression
+    pub fn learn(&self, progression: &[Chord], source_id: impl Into<String>) -> AiResult<()> {
+        self.engine
+            .add_training_pattern(progression, source_id.into(), None)
+    }
+
+    /// The learned patterns as a trie asset for [`Composer::from_trie_asset`]
+    pub fn export(&self) -> AiResult<Vec<u8>> {
+        self.engine.export_trie_asset()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_learn_and_suggest() {
+        let composer = Composer::new();
+        assert!(composer
+            .suggest(&[Chord::triad(1).unwrap()], 3)
+            .unwrap()
+            .is_empty());
+
+        for _ in 0..3 {
+            composer
+                .learn(
+                    &[
+                        Chord::triad(1).unwrap(),
+                        Chord::triad(4).unwrap(),
+                        Chord::triad(5).unwrap(),
+                    ],
+                    "I-IV-V",
+                )
+                .unwrap();
+        }
+        let suggestions = composer.suggest(&[Chord::triad(1).unwrap()], 1).unwrap();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].chord, Chord::triad(4).unwrap());
+        assert!(composer.learn(&[], "empty").is_err());
+    }
+}
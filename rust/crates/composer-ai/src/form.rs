@@ -0,0 +1,487 @@
+//! Phrase and form segmentation
+//!
+//! Splits a timeline into phrases at cadence points, rests and unusually long
+//! chords (a slowing harmonic rhythm), labels the phrases by repetition (`A`,
+//! `B`, ...), and groups them into sections with verse/chorus-style role
+//! candidates. Everything works on scale degrees, so the result does not depend
+//! on the key the timeline was written in.
+
+use crate::analysis::MusicalAnalyzer;
+use crate::error::{AiError, AiResult};
+use composer_core::Chord;
+use composer_serialization::{serialize_chord, ChordBinary, Timeline, TokenEventType};
+use serde::{Deserialize, Serialize};
+
+/// Phrases shorter than this never end at a cadence
+const MIN_PHRASE_CHORDS: usize = 2;
+
+/// Phrases are split after this many chords even without a cadence
+const MAX_PHRASE_CHORDS: usize = 8;
+
+/// A chord held at least this many times the median duration ends a phrase
+const LONG_CHORD_RATIO: f64 = 2.0;
+
+/// Phrases at least this similar share a label
+const REPETITION_THRESHOLD: f64 = 0.75;
+
+/// Cadence types recognized at phrase endings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CadenceType {
+    /// V or vii° resolving to I
+    Authentic,
+    /// IV resolving to I
+    Plagal,
+    /// Phrase ending on V
+    Half,
+    /// V resolving to vi
+    Deceptive,
+}
+
+/// Candidate role of a section within a song
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum SectionRole {
+    /// Unrepeated opening material
+    Intro,
+    /// Repeated material preceding the chorus
+    Verse,
+    /// The most repeated material
+    Chorus,
+    /// Unrepeated material between repeated sections
+    Bridge,
+    /// Unrepeated closing material
+    Outro,
+    /// No role could be inferred
+    #[default]
+    Other,
+}
+
+/// One phrase found by [`MusicalAnalyzer::analyze_form`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Phrase {
+    /// Repetition label (`A`, `B`, ...); repeated phrases share a label
+    pub label: String,
+
+    /// Range of chord indices, end exclusive, counting chords only
+    pub chord_range: (usize, usize),
+
+    /// Beat where the phrase starts
+    pub start_beat: f64,
+
+    /// Beat where the phrase ends
+    pub end_beat: f64,
+
+    /// Cadence closing the phrase, if any
+    pub cadence: Option<CadenceType>,
+}
+
+/// Consecutive phrases sharing a label
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Section {
+    /// Label shared by the section's phrases
+    pub label: String,
+
+    /// Candidate role of the section
+    pub role: SectionRole,
+
+    /// Range of phrase indices, end exclusive
+    pub phrase_range: (usize, usize),
+
+    /// Beat where the section starts
+    pub start_beat: f64,
+
+    /// Beat where the section ends
+    pub end_beat: f64,
+}
+
+/// Result of [`MusicalAnalyzer::analyze_form`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FormAnalysis {
+    /// Phrases in timeline order
+    pub phrases: Vec<Phrase>,
+
+    /// Sections in timeline order
+    pub sections: Vec<Section>,
+
+    /// Phrase labels in order, e.g. "AABA"
+    pub form: String,
+}
+
+/// Chord with its position on the timeline
+#[derive(Debug, Clone)]
+pub(crate) struct TimedChord {
+    pub(crate) chord: Chord,
+    key: Option<ChordBinary>,
+    pub(crate) start: f64,
+    pub(crate) end: f64,
+    rest_after: bool,
+}
+
+impl MusicalAnalyzer {
+    /// Segment a timeline into phrases and sections.
+    ///
+    /// A phrase ends after an authentic or plagal cadence, before a rest,
+    /// after a chord held at least twice the median chord duration (which is
+    /// how half cadences usually close), or after eight chords at the latest. Phrases whose chords mostly match position by position share a
+    /// label, consecutive phrases with the same label form a section, and
+    /// sections get role candidates: the most repeated label is the chorus,
+    /// another repeated label the verse, and unrepeated sections are intro,
+    /// bridge or outro by position.
+    ///
+    /// Chord durations come from the next event on the timeline, or from
+    /// `total_duration` for the last one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{ChordProgressionTrie, MusicalAnalyzer, SectionRole};
+    /// use composer_core::Chord;
+    /// use composer_serialization::{Timeline, TokenEvent, TokenEventType};
+    /// use std::sync::Arc;
+    ///
+    /// let analyzer = MusicalAnalyzer::new(Arc::new(ChordProgressionTrie::new()));
+    ///
+    /// // I-IV-V-I then vi-ii-IV-I, each ending on a cadence
+    /// let verse = [1, 4, 5, 1];
+    /// let chorus = [6, 2, 4, 1];
+    /// let roots: Vec<u8> = [verse, chorus, verse, chorus].concat();
+    /// let events = roots
+    ///     .iter()
+    ///     .enumerate()
+    ///     .map(|(i, &root)| TokenEvent {
+    ///         beat: i as f64 * 4.0,
+    ///         event_type: TokenEventType::Chord(Chord::new(root, 5).unwrap()),
+    ///     })
+    ///     .collect();
+    /// let timeline = Timeline {
+    ///     events,
+    ///     total_duration: roots.len() as f64 * 4.0,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let form = analyzer.analyze_form(&timeline)?;
+    /// assert_eq!(form.form, "ABAB");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn analyze_form(&self, timeline: &Timeline) -> AiResult<FormAnalysis> {
+        let chords = timed_chords(timeline);
+        if chords.is_empty() {
+            return Err(AiError::AnalysisFailed {
+                reason: "Cannot analyze the form of a timeline without chords".to_string(),
+            });
+        }
+
+        let mut phrases = segment_phrases(&chords);
+        label_phrases(&mut phrases, &chords);
+        let sections = group_sections(&phrases);
+        let form = phrases.iter().map(|phrase| phrase.label.as_str()).collect();
+
+        Ok(FormAnalysis {
+            phrases,
+            sections,
+            form,
+        })
+    }
+}
+
+/// Chords of a timeline in beat order with their spans
+pub(crate) fn timed_chords(timeline: &Timeline) -> Vec<TimedChord> {
+    let mut events: Vec<_> = timeline.events.iter().collect();
+    events.sort_by(|a, b| a.beat.total_cmp(&b.beat));
+
+    let mut chords: Vec<TimedChord> = Vec::new();
+    for (index, event) in events.iter().enumerate() {
+        let next_beat = events
+            .get(index + 1)
+            .map_or(timeline.total_duration.max(event.beat), |next| next.beat);
+        match &event.event_type {
+            TokenEventType::Chord(chord) if !chord.is_rest => chords.push(TimedChord {
+                chord: chord.clone(),
+                key: serialize_chord(chord).ok(),
+                start: event.beat,
+                end: next_beat,
+                rest_after: false,
+            }),
+            TokenEventType::Chord(_) | TokenEventType::Rest => {
+                if let Some(last) = chords.last_mut() {
+                    last.rest_after = true;
+                }
+            },
+            TokenEventType::Note(_) | TokenEventType::Cluster(_) => {},
+        }
+    }
+    chords
+}
+
+/// Cadence formed by arriving on `chords[index]`
+fn cadence_at(chords: &[TimedChord], index: usize) -> Option<CadenceType> {
+    let diatonic = |c: &Chord| c.applied == 0 && c.borrowed.is_none();
+    let arrival = &chords[index].chord;
+    let previous = index.checked_sub(1).map(|i| &chords[i].chord);
+
+    match (previous, arrival.root) {
+        (Some(p), 1) if diatonic(p) && diatonic(arrival) && matches!(p.root, 5 | 7) => {
+            Some(CadenceType::Authentic)
+        },
+        (Some(p), 1) if diatonic(p) && diatonic(arrival) && p.root == 4 => {
+            Some(CadenceType::Plagal)
+        },
+        (Some(p), 6) if diatonic(p) && diatonic(arrival) && p.root == 5 => {
+            Some(CadenceType::Deceptive)
+        },
+        (_, 5) if diatonic(arrival) => Some(CadenceType::Half),
+        _ => None,
+    }
+}
+
+/// Split chords into unlabeled phrases
+fn segment_phrases(chords: &[TimedChord]) -> Vec<Phrase> {
+    let mut durations: Vec<f64> = chords.iter().map(|c| c.end - c.start).collect();
+    durations.sort_by(f64::total_cmp);
+    let median = durations[durations.len() / 2];
+
+    let mut phrases = Vec::new();
+    let mut start = 0;
+    for index in 0..chords.len() {
+        let length = index + 1 - start;
+        let chord = &chords[index];
+        let held = median > 0.0 && chord.end - chord.start >= LONG_CHORD_RATIO * median;
+        let cadence = cadence_at(chords, index);
+
+        let ends = index + 1 == chords.len()
+            || chord.rest_after
+            || length >= MAX_PHRASE_CHORDS
+            || (length >= MIN_PHRASE_CHORDS
+                && (held || matches!(cadence, Some(CadenceType::Authentic | CadenceType::Plagal))));
+
+        if ends {
+            phrases.push(Phrase {
+                label: String::new(),
+                chord_range: (start, index + 1),
+                start_beat: chords[start].start,
+                end_beat: chord.end,
+                cadence: if length >= MIN_PHRASE_CHORDS {
+                    cadence
+                } else {
+                    None
+                },
+            });
+            start = index + 1;
+        }
+    }
+    phrases
+}
+
+/// Fraction of positions where two chord sequences agree, over the longer length
+fn phrase_similarity(a: &[TimedChord], b: &[TimedChord]) -> f64 {
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    let matching = a
+        .iter()
+        .zip(b)
+        .filter(|(x, y)| x.key.is_some() && x.key == y.key)
+        .count();
+    matching as f64 / longest as f64
+}
+
+/// Label phrases by repetition in order of first appearance
+fn label_phrases(phrases: &mut [Phrase], chords: &[TimedChord]) {
+    let slice = |phrase: &Phrase| &chords[phrase.chord_range.0..phrase.chord_range.1];
+    let mut representatives: Vec<(usize, String)> = Vec::new();
+
+    for index in 0..phrases.len() {
+        let current = slice(&phrases[index]);
+        let best = representatives
+            .iter()
+            .map(|(rep, label)| (phrase_similarity(slice(&phrases[*rep]), current), label))
+            .filter(|(similarity, _)| *similarity >= REPETITION_THRESHOLD)
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, label)| label.clone());
+
+        let label = best.unwrap_or_else(|| {
+            let label = section_label(representatives.len());
+            representatives.push((index, label.clone()));
+            label
+        });
+        phrases[index].label = label;
+    }
+}
+
+/// `A`..`Z`, then `A2`..`Z2` and so on
+fn section_label(index: usize) -> String {
+    let letter = (b'A' + (index % 26) as u8) as char;
+    match index / 26 {
+        0 => letter.to_string(),
+        round => format!("{}{}", letter, round + 1),
+    }
+}
+
+/// Group consecutive phrases with equal labels and assign role candidates
+fn group_sections(phrases: &[Phrase]) -> Vec<Section> {
+    let mut sections: Vec<Section> = Vec::new();
+    for (index, phrase) in phrases.iter().enumerate() {
+        match sections.last_mut() {
+            Some(section) if section.label == phrase.label => {
+                section.phrase_range.1 = index + 1;
+                section.end_beat = phrase.end_beat;
+            },
+            _ => sections.push(Section {
+                label: phrase.label.clone(),
+                role: SectionRole::Other,
+                phrase_range: (index, index + 1),
+                start_beat: phrase.start_beat,
+                end_beat: phrase.end_beat,
+            }),
+        }
+    }
+
+    let count = |label: &str| sections.iter().filter(|s| s.label == label).count();
+    let first = |label: &str| sections.iter().position(|s| s.label == label);
+
+    // Most repeated label, later first appearance winning ties
+    let mut repeated: Vec<&str> = Vec::new();
+    for section in &sections {
+        if count(&section.label) >= 2 && !repeated.contains(&section.label.as_str()) {
+            repeated.push(&section.label);
+        }
+    }
+    let chorus = repeated
+        .iter()
+        .copied()
+        .max_by_key(|label| (count(label), first(label)))
+        .map(str::to_string);
+    let verse = repeated
+        .iter()
+        .copied()
+        .filter(|label| {
+            Some(*label) != chorus.as_deref() && first(label) < chorus.as_deref().and_then(first)
+        })
+        .max_by_key(|label| count(label))
+        .map(str::to_string);
+
+    let last = sections.len() - 1;
+    let roles: Vec<SectionRole> = sections
+        .iter()
+        .enumerate()
+        .map(|(index, section)| {
+            let label = Some(&section.label);
+            if label == chorus.as_ref() {
+                SectionRole::Chorus
+            } else if label == verse.as_ref() {
+                SectionRole::Verse
+            } else if count(&section.label) >= 2 || sections.len() < 3 {
+                SectionRole::Other
+            } else if index == 0 {
+                SectionRole::Intro
+            } else if index == last {
+                SectionRole::Outro
+            } else {
+                SectionRole::Bridge
+            }
+        })
+        .collect();
+    for (section, role) in sections.iter_mut().zip(roles) {
+        section.role = role;
+    }
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trie::ChordProgressionTrie;
+    use composer_serialization::TokenEvent;
+    use std::sync::Arc;
+
+    fn timeline(chords: &[(u8, f64)]) -> Timeline {
+        let mut beat = 0.0;
+        let mut events = Vec::new();
+        for &(root, duration) in chords {
+            let event_type = if root == 0 {
+                TokenEventType::Rest
+            } else {
+                TokenEventType::Chord(Chord::new(root, 5).unwrap())
+            };
+            events.push(TokenEvent { beat, event_type });
+            beat += duration;
+        }
+        Timeline {
+            events,
+            total_duration: beat,
+            meter_changes: Vec::new(),
+            key_changes: Vec::new(),
+        }
+    }
+
+    fn analyzer() -> MusicalAnalyzer {
+        MusicalAnalyzer::new(Arc::new(ChordProgressionTrie::new()))
+    }
+
+    #[test]
+    fn test_cadences_and_rests_end_phrases() {
+        // I-IV-V-I | vi-ii-V (held) | rest | I-IV
+        let form = analyzer()
+            .analyze_form(&timeline(&[
+                (1, 2.0),
+                (4, 2.0),
+                (5, 2.0),
+                (1, 2.0),
+                (6, 2.0),
+                (2, 2.0),
+                (5, 4.0),
+                (0, 2.0),
+                (1, 2.0),
+                (4, 2.0),
+            ]))
+            .unwrap();
+
+        let ranges: Vec<_> = form.phrases.iter().map(|p| p.chord_range).collect();
+        assert_eq!(ranges, [(0, 4), (4, 7), (7, 9)]);
+        assert_eq!(form.phrases[0].cadence, Some(CadenceType::Authentic));
+        assert_eq!(form.phrases[1].cadence, Some(CadenceType::Half));
+        assert_eq!(form.phrases[1].end_beat, 16.0);
+        assert_eq!(form.phrases[2].start_beat, 18.0);
+    }
+
+    #[test]
+    fn test_repetition_labels_and_roles() {
+        let intro: &[(u8, f64)] = &[(6, 2.0), (4, 2.0), (6, 2.0), (4, 8.0)];
+        let verse: &[(u8, f64)] = &[(1, 2.0), (6, 2.0), (2, 2.0), (5, 2.0), (1, 4.0)];
+        let chorus: &[(u8, f64)] = &[
+            (4, 2.0),
+            (5, 2.0),
+            (3, 2.0),
+            (6, 2.0),
+            (2, 2.0),
+            (5, 2.0),
+            (1, 4.0),
+        ];
+        let bridge: &[(u8, f64)] = &[(2, 2.0), (3, 2.0), (4, 2.0), (5, 8.0)];
+        let song = [intro, verse, chorus, verse, chorus, bridge, chorus].concat();
+
+        let form = analyzer().analyze_form(&timeline(&song)).unwrap();
+        assert_eq!(form.form, "ABCBCDC");
+        let roles: Vec<_> = form.sections.iter().map(|s| s.role).collect();
+        assert_eq!(
+            roles,
+            [
+                SectionRole::Intro,
+                SectionRole::Verse,
+                SectionRole::Chorus,
+                SectionRole::Verse,
+                SectionRole::Chorus,
+                SectionRole::Bridge,
+                SectionRole::Chorus,
+            ]
+        );
+
+        // Consecutive repeats merge into one section
+        let form = analyzer()
+            .analyze_form(&timeline(&[verse, verse, chorus].concat()))
+            .unwrap();
+        assert_eq!(form.form, "AAB");
+        assert_eq!(form.sections[0].phrase_range, (0, 2));
+        assert!(analyzer().analyze_form(&Timeline::default()).is_err());
+    }
+}
@@ -0,0 +1,496 @@
+//! Genre models and style transfer between them
+//!
+//! A [`Genre`] knows the chord vocabulary typical of it: how well a chord fits
+//! the style, which idioms it decorates a functional progression with, and how
+//! to strip those idioms again. [`restyle_progression`] moves a progression
+//! from one genre to another by reducing it to plain triads in the source
+//! genre, keeping each chord's function, and decorating the result in the
+//! target genre at increasing intensity.
+
+use crate::error::{AiError, AiResult};
+use composer_core::{BorrowedScale, Chord, ChordTheoryResult, ScaleType, TRITONE_SUBSTITUTION};
+use serde::{Deserialize, Serialize};
+
+/// Share of confidence kept for each intensity above the first, since bolder
+/// rewrites stray further from the original
+const INTENSITY_DISCOUNT: f64 = 0.95;
+
+/// Musical genre with a characteristic chord vocabulary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Genre {
+    /// Triads, with added ninths and suspensions for color
+    Pop,
+    /// Seventh and extended chords, ii-V chains and tritone substitutes
+    Jazz,
+    /// Triads and dominant sevenths, with pre-dominant and cadential formulas
+    Classical,
+    /// Dominant sevenths on the primary chords
+    Blues,
+    /// Triads and power chords, with the ♭VII in place of the dominant
+    Rock,
+}
+
+impl Genre {
+    /// Every genre, in declaration order
+    pub const ALL: [Genre; 5] = [
+        Genre::Pop,
+        Genre::Jazz,
+        Genre::Classical,
+        Genre::Blues,
+        Genre::Rock,
+    ];
+
+    /// Lowercase genre name (e.g. "jazz")
+    pub fn name(&self) -> &'static str {
+        match self {
+            Genre::Pop => "pop",
+            Genre::Jazz => "jazz",
+            Genre::Classical => "classical",
+            Genre::Blues => "blues",
+            Genre::Rock => "rock",
+        }
+    }
+
+    /// Parse a genre name, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|genre| genre.name().eq_ignore_ascii_case(name))
+    }
+
+    /// How typical a chord is of the genre, around 0.0-1.0
+    pub fn chord_fit(&self, chord: &Chord) -> f64 {
+        match self {
+            // Pop favors simple triads and basic sevenths
+            Genre::Pop => match chord.chord_type {
+                5 => 0.9,
+                7 => 0.6,
+                _ => 0.3,
+            },
+            // Jazz favors complex harmonies
+            Genre::Jazz => {
+                (match chord.chord_type {
+                    5 => 0.4,
+                    7 => 0.8,
+                    9 | 11 | 13 => 0.9,
+                    _ => 0.5,
+                }) + (chord.alterations.len() as f64 * 0.1)
+            },
+            // Classical favors traditional voice leading
+            Genre::Classical => {
+                (match chord.chord_type {
+                    5 => 0.8,
+                    7 => 0.7,
+                    _ => 0.5,
+                }) - (chord.alterations.len() as f64 * 0.05)
+            },
+            // Blues favors sevenths and dominant chords
+            Genre::Blues => {
+                let base = match chord.chord_type {
+                    7 => 0.9,
+                    5 => 0.6,
+                    _ => 0.4,
+                };
+                if chord.root == 5 {
+                    base + 0.1
+                } else {
+                    base
+                }
+            },
+            // Rock favors power chords and simple progressions
+            Genre::Rock => match chord.chord_type {
+                5 => 0.8,
+                7 => 0.5,
+                _ => 0.3,
+            },
+        }
+    }
+
+    /// Number of decoration intensities [`restyle_progression`] offers
+    pub fn intensities(&self) -> usize {
+        match self {
+            Genre::Pop | Genre::Blues => 2,
+            Genre::Classical | Genre::Rock => 3,
+            Genre::Jazz => 4,
+        }
+    }
+
+    /// Plain triad with the chord's function, undoing this genre's idioms
+    fn core(&self, chord: &Chord) -> ChordTheoryResult<Chord> {
+        if chord.is_rest {
+            return Ok(Chord::rest());
+        }
+        let borrowed_from =
+            |scale: ScaleType| chord.borrowed == Some(BorrowedScale::ScaleType(scale));
+        match self {
+            // ♭VII standing in for the dominant
+            Genre::Rock if chord.root == 7 && borrowed_from(ScaleType::Mixolydian) => {
+                Chord::triad(5)
+            },
+            // Dominant sevenths on I and IV are borrowed for their flat seventh
+            Genre::Blues
+                if borrowed_from(ScaleType::Mixolydian) || borrowed_from(ScaleType::Dorian) =>
+            {
+                Chord::triad(chord.root)
+            },
+            _ => {
+                let mut core = Chord::triad(chord.root)?.with_applied(chord.applied)?;
+                core.borrowed = chord.borrowed.clone();
+                Ok(core)
+            },
+        }
+    }
+
+    /// A core progression decorated at `intensity` (1 to [`Genre::intensities`])
+    fn decorate(&self, core: &[Chord], intensity: usize) -> ChordTheoryResult<Vec<Chord>> {
+        let mut chords = core.to_vec();
+        match self {
+            Genre::Pop => {
+                if intensity >= 2 {
+                    for chord in chords.iter_mut().filter(|chord| is_diatonic(chord)) {
+                        *chord = match chord.root {
+                            1 | 4 => chord.clone().with_add(9)?,
+                            5 => chord.clone().with_suspension(4)?,
+                            _ => continue,
+                        };
+                    }
+                }
+            },
+            Genre::Jazz => {
+                for chord in chords.iter_mut().filter(|chord| !chord.is_rest) {
+                    chord.chord_type = if is_dominant(chord) { 9 } else { 7 };
+                }
+                if intensity >= 2 {
+                    // ii7 before every dominant that has no pre-dominant ii
+                    chords = insert_before(&chords, |previous, chord| {
+                        let has_two = previous.is_some_and(|p| p.root == 2 && p.applied == 0);
+                        Ok((is_dominant(chord) && !has_two).then_some(Chord::seventh(2)?))
+                    })?;
+                }
+                if intensity >= 3 {
+                    // V7/ii before every ii7 leading to the dominant
+                    let leads: Vec<bool> = chords
+                        .windows(2)
+                        .map(|pair| {
+                            is_diatonic(&pair[0]) && pair[0].root == 2 && is_dominant(&pair[1])
+                        })
+                        .chain([false])
+                        .collect();
+                    let mut index = 0;
+                    chords = insert_before(&chords, |previous, _| {
+                        let leads_to_dominant = leads[index];
+                        index += 1;
+                        let has_secondary = previous.is_some_and(|p| p.applied == 2);
+                        if leads_to_dominant && !has_secondary {
+                            Ok(Some(Chord::seventh(5)?.with_applied(2)?))
+                        } else {
+                            Ok(None)
+                        }
+                    })?;
+                }
+                if intensity >= 4 {
+                    for chord in chords.iter_mut().filter(|chord| is_dominant(chord)) {
+                        chord.chord_type = 7;
+                        chord.substitutions.push(TRITONE_SUBSTITUTION.to_string());
+                    }
+                }
+            },
+            Genre::Classical => {
+                for chord in chords.iter_mut().filter(|chord| is_dominant(chord)) {
+                    chord.chord_type = 7;
+                }
+                if intensity >= 2 {
+                    // ii6 in place of IV before the dominant
+                    for index in 1..chords.len() {
+                        if is_dominant(&chords[index])
+                            && is_diatonic(&chords[index - 1])
+                            && chords[index - 1].root == 4
+                        {
+                            chords[index - 1] = Chord::triad(2)?.with_inversion(1)?;
+                        }
+                    }
+                }
+                if intensity >= 3 {
+                    // Cadential six-four before a final dominant-tonic cadence
+                    let length = chords.len();
+                    if length >= 2
+                        && is_dominant(&chords[length - 2])
+                        && is_diatonic(&chords[length - 1])
+                        && chords[length - 1].root == 1
+                    {
+                        chords.insert(length - 2, Chord::triad(1)?.with_inversion(2)?);
+                    }
+                }
+            },
+            Genre::Blues => {
+                for chord in chords.iter_mut().filter(|chord| !chord.is_rest) {
+                    let extension = if intensity >= 2 { 9 } else { 7 };
+                    *chord = match (chord.root, is_diatonic(chord)) {
+                        (1, true) => Chord::new(1, extension)?
+                            .with_borrowed_scale(BorrowedScale::ScaleType(ScaleType::Mixolydian))?,
+                        (4, true) => Chord::new(4, extension)?
+                            .with_borrowed_scale(BorrowedScale::ScaleType(ScaleType::Dorian))?,
+                        (5, true) => Chord::new(5, extension)?,
+                        _ => {
+                            chord.chord_type = 7;
+                            continue;
+                        },
+                    };
+                }
+            },
+            Genre::Rock => {
+                if intensity >= 2 {
+                    // ♭VII in place of a dominant resolving to the tonic
+                    for index in 0..chords.len().saturating_sub(1) {
+                        if is_dominant(&chords[index])
+                            && is_diatonic(&chords[index + 1])
+                            && chords[index + 1].root == 1
+                        {
+                            chords[index] = Chord::triad(7)?.with_borrowed_scale(
+                                BorrowedScale::ScaleType(ScaleType::Mixolydian),
+                            )?;
+                        }
+                    }
+                }
+                if intensity >= 3 {
+                    for chord in chords.iter_mut().filter(|chord| !chord.is_rest) {
+                        *chord = chord.clone().with_omit(3)?;
+                    }
+                }
+            },
+        }
+        Ok(chords)
+    }
+}
+
+/// Whether a chord is neither applied nor borrowed
+fn is_diatonic(chord: &Chord) -> bool {
+    !chord.is_rest && chord.applied == 0 && chord.borrowed.is_none()
+}
+
+/// Whether a chord is the diatonic dominant
+fn is_dominant(chord: &Chord) -> bool {
+    is_diatonic(chord) && chord.root == 5
+}
+
+/// Copy of `chords` with the chord `insert` returns placed before each chord,
+/// given the chord before it in the result so far
+fn insert_before<F>(chords: &[Chord], mut insert: F) -> ChordTheoryResult<Vec<Chord>>
+where
+    F: FnMut(Option<&Chord>, &Chord) -> ChordTheoryResult<Option<Chord>>,
+{
+    let mut result: Vec<Chord> = Vec::with_capacity(chords.len());
+    for chord in chords {
+        if let Some(inserted) = insert(result.last(), chord)? {
+            result.push(inserted);
+        }
+        result.push(chord.clone());
+    }
+    Ok(result)
+}
+
+/// A progression rewritten in another genre
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RestyledProgression {
+    pub progression: Vec<Chord>,
+
+    /// Decoration intensity in the target genre, from 1 (its basic vocabulary)
+    pub intensity: usize,
+
+    /// Confidence that the rewrite is idiomatic and faithful (0.0-1.0)
+    pub confidence: f64,
+}
+
+/// Rewrite a progression from one genre's idiom into another's.
+///
+/// Each chord is first reduced to a triad with the same function by undoing
+/// the source genre's idioms: extensions, added tones, suspensions and
+/// tritone markers always, dominant sevenths borrowed onto I and IV from
+/// blues, and the ♭VII standing in for V from rock. The core is then
+/// decorated in the target genre at each of its [`Genre::intensities`]:
+///
+/// - **Pop**: triads; then add9 on I and IV and a suspended V
+/// - **Jazz**: sevenths with a V9; then a ii7 before each V; then V7/ii before
+///   those ii7s; then the dominant tritone-substituted
+/// - **Classical**: triads with V7; then ii6 for IV before V; then a cadential
+///   six-four before a closing V-I
+/// - **Blues**: dominant sevenths on I, IV and V; then dominant ninths
+/// - **Rock**: triads; then ♭VII for V before I; then power chords
+///
+/// Confidence is the mean fit of the chords to the target genre (see
+/// [`Genre::chord_fit`]), scaled down by the share of chords inserted and by
+/// intensity. Alternatives come back most confident first, without
+/// duplicates. Fails with `AiError::SuggestionFailed` for an empty progression
+/// or an invalid chord.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::{restyle_progression, Genre};
+/// use composer_core::Chord;
+///
+/// let pop = vec![Chord::triad(1)?, Chord::triad(5)?, Chord::triad(1)?];
+/// let alternatives = restyle_progression(&pop, Genre::Pop, Genre::Jazz)?;
+///
+/// // Imaj7 V9 Imaj7 first, then with a ii7 before the V9
+/// assert_eq!(alternatives[0].progression[1], Chord::new(5, 9)?);
+/// assert_eq!(alternatives[1].progression[1], Chord::seventh(2)?);
+/// assert!(alternatives[0].confidence >= alternatives[1].confidence);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`composer_core::get_substitutions`] - Single-chord substitutions in a key
+pub fn restyle_progression(
+    progression: &[Chord],
+    from: Genre,
+    to: Genre,
+) -> AiResult<Vec<RestyledProgression>> {
+    if progression.is_empty() {
+        return Err(AiError::SuggestionFailed {
+            reason: "Cannot restyle an empty progression".to_string(),
+        });
+    }
+    let invalid = |e| AiError::SuggestionFailed {
+        reason: format!("Cannot restyle progression: {}", e),
+    };
+
+    let core = progression
+        .iter()
+        .map(|chord| from.core(chord))
+        .collect::<ChordTheoryResult<Vec<Chord>>>()
+        .map_err(invalid)?;
+
+    let mut alternatives: Vec<RestyledProgression> = Vec::new();
+    for intensity in 1..=to.intensities() {
+        let restyled = to.decorate(&core, intensity).map_err(invalid)?;
+        if alternatives
+            .iter()
+            .any(|alternative| alternative.progression == restyled)
+        {
+            continue;
+        }
+
+        let chords: Vec<&Chord> = restyled.iter().filter(|chord| !chord.is_rest).collect();
+        let fit = if chords.is_empty() {
+            0.0
+        } else {
+            chords
+                .iter()
+                .map(|chord| to.chord_fit(chord).clamp(0.0, 1.0))
+                .sum::<f64>()
+                / chords.len() as f64
+        };
+        let fidelity = core.len() as f64 / restyled.len() as f64;
+        let confidence =
+            fit * (0.5 + 0.5 * fidelity) * INTENSITY_DISCOUNT.powi(intensity as i32 - 1);
+        alternatives.push(RestyledProgression {
+            progression: restyled,
+            intensity,
+            confidence,
+        });
+    }
+
+    alternatives.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    Ok(alternatives)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Root, chord type and applied target of a chord
+    type Shape = (u8, u8, u8);
+
+    fn at(alternatives: &[RestyledProgression], intensity: usize) -> &[Chord] {
+        &alternatives
+            .iter()
+            .find(|alternative| alternative.intensity == intensity)
+            .unwrap()
+            .progression
+    }
+
+    #[test]
+    fn test_pop_to_jazz_chains() {
+        let pop = [
+            Chord::triad(1).unwrap(),
+            Chord::triad(6).unwrap(),
+            Chord::triad(5).unwrap(),
+            Chord::triad(1).unwrap(),
+        ];
+        let alternatives = restyle_progression(&pop, Genre::Pop, Genre::Jazz).unwrap();
+        assert_eq!(alternatives.len(), 4);
+        assert!(alternatives
+            .windows(2)
+            .all(|pair| pair[0].confidence >= pair[1].confidence));
+
+        let chain = at(&alternatives, 3);
+        let shapes: Vec<Shape> = chain
+            .iter()
+            .map(|chord| (chord.root, chord.chord_type, chord.applied))
+            .collect();
+        // Imaj7 vi7 V7/ii ii7 V9 Imaj7
+        assert_eq!(
+            shapes,
+            vec![
+                (1, 7, 0),
+                (6, 7, 0),
+                (5, 7, 2),
+                (2, 7, 0),
+                (5, 9, 0),
+                (1, 7, 0)
+            ]
+        );
+        assert!(at(&alternatives, 4)[4]
+            .substitutions
+            .iter()
+            .any(|s| s == TRITONE_SUBSTITUTION));
+    }
+
+    #[test]
+    fn test_jazz_to_pop_reduces_to_function() {
+        let jazz = [
+            Chord::seventh(2).unwrap(),
+            Chord::new(5, 13).unwrap().with_alteration("b9").unwrap(),
+            Chord::seventh(1).unwrap(),
+        ];
+        let alternatives = restyle_progression(&jazz, Genre::Jazz, Genre::Pop).unwrap();
+        assert_eq!(
+            at(&alternatives, 1),
+            &[
+                Chord::triad(2).unwrap(),
+                Chord::triad(5).unwrap(),
+                Chord::triad(1).unwrap()
+            ]
+        );
+        assert_eq!(at(&alternatives, 2)[1].suspensions.as_slice(), &[4]);
+        assert!(alternatives[0].confidence > 0.8);
+    }
+
+    #[test]
+    fn test_blues_and_rock_idioms_round_trip() {
+        let pop = [
+            Chord::triad(1).unwrap(),
+            Chord::triad(4).unwrap(),
+            Chord::triad(5).unwrap(),
+            Chord::triad(1).unwrap(),
+        ];
+        let blues = restyle_progression(&pop, Genre::Pop, Genre::Blues).unwrap();
+        let blues_core = at(&blues, 1);
+        assert!(blues_core.iter().all(|chord| chord.chord_type == 7));
+        assert_eq!(
+            blues_core[0].borrowed,
+            Some(BorrowedScale::ScaleType(ScaleType::Mixolydian))
+        );
+
+        let rock = restyle_progression(blues_core, Genre::Blues, Genre::Rock).unwrap();
+        assert_eq!(at(&rock, 1), &pop);
+        assert_eq!(at(&rock, 2)[2].root, 7);
+        let back = restyle_progression(at(&rock, 2), Genre::Rock, Genre::Pop).unwrap();
+        assert_eq!(at(&back, 1), &pop);
+
+        assert!(restyle_progression(&[], Genre::Pop, Genre::Jazz).is_err());
+        assert_eq!(Genre::from_name("Jazz"), Some(Genre::Jazz));
+    }
+}
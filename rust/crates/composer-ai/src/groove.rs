@@ -0,0 +1,402 @@
+//! Drum grooves aligned with the harmonic rhythm
+//!
+//! Suggests kick, snare and hi-hat parts for the chords of a timeline. The
+//! hi-hat keeps a steady pulse, accented on the beat, and the snare takes the
+//! backbeats. The kick marks downbeats and mid-bar beats, and every chord
+//! change with an accent; a change anticipated just before a downbeat pushes
+//! the kick ahead of the bar line. Grooves follow the timeline's meter changes
+//! and render to note clusters of General MIDI drum notes, so they tokenize
+//! like any other part.
+
+use crate::accompaniment::AccompanimentDensity;
+use crate::error::{AiError, AiResult};
+use composer_core::ScaleFingerprint;
+use composer_serialization::{
+    metric_position, tokenize_polyphonic, ClusterNote, MeterChange, MetricPosition, Timeline,
+    TokenEvent, TokenEventType, TICKS_PER_BEAT,
+};
+use serde::{Deserialize, Serialize};
+
+/// Meter assumed where none is given
+const DEFAULT_TIME_SIGNATURE: (u8, u8) = (4, 4);
+
+/// Drums a groove is played on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DrumVoice {
+    Kick,
+    Snare,
+    HiHat,
+}
+
+impl DrumVoice {
+    /// General MIDI percussion note: bass drum 1, acoustic snare or closed hi-hat
+    pub fn midi_note(&self) -> u8 {
+        match self {
+            DrumVoice::Kick => 36,
+            DrumVoice::Snare => 38,
+            DrumVoice::HiHat => 42,
+        }
+    }
+}
+
+/// One stroke of a groove
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DrumHit {
+    /// Onset in quarter notes from the start
+    pub beat: f64,
+    pub voice: DrumVoice,
+    pub accent: bool,
+}
+
+/// Options for [`suggest_groove`]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GrooveOptions {
+    /// Hi-hat pulse
+    pub density: AccompanimentDensity,
+    /// Meter before the timeline's first meter change, or throughout when it
+    /// has none
+    pub time_signature: (u8, u8),
+}
+
+impl Default for GrooveOptions {
+    fn default() -> Self {
+        Self {
+            density: AccompanimentDensity::default(),
+            time_signature: DEFAULT_TIME_SIGNATURE,
+        }
+    }
+}
+
+/// Drum part suggested for a timeline
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Groove {
+    /// Strokes ordered by onset, kick then snare then hi-hat at each onset
+    pub hits: Vec<DrumHit>,
+    /// Length in quarter notes
+    pub total_duration: f64,
+    /// Meter changes the groove was laid out in, starting at beat 0
+    pub meter_changes: Vec<MeterChange>,
+}
+
+impl Groove {
+    /// Strokes on one drum
+    pub fn voice(&self, voice: DrumVoice) -> impl Iterator<Item = &DrumHit> {
+        self.hits.iter().filter(move |hit| hit.voice == voice)
+    }
+
+    /// The groove as a timeline with one note cluster per onset
+    ///
+    /// Strokes are written as their General MIDI notes, lasting until the next
+    /// onset. Accents are not kept.
+    pub fn to_timeline(&self) -> Timeline {
+        let mut events: Vec<TokenEvent> = Vec::new();
+        for hit in &self.hits {
+            let note = ClusterNote {
+                pitch_class: hit.voice.midi_note() % 12,
+                octave: hit.voice.midi_note() / 12 - 1,
+                duration: 0.0,
+            };
+            match events.last_mut() {
+                Some(TokenEvent {
+                    beat,
+                    event_type: TokenEventType::Cluster(notes),
+                }) if *beat == hit.beat => notes.push(note),
+                _ => events.push(TokenEvent {
+                    beat: hit.beat,
+                    event_type: TokenEventType::Cluster(vec![note]),
+                }),
+            }
+        }
+
+        let onsets: Vec<f64> = events.iter().map(|event| event.beat).collect();
+        for (index, event) in events.iter_mut().enumerate() {
+            let end = onsets
+                .get(index + 1)
+                .copied()
+                .unwrap_or(self.total_duration);
+            if let TokenEventType::Cluster(notes) = &mut event.event_type {
+                for note in notes {
+                    note.duration = (end - event.beat).max(0.0);
+                }
+            }
+        }
+
+        Timeline {
+            events,
+            total_duration: self.total_duration,
+            meter_changes: self.meter_changes.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// The groove as polyphonic tokens of [`Groove::to_timeline`]
+    ///
+    /// See [`tokenize_polyphonic`] for the format.
+    pub fn tokens(&self) -> AiResult<Vec<String>> {
+        tokenize_polyphonic(&self.to_timeline(), &ScaleFingerprint::major_scale()).map_err(|e| {
+            AiError::SuggestionFailed {
+                reason: format!("Cannot tokenize groove: {}", e),
+            }
+        })
+    }
+}
+
+/// Beat and bar lengths in ticks, counting dotted-quarter beats in compound
+/// meters as [`metric_position`] does
+fn meter_ticks(time_signature: (u8, u8)) -> AiResult<(i64, i64)> {
+    let (numerator, denominator) = time_signature;
+    if numerator == 0 || !matches!(denominator, 1 | 2 | 4 | 8 | 16 | 32) {
+        return Err(AiError::SuggestionFailed {
+            reason: format!("Invalid time signature {}/{}", numerator, denominator),
+        });
+    }
+    let compound = denominator >= 8 && numerator > 3 && numerator % 3 == 0;
+    let unit = TICKS_PER_BEAT as i64 * 4 / denominator as i64;
+    let beat = if compound { unit * 3 } else { unit };
+    Ok((beat, unit * numerator as i64))
+}
+
+/// Suggest a drum groove for the chords of a timeline.
+///
+/// Counting beats from each bar line, odd beats (2 and 4 of 4/4, the second
+/// dotted quarter of 6/8) take the snare and even downbeats and mid-bar beats
+/// take the kick. Each chord change adds an accented kick, also off the pulse;
+/// when a change falls within half a beat before a downbeat, that downbeat's
+/// own kick is left out. Rest chords are not changes. Bars restart at each
+/// meter change.
+///
+/// Fails with `AiError::SuggestionFailed` for an invalid time signature.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::{suggest_groove, AccompanimentDensity, DrumVoice, GrooveOptions};
+/// use composer_core::Chord;
+/// use composer_serialization::{Timeline, TokenEvent, TokenEventType};
+///
+/// let events = [(1, 0.0), (4, 2.0)]
+///     .into_iter()
+///     .map(|(root, beat)| Ok(TokenEvent { beat, event_type: TokenEventType::Chord(Chord::triad(root)?) }))
+///     .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+/// let timeline = Timeline { events, total_duration: 4.0, ..Default::default() };
+/// let options = GrooveOptions {
+///     density: AccompanimentDensity::Sparse,
+///     ..Default::default()
+/// };
+///
+/// let groove = suggest_groove(&timeline, &options)?;
+/// let kicks: Vec<f64> = groove.voice(DrumVoice::Kick).map(|hit| hit.beat).collect();
+/// let snares: Vec<f64> = groove.voice(DrumVoice::Snare).map(|hit| hit.beat).collect();
+/// assert_eq!(kicks, vec![0.0, 2.0]);
+/// assert_eq!(snares, vec![1.0, 3.0]);
+/// assert_eq!(groove.voice(DrumVoice::HiHat).count(), 4);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`crate::render_accompaniment`] - Render the chords themselves
+/// - [`Groove::tokens`] - Tokenize the groove
+pub fn suggest_groove(timeline: &Timeline, options: &GrooveOptions) -> AiResult<Groove> {
+    let mut meters = timeline.meter_changes.clone();
+    meters.sort_by(|a, b| a.beat.total_cmp(&b.beat));
+    if !matches!(meters.first(), Some(first) if first.beat <= 0.0) {
+        meters.insert(
+            0,
+            MeterChange {
+                beat: 0.0,
+                time_signature: options.time_signature,
+            },
+        );
+    }
+
+    let ticks_per_beat = TICKS_PER_BEAT as f64;
+    let tick = |beat: f64| (beat * ticks_per_beat).round() as i64;
+    let mut changes: Vec<i64> = timeline
+        .events
+        .iter()
+        .filter_map(|event| match &event.event_type {
+            TokenEventType::Chord(chord) if !chord.is_rest => Some(tick(event.beat)),
+            _ => None,
+        })
+        .collect();
+    changes.sort_unstable();
+    changes.dedup();
+
+    let pulse = tick(options.density.pulse());
+    let end = tick(timeline.total_duration);
+    let mut hits = Vec::new();
+    for (index, meter) in meters.iter().enumerate() {
+        let (beat_ticks, bar_ticks) = meter_ticks(meter.time_signature)?;
+        let start = tick(meter.beat).max(0);
+        let stop = meters
+            .get(index + 1)
+            .map_or(end, |next| tick(next.beat))
+            .min(end);
+        if start >= stop {
+            continue;
+        }
+
+        let mut onsets: Vec<i64> = (start..stop).step_by(pulse as usize).collect();
+        onsets.extend((start..stop).step_by(beat_ticks as usize));
+        onsets.extend(
+            changes
+                .iter()
+                .filter(|change| (start..stop).contains(*change)),
+        );
+        onsets.sort_unstable();
+        onsets.dedup();
+
+        for onset in onsets {
+            let offset = onset - start;
+            let position = metric_position(offset as f64 / ticks_per_beat, meter.time_signature)
+                .map_err(|e| AiError::SuggestionFailed {
+                    reason: format!("Cannot place beat {}: {}", onset as f64 / ticks_per_beat, e),
+                })?;
+            let on_beat = matches!(
+                position,
+                MetricPosition::Downbeat | MetricPosition::MidBar | MetricPosition::Beat
+            );
+            let beat_index = offset % bar_ticks / beat_ticks;
+            let change = changes.binary_search(&onset).is_ok();
+            let pushed = position == MetricPosition::Downbeat
+                && changes
+                    .iter()
+                    .any(|change| (onset - beat_ticks / 2..onset).contains(change));
+
+            let beat = onset as f64 / ticks_per_beat;
+            let mut strike = |voice: DrumVoice, accent: bool| {
+                hits.push(DrumHit {
+                    beat,
+                    voice,
+                    accent,
+                })
+            };
+            let strong = matches!(position, MetricPosition::Downbeat | MetricPosition::MidBar);
+            if change {
+                strike(DrumVoice::Kick, true);
+            } else if strong && beat_index % 2 == 0 && !pushed {
+                strike(DrumVoice::Kick, false);
+            }
+            if on_beat && beat_index % 2 == 1 {
+                strike(DrumVoice::Snare, true);
+            }
+            if offset % pulse == 0 {
+                strike(DrumVoice::HiHat, on_beat);
+            }
+        }
+    }
+
+    Ok(Groove {
+        hits,
+        total_duration: timeline.total_duration,
+        meter_changes: meters,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use composer_core::Chord;
+
+    fn chord_timeline(chords: &[(f64, Chord)], total_duration: f64) -> Timeline {
+        Timeline {
+            events: chords
+                .iter()
+                .map(|(beat, chord)| TokenEvent {
+                    beat: *beat,
+                    event_type: TokenEventType::Chord(chord.clone()),
+                })
+                .collect(),
+            total_duration,
+            ..Default::default()
+        }
+    }
+
+    fn strokes(groove: &Groove, voice: DrumVoice) -> Vec<(f64, bool)> {
+        groove
+            .voice(voice)
+            .map(|hit| (hit.beat, hit.accent))
+            .collect()
+    }
+
+    #[test]
+    fn test_kicks_follow_chord_changes() {
+        let timeline = chord_timeline(
+            &[
+                (0.0, Chord::triad(1).unwrap()),
+                (2.0, Chord::triad(4).unwrap()),
+                (3.5, Chord::triad(5).unwrap()),
+            ],
+            8.0,
+        );
+        let options = GrooveOptions {
+            density: AccompanimentDensity::Sparse,
+            ..Default::default()
+        };
+        let groove = suggest_groove(&timeline, &options).unwrap();
+
+        // The anticipated change at 3.5 replaces the kick on the next downbeat
+        assert_eq!(
+            strokes(&groove, DrumVoice::Kick),
+            vec![(0.0, true), (2.0, true), (3.5, true), (6.0, false)]
+        );
+        assert_eq!(
+            strokes(&groove, DrumVoice::Snare),
+            vec![(1.0, true), (3.0, true), (5.0, true), (7.0, true)]
+        );
+        let hats = strokes(&groove, DrumVoice::HiHat);
+        assert_eq!(hats.len(), 8);
+        assert!(hats.iter().all(|(_, accent)| *accent));
+    }
+
+    #[test]
+    fn test_compound_meter_and_rendering() {
+        let mut timeline = chord_timeline(&[(0.0, Chord::triad(1).unwrap())], 6.0);
+        timeline.meter_changes = vec![MeterChange {
+            beat: 0.0,
+            time_signature: (6, 8),
+        }];
+        let groove = suggest_groove(&timeline, &GrooveOptions::default()).unwrap();
+
+        assert_eq!(
+            strokes(&groove, DrumVoice::Kick),
+            vec![(0.0, true), (3.0, false)]
+        );
+        assert_eq!(
+            strokes(&groove, DrumVoice::Snare),
+            vec![(1.5, true), (4.5, true)]
+        );
+        let hats = strokes(&groove, DrumVoice::HiHat);
+        assert_eq!(hats.len(), 12);
+        assert_eq!(hats.iter().filter(|(_, accent)| *accent).count(), 4);
+
+        // Kick and hi-hat together on the downbeat, for an eighth note
+        let rendered = groove.to_timeline();
+        assert_eq!(rendered.events.len(), 12);
+        let TokenEventType::Cluster(notes) = &rendered.events[0].event_type else {
+            unreachable!()
+        };
+        assert_eq!(
+            notes
+                .iter()
+                .map(|note| (note.pitch_class, note.octave, note.duration))
+                .collect::<Vec<_>>(),
+            vec![(0, 2, 0.5), (6, 2, 0.5)]
+        );
+        assert!(!groove.tokens().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_time_signature() {
+        let timeline = chord_timeline(&[(0.0, Chord::triad(1).unwrap())], 4.0);
+        let options = GrooveOptions {
+            time_signature: (4, 3),
+            ..Default::default()
+        };
+        assert!(matches!(
+            suggest_groove(&timeline, &options),
+            Err(AiError::SuggestionFailed { .. })
+        ));
+    }
+}
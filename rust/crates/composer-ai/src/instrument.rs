@@ -0,0 +1,264 @@
+//! Instrument-specific difficulty profiles
+//!
+//! Measures the technical complexity of a progression on a given instrument
+//! from concrete voicings rather than chord symbols: guitar and ukulele from
+//! the easiest playable fingering of each chord (barres, fret spans, position
+//! shifts), piano from close voicings (hand span, black-key density, bass
+//! jumps) and voice from the sung bass line (leaps, chromatic tones). Chords
+//! are voiced in C major, since progressions carry scale degrees only.
+
+use composer_core::{
+    chord_pitch_classes, guitar_fingerings, piano_voicing, Chord, GuitarFingering, GuitarTuning,
+    GuitarVoicingOptions, PianoVoicingStyle, ScaleFingerprint,
+};
+use serde::{Deserialize, Serialize};
+
+/// Pitch classes of the black piano keys
+const BLACK_KEYS: [u8; 5] = [1, 3, 6, 8, 10];
+
+/// Pitch classes of C major, the key chords are voiced in
+const DIATONIC_PITCH_CLASSES: [u8; 7] = [0, 2, 4, 5, 7, 9, 11];
+
+/// Cost of a chord with no playable fingering within the span limit
+const UNPLAYABLE_CHORD_COST: f64 = 4.0;
+
+/// Cost of playing a chord and the anchor (hand position or pitch) movement
+/// to the next chord is measured from
+type ChordCost = (f64, Option<f64>);
+
+/// Instrument a difficulty assessment is made for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DifficultyProfile {
+    /// Piano: hand span, black-key density and bass jumps
+    Piano,
+    /// Six-string guitar in standard tuning: barres, fret spans and shifts
+    Guitar,
+    /// Re-entrant GCEA ukulele: barres, fret spans and shifts on four strings
+    Ukulele,
+    /// Voice singing the bass line: leaps and chromatic tones
+    Voice,
+}
+
+impl DifficultyProfile {
+    /// Every profile, in declaration order
+    pub const ALL: [DifficultyProfile; 4] = [
+        DifficultyProfile::Piano,
+        DifficultyProfile::Guitar,
+        DifficultyProfile::Ukulele,
+        DifficultyProfile::Voice,
+    ];
+
+    /// Lowercase profile name (e.g. "guitar")
+    pub fn name(&self) -> &'static str {
+        match self {
+            DifficultyProfile::Piano => "piano",
+            DifficultyProfile::Guitar => "guitar",
+            DifficultyProfile::Ukulele => "ukulele",
+            DifficultyProfile::Voice => "voice",
+        }
+    }
+
+    /// Parse a profile name, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|profile| profile.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// Technical complexity (0.0-10.0) of a progression on an instrument
+///
+/// Made of a base of 1.0, the mean per-chord cost and the mean cost of moving
+/// between consecutive chords. Rests are skipped.
+pub(crate) fn technical_complexity(profile: DifficultyProfile, progression: &[Chord]) -> f64 {
+    let scale = ScaleFingerprint::major_scale();
+    let chords: Vec<&Chord> = progression.iter().filter(|chord| !chord.is_rest).collect();
+    if chords.is_empty() {
+        return 1.0;
+    }
+
+    let costs: Vec<ChordCost> = chords
+        .iter()
+        .map(|chord| match profile {
+            DifficultyProfile::Piano => piano_cost(chord, &scale),
+            DifficultyProfile::Guitar => fretted_cost(chord, &scale, guitar_options()),
+            DifficultyProfile::Ukulele => fretted_cost(chord, &scale, ukulele_options()),
+            DifficultyProfile::Voice => voice_cost(chord, &scale),
+        })
+        .collect();
+
+    let movement: f64 = costs
+        .windows(2)
+        .map(|pair| match pair {
+            [(_, Some(from)), (_, Some(to))] => movement_cost(profile, *from, *to),
+            _ => 0.0,
+        })
+        .sum();
+    let transitions = (chords.len() - 1).max(1) as f64;
+
+    let mean_chord_cost = costs.iter().map(|(cost, _)| cost).sum::<f64>() / chords.len() as f64;
+    (1.0 + mean_chord_cost + movement / transitions).min(10.0)
+}
+
+/// Cost of moving between the anchors (hand position or pitch) of two chords
+fn movement_cost(profile: DifficultyProfile, from: f64, to: f64) -> f64 {
+    let distance = (to - from).abs();
+    match profile {
+        // Bass jumps in semitones, an octave or more costing 1.0
+        DifficultyProfile::Piano => distance.min(12.0) / 12.0,
+        // Position shifts in frets
+        DifficultyProfile::Guitar | DifficultyProfile::Ukulele => distance.min(12.0) / 6.0,
+        // Sung leaps in semitones, the tritone being the widest
+        DifficultyProfile::Voice => match folded_interval(from, to) as u8 {
+            0..=2 => 0.0,
+            3..=4 => 0.3,
+            5 => 0.6,
+            _ => 1.0,
+        },
+    }
+}
+
+/// Piano chord cost and its bass note
+fn piano_cost(chord: &Chord, scale: &ScaleFingerprint) -> ChordCost {
+    let Ok(notes) = piano_voicing(chord, scale, 0, PianoVoicingStyle::Close) else {
+        return (0.0, None);
+    };
+    let (Some(&low), Some(&high)) = (notes.first(), notes.last()) else {
+        return (0.0, None);
+    };
+
+    let black_keys = notes
+        .iter()
+        .filter(|note| BLACK_KEYS.contains(&(*note % 12)))
+        .count();
+    let black_key_density = black_keys as f64 / notes.len() as f64;
+    let span_beyond_octave = f64::from(high - low).max(12.0) - 12.0;
+    let extra_notes = notes.len().saturating_sub(3) as f64;
+
+    let cost = black_key_density * 2.0 + span_beyond_octave * 0.4 + extra_notes * 0.4;
+    (cost, Some(f64::from(low)))
+}
+
+fn guitar_options() -> GuitarVoicingOptions {
+    GuitarVoicingOptions::default()
+}
+
+fn ukulele_options() -> GuitarVoicingOptions {
+    GuitarVoicingOptions {
+        tuning: GuitarTuning::ukulele(),
+        bass_lowest: false,
+        ..GuitarVoicingOptions::default()
+    }
+}
+
+/// Cost of one fretted-instrument fingering
+fn fingering_cost(fingering: &GuitarFingering) -> f64 {
+    let position = fingering.position();
+    let barred = fingering
+        .frets
+        .iter()
+        .filter(|&&fret| position > 0 && fret == Some(position))
+        .count();
+
+    let mut cost = f64::from(fingering.fret_span().saturating_sub(2)) * 0.5;
+    if barred > 1 {
+        cost += 2.0;
+    }
+    if fingering.finger_count() >= 4 {
+        cost += 0.5;
+    }
+    if position > 5 {
+        cost += 0.5;
+    }
+    cost
+}
+
+/// Cost of the easiest fingering of a chord and its hand position
+fn fretted_cost(
+    chord: &Chord,
+    scale: &ScaleFingerprint,
+    options: GuitarVoicingOptions,
+) -> ChordCost {
+    let fingerings = guitar_fingerings(chord, scale, 0, &options).unwrap_or_default();
+    fingerings
+        .iter()
+        .map(|fingering| (fingering_cost(fingering), fingering.position()))
+        .min_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)))
+        .map_or((UNPLAYABLE_CHORD_COST, None), |(cost, position)| {
+            (cost, Some(f64::from(position)))
+        })
+}
+
+/// Cost of singing a chord's bass and the bass pitch class
+fn voice_cost(chord: &Chord, scale: &ScaleFingerprint) -> ChordCost {
+    let Ok(pitch_classes) = chord_pitch_classes(chord, scale) else {
+        return (0.0, None);
+    };
+    let Some(&root) = pitch_classes.first() else {
+        return (0.0, None);
+    };
+    let bass_index = (chord.inversion as usize).min(pitch_classes.len() - 1);
+    let bass = pitch_classes.get(bass_index).copied().unwrap_or(root);
+
+    let chromatic = pitch_classes
+        .iter()
+        .filter(|pitch_class| !DIATONIC_PITCH_CLASSES.contains(pitch_class))
+        .count();
+    let cost = chromatic as f64 / pitch_classes.len() as f64 * 3.0;
+    (cost, Some(f64::from(bass)))
+}
+
+/// Interval between two sung bass pitch classes, folded to at most a tritone
+fn folded_interval(from: f64, to: f64) -> f64 {
+    let up = (to - from).rem_euclid(12.0);
+    up.min(12.0 - up)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use composer_core::{BorrowedScale, ScaleType};
+
+    fn triads(roots: &[u8]) -> Vec<Chord> {
+        roots
+            .iter()
+            .map(|&root| Chord::triad(root).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_profile_names() {
+        for profile in DifficultyProfile::ALL {
+            assert_eq!(DifficultyProfile::from_name(profile.name()), Some(profile));
+        }
+        assert_eq!(
+            DifficultyProfile::from_name("Guitar"),
+            Some(DifficultyProfile::Guitar)
+        );
+        assert_eq!(DifficultyProfile::from_name("banjo"), None);
+    }
+
+    #[test]
+    fn test_instruments_disagree_on_technique() {
+        // Open chords on guitar, all white keys on piano
+        let open = triads(&[1, 6, 2, 5]);
+        // F major and B♭ major need barres on guitar and a black key on piano
+        let flat_seven = Chord::triad(7)
+            .unwrap()
+            .with_borrowed_scale(BorrowedScale::ScaleType(ScaleType::Mixolydian))
+            .unwrap();
+        let fourth = Chord::triad(4).unwrap();
+        let barre = vec![fourth.clone(), flat_seven.clone(), fourth, flat_seven];
+
+        for profile in DifficultyProfile::ALL {
+            let easy = technical_complexity(profile, &open);
+            let hard = technical_complexity(profile, &barre);
+            assert!((1.0..=10.0).contains(&easy));
+            assert!(hard > easy, "{:?}: {} <= {}", profile, hard, easy);
+        }
+        assert_eq!(
+            technical_complexity(DifficultyProfile::Piano, &[Chord::rest()]),
+            1.0
+        );
+    }
+}
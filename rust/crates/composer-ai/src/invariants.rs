@@ -0,0 +1,232 @@
+//! Invariant checks for AI engine results
+//!
+//! Companion to [`composer_serialization::invariants`]: these checks describe what
+//! every suggestion list and difficulty assessment must satisfy. They back the
+//! engine's property tests and let plugin authors verify that custom scorers or
+//! post-processors keep results well-formed.
+
+use crate::analysis::DifficultyAssessment;
+use crate::suggestions::{ChordSuggestion, SuggestionConfig};
+use composer_serialization::ensure;
+pub use composer_serialization::{InvariantResult, InvariantViolation};
+
+/// Checks a suggestion list against the config that produced it.
+///
+/// Every suggestion must hold a valid chord, have finite scores in `0.0..=1.0`, and
+/// meet `config.min_confidence`. The list may not exceed `config.max_suggestions`,
+/// and unless probabilistic selection was requested it must be ordered by
+/// descending weighted score.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::{check_suggestion_invariants, AiEngine, SuggestionConfig, SuggestionContext};
+/// use composer_core::Chord;
+///
+/// let engine = AiEngine::new(Default::default());
+/// let cadence = vec![Chord::new(2, 7)?, Chord::new(5, 7)?, Chord::new(1, 7)?];
+/// engine.initialize(vec![(cadence, "ii-V-I".to_string(), None)])?;
+///
+/// let config = SuggestionConfig::default();
+/// let suggestions = engine.get_chord_suggestions(
+///     &[Chord::new(2, 7)?],
+///     &SuggestionContext::default(),
+///     &config,
+/// )?;
+/// assert!(check_suggestion_invariants(&suggestions, &config).is_ok());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`check_difficulty_invariants`] - Equivalent check for difficulty assessments
+/// - [`composer_serialization::check_roundtrip`] - Serialization invariants for chords
+pub fn check_suggestion_invariants(
+    suggestions: &[ChordSuggestion],
+    config: &SuggestionConfig,
+) -> InvariantResult {
+    ensure(
+        suggestions.len() <= config.max_suggestions,
+        "suggestion_count",
+        || {
+            format!(
+                "{} suggestions exceed max_suggestions {}",
+                suggestions.len(),
+                config.max_suggestions
+            )
+        },
+    )?;
+
+    for (i, suggestion) in suggestions.iter().enumerate() {
+        ensure(
+            suggestion.chord.validate().is_ok(),
+            "suggestion_chord_valid",
+            || format!("suggestion {} holds invalid chord {}", i, suggestion.chord),
+        )?;
+
+        for (name, score) in [
+            ("confidence", suggestion.confidence),
+            ("frequency_score", suggestion.frequency_score),
+            ("context_score", suggestion.context_score),
+            ("theory_score", suggestion.theory_score),
+            ("weighted_score", suggestion.weighted_score),
+        ] {
+            ensure(unit_range(score), "suggestion_score_range", || {
+                format!("suggestion {} has {} = {}", i, name, score)
+            })?;
+        }
+
+        ensure(
+            suggestion.confidence >= config.min_confidence,
+            "suggestion_min_confidence",
+            || {
+                format!(
+                    "suggestion {} confidence {} is below min_confidence {}",
+                    i, suggestion.confidence, config.min_confidence
+                )
+            },
+        )?;
+    }
+
+    if !config.use_probabilistic {
+        for (i, pair) in suggestions.windows(2).enumerate() {
+            ensure(
+                pair[0].weighted_score >= pair[1].weighted_score,
+                "suggestion_ordering",
+                || {
+                    format!(
+                        "suggestion {} ({}) ranks above a higher score ({})",
+                        i, pair[0].weighted_score, pair[1].weighted_score
+                    )
+                },
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that every score in a difficulty assessment is finite and in range.
+///
+/// Complexity scores must lie in `0.0..=10.0` and confidence in `0.0..=1.0`.
+pub fn check_difficulty_invariants(assessment: &DifficultyAssessment) -> InvariantResult {
+    for (name, score) in [
+        ("overall_score", assessment.overall_score),
+        ("harmonic_complexity", assessment.harmonic_complexity),
+        ("rhythmic_complexity", assessment.rhythmic_complexity),
+        ("technical_complexity", assessment.technical_complexity),
+        ("melodic_complexity", assessment.melodic_complexity),
+    ] {
+        ensure(
+            score.is_finite() && (0.0..=10.0).contains(&score),
+            "difficulty_score_range",
+            || format!("{} = {}", name, score),
+        )?;
+    }
+
+    ensure(
+        unit_range(assessment.confidence),
+        "difficulty_confidence_range",
+        || format!("confidence = {}", assessment.confidence),
+    )
+}
+
+fn unit_range(value: f64) -> bool {
+    value.is_finite() && (0.0..=1.0).contains(&value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{AiEngine, AiEngineConfig};
+    use crate::suggestions::SuggestionContext;
+    use composer_core::{ChordGenerator, DifficultyBand, GeneratorConfig};
+    use proptest::prelude::*;
+
+    fn trained_engine(seed: u64) -> AiEngine {
+        let config = GeneratorConfig::for_band(DifficultyBand::Intermediate);
+        let mut generator = ChordGenerator::with_config(seed, config);
+        let patterns = generator
+            .progressions(12, 3, 8)
+            .unwrap()
+            .into_iter()
+            .enumerate()
+            .map(|(i, chords)| (chords, format!("song_{}", i), None))
+            .collect();
+
+        let engine = AiEngine::new(AiEngineConfig::default());
+        engine.initialize(patterns).unwrap();
+        engine
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn prop_engine_results_hold_invariants(
+            seed in any::<u64>(),
+            max_suggestions in 1usize..10,
+            use_probabilistic in any::<bool>(),
+        ) {
+            let engine = trained_engine(seed);
+            let config = SuggestionConfig {
+                max_suggestions,
+                min_confidence: 0.0,
+                use_probabilistic,
+                ..Default::default()
+            };
+            let mut generator = ChordGenerator::new(seed.wrapping_add(1));
+            let pattern = generator.progression(3).unwrap();
+
+            let suggestions = engine
+                .get_chord_suggestions(&pattern[..2], &SuggestionContext::default(), &config)
+                .unwrap();
+            let checked = check_suggestion_invariants(&suggestions, &config);
+            prop_assert!(checked.is_ok(), "{:?}", checked);
+
+            let assessment = engine.assess_difficulty(&pattern, Some(120.0), Some((4, 4))).unwrap();
+            let checked = check_difficulty_invariants(&assessment);
+            prop_assert!(checked.is_ok(), "{:?}", checked);
+        }
+    }
+
+    #[test]
+    fn test_violations_are_detected() {
+        let config = SuggestionConfig {
+            max_suggestions: 2,
+            ..Default::default()
+        };
+        let suggestion = |weighted_score: f64| ChordSuggestion {
+            chord: composer_core::Chord::new(1, 5).unwrap(),
+            confidence: 0.9,
+            weighted_score,
+            ..Default::default()
+        };
+
+        assert!(check_suggestion_invariants(&[suggestion(0.8), suggestion(0.5)], &config).is_ok());
+        assert_eq!(
+            check_suggestion_invariants(&[suggestion(0.5), suggestion(0.8)], &config)
+                .unwrap_err()
+                .invariant,
+            "suggestion_ordering"
+        );
+        assert_eq!(
+            check_suggestion_invariants(&[suggestion(f64::NAN)], &config)
+                .unwrap_err()
+                .invariant,
+            "suggestion_score_range"
+        );
+        assert_eq!(
+            check_suggestion_invariants(&vec![suggestion(0.5); 3], &config)
+                .unwrap_err()
+                .invariant,
+            "suggestion_count"
+        );
+
+        let assessment = DifficultyAssessment {
+            overall_score: 11.0,
+            ..Default::default()
+        };
+        assert!(check_difficulty_invariants(&assessment).is_err());
+    }
+}
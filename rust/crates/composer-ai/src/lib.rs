@@ -4,14 +4,72 @@
 //! suggestions, bass line harmonization, and difficulty assessment using statistical
 //! models and trie-based pattern matching.
 
+pub mod accompaniment;
 pub mod analysis;
+pub(crate) mod arena;
+pub mod augmentation;
+pub mod bass;
+pub mod budget;
+pub mod compatibility;
+pub mod continuation;
+pub mod difficulty_model;
+pub mod embedding;
 pub mod engine;
 pub mod error;
+pub mod facade;
+pub mod form;
+pub mod genre;
+pub mod groove;
+pub mod instrument;
+pub mod invariants;
+pub mod melody;
+pub mod meter;
+pub mod neural;
+pub mod ngram;
+pub mod novelty;
+#[cfg(feature = "onnx")]
+pub mod onnx;
+pub mod parallel;
+pub mod practice;
+pub mod prelude;
+pub mod replay;
+pub mod session;
 pub mod suggestions;
 pub mod trie;
+pub mod turnaround;
+pub mod valence;
+pub mod validation;
 
+pub use accompaniment::*;
 pub use analysis::*;
+pub use augmentation::*;
+pub use bass::*;
+pub use budget::*;
+pub use compatibility::*;
+pub use continuation::*;
+pub use difficulty_model::*;
+pub use embedding::*;
 pub use engine::*;
 pub use error::*;
+pub use facade::*;
+pub use form::*;
+pub use genre::*;
+pub use groove::*;
+pub use instrument::*;
+pub use invariants::*;
+pub use melody::*;
+pub use meter::*;
+pub use neural::*;
+pub use ngram::*;
+pub use novelty::*;
+#[cfg(feature = "onnx")]
+pub use onnx::*;
+pub use parallel::*;
+pub use practice::*;
+pub use replay::*;
+pub use session::*;
 pub use suggestions::*;
 pub use trie::*;
+pub use turnaround::*;
+pub use valence::*;
+pub use validation::*;
@@ -0,0 +1,408 @@
+//! Melodic analysis of note sequences
+//!
+//! Measures a melody from its notes instead of the chord roots the difficulty
+//! model otherwise estimates melodic complexity from: range and tessitura, a
+//! histogram of the intervals between consecutive notes, contour entropy and
+//! rhythm density. A note's pitch is `octave * 12 + scale_degree`, with the
+//! scale degree as a chromatic offset (0-11) as in MIDI note tokens.
+//!
+//! Untimed notes last one beat each. Timeline notes last until the next note
+//! or rest event, and chords and clusters are accompaniment that the melody
+//! sounds over.
+
+use crate::analysis::{DifficultyAssessment, MusicalAnalyzer};
+use crate::error::{AiError, AiResult};
+use crate::meter::MeterContext;
+use composer_core::Chord;
+use composer_serialization::{Note, Timeline, TokenEvent, TokenEventType};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Interval of the last histogram bucket; wider leaps are counted in it too
+pub const INTERVAL_HISTOGRAM_MAX: usize = 12;
+
+/// Share of sounding time the tessitura covers, centered on the median pitch
+const TESSITURA_SHARE: f64 = 0.8;
+
+/// Melody measurements and the complexity derived from them
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MelodicAnalysis {
+    /// Pitched notes in the melody; rests are not counted
+    pub note_count: usize,
+
+    /// Lowest and highest pitch
+    pub lowest_pitch: u8,
+    pub highest_pitch: u8,
+
+    /// Semitones from the lowest to the highest pitch
+    pub range: u8,
+
+    /// Lowest and highest pitch of the central 80% of sounding time, where
+    /// the melody spends most of its time regardless of isolated extremes
+    pub tessitura: (u8, u8),
+
+    /// Mean pitch weighted by duration
+    pub mean_pitch: f64,
+
+    /// Moves between consecutive pitched notes by size in semitones, from 0
+    /// (repeated note) to [`INTERVAL_HISTOGRAM_MAX`] and wider
+    pub interval_histogram: Vec<u32>,
+
+    /// How unpredictably the line changes direction (0.0-1.0): the entropy
+    /// of each move's direction (up, down or repeat) given the previous one.
+    /// Scales and strict zigzags score 0.0.
+    pub contour_entropy: f64,
+
+    /// Pitched notes per beat
+    pub rhythm_density: f64,
+
+    /// Melodic complexity score (0.0-10.0) fed to difficulty assessment
+    pub complexity: f64,
+}
+
+/// Pitch (`None` for a rest) and duration in beats of one melody event
+type MelodyEvent = (Option<u8>, f64);
+
+/// Difficulty (0.0-0.7) of moving a melody or root line by an interval
+pub(crate) fn interval_difficulty(semitones: u8) -> f64 {
+    match semitones {
+        0 => 0.0,      // Unison (no movement)
+        1 => 0.2,      // Minor second (chromatic)
+        2 => 0.1,      // Major second (step)
+        3 => 0.2,      // Minor third
+        4 => 0.3,      // Major third
+        5 => 0.4,      // Perfect fourth
+        6 => 0.5,      // Tritone (complex)
+        7 => 0.4,      // Perfect fifth
+        8..=11 => 0.6, // Large intervals (6th, 7th, etc.)
+        _ => 0.7,      // Very large intervals
+    }
+}
+
+fn note_pitch(note: &Note) -> Option<u8> {
+    if note.is_rest {
+        return None;
+    }
+    u8::try_from(note.octave as u16 * 12 + note.scale_degree as u16).ok()
+}
+
+/// Pitch at a share of the sounding time, walking pitches from the lowest
+fn weighted_quantile(sorted: &[(u8, f64)], total: f64, share: f64) -> u8 {
+    let mut elapsed = 0.0;
+    for &(pitch, duration) in sorted {
+        elapsed += duration;
+        if elapsed >= total * share {
+            return pitch;
+        }
+    }
+    sorted.last().map_or(0, |(pitch, _)| *pitch)
+}
+
+/// Entropy of move directions given the previous direction, normalized to 0.0-1.0
+fn contour_entropy(pitches: &[u8]) -> f64 {
+    let directions: Vec<std::cmp::Ordering> = pitches
+        .windows(2)
+        .map(|pair| pair[1].cmp(&pair[0]))
+        .collect();
+    if directions.len() < 2 {
+        return 0.0;
+    }
+
+    let mut transitions: HashMap<_, HashMap<_, u32>> = HashMap::new();
+    for pair in directions.windows(2) {
+        *transitions
+            .entry(pair[0])
+            .or_default()
+            .entry(pair[1])
+            .or_default() += 1;
+    }
+    let total = (directions.len() - 1) as f64;
+    let entropy: f64 = transitions
+        .values()
+        .map(|next| {
+            let from_count = next.values().sum::<u32>() as f64;
+            let conditional: f64 = next
+                .values()
+                .map(|&count| {
+                    let p = count as f64 / from_count;
+                    -p * p.log2()
+                })
+                .sum();
+            from_count / total * conditional
+        })
+        .sum();
+    entropy / 3f64.log2()
+}
+
+fn analyze_events(events: &[MelodyEvent]) -> AiResult<MelodicAnalysis> {
+    let pitched: Vec<(u8, f64)> = events
+        .iter()
+        .filter_map(|&(pitch, duration)| pitch.map(|pitch| (pitch, duration.max(0.0))))
+        .collect();
+    if pitched.is_empty() {
+        return Err(AiError::AnalysisFailed {
+            reason: "Cannot analyze a melody without pitched notes".to_string(),
+        });
+    }
+
+    let pitches: Vec<u8> = pitched.iter().map(|(pitch, _)| *pitch).collect();
+    let lowest_pitch = *pitches.iter().min().unwrap_or(&0);
+    let highest_pitch = *pitches.iter().max().unwrap_or(&0);
+    let range = highest_pitch - lowest_pitch;
+
+    let sounding: f64 = pitched.iter().map(|(_, duration)| duration).sum();
+    let mut by_pitch = pitched.clone();
+    by_pitch.sort_by_key(|(pitch, _)| *pitch);
+    let (tessitura, mean_pitch) = if sounding > 0.0 {
+        let margin = (1.0 - TESSITURA_SHARE) / 2.0;
+        (
+            (
+                weighted_quantile(&by_pitch, sounding, margin),
+                weighted_quantile(&by_pitch, sounding, 1.0 - margin),
+            ),
+            pitched
+                .iter()
+                .map(|(pitch, duration)| *pitch as f64 * duration)
+                .sum::<f64>()
+                / sounding,
+        )
+    } else {
+        (
+            (lowest_pitch, highest_pitch),
+            pitches.iter().map(|&pitch| pitch as f64).sum::<f64>() / pitches.len() as f64,
+        )
+    };
+
+    let intervals: Vec<u8> = pitches
+        .windows(2)
+        .map(|pair| pair[0].abs_diff(pair[1]))
+        .collect();
+    let mut interval_histogram = vec![0; INTERVAL_HISTOGRAM_MAX + 1];
+    for &interval in &intervals {
+        interval_histogram[(interval as usize).min(INTERVAL_HISTOGRAM_MAX)] += 1;
+    }
+
+    let contour_entropy = contour_entropy(&pitches);
+    let beats: f64 = events.iter().map(|(_, duration)| duration.max(0.0)).sum();
+    let rhythm_density = if beats > 0.0 {
+        pitched.len() as f64 / beats
+    } else {
+        0.0
+    };
+
+    let mean_interval_difficulty = if intervals.is_empty() {
+        0.0
+    } else {
+        intervals
+            .iter()
+            .map(|&interval| interval_difficulty(interval))
+            .sum::<f64>()
+            / intervals.len() as f64
+    };
+    let complexity = (1.0
+        + (range as f64 / 12.0).min(2.0)
+        + mean_interval_difficulty * 5.0
+        + contour_entropy * 1.5
+        + (rhythm_density - 1.0).clamp(0.0, 4.0) * 0.75)
+        .min(10.0);
+
+    Ok(MelodicAnalysis {
+        note_count: pitched.len(),
+        lowest_pitch,
+        highest_pitch,
+        range,
+        tessitura,
+        mean_pitch,
+        interval_histogram,
+        contour_entropy,
+        rhythm_density,
+        complexity,
+    })
+}
+
+/// Melody events of a timeline in beat order
+///
+/// Each note or rest lasts until the next note or rest, or the end of the
+/// timeline for the last one.
+fn timeline_melody(timeline: &Timeline) -> Vec<MelodyEvent> {
+    let mut events: Vec<&TokenEvent> = timeline
+        .events
+        .iter()
+        .filter(|event| {
+            matches!(
+                event.event_type,
+                TokenEventType::Note(_) | TokenEventType::Rest
+            )
+        })
+        .collect();
+    events.sort_by(|a, b| a.beat.total_cmp(&b.beat));
+
+    events
+        .iter()
+        .enumerate()
+        .map(|(index, event)| {
+            let end = events
+                .get(index + 1)
+                .map_or(timeline.total_duration.max(event.beat), |next| next.beat);
+            let pitch = match &event.event_type {
+                TokenEventType::Note(note) => note_pitch(note),
+                _ => None,
+            };
+            (pitch, end - event.beat)
+        })
+        .collect()
+}
+
+impl MusicalAnalyzer {
+    /// Analyze a melody given as untimed notes, one beat each
+    ///
+    /// Rests take time but have no pitch. Fails when no note is pitched.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{ChordProgressionTrie, MusicalAnalyzer};
+    /// use composer_serialization::Note;
+    /// use std::sync::Arc;
+    ///
+    /// let analyzer = MusicalAnalyzer::new(Arc::new(ChordProgressionTrie::new()));
+    /// let note = |scale_degree| Note { scale_degree, octave: 5, is_rest: false };
+    ///
+    /// // C D E F G: stepwise and always rising
+    /// let scale = analyzer.analyze_melody(&[note(0), note(2), note(4), note(5), note(7)])?;
+    /// assert_eq!(scale.range, 7);
+    /// assert_eq!(scale.contour_entropy, 0.0);
+    ///
+    /// // Leaps in an irregular contour
+    /// let leaps = analyzer.analyze_melody(&[note(0), note(9), note(2), note(2), note(11), note(0)])?;
+    /// assert!(leaps.complexity > scale.complexity);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`Self::analyze_timeline_melody`] - Melody of timed note events
+    /// - [`Self::assess_melody_difficulty`] - Difficulty with the melody's complexity
+    pub fn analyze_melody(&self, notes: &[Note]) -> AiResult<MelodicAnalysis> {
+        let events: Vec<MelodyEvent> = notes.iter().map(|note| (note_pitch(note), 1.0)).collect();
+        analyze_events(&events)
+    }
+
+    /// Analyze the note events of a timeline as a melody
+    ///
+    /// Fails when the timeline has no pitched note events.
+    pub fn analyze_timeline_melody(&self, timeline: &Timeline) -> AiResult<MelodicAnalysis> {
+        analyze_events(&timeline_melody(timeline))
+    }
+
+    /// Assess the difficulty of a progression accompanying a melody
+    ///
+    /// As [`Self::assess_difficulty`], except that melodic complexity is
+    /// measured from the melody (see [`Self::analyze_melody`]) instead of
+    /// estimated from chord roots, and the analysis is kept in
+    /// [`DifficultyAssessment::melody`].
+    pub fn assess_melody_difficulty(
+        &self,
+        progression: &[Chord],
+        melody: &[Note],
+        tempo_bpm: Option<f64>,
+        time_signature: Option<(u8, u8)>,
+    ) -> AiResult<DifficultyAssessment> {
+        let melody = self.analyze_melody(melody)?;
+        let meter = MeterContext::from_time_signature(time_signature);
+        self.assess_difficulty_in_meter(progression, tempo_bpm, &meter, None, Some(melody))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trie::ChordProgressionTrie;
+    use std::sync::Arc;
+
+    fn note(pitch: u8) -> Note {
+        Note {
+            scale_degree: pitch % 12,
+            octave: pitch / 12,
+            is_rest: false,
+        }
+    }
+
+    #[test]
+    fn test_analyze_melody() {
+        let analyzer = MusicalAnalyzer::new(Arc::new(ChordProgressionTrie::new()));
+        let rest = Note {
+            is_rest: true,
+            ..note(0)
+        };
+        let melody = [
+            note(60),
+            note(62),
+            note(64),
+            rest.clone(),
+            note(72),
+            note(60),
+        ];
+        let analysis = analyzer.analyze_melody(&melody).unwrap();
+
+        assert_eq!(analysis.note_count, 5);
+        assert_eq!((analysis.lowest_pitch, analysis.highest_pitch), (60, 72));
+        assert_eq!(analysis.range, 12);
+        assert_eq!(analysis.tessitura, (60, 72));
+        assert!((analysis.mean_pitch - 63.6).abs() < 1e-9);
+        assert_eq!(analysis.interval_histogram[2], 2);
+        assert_eq!(analysis.interval_histogram[8], 1);
+        assert_eq!(analysis.interval_histogram[12], 1);
+        assert!((analysis.rhythm_density - 5.0 / 6.0).abs() < 1e-9);
+        assert!(analysis.contour_entropy > 0.0 && analysis.contour_entropy <= 1.0);
+
+        let zigzag: Vec<Note> = [60, 64, 60, 64, 60, 64].map(note).to_vec();
+        assert_eq!(
+            analyzer.analyze_melody(&zigzag).unwrap().contour_entropy,
+            0.0
+        );
+        assert!(analyzer.analyze_melody(&[rest]).is_err());
+    }
+
+    #[test]
+    fn test_timeline_melody() {
+        let analyzer = MusicalAnalyzer::new(Arc::new(ChordProgressionTrie::new()));
+        let mut events = vec![TokenEvent {
+            beat: 0.0,
+            event_type: TokenEventType::Chord(Chord::triad(1).unwrap()),
+        }];
+        for (index, pitch) in [60, 62, 64, 65, 67, 65, 64, 62].into_iter().enumerate() {
+            events.push(TokenEvent {
+                beat: index as f64 * 0.5,
+                event_type: TokenEventType::Note(note(pitch)),
+            });
+        }
+        let timeline = Timeline {
+            events,
+            total_duration: 4.0,
+            ..Default::default()
+        };
+
+        let analysis = analyzer.analyze_timeline_melody(&timeline).unwrap();
+        assert_eq!(analysis.note_count, 8);
+        assert_eq!(analysis.rhythm_density, 2.0);
+        assert_eq!(analysis.range, 7);
+
+        let chords_only = Timeline {
+            events: timeline.events[..1].to_vec(),
+            ..timeline.clone()
+        };
+        assert!(analyzer.analyze_timeline_melody(&chords_only).is_err());
+
+        // The timeline assessment measures melodic complexity from the notes
+        let assessment = analyzer
+            .assess_timeline_difficulty(&timeline, None, None)
+            .unwrap();
+        assert_eq!(assessment.melody, Some(analysis.clone()));
+        assert_eq!(assessment.melodic_complexity, analysis.complexity);
+        let untimed = analyzer
+            .assess_difficulty(&[Chord::triad(1).unwrap()], None, None)
+            .unwrap();
+        assert!(untimed.melody.is_none());
+    }
+}
@@ -0,0 +1,218 @@
+//! Meter-aware rhythmic complexity
+//!
+//! Measures what the meter contributes to rhythmic difficulty: how often the
+//! time signature changes, how much of the music is in irregular meters such
+//! as 5/4 or 7/8, and how many chord changes land off the beat.
+
+use composer_serialization::{metric_position, MeterChange, MetricPosition, Timeline};
+
+/// Meter assumed where none is given
+const DEFAULT_TIME_SIGNATURE: (u8, u8) = (4, 4);
+
+/// Irregularity of a time signature, from 0.0 (even groupings) to 2.0
+///
+/// Meters whose beats group evenly into twos or threes (2/4, 3/4, 4/4, 6/8,
+/// 12/8, ...) score 0.0. Meters that need uneven groupings score by how many
+/// groups they take: 1.0 for 5 (3+2), 1.5 for 7 (2+2+3) and 2.0 for 11, 13
+/// and longer.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::meter_irregularity;
+///
+/// assert_eq!(meter_irregularity((4, 4)), 0.0);
+/// assert_eq!(meter_irregularity((6, 8)), 0.0);
+/// assert_eq!(meter_irregularity((5, 4)), 1.0);
+/// assert_eq!(meter_irregularity((7, 8)), 1.5);
+/// ```
+pub fn meter_irregularity(time_signature: (u8, u8)) -> f64 {
+    match time_signature.0 {
+        n if n % 2 == 0 || n % 3 == 0 || n < 5 => 0.0,
+        5 => 1.0,
+        7 => 1.5,
+        _ => 2.0,
+    }
+}
+
+/// Meter changes and chord onsets a progression is assessed in
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MeterContext {
+    /// Meter changes ordered by beat
+    meters: Vec<MeterChange>,
+    /// Chord onsets in quarter notes; empty when timing is unknown
+    onsets: Vec<f64>,
+    /// Length of the music in quarter notes
+    total_duration: f64,
+}
+
+impl MeterContext {
+    /// Context of an untimed progression in a single meter, or 4/4 for `None`
+    pub(crate) fn from_time_signature(time_signature: Option<(u8, u8)>) -> Self {
+        Self {
+            meters: vec![MeterChange {
+                beat: 0.0,
+                time_signature: time_signature.unwrap_or(DEFAULT_TIME_SIGNATURE),
+            }],
+            onsets: Vec::new(),
+            total_duration: 0.0,
+        }
+    }
+
+    /// Context of a timeline's meter changes and chord `onsets`
+    ///
+    /// `time_signature` applies before the first change, or throughout when
+    /// the timeline has none.
+    pub(crate) fn from_timeline(
+        timeline: &Timeline,
+        onsets: Vec<f64>,
+        time_signature: Option<(u8, u8)>,
+    ) -> Self {
+        let mut meters = timeline.meter_changes.clone();
+        meters.sort_by(|a, b| a.beat.total_cmp(&b.beat));
+        if !matches!(meters.first(), Some(first) if first.beat <= 0.0) {
+            meters.insert(
+                0,
+                MeterChange {
+                    beat: 0.0,
+                    time_signature: time_signature.unwrap_or(DEFAULT_TIME_SIGNATURE),
+                },
+            );
+        }
+        Self {
+            meters,
+            onsets,
+            total_duration: timeline.total_duration,
+        }
+    }
+
+    /// The same meters without chord onsets
+    pub(crate) fn untimed(&self) -> Self {
+        Self {
+            meters: self.meters.clone(),
+            onsets: Vec::new(),
+            total_duration: self.total_duration,
+        }
+    }
+
+    /// Number of changes to a different time signature
+    pub(crate) fn signature_changes(&self) -> usize {
+        self.meters
+            .windows(2)
+            .filter(|pair| pair[0].time_signature != pair[1].time_signature)
+            .count()
+    }
+
+    /// [`meter_irregularity`] averaged over the music, weighted by duration
+    pub(crate) fn irregularity(&self) -> f64 {
+        let mut weighted = 0.0;
+        let mut total = 0.0;
+        for (index, meter) in self.meters.iter().enumerate() {
+            let end = self
+                .meters
+                .get(index + 1)
+                .map_or(self.total_duration.max(meter.beat + 1.0), |next| next.beat);
+            let span = (end - meter.beat).max(0.0);
+            weighted += meter_irregularity(meter.time_signature) * span;
+            total += span;
+        }
+        if total > 0.0 {
+            weighted / total
+        } else {
+            0.0
+        }
+    }
+
+    /// Share (0.0-1.0) of chord onsets that land off the beat
+    ///
+    /// Anticipations of a downbeat or mid-bar beat count fully, other offbeats
+    /// half. Untimed progressions have no syncopation.
+    pub(crate) fn syncopation(&self) -> f64 {
+        if self.onsets.is_empty() {
+            return 0.0;
+        }
+        let weight: f64 = self
+            .onsets
+            .iter()
+            .map(|&onset| {
+                let meter = self
+                    .meters
+                    .iter()
+                    .take_while(|change| change.beat <= onset)
+                    .last()
+                    .copied()
+                    .unwrap_or(MeterChange {
+                        beat: 0.0,
+                        time_signature: DEFAULT_TIME_SIGNATURE,
+                    });
+                match metric_position(onset - meter.beat, meter.time_signature) {
+                    Ok(MetricPosition::Anticipation) => 1.0,
+                    Ok(MetricPosition::Offbeat) => 0.5,
+                    _ => 0.0,
+                }
+            })
+            .sum();
+        weight / self.onsets.len() as f64
+    }
+
+    /// Cache key fragment identifying the context
+    pub(crate) fn cache_key(&self) -> String {
+        let meters: Vec<String> = self
+            .meters
+            .iter()
+            .map(|change| {
+                format!(
+                    "{:.3}@{}/{}",
+                    change.beat, change.time_signature.0, change.time_signature.1
+                )
+            })
+            .collect();
+        let onsets: Vec<String> = self
+            .onsets
+            .iter()
+            .map(|onset| format!("{:.3}", onset))
+            .collect();
+        format!(
+            "[{}]_[{}]_{:.3}",
+            meters.join(","),
+            onsets.join(","),
+            self.total_duration
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_meter_context() {
+        let single = MeterContext::from_time_signature(Some((7, 8)));
+        assert_eq!(single.signature_changes(), 0);
+        assert_eq!(single.irregularity(), 1.5);
+        assert_eq!(single.syncopation(), 0.0);
+
+        // 4 beats of 4/4, 4 of 5/4, then 4/4 again for 8 beats
+        let timeline = Timeline {
+            events: Vec::new(),
+            total_duration: 16.0,
+            meter_changes: vec![
+                MeterChange {
+                    beat: 4.0,
+                    time_signature: (5, 4),
+                },
+                MeterChange {
+                    beat: 8.0,
+                    time_signature: (4, 4),
+                },
+            ],
+            ..Default::default()
+        };
+        let context = MeterContext::from_timeline(&timeline, vec![0.0, 3.5, 4.0, 6.5], None);
+        assert_eq!(context.signature_changes(), 2);
+        assert_eq!(context.irregularity(), 0.25);
+        // An anticipation of beat 4 in 4/4, an offbeat in 5/4
+        assert_eq!(context.syncopation(), 0.375);
+        assert_ne!(context.cache_key(), single.cache_key());
+    }
+}
@@ -0,0 +1,181 @@
+//! Hook for blending external neural models into suggestion ranking
+//!
+//! The trie only knows how often a continuation followed the query pattern. A
+//! [`NeuralScorer`] sees the whole progression at once, so sequence models trained
+//! offline (recurrent and state-space "attention-free" models, or transformers run
+//! through an inference runtime such as ONNX Runtime) can rank candidates using
+//! long-range context. The engine still generates candidates from the trie; the
+//! model only re-scores them, and its score is blended with the trie statistics
+//! according to [`SuggestionConfig::neural_weight`]. With the `onnx` feature,
+//! `OnnxScorer` runs models exported to ONNX in-process, including on WASM.
+
+use crate::error::{AiError, AiResult};
+use crate::suggestions::{ChordSuggestion, SuggestionConfig};
+use composer_core::Chord;
+use std::sync::Arc;
+
+/// External model that scores candidate continuations of a progression.
+///
+/// Implementations must be thread-safe: the engine may call the scorer from several
+/// threads at once. Returning an error fails the suggestion request rather than
+/// silently falling back to trie-only ranking.
+///
+/// Closures of the form `Fn(&[Chord], &[Chord]) -> AiResult<Vec<f64>>` implement
+/// this trait, which is usually the easiest way to wrap an inference session.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::{AiEngine, AiEngineConfig, AiResult, SuggestionConfig, SuggestionContext};
+/// use composer_core::Chord;
+/// use std::sync::Arc;
+///
+/// // Stand-in for a trained model: prefer dominant chords
+/// let scorer = |_history: &[Chord], candidates: &[Chord]| -> AiResult<Vec<f64>> {
+///     Ok(candidates.iter().map(|c| if c.root == 5 { 1.0 } else { 0.0 }).collect())
+/// };
+///
+/// let engine = AiEngine::new(AiEngineConfig::default());
+/// let cadence = vec![Chord::new(1, 5)?, Chord::new(4, 5)?, Chord::new(5, 7)?];
+/// engine.initialize(vec![(cadence, "cadence".to_string(), None)])?;
+/// engine.set_neural_scorer(Some(Arc::new(scorer)));
+///
+/// let config = SuggestionConfig {
+///     neural_weight: 0.5,
+///     min_confidence: 0.0,
+///     ..Default::default()
+/// };
+/// let pattern = [Chord::new(1, 5)?, Chord::new(4, 5)?];
+/// let suggestions =
+///     engine.get_chord_suggestions(&pattern, &SuggestionContext::default(), &config)?;
+/// assert_eq!(suggestions[0].chord.root, 5);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`crate::AiEngine::set_neural_scorer`] - Attach a scorer to an engine
+/// - [`SuggestionConfig::neural_weight`] - Blend between trie and model scores
+pub trait NeuralScorer: Send + Sync {
+    /// Score each candidate as the next chord after `history`.
+    ///
+    /// Must return exactly one score per candidate, in order, each in `0.0..=1.0`.
+    /// Values outside the range are clamped and non-finite values count as 0.
+    fn score_candidates(&self, history: &[Chord], candidates: &[Chord]) -> AiResult<Vec<f64>>;
+
+    /// Short model name used in suggestion reasoning
+    fn name(&self) -> &str {
+        "neural model"
+    }
+}
+
+impl<F> NeuralScorer for F
+where
+    F: Fn(&[Chord], &[Chord]) -> AiResult<Vec<f64>> + Send + Sync,
+{
+    fn score_candidates(&self, history: &[Chord], candidates: &[Chord]) -> AiResult<Vec<f64>> {
+        self(history, candidates)
+    }
+}
+
+/// Shared handle to a neural scorer
+pub type SharedNeuralScorer = Arc<dyn NeuralScorer>;
+
+/// Blend model scores into already scored suggestions.
+///
+/// `confidence` maps a blended weighted score back to a confidence value so that
+/// callers keep their own confidence formula.
+pub(crate) fn blend_neural_scores(
+    scorer: &dyn NeuralScorer,
+    history: &[Chord],
+    suggestions: &mut [ChordSuggestion],
+    config: &SuggestionConfig,
+    confidence: impl Fn(&ChordSuggestion) -> f64,
+) -> AiResult<()> {
+    if suggestions.is_empty() {
+        return Ok(());
+    }
+
+    let candidates: Vec<Chord> = suggestions.iter().map(|s| s.chord.clone()).collect();
+    let scores = scorer.score_candidates(history, &candidates)?;
+    if scores.len() != candidates.len() {
+        return Err(AiError::SuggestionFailed {
+            reason: format!(
+                "{} returned {} scores for {} candidates",
+                scorer.name(),
+                scores.len(),
+                candidates.len()
+            ),
+        });
+    }
+
+    let weight = config.neural_weight.clamp(0.0, 1.0);
+    for (suggestion, score) in suggestions.iter_mut().zip(scores) {
+        let score = if score.is_finite() {
+            score.clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        suggestion.weighted_score = (1.0 - weight) * suggestion.weighted_score + weight * score;
+        suggestion.confidence = confidence(suggestion);
+        suggestion.reasoning = format!(
+            "{} ({} score {:.2})",
+            suggestion.reasoning,
+            scorer.name(),
+            score
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suggestion(root: u8, weighted_score: f64) -> ChordSuggestion {
+        ChordSuggestion {
+            chord: Chord::new(root, 5).unwrap(),
+            weighted_score,
+            reasoning: "Suggested".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_blend_weights_model_scores() {
+        let scorer =
+            |_: &[Chord], candidates: &[Chord]| Ok(candidates.iter().map(|_| 1.0).collect());
+        let config = SuggestionConfig {
+            neural_weight: 0.25,
+            ..Default::default()
+        };
+        let mut suggestions = vec![suggestion(1, 0.2), suggestion(5, 0.6)];
+
+        blend_neural_scores(&scorer, &[], &mut suggestions, &config, |s| {
+            s.weighted_score
+        })
+        .unwrap();
+
+        assert!((suggestions[0].weighted_score - 0.4).abs() < 1e-9);
+        assert!((suggestions[1].weighted_score - 0.7).abs() < 1e-9);
+        assert_eq!(suggestions[0].confidence, suggestions[0].weighted_score);
+        assert!(suggestions[1].reasoning.contains("neural model score 1.00"));
+    }
+
+    #[test]
+    fn test_blend_rejects_wrong_score_count() {
+        let scorer = |_: &[Chord], _: &[Chord]| Ok(vec![f64::NAN]);
+        let config = SuggestionConfig {
+            neural_weight: 1.0,
+            ..Default::default()
+        };
+
+        let mut one = vec![suggestion(1, 0.5)];
+        blend_neural_scores(&scorer, &[], &mut one, &config, |_| 0.0).unwrap();
+        assert_eq!(one[0].weighted_score, 0.0);
+
+        let mut two = vec![suggestion(1, 0.5), suggestion(4, 0.5)];
+        let result = blend_neural_scores(&scorer, &[], &mut two, &config, |_| 0.0);
+        assert!(matches!(result, Err(AiError::SuggestionFailed { .. })));
+    }
+}
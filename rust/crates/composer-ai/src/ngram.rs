@@ -0,0 +1,415 @@
+//! N-gram chord model and pluggable suggestion backends
+//!
+//! The trie stores whole patterns from their first chord, so a query only
+//! matches progressions that began exactly the same way; a long or unusual
+//! history often has no continuation at all. [`NGramModel`] instead conditions
+//! on the last few chords only, and interpolates each context with the shorter
+//! ones below it (Witten-Bell smoothing) so that a history never seen in full
+//! still gets probabilities from its suffixes.
+//!
+//! Both sources implement [`SuggestionBackend`]. An engine keeps generating
+//! candidates from its trie; an attached backend adds its own candidates and is
+//! blended into the frequency score according to
+//! [`SuggestionConfig::backend_weight`].
+
+use crate::engine::TrainingPattern;
+use crate::error::{AiError, AiResult};
+use crate::trie::{BulkAddReport, ChordProgressionTrie, PatternRejection};
+use ahash::AHashMap;
+use composer_core::Chord;
+use composer_serialization::{deserialize_chord, serialize_chord, ChordBinary};
+use std::sync::Arc;
+
+#[cfg(doc)]
+use crate::suggestions::SuggestionConfig;
+
+/// Highest supported model order
+pub const MAX_NGRAM_ORDER: usize = 4;
+
+/// A candidate next chord and its probability
+pub type Continuation = (Chord, f64);
+
+/// Source of continuation probabilities for the suggester.
+///
+/// Implementations must be thread-safe: the engine may query the backend from
+/// several threads at once.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::{ChordProgressionTrie, NGramModel, SuggestionBackend};
+/// use composer_core::Chord;
+///
+/// let progression = vec![Chord::new(2, 7)?, Chord::new(5, 7)?, Chord::triad(1)?];
+///
+/// // The trie only knows continuations of patterns starting with the history
+/// let trie = ChordProgressionTrie::new();
+/// trie.add_pattern(&progression, "ii-V-I".to_string(), None)?;
+/// let history = [Chord::triad(4)?, Chord::new(2, 7)?];
+/// assert!(trie.continuations(&history, 3)?.is_empty());
+///
+/// // The bigram model backs off to the last chord
+/// let model = NGramModel::new(2)?;
+/// model.add_pattern(&progression)?;
+/// let continuations = model.continuations(&history, 3)?;
+/// assert_eq!(continuations[0].0, Chord::new(5, 7)?);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`crate::AiEngine::set_suggestion_backend`] - Attach a backend to an engine
+/// - [`SuggestionConfig::backend_weight`] - Blend between trie and backend scores
+pub trait SuggestionBackend: Send + Sync {
+    /// Up to `limit` chords to follow `history` with their probabilities
+    /// (0.0-1.0), most likely first
+    fn continuations(&self, history: &[Chord], limit: usize) -> AiResult<Vec<Continuation>>;
+
+    /// Short backend name used in suggestion reasoning
+    fn name(&self) -> &str;
+}
+
+/// Shared handle to a suggestion backend
+pub type SharedSuggestionBackend = Arc<dyn SuggestionBackend>;
+
+impl SuggestionBackend for ChordProgressionTrie {
+    /// Continuations of stored patterns that start with `history`, with the
+    /// share of those patterns that continue with each chord
+    fn continuations(&self, history: &[Chord], limit: usize) -> AiResult<Vec<Continuation>> {
+        let mut search_pattern: Vec<Option<Chord>> = history.iter().cloned().map(Some).collect();
+        search_pattern.push(None);
+
+        let mut continuations: Vec<Continuation> = self
+            .search_with_wildcard(&search_pattern, limit)?
+            .into_iter()
+            .filter_map(|result| {
+                let chord = deserialize_chord(&result.serialized_chord).ok()?;
+                Some((chord, result.relative_count.clamp(0.0, 1.0)))
+            })
+            .collect();
+        continuations.sort_by(|a, b| b.1.total_cmp(&a.1));
+        continuations.truncate(limit);
+        Ok(continuations)
+    }
+
+    fn name(&self) -> &str {
+        "pattern trie"
+    }
+}
+
+/// Counts keyed by context
+type NGramCounts = AHashMap<Vec<ChordBinary>, ContextCounts>;
+
+/// Continuation counts of one context
+#[derive(Debug, Clone)]
+struct ContextCounts {
+    /// Times the context was followed by any chord
+    total: u64,
+    /// Times it was followed by each chord
+    next: AHashMap<ChordBinary, u64>,
+}
+
+impl Default for ContextCounts {
+    fn default() -> Self {
+        Self {
+            total: 0,
+            next: AHashMap::new(),
+        }
+    }
+}
+
+/// Markov chord model of order 1-4 with Witten-Bell backoff smoothing
+///
+/// A model of order `n` predicts the next chord from the `n - 1` chords before
+/// it; order 1 ignores the history and ranks chords by overall frequency. The
+/// probability of a chord after a context is interpolated with its probability
+/// after the context's shorter suffixes, each context trusted in proportion to
+/// how often it was seen relative to how many different chords followed it.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::{NGramModel, SuggestionBackend};
+/// use composer_core::Chord;
+///
+/// let model = NGramModel::new(3)?;
+/// let report = model.add_patterns_bulk(vec![
+///     (vec![Chord::triad(1)?, Chord::triad(4)?, Chord::triad(5)?], "I-IV-V".to_string(), None),
+///     (vec![Chord::triad(6)?, Chord::triad(4)?, Chord::triad(1)?], "vi-IV-I".to_string(), None),
+/// ]);
+/// assert!(report.is_complete());
+///
+/// // Two chords of context tell the progressions apart
+/// let after_one_four = model.probability(&[Chord::triad(1)?, Chord::triad(4)?], &Chord::triad(5)?)?;
+/// let after_six_four = model.probability(&[Chord::triad(6)?, Chord::triad(4)?], &Chord::triad(5)?)?;
+/// assert!(after_one_four > after_six_four);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`SuggestionBackend`] - How the suggester queries the model
+/// - [`crate::AiEngine::set_suggestion_backend`] - Blend the model into suggestions
+#[derive(Debug)]
+pub struct NGramModel {
+    order: usize,
+    /// Counts keyed by context, from the empty context up to `order - 1` chords
+    counts: parking_lot::RwLock<NGramCounts>,
+}
+
+impl NGramModel {
+    /// An untrained model of `order` (1-4)
+    pub fn new(order: usize) -> AiResult<Self> {
+        if !(1..=MAX_NGRAM_ORDER).contains(&order) {
+            return Err(AiError::InvalidModel {
+                reason: format!(
+                    "N-gram order must be between 1 and {}, got {}",
+                    MAX_NGRAM_ORDER, order
+                ),
+            });
+        }
+
+        Ok(Self {
+            order,
+            counts: parking_lot::RwLock::new(AHashMap::new()),
+        })
+    }
+
+    /// Model order
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// Number of distinct chords seen in training
+    pub fn vocabulary_size(&self) -> usize {
+        self.counts
+            .read()
+            .get(&Vec::new())
+            .map_or(0, |unigrams| unigrams.next.len())
+    }
+
+    /// Learn one progression
+    pub fn add_pattern(&self, pattern: &[Chord]) -> AiResult<()> {
+        let serialized = Self::serialize_pattern(pattern)?;
+        self.insert(&mut self.counts.write(), &serialized);
+        Ok(())
+    }
+
+    /// Learn many progressions under a single write lock
+    ///
+    /// Takes the same patterns as [`ChordProgressionTrie::add_patterns_bulk`],
+    /// ignoring their sources and keys. Patterns that fail validation are
+    /// reported by their position in the input and skipped.
+    pub fn add_patterns_bulk<I>(&self, patterns: I) -> BulkAddReport
+    where
+        I: IntoIterator<Item = TrainingPattern>,
+    {
+        let mut report = BulkAddReport::default();
+        let mut counts = self.counts.write();
+        for (index, (pattern, source_id, _)) in patterns.into_iter().enumerate() {
+            match Self::serialize_pattern(&pattern) {
+                Ok(serialized) => {
+                    self.insert(&mut counts, &serialized);
+                    report.added += 1;
+                },
+                Err(error) => report.rejected.push(PatternRejection {
+                    index,
+                    source_id,
+                    error,
+                }),
+            }
+        }
+        report
+    }
+
+    /// Smoothed probability of `chord` following `history`
+    ///
+    /// Only the last `order - 1` chords of the history are considered. A chord
+    /// never seen in training has probability 0.
+    pub fn probability(&self, history: &[Chord], chord: &Chord) -> AiResult<f64> {
+        let contexts = self.contexts(history)?;
+        let key = serialize_chord(chord).map_err(|e| AiError::SuggestionFailed {
+            reason: format!("Chord serialization failed: {}", e),
+        })?;
+        Ok(Self::interpolate(&self.counts.read(), &contexts, key))
+    }
+
+    /// Count every n-gram of a serialized pattern, up to the model order
+    fn insert(&self, counts: &mut NGramCounts, pattern: &[ChordBinary]) {
+        for (position, next) in pattern.iter().enumerate() {
+            for length in 0..self.order.min(position + 1) {
+                let context = counts
+                    .entry(pattern[position - length..position].to_vec())
+                    .or_default();
+                context.total += 1;
+                *context.next.entry(*next).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// Contexts of `history` from the empty one up to `order - 1` chords
+    fn contexts(&self, history: &[Chord]) -> AiResult<Vec<Vec<ChordBinary>>> {
+        let start = history.len().saturating_sub(self.order - 1);
+        let suffix = history[start..]
+            .iter()
+            .map(|chord| {
+                serialize_chord(chord).map_err(|e| AiError::SuggestionFailed {
+                    reason: format!("Chord serialization failed: {}", e),
+                })
+            })
+            .collect::<AiResult<Vec<_>>>()?;
+        Ok((0..=suffix.len())
+            .map(|length| suffix[suffix.len() - length..].to_vec())
+            .collect())
+    }
+
+    /// Witten-Bell interpolation of `next` over contexts ordered shortest first
+    ///
+    /// P(w | h) = (c(h, w) + T(h) * P(w | h')) / (c(h) + T(h)), where h' is h
+    /// without its oldest chord and T(h) the number of distinct chords seen
+    /// after h. Unseen contexts pass the shorter estimate through unchanged.
+    fn interpolate(counts: &NGramCounts, contexts: &[Vec<ChordBinary>], next: ChordBinary) -> f64 {
+        let mut probability = 0.0;
+        for context in contexts {
+            let Some(seen) = counts.get(context) else {
+                continue;
+            };
+            let count = seen.next.get(&next).copied().unwrap_or(0) as f64;
+            let types = seen.next.len() as f64;
+            probability = if context.is_empty() {
+                count / seen.total as f64
+            } else {
+                (count + types * probability) / (seen.total as f64 + types)
+            };
+        }
+        probability
+    }
+
+    fn serialize_pattern(pattern: &[Chord]) -> AiResult<Vec<ChordBinary>> {
+        if pattern.is_empty() {
+            return Err(AiError::InvalidPattern {
+                reason: "Pattern cannot be empty".to_string(),
+            });
+        }
+
+        pattern
+            .iter()
+            .map(|chord| {
+                serialize_chord(chord).map_err(|e| AiError::InvalidPattern {
+                    reason: format!("Chord serialization failed: {}", e),
+                })
+            })
+            .collect()
+    }
+}
+
+impl SuggestionBackend for NGramModel {
+    /// Every chord seen in training, ranked by smoothed probability
+    fn continuations(&self, history: &[Chord], limit: usize) -> AiResult<Vec<Continuation>> {
+        let contexts = self.contexts(history)?;
+        let counts = self.counts.read();
+        let Some(vocabulary) = counts.get(&Vec::new()) else {
+            return Ok(Vec::new());
+        };
+
+        let mut ranked: Vec<(ChordBinary, f64)> = vocabulary
+            .next
+            .keys()
+            .map(|key| (*key, Self::interpolate(&counts, &contexts, *key)))
+            .collect();
+        // Ties are broken by serialized chord for reproducible rankings
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        Ok(ranked
+            .into_iter()
+            .filter_map(|(key, probability)| Some((deserialize_chord(&key).ok()?, probability)))
+            .take(limit)
+            .collect())
+    }
+
+    fn name(&self) -> &str {
+        "n-gram model"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(roots: &[u8]) -> Vec<Chord> {
+        roots
+            .iter()
+            .map(|root| Chord::triad(*root).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_order_validation() {
+        assert!(NGramModel::new(0).is_err());
+        assert!(NGramModel::new(MAX_NGRAM_ORDER + 1).is_err());
+        let model = NGramModel::new(MAX_NGRAM_ORDER).unwrap();
+        assert_eq!(model.order(), 4);
+        assert!(model.add_pattern(&[]).is_err());
+        assert!(model.continuations(&pattern(&[1]), 5).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_probabilities_are_distributions() {
+        let model = NGramModel::new(3).unwrap();
+        let report = model.add_patterns_bulk(vec![
+            (pattern(&[1, 4, 5, 1]), "a".to_string(), None),
+            (pattern(&[1, 6, 4, 5]), "b".to_string(), None),
+            (Vec::new(), "empty".to_string(), None),
+        ]);
+        assert_eq!(report.added, 2);
+        assert_eq!(report.rejected[0].index, 2);
+        assert_eq!(model.vocabulary_size(), 4);
+
+        // Seen, partly seen and unseen histories all sum to one
+        for history in [pattern(&[1, 4]), pattern(&[2, 4]), pattern(&[3]), vec![]] {
+            let continuations = model.continuations(&history, 10).unwrap();
+            let total: f64 = continuations.iter().map(|(_, p)| p).sum();
+            assert!(
+                (total - 1.0).abs() < 1e-9,
+                "{:?} sums to {}",
+                history,
+                total
+            );
+        }
+
+        // Unigram probability is the chord's share of all chords
+        let unigram = NGramModel::new(1).unwrap();
+        unigram.add_pattern(&pattern(&[1, 4, 1, 5])).unwrap();
+        let p = unigram
+            .probability(&pattern(&[5]), &Chord::triad(1).unwrap())
+            .unwrap();
+        assert!((p - 0.5).abs() < 1e-9);
+        assert_eq!(
+            unigram.probability(&[], &Chord::triad(2).unwrap()).unwrap(),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_backoff_ranks_continuations() {
+        let model = NGramModel::new(2).unwrap();
+        for _ in 0..3 {
+            model.add_pattern(&pattern(&[2, 5, 1])).unwrap();
+        }
+        model.add_pattern(&pattern(&[4, 5, 6])).unwrap();
+
+        // After V, I is three times as likely as vi
+        let continuations = model.continuations(&pattern(&[3, 5]), 2).unwrap();
+        assert_eq!(continuations.len(), 2);
+        assert_eq!(continuations[0].0, Chord::triad(1).unwrap());
+        assert_eq!(continuations[1].0, Chord::triad(6).unwrap());
+
+        // The trie has nothing for a history that never started a pattern
+        let trie = ChordProgressionTrie::new();
+        trie.add_pattern(&pattern(&[2, 5, 1]), "ii-V-I".to_string(), None)
+            .unwrap();
+        assert!(trie.continuations(&pattern(&[3, 5]), 2).unwrap().is_empty());
+        let from_start = trie.continuations(&pattern(&[2, 5]), 2).unwrap();
+        assert_eq!(from_start, vec![(Chord::triad(1).unwrap(), 1.0)]);
+    }
+}
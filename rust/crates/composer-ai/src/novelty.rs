@@ -0,0 +1,173 @@
+//! Novelty of progressions against the training corpus
+//!
+//! A progression is novel when its chord moves are ones the corpus rarely
+//! makes. Each transition is scored by the inverse of its frequency in the
+//! trie: the share of the corpus's continuations of the first chord that go to
+//! the second. The same measure ranks suggestions when
+//! [`crate::SuggestionConfig::novelty_weight`] asks for unusual continuations.
+
+use crate::error::AiResult;
+use crate::trie::ChordProgressionTrie;
+use composer_core::Chord;
+use serde::{Deserialize, Serialize};
+
+/// Novelty of the move into one chord of a progression
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TransitionNovelty {
+    /// Position of the chord moved to; the move is from the chord before it
+    pub index: usize,
+
+    /// Occurrences of the move in the pattern database
+    pub count: u32,
+
+    /// Share of the corpus's continuations of the previous chord that go to
+    /// this chord (0.0-1.0)
+    pub probability: f64,
+
+    /// How unusual the move is, from 0.0 (the only continuation the corpus
+    /// knows) to 1.0 (never seen)
+    pub novelty: f64,
+
+    /// Whether the probability is below the novelty threshold
+    pub novel: bool,
+}
+
+/// Result of [`compute_novelty`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NoveltyReport {
+    /// Each transition in progression order
+    pub transitions: Vec<TransitionNovelty>,
+
+    /// Mean novelty of the transitions; 0.0 without any
+    pub novelty: f64,
+
+    /// Whether the transitions are, on average, rarer than the novelty
+    /// threshold: their mean probability is below it
+    pub novel: bool,
+}
+
+/// Novelty of a move the corpus makes with `probability` (0.0-1.0)
+pub(crate) fn transition_novelty(probability: f64) -> f64 {
+    1.0 - probability.clamp(0.0, 1.0)
+}
+
+/// Score how statistically unusual a progression is in a trained trie.
+///
+/// A transition is `novel` when fewer than `threshold` (0.0-1.0) of the
+/// corpus's continuations of its first chord go to its second, as with
+/// [`composer_config::AnalysisParameters::novelty_threshold`]. Progressions of
+/// fewer than two chords have no transitions and no novelty.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::{compute_novelty, ChordProgressionTrie};
+/// use composer_core::Chord;
+///
+/// let trie = ChordProgressionTrie::new();
+/// let (tonic, subdominant, dominant) = (Chord::triad(1)?, Chord::triad(4)?, Chord::triad(5)?);
+/// for source in ["a", "b", "c"] {
+///     trie.add_pattern(&[tonic.clone(), dominant.clone()], source.to_string(), None)?;
+/// }
+/// trie.add_pattern(&[tonic.clone(), subdominant.clone()], "d".to_string(), None)?;
+///
+/// let common = compute_novelty(&trie, &[tonic.clone(), dominant], 0.15)?;
+/// assert_eq!(common.transitions[0].probability, 0.75);
+/// assert!(!common.novel);
+///
+/// let unheard = compute_novelty(&trie, &[tonic, Chord::triad(6)?], 0.15)?;
+/// assert_eq!(unheard.novelty, 1.0);
+/// assert!(unheard.novel);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn compute_novelty(
+    trie: &ChordProgressionTrie,
+    progression: &[Chord],
+    threshold: f64,
+) -> AiResult<NoveltyReport> {
+    let transitions = progression
+        .windows(2)
+        .enumerate()
+        .map(|(index, pair)| {
+            let pattern = [Some(pair[0].clone()), Some(pair[1].clone())];
+            let found = trie.search_with_wildcard(&pattern, 1)?;
+            let (count, probability) = found
+                .first()
+                .map_or((0, 0.0), |result| (result.count, result.relative_count));
+            Ok(TransitionNovelty {
+                index: index + 1,
+                count,
+                probability,
+                novelty: transition_novelty(probability),
+                novel: probability < threshold,
+            })
+        })
+        .collect::<AiResult<Vec<_>>>()?;
+
+    if transitions.is_empty() {
+        return Ok(NoveltyReport::default());
+    }
+    let novelty = transitions.iter().map(|t| t.novelty).sum::<f64>() / transitions.len() as f64;
+    Ok(NoveltyReport {
+        transitions,
+        novelty,
+        novel: 1.0 - novelty < threshold,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transition_scores() {
+        let trie = ChordProgressionTrie::new();
+        let (tonic, subdominant, dominant) = (
+            Chord::triad(1).unwrap(),
+            Chord::triad(4).unwrap(),
+            Chord::triad(5).unwrap(),
+        );
+        for i in 0..9 {
+            trie.add_pattern(
+                &[tonic.clone(), dominant.clone(), tonic.clone()],
+                format!("authentic-{}", i),
+                None,
+            )
+            .unwrap();
+        }
+        trie.add_pattern(
+            &[tonic.clone(), subdominant.clone()],
+            "plagal".to_string(),
+            None,
+        )
+        .unwrap();
+
+        let report = compute_novelty(
+            &trie,
+            &[tonic.clone(), subdominant.clone(), dominant.clone()],
+            0.15,
+        )
+        .unwrap();
+        assert_eq!(report.transitions.len(), 2);
+        let first = &report.transitions[0];
+        assert_eq!((first.index, first.count), (1, 1));
+        assert!((first.probability - 0.1).abs() < 1e-9);
+        assert!(first.novel);
+        // IV never moves to V in the corpus
+        let second = &report.transitions[1];
+        assert_eq!((second.count, second.novelty), (0, 1.0));
+        assert!((report.novelty - 0.95).abs() < 1e-9);
+        assert!(report.novel);
+
+        let familiar = compute_novelty(&trie, &[tonic.clone(), dominant], 0.15).unwrap();
+        assert!((familiar.novelty - 0.1).abs() < 1e-9);
+        assert!(!familiar.novel && !familiar.transitions[0].novel);
+
+        for progression in [vec![], vec![tonic]] {
+            assert_eq!(
+                compute_novelty(&trie, &progression, 0.15).unwrap(),
+                NoveltyReport::default()
+            );
+        }
+    }
+}
@@ -0,0 +1,316 @@
+//! ONNX models as neural suggestion re-rankers
+//!
+//! [`OnnxScorer`] runs a model exported from a Python training pipeline through
+//! tract, a pure-Rust inference engine, so the same model ranks suggestions in
+//! native builds and in the WASM engine. It implements [`NeuralScorer`] and is
+//! attached with [`crate::AiEngine::set_neural_scorer`] like any other scorer.
+//!
+//! The model sees chords as integer ids from a vocabulary saved alongside it,
+//! keyed by [`chord_token`]. It must take two `int64` inputs, in order:
+//!
+//! - the context, shape `[1, context_length]`: ids of the last chords of the
+//!   progression, oldest first, left-padded with `pad_id`
+//! - the candidates, shape `[1, K]`: ids of the chords to score
+//!
+//! and return one `float32` score per candidate, shape `[1, K]` or `[K]`, in
+//! `0.0..=1.0` (end the model with a sigmoid or softmax). Chords missing from the
+//! vocabulary get `unknown_id`.
+
+use crate::error::{AiError, AiResult};
+use crate::neural::NeuralScorer;
+use ahash::AHashMap;
+use composer_core::Chord;
+use composer_serialization::{chord_binary_to_hex, serialize_chord};
+use tract_onnx::prelude::*;
+
+/// Runnable tract model
+type OnnxPlan = TypedRunnableModel<TypedModel>;
+
+/// Token of a chord in ONNX vocabularies: its binary serialization in hex
+pub fn chord_token(chord: &Chord) -> AiResult<String> {
+    serialize_chord(chord)
+        .map(|binary| chord_binary_to_hex(&binary))
+        .map_err(|e| AiError::InvalidPattern {
+            reason: format!("Chord serialization failed: {}", e),
+        })
+}
+
+/// How an [`OnnxScorer`] encodes its inputs
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnnxScorerConfig {
+    /// Chords of history passed to the model
+    pub context_length: usize,
+
+    /// Id filling the context before the first chord
+    pub pad_id: i64,
+
+    /// Id of chords missing from the vocabulary
+    pub unknown_id: i64,
+
+    /// Model name used in suggestion reasoning
+    pub name: String,
+}
+
+impl Default for OnnxScorerConfig {
+    fn default() -> Self {
+        Self {
+            context_length: 8,
+            pad_id: 0,
+            unknown_id: 1,
+            name: "ONNX model".to_string(),
+        }
+    }
+}
+
+/// Suggestion re-ranker backed by an ONNX model
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use composer_ai::{chord_token, AiEngine, AiEngineConfig, OnnxScorer, OnnxScorerConfig};
+/// use composer_core::Chord;
+/// use std::sync::Arc;
+///
+/// // Written by the training pipeline
+/// let model = std::fs::read("ranker.onnx")?;
+/// let vocabulary = vec![
+///     (chord_token(&Chord::triad(1)?)?, 2),
+///     (chord_token(&Chord::triad(4)?)?, 3),
+///     (chord_token(&Chord::triad(5)?)?, 4),
+/// ];
+/// let scorer = OnnxScorer::from_bytes(&model, vocabulary, OnnxScorerConfig::default())?;
+///
+/// let engine = AiEngine::new(AiEngineConfig::default());
+/// engine.set_neural_scorer(Some(Arc::new(scorer)));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`NeuralScorer`] - The interface the scorer implements
+/// - [`crate::SuggestionConfig::neural_weight`] - Blend between trie and model scores
+pub struct OnnxScorer {
+    plan: OnnxPlan,
+    vocabulary: AHashMap<String, i64>,
+    config: OnnxScorerConfig,
+}
+
+impl std::fmt::Debug for OnnxScorer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OnnxScorer")
+            .field("vocabulary", &self.vocabulary.len())
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl OnnxScorer {
+    /// Load and optimize a serialized ONNX model.
+    ///
+    /// `vocabulary` maps [`chord_token`]s to the ids the model was trained on.
+    /// Fails with `AiError::InvalidModel` when the model cannot be parsed or
+    /// prepared for inference, or the context length is zero.
+    pub fn from_bytes(
+        model: &[u8],
+        vocabulary: impl IntoIterator<Item = (String, i64)>,
+        config: OnnxScorerConfig,
+    ) -> AiResult<Self> {
+        if config.context_length == 0 {
+            return Err(AiError::InvalidModel {
+                reason: "ONNX context length must be positive".to_string(),
+            });
+        }
+
+        let plan = tract_onnx::onnx()
+            .model_for_read(&mut std::io::Cursor::new(model))
+            .and_then(|model| model.into_optimized())
+            .and_then(|model| model.into_runnable())
+            .map_err(|e| AiError::InvalidModel {
+                reason: format!("Cannot load ONNX model: {}", e),
+            })?;
+
+        Ok(Self {
+            plan,
+            vocabulary: vocabulary.into_iter().collect(),
+            config,
+        })
+    }
+
+    /// Input encoding in use
+    pub fn config(&self) -> &OnnxScorerConfig {
+        &self.config
+    }
+
+    /// Vocabulary id of `chord`, or the unknown id
+    pub fn token_id(&self, chord: &Chord) -> i64 {
+        chord_token(chord)
+            .ok()
+            .and_then(|token| self.vocabulary.get(&token).copied())
+            .unwrap_or(self.config.unknown_id)
+    }
+
+    /// Context ids of `history`: its last chords, left-padded
+    fn context_ids(&self, history: &[Chord]) -> Vec<i64> {
+        let length = self.config.context_length;
+        let recent = &history[history.len().saturating_sub(length)..];
+        let mut ids = vec![self.config.pad_id; length - recent.len()];
+        ids.extend(recent.iter().map(|chord| self.token_id(chord)));
+        ids
+    }
+}
+
+impl NeuralScorer for OnnxScorer {
+    fn score_candidates(&self, history: &[Chord], candidates: &[Chord]) -> AiResult<Vec<f64>> {
+        if candidates.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let inference_failed = |e: TractError| AiError::SuggestionFailed {
+            reason: format!("{} inference failed: {}", self.config.name, e),
+        };
+        let context = self.context_ids(history);
+        let candidate_ids: Vec<i64> = candidates.iter().map(|c| self.token_id(c)).collect();
+        let context =
+            Tensor::from_shape(&[1, context.len()], &context).map_err(inference_failed)?;
+        let candidate_ids = Tensor::from_shape(&[1, candidate_ids.len()], &candidate_ids)
+            .map_err(inference_failed)?;
+
+        let outputs = self
+            .plan
+            .run(tvec!(context.into(), candidate_ids.into()))
+            .map_err(inference_failed)?;
+        let scores = outputs
+            .first()
+            .ok_or_else(|| AiError::SuggestionFailed {
+                reason: format!("{} returned no outputs", self.config.name),
+            })?
+            .to_array_view::<f32>()
+            .map_err(inference_failed)?;
+        Ok(scores.iter().map(|score| *score as f64).collect())
+    }
+
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message;
+    use tract_onnx::pb;
+
+    fn int64_input(
+        name: &str,
+        length: pb::tensor_shape_proto::dimension::Value,
+    ) -> pb::ValueInfoProto {
+        use pb::tensor_shape_proto::{dimension::Value, Dimension};
+        let dim = |value| Dimension {
+            value: Some(value),
+            ..Default::default()
+        };
+        pb::ValueInfoProto {
+            name: name.to_string(),
+            r#type: Some(pb::TypeProto {
+                value: Some(pb::type_proto::Value::TensorType(pb::type_proto::Tensor {
+                    elem_type: pb::tensor_proto::DataType::Int64 as i32,
+                    shape: Some(pb::TensorShapeProto {
+                        dim: vec![dim(Value::DimValue(1)), dim(length)],
+                    }),
+                })),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Model scoring each candidate as its id divided by ten
+    fn id_ranker(context_length: i64) -> Vec<u8> {
+        use pb::tensor_shape_proto::dimension::Value;
+        let node = |op_type: &str, input: &[&str], output: &str| pb::NodeProto {
+            op_type: op_type.to_string(),
+            input: input.iter().map(|name| name.to_string()).collect(),
+            output: vec![output.to_string()],
+            ..Default::default()
+        };
+        let mut cast = node("Cast", &["candidates"], "ids");
+        cast.attribute.push(pb::AttributeProto {
+            name: "to".to_string(),
+            r#type: pb::attribute_proto::AttributeType::Int as i32,
+            i: pb::tensor_proto::DataType::Float as i64,
+            ..Default::default()
+        });
+
+        let graph = pb::GraphProto {
+            name: "id_ranker".to_string(),
+            node: vec![cast, node("Div", &["ids", "ten"], "scores")],
+            initializer: vec![pb::TensorProto {
+                name: "ten".to_string(),
+                data_type: pb::tensor_proto::DataType::Float as i32,
+                float_data: vec![10.0],
+                ..Default::default()
+            }],
+            input: vec![
+                int64_input("context", Value::DimValue(context_length)),
+                int64_input("candidates", Value::DimParam("K".to_string())),
+            ],
+            output: vec![pb::ValueInfoProto {
+                name: "scores".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        pb::ModelProto {
+            ir_version: 7,
+            opset_import: vec![pb::OperatorSetIdProto {
+                domain: String::new(),
+                version: 13,
+            }],
+            graph: Some(graph),
+            ..Default::default()
+        }
+        .encode_to_vec()
+    }
+
+    #[test]
+    fn test_scores_candidates() {
+        let vocabulary = [(1, 2), (4, 5), (5, 8)]
+            .map(|(root, id)| (chord_token(&Chord::triad(root).unwrap()).unwrap(), id));
+        let config = OnnxScorerConfig {
+            context_length: 4,
+            ..Default::default()
+        };
+        let scorer = OnnxScorer::from_bytes(&id_ranker(4), vocabulary, config).unwrap();
+
+        let history = [Chord::triad(1).unwrap(), Chord::triad(4).unwrap()];
+        assert_eq!(scorer.context_ids(&history), vec![0, 0, 2, 5]);
+        let candidates = [
+            Chord::triad(5).unwrap(),
+            Chord::triad(4).unwrap(),
+            Chord::triad(2).unwrap(),
+        ];
+        let scores = scorer.score_candidates(&history, &candidates).unwrap();
+        let expected = [0.8, 0.5, 0.1];
+        assert!(scores
+            .iter()
+            .zip(expected)
+            .all(|(score, expected)| (score - expected).abs() < 1e-6));
+        assert_eq!(scorer.name(), "ONNX model");
+    }
+
+    #[test]
+    fn test_rejects_invalid_models() {
+        let result = OnnxScorer::from_bytes(b"not a model", Vec::new(), Default::default());
+        assert!(matches!(result, Err(AiError::InvalidModel { .. })));
+
+        let config = OnnxScorerConfig {
+            context_length: 0,
+            ..Default::default()
+        };
+        assert!(OnnxScorer::from_bytes(&[], Vec::new(), config).is_err());
+
+        let token = chord_token(&Chord::triad(1).unwrap()).unwrap();
+        assert_eq!(token.len(), 10);
+        assert_ne!(token, chord_token(&Chord::triad(5).unwrap()).unwrap());
+    }
+}
@@ -0,0 +1,155 @@
+//! Parallel execution control for suggestion scoring and analysis
+//!
+//! Scoring large candidate batches uses rayon when the `parallel` feature is enabled
+//! and parallel execution is switched on at runtime. Otherwise, and for batches
+//! smaller than [`PARALLEL_MIN_BATCH`], work runs sequentially on the calling thread.
+//!
+//! On `wasm32` parallel execution starts disabled: browsers only get worker threads
+//! once a pool has been initialized from JavaScript, and rayon cannot spawn threads on
+//! its own there. Native builds start enabled on rayon's default global pool.
+
+use crate::error::{AiError, AiResult};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Batches smaller than this are always scored sequentially
+pub const PARALLEL_MIN_BATCH: usize = 64;
+
+static PARALLEL_ENABLED: AtomicBool = AtomicBool::new(cfg!(not(target_arch = "wasm32")));
+
+/// Check if this build was compiled with parallel execution support
+pub fn parallel_supported() -> bool {
+    cfg!(feature = "parallel")
+}
+
+/// Enable or disable parallel execution at runtime.
+///
+/// Has no effect in builds without the `parallel` feature.
+pub fn set_parallel_enabled(enabled: bool) {
+    PARALLEL_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Check if work will be distributed across the thread pool
+pub fn is_parallel_enabled() -> bool {
+    parallel_supported() && PARALLEL_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Number of threads used for parallel work (1 when running sequentially)
+pub fn current_num_threads() -> usize {
+    #[cfg(feature = "parallel")]
+    if is_parallel_enabled() {
+        return rayon::current_num_threads();
+    }
+    1
+}
+
+/// Configures the size of the global thread pool and enables parallel execution.
+///
+/// The global pool can only be configured once, before any parallel work has run;
+/// later calls fail with [`AiError::ThreadPool`]. Browser builds size their pool from
+/// JavaScript instead (see the `threads` feature of composer-wasm).
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::{configure_thread_pool, current_num_threads, is_parallel_enabled};
+///
+/// if configure_thread_pool(2).is_ok() && is_parallel_enabled() {
+///     assert_eq!(current_num_threads(), 2);
+/// }
+/// ```
+pub fn configure_thread_pool(num_threads: usize) -> AiResult<()> {
+    if num_threads == 0 {
+        return Err(AiError::ThreadPool {
+            reason: "thread pool size must be at least 1".to_string(),
+        });
+    }
+
+    #[cfg(feature = "parallel")]
+    {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build_global()
+            .map_err(|e| AiError::ThreadPool {
+                reason: e.to_string(),
+            })?;
+        set_parallel_enabled(true);
+        Ok(())
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    Err(AiError::ThreadPool {
+        reason: "built without the `parallel` feature".to_string(),
+    })
+}
+
+/// Check if a batch of `len` items should be processed in parallel
+#[cfg(feature = "parallel")]
+fn use_parallel(len: usize) -> bool {
+    len >= PARALLEL_MIN_BATCH && is_parallel_enabled()
+}
+
+/// Filter-map a slice, preserving order
+pub(crate) fn filter_map_collect<T, U, F>(items: &[T], f: F) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&T) -> Option<U> + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    if use_parallel(items.len()) {
+        return items.par_iter().filter_map(f).collect();
+    }
+    items.iter().filter_map(f).collect()
+}
+
+/// Sum a mapped slice
+pub(crate) fn map_sum<T, F>(items: &[T], f: F) -> f64
+where
+    T: Sync,
+    F: Fn(&T) -> f64 + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    if use_parallel(items.len()) {
+        return items.par_iter().map(f).sum();
+    }
+    items.iter().map(f).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequential_and_parallel_paths_agree() {
+        let items: Vec<u32> = (0..500).collect();
+        let even = |x: &u32| (x % 2 == 0).then_some(*x * 3);
+
+        let was_enabled = PARALLEL_ENABLED.load(Ordering::Relaxed);
+
+        set_parallel_enabled(false);
+        assert_eq!(current_num_threads(), 1);
+        let sequential = filter_map_collect(&items, even);
+        let sequential_sum = map_sum(&items, |x| *x as f64);
+
+        set_parallel_enabled(true);
+        let parallel = filter_map_collect(&items, even);
+        let parallel_sum = map_sum(&items, |x| *x as f64);
+
+        set_parallel_enabled(was_enabled);
+
+        assert_eq!(sequential, parallel);
+        assert_eq!(sequential.len(), 250);
+        assert_eq!(sequential_sum, parallel_sum);
+    }
+
+    #[test]
+    fn test_zero_threads_rejected() {
+        assert!(matches!(
+            configure_thread_pool(0),
+            Err(AiError::ThreadPool { .. })
+        ));
+    }
+}
@@ -0,0 +1,567 @@
+//! Practice plans and difficulty-targeted rewrites
+//!
+//! Splits a progression into short segments, assesses each one on its own and
+//! ranks them hardest first, so practice starts where the difficulty is. For
+//! segments above the player's level, chords are simplified to what the level
+//! plays comfortably (e.g. V13#11 becomes V7 for an intermediate player), and
+//! practice time grows with how far a segment is above the level.
+//!
+//! The same reductions, and their counterpart of idiomatic extensions, rewrite
+//! whole progressions toward a target level. Rewrites never change a chord's
+//! harmonic function, and the analyzer re-assesses the result after each
+//! changed chord so no more chords change than needed.
+
+use crate::analysis::{MusicalAnalyzer, SkillLevel};
+use crate::error::{AiError, AiResult};
+use composer_core::{harmonic_function, Chord, ScaleFingerprint};
+use serde::{Deserialize, Serialize};
+
+/// Chords per practice segment, about one four-bar phrase
+const SEGMENT_CHORDS: usize = 4;
+
+/// A trailing segment shorter than this joins the segment before it
+const MIN_SEGMENT_CHORDS: usize = 2;
+
+/// Practice minutes per chord of a segment within the player's level
+const MINUTES_PER_CHORD: f64 = 2.0;
+
+/// Per-chord rewrite toward a skill level, such as [`simplify_chord`]
+type ChordRewrite = fn(&Chord, SkillLevel) -> Chord;
+
+/// Ranked practice segments with simplifications and a time estimate
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PracticePlan {
+    /// Level the plan was made for
+    pub player_level: SkillLevel,
+
+    /// Overall difficulty score of the whole progression
+    pub overall_score: f64,
+
+    /// Segments ranked hardest first
+    pub segments: Vec<PracticeSegment>,
+
+    /// The progression with every suggested substitution applied
+    pub simplified_progression: Vec<Chord>,
+
+    /// Estimated practice time over all segments, in minutes
+    pub estimated_minutes: f64,
+}
+
+/// Consecutive chords practiced together
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PracticeSegment {
+    /// Range of chord indices in the progression, end exclusive
+    pub chord_range: (usize, usize),
+
+    /// Overall difficulty score of the segment on its own
+    pub difficulty: f64,
+
+    /// Skill level the segment is assessed at
+    pub skill_level: SkillLevel,
+
+    /// Whether the segment is assessed above the player's level
+    pub above_level: bool,
+
+    /// Simpler chords to learn the segment with; empty within the player's level
+    pub substitutions: Vec<ChordSubstitution>,
+
+    /// Estimated practice time for the segment, in minutes
+    pub estimated_minutes: f64,
+}
+
+/// Simpler chord suggested in place of one above the player's level
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChordSubstitution {
+    /// Position of the chord in the progression
+    pub index: usize,
+
+    /// The chord as written
+    pub original: Chord,
+
+    /// The chord reduced to the player's level
+    pub simplified: Chord,
+
+    /// Drop in single-chord complexity from the substitution
+    pub complexity_reduction: f64,
+}
+
+/// Progression rewritten toward a target skill level
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProgressionRewrite {
+    /// The rewritten progression
+    pub progression: Vec<Chord>,
+
+    /// Indices of the chords that were rewritten, in order
+    pub changed: Vec<usize>,
+
+    /// Overall difficulty score of the original progression
+    pub original_score: f64,
+
+    /// Overall difficulty score of the rewritten progression
+    pub score: f64,
+
+    /// Skill level the rewritten progression is assessed at
+    pub skill_level: SkillLevel,
+
+    /// Whether the rewritten progression is assessed at the target level or
+    /// beyond it in the direction of the rewrite
+    pub reached_target: bool,
+}
+
+impl MusicalAnalyzer {
+    /// Plan practice of a progression for a player at `player_level`.
+    ///
+    /// The progression is split into segments of four chords (a trailing
+    /// single chord joins the segment before it), each assessed with
+    /// [`Self::assess_difficulty`] and ranked by overall score, hardest first.
+    /// Chords of segments assessed above `player_level` get simpler
+    /// substitutes: triads without additions or inversions for beginners,
+    /// unaltered chords up to sevenths for intermediate players and chords up
+    /// to ninths for advanced players. Experts get no substitutions.
+    ///
+    /// Each segment takes two minutes per chord, multiplied by one plus the
+    /// number of score points the segment lies above the player's level.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{ChordProgressionTrie, MusicalAnalyzer, SkillLevel};
+    /// use composer_core::Chord;
+    /// use std::sync::Arc;
+    ///
+    /// let analyzer = MusicalAnalyzer::new(Arc::new(ChordProgressionTrie::new()));
+    /// let dominant = Chord::new(5, 13)?.with_alteration("#11")?;
+    /// let progression = vec![Chord::new(2, 7)?, dominant, Chord::new(1, 7)?];
+    ///
+    /// let plan = analyzer.generate_practice_plan(&progression, SkillLevel::Beginner)?;
+    /// assert_eq!(plan.segments.len(), 1);
+    /// assert_eq!(plan.simplified_progression[1], Chord::triad(5)?);
+    /// assert!(plan.estimated_minutes >= 6.0);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`Self::assess_difficulty`] - Difficulty of the whole progression
+    /// - [`simplify_chord`] - The reduction applied to above-level chords
+    pub fn generate_practice_plan(
+        &self,
+        progression: &[Chord],
+        player_level: SkillLevel,
+    ) -> AiResult<PracticePlan> {
+        if progression.is_empty() {
+            return Err(AiError::AnalysisFailed {
+                reason: "Cannot plan practice of an empty progression".to_string(),
+            });
+        }
+
+        let overall_score = self
+            .assess_difficulty(progression, None, None)?
+            .overall_score;
+        let level_ceiling = self
+            .skill_thresholds()
+            .bands()
+            .iter()
+            .find(|band| band.level.rank() > player_level.rank())
+            .map_or(10.0, |band| band.min_score);
+
+        let mut simplified_progression = progression.to_vec();
+        let mut segments = Vec::new();
+        for (start, end) in segment_ranges(progression.len()) {
+            let assessment = self.assess_difficulty(&progression[start..end], None, None)?;
+            let above_level = assessment.skill_level.rank() > player_level.rank();
+
+            let mut substitutions = Vec::new();
+            if above_level {
+                for (index, chord) in progression.iter().enumerate().take(end).skip(start) {
+                    let simplified = simplify_chord(chord, player_level);
+                    let complexity_reduction = self.calculate_single_chord_complexity(chord)
+                        - self.calculate_single_chord_complexity(&simplified);
+                    if complexity_reduction > 0.0 {
+                        simplified_progression[index] = simplified.clone();
+                        substitutions.push(ChordSubstitution {
+                            index,
+                            original: chord.clone(),
+                            simplified,
+                            complexity_reduction,
+                        });
+                    }
+                }
+            }
+
+            let gap = (assessment.overall_score - level_ceiling).max(0.0);
+            segments.push(PracticeSegment {
+                chord_range: (start, end),
+                difficulty: assessment.overall_score,
+                skill_level: assessment.skill_level,
+                above_level,
+                substitutions,
+                estimated_minutes: (end - start) as f64 * MINUTES_PER_CHORD * (1.0 + gap),
+            });
+        }
+        segments.sort_by(|a, b| {
+            b.difficulty
+                .total_cmp(&a.difficulty)
+                .then_with(|| a.chord_range.cmp(&b.chord_range))
+        });
+
+        Ok(PracticePlan {
+            player_level,
+            overall_score,
+            estimated_minutes: segments.iter().map(|s| s.estimated_minutes).sum(),
+            segments,
+            simplified_progression,
+        })
+    }
+}
+
+impl MusicalAnalyzer {
+    /// Simplify a progression until it is assessed at `target_level` or below.
+    ///
+    /// Chords are reduced with [`simplify_chord`] one at a time, the largest
+    /// complexity reduction first, and the progression is re-assessed after
+    /// each; chords whose reduction would change their harmonic function in the
+    /// major key are kept. When every reduction still leaves the progression
+    /// above the target, all of them are applied and
+    /// [`ProgressionRewrite::reached_target`] is false.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{ChordProgressionTrie, MusicalAnalyzer, SkillLevel};
+    /// use composer_core::Chord;
+    /// use std::sync::Arc;
+    ///
+    /// let analyzer = MusicalAnalyzer::new(Arc::new(ChordProgressionTrie::new()));
+    /// let progression = vec![Chord::new(2, 11)?, Chord::new(5, 13)?, Chord::new(1, 9)?];
+    ///
+    /// let rewrite = analyzer.simplify_progression(&progression, SkillLevel::Beginner)?;
+    /// assert!(rewrite.score < rewrite.original_score);
+    /// assert!(rewrite.progression.iter().all(|chord| chord.chord_type <= 7));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`Self::enrich_progression`] - Rewrite toward a harder level
+    /// - [`simplify_chord`] - The reduction applied to each chord
+    pub fn simplify_progression(
+        &self,
+        progression: &[Chord],
+        target_level: SkillLevel,
+    ) -> AiResult<ProgressionRewrite> {
+        self.rewrite_toward(progression, target_level, simplify_chord, |level| {
+            level.rank() <= target_level.rank()
+        })
+    }
+
+    /// Enrich a progression until it is assessed at `target_level` or above.
+    ///
+    /// Chords gain the extensions of [`enrich_chord`] one at a time, the
+    /// largest complexity increase first, re-assessing after each, and never in
+    /// a way that changes their harmonic function in the major key. When every
+    /// enrichment still leaves the progression below the target, all of them
+    /// are applied and [`ProgressionRewrite::reached_target`] is false.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{ChordProgressionTrie, MusicalAnalyzer, SkillLevel};
+    /// use composer_core::Chord;
+    /// use std::sync::Arc;
+    ///
+    /// let analyzer = MusicalAnalyzer::new(Arc::new(ChordProgressionTrie::new()));
+    /// let progression: Vec<Chord> = [2, 5, 1, 1]
+    ///     .iter()
+    ///     .map(|&root| Chord::triad(root))
+    ///     .collect::<Result<_, _>>()?;
+    ///
+    /// let rewrite = analyzer.enrich_progression(&progression, SkillLevel::Expert)?;
+    /// assert!(rewrite.score > rewrite.original_score);
+    /// assert_eq!(rewrite.progression[1].chord_type, 13);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`Self::simplify_progression`] - Rewrite toward an easier level
+    /// - [`enrich_chord`] - The extensions added to each chord
+    pub fn enrich_progression(
+        &self,
+        progression: &[Chord],
+        target_level: SkillLevel,
+    ) -> AiResult<ProgressionRewrite> {
+        self.rewrite_toward(progression, target_level, enrich_chord, |level| {
+            level.rank() >= target_level.rank()
+        })
+    }
+
+    /// Apply `rewrite` chord by chord, largest complexity change first, until
+    /// the assessed level satisfies `reached`
+    fn rewrite_toward(
+        &self,
+        progression: &[Chord],
+        target_level: SkillLevel,
+        rewrite: ChordRewrite,
+        reached: impl Fn(SkillLevel) -> bool,
+    ) -> AiResult<ProgressionRewrite> {
+        if progression.is_empty() {
+            return Err(AiError::AnalysisFailed {
+                reason: "Cannot rewrite an empty progression".to_string(),
+            });
+        }
+
+        let scale = ScaleFingerprint::major_scale();
+        let function = |chord: &Chord| {
+            harmonic_function(chord, &scale).map_err(|e| AiError::AnalysisFailed {
+                reason: format!("Cannot determine harmonic function: {}", e),
+            })
+        };
+
+        let mut candidates = Vec::new();
+        for (index, chord) in progression.iter().enumerate() {
+            let rewritten = rewrite(chord, target_level);
+            let change = (self.calculate_single_chord_complexity(&rewritten)
+                - self.calculate_single_chord_complexity(chord))
+            .abs();
+            if change > 0.0 && function(&rewritten)? == function(chord)? {
+                candidates.push((index, rewritten, change));
+            }
+        }
+        candidates.sort_by(|a, b| b.2.total_cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+
+        let original = self.assess_difficulty(progression, None, None)?;
+        let mut assessment = original.clone();
+        let mut rewritten = progression.to_vec();
+        let mut changed = Vec::new();
+        for (index, chord, _) in candidates {
+            if reached(assessment.skill_level) {
+                break;
+            }
+            rewritten[index] = chord;
+            changed.push(index);
+            assessment = self.assess_difficulty(&rewritten, None, None)?;
+        }
+        changed.sort_unstable();
+
+        Ok(ProgressionRewrite {
+            progression: rewritten,
+            changed,
+            original_score: original.overall_score,
+            score: assessment.overall_score,
+            skill_level: assessment.skill_level,
+            reached_target: reached(assessment.skill_level),
+        })
+    }
+}
+
+/// Chord index ranges of the practice segments of a progression
+fn segment_ranges(len: usize) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<(usize, usize)> = (0..len)
+        .step_by(SEGMENT_CHORDS)
+        .map(|start| (start, (start + SEGMENT_CHORDS).min(len)))
+        .collect();
+    if let [.., previous, (start, end)] = ranges.as_mut_slice() {
+        if *end - *start < MIN_SEGMENT_CHORDS {
+            previous.1 = *end;
+            ranges.pop();
+        }
+    }
+    ranges
+}
+
+/// Reduce a chord to what a player at `level` plays comfortably
+///
+/// Beginners get the plain root-position triad, keeping suspensions, applied
+/// targets and borrowed scales. Intermediate players keep sevenths and
+/// inversions but lose extensions, alterations and added tones; advanced
+/// players keep everything up to the ninth. Experts and rests are unchanged.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::{simplify_chord, SkillLevel};
+/// use composer_core::Chord;
+///
+/// let dominant = Chord::new(5, 13)?.with_alteration("#11")?;
+/// assert_eq!(simplify_chord(&dominant, SkillLevel::Intermediate), Chord::seventh(5)?);
+/// assert_eq!(simplify_chord(&dominant, SkillLevel::Expert), dominant);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn simplify_chord(chord: &Chord, level: SkillLevel) -> Chord {
+    let mut simplified = chord.clone();
+    if chord.is_rest {
+        return simplified;
+    }
+    match level {
+        SkillLevel::Beginner => {
+            simplified.chord_type = 5;
+            simplified.inversion = 0;
+            simplified.alterations.clear();
+            simplified.adds.clear();
+            simplified.omits.clear();
+        },
+        SkillLevel::Intermediate => {
+            simplified.chord_type = simplified.chord_type.min(7);
+            simplified.alterations.clear();
+            simplified.adds.clear();
+        },
+        SkillLevel::Advanced => {
+            simplified.chord_type = simplified.chord_type.min(9);
+        },
+        SkillLevel::Expert => {},
+    }
+    if simplified.chord_type == 5 {
+        // Third inversion needs a seventh
+        simplified.inversion = simplified.inversion.min(2);
+    }
+    simplified
+}
+
+/// Extend a chord the way a player at `level` would voice it
+///
+/// Beginners keep their chords. Intermediate players play diatonic sevenths;
+/// advanced players add ninths, except on iii and vii where the ninth clashes
+/// with the key. Experts play the dominant as a thirteenth, ii and vi as
+/// elevenths, I and IV as ninths with the lydian ♯11 on IV, and applied
+/// dominants as 7♭9. Chords are only ever extended, never reduced, and rests
+/// are unchanged.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::{enrich_chord, SkillLevel};
+/// use composer_core::Chord;
+///
+/// assert_eq!(enrich_chord(&Chord::triad(5)?, SkillLevel::Intermediate), Chord::seventh(5)?);
+/// assert_eq!(enrich_chord(&Chord::triad(3)?, SkillLevel::Advanced).chord_type, 7);
+/// assert_eq!(enrich_chord(&Chord::triad(5)?, SkillLevel::Expert).chord_type, 13);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn enrich_chord(chord: &Chord, level: SkillLevel) -> Chord {
+    let mut enriched = chord.clone();
+    if chord.is_rest {
+        return enriched;
+    }
+    let chord_type = match (level, chord.root) {
+        (SkillLevel::Beginner, _) => 5,
+        (SkillLevel::Intermediate, _) | (_, 3 | 7) => 7,
+        (SkillLevel::Expert, _) if chord.applied != 0 => 7,
+        (SkillLevel::Advanced, _) | (SkillLevel::Expert, 1 | 4) => 9,
+        (SkillLevel::Expert, 2 | 6) => 11,
+        (SkillLevel::Expert, _) => 13,
+    };
+    enriched.chord_type = enriched.chord_type.max(chord_type);
+
+    if level == SkillLevel::Expert && enriched.alterations.is_empty() {
+        let alteration = match (chord.applied, chord.root) {
+            (0, 4) => Some("#11"),
+            (0, _) => None,
+            (_, 5) => Some("b9"),
+            _ => None,
+        };
+        if let Some(alteration) = alteration {
+            enriched.alterations.push(alteration.to_string());
+        }
+    }
+    enriched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trie::ChordProgressionTrie;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_progression_rewrites() {
+        let analyzer = MusicalAnalyzer::new(Arc::new(ChordProgressionTrie::new()));
+        let scale = ScaleFingerprint::major_scale();
+        let progression: Vec<Chord> = [1, 6, 2, 5, 3, 7, 4, 1]
+            .iter()
+            .map(|&root| Chord::triad(root).unwrap())
+            .collect();
+
+        let enriched = analyzer
+            .enrich_progression(&progression, SkillLevel::Expert)
+            .unwrap();
+        assert!(!enriched.changed.is_empty());
+        assert!(enriched.score > enriched.original_score);
+        for (original, rewritten) in progression.iter().zip(&enriched.progression) {
+            assert_eq!(
+                harmonic_function(original, &scale).unwrap(),
+                harmonic_function(rewritten, &scale).unwrap()
+            );
+        }
+        let unchanged: Vec<usize> = (0..progression.len())
+            .filter(|index| !enriched.changed.contains(index))
+            .collect();
+        for index in unchanged {
+            assert_eq!(enriched.progression[index], progression[index]);
+        }
+
+        let simplified = analyzer
+            .simplify_progression(&enriched.progression, SkillLevel::Beginner)
+            .unwrap();
+        assert!(simplified.score < enriched.score);
+        assert!(!simplified.changed.is_empty());
+
+        // Targets already met leave the progression alone
+        let same = analyzer
+            .enrich_progression(&progression, SkillLevel::Beginner)
+            .unwrap();
+        assert!(same.changed.is_empty() && same.reached_target);
+        assert_eq!(same.progression, progression);
+        assert!(analyzer
+            .simplify_progression(&[], SkillLevel::Beginner)
+            .is_err());
+    }
+
+    #[test]
+    fn test_segment_ranges() {
+        assert_eq!(segment_ranges(3), vec![(0, 3)]);
+        assert_eq!(segment_ranges(8), vec![(0, 4), (4, 8)]);
+        assert_eq!(segment_ranges(9), vec![(0, 4), (4, 9)]);
+        assert_eq!(segment_ranges(10), vec![(0, 4), (4, 8), (8, 10)]);
+    }
+
+    #[test]
+    fn test_practice_plan() {
+        let analyzer = MusicalAnalyzer::new(Arc::new(ChordProgressionTrie::new()));
+        let dominant = Chord::new(5, 13).unwrap().with_alteration("#11").unwrap();
+        let mut progression: Vec<Chord> = [1, 4, 5, 1]
+            .iter()
+            .map(|&root| Chord::triad(root).unwrap())
+            .collect();
+        progression.extend([
+            Chord::new(2, 9).unwrap(),
+            dominant,
+            Chord::new(1, 11).unwrap(),
+        ]);
+
+        let plan = analyzer
+            .generate_practice_plan(&progression, SkillLevel::Beginner)
+            .unwrap();
+        assert_eq!(plan.segments.len(), 2);
+        assert_eq!(plan.segments[0].chord_range, (4, 7));
+        assert!(plan.segments[0].difficulty >= plan.segments[1].difficulty);
+        assert!(plan.segments[1].substitutions.is_empty());
+        assert_eq!(plan.simplified_progression[..4], progression[..4]);
+        let total: f64 = plan.segments.iter().map(|s| s.estimated_minutes).sum();
+        assert!((plan.estimated_minutes - total).abs() < 1e-9);
+
+        assert!(plan.segments[0].above_level);
+        assert_eq!(plan.segments[0].substitutions.len(), 3);
+        assert_eq!(plan.simplified_progression[5], Chord::triad(5).unwrap());
+        assert!(plan.segments[0].estimated_minutes > 3.0 * MINUTES_PER_CHORD);
+
+        let expert = analyzer
+            .generate_practice_plan(&progression, SkillLevel::Expert)
+            .unwrap();
+        assert!(expert.segments.iter().all(|s| s.substitutions.is_empty()));
+        assert_eq!(expert.simplified_progression, progression);
+        assert!(analyzer
+            .generate_practice_plan(&[], SkillLevel::Beginner)
+            .is_err());
+    }
+}
@@ -0,0 +1,15 @@
+//! Common types of the Composer crates in one import
+//!
+//! `use composer_ai::prelude::*;` brings in chords and scales from
+//! `composer-core`, timelines and notes from `composer-serialization`, and the
+//! engine, its results and the [`Composer`] entry point from this crate, so
+//! basic tasks need no knowledge of how the crates are split.
+
+pub use crate::accompaniment::{AccompanimentOptions, AccompanimentStyle};
+pub use crate::analysis::{DifficultyAssessment, ProgressionAnalysis, SkillLevel};
+pub use crate::engine::{AiEngine, AiEngineConfig, TrainingPattern};
+pub use crate::error::{AiError, AiResult};
+pub use crate::facade::{Composer, ProgressionReport};
+pub use crate::suggestions::{ChordSuggestion, SuggestionConfig, SuggestionContext};
+pub use composer_core::{Chord, ChordTheoryError, ChordTheoryResult, ScaleFingerprint};
+pub use composer_serialization::{ClusterNote, Note, Timeline, TokenEvent, TokenEventType};
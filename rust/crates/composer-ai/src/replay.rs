@@ -0,0 +1,612 @@
+//! Replay-based regression harness
+//!
+//! Re-executes the queries of a recorded [`Session`] against an engine (typically a
+//! newer build) and diffs the results against what was originally recorded. The
+//! resulting [`ReplayReport`] quantifies how suggestion rankings, suggestion scores,
+//! and difficulty assessments changed between versions.
+
+use crate::analysis::DifficultyAssessment;
+use crate::engine::AiEngine;
+use crate::error::{AiError, AiResult};
+use crate::session::{Session, SessionOutcome, SessionQuery};
+use crate::suggestions::ChordSuggestion;
+use composer_config::APPLICATION;
+use composer_core::{Mode, PitchClass};
+use serde::{Deserialize, Serialize};
+
+/// Options controlling how replayed results are compared
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayOptions {
+    /// Score differences at or below this value are treated as unchanged
+    pub score_tolerance: f64,
+
+    /// Only compare the top N suggestions of each ranking (`None` compares all)
+    pub compare_top_n: Option<usize>,
+}
+
+impl Default for ReplayOptions {
+    fn default() -> Self {
+        Self {
+            score_tolerance: 1e-6,
+            compare_top_n: None,
+        }
+    }
+}
+
+/// How a replayed interaction compares to its recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReplayStatus {
+    /// Same result within tolerance
+    Unchanged,
+
+    /// Both runs succeeded but results differ
+    Changed,
+
+    /// The recorded query succeeded but the replay failed
+    NewError,
+
+    /// The recorded query failed but the replay succeeded
+    Fixed,
+
+    /// Both runs failed
+    BothFailed,
+}
+
+/// Difference between a recorded and a replayed suggestion ranking
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankingDiff {
+    /// Recorded ranking (chord symbols, best first)
+    pub recorded: Vec<String>,
+
+    /// Replayed ranking (chord symbols, best first)
+    pub replayed: Vec<String>,
+
+    /// Whether the top suggestion changed
+    pub top_changed: bool,
+
+    /// Fraction of suggestions present in both rankings (0.0-1.0)
+    pub overlap: f64,
+
+    /// Sum of absolute rank displacements of suggestions present in both rankings
+    pub rank_displacement: usize,
+
+    /// Largest absolute weighted score change among shared suggestions
+    pub max_score_delta: f64,
+
+    /// Mean absolute weighted score change among shared suggestions
+    pub mean_score_delta: f64,
+}
+
+/// Difference between a recorded and a replayed difficulty assessment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DifficultyDiff {
+    /// Change in overall score (replayed - recorded)
+    pub overall_delta: f64,
+
+    /// Change in harmonic complexity
+    pub harmonic_delta: f64,
+
+    /// Change in rhythmic complexity
+    pub rhythmic_delta: f64,
+
+    /// Change in technical complexity
+    pub technical_delta: f64,
+
+    /// Change in melodic complexity
+    pub melodic_delta: f64,
+
+    /// Whether the skill level classification changed
+    pub skill_level_changed: bool,
+}
+
+/// Comparison result for a single replayed interaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InteractionDiff {
+    /// Id of the recorded interaction
+    pub interaction_id: u64,
+
+    /// Engine operation that was replayed
+    pub operation: String,
+
+    /// Overall comparison status
+    pub status: ReplayStatus,
+
+    /// Ranking comparison for suggestion queries
+    pub ranking: Option<RankingDiff>,
+
+    /// Score comparison for difficulty queries
+    pub difficulty: Option<DifficultyDiff>,
+
+    /// Error message from the replay, if it failed
+    pub replay_error: Option<String>,
+}
+
+/// Aggregate statistics over a replay
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplaySummary {
+    /// Interactions replayed
+    pub total: usize,
+
+    /// Interactions with unchanged results
+    pub unchanged: usize,
+
+    /// Interactions with changed results
+    pub changed: usize,
+
+    /// Interactions that newly fail
+    pub new_errors: usize,
+
+    /// Interactions that no longer fail
+    pub fixed: usize,
+
+    /// Interactions that failed in both runs
+    pub both_failed: usize,
+
+    /// Suggestion queries whose top suggestion changed
+    pub top_changes: usize,
+
+    /// Mean ranking overlap across suggestion queries (1.0 if there were none)
+    pub mean_overlap: f64,
+
+    /// Largest score change observed across all interactions
+    pub max_score_delta: f64,
+}
+
+/// Full report produced by [`replay_session`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayReport {
+    /// Version that recorded the session
+    pub recorded_version: String,
+
+    /// Version that replayed the session
+    pub replayed_version: String,
+
+    /// Per-interaction comparisons, in recording order
+    pub interactions: Vec<InteractionDiff>,
+
+    /// Aggregate statistics
+    pub summary: ReplaySummary,
+}
+
+impl ReplayReport {
+    /// Check if every replayed interaction matched its recording
+    pub fn is_unchanged(&self) -> bool {
+        self.summary.unchanged + self.summary.both_failed == self.summary.total
+    }
+
+    /// Interactions whose results differ from the recording
+    pub fn regressions(&self) -> Vec<&InteractionDiff> {
+        self.interactions
+            .iter()
+            .filter(|d| matches!(d.status, ReplayStatus::Changed | ReplayStatus::NewError))
+            .collect()
+    }
+}
+
+impl SessionQuery {
+    /// Run this query against an engine and capture the outcome
+    pub fn execute(&self, engine: &AiEngine) -> SessionOutcome {
+        let result: AiResult<SessionOutcome> = match self {
+            SessionQuery::ChordSuggestions {
+                pattern,
+                context,
+                config,
+            } => engine
+                .get_chord_suggestions(pattern, context, config)
+                .map(Into::into),
+            SessionQuery::MagicChordSolutions {
+                previous_chords,
+                following_chords,
+                scale,
+                limit,
+            } => parse_mode(scale)
+                .and_then(|mode| {
+                    engine.get_magic_chord_solutions(
+                        previous_chords,
+                        following_chords,
+                        mode,
+                        *limit,
+                    )
+                })
+                .map(Into::into),
+            SessionQuery::MagicBassSolutions {
+                bass_note,
+                scale,
+                limit,
+            } => parse_bass_note(bass_note)
+                .and_then(|bass| engine.get_magic_bass_solutions(bass, parse_mode(scale)?, *limit))
+                .map(Into::into),
+            SessionQuery::HarmonizeBySdSolutions {
+                scale_degree_bits,
+                scale,
+                limit,
+                config,
+            } => parse_mode(scale)
+                .and_then(|mode| {
+                    engine.get_harmonize_by_sd_solutions_with_config(
+                        *scale_degree_bits,
+                        mode,
+                        *limit,
+                        config,
+                    )
+                })
+                .map(Into::into),
+            SessionQuery::AssessDifficulty {
+                progression,
+                tempo_bpm,
+                time_signature,
+                instrument,
+            } => match instrument {
+                Some(profile) => engine.assess_instrument_difficulty(
+                    progression,
+                    *tempo_bpm,
+                    *time_signature,
+                    *profile,
+                ),
+                None => engine.assess_difficulty(progression, *tempo_bpm, *time_signature),
+            }
+            .map(Into::into),
+            SessionQuery::HarmonizeBassLine {
+                progression,
+                options,
+            } => engine
+                .harmonize_bass_line(progression, options)
+                .map(Into::into),
+        };
+
+        result.unwrap_or_else(|e| SessionOutcome::Error(e.to_string()))
+    }
+}
+
+/// Mode recorded by name in a session query
+fn parse_mode(scale: &str) -> AiResult<Mode> {
+    scale.parse().map_err(|_| AiError::InvalidPattern {
+        reason: format!("Unknown scale: {}", scale),
+    })
+}
+
+/// Bass note recorded by name in a session query
+fn parse_bass_note(bass_note: &str) -> AiResult<PitchClass> {
+    bass_note.parse().map_err(|_| AiError::InvalidPattern {
+        reason: format!("Invalid bass note: {}", bass_note),
+    })
+}
+
+/// Replays a recorded session against an engine and diffs the results.
+///
+/// Every recorded query is executed in order against `engine`, and its outcome is
+/// compared with the recorded outcome. Suggestion queries are compared by ranking
+/// (order of suggested chords) and by weighted score; difficulty queries by each
+/// complexity score and the skill level; bass line queries by the generated notes.
+///
+/// The engine must be initialized, ideally with the same training data that was
+/// loaded when the session was recorded, so that differences reflect changes in
+/// engine behavior rather than in data.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::{replay_session, AiEngine, AiEngineConfig, ReplayOptions};
+/// use composer_core::Chord;
+///
+/// let engine = AiEngine::new(AiEngineConfig::default());
+/// engine.initialize(vec![])?;
+///
+/// engine.start_session_recording();
+/// engine.assess_difficulty(&[Chord::triad(1)?, Chord::triad(5)?], None, None)?;
+/// let session = engine.stop_session_recording().unwrap();
+///
+/// let report = replay_session(&engine, &session, &ReplayOptions::default());
+/// assert!(report.is_unchanged());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`AiEngine::start_session_recording`] - Record a session to replay
+/// - [`Session::load`] - Load a recorded session file
+pub fn replay_session(
+    engine: &AiEngine,
+    session: &Session,
+    options: &ReplayOptions,
+) -> ReplayReport {
+    let interactions: Vec<InteractionDiff> = session
+        .interactions
+        .iter()
+        .map(|interaction| {
+            let replayed = interaction.query.execute(engine);
+            compare_outcomes(
+                interaction.id,
+                interaction.query.operation(),
+                &interaction.outcome,
+                &replayed,
+                options,
+            )
+        })
+        .collect();
+
+    let summary = summarize(&interactions);
+
+    ReplayReport {
+        recorded_version: session.engine_version.clone(),
+        replayed_version: APPLICATION.version.to_string(),
+        interactions,
+        summary,
+    }
+}
+
+/// Compare a recorded outcome with a replayed one
+fn compare_outcomes(
+    interaction_id: u64,
+    operation: &str,
+    recorded: &SessionOutcome,
+    replayed: &SessionOutcome,
+    options: &ReplayOptions,
+) -> InteractionDiff {
+    let mut diff = InteractionDiff {
+        interaction_id,
+        operation: operation.to_string(),
+        status: ReplayStatus::Unchanged,
+        ranking: None,
+        difficulty: None,
+        replay_error: None,
+    };
+
+    match (recorded, replayed) {
+        (SessionOutcome::Error(_), SessionOutcome::Error(e)) => {
+            diff.status = ReplayStatus::BothFailed;
+            diff.replay_error = Some(e.clone());
+        },
+        (_, SessionOutcome::Error(e)) => {
+            diff.status = ReplayStatus::NewError;
+            diff.replay_error = Some(e.clone());
+        },
+        (SessionOutcome::Error(_), _) => diff.status = ReplayStatus::Fixed,
+        (SessionOutcome::Suggestions(before), SessionOutcome::Suggestions(after)) => {
+            let ranking = diff_rankings(before, after, options);
+            let changed = ranking.recorded != ranking.replayed
+                || ranking.max_score_delta > options.score_tolerance;
+            if changed {
+                diff.status = ReplayStatus::Changed;
+            }
+            diff.ranking = Some(ranking);
+        },
+        (SessionOutcome::Difficulty(before), SessionOutcome::Difficulty(after)) => {
+            let difficulty = diff_difficulty(before, after);
+            let max_delta = [
+                difficulty.overall_delta,
+                difficulty.harmonic_delta,
+                difficulty.rhythmic_delta,
+                difficulty.technical_delta,
+                difficulty.melodic_delta,
+            ]
+            .iter()
+            .fold(0.0_f64, |acc, d| acc.max(d.abs()));
+            if difficulty.skill_level_changed || max_delta > options.score_tolerance {
+                diff.status = ReplayStatus::Changed;
+            }
+            diff.difficulty = Some(difficulty);
+        },
+        (SessionOutcome::BassLine(before), SessionOutcome::BassLine(after)) => {
+            if before.bass_notes != after.bass_notes
+                || before.rhythm != after.rhythm
+                || (before.confidence - after.confidence).abs() > options.score_tolerance
+            {
+                diff.status = ReplayStatus::Changed;
+            }
+        },
+        _ => diff.status = ReplayStatus::Changed,
+    }
+
+    diff
+}
+
+/// Compare two suggestion rankings
+fn diff_rankings(
+    before: &[ChordSuggestion],
+    after: &[ChordSuggestion],
+    options: &ReplayOptions,
+) -> RankingDiff {
+    let limit = options.compare_top_n.unwrap_or(usize::MAX);
+    let before = &before[..before.len().min(limit)];
+    let after = &after[..after.len().min(limit)];
+
+    let recorded: Vec<String> = before.iter().map(|s| s.chord.to_string()).collect();
+    let replayed: Vec<String> = after.iter().map(|s| s.chord.to_string()).collect();
+
+    let mut shared = 0;
+    let mut rank_displacement = 0;
+    let mut total_delta = 0.0;
+    let mut max_score_delta: f64 = 0.0;
+
+    for (old_rank, suggestion) in before.iter().enumerate() {
+        if let Some(new_rank) = after.iter().position(|s| s.chord == suggestion.chord) {
+            shared += 1;
+            rank_displacement += old_rank.abs_diff(new_rank);
+
+            let delta = (after[new_rank].weighted_score - suggestion.weighted_score).abs();
+            total_delta += delta;
+            max_score_delta = max_score_delta.max(delta);
+        }
+    }
+
+    let longest = before.len().max(after.len());
+    let overlap = if longest == 0 {
+        1.0
+    } else {
+        shared as f64 / longest as f64
+    };
+
+    RankingDiff {
+        top_changed: recorded.first() != replayed.first(),
+        recorded,
+        replayed,
+        overlap,
+        rank_displacement,
+        max_score_delta,
+        mean_score_delta: if shared == 0 {
+            0.0
+        } else {
+            total_delta / shared as f64
+        },
+    }
+}
+
+/// Compare two difficulty assessments
+fn diff_difficulty(before: &DifficultyAssessment, after: &DifficultyAssessment) -> DifficultyDiff {
+    DifficultyDiff {
+        overall_delta: after.overall_score - before.overall_score,
+        harmonic_delta: after.harmonic_complexity - before.harmonic_complexity,
+        rhythmic_delta: after.rhythmic_complexity - before.rhythmic_complexity,
+        technical_delta: after.technical_complexity - before.technical_complexity,
+        melodic_delta: after.melodic_complexity - before.melodic_complexity,
+        skill_level_changed: before.skill_level != after.skill_level,
+    }
+}
+
+/// Aggregate per-interaction diffs into summary statistics
+fn summarize(interactions: &[InteractionDiff]) -> ReplaySummary {
+    let mut summary = ReplaySummary {
+        total: interactions.len(),
+        ..Default::default()
+    };
+
+    let mut overlap_sum = 0.0;
+    let mut ranking_count = 0;
+
+    for diff in interactions {
+        match diff.status {
+            ReplayStatus::Unchanged => summary.unchanged += 1,
+            ReplayStatus::Changed => summary.changed += 1,
+            ReplayStatus::NewError => summary.new_errors += 1,
+            ReplayStatus::Fixed => summary.fixed += 1,
+            ReplayStatus::BothFailed => summary.both_failed += 1,
+        }
+
+        if let Some(ranking) = &diff.ranking {
+            ranking_count += 1;
+            overlap_sum += ranking.overlap;
+            if ranking.top_changed {
+                summary.top_changes += 1;
+            }
+            summary.max_score_delta = summary.max_score_delta.max(ranking.max_score_delta);
+        }
+
+        if let Some(difficulty) = &diff.difficulty {
+            summary.max_score_delta = summary.max_score_delta.max(difficulty.overall_delta.abs());
+        }
+    }
+
+    summary.mean_overlap = if ranking_count == 0 {
+        1.0
+    } else {
+        overlap_sum / ranking_count as f64
+    };
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::AiEngineConfig;
+    use crate::trie::PatternResult;
+    use composer_core::Chord;
+
+    fn suggestion(root: u8, score: f64) -> ChordSuggestion {
+        ChordSuggestion {
+            chord: Chord::new(root, 5).unwrap(),
+            confidence: score,
+            frequency_score: score,
+            context_score: score,
+            theory_score: score,
+            weighted_score: score,
+            pattern_info: PatternResult {
+                serialized_chord: [0; 5],
+                count: 1,
+                duration_weight: 1.0,
+                rank: 1,
+                relative_count: 1.0,
+                id_list: vec![],
+                weight: score,
+                branch: None,
+                mean_duration: None,
+            },
+            reasoning: String::new(),
+            recommended_inversion: None,
+            suggested_duration: None,
+        }
+    }
+
+    #[test]
+    fn test_identical_rankings_are_unchanged() {
+        let ranking = vec![suggestion(1, 0.9), suggestion(5, 0.5)];
+        let diff = compare_outcomes(
+            0,
+            "get_magic_chord_solutions",
+            &SessionOutcome::Suggestions(ranking.clone()),
+            &SessionOutcome::Suggestions(ranking),
+            &ReplayOptions::default(),
+        );
+        assert_eq!(diff.status, ReplayStatus::Unchanged);
+        let ranking = diff.ranking.unwrap();
+        assert_eq!(ranking.overlap, 1.0);
+        assert_eq!(ranking.rank_displacement, 0);
+    }
+
+    #[test]
+    fn test_reordered_ranking_is_changed() {
+        let before = vec![suggestion(1, 0.9), suggestion(5, 0.5), suggestion(4, 0.2)];
+        let after = vec![suggestion(5, 0.8), suggestion(1, 0.7)];
+        let ranking = diff_rankings(&before, &after, &ReplayOptions::default());
+
+        assert!(ranking.top_changed);
+        assert_eq!(ranking.rank_displacement, 2);
+        assert!((ranking.overlap - 2.0 / 3.0).abs() < 1e-9);
+        assert!((ranking.max_score_delta - 0.3).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_error_transitions() {
+        let ok = SessionOutcome::Suggestions(vec![]);
+        let err = SessionOutcome::Error("failed".to_string());
+        let options = ReplayOptions::default();
+
+        assert_eq!(
+            compare_outcomes(0, "op", &ok, &err, &options).status,
+            ReplayStatus::NewError
+        );
+        assert_eq!(
+            compare_outcomes(0, "op", &err, &ok, &options).status,
+            ReplayStatus::Fixed
+        );
+        assert_eq!(
+            compare_outcomes(0, "op", &err, &err, &options).status,
+            ReplayStatus::BothFailed
+        );
+    }
+
+    #[test]
+    fn test_replay_against_engines() {
+        let progression = vec![Chord::new(1, 5).unwrap(), Chord::new(5, 5).unwrap()];
+
+        let recording_engine = AiEngine::new(AiEngineConfig::default());
+        recording_engine.initialize(vec![]).unwrap();
+        recording_engine.start_session_recording();
+        recording_engine
+            .assess_difficulty(&progression, None, None)
+            .unwrap();
+        let session = recording_engine.stop_session_recording().unwrap();
+
+        let same_engine = AiEngine::new(AiEngineConfig::default());
+        same_engine.initialize(vec![]).unwrap();
+        let report = replay_session(&same_engine, &session, &ReplayOptions::default());
+        assert!(report.is_unchanged());
+        assert_eq!(report.summary.total, 1);
+
+        let uninitialized = AiEngine::new(AiEngineConfig::default());
+        let report = replay_session(&uninitialized, &session, &ReplayOptions::default());
+        assert_eq!(report.summary.new_errors, 1);
+        assert_eq!(report.regressions().len(), 1);
+    }
+}
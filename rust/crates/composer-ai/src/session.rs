@@ -0,0 +1,507 @@
+//! Session recording of engine interactions
+//!
+//! Provides an opt-in recorder that captures every query sent to the [`AiEngine`],
+//! the context it was issued with, the results that came back, and any feedback the
+//! user gave on those results. A recorded [`Session`] is plain serde data: it can be
+//! written to a JSON session file, loaded again for debugging, replayed against a
+//! different engine build, or mined as personalization training data.
+//!
+//! [`AiEngine`]: crate::engine::AiEngine
+
+use crate::analysis::DifficultyAssessment;
+use crate::engine::{AiEngineConfig, BassHarmonization, BassHarmonizationOptions};
+use crate::error::{AiError, AiResult};
+use crate::instrument::DifficultyProfile;
+use crate::suggestions::{ChordSuggestion, SuggestionConfig, SuggestionContext};
+use composer_config::APPLICATION;
+use composer_core::Chord;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Current version of the session file format
+pub const SESSION_FORMAT_VERSION: u32 = 1;
+
+/// A query issued to the engine, with every argument needed to re-run it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SessionQuery {
+    /// Context-aware chord suggestions
+    ChordSuggestions {
+        pattern: Vec<Chord>,
+        context: Box<SuggestionContext>,
+        config: SuggestionConfig,
+    },
+
+    /// Magic chord solutions between surrounding chords
+    MagicChordSolutions {
+        previous_chords: Vec<Chord>,
+        following_chords: Vec<Chord>,
+        scale: String,
+        limit: usize,
+    },
+
+    /// Chords harmonizing a bass note
+    MagicBassSolutions {
+        bass_note: String,
+        scale: String,
+        limit: usize,
+    },
+
+    /// Chords harmonizing a set of scale degrees
+    HarmonizeBySdSolutions {
+        scale_degree_bits: u32,
+        scale: String,
+        limit: usize,
+        #[serde(default)]
+        config: SuggestionConfig,
+    },
+
+    /// Difficulty assessment of a progression
+    AssessDifficulty {
+        progression: Vec<Chord>,
+        tempo_bpm: Option<f64>,
+        time_signature: Option<(u8, u8)>,
+        /// Instrument profile, absent from sessions recorded before profiles
+        #[serde(default)]
+        instrument: Option<DifficultyProfile>,
+    },
+
+    /// Bass line generation for a progression
+    HarmonizeBassLine {
+        progression: Vec<Chord>,
+        options: BassHarmonizationOptions,
+    },
+}
+
+impl SessionQuery {
+    /// Short name of the engine operation this query targets
+    pub fn operation(&self) -> &'static str {
+        match self {
+            SessionQuery::ChordSuggestions { .. } => "get_chord_suggestions",
+            SessionQuery::MagicChordSolutions { .. } => "get_magic_chord_solutions",
+            SessionQuery::MagicBassSolutions { .. } => "get_magic_bass_solutions",
+            SessionQuery::HarmonizeBySdSolutions { .. } => "get_harmonize_by_sd_solutions",
+            SessionQuery::AssessDifficulty { .. } => "assess_difficulty",
+            SessionQuery::HarmonizeBassLine { .. } => "harmonize_bass_line",
+        }
+    }
+}
+
+/// What the engine returned for a recorded query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(clippy::large_enum_variant)]
+pub enum SessionOutcome {
+    /// Ranked chord suggestions
+    Suggestions(Vec<ChordSuggestion>),
+
+    /// Difficulty assessment
+    Difficulty(DifficultyAssessment),
+
+    /// Generated bass line
+    BassLine(BassHarmonization),
+
+    /// The query failed with this error message
+    Error(String),
+}
+
+impl SessionOutcome {
+    /// Check if the recorded query failed
+    pub fn is_error(&self) -> bool {
+        matches!(self, SessionOutcome::Error(_))
+    }
+}
+
+impl From<Vec<ChordSuggestion>> for SessionOutcome {
+    fn from(suggestions: Vec<ChordSuggestion>) -> Self {
+        SessionOutcome::Suggestions(suggestions)
+    }
+}
+
+impl From<DifficultyAssessment> for SessionOutcome {
+    fn from(assessment: DifficultyAssessment) -> Self {
+        SessionOutcome::Difficulty(assessment)
+    }
+}
+
+impl From<BassHarmonization> for SessionOutcome {
+    fn from(harmonization: BassHarmonization) -> Self {
+        SessionOutcome::BassLine(harmonization)
+    }
+}
+
+/// A single recorded query together with its outcome
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionInteraction {
+    /// Identifier unique within the session, used to attach feedback
+    pub id: u64,
+
+    /// Wall-clock time of the query (milliseconds since the Unix epoch)
+    pub timestamp_ms: u64,
+
+    /// Time the engine spent on the query in milliseconds
+    pub elapsed_ms: f64,
+
+    /// The query as issued
+    pub query: SessionQuery,
+
+    /// The result returned by the engine
+    pub outcome: SessionOutcome,
+}
+
+/// Kind of feedback a user gave on a recorded interaction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FeedbackKind {
+    /// The user picked the suggestion at this index
+    Accepted { suggestion_index: usize },
+
+    /// The user dismissed the suggestion at this index, or all of them if `None`
+    Rejected { suggestion_index: Option<usize> },
+
+    /// Explicit rating (0.0-1.0)
+    Rating { score: f64 },
+
+    /// Free-form comment
+    Comment { text: String },
+}
+
+/// User feedback attached to a recorded interaction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionFeedback {
+    /// Interaction the feedback refers to
+    pub interaction_id: u64,
+
+    /// Wall-clock time of the feedback (milliseconds since the Unix epoch)
+    pub timestamp_ms: u64,
+
+    /// The feedback itself
+    pub kind: FeedbackKind,
+}
+
+/// A complete recorded session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    /// Session file format version
+    pub format_version: u32,
+
+    /// Application version that produced the session
+    pub engine_version: String,
+
+    /// Wall-clock time recording started (milliseconds since the Unix epoch)
+    pub started_at_ms: u64,
+
+    /// Engine configuration at the time recording started
+    pub config: AiEngineConfig,
+
+    /// Recorded queries in the order they were issued
+    pub interactions: Vec<SessionInteraction>,
+
+    /// Feedback in the order it was given
+    pub feedback: Vec<SessionFeedback>,
+}
+
+impl Session {
+    /// Create an empty session for the given engine configuration
+    pub fn new(config: AiEngineConfig) -> Self {
+        Self {
+            format_version: SESSION_FORMAT_VERSION,
+            engine_version: APPLICATION.version.to_string(),
+            started_at_ms: now_ms(),
+            config,
+            interactions: Vec::new(),
+            feedback: Vec::new(),
+        }
+    }
+
+    /// Look up a recorded interaction by id
+    pub fn interaction(&self, id: u64) -> Option<&SessionInteraction> {
+        self.interactions.iter().find(|i| i.id == id)
+    }
+
+    /// All feedback given on a recorded interaction
+    pub fn feedback_for(&self, interaction_id: u64) -> Vec<&SessionFeedback> {
+        self.feedback
+            .iter()
+            .filter(|f| f.interaction_id == interaction_id)
+            .collect()
+    }
+
+    /// Serialize the session to a JSON string
+    pub fn to_json(&self) -> AiResult<String> {
+        serde_json::to_string_pretty(self).map_err(|e| AiError::SessionError {
+            reason: e.to_string(),
+        })
+    }
+
+    /// Parse a session from a JSON string
+    pub fn from_json(json: &str) -> AiResult<Self> {
+        let session: Session = serde_json::from_str(json).map_err(|e| AiError::SessionError {
+            reason: e.to_string(),
+        })?;
+
+        if session.format_version > SESSION_FORMAT_VERSION {
+            return Err(AiError::SessionError {
+                reason: format!(
+                    "Unsupported session format version {} (max {})",
+                    session.format_version, SESSION_FORMAT_VERSION
+                ),
+            });
+        }
+
+        Ok(session)
+    }
+
+    /// Write the session to a JSON session file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> AiResult<()> {
+        let json = self.to_json()?;
+        std::fs::write(path.as_ref(), json).map_err(|e| AiError::SessionError {
+            reason: format!("Failed to write {}: {}", path.as_ref().display(), e),
+        })
+    }
+
+    /// Read a session from a JSON session file
+    pub fn load<P: AsRef<Path>>(path: P) -> AiResult<Self> {
+        let json = std::fs::read_to_string(path.as_ref()).map_err(|e| AiError::SessionError {
+            reason: format!("Failed to read {}: {}", path.as_ref().display(), e),
+        })?;
+        Self::from_json(&json)
+    }
+}
+
+/// Opt-in recorder for engine interactions
+///
+/// Recording is off until [`SessionRecorder::start`] is called, and recording calls
+/// are no-ops while inactive, so an idle recorder costs a single lock check per query.
+#[derive(Debug, Default)]
+pub struct SessionRecorder {
+    /// Session being recorded, if any
+    active: RwLock<Option<Session>>,
+
+    /// Next interaction id to hand out
+    next_id: RwLock<u64>,
+}
+
+impl SessionRecorder {
+    /// Create an inactive recorder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start recording a new session, discarding any session in progress
+    pub fn start(&self, config: AiEngineConfig) {
+        *self.next_id.write() = 0;
+        *self.active.write() = Some(Session::new(config));
+    }
+
+    /// Stop recording and return the recorded session
+    pub fn stop(&self) -> Option<Session> {
+        self.active.write().take()
+    }
+
+    /// Check if a session is being recorded
+    pub fn is_recording(&self) -> bool {
+        self.active.read().is_some()
+    }
+
+    /// Copy of the session recorded so far
+    pub fn snapshot(&self) -> Option<Session> {
+        self.active.read().clone()
+    }
+
+    /// Record a query and its outcome, returning the new interaction id
+    ///
+    /// Returns `None` when no session is being recorded.
+    pub fn record_interaction(
+        &self,
+        query: SessionQuery,
+        outcome: SessionOutcome,
+        elapsed_ms: f64,
+    ) -> Option<u64> {
+        let mut active = self.active.write();
+        let session = active.as_mut()?;
+
+        let mut next_id = self.next_id.write();
+        let id = *next_id;
+        *next_id += 1;
+
+        session.interactions.push(SessionInteraction {
+            id,
+            timestamp_ms: now_ms(),
+            elapsed_ms,
+            query,
+            outcome,
+        });
+
+        Some(id)
+    }
+
+    /// Attach user feedback to a recorded interaction
+    pub fn record_feedback(&self, interaction_id: u64, kind: FeedbackKind) -> AiResult<()> {
+        let mut active = self.active.write();
+        let session = active.as_mut().ok_or_else(|| AiError::SessionError {
+            reason: "No session is being recorded".to_string(),
+        })?;
+
+        if session.interaction(interaction_id).is_none() {
+            return Err(AiError::SessionError {
+                reason: format!("Unknown interaction id {}", interaction_id),
+            });
+        }
+
+        session.feedback.push(SessionFeedback {
+            interaction_id,
+            timestamp_ms: now_ms(),
+            kind,
+        });
+
+        Ok(())
+    }
+
+    /// Id of the most recently recorded interaction
+    pub fn last_interaction_id(&self) -> Option<u64> {
+        self.active
+            .read()
+            .as_ref()
+            .and_then(|session| session.interactions.last())
+            .map(|interaction| interaction.id)
+    }
+}
+
+/// Current wall-clock time in milliseconds since the Unix epoch
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn difficulty_query() -> SessionQuery {
+        SessionQuery::AssessDifficulty {
+            progression: vec![Chord::new(1, 5).unwrap(), Chord::new(5, 7).unwrap()],
+            tempo_bpm: Some(120.0),
+            time_signature: Some((4, 4)),
+            instrument: None,
+        }
+    }
+
+    #[test]
+    fn test_recorder_inactive_by_default() {
+        let recorder = SessionRecorder::new();
+        assert!(!recorder.is_recording());
+
+        let id =
+            recorder.record_interaction(difficulty_query(), SessionOutcome::Error("x".into()), 0.0);
+        assert!(id.is_none());
+        assert!(recorder.stop().is_none());
+    }
+
+    #[test]
+    fn test_record_interactions_and_feedback() {
+        let recorder = SessionRecorder::new();
+        recorder.start(AiEngineConfig::default());
+
+        let first = recorder
+            .record_interaction(difficulty_query(), SessionOutcome::Suggestions(vec![]), 1.0)
+            .unwrap();
+        let second = recorder
+            .record_interaction(
+                difficulty_query(),
+                SessionOutcome::Error("boom".into()),
+                2.0,
+            )
+            .unwrap();
+        assert_eq!(first, 0);
+        assert_eq!(second, 1);
+        assert_eq!(recorder.last_interaction_id(), Some(1));
+
+        recorder
+            .record_feedback(
+                first,
+                FeedbackKind::Accepted {
+                    suggestion_index: 0,
+                },
+            )
+            .unwrap();
+        assert!(recorder
+            .record_feedback(42, FeedbackKind::Rating { score: 1.0 })
+            .is_err());
+
+        let session = recorder.stop().unwrap();
+        assert!(!recorder.is_recording());
+        assert_eq!(session.interactions.len(), 2);
+        assert_eq!(session.feedback_for(first).len(), 1);
+        assert!(session.interaction(second).unwrap().outcome.is_error());
+    }
+
+    #[test]
+    fn test_feedback_requires_active_session() {
+        let recorder = SessionRecorder::new();
+        let result = recorder.record_feedback(
+            0,
+            FeedbackKind::Rejected {
+                suggestion_index: None,
+            },
+        );
+        assert!(matches!(result, Err(AiError::SessionError { .. })));
+    }
+
+    #[test]
+    fn test_session_json_roundtrip() {
+        let mut session = Session::new(AiEngineConfig::default());
+        session.interactions.push(SessionInteraction {
+            id: 0,
+            timestamp_ms: 0,
+            elapsed_ms: 0.5,
+            query: difficulty_query(),
+            outcome: SessionOutcome::Error("none".into()),
+        });
+
+        let json = session.to_json().unwrap();
+        let restored = Session::from_json(&json).unwrap();
+        assert_eq!(restored.format_version, SESSION_FORMAT_VERSION);
+        assert_eq!(restored.engine_version, APPLICATION.version);
+        assert_eq!(restored.interactions.len(), 1);
+        assert_eq!(
+            restored.interactions[0].query.operation(),
+            "assess_difficulty"
+        );
+    }
+
+    #[test]
+    fn test_session_file_roundtrip() {
+        let recorder = SessionRecorder::new();
+        recorder.start(AiEngineConfig::default());
+        let id = recorder
+            .record_interaction(difficulty_query(), SessionOutcome::Suggestions(vec![]), 0.1)
+            .unwrap();
+        recorder
+            .record_feedback(
+                id,
+                FeedbackKind::Comment {
+                    text: "too busy".to_string(),
+                },
+            )
+            .unwrap();
+        let session = recorder.stop().unwrap();
+
+        let path = std::env::temp_dir().join(format!("composer-session-{}.json", now_ms()));
+        session.save(&path).unwrap();
+        let loaded = Session::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.interactions.len(), 1);
+        assert_eq!(loaded.feedback, session.feedback);
+    }
+
+    #[test]
+    fn test_rejects_newer_format_version() {
+        let mut session = Session::new(AiEngineConfig::default());
+        session.format_version = SESSION_FORMAT_VERSION + 1;
+        let json = serde_json::to_string(&session).unwrap();
+        assert!(Session::from_json(&json).is_err());
+    }
+}
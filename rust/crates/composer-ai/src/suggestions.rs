@@ -3,19 +3,32 @@
 //! Implements AI-powered chord progression generation using pattern matching,
 //! context-aware weighting, and statistical analysis of musical progressions.
 
+use crate::budget::{BudgetViolations, PerformanceBudget};
+use crate::embedding::ChordEmbeddingTable;
 use crate::error::{AiError, AiResult};
-use crate::trie::{ChordProgressionTrie, PatternResult};
-use composer_config::{PERFORMANCE, PROCESSING, QUALITY};
-use composer_core::{Chord, ScaleFingerprint};
-use rayon::prelude::*;
+use crate::genre::Genre;
+use crate::neural::{blend_neural_scores, SharedNeuralScorer};
+use crate::ngram::SharedSuggestionBackend;
+use crate::novelty::transition_novelty;
+use crate::trie::{ChordCount, ChordProgressionTrie, MatchMode, PatternResult};
+use crate::valence::chord_valence;
+use composer_config::{ComposerConfig, QualityThresholds};
+use composer_core::{
+    analyze_harmonic_function, BorrowedScale, Chord, EquivalenceLevel, FunctionViolationKind,
+    HarmonicFunction, Key, Mode, PitchClass, ScaleFingerprint, ScaleType, Substitution,
+};
+use composer_serialization::{serialize_chord, ChordBinary};
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Instant;
 
+/// Similar chords tried in place of each chord of a pattern with no match
+const BACKOFF_NEIGHBORS: usize = 3;
+
 /// Suggestion context for contextual weighting
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SuggestionContext {
     /// Current scale/key context
     pub scale_fingerprint: Option<ScaleFingerprint>,
@@ -37,10 +50,14 @@ pub struct SuggestionContext {
 
     /// Recent chord history for repetition avoidance
     pub recent_chords: SmallVec<[Chord; 8]>,
+
+    /// Key of the pattern (e.g. "C", "F#m"), for absolute matching
+    #[serde(default)]
+    pub key_tonic: Option<Key>,
 }
 
 /// Weighted chord suggestion result
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ChordSuggestion {
     /// The suggested chord
     pub chord: Chord,
@@ -65,10 +82,69 @@ pub struct ChordSuggestion {
 
     /// Explanation of suggestion reasoning
     pub reasoning: String,
+
+    /// Inversion giving the smoothest bass motion from the last chord of the
+    /// query, when [`SuggestionConfig::suggest_inversions`] is set
+    #[serde(default)]
+    pub recommended_inversion: Option<u8>,
+
+    /// Beats the chord is held for after the matched context in the timed
+    /// training patterns; `None` when none of them were timed
+    #[serde(default)]
+    pub suggested_duration: Option<f64>,
+}
+
+impl ChordSuggestion {
+    /// The suggested chord timed for insertion at `beat`
+    ///
+    /// The chord is held for the suggested duration, or `default_beats` when
+    /// the corpus has no timing for it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{
+    ///     ChordProgressionSuggester, ChordProgressionTrie, SuggestionConfig, SuggestionContext,
+    /// };
+    /// use composer_core::Chord;
+    /// use std::sync::Arc;
+    ///
+    /// let trie = Arc::new(ChordProgressionTrie::new());
+    /// let pattern = vec![Chord::triad(1)?, Chord::triad(5)?];
+    /// trie.add_timed_patterns_bulk([((pattern, vec![4.0, 2.0]), "song".to_string(), None)]);
+    /// let suggester = ChordProgressionSuggester::new(trie);
+    ///
+    /// let config = SuggestionConfig {
+    ///     min_confidence: 0.0,
+    ///     ..Default::default()
+    /// };
+    /// let context = SuggestionContext::default();
+    /// let suggestions = suggester.get_chord_suggestions(&[Chord::triad(1)?], &context, &config)?;
+    /// assert_eq!(suggestions[0].suggested_duration, Some(2.0));
+    ///
+    /// let timed = suggestions[0].timed_chord(8.0, 4.0);
+    /// assert_eq!((timed.beat, timed.duration), (Some(8.0), Some(2.0)));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn timed_chord(&self, beat: f64, default_beats: f64) -> Chord {
+        self.chord
+            .clone()
+            .with_timing(beat, self.suggested_duration.unwrap_or(default_beats))
+    }
+}
+
+/// Chord substitution ranked by corpus usage
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubstitutionSuggestion {
+    /// The theory-valid substitution
+    pub substitution: Substitution,
+
+    /// Occurrences of the substitute chord in the training corpus
+    pub frequency: u64,
 }
 
 /// Configuration for suggestion generation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SuggestionConfig {
     /// Maximum number of suggestions to return
     pub max_suggestions: usize,
@@ -87,6 +163,81 @@ pub struct SuggestionConfig {
 
     /// Enable advanced context weighting
     pub enable_context_weighting: bool,
+
+    /// Share of the final score taken from the attached neural scorer (0.0-1.0).
+    /// Ignored when no scorer is attached; 0.0 ranks by trie statistics alone.
+    #[serde(default)]
+    pub neural_weight: f64,
+
+    /// Share of the frequency score taken from the attached suggestion backend
+    /// (0.0-1.0). Above 0.0 the backend also contributes candidates the trie
+    /// did not find. Ignored when no backend is attached.
+    #[serde(default)]
+    pub backend_weight: f64,
+
+    /// When a pattern has no match, suggest from its closest variants, with
+    /// chords swapped for similar ones (see [`crate::ChordEmbeddingTable`])
+    #[serde(default = "default_similarity_backoff")]
+    pub similarity_backoff: bool,
+
+    /// Whether stored patterns match by scale degree, by the context's key
+    /// tonic, or both (see [`ChordProgressionTrie::search_with_mode`])
+    #[serde(default)]
+    pub match_mode: MatchMode,
+
+    /// Share of each pick decided by score rather than by novelty (0.0-1.0).
+    /// Below 1.0 the top suggestions are chosen by maximal marginal relevance,
+    /// penalising candidates that repeat an already chosen root and chord type;
+    /// 1.0 keeps the plain top-scored list.
+    #[serde(default = "default_diversity_threshold")]
+    pub diversity_threshold: f64,
+
+    /// Recommend an inversion for each suggestion that keeps the bass close to
+    /// the bass of the query's last chord
+    #[serde(default)]
+    pub suggest_inversions: bool,
+
+    /// Weight given to chromatic harmonization candidates (0.0-1.0): applied
+    /// dominants, chords borrowed from the parallel minor and the Neapolitan.
+    /// 0.0 keeps candidates diatonic.
+    #[serde(default)]
+    pub chromaticism: f64,
+
+    /// Drop suggestions equivalent at this level to a higher-scored one (see
+    /// [`Chord::equivalent`]), judged in the context's scale or else the major
+    /// scale. `None` keeps every distinct chord.
+    #[serde(default)]
+    pub deduplicate: Option<EquivalenceLevel>,
+
+    /// Share of the final score given to novelty against the corpus
+    /// (0.0-1.0), favouring continuations the matched context rarely takes
+    /// (see [`crate::compute_novelty`]). 0.0 ranks common continuations first.
+    #[serde(default)]
+    pub novelty_weight: f64,
+}
+
+fn default_similarity_backoff() -> bool {
+    true
+}
+
+fn default_diversity_threshold() -> f64 {
+    QualityThresholds::default().diversity_threshold
+}
+
+/// When scale-aware searches also use the global trie
+///
+/// Magic chord solutions search the branch of the requested scale first (see
+/// [`ChordProgressionTrie::search_scale_with_wildcard`]); this decides when
+/// results from every pattern, keyed or not, are added.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScaleFallback {
+    /// Fill up to the requested number of results from the global trie
+    #[default]
+    Fill,
+    /// Use the global trie only when the scale branch finds nothing
+    WhenEmpty,
+    /// Never leave the scale branch
+    Never,
 }
 
 impl Default for SuggestionContext {
@@ -99,33 +250,90 @@ impl Default for SuggestionContext {
             genre_weights: HashMap::new(),
             avoid_repetition_within: 4,
             recent_chords: SmallVec::new(),
+            key_tonic: None,
         }
     }
 }
 
 impl Default for SuggestionConfig {
     fn default() -> Self {
+        Self::from_config(&ComposerConfig::default())
+    }
+}
+
+impl SuggestionConfig {
+    /// Defaults with the result limit and thresholds of a runtime configuration
+    pub fn from_config(config: &ComposerConfig) -> Self {
         Self {
-            max_suggestions: PROCESSING.max_suggestions as usize,
-            min_confidence: QUALITY.confidence_threshold,
+            max_suggestions: config.processing.max_suggestions as usize,
+            min_confidence: config.quality.confidence_threshold,
             search_depth: 3, // Default search depth
             use_probabilistic: false,
             temperature: 1.0,
             enable_context_weighting: true,
+            neural_weight: 0.0,
+            backend_weight: 0.0,
+            similarity_backoff: true,
+            match_mode: MatchMode::Relative,
+            diversity_threshold: config.quality.diversity_threshold,
+            suggest_inversions: false,
+            chromaticism: 0.0,
+            deduplicate: None,
+            novelty_weight: 0.0,
         }
     }
 }
 
+/// Cached suggestion lists keyed by query
+type SuggestionCache = dashmap::DashMap<String, Vec<ChordSuggestion>>;
+
 /// Main chord progression suggestion engine
 pub struct ChordProgressionSuggester {
     /// Pattern storage trie
     trie: Arc<ChordProgressionTrie>,
 
     /// Cached suggestions for performance
-    suggestion_cache: Arc<dashmap::DashMap<String, Vec<ChordSuggestion>>>,
+    suggestion_cache: Arc<SuggestionCache>,
 
     /// Performance metrics
     avg_suggestion_time_ms: Arc<parking_lot::RwLock<f64>>,
+
+    /// Optional external model blended into the ranking
+    neural_scorer: parking_lot::RwLock<Option<SharedNeuralScorer>>,
+
+    /// Optional second source of candidates and frequencies
+    backend: parking_lot::RwLock<Option<SharedSuggestionBackend>>,
+
+    /// When scale-aware searches fall back to the global trie
+    scale_fallback: parking_lot::RwLock<ScaleFallback>,
+
+    /// Time limit for suggestion queries and what happens when it is exceeded
+    performance_budget: parking_lot::RwLock<PerformanceBudget>,
+
+    /// Queries that exceeded the time limit, by operation
+    budget_violations: parking_lot::RwLock<BudgetViolations>,
+
+    /// Weight cutoffs for magic chord, bass and harmonization results
+    quality_thresholds: parking_lot::RwLock<QualityThresholds>,
+}
+
+/// Continuation probabilities from the attached backend for one query
+struct BackendScores {
+    name: String,
+    weight: f64,
+    probabilities: HashMap<ChordBinary, f64>,
+}
+
+impl BackendScores {
+    /// Backend probability of `chord`, 0.0 when it was not a candidate
+    fn probability(&self, chord: &ChordBinary) -> f64 {
+        self.probabilities.get(chord).copied().unwrap_or(0.0)
+    }
+
+    /// Mix a trie-derived value with the backend's probability of `chord`
+    fn blend(&self, trie_value: f64, chord: &ChordBinary) -> f64 {
+        (1.0 - self.weight) * trie_value + self.weight * self.probability(chord)
+    }
 }
 
 impl ChordProgressionSuggester {
@@ -135,16 +343,134 @@ impl ChordProgressionSuggester {
             trie,
             suggestion_cache: Arc::new(dashmap::DashMap::new()),
             avg_suggestion_time_ms: Arc::new(parking_lot::RwLock::new(0.0)),
+            neural_scorer: parking_lot::RwLock::new(None),
+            backend: parking_lot::RwLock::new(None),
+            scale_fallback: parking_lot::RwLock::new(ScaleFallback::default()),
+            performance_budget: parking_lot::RwLock::new(PerformanceBudget::default()),
+            budget_violations: parking_lot::RwLock::new(BudgetViolations::new()),
+            quality_thresholds: parking_lot::RwLock::new(QualityThresholds::default()),
         }
     }
 
+    /// Attach or detach the neural scorer used when `neural_weight` is positive.
+    ///
+    /// Clears the suggestion cache, since cached rankings no longer apply.
+    pub fn set_neural_scorer(&self, scorer: Option<SharedNeuralScorer>) {
+        *self.neural_scorer.write() = scorer;
+        self.clear_cache();
+    }
+
+    /// Currently attached neural scorer, if any
+    pub fn neural_scorer(&self) -> Option<SharedNeuralScorer> {
+        self.neural_scorer.read().clone()
+    }
+
+    /// Attach or detach the backend blended in when `backend_weight` is positive.
+    ///
+    /// Clears the suggestion cache, since cached rankings no longer apply.
+    pub fn set_backend(&self, backend: Option<SharedSuggestionBackend>) {
+        *self.backend.write() = backend;
+        self.clear_cache();
+    }
+
+    /// Currently attached suggestion backend, if any
+    pub fn backend(&self) -> Option<SharedSuggestionBackend> {
+        self.backend.read().clone()
+    }
+
+    /// Set when scale-aware searches fall back to the global trie.
+    ///
+    /// Clears the suggestion cache, since cached rankings no longer apply.
+    pub fn set_scale_fallback(&self, fallback: ScaleFallback) {
+        *self.scale_fallback.write() = fallback;
+        self.clear_cache();
+    }
+
+    /// When scale-aware searches fall back to the global trie
+    pub fn scale_fallback(&self) -> ScaleFallback {
+        *self.scale_fallback.read()
+    }
+
+    /// Set the time limit for suggestion queries and what happens when it is exceeded
+    pub fn set_performance_budget(&self, budget: PerformanceBudget) {
+        *self.performance_budget.write() = budget;
+    }
+
+    /// Time limit for suggestion queries and what happens when it is exceeded
+    pub fn performance_budget(&self) -> PerformanceBudget {
+        *self.performance_budget.read()
+    }
+
+    /// Set the weight cutoffs for magic chord, bass and harmonization results.
+    ///
+    /// Clears the suggestion cache, since cached results no longer apply.
+    pub fn set_quality_thresholds(&self, thresholds: QualityThresholds) {
+        *self.quality_thresholds.write() = thresholds;
+        self.clear_cache();
+    }
+
+    /// Weight cutoffs for magic chord, bass and harmonization results
+    pub fn quality_thresholds(&self) -> QualityThresholds {
+        self.quality_thresholds.read().clone()
+    }
+
+    /// Number of queries that exceeded the time limit, by operation
+    pub fn budget_violations(&self) -> BudgetViolations {
+        self.budget_violations.read().clone()
+    }
+
+    /// Count a query over the time limit, failing in [`crate::BudgetMode::Fail`]
+    fn check_budget(&self, operation: &str, elapsed_ms: f64) -> AiResult<()> {
+        let budget = self.performance_budget();
+        let result = budget.check(operation, elapsed_ms, budget.chord_lookup_max_ms);
+        if !matches!(result, Ok(false)) {
+            *self
+                .budget_violations
+                .write()
+                .entry(operation.to_string())
+                .or_insert(0) += 1;
+        }
+        result.map(|_| ())
+    }
+
+    /// Wildcard search of the mode's scale branch, completed from the global
+    /// trie according to the scale fallback.
+    ///
+    /// Global results for chords the branch already found are skipped; each
+    /// result names the branch it came from.
+    fn search_in_scale(
+        &self,
+        mode: Mode,
+        pattern: &[Option<Chord>],
+        limit: usize,
+    ) -> AiResult<Vec<PatternResult>> {
+        let mut results = self.trie.search_scale_with_wildcard(mode, pattern, limit)?;
+        let fall_back = match self.scale_fallback() {
+            ScaleFallback::Fill => results.len() < limit,
+            ScaleFallback::WhenEmpty => results.is_empty(),
+            ScaleFallback::Never => false,
+        };
+        if fall_back {
+            let found: Vec<ChordBinary> = results.iter().map(|r| r.serialized_chord).collect();
+            let remaining = limit - results.len();
+            let global = self.trie.search_with_wildcard(pattern, limit)?;
+            results.extend(
+                global
+                    .into_iter()
+                    .filter(|result| !found.contains(&result.serialized_chord))
+                    .take(remaining),
+            );
+        }
+        Ok(results)
+    }
+
     /// Generate chord suggestions using magic chord algorithm from test specifications
     /// Implements getMagicChordSolutions with proper statistical weighting
     pub fn get_magic_chord_solutions(
         &self,
         previous_chords: &[Chord],
         following_chords: &[Chord],
-        _scale: &str,
+        mode: Mode,
         limit: usize,
     ) -> AiResult<Vec<ChordSuggestion>> {
         let start_time = Instant::now();
@@ -171,36 +497,40 @@ impl ChordProgressionSuggester {
         search_pattern.extend(following_chords.iter().map(|chord| Some(chord.clone())));
 
         // Search for patterns in scale-specific trie branch
-        let pattern_results = self.trie.search_with_wildcard(
+        let pattern_results = self.search_in_scale(
+            mode,
             &search_pattern,
             limit * 3, // Get more results for filtering
         )?;
 
         // Apply statistical weighting algorithm from test specs (lines 150-176)
+        let scale_fingerprint = mode.scale();
         let suggestions = self.apply_magic_chord_weighting(
             pattern_results,
             previous_chords.len(),
             following_chords.len(),
             search_pattern.len(),
+            &scale_fingerprint,
             limit,
         )?;
 
         // Check performance threshold (sub-millisecond requirement)
         let elapsed_ms = start_time.elapsed().as_millis() as f64;
         self.update_performance_metrics(elapsed_ms);
-
-        if elapsed_ms > PERFORMANCE.chord_lookup_max_ms as f64 {
-            return Err(AiError::PerformanceDegradation {
-                operation: "magic_chord_solutions".to_string(),
-                ms: elapsed_ms as u64,
-                limit_ms: PERFORMANCE.chord_lookup_max_ms as u64,
-            });
-        }
+        self.check_budget("magic_chord_solutions", elapsed_ms)?;
 
         Ok(suggestions)
     }
 
     /// Original context-aware suggestion method for backward compatibility
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(pattern_len = pattern.len(), max_suggestions = config.max_suggestions)
+        )
+    )]
     pub fn get_chord_suggestions(
         &self,
         pattern: &[Chord],
@@ -226,6 +556,8 @@ impl ChordProgressionSuggester {
         // Check cache first
         let cache_key = self.generate_cache_key(pattern, context, config);
         if let Some(cached) = self.suggestion_cache.get(&cache_key) {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(suggestions = cached.len(), "suggestion cache hit");
             return Ok(cached.clone());
         }
 
@@ -235,14 +567,34 @@ impl ChordProgressionSuggester {
         search_pattern.push(None); // Wildcard for suggestions
 
         // Search for patterns
-        let pattern_results = self.trie.search_with_wildcard(
+        let mut pattern_results = self.trie.search_with_mode(
             &search_pattern,
             config.max_suggestions * 3, // Get more results for filtering
+            config.match_mode,
+            context.key_tonic.as_ref(),
         )?;
 
+        // Add the backend's candidates that the trie did not find
+        let backend_scores = self.backend_scores(pattern, config)?;
+        if let Some(scores) = &backend_scores {
+            for key in scores.probabilities.keys() {
+                if !pattern_results.iter().any(|r| r.serialized_chord == *key) {
+                    pattern_results.push(PatternResult {
+                        serialized_chord: *key,
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+
         // Convert pattern results to suggestions with scoring
-        let suggestions =
-            self.score_and_rank_suggestions(pattern_results, pattern, context, config)?;
+        let suggestions = self.score_and_rank_suggestions(
+            pattern_results,
+            pattern,
+            context,
+            config,
+            backend_scores.as_ref(),
+        )?;
 
         // Cache the results
         self.suggestion_cache.insert(cache_key, suggestions.clone());
@@ -251,29 +603,109 @@ impl ChordProgressionSuggester {
         let elapsed_ms = start_time.elapsed().as_millis() as f64;
         self.update_performance_metrics(elapsed_ms);
 
-        // Check performance threshold
-        if elapsed_ms > PERFORMANCE.chord_lookup_max_ms as f64 {
-            return Err(AiError::PerformanceDegradation {
-                operation: "chord_suggestion".to_string(),
-                ms: elapsed_ms as u64,
-                limit_ms: PERFORMANCE.chord_lookup_max_ms as u64,
-            });
-        }
+        self.check_budget("chord_suggestion", elapsed_ms)?;
 
         Ok(suggestions)
     }
 
+    /// Suggestions for the closest variant of a pattern that has no match.
+    ///
+    /// Each chord of the pattern is swapped in turn for its nearest neighbors in
+    /// `embeddings`. Variants are tried from the most similar swap down and the
+    /// first that yields suggestions wins; their scores and confidence are
+    /// scaled by the similarity of the swap.
+    pub fn similar_context_suggestions(
+        &self,
+        pattern: &[Chord],
+        context: &SuggestionContext,
+        config: &SuggestionConfig,
+        embeddings: &ChordEmbeddingTable,
+    ) -> AiResult<Vec<ChordSuggestion>> {
+        let mut variants = Vec::new();
+        for (position, chord) in pattern.iter().enumerate() {
+            for similar in embeddings.nearest_chords(chord, BACKOFF_NEIGHBORS)? {
+                if similar.similarity > 0.0 {
+                    variants.push((position, similar));
+                }
+            }
+        }
+        // Most similar first; among equals, swapping later chords first
+        variants.sort_by(|(a_position, a), (b_position, b)| {
+            b.similarity
+                .total_cmp(&a.similarity)
+                .then(b_position.cmp(a_position))
+        });
+
+        for (position, similar) in variants {
+            let mut variant = pattern.to_vec();
+            variant[position] = similar.chord.clone();
+            let mut suggestions = self.get_chord_suggestions(&variant, context, config)?;
+            let scale = similar.similarity.min(1.0);
+            for suggestion in &mut suggestions {
+                suggestion.weighted_score *= scale;
+                suggestion.confidence *= scale;
+                suggestion.reasoning = format!(
+                    "{} (after {} in place of {}, similarity {:.2})",
+                    suggestion.reasoning, similar.chord, pattern[position], similar.similarity
+                );
+            }
+            suggestions.retain(|s| s.confidence >= config.min_confidence);
+            if !suggestions.is_empty() {
+                return Ok(suggestions);
+            }
+        }
+        Ok(Vec::new())
+    }
+
+    /// Query the attached backend when `backend_weight` is positive
+    fn backend_scores(
+        &self,
+        pattern: &[Chord],
+        config: &SuggestionConfig,
+    ) -> AiResult<Option<BackendScores>> {
+        let Some(backend) = self.backend().filter(|_| config.backend_weight > 0.0) else {
+            return Ok(None);
+        };
+
+        let mut probabilities = HashMap::new();
+        for (chord, probability) in backend.continuations(pattern, config.max_suggestions * 3)? {
+            let key = serialize_chord(&chord).map_err(|e| AiError::SuggestionFailed {
+                reason: format!("{} returned an invalid chord: {}", backend.name(), e),
+            })?;
+            let probability = if probability.is_finite() {
+                probability.clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            probabilities.insert(key, probability);
+        }
+
+        Ok(Some(BackendScores {
+            name: backend.name().to_string(),
+            weight: config.backend_weight.clamp(0.0, 1.0),
+            probabilities,
+        }))
+    }
+
     /// Score and rank pattern results as chord suggestions
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(candidates = pattern_results.len(), backend = backend.map(|b| b.name.as_str()))
+        )
+    )]
     fn score_and_rank_suggestions(
         &self,
         pattern_results: Vec<PatternResult>,
         current_pattern: &[Chord],
         context: &SuggestionContext,
         config: &SuggestionConfig,
+        backend: Option<&BackendScores>,
     ) -> AiResult<Vec<ChordSuggestion>> {
-        let suggestions: Vec<ChordSuggestion> = pattern_results
-            .par_iter()
-            .filter_map(|result| {
+        let suggestions: Vec<ChordSuggestion> =
+            crate::parallel::filter_map_collect(&pattern_results, |result| {
                 // Deserialize the chord from binary
                 let chord =
                     match composer_serialization::deserialize_chord(&result.serialized_chord) {
@@ -287,7 +719,10 @@ impl ChordProgressionSuggester {
                 }
 
                 // Calculate various scores
-                let frequency_score = self.calculate_frequency_score(result);
+                let mut frequency_score = self.calculate_frequency_score(result);
+                if let Some(backend) = backend {
+                    frequency_score = backend.blend(frequency_score, &result.serialized_chord);
+                }
                 let context_score = if config.enable_context_weighting {
                     self.calculate_context_score(&chord, context)
                 } else {
@@ -296,23 +731,23 @@ impl ChordProgressionSuggester {
                 let theory_score = self.calculate_theory_score(&chord, current_pattern, context);
 
                 // Calculate weighted final score
-                let weighted_score = self.calculate_weighted_score(
+                let mut weighted_score = self.calculate_weighted_score(
                     frequency_score,
                     context_score,
                     theory_score,
                     config,
                 );
+                if config.novelty_weight > 0.0 {
+                    let weight = config.novelty_weight.min(1.0);
+                    weighted_score = (1.0 - weight) * weighted_score
+                        + weight * transition_novelty(result.relative_count);
+                }
 
                 // Calculate overall confidence
-                let confidence = self.calculate_confidence(weighted_score, result);
-
-                // Filter by minimum confidence
-                if confidence < config.min_confidence {
-                    return None;
-                }
+                let confidence = self.calculate_confidence(weighted_score, result, backend);
 
                 // Generate reasoning explanation
-                let reasoning = self.generate_reasoning(
+                let mut reasoning = self.generate_reasoning(
                     &chord,
                     current_pattern,
                     context,
@@ -320,6 +755,22 @@ impl ChordProgressionSuggester {
                     context_score,
                     theory_score,
                 );
+                if let Some(backend) = backend {
+                    reasoning = format!(
+                        "{} ({} probability {:.2})",
+                        reasoning,
+                        backend.name,
+                        backend.probability(&result.serialized_chord)
+                    );
+                }
+
+                let recommended_inversion = if config.suggest_inversions {
+                    current_pattern
+                        .last()
+                        .and_then(|prev_chord| self.recommend_inversion(prev_chord, &chord))
+                } else {
+                    None
+                };
 
                 Some(ChordSuggestion {
                     chord,
@@ -330,18 +781,43 @@ impl ChordProgressionSuggester {
                     weighted_score,
                     pattern_info: result.clone(),
                     reasoning,
+                    recommended_inversion,
+                    suggested_duration: result.mean_duration,
                 })
-            })
-            .collect();
+            });
 
-        // Sort by weighted score descending
+        // Blend in the neural model before filtering, since it can raise or lower
+        // a candidate's confidence
         let mut sorted_suggestions = suggestions;
+        if config.neural_weight > 0.0 {
+            if let Some(scorer) = self.neural_scorer() {
+                blend_neural_scores(
+                    scorer.as_ref(),
+                    current_pattern,
+                    &mut sorted_suggestions,
+                    config,
+                    |s| self.calculate_confidence(s.weighted_score, &s.pattern_info, backend),
+                )?;
+            }
+        }
+
+        // Filter by minimum confidence
+        sorted_suggestions.retain(|s| s.confidence >= config.min_confidence);
+
+        // Sort by weighted score descending
         sorted_suggestions.sort_by(|a, b| {
             b.weighted_score
                 .partial_cmp(&a.weighted_score)
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
 
+        if let Some(level) = config.deduplicate {
+            let scale = context
+                .scale_fingerprint
+                .unwrap_or_else(ScaleFingerprint::major_scale);
+            sorted_suggestions = dedup_equivalent(sorted_suggestions, level, &scale);
+        }
+
         // Apply probabilistic selection if requested
         if config.use_probabilistic && !sorted_suggestions.is_empty() {
             sorted_suggestions = self.apply_probabilistic_selection(
@@ -350,8 +826,12 @@ impl ChordProgressionSuggester {
                 config.max_suggestions,
             );
         } else {
-            // Take top suggestions
-            sorted_suggestions.truncate(config.max_suggestions);
+            // Take top suggestions, trading score for variety
+            sorted_suggestions = self.apply_diversity_selection(
+                sorted_suggestions,
+                config.diversity_threshold,
+                config.max_suggestions,
+            );
         }
 
         Ok(sorted_suggestions)
@@ -373,9 +853,9 @@ impl ChordProgressionSuggester {
 
     /// Calculate frequency-based score from pattern results
     fn calculate_frequency_score(&self, result: &PatternResult) -> f64 {
-        // Normalize frequency score
+        // Normalize the duration-weighted frequency so held chords outweigh passing ones
         let max_count = 1000.0; // Assumed maximum count for normalization
-        (result.count as f64 / max_count).min(1.0)
+        (result.duration_weight / max_count).min(1.0)
     }
 
     /// Calculate context-aware relevance score
@@ -391,7 +871,11 @@ impl ChordProgressionSuggester {
         score += self.calculate_position_score(chord, context.position_in_progression) * 0.2;
 
         // Valence matching
-        score += self.calculate_valence_score(chord, context.target_valence) * 0.2;
+        score += self.calculate_valence_score(
+            chord,
+            context.scale_fingerprint.as_ref(),
+            context.target_valence,
+        ) * 0.2;
 
         // Complexity matching
         score += self.calculate_complexity_score(chord, context.complexity_preference) * 0.1;
@@ -409,7 +893,7 @@ impl ChordProgressionSuggester {
         &self,
         chord: &Chord,
         pattern: &[Chord],
-        _context: &SuggestionContext,
+        context: &SuggestionContext,
     ) -> f64 {
         let mut score = 0.5;
 
@@ -419,7 +903,11 @@ impl ChordProgressionSuggester {
         }
 
         // Harmonic function analysis
-        score += self.calculate_harmonic_function_score(chord, pattern) * 0.3;
+        score += self.calculate_harmonic_function_score(
+            chord,
+            pattern,
+            context.scale_fingerprint.as_ref(),
+        ) * 0.3;
 
         // Resolution tendency analysis
         score += self.calculate_resolution_score(chord, pattern) * 0.3;
@@ -449,9 +937,17 @@ impl ChordProgressionSuggester {
     }
 
     /// Calculate overall confidence in suggestion
-    fn calculate_confidence(&self, weighted_score: f64, result: &PatternResult) -> f64 {
+    fn calculate_confidence(
+        &self,
+        weighted_score: f64,
+        result: &PatternResult,
+        backend: Option<&BackendScores>,
+    ) -> f64 {
         let score_confidence = weighted_score;
-        let frequency_confidence = (result.count as f64 / 100.0).min(1.0);
+        let mut frequency_confidence = (result.count as f64 / 100.0).min(1.0);
+        if let Some(backend) = backend {
+            frequency_confidence = backend.blend(frequency_confidence, &result.serialized_chord);
+        }
 
         (score_confidence + frequency_confidence) / 2.0
     }
@@ -578,6 +1074,50 @@ impl ChordProgressionSuggester {
         selected
     }
 
+    /// Pick the top suggestions by maximal marginal relevance
+    ///
+    /// Each pick maximises `threshold * score - (1 - threshold) * similarity`,
+    /// where similarity is to the closest suggestion already picked. The picks
+    /// are returned in score order.
+    fn apply_diversity_selection(
+        &self,
+        mut suggestions: Vec<ChordSuggestion>,
+        threshold: f64,
+        max_suggestions: usize,
+    ) -> Vec<ChordSuggestion> {
+        let threshold = threshold.clamp(0.0, 1.0);
+        if threshold >= 1.0 || suggestions.len() <= max_suggestions {
+            suggestions.truncate(max_suggestions);
+            return suggestions;
+        }
+
+        let mut selected: Vec<ChordSuggestion> = Vec::with_capacity(max_suggestions);
+        while selected.len() < max_suggestions && !suggestions.is_empty() {
+            let mut best_index = 0;
+            let mut best_value = f64::NEG_INFINITY;
+            for (index, candidate) in suggestions.iter().enumerate() {
+                let redundancy = selected
+                    .iter()
+                    .map(|chosen| chord_similarity(&candidate.chord, &chosen.chord))
+                    .fold(0.0, f64::max);
+                let value = threshold * candidate.weighted_score - (1.0 - threshold) * redundancy;
+                // Candidates arrive sorted, so ties keep the higher score
+                if value > best_value {
+                    best_value = value;
+                    best_index = index;
+                }
+            }
+            selected.push(suggestions.remove(best_index));
+        }
+
+        selected.sort_by(|a, b| {
+            b.weighted_score
+                .partial_cmp(&a.weighted_score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        selected
+    }
+
     /// Weighted random selection helper
     fn weighted_random_selection(
         &self,
@@ -627,14 +1167,9 @@ impl ChordProgressionSuggester {
                     0.6 // Moderate in middle
                 }
             },
-            7 => {
-                // Seventh chords
-                if position > 0.5 {
-                    0.8 // Seventh chords create good tension toward end
-                } else {
-                    0.5 // Less ideal at beginning
-                }
-            },
+            // Seventh chords create good tension toward end, less ideal at beginning
+            7 if position > 0.5 => 0.8,
+            7 => 0.5,
             _ => 0.5, // Extended chords - neutral
         };
 
@@ -652,36 +1187,17 @@ impl ChordProgressionSuggester {
         (base_score + root_adjustment).min(1.0_f64)
     }
 
-    fn calculate_valence_score(&self, chord: &Chord, target_valence: f64) -> f64 {
-        // Map chord types to emotional valence (-1.0 to 1.0)
-        let chord_valence = match chord.chord_type {
-            5 => {
-                // Triads
-                match chord.root {
-                    1 | 4 | 5 => 0.6,  // Major triads: positive
-                    2 | 3 | 6 => -0.2, // Minor triads: slightly negative
-                    7 => -0.4,         // Diminished: negative
-                    _ => 0.0,
-                }
-            },
-            7 => {
-                // Seventh chords
-                match chord.root {
-                    1 => 0.4,      // Major 7: warm positive
-                    5 => 0.1,      // Dominant 7: tension (neutral)
-                    2 | 6 => -0.1, // Minor 7: mild negative
-                    _ => 0.0,
-                }
-            },
-            _ => 0.0, // Extended chords: neutral
-        };
-
-        // Adjust for alterations (generally more dissonant)
-        let alteration_adjustment = -0.1 * chord.alterations.len() as f64;
-        let final_valence = (chord_valence + alteration_adjustment).clamp(-1.0, 1.0);
+    fn calculate_valence_score(
+        &self,
+        chord: &Chord,
+        scale: Option<&ScaleFingerprint>,
+        target_valence: f64,
+    ) -> f64 {
+        let major = ScaleFingerprint::major_scale();
+        let chord_valence = chord_valence(chord, scale.unwrap_or(&major)).unwrap_or(0.0);
 
         // Score based on how close to target valence
-        let distance = (final_valence - target_valence).abs();
+        let distance = (chord_valence - target_valence).abs();
         (1.0 - distance / 2.0).max(0.0)
     }
 
@@ -717,55 +1233,9 @@ impl ChordProgressionSuggester {
 
         for (genre, weight) in genre_weights {
             if *weight > 0.0 {
-                let genre_score = match genre.to_lowercase().as_str() {
-                    "pop" => {
-                        // Pop favors simple triads and basic sevenths
-                        match chord.chord_type {
-                            5 => 0.9,
-                            7 => 0.6,
-                            _ => 0.3,
-                        }
-                    },
-                    "jazz" => {
-                        // Jazz favors complex harmonies
-                        (match chord.chord_type {
-                            5 => 0.4,
-                            7 => 0.8,
-                            9 | 11 | 13 => 0.9,
-                            _ => 0.5,
-                        }) + (chord.alterations.len() as f64 * 0.1)
-                    },
-                    "classical" => {
-                        // Classical favors traditional voice leading
-                        (match chord.chord_type {
-                            5 => 0.8,
-                            7 => 0.7,
-                            _ => 0.5,
-                        }) - (chord.alterations.len() as f64 * 0.05)
-                    },
-                    "blues" => {
-                        // Blues favors sevenths and dominant chords
-                        let base = match chord.chord_type {
-                            7 => 0.9,
-                            5 => 0.6,
-                            _ => 0.4,
-                        };
-                        if chord.root == 5 {
-                            base + 0.1
-                        } else {
-                            base
-                        } // Bonus for dominant
-                    },
-                    "rock" => {
-                        // Rock favors power chords and simple progressions
-                        match chord.chord_type {
-                            5 => 0.8,
-                            7 => 0.5,
-                            _ => 0.3,
-                        }
-                    },
-                    _ => 0.5, // Unknown genre
-                };
+                // Unknown genres are neutral
+                let genre_score =
+                    Genre::from_name(genre).map_or(0.5, |genre| genre.chord_fit(chord));
 
                 total_score += genre_score * weight;
                 total_weight += weight;
@@ -821,7 +1291,29 @@ impl ChordProgressionSuggester {
         (root_score + pattern_bonus + type_score).min(1.0_f64)
     }
 
-    fn calculate_harmonic_function_score(&self, chord: &Chord, pattern: &[Chord]) -> f64 {
+    /// Inversion of `chord` whose bass moves least from the bass of `prev_chord`
+    ///
+    /// Bass notes are compared as diatonic scale degrees; ties keep the lower
+    /// inversion. Rests have no bass and get no recommendation.
+    fn recommend_inversion(&self, prev_chord: &Chord, chord: &Chord) -> Option<u8> {
+        if prev_chord.is_rest || chord.is_rest {
+            return None;
+        }
+
+        let from = bass_degree(prev_chord.root, prev_chord.inversion);
+        let inversions = chord.expected_tone_count().min(4) as u8;
+        (0..inversions).min_by_key(|&inversion| {
+            let steps = (bass_degree(chord.root, inversion) + 7 - from) % 7;
+            steps.min(7 - steps)
+        })
+    }
+
+    fn calculate_harmonic_function_score(
+        &self,
+        chord: &Chord,
+        pattern: &[Chord],
+        scale: Option<&ScaleFingerprint>,
+    ) -> f64 {
         if pattern.is_empty() {
             return 0.5; // Neutral if no context
         }
@@ -838,23 +1330,17 @@ impl ChordProgressionSuggester {
             _ => 0.3,
         };
 
-        // Analyze context from previous chord
-        let context_bonus: f64 = if let Some(prev_chord) = pattern.last() {
-            match (prev_chord.root, chord.root) {
-                // Strong functional progressions
-                (5, 1) => 0.3, // V-I (dominant to tonic)
-                (2, 5) => 0.2, // ii-V
-                (4, 5) => 0.2, // IV-V
-                (1, 4) => 0.1, // I-IV (tonic to subdominant)
-                (1, 6) => 0.1, // I-vi (tonic to relative minor)
-                (6, 4) => 0.1, // vi-IV
-                // Avoid weak progressions
-                (1, 2) => -0.1, // I-ii (weak)
-                (1, 3) => -0.1, // I-iii (weak)
-                _ => 0.0,
-            }
-        } else {
-            0.0
+        // Analyze functional motion from the last sounding chord
+        let context_bonus: f64 = match pattern.iter().rev().find(|c| !c.is_rest) {
+            Some(prev_chord) => {
+                let default_scale = ScaleFingerprint::major_scale();
+                self.calculate_function_motion_score(
+                    prev_chord,
+                    chord,
+                    scale.unwrap_or(&default_scale),
+                )
+            },
+            None => 0.0,
         };
 
         // Consider chord type appropriateness for function
@@ -869,6 +1355,48 @@ impl ChordProgressionSuggester {
         (function_strength + context_bonus + type_bonus).clamp(0.0_f64, 1.0_f64)
     }
 
+    /// Bonus for the functional motion between two chords, negative for
+    /// functional-syntax violations
+    fn calculate_function_motion_score(
+        &self,
+        prev_chord: &Chord,
+        chord: &Chord,
+        scale: &ScaleFingerprint,
+    ) -> f64 {
+        let analysis = match analyze_harmonic_function(&[prev_chord.clone(), chord.clone()], scale)
+        {
+            Ok(analysis) => analysis,
+            Err(_) => return 0.0,
+        };
+        if let Some(violation) = analysis.violations.first() {
+            return match violation.kind {
+                FunctionViolationKind::UnresolvedApplied => -0.2,
+                FunctionViolationKind::Retrogression => -0.1,
+                FunctionViolationKind::PredominantToTonic => -0.1,
+            };
+        }
+        if prev_chord.applied != 0 {
+            return 0.3; // Applied chord resolving to its target
+        }
+
+        let (Some(prev_label), Some(label)) = (analysis.functions[0], analysis.functions[1]) else {
+            return 0.0;
+        };
+        match (prev_label.function, label.function) {
+            (HarmonicFunction::Dominant, HarmonicFunction::Tonic) if chord.root == 1 => 0.3,
+            (HarmonicFunction::Dominant, HarmonicFunction::Tonic) => 0.1, // Deceptive
+            (
+                HarmonicFunction::Subdominant | HarmonicFunction::Predominant,
+                HarmonicFunction::Dominant,
+            ) => 0.2,
+            (
+                HarmonicFunction::Tonic,
+                HarmonicFunction::Subdominant | HarmonicFunction::Predominant,
+            ) => 0.1,
+            _ => 0.0,
+        }
+    }
+
     fn calculate_resolution_score(&self, chord: &Chord, pattern: &[Chord]) -> f64 {
         if pattern.is_empty() {
             return 0.5; // Neutral if no context
@@ -926,12 +1454,28 @@ impl ChordProgressionSuggester {
         config: &SuggestionConfig,
     ) -> String {
         // Simplified cache key - in practice would be more sophisticated
+        let chords: String = pattern
+            .iter()
+            .filter_map(|chord| serialize_chord(chord).ok())
+            .flat_map(|bytes| bytes.map(|byte| format!("{:02x}", byte)))
+            .collect();
         format!(
-            "{}_{:.2}_{:.2}_{}",
-            pattern.len(),
+            "{}_{:.2}_{:.2}_{}_{:.2}_{:.2}_{:?}_{:.2}_{}_{:?}_{:.2}_{}",
+            chords,
             context.position_in_progression,
             context.target_valence,
-            config.max_suggestions
+            config.max_suggestions,
+            config.neural_weight,
+            config.backend_weight,
+            config.match_mode,
+            config.diversity_threshold,
+            config.suggest_inversions,
+            config.deduplicate,
+            config.novelty_weight,
+            context
+                .key_tonic
+                .map(|key| key.to_string())
+                .unwrap_or_default()
         )
     }
 
@@ -966,11 +1510,12 @@ impl ChordProgressionSuggester {
         prev_length: usize,
         next_length: usize,
         total_length: usize,
+        scale_fingerprint: &ScaleFingerprint,
         limit: usize,
     ) -> AiResult<Vec<ChordSuggestion>> {
-        let suggestions: Vec<ChordSuggestion> = pattern_results
-            .par_iter()
-            .filter_map(|result| {
+        let min_weight = self.quality_thresholds.read().confidence_threshold;
+        let suggestions: Vec<ChordSuggestion> =
+            crate::parallel::filter_map_collect(&pattern_results, |result| {
                 // Deserialize the chord from binary
                 let chord =
                     match composer_serialization::deserialize_chord(&result.serialized_chord) {
@@ -988,7 +1533,7 @@ impl ChordProgressionSuggester {
                 );
 
                 // Filter by minimum weight threshold
-                if weight < QUALITY.confidence_threshold {
+                if weight < min_weight {
                     return None;
                 }
 
@@ -1004,9 +1549,10 @@ impl ChordProgressionSuggester {
                     weighted_score: weight,
                     pattern_info: result.clone(),
                     reasoning,
+                    recommended_inversion: None,
+                    suggested_duration: None,
                 })
-            })
-            .collect();
+            });
 
         // Sort by weighted score descending
         let mut sorted_suggestions = suggestions;
@@ -1016,7 +1562,13 @@ impl ChordProgressionSuggester {
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
 
-        // Apply deduplication and limit
+        // Chords stored apart but spelled alike, such as I7 and V7/IV, are one
+        // solution
+        let mut sorted_suggestions = dedup_equivalent(
+            sorted_suggestions,
+            EquivalenceLevel::ExactSpelling,
+            scale_fingerprint,
+        );
         sorted_suggestions.truncate(limit);
         Ok(sorted_suggestions)
     }
@@ -1105,55 +1657,95 @@ impl ChordProgressionSuggester {
         }
     }
 
-    /// Bass line harmonization algorithm from test specification
+    /// Chords heard over a bass note in the training corpus
+    ///
+    /// `bass_note` names a pitch class above the tonic ("C" is the tonic, "E"
+    /// the major third) and `mode` places the corpus's scale degrees on pitch
+    /// classes. Patterns stored in the mode's scale branch are used first, then
+    /// the whole corpus according to the configured [`ScaleFallback`].
     pub fn get_magic_bass_solutions(
         &self,
-        bass_note: &str,
-        _scale: &str,
+        bass_note: PitchClass,
+        mode: Mode,
         limit: usize,
     ) -> AiResult<Vec<ChordSuggestion>> {
         let start_time = Instant::now();
 
-        // Convert bass note to raw chromatic value (0-11)
-        let chromatic_bass = self.parse_bass_note(bass_note)?;
+        // Raw chromatic value (0-11) of the bass note
+        let chromatic_bass = bass_note.chromatic();
+        let fingerprint = mode.scale();
+        let branch = ChordProgressionTrie::scale_branch(mode).name();
 
-        // Query bass progression database for matching chords
-        // For now, use a simplified approach with common chord types that contain the bass note
-        let mut bass_suggestions = Vec::new();
-
-        // Generate common chord types with this bass note as root
-        for chord_type in [5, 7, 9] {
-            // Triad, seventh, ninth
-            if let Ok(chord) = Chord::new(chromatic_bass + 1, chord_type) {
-                // Convert to scale degree
-                let complexity = self.calculate_bass_chord_complexity(&chord);
-                let frequency = self.estimate_bass_chord_frequency(chromatic_bass, chord_type);
-
-                // Apply frequency-complexity weighting from test spec
-                let weight = frequency * (1.0 / (1.0 + complexity * 0.1));
-
-                if weight > QUALITY.confidence_threshold {
-                    bass_suggestions.push(ChordSuggestion {
-                        chord,
-                        confidence: weight,
-                        frequency_score: frequency,
-                        context_score: 0.5,
-                        theory_score: 1.0 - complexity / 10.0,
-                        weighted_score: weight,
-                        pattern_info: PatternResult {
-                            serialized_chord: [0; 5], // Simplified
-                            count: (frequency * 1000.0) as u32,
-                            rank: 1,
-                            relative_count: frequency,
-                            id_list: vec![],
-                            weight,
-                        },
-                        reasoning: format!("Bass note {} harmonization", bass_note),
-                    });
+        // Query the bass-note index of the scale branch, then the whole corpus
+        let branch_index = self.trie.bass_note_index(&fingerprint, Some(mode));
+        let mut candidates: Vec<BassCandidate> = branch_index
+            .chords(chromatic_bass)
+            .iter()
+            .map(|entry| {
+                (
+                    *entry,
+                    branch_index.total(chromatic_bass),
+                    Some(branch.to_string()),
+                )
+            })
+            .collect();
+        let fall_back = match self.scale_fallback() {
+            ScaleFallback::Fill => candidates.len() < limit,
+            ScaleFallback::WhenEmpty => candidates.is_empty(),
+            ScaleFallback::Never => false,
+        };
+        if fall_back {
+            let global_index = self.trie.bass_note_index(&fingerprint, None);
+            let total = global_index.total(chromatic_bass);
+            for entry in global_index.chords(chromatic_bass) {
+                if !candidates.iter().any(|((key, _), _, _)| *key == entry.0) {
+                    candidates.push((*entry, total, None));
                 }
             }
         }
 
+        let min_weight = self.quality_thresholds.read().min_suggestion_weight;
+        let mut bass_suggestions = Vec::new();
+        for ((key, count), total, branch) in candidates {
+            let Ok(chord) = composer_serialization::deserialize_chord(&key) else {
+                continue;
+            };
+            let complexity = self.calculate_bass_chord_complexity(&chord);
+            let frequency = count as f64 / total.max(1) as f64;
+
+            // Apply frequency-complexity weighting from test spec
+            let weight = frequency * (1.0 / (1.0 + complexity * 0.1));
+            if weight < min_weight {
+                continue;
+            }
+
+            bass_suggestions.push(ChordSuggestion {
+                chord,
+                confidence: weight,
+                frequency_score: frequency,
+                context_score: 0.5,
+                theory_score: 1.0 - complexity / 10.0,
+                weighted_score: weight,
+                pattern_info: PatternResult {
+                    serialized_chord: key,
+                    count: count.min(u32::MAX as u64) as u32,
+                    duration_weight: count as f64,
+                    rank: 0,
+                    relative_count: frequency,
+                    id_list: vec![],
+                    weight,
+                    branch,
+                    mean_duration: None,
+                },
+                reasoning: format!(
+                    "Bass note {} harmonization heard {} of {} times over this bass",
+                    bass_note, count, total
+                ),
+                recommended_inversion: None,
+                suggested_duration: None,
+            });
+        }
+
         // Sort by weight and limit
         bass_suggestions.sort_by(|a, b| {
             b.weighted_score
@@ -1161,6 +1753,9 @@ impl ChordProgressionSuggester {
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
         bass_suggestions.truncate(limit);
+        for (rank, suggestion) in bass_suggestions.iter_mut().enumerate() {
+            suggestion.pattern_info.rank = rank as u32 + 1;
+        }
 
         // Performance check
         let elapsed_ms = start_time.elapsed().as_millis() as f64;
@@ -1173,8 +1768,27 @@ impl ChordProgressionSuggester {
     pub fn get_harmonize_by_sd_solutions(
         &self,
         scale_degree_bits: u32,
-        _scale: &str,
+        mode: Mode,
+        limit: usize,
+    ) -> AiResult<Vec<ChordSuggestion>> {
+        self.get_harmonize_by_sd_solutions_with_config(
+            scale_degree_bits,
+            mode,
+            limit,
+            &SuggestionConfig::default(),
+        )
+    }
+
+    /// Scale degree harmonization over the candidates enabled by `config`
+    ///
+    /// With [`SuggestionConfig::chromaticism`] above 0.0 the candidates include
+    /// chromatic chords, whose scores are scaled by it.
+    pub fn get_harmonize_by_sd_solutions_with_config(
+        &self,
+        scale_degree_bits: u32,
+        _mode: Mode,
         limit: usize,
+        config: &SuggestionConfig,
     ) -> AiResult<Vec<ChordSuggestion>> {
         let start_time = Instant::now();
 
@@ -1188,49 +1802,60 @@ impl ChordProgressionSuggester {
         }
 
         // Search harmonization database for matching chords
+        let min_score = self.quality_thresholds.read().confidence_threshold;
         let mut harmonizations = Vec::new();
 
-        // Try different chord types that can harmonize these scale degrees
-        for chord_type in [5, 7, 9, 11, 13] {
-            for root in 1..=7 {
-                if let Ok(chord) = Chord::new(root, chord_type) {
-                    if self.chord_contains_scale_degrees(&chord, &scale_degrees) {
-                        // For single scale degree, only allow chords with that root
-                        if scale_degrees.len() == 1 && scale_degrees[0] != root {
-                            continue; // Skip chords that don't have the target root
-                        }
-                        // Apply multi-factor scoring from test spec (lines 324-334)
-                        let lookup_score = self.calculate_lookup_score(&chord);
-                        let length_score = self.calculate_length_score(&chord);
-                        let complexity_score = self.calculate_complexity_score_for_sd(&chord);
-                        let magic_score = self.calculate_magic_score(&chord);
-
-                        // Total score formula from test spec
-                        let total_score = lookup_score * 0.2
-                            + length_score * 0.2
-                            + complexity_score * 0.3
-                            + magic_score * 0.3;
-
-                        if total_score > QUALITY.confidence_threshold {
-                            harmonizations.push(ChordSuggestion {
-                                chord,
-                                confidence: total_score,
-                                frequency_score: magic_score,
-                                context_score: 0.5,
-                                theory_score: complexity_score,
-                                weighted_score: total_score,
-                                pattern_info: PatternResult {
-                                    serialized_chord: [0; 5], // Simplified
-                                    count: (magic_score * 100.0) as u32,
-                                    rank: 1,
-                                    relative_count: magic_score,
-                                    id_list: vec![],
-                                    weight: total_score,
-                                },
-                                reasoning: format!("Harmonizes scale degrees {:?}", scale_degrees),
-                            });
-                        }
-                    }
+        // Try the candidate chords that can harmonize these scale degrees
+        for (chord, candidate_weight, origin) in harmonization_candidates(config.chromaticism) {
+            if self.chord_contains_scale_degrees(&chord, &scale_degrees) {
+                // For single scale degree, only allow chords with that root
+                if scale_degrees.len() == 1 && scale_degrees[0] != harmonic_root(&chord) {
+                    continue; // Skip chords that don't have the target root
+                }
+                // Apply multi-factor scoring from test spec (lines 324-334)
+                let lookup_score = self.calculate_lookup_score(&chord);
+                let length_score = self.calculate_length_score(&chord);
+                let complexity_score = self.calculate_complexity_score_for_sd(&chord);
+                let magic_score = self.calculate_magic_score(&chord);
+
+                // Total score formula from test spec
+                let total_score = (lookup_score * 0.2
+                    + length_score * 0.2
+                    + complexity_score * 0.3
+                    + magic_score * 0.3)
+                    * candidate_weight;
+
+                if total_score > min_score {
+                    harmonizations.push(ChordSuggestion {
+                        chord,
+                        confidence: total_score,
+                        frequency_score: magic_score,
+                        context_score: 0.5,
+                        theory_score: complexity_score,
+                        weighted_score: total_score,
+                        pattern_info: PatternResult {
+                            serialized_chord: [0; 5], // Simplified
+                            count: (magic_score * 100.0) as u32,
+                            duration_weight: magic_score * 100.0,
+                            rank: 1,
+                            relative_count: magic_score,
+                            id_list: vec![],
+                            weight: total_score,
+                            branch: None,
+                            mean_duration: None,
+                        },
+                        reasoning: match origin {
+                            Some(origin) => format!(
+                                "Harmonizes scale degrees {:?} with {}",
+                                scale_degrees, origin
+                            ),
+                            None => {
+                                format!("Harmonizes scale degrees {:?}", scale_degrees)
+                            },
+                        },
+                        recommended_inversion: None,
+                        suggested_duration: None,
+                    });
                 }
             }
         }
@@ -1252,6 +1877,7 @@ impl ChordProgressionSuggester {
 
     // Helper methods for the new algorithms
 
+    #[deprecated(note = "parse a `PitchClass` and use `PitchClass::chromatic` instead")]
     pub fn parse_bass_note(&self, bass_note: &str) -> AiResult<u8> {
         // Simple note parsing (could be enhanced)
         match bass_note.to_uppercase().as_str() {
@@ -1287,16 +1913,6 @@ impl ChordProgressionSuggester {
         complexity.min(10.0)
     }
 
-    fn estimate_bass_chord_frequency(&self, _bass_note: u8, chord_type: u8) -> f64 {
-        // Simplified frequency estimation
-        match chord_type {
-            5 => 0.8, // Triads are common
-            7 => 0.6, // Sevenths moderately common
-            9 => 0.3, // Ninths less common
-            _ => 0.1,
-        }
-    }
-
     pub fn bits_to_scale_degrees(&self, bits: u32) -> Vec<u8> {
         let mut degrees = Vec::new();
         for i in 0..13 {
@@ -1312,27 +1928,25 @@ impl ChordProgressionSuggester {
             return false;
         }
 
-        // Get the chord tones based on chord type
+        // Get the chord tones based on chord type, built on the tonicized
+        // degree for applied chords
+        let root = harmonic_root(chord);
         let chord_tones = match chord.chord_type {
-            5 => vec![
-                chord.root,
-                ((chord.root + 2 - 1) % 7) + 1,
-                ((chord.root + 4 - 1) % 7) + 1,
-            ], // 1, 3, 5
+            5 => vec![root, ((root + 2 - 1) % 7) + 1, ((root + 4 - 1) % 7) + 1], // 1, 3, 5
             7 => vec![
-                chord.root,
-                ((chord.root + 2 - 1) % 7) + 1,
-                ((chord.root + 4 - 1) % 7) + 1,
-                ((chord.root + 6 - 1) % 7) + 1,
+                root,
+                ((root + 2 - 1) % 7) + 1,
+                ((root + 4 - 1) % 7) + 1,
+                ((root + 6 - 1) % 7) + 1,
             ], // 1, 3, 5, 7
             9 => vec![
-                chord.root,
-                ((chord.root + 2 - 1) % 7) + 1,
-                ((chord.root + 4 - 1) % 7) + 1,
-                ((chord.root + 6 - 1) % 7) + 1,
-                ((chord.root + 1 - 1) % 7) + 1,
+                root,
+                ((root + 2 - 1) % 7) + 1,
+                ((root + 4 - 1) % 7) + 1,
+                ((root + 6 - 1) % 7) + 1,
+                ((root + 1 - 1) % 7) + 1,
             ], // 1, 3, 5, 7, 9
-            _ => vec![chord.root], // Basic fallback
+            _ => vec![root],                                                     // Basic fallback
         };
 
         // Check if any chord tones match the required scale degrees
@@ -1460,6 +2074,111 @@ impl ChordProgressionSuggester {
     }
 }
 
+/// Chord heard over a bass note, the total count of chords over that bass and
+/// the scale branch it was found in
+type BassCandidate = (ChordCount, u64, Option<String>);
+
+/// Candidate chord, its score multiplier and a description of its chromatic
+/// origin, if any
+type HarmonizationCandidate = (Chord, f64, Option<&'static str>);
+
+/// Harmonization candidates: plain chords on every diatonic root and, when
+/// `chromaticism` is above 0.0, chromatic chords weighted by it
+fn harmonization_candidates(chromaticism: f64) -> Vec<HarmonizationCandidate> {
+    let mut candidates: Vec<_> = [5, 7, 9, 11, 13]
+        .into_iter()
+        .flat_map(|chord_type| (1..=7).map(move |root| (root, chord_type)))
+        .filter_map(|(root, chord_type)| Chord::new(root, chord_type).ok())
+        .map(|chord| (chord, 1.0, None))
+        .collect();
+
+    let chromaticism = chromaticism.clamp(0.0, 1.0);
+    if chromaticism <= 0.0 {
+        return candidates;
+    }
+
+    let mut chromatic = Vec::new();
+    for chord_type in [5, 7] {
+        // Applied dominants of every degree with a consonant triad
+        for target in 2..=6 {
+            chromatic.push((
+                Chord::new(5, chord_type).and_then(|chord| chord.with_applied(target)),
+                "an applied dominant",
+            ));
+        }
+        // Modal mixture from the parallel minor: bIII, iv, bVI and bVII
+        for root in [3, 4, 6, 7] {
+            chromatic.push((
+                Chord::new(root, chord_type).and_then(|chord| {
+                    chord.with_borrowed_scale(BorrowedScale::ScaleType(ScaleType::Minor))
+                }),
+                "a chord borrowed from the parallel minor",
+            ));
+        }
+    }
+    // Neapolitan: bII from the phrygian mode on the tonic
+    chromatic.push((
+        Chord::new(2, 5).and_then(|chord| chord.with_borrowed_scale(BorrowedScale::Numeric(2))),
+        "the Neapolitan",
+    ));
+
+    candidates.extend(
+        chromatic
+            .into_iter()
+            .filter_map(|(chord, origin)| Some((chord.ok()?, chromaticism, Some(origin)))),
+    );
+    candidates
+}
+
+/// Scale degree an applied chord tonicizes its root from, or the chord's own
+/// root otherwise (`V/ii` sits on degree 6)
+fn harmonic_root(chord: &Chord) -> u8 {
+    if chord.applied == 0 {
+        chord.root
+    } else {
+        (chord.root + chord.applied + 5) % 7 + 1
+    }
+}
+
+/// Scale degree (1-7) in the bass of a chord: its root, third, fifth or
+/// seventh for inversions 0-3
+fn bass_degree(root: u8, inversion: u8) -> u8 {
+    (root + 6 + 2 * inversion) % 7 + 1
+}
+
+/// How interchangeable two suggestions are: 1.0 for the same root and chord
+/// type (variants differing only in extensions, alterations or inversion),
+/// 0.5 for the same root with another chord type, otherwise 0.0
+fn chord_similarity(a: &Chord, b: &Chord) -> f64 {
+    match (a.root == b.root, a.chord_type == b.chord_type) {
+        (true, true) => 1.0,
+        (true, false) => 0.5,
+        _ => 0.0,
+    }
+}
+
+/// Suggestions in order without those equivalent at `level` to an earlier
+/// one; chords that cannot be compared are kept
+fn dedup_equivalent(
+    suggestions: Vec<ChordSuggestion>,
+    level: EquivalenceLevel,
+    scale: &ScaleFingerprint,
+) -> Vec<ChordSuggestion> {
+    let mut kept: Vec<ChordSuggestion> = Vec::with_capacity(suggestions.len());
+    for suggestion in suggestions {
+        let duplicate = kept.iter().any(|earlier| {
+            earlier
+                .chord
+                .equivalent(&suggestion.chord, level, scale)
+                .unwrap_or(false)
+        });
+        if !duplicate {
+            kept.push(suggestion);
+        }
+    }
+    kept
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1506,6 +2225,86 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_scale_fallback() {
+        let trie = Arc::new(ChordProgressionTrie::new());
+        let (i, iv, v, vi) = (
+            Chord::new(1, 5).unwrap(),
+            Chord::new(4, 5).unwrap(),
+            Chord::new(5, 5).unwrap(),
+            Chord::new(6, 5).unwrap(),
+        );
+        trie.add_pattern(
+            &[i.clone(), v.clone()],
+            "minor".to_string(),
+            Some("Am".parse().unwrap()),
+        )
+        .unwrap();
+        trie.add_pattern(
+            &[i.clone(), iv],
+            "major".to_string(),
+            Some("C".parse().unwrap()),
+        )
+        .unwrap();
+        trie.add_pattern(
+            &[i.clone(), v.clone()],
+            "major".to_string(),
+            Some("G".parse().unwrap()),
+        )
+        .unwrap();
+        trie.add_pattern(&[i.clone(), vi.clone()], "untagged".to_string(), None)
+            .unwrap();
+        trie.calculate_all_ranks();
+        let suggester = ChordProgressionSuggester::new(trie);
+        let pattern = [Some(i), None];
+        let branches = |results: Vec<PatternResult>| -> Vec<Option<String>> {
+            results.into_iter().map(|r| r.branch).collect()
+        };
+
+        assert_eq!(suggester.scale_fallback(), ScaleFallback::Fill);
+        let filled = suggester
+            .search_in_scale(Mode::Minor, &pattern, 10)
+            .unwrap();
+        assert_eq!(filled.len(), 3);
+        assert_eq!(filled[0].serialized_chord, serialize_chord(&v).unwrap());
+        assert_eq!(
+            branches(filled),
+            vec![Some("minor".to_string()), None, None]
+        );
+
+        // Only the untagged pattern has a chord before vi
+        let before_vi = [None, Some(vi)];
+        suggester.set_scale_fallback(ScaleFallback::WhenEmpty);
+        assert_eq!(
+            suggester
+                .search_in_scale(Mode::Minor, &pattern, 10)
+                .unwrap()
+                .len(),
+            1
+        );
+        let dorian = suggester
+            .search_in_scale(Mode::Dorian, &pattern, 10)
+            .unwrap();
+        assert_eq!(branches(dorian), vec![Some("minor".to_string())]);
+        let unkeyed = suggester
+            .search_in_scale(Mode::Minor, &before_vi, 10)
+            .unwrap();
+        assert_eq!(branches(unkeyed), vec![None]);
+
+        suggester.set_scale_fallback(ScaleFallback::Never);
+        assert!(suggester
+            .search_in_scale(Mode::Minor, &before_vi, 10)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            suggester
+                .search_in_scale(Mode::Mixolydian, &pattern, 10)
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
     #[test]
     fn test_repetition_avoidance() {
         let trie = Arc::new(ChordProgressionTrie::new());
@@ -1527,10 +2326,13 @@ mod tests {
         let pattern_result = PatternResult {
             serialized_chord: [0; 5],
             count: 50,
+            duration_weight: 50.0,
             rank: 1,
             relative_count: 0.5,
             id_list: vec![],
             weight: 0.8,
+            branch: None,
+            mean_duration: None,
         };
 
         let frequency_score = suggester.calculate_frequency_score(&pattern_result);
@@ -1542,6 +2344,28 @@ mod tests {
         assert!((0.0..=1.0).contains(&context_score));
     }
 
+    #[test]
+    fn test_harmonic_function_scoring() {
+        let trie = Arc::new(ChordProgressionTrie::new());
+        let suggester = ChordProgressionSuggester::new(trie);
+        let function_score = |pattern: &[Chord], chord: Chord| {
+            suggester.calculate_harmonic_function_score(&chord, pattern, None)
+        };
+
+        // V/ii resolving to ii beats leaving it for IV
+        let applied = [Chord::seventh(5).unwrap().with_applied(2).unwrap()];
+        assert!(
+            function_score(&applied, Chord::seventh(2).unwrap())
+                > function_score(&applied, Chord::triad(4).unwrap())
+        );
+
+        // ii–V beats the retrogression V–ii
+        assert!(
+            function_score(&[Chord::seventh(2).unwrap()], Chord::seventh(5).unwrap())
+                > function_score(&[Chord::seventh(5).unwrap()], Chord::seventh(2).unwrap())
+        );
+    }
+
     #[test]
     fn test_cache_operations() {
         let trie = Arc::new(ChordProgressionTrie::new());
@@ -1573,13 +2397,396 @@ mod tests {
         let pattern_result = PatternResult {
             serialized_chord: [0; 5],
             count: 25,
+            duration_weight: 25.0,
             rank: 2,
             relative_count: 0.25,
             id_list: vec![],
             weight: 0.6,
+            branch: None,
+            mean_duration: None,
         };
 
-        let confidence = suggester.calculate_confidence(0.7, &pattern_result);
+        let confidence = suggester.calculate_confidence(0.7, &pattern_result, None);
         assert!((0.0..=1.0).contains(&confidence));
     }
+
+    #[test]
+    fn test_backend_blending() {
+        let progression = [
+            Chord::triad(1).unwrap(),
+            Chord::triad(4).unwrap(),
+            Chord::triad(5).unwrap(),
+        ];
+        let trie = Arc::new(ChordProgressionTrie::new());
+        trie.add_pattern(&progression, "I-IV-V".to_string(), None)
+            .unwrap();
+        let model = crate::ngram::NGramModel::new(2).unwrap();
+        model.add_pattern(&progression).unwrap();
+        let suggester = ChordProgressionSuggester::new(trie);
+        suggester.set_backend(Some(Arc::new(model)));
+
+        // A history that never started a pattern finds nothing in the trie alone
+        let history = [Chord::triad(6).unwrap(), Chord::triad(4).unwrap()];
+        let context = SuggestionContext::default();
+        let config = SuggestionConfig {
+            min_confidence: 0.0,
+            ..Default::default()
+        };
+        assert!(suggester
+            .get_chord_suggestions(&history, &context, &config)
+            .unwrap()
+            .is_empty());
+
+        let config = SuggestionConfig {
+            backend_weight: 0.5,
+            ..config
+        };
+        let suggestions = suggester
+            .get_chord_suggestions(&history, &context, &config)
+            .unwrap();
+        assert_eq!(suggestions[0].chord, Chord::triad(5).unwrap());
+        assert!(suggestions[0].frequency_score > 0.0);
+        assert!(suggestions[0]
+            .reasoning
+            .contains("n-gram model probability"));
+    }
+    #[test]
+    fn test_chromatic_harmonization() {
+        let suggester = ChordProgressionSuggester::new(Arc::new(ChordProgressionTrie::new()));
+        // Scale degrees 2 and 4 of the key
+        let bits = (1 << 1) | (1 << 3);
+
+        let diatonic = suggester
+            .get_harmonize_by_sd_solutions(bits, Mode::Major, 100)
+            .unwrap();
+        assert!(!diatonic.is_empty());
+        assert!(diatonic
+            .iter()
+            .all(|s| !s.chord.is_applied() && !s.chord.is_borrowed()));
+
+        let config = SuggestionConfig {
+            chromaticism: 1.0,
+            ..Default::default()
+        };
+        let chromatic = suggester
+            .get_harmonize_by_sd_solutions_with_config(bits, Mode::Major, 100, &config)
+            .unwrap();
+        assert!(chromatic.len() > diatonic.len());
+        let applied = chromatic
+            .iter()
+            .find(|s| s.chord.is_applied())
+            .expect("an applied dominant");
+        assert!(applied.reasoning.contains("applied dominant"));
+        assert!(chromatic.iter().any(|s| s.chord.is_borrowed()));
+
+        // Lower chromaticism ranks the same chromatic chords lower
+        let config = SuggestionConfig {
+            chromaticism: 0.6,
+            ..config
+        };
+        let muted = suggester
+            .get_harmonize_by_sd_solutions_with_config(bits, Mode::Major, 100, &config)
+            .unwrap();
+        let score = |suggestions: &[ChordSuggestion]| {
+            suggestions
+                .iter()
+                .find(|s| s.chord == applied.chord)
+                .map(|s| s.weighted_score)
+        };
+        assert!(score(&muted).unwrap_or(0.0) < score(&chromatic).unwrap());
+    }
+
+    #[test]
+    fn test_recommended_inversions() {
+        let trie = Arc::new(ChordProgressionTrie::new());
+        let tonic = Chord::triad(1).unwrap();
+        for root in [4, 5] {
+            trie.add_pattern(
+                &[tonic.clone(), Chord::triad(root).unwrap()],
+                format!("to-{}", root),
+                None,
+            )
+            .unwrap();
+        }
+        trie.calculate_all_ranks();
+        let suggester = ChordProgressionSuggester::new(trie);
+        let context = SuggestionContext::default();
+        let config = SuggestionConfig {
+            min_confidence: 0.0,
+            ..Default::default()
+        };
+        let inversions = |config: &SuggestionConfig| {
+            let mut inversions: Vec<_> = suggester
+                .get_chord_suggestions(std::slice::from_ref(&tonic), &context, config)
+                .unwrap()
+                .iter()
+                .map(|s| (s.chord.root, s.recommended_inversion))
+                .collect();
+            inversions.sort();
+            inversions
+        };
+
+        assert_eq!(inversions(&config), vec![(4, None), (5, None)]);
+
+        // IV keeps the tonic in the bass as a 6/4; V steps down to its third
+        let voiced = SuggestionConfig {
+            suggest_inversions: true,
+            ..config
+        };
+        assert_eq!(inversions(&voiced), vec![(4, Some(2)), (5, Some(1))]);
+    }
+
+    #[test]
+    fn test_suggested_durations() {
+        let trie = Arc::new(ChordProgressionTrie::new());
+        let tonic = Chord::triad(1).unwrap();
+        let timed = |root: u8, beats: f64| {
+            (
+                (
+                    vec![tonic.clone(), Chord::triad(root).unwrap()],
+                    vec![4.0, beats],
+                ),
+                format!("to-{}", root),
+                None,
+            )
+        };
+        trie.add_timed_patterns_bulk(vec![timed(5, 2.0), timed(5, 4.0)]);
+        trie.add_pattern(
+            &[tonic.clone(), Chord::triad(4).unwrap()],
+            "untimed".to_string(),
+            None,
+        )
+        .unwrap();
+        trie.calculate_all_ranks();
+        let suggester = ChordProgressionSuggester::new(trie);
+        let config = SuggestionConfig {
+            min_confidence: 0.0,
+            ..Default::default()
+        };
+        let suggestions = suggester
+            .get_chord_suggestions(
+                std::slice::from_ref(&tonic),
+                &SuggestionContext::default(),
+                &config,
+            )
+            .unwrap();
+        let find = |root: u8| suggestions.iter().find(|s| s.chord.root == root).unwrap();
+
+        assert_eq!(find(5).suggested_duration, Some(3.0));
+        assert_eq!(find(4).suggested_duration, None);
+
+        let inserted = find(5).timed_chord(4.0, 1.0);
+        assert_eq!(inserted.beat, Some(4.0));
+        assert_eq!(inserted.duration, Some(3.0));
+        assert_eq!(find(4).timed_chord(4.0, 1.0).duration, Some(1.0));
+    }
+
+    #[test]
+    fn test_diversity_selection() {
+        let trie = Arc::new(ChordProgressionTrie::new());
+        let tonic = Chord::triad(1).unwrap();
+        let dominant = Chord::new(5, 7).unwrap();
+        let variants = [
+            dominant.clone(),
+            dominant.clone().with_inversion(1).unwrap(),
+            dominant.clone().with_inversion(2).unwrap(),
+        ];
+        for (i, variant) in variants.iter().enumerate() {
+            for j in 0..3 {
+                trie.add_pattern(
+                    &[tonic.clone(), variant.clone()],
+                    format!("dominant-{}-{}", i, j),
+                    None,
+                )
+                .unwrap();
+            }
+        }
+        trie.add_pattern(
+            &[tonic.clone(), Chord::triad(4).unwrap()],
+            "plagal".to_string(),
+            None,
+        )
+        .unwrap();
+        trie.calculate_all_ranks();
+        let suggester = ChordProgressionSuggester::new(trie);
+        let context = SuggestionContext::default();
+        let roots = |config: &SuggestionConfig| -> Vec<u8> {
+            suggester
+                .get_chord_suggestions(std::slice::from_ref(&tonic), &context, config)
+                .unwrap()
+                .iter()
+                .map(|s| s.chord.root)
+                .collect()
+        };
+
+        let plain = SuggestionConfig {
+            max_suggestions: 2,
+            min_confidence: 0.0,
+            diversity_threshold: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(roots(&plain), vec![5, 5]);
+
+        let diverse = SuggestionConfig {
+            diversity_threshold: 0.5,
+            ..plain
+        };
+        let picked = roots(&diverse);
+        assert_eq!(picked.len(), 2);
+        assert!(picked.contains(&5) && picked.contains(&4));
+    }
+
+    #[test]
+    fn test_equivalence_deduplication() {
+        let trie = Arc::new(ChordProgressionTrie::new());
+        let tonic = Chord::triad(1).unwrap();
+        let dominant = Chord::seventh(5).unwrap();
+        let continuations = [
+            (dominant.clone(), 3),
+            (dominant.clone().with_inversion(1).unwrap(), 2),
+            (Chord::triad(7).unwrap(), 1),
+        ];
+        for (chord, count) in &continuations {
+            for i in 0..*count {
+                trie.add_pattern(
+                    &[tonic.clone(), chord.clone()],
+                    format!("{}-{}", chord, i),
+                    None,
+                )
+                .unwrap();
+            }
+        }
+        trie.calculate_all_ranks();
+        let suggester = ChordProgressionSuggester::new(trie);
+        let context = SuggestionContext::default();
+        let suggested = |deduplicate: Option<EquivalenceLevel>| -> Vec<Chord> {
+            let config = SuggestionConfig {
+                min_confidence: 0.0,
+                diversity_threshold: 1.0,
+                deduplicate,
+                ..Default::default()
+            };
+            suggester
+                .get_chord_suggestions(std::slice::from_ref(&tonic), &context, &config)
+                .unwrap()
+                .into_iter()
+                .map(|s| s.chord)
+                .collect()
+        };
+
+        assert_eq!(suggested(None).len(), 3);
+        assert_eq!(suggested(Some(EquivalenceLevel::ExactSpelling)).len(), 3);
+        // The inversion sounds the same pitches as the root position chord
+        let by_pitch = suggested(Some(EquivalenceLevel::PitchContent));
+        assert_eq!(by_pitch.len(), 2);
+        assert_eq!(by_pitch[0], dominant);
+        // vii° shares the dominant function
+        assert_eq!(
+            suggested(Some(EquivalenceLevel::Functional)),
+            vec![dominant]
+        );
+    }
+
+    #[test]
+    fn test_novelty_weight() {
+        let trie = Arc::new(ChordProgressionTrie::new());
+        let tonic = Chord::triad(1).unwrap();
+        for (chord, count) in [(Chord::triad(5).unwrap(), 6), (Chord::triad(2).unwrap(), 1)] {
+            for i in 0..count {
+                trie.add_pattern(
+                    &[tonic.clone(), chord.clone()],
+                    format!("{}-{}", chord, i),
+                    None,
+                )
+                .unwrap();
+            }
+        }
+        trie.calculate_all_ranks();
+        let suggester = ChordProgressionSuggester::new(trie);
+        let context = SuggestionContext::default();
+        // Score of V less the score of ii
+        let lead = |novelty_weight: f64| -> f64 {
+            let config = SuggestionConfig {
+                min_confidence: 0.0,
+                diversity_threshold: 1.0,
+                novelty_weight,
+                ..Default::default()
+            };
+            let suggestions = suggester
+                .get_chord_suggestions(std::slice::from_ref(&tonic), &context, &config)
+                .unwrap();
+            let score = |root: u8| {
+                suggestions
+                    .iter()
+                    .find(|s| s.chord.root == root)
+                    .map(|s| s.weighted_score)
+                    .unwrap()
+            };
+            score(5) - score(2)
+        };
+
+        // The rarer continuation gains on the common one as novelty counts more
+        assert!(lead(0.5) < lead(0.0));
+        assert!(lead(1.0) < 0.0);
+    }
+
+    #[test]
+    fn test_match_modes() {
+        let trie = Arc::new(ChordProgressionTrie::new());
+        let tonic = Chord::triad(1).unwrap();
+        trie.add_pattern(
+            &[tonic.clone(), Chord::triad(4).unwrap()],
+            "plagal".to_string(),
+            Some("C".parse().unwrap()),
+        )
+        .unwrap();
+        for id in ["a", "b"] {
+            trie.add_pattern(
+                &[tonic.clone(), Chord::triad(5).unwrap()],
+                id.to_string(),
+                Some("G".parse().unwrap()),
+            )
+            .unwrap();
+        }
+        trie.calculate_all_ranks();
+        let suggester = ChordProgressionSuggester::new(trie);
+        let chords = |suggestions: Vec<ChordSuggestion>| -> Vec<u8> {
+            suggestions.iter().map(|s| s.chord.root).collect()
+        };
+        let context = SuggestionContext {
+            key_tonic: Some("C".parse().unwrap()),
+            ..Default::default()
+        };
+        let config = SuggestionConfig {
+            min_confidence: 0.0,
+            ..Default::default()
+        };
+
+        let relative = suggester
+            .get_chord_suggestions(std::slice::from_ref(&tonic), &context, &config)
+            .unwrap();
+        assert_eq!(chords(relative).len(), 2);
+
+        let absolute = SuggestionConfig {
+            match_mode: MatchMode::Absolute,
+            ..config.clone()
+        };
+        let suggestions = suggester
+            .get_chord_suggestions(std::slice::from_ref(&tonic), &context, &absolute)
+            .unwrap();
+        assert_eq!(chords(suggestions), vec![4]);
+
+        let both = SuggestionConfig {
+            match_mode: MatchMode::Both,
+            ..config
+        };
+        let suggestions = suggester
+            .get_chord_suggestions(std::slice::from_ref(&tonic), &context, &both)
+            .unwrap();
+        assert_eq!(chords(suggestions).len(), 2);
+
+        assert!(suggester
+            .get_chord_suggestions(&[tonic], &SuggestionContext::default(), &absolute)
+            .is_err());
+    }
 }
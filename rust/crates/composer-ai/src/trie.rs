@@ -3,13 +3,17 @@
 //! Implements high-performance trie data structure for storing and querying
 //! chord progression patterns with statistical analysis capabilities.
 
+use crate::arena::{NodeId, TrieArena, ROOT_NODE};
+use crate::engine::TrainingPattern;
 use crate::error::{AiError, AiResult};
-use ahash::AHashMap;
-use composer_core::Chord;
-use composer_serialization::{serialize_chord, ChordBinary};
+use ahash::{AHashMap, AHashSet};
+use composer_core::{chord_pitch_classes, Chord, Key, Mode, ScaleFingerprint};
+use composer_serialization::{
+    deserialize_chord, fast_hash, fold_hash, serialize_chord, ChordBinary,
+};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
-use smallvec::SmallVec;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 /// Reference to source data (song/progression)
@@ -17,48 +21,221 @@ use std::sync::Arc;
 pub struct Reference {
     /// Source identifier (song ID, file hash, etc.)
     pub id: String,
-    /// Original key tonic (optional)
-    pub key_tonic: Option<String>,
+    /// Original key (optional); serialized by name, such as `"F#m"`
+    pub key_tonic: Option<Key>,
 }
 
-/// Trie node for chord progression storage
+impl Reference {
+    /// Create a reference
+    pub fn new(id: String, key_tonic: Option<Key>) -> Self {
+        Self { id, key_tonic }
+    }
+}
+
+/// Stored progression with the reference that added it
+pub type StoredPattern = (Reference, Vec<ChordBinary>);
+
+/// Chord followed by another, with the number of times it was
+pub type ChordTransition = (ChordBinary, ChordBinary, u64);
+
+/// Chord progression with the duration of each chord in beats
+pub type TimedPattern = (Vec<Chord>, Vec<f64>);
+
+/// Training pattern with how much it counts relative to a pattern of weight 1.0
+pub type WeightedPattern = (TrainingPattern, f64);
+
+/// Validated pattern awaiting insertion: input position, chord keys, per-chord
+/// weights, reference
+type PendingPattern = (usize, Vec<ChordBinary>, Option<ChordWeights>, Reference);
+
+/// Per-chord count weights of a pattern being stored and, for timed patterns,
+/// the chord durations in beats
+#[derive(Debug, Clone, Default)]
+struct ChordWeights {
+    weights: Vec<f64>,
+    beats: Option<Vec<f64>>,
+}
+
+impl ChordWeights {
+    /// The same weight for each of `length` chords
+    fn uniform(weight: f64, length: usize) -> Self {
+        Self {
+            weights: vec![weight; length],
+            beats: None,
+        }
+    }
+}
+
+/// How the trie handles a pattern identical to one already stored
+///
+/// Patterns are identical when they hold the same chords in the same order
+/// under the same key tonic, whatever their source ids.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DuplicatePolicy {
+    /// Store every copy, counting duplicates in the statistics
+    #[default]
+    Track,
+    /// Keep one copy's counts, adding the duplicate's reference to it
+    Merge,
+    /// Refuse duplicates with `AiError::InvalidPattern`
+    Reject,
+}
+
+/// How trie searches compare query chords with stored ones
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MatchMode {
+    /// Match scale degrees, whatever key a pattern was stored in
+    #[default]
+    Relative,
+    /// Match only patterns stored in the query's key tonic
+    Absolute,
+    /// Absolute matches first, then relative matches for other chords
+    Both,
+}
+
+/// Pattern rejected by [`ChordProgressionTrie::add_patterns_bulk`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternRejection {
+    /// Position of the pattern in the input
+    pub index: usize,
+    /// Source identifier of the pattern
+    pub source_id: String,
+    /// Why the pattern was rejected
+    pub error: AiError,
+}
+
+/// Outcome of [`ChordProgressionTrie::add_patterns_bulk`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BulkAddReport {
+    /// Number of patterns stored
+    pub added: usize,
+    /// Patterns identical to one already stored, including earlier in the batch
+    pub duplicates: usize,
+    /// Patterns that failed validation, in input order
+    pub rejected: Vec<PatternRejection>,
+}
+
+impl BulkAddReport {
+    /// Whether every pattern was stored
+    pub fn is_complete(&self) -> bool {
+        self.rejected.is_empty()
+    }
+}
+
+/// Scale-specific trie branches keyed by the mode naming each branch
+type ScaleBranches = AHashMap<Mode, TrieArena>;
+
+/// Stored chord with its total occurrence count
+pub type ChordCount = (ChordBinary, u64);
+
+/// Complete pattern with how many times it was added
+type CountedPattern = (Vec<ChordBinary>, u64);
+
+/// Stored patterns and distinct chords of one key
+type KeyTally = (u64, std::collections::HashSet<ChordBinary>);
+
+/// Content hashes of stored patterns
+///
+/// Hashes present when a snapshot was taken are frozen in `shared`, which the
+/// trie and its snapshots hold without copying; later hashes go to `own`.
 #[derive(Debug, Clone)]
-pub struct TrieNode {
-    /// Occurrence frequency at this node
-    pub count: u32,
+struct ContentHashes {
+    shared: Arc<AHashSet<u32>>,
+    own: AHashSet<u32>,
+}
 
-    /// Child nodes indexed by serialized chord keys
-    pub children: AHashMap<ChordBinary, TrieNode>,
+impl Default for ContentHashes {
+    fn default() -> Self {
+        Self {
+            shared: Arc::new(AHashSet::new()),
+            own: AHashSet::new(),
+        }
+    }
+}
 
-    /// References to source data
-    pub id_list: SmallVec<[Reference; 4]>,
+impl ContentHashes {
+    /// Record `hash`, returning whether it was new
+    fn insert(&mut self, hash: u32) -> bool {
+        !self.shared.contains(&hash) && self.own.insert(hash)
+    }
 
-    /// Popularity ranking (1-based, calculated)
-    pub rank: u32,
+    /// Freeze the hashes recorded so far and return a copy sharing them
+    fn snapshot(&mut self) -> Self {
+        if !self.own.is_empty() {
+            let shared = std::mem::replace(&mut self.shared, Arc::new(AHashSet::new()));
+            let mut shared = Arc::try_unwrap(shared).unwrap_or_else(|shared| (*shared).clone());
+            shared.extend(self.own.drain());
+            self.shared = Arc::new(shared);
+        }
+        self.clone()
+    }
 
-    /// Whether to include key tonic in references
-    pub include_key_tonic: bool,
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.own.shrink_to_fit();
+    }
 }
 
 /// Chord progression trie for pattern storage
 #[derive(Debug)]
 pub struct ChordProgressionTrie {
-    /// Root node
-    root: Arc<RwLock<TrieNode>>,
+    /// Pattern tree of every stored pattern
+    root: Arc<RwLock<TrieArena>>,
 
     /// Scale-specific branches
-    scale_branches: Arc<RwLock<AHashMap<String, TrieNode>>>,
+    scale_branches: Arc<RwLock<ScaleBranches>>,
 
     /// Total patterns stored
     total_patterns: Arc<RwLock<u64>>,
 
+    /// Handling of patterns identical to stored ones
+    duplicate_policy: DuplicatePolicy,
+
+    /// Content hashes of the patterns added since the tree was last replaced
+    content_hashes: Arc<RwLock<ContentHashes>>,
+
+    /// Duplicate patterns seen since the tree was last replaced
+    duplicate_patterns: Arc<RwLock<u64>>,
+
     /// Memory usage tracking
     #[allow(dead_code)]
     memory_usage_bytes: Arc<RwLock<u64>>,
 }
 
+/// Stored chords grouped by the pitch class of their bass
+///
+/// Built by [`ChordProgressionTrie::bass_note_index`]; pitch classes count
+/// semitones above the tonic.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BassNoteIndex {
+    buckets: [Vec<ChordCount>; 12],
+}
+
+impl BassNoteIndex {
+    /// Chords over `pitch_class` (taken modulo 12), most frequent first
+    pub fn chords(&self, pitch_class: u8) -> &[ChordCount] {
+        &self.buckets[(pitch_class % 12) as usize]
+    }
+
+    /// Occurrences of any chord over `pitch_class`
+    pub fn total(&self, pitch_class: u8) -> u64 {
+        self.chords(pitch_class)
+            .iter()
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    /// Whether no chord was indexed
+    pub fn is_empty(&self) -> bool {
+        self.buckets.iter().all(Vec::is_empty)
+    }
+}
+
 /// Pattern search result
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PatternResult {
     /// Serialized chord that matches
     pub serialized_chord: ChordBinary,
@@ -66,6 +243,10 @@ pub struct PatternResult {
     /// Frequency count
     pub count: u32,
 
+    /// Frequency weighted by chord duration and pattern weight
+    #[serde(default)]
+    pub duration_weight: f64,
+
     /// Popularity rank
     pub rank: u32,
 
@@ -77,117 +258,352 @@ pub struct PatternResult {
 
     /// Computed relevance weight
     pub weight: f64,
-}
 
-impl Default for TrieNode {
-    fn default() -> Self {
-        Self {
-            count: 0,
-            children: AHashMap::new(),
-            id_list: SmallVec::new(),
-            rank: 0,
-            include_key_tonic: false,
-        }
-    }
-}
+    /// Scale branch the result was found in; `None` for the global trie
+    #[serde(default)]
+    pub branch: Option<String>,
 
-impl TrieNode {
-    /// Create a new trie node
-    pub fn new(include_key_tonic: bool) -> Self {
-        Self {
-            include_key_tonic,
-            ..Default::default()
-        }
-    }
-
-    /// Get memory usage in bytes
-    pub fn memory_usage(&self) -> u64 {
-        let base_size = std::mem::size_of::<Self>() as u64;
-        let children_size = self.children.capacity() as u64
-            * (std::mem::size_of::<ChordBinary>() + std::mem::size_of::<TrieNode>()) as u64;
-        let id_list_size = self.id_list.capacity() as u64 * std::mem::size_of::<Reference>() as u64;
-
-        base_size
-            + children_size
-            + id_list_size
-            + self
-                .children
-                .values()
-                .map(|child| child.memory_usage())
-                .sum::<u64>()
-    }
-
-    /// Calculate ranks for all children based on count
-    pub fn calculate_ranks(&mut self) {
-        // Collect keys and counts separately to avoid borrowing conflicts
-        let mut key_count_pairs: Vec<(ChordBinary, u32)> = self
-            .children
-            .iter()
-            .map(|(key, node)| (*key, node.count))
-            .collect();
-
-        // Sort by count descending
-        key_count_pairs.sort_by(|a, b| b.1.cmp(&a.1));
-
-        // Assign ranks
-        for (rank, (key, _)) in key_count_pairs.iter().enumerate() {
-            if let Some(child) = self.children.get_mut(key) {
-                child.rank = (rank + 1) as u32;
-                child.calculate_ranks(); // Recursively calculate for children
-            }
-        }
-    }
+    /// Mean duration in beats of the chord after the matched context, over
+    /// the occurrences added with timing; `None` when none were timed
+    #[serde(default)]
+    pub mean_duration: Option<f64>,
 }
 
 impl ChordProgressionTrie {
     /// Create a new chord progression trie
     pub fn new() -> Self {
+        Self::with_duplicate_policy(DuplicatePolicy::default())
+    }
+
+    /// Create a trie that handles duplicate patterns according to `policy`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{ChordProgressionTrie, DuplicatePolicy};
+    /// use composer_core::Chord;
+    ///
+    /// let trie = ChordProgressionTrie::with_duplicate_policy(DuplicatePolicy::Merge);
+    /// let cadence = [Chord::new(5, 7)?, Chord::new(1, 5)?];
+    /// trie.add_pattern(&cadence, "a".to_string(), Some("C".parse()?))?;
+    /// trie.add_pattern(&cadence, "b".to_string(), Some("C".parse()?))?;
+    ///
+    /// let stats = trie.statistics();
+    /// assert_eq!(stats.total_patterns, 1);
+    /// assert_eq!(stats.duplicate_patterns, 1);
+    /// assert_eq!(trie.stored_progressions().len(), 2);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn with_duplicate_policy(policy: DuplicatePolicy) -> Self {
         Self {
-            root: Arc::new(RwLock::new(TrieNode::new(false))),
+            root: Arc::new(RwLock::new(TrieArena::new(false))),
             scale_branches: Arc::new(RwLock::new(AHashMap::new())),
             total_patterns: Arc::new(RwLock::new(0)),
+            duplicate_policy: policy,
+            content_hashes: Arc::new(RwLock::new(ContentHashes::default())),
+            duplicate_patterns: Arc::new(RwLock::new(0)),
             memory_usage_bytes: Arc::new(RwLock::new(0)),
         }
     }
 
+    /// Handling of patterns identical to stored ones
+    pub fn duplicate_policy(&self) -> DuplicatePolicy {
+        self.duplicate_policy
+    }
+
+    /// Content hash of a serialized pattern in a key.
+    ///
+    /// Identical chords in the same order in the same key hash alike; the
+    /// source id does not take part.
+    pub fn content_hash(pattern: &[ChordBinary], key_tonic: Option<&Key>) -> u32 {
+        let chords: String = pattern
+            .iter()
+            .flatten()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        let key = key_tonic.map(Key::to_string).unwrap_or_default();
+        fold_hash(fast_hash(&chords), &key)
+    }
+
     /// Add a chord progression pattern to the trie
+    ///
+    /// Patterns with a key are also stored in the branch of their mode (see
+    /// [`Self::scale_branch`]) for scale-aware searches.
+    ///
+    /// A pattern identical to a stored one is counted as a duplicate and then
+    /// handled according to the trie's [`DuplicatePolicy`]; under
+    /// `DuplicatePolicy::Reject` it fails with `AiError::InvalidPattern`.
     pub fn add_pattern(
         &self,
         pattern: &[Chord],
         source_id: String,
-        key_tonic: Option<String>,
+        key_tonic: Option<Key>,
     ) -> AiResult<()> {
-        if pattern.is_empty() {
-            return Err(AiError::InvalidPattern {
-                reason: "Pattern cannot be empty".to_string(),
+        let serialized_pattern = Self::serialize_pattern(pattern)?;
+
+        // Add to the trie
+        let reference = Reference::new(source_id, key_tonic);
+
+        self.add_serialized_pattern(&serialized_pattern, None, reference)
+    }
+
+    /// Add a chord progression pattern that counts `weight` times as much as
+    /// one added with [`Self::add_pattern`].
+    ///
+    /// The weight scales the duration-weighted counts that ranks, relevance
+    /// weights and suggestion scores follow, so a canonical corpus can outweigh
+    /// user-submitted patterns; raw occurrence counts are unaffected. Fails with
+    /// `AiError::InvalidPattern` unless `weight` is positive and finite.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::ChordProgressionTrie;
+    /// use composer_core::Chord;
+    ///
+    /// let trie = ChordProgressionTrie::new();
+    /// let (tonic, subdominant) = (Chord::new(1, 5)?, Chord::new(4, 5)?);
+    /// trie.add_weighted_pattern(&[tonic.clone(), subdominant.clone()], "canon".to_string(), None, 3.0)?;
+    /// trie.add_pattern(&[tonic.clone(), Chord::new(5, 5)?], "user".to_string(), None)?;
+    /// trie.add_pattern(&[tonic.clone(), Chord::new(5, 5)?], "user".to_string(), None)?;
+    /// trie.calculate_all_ranks();
+    ///
+    /// let next = trie.search_with_wildcard(&[Some(tonic), None], 10)?;
+    /// assert_eq!(next[0].serialized_chord, composer_serialization::serialize_chord(&subdominant)?);
+    /// assert_eq!((next[0].count, next[0].rank, next[0].duration_weight), (1, 1, 3.0));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn add_weighted_pattern(
+        &self,
+        pattern: &[Chord],
+        source_id: String,
+        key_tonic: Option<Key>,
+        weight: f64,
+    ) -> AiResult<()> {
+        Self::check_pattern_weight(weight)?;
+        let serialized_pattern = Self::serialize_pattern(pattern)?;
+        let weights = ChordWeights::uniform(weight, serialized_pattern.len());
+
+        let reference = Reference::new(source_id, key_tonic);
+        self.add_serialized_pattern(&serialized_pattern, Some(&weights), reference)
+    }
+
+    /// Add a pattern of already-serialized chords to the trie.
+    ///
+    /// Skips chord serialization, so callers holding chord binaries (for
+    /// example from a token library) never build `Chord` values. The binaries
+    /// are stored as given; callers are responsible for their validity.
+    pub fn add_binary_pattern(
+        &self,
+        pattern: &[ChordBinary],
+        source_id: String,
+        key_tonic: Option<Key>,
+    ) -> AiResult<()> {
+        Self::check_pattern_length(pattern.len())?;
+
+        let reference = Reference::new(source_id, key_tonic);
+        self.add_serialized_pattern(pattern, None, reference)
+    }
+
+    /// Add many patterns in one pass, recalculating ranks once.
+    ///
+    /// Every pattern is validated before any is stored; patterns that fail are
+    /// reported by their position in the input and skipped, and the rest are
+    /// loaded under a single write lock. Ranks are recalculated once at the end
+    /// rather than after each pattern. Duplicates are counted in the report and
+    /// handled according to the trie's [`DuplicatePolicy`]; rejected duplicates
+    /// are reported alongside invalid patterns.
+    pub fn add_patterns_bulk<I>(&self, patterns: I) -> BulkAddReport
+    where
+        I: IntoIterator<Item = TrainingPattern>,
+    {
+        let mut report = BulkAddReport::default();
+        let mut accepted = Vec::new();
+        for (index, (pattern, source_id, key_tonic)) in patterns.into_iter().enumerate() {
+            match Self::serialize_pattern(&pattern) {
+                Ok(serialized) => accepted.push((
+                    index,
+                    serialized,
+                    None,
+                    Reference::new(source_id, key_tonic),
+                )),
+                Err(error) => report.rejected.push(PatternRejection {
+                    index,
+                    source_id,
+                    error,
+                }),
+            }
+        }
+
+        self.insert_bulk(accepted, &mut report);
+        report
+    }
+
+    /// Add many weighted patterns in one pass, recalculating ranks once.
+    ///
+    /// Each pattern counts as with [`Self::add_weighted_pattern`]; validation
+    /// and reporting are as for [`Self::add_patterns_bulk`], with invalid
+    /// weights rejected alongside invalid patterns.
+    pub fn add_weighted_patterns_bulk<I>(&self, patterns: I) -> BulkAddReport
+    where
+        I: IntoIterator<Item = WeightedPattern>,
+    {
+        let mut report = BulkAddReport::default();
+        let mut accepted = Vec::new();
+        for (index, ((pattern, source_id, key_tonic), weight)) in patterns.into_iter().enumerate() {
+            let validated =
+                Self::check_pattern_weight(weight).and_then(|_| Self::serialize_pattern(&pattern));
+            match validated {
+                Ok(serialized) => accepted.push((
+                    index,
+                    serialized.clone(),
+                    Some(ChordWeights::uniform(weight, serialized.len())),
+                    Reference::new(source_id, key_tonic),
+                )),
+                Err(error) => report.rejected.push(PatternRejection {
+                    index,
+                    source_id,
+                    error,
+                }),
+            }
+        }
+
+        self.insert_bulk(accepted, &mut report);
+        report
+    }
+
+    /// Add many timed patterns in one pass, weighting counts by duration.
+    ///
+    /// Each chord counts in proportion to how long it sounds, scaled so the
+    /// chords of a pattern still add up to one count each on average: a whole
+    /// note next to a passing eighth note counts eight times as much, but the
+    /// pattern as a whole weighs the same as an untimed one. Raw occurrence
+    /// counts are kept alongside; ranks and relevance weights follow the
+    /// duration-weighted counts, and the durations themselves are kept for
+    /// [`PatternResult::mean_duration`]. Validation and reporting are as for
+    /// [`Self::add_patterns_bulk`], and durations must be positive and finite
+    /// with one per chord.
+    pub fn add_timed_patterns_bulk<I>(&self, patterns: I) -> BulkAddReport
+    where
+        I: IntoIterator<Item = (TimedPattern, String, Option<Key>)>,
+    {
+        let mut report = BulkAddReport::default();
+        let mut accepted = Vec::new();
+        for (index, ((pattern, durations), source_id, key_tonic)) in
+            patterns.into_iter().enumerate()
+        {
+            let validated = Self::serialize_pattern(&pattern).and_then(|serialized| {
+                Self::duration_weights(&durations, serialized.len()).map(|weights| {
+                    let weights = ChordWeights {
+                        weights,
+                        beats: Some(durations),
+                    };
+                    (serialized, weights)
+                })
             });
+            match validated {
+                Ok((serialized, weights)) => accepted.push((
+                    index,
+                    serialized,
+                    Some(weights),
+                    Reference::new(source_id, key_tonic),
+                )),
+                Err(error) => report.rejected.push(PatternRejection {
+                    index,
+                    source_id,
+                    error,
+                }),
+            }
         }
 
-        if pattern.len() > 20 {
+        self.insert_bulk(accepted, &mut report);
+        report
+    }
+
+    /// Store validated patterns under one write lock and recalculate ranks
+    fn insert_bulk(&self, accepted: Vec<PendingPattern>, report: &mut BulkAddReport) {
+        let duplicates_before = *self.duplicate_patterns.read();
+        {
+            let mut root = self.root.write();
+            for (index, serialized, weights, reference) in accepted {
+                match self.insert_pattern(&mut root, &serialized, weights.as_ref(), &reference) {
+                    Ok(true) => report.added += 1,
+                    Ok(false) => {},
+                    Err(error) => report.rejected.push(PatternRejection {
+                        index,
+                        source_id: reference.id,
+                        error,
+                    }),
+                }
+            }
+        }
+        report.duplicates = (*self.duplicate_patterns.read() - duplicates_before) as usize;
+        report.rejected.sort_by_key(|rejection| rejection.index);
+
+        self.calculate_all_ranks();
+    }
+
+    /// Reject pattern weights that are not positive and finite
+    fn check_pattern_weight(weight: f64) -> AiResult<()> {
+        if !weight.is_finite() || weight <= 0.0 {
             return Err(AiError::InvalidPattern {
-                reason: format!("Pattern too long: {} (max: {})", pattern.len(), 20),
+                reason: format!("Pattern weight must be positive, got {}", weight),
             });
         }
+        Ok(())
+    }
 
-        // Serialize the pattern
-        let mut serialized_pattern = Vec::new();
-        for chord in pattern {
-            let binary = serialize_chord(chord).map_err(|e| AiError::InvalidPattern {
-                reason: format!("Chord serialization failed: {}", e),
-            })?;
-            serialized_pattern.push(binary);
+    /// Per-chord weights from durations, normalized to a mean of 1.0
+    fn duration_weights(durations: &[f64], length: usize) -> AiResult<Vec<f64>> {
+        if durations.len() != length {
+            return Err(AiError::InvalidPattern {
+                reason: format!(
+                    "Expected {} chord durations, got {}",
+                    length,
+                    durations.len()
+                ),
+            });
+        }
+        if let Some(duration) = durations
+            .iter()
+            .find(|duration| !duration.is_finite() || **duration <= 0.0)
+        {
+            return Err(AiError::InvalidPattern {
+                reason: format!("Chord duration must be positive, got {}", duration),
+            });
         }
 
-        // Add to the trie
-        let reference = Reference {
-            id: source_id,
-            key_tonic,
-        };
+        let mean = durations.iter().sum::<f64>() / length as f64;
+        Ok(durations.iter().map(|duration| duration / mean).collect())
+    }
 
-        self.add_serialized_pattern(&serialized_pattern, reference)?;
+    /// Validate a pattern and serialize its chords
+    fn serialize_pattern(pattern: &[Chord]) -> AiResult<Vec<ChordBinary>> {
+        Self::check_pattern_length(pattern.len())?;
 
-        // Update counters
-        *self.total_patterns.write() += 1;
+        pattern
+            .iter()
+            .map(|chord| {
+                serialize_chord(chord).map_err(|e| AiError::InvalidPattern {
+                    reason: format!("Chord serialization failed: {}", e),
+                })
+            })
+            .collect()
+    }
+
+    /// Reject empty patterns and patterns longer than 20 chords
+    fn check_pattern_length(length: usize) -> AiResult<()> {
+        if length == 0 {
+            return Err(AiError::InvalidPattern {
+                reason: "Pattern cannot be empty".to_string(),
+            });
+        }
+
+        if length > 20 {
+            return Err(AiError::InvalidPattern {
+                reason: format!("Pattern too long: {} (max: {})", length, 20),
+            });
+        }
 
         Ok(())
     }
@@ -196,50 +612,281 @@ impl ChordProgressionTrie {
     fn add_serialized_pattern(
         &self,
         pattern: &[ChordBinary],
+        weights: Option<&ChordWeights>,
         reference: Reference,
     ) -> AiResult<()> {
-        // Simple recursive implementation
-        self.add_pattern_recursive(&mut self.root.write(), pattern, 0, &reference)
+        self.insert_pattern(&mut self.root.write(), pattern, weights, &reference)
+            .map(|_| ())
     }
 
-    /// Recursively add pattern to trie
-    fn add_pattern_recursive(
+    /// Store a pattern under the duplicate policy, updating the pattern and
+    /// duplicate totals.
+    ///
+    /// Patterns with a key tonic are stored in their scale branch as well.
+    /// Returns whether the pattern's counts were added; merged duplicates only
+    /// add their reference.
+    fn insert_pattern(
         &self,
-        node: &mut TrieNode,
+        root: &mut TrieArena,
         pattern: &[ChordBinary],
-        depth: usize,
+        weights: Option<&ChordWeights>,
         reference: &Reference,
-    ) -> AiResult<()> {
-        // Update current node
-        node.count += 1;
-        if !node.id_list.iter().any(|r| r.id == reference.id) {
-            node.id_list.push(reference.clone());
+    ) -> AiResult<bool> {
+        let hash = Self::content_hash(pattern, reference.key_tonic.as_ref());
+        // The hash only narrows the search; the tree confirms the match
+        let duplicate =
+            !self.content_hashes.write().insert(hash) && Self::ends_pattern(root, pattern);
+        if duplicate {
+            *self.duplicate_patterns.write() += 1;
+            match self.duplicate_policy {
+                DuplicatePolicy::Track => {},
+                DuplicatePolicy::Merge => {
+                    Self::merge_reference(root, pattern, reference)?;
+                    if let Some(key_tonic) = &reference.key_tonic {
+                        if let Some(branch) = self
+                            .scale_branches
+                            .write()
+                            .get_mut(&Self::scale_branch(key_tonic.mode))
+                        {
+                            Self::merge_reference(branch, pattern, reference)?;
+                        }
+                    }
+                    return Ok(false);
+                },
+                DuplicatePolicy::Reject => {
+                    return Err(AiError::InvalidPattern {
+                        reason: format!("Pattern {} duplicates a stored pattern", reference.id),
+                    });
+                },
+            }
         }
 
-        // If we've processed the entire pattern, we're done
-        if depth >= pattern.len() {
-            return Ok(());
+        Self::add_pattern_path(root, pattern, weights, reference)?;
+        if let Some(key_tonic) = &reference.key_tonic {
+            let mut branches = self.scale_branches.write();
+            let branch = branches
+                .entry(Self::scale_branch(key_tonic.mode))
+                .or_insert_with(|| TrieArena::new(root.include_key_tonic()));
+            Self::add_pattern_path(branch, pattern, weights, reference)?;
         }
+        *self.total_patterns.write() += 1;
+        Ok(true)
+    }
 
-        // Get the next chord in the pattern
-        let chord_binary = pattern[depth];
+    /// Whether a stored pattern ends at the node `pattern` leads to
+    fn ends_pattern(root: &TrieArena, pattern: &[ChordBinary]) -> bool {
+        let Some(node) = root.path(pattern) else {
+            return false;
+        };
+        let continuing: u32 = root.children(node).map(|(_, child)| child.count).sum();
+        root.node(node).count > continuing
+    }
 
-        // Create child if it doesn't exist
-        if !node.children.contains_key(&chord_binary) {
-            node.children
-                .insert(chord_binary, TrieNode::new(node.include_key_tonic));
+    /// Add a reference along a stored pattern's path without adding counts
+    fn merge_reference(
+        tree: &mut TrieArena,
+        pattern: &[ChordBinary],
+        reference: &Reference,
+    ) -> AiResult<()> {
+        let mut node = ROOT_NODE;
+        tree.add_reference(node, reference)?;
+        for key in pattern {
+            match tree.child(node, key) {
+                Some(child) => node = child,
+                None => break,
+            }
+            tree.add_reference(node, reference)?;
         }
+        Ok(())
+    }
 
-        // Recursively add to child
-        let child = node.children.get_mut(&chord_binary).unwrap();
-        self.add_pattern_recursive(child, pattern, depth + 1, reference)
+    /// Add a pattern's counts and reference along its path, creating nodes as needed
+    ///
+    /// `weights` holds one duration weight per chord, and the chord durations
+    /// of timed patterns; without it every chord weighs 1.0. The node at
+    /// `depth` stands for the chord at `depth - 1`.
+    fn add_pattern_path(
+        tree: &mut TrieArena,
+        pattern: &[ChordBinary],
+        weights: Option<&ChordWeights>,
+        reference: &Reference,
+    ) -> AiResult<()> {
+        let mut node = ROOT_NODE;
+        for depth in 0..=pattern.len() {
+            if depth > 0 {
+                node = tree.child_or_insert(node, pattern[depth - 1])?;
+            }
+
+            let index = depth.checked_sub(1);
+            let target = tree.node_mut(node);
+            target.count += 1;
+            target.duration_weight += index
+                .and_then(|index| weights?.weights.get(index))
+                .copied()
+                .unwrap_or(1.0);
+            if let Some(beats) = index.and_then(|index| weights?.beats.as_ref()?.get(index)) {
+                target.beats += beats;
+                target.timed_count += 1;
+            }
+            tree.add_reference(node, reference)?;
+        }
+        Ok(())
     }
 
     /// Search for patterns with wildcard support
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip(self, pattern),
+            fields(pattern_len = pattern.len())
+        )
+    )]
     pub fn search_with_wildcard(
         &self,
         pattern: &[Option<Chord>],
         limit: usize,
+    ) -> AiResult<Vec<PatternResult>> {
+        self.search_node_with_wildcard(&self.root.read(), pattern, None, limit)
+    }
+
+    /// Search with wildcard support in the given matching mode.
+    ///
+    /// Chords are stored as scale degrees, so [`MatchMode::Relative`] matches
+    /// patterns from every key. [`MatchMode::Absolute`] only matches patterns
+    /// stored in `key_tonic`, counting their sources, for queries where
+    /// absolute pitch matters such as bass lines. [`MatchMode::Both`] lists the
+    /// absolute matches first, then relative matches for other chords. Fails
+    /// with `AiError::InvalidPattern` when an absolute mode has no key.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{ChordProgressionTrie, MatchMode};
+    /// use composer_core::Chord;
+    ///
+    /// let trie = ChordProgressionTrie::new();
+    /// let (i, iv, v) = (Chord::new(1, 5)?, Chord::new(4, 5)?, Chord::new(5, 5)?);
+    /// let (c_major, g_major) = ("C".parse()?, "G".parse()?);
+    /// trie.add_pattern(&[i.clone(), iv], "a".to_string(), Some(c_major))?;
+    /// trie.add_pattern(&[i.clone(), v.clone()], "b".to_string(), Some(g_major))?;
+    /// trie.add_pattern(&[i.clone(), v], "c".to_string(), Some(g_major))?;
+    /// let query = [Some(i), None];
+    ///
+    /// let relative = trie.search_with_mode(&query, 10, MatchMode::Relative, None)?;
+    /// assert_eq!(relative.len(), 2);
+    /// let absolute = trie.search_with_mode(&query, 10, MatchMode::Absolute, Some(&c_major))?;
+    /// assert_eq!((absolute.len(), absolute[0].count), (1, 1));
+    /// let both = trie.search_with_mode(&query, 10, MatchMode::Both, Some(&c_major))?;
+    /// assert_eq!(both[0].serialized_chord, absolute[0].serialized_chord);
+    /// assert_eq!(both.len(), 2);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip(self, pattern),
+            fields(pattern_len = pattern.len())
+        )
+    )]
+    pub fn search_with_mode(
+        &self,
+        pattern: &[Option<Chord>],
+        limit: usize,
+        mode: MatchMode,
+        key_tonic: Option<&Key>,
+    ) -> AiResult<Vec<PatternResult>> {
+        if mode == MatchMode::Relative {
+            return self.search_with_wildcard(pattern, limit);
+        }
+        let key = key_tonic.ok_or_else(|| AiError::InvalidPattern {
+            reason: format!("{:?} matching requires a key", mode),
+        })?;
+
+        let root = self.root.read();
+        let mut results = self.search_node_with_wildcard(&root, pattern, Some(key), limit)?;
+        if mode == MatchMode::Both && results.len() < limit {
+            let found: Vec<ChordBinary> = results.iter().map(|r| r.serialized_chord).collect();
+            let remaining = limit - results.len();
+            let relative = self.search_node_with_wildcard(&root, pattern, None, limit)?;
+            results.extend(
+                relative
+                    .into_iter()
+                    .filter(|result| !found.contains(&result.serialized_chord))
+                    .take(remaining),
+            );
+        }
+        Ok(results)
+    }
+
+    /// Search the branch of one mode with wildcard support.
+    ///
+    /// Only patterns added with a key in a mode sharing that branch are
+    /// searched (see [`Self::scale_branch`]); each result names the branch.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::ChordProgressionTrie;
+    /// use composer_core::{Chord, Mode};
+    ///
+    /// let trie = ChordProgressionTrie::new();
+    /// let (i, iv, v) = (Chord::new(1, 5)?, Chord::new(4, 5)?, Chord::new(5, 5)?);
+    /// trie.add_pattern(&[i.clone(), iv], "major".to_string(), Some("C".parse()?))?;
+    /// trie.add_pattern(&[i.clone(), v], "minor".to_string(), Some("Am".parse()?))?;
+    ///
+    /// let results = trie.search_scale_with_wildcard(Mode::Minor, &[Some(i.clone()), None], 10)?;
+    /// assert_eq!(results.len(), 1);
+    /// assert_eq!(results[0].branch.as_deref(), Some("minor"));
+    /// assert_eq!(trie.search_with_wildcard(&[Some(i), None], 10)?.len(), 2);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip(self, pattern),
+            fields(pattern_len = pattern.len())
+        )
+    )]
+    pub fn search_scale_with_wildcard(
+        &self,
+        mode: Mode,
+        pattern: &[Option<Chord>],
+        limit: usize,
+    ) -> AiResult<Vec<PatternResult>> {
+        let branch_mode = Self::scale_branch(mode);
+        let branches = self.scale_branches.read();
+        let Some(branch) = branches.get(&branch_mode) else {
+            return Ok(Vec::new());
+        };
+        let mut results = self.search_node_with_wildcard(branch, pattern, None, limit)?;
+        for result in &mut results {
+            result.branch = Some(branch_mode.name().to_string());
+        }
+        Ok(results)
+    }
+
+    /// Scale branch that patterns in a mode are stored in: [`Mode::Minor`]
+    /// for modes with a minor third (minor, harmonic minor, dorian, phrygian,
+    /// locrian), [`Mode::Major`] otherwise
+    pub fn scale_branch(mode: Mode) -> Mode {
+        if mode.is_minor() {
+            Mode::Minor
+        } else {
+            Mode::Major
+        }
+    }
+
+    /// Wildcard search from `node`, sorted by weight; with a `key`, only
+    /// patterns stored in that key match
+    fn search_node_with_wildcard(
+        &self,
+        tree: &TrieArena,
+        pattern: &[Option<Chord>],
+        key: Option<&Key>,
+        limit: usize,
     ) -> AiResult<Vec<PatternResult>> {
         let mut results = Vec::new();
 
@@ -260,7 +907,15 @@ impl ChordProgressionTrie {
         }
 
         // Search the trie
-        self.search_trie_recursive(&self.root.read(), &binary_pattern, 0, &mut results, limit)?;
+        self.search_trie_recursive(
+            tree,
+            ROOT_NODE,
+            &binary_pattern,
+            0,
+            key,
+            &mut results,
+            limit,
+        )?;
 
         // Sort by weight descending
         results.sort_by(|a, b| {
@@ -275,12 +930,16 @@ impl ChordProgressionTrie {
         Ok(results)
     }
 
-    /// Recursive trie search helper
+    /// Recursive trie search helper; with a `key`, only patterns stored in
+    /// that key match
+    #[allow(clippy::too_many_arguments)]
     fn search_trie_recursive(
         &self,
-        node: &TrieNode,
+        tree: &TrieArena,
+        node: NodeId,
         pattern: &[Option<ChordBinary>],
         pattern_index: usize,
+        key: Option<&Key>,
         results: &mut Vec<PatternResult>,
         limit: usize,
     ) -> AiResult<()> {
@@ -296,23 +955,18 @@ impl ChordProgressionTrie {
         match &pattern[pattern_index] {
             Some(chord_binary) => {
                 // Look for exact match
-                if let Some(child) = node.children.get(chord_binary) {
+                if let Some(child) = tree.child(node, chord_binary) {
                     if pattern_index == pattern.len() - 1 {
                         // End of pattern, add result
-                        results.push(PatternResult {
-                            serialized_chord: *chord_binary,
-                            count: child.count,
-                            rank: child.rank,
-                            relative_count: child.count as f64 / node.count.max(1) as f64,
-                            id_list: child.id_list.to_vec(),
-                            weight: self.calculate_weight(child.count, child.rank),
-                        });
+                        results.extend(self.pattern_result(tree, node, child, key));
                     } else {
                         // Continue searching
                         self.search_trie_recursive(
+                            tree,
                             child,
                             pattern,
                             pattern_index + 1,
+                            key,
                             results,
                             limit,
                         )?;
@@ -321,23 +975,18 @@ impl ChordProgressionTrie {
             },
             None => {
                 // Wildcard - try all children
-                for (chord_binary, child) in &node.children {
+                for (child, _) in tree.children(node) {
                     if pattern_index == pattern.len() - 1 {
                         // End of pattern, add result
-                        results.push(PatternResult {
-                            serialized_chord: *chord_binary,
-                            count: child.count,
-                            rank: child.rank,
-                            relative_count: child.count as f64 / node.count.max(1) as f64,
-                            id_list: child.id_list.to_vec(),
-                            weight: self.calculate_weight(child.count, child.rank),
-                        });
+                        results.extend(self.pattern_result(tree, node, child, key));
                     } else {
                         // Continue searching
                         self.search_trie_recursive(
+                            tree,
                             child,
                             pattern,
                             pattern_index + 1,
+                            key,
                             results,
                             limit,
                         )?;
@@ -353,22 +1002,176 @@ impl ChordProgressionTrie {
         Ok(())
     }
 
+    /// Result for reaching `child` from `node`.
+    ///
+    /// With a `key`, counts are the number of sources stored in that key,
+    /// the duration weight is scaled to match, and `None` is returned if no
+    /// such source reaches `child`.
+    fn pattern_result(
+        &self,
+        tree: &TrieArena,
+        node: NodeId,
+        child: NodeId,
+        key: Option<&Key>,
+    ) -> Option<PatternResult> {
+        let (parent, child_node) = (tree.node(node), tree.node(child));
+        let (count, parent_count, duration_weight, id_list) = match key {
+            None => (
+                child_node.count,
+                parent.count,
+                child_node.duration_weight,
+                tree.references(child).cloned().collect(),
+            ),
+            Some(key) => {
+                let in_key = |r: &&Reference| r.key_tonic.as_ref() == Some(key);
+                let id_list: Vec<Reference> =
+                    tree.references(child).filter(in_key).cloned().collect();
+                if id_list.is_empty() {
+                    return None;
+                }
+                let count = id_list.len() as u32;
+                let parent_count = tree.references(node).filter(in_key).count() as u32;
+                let duration_weight = child_node.duration_weight * f64::from(count)
+                    / f64::from(child_node.count.max(1));
+                (count, parent_count, duration_weight, id_list)
+            },
+        };
+
+        Some(PatternResult {
+            serialized_chord: child_node.key,
+            count,
+            duration_weight,
+            rank: child_node.rank,
+            relative_count: count as f64 / parent_count.max(1) as f64,
+            id_list,
+            weight: self.calculate_weight(duration_weight, child_node.rank),
+            branch: None,
+            mean_duration: (child_node.timed_count > 0)
+                .then(|| child_node.beats / f64::from(child_node.timed_count)),
+        })
+    }
+
     /// Calculate weight for a pattern result
-    fn calculate_weight(&self, count: u32, rank: u32) -> f64 {
-        let frequency_score = count as f64;
+    fn calculate_weight(&self, duration_weight: f64, rank: u32) -> f64 {
+        let frequency_score = duration_weight;
         let rank_penalty = if rank > 0 { 1.0 / rank as f64 } else { 1.0 };
 
         frequency_score * rank_penalty
     }
 
-    /// Get scale-specific branch
-    pub fn get_scale_branch(&self, scale_name: &str) -> Option<TrieNode> {
-        self.scale_branches.read().get(scale_name).cloned()
+    /// Convert the pattern tree to the `composer_serialization` trie format.
+    ///
+    /// The binary format stores numeric source ids, so references whose id is not a
+    /// `u32` are stored as the id's `fast_hash`. Key tonics are not preserved; use
+    /// [`Self::to_referenced_binary_node`] to keep both.
+    pub fn to_binary_node(&self) -> composer_serialization::TrieNode {
+        binary_node(&self.root.read(), ROOT_NODE)
+    }
+
+    /// Convert the pattern tree to the `composer_serialization` trie format, with
+    /// ids indexing the returned reference table.
+    ///
+    /// Every source id and key survives, written together by
+    /// `composer_serialization::serialize_trie_with_references`.
+    pub fn to_referenced_binary_node(
+        &self,
+    ) -> (
+        composer_serialization::TrieNode,
+        Vec<composer_serialization::TrieReference>,
+    ) {
+        fn convert(tree: &TrieArena, node: NodeId) -> composer_serialization::TrieNode {
+            composer_serialization::TrieNode {
+                node_count: tree.node(node).count,
+                id_list: tree.node(node).references.to_vec(),
+                children: tree
+                    .children(node)
+                    .map(|(id, child)| (child.key.to_vec(), convert(tree, id)))
+                    .collect(),
+            }
+        }
+
+        let tree = self.root.read();
+        let references = tree
+            .reference_table()
+            .map(|reference| composer_serialization::TrieReference {
+                id: reference.id.clone(),
+                key: reference.key_tonic,
+            })
+            .collect();
+        (convert(&tree, ROOT_NODE), references)
+    }
+
+    /// Replace the pattern tree with one loaded from the `composer_serialization` trie format.
+    ///
+    /// Returns the number of patterns loaded.
+    pub fn load_binary_node(&self, binary: &composer_serialization::TrieNode) -> AiResult<u64> {
+        let root = TrieArena::from_binary_node(binary)?;
+        Ok(self.replace_root(root))
+    }
+
+    /// Replace the pattern tree with one from [`Self::to_referenced_binary_node`].
+    ///
+    /// Returns the number of patterns loaded.
+    pub fn load_referenced_binary_node(
+        &self,
+        binary: &composer_serialization::TrieNode,
+        references: &[composer_serialization::TrieReference],
+    ) -> AiResult<u64> {
+        let root = TrieArena::from_referenced_binary_node(binary, references)?;
+        Ok(self.replace_root(root))
+    }
+
+    /// Replace the pattern tree, recalculating ranks and taking the pattern total
+    /// from the root count. Returns the new pattern total.
+    ///
+    /// Scale branches are dropped, and content hashes and the duplicate total
+    /// start over, so patterns in the new tree are neither searched by scale
+    /// nor recognized as duplicates of later additions.
+    pub(crate) fn replace_root(&self, mut root: TrieArena) -> u64 {
+        root.calculate_ranks();
+        let total = root.root().count as u64;
+
+        *self.root.write() = root;
+        *self.total_patterns.write() = total;
+        self.scale_branches.write().clear();
+        self.content_hashes.write().clear();
+        *self.duplicate_patterns.write() = 0;
+        total
     }
 
-    /// Add scale-specific branch
-    pub fn add_scale_branch(&self, scale_name: String, branch: TrieNode) {
-        self.scale_branches.write().insert(scale_name, branch);
+    /// Get the scale branch for a mode or scale name such as "minor", in the
+    /// `composer_serialization` trie format of [`Self::to_binary_node`].
+    ///
+    /// Modes share the branch named by [`Self::scale_branch`].
+    pub fn get_scale_branch(&self, scale_name: &str) -> Option<composer_serialization::TrieNode> {
+        let mode = scale_name.parse::<Mode>().ok()?;
+        self.scale_branches
+            .read()
+            .get(&Self::scale_branch(mode))
+            .map(|branch| binary_node(branch, ROOT_NODE))
+    }
+
+    /// Replace the scale branch for a mode or scale name such as "minor" with
+    /// one in the `composer_serialization` trie format.
+    ///
+    /// The pattern total is unchanged, since every pattern of a branch is also
+    /// stored in the main tree.
+    pub fn add_scale_branch(
+        &self,
+        scale_name: String,
+        branch: composer_serialization::TrieNode,
+    ) -> AiResult<()> {
+        let mode = scale_name
+            .parse::<Mode>()
+            .map_err(|_| AiError::InvalidPattern {
+                reason: format!("Unknown scale branch: {}", scale_name),
+            })?;
+        let mut branch = TrieArena::from_binary_node(&branch)?;
+        branch.calculate_ranks();
+        self.scale_branches
+            .write()
+            .insert(Self::scale_branch(mode), branch);
+        Ok(())
     }
 
     /// Get total number of patterns
@@ -389,6 +1192,225 @@ impl ChordProgressionTrie {
         root_usage + branches_usage
     }
 
+    /// Release spare capacity in the pattern trees and lay out their nodes
+    /// depth-first, so a search reads them front to back.
+    ///
+    /// Worth running once after loading a corpus; patterns can still be added
+    /// afterwards. Returns the memory used before and after.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::ChordProgressionTrie;
+    /// use composer_core::Chord;
+    ///
+    /// let trie = ChordProgressionTrie::new();
+    /// for song in 0..100 {
+    ///     let pattern = [Chord::triad(1)?, Chord::triad(song % 7 + 1)?];
+    ///     trie.add_pattern(&pattern, format!("song-{}", song), Some("C".parse()?))?;
+    /// }
+    ///
+    /// let stats = trie.compact();
+    /// assert!(stats.bytes_after < stats.bytes_before);
+    /// assert_eq!(stats.bytes_after, trie.memory_usage());
+    /// assert_eq!(trie.statistics().total_patterns, 100);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn compact(&self) -> CompactionStats {
+        let bytes_before = self.memory_usage();
+        let mut nodes = 0;
+        {
+            let mut root = self.root.write();
+            root.compact();
+            nodes += root.node_count() as u64;
+
+            let mut branches = self.scale_branches.write();
+            for branch in branches.values_mut() {
+                branch.compact();
+                nodes += branch.node_count() as u64;
+            }
+            branches.shrink_to_fit();
+        }
+        self.content_hashes.write().shrink_to_fit();
+
+        CompactionStats {
+            nodes,
+            bytes_before,
+            bytes_after: self.memory_usage(),
+        }
+    }
+
+    /// Copy of the trie that shares its storage with this one.
+    ///
+    /// Taking a snapshot copies a pointer per 256 nodes rather than the nodes
+    /// themselves. Patterns added to either trie afterwards are not seen by
+    /// the other; each copies only the storage chunks its additions write to.
+    /// Use it to fork a loaded corpus per session.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::ChordProgressionTrie;
+    /// use composer_core::Chord;
+    ///
+    /// let base = ChordProgressionTrie::new();
+    /// let cadence = [Chord::triad(5)?, Chord::triad(1)?];
+    /// base.add_pattern(&cadence, "corpus".to_string(), Some("C".parse()?))?;
+    ///
+    /// let session = base.snapshot();
+    /// let plagal = [Chord::triad(4)?, Chord::triad(1)?];
+    /// session.add_pattern(&plagal, "user".to_string(), Some("C".parse()?))?;
+    ///
+    /// assert_eq!(session.total_patterns(), 2);
+    /// assert_eq!(base.total_patterns(), 1);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn snapshot(&self) -> Self {
+        let root = self.root.read();
+        let content_hashes = self.content_hashes.write().snapshot();
+        let scale_branches = self.scale_branches.read().clone();
+
+        Self {
+            root: Arc::new(RwLock::new(root.clone())),
+            scale_branches: Arc::new(RwLock::new(scale_branches)),
+            total_patterns: Arc::new(RwLock::new(*self.total_patterns.read())),
+            duplicate_policy: self.duplicate_policy,
+            content_hashes: Arc::new(RwLock::new(content_hashes)),
+            duplicate_patterns: Arc::new(RwLock::new(*self.duplicate_patterns.read())),
+            memory_usage_bytes: Arc::new(RwLock::new(0)),
+        }
+    }
+
+    /// Distinct chords stored in the pattern tree with their total occurrence counts.
+    ///
+    /// Counts are summed over every position a chord occupies; the result is sorted
+    /// by serialized chord for reproducible iteration.
+    pub fn chord_vocabulary(&self) -> Vec<ChordCount> {
+        node_vocabulary(&self.root.read())
+    }
+
+    /// Chords stored over each bass pitch class, with their occurrence counts.
+    ///
+    /// A chord's bass is the chord tone its inversion selects, placed on a pitch
+    /// class above the tonic by `scale_fingerprint`. With a `mode` only the
+    /// patterns of its scale branch are indexed (see
+    /// [`ChordProgressionTrie::scale_branch`]); without one, the whole tree.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::ChordProgressionTrie;
+    /// use composer_core::{Chord, Mode, ScaleFingerprint};
+    ///
+    /// let trie = ChordProgressionTrie::new();
+    /// let first_inversion_v = Chord::triad(5)?.with_inversion(1)?;
+    /// trie.add_pattern(&[Chord::triad(1)?, first_inversion_v], "song".to_string(), None)?;
+    ///
+    /// let major = ScaleFingerprint::major_scale();
+    /// let index = trie.bass_note_index(&major, None);
+    /// assert_eq!(index.total(0), 1); // I over the tonic
+    /// assert_eq!(index.total(11), 1); // V6 over the leading tone
+    /// assert!(trie.bass_note_index(&major, Some(Mode::Minor)).is_empty());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`ChordProgressionTrie::chord_vocabulary`] - Chord counts without bass placement
+    /// - [`crate::ChordProgressionSuggester::get_magic_bass_solutions`] - Harmonizes a bass note from this index
+    pub fn bass_note_index(
+        &self,
+        scale_fingerprint: &ScaleFingerprint,
+        mode: Option<Mode>,
+    ) -> BassNoteIndex {
+        let vocabulary = match mode {
+            Some(mode) => self
+                .scale_branches
+                .read()
+                .get(&Self::scale_branch(mode))
+                .map(node_vocabulary)
+                .unwrap_or_default(),
+            None => self.chord_vocabulary(),
+        };
+
+        let mut index = BassNoteIndex::default();
+        for (key, count) in vocabulary {
+            let Ok(chord) = deserialize_chord(&key) else {
+                continue;
+            };
+            let Ok(tones) = chord_pitch_classes(&chord, scale_fingerprint) else {
+                continue;
+            };
+            let bass = (chord.inversion as usize).min(tones.len().saturating_sub(1));
+            if let Some(bass) = tones.get(bass) {
+                index.buckets[(*bass % 12) as usize].push((key, count));
+            }
+        }
+        for bucket in &mut index.buckets {
+            bucket.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        }
+        index
+    }
+
+    /// Every pair of consecutive chords in the pattern tree with its count.
+    ///
+    /// Counts are summed over every position the pair occupies; the result is
+    /// sorted by the two serialized chords.
+    pub fn chord_transitions(&self) -> Vec<ChordTransition> {
+        type Counts = AHashMap<(ChordBinary, ChordBinary), u64>;
+
+        let root = self.root.read();
+        let mut counts: Counts = AHashMap::new();
+        for node in 0..root.node_count() as NodeId {
+            for (child, from) in root.children(node) {
+                for (_, to) in root.children(child) {
+                    *counts.entry((from.key, to.key)).or_insert(0) += to.count as u64;
+                }
+            }
+        }
+        let mut transitions: Vec<_> = counts
+            .into_iter()
+            .map(|((from, to), count)| (from, to, count))
+            .collect();
+        transitions.sort_unstable();
+        transitions
+    }
+
+    /// Complete stored progressions with the reference that added each one.
+    ///
+    /// A progression ends at the deepest node on its path that still lists the
+    /// reference, so prefixes shared with other sources are not reported
+    /// separately. Sorted by reference id, then by progression.
+    pub fn stored_progressions(&self) -> Vec<StoredPattern> {
+        fn collect(
+            tree: &TrieArena,
+            node: NodeId,
+            path: &mut Vec<ChordBinary>,
+            found: &mut Vec<StoredPattern>,
+        ) {
+            if !path.is_empty() {
+                for reference in tree.references(node) {
+                    let continues = tree
+                        .children(node)
+                        .any(|(child, _)| tree.has_reference(child, &reference.id));
+                    if !continues {
+                        found.push((reference.clone(), path.clone()));
+                    }
+                }
+            }
+            for (id, child) in tree.children(node) {
+                path.push(child.key);
+                collect(tree, id, path, found);
+                path.pop();
+            }
+        }
+
+        let mut found = Vec::new();
+        collect(&self.root.read(), ROOT_NODE, &mut Vec::new(), &mut found);
+        found.sort_by(|a, b| a.0.id.cmp(&b.0.id).then_with(|| a.1.cmp(&b.1)));
+        found
+    }
+
     /// Calculate ranks for all nodes
     pub fn calculate_all_ranks(&self) {
         self.root.write().calculate_ranks();
@@ -405,70 +1427,228 @@ impl ChordProgressionTrie {
         let memory_usage = self.memory_usage();
         let scale_branches = self.scale_branches.read().len();
 
+        let total_nodes = root.node_count() as u64;
+
         TrieStatistics {
             total_patterns,
-            total_nodes: self.count_nodes(&root),
+            total_nodes,
             memory_usage_bytes: memory_usage,
             scale_branches,
-            max_depth: self.calculate_max_depth(&root, 0),
-            avg_branching_factor: self.calculate_avg_branching_factor(&root),
+            max_depth: Self::calculate_max_depth(&root, ROOT_NODE, 0),
+            // Every node but the root is one branch of its parent
+            avg_branching_factor: (total_nodes - 1) as f64 / total_nodes as f64,
+            duplicate_patterns: *self.duplicate_patterns.read(),
         }
     }
 
-    /// Count total nodes in trie
-    fn count_nodes(&self, node: &TrieNode) -> u64 {
-        1 + node
-            .children
-            .values()
-            .map(|child| self.count_nodes(child))
-            .sum::<u64>()
-    }
+    /// Corpus statistics: chord, root and chord type histograms, frequencies of
+    /// `progression_length`-chord progressions, bigrams and trigrams, average
+    /// progression length, and patterns per key.
+    ///
+    /// Progression counts include every position a progression occurs at within
+    /// the stored patterns, counting patterns added more than once each time.
+    /// Fails with `AiError::InvalidPattern` for a zero `progression_length` and
+    /// `AiError::DataCorruption` if a stored chord key does not deserialize.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::ChordProgressionTrie;
+    /// use composer_core::Chord;
+    ///
+    /// let trie = ChordProgressionTrie::new();
+    /// let turnaround = [Chord::new(2, 7)?, Chord::new(5, 7)?, Chord::new(1, 7)?];
+    /// trie.add_pattern(&turnaround, "a".to_string(), Some("C".parse()?))?;
+    /// trie.add_pattern(&turnaround[1..], "b".to_string(), Some("F".parse()?))?;
+    ///
+    /// let stats = trie.corpus_statistics(2)?;
+    /// assert_eq!(stats.chord_histogram[0].count, 2);
+    /// assert_eq!(stats.progression_frequencies[0].chords, turnaround[1..]);
+    /// assert_eq!(stats.progression_frequencies[0].count, 2);
+    /// assert_eq!(stats.key_coverage.len(), 2);
+    /// assert_eq!(stats.root_histogram[0].root, 1);
+    /// assert_eq!(stats.trigrams.len(), 1);
+    /// assert_eq!(stats.average_progression_length, 2.5);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn corpus_statistics(&self, progression_length: usize) -> AiResult<CorpusStatistics> {
+        /// Complete patterns with how many times each was added
+        fn collect(
+            tree: &TrieArena,
+            node: NodeId,
+            path: &mut Vec<ChordBinary>,
+            found: &mut Vec<CountedPattern>,
+        ) {
+            let continuing: u32 = tree.children(node).map(|(_, child)| child.count).sum();
+            let ending = tree.node(node).count.saturating_sub(continuing);
+            if !path.is_empty() && ending > 0 {
+                found.push((path.clone(), ending as u64));
+            }
+            for (id, child) in tree.children(node) {
+                path.push(child.key);
+                collect(tree, id, path, found);
+                path.pop();
+            }
+        }
 
-    /// Calculate maximum depth
-    fn calculate_max_depth(&self, node: &TrieNode, current_depth: u32) -> u32 {
-        if node.children.is_empty() {
-            current_depth
-        } else {
-            node.children
-                .values()
-                .map(|child| self.calculate_max_depth(child, current_depth + 1))
-                .max()
-                .unwrap_or(current_depth)
+        if progression_length == 0 {
+            return Err(AiError::InvalidPattern {
+                reason: "Progression length must be at least 1".to_string(),
+            });
         }
-    }
+        let chord = |key: &ChordBinary| {
+            deserialize_chord(key).map_err(|e| AiError::DataCorruption {
+                details: format!("Invalid chord key in trie: {}", e),
+            })
+        };
+        let share = |count: u64, total: u64| {
+            if total > 0 {
+                count as f64 / total as f64
+            } else {
+                0.0
+            }
+        };
+
+        let mut patterns = Vec::new();
+        collect(&self.root.read(), ROOT_NODE, &mut Vec::new(), &mut patterns);
 
-    /// Calculate average branching factor
-    fn calculate_avg_branching_factor(&self, node: &TrieNode) -> f64 {
-        let mut total_branches = 0u64;
-        let mut total_nodes = 0u64;
+        let mut vocabulary = self.chord_vocabulary();
+        vocabulary.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let total_chords = vocabulary.iter().map(|(_, count)| count).sum();
+        let chord_histogram: Vec<ChordFrequency> = vocabulary
+            .iter()
+            .map(|(key, count)| {
+                Ok(ChordFrequency {
+                    chord: chord(key)?,
+                    count: *count,
+                    share: share(*count, total_chords),
+                })
+            })
+            .collect::<AiResult<_>>()?;
+
+        let mut roots: BTreeMap<u8, u64> = BTreeMap::new();
+        let mut chord_types: BTreeMap<u8, u64> = BTreeMap::new();
+        for entry in &chord_histogram {
+            *roots.entry(entry.chord.root).or_insert(0) += entry.count;
+            *chord_types.entry(entry.chord.chord_type).or_insert(0) += entry.count;
+        }
+        let root_histogram = roots
+            .into_iter()
+            .map(|(root, count)| RootFrequency {
+                root,
+                count,
+                share: share(count, total_chords),
+            })
+            .collect();
+        let chord_type_histogram = chord_types
+            .into_iter()
+            .map(|(chord_type, count)| ChordTypeFrequency {
+                chord_type,
+                count,
+                share: share(count, total_chords),
+            })
+            .collect();
 
-        self.collect_branching_stats(node, &mut total_branches, &mut total_nodes);
+        let frequencies = |length: usize| -> AiResult<Vec<ProgressionFrequency>> {
+            let mut progressions: AHashMap<&[ChordBinary], u64> = AHashMap::new();
+            for (pattern, times) in &patterns {
+                for window in pattern.windows(length) {
+                    *progressions.entry(window).or_insert(0) += times;
+                }
+            }
+            let mut progressions: Vec<_> = progressions.into_iter().collect();
+            progressions.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+            let total_progressions = progressions.iter().map(|(_, count)| count).sum();
+            progressions
+                .iter()
+                .map(|(keys, count)| {
+                    Ok(ProgressionFrequency {
+                        chords: keys.iter().map(chord).collect::<AiResult<_>>()?,
+                        count: *count,
+                        share: share(*count, total_progressions),
+                    })
+                })
+                .collect()
+        };
+        let progression_frequencies = frequencies(progression_length)?;
+        let bigrams = frequencies(2)?;
+        let trigrams = frequencies(3)?;
 
-        if total_nodes > 0 {
-            total_branches as f64 / total_nodes as f64
+        let added: u64 = patterns.iter().map(|(_, times)| times).sum();
+        let added_chords: u64 = patterns
+            .iter()
+            .map(|(pattern, times)| pattern.len() as u64 * times)
+            .sum();
+        let average_progression_length = if added > 0 {
+            added_chords as f64 / added as f64
         } else {
             0.0
+        };
+
+        let stored = self.stored_progressions();
+        let mut keys: AHashMap<Option<Key>, KeyTally> = AHashMap::new();
+        for (reference, pattern) in &stored {
+            let (count, chords) = keys.entry(reference.key_tonic).or_default();
+            *count += 1;
+            chords.extend(pattern.iter().copied());
         }
+        let mut key_coverage: Vec<KeyCoverage> = keys
+            .into_iter()
+            .map(|(key_tonic, (patterns, chords))| KeyCoverage {
+                key_tonic,
+                patterns,
+                share: share(patterns, stored.len() as u64),
+                distinct_chords: chords.len(),
+            })
+            .collect();
+        key_coverage.sort_by(|a, b| {
+            b.patterns.cmp(&a.patterns).then_with(|| {
+                let name = |key: &Option<Key>| key.map(|key| key.to_string());
+                name(&a.key_tonic).cmp(&name(&b.key_tonic))
+            })
+        });
+
+        Ok(CorpusStatistics {
+            total_patterns: self.total_patterns(),
+            progression_length,
+            average_progression_length,
+            chord_histogram,
+            root_histogram,
+            chord_type_histogram,
+            progression_frequencies,
+            bigrams,
+            trigrams,
+            key_coverage,
+        })
     }
 
-    /// Collect branching statistics recursively
-    fn collect_branching_stats(
-        &self,
-        node: &TrieNode,
-        total_branches: &mut u64,
-        total_nodes: &mut u64,
-    ) {
-        *total_nodes += 1;
-        *total_branches += node.children.len() as u64;
+    /// Calculate maximum depth
+    fn calculate_max_depth(tree: &TrieArena, node: NodeId, current_depth: u32) -> u32 {
+        tree.children(node)
+            .map(|(child, _)| Self::calculate_max_depth(tree, child, current_depth + 1))
+            .max()
+            .unwrap_or(current_depth)
+    }
+}
 
-        for child in node.children.values() {
-            self.collect_branching_stats(child, total_branches, total_nodes);
-        }
+/// Subtree below `node` in the `composer_serialization` trie format, with
+/// source ids that are not a `u32` stored as their `fast_hash`
+fn binary_node(tree: &TrieArena, node: NodeId) -> composer_serialization::TrieNode {
+    composer_serialization::TrieNode {
+        node_count: tree.node(node).count,
+        id_list: tree
+            .references(node)
+            .map(|r| r.id.parse().unwrap_or_else(|_| fast_hash(&r.id)))
+            .collect(),
+        children: tree
+            .children(node)
+            .map(|(id, child)| (child.key.to_vec(), binary_node(tree, id)))
+            .collect(),
     }
 }
 
 /// Trie statistics for monitoring and optimization
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrieStatistics {
     pub total_patterns: u64,
     pub total_nodes: u64,
@@ -476,6 +1656,121 @@ pub struct TrieStatistics {
     pub scale_branches: usize,
     pub max_depth: u32,
     pub avg_branching_factor: f64,
+
+    /// Patterns added that were identical to a stored one
+    #[serde(default)]
+    pub duplicate_patterns: u64,
+}
+
+/// Memory used by the pattern trees around [`ChordProgressionTrie::compact`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactionStats {
+    /// Nodes in the global tree and the scale branches
+    pub nodes: u64,
+
+    /// Bytes used before compaction
+    pub bytes_before: u64,
+
+    /// Bytes used after compaction
+    pub bytes_after: u64,
+}
+
+impl CompactionStats {
+    /// Bytes released by compaction
+    pub fn bytes_saved(&self) -> u64 {
+        self.bytes_before.saturating_sub(self.bytes_after)
+    }
+}
+
+/// Corpus statistics for reporting and plotting; see
+/// [`ChordProgressionTrie::corpus_statistics`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CorpusStatistics {
+    pub total_patterns: u64,
+
+    /// Length of the progressions in `progression_frequencies`
+    pub progression_length: usize,
+
+    /// Mean number of chords per added pattern
+    pub average_progression_length: f64,
+
+    /// Chords by number of occurrences, most frequent first
+    pub chord_histogram: Vec<ChordFrequency>,
+
+    /// Chord occurrences per root scale degree, in degree order
+    pub root_histogram: Vec<RootFrequency>,
+
+    /// Chord occurrences per chord type, in type order
+    pub chord_type_histogram: Vec<ChordTypeFrequency>,
+
+    /// Progressions by number of occurrences, most frequent first
+    pub progression_frequencies: Vec<ProgressionFrequency>,
+
+    /// Two-chord progressions, most frequent first
+    pub bigrams: Vec<ProgressionFrequency>,
+
+    /// Three-chord progressions, most frequent first
+    pub trigrams: Vec<ProgressionFrequency>,
+
+    /// Stored progressions per key tonic, most covered key first
+    pub key_coverage: Vec<KeyCoverage>,
+}
+
+/// Occurrences of one chord in the corpus
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChordFrequency {
+    pub chord: Chord,
+    pub count: u64,
+
+    /// Share of all chord occurrences (0.0-1.0)
+    pub share: f64,
+}
+
+/// Chord occurrences on one root scale degree
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RootFrequency {
+    /// Scale degree (0 for rests)
+    pub root: u8,
+    pub count: u64,
+
+    /// Share of all chord occurrences (0.0-1.0)
+    pub share: f64,
+}
+
+/// Chord occurrences of one chord type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChordTypeFrequency {
+    pub chord_type: u8,
+    pub count: u64,
+
+    /// Share of all chord occurrences (0.0-1.0)
+    pub share: f64,
+}
+
+/// Occurrences of one progression in the corpus
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProgressionFrequency {
+    pub chords: Vec<Chord>,
+    pub count: u64,
+
+    /// Share of all progressions of the same length (0.0-1.0)
+    pub share: f64,
+}
+
+/// Stored progressions in one key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyCoverage {
+    /// Key; `None` for progressions stored without one
+    pub key_tonic: Option<Key>,
+
+    /// Number of stored progressions in the key
+    pub patterns: u64,
+
+    /// Share of all stored progressions (0.0-1.0)
+    pub share: f64,
+
+    /// Distinct chords used by progressions in the key
+    pub distinct_chords: usize,
 }
 
 impl Default for ChordProgressionTrie {
@@ -484,6 +1779,20 @@ impl Default for ChordProgressionTrie {
     }
 }
 
+/// Distinct chords in `tree` with their total occurrence counts, sorted by
+/// serialized chord
+fn node_vocabulary(tree: &TrieArena) -> Vec<ChordCount> {
+    let mut counts: AHashMap<ChordBinary, u64> = AHashMap::new();
+    // Every node but the root stands for one chord position
+    for node in 1..tree.node_count() as NodeId {
+        let node = tree.node(node);
+        *counts.entry(node.key).or_insert(0) += node.count as u64;
+    }
+    let mut vocabulary: Vec<_> = counts.into_iter().collect();
+    vocabulary.sort_unstable_by_key(|(key, _)| *key);
+    vocabulary
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -506,7 +1815,11 @@ mod tests {
             Chord::new(6, 5).unwrap(), // vi
         ];
 
-        let result = trie.add_pattern(&pattern, "test_song_1".to_string(), Some("C".to_string()));
+        let result = trie.add_pattern(
+            &pattern,
+            "test_song_1".to_string(),
+            Some("C".parse().unwrap()),
+        );
         assert!(result.is_ok());
         assert_eq!(trie.total_patterns(), 1);
     }
@@ -541,6 +1854,172 @@ mod tests {
         assert_eq!(results.len(), 2); // Should find both V7 and IV
     }
 
+    #[test]
+    fn test_keys_are_stored_as_given() {
+        let trie = ChordProgressionTrie::new();
+        let (i, iv) = (Chord::new(1, 5).unwrap(), Chord::new(4, 5).unwrap());
+        let e_flat_minor: Key = "Eb minor".parse().unwrap();
+        let d_dorian: Key = "D Dorian".parse().unwrap();
+        trie.add_pattern(&[i.clone(), iv], "song".to_string(), Some(e_flat_minor))
+            .unwrap();
+        trie.add_pattern(
+            std::slice::from_ref(&i),
+            "modal".to_string(),
+            Some(d_dorian),
+        )
+        .unwrap();
+
+        let mut keys: Vec<_> = trie
+            .stored_progressions()
+            .into_iter()
+            .map(|(reference, _)| reference.key_tonic.unwrap().to_string())
+            .collect();
+        keys.sort();
+        assert_eq!(keys, ["D dorian", "Ebm"]);
+
+        let query = [Some(i), None];
+        let results = trie
+            .search_with_mode(&query, 10, MatchMode::Absolute, Some(&e_flat_minor))
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        // Enharmonic keys are spelled differently and stored apart
+        let d_sharp_minor: Key = "D#m".parse().unwrap();
+        assert!(trie
+            .search_with_mode(&query, 10, MatchMode::Absolute, Some(&d_sharp_minor))
+            .unwrap()
+            .is_empty());
+
+        assert_eq!(
+            ChordProgressionTrie::scale_branch(Mode::Dorian),
+            Mode::Minor
+        );
+        assert_eq!(
+            ChordProgressionTrie::scale_branch(Mode::Lydian),
+            Mode::Major
+        );
+    }
+
+    #[test]
+    fn test_stored_progressions() {
+        let trie = ChordProgressionTrie::new();
+        let long = vec![
+            Chord::new(1, 5).unwrap(),
+            Chord::new(5, 7).unwrap(),
+            Chord::new(6, 5).unwrap(),
+        ];
+        let short = vec![Chord::new(1, 5).unwrap(), Chord::new(4, 5).unwrap()];
+        trie.add_pattern(&long, "b".to_string(), Some("G".parse().unwrap()))
+            .unwrap();
+        trie.add_pattern(&short, "a".to_string(), None).unwrap();
+
+        let stored = trie.stored_progressions();
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored[0].0.id, "a");
+        assert_eq!(stored[0].1.len(), 2);
+        assert_eq!(stored[1].0.key_tonic, Some("G".parse().unwrap()));
+        assert_eq!(stored[1].1[2], serialize_chord(&long[2]).unwrap());
+
+        trie.add_pattern(&short, "c".to_string(), None).unwrap();
+        let transitions = trie.chord_transitions();
+        assert_eq!(transitions.len(), 3);
+        let tonic = serialize_chord(&short[0]).unwrap();
+        let subdominant = serialize_chord(&short[1]).unwrap();
+        assert!(transitions.contains(&(tonic, subdominant, 2)));
+    }
+
+    #[test]
+    fn test_binary_node_round_trip() {
+        let trie = ChordProgressionTrie::new();
+        let pattern = vec![Chord::new(1, 5).unwrap(), Chord::new(5, 7).unwrap()];
+        trie.add_pattern(&pattern, "42".to_string(), None).unwrap();
+        trie.add_pattern(&pattern[..1], "named-song".to_string(), None)
+            .unwrap();
+
+        let binary = trie.to_binary_node();
+        let bytes = composer_serialization::serialize_trie(&binary).unwrap();
+        let decoded = composer_serialization::deserialize_trie(&bytes, false).unwrap();
+
+        let restored = ChordProgressionTrie::new();
+        assert_eq!(restored.load_binary_node(&decoded).unwrap(), 2);
+        assert_eq!(restored.total_patterns(), 2);
+        assert_eq!(
+            restored.statistics().total_nodes,
+            trie.statistics().total_nodes
+        );
+
+        let results = restored
+            .search_with_wildcard(&[Some(Chord::new(1, 5).unwrap()), None], 10)
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id_list[0].id, "42");
+    }
+
+    #[test]
+    fn test_referenced_binary_node_keeps_ids_and_keys() {
+        let trie = ChordProgressionTrie::new();
+        let pattern = vec![Chord::new(1, 5).unwrap(), Chord::new(5, 7).unwrap()];
+        let key: Key = "F#m".parse().unwrap();
+        trie.add_pattern(&pattern, "named-song".to_string(), Some(key))
+            .unwrap();
+        trie.add_pattern(&pattern, "42".to_string(), None).unwrap();
+
+        let (binary, references) = trie.to_referenced_binary_node();
+        let bytes =
+            composer_serialization::serialize_trie_with_references(&binary, &references).unwrap();
+        let limits = composer_serialization::DeserializationLimits::default();
+        let (decoded, table) =
+            composer_serialization::deserialize_trie_with_references(&bytes, false, &limits)
+                .unwrap();
+
+        let restored = ChordProgressionTrie::new();
+        restored
+            .load_referenced_binary_node(&decoded, &table.unwrap())
+            .unwrap();
+        let results = restored
+            .search_with_wildcard(&[Some(pattern[0].clone()), None], 10)
+            .unwrap();
+        let sources: Vec<_> = results[0]
+            .id_list
+            .iter()
+            .map(|r| (r.id.as_str(), r.key_tonic))
+            .collect();
+        assert_eq!(sources, [("named-song", Some(key)), ("42", None)]);
+    }
+
+    #[test]
+    fn test_scale_branch_round_trip() {
+        let trie = ChordProgressionTrie::new();
+        let pattern = vec![Chord::new(1, 5).unwrap(), Chord::new(4, 5).unwrap()];
+        trie.add_pattern(&pattern, "7".to_string(), Some("Am".parse().unwrap()))
+            .unwrap();
+
+        let branch = trie.get_scale_branch("minor").unwrap();
+        assert_eq!(branch.node_count, 1);
+        assert!(trie.get_scale_branch("major").is_none());
+        assert!(trie.get_scale_branch("not-a-scale").is_none());
+
+        let copy = ChordProgressionTrie::new();
+        copy.add_scale_branch("minor".to_string(), branch.clone())
+            .unwrap();
+        assert_eq!(copy.get_scale_branch("minor").unwrap(), branch);
+        assert_eq!(copy.total_patterns(), 0);
+        assert!(copy
+            .add_scale_branch("not-a-scale".to_string(), branch)
+            .is_err());
+    }
+
+    #[test]
+    fn test_load_binary_node_rejects_bad_keys() {
+        let mut binary = composer_serialization::TrieNode::new();
+        binary
+            .children
+            .insert(vec![1, 2, 3], composer_serialization::TrieNode::new());
+
+        let trie = ChordProgressionTrie::new();
+        assert!(trie.load_binary_node(&binary).is_err());
+        assert_eq!(trie.total_patterns(), 0);
+    }
+
     #[test]
     fn test_invalid_pattern() {
         let trie = ChordProgressionTrie::new();
@@ -555,6 +2034,110 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_add_patterns_bulk() {
+        let trie = ChordProgressionTrie::new();
+        let long_pattern: Vec<Chord> = (0..50).map(|_| Chord::new(1, 5).unwrap()).collect();
+        let report = trie.add_patterns_bulk(vec![
+            (
+                vec![Chord::new(1, 5).unwrap(), Chord::new(4, 5).unwrap()],
+                "a".to_string(),
+                None,
+            ),
+            (Vec::new(), "empty".to_string(), None),
+            (
+                vec![Chord::new(1, 5).unwrap(), Chord::new(5, 5).unwrap()],
+                "b".to_string(),
+                None,
+            ),
+            (long_pattern, "long".to_string(), None),
+            (
+                vec![Chord::new(1, 5).unwrap(), Chord::new(4, 5).unwrap()],
+                "c".to_string(),
+                None,
+            ),
+        ]);
+
+        assert_eq!(report.added, 3);
+        assert!(!report.is_complete());
+        let rejected: Vec<(usize, &str)> = report
+            .rejected
+            .iter()
+            .map(|r| (r.index, r.source_id.as_str()))
+            .collect();
+        assert_eq!(rejected, [(1, "empty"), (3, "long")]);
+        assert!(matches!(
+            report.rejected[0].error,
+            AiError::InvalidPattern { .. }
+        ));
+        assert_eq!(trie.total_patterns(), 3);
+
+        // Ranks are calculated once the batch is loaded: I-IV was seen twice
+        let results = trie
+            .search_with_wildcard(&[Some(Chord::new(1, 5).unwrap()), None], 10)
+            .unwrap();
+        assert_eq!(results[0].rank, 1);
+        assert_eq!(results[0].count, 2);
+    }
+
+    #[test]
+    fn test_add_timed_patterns_bulk() {
+        let tonic = Chord::new(1, 5).unwrap();
+        let passing = Chord::new(2, 5).unwrap();
+        let held = Chord::new(4, 5).unwrap();
+        let timed = |chord: &Chord, durations: [f64; 2]| {
+            (
+                (vec![tonic.clone(), chord.clone()], durations.to_vec()),
+                "song".to_string(),
+                None,
+            )
+        };
+
+        let trie = ChordProgressionTrie::new();
+        let report = trie.add_timed_patterns_bulk(vec![
+            timed(&passing, [3.5, 0.5]),
+            timed(&passing, [3.5, 0.5]),
+            timed(&held, [2.0, 2.0]),
+            timed(&held, [2.0, -1.0]),
+            (
+                (vec![tonic.clone()], vec![1.0, 1.0]),
+                "short".to_string(),
+                None,
+            ),
+        ]);
+        let rejected: Vec<usize> = report.rejected.iter().map(|r| r.index).collect();
+        assert_eq!(rejected, [3, 4]);
+
+        let results = trie
+            .search_with_wildcard(&[Some(tonic.clone()), None], 10)
+            .unwrap();
+        let find = |chord: &Chord| {
+            let key = serialize_chord(chord).unwrap();
+            results.iter().find(|r| r.serialized_chord == key).unwrap()
+        };
+        // The passing chord occurs twice but sounds for a quarter of its patterns
+        assert_eq!(find(&passing).count, 2);
+        assert_eq!(find(&passing).duration_weight, 0.5);
+        assert_eq!(find(&held).count, 1);
+        assert_eq!(find(&held).duration_weight, 1.0);
+        assert_eq!(find(&held).rank, 1);
+        assert!(find(&held).weight > find(&passing).weight);
+        assert_eq!(find(&passing).mean_duration, Some(0.5));
+        assert_eq!(find(&held).mean_duration, Some(2.0));
+
+        // Untimed patterns add counts without diluting the learned durations
+        trie.add_pattern(&[tonic.clone(), held.clone()], "untimed".to_string(), None)
+            .unwrap();
+        let results = trie.search_with_wildcard(&[Some(tonic), None], 10).unwrap();
+        let held_key = serialize_chord(&held).unwrap();
+        let held_result = results
+            .iter()
+            .find(|r| r.serialized_chord == held_key)
+            .unwrap();
+        assert_eq!(held_result.count, 2);
+        assert_eq!(held_result.mean_duration, Some(2.0));
+    }
+
     #[test]
     fn test_trie_statistics() {
         let trie = ChordProgressionTrie::new();
@@ -575,6 +2158,183 @@ mod tests {
         assert!(stats.max_depth > 0);
     }
 
+    #[test]
+    fn test_corpus_statistics() {
+        let trie = ChordProgressionTrie::new();
+        let cadence = [
+            Chord::new(2, 7).unwrap(),
+            Chord::new(5, 7).unwrap(),
+            Chord::new(1, 5).unwrap(),
+        ];
+        trie.add_pattern(&cadence, "a".to_string(), Some("C".parse().unwrap()))
+            .unwrap();
+        trie.add_pattern(&cadence, "b".to_string(), Some("C".parse().unwrap()))
+            .unwrap();
+        trie.add_pattern(&cadence[1..], "c".to_string(), Some("G".parse().unwrap()))
+            .unwrap();
+
+        let stats = trie.corpus_statistics(4).unwrap();
+        assert!(stats.progression_frequencies.is_empty());
+        assert!((stats.average_progression_length - 8.0 / 3.0).abs() < 1e-9);
+
+        let roots: Vec<_> = stats
+            .root_histogram
+            .iter()
+            .map(|entry| (entry.root, entry.count))
+            .collect();
+        assert_eq!(roots, vec![(1, 3), (2, 2), (5, 3)]);
+        let types: Vec<_> = stats
+            .chord_type_histogram
+            .iter()
+            .map(|entry| (entry.chord_type, entry.count))
+            .collect();
+        assert_eq!(types, vec![(5, 3), (7, 5)]);
+        assert!((stats.chord_type_histogram[1].share - 5.0 / 8.0).abs() < 1e-9);
+
+        assert_eq!(stats.bigrams[0].chords, cadence[1..]);
+        assert_eq!(stats.bigrams[0].count, 3);
+        assert_eq!(stats.trigrams.len(), 1);
+        assert_eq!(stats.trigrams[0].count, 2);
+
+        assert_eq!(stats.key_coverage[0].key_tonic, Some("C".parse().unwrap()));
+        assert_eq!(stats.key_coverage[0].patterns, 2);
+
+        let json = serde_json::to_string(&stats).unwrap();
+        let restored: CorpusStatistics = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.bigrams, stats.bigrams);
+    }
+
+    #[test]
+    fn test_duplicate_policies() {
+        let cadence = vec![Chord::new(5, 7).unwrap(), Chord::new(1, 5).unwrap()];
+        let patterns = || {
+            vec![
+                (cadence.clone(), "a".to_string(), Some("C".parse().unwrap())),
+                (cadence.clone(), "b".to_string(), Some("C".parse().unwrap())),
+                (cadence.clone(), "c".to_string(), Some("G".parse().unwrap())),
+                (
+                    cadence[..1].to_vec(),
+                    "d".to_string(),
+                    Some("C".parse().unwrap()),
+                ),
+            ]
+        };
+
+        let tracked = ChordProgressionTrie::new();
+        let report = tracked.add_patterns_bulk(patterns());
+        assert_eq!((report.added, report.duplicates), (4, 1));
+        assert_eq!(tracked.statistics().total_patterns, 4);
+        assert_eq!(tracked.statistics().duplicate_patterns, 1);
+
+        let merged = ChordProgressionTrie::with_duplicate_policy(DuplicatePolicy::Merge);
+        let report = merged.add_patterns_bulk(patterns());
+        assert_eq!((report.added, report.duplicates), (3, 1));
+        assert!(report.is_complete());
+        assert_eq!(merged.total_patterns(), 3);
+        let ids: Vec<_> = merged
+            .stored_progressions()
+            .into_iter()
+            .map(|(reference, _)| reference.id)
+            .collect();
+        assert_eq!(ids, vec!["a", "b", "c", "d"]);
+
+        let rejecting = ChordProgressionTrie::with_duplicate_policy(DuplicatePolicy::Reject);
+        let report = rejecting.add_patterns_bulk(patterns());
+        assert_eq!((report.added, report.duplicates), (3, 1));
+        assert_eq!(report.rejected.len(), 1);
+        assert_eq!(report.rejected[0].index, 1);
+        assert!(rejecting
+            .add_pattern(&cadence, "e".to_string(), Some("C".parse().unwrap()))
+            .is_err());
+        assert_eq!(rejecting.statistics().duplicate_patterns, 2);
+        assert_eq!(rejecting.total_patterns(), 3);
+
+        rejecting.replace_root(TrieArena::new(false));
+        assert_eq!(rejecting.statistics().duplicate_patterns, 0);
+        assert!(rejecting
+            .add_pattern(&cadence, "e".to_string(), Some("C".parse().unwrap()))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_snapshot_forks_independently() {
+        let cadence = vec![Chord::new(5, 7).unwrap(), Chord::new(1, 5).unwrap()];
+        let plagal = vec![Chord::new(4, 5).unwrap(), Chord::new(1, 5).unwrap()];
+        let base = ChordProgressionTrie::with_duplicate_policy(DuplicatePolicy::Reject);
+        base.add_pattern(&cadence, "corpus".to_string(), Some("C".parse().unwrap()))
+            .unwrap();
+
+        let session = base.snapshot();
+        assert_eq!(session.duplicate_policy(), DuplicatePolicy::Reject);
+        assert_eq!(
+            session.root.read().shared_nodes(&base.root.read()),
+            base.statistics().total_nodes as usize
+        );
+        // Duplicates of patterns stored before the snapshot are still caught
+        assert!(session
+            .add_pattern(&cadence, "user".to_string(), Some("C".parse().unwrap()))
+            .is_err());
+        session
+            .add_pattern(&plagal, "user".to_string(), Some("C".parse().unwrap()))
+            .unwrap();
+        base.add_pattern(&plagal, "other".to_string(), Some("C".parse().unwrap()))
+            .unwrap();
+
+        let ids = |trie: &ChordProgressionTrie| -> Vec<String> {
+            let mut ids: Vec<_> = trie
+                .stored_progressions()
+                .into_iter()
+                .map(|(reference, _)| reference.id)
+                .collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(ids(&base), vec!["corpus", "other"]);
+        assert_eq!(ids(&session), vec!["corpus", "user"]);
+        assert_eq!(session.total_patterns(), 2);
+        assert_eq!(session.statistics().duplicate_patterns, 1);
+        assert_eq!(base.statistics().duplicate_patterns, 0);
+    }
+
+    #[test]
+    fn test_add_weighted_patterns_bulk() {
+        let tonic = Chord::new(1, 5).unwrap();
+        let dominant = Chord::new(5, 7).unwrap();
+        let trie = ChordProgressionTrie::new();
+        let report = trie.add_weighted_patterns_bulk(vec![
+            (
+                (
+                    vec![tonic.clone(), dominant.clone()],
+                    "canon".to_string(),
+                    None,
+                ),
+                2.5,
+            ),
+            ((vec![tonic.clone()], "user".to_string(), None), 1.0),
+            ((vec![dominant.clone()], "bad".to_string(), None), 0.0),
+            ((vec![dominant.clone()], "nan".to_string(), None), f64::NAN),
+        ]);
+        assert_eq!(report.added, 2);
+        let rejected: Vec<_> = report.rejected.iter().map(|r| r.index).collect();
+        assert_eq!(rejected, vec![2, 3]);
+
+        let root = trie.root.read();
+        let path = [
+            serialize_chord(&tonic).unwrap(),
+            serialize_chord(&dominant).unwrap(),
+        ];
+        let first = root.node(root.path(&path[..1]).unwrap());
+        assert_eq!(first.count, 2);
+        assert!((first.duration_weight - 3.5).abs() < 1e-9);
+        let second = root.node(root.path(&path).unwrap());
+        assert!((second.duration_weight - 2.5).abs() < 1e-9);
+        drop(root);
+
+        assert!(trie
+            .add_weighted_pattern(&[tonic], "neg".to_string(), None, -1.0)
+            .is_err());
+    }
+
     #[test]
     fn test_memory_tracking() {
         let trie = ChordProgressionTrie::new();
@@ -0,0 +1,247 @@
+//! Loop and turnaround analysis
+//!
+//! Songwriters often cycle a 4- or 8-bar progression, so its last chord also
+//! leads back into its first. [`MusicalAnalyzer::analyze_loop`] rates that
+//! wrap-around move against the progression's own transitions, and
+//! [`MusicalAnalyzer::suggest_turnarounds`] proposes last chords that make the
+//! loop seamless. A move is rated by how common it is in the pattern database
+//! and by the strength of its root motion, so loops can be judged before any
+//! patterns are loaded.
+
+use crate::analysis::MusicalAnalyzer;
+use crate::error::{AiError, AiResult};
+use composer_core::Chord;
+use serde::{Deserialize, Serialize};
+
+/// Occurrences in the pattern database at which a move's corpus rating saturates
+const COMMON_MOVE_COUNT: u32 = 5;
+
+/// Wrap-around quality from which a loop counts as smooth
+const SMOOTH_LOOP_QUALITY: f64 = 0.5;
+
+/// Chord types tried as turnaround chords on every scale degree
+const TURNAROUND_TYPES: [u8; 2] = [5, 7];
+
+/// How well a progression repeats, from [`MusicalAnalyzer::analyze_loop`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoopAnalysis {
+    /// Quality of the move from the last chord back to the first (0.0-1.0)
+    pub wrap_quality: f64,
+
+    /// Mean quality of the moves within the progression (0.0-1.0)
+    pub internal_quality: f64,
+
+    /// Occurrences of the wrap-around move in the pattern database
+    pub wrap_count: u32,
+
+    /// Whether the wrap-around move is strong enough to loop without a seam
+    pub loops_smoothly: bool,
+}
+
+/// Replacement last chord proposed by [`MusicalAnalyzer::suggest_turnarounds`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Turnaround {
+    /// Chord for the last bar
+    pub chord: Chord,
+
+    /// Quality of the move from the chord back to the first chord (0.0-1.0),
+    /// comparable with [`LoopAnalysis::wrap_quality`]
+    pub wrap_quality: f64,
+
+    /// Quality of the move into the chord from the one before it (0.0-1.0)
+    pub approach_quality: f64,
+
+    /// Occurrences of the previous chord, this chord and the first chord in a
+    /// row in the pattern database
+    pub frequency: u32,
+}
+
+impl MusicalAnalyzer {
+    /// Rate how smoothly a progression loops back to its start.
+    ///
+    /// Each move is rated from 0.0 to 1.0, half from how common it is in the
+    /// pattern database and half from its root motion: down a fifth is
+    /// strongest, then steps, thirds and up a fifth, and a repeated root is
+    /// weakest. The loop is smooth when the wrap-around move from the last
+    /// chord to the first rates at least 0.5.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{ChordProgressionTrie, MusicalAnalyzer};
+    /// use composer_core::Chord;
+    /// use std::sync::Arc;
+    ///
+    /// let analyzer = MusicalAnalyzer::new(Arc::new(ChordProgressionTrie::new()));
+    /// let chords = |roots: &[u8]| roots.iter().map(|&r| Chord::triad(r)).collect::<Result<Vec<_>, _>>();
+    ///
+    /// // V leads back to I, while I-V-vi-I stalls on the repeated tonic
+    /// assert!(analyzer.analyze_loop(&chords(&[1, 6, 4, 5])?)?.loops_smoothly);
+    /// assert!(!analyzer.analyze_loop(&chords(&[1, 5, 6, 1])?)?.loops_smoothly);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`MusicalAnalyzer::suggest_turnarounds`] - Last chords that smooth the loop
+    pub fn analyze_loop(&self, progression: &[Chord]) -> AiResult<LoopAnalysis> {
+        let (first, last) = loop_ends(progression)?;
+
+        let mut internal = 0.0;
+        for window in progression.windows(2) {
+            internal += self.move_quality(&window[0], &window[1])?;
+        }
+        let wrap_quality = self.move_quality(last, first)?;
+
+        Ok(LoopAnalysis {
+            wrap_quality,
+            internal_quality: internal / (progression.len() - 1) as f64,
+            wrap_count: self.transition_count(last, first)?,
+            loops_smoothly: wrap_quality >= SMOOTH_LOOP_QUALITY,
+        })
+    }
+
+    /// Propose last chords that make a progression loop seamlessly.
+    ///
+    /// Triads and seventh chords on every scale degree replace the last chord
+    /// and are ranked by the quality (see [`Self::analyze_loop`]) of the move
+    /// from them back to the first chord, then of the move into them, then by
+    /// how often the three chords occur in a row in the pattern database. The
+    /// current last chord is left out.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_ai::{ChordProgressionTrie, MusicalAnalyzer};
+    /// use composer_core::Chord;
+    /// use std::sync::Arc;
+    ///
+    /// let analyzer = MusicalAnalyzer::new(Arc::new(ChordProgressionTrie::new()));
+    /// let progression = vec![Chord::triad(1)?, Chord::triad(6)?, Chord::triad(2)?, Chord::triad(4)?];
+    ///
+    /// // ii wants to move to V, which resolves to I
+    /// let turnarounds = analyzer.suggest_turnarounds(&progression)?;
+    /// assert_eq!(turnarounds[0].chord.root, 5);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn suggest_turnarounds(&self, progression: &[Chord]) -> AiResult<Vec<Turnaround>> {
+        let (first, last) = loop_ends(progression)?;
+        let previous = &progression[progression.len() - 2];
+
+        let mut turnarounds = Vec::new();
+        for root in 1..=7 {
+            for chord_type in TURNAROUND_TYPES {
+                let chord = Chord::new(root, chord_type).map_err(|e| AiError::AnalysisFailed {
+                    reason: format!("Invalid turnaround chord: {}", e),
+                })?;
+                if chord == *last {
+                    continue;
+                }
+                let frequency =
+                    self.sequence_count(&[previous.clone(), chord.clone(), first.clone()])?;
+                turnarounds.push(Turnaround {
+                    wrap_quality: self.move_quality(&chord, first)?,
+                    approach_quality: self.move_quality(previous, &chord)?,
+                    chord,
+                    frequency,
+                });
+            }
+        }
+
+        turnarounds.sort_by(|a, b| {
+            b.wrap_quality
+                .total_cmp(&a.wrap_quality)
+                .then(b.approach_quality.total_cmp(&a.approach_quality))
+                .then(b.frequency.cmp(&a.frequency))
+        });
+        Ok(turnarounds)
+    }
+
+    /// Rating of the move from `from` to `to` (0.0-1.0)
+    fn move_quality(&self, from: &Chord, to: &Chord) -> AiResult<f64> {
+        let count = self.transition_count(from, to)?;
+        let corpus = count.min(COMMON_MOVE_COUNT) as f64 / COMMON_MOVE_COUNT as f64;
+        Ok((corpus + root_motion_strength(from, to)) / 2.0)
+    }
+}
+
+/// First and last chords of a progression long enough to loop
+fn loop_ends(progression: &[Chord]) -> AiResult<(&Chord, &Chord)> {
+    match progression {
+        [first, .., last] => Ok((first, last)),
+        _ => Err(AiError::AnalysisFailed {
+            reason: "A loop needs at least two chords".to_string(),
+        }),
+    }
+}
+
+/// Strength of the root motion from `from` to `to` (0.0-1.0)
+fn root_motion_strength(from: &Chord, to: &Chord) -> f64 {
+    // Diatonic steps up from the first root to the second
+    match (to.root + 7 - from.root) % 7 {
+        3 => 1.0,     // Down a fifth
+        1 | 6 => 0.7, // Step
+        5 => 0.6,     // Down a third
+        4 => 0.5,     // Up a fifth
+        2 => 0.4,     // Up a third
+        _ => 0.2,     // Same root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trie::ChordProgressionTrie;
+    use std::sync::Arc;
+
+    fn chords(roots: &[u8]) -> Vec<Chord> {
+        roots
+            .iter()
+            .map(|&root| Chord::triad(root).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_loop_analysis() {
+        let analyzer = MusicalAnalyzer::new(Arc::new(ChordProgressionTrie::new()));
+
+        let smooth = analyzer.analyze_loop(&chords(&[1, 6, 4, 5])).unwrap();
+        assert_eq!(smooth.wrap_quality, 0.5);
+        assert_eq!(smooth.wrap_count, 0);
+        assert!(smooth.loops_smoothly);
+
+        let stalled = analyzer.analyze_loop(&chords(&[1, 4, 5, 1])).unwrap();
+        assert_eq!(stalled.wrap_quality, 0.1);
+        assert!(!stalled.loops_smoothly);
+        assert!(stalled.internal_quality > stalled.wrap_quality);
+
+        assert!(analyzer.analyze_loop(&chords(&[1])).is_err());
+    }
+
+    #[test]
+    fn test_turnarounds_use_corpus() {
+        let trie = Arc::new(ChordProgressionTrie::new());
+        for i in 0..COMMON_MOVE_COUNT {
+            for roots in [[4, 7, 1].as_slice(), &[7, 1]] {
+                trie.add_pattern(&chords(roots), format!("song-{}", i), None)
+                    .unwrap();
+            }
+        }
+        let analyzer = MusicalAnalyzer::new(trie);
+        let progression = chords(&[1, 5, 6, 4, 1]);
+
+        let theory = MusicalAnalyzer::new(Arc::new(ChordProgressionTrie::new()))
+            .suggest_turnarounds(&progression)
+            .unwrap();
+        assert_eq!(theory[0].chord, Chord::triad(5).unwrap());
+        assert_eq!(theory.len(), 13);
+        assert!(theory.iter().all(|t| t.chord != progression[4]));
+
+        // vii resolves by step in theory, but the corpus makes it the strongest
+        let learned = analyzer.suggest_turnarounds(&progression).unwrap();
+        assert_eq!(learned[0].chord, Chord::triad(7).unwrap());
+        assert_eq!(learned[0].frequency, COMMON_MOVE_COUNT);
+        assert!(learned[0].wrap_quality > theory[0].wrap_quality);
+        assert!(learned[0].approach_quality > theory[0].approach_quality);
+    }
+}
@@ -0,0 +1,242 @@
+//! Emotional valence and arousal estimation
+//!
+//! Places chords on the valence (sad to happy) and arousal (calm to excited)
+//! axes of the circumplex model of emotion. Valence follows chord quality in
+//! the home key, so the same scale degree reads brighter in major than in
+//! minor, and darkens with alterations. Arousal rises with extensions,
+//! dominant and diminished tension, alterations and tempo.
+
+use crate::error::{AiError, AiResult};
+use composer_core::{get_relative_chord_graphic, Chord, ScaleFingerprint};
+use serde::{Deserialize, Serialize};
+
+/// Valence lost per chord alteration
+const ALTERATION_VALENCE: f64 = -0.1;
+
+/// Arousal gained per chord alteration
+const ALTERATION_AROUSAL: f64 = 0.1;
+
+/// Tempo with no effect on valence or arousal
+const NEUTRAL_TEMPO_BPM: f64 = 120.0;
+
+/// Valence and arousal of one chord
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChordEmotion {
+    /// The chord
+    pub chord: Chord,
+
+    /// Valence from sad (-1.0) to happy (1.0)
+    pub valence: f64,
+
+    /// Arousal from calm (-1.0) to excited (1.0)
+    pub arousal: f64,
+}
+
+/// Result of [`estimate_valence`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ValenceEstimate {
+    /// Per-chord estimates in progression order, rests included
+    pub chords: Vec<ChordEmotion>,
+
+    /// Duration-weighted mean valence of the sounding chords
+    pub valence: f64,
+
+    /// Duration-weighted mean arousal of the sounding chords
+    pub arousal: f64,
+}
+
+/// Tempo factor: -0.5 at half the neutral tempo or slower, 0.5 at 1.5x or faster
+fn tempo_factor(tempo_bpm: Option<f64>) -> f64 {
+    tempo_bpm.map_or(0.0, |bpm| {
+        ((bpm - NEUTRAL_TEMPO_BPM) / NEUTRAL_TEMPO_BPM).clamp(-0.5, 0.5)
+    })
+}
+
+/// Valence of a chord in a key, ignoring tempo
+///
+/// Quality comes from Roman numeral analysis against `scale_fingerprint`, so
+/// borrowed and applied chords take the quality of their source scale. This is
+/// the value [`crate::ChordProgressionSuggester`] matches against
+/// [`crate::SuggestionContext::target_valence`].
+pub fn chord_valence(chord: &Chord, scale_fingerprint: &ScaleFingerprint) -> AiResult<f64> {
+    if chord.is_rest {
+        return Ok(0.0);
+    }
+    let quality = chord_quality(chord, scale_fingerprint)?;
+    let seventh = chord.chord_type >= 7;
+
+    let base = match (quality.as_str(), seventh) {
+        ("", false) => 0.6,
+        ("", true) => 0.1,
+        ("maj", _) => 0.4,
+        ("m", false) => -0.2,
+        ("m", true) => -0.1,
+        ("ø", _) => -0.3,
+        ("°", false) => -0.4,
+        ("°", true) => -0.5,
+        ("+", _) => -0.1,
+        _ => 0.0,
+    };
+    let alterations = ALTERATION_VALENCE * chord.alterations.len() as f64;
+    Ok((base + alterations).clamp(-1.0, 1.0))
+}
+
+/// Arousal of a chord in a key, ignoring tempo
+fn chord_arousal(chord: &Chord, scale_fingerprint: &ScaleFingerprint) -> AiResult<f64> {
+    if chord.is_rest {
+        return Ok(-1.0);
+    }
+    let quality = chord_quality(chord, scale_fingerprint)?;
+
+    let mut arousal = -0.3;
+    arousal += 0.075 * chord.chord_type.saturating_sub(5) as f64;
+    arousal += match quality.as_str() {
+        "" if chord.chord_type >= 7 => 0.15,
+        "°" | "ø" | "+" => 0.2,
+        _ => 0.0,
+    };
+    arousal += ALTERATION_AROUSAL * chord.alterations.len() as f64;
+    arousal += 0.05 * chord.suspensions.len() as f64;
+    if chord.inversion > 0 {
+        arousal += 0.05;
+    }
+    Ok(arousal.clamp(-1.0, 1.0))
+}
+
+/// Quality symbol of a chord ("", "m", "maj", "°", "ø" or "+")
+fn chord_quality(chord: &Chord, scale_fingerprint: &ScaleFingerprint) -> AiResult<String> {
+    get_relative_chord_graphic(chord, scale_fingerprint)
+        .map(|graphic| graphic.quality)
+        .map_err(|e| AiError::AnalysisFailed {
+            reason: format!("Cannot determine chord quality: {}", e),
+        })
+}
+
+/// Estimate per-chord and overall valence and arousal of a progression.
+///
+/// Chord valence comes from [`chord_valence`]; arousal starts calm and rises
+/// with extensions, dominant or diminished tension, alterations, suspensions
+/// and inversions. A tempo above 120 BPM raises both arousal and, to a lesser
+/// degree, valence; slower tempos lower them. Rests are reported as neutral
+/// and calm but left out of the overall means, which weight each chord by
+/// its duration (1 beat when unset).
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::estimate_valence;
+/// use composer_core::{Chord, ScaleFingerprint};
+///
+/// let progression = vec![Chord::new(1, 5)?, Chord::new(4, 5)?, Chord::new(5, 7)?];
+/// let major = estimate_valence(&progression, &ScaleFingerprint::major_scale(), None)?;
+/// let minor = estimate_valence(&progression, &ScaleFingerprint::minor_scale(), None)?;
+/// assert!(major.valence > minor.valence);
+///
+/// let fast = estimate_valence(&progression, &ScaleFingerprint::major_scale(), Some(170.0))?;
+/// assert!(fast.arousal > major.arousal);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`chord_valence`] - Tempo-independent valence of a single chord
+/// - [`crate::AiEngine::estimate_valence`] - Engine entry point
+pub fn estimate_valence(
+    progression: &[Chord],
+    scale_fingerprint: &ScaleFingerprint,
+    tempo_bpm: Option<f64>,
+) -> AiResult<ValenceEstimate> {
+    if progression.is_empty() {
+        return Err(AiError::AnalysisFailed {
+            reason: "Cannot estimate valence of empty progression".to_string(),
+        });
+    }
+
+    let tempo = tempo_factor(tempo_bpm);
+    let mut chords = Vec::with_capacity(progression.len());
+    let (mut valence_sum, mut arousal_sum, mut weight_sum) = (0.0, 0.0, 0.0);
+
+    for chord in progression {
+        let (valence, arousal) = if chord.is_rest {
+            (0.0, -1.0)
+        } else {
+            let valence =
+                (chord_valence(chord, scale_fingerprint)? + 0.25 * tempo).clamp(-1.0, 1.0);
+            let arousal = (chord_arousal(chord, scale_fingerprint)? + tempo).clamp(-1.0, 1.0);
+            let weight = chord.duration.filter(|d| *d > 0.0).unwrap_or(1.0);
+            valence_sum += valence * weight;
+            arousal_sum += arousal * weight;
+            weight_sum += weight;
+            (valence, arousal)
+        };
+        chords.push(ChordEmotion {
+            chord: chord.clone(),
+            valence,
+            arousal,
+        });
+    }
+
+    // Only rests: as neutral and calm as a single rest
+    let (valence, arousal) = if weight_sum > 0.0 {
+        (valence_sum / weight_sum, arousal_sum / weight_sum)
+    } else {
+        (0.0, -1.0)
+    };
+    Ok(ValenceEstimate {
+        chords,
+        valence,
+        arousal,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use composer_core::BorrowedScale;
+
+    #[test]
+    fn test_chord_valence_follows_quality() {
+        let major = ScaleFingerprint::major_scale();
+        let minor = ScaleFingerprint::minor_scale();
+        let tonic = Chord::new(1, 5).unwrap();
+
+        assert_eq!(chord_valence(&tonic, &major).unwrap(), 0.6);
+        assert_eq!(chord_valence(&tonic, &minor).unwrap(), -0.2);
+        assert_eq!(
+            chord_valence(&Chord::new(7, 5).unwrap(), &major).unwrap(),
+            -0.4
+        );
+
+        // Borrowed iv in major is minor, and alterations darken
+        let borrowed = Chord::new(4, 5)
+            .unwrap()
+            .with_borrowed_scale(BorrowedScale::Named("minor".into()))
+            .unwrap();
+        assert_eq!(chord_valence(&borrowed, &major).unwrap(), -0.2);
+        let altered = Chord::new(5, 7).unwrap().with_alteration("b9").unwrap();
+        assert!((chord_valence(&altered, &major).unwrap() - 0.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_estimate_valence_curve() {
+        let major = ScaleFingerprint::major_scale();
+        let progression = vec![
+            Chord::new(1, 5).unwrap().with_timing(0.0, 3.0),
+            Chord::rest(),
+            Chord::new(7, 7).unwrap().with_timing(4.0, 1.0),
+        ];
+        let estimate = estimate_valence(&progression, &major, None).unwrap();
+
+        assert_eq!(estimate.chords.len(), 3);
+        assert_eq!(estimate.chords[1].arousal, -1.0);
+        assert!(estimate.chords[2].arousal > estimate.chords[0].arousal);
+        // (0.6 * 3 - 0.3 * 1) / 4, the rest left out
+        assert!((estimate.valence - 0.375).abs() < 1e-9);
+
+        let slow = estimate_valence(&progression, &major, Some(50.0)).unwrap();
+        assert!(slow.arousal < estimate.arousal);
+        assert!(slow.valence < estimate.valence);
+
+        assert!(estimate_valence(&[], &major, None).is_err());
+    }
+}
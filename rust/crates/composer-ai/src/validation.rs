@@ -0,0 +1,851 @@
+//! Structural validation of serialized assets
+//!
+//! [`validate_asset`] inspects chord binaries, trie files, tokenizer vocabularies, and
+//! difficulty models without loading them into an engine, and reports the status of
+//! each section of the file so corrupted or mismatched assets can be diagnosed.
+
+use crate::analysis::PolynomialModel;
+use crate::compatibility::{AssetHeader, AssetKind, ModelVersion, ASSET_HEADER_LEN};
+use composer_serialization::{
+    decompress, deserialize_chord, deserialize_token_library, deserialize_trie_with_references,
+    detect_compression, detect_version, serialize_trie, serialize_trie_with_references,
+    ChordBinary, Compression, DeserializationLimits, TrieNode, TOKEN_LIBRARY_HEADER_LEN,
+    TOKEN_LIBRARY_MAGIC, TRIE_BINARY_HEADER_LEN, TRIE_FLAG_KEY_TONIC,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Detected asset type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AssetType {
+    /// Single 5-byte serialized chord
+    ChordBinary,
+    /// Chord progression trie, bare or with a versioned header
+    ChordTrie,
+    /// Tokenizer vocabulary (`serialize_token_library` output)
+    TokenLibrary,
+    /// Difficulty assessment model asset
+    DifficultyModel,
+    /// Bytes matching no known format
+    Unknown,
+}
+
+/// Status of a validated section
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum SectionStatus {
+    Valid,
+    Warning,
+    Invalid,
+}
+
+/// Validation result for one section of an asset
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectionReport {
+    /// Section name (e.g. "header", "structure")
+    pub name: String,
+    /// Byte offset of the section within the asset
+    pub offset: usize,
+    /// Section length in bytes
+    pub length: usize,
+    /// Section status
+    pub status: SectionStatus,
+    /// Human-readable details
+    pub message: String,
+}
+
+impl SectionReport {
+    fn new(
+        name: &str,
+        offset: usize,
+        length: usize,
+        status: SectionStatus,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            offset,
+            length,
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+/// Validation report for a serialized asset
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    /// Detected asset type
+    pub asset_type: AssetType,
+    /// Total asset size in bytes
+    pub size_bytes: usize,
+    /// Model version from the versioned header, if present
+    pub model_version: Option<ModelVersion>,
+    /// Per-section results, in file order
+    pub sections: Vec<SectionReport>,
+}
+
+impl ValidationReport {
+    /// Check if no section is invalid
+    pub fn is_valid(&self) -> bool {
+        self.asset_type != AssetType::Unknown
+            && self
+                .sections
+                .iter()
+                .all(|section| section.status != SectionStatus::Invalid)
+    }
+
+    /// Check if any section produced a warning
+    pub fn has_warnings(&self) -> bool {
+        self.sections
+            .iter()
+            .any(|section| section.status == SectionStatus::Warning)
+    }
+
+    /// Sections that failed validation
+    pub fn errors(&self) -> Vec<&SectionReport> {
+        self.sections
+            .iter()
+            .filter(|section| section.status == SectionStatus::Invalid)
+            .collect()
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} ({} bytes)", self.asset_type, self.size_bytes)?;
+        if let Some(version) = self.model_version {
+            write!(f, ", model {}", version)?;
+        }
+        writeln!(f, ": {}", if self.is_valid() { "valid" } else { "INVALID" })?;
+
+        for section in &self.sections {
+            let tag = match section.status {
+                SectionStatus::Valid => "ok",
+                SectionStatus::Warning => "warn",
+                SectionStatus::Invalid => "FAIL",
+            };
+            writeln!(
+                f,
+                "  [{:>4}] {} @{}+{}: {}",
+                tag, section.name, section.offset, section.length, section.message
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Validates a serialized asset and reports the status of each section.
+///
+/// The asset type is detected from its contents: versioned assets (tries and
+/// difficulty models) by their header, tokenizer vocabularies by their magic bytes,
+/// and 5-byte inputs as chord binaries. Anything else is tried as a bare trie.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::{validate_asset, AiEngine, AiEngineConfig, AssetType};
+/// use composer_core::Chord;
+/// use composer_serialization::serialize_chord;
+///
+/// let chord = serialize_chord(&Chord::triad(5)?)?;
+/// let report = validate_asset(&chord);
+/// assert_eq!(report.asset_type, AssetType::ChordBinary);
+/// assert!(report.is_valid());
+///
+/// let engine = AiEngine::new(AiEngineConfig::default());
+/// let mut trie = engine.export_trie_asset()?;
+/// trie.truncate(trie.len() - 2);
+/// let report = validate_asset(&trie);
+/// assert_eq!(report.asset_type, AssetType::ChordTrie);
+/// assert!(!report.is_valid());
+/// println!("{}", report);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`AiEngine::compatibility_report`](crate::AiEngine::compatibility_report) - Check
+///   model versions against an engine
+/// - [`composer_serialization::validate_binary_format`] - Quick boolean trie check
+pub fn validate_asset(bytes: &[u8]) -> ValidationReport {
+    if detect_compression(bytes) != Compression::None {
+        return match decompress_section(bytes, 0) {
+            Ok((data, section)) => {
+                let mut report = validate_asset(&data);
+                report.sections.insert(0, section);
+                report.size_bytes = bytes.len();
+                report
+            },
+            Err(section) => ValidationReport {
+                asset_type: AssetType::Unknown,
+                size_bytes: bytes.len(),
+                model_version: None,
+                sections: vec![section],
+            },
+        };
+    }
+
+    if AssetHeader::is_present(bytes) {
+        return validate_versioned_asset(bytes);
+    }
+
+    if bytes.len() >= TOKEN_LIBRARY_MAGIC.len() && bytes[..4] == TOKEN_LIBRARY_MAGIC {
+        return validate_token_library(bytes);
+    }
+
+    if bytes.len() == std::mem::size_of::<ChordBinary>() {
+        return validate_chord_binary(bytes);
+    }
+
+    let sections = validate_trie_payload(bytes, 0);
+    // A trie header identifies the asset even when its version is unsupported
+    let parsed = sections.iter().any(|s| {
+        (s.name == "structure" && s.status != SectionStatus::Invalid) || s.name == "header"
+    });
+
+    ValidationReport {
+        asset_type: if parsed {
+            AssetType::ChordTrie
+        } else {
+            AssetType::Unknown
+        },
+        size_bytes: bytes.len(),
+        model_version: None,
+        sections,
+    }
+}
+
+fn validate_versioned_asset(bytes: &[u8]) -> ValidationReport {
+    let header = match AssetHeader::decode(bytes) {
+        Ok(header) => header,
+        Err(e) => {
+            return ValidationReport {
+                asset_type: AssetType::Unknown,
+                size_bytes: bytes.len(),
+                model_version: None,
+                sections: vec![SectionReport::new(
+                    "header",
+                    0,
+                    ASSET_HEADER_LEN.min(bytes.len()),
+                    SectionStatus::Invalid,
+                    e.to_string(),
+                )],
+            }
+        },
+    };
+
+    let mut sections = vec![SectionReport::new(
+        "header",
+        0,
+        ASSET_HEADER_LEN,
+        SectionStatus::Valid,
+        format!("{:?} asset, model {}", header.kind, header.model_version),
+    )];
+
+    let payload = &bytes[ASSET_HEADER_LEN..];
+    let asset_type = match header.kind {
+        AssetKind::ChordTrie => {
+            sections.extend(validate_trie_payload(payload, ASSET_HEADER_LEN));
+            AssetType::ChordTrie
+        },
+        AssetKind::DifficultyModel => {
+            sections.extend(validate_difficulty_model(payload, ASSET_HEADER_LEN));
+            AssetType::DifficultyModel
+        },
+    };
+
+    ValidationReport {
+        asset_type,
+        size_bytes: bytes.len(),
+        model_version: Some(header.model_version),
+        sections,
+    }
+}
+
+fn validate_chord_binary(bytes: &[u8]) -> ValidationReport {
+    let mut binary: ChordBinary = [0; 5];
+    binary.copy_from_slice(bytes);
+
+    let section = match deserialize_chord(&binary) {
+        Ok(chord) => SectionReport::new(
+            "chord",
+            0,
+            bytes.len(),
+            SectionStatus::Valid,
+            format!("{}", chord),
+        ),
+        Err(e) => SectionReport::new(
+            "chord",
+            0,
+            bytes.len(),
+            SectionStatus::Invalid,
+            e.to_string(),
+        ),
+    };
+
+    ValidationReport {
+        asset_type: AssetType::ChordBinary,
+        size_bytes: bytes.len(),
+        model_version: None,
+        sections: vec![section],
+    }
+}
+
+/// Decompressed bytes and the section describing their frame
+type Decompressed = (Vec<u8>, SectionReport);
+
+/// Decompress a compressed asset or payload, reporting the frame as a section
+fn decompress_section(bytes: &[u8], base: usize) -> Result<Decompressed, SectionReport> {
+    let name = detect_compression(bytes).name();
+    let limit = DeserializationLimits::default().max_decompressed_bytes;
+    match decompress(bytes, limit) {
+        Ok(data) => {
+            let message = format!(
+                "{} frame, {} bytes decompressed; later offsets are into the decompressed data",
+                name,
+                data.len()
+            );
+            Ok((
+                data.into_owned(),
+                SectionReport::new(
+                    "compression",
+                    base,
+                    bytes.len(),
+                    SectionStatus::Valid,
+                    message,
+                ),
+            ))
+        },
+        Err(e) => Err(SectionReport::new(
+            "compression",
+            base,
+            bytes.len(),
+            SectionStatus::Invalid,
+            format!("{} frame: {}", name, e),
+        )),
+    }
+}
+
+fn validate_trie_payload(payload: &[u8], base: usize) -> Vec<SectionReport> {
+    const ROOT_HEADER_LEN: usize = 12;
+
+    let mut sections = Vec::new();
+
+    if detect_compression(payload) != Compression::None {
+        match decompress_section(payload, base) {
+            Ok((data, section)) => {
+                sections.push(section);
+                sections.extend(validate_trie_payload(&data, 0));
+            },
+            Err(section) => sections.push(section),
+        }
+        return sections;
+    }
+
+    // Version 1 tries have no header and start with the root node
+    let header_len = match detect_version(payload) {
+        Ok(1) => 0,
+        Ok(version) => {
+            sections.push(SectionReport::new(
+                "header",
+                base,
+                TRIE_BINARY_HEADER_LEN,
+                SectionStatus::Valid,
+                format!("format version {}", version),
+            ));
+            TRIE_BINARY_HEADER_LEN
+        },
+        Err(e) => {
+            sections.push(SectionReport::new(
+                "header",
+                base,
+                TRIE_BINARY_HEADER_LEN.min(payload.len()),
+                SectionStatus::Invalid,
+                e.to_string(),
+            ));
+            return sections;
+        },
+    };
+    let key_tonic = header_len > 0 && payload[5] & TRIE_FLAG_KEY_TONIC != 0;
+    let full_payload = payload;
+    let payload = &payload[header_len..];
+    let base = base + header_len;
+
+    if payload.len() < ROOT_HEADER_LEN {
+        sections.push(SectionReport::new(
+            "root_node",
+            base,
+            payload.len(),
+            SectionStatus::Invalid,
+            format!(
+                "need at least {} bytes for the root node, found {}",
+                ROOT_HEADER_LEN,
+                payload.len()
+            ),
+        ));
+        return sections;
+    }
+
+    let read_u32 = |offset: usize| {
+        u32::from_be_bytes([
+            payload[offset],
+            payload[offset + 1],
+            payload[offset + 2],
+            payload[offset + 3],
+        ])
+    };
+    sections.push(SectionReport::new(
+        "root_node",
+        base,
+        ROOT_HEADER_LEN,
+        SectionStatus::Valid,
+        format!("count {}, {} ids", read_u32(0), read_u32(4)),
+    ));
+
+    // Trailing bytes are reported as a warning below rather than failing the structure
+    let limits = DeserializationLimits {
+        allow_trailing_data: true,
+        ..Default::default()
+    };
+    let (trie, references) = match deserialize_trie_with_references(full_payload, false, &limits) {
+        Ok(decoded) => decoded,
+        Err(e) => {
+            sections.push(SectionReport::new(
+                "structure",
+                base,
+                payload.len(),
+                SectionStatus::Invalid,
+                e.to_string(),
+            ));
+            return sections;
+        },
+    };
+
+    let mut node_count = 0usize;
+    let mut invalid_keys = Vec::new();
+    walk_trie(&trie, &mut node_count, &mut invalid_keys);
+
+    // Re-encoding gives the root node's length; it writes ids without key tonics
+    let consumed = serialize_trie(&trie)
+        .map(|b| b.len() - TRIE_BINARY_HEADER_LEN)
+        .unwrap_or(0)
+        + if key_tonic { 2 * id_count(&trie) } else { 0 };
+    sections.push(SectionReport::new(
+        "structure",
+        base,
+        consumed,
+        SectionStatus::Valid,
+        format!("{} nodes", node_count),
+    ));
+
+    let mut table_len = 0;
+    if let Some(references) = &references {
+        table_len = match (
+            serialize_trie_with_references(&trie, references),
+            serialize_trie(&trie),
+        ) {
+            (Ok(with_table), Ok(without)) => with_table.len() - without.len(),
+            _ => 0,
+        };
+        sections.push(SectionReport::new(
+            "reference_table",
+            base + consumed,
+            table_len,
+            SectionStatus::Valid,
+            format!("{} references", references.len()),
+        ));
+    }
+
+    let trailing = payload.len().saturating_sub(consumed + table_len);
+
+    if trailing > 0 {
+        sections.push(SectionReport::new(
+            "trailing_data",
+            base + consumed + table_len,
+            trailing,
+            SectionStatus::Warning,
+            format!("{} unused bytes after the trie", trailing),
+        ));
+    }
+
+    sections.push(if invalid_keys.is_empty() {
+        SectionReport::new(
+            "chord_keys",
+            base,
+            consumed,
+            SectionStatus::Valid,
+            format!(
+                "{} keys decode to valid chords",
+                node_count.saturating_sub(1)
+            ),
+        )
+    } else {
+        SectionReport::new(
+            "chord_keys",
+            base,
+            consumed,
+            SectionStatus::Invalid,
+            format!(
+                "{} keys are not valid chords (first: {:02x?})",
+                invalid_keys.len(),
+                invalid_keys[0]
+            ),
+        )
+    });
+
+    sections
+}
+
+fn walk_trie(node: &TrieNode, node_count: &mut usize, invalid_keys: &mut Vec<Vec<u8>>) {
+    *node_count += 1;
+    for (key, child) in &node.children {
+        let valid = <ChordBinary>::try_from(key.as_slice())
+            .ok()
+            .map(|binary| deserialize_chord(&binary).is_ok())
+            .unwrap_or(false);
+        if !valid {
+            invalid_keys.push(key.clone());
+        }
+        walk_trie(child, node_count, invalid_keys);
+    }
+}
+
+fn id_count(node: &TrieNode) -> usize {
+    node.id_list.len() + node.children.values().map(id_count).sum::<usize>()
+}
+
+fn validate_token_library(bytes: &[u8]) -> ValidationReport {
+    let mut sections = Vec::new();
+    let header_len = TOKEN_LIBRARY_HEADER_LEN.min(bytes.len());
+
+    let library = match deserialize_token_library(bytes) {
+        Ok(library) => library,
+        Err(e) => {
+            let header_ok = bytes.len() >= TOKEN_LIBRARY_HEADER_LEN;
+            sections.push(SectionReport::new(
+                "header",
+                0,
+                header_len,
+                if header_ok {
+                    SectionStatus::Valid
+                } else {
+                    SectionStatus::Invalid
+                },
+                if header_ok {
+                    format!("format version {}", bytes[4])
+                } else {
+                    "truncated header".to_string()
+                },
+            ));
+            if header_ok {
+                sections.push(SectionReport::new(
+                    "entries",
+                    header_len,
+                    bytes.len() - header_len,
+                    SectionStatus::Invalid,
+                    e.to_string(),
+                ));
+            }
+            return ValidationReport {
+                asset_type: AssetType::TokenLibrary,
+                size_bytes: bytes.len(),
+                model_version: None,
+                sections,
+            };
+        },
+    };
+
+    sections.push(SectionReport::new(
+        "header",
+        0,
+        header_len,
+        SectionStatus::Valid,
+        if bytes[4] == library.schema_version {
+            format!("format version {}", bytes[4])
+        } else {
+            format!(
+                "format version {}, upgraded to {}",
+                bytes[4], library.schema_version
+            )
+        },
+    ));
+    sections.push(SectionReport::new(
+        "entries",
+        header_len,
+        bytes.len() - header_len,
+        SectionStatus::Valid,
+        format!("{} tokens", library.get_library_size()),
+    ));
+
+    let mut invalid: Vec<&String> = library
+        .chord_tokens
+        .iter()
+        .filter(|(_, binary)| {
+            <ChordBinary>::try_from(binary.as_slice())
+                .map(|b| deserialize_chord(&b).is_err())
+                .unwrap_or(true)
+        })
+        .map(|(token, _)| token)
+        .collect();
+    invalid.sort();
+
+    sections.push(if invalid.is_empty() {
+        SectionReport::new(
+            "chord_binaries",
+            header_len,
+            bytes.len() - header_len,
+            SectionStatus::Valid,
+            "all tokens map to valid chords",
+        )
+    } else {
+        SectionReport::new(
+            "chord_binaries",
+            header_len,
+            bytes.len() - header_len,
+            SectionStatus::Invalid,
+            format!(
+                "{} tokens map to invalid chords (first: {})",
+                invalid.len(),
+                invalid[0]
+            ),
+        )
+    });
+
+    ValidationReport {
+        asset_type: AssetType::TokenLibrary,
+        size_bytes: bytes.len(),
+        model_version: None,
+        sections,
+    }
+}
+
+fn validate_difficulty_model(payload: &[u8], base: usize) -> Vec<SectionReport> {
+    const EXPECTED_FEATURES: [&str; 4] = [
+        "harmonic_complexity",
+        "rhythmic_complexity",
+        "technical_complexity",
+        "melodic_complexity",
+    ];
+
+    let model: PolynomialModel = match serde_json::from_slice(payload) {
+        Ok(model) => model,
+        Err(e) => {
+            return vec![SectionReport::new(
+                "model",
+                base,
+                payload.len(),
+                SectionStatus::Invalid,
+                format!("not a difficulty model: {}", e),
+            )]
+        },
+    };
+
+    let span = |name: &str, status, message: String| {
+        SectionReport::new(name, base, payload.len(), status, message)
+    };
+    let mut sections = vec![span("model", SectionStatus::Valid, "parsed".to_string())];
+
+    sections.push(if model.coefficients.iter().all(|c| c.is_finite()) {
+        span(
+            "coefficients",
+            SectionStatus::Valid,
+            format!("{:?}", model.coefficients),
+        )
+    } else {
+        span(
+            "coefficients",
+            SectionStatus::Invalid,
+            "coefficients must be finite".to_string(),
+        )
+    });
+
+    let missing: Vec<&str> = EXPECTED_FEATURES
+        .iter()
+        .copied()
+        .filter(|name| !model.feature_weights.contains_key(*name))
+        .collect();
+    let weight_sum: f64 = model.feature_weights.values().sum();
+
+    sections.push(
+        if model
+            .feature_weights
+            .values()
+            .any(|w| !w.is_finite() || *w < 0.0)
+        {
+            span(
+                "feature_weights",
+                SectionStatus::Invalid,
+                "weights must be finite and non-negative".to_string(),
+            )
+        } else if !missing.is_empty() {
+            span(
+                "feature_weights",
+                SectionStatus::Warning,
+                format!("missing weights: {}", missing.join(", ")),
+            )
+        } else if (weight_sum - 1.0).abs() > 0.01 {
+            span(
+                "feature_weights",
+                SectionStatus::Warning,
+                format!("weights sum to {:.3}, expected 1.0", weight_sum),
+            )
+        } else {
+            span(
+                "feature_weights",
+                SectionStatus::Valid,
+                format!("{} weights", model.feature_weights.len()),
+            )
+        },
+    );
+
+    sections.push(if (0.0..=1.0).contains(&model.accuracy) {
+        span(
+            "accuracy",
+            SectionStatus::Valid,
+            format!("{:.3}", model.accuracy),
+        )
+    } else {
+        span(
+            "accuracy",
+            SectionStatus::Invalid,
+            format!("accuracy {} outside 0.0-1.0", model.accuracy),
+        )
+    });
+
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compatibility::encode_asset;
+    use crate::{AiEngine, AiEngineConfig};
+    use composer_core::Chord;
+    use composer_serialization::{compress, serialize_token_library, TokenLibrary};
+
+    fn trained_engine() -> AiEngine {
+        let engine = AiEngine::new(AiEngineConfig::default());
+        engine
+            .initialize(vec![(
+                vec![Chord::new(1, 5).unwrap(), Chord::new(5, 7).unwrap()],
+                "1".to_string(),
+                None,
+            )])
+            .unwrap();
+        engine
+    }
+
+    #[test]
+    fn test_chord_binary() {
+        assert!(validate_asset(&[0x10, 0, 0, 0, 0]).is_valid());
+
+        // Chord type index 7 is unused
+        let report = validate_asset(&[0x10, 0x38, 0, 0, 0]);
+        assert_eq!(report.asset_type, AssetType::ChordBinary);
+        assert!(!report.is_valid());
+    }
+
+    #[test]
+    fn test_trie_assets() {
+        let asset = trained_engine().export_trie_asset().unwrap();
+        let report = validate_asset(&asset);
+        assert_eq!(report.asset_type, AssetType::ChordTrie);
+        assert!(report.model_version.is_some());
+        assert!(report.is_valid(), "{}", report);
+
+        let bare = validate_asset(&asset[ASSET_HEADER_LEN..]);
+        assert_eq!(bare.asset_type, AssetType::ChordTrie);
+        assert!(bare.is_valid());
+
+        let mut padded = asset.clone();
+        padded.extend_from_slice(&[0, 0]);
+        let report = validate_asset(&padded);
+        assert!(report.is_valid());
+        assert!(report.has_warnings());
+
+        assert!(!validate_asset(&asset[..asset.len() - 1]).is_valid());
+        assert_eq!(validate_asset(&[1, 2, 3]).asset_type, AssetType::Unknown);
+
+        assert!(bare.sections.iter().any(|s| s.name == "reference_table"));
+
+        // Headerless version 1 tries still validate; future versions are reported
+        let trie = &asset[ASSET_HEADER_LEN..];
+        let limits = DeserializationLimits::default();
+        let (decoded, _) = deserialize_trie_with_references(trie, false, &limits).unwrap();
+        let plain = serialize_trie(&decoded).unwrap();
+        let legacy = validate_asset(&plain[TRIE_BINARY_HEADER_LEN..]);
+        assert!(legacy.is_valid(), "{}", legacy);
+        assert!(!legacy.has_warnings());
+        let mut future = trie.to_vec();
+        future[4] += 1;
+        let report = validate_asset(&future);
+        assert_eq!(report.asset_type, AssetType::ChordTrie);
+        assert_eq!(report.errors()[0].name, "header");
+
+        // Compressed files and compressed payloads are unpacked first
+        let packed = compress(&asset, Compression::Zstd).unwrap();
+        let report = validate_asset(&packed);
+        assert!(report.is_valid(), "{}", report);
+        assert_eq!(report.sections[0].name, "compression");
+        assert!(report.model_version.is_some());
+        let report = validate_asset(&compress(trie, Compression::Lz4).unwrap());
+        assert_eq!(report.asset_type, AssetType::ChordTrie);
+        assert!(report.is_valid(), "{}", report);
+        let report = validate_asset(&packed[..packed.len() / 2]);
+        assert_eq!(report.asset_type, AssetType::Unknown);
+        assert_eq!(report.errors()[0].name, "compression");
+    }
+
+    #[test]
+    fn test_invalid_chord_keys() {
+        let mut trie = TrieNode::new();
+        trie.add_pattern(&[vec![0x10, 0x38, 0, 0, 0]], 1);
+        let bytes = serialize_trie(&trie).unwrap();
+
+        let report = validate_asset(&bytes);
+        assert_eq!(report.asset_type, AssetType::ChordTrie);
+        assert_eq!(report.errors()[0].name, "chord_keys");
+    }
+
+    #[test]
+    fn test_token_library() {
+        let mut library = TokenLibrary::new();
+        library.add_chord_token("I".to_string(), vec![0x10, 0, 0, 0, 0]);
+        let bytes = serialize_token_library(&library).unwrap();
+        assert!(validate_asset(&bytes).is_valid());
+
+        library.add_chord_token("broken".to_string(), vec![1, 2]);
+        let report = validate_asset(&serialize_token_library(&library).unwrap());
+        assert_eq!(report.asset_type, AssetType::TokenLibrary);
+        assert_eq!(report.errors()[0].name, "chord_binaries");
+    }
+
+    #[test]
+    fn test_difficulty_model() {
+        let asset = trained_engine().export_difficulty_model_asset().unwrap();
+        let report = validate_asset(&asset);
+        assert_eq!(report.asset_type, AssetType::DifficultyModel);
+        assert!(report.is_valid(), "{}", report);
+
+        let mut model = PolynomialModel {
+            accuracy: 1.5,
+            ..Default::default()
+        };
+        model.feature_weights.remove("melodic_complexity");
+        let asset = encode_asset(
+            AssetKind::DifficultyModel,
+            ModelVersion::current().unwrap(),
+            &serde_json::to_vec(&model).unwrap(),
+        );
+        let report = validate_asset(&asset);
+        assert!(!report.is_valid());
+        assert!(report.has_warnings());
+
+        let garbage = encode_asset(
+            AssetKind::DifficultyModel,
+            ModelVersion::current().unwrap(),
+            b"{",
+        );
+        assert_eq!(validate_asset(&garbage).errors()[0].name, "model");
+    }
+}
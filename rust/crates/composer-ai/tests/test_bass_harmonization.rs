@@ -1,15 +1,40 @@
 //! Tests for bass line harmonization algorithm based on test specifications
 
-use composer_ai::{ChordProgressionSuggester, ChordProgressionTrie};
+use composer_ai::{ChordProgressionSuggester, ChordProgressionTrie, ScaleFallback};
+use composer_core::{Chord, Key, Mode, PitchClass};
 use std::sync::Arc;
 
+/// Pitch class named `name`
+fn pitch(name: &str) -> PitchClass {
+    name.parse().unwrap()
+}
+
+/// Suggester over a small corpus with several chords above the tonic
+fn trained_suggester() -> ChordProgressionSuggester {
+    let trie = Arc::new(ChordProgressionTrie::new());
+    let triad = |root: u8| Chord::triad(root).unwrap();
+    let inverted = |root: u8, inversion: u8| triad(root).with_inversion(inversion).unwrap();
+    let patterns = [
+        vec![triad(1), triad(4), triad(5), triad(1)],
+        vec![triad(1), triad(6), triad(2), Chord::seventh(5).unwrap()],
+        vec![triad(1), inverted(4, 2), triad(1)],
+        vec![inverted(6, 1), triad(2), inverted(1, 2), triad(5)],
+        vec![inverted(1, 1), triad(3), triad(4)],
+    ];
+    for (i, pattern) in patterns.iter().enumerate() {
+        trie.add_pattern(pattern, format!("song_{}", i), None)
+            .unwrap();
+    }
+    trie.calculate_all_ranks();
+    ChordProgressionSuggester::new(trie)
+}
+
 #[test]
 fn test_basic_bass_harmonization() {
-    let trie = Arc::new(ChordProgressionTrie::new());
-    let suggester = ChordProgressionSuggester::new(trie);
+    let suggester = trained_suggester();
 
     // Test basic bass harmonization from test spec
-    let result = suggester.get_magic_bass_solutions("C", "major", 5);
+    let result = suggester.get_magic_bass_solutions(pitch("C"), Mode::Major, 5);
 
     assert!(result.is_ok());
     let suggestions = result.unwrap();
@@ -30,28 +55,31 @@ fn test_basic_bass_harmonization() {
 
 #[test]
 fn test_bass_note_conversion() {
-    let trie = Arc::new(ChordProgressionTrie::new());
-    let suggester = ChordProgressionSuggester::new(trie);
+    let suggester = trained_suggester();
 
     // Test bass note conversion from test spec
-    assert_eq!(suggester.parse_bass_note("C").unwrap(), 0);
-    assert_eq!(suggester.parse_bass_note("F#").unwrap(), 6);
-    assert_eq!(suggester.parse_bass_note("Gb").unwrap(), 6);
-    assert_eq!(suggester.parse_bass_note("B").unwrap(), 11);
+    assert_eq!(pitch("C").chromatic(), 0);
+    assert_eq!(pitch("F#").chromatic(), 6);
+    assert_eq!(pitch("Gb").chromatic(), 6);
+    assert_eq!(pitch("B").chromatic(), 11);
 
     // Test invalid bass note
-    assert!(suggester.parse_bass_note("H").is_err());
+    assert!("H".parse::<PitchClass>().is_err());
+
+    // Enharmonic spellings harmonize alike
+    let sharp = suggester.get_magic_bass_solutions(pitch("F#"), Mode::Major, 5);
+    let flat = suggester.get_magic_bass_solutions(pitch("Gb"), Mode::Major, 5);
+    assert_eq!(sharp.unwrap().len(), flat.unwrap().len());
 }
 
 #[test]
 fn test_frequency_complexity_weighting() {
-    let trie = Arc::new(ChordProgressionTrie::new());
-    let suggester = ChordProgressionSuggester::new(trie);
+    let suggester = trained_suggester();
 
     // Test frequency-complexity weighting from test spec
     // Formula: weight = frequency × (1 / (1 + complexity × 0.1))
 
-    let result = suggester.get_magic_bass_solutions("C", "major", 10);
+    let result = suggester.get_magic_bass_solutions(pitch("C"), Mode::Major, 10);
     assert!(result.is_ok());
 
     let suggestions = result.unwrap();
@@ -66,12 +94,11 @@ fn test_frequency_complexity_weighting() {
 
 #[test]
 fn test_scale_compatibility_filtering() {
-    let trie = Arc::new(ChordProgressionTrie::new());
-    let suggester = ChordProgressionSuggester::new(trie);
+    let suggester = trained_suggester();
 
     // Test scale compatibility filtering from test spec
-    let major_result = suggester.get_magic_bass_solutions("C", "major", 5);
-    let minor_result = suggester.get_magic_bass_solutions("C", "minor", 5);
+    let major_result = suggester.get_magic_bass_solutions(pitch("C"), Mode::Major, 5);
+    let minor_result = suggester.get_magic_bass_solutions(pitch("C"), Mode::Minor, 5);
 
     assert!(major_result.is_ok());
     assert!(minor_result.is_ok());
@@ -88,12 +115,11 @@ fn test_scale_compatibility_filtering() {
 
 #[test]
 fn test_slash_chord_recognition() {
-    let trie = Arc::new(ChordProgressionTrie::new());
-    let suggester = ChordProgressionSuggester::new(trie);
+    let suggester = trained_suggester();
 
     // Test slash chord recognition from test spec
     // Bass note different from chord root should suggest slash chords
-    let result = suggester.get_magic_bass_solutions("E", "major", 5);
+    let result = suggester.get_magic_bass_solutions(pitch("E"), Mode::Major, 5);
 
     assert!(result.is_ok());
     let suggestions = result.unwrap();
@@ -107,11 +133,10 @@ fn test_slash_chord_recognition() {
 
 #[test]
 fn test_bass_harmonization_performance() {
-    let trie = Arc::new(ChordProgressionTrie::new());
-    let suggester = ChordProgressionSuggester::new(trie);
+    let suggester = trained_suggester();
 
     let start = std::time::Instant::now();
-    let _result = suggester.get_magic_bass_solutions("G", "major", 10);
+    let _result = suggester.get_magic_bass_solutions(pitch("G"), Mode::Major, 10);
     let elapsed = start.elapsed();
 
     // Should complete within reasonable time (sub-millisecond target)
@@ -124,17 +149,16 @@ fn test_bass_harmonization_performance() {
 
 #[test]
 fn test_bass_harmonization_edge_cases() {
-    let trie = Arc::new(ChordProgressionTrie::new());
-    let suggester = ChordProgressionSuggester::new(trie);
+    let suggester = trained_suggester();
 
     // Test edge cases
 
     // Empty scale name
-    let _result = suggester.get_magic_bass_solutions("C", "", 5);
-    // Should handle gracefully
+    assert!("".parse::<Mode>().is_err());
+    assert!("".parse::<Key>().is_err());
 
     // Zero limit
-    let result = suggester.get_magic_bass_solutions("C", "major", 0);
+    let result = suggester.get_magic_bass_solutions(pitch("C"), Mode::Major, 0);
     assert!(result.is_ok());
     let suggestions = result.unwrap();
     assert!(
@@ -143,15 +167,14 @@ fn test_bass_harmonization_edge_cases() {
     );
 
     // Very high limit
-    let result = suggester.get_magic_bass_solutions("C", "major", 1000);
+    let result = suggester.get_magic_bass_solutions(pitch("C"), Mode::Major, 1000);
     // Should not crash and should respect available suggestions
     assert!(result.is_ok());
 }
 
 #[test]
 fn test_chromatic_bass_notes() {
-    let trie = Arc::new(ChordProgressionTrie::new());
-    let suggester = ChordProgressionSuggester::new(trie);
+    let suggester = trained_suggester();
 
     // Test all chromatic bass notes
     let notes = [
@@ -159,7 +182,7 @@ fn test_chromatic_bass_notes() {
     ];
 
     for note in &notes {
-        let result = suggester.get_magic_bass_solutions(note, "major", 3);
+        let result = suggester.get_magic_bass_solutions(pitch(note), Mode::Major, 3);
         assert!(result.is_ok(), "Should handle bass note: {}", note);
 
         let suggestions = result.unwrap();
@@ -173,10 +196,9 @@ fn test_chromatic_bass_notes() {
 
 #[test]
 fn test_bass_complexity_scoring() {
-    let trie = Arc::new(ChordProgressionTrie::new());
-    let suggester = ChordProgressionSuggester::new(trie);
+    let suggester = trained_suggester();
 
-    let result = suggester.get_magic_bass_solutions("C", "major", 10);
+    let result = suggester.get_magic_bass_solutions(pitch("C"), Mode::Major, 10);
     assert!(result.is_ok());
 
     let suggestions = result.unwrap();
@@ -199,3 +221,74 @@ fn test_bass_complexity_scoring() {
         );
     }
 }
+
+#[test]
+fn test_bass_harmonization_uses_corpus() {
+    let suggester = trained_suggester();
+
+    let suggestions = suggester
+        .get_magic_bass_solutions(pitch("C"), Mode::Major, 10)
+        .unwrap();
+    // I is the most frequent chord over the tonic
+    assert_eq!(suggestions[0].chord, Chord::triad(1).unwrap());
+    assert_eq!(suggestions[0].pattern_info.rank, 1);
+    // IV 6/4 and vi6 also put the tonic in the bass
+    let chords: Vec<Chord> = suggestions.iter().map(|s| s.chord.clone()).collect();
+    assert!(chords.contains(&Chord::triad(4).unwrap().with_inversion(2).unwrap()));
+    assert!(chords.contains(&Chord::triad(6).unwrap().with_inversion(1).unwrap()));
+    assert!(!chords.contains(&Chord::triad(5).unwrap()));
+
+    // Bass notes with no chord in the corpus have no harmonization
+    assert!(suggester
+        .get_magic_bass_solutions(pitch("C#"), Mode::Major, 10)
+        .unwrap()
+        .is_empty());
+
+    // The scale places degrees on pitch classes: the minor third is Eb
+    let minor = suggester
+        .get_magic_bass_solutions(pitch("Eb"), Mode::Minor, 10)
+        .unwrap();
+    assert!(minor.iter().any(|s| s.chord == Chord::triad(3).unwrap()));
+    assert!("lydian-ish".parse::<Mode>().is_err());
+    assert!("C lydian-ish".parse::<Key>().is_err());
+}
+
+#[test]
+fn test_bass_harmonization_scale_branch() {
+    let trie = Arc::new(ChordProgressionTrie::new());
+    let triad = |root: u8| Chord::triad(root).unwrap();
+    trie.add_pattern(
+        &[triad(1), triad(5)],
+        "major".to_string(),
+        Some("C".parse().unwrap()),
+    )
+    .unwrap();
+    trie.add_pattern(
+        &[triad(6), triad(1)],
+        "minor".to_string(),
+        Some("Am".parse().unwrap()),
+    )
+    .unwrap();
+    trie.add_pattern(&[triad(4), triad(1)], "unkeyed".to_string(), None)
+        .unwrap();
+    let suggester = ChordProgressionSuggester::new(trie);
+
+    let suggestions = suggester
+        .get_magic_bass_solutions(pitch("C"), Mode::Minor, 5)
+        .unwrap();
+    assert_eq!(suggestions[0].pattern_info.branch.as_deref(), Some("minor"));
+    assert_eq!(suggestions.len(), 1);
+
+    suggester.set_scale_fallback(ScaleFallback::Never);
+    let suggestions = suggester
+        .get_magic_bass_solutions(pitch("F"), Mode::Minor, 5)
+        .unwrap();
+    assert!(suggestions.is_empty());
+
+    suggester.set_scale_fallback(ScaleFallback::Fill);
+    let suggestions = suggester
+        .get_magic_bass_solutions(pitch("F"), Mode::Minor, 5)
+        .unwrap();
+    assert_eq!(suggestions[0].chord, triad(4));
+    assert_eq!(suggestions[0].pattern_info.branch, None);
+}
@@ -1,7 +1,7 @@
 //! Tests for magic chord algorithm implementation based on test specifications
 
 use composer_ai::{ChordProgressionSuggester, ChordProgressionTrie};
-use composer_core::Chord;
+use composer_core::{Chord, Mode};
 use std::sync::Arc;
 
 #[test]
@@ -14,7 +14,7 @@ fn test_get_magic_chord_solutions_basic() {
     let following_chords = vec![Chord::new(6, 5).unwrap()]; // vi
 
     let result =
-        suggester.get_magic_chord_solutions(&previous_chords, &following_chords, "major", 10);
+        suggester.get_magic_chord_solutions(&previous_chords, &following_chords, Mode::Major, 10);
 
     assert!(result.is_ok());
     let suggestions = result.unwrap();
@@ -47,7 +47,7 @@ fn test_empty_context_handling() {
     let suggester = ChordProgressionSuggester::new(trie);
 
     // Test empty context handling from test spec
-    let result = suggester.get_magic_chord_solutions(&[], &[], "major", 10);
+    let result = suggester.get_magic_chord_solutions(&[], &[], Mode::Major, 10);
 
     assert!(result.is_ok());
     let suggestions = result.unwrap();
@@ -74,7 +74,7 @@ fn test_complex_context_weighting() {
     ];
 
     let result =
-        suggester.get_magic_chord_solutions(&previous_chords, &following_chords, "major", 5);
+        suggester.get_magic_chord_solutions(&previous_chords, &following_chords, Mode::Major, 5);
 
     assert!(result.is_ok());
     let suggestions = result.unwrap();
@@ -131,7 +131,7 @@ fn test_deduplication_logic() {
     let trie = Arc::new(ChordProgressionTrie::new());
     let suggester = ChordProgressionSuggester::new(trie);
 
-    let result = suggester.get_magic_chord_solutions(&[], &[], "major", 5);
+    let result = suggester.get_magic_chord_solutions(&[], &[], Mode::Major, 5);
 
     assert!(result.is_ok());
     let suggestions = result.unwrap();
@@ -153,7 +153,7 @@ fn test_limit_validation() {
     let suggester = ChordProgressionSuggester::new(trie);
 
     // Test limit parameter validation from test spec
-    let result = suggester.get_magic_chord_solutions(&[], &[], "major", 101);
+    let result = suggester.get_magic_chord_solutions(&[], &[], Mode::Major, 101);
 
     assert!(result.is_err());
     if let Err(error) = result {
@@ -174,7 +174,7 @@ fn test_pattern_length_validation() {
         .map(|i| Chord::new((i % 7) + 1, 5).unwrap())
         .collect();
 
-    let result = suggester.get_magic_chord_solutions(&long_pattern, &[], "major", 5);
+    let result = suggester.get_magic_chord_solutions(&long_pattern, &[], Mode::Major, 5);
 
     assert!(result.is_err());
     if let Err(error) = result {
@@ -193,7 +193,7 @@ fn test_performance_monitoring() {
     // Test suggestion generation time tracking
     let _initial_time = suggester.avg_suggestion_time_ms();
 
-    let _result = suggester.get_magic_chord_solutions(&[], &[], "major", 5);
+    let _result = suggester.get_magic_chord_solutions(&[], &[], Mode::Major, 5);
 
     // Performance metrics should be updated
     let final_time = suggester.avg_suggestion_time_ms();
@@ -263,7 +263,7 @@ fn test_model_saturation_prevention() {
     let analyzer = MusicalAnalyzer::new(trie);
 
     // Create progressions with varying complexity to test score spread
-    let progressions = vec![
+    let progressions = [
         // Very simple
         vec![Chord::new(1, 5).unwrap()],
         // Simple
@@ -1,6 +1,7 @@
 //! Tests for scale degree harmonization algorithm based on test specifications
 
 use composer_ai::{ChordProgressionSuggester, ChordProgressionTrie};
+use composer_core::Mode;
 use std::sync::Arc;
 
 #[test]
@@ -12,7 +13,7 @@ fn test_bit_mask_conversion() {
     // scaleDegreeBits=0b1010100 (degrees 1, 3, 6)
     let scale_degree_bits = 0b1010100; // Bits 2, 4, 6 set (degrees 3, 5, 7 in 1-based)
 
-    let result = suggester.get_harmonize_by_sd_solutions(scale_degree_bits, "major", 5);
+    let result = suggester.get_harmonize_by_sd_solutions(scale_degree_bits, Mode::Major, 5);
 
     assert!(result.is_ok());
     let suggestions = result.unwrap();
@@ -35,7 +36,7 @@ fn test_multi_factor_scoring() {
     // Test multi-factor scoring from test spec lines 324-334
     let scale_degree_bits = 0b1000001; // Degrees 1 and 7
 
-    let result = suggester.get_harmonize_by_sd_solutions(scale_degree_bits, "major", 10);
+    let result = suggester.get_harmonize_by_sd_solutions(scale_degree_bits, Mode::Major, 10);
 
     assert!(result.is_ok());
     let suggestions = result.unwrap();
@@ -76,7 +77,7 @@ fn test_chromatic_extension_support() {
     // Test chromatic extension support from test spec
     let chromatic_bits = 0b111111111111; // All 12 chromatic notes
 
-    let result = suggester.get_harmonize_by_sd_solutions(chromatic_bits, "major", 5);
+    let result = suggester.get_harmonize_by_sd_solutions(chromatic_bits, Mode::Major, 5);
 
     assert!(result.is_ok());
     let suggestions = result.unwrap();
@@ -100,8 +101,8 @@ fn test_enharmonic_equivalent_handling() {
     let bits1 = 0b0000010; // Degree 2 (D)
     let bits2 = 0b0000010; // Same bit pattern
 
-    let result1 = suggester.get_harmonize_by_sd_solutions(bits1, "major", 5);
-    let result2 = suggester.get_harmonize_by_sd_solutions(bits2, "major", 5);
+    let result1 = suggester.get_harmonize_by_sd_solutions(bits1, Mode::Major, 5);
+    let result2 = suggester.get_harmonize_by_sd_solutions(bits2, Mode::Major, 5);
 
     assert!(result1.is_ok());
     assert!(result2.is_ok());
@@ -126,7 +127,7 @@ fn test_set_operations_efficiency() {
     let complex_bits = 0b101010101010; // Complex scale degree combination
 
     let start = std::time::Instant::now();
-    let result = suggester.get_harmonize_by_sd_solutions(complex_bits, "major", 10);
+    let result = suggester.get_harmonize_by_sd_solutions(complex_bits, Mode::Major, 10);
     let elapsed = start.elapsed();
 
     assert!(result.is_ok());
@@ -147,7 +148,7 @@ fn test_empty_scale_degrees() {
     // Test empty scale degrees
     let empty_bits = 0b000000000000; // No degrees set
 
-    let result = suggester.get_harmonize_by_sd_solutions(empty_bits, "major", 5);
+    let result = suggester.get_harmonize_by_sd_solutions(empty_bits, Mode::Major, 5);
 
     assert!(result.is_err());
     if let Err(error) = result {
@@ -166,7 +167,7 @@ fn test_single_scale_degree() {
     // Test single scale degree harmonization
     let single_bit = 0b0000001; // Just degree 1 (tonic)
 
-    let result = suggester.get_harmonize_by_sd_solutions(single_bit, "major", 5);
+    let result = suggester.get_harmonize_by_sd_solutions(single_bit, Mode::Major, 5);
 
     assert!(result.is_ok());
     let suggestions = result.unwrap();
@@ -194,7 +195,7 @@ fn test_multiple_scale_degrees() {
     // Test multiple scale degrees (typical triad)
     let triad_bits = 0b0010101; // Degrees 1, 3, 5 (major triad degrees)
 
-    let result = suggester.get_harmonize_by_sd_solutions(triad_bits, "major", 10);
+    let result = suggester.get_harmonize_by_sd_solutions(triad_bits, Mode::Major, 10);
 
     assert!(result.is_ok());
     let suggestions = result.unwrap();
@@ -221,7 +222,7 @@ fn test_scoring_formula_validation() {
 
     let scale_degree_bits = 0b0000101; // Degrees 1 and 3
 
-    let result = suggester.get_harmonize_by_sd_solutions(scale_degree_bits, "major", 5);
+    let result = suggester.get_harmonize_by_sd_solutions(scale_degree_bits, Mode::Major, 5);
 
     assert!(result.is_ok());
     let suggestions = result.unwrap();
@@ -0,0 +1,360 @@
+#ifndef COMPOSER_H
+#define COMPOSER_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+// Size in bytes of a serialized chord
+#define COMPOSER_CHORD_BINARY_SIZE 5
+
+// Scale context for theory analysis
+typedef enum ComposerScale {
+  // Major (Ionian)
+  COMPOSER_SCALE_MAJOR = 0,
+  // Natural minor (Aeolian)
+  COMPOSER_SCALE_MINOR = 1,
+  // Harmonic minor
+  COMPOSER_SCALE_HARMONIC_MINOR = 2,
+  // Dorian mode
+  COMPOSER_SCALE_DORIAN = 3,
+  // Mixolydian mode
+  COMPOSER_SCALE_MIXOLYDIAN = 4,
+  // All twelve pitch classes
+  COMPOSER_SCALE_CHROMATIC = 5,
+} ComposerScale;
+
+// Result code returned by every fallible C function
+typedef enum ComposerStatus {
+  // The call succeeded
+  COMPOSER_STATUS_OK = 0,
+  // A required pointer argument was null
+  COMPOSER_STATUS_NULL_POINTER = 1,
+  // A string argument was not valid UTF-8
+  COMPOSER_STATUS_INVALID_UTF8 = 2,
+  // The caller's output buffer is too small; the required size was reported
+  COMPOSER_STATUS_BUFFER_TOO_SMALL = 3,
+  // A chord or theory argument was out of range
+  COMPOSER_STATUS_INVALID_ARGUMENT = 4,
+  // Binary or hex data could not be decoded
+  COMPOSER_STATUS_SERIALIZATION_FAILED = 5,
+  // The engine must be trained or loaded before this call
+  COMPOSER_STATUS_ENGINE_NOT_INITIALIZED = 6,
+  // The AI engine reported an error
+  COMPOSER_STATUS_ENGINE_FAILED = 7,
+  // The call panicked; handles it was given may be left half-updated
+  COMPOSER_STATUS_INTERNAL = 8,
+} ComposerStatus;
+
+// Opaque chord handle, created by the `composer_chord_*` constructors and released
+// with [`composer_chord_free`]
+typedef struct ComposerChord ComposerChord;
+
+// Opaque engine handle created by [`composer_engine_new`] and released with
+// [`composer_engine_free`].
+//
+// Engine calls take `&self` internally, so one handle may be shared between an
+// audio plugin's UI and worker threads.
+typedef struct ComposerEngine ComposerEngine;
+
+// A training progression: an array of `len` chord handles
+typedef struct ComposerProgression {
+  // Chord handles in playing order
+  const ComposerChord *const *chords;
+  // Number of chords
+  size_t len;
+} ComposerProgression;
+
+// One ranked chord suggestion
+typedef struct ComposerSuggestion {
+  // Suggested chord in its 5-byte binary form, decodable with
+  // `composer_chord_deserialize`
+  uint8_t binary[COMPOSER_CHORD_BINARY_SIZE];
+  // Scale-degree root of the suggested chord
+  uint8_t root;
+  // Chord type of the suggested chord
+  uint8_t chord_type;
+  // Inversion of the suggested chord
+  uint8_t inversion;
+  // Suggestion confidence (0.0-1.0)
+  double confidence;
+  // Final ranking score (0.0-1.0)
+  double weighted_score;
+} ComposerSuggestion;
+
+// Difficulty assessment of a progression
+typedef struct ComposerDifficulty {
+  // Overall difficulty (0.0-10.0)
+  double overall_score;
+  // Harmonic complexity (0.0-10.0)
+  double harmonic_complexity;
+  // Rhythmic complexity (0.0-10.0)
+  double rhythmic_complexity;
+  // Technical complexity (0.0-10.0)
+  double technical_complexity;
+  // Melodic complexity (0.0-10.0)
+  double melodic_complexity;
+  // Confidence in the assessment (0.0-1.0)
+  double confidence;
+  // Lower bound of the overall score interval
+  double score_lower;
+  // Upper bound of the overall score interval
+  double score_upper;
+  // Whether the progression was too short for a stable score
+  bool insufficient_data;
+} ComposerDifficulty;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Create a chord from a scale-degree root (1-7) and chord type (5, 7, 9, 11, 13).
+//
+// On success `*out_chord` receives a new handle owned by the caller.
+//
+// # Safety
+//
+// `out_chord` must be valid for a write.
+ComposerStatus composer_chord_new(uint8_t root, uint8_t chord_type, ComposerChord **out_chord);
+
+// Create a rest
+//
+// # Safety
+//
+// `out_chord` must be valid for a write.
+ComposerStatus composer_chord_rest(ComposerChord **out_chord);
+
+// Create an independent copy of a chord
+//
+// # Safety
+//
+// `chord` must be a valid handle and `out_chord` valid for a write.
+ComposerStatus composer_chord_clone(const ComposerChord *chord, ComposerChord **out_chord);
+
+// Release a chord handle. Passing null is a no-op.
+//
+// # Safety
+//
+// `chord` must be null or a handle that has not already been freed.
+void composer_chord_free(ComposerChord *chord);
+
+// Scale-degree root of a chord, or 0 for a rest or null handle
+//
+// # Safety
+//
+// `chord` must be null or a valid handle.
+uint8_t composer_chord_root(const ComposerChord *chord);
+
+// Chord type (5, 7, 9, 11, 13), or 0 for a null handle
+//
+// # Safety
+//
+// `chord` must be null or a valid handle.
+uint8_t composer_chord_type(const ComposerChord *chord);
+
+// Inversion of a chord, or 0 for a null handle
+//
+// # Safety
+//
+// `chord` must be null or a valid handle.
+uint8_t composer_chord_inversion(const ComposerChord *chord);
+
+// Whether a chord is a rest; false for a null handle
+//
+// # Safety
+//
+// `chord` must be null or a valid handle.
+bool composer_chord_is_rest(const ComposerChord *chord);
+
+// Set the inversion of a chord in place
+//
+// # Safety
+//
+// `chord` must be a valid handle not used concurrently from another thread.
+ComposerStatus composer_chord_set_inversion(ComposerChord *chord, uint8_t inversion);
+
+// Add an alteration such as `"b9"` or `"#11"` to a chord in place
+//
+// # Safety
+//
+// `chord` must be a valid handle not used concurrently from another thread, and
+// `alteration` a NUL-terminated string.
+ComposerStatus composer_chord_add_alteration(ComposerChord *chord, const char *alteration);
+
+// Write the chord's display form into `buf` as a NUL-terminated string.
+//
+// `*out_len` receives the string length without the terminator, even when the call
+// fails with `COMPOSER_STATUS_BUFFER_TOO_SMALL`, so callers can pass a null buffer
+// to size it first.
+//
+// # Safety
+//
+// `chord` must be a valid handle; `buf` must be null or valid for `buf_len` bytes;
+// `out_len` must be null or valid for a write.
+ComposerStatus composer_chord_to_string(const ComposerChord *chord,
+                                        char *buf,
+                                        size_t buf_len,
+                                        size_t *out_len);
+
+// Harmonic complexity of a chord on the 0-10 scale
+//
+// # Safety
+//
+// `chord` must be a valid handle and `out_complexity` valid for a write.
+ComposerStatus composer_chord_complexity(const ComposerChord *chord,
+                                         ComposerScale scale,
+                                         double *out_complexity);
+
+// Write the Roman numeral for a chord in `scale` (e.g. `"V7/vi"`) into `buf`.
+//
+// Buffer handling follows [`composer_chord_to_string`].
+//
+// # Safety
+//
+// Same requirements as [`composer_chord_to_string`].
+ComposerStatus composer_chord_roman_numeral(const ComposerChord *chord,
+                                            ComposerScale scale,
+                                            char *buf,
+                                            size_t buf_len,
+                                            size_t *out_len);
+
+// Serialize a chord into exactly [`COMPOSER_CHORD_BINARY_SIZE`] bytes at `out_bytes`
+//
+// # Safety
+//
+// `chord` must be a valid handle and `out_bytes` valid for 5 bytes of writes.
+ComposerStatus composer_chord_serialize(const ComposerChord *chord, uint8_t *out_bytes);
+
+// Decode a chord from its 5-byte binary form
+//
+// # Safety
+//
+// `bytes` must point to `len` readable bytes and `out_chord` be valid for a write.
+ComposerStatus composer_chord_deserialize(const uint8_t *bytes,
+                                          size_t len,
+                                          ComposerChord **out_chord);
+
+// Write the chord's 10-character hex form into `buf`.
+//
+// Buffer handling follows [`composer_chord_to_string`].
+//
+// # Safety
+//
+// Same requirements as [`composer_chord_to_string`].
+ComposerStatus composer_chord_to_hex(const ComposerChord *chord,
+                                     char *buf,
+                                     size_t buf_len,
+                                     size_t *out_len);
+
+// Decode a chord from its hex form
+//
+// # Safety
+//
+// `hex` must be a NUL-terminated string and `out_chord` valid for a write.
+ComposerStatus composer_chord_from_hex(const char *hex, ComposerChord **out_chord);
+
+// Create an untrained engine with the given memory budget (0 uses the default).
+//
+// # Safety
+//
+// `out_engine` must be valid for a write.
+ComposerStatus composer_engine_new(uint32_t max_memory_mb, ComposerEngine **out_engine);
+
+// Release an engine handle. Passing null is a no-op.
+//
+// # Safety
+//
+// `engine` must be null or a handle that has not already been freed, and no other
+// thread may be using it.
+void composer_engine_free(ComposerEngine *engine);
+
+// Train the engine on `count` progressions. The chord handles are copied and
+// remain owned by the caller.
+//
+// # Safety
+//
+// `engine` must be a valid handle and `progressions` must point to `count` entries
+// whose `chords` arrays hold `len` valid chord handles each.
+ComposerStatus composer_engine_train(const ComposerEngine *engine,
+                                     const ComposerProgression *progressions,
+                                     size_t count);
+
+// Load a trained engine from a trie asset produced by
+// [`composer_engine_export_asset`] or the other bindings
+//
+// # Safety
+//
+// `engine` must be a valid handle and `data` must point to `len` readable bytes.
+ComposerStatus composer_engine_load_asset(const ComposerEngine *engine,
+                                          const uint8_t *data,
+                                          size_t len);
+
+// Export the trained patterns as a trie asset, e.g. to store in plugin state.
+//
+// `*out_len` always receives the asset size; pass a null buffer to query it.
+//
+// # Safety
+//
+// `engine` must be a valid handle; `buf` must be null or valid for `buf_len` bytes;
+// `out_len` must be null or valid for a write.
+ComposerStatus composer_engine_export_asset(const ComposerEngine *engine,
+                                            uint8_t *buf,
+                                            size_t buf_len,
+                                            size_t *out_len);
+
+// Whether the engine has been trained or loaded; false for a null handle
+//
+// # Safety
+//
+// `engine` must be null or a valid handle.
+bool composer_engine_is_initialized(const ComposerEngine *engine);
+
+// Suggest chords to follow `pattern`, best first.
+//
+// Suggestions below `min_confidence` (0.0-1.0) are dropped. Up to `capacity`
+// suggestions are written to `out_suggestions` and their number to `*out_count`.
+//
+// # Safety
+//
+// `engine` must be a valid handle; `pattern` must point to `pattern_len` valid
+// chord handles; `out_suggestions` must be valid for `capacity` writes and
+// `out_count` for one.
+ComposerStatus composer_engine_suggest(const ComposerEngine *engine,
+                                       const ComposerChord *const *pattern,
+                                       size_t pattern_len,
+                                       double min_confidence,
+                                       ComposerSuggestion *out_suggestions,
+                                       size_t capacity,
+                                       size_t *out_count);
+
+// Assess the playing difficulty of a progression.
+//
+// Pass `tempo_bpm <= 0` to use the default tempo and `beats_per_bar == 0` to
+// assume common time.
+//
+// # Safety
+//
+// `engine` must be a valid handle; `progression` must point to `len` valid chord
+// handles; `out_difficulty` must be valid for a write.
+ComposerStatus composer_engine_assess_difficulty(const ComposerEngine *engine,
+                                                 const ComposerChord *const *progression,
+                                                 size_t len,
+                                                 double tempo_bpm,
+                                                 uint8_t beats_per_bar,
+                                                 uint8_t beat_unit,
+                                                 ComposerDifficulty *out_difficulty);
+
+// Message describing the most recent failure on the calling thread.
+//
+// Returns null if no call on this thread has failed yet. The string is owned by
+// the library and stays valid until the next failing call on the same thread.
+const char *composer_last_error_message(void);
+
+// Library version as a NUL-terminated string owned by the library
+const char *composer_version(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  // COMPOSER_H
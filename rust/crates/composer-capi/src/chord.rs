@@ -0,0 +1,452 @@
+//! Chord handles, theory analysis and chord serialization
+
+use crate::error::{
+    c_str, copy_str, deref, deref_mut, run, slice, write_out, CapiError, CapiResult, ComposerStatus,
+};
+use composer_core::{get_chord_complexity, get_relative_chord_graphic, Chord, Mode, ScaleType};
+use composer_serialization::{
+    chord_binary_to_hex, deserialize_chord, hex_to_chord_binary, serialize_chord, ChordBinary,
+    SerializationError,
+};
+use std::ffi::c_char;
+
+/// Size in bytes of a serialized chord
+pub const COMPOSER_CHORD_BINARY_SIZE: usize = 5;
+
+/// Opaque chord handle, created by the `composer_chord_*` constructors and released
+/// with [`composer_chord_free`]
+pub struct ComposerChord(pub(crate) Chord);
+
+/// Scale context for theory analysis
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComposerScale {
+    /// Major (Ionian)
+    Major = 0,
+    /// Natural minor (Aeolian)
+    Minor = 1,
+    /// Harmonic minor
+    HarmonicMinor = 2,
+    /// Dorian mode
+    Dorian = 3,
+    /// Mixolydian mode
+    Mixolydian = 4,
+    /// All twelve pitch classes
+    Chromatic = 5,
+}
+
+impl From<ComposerScale> for ScaleType {
+    fn from(scale: ComposerScale) -> Self {
+        match scale {
+            ComposerScale::Major => ScaleType::Major,
+            ComposerScale::Minor => ScaleType::Minor,
+            ComposerScale::HarmonicMinor => ScaleType::HarmonicMinor,
+            ComposerScale::Dorian => ScaleType::Dorian,
+            ComposerScale::Mixolydian => ScaleType::Mixolydian,
+            ComposerScale::Chromatic => ScaleType::Chromatic,
+        }
+    }
+}
+
+/// Hand a new chord handle to the caller through `out_chord`
+///
+/// # Safety
+///
+/// `out_chord` must be null or valid for a write.
+pub(crate) unsafe fn write_handle(
+    out_chord: *mut *mut ComposerChord,
+    chord: Chord,
+) -> CapiResult<()> {
+    if out_chord.is_null() {
+        return Err(CapiError::NullPointer("out_chord"));
+    }
+    out_chord.write(Box::into_raw(Box::new(ComposerChord(chord))));
+    Ok(())
+}
+
+/// Create a chord from a scale-degree root (1-7) and chord type (5, 7, 9, 11, 13).
+///
+/// On success `*out_chord` receives a new handle owned by the caller.
+///
+/// # Safety
+///
+/// `out_chord` must be valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn composer_chord_new(
+    root: u8,
+    chord_type: u8,
+    out_chord: *mut *mut ComposerChord,
+) -> ComposerStatus {
+    run(|| {
+        let chord = Chord::new(root, chord_type)?;
+        write_handle(out_chord, chord)
+    })
+}
+
+/// Create a rest
+///
+/// # Safety
+///
+/// `out_chord` must be valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn composer_chord_rest(out_chord: *mut *mut ComposerChord) -> ComposerStatus {
+    run(|| write_handle(out_chord, Chord::rest()))
+}
+
+/// Create an independent copy of a chord
+///
+/// # Safety
+///
+/// `chord` must be a valid handle and `out_chord` valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn composer_chord_clone(
+    chord: *const ComposerChord,
+    out_chord: *mut *mut ComposerChord,
+) -> ComposerStatus {
+    run(|| {
+        let chord = deref(chord, "chord")?.0.clone();
+        write_handle(out_chord, chord)
+    })
+}
+
+/// Release a chord handle. Passing null is a no-op.
+///
+/// # Safety
+///
+/// `chord` must be null or a handle that has not already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn composer_chord_free(chord: *mut ComposerChord) {
+    if !chord.is_null() {
+        drop(Box::from_raw(chord));
+    }
+}
+
+/// Scale-degree root of a chord, or 0 for a rest or null handle
+///
+/// # Safety
+///
+/// `chord` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn composer_chord_root(chord: *const ComposerChord) -> u8 {
+    chord.as_ref().map_or(0, |chord| chord.0.root)
+}
+
+/// Chord type (5, 7, 9, 11, 13), or 0 for a null handle
+///
+/// # Safety
+///
+/// `chord` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn composer_chord_type(chord: *const ComposerChord) -> u8 {
+    chord.as_ref().map_or(0, |chord| chord.0.chord_type)
+}
+
+/// Inversion of a chord, or 0 for a null handle
+///
+/// # Safety
+///
+/// `chord` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn composer_chord_inversion(chord: *const ComposerChord) -> u8 {
+    chord.as_ref().map_or(0, |chord| chord.0.inversion)
+}
+
+/// Whether a chord is a rest; false for a null handle
+///
+/// # Safety
+///
+/// `chord` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn composer_chord_is_rest(chord: *const ComposerChord) -> bool {
+    chord.as_ref().is_some_and(|chord| chord.0.is_rest)
+}
+
+/// Set the inversion of a chord in place
+///
+/// # Safety
+///
+/// `chord` must be a valid handle not used concurrently from another thread.
+#[no_mangle]
+pub unsafe extern "C" fn composer_chord_set_inversion(
+    chord: *mut ComposerChord,
+    inversion: u8,
+) -> ComposerStatus {
+    run(|| {
+        let chord = deref_mut(chord, "chord")?;
+        chord.0 = chord.0.clone().with_inversion(inversion)?;
+        Ok(())
+    })
+}
+
+/// Add an alteration such as `"b9"` or `"#11"` to a chord in place
+///
+/// # Safety
+///
+/// `chord` must be a valid handle not used concurrently from another thread, and
+/// `alteration` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn composer_chord_add_alteration(
+    chord: *mut ComposerChord,
+    alteration: *const c_char,
+) -> ComposerStatus {
+    run(|| {
+        let alteration = c_str(alteration, "alteration")?;
+        let chord = deref_mut(chord, "chord")?;
+        chord.0 = chord.0.clone().with_alteration(alteration)?;
+        Ok(())
+    })
+}
+
+/// Write the chord's display form into `buf` as a NUL-terminated string.
+///
+/// `*out_len` receives the string length without the terminator, even when the call
+/// fails with `COMPOSER_STATUS_BUFFER_TOO_SMALL`, so callers can pass a null buffer
+/// to size it first.
+///
+/// # Safety
+///
+/// `chord` must be a valid handle; `buf` must be null or valid for `buf_len` bytes;
+/// `out_len` must be null or valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn composer_chord_to_string(
+    chord: *const ComposerChord,
+    buf: *mut c_char,
+    buf_len: usize,
+    out_len: *mut usize,
+) -> ComposerStatus {
+    run(|| {
+        let chord = deref(chord, "chord")?;
+        copy_str(&chord.0.to_string(), buf, buf_len, out_len)
+    })
+}
+
+/// Harmonic complexity of a chord on the 0-10 scale
+///
+/// # Safety
+///
+/// `chord` must be a valid handle and `out_complexity` valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn composer_chord_complexity(
+    chord: *const ComposerChord,
+    scale: ComposerScale,
+    out_complexity: *mut f64,
+) -> ComposerStatus {
+    run(|| {
+        let chord = deref(chord, "chord")?;
+        let mode = Mode::from_name(ScaleType::from(scale).name()).unwrap_or_default();
+        let complexity = get_chord_complexity(&chord.0, mode)?;
+        write_out(out_complexity, complexity, "out_complexity")
+    })
+}
+
+/// Write the Roman numeral for a chord in `scale` (e.g. `"V7/vi"`) into `buf`.
+///
+/// Buffer handling follows [`composer_chord_to_string`].
+///
+/// # Safety
+///
+/// Same requirements as [`composer_chord_to_string`].
+#[no_mangle]
+pub unsafe extern "C" fn composer_chord_roman_numeral(
+    chord: *const ComposerChord,
+    scale: ComposerScale,
+    buf: *mut c_char,
+    buf_len: usize,
+    out_len: *mut usize,
+) -> ComposerStatus {
+    run(|| {
+        let chord = deref(chord, "chord")?;
+        let graphic = get_relative_chord_graphic(&chord.0, &ScaleType::from(scale).fingerprint())?;
+        copy_str(&graphic.full_symbol(), buf, buf_len, out_len)
+    })
+}
+
+/// Serialize a chord into exactly [`COMPOSER_CHORD_BINARY_SIZE`] bytes at `out_bytes`
+///
+/// # Safety
+///
+/// `chord` must be a valid handle and `out_bytes` valid for 5 bytes of writes.
+#[no_mangle]
+pub unsafe extern "C" fn composer_chord_serialize(
+    chord: *const ComposerChord,
+    out_bytes: *mut u8,
+) -> ComposerStatus {
+    run(|| {
+        let binary = serialize_chord(&deref(chord, "chord")?.0)?;
+        write_out(out_bytes.cast::<ChordBinary>(), binary, "out_bytes")
+    })
+}
+
+/// Decode a chord from its 5-byte binary form
+///
+/// # Safety
+///
+/// `bytes` must point to `len` readable bytes and `out_chord` be valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn composer_chord_deserialize(
+    bytes: *const u8,
+    len: usize,
+    out_chord: *mut *mut ComposerChord,
+) -> ComposerStatus {
+    run(|| {
+        let binary: ChordBinary = slice(bytes, len, "bytes")?.try_into().map_err(|_| {
+            SerializationError::InvalidFormat {
+                message: format!("Expected {} bytes, got {}", COMPOSER_CHORD_BINARY_SIZE, len),
+            }
+        })?;
+        let chord = deserialize_chord(&binary)?;
+        write_handle(out_chord, chord)
+    })
+}
+
+/// Write the chord's 10-character hex form into `buf`.
+///
+/// Buffer handling follows [`composer_chord_to_string`].
+///
+/// # Safety
+///
+/// Same requirements as [`composer_chord_to_string`].
+#[no_mangle]
+pub unsafe extern "C" fn composer_chord_to_hex(
+    chord: *const ComposerChord,
+    buf: *mut c_char,
+    buf_len: usize,
+    out_len: *mut usize,
+) -> ComposerStatus {
+    run(|| {
+        let binary = serialize_chord(&deref(chord, "chord")?.0)?;
+        copy_str(&chord_binary_to_hex(&binary), buf, buf_len, out_len)
+    })
+}
+
+/// Decode a chord from its hex form
+///
+/// # Safety
+///
+/// `hex` must be a NUL-terminated string and `out_chord` valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn composer_chord_from_hex(
+    hex: *const c_char,
+    out_chord: *mut *mut ComposerChord,
+) -> ComposerStatus {
+    run(|| {
+        let binary = hex_to_chord_binary(c_str(hex, "hex")?)?;
+        let chord = deserialize_chord(&binary)?;
+        write_handle(out_chord, chord)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+    use std::ptr;
+
+    #[test]
+    fn test_chord_lifecycle_and_analysis() {
+        unsafe {
+            let mut chord = ptr::null_mut();
+            assert_eq!(composer_chord_new(5, 7, &mut chord), ComposerStatus::Ok);
+            assert_eq!(composer_chord_root(chord), 5);
+            assert_eq!(composer_chord_type(chord), 7);
+            assert_eq!(composer_chord_set_inversion(chord, 1), ComposerStatus::Ok);
+            assert_eq!(composer_chord_inversion(chord), 1);
+
+            let mut complexity = 0.0;
+            let status = composer_chord_complexity(chord, ComposerScale::Major, &mut complexity);
+            assert_eq!(status, ComposerStatus::Ok);
+            assert!((0.0..=10.0).contains(&complexity));
+
+            let mut len = 0;
+            let status = composer_chord_roman_numeral(
+                chord,
+                ComposerScale::Major,
+                ptr::null_mut(),
+                0,
+                &mut len,
+            );
+            assert_eq!(status, ComposerStatus::BufferTooSmall);
+            let mut buf = vec![0 as c_char; len + 1];
+            let status = composer_chord_roman_numeral(
+                chord,
+                ComposerScale::Major,
+                buf.as_mut_ptr(),
+                buf.len(),
+                &mut len,
+            );
+            assert_eq!(status, ComposerStatus::Ok);
+            assert!(CStr::from_ptr(buf.as_ptr())
+                .to_str()
+                .unwrap()
+                .starts_with('V'));
+
+            composer_chord_free(chord);
+            composer_chord_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_chord_serialization_roundtrip() {
+        unsafe {
+            let mut chord = ptr::null_mut();
+            assert_eq!(composer_chord_new(2, 9, &mut chord), ComposerStatus::Ok);
+            let alteration = c"b5";
+            assert_eq!(
+                composer_chord_add_alteration(chord, alteration.as_ptr()),
+                ComposerStatus::Ok
+            );
+
+            let mut bytes = [0u8; COMPOSER_CHORD_BINARY_SIZE];
+            assert_eq!(
+                composer_chord_serialize(chord, bytes.as_mut_ptr()),
+                ComposerStatus::Ok
+            );
+            let mut decoded = ptr::null_mut();
+            assert_eq!(
+                composer_chord_deserialize(bytes.as_ptr(), bytes.len(), &mut decoded),
+                ComposerStatus::Ok
+            );
+            assert_eq!((*decoded).0.alterations, (*chord).0.alterations);
+
+            let mut hex = [0 as c_char; 11];
+            let mut len = 0;
+            assert_eq!(
+                composer_chord_to_hex(chord, hex.as_mut_ptr(), hex.len(), &mut len),
+                ComposerStatus::Ok
+            );
+            let mut from_hex = ptr::null_mut();
+            assert_eq!(
+                composer_chord_from_hex(hex.as_ptr(), &mut from_hex),
+                ComposerStatus::Ok
+            );
+            assert_eq!((*from_hex).0.root, 2);
+
+            for handle in [chord, decoded, from_hex] {
+                composer_chord_free(handle);
+            }
+        }
+    }
+
+    #[test]
+    fn test_errors_set_status_and_message() {
+        unsafe {
+            let mut chord = ptr::null_mut();
+            assert_eq!(
+                composer_chord_new(9, 5, &mut chord),
+                ComposerStatus::InvalidArgument
+            );
+            assert!(chord.is_null());
+            let message = CStr::from_ptr(crate::composer_last_error_message());
+            assert!(message.to_str().unwrap().contains("Chord theory error"));
+
+            assert_eq!(
+                composer_chord_new(1, 5, ptr::null_mut()),
+                ComposerStatus::NullPointer
+            );
+            assert_eq!(
+                composer_chord_deserialize([0u8; 3].as_ptr(), 3, &mut chord),
+                ComposerStatus::SerializationFailed
+            );
+        }
+    }
+}
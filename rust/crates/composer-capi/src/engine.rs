@@ -0,0 +1,421 @@
+//! AI engine handles: training, asset loading, suggestions and difficulty
+
+use crate::chord::{ComposerChord, COMPOSER_CHORD_BINARY_SIZE};
+use crate::error::{chords, copy_bytes, deref, run, slice, write_out, CapiError, ComposerStatus};
+use composer_ai::{AiEngine, AiEngineConfig, SuggestionConfig, SuggestionContext};
+use composer_serialization::serialize_chord;
+
+/// Opaque engine handle created by [`composer_engine_new`] and released with
+/// [`composer_engine_free`].
+///
+/// Engine calls take `&self` internally, so one handle may be shared between an
+/// audio plugin's UI and worker threads.
+pub struct ComposerEngine(AiEngine);
+
+/// A training progression: an array of `len` chord handles
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ComposerProgression {
+    /// Chord handles in playing order
+    pub chords: *const *const ComposerChord,
+    /// Number of chords
+    pub len: usize,
+}
+
+/// One ranked chord suggestion
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ComposerSuggestion {
+    /// Suggested chord in its 5-byte binary form, decodable with
+    /// `composer_chord_deserialize`
+    pub binary: [u8; COMPOSER_CHORD_BINARY_SIZE],
+    /// Scale-degree root of the suggested chord
+    pub root: u8,
+    /// Chord type of the suggested chord
+    pub chord_type: u8,
+    /// Inversion of the suggested chord
+    pub inversion: u8,
+    /// Suggestion confidence (0.0-1.0)
+    pub confidence: f64,
+    /// Final ranking score (0.0-1.0)
+    pub weighted_score: f64,
+}
+
+/// Difficulty assessment of a progression
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ComposerDifficulty {
+    /// Overall difficulty (0.0-10.0)
+    pub overall_score: f64,
+    /// Harmonic complexity (0.0-10.0)
+    pub harmonic_complexity: f64,
+    /// Rhythmic complexity (0.0-10.0)
+    pub rhythmic_complexity: f64,
+    /// Technical complexity (0.0-10.0)
+    pub technical_complexity: f64,
+    /// Melodic complexity (0.0-10.0)
+    pub melodic_complexity: f64,
+    /// Confidence in the assessment (0.0-1.0)
+    pub confidence: f64,
+    /// Lower bound of the overall score interval
+    pub score_lower: f64,
+    /// Upper bound of the overall score interval
+    pub score_upper: f64,
+    /// Whether the progression was too short for a stable score
+    pub insufficient_data: bool,
+}
+
+/// Create an untrained engine with the given memory budget (0 uses the default).
+///
+/// # Safety
+///
+/// `out_engine` must be valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn composer_engine_new(
+    max_memory_mb: u32,
+    out_engine: *mut *mut ComposerEngine,
+) -> ComposerStatus {
+    run(|| {
+        if out_engine.is_null() {
+            return Err(CapiError::NullPointer("out_engine"));
+        }
+        let mut config = AiEngineConfig::default();
+        if max_memory_mb > 0 {
+            config.max_memory_mb = max_memory_mb;
+        }
+        out_engine.write(Box::into_raw(Box::new(ComposerEngine(AiEngine::new(
+            config,
+        )))));
+        Ok(())
+    })
+}
+
+/// Release an engine handle. Passing null is a no-op.
+///
+/// # Safety
+///
+/// `engine` must be null or a handle that has not already been freed, and no other
+/// thread may be using it.
+#[no_mangle]
+pub unsafe extern "C" fn composer_engine_free(engine: *mut ComposerEngine) {
+    if !engine.is_null() {
+        drop(Box::from_raw(engine));
+    }
+}
+
+/// Train the engine on `count` progressions. The chord handles are copied and
+/// remain owned by the caller.
+///
+/// # Safety
+///
+/// `engine` must be a valid handle and `progressions` must point to `count` entries
+/// whose `chords` arrays hold `len` valid chord handles each.
+#[no_mangle]
+pub unsafe extern "C" fn composer_engine_train(
+    engine: *const ComposerEngine,
+    progressions: *const ComposerProgression,
+    count: usize,
+) -> ComposerStatus {
+    run(|| {
+        let engine = deref(engine, "engine")?;
+        let patterns = slice(progressions, count, "progressions")?
+            .iter()
+            .enumerate()
+            .map(|(i, progression)| {
+                let chords = chords(progression.chords, progression.len, "progression.chords")?;
+                Ok((chords, format!("capi_{}", i), None))
+            })
+            .collect::<Result<Vec<_>, CapiError>>()?;
+        engine.0.initialize(patterns)?;
+        Ok(())
+    })
+}
+
+/// Load a trained engine from a trie asset produced by
+/// [`composer_engine_export_asset`] or the other bindings
+///
+/// # Safety
+///
+/// `engine` must be a valid handle and `data` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn composer_engine_load_asset(
+    engine: *const ComposerEngine,
+    data: *const u8,
+    len: usize,
+) -> ComposerStatus {
+    run(|| {
+        let engine = deref(engine, "engine")?;
+        engine.0.initialize_from_binary(slice(data, len, "data")?)?;
+        Ok(())
+    })
+}
+
+/// Export the trained patterns as a trie asset, e.g. to store in plugin state.
+///
+/// `*out_len` always receives the asset size; pass a null buffer to query it.
+///
+/// # Safety
+///
+/// `engine` must be a valid handle; `buf` must be null or valid for `buf_len` bytes;
+/// `out_len` must be null or valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn composer_engine_export_asset(
+    engine: *const ComposerEngine,
+    buf: *mut u8,
+    buf_len: usize,
+    out_len: *mut usize,
+) -> ComposerStatus {
+    run(|| {
+        let asset = deref(engine, "engine")?.0.export_trie_asset()?;
+        copy_bytes(&asset, buf, buf_len, out_len)
+    })
+}
+
+/// Whether the engine has been trained or loaded; false for a null handle
+///
+/// # Safety
+///
+/// `engine` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn composer_engine_is_initialized(engine: *const ComposerEngine) -> bool {
+    engine
+        .as_ref()
+        .is_some_and(|engine| engine.0.is_initialized())
+}
+
+/// Suggest chords to follow `pattern`, best first.
+///
+/// Suggestions below `min_confidence` (0.0-1.0) are dropped. Up to `capacity`
+/// suggestions are written to `out_suggestions` and their number to `*out_count`.
+///
+/// # Safety
+///
+/// `engine` must be a valid handle; `pattern` must point to `pattern_len` valid
+/// chord handles; `out_suggestions` must be valid for `capacity` writes and
+/// `out_count` for one.
+#[no_mangle]
+pub unsafe extern "C" fn composer_engine_suggest(
+    engine: *const ComposerEngine,
+    pattern: *const *const ComposerChord,
+    pattern_len: usize,
+    min_confidence: f64,
+    out_suggestions: *mut ComposerSuggestion,
+    capacity: usize,
+    out_count: *mut usize,
+) -> ComposerStatus {
+    run(|| {
+        let engine = deref(engine, "engine")?;
+        let pattern = chords(pattern, pattern_len, "pattern")?;
+        if out_suggestions.is_null() && capacity > 0 {
+            return Err(CapiError::NullPointer("out_suggestions"));
+        }
+
+        let config = SuggestionConfig {
+            max_suggestions: capacity,
+            min_confidence,
+            ..Default::default()
+        };
+        let suggestions =
+            engine
+                .0
+                .get_chord_suggestions(&pattern, &SuggestionContext::default(), &config)?;
+
+        let mut written = 0;
+        for suggestion in suggestions.iter().take(capacity) {
+            out_suggestions.add(written).write(ComposerSuggestion {
+                binary: serialize_chord(&suggestion.chord)?,
+                root: suggestion.chord.root,
+                chord_type: suggestion.chord.chord_type,
+                inversion: suggestion.chord.inversion,
+                confidence: suggestion.confidence,
+                weighted_score: suggestion.weighted_score,
+            });
+            written += 1;
+        }
+        write_out(out_count, written, "out_count")
+    })
+}
+
+/// Assess the playing difficulty of a progression.
+///
+/// Pass `tempo_bpm <= 0` to use the default tempo and `beats_per_bar == 0` to
+/// assume common time.
+///
+/// # Safety
+///
+/// `engine` must be a valid handle; `progression` must point to `len` valid chord
+/// handles; `out_difficulty` must be valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn composer_engine_assess_difficulty(
+    engine: *const ComposerEngine,
+    progression: *const *const ComposerChord,
+    len: usize,
+    tempo_bpm: f64,
+    beats_per_bar: u8,
+    beat_unit: u8,
+    out_difficulty: *mut ComposerDifficulty,
+) -> ComposerStatus {
+    run(|| {
+        let engine = deref(engine, "engine")?;
+        let progression = chords(progression, len, "progression")?;
+        let tempo = (tempo_bpm > 0.0).then_some(tempo_bpm);
+        let time_signature = (beats_per_bar > 0).then_some((beats_per_bar, beat_unit.max(1)));
+
+        let assessment = engine
+            .0
+            .assess_difficulty(&progression, tempo, time_signature)?;
+        let difficulty = ComposerDifficulty {
+            overall_score: assessment.overall_score,
+            harmonic_complexity: assessment.harmonic_complexity,
+            rhythmic_complexity: assessment.rhythmic_complexity,
+            technical_complexity: assessment.technical_complexity,
+            melodic_complexity: assessment.melodic_complexity,
+            confidence: assessment.confidence,
+            score_lower: assessment.score_interval.lower,
+            score_upper: assessment.score_interval.upper,
+            insufficient_data: assessment.insufficient_data,
+        };
+        write_out(out_difficulty, difficulty, "out_difficulty")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chord::{composer_chord_free, composer_chord_new};
+    use std::ptr;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    unsafe fn chord(root: u8, chord_type: u8) -> *mut ComposerChord {
+        let mut chord = ptr::null_mut();
+        assert_eq!(
+            composer_chord_new(root, chord_type, &mut chord),
+            ComposerStatus::Ok
+        );
+        chord
+    }
+
+    #[test]
+    fn test_engine_suggestions_and_difficulty() {
+        assert_send_sync::<ComposerEngine>();
+        unsafe {
+            let handles = [chord(1, 5), chord(4, 5), chord(5, 7), chord(1, 5)];
+            let pointers: Vec<*const ComposerChord> =
+                handles.iter().map(|&chord| chord.cast_const()).collect();
+
+            let mut engine = ptr::null_mut();
+            assert_eq!(composer_engine_new(0, &mut engine), ComposerStatus::Ok);
+            assert!(!composer_engine_is_initialized(engine));
+
+            let mut suggestions = [ComposerSuggestion::default(); 4];
+            let mut count = 0;
+            assert_eq!(
+                composer_engine_suggest(
+                    engine,
+                    pointers.as_ptr(),
+                    1,
+                    0.0,
+                    suggestions.as_mut_ptr(),
+                    suggestions.len(),
+                    &mut count
+                ),
+                ComposerStatus::EngineNotInitialized
+            );
+
+            let progression = ComposerProgression {
+                chords: pointers.as_ptr(),
+                len: pointers.len(),
+            };
+            assert_eq!(
+                composer_engine_train(engine, &progression, 1),
+                ComposerStatus::Ok
+            );
+            assert!(composer_engine_is_initialized(engine));
+
+            assert_eq!(
+                composer_engine_suggest(
+                    engine,
+                    pointers.as_ptr(),
+                    2,
+                    0.0,
+                    suggestions.as_mut_ptr(),
+                    suggestions.len(),
+                    &mut count
+                ),
+                ComposerStatus::Ok
+            );
+            assert!(count > 0 && count <= suggestions.len());
+            assert_eq!(suggestions[0].root, 5);
+
+            let mut difficulty = ComposerDifficulty::default();
+            assert_eq!(
+                composer_engine_assess_difficulty(
+                    engine,
+                    pointers.as_ptr(),
+                    pointers.len(),
+                    120.0,
+                    4,
+                    4,
+                    &mut difficulty
+                ),
+                ComposerStatus::Ok
+            );
+            assert!((0.0..=10.0).contains(&difficulty.overall_score));
+            assert!(!difficulty.insufficient_data);
+            assert!(difficulty.score_lower <= difficulty.overall_score);
+            assert!(difficulty.overall_score <= difficulty.score_upper);
+
+            composer_engine_free(engine);
+            for handle in handles {
+                composer_chord_free(handle);
+            }
+        }
+    }
+
+    #[test]
+    fn test_asset_export_and_load() {
+        unsafe {
+            let handles = [chord(2, 7), chord(5, 7), chord(1, 7)];
+            let pointers: Vec<*const ComposerChord> =
+                handles.iter().map(|&chord| chord.cast_const()).collect();
+            let progression = ComposerProgression {
+                chords: pointers.as_ptr(),
+                len: pointers.len(),
+            };
+
+            let mut trained = ptr::null_mut();
+            assert_eq!(composer_engine_new(0, &mut trained), ComposerStatus::Ok);
+            assert_eq!(
+                composer_engine_train(trained, &progression, 1),
+                ComposerStatus::Ok
+            );
+
+            let mut len = 0;
+            assert_eq!(
+                composer_engine_export_asset(trained, ptr::null_mut(), 0, &mut len),
+                ComposerStatus::BufferTooSmall
+            );
+            let mut asset = vec![0u8; len];
+            assert_eq!(
+                composer_engine_export_asset(trained, asset.as_mut_ptr(), asset.len(), &mut len),
+                ComposerStatus::Ok
+            );
+
+            let mut loaded = ptr::null_mut();
+            assert_eq!(composer_engine_new(0, &mut loaded), ComposerStatus::Ok);
+            assert_eq!(
+                composer_engine_load_asset(loaded, asset.as_ptr(), asset.len()),
+                ComposerStatus::Ok
+            );
+            assert!(composer_engine_is_initialized(loaded));
+
+            for engine in [trained, loaded] {
+                composer_engine_free(engine);
+            }
+            for handle in handles {
+                composer_chord_free(handle);
+            }
+        }
+    }
+}
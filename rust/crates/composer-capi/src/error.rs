@@ -0,0 +1,277 @@
+//! Status codes, last-error reporting and pointer helpers for the C ABI
+
+use composer_ai::AiError;
+use composer_core::{Chord, ChordTheoryError};
+use composer_serialization::SerializationError;
+use std::any::Any;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use thiserror::Error;
+
+use crate::chord::ComposerChord;
+
+/// Result code returned by every fallible C function
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComposerStatus {
+    /// The call succeeded
+    Ok = 0,
+    /// A required pointer argument was null
+    NullPointer = 1,
+    /// A string argument was not valid UTF-8
+    InvalidUtf8 = 2,
+    /// The caller's output buffer is too small; the required size was reported
+    BufferTooSmall = 3,
+    /// A chord or theory argument was out of range
+    InvalidArgument = 4,
+    /// Binary or hex data could not be decoded
+    SerializationFailed = 5,
+    /// The engine must be trained or loaded before this call
+    EngineNotInitialized = 6,
+    /// The AI engine reported an error
+    EngineFailed = 7,
+    /// The call panicked; handles it was given may be left half-updated
+    Internal = 8,
+}
+
+/// Errors raised inside the C ABI layer
+#[derive(Debug, Error)]
+pub enum CapiError {
+    #[error("Null pointer passed for `{0}`")]
+    NullPointer(&'static str),
+
+    #[error("Invalid UTF-8 in `{0}`")]
+    InvalidUtf8(&'static str),
+
+    #[error("Buffer too small: {required} bytes required")]
+    BufferTooSmall { required: usize },
+
+    #[error("Chord theory error: {0}")]
+    Theory(#[from] ChordTheoryError),
+
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] SerializationError),
+
+    #[error("AI engine error: {0}")]
+    Ai(#[from] AiError),
+
+    #[error("Internal error: {0}")]
+    Panic(String),
+}
+
+impl CapiError {
+    /// Status code reported to C callers for this error
+    pub fn status(&self) -> ComposerStatus {
+        match self {
+            CapiError::NullPointer(_) => ComposerStatus::NullPointer,
+            CapiError::InvalidUtf8(_) => ComposerStatus::InvalidUtf8,
+            CapiError::BufferTooSmall { .. } => ComposerStatus::BufferTooSmall,
+            CapiError::Theory(_) => ComposerStatus::InvalidArgument,
+            CapiError::Serialization(_) => ComposerStatus::SerializationFailed,
+            CapiError::Ai(AiError::EngineNotInitialized) => ComposerStatus::EngineNotInitialized,
+            CapiError::Ai(_) => ComposerStatus::EngineFailed,
+            CapiError::Panic(_) => ComposerStatus::Internal,
+        }
+    }
+}
+
+/// Result type for the C ABI layer
+pub type CapiResult<T> = Result<T, CapiError>;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Message describing the most recent failure on the calling thread.
+///
+/// Returns null if no call on this thread has failed yet. The string is owned by
+/// the library and stays valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn composer_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Run a C entry point body, recording any error for [`composer_last_error_message`].
+///
+/// A panic must not unwind into C, so it is caught and reported as
+/// [`ComposerStatus::Internal`].
+pub(crate) fn run(body: impl FnOnce() -> CapiResult<()>) -> ComposerStatus {
+    let result = panic::catch_unwind(AssertUnwindSafe(body))
+        .unwrap_or_else(|payload| Err(CapiError::Panic(panic_message(payload.as_ref()))));
+    match result {
+        Ok(()) => ComposerStatus::Ok,
+        Err(error) => {
+            let status = error.status();
+            let message = CString::new(error.to_string().replace('\0', " ")).unwrap_or_default();
+            LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+            status
+        },
+    }
+}
+
+/// Text of a panic payload, for the last-error message
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "panic with a non-string payload".to_string())
+}
+
+/// Borrow a value behind a caller-supplied pointer
+///
+/// # Safety
+///
+/// `ptr` must be null or point to a live, properly aligned `T`.
+pub(crate) unsafe fn deref<'a, T>(ptr: *const T, name: &'static str) -> CapiResult<&'a T> {
+    ptr.as_ref().ok_or(CapiError::NullPointer(name))
+}
+
+/// Mutably borrow a value behind a caller-supplied pointer
+///
+/// # Safety
+///
+/// `ptr` must be null or point to a live `T` not aliased for the duration of the call.
+pub(crate) unsafe fn deref_mut<'a, T>(ptr: *mut T, name: &'static str) -> CapiResult<&'a mut T> {
+    ptr.as_mut().ok_or(CapiError::NullPointer(name))
+}
+
+/// Write a value through a caller-supplied output pointer
+///
+/// # Safety
+///
+/// `out` must be null or valid for writes of `T`.
+pub(crate) unsafe fn write_out<T>(out: *mut T, value: T, name: &'static str) -> CapiResult<()> {
+    if out.is_null() {
+        return Err(CapiError::NullPointer(name));
+    }
+    out.write(value);
+    Ok(())
+}
+
+/// Borrow a caller-supplied array, allowing null when `len` is zero
+///
+/// # Safety
+///
+/// Unless `len` is zero, `ptr` must point to `len` initialized values of `T`.
+pub(crate) unsafe fn slice<'a, T>(
+    ptr: *const T,
+    len: usize,
+    name: &'static str,
+) -> CapiResult<&'a [T]> {
+    if len == 0 {
+        Ok(&[])
+    } else if ptr.is_null() {
+        Err(CapiError::NullPointer(name))
+    } else {
+        Ok(std::slice::from_raw_parts(ptr, len))
+    }
+}
+
+/// Borrow a NUL-terminated UTF-8 string
+///
+/// # Safety
+///
+/// `ptr` must be null or point to a NUL-terminated string.
+pub(crate) unsafe fn c_str<'a>(ptr: *const c_char, name: &'static str) -> CapiResult<&'a str> {
+    if ptr.is_null() {
+        return Err(CapiError::NullPointer(name));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| CapiError::InvalidUtf8(name))
+}
+
+/// Copy chords out of a caller-supplied array of chord handles
+///
+/// # Safety
+///
+/// Unless `len` is zero, `ptr` must point to `len` valid chord handles.
+pub(crate) unsafe fn chords(
+    ptr: *const *const ComposerChord,
+    len: usize,
+    name: &'static str,
+) -> CapiResult<Vec<Chord>> {
+    slice(ptr, len, name)?
+        .iter()
+        .map(|&chord| deref(chord, name).map(|chord| chord.0.clone()))
+        .collect()
+}
+
+/// Copy bytes into a caller buffer, always reporting the required length.
+///
+/// Callers may pass a null buffer with zero length to query the size first.
+///
+/// # Safety
+///
+/// `buf` must be null or valid for `buf_len` bytes of writes; `out_len` must be null
+/// or valid for a write.
+pub(crate) unsafe fn copy_bytes(
+    bytes: &[u8],
+    buf: *mut u8,
+    buf_len: usize,
+    out_len: *mut usize,
+) -> CapiResult<()> {
+    if !out_len.is_null() {
+        out_len.write(bytes.len());
+    }
+    if buf_len < bytes.len() || (buf.is_null() && !bytes.is_empty()) {
+        return Err(CapiError::BufferTooSmall {
+            required: bytes.len(),
+        });
+    }
+    if !bytes.is_empty() {
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), buf, bytes.len());
+    }
+    Ok(())
+}
+
+/// Copy a string into a caller buffer as NUL-terminated UTF-8.
+///
+/// `out_len` receives the string length excluding the terminator; the buffer needs
+/// one more byte than that.
+///
+/// # Safety
+///
+/// Same requirements as [`copy_bytes`].
+pub(crate) unsafe fn copy_str(
+    text: &str,
+    buf: *mut c_char,
+    buf_len: usize,
+    out_len: *mut usize,
+) -> CapiResult<()> {
+    if !out_len.is_null() {
+        out_len.write(text.len());
+    }
+    if buf.is_null() || buf_len <= text.len() {
+        return Err(CapiError::BufferTooSmall {
+            required: text.len() + 1,
+        });
+    }
+    let buf = buf.cast::<u8>();
+    std::ptr::copy_nonoverlapping(text.as_ptr(), buf, text.len());
+    buf.add(text.len()).write(0);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_reports_panics() {
+        let status = run(|| panic!("chord table missing"));
+        assert_eq!(status, ComposerStatus::Internal);
+
+        let message = unsafe { CStr::from_ptr(composer_last_error_message()) };
+        assert_eq!(
+            message.to_str().unwrap(),
+            "Internal error: chord table missing"
+        );
+    }
+}
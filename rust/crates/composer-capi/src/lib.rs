@@ -0,0 +1,78 @@
+//! C ABI bindings for the Composer music theory library
+//!
+//! This crate exposes chord creation, theory analysis, chord serialization and the
+//! AI engine through `extern "C"` functions so that native hosts (VST3, AU and CLAP
+//! plugins, DAW scripting layers) can embed Composer without a Python or JavaScript
+//! runtime. It builds as a `cdylib` and a `staticlib`; the matching declarations
+//! live in `include/composer.h`, generated with cbindgen from `cbindgen.toml`.
+//!
+//! # Conventions
+//!
+//! - Fallible functions return a [`ComposerStatus`]; on failure a description is
+//!   available from [`composer_last_error_message`] on the same thread.
+//! - Chords and engines are opaque handles. Every handle returned through an
+//!   `out_*` pointer is owned by the caller and released with the matching
+//!   `*_free` function.
+//! - Strings and variable-size data are written into caller buffers. The required
+//!   size is always reported through `out_len`, so a first call with a null buffer
+//!   can size the allocation and no memory crosses the allocator boundary.
+
+pub mod chord;
+pub mod engine;
+pub mod error;
+
+pub use chord::*;
+pub use engine::*;
+pub use error::*;
+
+use std::ffi::{c_char, CString};
+use std::sync::OnceLock;
+
+/// Library version as a NUL-terminated string owned by the library
+#[no_mangle]
+pub extern "C" fn composer_version() -> *const c_char {
+    static VERSION: OnceLock<CString> = OnceLock::new();
+    VERSION
+        .get_or_init(|| CString::new(composer_config::APPLICATION.version).unwrap_or_default())
+        .as_ptr()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn test_version() {
+        let version = unsafe { CStr::from_ptr(composer_version()) };
+        assert_eq!(
+            version.to_str().unwrap(),
+            composer_config::APPLICATION.version
+        );
+    }
+
+    #[test]
+    fn test_header_declares_every_export() {
+        let header = include_str!("../include/composer.h");
+        let sources = [
+            include_str!("lib.rs"),
+            include_str!("chord.rs"),
+            include_str!("engine.rs"),
+            include_str!("error.rs"),
+        ];
+
+        let exports: Vec<&str> = sources
+            .iter()
+            .flat_map(|source| source.split("extern \"C\" fn ").skip(1))
+            .filter_map(|rest| rest.split('(').next())
+            .collect();
+        assert!(exports.len() > 20);
+        for name in exports {
+            assert!(
+                header.contains(&format!("{}(", name)),
+                "include/composer.h is missing `{}`; regenerate it with cbindgen",
+                name
+            );
+        }
+    }
+}
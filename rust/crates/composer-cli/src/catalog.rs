@@ -0,0 +1,249 @@
+//! Difficulty manifests of score collections for `tag`
+//!
+//! Every MIDI and MusicXML file under the given paths is read into a chord
+//! progression and assessed, producing one manifest entry per file in path
+//! order. Files are processed on a fixed number of worker threads sharing one
+//! engine; a file that cannot be read or assessed gets an entry with its error
+//! instead of stopping the run, so catalogs of thousands of scores can be
+//! tagged in one pass and the failures fixed afterwards.
+
+use crate::error::{CliError, CliResult};
+use crate::ingest::{list_files, read_score, tonic_name, ScoreFormat};
+use crate::midi::MidiOptions;
+use composer_ai::{AiEngine, DifficultyProfile, SkillLevel};
+use serde::Serialize;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Options of `tag`
+#[derive(Debug, Clone)]
+pub struct CatalogOptions {
+    /// Chord extraction settings
+    pub midi: MidiOptions,
+    pub tempo: Option<f64>,
+    /// Instrument to assess technical difficulty on, or `None` for the generic model
+    pub instrument: Option<DifficultyProfile>,
+    /// Number of worker threads
+    pub jobs: usize,
+}
+
+/// Manifest entry of one score
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CatalogEntry {
+    pub path: String,
+    pub format: ScoreFormat,
+
+    /// Major key the chords were read in, e.g. `Bb`
+    pub key: Option<String>,
+
+    /// Number of extracted chords
+    pub chords: usize,
+
+    /// Overall difficulty score (0.0-10.0)
+    pub score: Option<f64>,
+
+    pub skill_level: Option<SkillLevel>,
+
+    /// Name of the skill band the score falls in
+    pub skill_band: Option<String>,
+
+    /// Confidence of the assessment (0.0-1.0)
+    pub confidence: Option<f64>,
+
+    /// Why the file could not be tagged
+    pub error: Option<String>,
+}
+
+impl CatalogEntry {
+    fn new(path: &Path, format: ScoreFormat) -> Self {
+        Self {
+            path: path.display().to_string(),
+            format,
+            key: None,
+            chords: 0,
+            score: None,
+            skill_level: None,
+            skill_band: None,
+            confidence: None,
+            error: None,
+        }
+    }
+}
+
+/// Read and assess one score; failures are recorded in the entry
+pub fn tag_file(
+    engine: &AiEngine,
+    path: &Path,
+    format: ScoreFormat,
+    options: &CatalogOptions,
+) -> CatalogEntry {
+    let mut entry = CatalogEntry::new(path, format);
+    let (progression, tonic) = match read_score(path, format, &options.midi) {
+        Ok(score) => score,
+        Err(error) => {
+            entry.error = Some(error.to_string());
+            return entry;
+        },
+    };
+    entry.key = Some(tonic_name(tonic).to_string());
+    entry.chords = progression.len();
+    if progression.is_empty() {
+        entry.error = Some("no chords found".to_string());
+        return entry;
+    }
+
+    let assessment = match options.instrument {
+        Some(profile) => {
+            engine.assess_instrument_difficulty(&progression, options.tempo, None, profile)
+        },
+        None => engine.assess_difficulty(&progression, options.tempo, None),
+    };
+    match assessment {
+        Ok(assessment) => {
+            entry.score = Some(assessment.overall_score);
+            entry.skill_level = Some(assessment.skill_level);
+            entry.skill_band = Some(assessment.skill_band);
+            entry.confidence = Some(assessment.confidence);
+        },
+        Err(error) => entry.error = Some(CliError::from(error).to_string()),
+    }
+    entry
+}
+
+/// Tag every MIDI and MusicXML file under `paths`, in path order
+///
+/// Other files are skipped. Workers take the next untagged file until none
+/// are left, so one slow score does not hold up a whole share of the batch.
+pub fn tag_files(
+    engine: &AiEngine,
+    paths: &[PathBuf],
+    options: &CatalogOptions,
+) -> CliResult<Vec<CatalogEntry>> {
+    let scores: Vec<(PathBuf, ScoreFormat)> = list_files(paths)?
+        .into_iter()
+        .filter_map(|file| ScoreFormat::from_path(&file).map(|format| (file, format)))
+        .collect();
+
+    let next = AtomicUsize::new(0);
+    let workers = options.jobs.clamp(1, scores.len().max(1));
+    let mut tagged: Vec<(usize, CatalogEntry)> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut entries = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some((path, format)) = scores.get(index) else {
+                            return entries;
+                        };
+                        entries.push((index, tag_file(engine, path, *format, options)));
+                    }
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_default())
+            .collect()
+    });
+    tagged.sort_by_key(|(index, _)| *index);
+    Ok(tagged.into_iter().map(|(_, entry)| entry).collect())
+}
+
+/// Quote a CSV field if it holds a separator, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Write the manifest as CSV under a header; missing values are empty fields
+pub fn write_csv(entries: &[CatalogEntry], out: &mut impl Write) -> io::Result<()> {
+    writeln!(
+        out,
+        "path,format,key,chords,score,skill_level,skill_band,confidence,error"
+    )?;
+    for entry in entries {
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{},{}",
+            csv_field(&entry.path),
+            entry.format.name(),
+            entry.key.as_deref().unwrap_or_default(),
+            entry.chords,
+            entry
+                .score
+                .map(|score| format!("{:.2}", score))
+                .unwrap_or_default(),
+            entry
+                .skill_level
+                .map(|level| format!("{:?}", level))
+                .unwrap_or_default(),
+            csv_field(entry.skill_band.as_deref().unwrap_or_default()),
+            entry
+                .confidence
+                .map(|confidence| format!("{:.2}", confidence))
+                .unwrap_or_default(),
+            csv_field(entry.error.as_deref().unwrap_or_default()),
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi::tests::midi_bytes;
+    use crate::musicxml::tests::musicxml_text;
+    use composer_ai::AiEngineConfig;
+
+    #[test]
+    fn test_tag_files() {
+        let dir = std::env::temp_dir().join(format!("composer-cli-tag-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cadence: [&[u8]; 4] = [
+            &[60, 64, 67],
+            &[65, 69, 72],
+            &[55, 59, 62, 65],
+            &[60, 64, 67],
+        ];
+        std::fs::write(dir.join("a.mid"), midi_bytes(&cadence)).unwrap();
+        std::fs::write(dir.join("b.musicxml"), musicxml_text(&cadence)).unwrap();
+        std::fs::write(dir.join("c, broken.xml"), "<score-partwise><part").unwrap();
+        std::fs::write(dir.join("notes.txt"), "I IV V I").unwrap();
+
+        let engine = AiEngine::new(AiEngineConfig::default());
+        engine.initialize(vec![]).unwrap();
+        let options = CatalogOptions {
+            midi: MidiOptions {
+                window_beats: 1.0,
+                tonic: None,
+            },
+            tempo: None,
+            instrument: None,
+            jobs: 2,
+        };
+        let entries = tag_files(&engine, std::slice::from_ref(&dir), &options).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].format, ScoreFormat::Midi);
+        assert_eq!(entries[1].format, ScoreFormat::MusicXml);
+        assert_eq!(entries[0].chords, 4);
+        assert_eq!(entries[0].key.as_deref(), Some("C"));
+        assert_eq!(entries[0].score, entries[1].score);
+        assert!(entries[0].error.is_none() && entries[0].skill_level.is_some());
+        assert!(entries[2].error.is_some() && entries[2].score.is_none());
+
+        let mut out = Vec::new();
+        write_csv(&entries, &mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].contains(",midi,C,4,"));
+        assert!(lines[3].starts_with('"') && lines[3].contains(",musicxml,,0,,,,,"));
+    }
+}
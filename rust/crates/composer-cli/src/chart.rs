@@ -0,0 +1,164 @@
+//! Plain-text chord charts
+//!
+//! A chart holds one progression per line. Chords are separated by whitespace,
+//! commas or bar lines (`|`), and `#` starts a comment. Each chord is written
+//! either as a Roman numeral (`ii7`, `V7/1`, `IV`) or in the scale-degree form
+//! printed by `Chord`'s `Display` (`27`, `57/1`, `4`); `REST` or `-` is a rest.
+//! Numeral case is accepted but not interpreted, because chord quality follows
+//! from the scale degree.
+
+use crate::error::{CliError, CliResult};
+use composer_core::Chord;
+
+/// Progression parsed from one chart line, with the 1-based line number
+pub type ChartLine = (usize, CliResult<Vec<Chord>>);
+
+/// Parse every progression in a chart, skipping blank and comment-only lines
+pub fn parse_chart(text: &str) -> CliResult<Vec<Vec<Chord>>> {
+    parse_chart_lines(text)
+        .into_iter()
+        .map(|(line, progression)| match progression {
+            Err(CliError::InvalidChord { token, reason }) => Err(CliError::InvalidChord {
+                token,
+                reason: format!("line {}: {}", line, reason),
+            }),
+            progression => progression,
+        })
+        .collect()
+}
+
+/// Parse each progression of a chart on its own
+///
+/// Unlike [`parse_chart`], an invalid line does not hide the lines after it.
+pub fn parse_chart_lines(text: &str) -> Vec<ChartLine> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let line = line.split('#').next().unwrap_or_default();
+            match parse_progression(line) {
+                Ok(progression) if progression.is_empty() => None,
+                progression => Some((index + 1, progression)),
+            }
+        })
+        .collect()
+}
+
+/// Parse a single progression such as `"ii7 | V7 | I"`
+pub fn parse_progression(line: &str) -> CliResult<Vec<Chord>> {
+    line.split(|c: char| c.is_whitespace() || c == ',' || c == '|')
+        .filter(|token| !token.is_empty())
+        .map(parse_chord)
+        .collect()
+}
+
+/// Parse one chord token
+pub fn parse_chord(token: &str) -> CliResult<Chord> {
+    let invalid = |reason: &str| CliError::InvalidChord {
+        token: token.to_string(),
+        reason: reason.to_string(),
+    };
+
+    if token == "-" || token.eq_ignore_ascii_case("rest") {
+        return Ok(Chord::rest());
+    }
+
+    let (body, inversion) = match token.split_once('/') {
+        Some((body, inversion)) => (
+            body,
+            inversion
+                .parse::<u8>()
+                .map_err(|_| invalid("inversion must be a number"))?,
+        ),
+        None => (token, 0),
+    };
+
+    let numeral_len = body
+        .find(|c: char| !matches!(c.to_ascii_uppercase(), 'I' | 'V'))
+        .unwrap_or(body.len());
+    let (root, chord_type) = if numeral_len > 0 {
+        let root = match body[..numeral_len].to_ascii_uppercase().as_str() {
+            "I" => 1,
+            "II" => 2,
+            "III" => 3,
+            "IV" => 4,
+            "V" => 5,
+            "VI" => 6,
+            "VII" => 7,
+            _ => return Err(invalid("unknown Roman numeral")),
+        };
+        (root, &body[numeral_len..])
+    } else {
+        let mut chars = body.chars();
+        let root = chars
+            .next()
+            .and_then(|c| c.to_digit(10))
+            .ok_or_else(|| invalid("expected a Roman numeral or scale degree"))?;
+        (root as u8, chars.as_str())
+    };
+
+    let chord_type = match chord_type {
+        "" => 5,
+        digits => digits
+            .parse::<u8>()
+            .map_err(|_| invalid("chord type must be 5, 7, 9, 11 or 13"))?,
+    };
+
+    let chord = Chord::new(root, chord_type).map_err(|e| invalid(&e.to_string()))?;
+    if inversion > 0 {
+        chord
+            .with_inversion(inversion)
+            .map_err(|e| invalid(&e.to_string()))
+    } else {
+        Ok(chord)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_chord_forms() {
+        let cases = [
+            ("I", (1, 5, 0)),
+            ("ii7", (2, 7, 0)),
+            ("V7/1", (5, 7, 1)),
+            ("vii", (7, 5, 0)),
+            ("57", (5, 7, 0)),
+            ("213", (2, 13, 0)),
+            ("4/2", (4, 5, 2)),
+        ];
+        for (token, (root, chord_type, inversion)) in cases {
+            let chord = parse_chord(token).unwrap();
+            assert_eq!(
+                (chord.root, chord.chord_type, chord.inversion),
+                (root, chord_type, inversion),
+                "{}",
+                token
+            );
+        }
+        assert!(parse_chord("REST").unwrap().is_rest);
+
+        for token in ["IIII", "8", "V6", "x", "V/a"] {
+            assert!(parse_chord(token).is_err(), "{}", token);
+        }
+    }
+
+    #[test]
+    fn test_parse_chart() {
+        let chart = "# ii-V-I in C\nii7 | V7 | I\n\n1, 6, 4, 5  # pop\n";
+        let progressions = parse_chart(chart).unwrap();
+
+        assert_eq!(progressions.len(), 2);
+        assert_eq!(progressions[0].len(), 3);
+        assert_eq!(progressions[1][1].root, 6);
+
+        let error = parse_chart("I IV\nI Q").unwrap_err().to_string();
+        assert!(error.contains("line 2"), "{}", error);
+
+        let lines = parse_chart_lines("I IV\nI Q\n\nV");
+        let numbers: Vec<usize> = lines.iter().map(|(line, _)| *line).collect();
+        assert_eq!(numbers, [1, 2, 4]);
+        assert!(lines[1].1.is_err() && lines[2].1.is_ok());
+    }
+}
@@ -0,0 +1,400 @@
+//! Checkpointed `ingest` runs for very large corpora
+//!
+//! Patterns are added to the trie as files are read, and every few files the
+//! trie is saved together with a ledger of the files processed so far. A run
+//! started again with the same checkpoint directory loads the last snapshot
+//! and skips the files in the ledger, so a crash late in a long run loses at
+//! most one checkpoint interval of work.
+//!
+//! The directory holds `ledger.jsonl` (one line per processed file),
+//! `trie-<N>.bin` (the trie at checkpoint N) and `checkpoint.json` (the current
+//! checkpoint, how many ledger lines it covers and the ingestion settings).
+//! `checkpoint.json` is replaced atomically once the ledger and snapshot are on
+//! disk, so a crash mid-checkpoint leaves the previous checkpoint intact;
+//! ledger lines past the ones it covers are discarded on resume.
+
+use crate::error::{CliError, CliResult};
+use crate::ingest::{list_files, read_file, IngestOptions};
+use composer_ai::{AiEngine, TrainingPattern};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+const MANIFEST_FILE: &str = "checkpoint.json";
+const LEDGER_FILE: &str = "ledger.jsonl";
+
+/// Options of a checkpointed run
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckpointOptions {
+    /// Directory holding the snapshot and ledger
+    pub dir: PathBuf,
+    /// Files processed between checkpoints
+    pub every: usize,
+}
+
+/// Outcome of a checkpointed run, including files processed by earlier runs
+#[derive(Debug, Default)]
+pub struct CheckpointedIngest {
+    /// Patterns stored in the trie
+    pub patterns: usize,
+    /// Files that contributed patterns
+    pub files_read: usize,
+    /// Files skipped for their extension
+    pub files_skipped: usize,
+    /// Files that failed to parse and patterns the trie rejected, with the reason
+    pub failures: Vec<(PathBuf, String)>,
+    /// Files taken from the ledger of an earlier run instead of being read again
+    pub files_resumed: usize,
+    /// Checkpoints written by this run
+    pub checkpoints: usize,
+}
+
+/// Settings a checkpoint was made with; resuming with others would mix corpora
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct IngestSettings {
+    window_beats: f64,
+    tonic: Option<u8>,
+    min_length: usize,
+}
+
+impl From<&IngestOptions> for IngestSettings {
+    fn from(options: &IngestOptions) -> Self {
+        Self {
+            window_beats: options.midi.window_beats,
+            tonic: options.midi.tonic,
+            min_length: options.min_length,
+        }
+    }
+}
+
+/// Contents of `checkpoint.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Manifest {
+    /// Number of the latest snapshot
+    generation: u64,
+    /// Ledger lines covered by the snapshot
+    files: usize,
+    /// Patterns stored in the snapshot
+    patterns: usize,
+    settings: IngestSettings,
+}
+
+/// Outcome of one processed file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct LedgerEntry {
+    path: String,
+    #[serde(default)]
+    patterns: usize,
+    #[serde(default)]
+    skipped: bool,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+impl CheckpointedIngest {
+    fn count(&mut self, entry: &LedgerEntry) {
+        if entry.skipped {
+            self.files_skipped += 1;
+        } else if let Some(error) = &entry.error {
+            self.failures
+                .push((PathBuf::from(&entry.path), error.clone()));
+        } else {
+            self.files_read += 1;
+        }
+    }
+}
+
+fn snapshot_path(dir: &Path, generation: u64) -> PathBuf {
+    dir.join(format!("trie-{}.bin", generation))
+}
+
+/// The checkpoint in `dir`, if a run has saved one
+fn read_manifest(dir: &Path) -> CliResult<Option<Manifest>> {
+    let path = dir.join(MANIFEST_FILE);
+    match std::fs::read(&path) {
+        Ok(bytes) => serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|e| CliError::InvalidCheckpoint(format!("{}: {}", path.display(), e))),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(CliError::file(path, error)),
+    }
+}
+
+/// Ledger entries covered by a checkpoint and the byte length they take up
+type CoveredLedger = (Vec<LedgerEntry>, u64);
+
+/// The first `files` ledger entries
+fn read_ledger(path: &Path, files: usize) -> CliResult<CoveredLedger> {
+    let file = File::open(path).map_err(|e| CliError::file(path, e))?;
+    let mut entries = Vec::with_capacity(files);
+    let mut length = 0;
+    for line in BufReader::new(file).lines().take(files) {
+        let line = line.map_err(|e| CliError::file(path, e))?;
+        length += line.len() as u64 + 1;
+        entries.push(
+            serde_json::from_str(&line)
+                .map_err(|e| CliError::InvalidCheckpoint(format!("{}: {}", path.display(), e)))?,
+        );
+    }
+    if entries.len() < files {
+        return Err(CliError::InvalidCheckpoint(format!(
+            "{} has {} of the {} entries the checkpoint covers",
+            path.display(),
+            entries.len(),
+            files
+        )));
+    }
+    Ok((entries, length))
+}
+
+/// Write `bytes` to `path` and flush them to disk
+fn write_synced(path: &Path, bytes: &[u8]) -> CliResult<()> {
+    let mut file = File::create(path).map_err(|e| CliError::file(path, e))?;
+    file.write_all(bytes)
+        .and_then(|_| file.sync_all())
+        .map_err(|e| CliError::file(path, e))
+}
+
+/// Ledger and snapshot state of a run
+struct Checkpointer<'a> {
+    dir: &'a Path,
+    ledger: File,
+    manifest: Manifest,
+}
+
+impl Checkpointer<'_> {
+    /// Add the patterns read since the last checkpoint and save a new one
+    fn save(
+        &mut self,
+        engine: &AiEngine,
+        entries: &mut Vec<LedgerEntry>,
+        patterns: &mut Vec<TrainingPattern>,
+        run: &mut CheckpointedIngest,
+    ) -> CliResult<()> {
+        let report = engine.add_patterns_bulk(patterns.drain(..));
+        for rejection in report.rejected {
+            run.failures.push((
+                PathBuf::from(rejection.source_id),
+                rejection.error.to_string(),
+            ));
+        }
+
+        let ledger_path = self.dir.join(LEDGER_FILE);
+        let mut lines = String::new();
+        for entry in entries.iter() {
+            lines.push_str(&serde_json::to_string(entry)?);
+            lines.push('\n');
+        }
+        self.ledger
+            .write_all(lines.as_bytes())
+            .and_then(|_| self.ledger.sync_data())
+            .map_err(|e| CliError::file(&ledger_path, e))?;
+
+        let previous = self.manifest.generation;
+        let manifest = Manifest {
+            generation: previous + 1,
+            files: self.manifest.files + entries.len(),
+            patterns: self.manifest.patterns + report.added,
+            settings: self.manifest.settings.clone(),
+        };
+        write_synced(
+            &snapshot_path(self.dir, manifest.generation),
+            &engine.export_trie_asset()?,
+        )?;
+        let staged = self.dir.join(format!("{}.tmp", MANIFEST_FILE));
+        write_synced(&staged, &serde_json::to_vec_pretty(&manifest)?)?;
+        let manifest_path = self.dir.join(MANIFEST_FILE);
+        std::fs::rename(&staged, &manifest_path).map_err(|e| CliError::file(manifest_path, e))?;
+        // The old snapshot is no longer referenced; failing to remove it only costs space
+        let _ = std::fs::remove_file(snapshot_path(self.dir, previous));
+
+        run.patterns = manifest.patterns;
+        run.checkpoints += 1;
+        self.manifest = manifest;
+        entries.clear();
+        Ok(())
+    }
+}
+
+/// Read every supported file under `paths` into `engine`, checkpointing as it goes
+///
+/// `engine` must be uninitialized. If `checkpoint.dir` holds a checkpoint, the
+/// engine starts from its snapshot and the files in its ledger are not read
+/// again; it fails with `CliError::InvalidCheckpoint` if the checkpoint was made
+/// with different settings. Files are matched by path, so a resumed run must
+/// name the corpus the same way.
+pub fn ingest_with_checkpoints(
+    engine: &AiEngine,
+    paths: &[PathBuf],
+    options: &IngestOptions,
+    checkpoint: &CheckpointOptions,
+) -> CliResult<CheckpointedIngest> {
+    let dir = checkpoint.dir.as_path();
+    std::fs::create_dir_all(dir).map_err(|e| CliError::file(dir, e))?;
+    let settings = IngestSettings::from(options);
+    let ledger_path = dir.join(LEDGER_FILE);
+
+    let mut run = CheckpointedIngest::default();
+    let (manifest, processed) = match read_manifest(dir)? {
+        Some(manifest) => {
+            if manifest.settings != settings {
+                return Err(CliError::InvalidCheckpoint(format!(
+                    "{} was made with different settings ({:?}); \
+                     rerun with them or use a new checkpoint directory",
+                    dir.display(),
+                    manifest.settings
+                )));
+            }
+            let snapshot = snapshot_path(dir, manifest.generation);
+            engine.initialize_from_binary(
+                &std::fs::read(&snapshot).map_err(|e| CliError::file(&snapshot, e))?,
+            )?;
+            let (entries, length) = read_ledger(&ledger_path, manifest.files)?;
+            (manifest, Some((entries, length)))
+        },
+        None => (
+            Manifest {
+                generation: 0,
+                files: 0,
+                patterns: 0,
+                settings,
+            },
+            None,
+        ),
+    };
+
+    let mut ledger = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&ledger_path)
+        .map_err(|e| CliError::file(&ledger_path, e))?;
+    let mut done = HashSet::new();
+    let covered = match processed {
+        Some((entries, length)) => {
+            for entry in entries {
+                run.count(&entry);
+                done.insert(entry.path);
+            }
+            length
+        },
+        None => 0,
+    };
+    // Drop entries written by a checkpoint that never completed
+    ledger
+        .set_len(covered)
+        .and_then(|_| ledger.seek(SeekFrom::End(0)))
+        .map_err(|e| CliError::file(&ledger_path, e))?;
+    run.files_resumed = done.len();
+    run.patterns = manifest.patterns;
+
+    let mut checkpointer = Checkpointer {
+        dir,
+        ledger,
+        manifest,
+    };
+    let mut entries = Vec::new();
+    let mut patterns = Vec::new();
+    for file in list_files(paths)? {
+        let path = file.display().to_string();
+        if done.contains(&path) {
+            continue;
+        }
+        let mut entry = LedgerEntry {
+            path,
+            patterns: 0,
+            skipped: false,
+            error: None,
+        };
+        match read_file(&file, options) {
+            Some(Ok(read)) => {
+                entry.patterns = read.len();
+                patterns.extend(read);
+            },
+            Some(Err(error)) => entry.error = Some(error.to_string()),
+            None => entry.skipped = true,
+        }
+        run.count(&entry);
+        entries.push(entry);
+
+        if entries.len() >= checkpoint.every.max(1) {
+            checkpointer.save(engine, &mut entries, &mut patterns, &mut run)?;
+        }
+    }
+    if !entries.is_empty() {
+        checkpointer.save(engine, &mut entries, &mut patterns, &mut run)?;
+    }
+    Ok(run)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi::MidiOptions;
+    use composer_ai::AiEngineConfig;
+
+    #[test]
+    fn test_resume_from_checkpoint() {
+        let root =
+            std::env::temp_dir().join(format!("composer-cli-checkpoint-{}", std::process::id()));
+        let corpus = root.join("corpus");
+        let dir = root.join("checkpoint");
+        std::fs::create_dir_all(&corpus).unwrap();
+        std::fs::write(corpus.join("a.chords"), "I IV V I\nii7 V7 I\n").unwrap();
+        std::fs::write(corpus.join("b.txt"), "I Q\n").unwrap();
+        std::fs::write(corpus.join("c.md"), "ignored").unwrap();
+
+        let options = IngestOptions {
+            midi: MidiOptions::default(),
+            min_length: 2,
+        };
+        let checkpoint = CheckpointOptions {
+            dir: dir.clone(),
+            every: 2,
+        };
+        let paths = std::slice::from_ref(&corpus);
+        let first = AiEngine::new(AiEngineConfig::default());
+        let run = ingest_with_checkpoints(&first, paths, &options, &checkpoint).unwrap();
+        assert_eq!(run.checkpoints, 2);
+        assert_eq!(run.patterns, 2);
+        assert_eq!((run.files_read, run.files_skipped), (1, 1));
+        assert_eq!(run.failures.len(), 1);
+        assert!(!snapshot_path(&dir, 1).exists() && snapshot_path(&dir, 2).exists());
+
+        // New files and a ledger line left behind by an interrupted checkpoint
+        std::fs::write(corpus.join("d.chart"), "vi IV I V\n").unwrap();
+        let mut ledger = OpenOptions::new()
+            .append(true)
+            .open(dir.join(LEDGER_FILE))
+            .unwrap();
+        writeln!(
+            ledger,
+            "{{\"path\":\"{}\"}}",
+            corpus.join("d.chart").display()
+        )
+        .unwrap();
+
+        let resumed = AiEngine::new(AiEngineConfig::default());
+        let run = ingest_with_checkpoints(&resumed, paths, &options, &checkpoint).unwrap();
+        assert_eq!(run.files_resumed, 3);
+        assert_eq!(run.checkpoints, 1);
+        assert_eq!(run.patterns, 3);
+        assert_eq!((run.files_read, run.files_skipped), (2, 1));
+        assert_eq!(run.failures.len(), 1);
+        assert_eq!(resumed.get_metrics().total_patterns, 3);
+
+        let ledger = std::fs::read_to_string(dir.join(LEDGER_FILE)).unwrap();
+        assert_eq!(ledger.lines().count(), 4);
+
+        let other = IngestOptions {
+            min_length: 3,
+            ..options
+        };
+        let engine = AiEngine::new(AiEngineConfig::default());
+        let result = ingest_with_checkpoints(&engine, paths, &other, &checkpoint);
+        std::fs::remove_dir_all(&root).unwrap();
+        assert!(matches!(result, Err(CliError::InvalidCheckpoint(_))));
+    }
+}
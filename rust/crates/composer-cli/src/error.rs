@@ -0,0 +1,60 @@
+//! Error types for the command-line tool
+
+use composer_ai::AiError;
+use composer_core::ChordTheoryError;
+use composer_serialization::SerializationError;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Errors reported by CLI commands
+#[derive(Debug, Error)]
+pub enum CliError {
+    #[error("Invalid chord `{token}`: {reason}")]
+    InvalidChord { token: String, reason: String },
+
+    #[error("Invalid MIDI file: {0}")]
+    InvalidMidi(String),
+
+    #[error("Invalid MusicXML file: {0}")]
+    InvalidMusicXml(String),
+
+    #[error("Invalid checkpoint: {0}")]
+    InvalidCheckpoint(String),
+
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+
+    #[error("{}: {source}", path.display())]
+    File {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Chord theory error: {0}")]
+    Theory(#[from] ChordTheoryError),
+
+    #[error("AI engine error: {0}")]
+    Ai(#[from] AiError),
+
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] SerializationError),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl CliError {
+    /// Wrap an I/O error with the path it occurred on
+    pub fn file(path: impl Into<PathBuf>, source: std::io::Error) -> Self {
+        CliError::File {
+            path: path.into(),
+            source,
+        }
+    }
+}
+
+/// Result type for CLI commands
+pub type CliResult<T> = Result<T, CliError>;
@@ -0,0 +1,281 @@
+//! Corpus ingestion: MIDI files, MusicXML scores and chord charts into a trie asset
+
+use crate::chart::parse_chart;
+use crate::error::{CliError, CliResult};
+use crate::midi::{detect_tonic, midi_to_progression, parse_midi, MidiOptions};
+use crate::musicxml::parse_musicxml;
+use composer_ai::TrainingPattern;
+use composer_core::{Chord, Key, Mode};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Extensions read as Standard MIDI Files
+const MIDI_EXTENSIONS: [&str; 2] = ["mid", "midi"];
+
+/// Extensions read as MusicXML; `.mxl` is recognized only to report it as unsupported
+const MUSICXML_EXTENSIONS: [&str; 3] = ["musicxml", "xml", "mxl"];
+
+/// Extensions read as plain-text chord charts
+const CHART_EXTENSIONS: [&str; 3] = ["txt", "chords", "chart"];
+
+/// Pitch class names used for detected MIDI keys
+const TONIC_NAMES: [&str; 12] = [
+    "C", "Db", "D", "Eb", "E", "F", "F#", "G", "Ab", "A", "Bb", "B",
+];
+
+/// Options for [`collect_patterns`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IngestOptions {
+    /// Chord extraction settings for MIDI input
+    pub midi: MidiOptions,
+    /// Progressions shorter than this are dropped
+    pub min_length: usize,
+}
+
+/// Outcome of scanning a corpus
+#[derive(Debug, Default)]
+pub struct Ingested {
+    /// Training patterns in file order
+    pub patterns: Vec<TrainingPattern>,
+    /// Files that contributed patterns
+    pub files_read: usize,
+    /// Files skipped for their extension
+    pub files_skipped: usize,
+    /// Files that failed to parse, with the reason
+    pub failures: Vec<(PathBuf, String)>,
+}
+
+/// Chords of a score and the tonic pitch class they were read in
+pub type ScoreChords = (Vec<Chord>, u8);
+
+/// Notated input chords are extracted from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScoreFormat {
+    Midi,
+    MusicXml,
+}
+
+impl ScoreFormat {
+    /// Format of a file from its extension, ignoring case
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = extension(path);
+        if MIDI_EXTENSIONS.contains(&extension.as_str()) {
+            Some(ScoreFormat::Midi)
+        } else if MUSICXML_EXTENSIONS.contains(&extension.as_str()) {
+            Some(ScoreFormat::MusicXml)
+        } else {
+            None
+        }
+    }
+
+    /// Lowercase format name (e.g. "musicxml")
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScoreFormat::Midi => "midi",
+            ScoreFormat::MusicXml => "musicxml",
+        }
+    }
+}
+
+/// Lowercase extension of a path, empty if it has none
+fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default()
+}
+
+/// Name of a tonic pitch class, e.g. `Eb` for 3
+pub fn tonic_name(tonic: u8) -> &'static str {
+    TONIC_NAMES[tonic as usize % 12]
+}
+
+/// Chords of a MIDI file or MusicXML score, with the tonic they were read in
+///
+/// The tonic is `options.tonic` if given and detected from the notes otherwise.
+pub fn read_score(
+    path: &Path,
+    format: ScoreFormat,
+    options: &MidiOptions,
+) -> CliResult<ScoreChords> {
+    let file = match format {
+        ScoreFormat::Midi => {
+            parse_midi(&std::fs::read(path).map_err(|e| CliError::file(path, e))?)?
+        },
+        ScoreFormat::MusicXml if extension(path) == "mxl" => {
+            return Err(CliError::InvalidMusicXml(
+                "compressed .mxl scores are not supported; export as .musicxml".to_string(),
+            ))
+        },
+        ScoreFormat::MusicXml => {
+            parse_musicxml(&std::fs::read_to_string(path).map_err(|e| CliError::file(path, e))?)?
+        },
+    };
+    let tonic = options.tonic.unwrap_or_else(|| detect_tonic(&file.notes));
+    let midi = MidiOptions {
+        tonic: Some(tonic),
+        ..*options
+    };
+    Ok((midi_to_progression(&file, &midi)?, tonic))
+}
+
+/// Parse a key name such as `C`, `F#` or `Bb` into a pitch class
+pub fn parse_tonic(name: &str) -> CliResult<u8> {
+    let mut chars = name.chars();
+    let letter = match chars.next().map(|c| c.to_ascii_uppercase()) {
+        Some('C') => 0,
+        Some('D') => 2,
+        Some('E') => 4,
+        Some('F') => 5,
+        Some('G') => 7,
+        Some('A') => 9,
+        Some('B') => 11,
+        _ => return Err(CliError::InvalidArgument(format!("unknown key `{}`", name))),
+    };
+    let accidental: i8 = match chars.as_str() {
+        "" => 0,
+        "#" => 1,
+        "b" => -1,
+        _ => return Err(CliError::InvalidArgument(format!("unknown key `{}`", name))),
+    };
+    Ok(((letter + 12 + accidental) % 12) as u8)
+}
+
+/// Recursively list the files under `paths`, sorted for reproducible output
+pub fn list_files(paths: &[PathBuf]) -> CliResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending: Vec<PathBuf> = paths.to_vec();
+    while let Some(path) = pending.pop() {
+        let metadata = std::fs::metadata(&path).map_err(|e| CliError::file(&path, e))?;
+        if metadata.is_dir() {
+            for entry in std::fs::read_dir(&path).map_err(|e| CliError::file(&path, e))? {
+                pending.push(entry.map_err(|e| CliError::file(&path, e))?.path());
+            }
+        } else {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Read every supported file under `paths` and collect its progressions
+pub fn collect_patterns(paths: &[PathBuf], options: &IngestOptions) -> CliResult<Ingested> {
+    let mut ingested = Ingested::default();
+    for file in list_files(paths)? {
+        let Some(result) = read_file(&file, options) else {
+            ingested.files_skipped += 1;
+            continue;
+        };
+
+        match result {
+            Ok(patterns) => {
+                ingested.files_read += 1;
+                ingested.patterns.extend(patterns);
+            },
+            Err(error) => ingested.failures.push((file, error.to_string())),
+        }
+    }
+    Ok(ingested)
+}
+
+/// Progressions of one file, or `None` if its extension is not supported
+pub fn read_file(path: &Path, options: &IngestOptions) -> Option<CliResult<Vec<TrainingPattern>>> {
+    if let Some(format) = ScoreFormat::from_path(path) {
+        Some(read_score_patterns(path, format, options))
+    } else if CHART_EXTENSIONS.contains(&extension(path).as_str()) {
+        Some(read_chart(path, options))
+    } else {
+        None
+    }
+}
+
+fn read_score_patterns(
+    path: &Path,
+    format: ScoreFormat,
+    options: &IngestOptions,
+) -> CliResult<Vec<TrainingPattern>> {
+    let (progression, tonic) = read_score(path, format, &options.midi)?;
+    if progression.len() < options.min_length {
+        return Ok(Vec::new());
+    }
+    Ok(vec![(
+        progression,
+        path.display().to_string(),
+        Some(Key::from_chromatic(tonic, Mode::Major)),
+    )])
+}
+
+fn read_chart(path: &Path, options: &IngestOptions) -> CliResult<Vec<TrainingPattern>> {
+    let text = std::fs::read_to_string(path).map_err(|e| CliError::file(path, e))?;
+    Ok(parse_chart(&text)?
+        .into_iter()
+        .enumerate()
+        .filter(|(_, progression)| progression.len() >= options.min_length)
+        .map(|(index, progression)| {
+            (
+                progression,
+                format!("{}#{}", path.display(), index + 1),
+                None,
+            )
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi::tests::midi_bytes;
+    use crate::musicxml::tests::musicxml_text;
+
+    #[test]
+    fn test_parse_tonic() {
+        assert_eq!(parse_tonic("C").unwrap(), 0);
+        assert_eq!(parse_tonic("f#").unwrap(), 6);
+        assert_eq!(parse_tonic("Cb").unwrap(), 11);
+        assert!(parse_tonic("H").is_err());
+        assert!(parse_tonic("C##").is_err());
+    }
+
+    #[test]
+    fn test_collect_patterns_from_directory() {
+        let dir = std::env::temp_dir().join(format!("composer-cli-ingest-{}", std::process::id()));
+        let nested = dir.join("nested");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(dir.join("songs.chords"), "ii7 V7 I\nI\nI vi IV V\n").unwrap();
+        std::fs::write(dir.join("broken.txt"), "I Q\n").unwrap();
+        std::fs::write(dir.join("README.md"), "ignored").unwrap();
+        std::fs::write(
+            nested.join("cadence.mid"),
+            midi_bytes(&[&[60, 64, 67], &[55, 59, 62, 65], &[60, 64, 67]]),
+        )
+        .unwrap();
+        std::fs::write(
+            nested.join("plagal.musicxml"),
+            musicxml_text(&[&[62, 65, 69], &[65, 69, 72], &[62, 65, 69]]),
+        )
+        .unwrap();
+        std::fs::write(nested.join("archive.mxl"), b"PK").unwrap();
+
+        let options = IngestOptions {
+            midi: MidiOptions::default(),
+            min_length: 2,
+        };
+        let ingested = collect_patterns(std::slice::from_ref(&dir), &options).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(ingested.files_read, 3);
+        assert_eq!(ingested.files_skipped, 1);
+        assert_eq!(ingested.failures.len(), 2);
+        // Two chart lines pass the length filter, plus the MIDI and MusicXML scores
+        assert_eq!(ingested.patterns.len(), 4);
+        let midi = ingested
+            .patterns
+            .iter()
+            .find(|(_, source, _)| source.ends_with("cadence.mid"))
+            .unwrap();
+        assert_eq!(midi.2, Some("C".parse().unwrap()));
+        assert_eq!(midi.0.len(), 3);
+    }
+}
@@ -0,0 +1,678 @@
+//! `composer-cli`: corpus ingestion, analysis and suggestions from the shell
+//!
+//! ```text
+//! composer-cli ingest corpus/ -o patterns.bin       # MIDI and chord charts -> trie
+//! echo "ii7 V7 I" | composer-cli assess --tempo 140  # difficulty per stdin line
+//! composer-cli suggest --trie patterns.bin ii7 V7    # next-chord suggestions
+//! composer-cli stats patterns.bin --format csv      # corpus statistics
+//! composer-cli validate patterns.bin                 # structural asset check
+//! composer-cli watch chart.txt --trie patterns.bin   # re-analyze a chart on save
+//! composer-cli tag scores/ -o manifest.csv           # difficulty manifest of scores
+//! ```
+//!
+//! Every command accepts `--json` for machine-readable output; failures exit with
+//! status 1 and usage errors with status 2.
+
+mod catalog;
+mod chart;
+mod checkpoint;
+mod error;
+mod ingest;
+mod midi;
+mod musicxml;
+mod stats;
+mod watch;
+
+use catalog::CatalogOptions;
+use chart::{parse_chart, parse_progression};
+use checkpoint::{ingest_with_checkpoints, CheckpointOptions, CheckpointedIngest};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use composer_ai::{
+    validate_asset, AiEngine, AiEngineConfig, DifficultyAssessment, DifficultyProfile,
+    SuggestionConfig, SuggestionContext,
+};
+use error::{CliError, CliResult};
+use ingest::{collect_patterns, parse_tonic, IngestOptions};
+use midi::MidiOptions;
+use stats::StatsFormat;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use watch::WatchOptions;
+
+fn cli() -> Command {
+    let json = Arg::new("json")
+        .long("json")
+        .action(ArgAction::SetTrue)
+        .help("Print machine-readable JSON");
+    let trie = Arg::new("trie")
+        .long("trie")
+        .value_name("FILE")
+        .value_parser(value_parser!(PathBuf))
+        .help("Trie binary produced by `ingest`");
+    let tempo = Arg::new("tempo")
+        .long("tempo")
+        .value_name("BPM")
+        .value_parser(value_parser!(f64));
+    let time_signature = Arg::new("time-signature")
+        .long("time-signature")
+        .value_name("N/D")
+        .help("Time signature such as 3/4");
+    let key = Arg::new("key")
+        .long("key")
+        .value_name("TONIC")
+        .help("Major key of MIDI input, e.g. `C` or `Bb` [default: detect]");
+    let window_beats = Arg::new("window-beats")
+        .long("window-beats")
+        .value_name("BEATS")
+        .default_value("1")
+        .value_parser(value_parser!(f64))
+        .help("Quarter notes per extracted MIDI chord");
+
+    Command::new("composer-cli")
+        .version(composer_config::APPLICATION.version)
+        .about("Corpus ingestion, difficulty assessment and chord suggestions")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("ingest")
+                .about("Build a trie binary from MIDI files and chord charts")
+                .arg(
+                    Arg::new("paths")
+                        .required(true)
+                        .num_args(1..)
+                        .value_parser(value_parser!(PathBuf))
+                        .help("Files or directories to read recursively"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .required(true)
+                        .value_name("FILE")
+                        .value_parser(value_parser!(PathBuf))
+                        .help("Where to write the trie binary"),
+                )
+                .arg(key.clone())
+                .arg(window_beats.clone())
+                .arg(
+                    Arg::new("min-length")
+                        .long("min-length")
+                        .value_name("N")
+                        .default_value("2")
+                        .value_parser(value_parser!(usize))
+                        .help("Drop progressions shorter than this"),
+                )
+                .arg(
+                    Arg::new("checkpoint")
+                        .long("checkpoint")
+                        .value_name("DIR")
+                        .value_parser(value_parser!(PathBuf))
+                        .help("Save progress to DIR and resume from it if a run was interrupted"),
+                )
+                .arg(
+                    Arg::new("checkpoint-every")
+                        .long("checkpoint-every")
+                        .value_name("N")
+                        .default_value("1000")
+                        .value_parser(value_parser!(usize))
+                        .requires("checkpoint")
+                        .help("Files to process between checkpoints"),
+                )
+                .arg(json.clone()),
+        )
+        .subcommand(
+            Command::new("assess")
+                .about("Assess the difficulty of each progression read from stdin")
+                .arg(trie.clone())
+                .arg(tempo.clone())
+                .arg(time_signature.clone())
+                .arg(json.clone()),
+        )
+        .subcommand(
+            Command::new("suggest")
+                .about("Suggest chords to follow a progression")
+                .arg(trie.clone().required(true))
+                .arg(
+                    Arg::new("chords")
+                        .num_args(0..)
+                        .help("Progression so far, e.g. `ii7 V7` [default: read stdin]"),
+                )
+                .arg(
+                    Arg::new("count")
+                        .short('n')
+                        .long("count")
+                        .default_value("5")
+                        .value_parser(value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("min-confidence")
+                        .long("min-confidence")
+                        .default_value("0")
+                        .value_parser(value_parser!(f64)),
+                )
+                .arg(json.clone()),
+        )
+        .subcommand(
+            Command::new("stats")
+                .about("Print chord, progression and key statistics of a trie binary")
+                .arg(
+                    Arg::new("file")
+                        .value_name("FILE")
+                        .value_parser(value_parser!(PathBuf))
+                        .conflicts_with("trie")
+                        .help("Trie binary produced by `ingest`"),
+                )
+                .arg(trie.clone().required_unless_present("file"))
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .default_value("text")
+                        .value_parser(["text", "json", "csv"])
+                        .help("Output format; `--json` is short for `--format json`"),
+                )
+                .arg(
+                    Arg::new("length")
+                        .long("length")
+                        .default_value("2")
+                        .value_parser(value_parser!(usize))
+                        .help("Number of chords per counted progression"),
+                )
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .value_parser(value_parser!(usize))
+                        .help("Keep only the N most frequent rows of each table"),
+                )
+                .arg(json.clone()),
+        )
+        .subcommand(
+            Command::new("validate")
+                .about("Check the structure of serialized assets")
+                .arg(
+                    Arg::new("files")
+                        .required(true)
+                        .num_args(1..)
+                        .value_parser(value_parser!(PathBuf)),
+                )
+                .arg(json.clone()),
+        )
+        .subcommand(
+            Command::new("watch")
+                .about("Re-analyze a chord chart whenever it changes")
+                .arg(
+                    Arg::new("chart")
+                        .required(true)
+                        .value_name("FILE")
+                        .value_parser(value_parser!(PathBuf))
+                        .help("Chord chart with one progression per line"),
+                )
+                .arg(
+                    trie.clone()
+                        .help("Trie binary to check chord moves against"),
+                )
+                .arg(tempo.clone())
+                .arg(time_signature)
+                .arg(
+                    Arg::new("interval")
+                        .long("interval")
+                        .value_name("MS")
+                        .default_value("500")
+                        .value_parser(value_parser!(u64))
+                        .help("Milliseconds between checks of the chart"),
+                )
+                .arg(
+                    Arg::new("once")
+                        .long("once")
+                        .action(ArgAction::SetTrue)
+                        .help("Analyze once and exit"),
+                )
+                .arg(json.help("Print one JSON report per line")),
+        )
+        .subcommand(
+            Command::new("tag")
+                .about("Write a difficulty manifest of MIDI and MusicXML scores")
+                .arg(
+                    Arg::new("paths")
+                        .required(true)
+                        .num_args(1..)
+                        .value_parser(value_parser!(PathBuf))
+                        .help("Files or directories to read recursively"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("FILE")
+                        .value_parser(value_parser!(PathBuf))
+                        .help("Where to write the manifest [default: stdout]"),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .default_value("csv")
+                        .value_parser(["csv", "json"])
+                        .help("Manifest format"),
+                )
+                .arg(trie)
+                .arg(key)
+                .arg(window_beats)
+                .arg(tempo)
+                .arg(
+                    Arg::new("instrument")
+                        .long("instrument")
+                        .value_name("NAME")
+                        .value_parser(["piano", "guitar", "ukulele", "voice"])
+                        .help("Assess technical difficulty on an instrument"),
+                )
+                .arg(
+                    Arg::new("jobs")
+                        .short('j')
+                        .long("jobs")
+                        .value_name("N")
+                        .value_parser(value_parser!(usize))
+                        .help("Files assessed in parallel [default: available cores]"),
+                ),
+        )
+}
+
+fn main() -> ExitCode {
+    let matches = cli().get_matches();
+    let (name, args) = matches.subcommand().unwrap_or(("", &matches));
+    let result = match name {
+        "ingest" => ingest(args),
+        "assess" => assess(args),
+        "suggest" => suggest(args),
+        "stats" => stats(args),
+        "watch" => watch(args),
+        "tag" => tag(args),
+        _ => validate(args),
+    };
+    match result {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        },
+    }
+}
+
+fn json(args: &ArgMatches) -> bool {
+    args.get_flag("json")
+}
+
+fn print_json(value: &impl serde::Serialize) -> CliResult<()> {
+    let mut stdout = std::io::stdout().lock();
+    serde_json::to_writer_pretty(&mut stdout, value)?;
+    writeln!(stdout)?;
+    Ok(())
+}
+
+fn read_stdin() -> CliResult<String> {
+    let mut text = String::new();
+    std::io::stdin().read_to_string(&mut text)?;
+    Ok(text)
+}
+
+/// Engine loaded from `--trie`, or an empty engine when the flag is absent
+fn load_engine(args: &ArgMatches) -> CliResult<AiEngine> {
+    match args.get_one::<PathBuf>("trie") {
+        Some(path) => load_trie(path),
+        None => {
+            let engine = AiEngine::new(AiEngineConfig::default());
+            engine.initialize(vec![])?;
+            Ok(engine)
+        },
+    }
+}
+
+/// Engine loaded from a trie binary
+fn load_trie(path: &Path) -> CliResult<AiEngine> {
+    let engine = AiEngine::new(AiEngineConfig::default());
+    let bytes = std::fs::read(path).map_err(|e| CliError::file(path, e))?;
+    engine.initialize_from_binary(&bytes)?;
+    Ok(engine)
+}
+
+/// Chord extraction settings from `--window-beats` and `--key`
+fn midi_options(args: &ArgMatches) -> CliResult<MidiOptions> {
+    Ok(MidiOptions {
+        window_beats: args.get_one::<f64>("window-beats").copied().unwrap_or(1.0),
+        tonic: args
+            .get_one::<String>("key")
+            .map(|key| parse_tonic(key))
+            .transpose()?,
+    })
+}
+
+fn paths(args: &ArgMatches) -> Vec<PathBuf> {
+    args.get_many::<PathBuf>("paths")
+        .into_iter()
+        .flatten()
+        .cloned()
+        .collect()
+}
+
+fn ingest(args: &ArgMatches) -> CliResult<ExitCode> {
+    let paths = paths(args);
+    let output = args
+        .get_one::<PathBuf>("output")
+        .ok_or_else(|| CliError::InvalidArgument("--output is required".to_string()))?;
+    let options = IngestOptions {
+        midi: midi_options(args)?,
+        min_length: args.get_one::<usize>("min-length").copied().unwrap_or(2),
+    };
+
+    let engine = AiEngine::new(AiEngineConfig::default());
+    let run = match args.get_one::<PathBuf>("checkpoint") {
+        Some(dir) => {
+            let checkpoint = CheckpointOptions {
+                dir: dir.clone(),
+                every: args
+                    .get_one::<usize>("checkpoint-every")
+                    .copied()
+                    .unwrap_or(1000),
+            };
+            ingest_with_checkpoints(&engine, &paths, &options, &checkpoint)?
+        },
+        None => {
+            let ingested = collect_patterns(&paths, &options)?;
+            let patterns = ingested.patterns.len();
+            engine.initialize(ingested.patterns)?;
+            CheckpointedIngest {
+                patterns,
+                files_read: ingested.files_read,
+                files_skipped: ingested.files_skipped,
+                failures: ingested.failures,
+                ..Default::default()
+            }
+        },
+    };
+    let asset = engine.export_trie_asset()?;
+    std::fs::write(output, &asset).map_err(|e| CliError::file(output, e))?;
+
+    for (path, reason) in &run.failures {
+        eprintln!("warning: skipped {}: {}", path.display(), reason);
+    }
+    if json(args) {
+        print_json(&serde_json::json!({
+            "output": output.display().to_string(),
+            "bytes": asset.len(),
+            "patterns": run.patterns,
+            "files_read": run.files_read,
+            "files_skipped": run.files_skipped,
+            "files_failed": run.failures.len(),
+            "files_resumed": run.files_resumed,
+        }))?;
+    } else {
+        if run.files_resumed > 0 {
+            println!(
+                "Resumed after {} files from the checkpoint",
+                run.files_resumed
+            );
+        }
+        println!(
+            "Wrote {} patterns from {} files to {} ({} bytes; {} unsupported, {} failed)",
+            run.patterns,
+            run.files_read,
+            output.display(),
+            asset.len(),
+            run.files_skipped,
+            run.failures.len()
+        );
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn assess(args: &ArgMatches) -> CliResult<ExitCode> {
+    let engine = load_engine(args)?;
+    let tempo = args.get_one::<f64>("tempo").copied();
+    let time_signature = args
+        .get_one::<String>("time-signature")
+        .map(|text| parse_time_signature(text))
+        .transpose()?;
+
+    let assessments: Vec<DifficultyAssessment> = parse_chart(&read_stdin()?)?
+        .iter()
+        .map(|progression| engine.assess_difficulty(progression, tempo, time_signature))
+        .collect::<Result<_, _>>()?;
+
+    if json(args) {
+        print_json(&assessments)?;
+    } else {
+        for (index, assessment) in assessments.iter().enumerate() {
+            println!(
+                "{}: {:.1}/10 {:?} (harmonic {:.1}, rhythmic {:.1}, technical {:.1}, melodic {:.1}; confidence {:.2}){}",
+                index + 1,
+                assessment.overall_score,
+                assessment.skill_level,
+                assessment.harmonic_complexity,
+                assessment.rhythmic_complexity,
+                assessment.technical_complexity,
+                assessment.melodic_complexity,
+                assessment.confidence,
+                if assessment.insufficient_data {
+                    " [insufficient data]"
+                } else {
+                    ""
+                }
+            );
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn suggest(args: &ArgMatches) -> CliResult<ExitCode> {
+    let engine = load_engine(args)?;
+    let tokens: Vec<&String> = args
+        .get_many::<String>("chords")
+        .into_iter()
+        .flatten()
+        .collect();
+    let pattern = if tokens.is_empty() {
+        parse_progression(&read_stdin()?.replace('\n', " "))?
+    } else {
+        tokens
+            .iter()
+            .map(|token| chart::parse_chord(token))
+            .collect::<CliResult<_>>()?
+    };
+
+    let config = SuggestionConfig {
+        max_suggestions: args.get_one::<usize>("count").copied().unwrap_or(5),
+        min_confidence: args
+            .get_one::<f64>("min-confidence")
+            .copied()
+            .unwrap_or(0.0),
+        ..Default::default()
+    };
+    let suggestions =
+        engine.get_chord_suggestions(&pattern, &SuggestionContext::default(), &config)?;
+
+    if json(args) {
+        print_json(&suggestions)?;
+    } else if suggestions.is_empty() {
+        println!("No suggestions");
+    } else {
+        for suggestion in &suggestions {
+            println!(
+                "{:<8} confidence {:.2}  score {:.3}  {}",
+                suggestion.chord.to_string(),
+                suggestion.confidence,
+                suggestion.weighted_score,
+                suggestion.reasoning
+            );
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn stats(args: &ArgMatches) -> CliResult<ExitCode> {
+    let engine = match args.get_one::<PathBuf>("file") {
+        Some(path) => load_trie(path)?,
+        None => load_engine(args)?,
+    };
+    let format = if json(args) {
+        StatsFormat::Json
+    } else {
+        args.get_one::<String>("format")
+            .and_then(|name| StatsFormat::parse(name))
+            .unwrap_or(StatsFormat::Text)
+    };
+    let length = args.get_one::<usize>("length").copied().unwrap_or(2);
+    let top = args.get_one::<usize>("top").copied();
+
+    let statistics = engine.trie_statistics();
+    let corpus = engine.corpus_statistics(length)?;
+    match format {
+        StatsFormat::Json => print_json(&stats::to_json(&statistics, &corpus, top))?,
+        StatsFormat::Csv => stats::write_csv(&corpus, top, &mut std::io::stdout().lock())?,
+        StatsFormat::Text => {
+            stats::write_text(&statistics, &corpus, top, &mut std::io::stdout().lock())?
+        },
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn validate(args: &ArgMatches) -> CliResult<ExitCode> {
+    let mut all_valid = true;
+    let mut reports = Vec::new();
+    for path in args.get_many::<PathBuf>("files").into_iter().flatten() {
+        let bytes = std::fs::read(path).map_err(|e| CliError::file(path, e))?;
+        let report = validate_asset(&bytes);
+        all_valid &= report.is_valid();
+        reports.push((path.display().to_string(), report));
+    }
+
+    if json(args) {
+        print_json(&reports)?;
+    } else {
+        for (path, report) in &reports {
+            print!("{}: {}", path, report);
+        }
+    }
+    Ok(if all_valid {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn watch(args: &ArgMatches) -> CliResult<ExitCode> {
+    let engine = load_engine(args)?;
+    let chart = args
+        .get_one::<PathBuf>("chart")
+        .ok_or_else(|| CliError::InvalidArgument("a chart file is required".to_string()))?;
+    let options = WatchOptions {
+        tempo: args.get_one::<f64>("tempo").copied(),
+        time_signature: args
+            .get_one::<String>("time-signature")
+            .map(|text| parse_time_signature(text))
+            .transpose()?,
+        interval: std::time::Duration::from_millis(
+            args.get_one::<u64>("interval").copied().unwrap_or(500),
+        ),
+        once: args.get_flag("once"),
+        json: json(args),
+    };
+    watch::watch(&engine, chart, &options)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn tag(args: &ArgMatches) -> CliResult<ExitCode> {
+    let engine = load_engine(args)?;
+    let options = CatalogOptions {
+        midi: midi_options(args)?,
+        tempo: args.get_one::<f64>("tempo").copied(),
+        instrument: args
+            .get_one::<String>("instrument")
+            .and_then(|name| DifficultyProfile::from_name(name)),
+        jobs: args
+            .get_one::<usize>("jobs")
+            .copied()
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |jobs| jobs.get())),
+    };
+
+    let entries = catalog::tag_files(&engine, &paths(args), &options)?;
+    let mut manifest = Vec::new();
+    if args.get_one::<String>("format").map(String::as_str) == Some("json") {
+        serde_json::to_writer_pretty(&mut manifest, &entries)?;
+        writeln!(manifest)?;
+    } else {
+        catalog::write_csv(&entries, &mut manifest)?;
+    }
+    match args.get_one::<PathBuf>("output") {
+        Some(output) => std::fs::write(output, &manifest).map_err(|e| CliError::file(output, e))?,
+        None => std::io::stdout().lock().write_all(&manifest)?,
+    }
+
+    let failed = entries.iter().filter(|entry| entry.error.is_some()).count();
+    eprintln!(
+        "Tagged {} of {} scores ({} failed)",
+        entries.len() - failed,
+        entries.len(),
+        failed
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+fn parse_time_signature(text: &str) -> CliResult<(u8, u8)> {
+    let invalid = || CliError::InvalidArgument(format!("invalid time signature `{}`", text));
+    let (beats, unit) = text.split_once('/').ok_or_else(invalid)?;
+    Ok((
+        beats.trim().parse().map_err(|_| invalid())?,
+        unit.trim().parse().map_err(|_| invalid())?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        cli().debug_assert();
+        let matches = cli()
+            .try_get_matches_from(["composer-cli", "suggest", "--trie", "t.bin", "ii7", "V7"])
+            .unwrap();
+        let (name, args) = matches.subcommand().unwrap();
+        assert_eq!(name, "suggest");
+        assert_eq!(args.get_many::<String>("chords").unwrap().count(), 2);
+        assert!(cli()
+            .try_get_matches_from(["composer-cli", "stats"])
+            .is_err());
+        assert!(cli()
+            .try_get_matches_from(["composer-cli", "stats", "t.bin", "--format", "csv"])
+            .is_ok());
+        assert!(cli()
+            .try_get_matches_from(["composer-cli", "stats", "t.bin", "--trie", "t.bin"])
+            .is_err());
+        assert!(cli()
+            .try_get_matches_from(["composer-cli", "tag", "scores", "--instrument", "guitar"])
+            .is_ok());
+        assert!(cli()
+            .try_get_matches_from(["composer-cli", "tag", "scores", "--format", "xml"])
+            .is_err());
+        assert!(cli()
+            .try_get_matches_from(["composer-cli", "ingest", "corpus", "-o", "t.bin"])
+            .is_ok());
+        assert!(cli()
+            .try_get_matches_from([
+                "composer-cli",
+                "ingest",
+                "corpus",
+                "-o",
+                "t.bin",
+                "--checkpoint-every",
+                "10"
+            ])
+            .is_err());
+    }
+
+    #[test]
+    fn test_parse_time_signature() {
+        assert_eq!(parse_time_signature("3/4").unwrap(), (3, 4));
+        assert!(parse_time_signature("3-4").is_err());
+        assert!(parse_time_signature("x/4").is_err());
+    }
+}
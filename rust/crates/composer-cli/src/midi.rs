@@ -0,0 +1,392 @@
+//! Chord extraction from Standard MIDI Files
+//!
+//! Parses format 0 and 1 files, merges note-on events from all tracks (skipping
+//! the General MIDI drum channel), slices the timeline into fixed windows and
+//! matches each window's pitch classes against the diatonic triads and sevenths of
+//! the key. The key is detected with the Krumhansl-Schmuckler major profile unless
+//! given explicitly; minor-key material maps onto its relative major.
+
+use crate::error::{CliError, CliResult};
+use composer_core::Chord;
+
+/// Semitone offsets of the major scale degrees
+const MAJOR_SCALE: [u8; 7] = [0, 2, 4, 5, 7, 9, 11];
+
+/// Krumhansl-Kessler major key profile, indexed by semitones above the tonic
+const MAJOR_PROFILE: [f64; 12] = [
+    6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88,
+];
+
+/// Zero-based MIDI channel reserved for percussion
+const DRUM_CHANNEL: u8 = 9;
+
+/// A sounding note: onset tick and MIDI key number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MidiNote {
+    pub tick: u64,
+    pub key: u8,
+}
+
+/// Note onsets of a parsed MIDI file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MidiFile {
+    /// Pulses per quarter note from the header
+    pub ticks_per_quarter: u16,
+    /// Note onsets from all tracks, sorted by tick
+    pub notes: Vec<MidiNote>,
+}
+
+/// Options for turning notes into chords
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MidiOptions {
+    /// Window length in quarter notes; each window yields at most one chord
+    pub window_beats: f64,
+    /// Tonic pitch class of the key (0 = C), or `None` to detect it
+    pub tonic: Option<u8>,
+}
+
+impl Default for MidiOptions {
+    fn default() -> Self {
+        Self {
+            window_beats: 1.0,
+            tonic: None,
+        }
+    }
+}
+
+/// Parse a Standard MIDI File
+pub fn parse_midi(bytes: &[u8]) -> CliResult<MidiFile> {
+    let mut reader = Reader { bytes, pos: 0 };
+    if reader.take(4)? != b"MThd" {
+        return Err(CliError::InvalidMidi("missing MThd header".to_string()));
+    }
+    let header_len = reader.u32()? as usize;
+    let _format = reader.u16()?;
+    let track_count = reader.u16()?;
+    let division = reader.u16()?;
+    reader.take(header_len.saturating_sub(6))?;
+    if division & 0x8000 != 0 || division == 0 {
+        return Err(CliError::InvalidMidi(
+            "SMPTE time division is not supported".to_string(),
+        ));
+    }
+
+    let mut notes = Vec::new();
+    for _ in 0..track_count {
+        let chunk_type = reader.take(4)?;
+        let chunk_len = reader.u32()? as usize;
+        let chunk = reader.take(chunk_len)?;
+        if chunk_type == b"MTrk" {
+            parse_track(chunk, &mut notes)?;
+        }
+    }
+    notes.sort_by_key(|note| (note.tick, note.key));
+
+    Ok(MidiFile {
+        ticks_per_quarter: division,
+        notes,
+    })
+}
+
+/// Detect the major key (tonic pitch class) that best fits the notes
+pub fn detect_tonic(notes: &[MidiNote]) -> u8 {
+    let mut histogram = [0.0; 12];
+    for note in notes {
+        histogram[(note.key % 12) as usize] += 1.0;
+    }
+
+    let correlation = |tonic: usize| -> f64 {
+        let profile = |pc: usize| MAJOR_PROFILE[(pc + 12 - tonic) % 12];
+        let mean_h = histogram.iter().sum::<f64>() / 12.0;
+        let mean_p = MAJOR_PROFILE.iter().sum::<f64>() / 12.0;
+        let (mut cov, mut var_h, mut var_p) = (0.0, 0.0, 0.0);
+        for (pc, &count) in histogram.iter().enumerate() {
+            let (dh, dp) = (count - mean_h, profile(pc) - mean_p);
+            cov += dh * dp;
+            var_h += dh * dh;
+            var_p += dp * dp;
+        }
+        if var_h == 0.0 {
+            0.0
+        } else {
+            cov / (var_h * var_p).sqrt()
+        }
+    };
+
+    (0..12)
+        .map(|tonic| (tonic as u8, correlation(tonic)))
+        .fold((0, f64::MIN), |best, candidate| {
+            if candidate.1 > best.1 {
+                candidate
+            } else {
+                best
+            }
+        })
+        .0
+}
+
+/// Extract a chord progression, merging repeated chords in adjacent windows
+pub fn midi_to_progression(file: &MidiFile, options: &MidiOptions) -> CliResult<Vec<Chord>> {
+    if options.window_beats <= 0.0 || !options.window_beats.is_finite() {
+        return Err(CliError::InvalidArgument(
+            "window length must be positive".to_string(),
+        ));
+    }
+    let tonic = options.tonic.unwrap_or_else(|| detect_tonic(&file.notes)) % 12;
+    let window_ticks = ((file.ticks_per_quarter as f64 * options.window_beats) as u64).max(1);
+
+    let mut progression: Vec<Chord> = Vec::new();
+    let mut start = 0;
+    while start < file.notes.len() {
+        let window = file.notes[start].tick / window_ticks;
+        let end = file.notes[start..]
+            .iter()
+            .position(|note| note.tick / window_ticks != window)
+            .map_or(file.notes.len(), |offset| start + offset);
+
+        if let Some(chord) = match_chord(&file.notes[start..end], tonic)? {
+            if progression.last() != Some(&chord) {
+                progression.push(chord);
+            }
+        }
+        start = end;
+    }
+    Ok(progression)
+}
+
+/// Score, zero-based scale degree and seventh flag of a candidate chord
+type Candidate = (i32, u8, bool);
+
+/// Best diatonic chord for the notes of one window, if any fits
+fn match_chord(notes: &[MidiNote], tonic: u8) -> CliResult<Option<Chord>> {
+    let relative = |key: u8| (key % 12 + 12 - tonic) % 12;
+    let mut pitch_classes = [false; 12];
+    for note in notes {
+        pitch_classes[relative(note.key) as usize] = true;
+    }
+    let Some(bass) = notes.iter().map(|note| note.key).min() else {
+        return Ok(None);
+    };
+
+    let mut best: Option<Candidate> = None;
+    for degree in 0..7 {
+        let tone = |step: usize| MAJOR_SCALE[(degree + step) % 7] as usize;
+        let triad = [tone(0), tone(2), tone(4)];
+        let matched = triad.iter().filter(|&&pc| pitch_classes[pc]).count() as i32;
+        if matched < 2 || !pitch_classes[triad[0]] {
+            continue;
+        }
+        let has_seventh = pitch_classes[tone(6)];
+        let chord_tones = matched + has_seventh as i32;
+        let extra = pitch_classes.iter().filter(|&&present| present).count() as i32 - chord_tones;
+        let score = 2 * matched + has_seventh as i32 - extra;
+        if !matches!(best, Some((best_score, _, _)) if best_score >= score) {
+            best = Some((score, degree as u8, has_seventh));
+        }
+    }
+
+    let Some((_, degree, has_seventh)) = best else {
+        return Ok(None);
+    };
+    let degree = degree as usize;
+    let bass_pc = relative(bass);
+    let inversion = [2, 4, 6]
+        .iter()
+        .position(|&step| MAJOR_SCALE[(degree + step) % 7] == bass_pc)
+        .map_or(0, |index| index as u8 + 1);
+    let inversion = if inversion == 3 && !has_seventh {
+        0
+    } else {
+        inversion
+    };
+
+    let chord = Chord::new(degree as u8 + 1, if has_seventh { 7 } else { 5 })?;
+    Ok(Some(if inversion > 0 {
+        chord.with_inversion(inversion)?
+    } else {
+        chord
+    }))
+}
+
+fn parse_track(track: &[u8], notes: &mut Vec<MidiNote>) -> CliResult<()> {
+    let mut reader = Reader {
+        bytes: track,
+        pos: 0,
+    };
+    let mut tick = 0u64;
+    let mut running_status = None;
+
+    while reader.pos < track.len() {
+        tick += reader.vlq()? as u64;
+        let mut status = reader.u8()?;
+        if status < 0x80 {
+            // Running status: this byte is the first data byte
+            status = running_status
+                .ok_or_else(|| CliError::InvalidMidi("data byte without status".to_string()))?;
+            reader.pos -= 1;
+        }
+
+        match status {
+            0xFF => {
+                let meta_type = reader.u8()?;
+                let len = reader.vlq()? as usize;
+                reader.take(len)?;
+                if meta_type == 0x2F {
+                    break;
+                }
+            },
+            0xF0 | 0xF7 => {
+                let len = reader.vlq()? as usize;
+                reader.take(len)?;
+            },
+            0x80..=0xEF => {
+                running_status = Some(status);
+                let data_len = if matches!(status & 0xF0, 0xC0 | 0xD0) {
+                    1
+                } else {
+                    2
+                };
+                let data = reader.take(data_len)?;
+                let channel = status & 0x0F;
+                if status & 0xF0 == 0x90 && data[1] > 0 && channel != DRUM_CHANNEL {
+                    notes.push(MidiNote { tick, key: data[0] });
+                }
+            },
+            _ => {
+                return Err(CliError::InvalidMidi(format!(
+                    "unexpected status byte {:#04x}",
+                    status
+                )))
+            },
+        }
+    }
+    Ok(())
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> CliResult<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| CliError::InvalidMidi("unexpected end of file".to_string()))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> CliResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> CliResult<u16> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> CliResult<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn vlq(&mut self) -> CliResult<u32> {
+        let mut value = 0u32;
+        for _ in 0..4 {
+            let byte = self.u8()?;
+            value = (value << 7) | (byte & 0x7F) as u32;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(CliError::InvalidMidi(
+            "variable-length quantity too long".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Build a format 0 file with one chord per quarter note
+    pub(crate) fn midi_bytes(chords: &[&[u8]]) -> Vec<u8> {
+        let mut track = Vec::new();
+        // Tempo meta event, then a drum hit that must be ignored
+        track.extend_from_slice(&[0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20]);
+        track.extend_from_slice(&[0x00, 0x99, 36, 100]);
+        for chord in chords {
+            for (i, &key) in chord.iter().enumerate() {
+                // First note sets the status, the rest use running status
+                if i == 0 {
+                    track.extend_from_slice(&[0x00, 0x90, key, 90]);
+                } else {
+                    track.extend_from_slice(&[0x00, key, 90]);
+                }
+            }
+            // Delta of 480 ticks as a two-byte VLQ, then note-offs as velocity 0
+            for (i, &key) in chord.iter().enumerate() {
+                let delta: &[u8] = if i == 0 { &[0x83, 0x60] } else { &[0x00] };
+                track.extend_from_slice(delta);
+                track.extend_from_slice(&[0x90, key, 0]);
+            }
+        }
+        track.extend_from_slice(&[0x00, 0xFF, 0x2F, 0x00]);
+
+        let mut bytes = b"MThd".to_vec();
+        bytes.extend_from_slice(&[0, 0, 0, 6, 0, 0, 0, 1, 0x01, 0xE0]);
+        bytes.extend_from_slice(b"MTrk");
+        bytes.extend_from_slice(&(track.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&track);
+        bytes
+    }
+
+    #[test]
+    fn test_parse_midi() {
+        let file = parse_midi(&midi_bytes(&[&[60, 64, 67], &[65, 69, 72]])).unwrap();
+        assert_eq!(file.ticks_per_quarter, 480);
+        assert_eq!(file.notes.len(), 6);
+        assert_eq!(file.notes[3], MidiNote { tick: 480, key: 65 });
+
+        assert!(parse_midi(b"RIFF").is_err());
+        let mut truncated = midi_bytes(&[&[60]]);
+        truncated.truncate(truncated.len() - 6);
+        assert!(parse_midi(&truncated).is_err());
+    }
+
+    #[test]
+    fn test_extracts_diatonic_progression() {
+        // C - Am/E - F - G7 - G7 - C in C major
+        let bytes = midi_bytes(&[
+            &[48, 64, 67],
+            &[52, 57, 60],
+            &[53, 57, 60],
+            &[43, 59, 62, 65],
+            &[43, 59, 62, 65],
+            &[48, 64, 67, 72],
+        ]);
+        let file = parse_midi(&bytes).unwrap();
+        assert_eq!(detect_tonic(&file.notes), 0);
+
+        let progression = midi_to_progression(&file, &MidiOptions::default()).unwrap();
+        let summary: Vec<_> = progression
+            .iter()
+            .map(|chord| (chord.root, chord.chord_type, chord.inversion))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![(1, 5, 0), (6, 5, 2), (4, 5, 0), (5, 7, 0), (1, 5, 0)]
+        );
+
+        // The same material read in G major shifts the degrees
+        let in_g = MidiOptions {
+            tonic: Some(7),
+            ..Default::default()
+        };
+        assert_eq!(midi_to_progression(&file, &in_g).unwrap()[0].root, 4);
+    }
+}
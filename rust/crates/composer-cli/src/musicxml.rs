@@ -0,0 +1,335 @@
+//! Note extraction from MusicXML scores
+//!
+//! Reads uncompressed partwise MusicXML (`.musicxml`, `.xml`) into the same
+//! note-onset form as [`crate::midi::parse_midi`], so chord extraction and key
+//! detection are shared with MIDI input. Only pitches and timing are read:
+//! `<divisions>`, `<note>` with `<pitch>`, `<duration>`, `<chord/>`, `<rest/>`
+//! and `<grace/>`, and the `<backup>`/`<forward>` moves between voices. Every
+//! part starts at the beginning of the score. Compressed `.mxl` archives and
+//! timewise scores are not supported.
+
+use crate::error::{CliError, CliResult};
+use crate::midi::{MidiFile, MidiNote};
+
+/// Tick resolution of the extracted notes, independent of `<divisions>`
+const TICKS_PER_QUARTER: u16 = 480;
+
+/// Semitones above C of the note steps `C` to `B`
+const STEP_SEMITONES: [(char, i32); 7] = [
+    ('C', 0),
+    ('D', 2),
+    ('E', 4),
+    ('F', 5),
+    ('G', 7),
+    ('A', 9),
+    ('B', 11),
+];
+
+/// One piece of markup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    Open(&'a str),
+    Close(&'a str),
+    Empty(&'a str),
+    Text(&'a str),
+}
+
+/// Element whose `<duration>` is being read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    Note,
+    Backup,
+    Forward,
+}
+
+/// Pitch and flags of the `<note>` being read
+#[derive(Debug, Clone, Default)]
+struct PendingNote {
+    step: Option<char>,
+    alter: i32,
+    octave: Option<i32>,
+    chord: bool,
+    rest: bool,
+    grace: bool,
+}
+
+impl PendingNote {
+    /// MIDI key number, or `None` for rests, unpitched and out-of-range notes
+    fn key(&self) -> Option<u8> {
+        if self.rest {
+            return None;
+        }
+        let step = self.step?;
+        let semitone = STEP_SEMITONES.iter().find(|(name, _)| *name == step)?.1;
+        let key = (self.octave? + 1) * 12 + semitone + self.alter;
+        u8::try_from(key).ok().filter(|key| *key <= 127)
+    }
+}
+
+/// Split markup into tags and text, skipping declarations and comments
+fn tokenize(text: &str) -> CliResult<Vec<Token<'_>>> {
+    let invalid = |reason: &str| CliError::InvalidMusicXml(reason.to_string());
+    let mut tokens = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        let content = rest[..start].trim();
+        if !content.is_empty() {
+            tokens.push(Token::Text(content));
+        }
+        rest = &rest[start..];
+
+        let skip_to = |rest: &str, end: &str| {
+            rest.find(end)
+                .map(|index| index + end.len())
+                .ok_or_else(|| invalid("unterminated markup"))
+        };
+        if rest.starts_with("<?") {
+            rest = &rest[skip_to(rest, "?>")?..];
+        } else if rest.starts_with("<!--") {
+            rest = &rest[skip_to(rest, "-->")?..];
+        } else if rest.starts_with("<!") {
+            rest = &rest[skip_to(rest, ">")?..];
+        } else {
+            // Find the closing `>` outside quoted attribute values
+            let mut quote = None;
+            let end = rest
+                .char_indices()
+                .find(|&(_, c)| match quote {
+                    Some(q) if c == q => {
+                        quote = None;
+                        false
+                    },
+                    Some(_) => false,
+                    None if c == '"' || c == '\'' => {
+                        quote = Some(c);
+                        false
+                    },
+                    None => c == '>',
+                })
+                .map(|(index, _)| index)
+                .ok_or_else(|| invalid("unterminated tag"))?;
+            let tag = &rest[1..end];
+            if let Some(closing) = tag.strip_prefix('/') {
+                tokens.push(Token::Close(closing.trim()));
+            } else {
+                let self_closing = tag.ends_with('/');
+                let tag_name = tag
+                    .split(|c: char| c.is_whitespace() || c == '/')
+                    .next()
+                    .unwrap_or_default();
+                if tag_name.is_empty() {
+                    return Err(invalid("tag without a name"));
+                }
+                tokens.push(if self_closing {
+                    Token::Empty(tag_name)
+                } else {
+                    Token::Open(tag_name)
+                });
+            }
+            rest = &rest[end + 1..];
+        }
+    }
+    Ok(tokens)
+}
+
+/// Parse an uncompressed partwise MusicXML score into note onsets
+pub fn parse_musicxml(text: &str) -> CliResult<MidiFile> {
+    let tokens = tokenize(text)?;
+    if !tokens
+        .iter()
+        .any(|token| matches!(token, Token::Open("score-partwise")))
+    {
+        return Err(CliError::InvalidMusicXml(
+            "expected a partwise score (<score-partwise>)".to_string(),
+        ));
+    }
+
+    let mut notes = Vec::new();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut divisions: u64 = 1;
+    let mut position: u64 = 0;
+    let mut last_onset: u64 = 0;
+    let mut container: Option<Container> = None;
+    let mut duration: u64 = 0;
+    let mut note = PendingNote::default();
+    let ticks = |duration: u64, divisions: u64| duration * TICKS_PER_QUARTER as u64 / divisions;
+
+    for token in tokens {
+        match token {
+            Token::Open(name) | Token::Empty(name) => {
+                match name {
+                    "part" => {
+                        position = 0;
+                        last_onset = 0;
+                    },
+                    "note" => {
+                        note = PendingNote::default();
+                        container = Some(Container::Note);
+                        duration = 0;
+                    },
+                    "backup" | "forward" => {
+                        container = Some(if name == "backup" {
+                            Container::Backup
+                        } else {
+                            Container::Forward
+                        });
+                        duration = 0;
+                    },
+                    "chord" if container == Some(Container::Note) => note.chord = true,
+                    "rest" if container == Some(Container::Note) => note.rest = true,
+                    "grace" if container == Some(Container::Note) => note.grace = true,
+                    _ => {},
+                }
+                if matches!(token, Token::Open(_)) {
+                    stack.push(name);
+                }
+            },
+            Token::Text(text) => match stack.last().copied() {
+                Some("divisions") => {
+                    divisions = text
+                        .parse::<u64>()
+                        .ok()
+                        .filter(|divisions| *divisions > 0)
+                        .ok_or_else(|| {
+                            CliError::InvalidMusicXml(format!("invalid divisions `{}`", text))
+                        })?;
+                },
+                Some("duration") => duration = text.parse::<f64>().unwrap_or(0.0).max(0.0) as u64,
+                Some("step") if container == Some(Container::Note) => {
+                    note.step = text.chars().next().map(|c| c.to_ascii_uppercase());
+                },
+                Some("alter") if container == Some(Container::Note) => {
+                    note.alter = text.parse::<f64>().unwrap_or(0.0).round() as i32;
+                },
+                Some("octave") if container == Some(Container::Note) => {
+                    note.octave = text.parse().ok();
+                },
+                _ => {},
+            },
+            Token::Close(name) => {
+                match (name, container) {
+                    ("note", Some(Container::Note)) => {
+                        let onset = if note.chord { last_onset } else { position };
+                        if let Some(key) = note.key() {
+                            notes.push(MidiNote { tick: onset, key });
+                        }
+                        if !note.chord {
+                            last_onset = position;
+                            if !note.grace {
+                                position += ticks(duration, divisions);
+                            }
+                        }
+                        container = None;
+                    },
+                    ("backup", Some(Container::Backup)) => {
+                        position = position.saturating_sub(ticks(duration, divisions));
+                        container = None;
+                    },
+                    ("forward", Some(Container::Forward)) => {
+                        position += ticks(duration, divisions);
+                        container = None;
+                    },
+                    _ => {},
+                }
+                if let Some(index) = stack.iter().rposition(|open| *open == name) {
+                    stack.truncate(index);
+                }
+            },
+        }
+    }
+    notes.sort_by_key(|note| (note.tick, note.key));
+
+    Ok(MidiFile {
+        ticks_per_quarter: TICKS_PER_QUARTER,
+        notes,
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A one-part score with one whole-note chord per measure, given as MIDI keys
+    pub(crate) fn musicxml_text(chords: &[&[u8]]) -> String {
+        const STEPS: [(&str, i32); 12] = [
+            ("C", 0),
+            ("C", 1),
+            ("D", 0),
+            ("E", -1),
+            ("E", 0),
+            ("F", 0),
+            ("F", 1),
+            ("G", 0),
+            ("A", -1),
+            ("A", 0),
+            ("B", -1),
+            ("B", 0),
+        ];
+        let mut measures = String::new();
+        for (number, chord) in chords.iter().enumerate() {
+            measures.push_str(&format!("<measure number=\"{}\">", number + 1));
+            if number == 0 {
+                measures.push_str("<attributes><divisions>2</divisions></attributes>");
+            }
+            for (index, &key) in chord.iter().enumerate() {
+                let (step, alter) = STEPS[key as usize % 12];
+                measures.push_str(&format!(
+                    "<note>{}<pitch><step>{}</step><alter>{}</alter><octave>{}</octave></pitch>\
+                     <duration>8</duration><type>whole</type></note>",
+                    if index > 0 { "<chord/>" } else { "" },
+                    step,
+                    alter,
+                    key as i32 / 12 - 1
+                ));
+            }
+            measures.push_str("</measure>");
+        }
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <!DOCTYPE score-partwise PUBLIC \"-//Recordare//DTD MusicXML 4.0 Partwise//EN\" \
+             \"http://www.musicxml.org/dtds/partwise.dtd\">\n\
+             <score-partwise version=\"4.0\"><!-- generated -->\
+             <part-list><score-part id=\"P1\"><part-name>Piano</part-name></score-part></part-list>\
+             <part id=\"P1\">{}</part></score-partwise>",
+            measures
+        )
+    }
+
+    #[test]
+    fn test_parse_musicxml() {
+        let text = musicxml_text(&[&[60, 64, 67], &[55, 59, 62, 65], &[61]]);
+        let file = parse_musicxml(&text).unwrap();
+        assert_eq!(file.ticks_per_quarter, TICKS_PER_QUARTER);
+        let onsets: Vec<(u64, u8)> = file.notes.iter().map(|n| (n.tick, n.key)).collect();
+        assert_eq!(
+            onsets,
+            [
+                (0, 60),
+                (0, 64),
+                (0, 67),
+                (1920, 55),
+                (1920, 59),
+                (1920, 62),
+                (1920, 65),
+                (3840, 61)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_voices_and_rests() {
+        let text = "<score-partwise><part id=\"P1\"><measure>\
+            <attributes><divisions>1</divisions></attributes>\
+            <note><rest/><duration>1</duration></note>\
+            <note><pitch><step>E</step><octave>4</octave></pitch><duration>1</duration></note>\
+            <backup><duration>2</duration></backup>\
+            <note><pitch><step>C</step><octave>3</octave></pitch><duration>2</duration></note>\
+            </measure></part></score-partwise>";
+        let file = parse_musicxml(text).unwrap();
+        let onsets: Vec<(u64, u8)> = file.notes.iter().map(|n| (n.tick, n.key)).collect();
+        assert_eq!(onsets, [(0, 48), (480, 64)]);
+
+        assert!(parse_musicxml("<score-timewise></score-timewise>").is_err());
+        assert!(parse_musicxml("<score-partwise><part").is_err());
+    }
+}
@@ -0,0 +1,299 @@
+//! Corpus statistics output
+//!
+//! `stats` prints a chord histogram, frequencies of progressions of a fixed
+//! length and stored progressions per key, each sorted by count, followed by
+//! chord occurrences per root degree and per chord type. Chords and progressions are written in chart notation so they read back
+//! with [`crate::chart::parse_progression`]. CSV output holds every table in one
+//! `table,label,count,share` sheet for plotting tools.
+
+use composer_ai::{CorpusStatistics, TrieStatistics};
+use serde_json::{json, Value};
+use std::io::{self, Write};
+
+/// Output format of `stats`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+impl StatsFormat {
+    /// Format named by `--format`
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "text" => Some(StatsFormat::Text),
+            "json" => Some(StatsFormat::Json),
+            "csv" => Some(StatsFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// One row of a statistics table
+#[derive(Debug, Clone, PartialEq)]
+struct Row {
+    /// Table the row belongs to: "chord", "progression", "key", "root" or "type"
+    table: &'static str,
+    label: String,
+    count: u64,
+    share: f64,
+}
+
+/// Rows of every table, keeping at most `top` rows per table
+fn rows(stats: &CorpusStatistics, top: Option<usize>) -> Vec<Row> {
+    let limit = top.unwrap_or(usize::MAX);
+    let chords = stats.chord_histogram.iter().take(limit).map(|entry| Row {
+        table: "chord",
+        label: entry.chord.to_string(),
+        count: entry.count,
+        share: entry.share,
+    });
+    let progressions = stats
+        .progression_frequencies
+        .iter()
+        .take(limit)
+        .map(|entry| Row {
+            table: "progression",
+            label: progression_label(&entry.chords),
+            count: entry.count,
+            share: entry.share,
+        });
+    let keys = stats.key_coverage.iter().take(limit).map(|entry| Row {
+        table: "key",
+        label: entry
+            .key_tonic
+            .map(|key| key.to_string())
+            .unwrap_or_default(),
+        count: entry.patterns,
+        share: entry.share,
+    });
+    let roots = stats.root_histogram.iter().take(limit).map(|entry| Row {
+        table: "root",
+        label: entry.root.to_string(),
+        count: entry.count,
+        share: entry.share,
+    });
+    let types = stats
+        .chord_type_histogram
+        .iter()
+        .take(limit)
+        .map(|entry| Row {
+            table: "type",
+            label: entry.chord_type.to_string(),
+            count: entry.count,
+            share: entry.share,
+        });
+    chords
+        .chain(progressions)
+        .chain(keys)
+        .chain(roots)
+        .chain(types)
+        .collect()
+}
+
+fn progression_label(chords: &[composer_core::Chord]) -> String {
+    chords
+        .iter()
+        .map(|chord| chord.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Quote a CSV field if it holds a separator, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Write every table as `table,label,count,share` rows under a header
+pub fn write_csv(
+    stats: &CorpusStatistics,
+    top: Option<usize>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    writeln!(out, "table,label,count,share")?;
+    for row in rows(stats, top) {
+        writeln!(
+            out,
+            "{},{},{},{:.6}",
+            row.table,
+            csv_field(&row.label),
+            row.count,
+            row.share
+        )?;
+    }
+    Ok(())
+}
+
+/// Trie statistics followed by the `chords`, `progressions`, `keys`, `roots`
+/// and `types` tables
+pub fn to_json(trie: &TrieStatistics, stats: &CorpusStatistics, top: Option<usize>) -> Value {
+    let limit = top.unwrap_or(usize::MAX);
+    let mut value = serde_json::to_value(trie).unwrap_or_else(|_| json!({}));
+    let chords: Vec<Value> = stats
+        .chord_histogram
+        .iter()
+        .take(limit)
+        .map(|entry| json!({"chord": entry.chord.to_string(), "count": entry.count, "share": entry.share}))
+        .collect();
+    let progressions: Vec<Value> = stats
+        .progression_frequencies
+        .iter()
+        .take(limit)
+        .map(|entry| {
+            json!({
+                "progression": progression_label(&entry.chords),
+                "count": entry.count,
+                "share": entry.share,
+            })
+        })
+        .collect();
+    let keys: Vec<Value> = stats
+        .key_coverage
+        .iter()
+        .take(limit)
+        .map(|entry| {
+            json!({
+                "key": entry.key_tonic,
+                "patterns": entry.patterns,
+                "share": entry.share,
+                "distinct_chords": entry.distinct_chords,
+            })
+        })
+        .collect();
+    let roots: Vec<Value> = stats
+        .root_histogram
+        .iter()
+        .map(|entry| json!({"root": entry.root, "count": entry.count, "share": entry.share}))
+        .collect();
+    let types: Vec<Value> = stats
+        .chord_type_histogram
+        .iter()
+        .map(|entry| {
+            json!({"chord_type": entry.chord_type, "count": entry.count, "share": entry.share})
+        })
+        .collect();
+
+    if let Value::Object(map) = &mut value {
+        map.insert(
+            "progression_length".to_string(),
+            json!(stats.progression_length),
+        );
+        map.insert(
+            "average_progression_length".to_string(),
+            json!(stats.average_progression_length),
+        );
+        map.insert("chords".to_string(), Value::Array(chords));
+        map.insert("progressions".to_string(), Value::Array(progressions));
+        map.insert("keys".to_string(), Value::Array(keys));
+        map.insert("roots".to_string(), Value::Array(roots));
+        map.insert("types".to_string(), Value::Array(types));
+    }
+    value
+}
+
+/// Trie statistics followed by each table
+pub fn write_text(
+    trie: &TrieStatistics,
+    stats: &CorpusStatistics,
+    top: Option<usize>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    writeln!(out, "patterns:           {}", trie.total_patterns)?;
+    writeln!(out, "nodes:              {}", trie.total_nodes)?;
+    writeln!(out, "max depth:          {}", trie.max_depth)?;
+    writeln!(out, "avg branching:      {:.2}", trie.avg_branching_factor)?;
+    writeln!(out, "scale branches:     {}", trie.scale_branches)?;
+    writeln!(out, "memory (bytes):     {}", trie.memory_usage_bytes)?;
+    writeln!(out, "duplicates:         {}", trie.duplicate_patterns)?;
+    writeln!(
+        out,
+        "avg length:         {:.2}",
+        stats.average_progression_length
+    )?;
+
+    let rows = rows(stats, top);
+    for (table, heading) in [
+        ("chord", "chords".to_string()),
+        (
+            "progression",
+            format!("progressions of {} chords", stats.progression_length),
+        ),
+        ("key", "keys".to_string()),
+        ("root", "root degrees".to_string()),
+        ("type", "chord types".to_string()),
+    ] {
+        writeln!(out, "\n{}:", heading)?;
+        for row in rows.iter().filter(|row| row.table == table) {
+            let label = if row.label.is_empty() {
+                "(none)"
+            } else {
+                &row.label
+            };
+            writeln!(
+                out,
+                "  {:<24} {:>8} {:>6.1}%",
+                label,
+                row.count,
+                row.share * 100.0
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use composer_ai::ChordProgressionTrie;
+    use composer_core::Chord;
+
+    fn statistics() -> (TrieStatistics, CorpusStatistics) {
+        let trie = ChordProgressionTrie::new();
+        let progression = [
+            Chord::new(2, 7).unwrap(),
+            Chord::new(5, 7).unwrap(),
+            Chord::new(1, 5).unwrap(),
+        ];
+        trie.add_pattern(&progression, "a".to_string(), Some("C".parse().unwrap()))
+            .unwrap();
+        trie.add_pattern(&progression[1..], "b".to_string(), None)
+            .unwrap();
+        (trie.statistics(), trie.corpus_statistics(2).unwrap())
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let (_, stats) = statistics();
+        let mut out = Vec::new();
+        write_csv(&stats, Some(1), &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "table,label,count,share\n\
+             chord,1,2,0.400000\n\
+             progression,57 1,2,0.666667\n\
+             key,,1,0.500000\n\
+             root,1,2,0.400000\n\
+             type,5,2,0.400000\n"
+        );
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn test_to_json() {
+        let (trie, stats) = statistics();
+        let value = to_json(&trie, &stats, None);
+        assert_eq!(value["total_patterns"], 2);
+        assert_eq!(value["progressions"][0]["progression"], "57 1");
+        assert_eq!(value["keys"].as_array().unwrap().len(), 2);
+        assert_eq!(value["average_progression_length"], 2.5);
+        assert_eq!(value["types"][1]["chord_type"], 7);
+        assert_eq!(StatsFormat::parse("csv"), Some(StatsFormat::Csv));
+        assert_eq!(StatsFormat::parse("xml"), None);
+    }
+}
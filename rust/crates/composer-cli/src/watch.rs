@@ -0,0 +1,268 @@
+//! Live chord chart analysis for `watch`
+//!
+//! Polls a chart file and re-analyzes it whenever its modification time or
+//! size changes, printing each progression as Roman numerals with its
+//! difficulty and warnings. Lines are parsed one by one, so a typo on one line
+//! is reported next to the analysis of the others instead of stopping it.
+//! Polling keeps the command free of platform file-notification APIs; the
+//! default interval of half a second is well below typing speed.
+
+use crate::chart::parse_chart_lines;
+use crate::error::{CliError, CliResult};
+use composer_ai::{AiEngine, SkillLevel};
+use composer_core::{get_relative_chord_graphic, Chord, ScaleFingerprint};
+use serde::Serialize;
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// Options of `watch`
+#[derive(Debug, Clone)]
+pub struct WatchOptions {
+    pub tempo: Option<f64>,
+    pub time_signature: Option<(u8, u8)>,
+    /// Time between checks of the chart file
+    pub interval: Duration,
+    /// Analyze once and return instead of watching
+    pub once: bool,
+    /// Print one JSON report per line instead of text
+    pub json: bool,
+}
+
+/// Analysis of every progression in a chart
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChartReport {
+    pub lines: Vec<LineReport>,
+}
+
+/// Analysis of one chart line
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LineReport {
+    /// 1-based line number in the chart
+    pub line: usize,
+
+    /// Roman numerals in C major; empty when the line does not parse
+    pub numerals: Vec<String>,
+
+    /// Overall difficulty score; `None` when the line could not be assessed
+    pub score: Option<f64>,
+
+    /// Skill level the score falls in
+    pub skill_level: Option<SkillLevel>,
+
+    /// Name of the skill band the score falls in
+    pub skill_band: Option<String>,
+
+    /// Parse errors and analysis warnings
+    pub warnings: Vec<String>,
+}
+
+/// Roman numeral of a chord in C major, or its scale-degree form
+fn numeral(chord: &Chord, scale: &ScaleFingerprint) -> String {
+    get_relative_chord_graphic(chord, scale)
+        .map(|graphic| graphic.full_symbol())
+        .unwrap_or_else(|_| chord.to_string())
+}
+
+/// Analyze every line of a chart
+///
+/// Moves between chords are only checked against the corpus when the engine
+/// holds patterns, since every move is novel to an empty engine.
+pub fn analyze_chart(engine: &AiEngine, text: &str, options: &WatchOptions) -> ChartReport {
+    let scale = ScaleFingerprint::major_scale();
+    let has_corpus = engine.trie_statistics().total_patterns > 0;
+
+    let lines = parse_chart_lines(text)
+        .into_iter()
+        .map(|(line, progression)| {
+            let mut report = LineReport {
+                line,
+                numerals: Vec::new(),
+                score: None,
+                skill_level: None,
+                skill_band: None,
+                warnings: Vec::new(),
+            };
+            let progression = match progression {
+                Ok(progression) => progression,
+                Err(error) => {
+                    report.warnings.push(error.to_string());
+                    return report;
+                },
+            };
+            report.numerals = progression
+                .iter()
+                .map(|chord| numeral(chord, &scale))
+                .collect();
+
+            match engine.assess_difficulty(&progression, options.tempo, options.time_signature) {
+                Ok(assessment) => {
+                    if assessment.insufficient_data {
+                        report.warnings.push(format!(
+                            "only {} chords; the difficulty score is indicative",
+                            progression.len()
+                        ));
+                    }
+                    if has_corpus {
+                        for contribution in &assessment.per_chord_breakdown {
+                            if contribution.index > 0 && contribution.novelty >= 1.0 {
+                                report.warnings.push(format!(
+                                    "{} -> {} does not occur in the corpus",
+                                    report.numerals[contribution.index - 1],
+                                    report.numerals[contribution.index]
+                                ));
+                            }
+                        }
+                    }
+                    report.score = Some(assessment.overall_score);
+                    report.skill_level = Some(assessment.skill_level);
+                    report.skill_band = Some(assessment.skill_band);
+                },
+                Err(error) => report.warnings.push(format!("analysis failed: {}", error)),
+            }
+            report
+        })
+        .collect();
+
+    ChartReport { lines }
+}
+
+/// Print a report as text under a heading naming the chart
+pub fn write_report(path: &Path, report: &ChartReport, out: &mut impl Write) -> io::Result<()> {
+    writeln!(
+        out,
+        "== {} ({} progressions) ==",
+        path.display(),
+        report.lines.len()
+    )?;
+    for line in &report.lines {
+        match (line.score, &line.skill_band) {
+            (Some(score), Some(band)) => writeln!(
+                out,
+                "{:>3}: {}  [{:.1}/10 {}]",
+                line.line,
+                line.numerals.join(" "),
+                score,
+                band
+            )?,
+            _ => writeln!(out, "{:>3}: {}", line.line, line.numerals.join(" "))?,
+        }
+        for warning in &line.warnings {
+            writeln!(out, "     warning: {}", warning)?;
+        }
+    }
+    Ok(())
+}
+
+/// Modification time and size of a file, which change when it is saved
+fn file_stamp(path: &Path) -> io::Result<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path)?;
+    Ok((metadata.modified()?, metadata.len()))
+}
+
+/// Analyze a chart now and again after every change until interrupted
+///
+/// A chart that cannot be read at the start is an error; once watching,
+/// unreadable states (such as an editor replacing the file while saving) are
+/// skipped until the next check.
+pub fn watch(engine: &AiEngine, path: &Path, options: &WatchOptions) -> CliResult<()> {
+    let clear_screen = !options.json && io::stdout().is_terminal();
+    let mut last_stamp = None;
+    loop {
+        let changed = file_stamp(path).and_then(|stamp| {
+            if last_stamp == Some(stamp) {
+                return Ok(None);
+            }
+            std::fs::read_to_string(path).map(|text| Some((stamp, text)))
+        });
+        match changed {
+            Ok(Some((stamp, text))) => {
+                last_stamp = Some(stamp);
+                let report = analyze_chart(engine, &text, options);
+                let mut stdout = io::stdout().lock();
+                if options.json {
+                    serde_json::to_writer(&mut stdout, &report)?;
+                    writeln!(stdout)?;
+                } else {
+                    if clear_screen {
+                        write!(stdout, "\x1b[2J\x1b[H")?;
+                    }
+                    write_report(path, &report, &mut stdout)?;
+                }
+                stdout.flush()?;
+            },
+            Ok(None) => {},
+            Err(error) if last_stamp.is_none() => return Err(CliError::file(path, error)),
+            Err(_) => {},
+        }
+        if options.once {
+            return Ok(());
+        }
+        std::thread::sleep(options.interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use composer_ai::AiEngineConfig;
+
+    fn options() -> WatchOptions {
+        WatchOptions {
+            tempo: None,
+            time_signature: None,
+            interval: Duration::from_millis(10),
+            once: true,
+            json: false,
+        }
+    }
+
+    #[test]
+    fn test_analyze_chart() {
+        let engine = AiEngine::new(AiEngineConfig::default());
+        engine.initialize(vec![]).unwrap();
+
+        let report = analyze_chart(&engine, "ii7 V7 I\n# comment\nI Q V\n", &options());
+        assert_eq!(report.lines.len(), 2);
+        assert_eq!(report.lines[0].line, 1);
+        assert_eq!(report.lines[0].numerals, ["ii7", "V7", "I"]);
+        assert!(report.lines[0].score.is_some());
+        assert!(report.lines[0]
+            .warnings
+            .iter()
+            .all(|warning| !warning.contains("corpus")));
+        assert_eq!(report.lines[1].line, 3);
+        assert!(report.lines[1].score.is_none());
+        assert!(report.lines[1].warnings[0].contains("`Q`"));
+
+        let mut out = Vec::new();
+        write_report(Path::new("chart.txt"), &report, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("== chart.txt (2 progressions) =="));
+        assert!(text.contains("  1: ii7 V7 I  ["));
+    }
+
+    #[test]
+    fn test_corpus_warnings() {
+        let engine = AiEngine::new(AiEngineConfig::default());
+        let cadence = vec![Chord::new(2, 7).unwrap(), Chord::new(5, 7).unwrap()];
+        engine
+            .initialize(vec![(cadence, "ii-V".to_string(), None)])
+            .unwrap();
+
+        let report = analyze_chart(&engine, "ii7 V7 iii", &options());
+        let warnings = &report.lines[0].warnings;
+        assert!(warnings
+            .iter()
+            .any(|w| w == "V7 -> iii does not occur in the corpus"));
+        assert!(!warnings.iter().any(|w| w.starts_with("ii7 -> V7")));
+    }
+
+    #[test]
+    fn test_watch_once() {
+        let engine = AiEngine::new(AiEngineConfig::default());
+        engine.initialize(vec![]).unwrap();
+        let missing = std::env::temp_dir().join("composer-cli-watch-missing.txt");
+        assert!(watch(&engine, &missing, &options()).is_err());
+    }
+}
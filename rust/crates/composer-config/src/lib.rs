@@ -1,10 +1,15 @@
 //! Configuration constants and system parameters for Composer
 //!
 //! This module provides all configuration constants, system parameters, and operational
-//! limits as defined in the Configuration & Constants Specification.
+//! limits as defined in the Configuration & Constants Specification. The tunable
+//! sections can be overridden at runtime through [`ComposerConfig`].
 
 use serde::{Deserialize, Serialize};
 
+mod runtime;
+
+pub use runtime::{ComposerConfig, ComposerConfigBuilder, ENV_PREFIX};
+
 /// Application metadata constants
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ApplicationMetadata {
@@ -63,6 +68,7 @@ pub const MUSICAL: MusicalConstants = MusicalConstants {
 
 /// Analysis parameters
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct AnalysisParameters {
     pub complexity_scale_max: f64,
     pub novelty_threshold: f64,
@@ -78,8 +84,15 @@ pub const ANALYSIS: AnalysisParameters = AnalysisParameters {
     difficulty_percentile_max: 99,
 };
 
+impl Default for AnalysisParameters {
+    fn default() -> Self {
+        ANALYSIS
+    }
+}
+
 /// Performance threshold constants (in milliseconds)
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct PerformanceThresholds {
     pub chord_lookup_max_ms: u32,
     pub chord_suggestion_max_ms: u32,
@@ -97,8 +110,15 @@ pub const PERFORMANCE: PerformanceThresholds = PerformanceThresholds {
     ui_response_max_ms: 16,
 };
 
+impl Default for PerformanceThresholds {
+    fn default() -> Self {
+        PERFORMANCE
+    }
+}
+
 /// Memory limit constants (in MB)
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct MemoryLimits {
     pub memory_usage_max_mb: u32,
     pub cache_size_max_entries: u32,
@@ -114,8 +134,15 @@ pub const MEMORY: MemoryLimits = MemoryLimits {
     trie_memory_max_mb: 100,
 };
 
+impl Default for MemoryLimits {
+    fn default() -> Self {
+        MEMORY
+    }
+}
+
 /// Processing limits
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct ProcessingLimits {
     pub max_pattern_length: u8,
     pub max_suggestions: u8,
@@ -131,8 +158,15 @@ pub const PROCESSING: ProcessingLimits = ProcessingLimits {
     max_concurrent_requests: 10,
 };
 
+impl Default for ProcessingLimits {
+    fn default() -> Self {
+        PROCESSING
+    }
+}
+
 /// Algorithm tuning constants
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct AlgorithmConstants {
     pub context_bonus_forward: f64,
     pub statistical_strength_divisor: f64,
@@ -156,8 +190,15 @@ pub const ALGORITHM: AlgorithmConstants = AlgorithmConstants {
     performance_target_ms: 50,
 };
 
+impl Default for AlgorithmConstants {
+    fn default() -> Self {
+        ALGORITHM
+    }
+}
+
 /// Quality thresholds for suggestions
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct QualityThresholds {
     pub min_suggestion_weight: f64,
     pub max_suggestions_per_query: u8,
@@ -173,6 +214,139 @@ pub const QUALITY: QualityThresholds = QualityThresholds {
     confidence_threshold: 0.3,
 };
 
+impl Default for QualityThresholds {
+    fn default() -> Self {
+        QUALITY
+    }
+}
+
+// Stable error codes reported by every binding. Codes are never reused or renamed.
+
+// Validation errors
+pub const ERROR_INVALID_CHORD_ROOT: &str = "invalid-chord-root";
+pub const ERROR_INVALID_CHORD_TYPE: &str = "invalid-chord-type";
+pub const ERROR_INVALID_INVERSION: &str = "invalid-inversion";
+pub const ERROR_INVALID_APPLIED: &str = "invalid-applied";
+pub const ERROR_INVALID_SCALE: &str = "invalid-scale-fingerprint";
+pub const ERROR_INCOMPATIBLE_ALTERATIONS: &str = "incompatible-alterations";
+pub const ERROR_MISSING_PROPERTY: &str = "missing-required-property";
+pub const ERROR_SCALE_DEGREE_OUT_OF_RANGE: &str = "scale-degree-out-of-range";
+pub const ERROR_INVALID_ALTERATION: &str = "invalid-alteration";
+pub const ERROR_INVALID_SUSPENSION: &str = "invalid-suspension";
+pub const ERROR_CALCULATION_OVERFLOW: &str = "calculation-overflow";
+pub const ERROR_GENERATION_FAILED: &str = "generation-failed";
+pub const ERROR_NO_MATCHING_CHORD: &str = "no-matching-chord";
+pub const ERROR_UNSUPPORTED_VOICING: &str = "unsupported-voicing";
+pub const ERROR_INVALID_COUNTERPOINT: &str = "invalid-counterpoint";
+pub const ERROR_INVALID_TUNING: &str = "invalid-tuning";
+pub const ERROR_INVALID_KEY: &str = "invalid-key";
+
+// Serialization errors
+pub const ERROR_INVALID_BINARY_FORMAT: &str = "invalid-binary-format";
+pub const ERROR_UNSUPPORTED_VERSION: &str = "unsupported-version";
+pub const ERROR_CORRUPTED_BINARY: &str = "corrupted-binary";
+pub const ERROR_TOKEN_LIBRARY_MISSING: &str = "token-library-missing";
+pub const ERROR_INVALID_TOKEN_FORMAT: &str = "invalid-token-format";
+pub const ERROR_INVALID_TOKEN_STREAM: &str = "invalid-token-stream";
+pub const ERROR_BUFFER_OVERFLOW: &str = "buffer-overflow";
+pub const ERROR_DESERIALIZATION_FAILED: &str = "deserialization-failed";
+pub const ERROR_INVALID_CHORD_DATA: &str = "invalid-chord-data";
+pub const ERROR_INVALID_TRIE_STRUCTURE: &str = "invalid-trie-structure";
+pub const ERROR_IO_FAILED: &str = "io-failed";
+pub const ERROR_INVALID_FORMAT: &str = "invalid-format";
+pub const ERROR_UNEXPECTED_EOF: &str = "unexpected-eof";
+pub const ERROR_LIMIT_EXCEEDED: &str = "limit-exceeded";
+pub const ERROR_DESERIALIZATION_TIMEOUT: &str = "deserialization-timeout";
+pub const ERROR_DECRYPTION_FAILED: &str = "decryption-failed";
+pub const ERROR_ENCRYPTION_FAILED: &str = "encryption-failed";
+
+// System errors
+pub const ERROR_ENGINE_NOT_INITIALIZED: &str = "engine-not-initialized";
+pub const ERROR_ASSET_LOAD_FAILED: &str = "asset-load-failed";
+pub const ERROR_MEMORY_EXHAUSTED: &str = "memory-exhausted";
+pub const ERROR_NETWORK_TIMEOUT: &str = "network-timeout";
+pub const ERROR_INVALID_TOKEN: &str = "invalid-auth-token";
+pub const ERROR_DATA_CORRUPTION: &str = "data-corruption";
+pub const ERROR_INVALID_PATTERN: &str = "invalid-pattern";
+pub const ERROR_SUGGESTION_FAILED: &str = "suggestion-failed";
+pub const ERROR_MODEL_NOT_FOUND: &str = "model-not-found";
+pub const ERROR_INVALID_MODEL_VERSION: &str = "invalid-model-version";
+pub const ERROR_INVALID_MODEL: &str = "invalid-model";
+pub const ERROR_ANALYSIS_FAILED: &str = "analysis-failed";
+pub const ERROR_SESSION_FAILED: &str = "session-failed";
+pub const ERROR_THREAD_POOL_FAILED: &str = "thread-pool-failed";
+
+// Performance errors
+pub const ERROR_RESPONSE_TIMEOUT: &str = "response-timeout";
+pub const ERROR_MEMORY_LIMIT_EXCEEDED: &str = "memory-limit-exceeded";
+pub const ERROR_RATE_LIMIT_EXCEEDED: &str = "rate-limit-exceeded";
+pub const ERROR_CACHE_FULL: &str = "cache-full";
+
+/// Every error code constant, keyed by its constant name
+pub const ERROR_CODES: &[(&str, &str)] = &[
+    ("ERROR_INVALID_CHORD_ROOT", ERROR_INVALID_CHORD_ROOT),
+    ("ERROR_INVALID_CHORD_TYPE", ERROR_INVALID_CHORD_TYPE),
+    ("ERROR_INVALID_INVERSION", ERROR_INVALID_INVERSION),
+    ("ERROR_INVALID_APPLIED", ERROR_INVALID_APPLIED),
+    ("ERROR_INVALID_SCALE", ERROR_INVALID_SCALE),
+    (
+        "ERROR_INCOMPATIBLE_ALTERATIONS",
+        ERROR_INCOMPATIBLE_ALTERATIONS,
+    ),
+    ("ERROR_MISSING_PROPERTY", ERROR_MISSING_PROPERTY),
+    (
+        "ERROR_SCALE_DEGREE_OUT_OF_RANGE",
+        ERROR_SCALE_DEGREE_OUT_OF_RANGE,
+    ),
+    ("ERROR_INVALID_ALTERATION", ERROR_INVALID_ALTERATION),
+    ("ERROR_INVALID_SUSPENSION", ERROR_INVALID_SUSPENSION),
+    ("ERROR_CALCULATION_OVERFLOW", ERROR_CALCULATION_OVERFLOW),
+    ("ERROR_GENERATION_FAILED", ERROR_GENERATION_FAILED),
+    ("ERROR_NO_MATCHING_CHORD", ERROR_NO_MATCHING_CHORD),
+    ("ERROR_UNSUPPORTED_VOICING", ERROR_UNSUPPORTED_VOICING),
+    ("ERROR_INVALID_COUNTERPOINT", ERROR_INVALID_COUNTERPOINT),
+    ("ERROR_INVALID_TUNING", ERROR_INVALID_TUNING),
+    ("ERROR_INVALID_KEY", ERROR_INVALID_KEY),
+    ("ERROR_INVALID_BINARY_FORMAT", ERROR_INVALID_BINARY_FORMAT),
+    ("ERROR_UNSUPPORTED_VERSION", ERROR_UNSUPPORTED_VERSION),
+    ("ERROR_CORRUPTED_BINARY", ERROR_CORRUPTED_BINARY),
+    ("ERROR_TOKEN_LIBRARY_MISSING", ERROR_TOKEN_LIBRARY_MISSING),
+    ("ERROR_INVALID_TOKEN_FORMAT", ERROR_INVALID_TOKEN_FORMAT),
+    ("ERROR_INVALID_TOKEN_STREAM", ERROR_INVALID_TOKEN_STREAM),
+    ("ERROR_BUFFER_OVERFLOW", ERROR_BUFFER_OVERFLOW),
+    ("ERROR_DESERIALIZATION_FAILED", ERROR_DESERIALIZATION_FAILED),
+    ("ERROR_INVALID_CHORD_DATA", ERROR_INVALID_CHORD_DATA),
+    ("ERROR_INVALID_TRIE_STRUCTURE", ERROR_INVALID_TRIE_STRUCTURE),
+    ("ERROR_IO_FAILED", ERROR_IO_FAILED),
+    ("ERROR_INVALID_FORMAT", ERROR_INVALID_FORMAT),
+    ("ERROR_UNEXPECTED_EOF", ERROR_UNEXPECTED_EOF),
+    ("ERROR_LIMIT_EXCEEDED", ERROR_LIMIT_EXCEEDED),
+    (
+        "ERROR_DESERIALIZATION_TIMEOUT",
+        ERROR_DESERIALIZATION_TIMEOUT,
+    ),
+    ("ERROR_DECRYPTION_FAILED", ERROR_DECRYPTION_FAILED),
+    ("ERROR_ENCRYPTION_FAILED", ERROR_ENCRYPTION_FAILED),
+    ("ERROR_ENGINE_NOT_INITIALIZED", ERROR_ENGINE_NOT_INITIALIZED),
+    ("ERROR_ASSET_LOAD_FAILED", ERROR_ASSET_LOAD_FAILED),
+    ("ERROR_MEMORY_EXHAUSTED", ERROR_MEMORY_EXHAUSTED),
+    ("ERROR_NETWORK_TIMEOUT", ERROR_NETWORK_TIMEOUT),
+    ("ERROR_INVALID_TOKEN", ERROR_INVALID_TOKEN),
+    ("ERROR_DATA_CORRUPTION", ERROR_DATA_CORRUPTION),
+    ("ERROR_INVALID_PATTERN", ERROR_INVALID_PATTERN),
+    ("ERROR_SUGGESTION_FAILED", ERROR_SUGGESTION_FAILED),
+    ("ERROR_MODEL_NOT_FOUND", ERROR_MODEL_NOT_FOUND),
+    ("ERROR_INVALID_MODEL_VERSION", ERROR_INVALID_MODEL_VERSION),
+    ("ERROR_INVALID_MODEL", ERROR_INVALID_MODEL),
+    ("ERROR_ANALYSIS_FAILED", ERROR_ANALYSIS_FAILED),
+    ("ERROR_SESSION_FAILED", ERROR_SESSION_FAILED),
+    ("ERROR_THREAD_POOL_FAILED", ERROR_THREAD_POOL_FAILED),
+    ("ERROR_RESPONSE_TIMEOUT", ERROR_RESPONSE_TIMEOUT),
+    ("ERROR_MEMORY_LIMIT_EXCEEDED", ERROR_MEMORY_LIMIT_EXCEEDED),
+    ("ERROR_RATE_LIMIT_EXCEEDED", ERROR_RATE_LIMIT_EXCEEDED),
+    ("ERROR_CACHE_FULL", ERROR_CACHE_FULL),
+];
+
 /// Error type for configuration validation
 #[derive(Debug, thiserror::Error)]
 pub enum ConfigError {
@@ -188,6 +362,12 @@ pub enum ConfigError {
 
     #[error("Missing required configuration: {field}")]
     MissingRequired { field: String },
+
+    #[error("Invalid configuration file: {reason}")]
+    Parse { reason: String },
+
+    #[error("Failed to read configuration file {path}: {reason}")]
+    Io { path: String, reason: String },
 }
 
 /// Validation trait for configuration structures
@@ -245,6 +425,39 @@ impl Validate for PerformanceThresholds {
     }
 }
 
+impl Validate for ProcessingLimits {
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.max_suggestions == 0 {
+            return Err(ConfigError::InvalidValue {
+                field: "max_suggestions".to_string(),
+                value: "0".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl Validate for QualityThresholds {
+    fn validate(&self) -> Result<(), ConfigError> {
+        for (field, value) in [
+            ("min_suggestion_weight", self.min_suggestion_weight),
+            ("diversity_threshold", self.diversity_threshold),
+            ("confidence_threshold", self.confidence_threshold),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(ConfigError::OutOfRange {
+                    field: field.to_string(),
+                    min: "0.0".to_string(),
+                    max: "1.0".to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -278,6 +491,15 @@ mod tests {
         assert_eq!(ALGORITHM.max_pattern_length, 20);
     }
 
+    #[test]
+    fn test_error_codes_are_unique() {
+        let mut codes: Vec<&str> = ERROR_CODES.iter().map(|(_, code)| *code).collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), ERROR_CODES.len());
+        assert!(ERROR_CODES.contains(&("ERROR_INVALID_CHORD_ROOT", "invalid-chord-root")));
+    }
+
     #[test]
     fn test_invalid_musical_constants() {
         let invalid = MusicalConstants {
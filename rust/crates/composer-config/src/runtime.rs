@@ -0,0 +1,417 @@
+//! Runtime configuration overrides
+//!
+//! The constants in this crate are the built-in defaults. [`ComposerConfig`]
+//! holds the tunable sections as values, so a deployment can change them
+//! without a rebuild: from a TOML file with one table per section, and from
+//! `COMPOSER__<SECTION>__<FIELD>` environment variables.
+//!
+//! ```toml
+//! [performance]
+//! chord_lookup_max_ms = 5
+//!
+//! [quality]
+//! confidence_threshold = 0.2
+//! ```
+//!
+//! Sections and fields that are left out keep their defaults. Musical
+//! constants and application metadata are facts of the data formats and stay
+//! compile-time only.
+
+use crate::{
+    AlgorithmConstants, AnalysisParameters, ConfigError, MemoryLimits, PerformanceThresholds,
+    ProcessingLimits, QualityThresholds, Validate,
+};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Prefix of the environment variables read by [`ComposerConfig::apply_env`]
+///
+/// The double underscore keeps the variables apart from those of unrelated
+/// tools named "composer" and separates the section from the field.
+pub const ENV_PREFIX: &str = "COMPOSER__";
+
+/// Tunable configuration sections, defaulting to the crate constants
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ComposerConfig {
+    pub analysis: AnalysisParameters,
+    pub performance: PerformanceThresholds,
+    pub memory: MemoryLimits,
+    pub processing: ProcessingLimits,
+    pub algorithm: AlgorithmConstants,
+    pub quality: QualityThresholds,
+}
+
+impl ComposerConfig {
+    /// Start a builder from the defaults
+    pub fn builder() -> ComposerConfigBuilder {
+        ComposerConfigBuilder::default()
+    }
+
+    /// Parse a TOML document, keeping defaults for anything it leaves out
+    pub fn from_toml_str(toml: &str) -> Result<Self, ConfigError> {
+        toml::from_str(toml).map_err(|e| ConfigError::Parse {
+            reason: e.to_string(),
+        })
+    }
+
+    /// Read and parse a TOML file, keeping defaults for anything it leaves out
+    pub fn from_toml_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let toml = std::fs::read_to_string(path).map_err(|e| ConfigError::Io {
+            path: path.display().to_string(),
+            reason: e.to_string(),
+        })?;
+        Self::from_toml_str(&toml).map_err(|e| match e {
+            ConfigError::Parse { reason } => ConfigError::Parse {
+                reason: format!("{}: {}", path.display(), reason),
+            },
+            e => e,
+        })
+    }
+
+    /// Override fields from `COMPOSER__<SECTION>__<FIELD>` variables.
+    ///
+    /// Section and field names are case-insensitive, and variables without
+    /// the prefix are ignored. A variable naming an unknown field, or whose
+    /// value does not fit the field, is an error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_config::ComposerConfig;
+    ///
+    /// let mut config = ComposerConfig::default();
+    /// config.apply_env([
+    ///     ("COMPOSER__PERFORMANCE__CHORD_LOOKUP_MAX_MS", "5"),
+    ///     ("HOME", "/root"),
+    /// ])?;
+    /// assert_eq!(config.performance.chord_lookup_max_ms, 5);
+    ///
+    /// assert!(config.apply_env([("COMPOSER__QUALITY__NO_SUCH_FIELD", "1")]).is_err());
+    /// # Ok::<(), composer_config::ConfigError>(())
+    /// ```
+    pub fn apply_env<I, K, V>(&mut self, vars: I) -> Result<(), ConfigError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let mut table = toml::Value::try_from(&*self).map_err(|e| ConfigError::Parse {
+            reason: e.to_string(),
+        })?;
+
+        for (key, value) in vars {
+            let (key, value) = (key.as_ref(), value.as_ref());
+            let Some(path) = key.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+            let invalid = || ConfigError::InvalidValue {
+                field: key.to_string(),
+                value: value.to_string(),
+            };
+
+            let (section, field) = path.split_once("__").ok_or_else(invalid)?;
+            let slot = table
+                .get_mut(section.to_ascii_lowercase())
+                .and_then(|section| section.get_mut(field.to_ascii_lowercase()))
+                .ok_or_else(invalid)?;
+            let value = value.trim();
+            *slot = match slot {
+                toml::Value::Integer(_) => value.parse().ok().map(toml::Value::Integer),
+                toml::Value::Float(_) => value.parse().ok().map(toml::Value::Float),
+                toml::Value::Boolean(_) => value.parse().ok().map(toml::Value::Boolean),
+                toml::Value::String(_) => Some(toml::Value::String(value.to_string())),
+                _ => None,
+            }
+            .ok_or_else(invalid)?;
+
+            // Catch values the field's type cannot hold, such as 300 for a u8
+            Self::deserialize(table.clone()).map_err(|_| invalid())?;
+        }
+
+        *self = Self::deserialize(table).map_err(|e| ConfigError::Parse {
+            reason: e.to_string(),
+        })?;
+        Ok(())
+    }
+
+    /// Override fields from the process environment (see [`Self::apply_env`])
+    pub fn apply_process_env(&mut self) -> Result<(), ConfigError> {
+        self.apply_env(std::env::vars())
+    }
+}
+
+impl Validate for ComposerConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        self.performance.validate()?;
+        self.processing.validate()?;
+        self.quality.validate()
+    }
+}
+
+/// Environment variables as name and value pairs
+type EnvVars = Vec<(String, String)>;
+
+/// Where the builder reads TOML from
+#[derive(Debug, Clone)]
+enum TomlSource {
+    File(PathBuf),
+    Str(String),
+}
+
+/// Layered construction of a [`ComposerConfig`]
+///
+/// Later layers win: the defaults, then the TOML document, then environment
+/// variables, then sections set with the `with_<section>` methods. The result
+/// is validated by [`ComposerConfigBuilder::build`].
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_config::{ComposerConfig, QualityThresholds};
+///
+/// let config = ComposerConfig::builder()
+///     .with_toml_str("[performance]\nchord_lookup_max_ms = 5\n")
+///     .with_env_vars([("COMPOSER__PERFORMANCE__CHORD_LOOKUP_MAX_MS", "8")])
+///     .with_quality(QualityThresholds {
+///         confidence_threshold: 0.2,
+///         ..QualityThresholds::default()
+///     })
+///     .build()?;
+///
+/// assert_eq!(config.performance.chord_lookup_max_ms, 8);
+/// assert_eq!(config.quality.confidence_threshold, 0.2);
+/// # Ok::<(), composer_config::ConfigError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ComposerConfigBuilder {
+    toml: Option<TomlSource>,
+    env: Option<EnvVars>,
+    analysis: Option<AnalysisParameters>,
+    performance: Option<PerformanceThresholds>,
+    memory: Option<MemoryLimits>,
+    processing: Option<ProcessingLimits>,
+    algorithm: Option<AlgorithmConstants>,
+    quality: Option<QualityThresholds>,
+}
+
+impl ComposerConfigBuilder {
+    /// Read the TOML file at `path`, replacing any earlier TOML source
+    pub fn with_toml_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.toml = Some(TomlSource::File(path.into()));
+        self
+    }
+
+    /// Parse a TOML document, replacing any earlier TOML source
+    pub fn with_toml_str(mut self, toml: impl Into<String>) -> Self {
+        self.toml = Some(TomlSource::Str(toml.into()));
+        self
+    }
+
+    /// Apply overrides from the process environment as it is now
+    pub fn with_env(self) -> Self {
+        self.with_env_vars(std::env::vars())
+    }
+
+    /// Apply overrides from the given variables instead of the process environment
+    pub fn with_env_vars<I, K, V>(mut self, vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.env = Some(
+            vars.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        );
+        self
+    }
+
+    /// Replace the analysis parameters
+    pub fn with_analysis(mut self, analysis: AnalysisParameters) -> Self {
+        self.analysis = Some(analysis);
+        self
+    }
+
+    /// Replace the performance thresholds
+    pub fn with_performance(mut self, performance: PerformanceThresholds) -> Self {
+        self.performance = Some(performance);
+        self
+    }
+
+    /// Replace the memory limits
+    pub fn with_memory(mut self, memory: MemoryLimits) -> Self {
+        self.memory = Some(memory);
+        self
+    }
+
+    /// Replace the processing limits
+    pub fn with_processing(mut self, processing: ProcessingLimits) -> Self {
+        self.processing = Some(processing);
+        self
+    }
+
+    /// Replace the algorithm constants
+    pub fn with_algorithm(mut self, algorithm: AlgorithmConstants) -> Self {
+        self.algorithm = Some(algorithm);
+        self
+    }
+
+    /// Replace the quality thresholds
+    pub fn with_quality(mut self, quality: QualityThresholds) -> Self {
+        self.quality = Some(quality);
+        self
+    }
+
+    /// Apply the layers in order and validate the result
+    pub fn build(self) -> Result<ComposerConfig, ConfigError> {
+        let mut config = match self.toml {
+            Some(TomlSource::File(path)) => ComposerConfig::from_toml_file(path)?,
+            Some(TomlSource::Str(toml)) => ComposerConfig::from_toml_str(&toml)?,
+            None => ComposerConfig::default(),
+        };
+        if let Some(vars) = self.env {
+            config.apply_env(vars)?;
+        }
+
+        if let Some(analysis) = self.analysis {
+            config.analysis = analysis;
+        }
+        if let Some(performance) = self.performance {
+            config.performance = performance;
+        }
+        if let Some(memory) = self.memory {
+            config.memory = memory;
+        }
+        if let Some(processing) = self.processing {
+            config.processing = processing;
+        }
+        if let Some(algorithm) = self.algorithm {
+            config.algorithm = algorithm;
+        }
+        if let Some(quality) = self.quality {
+            config.quality = quality;
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PERFORMANCE, QUALITY};
+
+    #[test]
+    fn test_defaults_match_constants() {
+        let config = ComposerConfig::default();
+        assert_eq!(config.performance, PERFORMANCE);
+        assert_eq!(config.quality, QUALITY);
+        assert!(config.validate().is_ok());
+        assert_eq!(ComposerConfig::builder().build().unwrap(), config);
+    }
+
+    #[test]
+    fn test_partial_toml() {
+        let config = ComposerConfig::from_toml_str(
+            "[memory]\nmemory_usage_max_mb = 64\n\n[quality]\nconfidence_threshold = 0.5\n",
+        )
+        .unwrap();
+        assert_eq!(config.memory.memory_usage_max_mb, 64);
+        assert_eq!(config.memory.cache_size_max_entries, 10000);
+        assert_eq!(config.quality.confidence_threshold, 0.5);
+        assert_eq!(config.performance, PERFORMANCE);
+
+        let round_trip = toml::to_string(&config).unwrap();
+        assert_eq!(ComposerConfig::from_toml_str(&round_trip).unwrap(), config);
+
+        assert!(matches!(
+            ComposerConfig::from_toml_str("[qualty]\nconfidence_threshold = 0.5\n"),
+            Err(ConfigError::Parse { .. })
+        ));
+        assert!(ComposerConfig::from_toml_str("[quality]\nconfidence = 0.5\n").is_err());
+    }
+
+    #[test]
+    fn test_toml_file() {
+        let path =
+            std::env::temp_dir().join(format!("composer-config-{}.toml", std::process::id()));
+        std::fs::write(&path, "[processing]\nmax_suggestions = 12\n").unwrap();
+        let config = ComposerConfig::builder()
+            .with_toml_file(&path)
+            .build()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(config.processing.max_suggestions, 12);
+
+        assert!(matches!(
+            ComposerConfig::from_toml_file(&path),
+            Err(ConfigError::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let mut config = ComposerConfig::default();
+        config
+            .apply_env([
+                ("COMPOSER__MEMORY__MEMORY_USAGE_MAX_MB", "200"),
+                ("composer__quality__diversity_threshold", "0.5"),
+                ("COMPOSER__ANALYSIS__COMPLEXITY_SCALE_MAX", "12"),
+                ("COMPOSER_HOME", "/tmp"),
+            ])
+            .unwrap();
+        assert_eq!(config.memory.memory_usage_max_mb, 200);
+        assert_eq!(config.analysis.complexity_scale_max, 12.0);
+        // The prefix itself is case-sensitive
+        assert_eq!(config.quality, QUALITY);
+
+        for (key, value) in [
+            ("COMPOSER__MEMORY__MEMORY_USAGE_MAX_MB", "lots"),
+            ("COMPOSER__PROCESSING__MAX_SUGGESTIONS", "300"),
+            ("COMPOSER__PROCESSING__MAX_SUGGESTIONS", "-1"),
+            ("COMPOSER__QUALITY", "0.5"),
+            ("COMPOSER__MUSICAL__SCALE_DEGREES", "8"),
+        ] {
+            let mut config = ComposerConfig::default();
+            assert!(
+                matches!(
+                    config.apply_env([(key, value)]),
+                    Err(ConfigError::InvalidValue { .. })
+                ),
+                "{}={}",
+                key,
+                value
+            );
+            assert_eq!(config, ComposerConfig::default());
+        }
+    }
+
+    #[test]
+    fn test_builder_layers_and_validation() {
+        let config = ComposerConfig::builder()
+            .with_toml_str("[performance]\nchord_lookup_max_ms = 5\nui_response_max_ms = 10\n")
+            .with_env_vars([("COMPOSER__PERFORMANCE__CHORD_LOOKUP_MAX_MS", "7")])
+            .build()
+            .unwrap();
+        assert_eq!(config.performance.chord_lookup_max_ms, 7);
+        assert_eq!(config.performance.ui_response_max_ms, 10);
+
+        let config = ComposerConfig::builder()
+            .with_env_vars([("COMPOSER__PERFORMANCE__CHORD_LOOKUP_MAX_MS", "7")])
+            .with_performance(PERFORMANCE)
+            .build()
+            .unwrap();
+        assert_eq!(config.performance, PERFORMANCE);
+
+        assert!(matches!(
+            ComposerConfig::builder()
+                .with_env_vars([("COMPOSER__QUALITY__CONFIDENCE_THRESHOLD", "1.5")])
+                .build(),
+            Err(ConfigError::OutOfRange { .. })
+        ));
+    }
+}
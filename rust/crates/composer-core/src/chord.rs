@@ -1,12 +1,16 @@
 //! Chord data structure and validation
 
-use crate::constants::{VALID_ADD_TONES, VALID_ALTERATIONS, VALID_OMIT_TONES, VALID_SUSPENSIONS};
+use crate::constants::{
+    INCOMPATIBLE_MODIFICATIONS, VALID_ADD_TONES, VALID_ALTERATIONS, VALID_OMIT_TONES,
+    VALID_SUSPENSIONS,
+};
 use crate::error::{ChordTheoryError, ChordTheoryResult};
 use crate::scale::ScaleType;
 use composer_config::MUSICAL;
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 use std::fmt;
+use std::str::FromStr;
 
 /// Core chord data structure with 27 fields as specified in the Composer specification.
 ///
@@ -81,6 +85,7 @@ use std::fmt;
 /// [`AiEngine::assess_difficulty`]: composer_ai::AiEngine::assess_difficulty
 /// [`AiEngine::get_chord_suggestions`]: composer_ai::AiEngine::get_chord_suggestions
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Chord {
     /// Scale degree (1-7), 0 for rest
     pub root: u8,
@@ -139,6 +144,39 @@ pub enum BorrowedScale {
     Numeric(i8),
 }
 
+/// A single change made by one of the chord builder methods
+///
+/// Written and parsed with the symbols [`Chord`]'s `Display` uses: `b9`,
+/// `sus4`, `add9`, `omit5`, `/1` for an inversion and `/V5` for an applied
+/// target.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChordModification {
+    /// Altered tone, as added by [`Chord::with_alteration`]
+    Alteration(String),
+    /// Suspended interval, as added by [`Chord::with_suspension`]
+    Suspension(u8),
+    /// Added tone, as added by [`Chord::with_add`]
+    Add(u8),
+    /// Omitted tone, as added by [`Chord::with_omit`]
+    Omit(u8),
+    /// Inversion level, as set by [`Chord::with_inversion`]
+    Inversion(u8),
+    /// Applied target degree, as set by [`Chord::with_applied`]
+    Applied(u8),
+}
+
+/// Why a [`ChordModification`] cannot be made to a chord
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ModificationConflict {
+    /// The modification's value is not allowed, e.g. `sus3` or `/4`
+    InvalidValue,
+    /// The chord already has a modification that contradicts this one
+    Incompatible {
+        /// The chord's existing modification
+        existing: ChordModification,
+    },
+}
+
 impl Default for Chord {
     fn default() -> Self {
         Self {
@@ -446,33 +484,42 @@ impl Chord {
 
     /// Validate that alterations are compatible with each other
     fn validate_alteration_compatibility(&self) -> ChordTheoryResult<()> {
-        // Check for conflicting alterations (b5 vs #5, etc.)
-        let has_flat5 = self.alterations.iter().any(|a| a == "b5");
-        let has_sharp5 = self.alterations.iter().any(|a| a == "#5");
-        if has_flat5 && has_sharp5 {
-            return Err(ChordTheoryError::IncompatibleAlterations {
-                alterations: vec!["b5".to_string(), "#5".to_string()],
-            });
-        }
-
-        let has_flat9 = self.alterations.iter().any(|a| a == "b9");
-        let has_sharp9 = self.alterations.iter().any(|a| a == "#9");
-        if has_flat9 && has_sharp9 {
-            return Err(ChordTheoryError::IncompatibleAlterations {
-                alterations: vec!["b9".to_string(), "#9".to_string()],
-            });
-        }
-
-        // Check suspension compatibility
-        if self.suspensions.contains(&2) && self.suspensions.contains(&4) {
-            return Err(ChordTheoryError::IncompatibleAlterations {
-                alterations: vec!["sus2".to_string(), "sus4".to_string()],
-            });
+        // Check for conflicting pairs (b5 vs #5, sus2 vs sus4, etc.)
+        let symbols: Vec<String> = self
+            .tone_modifications()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        for (first, second) in INCOMPATIBLE_MODIFICATIONS {
+            if symbols.iter().any(|s| s == first) && symbols.iter().any(|s| s == second) {
+                return Err(ChordTheoryError::IncompatibleAlterations {
+                    alterations: vec![first.to_string(), second.to_string()],
+                });
+            }
         }
 
         Ok(())
     }
 
+    /// Alterations, suspensions, added and omitted tones of the chord
+    fn tone_modifications(&self) -> Vec<ChordModification> {
+        let alterations = self
+            .alterations
+            .iter()
+            .map(|a| ChordModification::Alteration(a.clone()));
+        let suspensions = self
+            .suspensions
+            .iter()
+            .map(|&s| ChordModification::Suspension(s));
+        let adds = self.adds.iter().map(|&a| ChordModification::Add(a));
+        let omits = self.omits.iter().map(|&o| ChordModification::Omit(o));
+        alterations
+            .chain(suspensions)
+            .chain(adds)
+            .chain(omits)
+            .collect()
+    }
+
     /// Builder pattern methods
     ///
     /// Sets the inversion level of the chord.
@@ -767,6 +814,96 @@ impl Chord {
 
         base_count + self.adds.len() - self.omits.len()
     }
+
+    /// Lists the alterations that can be added to the chord without a conflict.
+    ///
+    /// Alterations the chord already has are included, so interfaces can grey
+    /// out exactly the options that [`Chord::with_alteration`] would reject.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_core::Chord;
+    ///
+    /// let flat_five = Chord::seventh(5)?.with_alteration("b5")?;
+    /// assert_eq!(flat_five.compatible_alterations(), ["b5", "b9", "#9", "#11", "b13"]);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Related Functions
+    ///
+    /// - [`validate_modification`] - Reasons any modification is rejected
+    pub fn compatible_alterations(&self) -> Vec<&'static str> {
+        VALID_ALTERATIONS
+            .iter()
+            .copied()
+            .filter(|alteration| {
+                let modification = ChordModification::Alteration(alteration.to_string());
+                validate_modification(self, &modification).is_empty()
+            })
+            .collect()
+    }
+}
+
+/// Checks whether a modification can be made to a chord, without making it.
+///
+/// Returns every reason the matching builder method (such as
+/// [`Chord::with_suspension`]) would fail, or an empty list when it would
+/// succeed: either [`ModificationConflict::InvalidValue`] on its own, or one
+/// [`ModificationConflict::Incompatible`] per existing modification it
+/// contradicts (see [`INCOMPATIBLE_MODIFICATIONS`]).
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_core::{validate_modification, Chord, ChordModification, ModificationConflict};
+///
+/// let sus4 = Chord::triad(5)?.with_suspension(4)?;
+/// assert_eq!(
+///     validate_modification(&sus4, &"sus2".parse()?),
+///     [ModificationConflict::Incompatible {
+///         existing: ChordModification::Suspension(4),
+///     }]
+/// );
+/// assert_eq!(
+///     validate_modification(&sus4, &ChordModification::Inversion(4)),
+///     [ModificationConflict::InvalidValue]
+/// );
+/// assert!(validate_modification(&sus4, &ChordModification::Add(9)).is_empty());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// [`INCOMPATIBLE_MODIFICATIONS`]: crate::constants::INCOMPATIBLE_MODIFICATIONS
+pub fn validate_modification(
+    chord: &Chord,
+    modification: &ChordModification,
+) -> Vec<ModificationConflict> {
+    let valid = match modification {
+        ChordModification::Alteration(alteration) => {
+            VALID_ALTERATIONS.contains(&alteration.as_str())
+        },
+        ChordModification::Suspension(suspension) => VALID_SUSPENSIONS.contains(suspension),
+        ChordModification::Add(add) => VALID_ADD_TONES.contains(add),
+        ChordModification::Omit(omit) => VALID_OMIT_TONES.contains(omit),
+        ChordModification::Inversion(inversion) => *inversion <= 3,
+        ChordModification::Applied(applied) => *applied <= 7,
+    };
+    if !valid {
+        return vec![ModificationConflict::InvalidValue];
+    }
+
+    let symbol = modification.to_string();
+    chord
+        .tone_modifications()
+        .into_iter()
+        .filter(|existing| {
+            let existing = existing.to_string();
+            INCOMPATIBLE_MODIFICATIONS.iter().any(|&(first, second)| {
+                (symbol == first && existing == second) || (symbol == second && existing == first)
+            })
+        })
+        .map(|existing| ModificationConflict::Incompatible { existing })
+        .collect()
 }
 
 impl fmt::Display for Chord {
@@ -817,6 +954,59 @@ impl fmt::Display for Chord {
     }
 }
 
+impl fmt::Display for ChordModification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChordModification::Alteration(alteration) => write!(f, "{}", alteration),
+            ChordModification::Suspension(suspension) => write!(f, "sus{}", suspension),
+            ChordModification::Add(add) => write!(f, "add{}", add),
+            ChordModification::Omit(omit) => write!(f, "omit{}", omit),
+            ChordModification::Inversion(inversion) => write!(f, "/{}", inversion),
+            ChordModification::Applied(applied) => write!(f, "/V{}", applied),
+        }
+    }
+}
+
+impl FromStr for ChordModification {
+    type Err = ChordTheoryError;
+
+    /// Parse a modification symbol; anything without a known prefix is taken
+    /// as an alteration, to be checked by [`validate_modification`]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = |digits: &str| {
+            digits
+                .parse::<u8>()
+                .map_err(|_| ChordTheoryError::InvalidAlteration {
+                    alteration: s.to_string(),
+                })
+        };
+        if let Some(digits) = s.strip_prefix("sus") {
+            Ok(ChordModification::Suspension(number(digits)?))
+        } else if let Some(digits) = s.strip_prefix("add") {
+            Ok(ChordModification::Add(number(digits)?))
+        } else if let Some(digits) = s.strip_prefix("omit") {
+            Ok(ChordModification::Omit(number(digits)?))
+        } else if let Some(digits) = s.strip_prefix("/V") {
+            Ok(ChordModification::Applied(number(digits)?))
+        } else if let Some(digits) = s.strip_prefix('/') {
+            Ok(ChordModification::Inversion(number(digits)?))
+        } else {
+            Ok(ChordModification::Alteration(s.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for ModificationConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModificationConflict::InvalidValue => write!(f, "invalid value"),
+            ModificationConflict::Incompatible { existing } => {
+                write!(f, "incompatible with {}", existing)
+            },
+        }
+    }
+}
+
 impl BorrowedScale {
     /// Get the scale name as string
     pub fn scale_name(&self) -> String {
@@ -891,6 +1081,49 @@ mod tests {
         assert!(chord.validate().is_err());
     }
 
+    #[test]
+    fn test_validate_modification() {
+        let altered = Chord::seventh(5)
+            .unwrap()
+            .with_alteration("b9")
+            .unwrap()
+            .with_alteration("#5")
+            .unwrap();
+        let check = |symbol: &str| validate_modification(&altered, &symbol.parse().unwrap());
+
+        // Every modification the builders would reject is flagged beforehand
+        for symbol in ["b5", "#9", "b7", "sus3", "add2", "omit7", "/4", "/V8"] {
+            let mut chord = altered.clone();
+            let built = match symbol.parse().unwrap() {
+                ChordModification::Alteration(a) => chord.with_alteration(&a),
+                ChordModification::Suspension(s) => chord.with_suspension(s),
+                ChordModification::Add(a) => chord.with_add(a),
+                ChordModification::Omit(o) => chord.with_omit(o),
+                ChordModification::Inversion(i) => chord.with_inversion(i),
+                ChordModification::Applied(a) => {
+                    chord.applied = a;
+                    chord.validate().map(|_| chord)
+                },
+            };
+            assert!(built.is_err(), "{} should be rejected", symbol);
+            assert!(!check(symbol).is_empty(), "{} should be flagged", symbol);
+        }
+        assert_eq!(
+            check("#9"),
+            [ModificationConflict::Incompatible {
+                existing: ChordModification::Alteration("b9".to_string()),
+            }]
+        );
+        assert_eq!(check("b7"), [ModificationConflict::InvalidValue]);
+        for symbol in ["b9", "#11", "sus4", "add6", "omit5", "/3", "/V5"] {
+            assert!(check(symbol).is_empty(), "{} should be allowed", symbol);
+        }
+
+        assert_eq!(altered.compatible_alterations(), ["#5", "b9", "#11", "b13"]);
+        assert!("susx".parse::<ChordModification>().is_err());
+        assert_eq!(ChordModification::Applied(5).to_string(), "/V5");
+    }
+
     #[test]
     fn test_builder_pattern() {
         let chord = Chord::new(5, 7)
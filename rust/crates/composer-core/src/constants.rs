@@ -7,8 +7,14 @@ use std::collections::HashMap;
 pub const UPPER_CASE_NUMERALS: [&str; 7] = ["I", "II", "III", "IV", "V", "VI", "VII"];
 pub const LOWER_CASE_NUMERALS: [&str; 7] = ["i", "ii", "iii", "iv", "v", "vi", "vii"];
 
+/// Figured bass symbols keyed by chord type, indexed by inversion
+pub type FiguredBassMap = HashMap<u8, Vec<&'static str>>;
+
+/// Scale degrees grouped by harmonic function name
+pub type HarmonicFunctionMap = HashMap<&'static str, Vec<u8>>;
+
 /// Figured bass notation for different chord types and inversions
-pub static FIGURED_BASS: Lazy<HashMap<u8, Vec<&'static str>>> = Lazy::new(|| {
+pub static FIGURED_BASS: Lazy<FiguredBassMap> = Lazy::new(|| {
     let mut map = HashMap::new();
     map.insert(5, vec!["", "6", "64"]); // Triad inversions
     map.insert(7, vec!["7", "65", "43", "42"]); // Seventh chord inversions
@@ -66,7 +72,7 @@ pub static VOICE_LEADING_PENALTIES: Lazy<HashMap<&'static str, f64>> = Lazy::new
 });
 
 /// Harmonic function classification by scale degree
-pub static HARMONIC_FUNCTIONS: Lazy<HashMap<&'static str, Vec<u8>>> = Lazy::new(|| {
+pub static HARMONIC_FUNCTIONS: Lazy<HarmonicFunctionMap> = Lazy::new(|| {
     let mut map = HashMap::new();
     map.insert("tonic", vec![1, 6, 3]); // I, vi, iii
     map.insert("predominant", vec![4, 2]); // IV, ii
@@ -102,6 +108,10 @@ pub const VALID_ADD_TONES: [u8; 3] = [4, 6, 9];
 /// Valid omit tone intervals
 pub const VALID_OMIT_TONES: [u8; 2] = [3, 5];
 
+/// Modifications that cannot appear on the same chord, by symbol
+pub const INCOMPATIBLE_MODIFICATIONS: [(&str, &str); 3] =
+    [("b5", "#5"), ("b9", "#9"), ("sus2", "sus4")];
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,511 @@
+//! Species counterpoint rule checking
+//!
+//! Checks simultaneous melodic lines against the rules of strict (species)
+//! counterpoint and reports every violation with the beat and notes involved,
+//! so a student learns which notes to change instead of receiving a single
+//! voice-leading score. Lines are compared at each note onset in any line:
+//!
+//! - parallel fifths and octaves (unisons included) between any two lines, and
+//!   hidden fifths and octaves between the outer lines when the upper one leaps
+//! - voice crossing, with lines given from the highest voice down
+//! - dissonance treatment: a dissonance on a downbeat must be a suspension,
+//!   prepared at the same pitch and resolved down by step, and one off the
+//!   downbeat must be approached and left by step (passing and neighbor tones)
+//!
+//! Seconds, tritones and sevenths and their compounds are dissonant, and so is
+//! a fourth above the lowest sounding note.
+
+use crate::error::{ChordTheoryError, ChordTheoryResult};
+use serde::{Deserialize, Serialize};
+
+/// Tolerance when comparing beat positions
+const BEAT_EPSILON: f64 = 1e-9;
+
+/// Widest melodic move, in semitones, that counts as a step
+const STEP_SEMITONES: u8 = 2;
+
+/// One note or rest of a counterpoint line
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VoiceNote {
+    /// MIDI pitch, or `None` for a rest
+    pub pitch: Option<u8>,
+
+    /// Length in beats
+    pub duration: f64,
+}
+
+impl VoiceNote {
+    /// Create a note of a MIDI pitch
+    pub fn new(pitch: u8, duration: f64) -> Self {
+        Self {
+            pitch: Some(pitch),
+            duration,
+        }
+    }
+
+    /// Create a rest
+    pub fn rest(duration: f64) -> Self {
+        Self {
+            pitch: None,
+            duration,
+        }
+    }
+}
+
+/// Meter the lines are checked in
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CounterpointOptions {
+    /// Beats per measure; onsets at a multiple of it are downbeats
+    pub beats_per_measure: f64,
+}
+
+impl Default for CounterpointOptions {
+    fn default() -> Self {
+        Self {
+            beats_per_measure: 4.0,
+        }
+    }
+}
+
+/// Kinds of rule violation found by [`check_counterpoint`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CounterpointViolationKind {
+    /// Two lines moving in similar motion from a fifth to a fifth
+    ParallelFifths,
+    /// Two lines moving in similar motion from an octave or unison to another
+    ParallelOctaves,
+    /// Outer lines reaching a fifth or octave in similar motion with a leap above
+    HiddenPerfect,
+    /// A line sounding below the line given after it
+    VoiceCrossing,
+    /// Dissonance on a downbeat that was not prepared at the same pitch
+    UnpreparedDissonance,
+    /// Prepared downbeat dissonance not resolving down by step
+    UnresolvedSuspension,
+    /// Dissonance off the downbeat approached or left by leap
+    DissonantLeap,
+}
+
+impl CounterpointViolationKind {
+    /// Get the string name of this violation kind
+    pub fn name(&self) -> &str {
+        match self {
+            CounterpointViolationKind::ParallelFifths => "parallel_fifths",
+            CounterpointViolationKind::ParallelOctaves => "parallel_octaves",
+            CounterpointViolationKind::HiddenPerfect => "hidden_perfect",
+            CounterpointViolationKind::VoiceCrossing => "voice_crossing",
+            CounterpointViolationKind::UnpreparedDissonance => "unprepared_dissonance",
+            CounterpointViolationKind::UnresolvedSuspension => "unresolved_suspension",
+            CounterpointViolationKind::DissonantLeap => "dissonant_leap",
+        }
+    }
+}
+
+/// A rule violation between two lines
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CounterpointViolation {
+    /// Kind of violation
+    pub kind: CounterpointViolationKind,
+
+    /// Beat the violating sonority starts at
+    pub beat: f64,
+
+    /// Lines involved, offending line first: the line holding the dissonance,
+    /// otherwise the upper line of the pair
+    pub voices: (usize, usize),
+
+    /// Index of the sounding note of each line in `voices`
+    pub notes: (usize, usize),
+}
+
+/// Pitched note of a line sounding at an onset
+#[derive(Debug, Clone, Copy)]
+struct Sounding {
+    index: usize,
+    pitch: u8,
+    /// Whether the note starts at the onset rather than being held into it
+    attacked: bool,
+}
+
+/// Index of a line and its note sounding at an onset
+type LineSounding = (usize, Sounding);
+
+/// Notes of every line at one onset, `None` for lines resting or ended
+struct Sonority {
+    beat: f64,
+    voices: Vec<Option<Sounding>>,
+}
+
+fn validate_lines(
+    lines: &[Vec<VoiceNote>],
+    options: &CounterpointOptions,
+) -> ChordTheoryResult<()> {
+    let invalid = |reason: String| Err(ChordTheoryError::InvalidCounterpoint { reason });
+    if lines.len() < 2 {
+        return invalid(format!("need at least two lines, got {}", lines.len()));
+    }
+    if !(options.beats_per_measure.is_finite() && options.beats_per_measure > 0.0) {
+        return invalid(format!(
+            "beats per measure must be positive, got {}",
+            options.beats_per_measure
+        ));
+    }
+    for (line, notes) in lines.iter().enumerate() {
+        for (index, note) in notes.iter().enumerate() {
+            if !(note.duration.is_finite() && note.duration > 0.0) {
+                return invalid(format!(
+                    "note {} of line {} has duration {}",
+                    index, line, note.duration
+                ));
+            }
+            if note.pitch.is_some_and(|pitch| pitch > 127) {
+                return invalid(format!(
+                    "note {} of line {} is not a MIDI pitch",
+                    index, line
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Sonorities at every onset of any line, in beat order
+fn sonorities(lines: &[Vec<VoiceNote>]) -> Vec<Sonority> {
+    let starts: Vec<Vec<f64>> = lines
+        .iter()
+        .map(|notes| {
+            notes
+                .iter()
+                .scan(0.0, |beat, note| {
+                    let start = *beat;
+                    *beat += note.duration;
+                    Some(start)
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut onsets: Vec<f64> = starts.iter().flatten().copied().collect();
+    onsets.sort_by(f64::total_cmp);
+    onsets.dedup_by(|a, b| (*a - *b).abs() < BEAT_EPSILON);
+
+    onsets
+        .into_iter()
+        .map(|beat| Sonority {
+            beat,
+            voices: lines
+                .iter()
+                .zip(&starts)
+                .map(|(notes, starts)| {
+                    let index = starts.iter().zip(notes).position(|(start, note)| {
+                        *start <= beat + BEAT_EPSILON && beat < start + note.duration - BEAT_EPSILON
+                    })?;
+                    Some(Sounding {
+                        index,
+                        pitch: notes[index].pitch?,
+                        attacked: (starts[index] - beat).abs() < BEAT_EPSILON,
+                    })
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+fn is_dissonant(a: u8, b: u8, lowest: u8) -> bool {
+    match a.abs_diff(b) % 12 {
+        1 | 2 | 6 | 10 | 11 => true,
+        5 => a.min(b) == lowest,
+        _ => false,
+    }
+}
+
+/// Pitch of the note `offset` places from `index` in a line, if pitched
+fn neighbor_pitch(line: &[VoiceNote], index: usize, offset: isize) -> Option<u8> {
+    line.get(index.checked_add_signed(offset)?)?.pitch
+}
+
+/// Parallel or hidden perfect interval arriving at `next` between two lines
+fn perfect_motion(
+    before: (Sounding, Sounding),
+    after: (Sounding, Sounding),
+    outer: bool,
+) -> Option<CounterpointViolationKind> {
+    let upper_motion = after.0.pitch as i16 - before.0.pitch as i16;
+    let lower_motion = after.1.pitch as i16 - before.1.pitch as i16;
+    if upper_motion == 0 || lower_motion == 0 || upper_motion.signum() != lower_motion.signum() {
+        return None;
+    }
+
+    let interval = |(upper, lower): (Sounding, Sounding)| upper.pitch.abs_diff(lower.pitch) % 12;
+    match (interval(before), interval(after)) {
+        (7, 7) => Some(CounterpointViolationKind::ParallelFifths),
+        (0, 0) => Some(CounterpointViolationKind::ParallelOctaves),
+        (_, 0 | 7) if outer && upper_motion.unsigned_abs() > STEP_SEMITONES as u16 => {
+            Some(CounterpointViolationKind::HiddenPerfect)
+        },
+        _ => None,
+    }
+}
+
+/// Treatment violation of a dissonance between lines `upper` and `lower`
+fn dissonance_violation(
+    lines: &[Vec<VoiceNote>],
+    (upper, lower): (LineSounding, LineSounding),
+    downbeat: bool,
+) -> Option<(CounterpointViolationKind, usize)> {
+    let step = |from: Option<u8>, to: u8| {
+        from.is_some_and(|from| (1..=STEP_SEMITONES).contains(&from.abs_diff(to)))
+    };
+    let pair = [upper, lower];
+
+    if downbeat {
+        let prepared = pair.iter().find(|(line, note)| {
+            !note.attacked || neighbor_pitch(&lines[*line], note.index, -1) == Some(note.pitch)
+        });
+        return match prepared {
+            Some(&(line, note)) => {
+                let resolution = neighbor_pitch(&lines[line], note.index, 1);
+                let resolves = resolution.is_some_and(|next| next < note.pitch)
+                    && step(resolution, note.pitch);
+                (!resolves).then_some((CounterpointViolationKind::UnresolvedSuspension, line))
+            },
+            None => Some((CounterpointViolationKind::UnpreparedDissonance, upper.0)),
+        };
+    }
+
+    let attacked: Vec<&LineSounding> = pair.iter().filter(|(_, note)| note.attacked).collect();
+    let by_step = attacked.iter().any(|(line, note)| {
+        step(neighbor_pitch(&lines[*line], note.index, -1), note.pitch)
+            && step(neighbor_pitch(&lines[*line], note.index, 1), note.pitch)
+    });
+    (!by_step).then(|| (CounterpointViolationKind::DissonantLeap, attacked[0].0))
+}
+
+/// Check lines against species counterpoint rules.
+///
+/// Lines are given from the highest voice down and all start at beat 0. Every
+/// violation is reported once, in beat order. Fails when fewer than two lines
+/// are given, a duration is not positive or a pitch is above 127.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_core::{
+///     check_counterpoint, CounterpointOptions, CounterpointViolationKind, VoiceNote,
+/// };
+///
+/// // C5 D5 over F4 G4: a fifth moving up to another fifth
+/// let upper = vec![VoiceNote::new(72, 4.0), VoiceNote::new(74, 4.0)];
+/// let lower = vec![VoiceNote::new(65, 4.0), VoiceNote::new(67, 4.0)];
+/// let violations = check_counterpoint(&[upper, lower], &CounterpointOptions::default())?;
+///
+/// assert_eq!(violations.len(), 1);
+/// assert_eq!(violations[0].kind, CounterpointViolationKind::ParallelFifths);
+/// assert_eq!(violations[0].beat, 4.0);
+/// assert_eq!(violations[0].notes, (1, 1));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`analyze_harmonic_function`](crate::analyze_harmonic_function) - Functional syntax of chords
+pub fn check_counterpoint(
+    lines: &[Vec<VoiceNote>],
+    options: &CounterpointOptions,
+) -> ChordTheoryResult<Vec<CounterpointViolation>> {
+    validate_lines(lines, options)?;
+
+    let sonorities = sonorities(lines);
+    let outer = (0, lines.len() - 1);
+    let mut violations: Vec<CounterpointViolation> = Vec::new();
+    let mut report = |kind, beat, first: LineSounding, second: LineSounding| {
+        let violation = CounterpointViolation {
+            kind,
+            beat,
+            voices: (first.0, second.0),
+            notes: (first.1.index, second.1.index),
+        };
+        // Held dissonances and crossings are only reported where they begin
+        let repeated = violations.iter().any(|seen| {
+            seen.kind == kind && seen.voices == violation.voices && seen.notes == violation.notes
+        });
+        if !repeated {
+            violations.push(violation);
+        }
+    };
+
+    for (position, sonority) in sonorities.iter().enumerate() {
+        let measure_position = sonority.beat / options.beats_per_measure;
+        let downbeat = (measure_position - measure_position.round()).abs() < BEAT_EPSILON;
+        let lowest = sonority
+            .voices
+            .iter()
+            .flatten()
+            .map(|note| note.pitch)
+            .min();
+
+        for upper in 0..lines.len() {
+            for lower in upper + 1..lines.len() {
+                let (Some(high), Some(low)) = (sonority.voices[upper], sonority.voices[lower])
+                else {
+                    continue;
+                };
+                if !(high.attacked || low.attacked) {
+                    continue;
+                }
+                let pair = ((upper, high), (lower, low));
+
+                let previous = position
+                    .checked_sub(1)
+                    .map(|previous| &sonorities[previous].voices);
+                if let Some((Some(high_before), Some(low_before))) =
+                    previous.map(|voices| (voices[upper], voices[lower]))
+                {
+                    let outer = (upper, lower) == outer;
+                    if let Some(kind) =
+                        perfect_motion((high_before, low_before), (high, low), outer)
+                    {
+                        report(kind, sonority.beat, pair.0, pair.1);
+                    }
+                }
+
+                if high.pitch < low.pitch {
+                    report(
+                        CounterpointViolationKind::VoiceCrossing,
+                        sonority.beat,
+                        pair.0,
+                        pair.1,
+                    );
+                }
+
+                if lowest.is_some_and(|lowest| is_dissonant(high.pitch, low.pitch, lowest)) {
+                    if let Some((kind, line)) = dissonance_violation(lines, pair, downbeat) {
+                        let (first, second) = if line == upper {
+                            pair
+                        } else {
+                            (pair.1, pair.0)
+                        };
+                        report(kind, sonority.beat, first, second);
+                    }
+                }
+            }
+        }
+    }
+    Ok(violations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(notes: &[(u8, f64)]) -> Vec<VoiceNote> {
+        notes
+            .iter()
+            .map(|&(pitch, duration)| VoiceNote::new(pitch, duration))
+            .collect()
+    }
+
+    fn kinds(upper: &[(u8, f64)], lower: &[(u8, f64)]) -> Vec<CounterpointViolationKind> {
+        check_counterpoint(&[line(upper), line(lower)], &CounterpointOptions::default())
+            .unwrap()
+            .into_iter()
+            .map(|violation| violation.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_perfect_motion() {
+        use CounterpointViolationKind::*;
+
+        assert_eq!(
+            kinds(&[(72, 4.0), (74, 4.0)], &[(60, 4.0), (62, 4.0)]),
+            [ParallelOctaves]
+        );
+        // E5 G5 over C4 G4: similar motion into an octave with a leap above
+        assert_eq!(
+            kinds(&[(76, 4.0), (79, 4.0)], &[(60, 4.0), (67, 4.0)]),
+            [HiddenPerfect]
+        );
+        // Contrary motion into an octave is fine
+        assert!(kinds(&[(74, 4.0), (72, 4.0)], &[(59, 4.0), (60, 4.0)]).is_empty());
+
+        // Only the outer lines are checked for hidden perfects
+        let lines = [
+            line(&[(84, 4.0), (83, 4.0)]),
+            line(&[(76, 4.0), (79, 4.0)]),
+            line(&[(60, 4.0), (67, 4.0)]),
+        ];
+        assert!(check_counterpoint(&lines, &CounterpointOptions::default())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_voice_crossing() {
+        let violations = check_counterpoint(
+            &[line(&[(67, 4.0), (60, 4.0)]), line(&[(64, 4.0), (64, 4.0)])],
+            &CounterpointOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].kind, CounterpointViolationKind::VoiceCrossing);
+        assert_eq!((violations[0].beat, violations[0].voices), (4.0, (0, 1)));
+    }
+
+    #[test]
+    fn test_passing_tones() {
+        // E4 D4 C4 over C3 A2: D4 passes between E4 and C4
+        assert!(kinds(&[(64, 2.0), (62, 2.0), (60, 4.0)], &[(48, 4.0), (45, 4.0)]).is_empty());
+
+        // E4 D4 G4: the dissonant D4 is left by leap
+        let violations = check_counterpoint(
+            &[
+                line(&[(64, 2.0), (62, 2.0), (67, 4.0)]),
+                line(&[(48, 4.0), (43, 4.0)]),
+            ],
+            &CounterpointOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].kind, CounterpointViolationKind::DissonantLeap);
+        assert_eq!(violations[0].beat, 2.0);
+        assert_eq!(violations[0].notes, (1, 0));
+    }
+
+    #[test]
+    fn test_suspensions() {
+        use CounterpointViolationKind::*;
+
+        // C5 held over the bass moving F3 to G3 makes a fourth, resolving to B4
+        let lower = [(53, 4.0), (55, 4.0)];
+        assert!(kinds(&[(69, 2.0), (72, 4.0), (71, 2.0)], &lower).is_empty());
+        assert_eq!(
+            kinds(&[(69, 2.0), (72, 4.0), (74, 2.0)], &lower),
+            [UnresolvedSuspension]
+        );
+        // Struck without preparation
+        assert_eq!(
+            kinds(&[(69, 4.0), (72, 4.0)], &lower),
+            [UnpreparedDissonance]
+        );
+
+        // A fourth between upper lines is consonant
+        let lines = [line(&[(72, 4.0)]), line(&[(67, 4.0)]), line(&[(48, 4.0)])];
+        assert!(check_counterpoint(&lines, &CounterpointOptions::default())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_invalid_lines() {
+        let options = CounterpointOptions::default();
+        let upper = line(&[(72, 4.0)]);
+        assert!(check_counterpoint(std::slice::from_ref(&upper), &options).is_err());
+        assert!(check_counterpoint(&[upper.clone(), line(&[(60, 0.0)])], &options).is_err());
+        assert!(check_counterpoint(&[upper.clone(), line(&[(128, 4.0)])], &options).is_err());
+        let rests = vec![VoiceNote::rest(2.0), VoiceNote::new(64, 2.0)];
+        assert!(check_counterpoint(&[upper, rests], &options)
+            .unwrap()
+            .is_empty());
+    }
+}
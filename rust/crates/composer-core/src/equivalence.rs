@@ -0,0 +1,150 @@
+//! Chord equivalence
+//!
+//! Two chords can be written differently and still be the same chord for a
+//! given purpose. [`EquivalenceLevel`] names how much has to match: the spelled
+//! chord tones, only the pitches they sound, or only the harmonic function in
+//! the key. [`Chord::equivalent`] compares chords at a level, and
+//! [`is_isotonal`] is the pitch-content comparison on its own.
+
+use crate::chord::Chord;
+use crate::error::ChordTheoryResult;
+use crate::harmonic_function::harmonic_function;
+use crate::scale::ScaleFingerprint;
+use crate::theory::get_stable_scale_degrees;
+use crate::transform::chord_pitch_classes;
+use serde::{Deserialize, Serialize};
+
+/// How much two chords must share to count as equivalent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum EquivalenceLevel {
+    /// Same spelled scale degrees from the bass up: I7 and V7/IV, but not a
+    /// chord and its inversion, and not ♯4 and ♭5
+    ExactSpelling,
+    /// Same pitch classes, whatever the spelling, root or inversion (isotonal
+    /// chords): I6 and vi7, or a chord and its inversions
+    #[default]
+    PitchContent,
+    /// Same harmonic function in the same key (see [`harmonic_function`]): I
+    /// and vi are both tonic, V/V and vii°/V both dominant of V
+    Functional,
+}
+
+impl Chord {
+    /// Check if two chords are equivalent at `level` in a scale.
+    ///
+    /// Rests are equivalent to each other and to no chord. Chords equivalent by
+    /// spelling always have the same pitch content. Fails if either chord is
+    /// invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_core::{Chord, EquivalenceLevel, ScaleFingerprint};
+    ///
+    /// let major = ScaleFingerprint::major_scale();
+    /// let tonic = Chord::triad(1)?;
+    /// let first_inversion = Chord::triad(1)?.with_inversion(1)?;
+    /// assert!(!tonic.equivalent(&first_inversion, EquivalenceLevel::ExactSpelling, &major)?);
+    /// assert!(tonic.equivalent(&first_inversion, EquivalenceLevel::PitchContent, &major)?);
+    ///
+    /// // vi shares the tonic function without sharing its pitches
+    /// let submediant = Chord::triad(6)?;
+    /// assert!(!tonic.equivalent(&submediant, EquivalenceLevel::PitchContent, &major)?);
+    /// assert!(tonic.equivalent(&submediant, EquivalenceLevel::Functional, &major)?);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn equivalent(
+        &self,
+        other: &Chord,
+        level: EquivalenceLevel,
+        scale_fingerprint: &ScaleFingerprint,
+    ) -> ChordTheoryResult<bool> {
+        self.validate()?;
+        other.validate()?;
+        if self.is_rest || other.is_rest {
+            return Ok(self.is_rest && other.is_rest);
+        }
+        match level {
+            EquivalenceLevel::ExactSpelling => {
+                Ok(get_stable_scale_degrees(self, scale_fingerprint)?
+                    == get_stable_scale_degrees(other, scale_fingerprint)?)
+            },
+            EquivalenceLevel::PitchContent => is_isotonal(self, other, scale_fingerprint),
+            EquivalenceLevel::Functional => Ok(harmonic_function(self, scale_fingerprint)?
+                == harmonic_function(other, scale_fingerprint)?),
+        }
+    }
+}
+
+/// Check if two chords sound the same pitch classes in a scale.
+///
+/// Spelling, root and inversion are ignored, so a chord is isotonal with its
+/// inversions and I6 with vi7. Rests have no pitches and are isotonal with each
+/// other only.
+pub fn is_isotonal(
+    chord1: &Chord,
+    chord2: &Chord,
+    scale_fingerprint: &ScaleFingerprint,
+) -> ChordTheoryResult<bool> {
+    if chord1.is_rest || chord2.is_rest {
+        return Ok(chord1.is_rest && chord2.is_rest);
+    }
+    let pitch_content = |chord: &Chord| -> ChordTheoryResult<u16> {
+        Ok(chord_pitch_classes(chord, scale_fingerprint)?
+            .into_iter()
+            .fold(0, |set, pitch_class| set | 1 << pitch_class))
+    };
+    Ok(pitch_content(chord1)? == pitch_content(chord2)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chord::BorrowedScale;
+    use crate::scale::ScaleType;
+
+    fn equivalent(a: &Chord, b: &Chord, level: EquivalenceLevel) -> bool {
+        a.equivalent(b, level, &ScaleFingerprint::major_scale())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_levels() {
+        use EquivalenceLevel::*;
+
+        let tonic_seventh = Chord::seventh(1)
+            .unwrap()
+            .with_borrowed_scale(BorrowedScale::ScaleType(ScaleType::Mixolydian))
+            .unwrap();
+        let five_of_four = Chord::seventh(5).unwrap().with_applied(4).unwrap();
+        for level in [ExactSpelling, PitchContent, Functional] {
+            let tonic = Chord::triad(1).unwrap();
+            assert!(equivalent(
+                &tonic,
+                &tonic.clone().with_timing(2.0, 1.0),
+                level
+            ));
+            assert!(equivalent(&Chord::rest(), &Chord::rest(), level));
+            assert!(!equivalent(&tonic, &Chord::rest(), level));
+        }
+        assert!(equivalent(&tonic_seventh, &five_of_four, ExactSpelling));
+        assert!(equivalent(&tonic_seventh, &five_of_four, PitchContent));
+
+        let sixth = Chord::triad(1).unwrap().with_add(6).unwrap();
+        let submediant_seventh = Chord::seventh(6).unwrap();
+        assert!(!equivalent(&sixth, &submediant_seventh, ExactSpelling));
+        assert!(equivalent(&sixth, &submediant_seventh, PitchContent));
+
+        let dominant = Chord::triad(5).unwrap();
+        let leading_tone = Chord::triad(7).unwrap();
+        assert!(!equivalent(&dominant, &leading_tone, PitchContent));
+        assert!(equivalent(&dominant, &leading_tone, Functional));
+        assert!(!equivalent(
+            &dominant,
+            &Chord::triad(4).unwrap(),
+            Functional
+        ));
+        let secondary = Chord::triad(5).unwrap().with_applied(5).unwrap();
+        assert!(!equivalent(&dominant, &secondary, Functional));
+    }
+}
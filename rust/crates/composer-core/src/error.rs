@@ -1,48 +1,126 @@
 //! Error types for chord theory operations
 
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 /// Error types for chord theory operations
-#[derive(Debug, Error, Clone, PartialEq)]
+///
+/// Serializes as `{"code": ..., "details": {...}}`, where `code` is the stable
+/// code from [`ChordTheoryError::code`] and `details` holds the variant's fields.
+#[derive(Debug, Error, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "code", content = "details")]
 pub enum ChordTheoryError {
     #[error("Invalid chord root degree: {root}. Must be 0-7")]
+    #[serde(rename = "invalid-chord-root")]
     InvalidChordRoot { root: u8 },
 
     #[error("Invalid chord type: {chord_type}. Must be one of [5, 7, 9, 11, 13]")]
+    #[serde(rename = "invalid-chord-type")]
     InvalidChordType { chord_type: u8 },
 
     #[error("Invalid inversion level: {inversion}. Must be 0-3")]
+    #[serde(rename = "invalid-inversion")]
     InvalidInversion { inversion: u8 },
 
     #[error("Invalid applied degree: {applied}. Must be 0-7")]
+    #[serde(rename = "invalid-applied")]
     InvalidApplied { applied: u8 },
 
     #[error("Invalid scale fingerprint: {reason}")]
+    #[serde(rename = "invalid-scale-fingerprint")]
     InvalidScaleFingerprint { reason: String },
 
     #[error("Incompatible chord alterations: {alterations:?}")]
+    #[serde(rename = "incompatible-alterations")]
     IncompatibleAlterations { alterations: Vec<String> },
 
     #[error("Missing required chord property: {property}")]
+    #[serde(rename = "missing-required-property")]
     MissingRequiredProperty { property: String },
 
     #[error("Scale degree out of range: {degree}. Must be 1-7")]
+    #[serde(rename = "scale-degree-out-of-range")]
     ScaleDegreeOutOfRange { degree: u8 },
 
     #[error("Invalid alteration: {alteration}. Must be one of [b5, #5, b9, #9, #11, b13]")]
+    #[serde(rename = "invalid-alteration")]
     InvalidAlteration { alteration: String },
 
     #[error("Invalid suspension: {suspension}. Must be 2 or 4")]
+    #[serde(rename = "invalid-suspension")]
     InvalidSuspension { suspension: u8 },
 
     #[error("Calculation overflow in {operation}")]
+    #[serde(rename = "calculation-overflow")]
     CalculationOverflow { operation: String },
+
+    #[error("No chord satisfied the generator constraints after {attempts} attempts")]
+    #[serde(rename = "generation-failed")]
+    GenerationFailed { attempts: usize },
+
+    #[error("No chord in the key spells pitch classes {pitch_classes:?}")]
+    #[serde(rename = "no-matching-chord")]
+    NoMatchingChord { pitch_classes: Vec<u8> },
+
+    #[error("Unsupported voicing: {reason}")]
+    #[serde(rename = "unsupported-voicing")]
+    UnsupportedVoicing { reason: String },
+
+    #[error("Invalid counterpoint lines: {reason}")]
+    #[serde(rename = "invalid-counterpoint")]
+    InvalidCounterpoint { reason: String },
+
+    #[error("Invalid tuning: {reason}")]
+    #[serde(rename = "invalid-tuning")]
+    InvalidTuning { reason: String },
+
+    #[error("Invalid key: {key}")]
+    #[serde(rename = "invalid-key")]
+    InvalidKey { key: String },
 }
 
 /// Result type for chord theory operations
 pub type ChordTheoryResult<T> = Result<T, ChordTheoryError>;
 
 impl ChordTheoryError {
+    /// Stable machine-readable code for this error, one of the `ERROR_*`
+    /// constants in `composer_config`
+    pub fn code(&self) -> &'static str {
+        match self {
+            ChordTheoryError::InvalidChordRoot { .. } => composer_config::ERROR_INVALID_CHORD_ROOT,
+            ChordTheoryError::InvalidChordType { .. } => composer_config::ERROR_INVALID_CHORD_TYPE,
+            ChordTheoryError::InvalidInversion { .. } => composer_config::ERROR_INVALID_INVERSION,
+            ChordTheoryError::InvalidApplied { .. } => composer_config::ERROR_INVALID_APPLIED,
+            ChordTheoryError::InvalidScaleFingerprint { .. } => {
+                composer_config::ERROR_INVALID_SCALE
+            },
+            ChordTheoryError::IncompatibleAlterations { .. } => {
+                composer_config::ERROR_INCOMPATIBLE_ALTERATIONS
+            },
+            ChordTheoryError::MissingRequiredProperty { .. } => {
+                composer_config::ERROR_MISSING_PROPERTY
+            },
+            ChordTheoryError::ScaleDegreeOutOfRange { .. } => {
+                composer_config::ERROR_SCALE_DEGREE_OUT_OF_RANGE
+            },
+            ChordTheoryError::InvalidAlteration { .. } => composer_config::ERROR_INVALID_ALTERATION,
+            ChordTheoryError::InvalidSuspension { .. } => composer_config::ERROR_INVALID_SUSPENSION,
+            ChordTheoryError::CalculationOverflow { .. } => {
+                composer_config::ERROR_CALCULATION_OVERFLOW
+            },
+            ChordTheoryError::GenerationFailed { .. } => composer_config::ERROR_GENERATION_FAILED,
+            ChordTheoryError::NoMatchingChord { .. } => composer_config::ERROR_NO_MATCHING_CHORD,
+            ChordTheoryError::UnsupportedVoicing { .. } => {
+                composer_config::ERROR_UNSUPPORTED_VOICING
+            },
+            ChordTheoryError::InvalidCounterpoint { .. } => {
+                composer_config::ERROR_INVALID_COUNTERPOINT
+            },
+            ChordTheoryError::InvalidTuning { .. } => composer_config::ERROR_INVALID_TUNING,
+            ChordTheoryError::InvalidKey { .. } => composer_config::ERROR_INVALID_KEY,
+        }
+    }
+
     /// Check if this error is recoverable
     pub fn is_recoverable(&self) -> bool {
         match self {
@@ -52,12 +130,18 @@ impl ChordTheoryError {
             | ChordTheoryError::InvalidApplied { .. }
             | ChordTheoryError::ScaleDegreeOutOfRange { .. }
             | ChordTheoryError::InvalidAlteration { .. }
-            | ChordTheoryError::InvalidSuspension { .. } => false,
+            | ChordTheoryError::InvalidSuspension { .. }
+            | ChordTheoryError::InvalidCounterpoint { .. }
+            | ChordTheoryError::InvalidTuning { .. }
+            | ChordTheoryError::InvalidKey { .. } => false,
 
             ChordTheoryError::InvalidScaleFingerprint { .. }
             | ChordTheoryError::IncompatibleAlterations { .. }
             | ChordTheoryError::MissingRequiredProperty { .. }
-            | ChordTheoryError::CalculationOverflow { .. } => true,
+            | ChordTheoryError::CalculationOverflow { .. }
+            | ChordTheoryError::GenerationFailed { .. }
+            | ChordTheoryError::NoMatchingChord { .. }
+            | ChordTheoryError::UnsupportedVoicing { .. } => true,
         }
     }
 }
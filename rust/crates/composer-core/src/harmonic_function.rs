@@ -0,0 +1,365 @@
+//! Harmonic function labeling
+//!
+//! Assigns each chord of a progression a tonic, subdominant, predominant or
+//! dominant function in the key it belongs to. Applied chords take their
+//! function in the key of their target, so V/V is a dominant of the dominant
+//! rather than a chromatic supertonic. The function sequence is then checked
+//! against the T → S/PD → D → T cycle of functional syntax.
+
+use crate::chord::Chord;
+use crate::error::ChordTheoryResult;
+use crate::roman::{QualityType, RomanCase};
+use crate::scale::ScaleFingerprint;
+use crate::theory::determine_chord_quality;
+use serde::{Deserialize, Serialize};
+
+/// Functional category of a chord
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HarmonicFunction {
+    /// Point of rest: I, and its substitutes iii and vi
+    Tonic,
+    /// IV, iv and ♭VII, which may return straight to the tonic (plagal motion)
+    Subdominant,
+    /// ii, ii° and ♭VI, which lead to the dominant
+    Predominant,
+    /// V and vii°, which resolve to the tonic
+    Dominant,
+}
+
+/// Function of one chord and the key it functions in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FunctionLabel {
+    /// Functional category
+    pub function: HarmonicFunction,
+
+    /// Scale degree of the key the function belongs to: 1 for the home key,
+    /// the applied target for applied chords
+    pub target: u8,
+}
+
+/// Kinds of functional-syntax violation found by [`analyze_harmonic_function`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FunctionViolationKind {
+    /// Dominant moving back to a subdominant or predominant chord (V–IV)
+    Retrogression,
+    /// Predominant moving to a tonic chord without passing the dominant (ii–I)
+    PredominantToTonic,
+    /// Applied chord not followed by its target
+    UnresolvedApplied,
+}
+
+impl FunctionViolationKind {
+    /// Get the string name of this violation kind
+    pub fn name(&self) -> &str {
+        match self {
+            FunctionViolationKind::Retrogression => "retrogression",
+            FunctionViolationKind::PredominantToTonic => "predominant_to_tonic",
+            FunctionViolationKind::UnresolvedApplied => "unresolved_applied",
+        }
+    }
+}
+
+/// A functional-syntax violation between two consecutive chords
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FunctionViolation {
+    /// Kind of violation
+    pub kind: FunctionViolationKind,
+
+    /// Index of the chord the violating motion arrives at
+    pub index: usize,
+}
+
+/// Result of [`analyze_harmonic_function`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HarmonicFunctionAnalysis {
+    /// Function of each chord in progression order, `None` for rests
+    pub functions: Vec<Option<FunctionLabel>>,
+
+    /// Violations in progression order
+    pub violations: Vec<FunctionViolation>,
+}
+
+/// Harmonic function of a single chord in a key, `None` for rests.
+///
+/// Diatonic and borrowed chords are classified by scale degree, with quality
+/// deciding the ambiguous degrees: a major VI or ♭VI is predominant while vi
+/// is a tonic substitute, and a diminished vii° is dominant while ♭VII is
+/// subdominant. Applied chords are classified as the same degree in the major
+/// key of their target.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_core::{harmonic_function, Chord, HarmonicFunction, ScaleFingerprint};
+///
+/// let major = ScaleFingerprint::major_scale();
+/// let label = harmonic_function(&Chord::seventh(5)?, &major)?.unwrap();
+/// assert_eq!(label.function, HarmonicFunction::Dominant);
+/// assert_eq!(label.target, 1);
+///
+/// // V/V is a dominant in the key of V
+/// let applied = harmonic_function(&Chord::seventh(5)?.with_applied(5)?, &major)?.unwrap();
+/// assert_eq!(applied.function, HarmonicFunction::Dominant);
+/// assert_eq!(applied.target, 5);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn harmonic_function(
+    chord: &Chord,
+    scale_fingerprint: &ScaleFingerprint,
+) -> ChordTheoryResult<Option<FunctionLabel>> {
+    chord.validate()?;
+    if chord.is_rest {
+        return Ok(None);
+    }
+
+    if chord.applied != 0 {
+        let function = match chord.root {
+            1 | 3 | 6 => HarmonicFunction::Tonic,
+            4 => HarmonicFunction::Subdominant,
+            2 => HarmonicFunction::Predominant,
+            _ => HarmonicFunction::Dominant,
+        };
+        return Ok(Some(FunctionLabel {
+            function,
+            target: chord.applied,
+        }));
+    }
+
+    let quality = determine_chord_quality(chord, scale_fingerprint)?;
+    let function = match chord.root {
+        1 | 3 => HarmonicFunction::Tonic,
+        2 => HarmonicFunction::Predominant,
+        4 => HarmonicFunction::Subdominant,
+        5 => HarmonicFunction::Dominant,
+        6 if quality.roman_case() == RomanCase::Upper => HarmonicFunction::Predominant,
+        6 => HarmonicFunction::Tonic,
+        _ if matches!(
+            quality,
+            QualityType::Diminished | QualityType::HalfDiminished
+        ) =>
+        {
+            HarmonicFunction::Dominant
+        },
+        _ => HarmonicFunction::Subdominant,
+    };
+    Ok(Some(FunctionLabel {
+        function,
+        target: 1,
+    }))
+}
+
+/// Scale degree of the home key a chord's root sounds on
+///
+/// V/ii sounds on the sixth degree, V/V on the second.
+fn sounding_degree(chord: &Chord) -> u8 {
+    if chord.applied == 0 {
+        chord.root
+    } else {
+        (chord.applied + chord.root - 2) % 7 + 1
+    }
+}
+
+/// Violation in the motion from one sounding chord to the next, if any
+fn function_violation(
+    previous: (&Chord, FunctionLabel),
+    next: (&Chord, FunctionLabel),
+) -> Option<FunctionViolationKind> {
+    let (previous_chord, previous_label) = previous;
+    let (next_chord, next_label) = next;
+
+    if previous_chord.applied != 0 {
+        return (sounding_degree(next_chord) != previous_chord.applied)
+            .then_some(FunctionViolationKind::UnresolvedApplied);
+    }
+    if next_chord.applied != 0 || previous_label.target != next_label.target {
+        return None;
+    }
+
+    match (previous_label.function, next_label.function) {
+        (
+            HarmonicFunction::Dominant,
+            HarmonicFunction::Subdominant | HarmonicFunction::Predominant,
+        ) => Some(FunctionViolationKind::Retrogression),
+        (HarmonicFunction::Predominant, HarmonicFunction::Tonic) => {
+            Some(FunctionViolationKind::PredominantToTonic)
+        },
+        _ => None,
+    }
+}
+
+/// Label the harmonic function of every chord and check functional syntax.
+///
+/// Each chord is labeled with [`harmonic_function`]. Consecutive sounding
+/// chords (rests are skipped) are then checked for:
+///
+/// - **Unresolved applied chords**: an applied chord must be followed by a
+///   chord sounding on its target, which may itself be applied (V/ii → V/V)
+/// - **Retrogression**: a dominant moving back to a subdominant or
+///   predominant chord in the same key
+/// - **Predominant to tonic**: a predominant chord skipping the dominant;
+///   subdominant chords may return to the tonic directly
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_core::{analyze_harmonic_function, Chord, FunctionViolationKind, ScaleFingerprint};
+///
+/// let major = ScaleFingerprint::major_scale();
+/// let progression = vec![Chord::triad(1)?, Chord::triad(5)?, Chord::triad(4)?, Chord::triad(1)?];
+/// let analysis = analyze_harmonic_function(&progression, &major)?;
+///
+/// assert_eq!(analysis.functions.len(), 4);
+/// assert_eq!(analysis.violations.len(), 1);
+/// assert_eq!(analysis.violations[0].kind, FunctionViolationKind::Retrogression);
+/// assert_eq!(analysis.violations[0].index, 2);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`harmonic_function`] - Function of a single chord
+/// - [`get_relative_chord_graphic`](crate::get_relative_chord_graphic) - Roman numeral rendering
+pub fn analyze_harmonic_function(
+    progression: &[Chord],
+    scale_fingerprint: &ScaleFingerprint,
+) -> ChordTheoryResult<HarmonicFunctionAnalysis> {
+    let functions = progression
+        .iter()
+        .map(|chord| harmonic_function(chord, scale_fingerprint))
+        .collect::<ChordTheoryResult<Vec<_>>>()?;
+
+    let sounding: Vec<_> = progression
+        .iter()
+        .zip(&functions)
+        .enumerate()
+        .filter_map(|(index, (chord, label))| label.map(|label| (index, chord, label)))
+        .collect();
+
+    let violations = sounding
+        .windows(2)
+        .filter_map(|pair| {
+            let (_, previous_chord, previous_label) = pair[0];
+            let (index, next_chord, next_label) = pair[1];
+            function_violation((previous_chord, previous_label), (next_chord, next_label))
+                .map(|kind| FunctionViolation { kind, index })
+        })
+        .collect();
+
+    Ok(HarmonicFunctionAnalysis {
+        functions,
+        violations,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chord::BorrowedScale;
+    use crate::scale::ScaleType;
+
+    fn function_of(chord: &Chord, scale: &ScaleFingerprint) -> HarmonicFunction {
+        harmonic_function(chord, scale).unwrap().unwrap().function
+    }
+
+    #[test]
+    fn test_diatonic_functions() {
+        let major = ScaleFingerprint::major_scale();
+        let expected = [
+            HarmonicFunction::Tonic,
+            HarmonicFunction::Predominant,
+            HarmonicFunction::Tonic,
+            HarmonicFunction::Subdominant,
+            HarmonicFunction::Dominant,
+            HarmonicFunction::Tonic,
+            HarmonicFunction::Dominant,
+        ];
+        for (root, function) in (1..=7).zip(expected) {
+            assert_eq!(function_of(&Chord::triad(root).unwrap(), &major), function);
+        }
+
+        // In minor, VI is predominant and ♭VII subdominant
+        let minor = ScaleFingerprint::minor_scale();
+        let six = function_of(&Chord::triad(6).unwrap(), &minor);
+        assert_eq!(six, HarmonicFunction::Predominant);
+        assert_eq!(
+            function_of(&Chord::triad(7).unwrap(), &minor),
+            HarmonicFunction::Subdominant
+        );
+
+        // Borrowed ♭VI in major
+        let flat_six = Chord::triad(6)
+            .unwrap()
+            .with_borrowed_scale(BorrowedScale::ScaleType(ScaleType::Minor))
+            .unwrap();
+        assert_eq!(function_of(&flat_six, &major), six);
+        assert_eq!(harmonic_function(&Chord::rest(), &major).unwrap(), None);
+    }
+
+    #[test]
+    fn test_applied_resolution() {
+        let major = ScaleFingerprint::major_scale();
+        let secondary = Chord::seventh(5).unwrap().with_applied(2).unwrap();
+        let label = harmonic_function(&secondary, &major).unwrap().unwrap();
+        assert_eq!(label.target, 2);
+
+        // V/ii → ii resolves; V/ii → V/V resolves through a chain of dominants
+        let resolved = analyze_harmonic_function(
+            &[
+                secondary.clone(),
+                Chord::seventh(2).unwrap(),
+                Chord::seventh(5).unwrap(),
+                Chord::triad(1).unwrap(),
+            ],
+            &major,
+        )
+        .unwrap();
+        assert!(resolved.violations.is_empty());
+        let chain = analyze_harmonic_function(
+            &[
+                secondary.clone(),
+                Chord::seventh(5).unwrap().with_applied(5).unwrap(),
+                Chord::seventh(5).unwrap(),
+            ],
+            &major,
+        )
+        .unwrap();
+        assert!(chain.violations.is_empty());
+
+        // V/ii → IV, with a rest in between, is left unresolved
+        let unresolved = analyze_harmonic_function(
+            &[secondary, Chord::rest(), Chord::triad(4).unwrap()],
+            &major,
+        )
+        .unwrap();
+        assert_eq!(
+            unresolved.violations,
+            [FunctionViolation {
+                kind: FunctionViolationKind::UnresolvedApplied,
+                index: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_syntax_violations() {
+        let major = ScaleFingerprint::major_scale();
+        let kinds = |roots: &[u8]| -> Vec<FunctionViolationKind> {
+            let progression: Vec<Chord> = roots.iter().map(|&r| Chord::triad(r).unwrap()).collect();
+            analyze_harmonic_function(&progression, &major)
+                .unwrap()
+                .violations
+                .into_iter()
+                .map(|v| v.kind)
+                .collect()
+        };
+
+        assert!(kinds(&[1, 4, 5, 1]).is_empty());
+        assert!(kinds(&[1, 6, 2, 5, 1]).is_empty());
+        // Plagal IV → I is fine, ii → I is not
+        assert!(kinds(&[1, 4, 1]).is_empty());
+        assert_eq!(kinds(&[2, 1]), [FunctionViolationKind::PredominantToTonic]);
+        assert_eq!(kinds(&[5, 2]), [FunctionViolationKind::Retrogression]);
+        // Deceptive resolution is not a violation
+        assert!(kinds(&[5, 6]).is_empty());
+    }
+}
@@ -0,0 +1,607 @@
+//! Keys, modes and key signatures
+//!
+//! A [`Key`] is a spelled tonic and a [`Mode`]. Keys parse from and display as
+//! the names used throughout Composer: `C`, `F#m`, `Bb minor`, `D dorian`.
+//! The tonic keeps its spelling, so F# major and Gb major are different keys
+//! that are enharmonically equivalent; [`Key::from_chromatic`] and
+//! [`Key::transpose`] pick whichever spelling has the simpler signature.
+
+use crate::error::{ChordTheoryError, ChordTheoryResult};
+use crate::scale::ScaleFingerprint;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Note letter, C to B
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum NoteLetter {
+    C,
+    D,
+    E,
+    F,
+    G,
+    A,
+    B,
+}
+
+impl NoteLetter {
+    const ALL: [NoteLetter; 7] = [
+        NoteLetter::C,
+        NoteLetter::D,
+        NoteLetter::E,
+        NoteLetter::F,
+        NoteLetter::G,
+        NoteLetter::A,
+        NoteLetter::B,
+    ];
+
+    /// Letters in order of the line of fifths, starting on F
+    const FIFTHS: [NoteLetter; 7] = [
+        NoteLetter::F,
+        NoteLetter::C,
+        NoteLetter::G,
+        NoteLetter::D,
+        NoteLetter::A,
+        NoteLetter::E,
+        NoteLetter::B,
+    ];
+
+    /// Semitones above C of the natural note
+    pub fn semitones(&self) -> u8 {
+        match self {
+            NoteLetter::C => 0,
+            NoteLetter::D => 2,
+            NoteLetter::E => 4,
+            NoteLetter::F => 5,
+            NoteLetter::G => 7,
+            NoteLetter::A => 9,
+            NoteLetter::B => 11,
+        }
+    }
+
+    /// Letter `steps` letters above this one, wrapping from B to C
+    pub fn up(&self, steps: usize) -> NoteLetter {
+        Self::ALL[(*self as usize + steps) % 7]
+    }
+
+    /// Parse a letter, in either case
+    pub fn from_char(letter: char) -> Option<NoteLetter> {
+        Self::ALL
+            .into_iter()
+            .find(|candidate| format!("{:?}", candidate).starts_with(letter.to_ascii_uppercase()))
+    }
+}
+
+impl fmt::Display for NoteLetter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Spelled pitch class: a letter and its alteration in semitones
+///
+/// Parses and displays with `#` per sharp and `b` per flat, such as `F##` or
+/// `Bbb`; `x`, `♯` and `♭` are also accepted when parsing. Enharmonic spellings
+/// such as F# and Gb are different values with the same [`chromatic`] value.
+///
+/// [`chromatic`]: PitchClass::chromatic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PitchClass {
+    pub letter: NoteLetter,
+    /// Semitones above the natural note; negative for flats
+    pub alter: i8,
+}
+
+impl PitchClass {
+    /// Create a pitch class
+    pub fn new(letter: NoteLetter, alter: i8) -> Self {
+        Self { letter, alter }
+    }
+
+    /// The spelling of a chromatic pitch class (0-11) on a given letter
+    pub fn on_letter(letter: NoteLetter, chromatic: u8) -> Self {
+        let alter = (chromatic as i32 - letter.semitones() as i32 + 6).rem_euclid(12) - 6;
+        Self::new(letter, alter as i8)
+    }
+
+    /// Chromatic pitch class (0-11)
+    pub fn chromatic(&self) -> u8 {
+        (self.letter.semitones() as i32 + self.alter as i32).rem_euclid(12) as u8
+    }
+
+    /// Position on the line of fifths: C is 0, G 1, F -1, F# 6, Gb -6
+    pub fn fifths(&self) -> i32 {
+        let natural = Self::natural_fifths(self.letter);
+        natural + 7 * self.alter as i32
+    }
+
+    /// The pitch class at a position on the line of fifths
+    pub fn from_fifths(fifths: i32) -> Self {
+        Self::new(
+            NoteLetter::FIFTHS[(fifths + 1).rem_euclid(7) as usize],
+            (fifths + 1).div_euclid(7) as i8,
+        )
+    }
+
+    /// Check if two spellings name the same chromatic pitch class
+    pub fn is_enharmonic_to(&self, other: &PitchClass) -> bool {
+        self.chromatic() == other.chromatic()
+    }
+
+    fn natural_fifths(letter: NoteLetter) -> i32 {
+        NoteLetter::FIFTHS
+            .iter()
+            .position(|&candidate| candidate == letter)
+            .unwrap_or(1) as i32
+            - 1
+    }
+
+    /// Parse a pitch class at the start of `text`, returning the rest
+    fn parse_prefix(text: &str) -> Option<(Self, &str)> {
+        let mut chars = text.char_indices();
+        let letter = NoteLetter::from_char(chars.next()?.1)?;
+        let mut alter: i8 = 0;
+        let mut end = text.len();
+        for (index, c) in chars {
+            match c {
+                '#' | '♯' => alter += 1,
+                'x' => alter += 2,
+                'b' | '♭' => alter -= 1,
+                _ => {
+                    end = index;
+                    break;
+                },
+            }
+            if alter.abs() > 2 {
+                return None;
+            }
+        }
+        Some((Self::new(letter, alter), &text[end..]))
+    }
+}
+
+impl FromStr for PitchClass {
+    type Err = ChordTheoryError;
+
+    fn from_str(s: &str) -> ChordTheoryResult<Self> {
+        match Self::parse_prefix(s.trim()) {
+            Some((pitch_class, "")) => Ok(pitch_class),
+            _ => Err(ChordTheoryError::InvalidKey { key: s.to_string() }),
+        }
+    }
+}
+
+impl fmt::Display for PitchClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let accidental = if self.alter >= 0 { "#" } else { "b" };
+        write!(
+            f,
+            "{}{}",
+            self.letter,
+            accidental.repeat(self.alter.unsigned_abs() as usize)
+        )
+    }
+}
+
+/// Mode of a key
+///
+/// `Minor` is the natural minor (aeolian) scale. Keys in harmonic minor share
+/// the natural minor key signature, as in written music.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Mode {
+    #[default]
+    #[serde(alias = "Ionian")]
+    Major,
+    #[serde(alias = "Aeolian")]
+    Minor,
+    HarmonicMinor,
+    Dorian,
+    Phrygian,
+    Lydian,
+    Mixolydian,
+    Locrian,
+}
+
+impl Mode {
+    const ALL: [Mode; 8] = [
+        Mode::Major,
+        Mode::Minor,
+        Mode::HarmonicMinor,
+        Mode::Dorian,
+        Mode::Phrygian,
+        Mode::Lydian,
+        Mode::Mixolydian,
+        Mode::Locrian,
+    ];
+
+    /// Scale of the mode on a C tonic
+    pub fn scale(&self) -> ScaleFingerprint {
+        let major = ScaleFingerprint::major_scale();
+        match self {
+            Mode::Major => major,
+            Mode::Minor => ScaleFingerprint::minor_scale(),
+            Mode::HarmonicMinor => ScaleFingerprint::harmonic_minor_scale(),
+            Mode::Dorian => major.rotate(1),
+            Mode::Phrygian => major.rotate(2),
+            Mode::Lydian => major.rotate(3),
+            Mode::Mixolydian => major.rotate(4),
+            Mode::Locrian => major.rotate(6),
+        }
+    }
+
+    /// Mode whose scale is `scale_fingerprint`, if any
+    pub fn from_scale(scale_fingerprint: &ScaleFingerprint) -> Option<Mode> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.scale() == *scale_fingerprint)
+    }
+
+    /// Check if the mode has a minor third
+    pub fn is_minor(&self) -> bool {
+        matches!(
+            self,
+            Mode::Minor | Mode::HarmonicMinor | Mode::Dorian | Mode::Phrygian | Mode::Locrian
+        )
+    }
+
+    /// Lowercase name, such as `major` or `harmonic minor`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::Major => "major",
+            Mode::Minor => "minor",
+            Mode::HarmonicMinor => "harmonic minor",
+            Mode::Dorian => "dorian",
+            Mode::Phrygian => "phrygian",
+            Mode::Lydian => "lydian",
+            Mode::Mixolydian => "mixolydian",
+            Mode::Locrian => "locrian",
+        }
+    }
+
+    /// Parse a mode name or abbreviation: `m`, `min` and `aeolian` are minor,
+    /// `M`, `maj` and `ionian` major; underscores and hyphens may stand for
+    /// spaces, and case is ignored except in `m` and `M`
+    pub fn from_name(name: &str) -> Option<Mode> {
+        match name {
+            "M" => return Some(Mode::Major),
+            "m" => return Some(Mode::Minor),
+            _ => {},
+        }
+        let name = name.trim().to_lowercase().replace(['_', '-'], " ");
+        match name.as_str() {
+            "maj" | "ionian" => Some(Mode::Major),
+            "min" | "aeolian" => Some(Mode::Minor),
+            _ => Self::ALL.into_iter().find(|mode| mode.name() == name),
+        }
+    }
+
+    /// Fifths from the tonic to the major key sharing the key signature
+    fn signature_offset(&self) -> i32 {
+        match self {
+            Mode::Lydian => 1,
+            Mode::Major => 0,
+            Mode::Mixolydian => -1,
+            Mode::Dorian => -2,
+            Mode::Minor | Mode::HarmonicMinor => -3,
+            Mode::Phrygian => -4,
+            Mode::Locrian => -5,
+        }
+    }
+}
+
+impl FromStr for Mode {
+    type Err = ChordTheoryError;
+
+    /// Parse a mode name as [`Mode::from_name`] does
+    fn from_str(s: &str) -> ChordTheoryResult<Self> {
+        Self::from_name(s).ok_or_else(|| ChordTheoryError::InvalidKey { key: s.to_string() })
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Key signature as a number of sharps (positive) or flats (negative)
+///
+/// The count is MusicXML's `<fifths>`. Keys beyond seven sharps or flats, such
+/// as G# major, have double sharps or flats in their signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct KeySignature {
+    pub fifths: i32,
+}
+
+impl KeySignature {
+    /// Number of sharps, counting double sharps twice
+    pub fn sharps(&self) -> u32 {
+        self.fifths.max(0) as u32
+    }
+
+    /// Number of flats, counting double flats twice
+    pub fn flats(&self) -> u32 {
+        (-self.fifths).max(0) as u32
+    }
+
+    /// Altered notes in the order they are written: F#, C#, G#, ... for
+    /// sharps and Bb, Eb, Ab, ... for flats
+    pub fn accidentals(&self) -> Vec<PitchClass> {
+        if self.fifths >= 0 {
+            (0..self.fifths)
+                .map(|index| PitchClass::from_fifths(index + 6))
+                .collect()
+        } else {
+            (0..-self.fifths)
+                .map(|index| PitchClass::from_fifths(-index - 2))
+                .collect()
+        }
+    }
+}
+
+impl fmt::Display for KeySignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (count, accidental) = match self.fifths {
+            0 => return write!(f, "no sharps or flats"),
+            fifths if fifths > 0 => (fifths, "sharp"),
+            fifths => (-fifths, "flat"),
+        };
+        let plural = if count == 1 { "" } else { "s" };
+        write!(f, "{} {}{}", count, accidental, plural)
+    }
+}
+
+/// Key: a spelled tonic and a mode
+///
+/// Serializes as its name, such as `"F#m"`.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_core::{Key, Mode};
+///
+/// let key: Key = "F# minor".parse()?;
+/// assert_eq!(key.mode, Mode::Minor);
+/// assert_eq!(key.to_string(), "F#m");
+/// assert_eq!(key.signature().sharps(), 3);
+///
+/// // Enharmonic keys sound the same but are spelled differently
+/// let g_flat: Key = "Gbm".parse()?;
+/// assert!(key.is_enharmonic_to(&g_flat));
+/// assert_ne!(key, g_flat);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Key {
+    pub tonic: PitchClass,
+    pub mode: Mode,
+}
+
+impl Key {
+    /// Create a key
+    pub fn new(tonic: PitchClass, mode: Mode) -> Self {
+        Self { tonic, mode }
+    }
+
+    /// Major key on a tonic
+    pub fn major(tonic: PitchClass) -> Self {
+        Self::new(tonic, Mode::Major)
+    }
+
+    /// Natural minor key on a tonic
+    pub fn minor(tonic: PitchClass) -> Self {
+        Self::new(tonic, Mode::Minor)
+    }
+
+    /// Key on a chromatic tonic (0-11), spelled with the fewest accidentals
+    /// in its signature; sharps win a tie, so 6 in major is F#, not Gb
+    pub fn from_chromatic(tonic: u8, mode: Mode) -> Self {
+        // Every pitch class is 7 fifths above the one a semitone below it
+        let fifths = (tonic as i32 * 7).rem_euclid(12);
+        let best = [fifths - 12, fifths]
+            .into_iter()
+            .min_by_key(|&candidate| {
+                let signature = candidate + mode.signature_offset();
+                (signature.abs(), signature < 0)
+            })
+            .unwrap_or(fifths);
+        Self::new(PitchClass::from_fifths(best), mode)
+    }
+
+    /// Chromatic pitch class of the tonic (0-11)
+    pub fn tonic_chromatic(&self) -> u8 {
+        self.tonic.chromatic()
+    }
+
+    /// Scale of the key, relative to its tonic
+    pub fn scale(&self) -> ScaleFingerprint {
+        self.mode.scale()
+    }
+
+    /// Key signature written for the key
+    pub fn signature(&self) -> KeySignature {
+        KeySignature {
+            fifths: self.tonic.fifths() + self.mode.signature_offset(),
+        }
+    }
+
+    /// Check if two keys sound the same: same mode, enharmonic tonics
+    pub fn is_enharmonic_to(&self, other: &Key) -> bool {
+        self.mode == other.mode && self.tonic.is_enharmonic_to(&other.tonic)
+    }
+
+    /// The enharmonically equivalent key spelled on the other side of the
+    /// circle of fifths: F# major for Gb major and the reverse
+    pub fn enharmonic(&self) -> Key {
+        let shift = if self.signature().fifths > 0 { -12 } else { 12 };
+        Self::new(
+            PitchClass::from_fifths(self.tonic.fifths() + shift),
+            self.mode,
+        )
+    }
+
+    /// The key `semitones` higher, spelled as in [`Key::from_chromatic`]
+    pub fn transpose(&self, semitones: i32) -> Key {
+        let tonic = (self.tonic_chromatic() as i32 + semitones).rem_euclid(12) as u8;
+        Self::from_chromatic(tonic, self.mode)
+    }
+}
+
+impl FromStr for Key {
+    type Err = ChordTheoryError;
+
+    /// Parse a tonic with an optional mode: `C`, `F#m`, `Bb minor`,
+    /// `Ebmin`, `A harmonic_minor`, `D dorian`
+    fn from_str(s: &str) -> ChordTheoryResult<Self> {
+        let invalid = || ChordTheoryError::InvalidKey { key: s.to_string() };
+        let (tonic, rest) = PitchClass::parse_prefix(s.trim()).ok_or_else(invalid)?;
+        let mode = match rest.trim_start() {
+            "" => Mode::Major,
+            name => Mode::from_name(name).ok_or_else(invalid)?,
+        };
+        Ok(Self::new(tonic, mode))
+    }
+}
+
+impl TryFrom<String> for Key {
+    type Error = ChordTheoryError;
+
+    fn try_from(name: String) -> ChordTheoryResult<Self> {
+        name.parse()
+    }
+}
+
+impl From<Key> for String {
+    fn from(key: Key) -> String {
+        key.to_string()
+    }
+}
+
+impl fmt::Display for Key {
+    /// `C` for major, `Cm` for minor, `C dorian` for other modes
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.mode {
+            Mode::Major => write!(f, "{}", self.tonic),
+            Mode::Minor => write!(f, "{}m", self.tonic),
+            mode => write!(f, "{} {}", self.tonic, mode),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: &str) -> Key {
+        name.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        for (name, display) in [
+            ("C", "C"),
+            ("c", "C"),
+            ("Am", "Am"),
+            ("Bbm", "Bbm"),
+            ("Ebmin", "Ebm"),
+            ("F# minor", "F#m"),
+            ("Gb major", "Gb"),
+            ("Fx", "F##"),
+            ("A harmonic_minor", "A harmonic minor"),
+            ("D Dorian", "D dorian"),
+            ("E aeolian", "Em"),
+            ("B♭", "Bb"),
+        ] {
+            assert_eq!(key(name).to_string(), display, "{}", name);
+            assert_eq!(key(display), key(name));
+        }
+        for invalid in ["", "H", "C###", "Cmajor7", "m"] {
+            assert!(matches!(
+                invalid.parse::<Key>(),
+                Err(ChordTheoryError::InvalidKey { .. })
+            ));
+        }
+        assert_eq!("Cb".parse::<PitchClass>().unwrap().chromatic(), 11);
+        assert!("C m".parse::<PitchClass>().is_err());
+    }
+
+    #[test]
+    fn test_signatures() {
+        for (name, fifths) in [
+            ("C", 0),
+            ("Am", 0),
+            ("A harmonic minor", 0),
+            ("E", 4),
+            ("F#m", 3),
+            ("Bb", -2),
+            ("Ebm", -6),
+            ("D dorian", 0),
+            ("F lydian", 0),
+            ("B locrian", 0),
+            ("G#", 8),
+        ] {
+            assert_eq!(key(name).signature().fifths, fifths, "{}", name);
+        }
+        let accidentals = |name: &str| -> Vec<String> {
+            key(name)
+                .signature()
+                .accidentals()
+                .iter()
+                .map(|pitch_class| pitch_class.to_string())
+                .collect()
+        };
+        assert_eq!(accidentals("D"), ["F#", "C#"]);
+        assert_eq!(accidentals("Ab"), ["Bb", "Eb", "Ab", "Db"]);
+        assert_eq!(accidentals("G#")[7], "F##");
+        assert_eq!(key("Eb").signature().to_string(), "3 flats");
+        assert_eq!(key("G").signature().to_string(), "1 sharp");
+    }
+
+    #[test]
+    fn test_enharmonics_and_transposition() {
+        assert_eq!(key("F#").enharmonic(), key("Gb"));
+        assert_eq!(key("Gb").enharmonic(), key("F#"));
+        assert!(key("C#m").is_enharmonic_to(&key("Dbm")));
+        assert!(!key("C#m").is_enharmonic_to(&key("C#")));
+
+        let majors: Vec<String> = (0..12)
+            .map(|tonic| Key::from_chromatic(tonic, Mode::Major).to_string())
+            .collect();
+        assert_eq!(
+            majors,
+            ["C", "Db", "D", "Eb", "E", "F", "F#", "G", "Ab", "A", "Bb", "B"]
+        );
+        let minors: Vec<String> = (0..12)
+            .map(|tonic| Key::from_chromatic(tonic, Mode::Minor).to_string())
+            .collect();
+        assert_eq!(
+            minors,
+            ["Cm", "C#m", "Dm", "D#m", "Em", "Fm", "F#m", "Gm", "G#m", "Am", "Bbm", "Bm"]
+        );
+        assert_eq!(key("Bbm").transpose(3), key("C#m"));
+        assert_eq!(key("D dorian").transpose(-2), key("C dorian"));
+        assert_eq!(Mode::Dorian.scale(), ScaleFingerprint::dorian_scale());
+        assert!(Mode::Locrian.is_minor() && !Mode::Lydian.is_minor());
+    }
+
+    #[test]
+    fn test_names_and_modes() {
+        // Serialized form
+        assert_eq!(String::from(key("F# minor")), "F#m");
+        assert_eq!(Key::try_from("F#m".to_string()).unwrap(), key("F# minor"));
+        assert!(Key::try_from("Hmin".to_string()).is_err());
+
+        assert_eq!(
+            "harmonic_minor".parse::<Mode>().unwrap(),
+            Mode::HarmonicMinor
+        );
+        assert!("blues".parse::<Mode>().is_err());
+        assert!("".parse::<Mode>().is_err());
+        assert_eq!(
+            Mode::from_scale(&ScaleFingerprint::dorian_scale()),
+            Some(Mode::Dorian)
+        );
+        assert_eq!(Mode::from_scale(&ScaleFingerprint::chromatic_scale()), None);
+    }
+}
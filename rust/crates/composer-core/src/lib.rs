@@ -2,17 +2,41 @@
 //!
 //! This crate provides the fundamental data structures and algorithms for chord theory,
 //! Roman numeral notation, and musical transformation as defined in the specification.
+//! Scales and chords in other equal temperaments are available with the
+//! `microtonal` feature.
 
 pub mod chord;
 pub mod constants;
+pub mod counterpoint;
+pub mod equivalence;
 pub mod error;
+pub mod generator;
+pub mod harmonic_function;
+pub mod key;
 pub mod roman;
 pub mod scale;
+pub mod spelling;
+pub mod substitutions;
 pub mod theory;
+pub mod transform;
+#[cfg(feature = "microtonal")]
+pub mod tuning;
+pub mod voicing;
 
 pub use chord::*;
 pub use constants::*;
+pub use counterpoint::*;
+pub use equivalence::*;
 pub use error::*;
+pub use generator::*;
+pub use harmonic_function::*;
+pub use key::*;
 pub use roman::*;
 pub use scale::*;
+pub use spelling::*;
+pub use substitutions::*;
 pub use theory::*;
+pub use transform::*;
+#[cfg(feature = "microtonal")]
+pub use tuning::*;
+pub use voicing::*;
@@ -36,6 +36,49 @@ pub struct RomanNumeralGraphic {
 
     /// Omit tone notations
     pub omits: SmallVec<[String; 2]>,
+
+    /// Accidental before the numeral for chromatic roots (♭, ♯)
+    #[serde(default)]
+    pub accidental: String,
+
+    /// Where the chord comes from relative to the home scale
+    #[serde(default)]
+    pub origin: ChordOrigin,
+}
+
+/// Harmonic origin of a chord relative to the home scale
+///
+/// Diatonic chords have no borrowed scale, no applied target and a root offset
+/// of zero. A borrowed ♭VI in major has `borrowed_from: Some("minor")` and a
+/// root offset of -1; V7/V has `applied_to: Some(5)`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChordOrigin {
+    /// Scale the chord is borrowed from
+    pub borrowed_from: Option<String>,
+
+    /// Scale degree the chord is applied to (tonicizes)
+    pub applied_to: Option<u8>,
+
+    /// Semitones between the chord's root and the home scale's degree
+    pub root_offset: i8,
+}
+
+impl ChordOrigin {
+    /// Check if the chord belongs to the home scale without modification
+    pub fn is_diatonic(&self) -> bool {
+        self.borrowed_from.is_none() && self.applied_to.is_none() && self.root_offset == 0
+    }
+
+    /// Check if the chord's root lies outside the home scale
+    pub fn is_chromatic_root(&self) -> bool {
+        self.root_offset != 0
+    }
+
+    /// Accidental symbol for the root offset (`♭`, `♭♭`, `♯`, or empty)
+    pub fn accidental(&self) -> String {
+        let symbol = if self.root_offset < 0 { "♭" } else { "♯" };
+        symbol.repeat(self.root_offset.unsigned_abs() as usize)
+    }
 }
 
 impl Default for RomanNumeralGraphic {
@@ -50,6 +93,8 @@ impl Default for RomanNumeralGraphic {
             suspensions: SmallVec::new(),
             adds: SmallVec::new(),
             omits: SmallVec::new(),
+            accidental: String::new(),
+            origin: ChordOrigin::default(),
         }
     }
 }
@@ -87,6 +132,18 @@ impl RomanNumeralGraphic {
         self
     }
 
+    /// Set the accidental shown before the numeral
+    pub fn with_accidental(mut self, accidental: String) -> Self {
+        self.accidental = accidental;
+        self
+    }
+
+    /// Set the harmonic origin
+    pub fn with_origin(mut self, origin: ChordOrigin) -> Self {
+        self.origin = origin;
+        self
+    }
+
     /// Add an alteration notation
     pub fn with_alteration(mut self, alteration: String) -> Self {
         if !self.alterations.contains(&alteration) {
@@ -177,20 +234,29 @@ impl RomanNumeralGraphic {
     }
 
     /// Build the complete chord symbol string
+    ///
+    /// Renders accidental, numeral, quality and figures in conventional order
+    /// (`♭VI`, `viiø7`, `V65/V`). The figured bass replaces the extension digits
+    /// of the symbol when present, and the `m` quality is left to the numeral's
+    /// case.
     pub fn full_symbol(&self) -> String {
         let mut result = String::new();
 
-        // Start with the basic symbol
-        result.push_str(&self.symbol);
+        // Start with the accidental and the numeral itself
+        result.push_str(&self.accidental);
+        let numeral = self.base_numeral();
+        result.push_str(&numeral);
 
         // Add quality
-        if !self.quality.is_empty() {
+        if !self.quality.is_empty() && self.quality != QualityType::Minor.symbol() {
             result.push_str(&self.quality);
         }
 
-        // Add figured bass
+        // Add figured bass, falling back to the symbol's own extension
         if !self.figured_bass.is_empty() {
             result.push_str(&self.figured_bass);
+        } else {
+            result.push_str(&self.symbol[numeral.len()..]);
         }
 
         // Add alterations
@@ -234,6 +300,8 @@ impl RomanNumeralGraphic {
             quality: self.quality.clone(),
             applied: self.applied.clone(),
             borrowed: self.borrowed.clone(),
+            accidental: self.accidental.clone(),
+            origin: self.origin.clone(),
             ..Default::default()
         }
     }
@@ -427,6 +495,48 @@ mod tests {
         assert!(display.contains("°"));
     }
 
+    #[test]
+    fn test_full_symbol_ordering() {
+        let half_diminished = RomanNumeralGraphic::new("vii7".to_string())
+            .with_quality("ø".to_string())
+            .with_figured_bass("7".to_string());
+        assert_eq!(half_diminished.full_symbol(), "viiø7");
+
+        let minor = RomanNumeralGraphic::new("ii7".to_string())
+            .with_quality("m".to_string())
+            .with_figured_bass("65".to_string());
+        assert_eq!(minor.full_symbol(), "ii65");
+
+        let borrowed = RomanNumeralGraphic::new("VI".to_string())
+            .with_accidental("♭".to_string())
+            .with_borrowed("minor".to_string());
+        assert_eq!(borrowed.full_symbol(), "♭VI[minor]");
+        assert_eq!(borrowed.base_numeral(), "VI");
+        assert!(borrowed.is_major());
+    }
+
+    #[test]
+    fn test_chord_origin() {
+        assert!(ChordOrigin::default().is_diatonic());
+
+        let flat_six = ChordOrigin {
+            borrowed_from: Some("minor".to_string()),
+            applied_to: None,
+            root_offset: -1,
+        };
+        assert!(!flat_six.is_diatonic());
+        assert!(flat_six.is_chromatic_root());
+        assert_eq!(flat_six.accidental(), "♭");
+        assert_eq!(
+            ChordOrigin {
+                root_offset: 2,
+                ..Default::default()
+            }
+            .accidental(),
+            "♯♯"
+        );
+    }
+
     #[test]
     fn test_roman_case() {
         assert_eq!(RomanCase::Upper.apply("i"), "I");
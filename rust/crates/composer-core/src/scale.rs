@@ -1,6 +1,8 @@
 //! Scale fingerprint and scale-related data structures
 
+use crate::chord::Chord;
 use crate::error::{ChordTheoryError, ChordTheoryResult};
+use crate::transform::chord_pitch_classes;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -120,6 +122,82 @@ impl ScaleFingerprint {
             None
         }
     }
+
+    /// Mode starting on the note `n` steps up the scale, re-rooted on 0
+    ///
+    /// `n` wraps around the notes of the scale, so `rotate(0)` and
+    /// `rotate(note_count())` both give the scale itself.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_core::ScaleFingerprint;
+    ///
+    /// let major = ScaleFingerprint::major_scale();
+    /// assert_eq!(major.rotate(1), ScaleFingerprint::dorian_scale());
+    /// assert_eq!(major.rotate(4), ScaleFingerprint::mixolydian_scale());
+    /// assert_eq!(major.rotate(5), ScaleFingerprint::minor_scale());
+    /// ```
+    pub fn rotate(&self, n: usize) -> Self {
+        let notes = self.chromatic_notes();
+        let root = notes[n % notes.len()] as usize;
+        let mut semitones = self.0;
+        semitones.rotate_left(root);
+        ScaleFingerprint(semitones)
+    }
+
+    /// Notes in both scales, or `None` if they share no notes
+    pub fn intersection(&self, other: &ScaleFingerprint) -> Option<Self> {
+        self.combine(other, |a, b| a & b)
+    }
+
+    /// Notes in this scale but not in `other`, or `None` if there are none
+    pub fn difference(&self, other: &ScaleFingerprint) -> Option<Self> {
+        self.combine(other, |a, b| a & !b & 1)
+    }
+
+    /// Check if every tone of a chord, read in this scale, is in the scale
+    ///
+    /// Borrowed, applied and altered chords usually bring in notes from
+    /// outside the scale. Chords needing degrees the scale lacks, like
+    /// sevenths in a pentatonic scale, are not contained.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_core::{Chord, ScaleFingerprint};
+    ///
+    /// let major = ScaleFingerprint::major_scale();
+    /// assert!(major.contains_chord(&Chord::seventh(5)?));
+    /// assert!(!major.contains_chord(&Chord::triad(5)?.with_applied(5)?));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn contains_chord(&self, chord: &Chord) -> bool {
+        chord_pitch_classes(chord, self)
+            .map(|tones| tones.iter().all(|&tone| self.contains_chromatic(tone)))
+            .unwrap_or(false)
+    }
+
+    /// Number of semitones that are in one scale but not the other
+    ///
+    /// 0 for equal scales; major and natural minor are 6 apart, as each has
+    /// three notes the other lacks. Scales are
+    /// compared on the same tonic, so modes of one scale are usually far apart.
+    pub fn distance(&self, other: &ScaleFingerprint) -> usize {
+        self.0
+            .iter()
+            .zip(other.0.iter())
+            .filter(|(a, b)| a != b)
+            .count()
+    }
+
+    fn combine(&self, other: &ScaleFingerprint, op: impl Fn(u8, u8) -> u8) -> Option<Self> {
+        let mut semitones = [0u8; 12];
+        for (i, semitone) in semitones.iter_mut().enumerate() {
+            *semitone = op(self.0[i], other.0[i]);
+        }
+        Self::new(semitones).ok()
+    }
 }
 
 impl Default for ScaleFingerprint {
@@ -325,6 +403,42 @@ mod tests {
         assert_eq!(ScaleType::HarmonicMinor.name(), "harmonic_minor");
     }
 
+    #[test]
+    fn test_scale_arithmetic() {
+        let major = ScaleFingerprint::major_scale();
+        let minor = ScaleFingerprint::minor_scale();
+        assert_eq!(major.rotate(7), major);
+        assert_eq!(major.rotate(12), major.rotate(5));
+        assert_eq!(
+            ScaleFingerprint::harmonic_minor_scale()
+                .rotate(4)
+                .semitones(),
+            &[1, 1, 0, 0, 1, 1, 0, 1, 1, 0, 1, 0]
+        );
+
+        assert_eq!(
+            major.intersection(&minor).unwrap().chromatic_notes(),
+            vec![0, 2, 5, 7]
+        );
+        assert_eq!(
+            major.difference(&minor).unwrap().chromatic_notes(),
+            vec![4, 9, 11]
+        );
+        assert_eq!(major.difference(&ScaleFingerprint::chromatic_scale()), None);
+        let whole_tone = ScaleFingerprint::new([1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0]).unwrap();
+        let shifted = ScaleFingerprint::new([0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1]).unwrap();
+        assert_eq!(whole_tone.intersection(&shifted), None);
+
+        assert_eq!(major.distance(&major), 0);
+        assert_eq!(major.distance(&minor), 6);
+        assert_eq!(whole_tone.distance(&shifted), 12);
+
+        assert!(minor.contains_chord(&Chord::triad(5).unwrap()));
+        assert!(!major.contains_chord(&Chord::triad(1).unwrap().with_alteration("b5").unwrap()));
+        let pentatonic = ScaleFingerprint::new([1, 0, 1, 0, 1, 0, 0, 1, 0, 1, 0, 0]).unwrap();
+        assert!(!pentatonic.contains_chord(&Chord::seventh(1).unwrap()));
+    }
+
     #[test]
     fn test_display() {
         let major = ScaleFingerprint::major_scale();
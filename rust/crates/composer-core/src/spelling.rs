@@ -0,0 +1,247 @@
+//! Pitch spelling
+//!
+//! Names the notes of scale degrees and chords in a [`Key`], with letters
+//! chosen by scale degree and interval so every third of a chord is a letter
+//! third: F# minor is spelled F#, A, C# and never Gb, A, Db, and the leading
+//! tone of G# minor is F## rather than G. Spelled notes carry an octave and
+//! MIDI number, and map one to one onto MusicXML `<step>`, `<alter>` and
+//! `<octave>`.
+
+use crate::chord::Chord;
+use crate::error::{ChordTheoryError, ChordTheoryResult};
+use crate::key::{Key, PitchClass};
+use crate::transform::{incomplete_scale, named_chord_tones};
+use crate::voicing::{piano_voicing, PianoVoicingStyle};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Spelled note in a given octave, with octave 4 starting at middle C
+///
+/// The octave goes with the letter, so B#3 and C4 are both MIDI note 60.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SpelledNote {
+    pub name: PitchClass,
+    pub octave: i8,
+}
+
+impl SpelledNote {
+    /// Spelling of a MIDI note with a given pitch name
+    fn from_midi(midi: u8, name: PitchClass) -> Self {
+        let natural = midi as i32 - name.alter as i32 - name.letter.semitones() as i32;
+        Self {
+            name,
+            octave: (natural.div_euclid(12) - 1) as i8,
+        }
+    }
+
+    /// MIDI note number
+    pub fn midi(&self) -> i32 {
+        (self.octave as i32 + 1) * 12 + self.name.letter.semitones() as i32 + self.name.alter as i32
+    }
+}
+
+impl fmt::Display for SpelledNote {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.name, self.octave)
+    }
+}
+
+/// Spell a scale degree (1-7) of a key.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_core::{spell_scale_degree, Key};
+///
+/// let g_sharp_minor: Key = "G# harmonic minor".parse()?;
+/// assert_eq!(spell_scale_degree(7, &g_sharp_minor)?.to_string(), "F##");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn spell_scale_degree(degree: u8, key: &Key) -> ChordTheoryResult<PitchClass> {
+    if !(1..=7).contains(&degree) {
+        return Err(ChordTheoryError::ScaleDegreeOutOfRange { degree });
+    }
+    let semitones = key
+        .scale()
+        .scale_degree_to_chromatic(degree)
+        .ok_or_else(incomplete_scale)?;
+    Ok(PitchClass::on_letter(
+        key.tonic.letter.up(degree as usize - 1),
+        (key.tonic_chromatic() + semitones) % 12,
+    ))
+}
+
+/// Spell the seven degrees of a key, tonic first
+pub fn spell_scale(key: &Key) -> ChordTheoryResult<Vec<PitchClass>> {
+    (1..=7)
+        .map(|degree| spell_scale_degree(degree, key))
+        .collect()
+}
+
+/// Spell the tones of a chord in a key.
+///
+/// Tones come root first, as [`chord_pitch_classes`] gives them. The root
+/// takes the letter of its scale degree (counted from the target degree for
+/// applied chords) and every other tone the letter of its interval above the
+/// root, so borrowed and altered tones keep their function: the flat sixth of
+/// C major is Ab, and the raised fifth of an augmented chord on C is G#.
+///
+/// [`chord_pitch_classes`]: crate::chord_pitch_classes
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_core::{spell_chord, Chord, Key};
+///
+/// let c_major: Key = "C".parse()?;
+/// let names = |chord: &Chord| -> Result<Vec<String>, Box<dyn std::error::Error>> {
+///     Ok(spell_chord(chord, &c_major)?.iter().map(|name| name.to_string()).collect())
+/// };
+/// assert_eq!(names(&Chord::seventh(5)?)?, ["G", "B", "D", "F"]);
+/// // V/ii is A, C#, E
+/// assert_eq!(names(&Chord::triad(5)?.with_applied(2)?)?, ["A", "C#", "E"]);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn spell_chord(chord: &Chord, key: &Key) -> ChordTheoryResult<Vec<PitchClass>> {
+    let tonic = key.tonic_chromatic();
+    let root_letter = key
+        .tonic
+        .letter
+        .up(chord.applied.saturating_sub(1) as usize + chord.root.saturating_sub(1) as usize);
+    Ok(named_chord_tones(chord, &key.scale())?
+        .into_iter()
+        .map(|(interval, pitch_class)| {
+            PitchClass::on_letter(
+                root_letter.up(interval as usize - 1),
+                (tonic + pitch_class) % 12,
+            )
+        })
+        .collect())
+}
+
+/// Spell a chord as concrete notes of a piano voicing.
+///
+/// The notes and their MIDI numbers are those of [`piano_voicing`] in the
+/// key's tonic, lowest first, each named as in [`spell_chord`].
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_core::{spell_chord_notes, Chord, Key, PianoVoicingStyle};
+///
+/// let d_major: Key = "D".parse()?;
+/// let notes = spell_chord_notes(&Chord::triad(3)?, &d_major, PianoVoicingStyle::Close)?;
+/// let names: Vec<String> = notes.iter().map(|note| note.to_string()).collect();
+/// assert_eq!(names, ["F#4", "A4", "C#5"]);
+/// assert_eq!(notes[0].midi(), 66);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn spell_chord_notes(
+    chord: &Chord,
+    key: &Key,
+    style: PianoVoicingStyle,
+) -> ChordTheoryResult<Vec<SpelledNote>> {
+    let names = spell_chord(chord, key)?;
+    let midi_notes = piano_voicing(chord, &key.scale(), key.tonic_chromatic(), style)?;
+    midi_notes
+        .into_iter()
+        .map(|midi| {
+            names
+                .iter()
+                .find(|name| name.chromatic() == midi % 12)
+                .map(|&name| SpelledNote::from_midi(midi, name))
+                .ok_or_else(|| ChordTheoryError::NoMatchingChord {
+                    pitch_classes: vec![midi % 12],
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chord::BorrowedScale;
+    use crate::key::{Mode, NoteLetter};
+    use crate::scale::ScaleType;
+
+    fn names(spelled: &[PitchClass]) -> Vec<String> {
+        spelled.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_spell_scales() {
+        let f_sharp_major = Key::major(PitchClass::new(NoteLetter::F, 1));
+        assert_eq!(
+            names(&spell_scale(&f_sharp_major).unwrap()),
+            ["F#", "G#", "A#", "B", "C#", "D#", "E#"]
+        );
+        let g_flat_major = Key::major(PitchClass::new(NoteLetter::G, -1));
+        assert_eq!(
+            names(&spell_scale(&g_flat_major).unwrap()),
+            ["Gb", "Ab", "Bb", "Cb", "Db", "Eb", "F"]
+        );
+        let a_sharp_minor = Key::new(PitchClass::new(NoteLetter::A, 1), Mode::HarmonicMinor);
+        assert_eq!(
+            names(&spell_scale(&a_sharp_minor).unwrap()),
+            ["A#", "B#", "C#", "D#", "E#", "F#", "G##"]
+        );
+
+        assert!(spell_scale_degree(0, &f_sharp_major).is_err());
+    }
+
+    #[test]
+    fn test_spell_chords() {
+        let c_major = Key::major(PitchClass::new(NoteLetter::C, 0));
+        let borrowed = Chord::triad(6)
+            .unwrap()
+            .with_borrowed_scale(BorrowedScale::ScaleType(ScaleType::Minor))
+            .unwrap();
+        assert_eq!(
+            names(&spell_chord(&borrowed, &c_major).unwrap()),
+            ["Ab", "C", "Eb"]
+        );
+        let altered = Chord::seventh(5).unwrap().with_alteration("#9").unwrap();
+        assert_eq!(
+            names(&spell_chord(&altered, &c_major).unwrap()),
+            ["G", "B", "D", "F", "A#"]
+        );
+        let suspended = Chord::triad(1).unwrap().with_suspension(4).unwrap();
+        assert_eq!(
+            names(&spell_chord(&suspended, &c_major).unwrap()),
+            ["C", "G", "F"]
+        );
+
+        // The leading-tone diminished seventh of C# minor needs a double sharp
+        let c_sharp_minor = Key::new(PitchClass::new(NoteLetter::C, 1), Mode::HarmonicMinor);
+        assert_eq!(
+            names(&spell_chord(&Chord::seventh(7).unwrap(), &c_sharp_minor).unwrap()),
+            ["B#", "D#", "F#", "A"]
+        );
+        assert!(spell_chord(&Chord::rest(), &c_major).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_spell_chord_notes() {
+        let e_flat_major = Key::major(PitchClass::new(NoteLetter::E, -1));
+        let first_inversion = Chord::triad(1).unwrap().with_inversion(1).unwrap();
+        let notes =
+            spell_chord_notes(&first_inversion, &e_flat_major, PianoVoicingStyle::Close).unwrap();
+        let spelled: Vec<String> = notes.iter().map(|note| note.to_string()).collect();
+        assert_eq!(spelled, ["G4", "Bb4", "Eb5"]);
+        assert_eq!(
+            notes.iter().map(SpelledNote::midi).collect::<Vec<_>>(),
+            [67, 70, 75]
+        );
+
+        // The octave follows the letter: B#3 is middle C
+        let c_sharp_minor = Key::new(PitchClass::new(NoteLetter::C, 1), Mode::HarmonicMinor);
+        let notes = spell_chord_notes(
+            &Chord::triad(7).unwrap(),
+            &c_sharp_minor,
+            PianoVoicingStyle::Close,
+        )
+        .unwrap();
+        assert_eq!(notes[0].to_string(), "B#3");
+        assert_eq!(notes[0].midi(), 60);
+    }
+}
@@ -0,0 +1,403 @@
+//! Chord substitutions
+//!
+//! Enumerates the standard reharmonization moves for a chord in a key: replacing
+//! it with a tritone substitute, its relative or its parallel-mode counterpart,
+//! swapping V7 for the backdoor dominant, or approaching it through a secondary
+//! dominant or a diminished passing chord. Every candidate is checked against the
+//! key before it is offered, so a plain triad is never tritone-substituted and a
+//! diminished chord is never tonicized.
+
+use crate::chord::{BorrowedScale, Chord};
+use crate::error::ChordTheoryResult;
+use crate::key::Mode;
+use crate::roman::{QualityType, RomanCase};
+use crate::scale::{ScaleFingerprint, ScaleType};
+use crate::theory::{borrowed_fingerprint, determine_chord_quality, is_valid_tri_sub};
+use serde::{Deserialize, Serialize};
+
+/// Marker stored in [`Chord::substitutions`] for a tritone substitute
+///
+/// The substitute keeps the scale degree of the dominant it replaces (a tritone
+/// substitute of V7 is stored as V7 with this marker and sounds as ♭II7).
+pub const TRITONE_SUBSTITUTION: &str = "tritone";
+
+/// Kinds of substitution produced by [`get_substitutions`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SubstitutionKind {
+    /// Dominant seventh a tritone away, sharing the dominant's guide tones
+    Tritone,
+    /// Relative major or minor, a third away with two common tones
+    Relative,
+    /// Same root borrowed from (or returned to) the parallel mode
+    Parallel,
+    /// ♭VII7 resolving to the tonic in place of V7
+    BackdoorDominant,
+    /// V7 of the chord, played before it
+    SecondaryDominant,
+    /// Leading-tone seventh of the chord, played before it
+    DiminishedPassing,
+}
+
+impl SubstitutionKind {
+    /// Where the substitute goes relative to the original chord
+    pub fn placement(&self) -> SubstitutionPlacement {
+        match self {
+            SubstitutionKind::Tritone
+            | SubstitutionKind::Relative
+            | SubstitutionKind::Parallel
+            | SubstitutionKind::BackdoorDominant => SubstitutionPlacement::Replace,
+            SubstitutionKind::SecondaryDominant | SubstitutionKind::DiminishedPassing => {
+                SubstitutionPlacement::Precede
+            },
+        }
+    }
+
+    /// Get the string name of this substitution kind
+    pub fn name(&self) -> &str {
+        match self {
+            SubstitutionKind::Tritone => "tritone",
+            SubstitutionKind::Relative => "relative",
+            SubstitutionKind::Parallel => "parallel",
+            SubstitutionKind::BackdoorDominant => "backdoor_dominant",
+            SubstitutionKind::SecondaryDominant => "secondary_dominant",
+            SubstitutionKind::DiminishedPassing => "diminished_passing",
+        }
+    }
+}
+
+/// How a substitute is used in a progression
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SubstitutionPlacement {
+    /// The substitute replaces the original chord
+    Replace,
+    /// The substitute is inserted before the original chord, which is kept
+    Precede,
+}
+
+impl SubstitutionPlacement {
+    /// Get the string name of this placement
+    pub fn name(&self) -> &str {
+        match self {
+            SubstitutionPlacement::Replace => "replace",
+            SubstitutionPlacement::Precede => "precede",
+        }
+    }
+}
+
+/// A substitute chord for a chord in a key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Substitution {
+    /// Kind of substitution
+    pub kind: SubstitutionKind,
+
+    /// The substitute chord
+    pub chord: Chord,
+
+    /// Whether the substitute replaces or precedes the original
+    pub placement: SubstitutionPlacement,
+}
+
+impl Substitution {
+    fn new(kind: SubstitutionKind, chord: Chord) -> Self {
+        Self {
+            kind,
+            chord,
+            placement: kind.placement(),
+        }
+    }
+}
+
+/// Enumerates the valid substitutions for a chord in a key.
+///
+/// Candidates are produced in [`SubstitutionKind`] order, each only when it is
+/// valid in `scale_fingerprint`:
+///
+/// - **Tritone**: chords accepted by [`is_valid_tri_sub`], marked with
+///   [`TRITONE_SUBSTITUTION`]
+/// - **Relative**: a diatonic major chord's relative minor a third below, or a
+///   diatonic minor chord's relative major a third above
+/// - **Parallel**: the same root borrowed from the parallel major or minor when
+///   that changes the chord's quality, or the diatonic chord for a chord already
+///   borrowed from the parallel mode
+/// - **Backdoor dominant**: ♭VII7 for the dominant of a major key
+/// - **Secondary dominant** and **diminished passing**: V7 and vii°7 of the chord,
+///   for major and minor chords other than the tonic (the tonic's own leading-tone
+///   chord is borrowed from harmonic minor when the key has none)
+///
+/// Rests have no substitutions. The chord's extension (triad or seventh) is kept
+/// for relative and parallel swaps.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_core::{get_substitutions, Chord, ScaleFingerprint, SubstitutionKind};
+///
+/// let major = ScaleFingerprint::major_scale();
+///
+/// let kinds: Vec<SubstitutionKind> = get_substitutions(&Chord::seventh(5)?, &major)?
+///     .into_iter()
+///     .map(|substitution| substitution.kind)
+///     .collect();
+/// assert!(kinds.contains(&SubstitutionKind::Tritone));
+/// assert!(kinds.contains(&SubstitutionKind::BackdoorDominant));
+///
+/// // IV swaps with its relative minor, ii
+/// let relative = get_substitutions(&Chord::triad(4)?, &major)?
+///     .into_iter()
+///     .find(|substitution| substitution.kind == SubstitutionKind::Relative)
+///     .unwrap();
+/// assert_eq!(relative.chord.root, 2);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`is_valid_tri_sub`] - Tritone substitution eligibility
+/// - [`get_relative_chord_graphic`](crate::get_relative_chord_graphic) - Render substitutes as Roman numerals
+pub fn get_substitutions(
+    chord: &Chord,
+    scale_fingerprint: &ScaleFingerprint,
+) -> ChordTheoryResult<Vec<Substitution>> {
+    chord.validate()?;
+    if chord.is_rest {
+        return Ok(Vec::new());
+    }
+
+    let scale_type = named_scale_type(scale_fingerprint);
+    let quality = determine_chord_quality(chord, scale_fingerprint)?;
+    let diatonic = chord.applied == 0 && chord.borrowed.is_none();
+    let tonicizable = !matches!(
+        quality,
+        QualityType::Diminished | QualityType::HalfDiminished
+    );
+    let mut substitutions = Vec::new();
+
+    if is_valid_tri_sub(chord, Mode::from_scale(scale_fingerprint))
+        && !chord
+            .substitutions
+            .iter()
+            .any(|s| s == TRITONE_SUBSTITUTION)
+    {
+        let mut substitute = chord.clone();
+        substitute
+            .substitutions
+            .push(TRITONE_SUBSTITUTION.to_string());
+        substitutions.push(Substitution::new(SubstitutionKind::Tritone, substitute));
+    }
+
+    if diatonic && tonicizable {
+        let major = quality.roman_case() == RomanCase::Upper;
+        // A third below a major chord, a third above a minor one
+        let root = if major {
+            (chord.root + 4) % 7 + 1
+        } else {
+            (chord.root + 1) % 7 + 1
+        };
+        let relative = Chord::new(root, chord.chord_type)?;
+        let relative_quality = determine_chord_quality(&relative, scale_fingerprint)?;
+        if relative_quality.roman_case() != quality.roman_case()
+            && !matches!(
+                relative_quality,
+                QualityType::Diminished | QualityType::HalfDiminished
+            )
+        {
+            substitutions.push(Substitution::new(SubstitutionKind::Relative, relative));
+        }
+    }
+
+    if chord.applied == 0 {
+        if let Some(parallel) = parallel_scale(scale_type.as_ref()) {
+            let borrowed_from_parallel = chord
+                .borrowed
+                .as_ref()
+                .and_then(|borrowed| borrowed_fingerprint(borrowed, scale_fingerprint))
+                == Some(parallel.fingerprint());
+            let mut substitute = chord.clone();
+            if borrowed_from_parallel {
+                substitute.borrowed = None;
+            } else if chord.borrowed.is_none() {
+                substitute.borrowed = Some(BorrowedScale::ScaleType(parallel));
+            }
+            if substitute.borrowed != chord.borrowed
+                && determine_chord_quality(&substitute, scale_fingerprint)? != quality
+            {
+                substitutions.push(Substitution::new(SubstitutionKind::Parallel, substitute));
+            }
+        }
+    }
+
+    if diatonic
+        && chord.root == 5
+        && chord.chord_type >= 7
+        && quality == QualityType::Major
+        && scale_type == Some(ScaleType::Major)
+    {
+        let backdoor =
+            Chord::seventh(7)?.with_borrowed_scale(BorrowedScale::ScaleType(ScaleType::Minor))?;
+        substitutions.push(Substitution::new(
+            SubstitutionKind::BackdoorDominant,
+            backdoor,
+        ));
+    }
+
+    if diatonic && tonicizable {
+        if chord.root != 1 {
+            substitutions.push(Substitution::new(
+                SubstitutionKind::SecondaryDominant,
+                Chord::seventh(5)?.with_applied(chord.root)?,
+            ));
+            substitutions.push(Substitution::new(
+                SubstitutionKind::DiminishedPassing,
+                Chord::seventh(7)?.with_applied(chord.root)?,
+            ));
+        } else {
+            let mut leading_tone = Chord::seventh(7)?;
+            if determine_chord_quality(&leading_tone, scale_fingerprint)? != QualityType::Diminished
+            {
+                leading_tone = leading_tone
+                    .with_borrowed_scale(BorrowedScale::ScaleType(ScaleType::HarmonicMinor))?;
+            }
+            substitutions.push(Substitution::new(
+                SubstitutionKind::DiminishedPassing,
+                leading_tone,
+            ));
+        }
+    }
+
+    Ok(substitutions)
+}
+
+/// Named scale type whose fingerprint matches, if any
+fn named_scale_type(scale_fingerprint: &ScaleFingerprint) -> Option<ScaleType> {
+    [
+        ScaleType::Major,
+        ScaleType::Minor,
+        ScaleType::HarmonicMinor,
+        ScaleType::Dorian,
+        ScaleType::Mixolydian,
+        ScaleType::Chromatic,
+    ]
+    .into_iter()
+    .find(|scale| scale.fingerprint() == *scale_fingerprint)
+}
+
+/// Parallel mode used for modal interchange
+fn parallel_scale(scale_type: Option<&ScaleType>) -> Option<ScaleType> {
+    match scale_type? {
+        ScaleType::Major => Some(ScaleType::Minor),
+        ScaleType::Minor | ScaleType::HarmonicMinor => Some(ScaleType::Major),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(substitutions: &[Substitution]) -> Vec<SubstitutionKind> {
+        substitutions.iter().map(|s| s.kind).collect()
+    }
+
+    #[test]
+    fn test_dominant_substitutions() {
+        let major = ScaleFingerprint::major_scale();
+        let substitutions = get_substitutions(&Chord::seventh(5).unwrap(), &major).unwrap();
+        assert_eq!(
+            kinds(&substitutions),
+            [
+                SubstitutionKind::Tritone,
+                SubstitutionKind::Relative,
+                SubstitutionKind::Parallel,
+                SubstitutionKind::BackdoorDominant,
+                SubstitutionKind::SecondaryDominant,
+                SubstitutionKind::DiminishedPassing,
+            ]
+        );
+
+        let tritone = &substitutions[0].chord;
+        assert_eq!((tritone.root, tritone.chord_type), (5, 7));
+        assert_eq!(tritone.substitutions.as_slice(), [TRITONE_SUBSTITUTION]);
+        // A tritone substitute is not substituted again
+        let again = get_substitutions(tritone, &major).unwrap();
+        assert!(!kinds(&again).contains(&SubstitutionKind::Tritone));
+
+        let backdoor = &substitutions[3].chord;
+        assert_eq!(backdoor.root, 7);
+        assert_eq!(
+            backdoor.borrowed,
+            Some(BorrowedScale::ScaleType(ScaleType::Minor))
+        );
+        assert_eq!(substitutions[4].chord.applied, 5);
+        assert_eq!(substitutions[4].placement, SubstitutionPlacement::Precede);
+
+        // No tritone substitution or backdoor for a plain triad
+        let triad = get_substitutions(&Chord::triad(5).unwrap(), &major).unwrap();
+        assert!(!kinds(&triad).contains(&SubstitutionKind::Tritone));
+        assert!(!kinds(&triad).contains(&SubstitutionKind::BackdoorDominant));
+    }
+
+    #[test]
+    fn test_relative_and_parallel() {
+        let major = ScaleFingerprint::major_scale();
+        let relative = |root: u8| {
+            get_substitutions(&Chord::triad(root).unwrap(), &major)
+                .unwrap()
+                .into_iter()
+                .find(|s| s.kind == SubstitutionKind::Relative)
+                .map(|s| s.chord.root)
+        };
+        assert_eq!(relative(1), Some(6));
+        assert_eq!(relative(6), Some(1));
+        assert_eq!(relative(5), Some(3));
+        assert_eq!(relative(2), Some(4));
+        assert_eq!(relative(7), None);
+
+        // iv from the parallel minor, and back again
+        let iv = get_substitutions(&Chord::triad(4).unwrap(), &major)
+            .unwrap()
+            .into_iter()
+            .find(|s| s.kind == SubstitutionKind::Parallel)
+            .unwrap()
+            .chord;
+        assert_eq!(
+            iv.borrowed,
+            Some(BorrowedScale::ScaleType(ScaleType::Minor))
+        );
+        let back = get_substitutions(&iv, &major)
+            .unwrap()
+            .into_iter()
+            .find(|s| s.kind == SubstitutionKind::Parallel)
+            .unwrap();
+        assert_eq!(back.chord, Chord::triad(4).unwrap());
+    }
+
+    #[test]
+    fn test_tonic_and_diminished_chords() {
+        let major = ScaleFingerprint::major_scale();
+        let tonic = get_substitutions(&Chord::triad(1).unwrap(), &major).unwrap();
+        assert!(!kinds(&tonic).contains(&SubstitutionKind::SecondaryDominant));
+        let passing = tonic
+            .iter()
+            .find(|s| s.kind == SubstitutionKind::DiminishedPassing)
+            .unwrap();
+        assert_eq!(
+            passing.chord.borrowed,
+            Some(BorrowedScale::ScaleType(ScaleType::HarmonicMinor))
+        );
+
+        let harmonic_minor = ScaleFingerprint::harmonic_minor_scale();
+        let tonic = get_substitutions(&Chord::triad(1).unwrap(), &harmonic_minor).unwrap();
+        let passing = tonic
+            .iter()
+            .find(|s| s.kind == SubstitutionKind::DiminishedPassing)
+            .unwrap();
+        assert_eq!(passing.chord.borrowed, None);
+
+        // vii° is never tonicized
+        let leading = get_substitutions(&Chord::triad(7).unwrap(), &major).unwrap();
+        assert!(!kinds(&leading).contains(&SubstitutionKind::SecondaryDominant));
+        assert!(get_substitutions(&Chord::rest(), &major)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -3,11 +3,13 @@
 use crate::chord::{BorrowedScale, Chord};
 use crate::constants::*;
 use crate::error::{ChordTheoryError, ChordTheoryResult};
-use crate::roman::{QualityType, RomanCase, RomanNumeralGraphic};
-use crate::scale::ScaleFingerprint;
+use crate::key::Mode;
+use crate::roman::{ChordOrigin, QualityType, RomanCase, RomanNumeralGraphic};
+use crate::scale::{ScaleFingerprint, ScaleType};
+use serde::{Deserialize, Serialize};
 
 /// Scale degrees with accidentals
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct ScaleDegreeResult {
     /// Scale degree numbers (1-7)
     pub sd_numbers: Vec<u8>,
@@ -311,6 +313,32 @@ pub fn get_relative_scale_degrees(chord: &Chord) -> ChordTheoryResult<ScaleDegre
 ///
 /// Creates a comprehensive RomanNumeralGraphic with all visual elements
 /// including case, figured bass, quality symbols, and harmonic context.
+/// Chord quality is read from the scale the chord is drawn from, so a
+/// borrowed chord is spelled from its source scale (`♭VI` from the parallel
+/// minor), an applied chord from the key of its target (`V7/V`, `viiø7/ii`),
+/// and a diatonic seventh on the leading tone as half-diminished (`viiø7`).
+/// The [`ChordOrigin`] of the result records the borrowed scale, applied
+/// target and root accidental.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_core::{get_relative_chord_graphic, BorrowedScale, Chord, ScaleFingerprint};
+///
+/// let major = ScaleFingerprint::major_scale();
+///
+/// let flat_six = Chord::new(6, 5)?.with_borrowed_scale(BorrowedScale::Named("minor".into()))?;
+/// let graphic = get_relative_chord_graphic(&flat_six, &major)?;
+/// assert_eq!(graphic.full_symbol(), "♭VI[minor]");
+/// assert_eq!(graphic.origin.root_offset, -1);
+///
+/// let secondary = Chord::new(5, 7)?.with_applied(5)?;
+/// assert_eq!(get_relative_chord_graphic(&secondary, &major)?.full_symbol(), "V7/V");
+///
+/// let leading_tone = Chord::new(7, 7)?;
+/// assert_eq!(get_relative_chord_graphic(&leading_tone, &major)?.full_symbol(), "viiø7");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
 pub fn get_relative_chord_graphic(
     chord: &Chord,
     scale_fingerprint: &ScaleFingerprint,
@@ -335,16 +363,23 @@ pub fn get_relative_chord_graphic(
     // Generate figured bass notation
     let figured_bass = get_figured_bass_notation(chord)?;
 
+    let origin = chord_origin(chord, scale_fingerprint);
+
     // Build the graphic
     let mut graphic = RomanNumeralGraphic::new(symbol)
         .with_figured_bass(figured_bass)
-        .with_quality(quality_type.symbol().to_string());
+        .with_quality(quality_type.symbol().to_string())
+        .with_accidental(origin.accidental());
 
-    // Add applied chord notation
+    // Add applied chord notation, cased by the target's quality in the home scale
     if chord.applied != 0 {
+        let target_case = match stacked_intervals(scale_fingerprint, chord.applied) {
+            Some((third, fifth, _)) => quality_from_intervals(third, fifth, None).roman_case(),
+            None => get_scale_degree_quality(chord.applied, scale_fingerprint)?.roman_case(),
+        };
         graphic = graphic.with_applied(format!(
             "/{}",
-            get_roman_numeral_for_degree(chord.applied, RomanCase::Upper)?
+            get_roman_numeral_for_degree(chord.applied, target_case)?
         ));
     }
 
@@ -352,6 +387,7 @@ pub fn get_relative_chord_graphic(
     if let Some(borrowed) = &chord.borrowed {
         graphic = graphic.with_borrowed(borrowed.scale_name());
     }
+    graphic = graphic.with_origin(origin);
 
     // Add alteration symbols
     for alteration in &chord.alterations {
@@ -378,8 +414,9 @@ pub fn get_relative_chord_graphic(
 /// Calculate chord complexity score (0-10 scale)
 ///
 /// Assesses harmonic complexity based on chord type, inversions, alterations,
-/// applied chords, borrowed elements, and other factors.
-pub fn get_chord_complexity(chord: &Chord, _scale_name: &str) -> ChordTheoryResult<f64> {
+/// applied chords, borrowed elements, and other factors. The score is currently
+/// the same in every mode.
+pub fn get_chord_complexity(chord: &Chord, _mode: Mode) -> ChordTheoryResult<f64> {
     if chord.is_rest {
         return Ok(0.0);
     }
@@ -422,15 +459,16 @@ pub fn get_chord_complexity(chord: &Chord, _scale_name: &str) -> ChordTheoryResu
 /// Validate tritone substitution eligibility
 ///
 /// Checks if a chord qualifies for tritone substitution based on
-/// harmonic function and scale context.
-pub fn is_valid_tri_sub(chord: &Chord, scale_name: &str) -> bool {
+/// harmonic function and the mode of the key; `None` for scales that are not
+/// a mode, where only applied and borrowed dominants qualify.
+pub fn is_valid_tri_sub(chord: &Chord, mode: Option<Mode>) -> bool {
     // Check if chord is applied V7
     if chord.applied != 0 && chord.chord_type == 7 {
         return true;
     }
 
     // Check if chord is V7 in major scale
-    if chord.root == 5 && chord.chord_type == 7 && scale_name == "major" {
+    if chord.root == 5 && chord.chord_type == 7 && mode == Some(Mode::Major) {
         return true;
     }
 
@@ -551,7 +589,7 @@ fn apply_suspension(
 }
 
 /// Determine chord quality from chord structure and scale context
-fn determine_chord_quality(
+pub(crate) fn determine_chord_quality(
     chord: &Chord,
     scale_fingerprint: &ScaleFingerprint,
 ) -> ChordTheoryResult<QualityType> {
@@ -568,6 +606,13 @@ fn determine_chord_quality(
         return Ok(QualityType::Augmented);
     }
 
+    // Stack thirds in the scale the chord is drawn from
+    let source = source_scale(chord, scale_fingerprint);
+    if let Some((third, fifth, seventh)) = stacked_intervals(&source, chord.root) {
+        let seventh = (chord.chord_type >= 7).then_some(seventh);
+        return Ok(quality_from_intervals(third, fifth, seventh));
+    }
+
     // For seventh chords, check if it's major seventh
     if chord.chord_type == 7 {
         // Logic to determine if it's major seventh vs dominant seventh
@@ -582,6 +627,114 @@ fn determine_chord_quality(
     Ok(scale_degree_quality)
 }
 
+/// Scale a chord's tones are drawn from
+///
+/// Applied chords use the key of their target: major for a major or augmented
+/// target, harmonic minor otherwise. Borrowed chords use the borrowed scale when
+/// it can be resolved; everything else uses the home scale.
+pub(crate) fn source_scale(chord: &Chord, home: &ScaleFingerprint) -> ScaleFingerprint {
+    if chord.applied != 0 {
+        let target_is_major = match stacked_intervals(home, chord.applied) {
+            Some((third, fifth, _)) => {
+                quality_from_intervals(third, fifth, None).roman_case() == RomanCase::Upper
+            },
+            None => matches!(chord.applied, 1 | 4 | 5),
+        };
+        return if target_is_major {
+            ScaleFingerprint::major_scale()
+        } else {
+            ScaleFingerprint::harmonic_minor_scale()
+        };
+    }
+
+    chord
+        .borrowed
+        .as_ref()
+        .and_then(|borrowed| borrowed_fingerprint(borrowed, home))
+        .unwrap_or(*home)
+}
+
+/// Resolve a borrowed scale to a fingerprint sharing the home tonic
+///
+/// Numeric offsets select a mode of the home scale: `Numeric(5)` on a major
+/// home scale is the mode starting on its sixth degree (aeolian).
+pub(crate) fn borrowed_fingerprint(
+    borrowed: &BorrowedScale,
+    home: &ScaleFingerprint,
+) -> Option<ScaleFingerprint> {
+    match borrowed {
+        BorrowedScale::Named(name) => ScaleType::from_name(name).map(|scale| scale.fingerprint()),
+        BorrowedScale::ScaleType(scale_type) => Some(scale_type.fingerprint()),
+        BorrowedScale::Numeric(offset) => {
+            let notes = home.chromatic_notes();
+            if notes.is_empty() {
+                return None;
+            }
+            let start = notes[offset.rem_euclid(notes.len() as i8) as usize];
+            let mut semitones = [0u8; 12];
+            for note in notes {
+                semitones[((note + 12 - start) % 12) as usize] = 1;
+            }
+            ScaleFingerprint::new(semitones).ok()
+        },
+    }
+}
+
+/// Pitch offset from the tonic of a scale degree, continuing past the octave
+pub(crate) fn degree_pitch(scale: &ScaleFingerprint, degree: u8) -> Option<i32> {
+    let octave = (degree as i32 - 1) / 7;
+    let pitch = scale.scale_degree_to_chromatic((degree - 1) % 7 + 1)?;
+    Some(pitch as i32 + 12 * octave)
+}
+
+/// Third, fifth and seventh above a root, in semitones
+type StackedIntervals = (i32, i32, i32);
+
+/// Intervals above `root` when stacking thirds in a diatonic scale
+fn stacked_intervals(scale: &ScaleFingerprint, root: u8) -> Option<StackedIntervals> {
+    if !scale.is_diatonic() || root == 0 || root > 7 {
+        return None;
+    }
+    let base = degree_pitch(scale, root)?;
+    let interval = |step: u8| degree_pitch(scale, root + step).map(|pitch| pitch - base);
+    Some((interval(2)?, interval(4)?, interval(6)?))
+}
+
+/// Classify a chord from its stacked intervals in semitones
+fn quality_from_intervals(third: i32, fifth: i32, seventh: Option<i32>) -> QualityType {
+    match (third, fifth, seventh) {
+        (3, 6, Some(10)) => QualityType::HalfDiminished,
+        (3, 6, _) => QualityType::Diminished,
+        (4, 8, _) => QualityType::Augmented,
+        (4, 7, Some(11)) => QualityType::MajorSeventh,
+        (3, _, _) => QualityType::Minor,
+        _ => QualityType::Major,
+    }
+}
+
+/// Borrowed scale, applied target and root accidental of a chord
+fn chord_origin(chord: &Chord, home: &ScaleFingerprint) -> ChordOrigin {
+    let root_offset = chord
+        .borrowed
+        .as_ref()
+        .and_then(|borrowed| borrowed_fingerprint(borrowed, home))
+        .filter(|_| chord.applied == 0)
+        .and_then(|source| {
+            let offset = degree_pitch(&source, chord.root)? - degree_pitch(home, chord.root)?;
+            Some(((offset + 6).rem_euclid(12) - 6) as i8)
+        })
+        .unwrap_or(0);
+
+    ChordOrigin {
+        borrowed_from: chord
+            .borrowed
+            .as_ref()
+            .map(|borrowed| borrowed.scale_name()),
+        applied_to: (chord.applied != 0).then_some(chord.applied),
+        root_offset,
+    }
+}
+
 /// Get Roman numeral string for scale degree
 fn get_roman_numeral_for_degree(degree: u8, case: RomanCase) -> ChordTheoryResult<String> {
     if degree == 0 || degree > 7 {
@@ -698,7 +851,7 @@ pub fn chord_letter_to_upper_case(note_string: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::scale::ScaleFingerprint;
+    use crate::scale::{ScaleFingerprint, ScaleType};
     use smallvec::SmallVec;
 
     // Test data from specification
@@ -820,7 +973,7 @@ mod tests {
     #[test]
     fn test_get_chord_complexity_basic() {
         let chord = test_chord_c_major();
-        let complexity = get_chord_complexity(&chord, "major").unwrap();
+        let complexity = get_chord_complexity(&chord, Mode::Major).unwrap();
 
         assert_eq!(complexity, 1.0); // Basic triad
     }
@@ -828,7 +981,7 @@ mod tests {
     #[test]
     fn test_get_chord_complexity_seventh() {
         let chord = test_chord_v7();
-        let complexity = get_chord_complexity(&chord, "major").unwrap();
+        let complexity = get_chord_complexity(&chord, Mode::Major).unwrap();
 
         assert_eq!(complexity, 2.0); // Seventh chord
     }
@@ -839,7 +992,7 @@ mod tests {
         chord.inversion = 1;
         chord.alterations.push("b9".to_string());
         chord.alterations.push("#11".to_string());
-        let complexity = get_chord_complexity(&chord, "major").unwrap();
+        let complexity = get_chord_complexity(&chord, Mode::Major).unwrap();
 
         // 2.0 (seventh) + 0.5 (inversion) + 1.0 (2 alterations) = 3.5
         assert_eq!(complexity, 3.5);
@@ -848,13 +1001,15 @@ mod tests {
     #[test]
     fn test_is_valid_tri_sub() {
         let v7 = test_chord_v7();
-        assert!(is_valid_tri_sub(&v7, "major"));
+        assert!(is_valid_tri_sub(&v7, Some(Mode::Major)));
+        assert!(!is_valid_tri_sub(&v7, Some(Mode::Minor)));
 
         let applied = test_chord_applied_v7_of_v();
-        assert!(is_valid_tri_sub(&applied, "major"));
+        assert!(is_valid_tri_sub(&applied, Some(Mode::Major)));
+        assert!(is_valid_tri_sub(&applied, None));
 
         let triad = test_chord_c_major();
-        assert!(!is_valid_tri_sub(&triad, "major"));
+        assert!(!is_valid_tri_sub(&triad, Some(Mode::Major)));
     }
 
     #[test]
@@ -895,4 +1050,84 @@ mod tests {
         assert_eq!(graphic.symbol, "ii7"); // ii7 is correct for D7 (2nd degree) in major
         assert_eq!(graphic.applied, "/V");
     }
+
+    #[test]
+    fn test_get_relative_chord_graphic_borrowed() {
+        let major = ScaleFingerprint::major_scale();
+        let borrowed = |root, scale: BorrowedScale| Chord {
+            root,
+            borrowed: Some(scale),
+            ..test_chord_c_major()
+        };
+
+        // bVI and iv from the parallel minor
+        let flat_six = get_relative_chord_graphic(
+            &borrowed(6, BorrowedScale::Named("minor".to_string())),
+            &major,
+        )
+        .unwrap();
+        assert_eq!(flat_six.full_symbol(), "♭VI[minor]");
+        assert_eq!(flat_six.origin.borrowed_from.as_deref(), Some("minor"));
+        assert_eq!(flat_six.origin.root_offset, -1);
+
+        let minor_four = get_relative_chord_graphic(
+            &borrowed(4, BorrowedScale::ScaleType(ScaleType::Minor)),
+            &major,
+        )
+        .unwrap();
+        assert_eq!(minor_four.symbol, "iv");
+        assert!(minor_four.accidental.is_empty());
+
+        // Numeric offsets borrow a mode of the home scale: +5 is aeolian
+        let flat_seven =
+            get_relative_chord_graphic(&borrowed(7, BorrowedScale::Numeric(5)), &major).unwrap();
+        assert_eq!(flat_seven.accidental, "♭");
+        assert_eq!(flat_seven.symbol, "VII");
+
+        // Diatonic chords report a diatonic origin
+        let tonic = get_relative_chord_graphic(&test_chord_c_major(), &major).unwrap();
+        assert!(tonic.origin.is_diatonic());
+    }
+
+    #[test]
+    fn test_get_relative_chord_graphic_applied_and_half_diminished() {
+        let major = ScaleFingerprint::major_scale();
+        let applied = |root, chord_type, target| Chord {
+            root,
+            chord_type,
+            applied: target,
+            ..test_chord_c_major()
+        };
+
+        let graphic = get_relative_chord_graphic(&applied(5, 7, 5), &major).unwrap();
+        assert_eq!(graphic.full_symbol(), "V7/V");
+        assert_eq!(graphic.origin.applied_to, Some(5));
+
+        // Minor targets take lower-case slash numerals and harmonic-minor qualities
+        let graphic = get_relative_chord_graphic(&applied(7, 7, 2), &major).unwrap();
+        assert_eq!(graphic.full_symbol(), "vii°7/ii");
+        let graphic = get_relative_chord_graphic(&applied(7, 7, 5), &major).unwrap();
+        assert_eq!(graphic.full_symbol(), "viiø7/V");
+
+        let leading_tone = Chord {
+            root: 7,
+            chord_type: 7,
+            inversion: 1,
+            ..test_chord_c_major()
+        };
+        let graphic = get_relative_chord_graphic(&leading_tone, &major).unwrap();
+        assert_eq!(graphic.quality, "ø");
+        assert_eq!(graphic.full_symbol(), "viiø65");
+
+        // Qualities follow the home scale: III is major in natural minor
+        let mediant = Chord {
+            root: 3,
+            ..test_chord_c_major()
+        };
+        let minor = ScaleFingerprint::minor_scale();
+        assert_eq!(
+            get_relative_chord_graphic(&mediant, &minor).unwrap().symbol,
+            "III"
+        );
+    }
 }
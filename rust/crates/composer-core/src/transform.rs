@@ -0,0 +1,608 @@
+//! Reharmonization transforms
+//!
+//! Algorithmic reharmonization tools working on pitch classes: mirroring chords
+//! about an axis (negative harmony), borrowing chords from a parallel scale
+//! (modal interchange), and applying either, or the substitutions of
+//! [`get_substitutions`], across a whole progression. Results are respelled as
+//! diatonic or borrowed chords of the home key so they stay analyzable.
+
+use crate::chord::{BorrowedScale, Chord};
+use crate::error::{ChordTheoryError, ChordTheoryResult};
+use crate::harmonic_function::{harmonic_function, HarmonicFunction};
+use crate::scale::{ScaleFingerprint, ScaleType};
+use crate::substitutions::{get_substitutions, SubstitutionKind};
+use crate::theory::{degree_pitch, source_scale};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+
+/// Scales tried, after the home scale, when respelling transformed chords
+const SPELLING_SCALES: [ScaleType; 5] = [
+    ScaleType::Minor,
+    ScaleType::Major,
+    ScaleType::HarmonicMinor,
+    ScaleType::Dorian,
+    ScaleType::Mixolydian,
+];
+
+/// Axis of reflection for [`negative_harmony_mirror`]
+///
+/// Stored as the sum of any two pitch classes that mirror onto each other, in
+/// semitones above the tonic. The default is the tonic–dominant axis, which
+/// swaps the tonic and the dominant and turns major into minor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MirrorAxis {
+    sum: u8,
+}
+
+impl MirrorAxis {
+    /// Axis halfway between the tonic and the dominant (between ♭3 and 3)
+    pub fn tonic_dominant() -> Self {
+        Self { sum: 7 }
+    }
+
+    /// Axis halfway between two pitch classes, in semitones above the tonic
+    pub fn between(lower: u8, upper: u8) -> Self {
+        Self {
+            sum: (lower % 12 + upper % 12) % 12,
+        }
+    }
+
+    /// Reflect a pitch class about the axis
+    pub fn reflect(&self, pitch_class: u8) -> u8 {
+        (self.sum + 12 - pitch_class % 12) % 12
+    }
+}
+
+impl Default for MirrorAxis {
+    fn default() -> Self {
+        Self::tonic_dominant()
+    }
+}
+
+/// Chord tone as (interval name, pitch class above the tonic)
+pub(crate) type NamedPitchClass = (u8, u8);
+
+/// Chord tone as (interval name, interval above the root)
+pub(crate) type NamedInterval = (u8, i32);
+
+/// Strategies for [`progression_reharmonize`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ReharmonizationStrategy {
+    /// Mirror every chord about an axis
+    NegativeHarmony(MirrorAxis),
+    /// Swap each chord for the same-root chord of another scale
+    ModalInterchange(BorrowedScale),
+    /// Replace each eligible dominant with its tritone substitute
+    TritoneSubstitution,
+    /// Precede each tonicizable chord with its secondary dominant
+    SecondaryDominants,
+}
+
+/// Pitch classes of a chord, in semitones above the tonic of the key.
+///
+/// Tones are stacked in the scale the chord is drawn from (the borrowed scale,
+/// or the key of an applied chord's target), then adjusted for omissions,
+/// suspensions, added tones and alterations. Tones come root first, without
+/// duplicates; inversions do not change the result.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_core::{chord_pitch_classes, Chord, ScaleFingerprint};
+///
+/// let major = ScaleFingerprint::major_scale();
+/// assert_eq!(chord_pitch_classes(&Chord::seventh(5)?, &major)?, vec![7, 11, 2, 5]);
+///
+/// // V/V brings in the raised fourth
+/// let applied = Chord::triad(5)?.with_applied(5)?;
+/// assert_eq!(chord_pitch_classes(&applied, &major)?, vec![2, 6, 9]);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn chord_pitch_classes(
+    chord: &Chord,
+    scale_fingerprint: &ScaleFingerprint,
+) -> ChordTheoryResult<Vec<u8>> {
+    Ok(named_chord_tones(chord, scale_fingerprint)?
+        .into_iter()
+        .map(|(_, pitch_class)| pitch_class)
+        .collect())
+}
+
+/// Chord tones with their interval names, in the order and with the pitch
+/// classes of [`chord_pitch_classes`]
+pub(crate) fn named_chord_tones(
+    chord: &Chord,
+    scale_fingerprint: &ScaleFingerprint,
+) -> ChordTheoryResult<Vec<NamedPitchClass>> {
+    chord.validate()?;
+    if chord.is_rest {
+        return Ok(Vec::new());
+    }
+
+    let source = source_scale(chord, scale_fingerprint);
+    let offset = match chord.applied {
+        0 => 0,
+        target => degree_pitch(scale_fingerprint, target).ok_or_else(incomplete_scale)?,
+    };
+    let root = degree_pitch(&source, chord.root).ok_or_else(incomplete_scale)?;
+    let intervals = chord_tone_intervals(
+        chord,
+        |steps| degree_pitch(&source, chord.root + steps).map(|pitch| pitch - root),
+        |semitones| semitones,
+    )?;
+
+    let mut tones: Vec<NamedPitchClass> = Vec::with_capacity(intervals.len());
+    for (name, interval) in intervals {
+        let pitch_class = (offset + root + interval).rem_euclid(12) as u8;
+        if !tones.iter().any(|&(_, tone)| tone == pitch_class) {
+            tones.push((name, pitch_class));
+        }
+    }
+    Ok(tones)
+}
+
+/// Error for scales without the degrees a chord is stacked from
+pub(crate) fn incomplete_scale() -> ChordTheoryError {
+    ChordTheoryError::InvalidScaleFingerprint {
+        reason: "scale needs seven degrees to stack chord tones".to_string(),
+    }
+}
+
+/// Chord tones with their interval names, root first
+///
+/// `step` gives the interval up to the scale degree that many steps above the
+/// root, and `semitones` converts the 12-TET intervals of alterations into the
+/// same units, so any tuning can stack chords the same way.
+pub(crate) fn chord_tone_intervals(
+    chord: &Chord,
+    step: impl Fn(u8) -> Option<i32>,
+    semitones: impl Fn(i32) -> i32,
+) -> ChordTheoryResult<Vec<NamedInterval>> {
+    let step = |steps: u8| step(steps).ok_or_else(incomplete_scale);
+
+    let mut tones: Vec<NamedInterval> = vec![(1, 0), (3, step(2)?), (5, step(4)?)];
+    for (name, steps) in [(7, 6), (9, 8), (11, 10), (13, 12)] {
+        if chord.chord_type >= name {
+            tones.push((name, step(steps)?));
+        }
+    }
+
+    tones.retain(|(name, _)| !chord.omits.contains(name));
+    if !chord.suspensions.is_empty() {
+        tones.retain(|(name, _)| *name != 3);
+        for &suspension in &chord.suspensions {
+            tones.push((suspension, step(suspension - 1)?));
+        }
+    }
+    for &add in &chord.adds {
+        if !tones.iter().any(|(name, _)| *name == add) {
+            tones.push((add, step(add - 1)?));
+        }
+    }
+    for alteration in &chord.alterations {
+        let (name, interval) = match alteration.as_str() {
+            "b5" => (5, 6),
+            "#5" => (5, 8),
+            "b9" => (9, 13),
+            "#9" => (9, 15),
+            "#11" => (11, 18),
+            "b13" => (13, 20),
+            _ => continue,
+        };
+        tones.retain(|(tone, _)| *tone != name);
+        tones.push((name, semitones(interval)));
+    }
+    Ok(tones)
+}
+
+/// Sorted pitch-class set of a chord, for order-insensitive comparison
+fn pitch_class_set(
+    chord: &Chord,
+    scale_fingerprint: &ScaleFingerprint,
+) -> ChordTheoryResult<Vec<u8>> {
+    let mut pitch_classes = chord_pitch_classes(chord, scale_fingerprint)?;
+    pitch_classes.sort_unstable();
+    Ok(pitch_classes)
+}
+
+/// Diatonic or borrowed chord of the key with exactly these pitch classes
+fn spell_chord(
+    pitch_classes: &[u8],
+    chord_type: u8,
+    scale_fingerprint: &ScaleFingerprint,
+) -> ChordTheoryResult<Chord> {
+    let mut target = pitch_classes.to_vec();
+    target.sort_unstable();
+    target.dedup();
+
+    let borrowed = std::iter::once(None).chain(
+        SPELLING_SCALES
+            .iter()
+            .filter(|scale| scale.fingerprint() != *scale_fingerprint)
+            .map(|scale| Some(BorrowedScale::ScaleType(scale.clone()))),
+    );
+    for borrowed in borrowed {
+        for root in 1..=7 {
+            let mut candidate = Chord::new(root, chord_type)?;
+            candidate.borrowed = borrowed.clone();
+            if pitch_class_set(&candidate, scale_fingerprint)? == target {
+                return Ok(candidate);
+            }
+        }
+    }
+
+    Err(ChordTheoryError::NoMatchingChord {
+        pitch_classes: pitch_classes.to_vec(),
+    })
+}
+
+/// Reflect a chord about an axis (negative harmony).
+///
+/// Every pitch class is reflected about `axis` and the result respelled as a
+/// root-position chord of the same size, diatonic if possible and otherwise
+/// borrowed from a parallel scale. About the default tonic–dominant axis in
+/// major, I becomes i, V7 becomes iiø7 and IV becomes v, all from the parallel
+/// minor. Rests are returned unchanged; timing is kept.
+///
+/// Returns [`ChordTheoryError::NoMatchingChord`] when no stacked-third chord
+/// spells the reflected pitch classes, as happens for most altered chords.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_core::{negative_harmony_mirror, BorrowedScale, Chord, MirrorAxis, ScaleFingerprint, ScaleType};
+///
+/// let major = ScaleFingerprint::major_scale();
+/// let mirrored = negative_harmony_mirror(&Chord::seventh(5)?, &major, MirrorAxis::default())?;
+/// assert_eq!((mirrored.root, mirrored.chord_type), (2, 7));
+/// assert_eq!(mirrored.borrowed, Some(BorrowedScale::ScaleType(ScaleType::Minor)));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`chord_pitch_classes`] - Pitch classes that are reflected
+/// - [`progression_reharmonize`] - Mirror a whole progression
+pub fn negative_harmony_mirror(
+    chord: &Chord,
+    scale_fingerprint: &ScaleFingerprint,
+    axis: MirrorAxis,
+) -> ChordTheoryResult<Chord> {
+    if chord.is_rest {
+        return Ok(chord.clone());
+    }
+
+    let reflected: Vec<u8> = chord_pitch_classes(chord, scale_fingerprint)?
+        .into_iter()
+        .map(|pitch_class| axis.reflect(pitch_class))
+        .collect();
+    let mut mirrored = spell_chord(&reflected, chord.chord_type, scale_fingerprint)?;
+    mirrored.beat = chord.beat;
+    mirrored.duration = chord.duration;
+    Ok(mirrored)
+}
+
+/// Harmonic function with subdominant and predominant chords counted together
+fn function_family(
+    chord: &Chord,
+    scale_fingerprint: &ScaleFingerprint,
+) -> ChordTheoryResult<Option<HarmonicFunction>> {
+    Ok(
+        harmonic_function(chord, scale_fingerprint)?.map(|label| match label.function {
+            HarmonicFunction::Subdominant => HarmonicFunction::Predominant,
+            function => function,
+        }),
+    )
+}
+
+/// Chords borrowed from `to_scale` that can stand in for a chord.
+///
+/// Candidates are the chords of `to_scale` with the same number of tones that
+/// are not already diatonic to `from_scale`, and that either share the
+/// chord's root or its [`harmonic_function`] (subdominant and predominant
+/// chords counting as one). Same-root options come first,
+/// then options sharing more pitch classes with the chord. Inversion and
+/// timing are copied from the chord.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_core::{modal_interchange_options, BorrowedScale, Chord, ScaleFingerprint, ScaleType};
+///
+/// let major = ScaleFingerprint::major_scale();
+/// let minor = BorrowedScale::ScaleType(ScaleType::Minor);
+/// let options = modal_interchange_options(&Chord::triad(4)?, &major, &minor)?;
+///
+/// // iv first, then ii°, ♭VI and ♭VII, which share IV's function
+/// assert_eq!(options[0].root, 4);
+/// assert!(options.iter().all(|option| option.borrowed == Some(minor.clone())));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn modal_interchange_options(
+    chord: &Chord,
+    from_scale: &ScaleFingerprint,
+    to_scale: &BorrowedScale,
+) -> ChordTheoryResult<Vec<Chord>> {
+    chord.validate()?;
+    if chord.is_rest {
+        return Ok(Vec::new());
+    }
+
+    let original = pitch_class_set(chord, from_scale)?;
+    let function = function_family(chord, from_scale)?;
+    let diatonic = (1..=7)
+        .map(|root| pitch_class_set(&Chord::new(root, chord.chord_type)?, from_scale))
+        .collect::<ChordTheoryResult<Vec<_>>>()?;
+
+    let mut options = Vec::new();
+    for root in 1..=7 {
+        let mut candidate =
+            Chord::new(root, chord.chord_type)?.with_borrowed_scale(to_scale.clone())?;
+        let pitch_classes = pitch_class_set(&candidate, from_scale)?;
+        if pitch_classes == original || diatonic.contains(&pitch_classes) {
+            continue;
+        }
+        let same_root = root == chord.root;
+        let same_function = function_family(&candidate, from_scale)? == function;
+        if !same_root && !same_function {
+            continue;
+        }
+
+        let common_tones = pitch_classes
+            .iter()
+            .filter(|pitch_class| original.contains(pitch_class))
+            .count();
+        candidate.inversion = chord.inversion;
+        candidate.beat = chord.beat;
+        candidate.duration = chord.duration;
+        options.push((!same_root, Reverse(common_tones), candidate));
+    }
+
+    options.sort_by_key(|(different_root, common_tones, candidate)| {
+        (*different_root, *common_tones, candidate.root)
+    });
+    Ok(options.into_iter().map(|(_, _, chord)| chord).collect())
+}
+
+/// Reharmonize a progression with one strategy.
+///
+/// - **Negative harmony**: every chord is mirrored with
+///   [`negative_harmony_mirror`]; chords with no spelled mirror are kept
+/// - **Modal interchange**: every chord with a same-root option from
+///   [`modal_interchange_options`] is swapped for it
+/// - **Tritone substitution**: every chord offered a tritone substitute by
+///   [`get_substitutions`] is replaced with it
+/// - **Secondary dominants**: every chord offered a secondary dominant is
+///   preceded by it, unless already preceded by that dominant; the inserted
+///   chord takes the first half of the chord's duration
+///
+/// Rests are kept in place.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_core::{progression_reharmonize, Chord, ReharmonizationStrategy, ScaleFingerprint};
+///
+/// let major = ScaleFingerprint::major_scale();
+/// let progression = vec![Chord::triad(1)?, Chord::seventh(2)?, Chord::seventh(5)?, Chord::triad(1)?];
+///
+/// let reharmonized = progression_reharmonize(
+///     &progression,
+///     &major,
+///     &ReharmonizationStrategy::SecondaryDominants,
+/// )?;
+/// // V7/ii before ii7, and V7/V before V7
+/// assert_eq!(reharmonized.len(), 6);
+/// assert_eq!((reharmonized[1].root, reharmonized[1].applied), (5, 2));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`negative_harmony_mirror`] - Single-chord negative harmony
+/// - [`modal_interchange_options`] - All borrowed options for a chord
+/// - [`get_substitutions`] - Single-chord substitutions
+pub fn progression_reharmonize(
+    progression: &[Chord],
+    scale_fingerprint: &ScaleFingerprint,
+    strategy: &ReharmonizationStrategy,
+) -> ChordTheoryResult<Vec<Chord>> {
+    let mut result: Vec<Chord> = Vec::with_capacity(progression.len());
+
+    for chord in progression {
+        if chord.is_rest {
+            result.push(chord.clone());
+            continue;
+        }
+
+        match strategy {
+            ReharmonizationStrategy::NegativeHarmony(axis) => {
+                match negative_harmony_mirror(chord, scale_fingerprint, *axis) {
+                    Ok(mirrored) => result.push(mirrored),
+                    Err(ChordTheoryError::NoMatchingChord { .. }) => result.push(chord.clone()),
+                    Err(e) => return Err(e),
+                }
+            },
+            ReharmonizationStrategy::ModalInterchange(to_scale) => {
+                let option = modal_interchange_options(chord, scale_fingerprint, to_scale)?
+                    .into_iter()
+                    .find(|option| option.root == chord.root);
+                result.push(option.unwrap_or_else(|| chord.clone()));
+            },
+            ReharmonizationStrategy::TritoneSubstitution => {
+                let substitute = get_substitutions(chord, scale_fingerprint)?
+                    .into_iter()
+                    .find(|s| s.kind == SubstitutionKind::Tritone)
+                    .map(|s| s.chord);
+                result.push(substitute.unwrap_or_else(|| chord.clone()));
+            },
+            ReharmonizationStrategy::SecondaryDominants => {
+                let dominant = get_substitutions(chord, scale_fingerprint)?
+                    .into_iter()
+                    .find(|s| s.kind == SubstitutionKind::SecondaryDominant)
+                    .map(|s| s.chord);
+                let already_tonicized = result
+                    .iter()
+                    .rev()
+                    .find(|previous| !previous.is_rest)
+                    .is_some_and(|previous| previous.root == 5 && previous.applied == chord.root);
+
+                match dominant {
+                    Some(mut dominant) if !already_tonicized => {
+                        let mut chord = chord.clone();
+                        if let (Some(beat), Some(duration)) = (chord.beat, chord.duration) {
+                            dominant = dominant.with_timing(beat, duration / 2.0);
+                            chord = chord.with_timing(beat + duration / 2.0, duration / 2.0);
+                        }
+                        result.push(dominant);
+                        result.push(chord);
+                    },
+                    _ => result.push(chord.clone()),
+                }
+            },
+        }
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minor() -> Option<BorrowedScale> {
+        Some(BorrowedScale::ScaleType(ScaleType::Minor))
+    }
+
+    #[test]
+    fn test_chord_pitch_classes() {
+        let major = ScaleFingerprint::major_scale();
+        assert_eq!(
+            chord_pitch_classes(&Chord::triad(1).unwrap(), &major).unwrap(),
+            [0, 4, 7]
+        );
+        let altered = Chord::seventh(5)
+            .unwrap()
+            .with_alteration("b9")
+            .unwrap()
+            .with_suspension(4)
+            .unwrap();
+        assert_eq!(
+            chord_pitch_classes(&altered, &major).unwrap(),
+            [7, 2, 5, 0, 8]
+        );
+
+        let borrowed = Chord::triad(6)
+            .unwrap()
+            .with_borrowed_scale(minor().unwrap())
+            .unwrap();
+        assert_eq!(chord_pitch_classes(&borrowed, &major).unwrap(), [8, 0, 3]);
+        assert!(chord_pitch_classes(&Chord::rest(), &major)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_negative_harmony_mirror() {
+        let major = ScaleFingerprint::major_scale();
+        let axis = MirrorAxis::default();
+        let mirror = |chord: Chord| negative_harmony_mirror(&chord, &major, axis).unwrap();
+
+        let tonic = mirror(Chord::triad(1).unwrap());
+        assert_eq!((tonic.root, tonic.borrowed.clone()), (1, minor()));
+        let subdominant = mirror(Chord::triad(4).unwrap());
+        assert_eq!((subdominant.root, subdominant.borrowed), (5, minor()));
+        // vi becomes ♭III
+        let submediant = mirror(Chord::triad(6).unwrap());
+        assert_eq!((submediant.root, submediant.borrowed), (3, minor()));
+
+        // Mirroring twice returns to the diatonic chord, timing kept
+        let timed = Chord::seventh(2).unwrap().with_timing(4.0, 2.0);
+        let twice = negative_harmony_mirror(&mirror(timed.clone()), &major, axis).unwrap();
+        assert_eq!(twice, timed);
+
+        assert_eq!(axis.reflect(0), 7);
+        assert_eq!(MirrorAxis::between(0, 0).reflect(4), 8);
+        let altered = Chord::seventh(5).unwrap().with_alteration("b9").unwrap();
+        assert!(matches!(
+            negative_harmony_mirror(&altered, &major, axis),
+            Err(ChordTheoryError::NoMatchingChord { .. })
+        ));
+    }
+
+    #[test]
+    fn test_modal_interchange_options() {
+        let major = ScaleFingerprint::major_scale();
+        let options = modal_interchange_options(
+            &Chord::triad(4).unwrap().with_inversion(1).unwrap(),
+            &major,
+            &minor().unwrap(),
+        )
+        .unwrap();
+        let roots: Vec<u8> = options.iter().map(|option| option.root).collect();
+        assert_eq!(roots, [4, 2, 6, 7]);
+        assert!(options.iter().all(|option| option.inversion == 1));
+
+        // The parallel major offers nothing new for a major-key chord
+        let major_options = modal_interchange_options(
+            &Chord::triad(1).unwrap(),
+            &major,
+            &BorrowedScale::ScaleType(ScaleType::Major),
+        )
+        .unwrap();
+        assert!(major_options.is_empty());
+    }
+
+    #[test]
+    fn test_progression_reharmonize() {
+        let major = ScaleFingerprint::major_scale();
+        let progression = vec![
+            Chord::triad(1).unwrap(),
+            Chord::rest(),
+            Chord::seventh(5).unwrap().with_timing(4.0, 2.0),
+            Chord::triad(1).unwrap(),
+        ];
+
+        let negative = progression_reharmonize(
+            &progression,
+            &major,
+            &ReharmonizationStrategy::NegativeHarmony(MirrorAxis::default()),
+        )
+        .unwrap();
+        assert!(negative[1].is_rest);
+        assert_eq!(negative[2].root, 2);
+
+        let modal = progression_reharmonize(
+            &progression,
+            &major,
+            &ReharmonizationStrategy::ModalInterchange(minor().unwrap()),
+        )
+        .unwrap();
+        assert!(modal
+            .iter()
+            .all(|chord| chord.is_rest || chord.borrowed == minor()));
+
+        let tritone = progression_reharmonize(
+            &progression,
+            &major,
+            &ReharmonizationStrategy::TritoneSubstitution,
+        )
+        .unwrap();
+        assert_eq!(tritone[2].substitutions.as_slice(), ["tritone"]);
+        assert_eq!(tritone[0], progression[0]);
+
+        let secondary = progression_reharmonize(
+            &progression,
+            &major,
+            &ReharmonizationStrategy::SecondaryDominants,
+        )
+        .unwrap();
+        assert_eq!(secondary.len(), 5);
+        assert_eq!(secondary[2].applied, 5);
+        assert_eq!(
+            (secondary[2].beat, secondary[2].duration),
+            (Some(4.0), Some(1.0))
+        );
+        assert_eq!(secondary[3].beat, Some(5.0));
+    }
+}
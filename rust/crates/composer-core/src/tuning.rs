@@ -0,0 +1,418 @@
+//! Equal temperaments other than 12-TET
+//!
+//! [`ScaleFingerprint`] has exactly 12 semitones. A [`TuningSystem`] divides
+//! the octave into any number of equal steps, such as 19-TET or the 24-TET
+//! quarter tones used for maqam, and a [`TunedScaleFingerprint`] is a scale in
+//! one of them. Chords are stacked from tuned scales exactly as from 12-TET
+//! ones, with alterations moved by the step nearest a semitone.
+//!
+//! Available with the `microtonal` feature.
+
+use crate::chord::Chord;
+use crate::error::{ChordTheoryError, ChordTheoryResult};
+use crate::scale::ScaleFingerprint;
+use crate::transform::{chord_tone_intervals, incomplete_scale};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Most steps a tuning may divide the octave into
+pub const MAX_TUNING_DIVISIONS: u8 = 72;
+
+const NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// Equal division of the octave into a number of steps
+///
+/// Serializes as its number of divisions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub struct TuningSystem {
+    divisions: u8,
+}
+
+impl TuningSystem {
+    /// Standard 12-tone equal temperament
+    pub const TWELVE_TET: TuningSystem = TuningSystem { divisions: 12 };
+
+    /// 19-tone equal temperament, close to quarter-comma meantone
+    pub const NINETEEN_TET: TuningSystem = TuningSystem { divisions: 19 };
+
+    /// 24-tone equal temperament, the quarter tones of Arabic maqam
+    pub const TWENTY_FOUR_TET: TuningSystem = TuningSystem { divisions: 24 };
+
+    /// Create a tuning of `divisions` equal steps per octave (1 to 72)
+    pub fn equal(divisions: u8) -> ChordTheoryResult<Self> {
+        if divisions == 0 || divisions > MAX_TUNING_DIVISIONS {
+            return Err(ChordTheoryError::InvalidTuning {
+                reason: format!(
+                    "octave must be divided into 1 to {} steps, got {}",
+                    MAX_TUNING_DIVISIONS, divisions
+                ),
+            });
+        }
+        Ok(TuningSystem { divisions })
+    }
+
+    /// Number of steps per octave
+    pub fn divisions(&self) -> u8 {
+        self.divisions
+    }
+
+    /// Size of one step in cents
+    pub fn step_cents(&self) -> f64 {
+        1200.0 / self.divisions as f64
+    }
+
+    /// Height of a step above the tonic in cents
+    pub fn cents(&self, step: i32) -> f64 {
+        step as f64 * self.step_cents()
+    }
+
+    /// Step nearest a height in cents
+    pub fn nearest_step(&self, cents: f64) -> i32 {
+        (cents / self.step_cents()).round() as i32
+    }
+
+    /// Step nearest a 12-TET interval in semitones
+    pub fn from_semitones(&self, semitones: i32) -> i32 {
+        self.nearest_step(semitones as f64 * 100.0)
+    }
+
+    /// Name of a step, taking the tonic as C
+    ///
+    /// Steps on a 12-TET pitch get its name; others get the nearest name and
+    /// their deviation from it in cents.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_core::TuningSystem;
+    ///
+    /// let quarter_tones = TuningSystem::TWENTY_FOUR_TET;
+    /// assert_eq!(quarter_tones.note_name(8), "E");
+    /// assert_eq!(quarter_tones.note_name(7), "E-50c");
+    /// assert_eq!(TuningSystem::NINETEEN_TET.note_name(1), "C#-37c");
+    /// ```
+    pub fn note_name(&self, step: i32) -> String {
+        let cents = self.cents(step.rem_euclid(self.divisions as i32));
+        let semitone = (cents / 100.0).round() as i32;
+        let name = NOTE_NAMES[semitone.rem_euclid(12) as usize];
+        match (cents - semitone as f64 * 100.0).round() as i32 {
+            0 => name.to_string(),
+            deviation => format!("{}{:+}c", name, deviation),
+        }
+    }
+}
+
+impl Default for TuningSystem {
+    /// Default to 12-TET
+    fn default() -> Self {
+        Self::TWELVE_TET
+    }
+}
+
+impl TryFrom<u8> for TuningSystem {
+    type Error = ChordTheoryError;
+
+    fn try_from(divisions: u8) -> ChordTheoryResult<Self> {
+        Self::equal(divisions)
+    }
+}
+
+impl From<TuningSystem> for u8 {
+    fn from(tuning: TuningSystem) -> u8 {
+        tuning.divisions
+    }
+}
+
+impl fmt::Display for TuningSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-TET", self.divisions)
+    }
+}
+
+/// Scale fingerprint in any equal temperament
+///
+/// One 0/1 flag per step of the tuning, like [`ScaleFingerprint`] has per
+/// semitone. Maqam Rast in 24-TET, with its half-flat third and seventh, is
+/// steps `[0, 4, 7, 10, 14, 18, 21]`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TunedScaleFingerprint {
+    tuning: TuningSystem,
+    steps: Vec<u8>,
+}
+
+impl TunedScaleFingerprint {
+    /// Create a scale from one 0/1 flag per step of the tuning
+    pub fn new(tuning: TuningSystem, steps: Vec<u8>) -> ChordTheoryResult<Self> {
+        if steps.len() != tuning.divisions() as usize {
+            return Err(ChordTheoryError::InvalidScaleFingerprint {
+                reason: format!(
+                    "{} scale fingerprint must have exactly {} elements, got {}",
+                    tuning,
+                    tuning.divisions(),
+                    steps.len()
+                ),
+            });
+        }
+        if let Some(i) = steps.iter().position(|&value| value > 1) {
+            return Err(ChordTheoryError::InvalidScaleFingerprint {
+                reason: format!("Position {} has value {}, must be 0 or 1", i, steps[i]),
+            });
+        }
+        if steps.iter().all(|&value| value == 0) {
+            return Err(ChordTheoryError::InvalidScaleFingerprint {
+                reason: "Scale must contain at least one note".to_string(),
+            });
+        }
+        Ok(TunedScaleFingerprint { tuning, steps })
+    }
+
+    /// Create a scale from the steps it contains
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_core::{Chord, TunedScaleFingerprint, TuningSystem};
+    ///
+    /// let rast = TunedScaleFingerprint::from_notes(
+    ///     TuningSystem::TWENTY_FOUR_TET,
+    ///     &[0, 4, 7, 10, 14, 18, 21],
+    /// )?;
+    /// assert_eq!(rast.spell_chord(&Chord::triad(1)?)?, ["C", "E-50c", "G"]);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_notes(tuning: TuningSystem, notes: &[u8]) -> ChordTheoryResult<Self> {
+        let mut steps = vec![0; tuning.divisions() as usize];
+        for &note in notes {
+            match steps.get_mut(note as usize) {
+                Some(step) => *step = 1,
+                None => {
+                    return Err(ChordTheoryError::InvalidScaleFingerprint {
+                        reason: format!("step {} is outside {}", note, tuning),
+                    })
+                },
+            }
+        }
+        Self::new(tuning, steps)
+    }
+
+    /// Nearest equivalent of a 12-TET scale in another tuning
+    ///
+    /// Each semitone moves to its nearest step, so 12-TET major becomes the
+    /// meantone major scale of 19-TET.
+    pub fn from_twelve_tet(scale: &ScaleFingerprint, tuning: TuningSystem) -> Self {
+        let divisions = tuning.divisions() as i32;
+        let mut steps = vec![0; divisions as usize];
+        for semitone in scale.chromatic_notes() {
+            steps[tuning.from_semitones(semitone as i32).rem_euclid(divisions) as usize] = 1;
+        }
+        TunedScaleFingerprint { tuning, steps }
+    }
+
+    /// The 12-TET scale with the same notes, if every note is on a semitone
+    pub fn to_twelve_tet(&self) -> Option<ScaleFingerprint> {
+        let divisions = self.tuning.divisions() as usize;
+        let mut semitones = [0u8; 12];
+        for note in self.notes() {
+            let twelfths = note as usize * 12;
+            if twelfths % divisions != 0 {
+                return None;
+            }
+            semitones[twelfths / divisions] = 1;
+        }
+        ScaleFingerprint::new(semitones).ok()
+    }
+
+    /// The tuning this scale is in
+    pub fn tuning(&self) -> TuningSystem {
+        self.tuning
+    }
+
+    /// Get the raw step flags
+    pub fn steps(&self) -> &[u8] {
+        &self.steps
+    }
+
+    /// Check if a step is in the scale
+    pub fn contains_step(&self, step: u8) -> bool {
+        self.steps.get(step as usize) == Some(&1)
+    }
+
+    /// Get all steps present in the scale
+    pub fn notes(&self) -> Vec<u8> {
+        self.steps
+            .iter()
+            .enumerate()
+            .filter(|(_, &present)| present == 1)
+            .map(|(i, _)| i as u8)
+            .collect()
+    }
+
+    /// Count the number of notes in the scale
+    pub fn note_count(&self) -> usize {
+        self.steps.iter().filter(|&&x| x == 1).count()
+    }
+
+    /// Convert a scale degree (1-based) to its step
+    pub fn scale_degree_to_step(&self, degree: u8) -> Option<u8> {
+        if degree == 0 {
+            return None;
+        }
+        self.notes().get(degree as usize - 1).copied()
+    }
+
+    /// Steps of a chord, stacked in this scale
+    ///
+    /// Tones come root first, without duplicates, like [`chord_pitch_classes`]
+    /// gives for 12-TET. Alterations move a tone by the step nearest a
+    /// semitone. Borrowed and applied chords are defined by 12-TET scales, so
+    /// they are rejected.
+    ///
+    /// [`chord_pitch_classes`]: crate::chord_pitch_classes
+    pub fn chord_steps(&self, chord: &Chord) -> ChordTheoryResult<Vec<u8>> {
+        chord.validate()?;
+        if chord.is_rest {
+            return Ok(Vec::new());
+        }
+        if chord.applied != 0 || chord.borrowed.is_some() {
+            return Err(ChordTheoryError::InvalidTuning {
+                reason: format!(
+                    "borrowed and applied chords need a 12-TET scale, not {}",
+                    self.tuning
+                ),
+            });
+        }
+
+        let root = self
+            .degree_height(chord.root)
+            .ok_or_else(incomplete_scale)?;
+        let intervals = chord_tone_intervals(
+            chord,
+            |steps| {
+                self.degree_height(chord.root + steps)
+                    .map(|step| step - root)
+            },
+            |semitones| self.tuning.from_semitones(semitones),
+        )?;
+
+        let divisions = self.tuning.divisions() as i32;
+        let mut steps = Vec::with_capacity(intervals.len());
+        for (_, interval) in intervals {
+            let step = (root + interval).rem_euclid(divisions) as u8;
+            if !steps.contains(&step) {
+                steps.push(step);
+            }
+        }
+        Ok(steps)
+    }
+
+    /// Note names of a chord stacked in this scale, taking the tonic as C
+    pub fn spell_chord(&self, chord: &Chord) -> ChordTheoryResult<Vec<String>> {
+        Ok(self
+            .chord_steps(chord)?
+            .into_iter()
+            .map(|step| self.tuning.note_name(step as i32))
+            .collect())
+    }
+
+    /// Steps above the tonic of a degree, counting on past the seventh into
+    /// higher octaves as chords are stacked
+    fn degree_height(&self, degree: u8) -> Option<i32> {
+        let octave = (degree as i32 - 1) / 7;
+        let step = self.scale_degree_to_step((degree - 1) % 7 + 1)?;
+        Some(step as i32 + self.tuning.divisions() as i32 * octave)
+    }
+}
+
+impl fmt::Display for TunedScaleFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<String> = self
+            .notes()
+            .into_iter()
+            .map(|step| self.tuning.note_name(step as i32))
+            .collect();
+        write!(f, "{} [{}]", self.tuning, names.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rast() -> TunedScaleFingerprint {
+        TunedScaleFingerprint::from_notes(TuningSystem::TWENTY_FOUR_TET, &[0, 4, 7, 10, 14, 18, 21])
+            .unwrap()
+    }
+
+    #[test]
+    fn test_tuning_system() {
+        assert!(TuningSystem::equal(0).is_err());
+        assert!(TuningSystem::equal(73).is_err());
+        assert_eq!(TuningSystem::equal(19).unwrap(), TuningSystem::NINETEEN_TET);
+        assert_eq!(TuningSystem::TWENTY_FOUR_TET.step_cents(), 50.0);
+        assert_eq!(TuningSystem::NINETEEN_TET.from_semitones(7), 11);
+        assert_eq!(TuningSystem::TWELVE_TET.note_name(-1), "B");
+        assert_eq!(TuningSystem::TWENTY_FOUR_TET.note_name(23), "C-50c");
+        assert_eq!(TuningSystem::NINETEEN_TET.to_string(), "19-TET");
+
+        // Serializes as its divisions, rejecting invalid ones
+        assert_eq!(u8::from(TuningSystem::NINETEEN_TET), 19);
+        assert!(TuningSystem::try_from(0).is_err());
+    }
+
+    #[test]
+    fn test_tuned_scale_fingerprint() {
+        let quarter_tones = TuningSystem::TWENTY_FOUR_TET;
+        assert!(TunedScaleFingerprint::new(quarter_tones, vec![1; 12]).is_err());
+        assert!(TunedScaleFingerprint::new(quarter_tones, vec![0; 24]).is_err());
+        assert!(TunedScaleFingerprint::from_notes(quarter_tones, &[24]).is_err());
+
+        let major = ScaleFingerprint::major_scale();
+        let meantone = TunedScaleFingerprint::from_twelve_tet(&major, TuningSystem::NINETEEN_TET);
+        assert_eq!(meantone.notes(), [0, 3, 6, 8, 11, 14, 17]);
+        assert_eq!(meantone.to_twelve_tet(), None);
+
+        let doubled = TunedScaleFingerprint::from_twelve_tet(&major, quarter_tones);
+        assert_eq!(doubled.notes(), [0, 4, 8, 10, 14, 18, 22]);
+        assert_eq!(doubled.to_twelve_tet(), Some(major));
+        assert_eq!(rast().to_twelve_tet(), None);
+        assert_eq!(rast().scale_degree_to_step(3), Some(7));
+        assert!(rast().contains_step(21));
+        assert_eq!(rast().to_string(), "24-TET [C, D, E-50c, F, G, A, B-50c]");
+    }
+
+    #[test]
+    fn test_tuned_chords() {
+        let dominant = Chord::seventh(5).unwrap();
+        assert_eq!(rast().chord_steps(&dominant).unwrap(), [14, 21, 4, 10]);
+        assert_eq!(
+            rast().spell_chord(&dominant).unwrap(),
+            ["G", "B-50c", "D", "F"]
+        );
+
+        // Alterations move by the step nearest a semitone
+        let altered = dominant.clone().with_alteration("b9").unwrap();
+        assert_eq!(rast().chord_steps(&altered).unwrap(), [14, 21, 4, 10, 16]);
+
+        // 12-TET chords agree with chord_pitch_classes
+        let major = ScaleFingerprint::major_scale();
+        let twelve = TunedScaleFingerprint::from_twelve_tet(&major, TuningSystem::TWELVE_TET);
+        let sus = Chord::triad(2).unwrap().with_suspension(4).unwrap();
+        assert_eq!(
+            twelve.chord_steps(&sus).unwrap(),
+            crate::chord_pitch_classes(&sus, &major).unwrap()
+        );
+
+        let applied = Chord::triad(5).unwrap().with_applied(5).unwrap();
+        assert!(matches!(
+            rast().chord_steps(&applied),
+            Err(ChordTheoryError::InvalidTuning { .. })
+        ));
+        let pentatonic =
+            TunedScaleFingerprint::from_notes(TuningSystem::TWENTY_FOUR_TET, &[0, 4, 8, 14, 18])
+                .unwrap();
+        assert!(pentatonic.chord_steps(&dominant).is_err());
+    }
+}
@@ -0,0 +1,458 @@
+//! Instrument voicings
+//!
+//! Maps chords to concrete notes for a given instrument: close, drop-2 and
+//! drop-3 piano voicings as MIDI note arrays, and playable guitar fingerings as
+//! per-string fret arrays. Chords are pitched from [`chord_pitch_classes`] and
+//! a key tonic given as a pitch class (0 = C).
+
+use crate::chord::Chord;
+use crate::error::{ChordTheoryError, ChordTheoryResult};
+use crate::scale::ScaleFingerprint;
+use crate::transform::chord_pitch_classes;
+use serde::{Deserialize, Serialize};
+
+/// Lowest note of a piano close voicing (middle C)
+const PIANO_VOICING_FLOOR: u8 = 60;
+
+/// Piano voicing layouts for [`piano_voicing`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum PianoVoicingStyle {
+    /// Chord tones stacked as closely as possible from the bass upward
+    #[default]
+    Close,
+    /// Close voicing with the second voice from the top dropped an octave
+    Drop2,
+    /// Close voicing with the third voice from the top dropped an octave
+    Drop3,
+}
+
+/// Guitar tuning as MIDI notes of the open strings, lowest string first
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GuitarTuning {
+    /// Open-string MIDI notes, lowest string first
+    pub strings: Vec<u8>,
+}
+
+impl GuitarTuning {
+    /// Standard six-string tuning, E2 A2 D3 G3 B3 E4
+    pub fn standard() -> Self {
+        Self {
+            strings: vec![40, 45, 50, 55, 59, 64],
+        }
+    }
+
+    /// Re-entrant ukulele tuning, G4 C4 E4 A4
+    pub fn ukulele() -> Self {
+        Self {
+            strings: vec![67, 60, 64, 69],
+        }
+    }
+}
+
+impl Default for GuitarTuning {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+/// Constraints for [`guitar_fingerings`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GuitarVoicingOptions {
+    /// Tuning of the instrument
+    pub tuning: GuitarTuning,
+    /// Highest fret that may be used
+    pub max_fret: u8,
+    /// Most frets the fretting hand may cover, counting both ends
+    pub max_fret_span: u8,
+    /// Whether the chord's bass must be the lowest note; re-entrant tunings
+    /// such as the ukulele's usually ignore inversions
+    pub bass_lowest: bool,
+}
+
+impl Default for GuitarVoicingOptions {
+    fn default() -> Self {
+        Self {
+            tuning: GuitarTuning::standard(),
+            max_fret: 12,
+            max_fret_span: 4,
+            bass_lowest: true,
+        }
+    }
+}
+
+/// Guitar fingering: one entry per string, lowest string first
+///
+/// `None` marks a muted string and `Some(0)` an open one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GuitarFingering {
+    /// Fret per string, lowest string first
+    pub frets: Vec<Option<u8>>,
+}
+
+impl GuitarFingering {
+    /// MIDI notes sounded in a tuning, lowest string first
+    pub fn midi_notes(&self, tuning: &GuitarTuning) -> Vec<u8> {
+        self.frets
+            .iter()
+            .zip(&tuning.strings)
+            .filter_map(|(fret, open)| fret.map(|fret| open + fret))
+            .collect()
+    }
+
+    /// Lowest fretted (non-open) fret, or 0 for an all-open fingering
+    pub fn position(&self) -> u8 {
+        self.fretted().min().unwrap_or(0)
+    }
+
+    /// Frets covered by the fretting hand, counting both ends; 0 when all open
+    pub fn fret_span(&self) -> u8 {
+        match (self.fretted().min(), self.fretted().max()) {
+            (Some(low), Some(high)) => high - low + 1,
+            _ => 0,
+        }
+    }
+
+    /// Fingers needed, counting a barre across the lowest fret as one finger
+    pub fn finger_count(&self) -> usize {
+        let fretted = self.fretted().count();
+        let position = self.position();
+        let barred = self.fretted().filter(|&fret| fret == position).count();
+        if barred > 1 {
+            fretted - barred + 1
+        } else {
+            fretted
+        }
+    }
+
+    /// Number of muted strings
+    pub fn muted_strings(&self) -> usize {
+        self.frets.iter().filter(|fret| fret.is_none()).count()
+    }
+
+    fn fretted(&self) -> impl Iterator<Item = u8> + '_ {
+        self.frets
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|&fret| fret > 0)
+    }
+}
+
+/// Chord tones as absolute pitch classes, bass first, then in stacking order
+fn voiced_pitch_classes(
+    chord: &Chord,
+    scale_fingerprint: &ScaleFingerprint,
+    tonic: u8,
+) -> ChordTheoryResult<Vec<u8>> {
+    let mut tones: Vec<u8> = chord_pitch_classes(chord, scale_fingerprint)?
+        .into_iter()
+        .map(|pitch_class| (pitch_class + tonic % 12) % 12)
+        .collect();
+    if tones.is_empty() {
+        return Err(ChordTheoryError::MissingRequiredProperty {
+            property: "pitched chord tones (rests cannot be voiced)".to_string(),
+        });
+    }
+    let inversion = (chord.inversion as usize).min(tones.len() - 1);
+    tones.rotate_left(inversion);
+    Ok(tones)
+}
+
+/// Lowest MIDI note of a pitch class strictly above `note`
+fn next_above(note: u8, pitch_class: u8) -> u8 {
+    let step = (pitch_class + 12 - note % 12) % 12;
+    note + if step == 0 { 12 } else { step }
+}
+
+/// Voice a chord for piano as ascending MIDI notes.
+///
+/// The close voicing puts the bass (the root, or the chord tone selected by
+/// the inversion in stacking order) at or above middle C and stacks the other
+/// tones above it, each in the nearest octave. Drop-2 and drop-3 voicings move
+/// the second or third voice from the top of that close voicing down an
+/// octave; they need at least four tones, so triads are rejected.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_core::{piano_voicing, Chord, PianoVoicingStyle, ScaleFingerprint};
+///
+/// let major = ScaleFingerprint::major_scale();
+/// let g7 = Chord::seventh(5)?;
+///
+/// // G7 in C: G4 B4 D5 F5
+/// assert_eq!(piano_voicing(&g7, &major, 0, PianoVoicingStyle::Close)?, vec![67, 71, 74, 77]);
+/// // Drop-2 moves D5 down to D4
+/// assert_eq!(piano_voicing(&g7, &major, 0, PianoVoicingStyle::Drop2)?, vec![62, 67, 71, 77]);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`guitar_fingerings`] - Voicings for guitar
+/// - [`chord_pitch_classes`] - Pitch classes that are voiced
+pub fn piano_voicing(
+    chord: &Chord,
+    scale_fingerprint: &ScaleFingerprint,
+    tonic: u8,
+    style: PianoVoicingStyle,
+) -> ChordTheoryResult<Vec<u8>> {
+    let tones = voiced_pitch_classes(chord, scale_fingerprint, tonic)?;
+
+    let mut notes: Vec<u8> = Vec::with_capacity(tones.len());
+    for &pitch_class in &tones {
+        let note = match notes.last() {
+            Some(&previous) => next_above(previous, pitch_class),
+            None => next_above(PIANO_VOICING_FLOOR - 1, pitch_class),
+        };
+        notes.push(note);
+    }
+
+    let dropped_voice = match style {
+        PianoVoicingStyle::Close => return Ok(notes),
+        PianoVoicingStyle::Drop2 => 2,
+        PianoVoicingStyle::Drop3 => 3,
+    };
+    if notes.len() < 4 {
+        return Err(ChordTheoryError::UnsupportedVoicing {
+            reason: format!(
+                "drop-{} voicings need at least four tones, chord has {}",
+                dropped_voice,
+                notes.len()
+            ),
+        });
+    }
+    let index = notes.len() - dropped_voice;
+    if let Some(note) = notes.get_mut(index) {
+        *note -= 12;
+    }
+    notes.sort_unstable();
+    Ok(notes)
+}
+
+/// Enumerate playable guitar fingerings of a chord.
+///
+/// A fingering is kept when it sounds every chord tone, its lowest note is
+/// the bass (the root, or the chord tone selected by the inversion) unless
+/// `bass_lowest` is off, its sounded strings are adjacent (muted strings only
+/// at the edges), its fretted notes fit within `max_fret_span` frets up to
+/// `max_fret`, and it needs at most four fingers, a barre across the lowest
+/// fret counting as one. Fingerings that leave the highest strings unsounded
+/// come last; otherwise the lowest position comes first, then fewer muted
+/// strings, fewer fingers and a smaller span.
+///
+/// Chords with more tones than the tuning has strings have no fingerings.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_core::{guitar_fingerings, Chord, GuitarVoicingOptions, ScaleFingerprint};
+///
+/// let major = ScaleFingerprint::major_scale();
+/// let fingerings = guitar_fingerings(&Chord::triad(1)?, &major, 0, &GuitarVoicingOptions::default())?;
+///
+/// // The open C chord, x32010
+/// assert_eq!(
+///     fingerings[0].frets,
+///     vec![None, Some(3), Some(2), Some(0), Some(1), Some(0)]
+/// );
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`piano_voicing`] - Voicings for piano
+/// - [`GuitarFingering::midi_notes`] - Notes sounded by a fingering
+pub fn guitar_fingerings(
+    chord: &Chord,
+    scale_fingerprint: &ScaleFingerprint,
+    tonic: u8,
+    options: &GuitarVoicingOptions,
+) -> ChordTheoryResult<Vec<GuitarFingering>> {
+    let tones = voiced_pitch_classes(chord, scale_fingerprint, tonic)?;
+    let strings = &options.tuning.strings;
+    if tones.len() > strings.len() {
+        return Ok(Vec::new());
+    }
+
+    // Candidate frets per string: chord tones up to the highest fret
+    let candidates: Vec<Vec<u8>> = strings
+        .iter()
+        .map(|&open| {
+            (0..=options.max_fret)
+                .filter(|&fret| tones.contains(&((open + fret) % 12)))
+                .collect()
+        })
+        .collect();
+
+    let mut fingerings = Vec::new();
+    let mut frets = Vec::with_capacity(strings.len());
+    collect_fingerings(&candidates, options, &mut frets, &mut fingerings);
+
+    let bass = tones.first().copied();
+    fingerings.retain(|fingering| {
+        let notes = fingering.midi_notes(&options.tuning);
+        (!options.bass_lowest || notes.iter().min().map(|note| note % 12) == bass)
+            && tones
+                .iter()
+                .all(|tone| notes.iter().any(|note| note % 12 == *tone))
+            && fingering.finger_count() <= 4
+    });
+    fingerings.sort_by_key(|fingering| {
+        let treble_mutes = fingering
+            .frets
+            .iter()
+            .rev()
+            .take_while(|fret| fret.is_none())
+            .count();
+        (
+            treble_mutes,
+            fingering.position(),
+            fingering.muted_strings(),
+            fingering.finger_count(),
+            fingering.fret_span(),
+        )
+    });
+    Ok(fingerings)
+}
+
+/// Depth-first search over strings, pruning muted gaps and wide spans
+fn collect_fingerings(
+    candidates: &[Vec<u8>],
+    options: &GuitarVoicingOptions,
+    frets: &mut Vec<Option<u8>>,
+    found: &mut Vec<GuitarFingering>,
+) {
+    let Some(string_candidates) = candidates.get(frets.len()) else {
+        if frets.iter().any(Option::is_some) {
+            found.push(GuitarFingering {
+                frets: frets.clone(),
+            });
+        }
+        return;
+    };
+
+    // Once a string has sounded, a mute ends the run of sounded strings
+    let run_ended = frets
+        .iter()
+        .skip_while(|fret| fret.is_none())
+        .any(Option::is_none);
+    if !run_ended {
+        for &fret in string_candidates {
+            frets.push(Some(fret));
+            let fingering = GuitarFingering {
+                frets: frets.clone(),
+            };
+            if fingering.fret_span() <= options.max_fret_span {
+                collect_fingerings(candidates, options, frets, found);
+            }
+            frets.pop();
+        }
+    }
+
+    frets.push(None);
+    collect_fingerings(candidates, options, frets, found);
+    frets.pop();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_piano_voicing() {
+        let major = ScaleFingerprint::major_scale();
+        let cmaj7 = Chord::seventh(1).unwrap();
+
+        assert_eq!(
+            piano_voicing(&cmaj7, &major, 0, PianoVoicingStyle::Close).unwrap(),
+            [60, 64, 67, 71]
+        );
+        assert_eq!(
+            piano_voicing(&cmaj7, &major, 0, PianoVoicingStyle::Drop3).unwrap(),
+            [52, 60, 67, 71]
+        );
+
+        // First inversion puts the third in the bass; D major key shifts by 2
+        let inverted = cmaj7.with_inversion(1).unwrap();
+        assert_eq!(
+            piano_voicing(&inverted, &major, 2, PianoVoicingStyle::Close).unwrap(),
+            [66, 69, 73, 74]
+        );
+
+        assert!(matches!(
+            piano_voicing(
+                &Chord::triad(1).unwrap(),
+                &major,
+                0,
+                PianoVoicingStyle::Drop2
+            ),
+            Err(ChordTheoryError::UnsupportedVoicing { .. })
+        ));
+        assert!(piano_voicing(&Chord::rest(), &major, 0, PianoVoicingStyle::Close).is_err());
+    }
+
+    #[test]
+    fn test_guitar_fingerings() {
+        let major = ScaleFingerprint::major_scale();
+        let options = GuitarVoicingOptions::default();
+
+        // G major in C: the open G shape, 320003
+        let g = guitar_fingerings(&Chord::triad(5).unwrap(), &major, 0, &options).unwrap();
+        assert_eq!(
+            g[0].frets,
+            [Some(3), Some(2), Some(0), Some(0), Some(0), Some(3)]
+        );
+
+        for fingering in &g {
+            assert!(fingering.fret_span() <= options.max_fret_span);
+            assert!(fingering.finger_count() <= 4);
+            let notes = fingering.midi_notes(&options.tuning);
+            assert_eq!(notes.iter().min().map(|note| note % 12), Some(7));
+        }
+
+        // A first-inversion chord keeps its third lowest
+        let inverted = Chord::triad(1).unwrap().with_inversion(1).unwrap();
+        let fingerings = guitar_fingerings(&inverted, &major, 0, &options).unwrap();
+        assert!(!fingerings.is_empty());
+        assert!(fingerings.iter().all(|fingering| {
+            fingering
+                .midi_notes(&options.tuning)
+                .iter()
+                .min()
+                .map(|n| n % 12)
+                == Some(4)
+        }));
+
+        // Ukulele C ignores the bass: 0003
+        let ukulele = GuitarVoicingOptions {
+            tuning: GuitarTuning::ukulele(),
+            bass_lowest: false,
+            ..GuitarVoicingOptions::default()
+        };
+        let c = guitar_fingerings(&Chord::triad(1).unwrap(), &major, 0, &ukulele).unwrap();
+        assert_eq!(c[0].frets, [Some(0), Some(0), Some(0), Some(3)]);
+
+        // Seven tones cannot fit on six strings
+        let thirteenth = Chord::new(5, 13).unwrap();
+        assert!(guitar_fingerings(&thirteenth, &major, 0, &options)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_guitar_fingering_measures() {
+        // F barre chord, 133211
+        let barre = GuitarFingering {
+            frets: vec![Some(1), Some(3), Some(3), Some(2), Some(1), Some(1)],
+        };
+        assert_eq!(barre.position(), 1);
+        assert_eq!(barre.fret_span(), 3);
+        assert_eq!(barre.finger_count(), 4);
+        assert_eq!(barre.muted_strings(), 0);
+        assert_eq!(
+            barre.midi_notes(&GuitarTuning::standard()),
+            [41, 48, 53, 57, 60, 65]
+        );
+    }
+}
@@ -6,7 +6,7 @@
 use composer_config::{ALGORITHM, MUSICAL, PERFORMANCE};
 use composer_core::{
     get_chord_complexity, get_relative_chord_graphic, get_relative_scale_degrees,
-    get_stable_scale_degrees, BorrowedScale, Chord, ScaleFingerprint,
+    get_stable_scale_degrees, BorrowedScale, Chord, Mode, ScaleFingerprint,
 };
 use composer_serialization::{
     chord_binary_to_hex, deserialize_chord, hex_to_chord_binary, serialize_chord,
@@ -37,7 +37,7 @@ fn test_complete_chord_analysis_workflow() {
     assert!(!stable_degrees.is_empty());
 
     // Test complexity calculation
-    let complexity = get_chord_complexity(&chord, "major").unwrap();
+    let complexity = get_chord_complexity(&chord, Mode::Major).unwrap();
     assert!(complexity > 3.0); // Complex chord should have higher complexity
 
     // Test Roman numeral generation
@@ -263,7 +263,7 @@ fn test_chord_theory_accuracy() {
     let degrees = get_stable_scale_degrees(&i_chord, &major_scale).unwrap();
     assert_eq!(degrees, vec!["1", "3", "5"]);
 
-    let complexity = get_chord_complexity(&i_chord, "major").unwrap();
+    let complexity = get_chord_complexity(&i_chord, Mode::Major).unwrap();
     assert_eq!(complexity, 1.0); // Simple triad
 
     let roman = get_relative_chord_graphic(&i_chord, &major_scale).unwrap();
@@ -275,7 +275,7 @@ fn test_chord_theory_accuracy() {
     let degrees = get_stable_scale_degrees(&ii7_chord, &major_scale).unwrap();
     assert_eq!(degrees, vec!["2", "4", "6", "1"]);
 
-    let complexity = get_chord_complexity(&ii7_chord, "major").unwrap();
+    let complexity = get_chord_complexity(&ii7_chord, Mode::Major).unwrap();
     assert_eq!(complexity, 2.0); // Seventh chord
 
     let roman = get_relative_chord_graphic(&ii7_chord, &major_scale).unwrap();
@@ -289,7 +289,7 @@ fn test_chord_theory_accuracy() {
 #[test]
 fn test_harmonic_analysis_workflow() {
     // Analyze the progression: I - vi - IV - V7
-    let progression = vec![
+    let progression = [
         Chord::new(1, 5).unwrap(), // I
         Chord::new(6, 5).unwrap(), // vi
         Chord::new(4, 5).unwrap(), // IV
@@ -302,7 +302,7 @@ fn test_harmonic_analysis_workflow() {
     for (i, chord) in progression.iter().enumerate() {
         // Analyze each chord
         let degrees = get_stable_scale_degrees(chord, &scale).unwrap();
-        let complexity = get_chord_complexity(chord, "major").unwrap();
+        let complexity = get_chord_complexity(chord, Mode::Major).unwrap();
         let roman = get_relative_chord_graphic(chord, &scale).unwrap();
 
         // Serialize for storage/transmission
@@ -383,7 +383,7 @@ fn test_edge_cases() {
     assert_eq!(complex_chord.root, deserialized.root);
 
     // Should calculate complexity
-    let complexity = get_chord_complexity(&complex_chord, "major").unwrap();
+    let complexity = get_chord_complexity(&complex_chord, Mode::Major).unwrap();
     assert!(complexity > 5.0); // Very complex chord
     assert!(complexity <= 10.0); // But within bounds
 
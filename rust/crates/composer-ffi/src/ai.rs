@@ -1,19 +1,370 @@
 //! Python bindings for AI-powered features
 
+use pyo3::exceptions::PyTypeError;
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyList};
+use pyo3::types::{PyBytes, PyDict, PyList};
 use std::collections::HashMap;
 
 use crate::error::ToPyResult;
-use crate::{PyChord, PyScaleFingerprint};
+use crate::pickle::{decode_state, encode_state, raw_state};
+use crate::{PyChord, PyNote, PyScaleFingerprint, PyTimeline, PyTokenLibrary};
 use composer_ai::{
-    AiEngine, AiEngineConfig, BassHarmonization, BassHarmonizationOptions, BassStyle,
-    ChordSuggestion, DifficultyAssessment, ProgressionAnalysis, SkillLevel, SuggestionConfig,
-    SuggestionContext,
+    augment_patterns, AccompanimentDensity, AccompanimentOptions, AccompanimentStyle, AiEngine,
+    AiEngineConfig, AugmentationConfig, BassHarmonization, BassHarmonizationOptions, BassStyle,
+    BudgetMode, BulkAddReport, ChordDuration, ChordSuggestion, ContinuationConfig,
+    DenseDifficultyModel, DifficultyAssessment, DifficultyProfile, FormAnalysis, Genre, Groove,
+    GrooveOptions, HarmonicRhythm, LabeledProgression, MatchMode, MelodicAnalysis,
+    PerformanceBudget, PolynomialModel, PracticePlan, ProgressionAnalysis, ProgressionRewrite,
+    SimilarProgression, SkillBand, SkillLevel, SkillThresholds, SuggestionConfig,
+    SuggestionContext, SwingFeel, TokenPattern, TrainingPattern, ValenceEstimate,
 };
+use composer_core::{Key, Mode, PitchClass};
+
+/// Training pattern as passed from Python: chords, source identifier, optional key tonic
+type PyTrainingPattern = (Vec<PyChord>, String, Option<String>);
+
+/// Tokenized training pattern as passed from Python: tokens, source identifier, optional key tonic
+type PyTokenPattern = (Vec<String>, String, Option<String>);
+
+/// Timeline training pattern as passed from Python: timeline, source identifier, optional key tonic
+type PyTimelinePattern = (PyTimeline, String, Option<String>);
+
+/// Python form of a rejected bulk pattern: (index, source_id, error)
+type PyPatternRejection = (usize, String, String);
+
+/// Python form of a similar chord: (chord, similarity, frequency)
+type PySimilarChord = (PyChord, f64, u64);
+
+/// Python form of a substitution suggestion: (kind, chord, placement, frequency)
+type PySubstitutionSuggestion = (String, PyChord, String, u64);
+
+/// Python form of a drum hit: (beat, voice, accent)
+type PyDrumHit = (f64, String, bool);
+
+/// Python form of a restyled progression: (progression, intensity, confidence)
+type PyRestyledProgression = (Vec<PyChord>, usize, f64);
+
+/// Python form of a similar progression: (source_id, key_tonic, progression, similarity)
+type PySimilarProgression = (String, Option<String>, Vec<PyChord>, f64);
+
+/// Pickle state of an engine: (configuration JSON, trie asset or empty)
+type PyEngineState = (String, Py<PyBytes>);
+
+/// Parse a key name such as "F#m" passed from Python
+fn parse_key(key_tonic: Option<String>) -> PyResult<Option<Key>> {
+    key_tonic
+        .map(|key| key.parse::<Key>())
+        .transpose()
+        .to_py_result()
+}
+
+/// Parse a mode or scale name such as "minor" passed from Python
+fn parse_mode(scale: &str) -> PyResult<Mode> {
+    scale.parse::<Mode>().to_py_result()
+}
+
+/// Convert training patterns passed from Python, parsing their key tonics
+fn to_training_patterns(patterns: Vec<PyTrainingPattern>) -> PyResult<Vec<TrainingPattern>> {
+    patterns
+        .into_iter()
+        .map(|(chords, source_id, key_tonic)| {
+            let chords = chords.into_iter().map(|c| c.inner).collect();
+            Ok((chords, source_id, parse_key(key_tonic)?))
+        })
+        .collect()
+}
+
+fn to_py_similar_progressions(similar: Vec<SimilarProgression>) -> Vec<PySimilarProgression> {
+    similar
+        .into_iter()
+        .map(|s| {
+            let progression = s
+                .progression
+                .into_iter()
+                .map(|inner| PyChord { inner })
+                .collect();
+            let key_tonic = s.key_tonic.map(|key| key.to_string());
+            (s.source_id, key_tonic, progression, s.similarity)
+        })
+        .collect()
+}
+
+/// Python form of a form analysis: a dict with `form`, `phrases` and `sections`
+fn to_py_form_analysis(py: Python, form: &FormAnalysis) -> PyResult<Py<PyDict>> {
+    let phrases = PyList::empty(py);
+    for phrase in &form.phrases {
+        let item = PyDict::new(py);
+        item.set_item("label", &phrase.label)?;
+        item.set_item("chord_range", phrase.chord_range)?;
+        item.set_item("start_beat", phrase.start_beat)?;
+        item.set_item("end_beat", phrase.end_beat)?;
+        item.set_item("cadence", phrase.cadence.map(|c| format!("{:?}", c)))?;
+        phrases.append(item)?;
+    }
+
+    let sections = PyList::empty(py);
+    for section in &form.sections {
+        let item = PyDict::new(py);
+        item.set_item("label", &section.label)?;
+        item.set_item("role", format!("{:?}", section.role))?;
+        item.set_item("phrase_range", section.phrase_range)?;
+        item.set_item("start_beat", section.start_beat)?;
+        item.set_item("end_beat", section.end_beat)?;
+        sections.append(item)?;
+    }
+
+    let dict = PyDict::new(py);
+    dict.set_item("form", &form.form)?;
+    dict.set_item("phrases", phrases)?;
+    dict.set_item("sections", sections)?;
+    Ok(dict.into())
+}
+
+/// Python form of a melody analysis: a dict of its measurements and `complexity`
+fn to_py_melodic_analysis(py: Python, analysis: &MelodicAnalysis) -> PyResult<Py<PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("note_count", analysis.note_count)?;
+    dict.set_item("lowest_pitch", analysis.lowest_pitch)?;
+    dict.set_item("highest_pitch", analysis.highest_pitch)?;
+    dict.set_item("range", analysis.range)?;
+    dict.set_item("tessitura", analysis.tessitura)?;
+    dict.set_item("mean_pitch", analysis.mean_pitch)?;
+    dict.set_item("interval_histogram", &analysis.interval_histogram)?;
+    dict.set_item("contour_entropy", analysis.contour_entropy)?;
+    dict.set_item("rhythm_density", analysis.rhythm_density)?;
+    dict.set_item("complexity", analysis.complexity)?;
+    Ok(dict.into())
+}
+
+/// Python form of a practice plan: a dict with ranked `segments`, each with its
+/// `substitutions`, the `simplified_progression` and `estimated_minutes`
+fn to_py_practice_plan(py: Python, plan: PracticePlan) -> PyResult<Py<PyDict>> {
+    let segments = PyList::empty(py);
+    for segment in plan.segments {
+        let substitutions = PyList::empty(py);
+        for substitution in segment.substitutions {
+            let item = PyDict::new(py);
+            item.set_item("index", substitution.index)?;
+            item.set_item(
+                "original",
+                PyChord {
+                    inner: substitution.original,
+                },
+            )?;
+            item.set_item(
+                "simplified",
+                PyChord {
+                    inner: substitution.simplified,
+                },
+            )?;
+            item.set_item("complexity_reduction", substitution.complexity_reduction)?;
+            substitutions.append(item)?;
+        }
+
+        let item = PyDict::new(py);
+        item.set_item("chord_range", segment.chord_range)?;
+        item.set_item("difficulty", segment.difficulty)?;
+        item.set_item("skill_level", skill_level_name(segment.skill_level))?;
+        item.set_item("above_level", segment.above_level)?;
+        item.set_item("substitutions", substitutions)?;
+        item.set_item("estimated_minutes", segment.estimated_minutes)?;
+        segments.append(item)?;
+    }
+
+    let simplified: Vec<PyChord> = plan
+        .simplified_progression
+        .into_iter()
+        .map(|inner| PyChord { inner })
+        .collect();
+
+    let dict = PyDict::new(py);
+    dict.set_item("player_level", skill_level_name(plan.player_level))?;
+    dict.set_item("overall_score", plan.overall_score)?;
+    dict.set_item("segments", segments)?;
+    dict.set_item("simplified_progression", simplified)?;
+    dict.set_item("estimated_minutes", plan.estimated_minutes)?;
+    Ok(dict.into())
+}
+
+/// Python form of a progression rewrite: a dict with the rewritten
+/// `progression`, `changed` indices, scores and the assessed `skill_level`
+fn to_py_progression_rewrite(py: Python, rewrite: ProgressionRewrite) -> PyResult<Py<PyDict>> {
+    let progression: Vec<PyChord> = rewrite
+        .progression
+        .into_iter()
+        .map(|inner| PyChord { inner })
+        .collect();
+
+    let dict = PyDict::new(py);
+    dict.set_item("progression", progression)?;
+    dict.set_item("changed", rewrite.changed)?;
+    dict.set_item("original_score", rewrite.original_score)?;
+    dict.set_item("score", rewrite.score)?;
+    dict.set_item("skill_level", skill_level_name(rewrite.skill_level))?;
+    dict.set_item("reached_target", rewrite.reached_target)?;
+    Ok(dict.into())
+}
+
+/// Python form of a valence estimate: a dict with `valence`, `arousal` and per-chord `chords`
+fn to_py_valence_estimate(py: Python, estimate: ValenceEstimate) -> PyResult<Py<PyDict>> {
+    let chords = PyList::empty(py);
+    for emotion in estimate.chords {
+        let item = PyDict::new(py);
+        item.set_item(
+            "chord",
+            PyChord {
+                inner: emotion.chord,
+            },
+        )?;
+        item.set_item("valence", emotion.valence)?;
+        item.set_item("arousal", emotion.arousal)?;
+        chords.append(item)?;
+    }
+
+    let dict = PyDict::new(py);
+    dict.set_item("valence", estimate.valence)?;
+    dict.set_item("arousal", estimate.arousal)?;
+    dict.set_item("chords", chords)?;
+    Ok(dict.into())
+}
+
+/// Display name of a skill level
+fn skill_level_name(level: SkillLevel) -> &'static str {
+    match level {
+        SkillLevel::Beginner => "Beginner",
+        SkillLevel::Intermediate => "Intermediate",
+        SkillLevel::Advanced => "Advanced",
+        SkillLevel::Expert => "Expert",
+    }
+}
+
+/// Skill level from its display name
+fn parse_skill_level(name: &str) -> PyResult<SkillLevel> {
+    match name {
+        "Beginner" => Ok(SkillLevel::Beginner),
+        "Intermediate" => Ok(SkillLevel::Intermediate),
+        "Advanced" => Ok(SkillLevel::Advanced),
+        "Expert" => Ok(SkillLevel::Expert),
+        _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Invalid skill level: {}",
+            name
+        ))),
+    }
+}
+
+fn parse_genre(name: &str) -> PyResult<Genre> {
+    Genre::from_name(name)
+        .ok_or_else(|| pyo3::exceptions::PyValueError::new_err(format!("Invalid genre: {}", name)))
+}
+
+/// Match mode as named in Python: "relative", "absolute" or "both"
+fn match_mode_name(mode: MatchMode) -> &'static str {
+    match mode {
+        MatchMode::Relative => "relative",
+        MatchMode::Absolute => "absolute",
+        MatchMode::Both => "both",
+    }
+}
+
+/// Match mode from its Python name
+fn parse_match_mode(name: &str) -> PyResult<MatchMode> {
+    match name {
+        "relative" => Ok(MatchMode::Relative),
+        "absolute" => Ok(MatchMode::Absolute),
+        "both" => Ok(MatchMode::Both),
+        _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Invalid match mode: {}",
+            name
+        ))),
+    }
+}
+
+/// Budget mode as named in Python: "warn" or "fail"
+fn budget_mode_name(mode: BudgetMode) -> &'static str {
+    match mode {
+        BudgetMode::Warn => "warn",
+        BudgetMode::Fail => "fail",
+    }
+}
+
+/// Budget mode from its Python name
+fn parse_budget_mode(name: &str) -> PyResult<BudgetMode> {
+    match name {
+        "warn" => Ok(BudgetMode::Warn),
+        "fail" => Ok(BudgetMode::Fail),
+        _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Invalid budget mode: {}",
+            name
+        ))),
+    }
+}
+
+fn parse_accompaniment_density(name: &str) -> PyResult<AccompanimentDensity> {
+    match name {
+        "Sparse" => Ok(AccompanimentDensity::Sparse),
+        "Moderate" => Ok(AccompanimentDensity::Moderate),
+        "Dense" => Ok(AccompanimentDensity::Dense),
+        _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Invalid accompaniment density: {}",
+            name
+        ))),
+    }
+}
+
+/// Python form of a groove: a dict of `hits` as (beat, voice, accent) tuples,
+/// the rendered `timeline` and its `tokens`
+fn to_py_groove(py: Python, groove: Groove) -> PyResult<Py<PyDict>> {
+    let hits: Vec<PyDrumHit> = groove
+        .hits
+        .iter()
+        .map(|hit| (hit.beat, format!("{:?}", hit.voice), hit.accent))
+        .collect();
+
+    let dict = PyDict::new(py);
+    dict.set_item("hits", hits)?;
+    dict.set_item("tokens", groove.tokens().to_py_result()?)?;
+    dict.set_item(
+        "timeline",
+        PyTimeline {
+            inner: groove.to_timeline(),
+        },
+    )?;
+    Ok(dict.into())
+}
+
+/// Bulk load report as a dict of `added` and `rejected` tuples
+fn bulk_report_to_py(py: Python, report: BulkAddReport) -> PyResult<Py<PyDict>> {
+    let rejected: Vec<PyPatternRejection> = report
+        .rejected
+        .into_iter()
+        .map(|r| (r.index, r.source_id, r.error.to_string()))
+        .collect();
+    let dict = PyDict::new(py);
+    dict.set_item("added", report.added)?;
+    dict.set_item("rejected", rejected)?;
+    Ok(dict.into())
+}
+
+/// Difficulty profile from its name ("piano", "guitar", "ukulele" or "voice")
+fn parse_difficulty_profile(name: &str) -> PyResult<DifficultyProfile> {
+    DifficultyProfile::from_name(name).ok_or_else(|| {
+        pyo3::exceptions::PyValueError::new_err(format!("Invalid instrument: {}", name))
+    })
+}
+
+/// Python form of a skill band: (name, skill level, minimum score)
+type PySkillBand = (String, String, f64);
+
+/// Labeled difficulty example as passed from Python: chords and target overall score
+type PyLabeledProgression = (Vec<PyChord>, f64);
 
 /// Python wrapper for SuggestionContext
-#[pyclass(name = "SuggestionContext")]
+///
+/// Every field of the Rust struct is exposed as a keyword argument and a
+/// read/write property. Omitted arguments take the struct's defaults: no
+/// scale, position 0.5, valence 0.0, complexity 0.5, no genre weights, a
+/// repetition window of 4, no recent chords and no key tonic.
+#[pyclass(name = "SuggestionContext", module = "composer.composer")]
 #[derive(Clone)]
 pub struct PySuggestionContext {
     pub inner: SuggestionContext,
@@ -24,33 +375,51 @@ impl PySuggestionContext {
     #[new]
     #[pyo3(signature = (
         scale_fingerprint=None,
-        position_in_progression=0.5,
-        target_valence=0.0,
-        complexity_preference=0.5,
+        position_in_progression=None,
+        target_valence=None,
+        complexity_preference=None,
         genre_weights=None,
-        avoid_repetition_within=4
+        avoid_repetition_within=None,
+        recent_chords=None,
+        key_tonic=None
     ))]
+    #[allow(clippy::too_many_arguments)]
     fn new(
         scale_fingerprint: Option<PyScaleFingerprint>,
-        position_in_progression: f64,
-        target_valence: f64,
-        complexity_preference: f64,
+        position_in_progression: Option<f64>,
+        target_valence: Option<f64>,
+        complexity_preference: Option<f64>,
         genre_weights: Option<HashMap<String, f64>>,
-        avoid_repetition_within: usize,
-    ) -> Self {
-        let mut context = SuggestionContext::default();
-
-        context.scale_fingerprint = scale_fingerprint.map(|s| s.inner);
-        context.position_in_progression = position_in_progression;
-        context.target_valence = target_valence;
-        context.complexity_preference = complexity_preference;
-        context.avoid_repetition_within = avoid_repetition_within;
+        avoid_repetition_within: Option<usize>,
+        recent_chords: Option<Vec<PyChord>>,
+        key_tonic: Option<String>,
+    ) -> PyResult<Self> {
+        let mut context = SuggestionContext {
+            scale_fingerprint: scale_fingerprint.map(|s| s.inner),
+            key_tonic: parse_key(key_tonic)?,
+            ..Default::default()
+        };
 
+        if let Some(position) = position_in_progression {
+            context.position_in_progression = position;
+        }
+        if let Some(valence) = target_valence {
+            context.target_valence = valence;
+        }
+        if let Some(complexity) = complexity_preference {
+            context.complexity_preference = complexity;
+        }
         if let Some(weights) = genre_weights {
             context.genre_weights = weights;
         }
+        if let Some(window) = avoid_repetition_within {
+            context.avoid_repetition_within = window;
+        }
+        if let Some(chords) = recent_chords {
+            context.recent_chords = chords.into_iter().map(|c| c.inner).collect();
+        }
 
-        PySuggestionContext { inner: context }
+        Ok(PySuggestionContext { inner: context })
     }
 
     fn add_recent_chord(&mut self, chord: &PyChord) {
@@ -61,6 +430,13 @@ impl PySuggestionContext {
         self.inner.genre_weights.insert(genre, weight);
     }
 
+    #[getter]
+    fn scale_fingerprint(&self) -> Option<PyScaleFingerprint> {
+        self.inner
+            .scale_fingerprint
+            .map(|inner| PyScaleFingerprint { inner })
+    }
+
     #[getter]
     fn position_in_progression(&self) -> f64 {
         self.inner.position_in_progression
@@ -75,10 +451,137 @@ impl PySuggestionContext {
     fn complexity_preference(&self) -> f64 {
         self.inner.complexity_preference
     }
+
+    #[getter]
+    fn genre_weights(&self) -> HashMap<String, f64> {
+        self.inner.genre_weights.clone()
+    }
+
+    #[getter]
+    fn avoid_repetition_within(&self) -> usize {
+        self.inner.avoid_repetition_within
+    }
+
+    #[getter]
+    fn recent_chords(&self) -> Vec<PyChord> {
+        self.inner
+            .recent_chords
+            .iter()
+            .map(|c| PyChord { inner: c.clone() })
+            .collect()
+    }
+
+    #[getter]
+    fn key_tonic(&self) -> Option<String> {
+        self.inner.key_tonic.map(|key| key.to_string())
+    }
+
+    // Setters
+    #[setter]
+    fn set_scale_fingerprint(&mut self, scale_fingerprint: Option<PyScaleFingerprint>) {
+        self.inner.scale_fingerprint = scale_fingerprint.map(|s| s.inner);
+    }
+
+    #[setter]
+    fn set_position_in_progression(&mut self, position: f64) {
+        self.inner.position_in_progression = position;
+    }
+
+    #[setter]
+    fn set_target_valence(&mut self, valence: f64) {
+        self.inner.target_valence = valence;
+    }
+
+    #[setter]
+    fn set_complexity_preference(&mut self, complexity: f64) {
+        self.inner.complexity_preference = complexity;
+    }
+
+    #[setter]
+    fn set_genre_weights(&mut self, weights: HashMap<String, f64>) {
+        self.inner.genre_weights = weights;
+    }
+
+    #[setter]
+    fn set_avoid_repetition_within(&mut self, window: usize) {
+        self.inner.avoid_repetition_within = window;
+    }
+
+    #[setter]
+    fn set_recent_chords(&mut self, chords: Vec<PyChord>) {
+        self.inner.recent_chords = chords.into_iter().map(|c| c.inner).collect();
+    }
+
+    #[setter]
+    fn set_key_tonic(&mut self, key_tonic: Option<String>) -> PyResult<()> {
+        self.inner.key_tonic = parse_key(key_tonic)?;
+        Ok(())
+    }
+
+    fn __repr__(&self) -> String {
+        let scale = match &self.inner.scale_fingerprint {
+            Some(scale) => format!(
+                "ScaleFingerprint({:?})",
+                scale.scale_degrees().into_iter().collect::<Vec<u8>>()
+            ),
+            None => "None".to_string(),
+        };
+        format!(
+            "SuggestionContext(scale_fingerprint={}, position_in_progression={:?}, \
+             target_valence={:?}, complexity_preference={:?}, genre_weights={:?}, \
+             avoid_repetition_within={}, recent_chords={}, key_tonic={})",
+            scale,
+            self.inner.position_in_progression,
+            self.inner.target_valence,
+            self.inner.complexity_preference,
+            self.inner.genre_weights,
+            self.inner.avoid_repetition_within,
+            self.inner.recent_chords.len(),
+            match &self.inner.key_tonic {
+                Some(key_tonic) => format!("{:?}", key_tonic.to_string()),
+                None => "None".to_string(),
+            }
+        )
+    }
+
+    fn __eq__(&self, other: &PySuggestionContext) -> bool {
+        self.inner == other.inner
+    }
+
+    fn __ne__(&self, other: &PySuggestionContext) -> bool {
+        self.inner != other.inner
+    }
+
+    fn __getstate__(&self, py: Python) -> PyResult<Py<PyBytes>> {
+        encode_state(py, &self.inner)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        self.inner = decode_state(state)?;
+        Ok(())
+    }
+}
+
+impl PySuggestionContext {
+    /// Apply a keyword override by field name; returns false for unknown names
+    fn apply_override(&mut self, name: &str, value: &Bound<'_, PyAny>) -> PyResult<bool> {
+        match name {
+            "scale_fingerprint" => self.set_scale_fingerprint(value.extract()?),
+            "position_in_progression" => self.set_position_in_progression(value.extract()?),
+            "target_valence" => self.set_target_valence(value.extract()?),
+            "complexity_preference" => self.set_complexity_preference(value.extract()?),
+            "genre_weights" => self.set_genre_weights(value.extract()?),
+            "avoid_repetition_within" => self.set_avoid_repetition_within(value.extract()?),
+            "recent_chords" => self.set_recent_chords(value.extract()?),
+            "key_tonic" => self.set_key_tonic(value.extract()?)?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
 }
 
 /// Python wrapper for SuggestionConfig
-#[pyclass(name = "SuggestionConfig")]
+#[pyclass(name = "SuggestionConfig", module = "composer.composer")]
 #[derive(Clone)]
 pub struct PySuggestionConfig {
     pub inner: SuggestionConfig,
@@ -93,8 +596,14 @@ impl PySuggestionConfig {
         search_depth=None,
         use_probabilistic=false,
         temperature=1.0,
-        enable_context_weighting=true
+        enable_context_weighting=true,
+        match_mode="relative",
+        diversity_threshold=None,
+        suggest_inversions=false,
+        chromaticism=0.0,
+        novelty_weight=0.0
     ))]
+    #[allow(clippy::too_many_arguments)]
     fn new(
         max_suggestions: Option<usize>,
         min_confidence: Option<f64>,
@@ -102,8 +611,16 @@ impl PySuggestionConfig {
         use_probabilistic: bool,
         temperature: f64,
         enable_context_weighting: bool,
-    ) -> Self {
-        let mut config = SuggestionConfig::default();
+        match_mode: &str,
+        diversity_threshold: Option<f64>,
+        suggest_inversions: bool,
+        chromaticism: f64,
+        novelty_weight: f64,
+    ) -> PyResult<Self> {
+        let mut config = SuggestionConfig {
+            match_mode: parse_match_mode(match_mode)?,
+            ..Default::default()
+        };
 
         if let Some(max) = max_suggestions {
             config.max_suggestions = max;
@@ -114,12 +631,18 @@ impl PySuggestionConfig {
         if let Some(depth) = search_depth {
             config.search_depth = depth;
         }
+        if let Some(threshold) = diversity_threshold {
+            config.diversity_threshold = threshold;
+        }
 
         config.use_probabilistic = use_probabilistic;
         config.temperature = temperature;
         config.enable_context_weighting = enable_context_weighting;
+        config.suggest_inversions = suggest_inversions;
+        config.chromaticism = chromaticism;
+        config.novelty_weight = novelty_weight;
 
-        PySuggestionConfig { inner: config }
+        Ok(PySuggestionConfig { inner: config })
     }
 
     #[getter]
@@ -132,14 +655,182 @@ impl PySuggestionConfig {
         self.inner.min_confidence
     }
 
+    #[getter]
+    fn search_depth(&self) -> usize {
+        self.inner.search_depth
+    }
+
+    #[getter]
+    fn use_probabilistic(&self) -> bool {
+        self.inner.use_probabilistic
+    }
+
     #[getter]
     fn temperature(&self) -> f64 {
         self.inner.temperature
     }
+
+    #[getter]
+    fn enable_context_weighting(&self) -> bool {
+        self.inner.enable_context_weighting
+    }
+
+    /// How stored patterns match: "relative", "absolute" (only patterns in the
+    /// context's key tonic) or "both"
+    #[getter]
+    fn match_mode(&self) -> &'static str {
+        match_mode_name(self.inner.match_mode)
+    }
+
+    /// Share of each pick decided by score rather than variety; 1.0 returns
+    /// the plain top-scored suggestions
+    #[getter]
+    fn diversity_threshold(&self) -> f64 {
+        self.inner.diversity_threshold
+    }
+
+    /// Whether suggestions carry a recommended inversion for smooth bass motion
+    #[getter]
+    fn suggest_inversions(&self) -> bool {
+        self.inner.suggest_inversions
+    }
+
+    /// Weight of chromatic harmonization candidates (applied dominants, modal
+    /// mixture, the Neapolitan); 0.0 keeps candidates diatonic
+    #[getter]
+    fn chromaticism(&self) -> f64 {
+        self.inner.chromaticism
+    }
+
+    /// Share of the score given to how rarely the corpus takes a continuation;
+    /// 0.0 ranks common continuations first
+    #[getter]
+    fn novelty_weight(&self) -> f64 {
+        self.inner.novelty_weight
+    }
+
+    // Setters
+    #[setter]
+    fn set_max_suggestions(&mut self, max_suggestions: usize) {
+        self.inner.max_suggestions = max_suggestions;
+    }
+
+    #[setter]
+    fn set_min_confidence(&mut self, min_confidence: f64) {
+        self.inner.min_confidence = min_confidence;
+    }
+
+    #[setter]
+    fn set_search_depth(&mut self, search_depth: usize) {
+        self.inner.search_depth = search_depth;
+    }
+
+    #[setter]
+    fn set_use_probabilistic(&mut self, use_probabilistic: bool) {
+        self.inner.use_probabilistic = use_probabilistic;
+    }
+
+    #[setter]
+    fn set_temperature(&mut self, temperature: f64) {
+        self.inner.temperature = temperature;
+    }
+
+    #[setter]
+    fn set_enable_context_weighting(&mut self, enabled: bool) {
+        self.inner.enable_context_weighting = enabled;
+    }
+
+    #[setter]
+    fn set_match_mode(&mut self, match_mode: &str) -> PyResult<()> {
+        self.inner.match_mode = parse_match_mode(match_mode)?;
+        Ok(())
+    }
+
+    #[setter]
+    fn set_diversity_threshold(&mut self, diversity_threshold: f64) {
+        self.inner.diversity_threshold = diversity_threshold;
+    }
+
+    #[setter]
+    fn set_suggest_inversions(&mut self, suggest_inversions: bool) {
+        self.inner.suggest_inversions = suggest_inversions;
+    }
+
+    #[setter]
+    fn set_chromaticism(&mut self, chromaticism: f64) {
+        self.inner.chromaticism = chromaticism;
+    }
+
+    #[setter]
+    fn set_novelty_weight(&mut self, novelty_weight: f64) {
+        self.inner.novelty_weight = novelty_weight;
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "SuggestionConfig(max_suggestions={}, min_confidence={:?}, search_depth={}, \
+             use_probabilistic={}, temperature={:?}, enable_context_weighting={}, \
+             match_mode={:?}, diversity_threshold={:?}, suggest_inversions={}, \
+             chromaticism={:?}, novelty_weight={:?})",
+            self.inner.max_suggestions,
+            self.inner.min_confidence,
+            self.inner.search_depth,
+            if self.inner.use_probabilistic {
+                "True"
+            } else {
+                "False"
+            },
+            self.inner.temperature,
+            if self.inner.enable_context_weighting {
+                "True"
+            } else {
+                "False"
+            },
+            match_mode_name(self.inner.match_mode),
+            self.inner.diversity_threshold,
+            if self.inner.suggest_inversions {
+                "True"
+            } else {
+                "False"
+            },
+            self.inner.chromaticism,
+            self.inner.novelty_weight
+        )
+    }
+
+    fn __getstate__(&self, py: Python) -> PyResult<Py<PyBytes>> {
+        encode_state(py, &self.inner)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        self.inner = decode_state(state)?;
+        Ok(())
+    }
+}
+
+impl PySuggestionConfig {
+    /// Apply a keyword override by field name; returns false for unknown names
+    fn apply_override(&mut self, name: &str, value: &Bound<'_, PyAny>) -> PyResult<bool> {
+        match name {
+            "max_suggestions" => self.set_max_suggestions(value.extract()?),
+            "min_confidence" => self.set_min_confidence(value.extract()?),
+            "search_depth" => self.set_search_depth(value.extract()?),
+            "use_probabilistic" => self.set_use_probabilistic(value.extract()?),
+            "temperature" => self.set_temperature(value.extract()?),
+            "enable_context_weighting" => self.set_enable_context_weighting(value.extract()?),
+            "match_mode" => self.set_match_mode(value.extract()?)?,
+            "diversity_threshold" => self.set_diversity_threshold(value.extract()?),
+            "suggest_inversions" => self.set_suggest_inversions(value.extract()?),
+            "chromaticism" => self.set_chromaticism(value.extract()?),
+            "novelty_weight" => self.set_novelty_weight(value.extract()?),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
 }
 
 /// Python wrapper for ChordSuggestion
-#[pyclass(name = "ChordSuggestion")]
+#[pyclass(name = "ChordSuggestion", module = "composer.composer")]
 #[derive(Clone)]
 pub struct PyChordSuggestion {
     pub inner: ChordSuggestion,
@@ -147,6 +838,14 @@ pub struct PyChordSuggestion {
 
 #[pymethods]
 impl PyChordSuggestion {
+    /// Create an empty suggestion; used when unpickling
+    #[new]
+    fn new() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+
     #[getter]
     fn chord(&self) -> PyChord {
         PyChord {
@@ -184,16 +883,45 @@ impl PyChordSuggestion {
         &self.inner.reasoning
     }
 
+    /// Scale branch the suggestion came from; `None` for the whole corpus
+    #[getter]
+    fn branch(&self) -> Option<&str> {
+        self.inner.pattern_info.branch.as_deref()
+    }
+
+    /// Inversion with the smoothest bass motion from the query's last chord;
+    /// `None` unless the config asked for inversions
+    #[getter]
+    fn recommended_inversion(&self) -> Option<u8> {
+        self.inner.recommended_inversion
+    }
+
+    /// Typical length in beats of the chord after the matched context, learned
+    /// from duration-weighted timeline training; `None` when no timing was seen
+    #[getter]
+    fn suggested_duration(&self) -> Option<f64> {
+        self.inner.suggested_duration
+    }
+
     fn __repr__(&self) -> String {
         format!(
             "ChordSuggestion(chord={}, confidence={:.3}, score={:.3})",
             self.inner.chord, self.inner.confidence, self.inner.weighted_score
         )
     }
+
+    fn __getstate__(&self, py: Python) -> PyResult<Py<PyBytes>> {
+        encode_state(py, &self.inner)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        self.inner = decode_state(state)?;
+        Ok(())
+    }
 }
 
 /// Python wrapper for DifficultyAssessment
-#[pyclass(name = "DifficultyAssessment")]
+#[pyclass(name = "DifficultyAssessment", module = "composer.composer")]
 #[derive(Clone)]
 pub struct PyDifficultyAssessment {
     pub inner: DifficultyAssessment,
@@ -201,6 +929,14 @@ pub struct PyDifficultyAssessment {
 
 #[pymethods]
 impl PyDifficultyAssessment {
+    /// Create an empty assessment; used when unpickling
+    #[new]
+    fn new() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+
     #[getter]
     fn overall_score(&self) -> f64 {
         self.inner.overall_score
@@ -233,12 +969,47 @@ impl PyDifficultyAssessment {
 
     #[getter]
     fn skill_level(&self) -> String {
-        match self.inner.skill_level {
-            SkillLevel::Beginner => "Beginner".to_string(),
-            SkillLevel::Intermediate => "Intermediate".to_string(),
-            SkillLevel::Advanced => "Advanced".to_string(),
-            SkillLevel::Expert => "Expert".to_string(),
-        }
+        skill_level_name(self.inner.skill_level).to_string()
+    }
+
+    /// Bounds `(lower, upper)` expected to contain the overall score
+    #[getter]
+    fn score_interval(&self) -> (f64, f64) {
+        (
+            self.inner.score_interval.lower,
+            self.inner.score_interval.upper,
+        )
+    }
+
+    /// Share of the minimum evidence the progression provides (0.0-1.0)
+    #[getter]
+    fn evidence(&self) -> f64 {
+        self.inner.evidence
+    }
+
+    /// Whether the progression was too short for a stable score
+    #[getter]
+    fn insufficient_data(&self) -> bool {
+        self.inner.insufficient_data
+    }
+
+    /// Name of the calibrated skill band, e.g. "Late Intermediate"
+    #[getter]
+    fn skill_band(&self) -> String {
+        self.inner.skill_band.clone()
+    }
+
+    /// Share of the calibration corpus this progression is harder than (0-99),
+    /// or None when the engine is not calibrated
+    #[getter]
+    fn percentile(&self) -> Option<u8> {
+        self.inner.percentile
+    }
+
+    /// Instrument the assessment was made for, e.g. "guitar", or None
+    #[getter]
+    fn instrument(&self) -> Option<&'static str> {
+        self.inner.instrument.map(|profile| profile.name())
     }
 
     #[getter]
@@ -251,200 +1022,954 @@ impl PyDifficultyAssessment {
         self.inner.factors.extended_harmonies
     }
 
-    fn __repr__(&self) -> String {
-        format!(
-            "DifficultyAssessment(score={:.1}, skill_level='{}', confidence={:.3})",
-            self.inner.overall_score,
-            self.skill_level(),
-            self.inner.confidence
-        )
+    #[getter]
+    fn time_signature_changes(&self) -> usize {
+        self.inner.factors.time_signature_changes
     }
-}
-
-/// Python wrapper for ProgressionAnalysis
-#[pyclass(name = "ProgressionAnalysis")]
-#[derive(Clone)]
-pub struct PyProgressionAnalysis {
-    pub inner: ProgressionAnalysis,
-}
 
-#[pymethods]
-impl PyProgressionAnalysis {
+    /// Each chord's contribution as dicts with `index`, `complexity`,
+    /// `voice_leading_cost` and `novelty`
     #[getter]
-    fn voice_leading_quality(&self) -> f64 {
+    fn per_chord_breakdown(&self, py: Python) -> PyResult<Py<PyList>> {
+        let list = PyList::empty(py);
+        for contribution in &self.inner.per_chord_breakdown {
+            let item = PyDict::new(py);
+            item.set_item("index", contribution.index)?;
+            item.set_item("complexity", contribution.complexity)?;
+            item.set_item("voice_leading_cost", contribution.voice_leading_cost)?;
+            item.set_item("novelty", contribution.novelty)?;
+            list.append(item)?;
+        }
+        Ok(list.into())
+    }
+
+    /// Melody analysis the melodic complexity was measured from, or None when
+    /// it was estimated from chord roots
+    #[getter]
+    fn melody(&self, py: Python) -> PyResult<Option<Py<PyDict>>> {
+        self.inner
+            .melody
+            .as_ref()
+            .map(|melody| to_py_melodic_analysis(py, melody))
+            .transpose()
+    }
+
+    /// Structured explanation of the score as nested dicts and lists:
+    /// `top_factors` (feature, value, weight, share) and `reference` (skill_level,
+    /// references, reference_score, score_difference and per-feature comparisons)
+    #[getter]
+    fn explanation(&self, py: Python) -> PyResult<Py<PyDict>> {
+        let explanation = &self.inner.explanation;
+
+        let top_factors = PyList::empty(py);
+        for factor in &explanation.top_factors {
+            let item = PyDict::new(py);
+            item.set_item("feature", &factor.feature)?;
+            item.set_item("value", factor.value)?;
+            item.set_item("weight", factor.weight)?;
+            item.set_item("share", factor.share)?;
+            top_factors.append(item)?;
+        }
+
+        let reference = &explanation.reference;
+        let features = PyList::empty(py);
+        for comparison in &reference.features {
+            let item = PyDict::new(py);
+            item.set_item("feature", &comparison.feature)?;
+            item.set_item("value", comparison.value)?;
+            item.set_item("reference_mean", comparison.reference_mean)?;
+            item.set_item("difference", comparison.difference)?;
+            features.append(item)?;
+        }
+        let reference_dict = PyDict::new(py);
+        reference_dict.set_item("skill_level", skill_level_name(reference.skill_level))?;
+        reference_dict.set_item("references", &reference.references)?;
+        reference_dict.set_item("reference_score", reference.reference_score)?;
+        reference_dict.set_item("score_difference", reference.score_difference)?;
+        reference_dict.set_item("features", features)?;
+
+        let dict = PyDict::new(py);
+        dict.set_item("top_factors", top_factors)?;
+        dict.set_item("reference", reference_dict)?;
+        Ok(dict.into())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "DifficultyAssessment(score={:.1}, skill_level='{}', confidence={:.3})",
+            self.inner.overall_score,
+            self.skill_level(),
+            self.inner.confidence
+        )
+    }
+
+    fn __getstate__(&self, py: Python) -> PyResult<Py<PyBytes>> {
+        encode_state(py, &self.inner)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        self.inner = decode_state(state)?;
+        Ok(())
+    }
+}
+
+/// Convert a harmonic rhythm analysis to a dict with `avg_chord_duration`,
+/// `regularity`, `duration_variance`, `trend` and `rhythm_patterns`
+fn to_py_harmonic_rhythm(py: Python, rhythm: &HarmonicRhythm) -> PyResult<Py<PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("avg_chord_duration", rhythm.avg_chord_duration)?;
+    dict.set_item("regularity", rhythm.regularity)?;
+    dict.set_item("duration_variance", rhythm.duration_variance)?;
+    dict.set_item("trend", format!("{:?}", rhythm.trend))?;
+    dict.set_item("rhythm_patterns", &rhythm.rhythm_patterns)?;
+    Ok(dict.into())
+}
+
+/// Python wrapper for ProgressionAnalysis
+#[pyclass(name = "ProgressionAnalysis", module = "composer.composer")]
+#[derive(Clone)]
+pub struct PyProgressionAnalysis {
+    pub inner: ProgressionAnalysis,
+}
+
+#[pymethods]
+impl PyProgressionAnalysis {
+    /// Create an empty analysis; used when unpickling
+    #[new]
+    fn new() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+
+    #[getter]
+    fn voice_leading_quality(&self) -> f64 {
         self.inner.voice_leading_quality
     }
 
-    #[getter]
-    fn improvements(&self, py: Python) -> PyResult<Py<PyList>> {
-        let list = PyList::new(py, &self.inner.improvements)?;
-        Ok(list.into())
+    /// Detected key centers as dicts with `key` (e.g. `"C"`), `confidence`,
+    /// `confidence_interval` and `chord_range`
+    #[getter]
+    fn key_centers(&self, py: Python) -> PyResult<Py<PyList>> {
+        let list = PyList::empty(py);
+        for key in &self.inner.key_centers {
+            let item = PyDict::new(py);
+            item.set_item("key", key.key.to_string())?;
+            item.set_item("confidence", key.confidence)?;
+            item.set_item(
+                "confidence_interval",
+                (key.confidence_interval.lower, key.confidence_interval.upper),
+            )?;
+            item.set_item("chord_range", key.chord_range)?;
+            list.append(item)?;
+        }
+        Ok(list.into())
+    }
+
+    #[getter]
+    fn improvements(&self, py: Python) -> PyResult<Py<PyList>> {
+        let list = PyList::new(py, &self.inner.improvements)?;
+        Ok(list.into())
+    }
+
+    /// Harmonic rhythm as a dict; see `AiEngine.analyze_harmonic_rhythm`
+    #[getter]
+    fn harmonic_rhythm(&self, py: Python) -> PyResult<Py<PyDict>> {
+        to_py_harmonic_rhythm(py, &self.inner.harmonic_rhythm)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ProgressionAnalysis(voice_leading={:.3}, improvements={})",
+            self.inner.voice_leading_quality,
+            self.inner.improvements.len()
+        )
+    }
+
+    fn __getstate__(&self, py: Python) -> PyResult<Py<PyBytes>> {
+        encode_state(py, &self.inner)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        self.inner = decode_state(state)?;
+        Ok(())
+    }
+}
+
+/// Python wrapper for BassHarmonization
+#[pyclass(name = "BassHarmonization", module = "composer.composer")]
+#[derive(Clone)]
+pub struct PyBassHarmonization {
+    pub inner: BassHarmonization,
+}
+
+#[pymethods]
+impl PyBassHarmonization {
+    /// Create an empty harmonization; used when unpickling
+    #[new]
+    fn new() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+
+    #[getter]
+    fn bass_notes(&self, py: Python) -> PyResult<Py<PyList>> {
+        let list = PyList::new(py, &self.inner.bass_notes)?;
+        Ok(list.into())
+    }
+
+    #[getter]
+    fn rhythm(&self, py: Python) -> PyResult<Py<PyList>> {
+        let list = PyList::new(py, &self.inner.rhythm)?;
+        Ok(list.into())
+    }
+
+    #[getter]
+    fn confidence(&self) -> f64 {
+        self.inner.confidence
+    }
+
+    #[getter]
+    fn style(&self) -> String {
+        match self.inner.style {
+            BassStyle::Root => "Root".to_string(),
+            BassStyle::Alternating => "Alternating".to_string(),
+            BassStyle::Walking => "Walking".to_string(),
+            BassStyle::Arpeggiated => "Arpeggiated".to_string(),
+            BassStyle::Rhythmic => "Rhythmic".to_string(),
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "BassHarmonization(style='{}', notes={}, confidence={:.3})",
+            self.style(),
+            self.inner.bass_notes.len(),
+            self.inner.confidence
+        )
+    }
+
+    fn __getstate__(&self, py: Python) -> PyResult<Py<PyBytes>> {
+        encode_state(py, &self.inner)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        self.inner = decode_state(state)?;
+        Ok(())
+    }
+}
+
+/// Python wrapper for the main AI Engine
+#[pyclass(name = "AiEngine", module = "composer.composer")]
+pub struct PyAiEngine {
+    inner: AiEngine,
+}
+
+#[pymethods]
+impl PyAiEngine {
+    #[new]
+    #[pyo3(signature = (max_memory_mb=None, enable_monitoring=true))]
+    fn new(max_memory_mb: Option<u32>, enable_monitoring: bool) -> Self {
+        let mut config = AiEngineConfig::default();
+
+        if let Some(memory) = max_memory_mb {
+            config.max_memory_mb = memory;
+        }
+        config.enable_performance_monitoring = enable_monitoring;
+
+        let engine = AiEngine::new(config);
+        PyAiEngine { inner: engine }
+    }
+
+    /// Initialize the engine with training patterns
+    fn initialize(&self, training_patterns: Vec<PyTrainingPattern>) -> PyResult<()> {
+        let patterns = to_training_patterns(training_patterns)?;
+
+        self.inner.initialize(patterns).to_py_result()
+    }
+
+    /// Initialize the engine from chord tokens resolved through a token library
+    ///
+    /// Each pattern is `(tokens, source_id, key_tonic)`.
+    fn initialize_from_tokens(
+        &self,
+        library: &PyTokenLibrary,
+        token_patterns: Vec<PyTokenPattern>,
+    ) -> PyResult<()> {
+        let token_patterns = token_patterns
+            .into_iter()
+            .map(|(tokens, source_id, key_tonic)| Ok((tokens, source_id, parse_key(key_tonic)?)))
+            .collect::<PyResult<Vec<TokenPattern>>>()?;
+        self.inner
+            .initialize_from_tokens(&library.inner, token_patterns)
+            .to_py_result()
+    }
+
+    /// Check if engine is initialized
+    fn is_initialized(&self) -> bool {
+        self.inner.is_initialized()
+    }
+
+    /// Get context-aware chord progression suggestions.
+    ///
+    /// `context` and `config` default to `SuggestionContext()` and `SuggestionConfig()`.
+    /// Any field of either can also be overridden by keyword, e.g.
+    /// `engine.get_chord_suggestions(chords, target_valence=0.6, max_suggestions=3)`;
+    /// the passed objects are not modified. Unknown keywords raise `TypeError`.
+    #[pyo3(signature = (pattern, context=None, config=None, **overrides))]
+    fn get_chord_suggestions(
+        &self,
+        pattern: Vec<PyChord>,
+        context: Option<&PySuggestionContext>,
+        config: Option<&PySuggestionConfig>,
+        overrides: Option<&Bound<'_, PyDict>>,
+        py: Python,
+    ) -> PyResult<Py<PyList>> {
+        let rust_pattern: Vec<composer_core::Chord> =
+            pattern.into_iter().map(|c| c.inner).collect();
+
+        let mut context = context.cloned().unwrap_or_else(|| PySuggestionContext {
+            inner: SuggestionContext::default(),
+        });
+        let mut config = config.cloned().unwrap_or_else(|| PySuggestionConfig {
+            inner: SuggestionConfig::default(),
+        });
+
+        if let Some(overrides) = overrides {
+            for (key, value) in overrides.iter() {
+                let name: String = key.extract()?;
+                if !context.apply_override(&name, &value)?
+                    && !config.apply_override(&name, &value)?
+                {
+                    return Err(PyTypeError::new_err(format!(
+                        "get_chord_suggestions() got an unexpected keyword argument '{}'",
+                        name
+                    )));
+                }
+            }
+        }
+
+        let suggestions = self
+            .inner
+            .get_chord_suggestions(&rust_pattern, &context.inner, &config.inner)
+            .to_py_result()?;
+
+        let py_suggestions: Vec<PyChordSuggestion> = suggestions
+            .into_iter()
+            .map(|s| PyChordSuggestion { inner: s })
+            .collect();
+
+        let list = PyList::new(py, py_suggestions)?;
+        Ok(list.into())
+    }
+
+    /// Generate the next `n` chords after `seed`
+    ///
+    /// Each chord is sampled from the suggestions for the progression so far at
+    /// `temperature` (0.0 always takes the best), reproducibly for a given
+    /// `random_seed`. With `target_cadence` the last two chords lean toward V-I.
+    /// Returns the chosen suggestions, fewer than `n` when the corpus runs out
+    /// of continuations.
+    #[pyo3(signature = (
+        seed,
+        n,
+        context=None,
+        config=None,
+        temperature=1.0,
+        random_seed=0,
+        target_cadence=true
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn continue_progression(
+        &self,
+        py: Python,
+        seed: Vec<PyChord>,
+        n: usize,
+        context: Option<&PySuggestionContext>,
+        config: Option<&PySuggestionConfig>,
+        temperature: f64,
+        random_seed: u64,
+        target_cadence: bool,
+    ) -> PyResult<Py<PyList>> {
+        let seed: Vec<composer_core::Chord> = seed.into_iter().map(|c| c.inner).collect();
+        let config = ContinuationConfig {
+            suggestion: config.map(|c| c.inner.clone()).unwrap_or_default(),
+            context: context.map(|c| c.inner.clone()).unwrap_or_default(),
+            temperature,
+            seed: random_seed,
+            target_cadence,
+        };
+
+        let continuation = self
+            .inner
+            .continue_progression(&seed, n, &config)
+            .to_py_result()?;
+
+        let py_suggestions: Vec<PyChordSuggestion> = continuation
+            .into_iter()
+            .map(|s| PyChordSuggestion { inner: s })
+            .collect();
+
+        let list = PyList::new(py, py_suggestions)?;
+        Ok(list.into())
+    }
+
+    /// Assess difficulty of a chord progression
+    #[pyo3(signature = (progression, tempo_bpm=None, time_signature=None))]
+    fn assess_difficulty(
+        &self,
+        progression: Vec<PyChord>,
+        tempo_bpm: Option<f64>,
+        time_signature: Option<(u8, u8)>,
+    ) -> PyResult<PyDifficultyAssessment> {
+        let rust_progression: Vec<composer_core::Chord> =
+            progression.into_iter().map(|c| c.inner).collect();
+
+        let assessment = self
+            .inner
+            .assess_difficulty(&rust_progression, tempo_bpm, time_signature)
+            .to_py_result()?;
+
+        Ok(PyDifficultyAssessment { inner: assessment })
+    }
+
+    /// Assess difficulty of the chords of a timeline.
+    ///
+    /// Rhythmic complexity follows the timeline's meter changes and chord
+    /// onsets; `time_signature` applies before the first change.
+    #[pyo3(signature = (timeline, tempo_bpm=None, time_signature=None))]
+    fn assess_timeline_difficulty(
+        &self,
+        timeline: &PyTimeline,
+        tempo_bpm: Option<f64>,
+        time_signature: Option<(u8, u8)>,
+    ) -> PyResult<PyDifficultyAssessment> {
+        let assessment = self
+            .inner
+            .assess_timeline_difficulty(&timeline.inner, tempo_bpm, time_signature)
+            .to_py_result()?;
+
+        Ok(PyDifficultyAssessment { inner: assessment })
+    }
+
+    /// Assess difficulty of a chord progression accompanying a melody.
+    ///
+    /// Melodic complexity is measured from the melody's notes, one beat each,
+    /// instead of estimated from chord roots.
+    #[pyo3(signature = (progression, melody, tempo_bpm=None, time_signature=None))]
+    fn assess_melody_difficulty(
+        &self,
+        progression: Vec<PyChord>,
+        melody: Vec<PyNote>,
+        tempo_bpm: Option<f64>,
+        time_signature: Option<(u8, u8)>,
+    ) -> PyResult<PyDifficultyAssessment> {
+        let rust_progression: Vec<composer_core::Chord> =
+            progression.into_iter().map(|c| c.inner).collect();
+        let rust_melody: Vec<composer_serialization::Note> =
+            melody.into_iter().map(|n| n.inner).collect();
+
+        let assessment = self
+            .inner
+            .assess_melody_difficulty(&rust_progression, &rust_melody, tempo_bpm, time_signature)
+            .to_py_result()?;
+
+        Ok(PyDifficultyAssessment { inner: assessment })
+    }
+
+    /// Measure a melody of notes, one beat each.
+    ///
+    /// Returns a dict with `note_count`, `lowest_pitch`, `highest_pitch`,
+    /// `range`, `tessitura`, `mean_pitch`, `interval_histogram`,
+    /// `contour_entropy`, `rhythm_density` and `complexity`.
+    fn analyze_melody(&self, py: Python, melody: Vec<PyNote>) -> PyResult<Py<PyDict>> {
+        let rust_melody: Vec<composer_serialization::Note> =
+            melody.into_iter().map(|n| n.inner).collect();
+
+        let analysis = self.inner.analyze_melody(&rust_melody).to_py_result()?;
+        to_py_melodic_analysis(py, &analysis)
+    }
+
+    /// Assess difficulty of a chord progression on one instrument.
+    ///
+    /// `instrument` is "piano", "guitar", "ukulele" or "voice"; technical
+    /// complexity is measured from voicings on that instrument.
+    #[pyo3(signature = (progression, instrument, tempo_bpm=None, time_signature=None))]
+    fn assess_instrument_difficulty(
+        &self,
+        progression: Vec<PyChord>,
+        instrument: &str,
+        tempo_bpm: Option<f64>,
+        time_signature: Option<(u8, u8)>,
+    ) -> PyResult<PyDifficultyAssessment> {
+        let profile = parse_difficulty_profile(instrument)?;
+        let rust_progression: Vec<composer_core::Chord> =
+            progression.into_iter().map(|c| c.inner).collect();
+
+        let assessment = self
+            .inner
+            .assess_instrument_difficulty(&rust_progression, tempo_bpm, time_signature, profile)
+            .to_py_result()?;
+
+        Ok(PyDifficultyAssessment { inner: assessment })
+    }
+
+    /// Assess difficulty of a chord progression on every instrument.
+    ///
+    /// Returns a dict from instrument name to `DifficultyAssessment`.
+    #[pyo3(signature = (progression, tempo_bpm=None, time_signature=None))]
+    fn assess_difficulty_by_instrument(
+        &self,
+        py: Python,
+        progression: Vec<PyChord>,
+        tempo_bpm: Option<f64>,
+        time_signature: Option<(u8, u8)>,
+    ) -> PyResult<Py<PyDict>> {
+        let rust_progression: Vec<composer_core::Chord> =
+            progression.into_iter().map(|c| c.inner).collect();
+
+        let assessments = self
+            .inner
+            .assess_difficulty_by_instrument(&rust_progression, tempo_bpm, time_signature)
+            .to_py_result()?;
+
+        let dict = PyDict::new(py);
+        for (profile, assessment) in assessments {
+            dict.set_item(profile.name(), PyDifficultyAssessment { inner: assessment })?;
+        }
+        Ok(dict.into())
+    }
+
+    /// Extract the difficulty model features of a progression.
+    ///
+    /// Values follow the order of `DIFFICULTY_FEATURE_NAMES`.
+    #[pyo3(signature = (progression, tempo_bpm=None, time_signature=None))]
+    fn extract_difficulty_features(
+        &self,
+        progression: Vec<PyChord>,
+        tempo_bpm: Option<f64>,
+        time_signature: Option<(u8, u8)>,
+    ) -> PyResult<Vec<f64>> {
+        let rust_progression: Vec<composer_core::Chord> =
+            progression.into_iter().map(|c| c.inner).collect();
+
+        let features = self
+            .inner
+            .extract_difficulty_features(&rust_progression, tempo_bpm, time_signature)
+            .to_py_result()?;
+
+        Ok(features.values().to_vec())
+    }
+
+    /// Replace the built-in difficulty model with an exported dense model.
+    ///
+    /// `model_json` is the JSON produced by `composer.difficulty_export`. Pass
+    /// `None` to restore the built-in model.
+    #[pyo3(signature = (model_json=None))]
+    fn set_difficulty_model(&self, model_json: Option<&str>) -> PyResult<()> {
+        let model = match model_json {
+            Some(json) => Some(DenseDifficultyModel::from_json(json).to_py_result()?),
+            None => None,
+        };
+        self.inner
+            .set_difficulty_model(model.map(|model| std::sync::Arc::new(model) as _));
+        Ok(())
+    }
+
+    /// Fit the polynomial difficulty model to `(progression, score)` examples.
+    ///
+    /// Scores are target overall difficulties on the 0-10 scale, such as teacher
+    /// grades. The fitted model is installed and returned as JSON for
+    /// `set_polynomial_model`.
+    fn fit_difficulty_model(&self, examples: Vec<PyLabeledProgression>) -> PyResult<String> {
+        let examples: Vec<LabeledProgression> = examples
+            .into_iter()
+            .map(|(progression, score)| (progression.into_iter().map(|c| c.inner).collect(), score))
+            .collect();
+        let model = self.inner.fit_difficulty_model(&examples).to_py_result()?;
+        model.to_json().to_py_result()
+    }
+
+    /// Install a polynomial difficulty model from JSON.
+    ///
+    /// `model_json` is the JSON returned by `fit_difficulty_model` or
+    /// `polynomial_model`. Pass `None` to restore the default coefficients.
+    #[pyo3(signature = (model_json=None))]
+    fn set_polynomial_model(&self, model_json: Option<&str>) -> PyResult<()> {
+        let model = match model_json {
+            Some(json) => PolynomialModel::from_json(json).to_py_result()?,
+            None => PolynomialModel::default(),
+        };
+        self.inner.set_polynomial_model(model).to_py_result()
+    }
+
+    /// Installed polynomial difficulty model as JSON
+    fn polynomial_model(&self) -> PyResult<String> {
+        self.inner.polynomial_model().to_json().to_py_result()
+    }
+
+    /// Calibrate skill bands as `(name, skill_level, min_score)` tuples.
+    ///
+    /// Bands must be ordered by increasing `min_score`. Pass `None` to restore
+    /// the default bands.
+    #[pyo3(signature = (bands=None))]
+    fn set_skill_thresholds(&self, bands: Option<Vec<PySkillBand>>) -> PyResult<()> {
+        let thresholds = match bands {
+            Some(bands) => {
+                let bands = bands
+                    .into_iter()
+                    .map(|(name, level, min_score)| {
+                        Ok(SkillBand::new(name, parse_skill_level(&level)?, min_score))
+                    })
+                    .collect::<PyResult<Vec<_>>>()?;
+                SkillThresholds::new(bands).to_py_result()?
+            },
+            None => SkillThresholds::default(),
+        };
+        self.inner.set_skill_thresholds(thresholds);
+        Ok(())
+    }
+
+    /// Calibrate difficulty percentiles against assessments of a corpus.
+    ///
+    /// Later assessments report in `percentile` the share of the corpus they
+    /// are harder than. Pass `None` to drop the calibration.
+    #[pyo3(signature = (corpus_assessments=None))]
+    fn calibrate_difficulty(
+        &self,
+        corpus_assessments: Option<Vec<PyDifficultyAssessment>>,
+    ) -> PyResult<()> {
+        match corpus_assessments {
+            Some(assessments) => {
+                let assessments: Vec<DifficultyAssessment> =
+                    assessments.into_iter().map(|a| a.inner).collect();
+                self.inner.calibrate_difficulty(&assessments).to_py_result()
+            },
+            None => {
+                self.inner.set_difficulty_calibration(None);
+                Ok(())
+            },
+        }
+    }
+
+    /// Current skill bands as `(name, skill_level, min_score)` tuples
+    fn skill_thresholds(&self) -> Vec<PySkillBand> {
+        self.inner
+            .skill_thresholds()
+            .bands()
+            .iter()
+            .map(|band| {
+                (
+                    band.name.clone(),
+                    skill_level_name(band.level).to_string(),
+                    band.min_score,
+                )
+            })
+            .collect()
     }
 
-    fn __repr__(&self) -> String {
-        format!(
-            "ProgressionAnalysis(voice_leading={:.3}, improvements={})",
-            self.inner.voice_leading_quality,
-            self.inner.improvements.len()
-        )
+    /// Analyze chord progression patterns
+    fn analyze_progression(&self, progression: Vec<PyChord>) -> PyResult<PyProgressionAnalysis> {
+        let rust_progression: Vec<composer_core::Chord> =
+            progression.into_iter().map(|c| c.inner).collect();
+
+        let analysis = self
+            .inner
+            .analyze_progression(&rust_progression)
+            .to_py_result()?;
+
+        Ok(PyProgressionAnalysis { inner: analysis })
     }
-}
 
-/// Python wrapper for BassHarmonization
-#[pyclass(name = "BassHarmonization")]
-#[derive(Clone)]
-pub struct PyBassHarmonization {
-    pub inner: BassHarmonization,
-}
+    /// Estimate per-chord and overall emotional valence and arousal.
+    ///
+    /// Defaults to the major scale when `scale` is not given.
+    #[pyo3(signature = (progression, scale=None, tempo_bpm=None))]
+    fn estimate_valence(
+        &self,
+        py: Python,
+        progression: Vec<PyChord>,
+        scale: Option<PyScaleFingerprint>,
+        tempo_bpm: Option<f64>,
+    ) -> PyResult<Py<PyDict>> {
+        let rust_progression: Vec<composer_core::Chord> =
+            progression.into_iter().map(|c| c.inner).collect();
 
-#[pymethods]
-impl PyBassHarmonization {
-    #[getter]
-    fn bass_notes(&self, py: Python) -> PyResult<Py<PyList>> {
-        let list = PyList::new(py, &self.inner.bass_notes)?;
-        Ok(list.into())
+        let estimate = self
+            .inner
+            .estimate_valence(
+                &rust_progression,
+                scale.as_ref().map(|s| &s.inner),
+                tempo_bpm,
+            )
+            .to_py_result()?;
+
+        to_py_valence_estimate(py, estimate)
     }
 
-    #[getter]
-    fn rhythm(&self, py: Python) -> PyResult<Py<PyList>> {
-        let list = PyList::new(py, &self.inner.rhythm)?;
-        Ok(list.into())
+    /// Analyze harmonic rhythm from `(chord, beats)` pairs.
+    ///
+    /// Returns a dict with `avg_chord_duration` and `duration_variance` (in
+    /// ticks), `regularity` (0-1), `trend` ("Steady", "Accelerating" or
+    /// "Decelerating") and `rhythm_patterns`.
+    fn analyze_harmonic_rhythm(
+        &self,
+        py: Python,
+        chords: Vec<(PyChord, f64)>,
+    ) -> PyResult<Py<PyDict>> {
+        let chords: Vec<ChordDuration> = chords
+            .into_iter()
+            .map(|(chord, beats)| (chord.inner, beats))
+            .collect();
+        let rhythm = self.inner.analyze_harmonic_rhythm(&chords).to_py_result()?;
+        to_py_harmonic_rhythm(py, &rhythm)
     }
 
-    #[getter]
-    fn confidence(&self) -> f64 {
-        self.inner.confidence
+    /// Analyze the chords of a timeline, with harmonic rhythm from their timing
+    fn analyze_timeline_progression(
+        &self,
+        timeline: &PyTimeline,
+    ) -> PyResult<PyProgressionAnalysis> {
+        let analysis = self
+            .inner
+            .analyze_timeline_progression(&timeline.inner)
+            .to_py_result()?;
+        Ok(PyProgressionAnalysis { inner: analysis })
     }
 
-    #[getter]
-    fn style(&self) -> String {
-        match self.inner.style {
-            BassStyle::Root => "Root".to_string(),
-            BassStyle::Alternating => "Alternating".to_string(),
-            BassStyle::Walking => "Walking".to_string(),
-            BassStyle::Arpeggiated => "Arpeggiated".to_string(),
-            BassStyle::Rhythmic => "Rhythmic".to_string(),
+    /// Segment a timeline into labeled phrases and sections
+    fn analyze_form(&self, py: Python, timeline: &PyTimeline) -> PyResult<Py<PyDict>> {
+        let form = self.inner.analyze_form(&timeline.inner).to_py_result()?;
+        to_py_form_analysis(py, &form)
+    }
+
+    /// Measure how statistically unusual a progression is in the training
+    /// corpus.
+    ///
+    /// Returns a dict with overall `novelty` (0-1), `novel` and `transitions`,
+    /// a dict per chord move with the `index` of the chord moved to, its
+    /// `count` and `probability` in the corpus, `novelty` and `novel`.
+    fn compute_novelty(&self, py: Python, progression: Vec<PyChord>) -> PyResult<Py<PyDict>> {
+        let rust_progression: Vec<composer_core::Chord> =
+            progression.into_iter().map(|c| c.inner).collect();
+
+        let report = self
+            .inner
+            .compute_novelty(&rust_progression)
+            .to_py_result()?;
+
+        let transitions = PyList::empty(py);
+        for transition in &report.transitions {
+            let item = PyDict::new(py);
+            item.set_item("index", transition.index)?;
+            item.set_item("count", transition.count)?;
+            item.set_item("probability", transition.probability)?;
+            item.set_item("novelty", transition.novelty)?;
+            item.set_item("novel", transition.novel)?;
+            transitions.append(item)?;
         }
+        let dict = PyDict::new(py);
+        dict.set_item("transitions", transitions)?;
+        dict.set_item("novelty", report.novelty)?;
+        dict.set_item("novel", report.novel)?;
+        Ok(dict.into())
     }
 
-    fn __repr__(&self) -> String {
-        format!(
-            "BassHarmonization(style='{}', notes={}, confidence={:.3})",
-            self.style(),
-            self.inner.bass_notes.len(),
-            self.inner.confidence
-        )
+    /// Rate how smoothly a progression loops from its last chord to its first.
+    ///
+    /// Returns a dict with `wrap_quality` and `internal_quality` (0-1),
+    /// `wrap_count` (occurrences of the wrap-around move in the training
+    /// patterns) and `loops_smoothly`.
+    fn analyze_loop(&self, py: Python, progression: Vec<PyChord>) -> PyResult<Py<PyDict>> {
+        let rust_progression: Vec<composer_core::Chord> =
+            progression.into_iter().map(|c| c.inner).collect();
+
+        let analysis = self.inner.analyze_loop(&rust_progression).to_py_result()?;
+
+        let dict = PyDict::new(py);
+        dict.set_item("wrap_quality", analysis.wrap_quality)?;
+        dict.set_item("internal_quality", analysis.internal_quality)?;
+        dict.set_item("wrap_count", analysis.wrap_count)?;
+        dict.set_item("loops_smoothly", analysis.loops_smoothly)?;
+        Ok(dict.into())
     }
-}
 
-/// Python wrapper for the main AI Engine
-#[pyclass(name = "AiEngine")]
-pub struct PyAiEngine {
-    inner: AiEngine,
-}
+    /// Propose last chords that make a progression loop seamlessly, best first.
+    ///
+    /// Returns dicts with the replacement `chord`, its `wrap_quality` back to
+    /// the first chord and `approach_quality` from the chord before (0-1), and
+    /// the `frequency` of the three chords in a row in the training patterns.
+    fn suggest_turnarounds(&self, py: Python, progression: Vec<PyChord>) -> PyResult<Py<PyList>> {
+        let rust_progression: Vec<composer_core::Chord> =
+            progression.into_iter().map(|c| c.inner).collect();
 
-#[pymethods]
-impl PyAiEngine {
-    #[new]
-    #[pyo3(signature = (max_memory_mb=None, enable_monitoring=true))]
-    fn new(max_memory_mb: Option<u32>, enable_monitoring: bool) -> Self {
-        let mut config = AiEngineConfig::default();
+        let turnarounds = self
+            .inner
+            .suggest_turnarounds(&rust_progression)
+            .to_py_result()?;
 
-        if let Some(memory) = max_memory_mb {
-            config.max_memory_mb = memory;
+        let list = PyList::empty(py);
+        for turnaround in turnarounds {
+            let item = PyDict::new(py);
+            item.set_item(
+                "chord",
+                PyChord {
+                    inner: turnaround.chord,
+                },
+            )?;
+            item.set_item("wrap_quality", turnaround.wrap_quality)?;
+            item.set_item("approach_quality", turnaround.approach_quality)?;
+            item.set_item("frequency", turnaround.frequency)?;
+            list.append(item)?;
         }
-        config.enable_performance_monitoring = enable_monitoring;
-
-        let engine = AiEngine::new(config);
-        PyAiEngine { inner: engine }
+        Ok(list.into())
     }
 
-    /// Initialize the engine with training patterns
-    fn initialize(
+    /// Expand the chords of a timeline into a note-level accompaniment timeline.
+    ///
+    /// `style` is "Block", "Alberti", "Arpeggio" or "Strum"; `density` is
+    /// "Sparse" (quarter notes), "Moderate" (eighths) or "Dense" (sixteenths).
+    /// Chords are read in `scale` (major when not given) on `tonic` (0 = C).
+    #[pyo3(signature = (timeline, style="Block", density="Moderate", scale=None, tonic=0))]
+    fn render_accompaniment(
         &self,
-        training_patterns: Vec<(Vec<PyChord>, String, Option<String>)>,
-    ) -> PyResult<()> {
-        let patterns: Vec<(Vec<composer_core::Chord>, String, Option<String>)> = training_patterns
-            .into_iter()
-            .map(|(chords, id, tonic)| {
-                let rust_chords = chords.into_iter().map(|c| c.inner).collect();
-                (rust_chords, id, tonic)
-            })
-            .collect();
+        timeline: &PyTimeline,
+        style: &str,
+        density: &str,
+        scale: Option<PyScaleFingerprint>,
+        tonic: u8,
+    ) -> PyResult<PyTimeline> {
+        let style = match style {
+            "Block" => AccompanimentStyle::Block,
+            "Alberti" => AccompanimentStyle::Alberti,
+            "Arpeggio" => AccompanimentStyle::Arpeggio,
+            "Strum" => AccompanimentStyle::Strum,
+            _ => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Invalid accompaniment style: {}",
+                    style
+                )))
+            },
+        };
+        let options = AccompanimentOptions {
+            style,
+            density: parse_accompaniment_density(density)?,
+            scale: scale
+                .map(|s| s.inner)
+                .unwrap_or_else(composer_core::ScaleFingerprint::major_scale),
+            tonic,
+        };
 
-        self.inner.initialize(patterns).to_py_result()
+        let accompaniment = self
+            .inner
+            .render_accompaniment(&timeline.inner, &options)
+            .to_py_result()?;
+        Ok(PyTimeline {
+            inner: accompaniment,
+        })
     }
 
-    /// Check if engine is initialized
-    fn is_initialized(&self) -> bool {
-        self.inner.is_initialized()
+    /// Suggest a kick, snare and hi-hat groove for the chords of a timeline.
+    ///
+    /// `density` sets the hi-hat pulse as for `render_accompaniment`;
+    /// `time_signature` applies before the timeline's first meter change (4/4
+    /// when not given). Returns a dict of `hits` as (beat, voice, accent)
+    /// tuples with voice "Kick", "Snare" or "HiHat", the groove as a
+    /// `timeline` of General MIDI drum notes, and its `tokens`.
+    #[pyo3(signature = (timeline, density="Moderate", time_signature=None))]
+    fn suggest_groove(
+        &self,
+        py: Python,
+        timeline: &PyTimeline,
+        density: &str,
+        time_signature: Option<(u8, u8)>,
+    ) -> PyResult<Py<PyDict>> {
+        let defaults = GrooveOptions::default();
+        let options = GrooveOptions {
+            density: parse_accompaniment_density(density)?,
+            time_signature: time_signature.unwrap_or(defaults.time_signature),
+        };
+
+        let groove = self
+            .inner
+            .suggest_groove(&timeline.inner, &options)
+            .to_py_result()?;
+        to_py_groove(py, groove)
     }
 
-    /// Get chord progression suggestions
-    fn get_chord_suggestions(
+    /// Plan practice of a progression for a player at `player_level`.
+    ///
+    /// Returns a dict with `player_level`, `overall_score`, `segments` ranked
+    /// hardest first, `simplified_progression` and `estimated_minutes`.
+    fn generate_practice_plan(
         &self,
-        pattern: Vec<PyChord>,
-        context: &PySuggestionContext,
-        config: &PySuggestionConfig,
         py: Python,
-    ) -> PyResult<Py<PyList>> {
-        let rust_pattern: Vec<composer_core::Chord> =
-            pattern.into_iter().map(|c| c.inner).collect();
+        progression: Vec<PyChord>,
+        player_level: &str,
+    ) -> PyResult<Py<PyDict>> {
+        let rust_progression: Vec<composer_core::Chord> =
+            progression.into_iter().map(|c| c.inner).collect();
 
-        let suggestions = self
+        let plan = self
             .inner
-            .get_chord_suggestions(&rust_pattern, &context.inner, &config.inner)
+            .generate_practice_plan(&rust_progression, parse_skill_level(player_level)?)
             .to_py_result()?;
 
-        let py_suggestions: Vec<PyChordSuggestion> = suggestions
-            .into_iter()
-            .map(|s| PyChordSuggestion { inner: s })
-            .collect();
-
-        let list = PyList::new(py, py_suggestions)?;
-        Ok(list.into())
+        to_py_practice_plan(py, plan)
     }
 
-    /// Assess difficulty of a chord progression
-    #[pyo3(signature = (progression, tempo_bpm=None, time_signature=None))]
-    fn assess_difficulty(
+    /// Simplify a progression toward `target_level`, keeping each chord's function.
+    ///
+    /// Returns a dict with the rewritten `progression`, the `changed` indices,
+    /// `original_score`, `score`, `skill_level` and `reached_target`.
+    fn simplify_progression(
         &self,
+        py: Python,
         progression: Vec<PyChord>,
-        tempo_bpm: Option<f64>,
-        time_signature: Option<(u8, u8)>,
-    ) -> PyResult<PyDifficultyAssessment> {
+        target_level: &str,
+    ) -> PyResult<Py<PyDict>> {
         let rust_progression: Vec<composer_core::Chord> =
             progression.into_iter().map(|c| c.inner).collect();
 
-        let assessment = self
+        let rewrite = self
             .inner
-            .assess_difficulty(&rust_progression, tempo_bpm, time_signature)
+            .simplify_progression(&rust_progression, parse_skill_level(target_level)?)
             .to_py_result()?;
 
-        Ok(PyDifficultyAssessment { inner: assessment })
+        to_py_progression_rewrite(py, rewrite)
     }
 
-    /// Analyze chord progression patterns
-    fn analyze_progression(&self, progression: Vec<PyChord>) -> PyResult<PyProgressionAnalysis> {
+    /// Enrich a progression toward `target_level`, keeping each chord's function.
+    ///
+    /// Returns the same dict as `simplify_progression`.
+    fn enrich_progression(
+        &self,
+        py: Python,
+        progression: Vec<PyChord>,
+        target_level: &str,
+    ) -> PyResult<Py<PyDict>> {
         let rust_progression: Vec<composer_core::Chord> =
             progression.into_iter().map(|c| c.inner).collect();
 
-        let analysis = self
+        let rewrite = self
             .inner
-            .analyze_progression(&rust_progression)
+            .enrich_progression(&rust_progression, parse_skill_level(target_level)?)
             .to_py_result()?;
 
-        Ok(PyProgressionAnalysis { inner: analysis })
+        to_py_progression_rewrite(py, rewrite)
     }
 
     /// Generate bass line harmonization
-    #[pyo3(signature = (progression, style="Root", complexity=0.5, enable_walking=false))]
+    #[pyo3(signature = (progression, style="Root", complexity=0.5, enable_walking=false, swing="Straight", tonic=0))]
     fn harmonize_bass_line(
         &self,
         progression: Vec<PyChord>,
         style: &str,
         complexity: f64,
         enable_walking: bool,
+        swing: &str,
+        tonic: u8,
     ) -> PyResult<PyBassHarmonization> {
         let rust_progression: Vec<composer_core::Chord> =
             progression.into_iter().map(|c| c.inner).collect();
@@ -462,12 +1987,26 @@ impl PyAiEngine {
                 )))
             },
         };
+        let swing = match swing {
+            "Straight" => SwingFeel::Straight,
+            "Triplet" => SwingFeel::Triplet,
+            "Dotted" => SwingFeel::Dotted,
+            _ => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Invalid swing feel: {}",
+                    swing
+                )))
+            },
+        };
 
         let options = BassHarmonizationOptions {
             style: bass_style,
             complexity,
             enable_walking,
             rhythm_pattern: None,
+            tonic: tonic % 12,
+            swing,
+            ..Default::default()
         };
 
         let harmonization = self
@@ -481,20 +2020,103 @@ impl PyAiEngine {
     }
 
     /// Add a training pattern to the engine
+    ///
+    /// The pattern counts `weight` times as much as one of weight 1.0 in
+    /// ranks and suggestion scores.
+    #[pyo3(signature = (pattern, source_id, key_tonic, weight=1.0))]
     fn add_training_pattern(
         &self,
         pattern: Vec<PyChord>,
         source_id: String,
         key_tonic: Option<String>,
+        weight: f64,
     ) -> PyResult<()> {
         let rust_pattern: Vec<composer_core::Chord> =
             pattern.into_iter().map(|c| c.inner).collect();
 
         self.inner
-            .add_training_pattern(&rust_pattern, source_id, key_tonic)
+            .add_weighted_training_pattern(&rust_pattern, source_id, parse_key(key_tonic)?, weight)
             .to_py_result()
     }
 
+    /// Add many training patterns, recalculating ranks once
+    ///
+    /// Every pattern counts `weight` times as much as one of weight 1.0, so a
+    /// canonical corpus can outweigh user-submitted data. Invalid patterns are
+    /// skipped. Returns a dict with the number of patterns `added` and the
+    /// `rejected` ones as `(index, source_id, error)` tuples.
+    #[pyo3(signature = (patterns, weight=1.0))]
+    fn add_patterns_bulk(
+        &self,
+        py: Python,
+        patterns: Vec<PyTrainingPattern>,
+        weight: f64,
+    ) -> PyResult<Py<PyDict>> {
+        let patterns = to_training_patterns(patterns)?
+            .into_iter()
+            .map(|pattern| (pattern, weight));
+        let report = self.inner.add_weighted_patterns_bulk(patterns);
+        bulk_report_to_py(py, report)
+    }
+
+    /// Augment a training corpus with variants of each pattern
+    ///
+    /// Copies patterns into all 12 keys, every inversion, extended and reduced
+    /// chords and each `time_stretch` factor (default `[0.5, 2.0]`); enabled
+    /// transformations compound. Returns the patterns and their variants as
+    /// `(chords, source_id, key_tonic)` tuples, variant sources tagged like
+    /// `"song [inv1 x0.5 Eb]"`; see `augment_patterns`.
+    #[staticmethod]
+    #[pyo3(signature = (patterns, transpose=true, invert=true, vary_extensions=true, time_stretch=None))]
+    fn augment_patterns(
+        patterns: Vec<PyTrainingPattern>,
+        transpose: bool,
+        invert: bool,
+        vary_extensions: bool,
+        time_stretch: Option<Vec<f64>>,
+    ) -> PyResult<Vec<PyTrainingPattern>> {
+        let patterns = to_training_patterns(patterns)?;
+        let defaults = AugmentationConfig::default();
+        let config = AugmentationConfig {
+            transpose,
+            invert,
+            vary_extensions,
+            time_stretch: time_stretch.unwrap_or(defaults.time_stretch),
+        };
+        let augmented = augment_patterns(patterns, &config).to_py_result()?;
+        Ok(augmented
+            .into_iter()
+            .map(|(chords, source_id, key_tonic)| {
+                let chords = chords.into_iter().map(|inner| PyChord { inner }).collect();
+                (chords, source_id, key_tonic.map(|key| key.to_string()))
+            })
+            .collect())
+    }
+
+    /// Add training patterns read from timelines, recalculating ranks once
+    ///
+    /// With `weight_by_duration`, each chord counts in proportion to how long
+    /// it sounds, so passing chords don't dominate the pattern statistics.
+    /// Returns the same dict as `add_patterns_bulk`.
+    #[pyo3(signature = (patterns, weight_by_duration=false))]
+    fn add_timeline_patterns(
+        &self,
+        py: Python,
+        patterns: Vec<PyTimelinePattern>,
+        weight_by_duration: bool,
+    ) -> PyResult<Py<PyDict>> {
+        let patterns = patterns
+            .into_iter()
+            .map(|(timeline, source_id, key_tonic)| {
+                Ok((timeline.inner, source_id, parse_key(key_tonic)?))
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        let report = self
+            .inner
+            .add_timeline_patterns(patterns, weight_by_duration);
+        bulk_report_to_py(py, report)
+    }
+
     /// Get engine performance metrics
     fn get_metrics(&self, py: Python) -> PyResult<Py<PyDict>> {
         let metrics = self.inner.get_metrics();
@@ -506,7 +2128,40 @@ impl PyAiEngine {
         dict.set_item("cache_hit_rate", metrics.cache_hit_rate)?;
         dict.set_item("total_patterns", metrics.total_patterns)?;
         dict.set_item("uptime_seconds", metrics.uptime_seconds)?;
+        dict.set_item("budget_violations", metrics.budget_violations)?;
+
+        Ok(dict.into())
+    }
+
+    /// Set the time limits for suggestion and asset loading calls.
+    ///
+    /// Arguments left as None keep their current values. In "warn" mode (the
+    /// default) slow calls return their results and are counted in the
+    /// `budget_violations` of `get_metrics()`; in "fail" mode they raise.
+    #[pyo3(signature = (chord_lookup_max_ms=None, asset_loading_max_ms=None, mode=None))]
+    fn set_performance_budget(
+        &self,
+        chord_lookup_max_ms: Option<u64>,
+        asset_loading_max_ms: Option<u64>,
+        mode: Option<&str>,
+    ) -> PyResult<()> {
+        let current = self.inner.get_config().performance_budget;
+        self.inner.set_performance_budget(PerformanceBudget {
+            chord_lookup_max_ms: chord_lookup_max_ms.unwrap_or(current.chord_lookup_max_ms),
+            asset_loading_max_ms: asset_loading_max_ms.unwrap_or(current.asset_loading_max_ms),
+            mode: mode.map_or(Ok(current.mode), parse_budget_mode)?,
+            ..current
+        });
+        Ok(())
+    }
 
+    /// Time limits for suggestion and asset loading calls, and the budget mode
+    fn get_performance_budget(&self, py: Python) -> PyResult<Py<PyDict>> {
+        let budget = self.inner.get_config().performance_budget;
+        let dict = PyDict::new(py);
+        dict.set_item("chord_lookup_max_ms", budget.chord_lookup_max_ms)?;
+        dict.set_item("asset_loading_max_ms", budget.asset_loading_max_ms)?;
+        dict.set_item("mode", budget_mode_name(budget.mode))?;
         Ok(dict.into())
     }
 
@@ -515,6 +2170,17 @@ impl PyAiEngine {
         self.inner.clear_caches();
     }
 
+    /// Compact the pattern trie, releasing unused node capacity
+    fn compact_trie(&self, py: Python) -> PyResult<Py<PyDict>> {
+        let stats = self.inner.compact_trie();
+        let dict = PyDict::new(py);
+        dict.set_item("nodes", stats.nodes)?;
+        dict.set_item("bytes_before", stats.bytes_before)?;
+        dict.set_item("bytes_after", stats.bytes_after)?;
+        dict.set_item("bytes_saved", stats.bytes_saved())?;
+        Ok(dict.into())
+    }
+
     /// Validate memory usage
     fn validate_memory_usage(&self) -> PyResult<()> {
         self.inner.validate_memory_usage().to_py_result()
@@ -537,7 +2203,7 @@ impl PyAiEngine {
 
         let suggestions = self
             .inner
-            .get_magic_chord_solutions(&rust_previous, &rust_following, scale, limit)
+            .get_magic_chord_solutions(&rust_previous, &rust_following, parse_mode(scale)?, limit)
             .to_py_result()?;
 
         let py_suggestions: Vec<PyChordSuggestion> = suggestions
@@ -560,7 +2226,11 @@ impl PyAiEngine {
     ) -> PyResult<Py<PyList>> {
         let suggestions = self
             .inner
-            .get_magic_bass_solutions(bass_note, scale, limit)
+            .get_magic_bass_solutions(
+                bass_note.parse::<PitchClass>().to_py_result()?,
+                parse_mode(scale)?,
+                limit,
+            )
             .to_py_result()?;
 
         let py_suggestions: Vec<PyChordSuggestion> = suggestions
@@ -573,17 +2243,28 @@ impl PyAiEngine {
     }
 
     /// Get scale degree harmonization solutions
-    #[pyo3(signature = (scale_degree_bits, scale="major", limit=5))]
+    ///
+    /// A config's `chromaticism` adds applied dominants, borrowed chords and
+    /// the Neapolitan to the candidates.
+    #[pyo3(signature = (scale_degree_bits, scale="major", limit=5, config=None))]
     fn get_harmonize_by_sd_solutions(
         &self,
         scale_degree_bits: u32,
         scale: &str,
         limit: usize,
+        config: Option<&PySuggestionConfig>,
         py: Python,
     ) -> PyResult<Py<PyList>> {
+        let default_config = SuggestionConfig::default();
+        let config = config.map_or(&default_config, |config| &config.inner);
         let suggestions = self
             .inner
-            .get_harmonize_by_sd_solutions(scale_degree_bits, scale, limit)
+            .get_harmonize_by_sd_solutions_with_config(
+                scale_degree_bits,
+                parse_mode(scale)?,
+                limit,
+                config,
+            )
             .to_py_result()?;
 
         let py_suggestions: Vec<PyChordSuggestion> = suggestions
@@ -595,6 +2276,134 @@ impl PyAiEngine {
         Ok(list.into())
     }
 
+    /// Find the trained vocabulary's chords most similar to `chord`.
+    ///
+    /// Returns up to `k` `(chord, similarity, frequency)` tuples, best first, where
+    /// similarity is the cosine similarity of the chords' pitch-content embeddings.
+    #[pyo3(signature = (chord, k=5))]
+    fn similar_chords(&self, chord: &PyChord, k: usize) -> PyResult<Vec<PySimilarChord>> {
+        let similar = self.inner.similar_chords(&chord.inner, k).to_py_result()?;
+        Ok(similar
+            .into_iter()
+            .map(|s| (PyChord { inner: s.chord }, s.similarity, s.frequency))
+            .collect())
+    }
+
+    /// Cosine similarity of two chords by how they are used in the trained corpus.
+    ///
+    /// Chords missing from the engine's embedding table are compared by pitch content.
+    fn chord_similarity(&self, a: &PyChord, b: &PyChord) -> PyResult<f64> {
+        self.inner
+            .chord_similarity(&a.inner, &b.inner)
+            .to_py_result()
+    }
+
+    /// Find the chords used most like `chord` in the trained corpus.
+    ///
+    /// Returns up to `k` `(chord, similarity, frequency)` tuples, best first.
+    #[pyo3(signature = (chord, k=5))]
+    fn nearest_chords(&self, chord: &PyChord, k: usize) -> PyResult<Vec<PySimilarChord>> {
+        let nearest = self.inner.nearest_chords(&chord.inner, k).to_py_result()?;
+        Ok(nearest
+            .into_iter()
+            .map(|s| (PyChord { inner: s.chord }, s.similarity, s.frequency))
+            .collect())
+    }
+
+    /// Suggest valid substitutions for `chord` in `scale`, most common in the corpus first.
+    ///
+    /// Returns `(kind, chord, placement, frequency)` tuples; see `get_substitutions`.
+    fn get_substitution_suggestions(
+        &self,
+        chord: &PyChord,
+        scale: &PyScaleFingerprint,
+    ) -> PyResult<Vec<PySubstitutionSuggestion>> {
+        let suggestions = self
+            .inner
+            .get_substitution_suggestions(&chord.inner, &scale.inner)
+            .to_py_result()?;
+        Ok(suggestions
+            .into_iter()
+            .map(|s| {
+                (
+                    s.substitution.kind.name().to_string(),
+                    PyChord {
+                        inner: s.substitution.chord,
+                    },
+                    s.substitution.placement.name().to_string(),
+                    s.frequency,
+                )
+            })
+            .collect())
+    }
+
+    /// Rewrite `progression` from the `from_genre` idiom into the `to_genre` one.
+    ///
+    /// Genres are "pop", "jazz", "classical", "blues" or "rock", in any case.
+    /// Returns `(progression, intensity, confidence)` tuples, most confident
+    /// first; see `restyle_progression`.
+    fn restyle_progression(
+        &self,
+        progression: Vec<PyChord>,
+        from_genre: &str,
+        to_genre: &str,
+    ) -> PyResult<Vec<PyRestyledProgression>> {
+        let rust_progression: Vec<composer_core::Chord> =
+            progression.into_iter().map(|c| c.inner).collect();
+
+        let alternatives = self
+            .inner
+            .restyle_progression(
+                &rust_progression,
+                parse_genre(from_genre)?,
+                parse_genre(to_genre)?,
+            )
+            .to_py_result()?;
+        Ok(alternatives
+            .into_iter()
+            .map(|alternative| {
+                (
+                    alternative
+                        .progression
+                        .into_iter()
+                        .map(|inner| PyChord { inner })
+                        .collect(),
+                    alternative.intensity,
+                    alternative.confidence,
+                )
+            })
+            .collect())
+    }
+
+    /// Find the stored sources whose progressions are most similar to `progression`.
+    ///
+    /// Returns up to `k` `(source_id, key_tonic, progression, similarity)` tuples,
+    /// best first, with one entry per source.
+    #[pyo3(signature = (progression, k=5))]
+    fn similar_progressions(
+        &self,
+        progression: Vec<PyChord>,
+        k: usize,
+    ) -> PyResult<Vec<PySimilarProgression>> {
+        let rust_progression: Vec<composer_core::Chord> =
+            progression.into_iter().map(|c| c.inner).collect();
+        let similar = self
+            .inner
+            .similar_progressions(&rust_progression, k)
+            .to_py_result()?;
+        Ok(to_py_similar_progressions(similar))
+    }
+
+    /// Recommend other stored sources similar to `source_id` ("more like this").
+    ///
+    /// Returns the same tuples as `similar_progressions`, never including the
+    /// source itself.
+    #[pyo3(signature = (source_id, k=5))]
+    fn more_like_source(&self, source_id: &str, k: usize) -> PyResult<Vec<PySimilarProgression>> {
+        let similar = self.inner.more_like_source(source_id, k).to_py_result()?;
+        Ok(to_py_similar_progressions(similar))
+    }
+
     /// Get average suggestion generation time
     fn avg_suggestion_time_ms(&self) -> f64 {
         self.inner.get_metrics().avg_response_time_ms
@@ -604,4 +2413,29 @@ impl PyAiEngine {
     fn shutdown(&self) -> PyResult<()> {
         self.inner.shutdown().to_py_result()
     }
+
+    /// Pickled engines carry their configuration as JSON and their trained
+    /// patterns as a versioned trie asset, source ids and keys included
+    fn __getstate__(&self, py: Python) -> PyResult<PyEngineState> {
+        let config = serde_json::to_string(&self.inner.get_config()).map_err(|e| {
+            pyo3::exceptions::PyValueError::new_err(format!("Failed to pickle: {}", e))
+        })?;
+        if !self.inner.is_initialized() {
+            return Ok((config, raw_state(py, &[])));
+        }
+        let asset = self.inner.export_trie_asset().to_py_result()?;
+        Ok((config, raw_state(py, &asset)))
+    }
+
+    fn __setstate__(&mut self, state: (String, Vec<u8>)) -> PyResult<()> {
+        let (config, asset) = state;
+        let config: AiEngineConfig = serde_json::from_str(&config).map_err(|e| {
+            pyo3::exceptions::PyValueError::new_err(format!("Failed to unpickle: {}", e))
+        })?;
+        self.inner = AiEngine::new(config);
+        if asset.is_empty() {
+            return Ok(());
+        }
+        self.inner.initialize_from_binary(&asset).to_py_result()
+    }
 }
@@ -1,12 +1,16 @@
 //! Python bindings for Chord data structure
 
 use crate::error::ToPyResult;
+use crate::pickle::{decode_state, encode_state, raw_state};
 use composer_core::{BorrowedScale, Chord};
+use composer_serialization::{
+    chord_from_json, chord_to_json, deserialize_chord, serialize_chord, ChordBinary,
+};
 use pyo3::prelude::*;
-use pyo3::types::PyList;
+use pyo3::types::{PyBytes, PyList};
 
 /// Python wrapper for BorrowedScale
-#[pyclass(name = "BorrowedScale")]
+#[pyclass(name = "BorrowedScale", module = "composer.composer")]
 #[derive(Clone)]
 pub struct PyBorrowedScale {
     pub inner: BorrowedScale,
@@ -36,10 +40,23 @@ impl PyBorrowedScale {
             _ => "BorrowedScale(unknown)".to_string(),
         }
     }
+
+    fn __getnewargs__(&self) -> (String,) {
+        (String::new(),)
+    }
+
+    fn __getstate__(&self, py: Python) -> PyResult<Py<PyBytes>> {
+        encode_state(py, &self.inner)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        self.inner = decode_state(state)?;
+        Ok(())
+    }
 }
 
 /// Python wrapper for Chord
-#[pyclass(name = "Chord")]
+#[pyclass(name = "Chord", module = "composer.composer")]
 #[derive(Clone)]
 pub struct PyChord {
     pub inner: Chord,
@@ -304,6 +321,23 @@ impl PyChord {
         self.inner.expected_tone_count()
     }
 
+    /// Alterations that can be added without a conflict, including present ones
+    fn compatible_alterations(&self) -> Vec<&'static str> {
+        self.inner.compatible_alterations()
+    }
+
+    /// Write the chord as human-readable JSON
+    fn to_json(&self) -> PyResult<String> {
+        chord_to_json(&self.inner).to_py_result()
+    }
+
+    /// Read a chord from JSON; fields left out take their defaults
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        let chord = chord_from_json(json).to_py_result()?;
+        Ok(PyChord { inner: chord })
+    }
+
     // Display
     fn __str__(&self) -> String {
         format!("{}", self.inner)
@@ -324,4 +358,32 @@ impl PyChord {
     fn __ne__(&self, other: &PyChord) -> bool {
         self.inner != other.inner
     }
+
+    fn __getnewargs__(&self) -> (u8, u8) {
+        (1, 5)
+    }
+
+    /// Pickled chords use the 5-byte binary format when it holds every field,
+    /// and chord JSON otherwise
+    fn __getstate__(&self, py: Python) -> PyResult<Py<PyBytes>> {
+        let binary = serialize_chord(&self.inner).to_py_result()?;
+        if deserialize_chord(&binary).is_ok_and(|chord| chord == self.inner) {
+            return Ok(raw_state(py, &binary));
+        }
+        let json = chord_to_json(&self.inner).to_py_result()?;
+        Ok(raw_state(py, json.as_bytes()))
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        self.inner = match ChordBinary::try_from(state) {
+            Ok(binary) => deserialize_chord(&binary).to_py_result()?,
+            Err(_) => {
+                let json = std::str::from_utf8(state).map_err(|e| {
+                    pyo3::exceptions::PyValueError::new_err(format!("Failed to unpickle: {}", e))
+                })?;
+                chord_from_json(json).to_py_result()?
+            },
+        };
+        Ok(())
+    }
 }
@@ -1,21 +1,149 @@
 //! Error handling for Python bindings
+//!
+//! Rust errors are raised as subclasses of `ComposerError` carrying the stable
+//! `code`, the `domain` of the crate that raised them and a `details` dict of
+//! the error's fields. Each subclass also derives from the built-in exception
+//! the bindings raised before codes existed, so `except ValueError` keeps working.
 
-use composer_ai::AiError;
+use composer_ai::{AiError, ComposerError, Severity};
 use composer_core::ChordTheoryError;
 use composer_serialization::SerializationError;
-use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::exceptions::{PyException, PyRuntimeError, PyValueError};
 use pyo3::prelude::*;
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyDict, PyList, PyTuple, PyType};
 
-/// Custom exception for Composer errors
-#[pyclass(extends=pyo3::exceptions::PyException)]
-pub struct PyComposerError;
+/// Exception classes raised for Rust errors
+struct ErrorTypes {
+    /// `ComposerError(Exception)`, the base of every class below
+    base: Py<PyType>,
+    /// `ChordTheoryError(ComposerError, ValueError)`
+    theory: Py<PyType>,
+    /// `SerializationError(ComposerError, RuntimeError)`
+    serialization: Py<PyType>,
+    /// `AiError(ComposerError, ValueError)`
+    ai: Py<PyType>,
+    /// `EngineError(AiError, RuntimeError)`, for critical AI errors
+    engine: Py<PyType>,
+}
 
-#[pymethods]
-impl PyComposerError {
-    #[new]
-    fn new(_message: String) -> Self {
-        PyComposerError
-    }
+static ERROR_TYPES: GILOnceCell<ErrorTypes> = GILOnceCell::new();
+
+/// Create an exception class with the given bases
+fn new_type<'py>(
+    py: Python<'py>,
+    name: &str,
+    doc: &str,
+    bases: &[Bound<'py, PyType>],
+) -> PyResult<Py<PyType>> {
+    let namespace = PyDict::new(py);
+    namespace.set_item("__module__", "composer.composer")?;
+    namespace.set_item("__doc__", doc)?;
+    let class = py
+        .get_type::<PyType>()
+        .call1((name, PyTuple::new(py, bases)?, namespace))?;
+    Ok(class.downcast_into::<PyType>()?.unbind())
+}
+
+fn error_types(py: Python<'_>) -> PyResult<&ErrorTypes> {
+    ERROR_TYPES.get_or_try_init(py, || {
+        let value_error = py.get_type::<PyValueError>();
+        let runtime_error = py.get_type::<PyRuntimeError>();
+        let base = new_type(
+            py,
+            "ComposerError",
+            "Base exception for composer errors.",
+            &[py.get_type::<PyException>()],
+        )?;
+        let ai = new_type(
+            py,
+            "AiError",
+            "Error raised by the AI engine.",
+            &[base.bind(py).clone(), value_error.clone()],
+        )?;
+        Ok(ErrorTypes {
+            theory: new_type(
+                py,
+                "ChordTheoryError",
+                "Invalid chord or music theory argument.",
+                &[base.bind(py).clone(), value_error],
+            )?,
+            serialization: new_type(
+                py,
+                "SerializationError",
+                "Binary, hex or token data could not be encoded or decoded.",
+                &[base.bind(py).clone(), runtime_error.clone()],
+            )?,
+            engine: new_type(
+                py,
+                "EngineError",
+                "Critical AI engine error, such as an uninitialized engine.",
+                &[ai.bind(py).clone(), runtime_error],
+            )?,
+            ai,
+            base,
+        })
+    })
+}
+
+/// Register the exception classes on the module
+pub(crate) fn register_error_types(py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let types = error_types(py)?;
+    m.add("ComposerError", types.base.bind(py))?;
+    m.add("ChordTheoryError", types.theory.bind(py))?;
+    m.add("SerializationError", types.serialization.bind(py))?;
+    m.add("AiError", types.ai.bind(py))?;
+    m.add("EngineError", types.engine.bind(py))?;
+    Ok(())
+}
+
+/// Convert a JSON value from an error's details into a Python object
+fn json_to_py<'py>(py: Python<'py>, value: &serde_json::Value) -> PyResult<Bound<'py, PyAny>> {
+    Ok(match value {
+        serde_json::Value::Null => py.None().into_bound(py),
+        serde_json::Value::Bool(b) => b.into_pyobject(py)?.to_owned().into_any(),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => i.into_pyobject(py)?.into_any(),
+            None => n.as_f64().unwrap_or_default().into_pyobject(py)?.into_any(),
+        },
+        serde_json::Value::String(s) => s.into_pyobject(py)?.into_any(),
+        serde_json::Value::Array(items) => {
+            let list = PyList::empty(py);
+            for item in items {
+                list.append(json_to_py(py, item)?)?;
+            }
+            list.into_any()
+        },
+        serde_json::Value::Object(fields) => {
+            let dict = PyDict::new(py);
+            for (key, field) in fields {
+                dict.set_item(key, json_to_py(py, field)?)?;
+            }
+            dict.into_any()
+        },
+    })
+}
+
+/// Build the Python exception for an error, prefixing its message
+fn build_py_err(py: Python<'_>, error: &ComposerError, prefix: &str) -> PyResult<PyErr> {
+    let types = error_types(py)?;
+    let class = match error {
+        ComposerError::Theory(_) => &types.theory,
+        ComposerError::Serialization(_) => &types.serialization,
+        ComposerError::Ai(e) if e.severity() == Severity::Critical => &types.engine,
+        ComposerError::Ai(_) => &types.ai,
+    };
+    let exception = class.bind(py).call1((format!("{}: {}", prefix, error),))?;
+    exception.setattr("code", error.code())?;
+    exception.setattr("domain", error.domain())?;
+    exception.setattr("details", json_to_py(py, &error.details())?)?;
+    Ok(PyErr::from_value(exception))
+}
+
+/// Raise an error as the matching `ComposerError` subclass
+fn to_py_err(error: impl Into<ComposerError>, prefix: &str) -> PyErr {
+    let error = error.into();
+    Python::with_gil(|py| build_py_err(py, &error, prefix).unwrap_or_else(|e| e))
 }
 
 /// Convert Rust errors to Python exceptions
@@ -25,23 +153,21 @@ pub trait ToPyResult<T> {
 
 impl<T> ToPyResult<T> for Result<T, ChordTheoryError> {
     fn to_py_result(self) -> PyResult<T> {
-        self.map_err(|e| PyValueError::new_err(format!("Chord theory error: {}", e)))
+        self.map_err(|e| to_py_err(e, "Chord theory error"))
     }
 }
 
 impl<T> ToPyResult<T> for Result<T, SerializationError> {
     fn to_py_result(self) -> PyResult<T> {
-        self.map_err(|e| PyRuntimeError::new_err(format!("Serialization error: {}", e)))
+        self.map_err(|e| to_py_err(e, "Serialization error"))
     }
 }
 
 impl<T> ToPyResult<T> for Result<T, AiError> {
     fn to_py_result(self) -> PyResult<T> {
         self.map_err(|e| match e.severity() {
-            composer_ai::Severity::Critical => {
-                PyRuntimeError::new_err(format!("AI engine error: {}", e))
-            },
-            _ => PyValueError::new_err(format!("AI error: {}", e)),
+            Severity::Critical => to_py_err(e, "AI engine error"),
+            _ => to_py_err(e, "AI error"),
         })
     }
 }
@@ -13,6 +13,7 @@ use std::collections::HashMap;
 mod ai;
 mod chord;
 mod error;
+mod pickle;
 mod scale;
 mod serialization;
 mod theory;
@@ -23,13 +24,14 @@ pub use error::*;
 pub use scale::*;
 pub use serialization::{
     chord_from_hex, chord_to_hex, deserialize_chord_from_binary, serialize_chord_to_binary, PyNote,
-    PyTimeline, PyTokenLibrary, PyTrieNode,
+    PyTimeline, PyTimelineIterator, PyTokenEvent, PyTokenLibrary, PyTrieNode,
+    PyTriePatternIterator,
 };
 pub use theory::{
-    analyze_harmonic_function, chord_letter_to_lower_case, chord_letter_to_upper_case,
-    get_chord_complexity, get_relative_chord_graphic, get_relative_scale_degrees,
-    get_stable_scale_degrees, is_isotonal, is_valid_tri_sub, PyChordGraphic,
-    PyRelativeScaleDegrees,
+    analyze_harmonic_function, analyze_harmonic_functions, check_counterpoint,
+    chord_letter_to_lower_case, chord_letter_to_upper_case, get_chord_complexity,
+    get_relative_chord_graphic, get_relative_scale_degrees, get_stable_scale_degrees,
+    get_substitutions, is_isotonal, is_valid_tri_sub, PyChordGraphic, PyRelativeScaleDegrees,
 };
 
 /// Get all configuration constants as a dictionary
@@ -95,30 +97,9 @@ fn get_configuration_constants() -> HashMap<String, String> {
     constants.insert("MAX_CONCURRENT_REQUESTS".to_string(), "10".to_string());
 
     // Error codes
-    constants.insert(
-        "ERROR_INVALID_CHORD_ROOT".to_string(),
-        "invalid-chord-root".to_string(),
-    );
-    constants.insert(
-        "ERROR_INVALID_CHORD_TYPE".to_string(),
-        "invalid-chord-type".to_string(),
-    );
-    constants.insert(
-        "ERROR_INVALID_INVERSION".to_string(),
-        "invalid-inversion".to_string(),
-    );
-    constants.insert(
-        "ERROR_INVALID_SCALE".to_string(),
-        "invalid-scale-fingerprint".to_string(),
-    );
-    constants.insert(
-        "ERROR_INCOMPATIBLE_ALTERATIONS".to_string(),
-        "incompatible-alterations".to_string(),
-    );
-    constants.insert(
-        "ERROR_MISSING_PROPERTY".to_string(),
-        "missing-required-property".to_string(),
-    );
+    for (name, code) in composer_config::ERROR_CODES {
+        constants.insert(name.to_string(), code.to_string());
+    }
 
     constants
 }
@@ -129,6 +110,39 @@ fn get_version() -> String {
     composer_config::APPLICATION.version.to_string()
 }
 
+/// Print spans for trie search, suggestion scoring and analysis to stderr.
+///
+/// Each span is printed when it closes, with its fields and the time spent in it.
+/// `level` is one of "error", "warn", "info", "debug" or "trace"; the spans are at
+/// "debug". Fails if a subscriber is already installed or the module was built
+/// without the `tracing` feature.
+#[pyfunction]
+#[pyo3(signature = (level = "debug"))]
+fn enable_tracing(level: &str) -> PyResult<()> {
+    #[cfg(feature = "tracing")]
+    {
+        use tracing_subscriber::filter::LevelFilter;
+        use tracing_subscriber::fmt::format::FmtSpan;
+
+        let level: LevelFilter = level.parse().map_err(|_| {
+            pyo3::exceptions::PyValueError::new_err(format!("Unknown tracing level: {}", level))
+        })?;
+        tracing_subscriber::fmt()
+            .with_max_level(level)
+            .with_span_events(FmtSpan::CLOSE)
+            .with_writer(std::io::stderr)
+            .try_init()
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+    }
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = level;
+        Err(pyo3::exceptions::PyRuntimeError::new_err(
+            "composer was built without the tracing feature",
+        ))
+    }
+}
+
 /// The main Composer module for Python
 #[pymodule]
 fn composer(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
@@ -147,16 +161,28 @@ fn composer(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(theory::get_stable_scale_degrees, m)?)?;
     m.add_function(wrap_pyfunction!(theory::get_relative_scale_degrees, m)?)?;
     m.add_function(wrap_pyfunction!(theory::is_valid_tri_sub, m)?)?;
+    m.add_function(wrap_pyfunction!(theory::get_substitutions, m)?)?;
+    m.add_function(wrap_pyfunction!(theory::negative_harmony_mirror, m)?)?;
+    m.add_function(wrap_pyfunction!(theory::modal_interchange_options, m)?)?;
+    m.add_function(wrap_pyfunction!(theory::progression_reharmonize, m)?)?;
+    m.add_function(wrap_pyfunction!(theory::piano_voicing, m)?)?;
+    m.add_function(wrap_pyfunction!(theory::guitar_fingerings, m)?)?;
     m.add_function(wrap_pyfunction!(theory::is_isotonal, m)?)?;
+    m.add_function(wrap_pyfunction!(theory::validate_modification, m)?)?;
     m.add_function(wrap_pyfunction!(theory::analyze_harmonic_function, m)?)?;
+    m.add_function(wrap_pyfunction!(theory::analyze_harmonic_functions, m)?)?;
+    m.add_function(wrap_pyfunction!(theory::check_counterpoint, m)?)?;
     m.add_function(wrap_pyfunction!(theory::chord_letter_to_lower_case, m)?)?;
     m.add_function(wrap_pyfunction!(theory::chord_letter_to_upper_case, m)?)?;
 
     // Serialization classes
     m.add_class::<PyNote>()?;
     m.add_class::<PyTokenLibrary>()?;
+    m.add_class::<PyTokenEvent>()?;
     m.add_class::<PyTimeline>()?;
+    m.add_class::<PyTimelineIterator>()?;
     m.add_class::<PyTrieNode>()?;
+    m.add_class::<PyTriePatternIterator>()?;
 
     // Basic serialization functions
     m.add_function(wrap_pyfunction!(
@@ -169,6 +195,10 @@ fn composer(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     )?)?;
     m.add_function(wrap_pyfunction!(serialization::chord_to_hex, m)?)?;
     m.add_function(wrap_pyfunction!(serialization::chord_from_hex, m)?)?;
+    m.add_function(wrap_pyfunction!(
+        serialization::py_validate_chord_binary,
+        m
+    )?)?;
 
     // Tokenization functions
     m.add_function(wrap_pyfunction!(serialization::py_tokenize_duration, m)?)?;
@@ -177,6 +207,14 @@ fn composer(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
         serialization::py_tokenize_chord_as_raw,
         m
     )?)?;
+    m.add_function(wrap_pyfunction!(
+        serialization::py_tokenize_metric_position,
+        m
+    )?)?;
+    m.add_function(wrap_pyfunction!(
+        serialization::py_timeline_metric_tokens,
+        m
+    )?)?;
     m.add_function(wrap_pyfunction!(serialization::py_detokenize_cluster, m)?)?;
     m.add_function(wrap_pyfunction!(serialization::py_detokenize_midi_like, m)?)?;
 
@@ -189,6 +227,14 @@ fn composer(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     // Trie serialization functions
     m.add_function(wrap_pyfunction!(serialization::py_serialize_trie, m)?)?;
     m.add_function(wrap_pyfunction!(serialization::py_deserialize_trie, m)?)?;
+    m.add_function(wrap_pyfunction!(
+        serialization::py_serialize_trie_to_file,
+        m
+    )?)?;
+    m.add_function(wrap_pyfunction!(
+        serialization::py_deserialize_trie_from_file,
+        m
+    )?)?;
     m.add_function(wrap_pyfunction!(
         serialization::py_validate_binary_format,
         m
@@ -217,10 +263,27 @@ fn composer(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
         serialization::py_validate_chord_cluster_token,
         m
     )?)?;
+    m.add_function(wrap_pyfunction!(
+        serialization::py_validate_metric_token,
+        m
+    )?)?;
+    m.add_function(wrap_pyfunction!(
+        serialization::py_validate_token_stream,
+        m
+    )?)?;
+    m.add_function(wrap_pyfunction!(serialization::py_upgrade_token_stream, m)?)?;
+    m.add_function(wrap_pyfunction!(serialization::py_tokenize_polyphonic, m)?)?;
+    m.add_function(wrap_pyfunction!(
+        serialization::py_detokenize_polyphonic,
+        m
+    )?)?;
+    m.add_function(wrap_pyfunction!(serialization::py_tokenize_timeline, m)?)?;
+    m.add_function(wrap_pyfunction!(serialization::py_detokenize_tokens, m)?)?;
 
     // Configuration and constants functions
     m.add_function(wrap_pyfunction!(get_configuration_constants, m)?)?;
     m.add_function(wrap_pyfunction!(get_version, m)?)?;
+    m.add_function(wrap_pyfunction!(enable_tracing, m)?)?;
     m.add_function(wrap_pyfunction!(
         serialization::get_serialization_constants,
         m
@@ -233,6 +296,7 @@ fn composer(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PyChordSuggestion>()?;
     m.add_class::<PyDifficultyAssessment>()?;
     m.add_class::<PyBassHarmonization>()?;
+    m.add_class::<PyProgressionAnalysis>()?;
 
     // Constants
     let constants = PyDict::new(_py);
@@ -243,9 +307,17 @@ fn composer(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     constants.set_item("MAX_PATTERN_LENGTH", 20)?;
     constants.set_item("COMPLEXITY_SCALE_MAX", 10.0)?;
     m.add("constants", constants)?;
+    m.add(
+        "DIFFICULTY_FEATURE_NAMES",
+        composer_ai::DIFFICULTY_FEATURE_NAMES.to_vec(),
+    )?;
+    m.add(
+        "DIFFICULTY_FEATURE_VERSION",
+        composer_ai::DIFFICULTY_FEATURE_VERSION,
+    )?;
 
     // Error types
-    m.add("ComposerError", _py.get_type::<PyComposerError>())?;
+    error::register_error_types(_py, m)?;
 
     // Add version as module attribute
     m.add("__version__", composer_config::APPLICATION.version)?;
@@ -0,0 +1,30 @@
+//! Pickle support shared by the Python wrapper classes
+//!
+//! Each wrapper implements `__getstate__`/`__setstate__` returning and accepting
+//! `bytes`. Types with a native binary format (chords, timelines, tries, token
+//! libraries, engine assets) use it; the rest are encoded as JSON so that every
+//! field survives a round trip through `pickle` or `multiprocessing`.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Encode a value as pickle state
+pub(crate) fn encode_state<T: Serialize>(py: Python<'_>, value: &T) -> PyResult<Py<PyBytes>> {
+    let bytes = serde_json::to_vec(value)
+        .map_err(|e| PyValueError::new_err(format!("Failed to pickle: {}", e)))?;
+    Ok(PyBytes::new(py, &bytes).into())
+}
+
+/// Decode pickle state produced by [`encode_state`]
+pub(crate) fn decode_state<T: DeserializeOwned>(state: &[u8]) -> PyResult<T> {
+    serde_json::from_slice(state)
+        .map_err(|e| PyValueError::new_err(format!("Failed to unpickle: {}", e)))
+}
+
+/// Wrap already-encoded bytes as pickle state
+pub(crate) fn raw_state(py: Python<'_>, bytes: &[u8]) -> Py<PyBytes> {
+    PyBytes::new(py, bytes).into()
+}
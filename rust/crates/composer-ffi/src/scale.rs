@@ -1,12 +1,15 @@
 //! Python bindings for scale data structures
 
+use crate::chord::PyChord;
 use crate::error::ToPyResult;
+use crate::pickle::raw_state;
 use composer_core::ScaleFingerprint;
+use composer_serialization::{scale_fingerprint_from_json, scale_fingerprint_to_json};
 use pyo3::prelude::*;
-use pyo3::types::PyList;
+use pyo3::types::{PyBytes, PyList};
 
 /// Python wrapper for ScaleFingerprint
-#[pyclass(name = "ScaleFingerprint")]
+#[pyclass(name = "ScaleFingerprint", module = "composer.composer")]
 #[derive(Clone)]
 pub struct PyScaleFingerprint {
     pub inner: ScaleFingerprint,
@@ -86,6 +89,49 @@ impl PyScaleFingerprint {
         self.inner.chromatic_to_scale_degree(note)
     }
 
+    /// Mode starting on the note `n` steps up the scale, re-rooted on 0
+    fn rotate(&self, n: usize) -> Self {
+        PyScaleFingerprint {
+            inner: self.inner.rotate(n),
+        }
+    }
+
+    /// Notes in both scales, or None if they share no notes
+    fn intersection(&self, other: &PyScaleFingerprint) -> Option<Self> {
+        self.inner
+            .intersection(&other.inner)
+            .map(|inner| PyScaleFingerprint { inner })
+    }
+
+    /// Notes in this scale but not in `other`, or None if there are none
+    fn difference(&self, other: &PyScaleFingerprint) -> Option<Self> {
+        self.inner
+            .difference(&other.inner)
+            .map(|inner| PyScaleFingerprint { inner })
+    }
+
+    /// Check if every tone of a chord, read in this scale, is in the scale
+    fn contains_chord(&self, chord: &PyChord) -> bool {
+        self.inner.contains_chord(&chord.inner)
+    }
+
+    /// Number of semitones that are in one scale but not the other
+    fn distance(&self, other: &PyScaleFingerprint) -> usize {
+        self.inner.distance(&other.inner)
+    }
+
+    /// Write the fingerprint as a JSON array of 12 semitone flags
+    fn to_json(&self) -> PyResult<String> {
+        scale_fingerprint_to_json(&self.inner).to_py_result()
+    }
+
+    /// Read a fingerprint from a JSON array of 12 semitone flags
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        let fingerprint = scale_fingerprint_from_json(json).to_py_result()?;
+        Ok(PyScaleFingerprint { inner: fingerprint })
+    }
+
     // Display
     fn __str__(&self) -> String {
         format!("{}", self.inner)
@@ -104,4 +150,17 @@ impl PyScaleFingerprint {
     fn __ne__(&self, other: &PyScaleFingerprint) -> bool {
         self.inner != other.inner
     }
+
+    fn __getnewargs__(&self) -> (Vec<u8>,) {
+        (self.inner.semitones().to_vec(),)
+    }
+
+    fn __getstate__(&self, py: Python) -> PyResult<Py<PyBytes>> {
+        Ok(raw_state(py, self.inner.semitones()))
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        self.inner = ScaleFingerprint::from_slice(state).to_py_result()?;
+        Ok(())
+    }
 }
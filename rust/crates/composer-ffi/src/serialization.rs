@@ -1,18 +1,28 @@
 //! Python bindings for chord serialization, tokenization, and data processing
 
 use crate::error::ToPyResult;
+use crate::pickle::{decode_state, encode_state, raw_state};
 use crate::{PyChord, PyScaleFingerprint};
+use composer_core::Key;
 use composer_serialization::{
-    augment_with_repeated, deserialize_chord, deserialize_trie, detokenize_cluster,
-    detokenize_midi_like, fast_hash, fold_hash, parse_duration_token, reduce_chord_vocab,
-    scale40_decode, scale40_encode, serialize_chord, serialize_trie, tokenize_chord_as_raw,
-    tokenize_duration, validate_binary_format, validate_chord_cluster_token,
-    validate_duration_token, validate_octave_token, validate_raw_note_token, validate_token,
-    ChordBinary, Note, Timeline, TokenLibrary, TrieNode, CHROMATIC_RANGE, OCTAVE_RANGE_MAX,
-    OCTAVE_RANGE_MIN, TICKS_PER_BEAT,
+    augment_with_repeated, deserialize_chord, deserialize_timeline, deserialize_token_library,
+    deserialize_token_vocabulary, deserialize_trie, deserialize_trie_from_reader,
+    detokenize_cluster, detokenize_midi_like, detokenize_polyphonic, detokenize_tokens, fast_hash,
+    fold_hash, parse_duration_token, reduce_chord_vocab, scale40_decode, scale40_encode,
+    serialize_chord, serialize_timeline, serialize_token_library, serialize_token_vocabulary,
+    serialize_trie, serialize_trie_chunks, split_token_stream, timeline_from_json,
+    timeline_metric_positions, timeline_to_json, token_vocabulary_from_json,
+    token_vocabulary_to_json, tokenize_chord_as_raw, tokenize_duration, tokenize_metric_position,
+    tokenize_polyphonic, tokenize_timeline, upgrade_token_stream, validate_binary_format,
+    validate_chord_binary, validate_chord_cluster_token, validate_duration_token,
+    validate_metric_token, validate_octave_token, validate_raw_note_token, validate_token,
+    validate_token_stream, ChordBinary, ClusterNote, DeserializationLimits, FieldStatus, KeyChange,
+    MeterChange, Note, SerializationError, Timeline, TokenEvent, TokenEventType, TokenLibrary,
+    TrieNode, ValidationMode, CHROMATIC_RANGE, OCTAVE_RANGE_MAX, OCTAVE_RANGE_MIN, TICKS_PER_BEAT,
+    TOKEN_LIBRARY_FORMAT_VERSION, TRIE_STREAM_CHUNK_LEN,
 };
 use pyo3::prelude::*;
-use pyo3::types::{PyBytes, PyDict, PyList};
+use pyo3::types::{PyBytes, PyDict, PyList, PySlice};
 
 // ============================================================================
 // Basic Chord Serialization
@@ -71,12 +81,68 @@ pub fn chord_from_hex(hex_string: &str) -> PyResult<PyChord> {
     Ok(PyChord { inner: chord })
 }
 
+/// Explain a 5-byte chord payload, given as bytes or a hex string, field by field
+#[pyfunction]
+#[pyo3(signature = (data, mode="strict"))]
+pub fn py_validate_chord_binary(
+    py: Python,
+    data: &Bound<'_, PyAny>,
+    mode: &str,
+) -> PyResult<Py<PyDict>> {
+    let mode = match mode {
+        "strict" => ValidationMode::Strict,
+        "lenient" => ValidationMode::Lenient,
+        _ => {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Invalid validation mode: {}",
+                mode
+            )))
+        },
+    };
+    let bytes = match data.extract::<String>() {
+        Ok(hex) => composer_serialization::hex_to_chord_binary(&hex)
+            .to_py_result()?
+            .to_vec(),
+        Err(_) => data.extract::<Vec<u8>>()?,
+    };
+    let report = validate_chord_binary(&bytes, mode).to_py_result()?;
+
+    let fields = PyList::empty(py);
+    for field in &report.fields {
+        let entry = PyDict::new(py);
+        entry.set_item("name", &field.name)?;
+        entry.set_item("byte", field.byte)?;
+        entry.set_item("mask", field.mask)?;
+        entry.set_item("raw", field.raw)?;
+        entry.set_item("value", &field.value)?;
+        entry.set_item(
+            "status",
+            match field.status {
+                FieldStatus::Valid => "valid",
+                FieldStatus::Warning => "warning",
+                FieldStatus::Invalid => "invalid",
+            },
+        )?;
+        entry.set_item("message", &field.message)?;
+        fields.append(entry)?;
+    }
+
+    let dict = PyDict::new(py);
+    dict.set_item("valid", report.is_valid())?;
+    dict.set_item("hex", &report.hex)?;
+    dict.set_item("bytes", PyBytes::new(py, &report.bytes))?;
+    dict.set_item("fields", fields)?;
+    dict.set_item("chord", report.chord.map(|inner| PyChord { inner }))?;
+    dict.set_item("chord_error", report.chord_error)?;
+    Ok(dict.into())
+}
+
 // ============================================================================
 // Tokenization Classes and Functions
 // ============================================================================
 
 /// Python wrapper for Note
-#[pyclass(name = "Note")]
+#[pyclass(name = "Note", module = "composer.composer")]
 #[derive(Clone)]
 pub struct PyNote {
     pub inner: Note,
@@ -121,21 +187,44 @@ impl PyNote {
             )
         }
     }
+
+    fn __getnewargs__(&self) -> (u8, u8) {
+        (self.inner.scale_degree, self.inner.octave)
+    }
+
+    fn __getstate__(&self, py: Python) -> PyResult<Py<PyBytes>> {
+        encode_state(py, &self.inner)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        self.inner = decode_state(state)?;
+        Ok(())
+    }
+}
+
+/// JSON text or bytes accepted by `TokenLibrary.import_vocab`
+#[derive(FromPyObject)]
+enum VocabularyData {
+    Json(String),
+    Binary(Vec<u8>),
 }
 
 /// Python wrapper for TokenLibrary
-#[pyclass(name = "TokenLibrary")]
+#[pyclass(name = "TokenLibrary", module = "composer.composer")]
 pub struct PyTokenLibrary {
-    inner: TokenLibrary,
+    pub(crate) inner: TokenLibrary,
 }
 
 #[pymethods]
 impl PyTokenLibrary {
+    /// Create an empty library; pass an older `schema_version` for tokens from
+    /// an older vocabulary, then call `upgrade`
     #[new]
-    fn new() -> Self {
-        PyTokenLibrary {
-            inner: TokenLibrary::new(),
-        }
+    #[pyo3(signature = (schema_version=TOKEN_LIBRARY_FORMAT_VERSION))]
+    fn new(schema_version: u8) -> PyResult<Self> {
+        Ok(PyTokenLibrary {
+            inner: TokenLibrary::with_schema_version(schema_version).to_py_result()?,
+        })
     }
 
     fn add_chord_token(&mut self, token: String, chord_binary: &[u8]) -> PyResult<()> {
@@ -162,20 +251,277 @@ impl PyTokenLibrary {
         self.inner.get_library_size()
     }
 
+    /// Token vocabulary version the tokens are written in
+    #[getter]
+    fn schema_version(&self) -> u8 {
+        self.inner.schema_version
+    }
+
+    /// Rewrite the tokens in the current vocabulary; returns how many were renamed
+    fn upgrade(&mut self) -> PyResult<usize> {
+        self.inner.upgrade().to_py_result()
+    }
+
+    /// Vocabulary of every token with stable ids, as JSON or, with
+    /// `binary=True`, as bytes
+    #[pyo3(signature = (binary=false))]
+    fn export_vocab(&self, py: Python, binary: bool) -> PyResult<PyObject> {
+        let vocabulary = self.inner.export_vocab().to_py_result()?;
+        if binary {
+            let bytes = serialize_token_vocabulary(&vocabulary).to_py_result()?;
+            Ok(PyBytes::new(py, &bytes).into())
+        } else {
+            let json = token_vocabulary_to_json(&vocabulary).to_py_result()?;
+            Ok(pyo3::types::PyString::new(py, &json).into_any().unbind())
+        }
+    }
+
+    /// Library of the chord tokens of a vocabulary written by `export_vocab`
+    #[staticmethod]
+    fn import_vocab(data: VocabularyData) -> PyResult<Self> {
+        let vocabulary = match data {
+            VocabularyData::Json(json) => token_vocabulary_from_json(&json),
+            VocabularyData::Binary(bytes) => deserialize_token_vocabulary(&bytes),
+        }
+        .to_py_result()?;
+        Ok(PyTokenLibrary {
+            inner: TokenLibrary::import_vocab(&vocabulary),
+        })
+    }
+
     fn __repr__(&self) -> String {
         format!("TokenLibrary(size={})", self.inner.get_library_size())
     }
+
+    fn __getstate__(&self, py: Python) -> PyResult<Py<PyBytes>> {
+        let bytes = serialize_token_library(&self.inner).to_py_result()?;
+        Ok(raw_state(py, &bytes))
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        self.inner = deserialize_token_library(state).to_py_result()?;
+        Ok(())
+    }
+}
+
+/// Python form of a cluster note: (pitch_class, octave, duration)
+type PyClusterNote = (u8, u8, f64);
+
+/// Python wrapper for a single timeline event
+#[pyclass(name = "TokenEvent", module = "composer.composer")]
+#[derive(Clone)]
+pub struct PyTokenEvent {
+    inner: TokenEvent,
+}
+
+#[pymethods]
+impl PyTokenEvent {
+    /// Create an event holding a note, a chord, a cluster of
+    /// `(pitch_class, octave, duration)` notes, or a rest when none is given
+    #[new]
+    #[pyo3(signature = (beat=0.0, note=None, chord=None, cluster=None))]
+    fn new(
+        beat: f64,
+        note: Option<PyNote>,
+        chord: Option<PyChord>,
+        cluster: Option<Vec<PyClusterNote>>,
+    ) -> PyResult<Self> {
+        let event_type = match (note, chord, cluster) {
+            (Some(note), None, None) => TokenEventType::Note(note.inner),
+            (None, Some(chord), None) => TokenEventType::Chord(chord.inner),
+            (None, None, Some(cluster)) => TokenEventType::Cluster(
+                cluster
+                    .into_iter()
+                    .map(|(pitch_class, octave, duration)| ClusterNote {
+                        pitch_class,
+                        octave,
+                        duration,
+                    })
+                    .collect(),
+            ),
+            (None, None, None) => TokenEventType::Rest,
+            _ => {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "An event holds one of a note, a chord or a cluster",
+                ))
+            },
+        };
+        Ok(Self {
+            inner: TokenEvent { beat, event_type },
+        })
+    }
+
+    #[getter]
+    fn beat(&self) -> f64 {
+        self.inner.beat
+    }
+
+    /// Event kind: "note", "chord", "cluster" or "rest"
+    #[getter]
+    fn kind(&self) -> &'static str {
+        match self.inner.event_type {
+            TokenEventType::Note(_) => "note",
+            TokenEventType::Chord(_) => "chord",
+            TokenEventType::Rest => "rest",
+            TokenEventType::Cluster(_) => "cluster",
+        }
+    }
+
+    /// Cluster notes as `(pitch_class, octave, duration)` tuples
+    #[getter]
+    fn cluster(&self) -> Option<Vec<PyClusterNote>> {
+        match &self.inner.event_type {
+            TokenEventType::Cluster(notes) => Some(
+                notes
+                    .iter()
+                    .map(|note| (note.pitch_class, note.octave, note.duration))
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+
+    #[getter]
+    fn note(&self) -> Option<PyNote> {
+        match &self.inner.event_type {
+            TokenEventType::Note(note) => Some(PyNote {
+                inner: note.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    #[getter]
+    fn chord(&self) -> Option<PyChord> {
+        match &self.inner.event_type {
+            TokenEventType::Chord(chord) => Some(PyChord {
+                inner: chord.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        match &self.inner.event_type {
+            TokenEventType::Note(note) => format!(
+                "TokenEvent(beat={:?}, note=Note(scale_degree={}, octave={}))",
+                self.inner.beat, note.scale_degree, note.octave
+            ),
+            TokenEventType::Chord(chord) => format!(
+                "TokenEvent(beat={:?}, chord=Chord(root={}, chord_type={}, inversion={}))",
+                self.inner.beat, chord.root, chord.chord_type, chord.inversion
+            ),
+            TokenEventType::Rest => format!("TokenEvent(beat={:?}, rest)", self.inner.beat),
+            TokenEventType::Cluster(notes) => format!(
+                "TokenEvent(beat={:?}, cluster={} notes)",
+                self.inner.beat,
+                notes.len()
+            ),
+        }
+    }
+
+    fn __getstate__(&self, py: Python) -> PyResult<Py<PyBytes>> {
+        encode_state(py, &self.inner)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        self.inner = decode_state(state)?;
+        Ok(())
+    }
+}
+
+/// Python form of a meter change: (beat, (numerator, denominator))
+type PyMeterChange = (f64, (u8, u8));
+
+/// Python form of a key change: (beat, key name such as "Am")
+type PyKeyChange = (f64, String);
+
+/// Integer index or slice accepted by `Timeline.__getitem__`
+#[derive(FromPyObject)]
+enum EventIndex<'py> {
+    Index(isize),
+    Slice(Bound<'py, PySlice>),
 }
 
 /// Python wrapper for Timeline
-#[pyclass(name = "Timeline")]
+#[pyclass(name = "Timeline", module = "composer.composer")]
 #[derive(Clone)]
 pub struct PyTimeline {
-    inner: Timeline,
+    pub inner: Timeline,
 }
 
 #[pymethods]
 impl PyTimeline {
+    /// Create a timeline from events, empty by default
+    ///
+    /// `total_duration` defaults to the beat of the last event. `meter_changes`
+    /// lists `(beat, (numerator, denominator))` time signature changes and
+    /// `key_changes` `(beat, key)` key changes, with keys named like `"F#m"`.
+    #[new]
+    #[pyo3(signature = (events=None, total_duration=None, meter_changes=None, key_changes=None))]
+    fn new(
+        events: Option<Vec<PyTokenEvent>>,
+        total_duration: Option<f64>,
+        meter_changes: Option<Vec<PyMeterChange>>,
+        key_changes: Option<Vec<PyKeyChange>>,
+    ) -> PyResult<Self> {
+        let events: Vec<TokenEvent> = events
+            .unwrap_or_default()
+            .into_iter()
+            .map(|event| event.inner)
+            .collect();
+        let total_duration = total_duration
+            .unwrap_or_else(|| events.iter().map(|event| event.beat).fold(0.0, f64::max));
+        let mut meter_changes: Vec<MeterChange> = meter_changes
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(beat, time_signature)| MeterChange {
+                beat,
+                time_signature,
+            })
+            .collect();
+        meter_changes.sort_by(|a, b| a.beat.total_cmp(&b.beat));
+        let mut key_changes: Vec<KeyChange> = key_changes
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(beat, key)| {
+                Ok(KeyChange {
+                    beat,
+                    key: key.parse::<Key>().to_py_result()?,
+                })
+            })
+            .collect::<PyResult<_>>()?;
+        key_changes.sort_by(|a, b| a.beat.total_cmp(&b.beat));
+        Ok(Self {
+            inner: Timeline {
+                events,
+                total_duration,
+                meter_changes,
+                key_changes,
+            },
+        })
+    }
+
+    /// Time signature changes as `(beat, (numerator, denominator))` tuples
+    #[getter]
+    fn meter_changes(&self) -> Vec<PyMeterChange> {
+        self.inner
+            .meter_changes
+            .iter()
+            .map(|change| (change.beat, change.time_signature))
+            .collect()
+    }
+
+    /// Key changes as `(beat, key)` tuples
+    #[getter]
+    fn key_changes(&self) -> Vec<PyKeyChange> {
+        self.inner
+            .key_changes
+            .iter()
+            .map(|change| (change.beat, change.key.to_string()))
+            .collect()
+    }
+
     #[getter]
     fn total_duration(&self) -> f64 {
         self.inner.total_duration
@@ -186,6 +532,46 @@ impl PyTimeline {
         self.inner.events.len()
     }
 
+    fn __len__(&self) -> usize {
+        self.inner.events.len()
+    }
+
+    /// Return one event, or a list of events for a slice
+    fn __getitem__(&self, py: Python, index: EventIndex) -> PyResult<PyObject> {
+        let events = &self.inner.events;
+        match index {
+            EventIndex::Index(i) => {
+                let resolved = if i < 0 { i + events.len() as isize } else { i };
+                let event = usize::try_from(resolved)
+                    .ok()
+                    .and_then(|i| events.get(i))
+                    .ok_or_else(|| {
+                        pyo3::exceptions::PyIndexError::new_err("Timeline index out of range")
+                    })?;
+                Ok(Py::new(
+                    py,
+                    PyTokenEvent {
+                        inner: event.clone(),
+                    },
+                )?
+                .into_any())
+            },
+            EventIndex::Slice(slice) => {
+                let indices = slice.indices(events.len() as isize)?;
+                let selected = (0..indices.slicelength).map(|n| PyTokenEvent {
+                    inner: events[(indices.start + n as isize * indices.step) as usize].clone(),
+                });
+                Ok(PyList::new(py, selected)?.into_any().unbind())
+            },
+        }
+    }
+
+    fn __iter__(&self) -> PyTimelineIterator {
+        PyTimelineIterator {
+            events: self.inner.events.clone().into_iter(),
+        }
+    }
+
     fn __repr__(&self) -> String {
         format!(
             "Timeline(duration={:.2}, events={})",
@@ -193,10 +579,49 @@ impl PyTimeline {
             self.inner.events.len()
         )
     }
+
+    /// Write the timeline as human-readable JSON
+    fn to_json(&self) -> PyResult<String> {
+        timeline_to_json(&self.inner).to_py_result()
+    }
+
+    /// Read a timeline from JSON
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        let timeline = timeline_from_json(json).to_py_result()?;
+        Ok(PyTimeline { inner: timeline })
+    }
+
+    fn __getstate__(&self, py: Python) -> PyResult<Py<PyBytes>> {
+        let bytes = serialize_timeline(&self.inner).to_py_result()?;
+        Ok(raw_state(py, &bytes))
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        self.inner = deserialize_timeline(state).to_py_result()?;
+        Ok(())
+    }
+}
+
+/// Iterator over the events of a Timeline snapshot
+#[pyclass(name = "TimelineIterator", module = "composer.composer")]
+pub struct PyTimelineIterator {
+    events: std::vec::IntoIter<TokenEvent>,
+}
+
+#[pymethods]
+impl PyTimelineIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> Option<PyTokenEvent> {
+        self.events.next().map(|inner| PyTokenEvent { inner })
+    }
 }
 
 /// Python wrapper for TrieNode
-#[pyclass(name = "TrieNode")]
+#[pyclass(name = "TrieNode", module = "composer.composer")]
 pub struct PyTrieNode {
     inner: TrieNode,
 }
@@ -238,6 +663,18 @@ impl PyTrieNode {
         self.inner.calculate_rank(total_nodes)
     }
 
+    /// Iterate over `(pattern, ids)` pairs for every stored pattern
+    fn __iter__(&self) -> PyTriePatternIterator {
+        PyTriePatternIterator {
+            patterns: self
+                .inner
+                .patterns()
+                .map(|(pattern, ids)| (pattern, ids.to_vec()))
+                .collect::<Vec<_>>()
+                .into_iter(),
+        }
+    }
+
     fn __repr__(&self) -> String {
         format!(
             "TrieNode(count={}, children={}, ids={})",
@@ -246,6 +683,43 @@ impl PyTrieNode {
             self.inner.id_list.len()
         )
     }
+
+    fn __getstate__(&self, py: Python) -> PyResult<Py<PyBytes>> {
+        let bytes = serialize_trie(&self.inner).to_py_result()?;
+        Ok(raw_state(py, &bytes))
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        self.inner = deserialize_trie(state, false).to_py_result()?;
+        Ok(())
+    }
+}
+
+/// Stored pattern and the ids recorded for it
+type StoredPattern = (Vec<Vec<u8>>, Vec<u32>);
+
+/// Pattern keys as a list of `bytes`, paired with their ids
+type PyStoredPattern = (Py<PyList>, Vec<u32>);
+
+/// Iterator over the patterns of a TrieNode snapshot
+#[pyclass(name = "TriePatternIterator", module = "composer.composer")]
+pub struct PyTriePatternIterator {
+    patterns: std::vec::IntoIter<StoredPattern>,
+}
+
+#[pymethods]
+impl PyTriePatternIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python) -> PyResult<Option<PyStoredPattern>> {
+        let Some((pattern, ids)) = self.patterns.next() else {
+            return Ok(None);
+        };
+        let keys = pattern.iter().map(|key| PyBytes::new(py, key));
+        Ok(Some((PyList::new(py, keys)?.unbind(), ids)))
+    }
 }
 
 // ============================================================================
@@ -264,6 +738,24 @@ pub fn py_parse_duration_token(token: &str) -> PyResult<f64> {
     parse_duration_token(token).to_py_result()
 }
 
+/// Tokenize the metric position of an onset beat (e.g. "M_DOWN")
+#[pyfunction]
+#[pyo3(signature = (beat, time_signature=(4, 4)))]
+pub fn py_tokenize_metric_position(beat: f64, time_signature: (u8, u8)) -> PyResult<String> {
+    tokenize_metric_position(beat, time_signature).to_py_result()
+}
+
+/// Metric position tokens of every event in a timeline, in event order
+#[pyfunction]
+#[pyo3(signature = (timeline, time_signature=(4, 4)))]
+pub fn py_timeline_metric_tokens(
+    timeline: &PyTimeline,
+    time_signature: (u8, u8),
+) -> PyResult<Vec<String>> {
+    let positions = timeline_metric_positions(&timeline.inner, time_signature).to_py_result()?;
+    Ok(positions.iter().map(|position| position.token()).collect())
+}
+
 /// Tokenize chord as raw chromatic cluster
 #[pyfunction]
 pub fn py_tokenize_chord_as_raw(chord: &PyChord, scale: &PyScaleFingerprint) -> PyResult<String> {
@@ -303,6 +795,43 @@ pub fn py_detokenize_midi_like(
     Ok(PyTimeline { inner: timeline })
 }
 
+/// Tokenize a timeline with note clusters and per-note durations
+#[pyfunction]
+pub fn py_tokenize_polyphonic(
+    timeline: &PyTimeline,
+    scale: &PyScaleFingerprint,
+) -> PyResult<Vec<String>> {
+    tokenize_polyphonic(&timeline.inner, &scale.inner).to_py_result()
+}
+
+/// Detokenize a polyphonic token string or list of tokens into a timeline
+#[pyfunction]
+pub fn py_detokenize_polyphonic(tokens: TokenStream) -> PyResult<PyTimeline> {
+    let tokens = match tokens {
+        TokenStream::Text(text) => split_token_stream(&text),
+        TokenStream::Tokens(tokens) => tokens,
+    };
+    let timeline = detokenize_polyphonic(&tokens).to_py_result()?;
+    Ok(PyTimeline { inner: timeline })
+}
+
+/// Tokenize a whole timeline, with bar lines, keys, meters and exact chords
+#[pyfunction]
+pub fn py_tokenize_timeline(timeline: &PyTimeline) -> PyResult<Vec<String>> {
+    tokenize_timeline(&timeline.inner).to_py_result()
+}
+
+/// Detokenize a token string or list of tokens written by `py_tokenize_timeline`
+#[pyfunction]
+pub fn py_detokenize_tokens(tokens: TokenStream) -> PyResult<PyTimeline> {
+    let tokens = match tokens {
+        TokenStream::Text(text) => split_token_stream(&text),
+        TokenStream::Tokens(tokens) => tokens,
+    };
+    let timeline = detokenize_tokens(&tokens).to_py_result()?;
+    Ok(PyTimeline { inner: timeline })
+}
+
 // ============================================================================
 // Hash and Compression Functions
 // ============================================================================
@@ -360,6 +889,75 @@ pub fn py_deserialize_trie(data: &[u8], include_key_tonic: bool) -> PyResult<PyT
     Ok(PyTrieNode { inner: trie })
 }
 
+/// Serialize trie to a binary file object in chunks, returning the bytes written
+#[pyfunction]
+#[pyo3(signature = (trie, file, chunk_size=TRIE_STREAM_CHUNK_LEN))]
+pub fn py_serialize_trie_to_file(
+    py: Python,
+    trie: &PyTrieNode,
+    file: &Bound<'_, PyAny>,
+    chunk_size: usize,
+) -> PyResult<u64> {
+    let chunks = serialize_trie_chunks(&trie.inner, chunk_size).to_py_result()?;
+    let written = chunks.encoded_len();
+    for chunk in chunks {
+        file.call_method1("write", (PyBytes::new(py, &chunk),))?;
+    }
+    Ok(written)
+}
+
+/// Deserialize trie from a binary file object, reading it in chunks
+#[pyfunction]
+#[pyo3(signature = (file, include_key_tonic=false))]
+pub fn py_deserialize_trie_from_file(
+    file: &Bound<'_, PyAny>,
+    include_key_tonic: bool,
+) -> PyResult<PyTrieNode> {
+    let mut reader = PyFileReader { file, error: None };
+    let result = deserialize_trie_from_reader(
+        &mut reader,
+        include_key_tonic,
+        &DeserializationLimits::default(),
+    );
+    // Re-raise the file's own exception rather than a wrapped I/O error
+    if let Some(error) = reader.error {
+        return Err(error);
+    }
+    Ok(PyTrieNode {
+        inner: result.to_py_result()?,
+    })
+}
+
+/// `Read` over a Python binary file object's `read` method
+struct PyFileReader<'a, 'py> {
+    file: &'a Bound<'py, PyAny>,
+    error: Option<PyErr>,
+}
+
+impl std::io::Read for PyFileReader<'_, '_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let chunk = self
+            .file
+            .call_method1("read", (buf.len(),))
+            .and_then(|chunk| Ok(chunk.downcast_into::<PyBytes>()?));
+        match chunk {
+            Ok(chunk) => {
+                let chunk = chunk.as_bytes();
+                let len = chunk.len().min(buf.len());
+                buf[..len].copy_from_slice(&chunk[..len]);
+                Ok(len)
+            },
+            Err(error) => {
+                self.error = Some(error);
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    "reading the Python file failed",
+                ))
+            },
+        }
+    }
+}
+
 /// Validate binary format
 #[pyfunction]
 pub fn py_validate_binary_format(data: &[u8]) -> bool {
@@ -416,12 +1014,63 @@ pub fn py_validate_octave_token(token: &str) -> bool {
     validate_octave_token(token)
 }
 
+/// Validate metric position token format
+#[pyfunction]
+pub fn py_validate_metric_token(token: &str) -> bool {
+    validate_metric_token(token)
+}
+
 /// Validate chord cluster token format
 #[pyfunction]
 pub fn py_validate_chord_cluster_token(token: &str) -> bool {
     validate_chord_cluster_token(token)
 }
 
+/// Python form of a token stream violation: (index, token, reason)
+type PyStreamViolation = (usize, String, String);
+
+/// Token string or list of tokens accepted by `py_validate_token_stream`,
+/// `py_upgrade_token_stream` and `py_detokenize_polyphonic`
+#[derive(FromPyObject)]
+pub enum TokenStream {
+    Text(String),
+    Tokens(Vec<String>),
+}
+
+/// Check a whole token stream against the token grammar
+///
+/// Accepts a token string (split on whitespace and group markers) or a list of
+/// tokens. Returns `None` for a valid stream, else `(index, token, reason)` of
+/// the first violation.
+#[pyfunction]
+pub fn py_validate_token_stream(tokens: TokenStream) -> Option<PyStreamViolation> {
+    let tokens = match tokens {
+        TokenStream::Text(text) => split_token_stream(&text),
+        TokenStream::Tokens(tokens) => tokens,
+    };
+    match validate_token_stream(&tokens) {
+        Err(SerializationError::InvalidTokenStream {
+            index,
+            token,
+            reason,
+        }) => Some((index, token, reason)),
+        _ => None,
+    }
+}
+
+/// Rewrite a token stream from an older vocabulary version in the current one
+///
+/// Accepts a token string or a list of tokens like `validate_token_stream`.
+#[pyfunction]
+#[pyo3(signature = (tokens, from_version))]
+pub fn py_upgrade_token_stream(tokens: TokenStream, from_version: u8) -> PyResult<Vec<String>> {
+    let tokens = match tokens {
+        TokenStream::Text(text) => split_token_stream(&text),
+        TokenStream::Tokens(tokens) => tokens,
+    };
+    upgrade_token_stream(&tokens, from_version).to_py_result()
+}
+
 // ============================================================================
 // Constants
 // ============================================================================
@@ -435,6 +1084,7 @@ pub fn get_serialization_constants(py: Python) -> PyResult<Py<PyDict>> {
     constants.set_item("OCTAVE_RANGE_MIN", OCTAVE_RANGE_MIN)?;
     constants.set_item("OCTAVE_RANGE_MAX", OCTAVE_RANGE_MAX)?;
     constants.set_item("CHROMATIC_RANGE", CHROMATIC_RANGE)?;
+    constants.set_item("TOKEN_LIBRARY_FORMAT_VERSION", TOKEN_LIBRARY_FORMAT_VERSION)?;
 
     Ok(constants.into())
 }
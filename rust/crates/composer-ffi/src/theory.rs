@@ -1,12 +1,18 @@
 //! Python bindings for music theory functions
 
 use crate::error::ToPyResult;
+use crate::pickle::{decode_state, encode_state};
 use crate::{PyChord, PyScaleFingerprint};
-use composer_core::theory;
+use composer_core::{
+    theory, BorrowedScale, ChordModification, CounterpointOptions, GuitarTuning,
+    GuitarVoicingOptions, MirrorAxis, Mode, PianoVoicingStyle, ReharmonizationStrategy, ScaleType,
+    VoiceNote,
+};
 use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyList};
 
 /// Python wrapper for RomanNumeralGraphic
-#[pyclass]
+#[pyclass(module = "composer.composer")]
 #[derive(Clone)]
 pub struct PyChordGraphic {
     inner: composer_core::roman::RomanNumeralGraphic,
@@ -14,6 +20,14 @@ pub struct PyChordGraphic {
 
 #[pymethods]
 impl PyChordGraphic {
+    /// Create an empty graphic; used when unpickling
+    #[new]
+    fn new() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+
     /// Roman numeral symbol (I, ii, V7, etc.)
     #[getter]
     fn symbol(&self) -> String {
@@ -68,8 +82,42 @@ impl PyChordGraphic {
         self.inner.omits.iter().cloned().collect()
     }
 
+    /// Accidental before the numeral for chromatic roots (♭, ♯)
+    #[getter]
+    fn accidental(&self) -> String {
+        self.inner.accidental.clone()
+    }
+
+    /// Scale the chord is borrowed from, if any
+    #[getter]
+    fn borrowed_from(&self) -> Option<String> {
+        self.inner.origin.borrowed_from.clone()
+    }
+
+    /// Scale degree the chord is applied to, if any
+    #[getter]
+    fn applied_to(&self) -> Option<u8> {
+        self.inner.origin.applied_to
+    }
+
+    /// Semitones between the chord's root and the home scale's degree
+    #[getter]
+    fn root_offset(&self) -> i8 {
+        self.inner.origin.root_offset
+    }
+
+    /// Check if the chord belongs to the home scale without modification
+    fn is_diatonic(&self) -> bool {
+        self.inner.origin.is_diatonic()
+    }
+
+    /// Complete symbol such as `♭VI`, `viiø7` or `V65/V`
+    fn full_symbol(&self) -> String {
+        self.inner.full_symbol()
+    }
+
     fn __str__(&self) -> String {
-        format!("{}{}", self.inner.symbol, self.inner.figured_bass)
+        self.inner.full_symbol()
     }
 
     fn __repr__(&self) -> String {
@@ -78,10 +126,19 @@ impl PyChordGraphic {
             self.inner.symbol, self.inner.figured_bass, self.inner.quality
         )
     }
+
+    fn __getstate__(&self, py: Python) -> PyResult<Py<PyBytes>> {
+        encode_state(py, &self.inner)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        self.inner = decode_state(state)?;
+        Ok(())
+    }
 }
 
 /// Python wrapper for ScaleDegreeResult
-#[pyclass]
+#[pyclass(module = "composer.composer")]
 #[derive(Clone)]
 pub struct PyRelativeScaleDegrees {
     inner: composer_core::theory::ScaleDegreeResult,
@@ -89,6 +146,14 @@ pub struct PyRelativeScaleDegrees {
 
 #[pymethods]
 impl PyRelativeScaleDegrees {
+    /// Create an empty result; used when unpickling
+    #[new]
+    fn new() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+
     /// Scale degree numbers (1-7)
     #[getter]
     fn sd_numbers(&self) -> Vec<u8> {
@@ -118,12 +183,21 @@ impl PyRelativeScaleDegrees {
             self.inner.sd_numbers, self.inner.sd_accs
         )
     }
+
+    fn __getstate__(&self, py: Python) -> PyResult<Py<PyBytes>> {
+        encode_state(py, &self.inner)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        self.inner = decode_state(state)?;
+        Ok(())
+    }
 }
 
 /// Calculate chord complexity score
 #[pyfunction]
 pub fn get_chord_complexity(chord: &PyChord) -> PyResult<f64> {
-    theory::get_chord_complexity(&chord.inner, "major").to_py_result()
+    theory::get_chord_complexity(&chord.inner, Mode::Major).to_py_result()
 }
 
 /// Generate complete Roman numeral representation
@@ -155,33 +229,306 @@ pub fn get_relative_scale_degrees(chord: &PyChord) -> PyResult<PyRelativeScaleDe
 /// Validate tritone substitution eligibility
 #[pyfunction]
 pub fn is_valid_tri_sub(chord: &PyChord, scale_type: &str) -> bool {
-    theory::is_valid_tri_sub(&chord.inner, scale_type)
+    theory::is_valid_tri_sub(&chord.inner, Mode::from_name(scale_type))
+}
+
+/// Python form of a substitution: (kind, chord, placement)
+type PySubstitution = (String, PyChord, String);
+
+/// Enumerate valid substitutions for a chord in a key
+///
+/// Returns `(kind, chord, placement)` tuples, where placement is `"replace"` or
+/// `"precede"` (the substitute is played before the original chord).
+#[pyfunction]
+pub fn get_substitutions(
+    chord: &PyChord,
+    scale: &PyScaleFingerprint,
+) -> PyResult<Vec<PySubstitution>> {
+    let substitutions =
+        composer_core::get_substitutions(&chord.inner, &scale.inner).to_py_result()?;
+    Ok(substitutions
+        .into_iter()
+        .map(|s| {
+            (
+                s.kind.name().to_string(),
+                PyChord { inner: s.chord },
+                s.placement.name().to_string(),
+            )
+        })
+        .collect())
+}
+
+/// Borrowed scale from a scale name, rejecting unknown names
+fn parse_borrowed_scale(name: &str) -> PyResult<BorrowedScale> {
+    ScaleType::from_name(name)
+        .map(BorrowedScale::ScaleType)
+        .ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!("Unknown scale type: {}", name))
+        })
+}
+
+/// Reflect a chord about an axis (negative harmony)
+///
+/// `axis` is a pair of pitch classes, in semitones above the tonic, that
+/// mirror onto each other; the default `(0, 7)` swaps tonic and dominant.
+#[pyfunction]
+#[pyo3(signature = (chord, scale, axis=(0, 7)))]
+pub fn negative_harmony_mirror(
+    chord: &PyChord,
+    scale: &PyScaleFingerprint,
+    axis: (u8, u8),
+) -> PyResult<PyChord> {
+    let axis = MirrorAxis::between(axis.0, axis.1);
+    let mirrored =
+        composer_core::negative_harmony_mirror(&chord.inner, &scale.inner, axis).to_py_result()?;
+    Ok(PyChord { inner: mirrored })
+}
+
+/// Chords borrowed from the scale named `to_scale` that can stand in for a chord
+#[pyfunction]
+pub fn modal_interchange_options(
+    chord: &PyChord,
+    from_scale: &PyScaleFingerprint,
+    to_scale: &str,
+) -> PyResult<Vec<PyChord>> {
+    let to_scale = parse_borrowed_scale(to_scale)?;
+    let options =
+        composer_core::modal_interchange_options(&chord.inner, &from_scale.inner, &to_scale)
+            .to_py_result()?;
+    Ok(options.into_iter().map(|inner| PyChord { inner }).collect())
+}
+
+/// Reharmonize a progression with one strategy
+///
+/// `strategy` is `"negative_harmony"` (about `axis`), `"modal_interchange"`
+/// (borrowing from `borrowed`), `"tritone_substitution"` or
+/// `"secondary_dominants"`.
+#[pyfunction]
+#[pyo3(signature = (progression, scale, strategy, axis=(0, 7), borrowed="minor"))]
+pub fn progression_reharmonize(
+    progression: Vec<PyChord>,
+    scale: &PyScaleFingerprint,
+    strategy: &str,
+    axis: (u8, u8),
+    borrowed: &str,
+) -> PyResult<Vec<PyChord>> {
+    let strategy = match strategy {
+        "negative_harmony" => {
+            ReharmonizationStrategy::NegativeHarmony(MirrorAxis::between(axis.0, axis.1))
+        },
+        "modal_interchange" => {
+            ReharmonizationStrategy::ModalInterchange(parse_borrowed_scale(borrowed)?)
+        },
+        "tritone_substitution" => ReharmonizationStrategy::TritoneSubstitution,
+        "secondary_dominants" => ReharmonizationStrategy::SecondaryDominants,
+        _ => {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Invalid reharmonization strategy: {}",
+                strategy
+            )))
+        },
+    };
+    let progression: Vec<composer_core::Chord> = progression.into_iter().map(|c| c.inner).collect();
+    let reharmonized =
+        composer_core::progression_reharmonize(&progression, &scale.inner, &strategy)
+            .to_py_result()?;
+    Ok(reharmonized
+        .into_iter()
+        .map(|inner| PyChord { inner })
+        .collect())
+}
+
+/// Voice a chord for piano as ascending MIDI notes
+///
+/// `tonic` is the key's pitch class (0 = C); `style` is `"close"`, `"drop2"`
+/// or `"drop3"`.
+#[pyfunction]
+#[pyo3(signature = (chord, scale, tonic=0, style="close"))]
+pub fn piano_voicing(
+    chord: &PyChord,
+    scale: &PyScaleFingerprint,
+    tonic: u8,
+    style: &str,
+) -> PyResult<Vec<u8>> {
+    let style = match style {
+        "close" => PianoVoicingStyle::Close,
+        "drop2" => PianoVoicingStyle::Drop2,
+        "drop3" => PianoVoicingStyle::Drop3,
+        _ => {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Invalid piano voicing style: {}",
+                style
+            )))
+        },
+    };
+    composer_core::piano_voicing(&chord.inner, &scale.inner, tonic, style).to_py_result()
+}
+
+/// Python form of a guitar fingering: one fret per string, `None` when muted
+type PyGuitarFingering = Vec<Option<u8>>;
+
+/// Enumerate playable guitar fingerings of a chord
+///
+/// Each fingering lists one fret per string, lowest string first, with `None`
+/// for a muted string. `tuning` gives open-string MIDI notes and defaults to
+/// standard tuning.
+#[pyfunction]
+#[pyo3(signature = (chord, scale, tonic=0, max_fret_span=4, max_fret=12, tuning=None))]
+pub fn guitar_fingerings(
+    chord: &PyChord,
+    scale: &PyScaleFingerprint,
+    tonic: u8,
+    max_fret_span: u8,
+    max_fret: u8,
+    tuning: Option<Vec<u8>>,
+) -> PyResult<Vec<PyGuitarFingering>> {
+    let options = GuitarVoicingOptions {
+        tuning: tuning.map_or_else(GuitarTuning::standard, |strings| GuitarTuning { strings }),
+        max_fret,
+        max_fret_span,
+        bass_lowest: true,
+    };
+    let fingerings = composer_core::guitar_fingerings(&chord.inner, &scale.inner, tonic, &options)
+        .to_py_result()?;
+    Ok(fingerings.into_iter().map(|f| f.frets).collect())
 }
 
-/// Check if two chords are harmonically equivalent (isotonal)
+/// Reasons a modification cannot be made to a chord
+///
+/// `modification` is written as in chord symbols: "b5", "sus4", "add9",
+/// "omit5", "/1" for an inversion or "/V5" for an applied target. Returns dicts
+/// with a `reason` ("InvalidValue" or "Incompatible") and the `existing`
+/// modification it conflicts with; an empty list when the modification is
+/// allowed.
+#[pyfunction]
+pub fn validate_modification(
+    py: Python,
+    chord: &PyChord,
+    modification: &str,
+) -> PyResult<Py<PyList>> {
+    let modification = modification.parse::<ChordModification>().to_py_result()?;
+    let list = PyList::empty(py);
+    for conflict in composer_core::validate_modification(&chord.inner, &modification) {
+        let item = PyDict::new(py);
+        match conflict {
+            composer_core::ModificationConflict::InvalidValue => {
+                item.set_item("reason", "InvalidValue")?;
+                item.set_item("existing", py.None())?;
+            },
+            composer_core::ModificationConflict::Incompatible { existing } => {
+                item.set_item("reason", "Incompatible")?;
+                item.set_item("existing", existing.to_string())?;
+            },
+        }
+        list.append(item)?;
+    }
+    Ok(list.into())
+}
+
+/// Check if two chords sound the same pitch classes (isotonal)
+///
+/// Spelling, root and inversion are ignored, so a chord is isotonal with its
+/// inversions.
 #[pyfunction]
 pub fn is_isotonal(
     chord1: &PyChord,
     chord2: &PyChord,
     scale: &PyScaleFingerprint,
 ) -> PyResult<bool> {
-    // Get stable scale degrees for both chords
-    let degrees1 = theory::get_stable_scale_degrees(&chord1.inner, &scale.inner).to_py_result()?;
-    let degrees2 = theory::get_stable_scale_degrees(&chord2.inner, &scale.inner).to_py_result()?;
-
-    // Check if they have the same scale degrees (isotonal)
-    Ok(degrees1 == degrees2)
+    composer_core::is_isotonal(&chord1.inner, &chord2.inner, &scale.inner).to_py_result()
 }
 
+/// Python form of a function label: (function, target degree)
+type PyFunctionLabel = (String, u8);
+
 /// Analyze harmonic function of a chord
+///
+/// Returns `"Tonic"`, `"Subdominant"`, `"Predominant"` or `"Dominant"`, or
+/// `None` for a rest. Applied chords are labeled in the key of their target.
 #[pyfunction]
-pub fn analyze_harmonic_function(chord: &PyChord, _scale_type: &str) -> String {
-    match chord.inner.root {
-        1 | 6 | 3 => "Tonic".to_string(),   // I, vi, iii
-        4 | 2 => "Predominant".to_string(), // IV, ii
-        5 | 7 => "Dominant".to_string(),    // V, vii°
-        _ => "Chromatic".to_string(),
-    }
+pub fn analyze_harmonic_function(chord: &PyChord, scale_type: &str) -> PyResult<Option<String>> {
+    let scale = ScaleType::from_name(scale_type).ok_or_else(|| {
+        pyo3::exceptions::PyValueError::new_err(format!("Unknown scale type: {}", scale_type))
+    })?;
+    let label =
+        composer_core::harmonic_function(&chord.inner, &scale.fingerprint()).to_py_result()?;
+    Ok(label.map(|label| format!("{:?}", label.function)))
+}
+
+/// Label the harmonic function of every chord and check functional syntax
+///
+/// Returns a dict with `functions`, a `(function, target)` tuple per chord
+/// (`None` for rests, target 1 for the home key), and `violations`, a list of
+/// `(kind, index)` tuples.
+#[pyfunction]
+pub fn analyze_harmonic_functions(
+    py: Python,
+    progression: Vec<PyChord>,
+    scale: &PyScaleFingerprint,
+) -> PyResult<Py<PyDict>> {
+    let progression: Vec<composer_core::Chord> = progression.into_iter().map(|c| c.inner).collect();
+    let analysis =
+        composer_core::analyze_harmonic_function(&progression, &scale.inner).to_py_result()?;
+
+    let functions: Vec<Option<PyFunctionLabel>> = analysis
+        .functions
+        .iter()
+        .map(|label| label.map(|label| (format!("{:?}", label.function), label.target)))
+        .collect();
+    let violations: Vec<(String, usize)> = analysis
+        .violations
+        .iter()
+        .map(|violation| (violation.kind.name().to_string(), violation.index))
+        .collect();
+
+    let dict = PyDict::new(py);
+    dict.set_item("functions", functions)?;
+    dict.set_item("violations", violations)?;
+    Ok(dict.into())
+}
+
+/// Python form of a counterpoint note: (MIDI pitch or None for a rest, duration in beats)
+type PyVoiceNote = (Option<u8>, f64);
+
+/// Python form of a counterpoint violation: (kind, beat, voices, notes)
+type PyCounterpointViolation = (String, f64, (usize, usize), (usize, usize));
+
+/// Check lines against species counterpoint rules
+///
+/// Each line is a list of `(pitch, duration)` tuples from the highest voice
+/// down, with MIDI pitches (`None` for rests) and durations in beats. Returns
+/// `(kind, beat, voices, notes)` tuples in beat order, where `voices` are the
+/// two line indices, offending line first, and `notes` the index of the
+/// sounding note in each.
+#[pyfunction]
+#[pyo3(signature = (lines, beats_per_measure=4.0))]
+pub fn check_counterpoint(
+    lines: Vec<Vec<PyVoiceNote>>,
+    beats_per_measure: f64,
+) -> PyResult<Vec<PyCounterpointViolation>> {
+    let lines: Vec<Vec<VoiceNote>> = lines
+        .into_iter()
+        .map(|line| {
+            line.into_iter()
+                .map(|(pitch, duration)| VoiceNote { pitch, duration })
+                .collect()
+        })
+        .collect();
+    let violations =
+        composer_core::check_counterpoint(&lines, &CounterpointOptions { beats_per_measure })
+            .to_py_result()?;
+
+    Ok(violations
+        .into_iter()
+        .map(|violation| {
+            (
+                violation.kind.name().to_string(),
+                violation.beat,
+                violation.voices,
+                violation.notes,
+            )
+        })
+        .collect())
 }
 
 /// Convert chord letter to lowercase while preserving accidentals
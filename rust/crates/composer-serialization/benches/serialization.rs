@@ -0,0 +1,77 @@
+//! Batch chord serialization and hashing against their one-at-a-time forms
+//!
+//! Run with `cargo bench -p composer-serialization`.
+
+use composer_core::Chord;
+use composer_serialization::{fast_hash_bytes, serialize_chord, serialize_chords};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+/// The byte-by-byte recurrence `fast_hash` computed before chunking
+fn bytewise_hash(data: &[u8]) -> u32 {
+    let mut hash: u32 = 0;
+    for &byte in data {
+        hash = hash
+            .wrapping_shl(5)
+            .wrapping_sub(hash)
+            .wrapping_add(byte as u32);
+    }
+    hash & 0x7FFFFFFF
+}
+
+/// Corpus-like mix: mostly triads and sevenths, one altered chord in eight
+fn corpus(len: usize) -> Vec<Chord> {
+    (0..len)
+        .map(|i| {
+            let root = (i % 7) as u8 + 1;
+            match i % 8 {
+                0 => Chord::seventh(root)
+                    .and_then(|chord| chord.with_alteration("b9"))
+                    .unwrap(),
+                1 | 2 => Chord::seventh(root).unwrap(),
+                3 => Chord::triad(root)
+                    .and_then(|chord| chord.with_inversion(1))
+                    .unwrap(),
+                _ => Chord::triad(root).unwrap(),
+            }
+        })
+        .collect()
+}
+
+fn bench_serialize_chords(c: &mut Criterion) {
+    // Small enough to stay in cache: much larger inputs are bound by reading
+    // the (several hundred byte) Chord structs rather than by the packing
+    let chords = corpus(1_000);
+    let mut group = c.benchmark_group("serialize_chords");
+    group.throughput(Throughput::Elements(chords.len() as u64));
+    group.bench_function("per_chord", |b| {
+        b.iter(|| {
+            black_box(&chords)
+                .iter()
+                .map(serialize_chord)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        })
+    });
+    group.bench_function("batch", |b| {
+        b.iter(|| serialize_chords(black_box(&chords)).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_fast_hash(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fast_hash");
+    for len in [64, 4096, 1 << 20] {
+        let data: Vec<u8> = (0..len).map(|i| (i * 7 % 251) as u8).collect();
+        group.throughput(Throughput::Bytes(len as u64));
+        group.bench_with_input(BenchmarkId::new("bytewise", len), &data, |b, data| {
+            b.iter(|| bytewise_hash(black_box(data)))
+        });
+        group.bench_with_input(BenchmarkId::new("chunked", len), &data, |b, data| {
+            b.iter(|| fast_hash_bytes(black_box(data)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_serialize_chords, bench_fast_hash);
+criterion_main!(benches);
@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Err(violation) = composer_serialization::check_chord_binary_input(data) {
+        panic!("{}", violation);
+    }
+});
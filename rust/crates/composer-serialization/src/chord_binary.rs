@@ -169,6 +169,105 @@ pub fn serialize_chord(chord: &Chord) -> SerializationResult<ChordBinary> {
     Ok(binary)
 }
 
+/// Chord type index (bits 5-3 of byte 1) by chord type; 0xFF marks unsupported
+const CHORD_TYPE_INDEX: [u8; 16] = {
+    let mut table = [0xFFu8; 16];
+    table[5] = 0;
+    table[7] = 1;
+    table[9] = 2;
+    table[11] = 3;
+    table[13] = 4;
+    table
+};
+
+/// Serializes many chords, producing the same bytes as [`serialize_chord`]
+/// for each.
+///
+/// Chords without alterations or a borrowed scale, the bulk of most corpora,
+/// are packed with table lookups and a single validity check instead of the
+/// per-byte encoders. Fails with the error of the first chord that
+/// [`serialize_chord`] rejects.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_serialization::{serialize_chord, serialize_chords};
+/// use composer_core::Chord;
+///
+/// let chords = vec![
+///     Chord::triad(1)?,
+///     Chord::seventh(5)?.with_inversion(1)?,
+///     Chord::new(2, 9)?.with_alteration("b9")?,
+/// ];
+///
+/// let binaries = serialize_chords(&chords)?;
+/// for (chord, binary) in chords.iter().zip(&binaries) {
+///     assert_eq!(serialize_chord(chord)?, *binary);
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn serialize_chords(chords: &[Chord]) -> SerializationResult<Vec<ChordBinary>> {
+    let mut binaries = Vec::with_capacity(chords.len());
+    for chord in chords {
+        let binary = match pack_plain_chord(chord) {
+            Some(binary) => binary,
+            None => serialize_chord(chord)?,
+        };
+        binaries.push(binary);
+    }
+    Ok(binaries)
+}
+
+/// Pack a chord without alterations or a borrowed scale, or `None` when it
+/// needs the full encoder (including to report invalid fields)
+#[inline]
+fn pack_plain_chord(chord: &Chord) -> Option<ChordBinary> {
+    let type_index = CHORD_TYPE_INDEX
+        .get(chord.chord_type as usize)
+        .copied()
+        .unwrap_or(0xFF);
+    let plain = (chord.root <= 7)
+        & (chord.inversion <= 3)
+        & (chord.applied <= 7)
+        & (type_index != 0xFF)
+        & chord.alterations.is_empty()
+        & chord.borrowed.is_none();
+    if !plain {
+        return None;
+    }
+
+    let adds = chord.adds.iter().fold(0u8, |bits, &add| {
+        bits | match add {
+            9 => 0x08,
+            6 => 0x04,
+            4 => 0x02,
+            _ => 0,
+        }
+    });
+    let suspensions = chord.suspensions.iter().fold(0u8, |bits, &suspension| {
+        bits | match suspension {
+            4 => 0x80,
+            2 => 0x40,
+            _ => 0,
+        }
+    });
+    let omits = chord.omits.iter().fold(0u8, |bits, &omit| {
+        bits | match omit {
+            5 => 0x02,
+            3 => 0x01,
+            _ => 0,
+        }
+    });
+
+    Some([
+        (chord.root << 4) | adds,
+        (chord.inversion << 6) | (type_index << 3) | chord.applied,
+        0,
+        suspensions,
+        omits,
+    ])
+}
+
 /// Deserializes a chord from the 5-byte binary format back to a Chord struct.
 ///
 /// This function reverses the [`serialize_chord`] process, reconstructing a complete
@@ -709,6 +808,15 @@ pub fn hex_to_chord_binary(hex: &str) -> SerializationResult<ChordBinary> {
         });
     }
 
+    if let Some((position, digit)) = hex.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        return Err(SerializationError::InvalidBinaryFormat {
+            reason: format!(
+                "invalid hex digit {:?} at position {} in {}",
+                digit, position, hex
+            ),
+        });
+    }
+
     let mut binary = [0u8; 5];
     for (i, chunk) in hex.as_bytes().chunks(2).enumerate() {
         if i >= 5 {
@@ -799,6 +907,45 @@ mod tests {
         assert!(deserialized.borrowed.is_some());
     }
 
+    #[test]
+    fn test_serialize_chords_matches_serialize_chord() {
+        let mut chords = vec![
+            test_chord_c_major(),
+            test_chord_v7(),
+            test_chord_complex(),
+            Chord::rest(),
+        ];
+        for root in 1..=7 {
+            for chord_type in [5, 7, 9, 11, 13] {
+                let chord = Chord::new(root, chord_type)
+                    .unwrap()
+                    .with_inversion(root % 4)
+                    .unwrap()
+                    .with_add(if root % 2 == 0 { 6 } else { 9 })
+                    .unwrap()
+                    .with_suspension(if chord_type == 7 { 4 } else { 2 })
+                    .unwrap()
+                    .with_omit(if root > 4 { 5 } else { 3 })
+                    .unwrap();
+                chords.push(chord);
+            }
+        }
+
+        let binaries = serialize_chords(&chords).unwrap();
+        assert_eq!(binaries.len(), chords.len());
+        for (chord, binary) in chords.iter().zip(&binaries) {
+            assert_eq!(serialize_chord(chord).unwrap(), *binary);
+        }
+
+        let mut invalid = test_chord_v7();
+        invalid.chord_type = 6;
+        chords.push(invalid);
+        assert!(matches!(
+            serialize_chords(&chords),
+            Err(SerializationError::InvalidChordData { .. })
+        ));
+    }
+
     #[test]
     fn test_serialize_rest_chord() {
         let chord = Chord::rest();
@@ -872,6 +1019,10 @@ mod tests {
         let back_to_binary = hex_to_chord_binary(&hex).unwrap();
 
         assert_eq!(binary, back_to_binary);
+
+        // Signs and non-ASCII input are rejected rather than parsed
+        assert!(hex_to_chord_binary("+f00000000").is_err());
+        assert!(hex_to_chord_binary("0000000é").is_err());
     }
 
     #[test]
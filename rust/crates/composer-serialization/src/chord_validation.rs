@@ -0,0 +1,381 @@
+//! Field-by-field validation of the 5-byte chord format
+//!
+//! [`deserialize_chord`] answers only whether a payload decodes. The report built
+//! here lists every field of the format with its bits and decoded value, marks
+//! the fields that are out of range, and checks that the decoded chord encodes
+//! back to the same bytes.
+
+use crate::chord_binary::{
+    chord_binary_to_hex, deserialize_chord, hex_to_chord_binary, serialize_chord, ChordBinary,
+};
+use crate::error::{SerializationError, SerializationResult};
+use composer_core::Chord;
+use serde::{Deserialize, Serialize};
+
+/// How [`validate_chord_binary`] treats bits the format reserves
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValidationMode {
+    /// Reserved bits must be zero
+    #[default]
+    Strict,
+    /// Reserved bits are reported as warnings and ignored, so payloads from a
+    /// newer format revision decode to the fields this version knows
+    Lenient,
+}
+
+/// Status of one field of a chord binary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldStatus {
+    Valid,
+    Warning,
+    Invalid,
+}
+
+/// One field of a chord binary and what it decoded to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChordBinaryField {
+    /// Field name (e.g. "root", "chord_type", "reserved")
+    pub name: String,
+    /// Index of the byte holding the field (0-4)
+    pub byte: usize,
+    /// Bits of the byte holding the field
+    pub mask: u8,
+    /// Field bits, shifted down to start at bit 0
+    pub raw: u8,
+    /// Decoded value (e.g. "7", "b9, #11", "none")
+    pub value: String,
+    /// Field status
+    pub status: FieldStatus,
+    /// Why the field is not valid; empty for valid fields
+    pub message: String,
+}
+
+/// Field-by-field validation of a chord binary, from [`validate_chord_binary`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChordBinaryReport {
+    /// Validated bytes
+    pub bytes: ChordBinary,
+    /// Lowercase hex of `bytes`
+    pub hex: String,
+    /// Treatment of reserved bits
+    pub mode: ValidationMode,
+    /// Every field of the format, in byte and bit order
+    pub fields: Vec<ChordBinaryField>,
+    /// Chord the fields decode to, when they do
+    pub chord: Option<Chord>,
+    /// Why the fields do not form a valid chord, or do not encode back to the
+    /// same bytes
+    pub chord_error: Option<String>,
+}
+
+impl ChordBinaryReport {
+    /// Check if the bytes decode to a chord and no field is invalid
+    pub fn is_valid(&self) -> bool {
+        self.chord.is_some()
+            && self.chord_error.is_none()
+            && self
+                .fields
+                .iter()
+                .all(|field| field.status != FieldStatus::Invalid)
+    }
+
+    /// Check if any field produced a warning
+    pub fn has_warnings(&self) -> bool {
+        self.fields
+            .iter()
+            .any(|field| field.status == FieldStatus::Warning)
+    }
+
+    /// Fields that failed validation
+    pub fn errors(&self) -> Vec<&ChordBinaryField> {
+        self.fields
+            .iter()
+            .filter(|field| field.status == FieldStatus::Invalid)
+            .collect()
+    }
+}
+
+/// Decoded value of a field, or its value and why it is invalid
+type Describe = Result<String, (String, String)>;
+
+/// Named scales by their index in byte 3
+const SCALE_NAMES: [&str; 6] = [
+    "major",
+    "minor",
+    "harmonic_minor",
+    "dorian",
+    "mixolydian",
+    "chromatic",
+];
+
+/// Validates a 5-byte chord payload field by field.
+///
+/// Fails only when `bytes` is not 5 bytes long; otherwise the report says
+/// which fields decoded to what and why the payload is invalid, if it is.
+/// The decoded chord must also pass [`Chord::validate`] and serialize back
+/// to the same bytes, less any reserved bits ignored in
+/// [`ValidationMode::Lenient`].
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_serialization::{validate_chord_binary, FieldStatus, ValidationMode};
+///
+/// // Chord type index 7 is unassigned
+/// let report = validate_chord_binary(&[0x10, 0x38, 0, 0, 0], ValidationMode::Strict)?;
+/// assert!(!report.is_valid());
+/// let error = &report.errors()[0];
+/// assert_eq!((error.name.as_str(), error.raw), ("chord_type", 7));
+///
+/// // A reserved bit set by a newer format revision
+/// let bytes = [0x50, 0x08, 0, 0, 0x80];
+/// assert!(!validate_chord_binary(&bytes, ValidationMode::Strict)?.is_valid());
+/// let report = validate_chord_binary(&bytes, ValidationMode::Lenient)?;
+/// assert!(report.is_valid() && report.has_warnings());
+/// assert_eq!(report.chord.unwrap().chord_type, 7);
+/// # Ok::<(), composer_serialization::SerializationError>(())
+/// ```
+pub fn validate_chord_binary(
+    bytes: &[u8],
+    mode: ValidationMode,
+) -> SerializationResult<ChordBinaryReport> {
+    let bytes: ChordBinary =
+        bytes
+            .try_into()
+            .map_err(|_| SerializationError::InvalidBinaryFormat {
+                reason: format!("chord binary must be exactly 5 bytes, got {}", bytes.len()),
+            })?;
+
+    let mut fields = Vec::new();
+    let mut field = |byte: usize, mask: u8, name: &str, describe: &dyn Fn(u8) -> Describe| {
+        let raw = (bytes[byte] & mask) >> mask.trailing_zeros();
+        let (value, status, message) = match describe(raw) {
+            Ok(value) => (value, FieldStatus::Valid, String::new()),
+            Err((value, message)) => (value, FieldStatus::Invalid, message),
+        };
+        fields.push(ChordBinaryField {
+            name: name.to_string(),
+            byte,
+            mask,
+            raw,
+            value,
+            status,
+            message,
+        });
+    };
+
+    let reserved = |raw: u8| -> Describe {
+        if raw == 0 {
+            Ok("0".to_string())
+        } else {
+            Err((
+                format!("{:#x}", raw),
+                "reserved bits must be zero".to_string(),
+            ))
+        }
+    };
+    let flags = |names: &[&str], raw: u8| -> String {
+        let set: Vec<&str> = names
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| raw & (1 << (names.len() - 1 - bit)) != 0)
+            .map(|(_, name)| *name)
+            .collect();
+        if set.is_empty() {
+            "none".to_string()
+        } else {
+            set.join(", ")
+        }
+    };
+
+    field(0, 0x80, "reserved", &reserved);
+    field(0, 0x70, "root", &|raw| {
+        Ok(if raw == 0 {
+            "rest".to_string()
+        } else {
+            raw.to_string()
+        })
+    });
+    field(0, 0x0E, "adds", &|raw| Ok(flags(&["9", "6", "4"], raw)));
+    field(0, 0x01, "reserved", &reserved);
+    field(1, 0xC0, "inversion", &|raw| Ok(raw.to_string()));
+    field(1, 0x38, "chord_type", &|raw| match raw {
+        0..=4 => Ok([5, 7, 9, 11, 13][raw as usize].to_string()),
+        _ => Err((
+            "unassigned".to_string(),
+            format!("chord type index {} is not one of 0-4", raw),
+        )),
+    });
+    field(1, 0x07, "applied", &|raw| {
+        Ok(if raw == 0 {
+            "none".to_string()
+        } else {
+            raw.to_string()
+        })
+    });
+    field(2, 0xC0, "reserved", &reserved);
+    field(2, 0x3F, "alterations", &|raw| {
+        Ok(flags(&["b13", "#11", "#9", "b9", "#5", "b5"], raw))
+    });
+    field(3, 0xC0, "suspensions", &|raw| Ok(flags(&["4", "2"], raw)));
+    field(3, 0x3F, "borrowed", &|raw| {
+        let data = raw & 0x1F;
+        if data == 0 {
+            Ok("none".to_string())
+        } else if raw & 0x20 != 0 {
+            Ok(format!("numeric {:+}", data as i8 - 8))
+        } else {
+            SCALE_NAMES
+                .get(data as usize)
+                .map(|name| format!("named {}", name))
+                .ok_or_else(|| {
+                    (
+                        "unknown".to_string(),
+                        format!("scale index {} is not one of 0-5", data),
+                    )
+                })
+        }
+    });
+    field(4, 0xFC, "reserved", &reserved);
+    field(4, 0x03, "omits", &|raw| Ok(flags(&["5", "3"], raw)));
+
+    // Reserved bits are only a warning when lenient, and are then ignored
+    let mut known = bytes;
+    for field in fields.iter_mut().filter(|field| field.name == "reserved") {
+        known[field.byte] &= !field.mask;
+        if mode == ValidationMode::Lenient && field.status == FieldStatus::Invalid {
+            field.status = FieldStatus::Warning;
+            field.message = "reserved bits are set; ignored in lenient mode".to_string();
+        }
+    }
+
+    let decodable = fields
+        .iter()
+        .all(|field| field.status != FieldStatus::Invalid || field.name == "reserved");
+    let (chord, chord_error) = if decodable {
+        match deserialize_chord(&known) {
+            Ok(chord) => {
+                let error = match serialize_chord(&chord) {
+                    Ok(reencoded) if reencoded == known => None,
+                    Ok(reencoded) => Some(format!(
+                        "decoded chord encodes as {}",
+                        chord_binary_to_hex(&reencoded)
+                    )),
+                    Err(e) => Some(format!("decoded chord does not encode: {}", e)),
+                };
+                (Some(chord), error)
+            },
+            Err(e) => (None, Some(e.to_string())),
+        }
+    } else {
+        (None, None)
+    };
+
+    Ok(ChordBinaryReport {
+        bytes,
+        hex: chord_binary_to_hex(&bytes),
+        mode,
+        fields,
+        chord,
+        chord_error,
+    })
+}
+
+/// Validates a chord given as 10 hex digits; see [`validate_chord_binary`]
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_serialization::{validate_chord_hex, ValidationMode};
+///
+/// let report = validate_chord_hex("5008000000", ValidationMode::Strict)?;
+/// assert!(report.is_valid());
+/// assert_eq!(report.fields[1].value, "5");
+///
+/// let error = validate_chord_hex("50080000zz", ValidationMode::Strict).unwrap_err();
+/// assert!(error.to_string().contains("position 8"));
+/// # Ok::<(), composer_serialization::SerializationError>(())
+/// ```
+pub fn validate_chord_hex(
+    hex: &str,
+    mode: ValidationMode,
+) -> SerializationResult<ChordBinaryReport> {
+    validate_chord_binary(&hex_to_chord_binary(hex)?, mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use composer_core::BorrowedScale;
+
+    #[test]
+    fn test_serialized_chords_validate() {
+        let chords = [
+            Chord::rest(),
+            Chord::triad(1).unwrap(),
+            Chord::new(5, 9)
+                .unwrap()
+                .with_inversion(1)
+                .unwrap()
+                .with_alteration("b9")
+                .unwrap()
+                .with_suspension(4)
+                .unwrap()
+                .with_add(6)
+                .unwrap()
+                .with_omit(5)
+                .unwrap()
+                .with_borrowed_scale(BorrowedScale::Named("dorian".to_string()))
+                .unwrap(),
+        ];
+        for chord in chords {
+            let binary = serialize_chord(&chord).unwrap();
+            let report = validate_chord_binary(&binary, ValidationMode::Strict).unwrap();
+            assert!(report.is_valid(), "{:?}", report);
+            assert!(!report.has_warnings());
+            assert_eq!(report.chord.unwrap().root, chord.root);
+        }
+    }
+
+    #[test]
+    fn test_fields_explain_invalid_payloads() {
+        let report =
+            validate_chord_binary(&[0x5F, 0x3F, 0x0C, 0x1F, 0x00], ValidationMode::Lenient)
+                .unwrap();
+        assert!(!report.is_valid());
+        assert!(report.chord.is_none());
+        let errors: Vec<_> = report
+            .errors()
+            .iter()
+            .map(|field| (field.name.as_str(), field.value.as_str()))
+            .collect();
+        assert_eq!(
+            errors,
+            [("chord_type", "unassigned"), ("borrowed", "unknown")]
+        );
+
+        let root = &report.fields[1];
+        assert_eq!((root.byte, root.mask, root.raw), (0, 0x70, 5));
+        assert_eq!(report.fields[2].value, "9, 6, 4");
+        assert_eq!(report.fields[8].value, "#9, b9");
+        assert_eq!(report.fields[0].status, FieldStatus::Valid);
+        assert_eq!(report.fields[3].status, FieldStatus::Warning);
+    }
+
+    #[test]
+    fn test_invalid_chords_are_reported() {
+        // b5 and #5 together fail chord validation
+        let report =
+            validate_chord_binary(&[0x10, 0x00, 0x03, 0, 0], ValidationMode::Strict).unwrap();
+        assert!(report.errors().is_empty());
+        assert!(!report.is_valid());
+        assert!(report.chord_error.is_some());
+
+        assert!(matches!(
+            validate_chord_binary(&[0x10, 0, 0, 0], ValidationMode::Strict),
+            Err(SerializationError::InvalidBinaryFormat { .. })
+        ));
+    }
+}
@@ -0,0 +1,344 @@
+//! Optional compression of binary assets
+//!
+//! Compressed data is a single standard zstd or LZ4 frame, recognised by the
+//! frame's magic bytes, so readers detect compression without a separate flag
+//! and compressed assets can also be unpacked with the `zstd` and `lz4` tools.
+//! Decompression streams into a buffer capped at a caller-supplied size, so a
+//! small hostile input cannot expand past the memory limits.
+
+use crate::error::{SerializationError, SerializationResult};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::io::Read;
+
+/// Magic bytes opening a zstd frame (0xFD2FB528, little-endian)
+pub const ZSTD_FRAME_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// Magic bytes opening an LZ4 frame (0x184D2204, little-endian)
+pub const LZ4_FRAME_MAGIC: [u8; 4] = [0x04, 0x22, 0x4D, 0x18];
+
+/// Compression applied to a serialized asset
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    /// Stored as-is
+    #[default]
+    None,
+    /// zstd frame; the better ratio, for assets sent over the network
+    Zstd,
+    /// LZ4 frame; the faster codec, for assets read often from local storage
+    Lz4,
+}
+
+impl Compression {
+    /// Name used in messages and configuration ("none", "zstd" or "lz4")
+    pub fn name(&self) -> &'static str {
+        match self {
+            Compression::None => "none",
+            Compression::Zstd => "zstd",
+            Compression::Lz4 => "lz4",
+        }
+    }
+
+    /// Check if this build can compress and decompress with this codec
+    pub fn is_available(&self) -> bool {
+        match self {
+            Compression::None => true,
+            Compression::Zstd => cfg!(feature = "zstd"),
+            Compression::Lz4 => cfg!(feature = "lz4"),
+        }
+    }
+}
+
+impl std::str::FromStr for Compression {
+    type Err = SerializationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Compression::None),
+            "zstd" => Ok(Compression::Zstd),
+            "lz4" => Ok(Compression::Lz4),
+            _ => Err(SerializationError::InvalidFormat {
+                message: format!("unknown compression {:?}; expected none, zstd or lz4", s),
+            }),
+        }
+    }
+}
+
+/// Detects the compression of serialized data from its magic bytes.
+///
+/// Data that does not open with a zstd or LZ4 frame is reported as
+/// [`Compression::None`].
+pub fn detect_compression(data: &[u8]) -> Compression {
+    if data.starts_with(&ZSTD_FRAME_MAGIC) {
+        Compression::Zstd
+    } else if data.starts_with(&LZ4_FRAME_MAGIC) {
+        Compression::Lz4
+    } else {
+        Compression::None
+    }
+}
+
+/// Compresses serialized data into a single frame.
+///
+/// [`Compression::None`] returns the data unchanged. Fails with
+/// [`SerializationError::InvalidFormat`] for codecs this build was compiled
+/// without (see the `zstd` and `lz4` crate features).
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_serialization::{compress, decompress, detect_compression, Compression};
+///
+/// let data = vec![7u8; 4096];
+/// let packed = compress(&data, Compression::Zstd)?;
+/// assert!(packed.len() < data.len());
+/// assert_eq!(detect_compression(&packed), Compression::Zstd);
+/// assert_eq!(decompress(&packed, data.len())?, data);
+/// # Ok::<(), composer_serialization::SerializationError>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`decompress`] - Reverse this, with a size cap
+pub fn compress(data: &[u8], compression: Compression) -> SerializationResult<Vec<u8>> {
+    match compression {
+        Compression::None => Ok(data.to_vec()),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => Ok(ruzstd::encoding::compress_to_vec(
+            data,
+            ruzstd::encoding::CompressionLevel::Fastest,
+        )),
+        #[cfg(feature = "lz4")]
+        Compression::Lz4 => {
+            use std::io::Write;
+
+            // The content size lets readers detect truncation, which the LZ4
+            // frame format otherwise cannot tell apart from the end of the stream
+            let frame_info = lz4_flex::frame::FrameInfo::new()
+                .content_size(Some(data.len() as u64))
+                .content_checksum(true);
+            let mut encoder =
+                lz4_flex::frame::FrameEncoder::with_frame_info(frame_info, Vec::new());
+            encoder.write_all(data).map_err(io_error)?;
+            encoder.finish().map_err(|e| SerializationError::IoError {
+                details: e.to_string(),
+            })
+        },
+        #[allow(unreachable_patterns)]
+        _ => Err(unavailable(compression)),
+    }
+}
+
+/// Decompresses data if it opens with a zstd or LZ4 frame.
+///
+/// Uncompressed data is borrowed unchanged. Compressed data is decoded as a
+/// stream and fails with [`SerializationError::LimitExceeded`] as soon as it
+/// expands past `max_len` bytes, so peak memory stays near `max_len` whatever
+/// the frame claims.
+pub fn decompress(data: &[u8], max_len: usize) -> SerializationResult<Cow<'_, [u8]>> {
+    let compression = detect_compression(data);
+    if compression == Compression::None {
+        return Ok(Cow::Borrowed(data));
+    }
+
+    let declared = lz4_content_size(data);
+    if let Some(declared) = declared.filter(|&size| size > max_len as u64) {
+        return Err(SerializationError::LimitExceeded {
+            resource: "decompressed bytes".to_string(),
+            limit: max_len as u64,
+            actual: declared,
+        });
+    }
+
+    let output = read_capped(stream_decoder(data, compression, max_len)?, max_len)?;
+    if declared.is_some_and(|size| size != output.len() as u64) {
+        return Err(SerializationError::CorruptedBinary {
+            details: format!(
+                "lz4 frame is truncated: {} of {} bytes",
+                output.len(),
+                declared.unwrap_or_default()
+            ),
+        });
+    }
+    Ok(Cow::Owned(output))
+}
+
+/// Content size declared in an LZ4 frame descriptor, when present
+fn lz4_content_size(data: &[u8]) -> Option<u64> {
+    const CONTENT_SIZE_FLAG: u8 = 0x08;
+    if detect_compression(data) != Compression::Lz4 || data.len() < 14 {
+        return None;
+    }
+    if data[4] & CONTENT_SIZE_FLAG == 0 {
+        return None;
+    }
+    let mut size = [0u8; 8];
+    size.copy_from_slice(&data[6..14]);
+    Some(u64::from_le_bytes(size))
+}
+
+/// Decompresses at most the first `len` bytes of data.
+///
+/// Only as much of the frame as `len` needs is decoded, which makes reading a
+/// header from a large compressed asset cheap. Uncompressed data is borrowed.
+pub fn decompress_prefix(data: &[u8], len: usize) -> SerializationResult<Cow<'_, [u8]>> {
+    let compression = detect_compression(data);
+    if compression == Compression::None {
+        return Ok(Cow::Borrowed(&data[..len.min(data.len())]));
+    }
+
+    let mut prefix = Vec::with_capacity(len);
+    stream_decoder(data, compression, usize::MAX)?
+        .take(len as u64)
+        .read_to_end(&mut prefix)
+        .map_err(io_error)?;
+    Ok(Cow::Owned(prefix))
+}
+
+/// Streaming decoder for one compressed frame read from `source`
+#[cfg_attr(not(feature = "zstd"), allow(unused_variables))]
+pub(crate) fn stream_decoder<'a, R: Read + 'a>(
+    source: R,
+    compression: Compression,
+    max_len: usize,
+) -> SerializationResult<Box<dyn Read + 'a>> {
+    match compression {
+        Compression::None => Ok(Box::new(source)),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => {
+            // The window is the decoder's own buffer; a frame asking for more
+            // than the output cap cannot be decoded within it anyway
+            let window = (max_len as u64).max(1 << 20);
+            ruzstd::decoding::StreamingDecoder::new_with_max_window_size(source, window)
+                .map(|decoder| Box::new(decoder) as Box<dyn Read>)
+                .map_err(|e| SerializationError::CorruptedBinary {
+                    details: format!("invalid zstd frame: {}", e),
+                })
+        },
+        #[cfg(feature = "lz4")]
+        Compression::Lz4 => Ok(Box::new(lz4_flex::frame::FrameDecoder::new(source))),
+        #[allow(unreachable_patterns)]
+        _ => Err(unavailable(compression)),
+    }
+}
+
+/// Reader that fails with [`SerializationError::LimitExceeded`] once more than
+/// `limit` bytes have been read through it
+pub(crate) struct CappedReader<R> {
+    inner: R,
+    limit: usize,
+    read: usize,
+}
+
+impl<R: Read> CappedReader<R> {
+    pub(crate) fn new(inner: R, limit: usize) -> Self {
+        Self {
+            inner,
+            limit,
+            read: 0,
+        }
+    }
+}
+
+impl<R: Read> Read for CappedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.read += read;
+        if self.read > self.limit {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                SerializationError::LimitExceeded {
+                    resource: "decompressed bytes".to_string(),
+                    limit: self.limit as u64,
+                    actual: self.read as u64,
+                },
+            ));
+        }
+        Ok(read)
+    }
+}
+
+/// Read a stream to the end, failing once it passes `max_len` bytes
+fn read_capped(reader: impl Read, max_len: usize) -> SerializationResult<Vec<u8>> {
+    let mut output = Vec::new();
+    reader
+        .take((max_len as u64).saturating_add(1))
+        .read_to_end(&mut output)
+        .map_err(io_error)?;
+
+    if output.len() > max_len {
+        return Err(SerializationError::LimitExceeded {
+            resource: "decompressed bytes".to_string(),
+            limit: max_len as u64,
+            actual: output.len() as u64,
+        });
+    }
+    Ok(output)
+}
+
+fn io_error(error: std::io::Error) -> SerializationError {
+    SerializationError::CorruptedBinary {
+        details: format!("decompression failed: {}", error),
+    }
+}
+
+fn unavailable(compression: Compression) -> SerializationError {
+    SerializationError::InvalidFormat {
+        message: format!(
+            "{} compression is not enabled in this build",
+            compression.name()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<u8> {
+        (0..20_000u32).map(|i| (i % 7 * 31 % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_codecs_roundtrip() {
+        let data = sample();
+        for compression in [Compression::None, Compression::Zstd, Compression::Lz4] {
+            let packed = compress(&data, compression).unwrap();
+            assert_eq!(detect_compression(&packed), compression);
+            assert_eq!(decompress(&packed, data.len()).unwrap(), data);
+            assert_eq!(*decompress_prefix(&packed, 6).unwrap(), data[..6]);
+            assert_eq!(compression.name().parse::<Compression>(), Ok(compression));
+        }
+        assert!(compress(&data, Compression::Zstd).unwrap().len() < data.len() / 10);
+    }
+
+    #[test]
+    fn test_decompression_is_capped() {
+        let data = sample();
+        for compression in [Compression::Zstd, Compression::Lz4] {
+            let packed = compress(&data, compression).unwrap();
+            assert!(matches!(
+                decompress(&packed, data.len() - 1),
+                Err(SerializationError::LimitExceeded { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_corrupted_frames_fail_cleanly() {
+        let data = sample();
+        for compression in [Compression::Zstd, Compression::Lz4] {
+            let packed = compress(&data, compression).unwrap();
+            // A bare magic number is an empty LZ4 stream, so cut after it
+            for len in [8, packed.len() / 2, packed.len() - 1] {
+                assert!(decompress(&packed[..len], data.len()).is_err());
+            }
+            for i in 4..packed.len().min(64) {
+                let mut corrupted = packed.clone();
+                corrupted[i] ^= 0x5A;
+                let _ = decompress(&corrupted, data.len());
+            }
+        }
+    }
+}
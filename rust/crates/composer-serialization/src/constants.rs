@@ -9,6 +9,10 @@ pub const BEAT_TO_TICK_MULTIPLIER: u32 = 24;
 /// Maximum representable beat value in 16-bit space
 pub const MAX_BEAT_VALUE: u32 = 65536 / 24; // floor(65536 / 24) = 2730
 
+/// Most bar lines [`crate::tokenize_timeline`] writes, bounding the output for
+/// timelines decoded from untrusted tokens (several hours of music in 4/4)
+pub const MAX_TIMELINE_BARS: u64 = 100_000;
+
 /// Minimum octave range
 pub const OCTAVE_RANGE_MIN: u8 = 2;
 
@@ -30,12 +34,66 @@ pub const RAW_NOTE_TOKEN_PREFIX: &str = "R_";
 pub const OCTAVE_TOKEN_PREFIX: &str = "O_";
 pub const REST_NOTE_TOKEN: &str = "NOTE-REST";
 pub const REST_CHORD_TOKEN: &str = "CHORD-REST";
+pub const METRIC_TOKEN_PREFIX: &str = "M_";
+pub const CHORD_GROUP_OPEN: &str = "<CHORD>";
+pub const CHORD_GROUP_CLOSE: &str = "</CHORD>";
+pub const NOTES_GROUP_OPEN: &str = "<NOTES>";
+pub const NOTES_GROUP_CLOSE: &str = "</NOTES>";
+pub const BAR_TOKEN: &str = "BAR";
+pub const KEY_TOKEN_PREFIX: &str = "K_";
+pub const TIME_SIGNATURE_TOKEN_PREFIX: &str = "TS_";
+pub const CHORD_TOKEN_PREFIX: &str = "CH_";
+
+/// Vocabulary-only tokens for ML training; never written to token streams
+pub const PAD_TOKEN: &str = "<PAD>";
+pub const UNKNOWN_TOKEN: &str = "<UNK>";
+pub const BOS_TOKEN: &str = "<BOS>";
+pub const EOS_TOKEN: &str = "<EOS>";
+
+/// Longest duration with its own vocabulary token: four bars of 4/4
+pub const VOCABULARY_MAX_DURATION_TICKS: u32 = 16 * TICKS_PER_BEAT;
+
+/// Token library binary header
+pub const TOKEN_LIBRARY_MAGIC: [u8; 4] = *b"CTOK";
+/// Token vocabulary version written by this release; version 1 wrote raw note
+/// pitch classes and durations in decimal, version 2 in hex
+pub const TOKEN_LIBRARY_FORMAT_VERSION: u8 = 2;
+/// Oldest token vocabulary version that can still be upgraded
+pub const TOKEN_LIBRARY_MIN_FORMAT_VERSION: u8 = 1;
+pub const TOKEN_LIBRARY_HEADER_LEN: usize = 9;
+
+/// Token vocabulary binary header
+pub const TOKEN_VOCABULARY_MAGIC: [u8; 4] = *b"CVOC";
+pub const TOKEN_VOCABULARY_HEADER_LEN: usize = 9;
+
+/// Trie binary header
+pub const TRIE_BINARY_MAGIC: [u8; 4] = *b"CTRI";
+/// Trie binary version written by this release; version 1 had no header and
+/// left the id width to the reader, version 2 records it in the header
+pub const TRIE_BINARY_FORMAT_VERSION: u8 = 2;
+/// Oldest trie binary version that can still be read and migrated
+pub const TRIE_BINARY_MIN_FORMAT_VERSION: u8 = 1;
+pub const TRIE_BINARY_HEADER_LEN: usize = 6;
+/// Trie header flag: every id is followed by two key tonic bytes
+pub const TRIE_FLAG_KEY_TONIC: u8 = 0x01;
+/// Trie header flag: ids index a table of source references after the root node
+pub const TRIE_FLAG_REFERENCES: u8 = 0x02;
+
+/// Timeline binary header
+pub const TIMELINE_BINARY_MAGIC: [u8; 4] = *b"CTML";
+/// Timeline binary version written by this release
+pub const TIMELINE_BINARY_FORMAT_VERSION: u8 = 1;
+pub const TIMELINE_BINARY_HEADER_LEN: usize = 5;
 
 /// Token validation patterns
 pub const DURATION_PATTERN: &str = r"^D_[0-9a-f]+$";
 pub const RAW_NOTE_PATTERN: &str = r"^R_[0-9a-b]$";
 pub const OCTAVE_PATTERN: &str = r"^O_[2-7]$";
 pub const CHORD_CLUSTER_PATTERN: &str = r"^(R_[0-9a-b]-?)+$";
+pub const METRIC_PATTERN: &str = r"^M_(DOWN|MID|BEAT|ANT|OFF)$";
+pub const KEY_PATTERN: &str = r"^K_[0-9a-b]_(MAJ|MIN)$";
+pub const TIME_SIGNATURE_PATTERN: &str = r"^TS_[1-9][0-9]{0,2}_(1|2|4|8|16|32)$";
+pub const CHORD_PATTERN: &str = r"^CH_[0-9a-f]{10}$";
 
 /// Error messages
 pub const ERROR_INVALID_CHORD_DATA: &str = "Invalid chord data structure";
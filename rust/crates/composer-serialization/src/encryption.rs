@@ -0,0 +1,367 @@
+//! Passphrase encryption of serialized data at rest
+//!
+//! Available with the `encryption` crate feature. Data is encrypted with
+//! XChaCha20-Poly1305 under a key derived from the passphrase with Argon2id, so
+//! a wrong passphrase and any tampering are both detected on decryption.
+//!
+//! Encrypted data opens with a [`ENCRYPTED_HEADER_LEN`]-byte header (big-endian):
+//! magic `CENC` (4), format version (1), Argon2id memory in KiB (4), iterations
+//! (4) and parallelism (4), salt (16) and nonce (24). The ciphertext and its
+//! 16-byte tag follow. The whole header is authenticated along with the data.
+//! Salt and nonce are random for every encryption, so encrypting the same data
+//! twice gives unrelated output.
+
+use crate::compression::Compression;
+use crate::error::{SerializationError, SerializationResult};
+use crate::timeline_binary::{deserialize_timeline, serialize_timeline_compressed};
+use crate::tokenization::Timeline;
+use crate::trie_binary::{deserialize_trie, serialize_trie_compressed, TrieNode};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, OsRng, Payload};
+use chacha20poly1305::{KeyInit, XChaCha20Poly1305, XNonce};
+use composer_config::{APPLICATION, MEMORY};
+
+/// Magic bytes opening encrypted data
+pub const ENCRYPTED_MAGIC: [u8; 4] = *b"CENC";
+
+/// Encrypted data format version written by this build
+pub const ENCRYPTION_FORMAT_VERSION: u8 = 1;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const TAG_LEN: usize = 16;
+
+/// Length of the header before the ciphertext
+pub const ENCRYPTED_HEADER_LEN: usize = 4 + 1 + 12 + SALT_LEN + NONCE_LEN;
+
+/// Largest Argon2id memory cost a header may ask for, so hostile files cannot
+/// exhaust memory before the tag is checked
+const MAX_MEMORY_KIB: u32 = MEMORY.memory_usage_max_mb * 1024;
+
+/// Most Argon2id iterations a header may ask for
+const MAX_ITERATIONS: u32 = 64;
+
+/// Most Argon2id lanes a header may ask for
+const MAX_PARALLELISM: u32 = 16;
+
+/// Argon2id cost of deriving a key from a passphrase
+///
+/// Higher costs make guessing passphrases slower, and decrypting too. The
+/// costs are stored with the data, so they can change without breaking
+/// existing files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    /// Memory in KiB
+    pub memory_kib: u32,
+    /// Passes over the memory
+    pub iterations: u32,
+    /// Lanes
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    /// OWASP's recommended minimum for Argon2id: 19 MiB, 2 iterations, 1 lane
+    fn default() -> Self {
+        Self {
+            memory_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+/// Check if data starts with the encrypted data magic bytes
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(&ENCRYPTED_MAGIC)
+}
+
+/// Encrypts data with a passphrase, using [`KdfParams::default`].
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_serialization::{decrypt, encrypt, is_encrypted, SerializationError};
+///
+/// let library = b"user chord library";
+/// let sealed = encrypt(library, "correct horse battery staple")?;
+/// assert!(is_encrypted(&sealed));
+/// assert_eq!(decrypt(&sealed, "correct horse battery staple")?, library);
+/// assert_eq!(decrypt(&sealed, "wrong"), Err(SerializationError::DecryptionFailed));
+/// # Ok::<(), SerializationError>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`decrypt`] - Reverse this
+/// - [`serialize_trie_encrypted`] and [`serialize_timeline_encrypted`] - Serialize and encrypt
+pub fn encrypt(plaintext: &[u8], passphrase: &str) -> SerializationResult<Vec<u8>> {
+    encrypt_with_params(plaintext, passphrase, &KdfParams::default())
+}
+
+/// Encrypts data with a passphrase and explicit key derivation costs
+pub fn encrypt_with_params(
+    plaintext: &[u8],
+    passphrase: &str,
+    params: &KdfParams,
+) -> SerializationResult<Vec<u8>> {
+    if passphrase.is_empty() {
+        return Err(SerializationError::InvalidFormat {
+            message: "encryption passphrase must not be empty".to_string(),
+        });
+    }
+    check_params(params)?;
+
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let mut output = Vec::with_capacity(ENCRYPTED_HEADER_LEN + plaintext.len() + TAG_LEN);
+    output.extend_from_slice(&ENCRYPTED_MAGIC);
+    output.push(ENCRYPTION_FORMAT_VERSION);
+    output.extend_from_slice(&params.memory_kib.to_be_bytes());
+    output.extend_from_slice(&params.iterations.to_be_bytes());
+    output.extend_from_slice(&params.parallelism.to_be_bytes());
+    output.extend_from_slice(&salt);
+    output.extend_from_slice(&nonce);
+
+    let cipher = derive_cipher(passphrase, &salt, params)?;
+    let ciphertext = cipher
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad: &output,
+            },
+        )
+        .map_err(|_| SerializationError::EncryptionFailed {
+            reason: "plaintext too large for XChaCha20-Poly1305".to_string(),
+        })?;
+    output.extend_from_slice(&ciphertext);
+    Ok(output)
+}
+
+/// Decrypts data made by [`encrypt`].
+///
+/// Fails with [`SerializationError::DecryptionFailed`] for a wrong passphrase
+/// or modified data, without telling the two apart, and with
+/// [`SerializationError::LimitExceeded`] if the header asks for key derivation
+/// costs beyond this build's caps.
+pub fn decrypt(data: &[u8], passphrase: &str) -> SerializationResult<Vec<u8>> {
+    if !is_encrypted(data) {
+        return Err(SerializationError::InvalidBinaryFormat {
+            reason: "data is not encrypted".to_string(),
+        });
+    }
+    if data.len() < ENCRYPTED_HEADER_LEN + TAG_LEN {
+        return Err(SerializationError::UnexpectedEof);
+    }
+    let version = data[4];
+    if version != ENCRYPTION_FORMAT_VERSION {
+        return Err(SerializationError::UnsupportedVersion {
+            version: format!(
+                "encryption format {} (this build reads {})",
+                version, ENCRYPTION_FORMAT_VERSION
+            ),
+        });
+    }
+
+    let (header, ciphertext) = data.split_at(ENCRYPTED_HEADER_LEN);
+    let read_u32 = |offset: usize| {
+        u32::from_be_bytes([
+            header[offset],
+            header[offset + 1],
+            header[offset + 2],
+            header[offset + 3],
+        ])
+    };
+    let params = KdfParams {
+        memory_kib: read_u32(5),
+        iterations: read_u32(9),
+        parallelism: read_u32(13),
+    };
+    check_params(&params)?;
+    let salt = &header[17..17 + SALT_LEN];
+    let nonce = &header[17 + SALT_LEN..];
+
+    derive_cipher(passphrase, salt, &params)?
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| SerializationError::DecryptionFailed)
+}
+
+/// Serialize a trie, compress it, and encrypt it with a passphrase
+pub fn serialize_trie_encrypted(
+    trie: &TrieNode,
+    compression: Compression,
+    passphrase: &str,
+) -> SerializationResult<Vec<u8>> {
+    encrypt(&serialize_trie_compressed(trie, compression)?, passphrase)
+}
+
+/// Decrypt and deserialize a trie written by [`serialize_trie_encrypted`]
+pub fn deserialize_trie_encrypted(
+    data: &[u8],
+    passphrase: &str,
+    include_key_tonic: bool,
+) -> SerializationResult<TrieNode> {
+    deserialize_trie(&decrypt(data, passphrase)?, include_key_tonic)
+}
+
+/// Serialize a timeline, compress it, and encrypt it with a passphrase
+pub fn serialize_timeline_encrypted(
+    timeline: &Timeline,
+    compression: Compression,
+    passphrase: &str,
+) -> SerializationResult<Vec<u8>> {
+    encrypt(
+        &serialize_timeline_compressed(timeline, compression)?,
+        passphrase,
+    )
+}
+
+/// Decrypt and deserialize a timeline written by [`serialize_timeline_encrypted`]
+pub fn deserialize_timeline_encrypted(
+    data: &[u8],
+    passphrase: &str,
+) -> SerializationResult<Timeline> {
+    deserialize_timeline(&decrypt(data, passphrase)?)
+}
+
+fn check_params(params: &KdfParams) -> SerializationResult<()> {
+    for (resource, actual, limit) in [
+        ("kdf memory KiB", params.memory_kib, MAX_MEMORY_KIB),
+        ("kdf iterations", params.iterations, MAX_ITERATIONS),
+        ("kdf parallelism", params.parallelism, MAX_PARALLELISM),
+    ] {
+        if actual > limit {
+            return Err(SerializationError::LimitExceeded {
+                resource: resource.to_string(),
+                limit: limit as u64,
+                actual: actual as u64,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Cipher keyed by Argon2id over the passphrase; the application's encryption
+/// salt is Argon2's secret input, so keys are specific to Composer
+fn derive_cipher(
+    passphrase: &str,
+    salt: &[u8],
+    params: &KdfParams,
+) -> SerializationResult<XChaCha20Poly1305> {
+    let invalid = |e: argon2::Error| SerializationError::InvalidFormat {
+        message: format!("invalid key derivation parameters: {}", e),
+    };
+    let params = Params::new(
+        params.memory_kib,
+        params.iterations,
+        params.parallelism,
+        Some(32),
+    )
+    .map_err(invalid)?;
+    let argon2 = Argon2::new_with_secret(
+        APPLICATION.encryption_salt.as_bytes(),
+        Algorithm::Argon2id,
+        Version::V0x13,
+        params,
+    )
+    .map_err(invalid)?;
+
+    let mut key = [0u8; 32];
+    argon2
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(invalid)?;
+    Ok(XChaCha20Poly1305::new(&key.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenization::{TokenEvent, TokenEventType};
+
+    /// Cheap enough for tests
+    const FAST: KdfParams = KdfParams {
+        memory_kib: 64,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    #[test]
+    fn test_encryption_round_trip() {
+        let data = b"progressions".repeat(100);
+        let sealed = encrypt_with_params(&data, "passphrase", &FAST).unwrap();
+        assert_eq!(sealed.len(), ENCRYPTED_HEADER_LEN + data.len() + TAG_LEN);
+        assert_eq!(decrypt(&sealed, "passphrase").unwrap(), data);
+
+        // Fresh salt and nonce every time
+        let again = encrypt_with_params(&data, "passphrase", &FAST).unwrap();
+        assert_ne!(sealed, again);
+
+        assert!(encrypt_with_params(&data, "", &FAST).is_err());
+        assert!(matches!(
+            decrypt(&data, "passphrase"),
+            Err(SerializationError::InvalidBinaryFormat { .. })
+        ));
+    }
+
+    #[test]
+    fn test_tampering_is_detected() {
+        let sealed = encrypt_with_params(b"library", "passphrase", &FAST).unwrap();
+        assert_eq!(
+            decrypt(&sealed, "Passphrase"),
+            Err(SerializationError::DecryptionFailed)
+        );
+
+        // Every byte after the version is authenticated
+        for i in 5..sealed.len() {
+            let mut tampered = sealed.clone();
+            tampered[i] ^= 0x01;
+            assert!(decrypt(&tampered, "passphrase").is_err(), "byte {}", i);
+        }
+        assert_eq!(
+            decrypt(&sealed[..sealed.len() - 1], "passphrase"),
+            Err(SerializationError::DecryptionFailed)
+        );
+
+        // Hostile costs are refused before any key is derived
+        let mut costly = sealed.clone();
+        costly[5..9].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(matches!(
+            decrypt(&costly, "passphrase"),
+            Err(SerializationError::LimitExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn test_encrypted_tries_and_timelines() {
+        let mut trie = TrieNode::new();
+        trie.add_pattern(&[vec![0x10, 0, 0, 0, 0], vec![0x50, 0x08, 0, 0, 0]], 3);
+        let sealed = serialize_trie_encrypted(&trie, Compression::Lz4, "pass").unwrap();
+        assert_eq!(
+            deserialize_trie_encrypted(&sealed, "pass", false).unwrap(),
+            trie
+        );
+
+        let timeline = Timeline {
+            events: vec![TokenEvent {
+                beat: 0.0,
+                event_type: TokenEventType::Rest,
+            }],
+            total_duration: 1.0,
+            ..Default::default()
+        };
+        let sealed = serialize_timeline_encrypted(&timeline, Compression::None, "pass").unwrap();
+        let restored = deserialize_timeline_encrypted(&sealed, "pass").unwrap();
+        assert_eq!(restored.events.len(), 1);
+        assert!(deserialize_timeline_encrypted(&sealed, "wrong").is_err());
+    }
+}
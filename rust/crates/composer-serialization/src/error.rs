@@ -1,67 +1,174 @@
 //! Error types for serialization operations
 
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 /// Error types for serialization operations
-#[derive(Debug, Error, Clone, PartialEq)]
+///
+/// Serializes as `{"code": ..., "details": {...}}`, where `code` is the stable
+/// code from [`SerializationError::code`] and `details` holds the variant's fields.
+#[derive(Debug, Error, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "code", content = "details")]
 pub enum SerializationError {
     #[error("Invalid binary format: {reason}")]
+    #[serde(rename = "invalid-binary-format")]
     InvalidBinaryFormat { reason: String },
 
     #[error("Unsupported serialization version: {version}")]
+    #[serde(rename = "unsupported-version")]
     UnsupportedVersion { version: String },
 
     #[error("Corrupted binary data: {details}")]
+    #[serde(rename = "corrupted-binary")]
     CorruptedBinary { details: String },
 
     #[error("Token library not initialized")]
+    #[serde(rename = "token-library-missing")]
     TokenLibraryMissing,
 
     #[error("Invalid token format: {token}")]
+    #[serde(rename = "invalid-token-format")]
     InvalidTokenFormat { token: String },
 
+    #[error("Invalid token stream at token {index} ({token}): {reason}")]
+    #[serde(rename = "invalid-token-stream")]
+    InvalidTokenStream {
+        index: usize,
+        token: String,
+        reason: String,
+    },
+
     #[error("Serialization buffer overflow")]
+    #[serde(rename = "buffer-overflow")]
     BufferOverflow,
 
     #[error("Deserialization failed: {reason}")]
+    #[serde(rename = "deserialization-failed")]
     DeserializationFailed { reason: String },
 
     #[error("Invalid chord data structure: {field}")]
+    #[serde(rename = "invalid-chord-data")]
     InvalidChordData { field: String },
 
     #[error("Trie structure validation failed: {reason}")]
+    #[serde(rename = "invalid-trie-structure")]
     InvalidTrieStructure { reason: String },
 
     #[error("IO error during serialization: {details}")]
+    #[serde(rename = "io-failed")]
     IoError { details: String },
 
     #[error("Invalid format: {message}")]
+    #[serde(rename = "invalid-format")]
     InvalidFormat { message: String },
 
     #[error("Unexpected end of file")]
+    #[serde(rename = "unexpected-eof")]
     UnexpectedEof,
+
+    #[error("Resource limit exceeded: {resource} {actual} exceeds limit {limit}")]
+    #[serde(rename = "limit-exceeded")]
+    LimitExceeded {
+        resource: String,
+        limit: u64,
+        actual: u64,
+    },
+
+    #[error("Deserialization timed out after {elapsed_ms}ms (limit: {limit_ms}ms)")]
+    #[serde(rename = "deserialization-timeout")]
+    Timeout { elapsed_ms: u64, limit_ms: u64 },
+
+    #[error("Decryption failed: wrong passphrase or tampered data")]
+    #[serde(rename = "decryption-failed")]
+    DecryptionFailed,
+
+    #[error("Encryption failed: {reason}")]
+    #[serde(rename = "encryption-failed")]
+    EncryptionFailed { reason: String },
 }
 
 /// Result type for serialization operations
 pub type SerializationResult<T> = Result<T, SerializationError>;
 
 impl SerializationError {
+    /// Stable machine-readable code for this error, one of the `ERROR_*`
+    /// constants in `composer_config`
+    pub fn code(&self) -> &'static str {
+        match self {
+            SerializationError::InvalidBinaryFormat { .. } => {
+                composer_config::ERROR_INVALID_BINARY_FORMAT
+            },
+            SerializationError::UnsupportedVersion { .. } => {
+                composer_config::ERROR_UNSUPPORTED_VERSION
+            },
+            SerializationError::CorruptedBinary { .. } => composer_config::ERROR_CORRUPTED_BINARY,
+            SerializationError::TokenLibraryMissing => composer_config::ERROR_TOKEN_LIBRARY_MISSING,
+            SerializationError::InvalidTokenFormat { .. } => {
+                composer_config::ERROR_INVALID_TOKEN_FORMAT
+            },
+            SerializationError::InvalidTokenStream { .. } => {
+                composer_config::ERROR_INVALID_TOKEN_STREAM
+            },
+            SerializationError::BufferOverflow => composer_config::ERROR_BUFFER_OVERFLOW,
+            SerializationError::DeserializationFailed { .. } => {
+                composer_config::ERROR_DESERIALIZATION_FAILED
+            },
+            SerializationError::InvalidChordData { .. } => {
+                composer_config::ERROR_INVALID_CHORD_DATA
+            },
+            SerializationError::InvalidTrieStructure { .. } => {
+                composer_config::ERROR_INVALID_TRIE_STRUCTURE
+            },
+            SerializationError::IoError { .. } => composer_config::ERROR_IO_FAILED,
+            SerializationError::InvalidFormat { .. } => composer_config::ERROR_INVALID_FORMAT,
+            SerializationError::UnexpectedEof => composer_config::ERROR_UNEXPECTED_EOF,
+            SerializationError::LimitExceeded { .. } => composer_config::ERROR_LIMIT_EXCEEDED,
+            SerializationError::Timeout { .. } => composer_config::ERROR_DESERIALIZATION_TIMEOUT,
+            SerializationError::DecryptionFailed => composer_config::ERROR_DECRYPTION_FAILED,
+            SerializationError::EncryptionFailed { .. } => composer_config::ERROR_ENCRYPTION_FAILED,
+        }
+    }
+
     /// Check if this error is recoverable
     pub fn is_recoverable(&self) -> bool {
         match self {
             SerializationError::InvalidBinaryFormat { .. }
             | SerializationError::UnsupportedVersion { .. }
             | SerializationError::CorruptedBinary { .. }
-            | SerializationError::BufferOverflow => false,
+            | SerializationError::BufferOverflow
+            | SerializationError::LimitExceeded { .. }
+            | SerializationError::EncryptionFailed { .. } => false,
 
             SerializationError::TokenLibraryMissing
             | SerializationError::InvalidTokenFormat { .. }
+            | SerializationError::InvalidTokenStream { .. }
             | SerializationError::DeserializationFailed { .. }
             | SerializationError::InvalidChordData { .. }
             | SerializationError::InvalidTrieStructure { .. }
             | SerializationError::IoError { .. }
             | SerializationError::InvalidFormat { .. }
-            | SerializationError::UnexpectedEof => true,
+            | SerializationError::UnexpectedEof
+            | SerializationError::Timeout { .. }
+            | SerializationError::DecryptionFailed => true,
+        }
+    }
+}
+
+impl From<std::io::Error> for SerializationError {
+    /// Keeps serialization errors raised inside readers and writers, such as
+    /// limits on decompressed size, and maps early end of input to `UnexpectedEof`
+    fn from(error: std::io::Error) -> Self {
+        if let Some(inner) = error
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<SerializationError>())
+        {
+            return inner.clone();
+        }
+        match error.kind() {
+            std::io::ErrorKind::UnexpectedEof => SerializationError::UnexpectedEof,
+            _ => SerializationError::IoError {
+                details: error.to_string(),
+            },
         }
     }
 }
@@ -1,18 +1,59 @@
-//! Hash and compression functions for serialization
+//! Hash, compression and share id functions for serialization
 
-use crate::error::SerializationResult;
+use crate::error::{SerializationError, SerializationResult};
+use composer_config::APPLICATION;
 use std::collections::HashMap;
 
+/// Bytes hashed per step by [`fast_hash_bytes`]
+const HASH_CHUNK: usize = 16;
+
+/// Powers of 31 modulo 2^32, from 31^0 to 31^HASH_CHUNK
+const HASH_POWERS: [u32; HASH_CHUNK + 1] = {
+    let mut powers = [1u32; HASH_CHUNK + 1];
+    let mut i = 1;
+    while i <= HASH_CHUNK {
+        powers[i] = powers[i - 1].wrapping_mul(31);
+        i += 1;
+    }
+    powers
+};
+
 /// Fast hash function for 32-bit hash generation
 pub fn fast_hash(data: &str) -> u32 {
+    fast_hash_bytes(data.as_bytes())
+}
+
+/// [`fast_hash`] over raw bytes.
+///
+/// The hash is `hash * 31 + byte` for each byte. Sixteen steps of that
+/// recurrence expand to one multiply of the running hash plus sixteen
+/// independent products, which the compiler vectorizes, so long inputs hash
+/// several times faster than byte by byte with the same result.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_serialization::{fast_hash, fast_hash_bytes};
+///
+/// let corpus = "I-IV-V-I ".repeat(1000);
+/// assert_eq!(fast_hash_bytes(corpus.as_bytes()), fast_hash(&corpus));
+/// ```
+pub fn fast_hash_bytes(data: &[u8]) -> u32 {
     let mut hash: u32 = 0;
 
-    for byte in data.bytes() {
+    let mut chunks = data.chunks_exact(HASH_CHUNK);
+    for chunk in &mut chunks {
+        let mut sum: u32 = 0;
+        for (i, &byte) in chunk.iter().enumerate() {
+            sum = sum.wrapping_add((byte as u32).wrapping_mul(HASH_POWERS[HASH_CHUNK - 1 - i]));
+        }
+        hash = hash.wrapping_mul(HASH_POWERS[HASH_CHUNK]).wrapping_add(sum);
+    }
+    for &byte in chunks.remainder() {
         hash = hash
             .wrapping_shl(5)
             .wrapping_sub(hash)
             .wrapping_add(byte as u32);
-        // Ensure 32-bit overflow behavior is handled by wrapping operations above
     }
 
     // Return absolute value by clearing sign bit
@@ -92,6 +133,296 @@ pub fn scale40_decode(encoded: &str) -> SerializationResult<[bool; 12]> {
     Ok(fingerprint)
 }
 
+/// Fewest distinct alphabet characters a [`ShareIdCodec`] accepts
+pub const SHARE_ID_MIN_ALPHABET_LEN: usize = 16;
+
+/// Longest payload, in bytes, a [`ShareIdCodec`] encodes
+///
+/// Enough for a progression of 200 chords. Ids longer than the longest
+/// encodable id are rejected before decoding, which takes time quadratic in the
+/// id length.
+pub const SHARE_ID_MAX_PAYLOAD_LEN: usize = 1024;
+
+/// Reversible short ids for sharing payloads such as serialized progressions,
+/// in the style of hashids.
+///
+/// The payload's bytes are written as one large number in the base of the
+/// alphabet, after a first "lottery" character derived from the payload picks
+/// a salted shuffle of the alphabet, so similar payloads give dissimilar ids.
+/// One alphabet character is kept back as a guard; ids shorter than the minimum
+/// length are padded after it. The same salt, alphabet and minimum length always
+/// give the same id for a payload, so ids make stable links; a different salt
+/// gives unrelated ids.
+///
+/// Ids are obfuscated, not encrypted: anyone with the salt can read them.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_serialization::{SerializationError, ShareIdCodec};
+///
+/// let codec = ShareIdCodec::new("my salt", "abcdefghijklmnopqrstuvwxyz0123456789", 10)?;
+/// let id = codec.encode(&[0x10, 0x00, 0x00, 0x00, 0x00])?;
+/// assert!(id.len() >= 10);
+/// assert_eq!(codec.decode(&id)?, [0x10, 0x00, 0x00, 0x00, 0x00]);
+/// # Ok::<(), SerializationError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShareIdCodec {
+    salt: Vec<u8>,
+    alphabet: Vec<u8>,
+    guard: u8,
+    min_length: usize,
+    max_length: usize,
+}
+
+impl ShareIdCodec {
+    /// Codec configured by the `hash_*` fields of [`APPLICATION`]
+    pub fn application() -> SerializationResult<Self> {
+        Self::new(
+            APPLICATION.hash_salt,
+            APPLICATION.hash_alphabet,
+            APPLICATION.hash_min_length as usize,
+        )
+    }
+
+    /// Create a codec; repeated alphabet characters are ignored
+    ///
+    /// Fails with [`SerializationError::InvalidFormat`] unless the alphabet is
+    /// ASCII without whitespace and has at least [`SHARE_ID_MIN_ALPHABET_LEN`]
+    /// distinct characters.
+    pub fn new(salt: &str, alphabet: &str, min_length: usize) -> SerializationResult<Self> {
+        let mut unique: Vec<u8> = Vec::new();
+        for byte in alphabet.bytes() {
+            if !byte.is_ascii_graphic() {
+                return Err(SerializationError::InvalidFormat {
+                    message: "share id alphabet must be printable ASCII".to_string(),
+                });
+            }
+            if !unique.contains(&byte) {
+                unique.push(byte);
+            }
+        }
+        if unique.len() < SHARE_ID_MIN_ALPHABET_LEN {
+            return Err(SerializationError::InvalidFormat {
+                message: format!(
+                    "share id alphabet needs at least {} distinct characters, got {}",
+                    SHARE_ID_MIN_ALPHABET_LEN,
+                    unique.len()
+                ),
+            });
+        }
+
+        let salt = salt.as_bytes().to_vec();
+        consistent_shuffle(&mut unique, &salt);
+        let guard = unique.pop().unwrap_or_default();
+
+        // The encoded number is a 1 byte followed by the payload, so below
+        // 2^(8 * SHARE_ID_MAX_PAYLOAD_LEN + 1); ids add the lottery character
+        let bits = (8 * SHARE_ID_MAX_PAYLOAD_LEN + 1) as f64;
+        let digits = (bits / (unique.len() as f64).log2()).ceil() as usize;
+        Ok(Self {
+            salt,
+            alphabet: unique,
+            guard,
+            min_length,
+            max_length: min_length.max(digits + 1),
+        })
+    }
+
+    /// Length of the longest id this codec makes
+    pub fn max_id_len(&self) -> usize {
+        self.max_length
+    }
+
+    /// Encode a payload as a share id
+    ///
+    /// Fails with [`SerializationError::LimitExceeded`] for payloads longer than
+    /// [`SHARE_ID_MAX_PAYLOAD_LEN`].
+    pub fn encode(&self, payload: &[u8]) -> SerializationResult<String> {
+        if payload.len() > SHARE_ID_MAX_PAYLOAD_LEN {
+            return Err(SerializationError::LimitExceeded {
+                resource: "share id payload bytes".to_string(),
+                limit: SHARE_ID_MAX_PAYLOAD_LEN as u64,
+                actual: payload.len() as u64,
+            });
+        }
+
+        let lottery = self.alphabet[fast_hash_bytes(payload) as usize % self.alphabet.len()];
+        let alphabet = self.shuffled_for(lottery);
+
+        // A leading 1 keeps the payload's leading zero bytes
+        let mut number = Vec::with_capacity(payload.len() + 1);
+        number.push(1);
+        number.extend_from_slice(payload);
+
+        let mut id = vec![lottery];
+        id.extend(
+            to_base(number, alphabet.len())
+                .into_iter()
+                .map(|digit| alphabet[digit]),
+        );
+
+        if id.len() < self.min_length {
+            id.push(self.guard);
+            let mut padding = alphabet.clone();
+            consistent_shuffle(&mut padding, &alphabet);
+            let missing = self.min_length - id.len();
+            id.extend(padding.iter().cycle().take(missing));
+        }
+
+        // Every byte comes from the ASCII alphabet
+        Ok(String::from_utf8(id).unwrap_or_default())
+    }
+
+    /// Decode a share id made by [`ShareIdCodec::encode`] with the same configuration
+    ///
+    /// Fails with [`SerializationError::LimitExceeded`] for ids longer than
+    /// [`ShareIdCodec::max_id_len`], before any decoding, and with
+    /// [`SerializationError::InvalidFormat`] for anything else that is not such
+    /// an id, including ids made with a different salt or alphabet.
+    pub fn decode(&self, id: &str) -> SerializationResult<Vec<u8>> {
+        if id.len() > self.max_length {
+            return Err(SerializationError::LimitExceeded {
+                resource: "share id length".to_string(),
+                limit: self.max_length as u64,
+                actual: id.len() as u64,
+            });
+        }
+
+        let invalid = || SerializationError::InvalidFormat {
+            message: format!("invalid share id {:?}", id),
+        };
+
+        let bytes = id.as_bytes();
+        let (&lottery, rest) = bytes.split_first().ok_or_else(invalid)?;
+        if !self.alphabet.contains(&lottery) {
+            return Err(invalid());
+        }
+        let alphabet = self.shuffled_for(lottery);
+
+        let body = rest.split(|&byte| byte == self.guard).next().unwrap_or(&[]);
+        let digits = body
+            .iter()
+            .map(|byte| alphabet.iter().position(|c| c == byte))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(invalid)?;
+        let number = from_base(&digits, alphabet.len());
+        let payload = match number.split_first() {
+            Some((1, payload)) => payload.to_vec(),
+            _ => return Err(invalid()),
+        };
+
+        // Only the canonical id of a payload is accepted, which also checks the
+        // lottery character and padding
+        if self.encode(&payload)? != id {
+            return Err(invalid());
+        }
+        Ok(payload)
+    }
+
+    /// Alphabet for an id opening with `lottery`
+    fn shuffled_for(&self, lottery: u8) -> Vec<u8> {
+        let mut key = Vec::with_capacity(self.salt.len() + 1);
+        key.push(lottery);
+        key.extend_from_slice(&self.salt);
+        let mut alphabet = self.alphabet.clone();
+        consistent_shuffle(&mut alphabet, &key);
+        alphabet
+    }
+}
+
+/// Generates a short share id for a payload, such as a serialized progression.
+///
+/// Uses [`ShareIdCodec::application`], configured by `APPLICATION.hash_salt`,
+/// `hash_alphabet` and `hash_min_length`, so ids are stable across releases
+/// with the same configuration. Fails for payloads longer than
+/// [`SHARE_ID_MAX_PAYLOAD_LEN`].
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_serialization::{generate_share_id, resolve_share_id, SerializationError};
+///
+/// let progression = [0x10, 0, 0, 0, 0, 0x40, 0, 0, 0, 0, 0x50, 0x08, 0, 0, 0];
+/// let id = generate_share_id(&progression)?;
+/// assert_eq!(resolve_share_id(&id)?, progression);
+/// # Ok::<(), SerializationError>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`resolve_share_id`] - Recover the payload
+/// - [`ShareIdCodec`] - Ids with a different salt, alphabet or length
+pub fn generate_share_id(payload: &[u8]) -> SerializationResult<String> {
+    ShareIdCodec::application()?.encode(payload)
+}
+
+/// Recovers the payload of an id made by [`generate_share_id`]
+///
+/// Ids usually come from untrusted links; overlong ones are rejected before
+/// decoding (see [`ShareIdCodec::decode`]).
+pub fn resolve_share_id(id: &str) -> SerializationResult<Vec<u8>> {
+    ShareIdCodec::application()?.decode(id)
+}
+
+/// Salted shuffle from hashids: the same alphabet and salt always give the same order
+fn consistent_shuffle(alphabet: &mut [u8], salt: &[u8]) {
+    if salt.is_empty() {
+        return;
+    }
+
+    let mut v = 0;
+    let mut p = 0;
+    for i in (1..alphabet.len()).rev() {
+        v %= salt.len();
+        let n = salt[v] as usize;
+        p += n;
+        let j = (n + v + p) % i;
+        alphabet.swap(i, j);
+        v += 1;
+    }
+}
+
+/// Digits of a big-endian byte string in `base`, most significant first
+fn to_base(mut number: Vec<u8>, base: usize) -> Vec<usize> {
+    let mut digits = Vec::new();
+    while !number.is_empty() {
+        let mut remainder = 0usize;
+        let mut quotient = Vec::with_capacity(number.len());
+        for &byte in &number {
+            let value = remainder * 256 + byte as usize;
+            let digit = value / base;
+            if !quotient.is_empty() || digit > 0 {
+                quotient.push(digit as u8);
+            }
+            remainder = value % base;
+        }
+        digits.push(remainder);
+        number = quotient;
+    }
+    digits.reverse();
+    digits
+}
+
+/// Big-endian byte string of digits in `base`, reversing [`to_base`]
+fn from_base(digits: &[usize], base: usize) -> Vec<u8> {
+    let mut number: Vec<u8> = Vec::new();
+    for &digit in digits {
+        let mut carry = digit;
+        for byte in number.iter_mut().rev() {
+            let value = *byte as usize * base + carry;
+            *byte = value as u8;
+            carry = value >> 8;
+        }
+        while carry > 0 {
+            number.insert(0, carry as u8);
+            carry >>= 8;
+        }
+    }
+    number
+}
+
 // Helper functions
 
 fn compress_bit_pattern(pattern: u16) -> Vec<u8> {
@@ -272,6 +603,22 @@ mod tests {
         assert!(hash1 < 0x80000000); // Should be positive
     }
 
+    #[test]
+    fn test_fast_hash_matches_bytewise_recurrence() {
+        let bytewise = |data: &[u8]| {
+            data.iter().fold(0u32, |hash, &byte| {
+                hash.wrapping_shl(5)
+                    .wrapping_sub(hash)
+                    .wrapping_add(byte as u32)
+            }) & 0x7FFFFFFF
+        };
+        let data: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+        for len in (0..40).chain([255, 256, 999, 1000]) {
+            assert_eq!(fast_hash_bytes(&data[..len]), bytewise(&data[..len]));
+        }
+        assert_eq!(fast_hash(""), 0);
+    }
+
     #[test]
     fn test_fold_hash() {
         let initial = fast_hash("initial");
@@ -281,6 +628,89 @@ mod tests {
         assert!(folded < 0x80000000); // Should be positive
     }
 
+    #[test]
+    fn test_share_ids_round_trip() {
+        let codec = ShareIdCodec::application().unwrap();
+        let payloads: [&[u8]; 5] = [&[], &[0], &[0, 0, 7], &[0xFF; 3], &[0x10, 0, 0, 0, 0]];
+        for payload in payloads {
+            let id = generate_share_id(payload).unwrap();
+            assert!(id.len() >= APPLICATION.hash_min_length as usize);
+            assert!(id
+                .bytes()
+                .all(|byte| APPLICATION.hash_alphabet.as_bytes().contains(&byte)));
+            assert_eq!(resolve_share_id(&id).unwrap(), payload);
+            assert_eq!(codec.encode(payload).unwrap(), id);
+        }
+
+        // Long payloads need no padding
+        let long: Vec<u8> = (0..=255).collect();
+        assert_eq!(
+            resolve_share_id(&generate_share_id(&long).unwrap()).unwrap(),
+            long
+        );
+
+        // Pinned: changing this breaks every link already shared
+        assert_eq!(
+            generate_share_id(&[0x10, 0, 0, 0, 0]).unwrap(),
+            SHARE_ID_OF_TONIC
+        );
+    }
+
+    /// Share id of a tonic triad under the default configuration
+    const SHARE_ID_OF_TONIC: &str = "tJijTxPZ";
+
+    #[test]
+    fn test_share_ids_reject_tampering() {
+        let id = generate_share_id(b"I-IV-V-I").unwrap();
+        assert!(resolve_share_id("").is_err());
+        assert!(resolve_share_id("!!!!!!!!").is_err());
+
+        let other = ShareIdCodec::new("another salt", APPLICATION.hash_alphabet, 8).unwrap();
+        assert_ne!(other.encode(b"I-IV-V-I").unwrap(), id);
+        assert!(other.decode(&id).is_err());
+
+        for i in 0..id.len() {
+            let mut tampered = id.clone().into_bytes();
+            tampered[i] = if tampered[i] == b'a' { b'b' } else { b'a' };
+            let tampered = String::from_utf8(tampered).unwrap();
+            if let Ok(payload) = resolve_share_id(&tampered) {
+                assert_eq!(generate_share_id(&payload).unwrap(), tampered);
+            }
+        }
+
+        assert!(ShareIdCodec::new("salt", "abc", 8).is_err());
+        assert!(ShareIdCodec::new("salt", "abcdefghijklmno p", 8).is_err());
+    }
+
+    #[test]
+    fn test_share_ids_bounded_length() {
+        let codec = ShareIdCodec::application().unwrap();
+        let largest = codec.encode(&[0xFF; SHARE_ID_MAX_PAYLOAD_LEN]).unwrap();
+        assert!(largest.len() <= codec.max_id_len());
+        assert_eq!(
+            resolve_share_id(&largest).unwrap(),
+            [0xFF; SHARE_ID_MAX_PAYLOAD_LEN]
+        );
+
+        let too_long = vec![0; SHARE_ID_MAX_PAYLOAD_LEN + 1];
+        assert!(matches!(
+            codec.encode(&too_long),
+            Err(SerializationError::LimitExceeded { .. })
+        ));
+
+        // Rejected on length alone, without decoding
+        let oversized = "a".repeat(codec.max_id_len() + 1);
+        assert_eq!(
+            resolve_share_id(&oversized),
+            Err(SerializationError::LimitExceeded {
+                resource: "share id length".to_string(),
+                limit: codec.max_id_len() as u64,
+                actual: oversized.len() as u64,
+            })
+        );
+        assert!(resolve_share_id(&"a".repeat(1_000_000)).is_err());
+    }
+
     #[test]
     fn test_scale40_roundtrip() {
         let original = [
@@ -0,0 +1,421 @@
+//! Invariant checks for serialized data
+//!
+//! These checks back the crate's property tests and are public so that embedders who
+//! extend Composer (custom encoders, trie builders, plugins producing chords) can
+//! verify their output preserves the same guarantees.
+//!
+//! The `check_*_input` functions take arbitrary bytes, as from a user upload or a
+//! network payload. Decoders must reject malformed input with an error rather than
+//! panic, and whatever they accept must round-trip. They are the entry points of the
+//! fuzz targets under `fuzz/`.
+
+use crate::chord_binary::{
+    chord_binary_to_hex, deserialize_chord, hex_to_chord_binary, serialize_chord,
+};
+use crate::error::SerializationError;
+use crate::tokenization::{
+    deserialize_token_library, detokenize_polyphonic, detokenize_tokens, parse_chord_binary_token,
+    parse_duration_token, parse_key_token, parse_metric_token, parse_time_signature_token,
+    serialize_token_library, split_token_stream, tokenize_timeline, validate_token_stream,
+    TokenLibrary,
+};
+use crate::trie_binary::{
+    deserialize_trie, deserialize_trie_with_limits, serialize_trie, DeserializationLimits, TrieNode,
+};
+use composer_core::Chord;
+use thiserror::Error;
+
+/// A violated invariant, naming the property and what was observed
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Invariant `{invariant}` violated: {details}")]
+pub struct InvariantViolation {
+    /// Short identifier of the property that failed
+    pub invariant: &'static str,
+    /// Description of the offending values
+    pub details: String,
+}
+
+impl InvariantViolation {
+    /// Create a violation
+    pub fn new(invariant: &'static str, details: impl Into<String>) -> Self {
+        Self {
+            invariant,
+            details: details.into(),
+        }
+    }
+}
+
+/// Result of an invariant check
+pub type InvariantResult = Result<(), InvariantViolation>;
+
+/// Fail with `invariant` unless `condition` holds
+pub fn ensure(
+    condition: bool,
+    invariant: &'static str,
+    details: impl FnOnce() -> String,
+) -> InvariantResult {
+    if condition {
+        Ok(())
+    } else {
+        Err(InvariantViolation::new(invariant, details()))
+    }
+}
+
+/// Checks that a chord survives the 5-byte binary and hex formats.
+///
+/// Verifies that the chord serializes, that decoding restores every field the binary
+/// format stores (root, type, inversion, applied target, extensions, alterations,
+/// suspensions, omissions, rest flag and presence of a borrowed scale), that encoding
+/// is idempotent, and that the hex form decodes to the same bytes. Timing and
+/// annotation fields are outside the binary format and are not compared.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_core::Chord;
+/// use composer_serialization::check_roundtrip;
+///
+/// let chord = Chord::seventh(5)?.with_alteration("b9")?.with_inversion(1)?;
+/// assert!(check_roundtrip(&chord).is_ok());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`check_trie_roundtrip`] - Equivalent check for tries
+/// - [`serialize_chord`] / [`deserialize_chord`] - The formats being checked
+pub fn check_roundtrip(chord: &Chord) -> InvariantResult {
+    let binary = serialize_chord(chord)
+        .map_err(|e| InvariantViolation::new("chord_serializes", format!("{}: {}", chord, e)))?;
+    let decoded = deserialize_chord(&binary).map_err(|e| {
+        InvariantViolation::new("chord_deserializes", format!("{:02x?}: {}", binary, e))
+    })?;
+
+    let mismatch = |field: &str| format!("{} differs after round trip of {}", field, chord);
+    ensure(decoded.is_rest == chord.is_rest, "chord_roundtrip", || {
+        mismatch("is_rest")
+    })?;
+    if chord.is_rest {
+        return Ok(());
+    }
+    ensure(decoded.root == chord.root, "chord_roundtrip", || {
+        mismatch("root")
+    })?;
+    ensure(
+        decoded.chord_type == chord.chord_type,
+        "chord_roundtrip",
+        || mismatch("chord_type"),
+    )?;
+    ensure(
+        decoded.inversion == chord.inversion,
+        "chord_roundtrip",
+        || mismatch("inversion"),
+    )?;
+    ensure(decoded.applied == chord.applied, "chord_roundtrip", || {
+        mismatch("applied")
+    })?;
+    ensure(
+        same_set(&decoded.adds, &chord.adds),
+        "chord_roundtrip",
+        || mismatch("adds"),
+    )?;
+    ensure(
+        same_set(&decoded.omits, &chord.omits),
+        "chord_roundtrip",
+        || mismatch("omits"),
+    )?;
+    ensure(
+        same_set(&decoded.alterations, &chord.alterations),
+        "chord_roundtrip",
+        || mismatch("alterations"),
+    )?;
+    ensure(
+        same_set(&decoded.suspensions, &chord.suspensions),
+        "chord_roundtrip",
+        || mismatch("suspensions"),
+    )?;
+    ensure(
+        decoded.borrowed.is_some() == chord.borrowed.is_some(),
+        "chord_roundtrip",
+        || mismatch("borrowed"),
+    )?;
+
+    let reencoded = serialize_chord(&decoded)
+        .map_err(|e| InvariantViolation::new("chord_serializes", e.to_string()))?;
+    ensure(reencoded == binary, "chord_encoding_idempotent", || {
+        format!("{:02x?} re-encoded as {:02x?}", binary, reencoded)
+    })?;
+
+    let hex = chord_binary_to_hex(&binary);
+    let from_hex = hex_to_chord_binary(&hex)
+        .map_err(|e| InvariantViolation::new("chord_hex_roundtrip", format!("{}: {}", hex, e)))?;
+    ensure(from_hex == binary, "chord_hex_roundtrip", || {
+        format!("{} decoded as {:02x?}", hex, from_hex)
+    })
+}
+
+/// Checks that a trie survives binary serialization unchanged.
+///
+/// The decoded trie must equal the original node for node, and re-encoding it must
+/// reproduce the original bytes exactly.
+pub fn check_trie_roundtrip(trie: &TrieNode) -> InvariantResult {
+    let bytes = serialize_trie(trie)
+        .map_err(|e| InvariantViolation::new("trie_serializes", e.to_string()))?;
+    let decoded = deserialize_trie(&bytes, false)
+        .map_err(|e| InvariantViolation::new("trie_deserializes", e.to_string()))?;
+    ensure(&decoded == trie, "trie_roundtrip", || {
+        format!("decoded trie differs from original ({} bytes)", bytes.len())
+    })?;
+
+    let reencoded = serialize_trie(&decoded)
+        .map_err(|e| InvariantViolation::new("trie_serializes", e.to_string()))?;
+    ensure(reencoded == bytes, "trie_encoding_idempotent", || {
+        format!("{} bytes re-encoded as {}", bytes.len(), reencoded.len())
+    })
+}
+
+/// Checks that a token library survives binary serialization unchanged
+pub fn check_token_library_roundtrip(library: &TokenLibrary) -> InvariantResult {
+    let bytes = serialize_token_library(library)
+        .map_err(|e| InvariantViolation::new("token_library_serializes", e.to_string()))?;
+    let decoded = deserialize_token_library(&bytes)
+        .map_err(|e| InvariantViolation::new("token_library_deserializes", e.to_string()))?;
+    ensure(
+        decoded.chord_tokens == library.chord_tokens,
+        "token_library_roundtrip",
+        || {
+            format!(
+                "{} tokens decoded as {}",
+                library.chord_tokens.len(),
+                decoded.chord_tokens.len()
+            )
+        },
+    )
+}
+
+/// Checks 5 bytes of untrusted input as a binary chord.
+///
+/// Inputs of any other length are ignored. A chord that decodes must pass
+/// [`check_roundtrip`].
+pub fn check_chord_binary_input(data: &[u8]) -> InvariantResult {
+    let Ok(binary) = <[u8; 5]>::try_from(data) else {
+        return Ok(());
+    };
+    match deserialize_chord(&binary) {
+        Ok(chord) => check_roundtrip(&chord),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Checks untrusted input as a hex chord string.
+///
+/// Bytes that are not UTF-8 are replaced. A string that decodes must be the
+/// hex of the decoded bytes, in any letter case.
+pub fn check_chord_hex_input(data: &[u8]) -> InvariantResult {
+    let hex = String::from_utf8_lossy(data);
+    let Ok(binary) = hex_to_chord_binary(&hex) else {
+        return Ok(());
+    };
+    ensure(
+        chord_binary_to_hex(&binary) == hex.to_ascii_lowercase(),
+        "chord_hex_roundtrip",
+        || format!("{} decoded as {:02x?}", hex, binary),
+    )
+}
+
+/// Checks untrusted input as a serialized trie.
+///
+/// The input is decoded under [`DeserializationLimits::default`], with and
+/// without key tonics in the ids; a trie decoded without them must pass
+/// [`check_trie_roundtrip`].
+pub fn check_trie_input(data: &[u8]) -> InvariantResult {
+    let limits = DeserializationLimits::default();
+    let _ = deserialize_trie_with_limits(data, true, &limits, None);
+    match deserialize_trie_with_limits(data, false, &limits, None) {
+        Ok(trie) => check_trie_roundtrip(&trie),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Checks untrusted input as a token stream.
+///
+/// Bytes that are not UTF-8 are replaced. Every token is passed to each token
+/// parser, and the stream to both detokenizers. A stream that
+/// [`detokenize_tokens`] accepts must tokenize again into a valid stream, unless
+/// it is too long to write ([`SerializationError::LimitExceeded`]).
+pub fn check_token_input(data: &[u8]) -> InvariantResult {
+    let text = String::from_utf8_lossy(data);
+    let tokens = split_token_stream(&text);
+    for token in &tokens {
+        let _ = parse_duration_token(token);
+        let _ = parse_metric_token(token);
+        let _ = parse_key_token(token);
+        let _ = parse_time_signature_token(token);
+        let _ = parse_chord_binary_token(token);
+    }
+    let _ = detokenize_polyphonic(&tokens);
+
+    let Ok(timeline) = detokenize_tokens(&tokens) else {
+        return Ok(());
+    };
+    let retokenized = match tokenize_timeline(&timeline) {
+        Ok(tokens) => tokens,
+        Err(SerializationError::LimitExceeded { .. }) => return Ok(()),
+        Err(e) => {
+            return Err(InvariantViolation::new(
+                "detokenized_timeline_tokenizes",
+                format!("{}: {}", text, e),
+            ))
+        },
+    };
+    validate_token_stream(&retokenized).map_err(|e| {
+        InvariantViolation::new(
+            "retokenized_stream_valid",
+            format!("{} became {:?}: {}", text, retokenized, e),
+        )
+    })
+}
+
+fn same_set<T: PartialEq>(a: &[T], b: &[T]) -> bool {
+    a.len() == b.len() && a.iter().all(|x| b.contains(x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use composer_core::{ChordGenerator, DifficultyBand, GeneratorConfig};
+    use proptest::prelude::*;
+
+    /// Token streams mixing well-formed tokens, near misses and group markers
+    fn token_stream() -> impl Strategy<Value = String> {
+        let token = prop_oneof![
+            "D_[0-9a-f]{1,9}",
+            "R_[0-9a-c]",
+            "(R_[0-9a-b]-?){1,4}",
+            "O_[0-9]",
+            "K_[0-9a-c]_(MAJ|MIN)",
+            "TS_[0-9]{1,3}_[0-9]{1,2}",
+            "M_(DOWN|MID|BEAT|ANT|OFF)",
+            "CH_[0-9a-fA-F]{9,11}",
+            "<CHORD>|</CHORD>|<NOTES>|</NOTES>|BAR|NOTE-REST|CHORD-REST",
+            "[A-Z_<>/-]{0,4}",
+        ];
+        proptest::collection::vec(token, 0..16).prop_map(|tokens| tokens.join(" "))
+    }
+
+    /// Serialized generated tries with a few bytes changed and possibly truncated
+    fn corrupted_trie() -> impl Strategy<Value = Vec<u8>> {
+        let edits = proptest::collection::vec((any::<prop::sample::Index>(), any::<u8>()), 0..4);
+        let cut = proptest::option::of(any::<prop::sample::Index>());
+        (any::<u64>(), edits, cut).prop_map(|(seed, edits, cut)| {
+            let mut generator = ChordGenerator::new(seed);
+            let mut trie = TrieNode::new();
+            for (id, progression) in generator.progressions(3, 1, 4).unwrap().iter().enumerate() {
+                let pattern: Vec<Vec<u8>> = progression
+                    .iter()
+                    .map(|chord| serialize_chord(chord).unwrap().to_vec())
+                    .collect();
+                trie.add_pattern(&pattern, id as u32);
+            }
+            let mut bytes = serialize_trie(&trie).unwrap();
+            for (index, flip) in edits {
+                let len = bytes.len();
+                bytes[index.index(len)] ^= flip;
+            }
+            if let Some(cut) = cut {
+                bytes.truncate(cut.index(bytes.len()));
+            }
+            bytes
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_generated_chords_roundtrip(seed in any::<u64>()) {
+            let mut generator = ChordGenerator::new(seed);
+            for _ in 0..8 {
+                let chord = generator.chord().unwrap();
+                prop_assert!(check_roundtrip(&chord).is_ok(), "{:?}", check_roundtrip(&chord));
+            }
+        }
+
+        #[test]
+        fn prop_generated_tries_roundtrip(seed in any::<u64>(), count in 1usize..20) {
+            let config = GeneratorConfig::for_band(DifficultyBand::Intermediate);
+            let mut generator = ChordGenerator::with_config(seed, config);
+            let mut trie = TrieNode::new();
+            let mut library = TokenLibrary::new();
+
+            for (id, progression) in generator.progressions(count, 1, 6).unwrap().iter().enumerate() {
+                let pattern: Vec<Vec<u8>> = progression
+                    .iter()
+                    .map(|chord| serialize_chord(chord).unwrap().to_vec())
+                    .collect();
+                trie.add_pattern(&pattern, id as u32);
+                library.add_chord_token(format!("P{}", id), pattern[0].clone());
+            }
+
+            prop_assert!(check_trie_roundtrip(&trie).is_ok(), "{:?}", check_trie_roundtrip(&trie));
+            prop_assert!(check_token_library_roundtrip(&library).is_ok(), "{:?}", check_token_library_roundtrip(&library));
+        }
+
+        #[test]
+        fn prop_chord_binary_inputs(binary in any::<[u8; 5]>()) {
+            prop_assert!(check_chord_binary_input(&binary).is_ok(), "{:?}", check_chord_binary_input(&binary));
+        }
+
+        #[test]
+        fn prop_chord_hex_inputs(hex in "[0-9a-fA-F]{10}|.{0,12}") {
+            prop_assert!(check_chord_hex_input(hex.as_bytes()).is_ok(), "{:?}", check_chord_hex_input(hex.as_bytes()));
+        }
+
+        #[test]
+        fn prop_trie_inputs(bytes in corrupted_trie()) {
+            prop_assert!(check_trie_input(&bytes).is_ok(), "{:?}", check_trie_input(&bytes));
+        }
+
+        #[test]
+        fn prop_token_inputs(stream in token_stream()) {
+            prop_assert!(check_token_input(stream.as_bytes()).is_ok(), "{:?}", check_token_input(stream.as_bytes()));
+        }
+
+        #[test]
+        fn prop_arbitrary_bytes(bytes in proptest::collection::vec(any::<u8>(), 0..96)) {
+            prop_assert!(check_chord_binary_input(&bytes).is_ok());
+            prop_assert!(check_chord_hex_input(&bytes).is_ok());
+            prop_assert!(check_trie_input(&bytes).is_ok());
+            prop_assert!(check_token_input(&bytes).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_malformed_tokens_are_rejected() {
+        // A trailing separator used to slice past the end of an empty note
+        assert!(check_token_input(b"<CHORD> R_0- </CHORD> D_18").is_ok());
+        assert!(detokenize_tokens(&["<CHORD>", "R_0-", "</CHORD>", "D_18"]).is_err());
+
+        // Hours of 1/32 bars would be over a billion bar lines
+        let timeline = detokenize_tokens(&["TS_1_32", "NOTE-REST", "D_ffffffff"]).unwrap();
+        assert!(matches!(
+            tokenize_timeline(&timeline),
+            Err(SerializationError::LimitExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn test_violations_are_reported() {
+        let violation = ensure(false, "example", || "details".to_string()).unwrap_err();
+        assert_eq!(violation.invariant, "example");
+        assert_eq!(
+            violation.to_string(),
+            "Invariant `example` violated: details"
+        );
+
+        let mut chord = Chord::new(1, 5).unwrap();
+        chord.root = 9;
+        assert_eq!(
+            check_roundtrip(&chord).unwrap_err().invariant,
+            "chord_serializes"
+        );
+    }
+}
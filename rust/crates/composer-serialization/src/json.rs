@@ -0,0 +1,193 @@
+//! Human-readable JSON for chords, timelines and scale fingerprints
+//!
+//! The JSON mirrors the Rust types field for field, with snake_case keys:
+//!
+//! - a [`Chord`] is an object of its fields, e.g.
+//!   `{"root": 5, "chord_type": 7, "alterations": ["b9"], ...}`; fields left out
+//!   take their [`Chord::default`] values, so hand-written config can stay short
+//! - a [`ScaleFingerprint`] is its 12 semitone flags, e.g. `[1,0,1,0,1,1,0,1,0,1,0,1]`
+//! - a [`Timeline`] is `{"events": [...], "total_duration": 4.0, "meter_changes": [...],
+//!   "key_changes": [...]}`, each event `{"beat": 0.0, "event_type": {"Chord": {...}}}`
+//!   or `{"beat": 1.0, "event_type": "Rest"}`
+//!
+//! This schema is stable: within a major version fields are only ever added,
+//! always with a default, so JSON written by any earlier release keeps loading.
+//! Output is pretty-printed with keys in declaration order, so equal values
+//! always produce identical text. JSON is also valid YAML 1.2, so the output can
+//! be pasted into YAML config files as is.
+//!
+//! Reading validates what it parses: chords must pass [`Chord::validate`] and
+//! fingerprints may only contain 0 and 1.
+
+use crate::error::{SerializationError, SerializationResult};
+use crate::tokenization::{Timeline, TokenEventType};
+use composer_core::{Chord, ScaleFingerprint};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Write a chord as JSON
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_core::Chord;
+/// use composer_serialization::{chord_from_json, chord_to_json, SerializationError};
+///
+/// let chord = Chord::new(5, 7)?.with_alteration("b9")?;
+/// let json = chord_to_json(&chord)?;
+/// assert!(json.contains("\"alterations\": [\n    \"b9\"\n  ]"));
+/// assert_eq!(chord_from_json(&json)?, chord);
+///
+/// // Fields left out take their defaults
+/// assert_eq!(chord_from_json(r#"{"root": 5, "chord_type": 7}"#)?, Chord::new(5, 7)?);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn chord_to_json(chord: &Chord) -> SerializationResult<String> {
+    to_json("chord", chord)
+}
+
+/// Read a chord written by [`chord_to_json`], or by hand
+pub fn chord_from_json(json: &str) -> SerializationResult<Chord> {
+    let chord: Chord = from_json("chord", json)?;
+    validate_chord(&chord)?;
+    Ok(chord)
+}
+
+/// Write a timeline as JSON
+pub fn timeline_to_json(timeline: &Timeline) -> SerializationResult<String> {
+    to_json("timeline", timeline)
+}
+
+/// Read a timeline written by [`timeline_to_json`], or by hand
+pub fn timeline_from_json(json: &str) -> SerializationResult<Timeline> {
+    let timeline: Timeline = from_json("timeline", json)?;
+    for event in &timeline.events {
+        if let TokenEventType::Chord(chord) = &event.event_type {
+            validate_chord(chord)?;
+        }
+    }
+    Ok(timeline)
+}
+
+/// Write a scale fingerprint as JSON
+pub fn scale_fingerprint_to_json(fingerprint: &ScaleFingerprint) -> SerializationResult<String> {
+    to_json("scale fingerprint", fingerprint)
+}
+
+/// Read a scale fingerprint written by [`scale_fingerprint_to_json`], or by hand
+pub fn scale_fingerprint_from_json(json: &str) -> SerializationResult<ScaleFingerprint> {
+    let semitones: Vec<u8> = from_json("scale fingerprint", json)?;
+    ScaleFingerprint::from_slice(&semitones).map_err(|e| {
+        SerializationError::DeserializationFailed {
+            reason: format!("Invalid scale fingerprint JSON: {}", e),
+        }
+    })
+}
+
+fn to_json<T: Serialize>(what: &str, value: &T) -> SerializationResult<String> {
+    serde_json::to_string_pretty(value).map_err(|e| SerializationError::InvalidFormat {
+        message: format!("{} JSON encoding failed: {}", what, e),
+    })
+}
+
+fn from_json<T: DeserializeOwned>(what: &str, json: &str) -> SerializationResult<T> {
+    serde_json::from_str(json).map_err(|e| SerializationError::DeserializationFailed {
+        reason: format!("Invalid {} JSON: {}", what, e),
+    })
+}
+
+fn validate_chord(chord: &Chord) -> SerializationResult<()> {
+    chord
+        .validate()
+        .map_err(|e| SerializationError::InvalidChordData {
+            field: e.to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenization::{KeyChange, MeterChange, Note, TokenEvent};
+
+    #[test]
+    fn test_schema_is_stable() {
+        // Pinned output: changing it breaks JSON that users have saved
+        let chord = Chord::new(5, 7).unwrap().with_alteration("b9").unwrap();
+        let compact: serde_json::Value =
+            serde_json::from_str(&chord_to_json(&chord).unwrap()).unwrap();
+        assert_eq!(
+            compact.to_string(),
+            r#"{"adds":[],"alterations":["b9"],"alternate":"","applied":0,"beat":null,"borrowed":null,"chord_type":7,"duration":null,"inversion":0,"is_rest":false,"omits":[],"pedal":null,"root":5,"substitutions":[],"suspensions":[]}"#
+        );
+
+        let major = ScaleFingerprint::major_scale();
+        let json = scale_fingerprint_to_json(&major).unwrap();
+        assert_eq!(
+            json.split_whitespace().collect::<String>(),
+            "[1,0,1,0,1,1,0,1,0,1,0,1]"
+        );
+        assert_eq!(scale_fingerprint_from_json(&json).unwrap(), major);
+    }
+
+    #[test]
+    fn test_timeline_round_trip() {
+        let timeline = Timeline {
+            events: vec![
+                TokenEvent {
+                    beat: 0.0,
+                    event_type: TokenEventType::Chord(Chord::new(1, 5).unwrap()),
+                },
+                TokenEvent {
+                    beat: 1.0,
+                    event_type: TokenEventType::Note(Note {
+                        scale_degree: 3,
+                        octave: 4,
+                        is_rest: false,
+                    }),
+                },
+                TokenEvent {
+                    beat: 2.0,
+                    event_type: TokenEventType::Rest,
+                },
+            ],
+            total_duration: 3.0,
+            meter_changes: vec![MeterChange {
+                beat: 0.0,
+                time_signature: (3, 4),
+            }],
+            key_changes: vec![KeyChange {
+                beat: 0.0,
+                key: "G".parse().unwrap(),
+            }],
+        };
+        let json = timeline_to_json(&timeline).unwrap();
+        assert!(json.contains("\"event_type\": \"Rest\""));
+        let restored = timeline_from_json(&json).unwrap();
+        assert_eq!(timeline_to_json(&restored).unwrap(), json);
+
+        // Meter and key changes are optional
+        let minimal = timeline_from_json(r#"{"events": [], "total_duration": 0.0}"#).unwrap();
+        assert!(minimal.meter_changes.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_json_is_rejected() {
+        assert!(matches!(
+            chord_from_json("{\"root\": 9}"),
+            Err(SerializationError::InvalidChordData { .. })
+        ));
+        assert!(matches!(
+            chord_from_json("[1, 5]x"),
+            Err(SerializationError::DeserializationFailed { .. })
+        ));
+        assert!(scale_fingerprint_from_json("[1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]").is_err());
+        assert!(scale_fingerprint_from_json("[1, 0]").is_err());
+
+        let bad_event = r#"{"events": [{"beat": 0.0, "event_type": {"Chord": {"root": 0}}}],
+            "total_duration": 1.0}"#;
+        assert!(matches!(
+            timeline_from_json(bad_event),
+            Err(SerializationError::InvalidChordData { .. })
+        ));
+    }
+}
@@ -1,19 +1,39 @@
 //! Binary serialization and data processing for Composer
 //!
 //! This crate provides binary formats for musical chord and note data serialization,
-//! including the 5-byte chord format, trie serialization, tokenization for ML,
-//! and hash functions for data integrity.
+//! including the 5-byte chord format, trie and timeline serialization with optional
+//! compression, streamed trie serialization, human-readable JSON, tokenization and
+//! token vocabularies for ML, hash functions for data integrity, and passphrase
+//! encryption of serialized data (`encryption` feature).
 
 pub mod chord_binary;
+pub mod chord_validation;
+pub mod compression;
 pub mod constants;
+#[cfg(feature = "encryption")]
+pub mod encryption;
 pub mod error;
 pub mod hash;
+pub mod invariants;
+pub mod json;
+pub mod timeline_binary;
 pub mod tokenization;
 pub mod trie_binary;
+pub mod trie_stream;
+pub mod vocabulary;
 
 pub use chord_binary::*;
+pub use chord_validation::*;
+pub use compression::*;
 pub use constants::*;
+#[cfg(feature = "encryption")]
+pub use encryption::*;
 pub use error::*;
 pub use hash::*;
+pub use invariants::*;
+pub use json::*;
+pub use timeline_binary::*;
 pub use tokenization::*;
 pub use trie_binary::*;
+pub use trie_stream::*;
+pub use vocabulary::*;
@@ -0,0 +1,183 @@
+//! Binary timeline serialization
+//!
+//! Timelines are written as a [`TIMELINE_BINARY_HEADER_LEN`]-byte header (magic
+//! `CTML`, format version) followed by the timeline in bincode's varint encoding.
+//! Like tries, they may be wrapped in a zstd or LZ4 frame, which
+//! [`deserialize_timeline`] detects and unpacks.
+
+use crate::compression::{compress, decompress, Compression};
+use crate::constants::{
+    TIMELINE_BINARY_FORMAT_VERSION, TIMELINE_BINARY_HEADER_LEN, TIMELINE_BINARY_MAGIC,
+};
+use crate::error::{SerializationError, SerializationResult};
+use crate::tokenization::Timeline;
+use bincode::Options;
+use composer_config::MEMORY;
+
+/// Largest timeline [`deserialize_timeline`] reads, decompressed and decoded
+const TIMELINE_MAX_BYTES: usize = MEMORY.memory_usage_max_mb as usize * 1024 * 1024;
+
+fn bincode_options() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_limit(TIMELINE_MAX_BYTES as u64)
+        .reject_trailing_bytes()
+}
+
+/// Serialize a timeline to binary format
+pub fn serialize_timeline(timeline: &Timeline) -> SerializationResult<Vec<u8>> {
+    let mut buffer = TIMELINE_BINARY_MAGIC.to_vec();
+    buffer.push(TIMELINE_BINARY_FORMAT_VERSION);
+    bincode_options()
+        .serialize_into(&mut buffer, timeline)
+        .map_err(|e| SerializationError::InvalidFormat {
+            message: format!("timeline cannot be serialized: {}", e),
+        })?;
+    Ok(buffer)
+}
+
+/// Serialize a timeline and compress it
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_serialization::{
+///     deserialize_timeline, serialize_timeline, serialize_timeline_compressed, Compression,
+///     Timeline, TokenEvent, TokenEventType,
+/// };
+///
+/// let timeline = Timeline {
+///     events: (0..1000)
+///         .map(|i| TokenEvent { beat: i as f64, event_type: TokenEventType::Rest })
+///         .collect(),
+///     total_duration: 1000.0,
+///     ..Default::default()
+/// };
+/// let plain = serialize_timeline(&timeline)?;
+/// let packed = serialize_timeline_compressed(&timeline, Compression::Lz4)?;
+/// assert!(packed.len() < plain.len());
+///
+/// let restored = deserialize_timeline(&packed)?;
+/// assert_eq!(restored.events.len(), 1000);
+/// # Ok::<(), composer_serialization::SerializationError>(())
+/// ```
+pub fn serialize_timeline_compressed(
+    timeline: &Timeline,
+    compression: Compression,
+) -> SerializationResult<Vec<u8>> {
+    compress(&serialize_timeline(timeline)?, compression)
+}
+
+/// Deserialize a timeline from binary format, compressed or not
+///
+/// Decompressed data and the decoded timeline are both capped at the
+/// `memory_usage_max_mb` memory limit; larger input fails with
+/// [`SerializationError::LimitExceeded`].
+pub fn deserialize_timeline(data: &[u8]) -> SerializationResult<Timeline> {
+    let data = decompress(data, TIMELINE_MAX_BYTES)?;
+    if data.len() < TIMELINE_BINARY_HEADER_LEN || data[..4] != TIMELINE_BINARY_MAGIC {
+        return Err(SerializationError::InvalidBinaryFormat {
+            reason: "Missing timeline header".to_string(),
+        });
+    }
+    if data[4] != TIMELINE_BINARY_FORMAT_VERSION {
+        return Err(SerializationError::UnsupportedVersion {
+            version: format!(
+                "timeline binary format {} (this build reads {})",
+                data[4], TIMELINE_BINARY_FORMAT_VERSION
+            ),
+        });
+    }
+
+    bincode_options()
+        .deserialize(&data[TIMELINE_BINARY_HEADER_LEN..])
+        .map_err(|e| match *e {
+            bincode::ErrorKind::SizeLimit => SerializationError::LimitExceeded {
+                resource: "timeline bytes".to_string(),
+                limit: TIMELINE_MAX_BYTES as u64,
+                actual: data.len() as u64,
+            },
+            e => SerializationError::CorruptedBinary {
+                details: format!("invalid timeline: {}", e),
+            },
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenization::{KeyChange, MeterChange, Note, TokenEvent, TokenEventType};
+    use composer_core::Chord;
+
+    fn sample_timeline() -> Timeline {
+        Timeline {
+            events: vec![
+                TokenEvent {
+                    beat: 0.0,
+                    event_type: TokenEventType::Chord(Chord::seventh(2).unwrap()),
+                },
+                TokenEvent {
+                    beat: 2.0,
+                    event_type: TokenEventType::Note(Note {
+                        scale_degree: 5,
+                        octave: 4,
+                        is_rest: false,
+                    }),
+                },
+                TokenEvent {
+                    beat: 3.5,
+                    event_type: TokenEventType::Rest,
+                },
+            ],
+            total_duration: 4.0,
+            meter_changes: vec![MeterChange {
+                beat: 0.0,
+                time_signature: (3, 4),
+            }],
+            key_changes: vec![KeyChange {
+                beat: 0.0,
+                key: "Am".parse().unwrap(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_timeline_roundtrip() {
+        let bytes = serialize_timeline(&sample_timeline()).unwrap();
+        assert_eq!(bytes[..4], TIMELINE_BINARY_MAGIC);
+
+        for compression in [Compression::None, Compression::Zstd, Compression::Lz4] {
+            let packed = serialize_timeline_compressed(&sample_timeline(), compression).unwrap();
+            let restored = deserialize_timeline(&packed).unwrap();
+            assert_eq!(serialize_timeline(&restored).unwrap(), bytes);
+            assert_eq!(restored.key_changes, sample_timeline().key_changes);
+        }
+    }
+
+    #[test]
+    fn test_invalid_timelines_are_rejected() {
+        let bytes = serialize_timeline(&sample_timeline()).unwrap();
+
+        let mut future = bytes.clone();
+        future[4] = TIMELINE_BINARY_FORMAT_VERSION + 1;
+        assert!(matches!(
+            deserialize_timeline(&future),
+            Err(SerializationError::UnsupportedVersion { .. })
+        ));
+        assert!(matches!(
+            deserialize_timeline(&bytes[4..]),
+            Err(SerializationError::InvalidBinaryFormat { .. })
+        ));
+
+        let mut padded = bytes.clone();
+        padded.push(0);
+        assert!(deserialize_timeline(&padded).is_err());
+        for len in TIMELINE_BINARY_HEADER_LEN..bytes.len() {
+            assert!(deserialize_timeline(&bytes[..len]).is_err());
+        }
+
+        // An event count far beyond the input must not be allocated up front
+        let mut hostile = bytes[..TIMELINE_BINARY_HEADER_LEN].to_vec();
+        hostile.extend_from_slice(&[0xFC, 0xFF, 0xFF, 0xFF, 0x7F]);
+        assert!(deserialize_timeline(&hostile).is_err());
+    }
+}
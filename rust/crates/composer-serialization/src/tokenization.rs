@@ -2,12 +2,13 @@
 
 use crate::constants::*;
 use crate::error::{SerializationError, SerializationResult};
-use composer_core::{Chord, ScaleFingerprint};
+use composer_core::{Chord, Key, Mode, ScaleFingerprint};
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Note structure for tokenization
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Note {
     pub scale_degree: u8,
     pub octave: u8,
@@ -15,24 +16,79 @@ pub struct Note {
 }
 
 /// Token event for timeline reconstruction
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenEvent {
     pub beat: f64,
     pub event_type: TokenEventType,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(clippy::large_enum_variant)]
 pub enum TokenEventType {
     Note(Note),
     Chord(Chord),
     Rest,
+    /// Notes struck together, each sounding for its own duration
+    Cluster(Vec<ClusterNote>),
+}
+
+/// Note of a cluster, sounding for its own duration
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ClusterNote {
+    /// Chromatic pitch class (0-11)
+    pub pitch_class: u8,
+    pub octave: u8,
+    /// Duration in quarter notes; may extend past later events
+    pub duration: f64,
+}
+
+/// Time signature taking effect at a beat of a timeline
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MeterChange {
+    /// Beat (in quarter notes from the start) where the meter takes effect; bars restart here
+    pub beat: f64,
+    pub time_signature: (u8, u8),
+}
+
+/// Key taking effect at a beat of a timeline
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct KeyChange {
+    /// Beat (in quarter notes from the start) where the key takes effect
+    pub beat: f64,
+    /// Key from this beat on; key tokens keep only its tonic pitch class and
+    /// whether the mode is minor
+    pub key: Key,
 }
 
 /// Musical timeline structure
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Timeline {
     pub events: Vec<TokenEvent>,
     pub total_duration: f64,
+    /// Meter changes ordered by beat; empty when the meter is unknown
+    #[serde(default)]
+    pub meter_changes: Vec<MeterChange>,
+    /// Key changes ordered by beat; empty when the key is unknown
+    #[serde(default)]
+    pub key_changes: Vec<KeyChange>,
+}
+
+impl Timeline {
+    /// Meter change in effect at `beat`, if any
+    pub fn meter_at(&self, beat: f64) -> Option<&MeterChange> {
+        self.meter_changes
+            .iter()
+            .take_while(|change| change.beat <= beat)
+            .last()
+    }
+
+    /// Key change in effect at `beat`, if any
+    pub fn key_at(&self, beat: f64) -> Option<&KeyChange> {
+        self.key_changes
+            .iter()
+            .take_while(|change| change.beat <= beat)
+            .last()
+    }
 }
 
 /// Token library for ML consistency
@@ -40,6 +96,8 @@ pub struct Timeline {
 pub struct TokenLibrary {
     pub chord_tokens: HashMap<String, Vec<u8>>, // token -> serialized chord
     pub library_size: usize,
+    /// Token vocabulary version the tokens are written in
+    pub schema_version: u8,
 }
 
 impl TokenLibrary {
@@ -47,9 +105,23 @@ impl TokenLibrary {
         Self {
             chord_tokens: HashMap::new(),
             library_size: 0,
+            schema_version: TOKEN_LIBRARY_FORMAT_VERSION,
         }
     }
 
+    /// Create an empty library for tokens written in an older vocabulary
+    ///
+    /// Fails with `SerializationError::UnsupportedVersion` for versions that
+    /// cannot be upgraded. Call [`TokenLibrary::upgrade`] once the tokens are
+    /// added.
+    pub fn with_schema_version(schema_version: u8) -> SerializationResult<Self> {
+        check_token_schema_version(schema_version)?;
+        Ok(Self {
+            schema_version,
+            ..Self::new()
+        })
+    }
+
     pub fn add_chord_token(&mut self, token: String, chord_binary: Vec<u8>) {
         self.chord_tokens.insert(token, chord_binary);
         self.library_size = self.chord_tokens.len();
@@ -65,7 +137,7 @@ impl TokenLibrary {
     }
 
     pub fn update_library(&mut self, tokens: Vec<String>, chord_binaries: Vec<Vec<u8>>) {
-        for (token, binary) in tokens.into_iter().zip(chord_binaries.into_iter()) {
+        for (token, binary) in tokens.into_iter().zip(chord_binaries) {
             self.add_chord_token(token, binary);
         }
     }
@@ -73,6 +145,57 @@ impl TokenLibrary {
     pub fn get_library_size(&self) -> usize {
         self.library_size
     }
+
+    /// Rewrite the library's tokens in the current vocabulary
+    ///
+    /// Tokens are upgraded with [`upgrade_token`]. Returns how many tokens were
+    /// renamed. Fails with `SerializationError::InvalidFormat` if two tokens
+    /// upgrade to the same token for different chords, leaving the library
+    /// unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use composer_serialization::{TokenLibrary, TOKEN_LIBRARY_FORMAT_VERSION};
+    ///
+    /// let mut library = TokenLibrary::with_schema_version(1)?;
+    /// library.add_chord_token("R_0-R_4-R_7".to_string(), vec![0x10, 0, 0, 0, 0]);
+    /// library.add_chord_token("R_2-R_5-R_9-R_11".to_string(), vec![0x50, 0x08, 0, 0, 0]);
+    ///
+    /// assert_eq!(library.upgrade()?, 1);
+    /// assert_eq!(library.schema_version, TOKEN_LIBRARY_FORMAT_VERSION);
+    /// assert!(library.resolve_chord_token("R_2-R_5-R_9-R_b").is_ok());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn upgrade(&mut self) -> SerializationResult<usize> {
+        if self.schema_version == TOKEN_LIBRARY_FORMAT_VERSION {
+            return Ok(0);
+        }
+
+        let mut upgraded = HashMap::with_capacity(self.chord_tokens.len());
+        let mut renamed = 0;
+        for (token, binary) in &self.chord_tokens {
+            let new_token = upgrade_token(token, self.schema_version)?;
+            if new_token != *token {
+                renamed += 1;
+            }
+            match upgraded.get(&new_token) {
+                Some(existing) if existing != binary => {
+                    return Err(SerializationError::InvalidFormat {
+                        message: format!("Tokens upgrade to {} for different chords", new_token),
+                    });
+                },
+                _ => {
+                    upgraded.insert(new_token, binary.clone());
+                },
+            }
+        }
+
+        self.chord_tokens = upgraded;
+        self.library_size = self.chord_tokens.len();
+        self.schema_version = TOKEN_LIBRARY_FORMAT_VERSION;
+        Ok(renamed)
+    }
 }
 
 impl Default for TokenLibrary {
@@ -81,6 +204,169 @@ impl Default for TokenLibrary {
     }
 }
 
+/// Serialize a token library to binary format
+///
+/// Layout (big-endian): magic `CTOK` (4), schema version (1), entry count (4), then per
+/// entry: token length (2), UTF-8 token, chord binary length (1), chord binary. Entries
+/// are written in token order so equal libraries produce identical bytes.
+pub fn serialize_token_library(library: &TokenLibrary) -> SerializationResult<Vec<u8>> {
+    let mut entries: Vec<_> = library.chord_tokens.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut buffer = Vec::new();
+    buffer.extend_from_slice(&TOKEN_LIBRARY_MAGIC);
+    buffer.push(library.schema_version);
+    buffer.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+
+    for (token, binary) in entries {
+        let token_len =
+            u16::try_from(token.len()).map_err(|_| SerializationError::BufferOverflow)?;
+        let binary_len =
+            u8::try_from(binary.len()).map_err(|_| SerializationError::BufferOverflow)?;
+
+        buffer.extend_from_slice(&token_len.to_be_bytes());
+        buffer.extend_from_slice(token.as_bytes());
+        buffer.push(binary_len);
+        buffer.extend_from_slice(binary);
+    }
+
+    Ok(buffer)
+}
+
+/// Deserialize a token library from binary format
+///
+/// Libraries written with an older token vocabulary are upgraded to the
+/// current one with [`TokenLibrary::upgrade`].
+pub fn deserialize_token_library(data: &[u8]) -> SerializationResult<TokenLibrary> {
+    if data.len() < TOKEN_LIBRARY_HEADER_LEN || data[0..4] != TOKEN_LIBRARY_MAGIC {
+        return Err(SerializationError::InvalidBinaryFormat {
+            reason: "Missing token library header".to_string(),
+        });
+    }
+
+    check_token_schema_version(data[4])?;
+
+    let count = u32::from_be_bytes([data[5], data[6], data[7], data[8]]) as usize;
+    let mut offset = TOKEN_LIBRARY_HEADER_LEN;
+    let mut take = |len: usize| -> SerializationResult<&[u8]> {
+        if offset + len > data.len() {
+            return Err(SerializationError::UnexpectedEof);
+        }
+        let slice = &data[offset..offset + len];
+        offset += len;
+        Ok(slice)
+    };
+
+    let mut library = TokenLibrary::with_schema_version(data[4])?;
+    for _ in 0..count {
+        let token_len = take(2)?;
+        let token_len = u16::from_be_bytes([token_len[0], token_len[1]]) as usize;
+        let token = std::str::from_utf8(take(token_len)?).map_err(|e| {
+            SerializationError::CorruptedBinary {
+                details: format!("token is not valid UTF-8: {}", e),
+            }
+        })?;
+        let binary_len = take(1)?[0] as usize;
+        let binary = take(binary_len)?;
+
+        library.add_chord_token(token.to_string(), binary.to_vec());
+    }
+
+    if offset != data.len() {
+        return Err(SerializationError::CorruptedBinary {
+            details: format!("{} trailing bytes after token library", data.len() - offset),
+        });
+    }
+
+    library.upgrade()?;
+    Ok(library)
+}
+
+pub(crate) fn check_token_schema_version(version: u8) -> SerializationResult<()> {
+    if (TOKEN_LIBRARY_MIN_FORMAT_VERSION..=TOKEN_LIBRARY_FORMAT_VERSION).contains(&version) {
+        Ok(())
+    } else {
+        Err(SerializationError::UnsupportedVersion {
+            version: version.to_string(),
+        })
+    }
+}
+
+/// Rewrite a token from an older vocabulary in the current one
+///
+/// Applies each vocabulary change since `from_version` in turn. Version 2
+/// writes the numbers in raw note and duration tokens in hex where version 1
+/// wrote them in decimal, so `R_11` becomes `R_b` and `D_24` becomes `D_18`;
+/// chord clusters are upgraded note by note. Tokens a change does not affect
+/// are returned unchanged.
+///
+/// Fails with `SerializationError::UnsupportedVersion` for versions that
+/// cannot be upgraded.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_serialization::{upgrade_token, TOKEN_LIBRARY_FORMAT_VERSION};
+///
+/// assert_eq!(upgrade_token("D_24", 1)?, "D_18");
+/// assert_eq!(upgrade_token("R_2-R_5-R_11", 1)?, "R_2-R_5-R_b");
+/// assert_eq!(upgrade_token("O_4", 1)?, "O_4");
+/// assert_eq!(upgrade_token("D_24", TOKEN_LIBRARY_FORMAT_VERSION)?, "D_24");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`upgrade_token_stream`] - Upgrade a whole token stream
+/// - [`TokenLibrary::upgrade`] - Upgrade a token library
+pub fn upgrade_token(token: &str, from_version: u8) -> SerializationResult<String> {
+    check_token_schema_version(from_version)?;
+
+    let mut token = token.to_string();
+    for version in from_version..TOKEN_LIBRARY_FORMAT_VERSION {
+        token = match version {
+            1 => upgrade_token_v1(&token),
+            _ => token,
+        };
+    }
+    Ok(token)
+}
+
+/// Upgrade every token of a stream written in an older vocabulary
+///
+/// See [`upgrade_token`].
+pub fn upgrade_token_stream<S: AsRef<str>>(
+    tokens: &[S],
+    from_version: u8,
+) -> SerializationResult<Vec<String>> {
+    tokens
+        .iter()
+        .map(|token| upgrade_token(token.as_ref(), from_version))
+        .collect()
+}
+
+/// Version 1 to 2: decimal numbers in raw note and duration tokens become hex
+fn upgrade_token_v1(token: &str) -> String {
+    let decimal_to_hex = |token: &str, prefix: &str| -> Option<String> {
+        let value: u32 = token.strip_prefix(prefix)?.parse().ok()?;
+        Some(format!("{}{:x}", prefix, value))
+    };
+
+    if let Some(upgraded) = decimal_to_hex(token, DURATION_TOKEN_PREFIX) {
+        return upgraded;
+    }
+    if token.starts_with(RAW_NOTE_TOKEN_PREFIX) {
+        let notes: Option<Vec<String>> = token
+            .split('-')
+            .map(|note| decimal_to_hex(note, RAW_NOTE_TOKEN_PREFIX))
+            .collect();
+        if let Some(notes) = notes {
+            return notes.join("-");
+        }
+    }
+    token.to_string()
+}
+
 /// Duration tokenization
 pub fn tokenize_duration(duration: f64) -> String {
     let ticks = (duration * TICKS_PER_BEAT as f64).round() as u32;
@@ -104,6 +390,152 @@ pub fn parse_duration_token(token: &str) -> SerializationResult<f64> {
     Ok(ticks as f64 / TICKS_PER_BEAT as f64)
 }
 
+/// Metric position of an event within its bar
+///
+/// Lets models learn where in the bar a chord or note lands, not just its
+/// order. Positions are resolved on the tick grid (`TICKS_PER_BEAT` per
+/// quarter note), so onsets within half a tick of a beat count as on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MetricPosition {
+    /// First beat of the bar
+    Downbeat,
+    /// Secondary strong beat halfway through an even-length bar (beat 3 of 4/4)
+    MidBar,
+    /// Any other beat
+    Beat,
+    /// Off the beat within half a beat before a downbeat or mid-bar beat
+    Anticipation,
+    /// Any other subdivision of a beat
+    Offbeat,
+}
+
+impl MetricPosition {
+    /// Every position, strongest first
+    pub const ALL: [MetricPosition; 5] = [
+        MetricPosition::Downbeat,
+        MetricPosition::MidBar,
+        MetricPosition::Beat,
+        MetricPosition::Anticipation,
+        MetricPosition::Offbeat,
+    ];
+
+    /// Token for the position (e.g. "M_DOWN")
+    pub fn token(&self) -> String {
+        let name = match self {
+            MetricPosition::Downbeat => "DOWN",
+            MetricPosition::MidBar => "MID",
+            MetricPosition::Beat => "BEAT",
+            MetricPosition::Anticipation => "ANT",
+            MetricPosition::Offbeat => "OFF",
+        };
+        format!("{}{}", METRIC_TOKEN_PREFIX, name)
+    }
+}
+
+/// Metric position of an onset `beat` (in quarter notes from the start)
+///
+/// Compound meters (6/8, 9/8, 12/8) count dotted-quarter beats. Bars with an
+/// even number of at least four beats, and compound duple and quadruple bars,
+/// have a mid-bar beat.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_serialization::{metric_position, MetricPosition};
+///
+/// assert_eq!(metric_position(4.0, (4, 4))?, MetricPosition::Downbeat);
+/// assert_eq!(metric_position(2.0, (4, 4))?, MetricPosition::MidBar);
+/// assert_eq!(metric_position(3.5, (4, 4))?, MetricPosition::Anticipation);
+/// assert_eq!(metric_position(1.5, (6, 8))?, MetricPosition::MidBar);
+/// # Ok::<(), composer_serialization::SerializationError>(())
+/// ```
+pub fn metric_position(beat: f64, time_signature: (u8, u8)) -> SerializationResult<MetricPosition> {
+    check_time_signature(time_signature)?;
+    let (numerator, denominator) = time_signature;
+    if !beat.is_finite() || beat < 0.0 {
+        return Err(SerializationError::InvalidFormat {
+            message: format!("Invalid beat position {}", beat),
+        });
+    }
+
+    let compound = denominator >= 8 && numerator > 3 && numerator % 3 == 0;
+    let unit_ticks = TICKS_PER_BEAT * 4 / u32::from(denominator);
+    let (beat_ticks, beats_per_bar) = if compound {
+        (unit_ticks * 3, u32::from(numerator / 3))
+    } else {
+        (unit_ticks, u32::from(numerator))
+    };
+    let bar_ticks = beat_ticks * beats_per_bar;
+    let has_mid_bar = beats_per_bar % 2 == 0 && (beats_per_bar >= 4 || compound);
+    let mid_bar_ticks = bar_ticks / 2;
+
+    let tick = (beat * TICKS_PER_BEAT as f64).round() as u64 % u64::from(bar_ticks);
+    let tick = tick as u32;
+    if tick == 0 {
+        return Ok(MetricPosition::Downbeat);
+    }
+    if has_mid_bar && tick == mid_bar_ticks {
+        return Ok(MetricPosition::MidBar);
+    }
+    if tick % beat_ticks == 0 {
+        return Ok(MetricPosition::Beat);
+    }
+
+    let window = beat_ticks / 2;
+    let before_downbeat = bar_ticks - tick <= window;
+    let before_mid_bar = has_mid_bar && tick < mid_bar_ticks && mid_bar_ticks - tick <= window;
+    if before_downbeat || before_mid_bar {
+        Ok(MetricPosition::Anticipation)
+    } else {
+        Ok(MetricPosition::Offbeat)
+    }
+}
+
+fn check_time_signature((numerator, denominator): (u8, u8)) -> SerializationResult<()> {
+    if numerator == 0 || !matches!(denominator, 1 | 2 | 4 | 8 | 16 | 32) {
+        return Err(SerializationError::InvalidFormat {
+            message: format!("Invalid time signature {}/{}", numerator, denominator),
+        });
+    }
+    Ok(())
+}
+
+/// Metric position token (e.g. "M_ANT") of an onset `beat`
+pub fn tokenize_metric_position(
+    beat: f64,
+    time_signature: (u8, u8),
+) -> SerializationResult<String> {
+    metric_position(beat, time_signature).map(|position| position.token())
+}
+
+/// Parse a metric position token
+pub fn parse_metric_token(token: &str) -> SerializationResult<MetricPosition> {
+    MetricPosition::ALL
+        .into_iter()
+        .find(|position| position.token() == token)
+        .ok_or_else(|| SerializationError::InvalidTokenFormat {
+            token: token.to_string(),
+        })
+}
+
+/// Metric position of every event in a timeline, in event order
+///
+/// Events follow the timeline's meter changes, with bars restarting at each
+/// change; `time_signature` applies before the first change.
+pub fn timeline_metric_positions(
+    timeline: &Timeline,
+    time_signature: (u8, u8),
+) -> SerializationResult<Vec<MetricPosition>> {
+    timeline
+        .events
+        .iter()
+        .map(|event| match timeline.meter_at(event.beat) {
+            Some(change) => metric_position(event.beat - change.beat, change.time_signature),
+            None => metric_position(event.beat, time_signature),
+        })
+        .collect()
+}
+
 /// Note tokenization as raw chromatic values
 pub fn tokenize_note_as_raw(note: &Note, _scale: &ScaleFingerprint) -> SerializationResult<String> {
     if note.is_rest {
@@ -202,11 +634,14 @@ fn scale_degree_to_raw(scale_degree: u8) -> u8 {
         .unwrap_or(0)
 }
 
+/// Chords, notes, and total duration recovered from a cluster token string
+pub type ClusterDetokenization = (Vec<Chord>, Vec<Note>, f64);
+
 /// Cluster-based detokenization
 pub fn detokenize_cluster(
     token_string: &str,
     _scale: &ScaleFingerprint,
-) -> SerializationResult<(Vec<Chord>, Vec<Note>, f64)> {
+) -> SerializationResult<ClusterDetokenization> {
     let mut chords = Vec::new();
     let mut notes = Vec::new();
     let mut total_duration = 0.0;
@@ -293,6 +728,609 @@ pub fn detokenize_midi_like(
     Ok(Timeline {
         events,
         total_duration: current_beat,
+        meter_changes: Vec::new(),
+        key_changes: Vec::new(),
+    })
+}
+
+/// Polyphonic tokenization of a timeline
+///
+/// Each event is written at its onset and followed by a duration token giving
+/// the time to the next event, or to the end of the timeline for the last:
+///
+/// - notes as `R_x O_y`, as [`tokenize_note_as_raw`] writes them
+/// - chords as a `<CHORD>` group holding their [`tokenize_chord_as_raw`] cluster
+/// - note clusters as a `<NOTES>` group with `R_x O_y D_z` per note, so each
+///   note keeps its own duration and can sustain past later onsets
+/// - rests as `NOTE-REST` or `CHORD-REST`
+///
+/// Events at the same beat are separated by `D_0`, and time before the first
+/// event is written as a rest. Beats and durations are rounded to the tick
+/// grid. Fails with `SerializationError::InvalidFormat` for negative beats or
+/// cluster notes out of range.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_core::ScaleFingerprint;
+/// use composer_serialization::{
+///     detokenize_polyphonic, tokenize_polyphonic, ClusterNote, Timeline, TokenEvent,
+///     TokenEventType,
+/// };
+///
+/// // A held bass note under a shorter two-note chord
+/// let cluster = vec![
+///     ClusterNote { pitch_class: 0, octave: 3, duration: 2.0 },
+///     ClusterNote { pitch_class: 4, octave: 4, duration: 1.0 },
+///     ClusterNote { pitch_class: 7, octave: 4, duration: 1.0 },
+/// ];
+/// let timeline = Timeline {
+///     events: vec![TokenEvent { beat: 0.0, event_type: TokenEventType::Cluster(cluster.clone()) }],
+///     total_duration: 2.0,
+///     ..Default::default()
+/// };
+///
+/// let tokens = tokenize_polyphonic(&timeline, &ScaleFingerprint::major_scale())?;
+/// assert_eq!(
+///     tokens.join(" "),
+///     "<NOTES> R_0 O_3 D_30 R_4 O_4 D_18 R_7 O_4 D_18 </NOTES> D_30"
+/// );
+///
+/// let restored = detokenize_polyphonic(&tokens)?;
+/// assert!(matches!(&restored.events[0].event_type, TokenEventType::Cluster(notes) if *notes == cluster));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`detokenize_polyphonic`] - Read the tokens back into a timeline
+/// - [`validate_token_stream`] - Check the token grammar
+pub fn tokenize_polyphonic(
+    timeline: &Timeline,
+    scale: &ScaleFingerprint,
+) -> SerializationResult<Vec<String>> {
+    let events = sorted_events(timeline)?;
+
+    let mut tokens = Vec::new();
+    if let Some(first) = events.first().filter(|first| first.beat > 0.0) {
+        tokens.push(REST_NOTE_TOKEN.to_string());
+        tokens.push(tokenize_duration(first.beat));
+    }
+
+    for (index, event) in events.iter().enumerate() {
+        push_event_tokens(&mut tokens, &event.event_type, scale)?;
+
+        let next_beat = events
+            .get(index + 1)
+            .map_or(timeline.total_duration.max(event.beat), |next| next.beat);
+        tokens.push(tokenize_duration(next_beat - event.beat));
+    }
+
+    Ok(tokens)
+}
+
+/// Events of a timeline ordered by beat, checking that beats are not negative
+fn sorted_events(timeline: &Timeline) -> SerializationResult<Vec<&TokenEvent>> {
+    let mut events: Vec<&TokenEvent> = timeline.events.iter().collect();
+    events.sort_by(|a, b| a.beat.total_cmp(&b.beat));
+
+    if let Some(event) = events
+        .iter()
+        .find(|event| !event.beat.is_finite() || event.beat < 0.0)
+    {
+        return Err(SerializationError::InvalidFormat {
+            message: format!("Event beat {} is not a non-negative number", event.beat),
+        });
+    }
+    Ok(events)
+}
+
+/// Tokens of one event as [`tokenize_polyphonic`] writes them, without the
+/// duration that follows
+fn push_event_tokens(
+    tokens: &mut Vec<String>,
+    event_type: &TokenEventType,
+    scale: &ScaleFingerprint,
+) -> SerializationResult<()> {
+    match event_type {
+        TokenEventType::Note(note) => {
+            let raw = tokenize_note_as_raw(note, scale)?;
+            tokens.extend(raw.split_whitespace().map(str::to_string));
+        },
+        TokenEventType::Chord(chord) if chord.is_rest => {
+            tokens.push(REST_CHORD_TOKEN.to_string());
+        },
+        TokenEventType::Chord(chord) => {
+            tokens.push(CHORD_GROUP_OPEN.to_string());
+            tokens.push(tokenize_chord_as_raw(chord, scale)?);
+            tokens.push(CHORD_GROUP_CLOSE.to_string());
+        },
+        TokenEventType::Rest => tokens.push(REST_NOTE_TOKEN.to_string()),
+        TokenEventType::Cluster(notes) => {
+            tokens.push(NOTES_GROUP_OPEN.to_string());
+            for note in notes {
+                check_cluster_note(note)?;
+                tokens.push(format!("{}{:x}", RAW_NOTE_TOKEN_PREFIX, note.pitch_class));
+                tokens.push(format!("{}{:x}", OCTAVE_TOKEN_PREFIX, note.octave));
+                tokens.push(tokenize_duration(note.duration));
+            }
+            tokens.push(NOTES_GROUP_CLOSE.to_string());
+        },
+    }
+    Ok(())
+}
+
+fn check_cluster_note(note: &ClusterNote) -> SerializationResult<()> {
+    if note.pitch_class >= CHROMATIC_RANGE
+        || !(OCTAVE_RANGE_MIN..=OCTAVE_RANGE_MAX).contains(&note.octave)
+        || !note.duration.is_finite()
+        || note.duration < 0.0
+    {
+        return Err(SerializationError::InvalidFormat {
+            message: format!(
+                "Cluster note out of range: pitch class {}, octave {}, duration {}",
+                note.pitch_class, note.octave, note.duration
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Event read from a polyphonic token stream, waiting for the duration that
+/// follows it
+#[allow(clippy::large_enum_variant)]
+enum PendingEvent {
+    Event(TokenEventType),
+    /// Chord cluster pitch classes; the notes last until the next event
+    ChordCluster(Vec<u8>),
+}
+
+impl PendingEvent {
+    /// The event, with chord cluster notes in `octave` lasting `duration`
+    fn into_event_type(self, duration: f64, octave: u8) -> TokenEventType {
+        match self {
+            PendingEvent::Event(event_type) => event_type,
+            PendingEvent::ChordCluster(pitch_classes) => TokenEventType::Cluster(
+                pitch_classes
+                    .into_iter()
+                    .map(|pitch_class| ClusterNote {
+                        pitch_class,
+                        octave,
+                        duration,
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Key token (e.g. "K_9_MIN") of a key's tonic pitch class and mode
+///
+/// Modes other than major and minor are written as whichever of the two shares
+/// their third, and enharmonic tonics share a token.
+pub fn tokenize_key(key: &Key) -> String {
+    let mode = if key.mode.is_minor() { "MIN" } else { "MAJ" };
+    format!("{}{:x}_{}", KEY_TOKEN_PREFIX, key.tonic.chromatic(), mode)
+}
+
+/// Major or minor key of a key token, spelled with the fewest accidentals
+pub fn parse_key_token(token: &str) -> SerializationResult<Key> {
+    let invalid = || SerializationError::InvalidTokenFormat {
+        token: token.to_string(),
+    };
+    if !validate_key_token(token) {
+        return Err(invalid());
+    }
+    let (tonic, mode) = token[KEY_TOKEN_PREFIX.len()..]
+        .split_once('_')
+        .ok_or_else(invalid)?;
+    let tonic = u8::from_str_radix(tonic, 16).map_err(|_| invalid())?;
+    let mode = if mode == "MIN" {
+        Mode::Minor
+    } else {
+        Mode::Major
+    };
+    Ok(Key::from_chromatic(tonic, mode))
+}
+
+/// Time signature token (e.g. "TS_6_8")
+pub fn tokenize_time_signature(time_signature: (u8, u8)) -> SerializationResult<String> {
+    check_time_signature(time_signature)?;
+    Ok(format!(
+        "{}{}_{}",
+        TIME_SIGNATURE_TOKEN_PREFIX, time_signature.0, time_signature.1
+    ))
+}
+
+/// Parse a time signature token
+pub fn parse_time_signature_token(token: &str) -> SerializationResult<(u8, u8)> {
+    let invalid = || SerializationError::InvalidTokenFormat {
+        token: token.to_string(),
+    };
+    if !validate_time_signature_token(token) {
+        return Err(invalid());
+    }
+    let (numerator, denominator) = token[TIME_SIGNATURE_TOKEN_PREFIX.len()..]
+        .split_once('_')
+        .ok_or_else(invalid)?;
+    Ok((
+        numerator.parse().map_err(|_| invalid())?,
+        denominator.parse().map_err(|_| invalid())?,
+    ))
+}
+
+/// Chord token naming one exact chord: `CH_` and its binary serialization in hex
+///
+/// Unlike a raw chromatic cluster, the token identifies the chord, so
+/// [`detokenize_tokens`] restores it unchanged.
+pub fn tokenize_chord_binary(chord: &Chord) -> SerializationResult<String> {
+    let binary = crate::chord_binary::serialize_chord(chord)?;
+    Ok(format!(
+        "{}{}",
+        CHORD_TOKEN_PREFIX,
+        crate::chord_binary::chord_binary_to_hex(&binary)
+    ))
+}
+
+/// Chord named by a token written by [`tokenize_chord_binary`]
+pub fn parse_chord_binary_token(token: &str) -> SerializationResult<Chord> {
+    if !validate_chord_token(token) {
+        return Err(SerializationError::InvalidTokenFormat {
+            token: token.to_string(),
+        });
+    }
+    let binary = crate::chord_binary::hex_to_chord_binary(&token[CHORD_TOKEN_PREFIX.len()..])?;
+    crate::chord_binary::deserialize_chord(&binary)
+}
+
+/// Structure token and the tick it is written at
+type StructureToken = (u64, String);
+
+/// Bar, key and time signature tokens of a timeline by tick, in stream order
+///
+/// Bars restart at each meter change and are 4/4 before the first. Only bars
+/// starting before `end` are written.
+fn structure_tokens(timeline: &Timeline, end: f64) -> SerializationResult<Vec<StructureToken>> {
+    // Tick, rank among tokens at that tick, token
+    type Marker = (u64, u8, String);
+    // Tick where a time signature takes effect
+    type TickMeter = (u64, (u8, u8));
+
+    let tick = |beat: f64| (beat.max(0.0) * TICKS_PER_BEAT as f64).round() as u64;
+    let end_tick = tick(end);
+    // Tokens at one tick are ordered bar line, key, time signature
+    let mut markers: Vec<Marker> = Vec::new();
+
+    let mut meters: Vec<TickMeter> = Vec::new();
+    if timeline
+        .meter_changes
+        .first()
+        .map_or(true, |first| tick(first.beat) > 0)
+    {
+        meters.push((0, (4, 4)));
+    }
+    for change in &timeline.meter_changes {
+        markers.push((
+            tick(change.beat),
+            2,
+            tokenize_time_signature(change.time_signature)?,
+        ));
+        meters.push((tick(change.beat), change.time_signature));
+    }
+    let mut bars: u64 = 0;
+    for (index, &(start, (numerator, denominator))) in meters.iter().enumerate() {
+        let segment_end = meters.get(index + 1).map_or(end_tick, |next| next.0);
+        let bar_ticks =
+            u64::from(numerator) * u64::from(TICKS_PER_BEAT) * 4 / u64::from(denominator);
+        bars += segment_end.min(end_tick).saturating_sub(start) / bar_ticks.max(1);
+        if bars > MAX_TIMELINE_BARS {
+            return Err(SerializationError::LimitExceeded {
+                resource: "bars".to_string(),
+                limit: MAX_TIMELINE_BARS,
+                actual: bars,
+            });
+        }
+        let mut bar = start;
+        while bar < segment_end.min(end_tick) {
+            markers.push((bar, 0, BAR_TOKEN.to_string()));
+            bar += bar_ticks.max(1);
+        }
+    }
+
+    for change in &timeline.key_changes {
+        markers.push((tick(change.beat), 1, tokenize_key(&change.key)));
+    }
+
+    markers.sort_by_key(|marker| (marker.0, marker.1));
+    Ok(markers
+        .into_iter()
+        .map(|(tick, _, token)| (tick, token))
+        .collect())
+}
+
+/// Tokenize a whole timeline as one sequence for a language model
+///
+/// Extends the [`tokenize_polyphonic`] format with the structure a model needs
+/// to learn form and harmony:
+///
+/// - `BAR` at the start of every bar, following the timeline's meter changes
+///   (4/4 before the first)
+/// - `TS_n_d` where the meter changes and `K_t_MAJ` or `K_t_MIN` where the key
+///   changes, `t` being the tonic pitch class in hex
+/// - chords as one `CH_` token naming the exact chord (see
+///   [`tokenize_chord_binary`]) instead of a raw cluster
+///
+/// Structure tokens are written before the first event at or after their beat,
+/// after the duration that reaches it; a change between two onsets therefore
+/// moves to the later one. Notes are written in the scale of the key in effect.
+/// Fails like [`tokenize_polyphonic`], or with `SerializationError::InvalidFormat`
+/// for an invalid time signature or key.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_core::Chord;
+/// use composer_serialization::{
+///     detokenize_tokens, tokenize_timeline, KeyChange, MeterChange, Timeline, TokenEvent,
+///     TokenEventType,
+/// };
+///
+/// let chord = |beat, root| TokenEvent { beat, event_type: TokenEventType::Chord(Chord::triad(root).unwrap()) };
+/// let timeline = Timeline {
+///     events: vec![chord(0.0, 1), chord(3.0, 4), chord(6.0, 5)],
+///     total_duration: 9.0,
+///     meter_changes: vec![MeterChange { beat: 0.0, time_signature: (3, 4) }],
+///     key_changes: vec![KeyChange { beat: 0.0, key: "D".parse()? }],
+/// };
+///
+/// let tokens = tokenize_timeline(&timeline)?;
+/// assert_eq!(&tokens[..3], ["BAR", "K_2_MAJ", "TS_3_4"]);
+/// assert_eq!(tokens.iter().filter(|token| *token == "BAR").count(), 3);
+///
+/// let restored = detokenize_tokens(&tokens)?;
+/// assert_eq!(restored.key_changes, timeline.key_changes);
+/// assert_eq!(restored.meter_changes, timeline.meter_changes);
+/// assert!(matches!(&restored.events[2].event_type, TokenEventType::Chord(c) if c.root == 5));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`detokenize_tokens`] - Read the sequence back into a timeline
+/// - [`crate::TokenVocabulary`] - Map tokens to model ids
+pub fn tokenize_timeline(timeline: &Timeline) -> SerializationResult<Vec<String>> {
+    let events = sorted_events(timeline)?;
+    let end = events.last().map_or(timeline.total_duration, |last| {
+        timeline.total_duration.max(last.beat)
+    });
+    let structure = structure_tokens(timeline, end)?;
+    let mut structure = structure.into_iter().peekable();
+    let mut tokens = Vec::new();
+
+    let leading_rest = events
+        .first()
+        .filter(|first| first.beat > 0.0)
+        .map(|_| TokenEvent {
+            beat: 0.0,
+            event_type: TokenEventType::Rest,
+        });
+    let events: Vec<&TokenEvent> = leading_rest.iter().chain(events).collect();
+
+    for (index, event) in events.iter().enumerate() {
+        let onset = (event.beat * TICKS_PER_BEAT as f64).round() as u64;
+        while let Some((_, token)) = structure.next_if(|(tick, _)| *tick <= onset) {
+            tokens.push(token);
+        }
+
+        match &event.event_type {
+            TokenEventType::Chord(chord) if !chord.is_rest => {
+                tokens.push(tokenize_chord_binary(chord)?);
+            },
+            event_type => {
+                let scale = match timeline.key_at(event.beat) {
+                    Some(change) if change.key.mode.is_minor() => ScaleFingerprint::minor_scale(),
+                    _ => ScaleFingerprint::major_scale(),
+                };
+                push_event_tokens(&mut tokens, event_type, &scale)?;
+            },
+        }
+
+        let next_beat = events.get(index + 1).map_or(end, |next| next.beat);
+        tokens.push(tokenize_duration(next_beat - event.beat));
+    }
+    tokens.extend(structure.map(|(_, token)| token));
+
+    Ok(tokens)
+}
+
+/// Polyphonic detokenization into a timeline
+///
+/// Reads the format written by [`tokenize_polyphonic`]. `<NOTES>` groups
+/// become [`TokenEventType::Cluster`] events with each note's own duration.
+/// `<CHORD>` groups become clusters too, since a raw chromatic cluster does not
+/// identify a chord: their notes last until the next event, in the current
+/// octave (4 if none has been set). Metric position tokens are skipped.
+///
+/// Fails with `SerializationError::InvalidTokenStream` if the stream breaks
+/// the token grammar (see [`validate_token_stream`]) or a cluster note has no
+/// duration.
+///
+/// # Related Functions
+///
+/// - [`tokenize_polyphonic`] - Write a timeline as tokens
+/// - [`detokenize_tokens`] - Also read bar, key, time signature and chord tokens
+pub fn detokenize_polyphonic<S: AsRef<str>>(tokens: &[S]) -> SerializationResult<Timeline> {
+    detokenize_tokens(tokens)
+}
+
+/// Detokenize a whole token sequence into a timeline
+///
+/// Reads the format written by [`tokenize_timeline`], and so every stream
+/// [`detokenize_polyphonic`] reads. `CH_` tokens become the exact chords they
+/// name; `K_` and `TS_` tokens become key and meter changes at the beat reached
+/// when they are read. Bar lines are checked by the grammar but otherwise
+/// skipped, since the meter determines them.
+///
+/// Fails with `SerializationError::InvalidTokenStream` if the stream breaks
+/// the token grammar (see [`validate_token_stream`]) or a cluster note has no
+/// duration.
+///
+/// # Related Functions
+///
+/// - [`tokenize_timeline`] - Write a timeline as one token sequence
+pub fn detokenize_tokens<S: AsRef<str>>(tokens: &[S]) -> SerializationResult<Timeline> {
+    const DEFAULT_OCTAVE: u8 = 4;
+
+    validate_token_stream(tokens)?;
+
+    let raw_note = |token: &str| -> SerializationResult<u8> {
+        token
+            .strip_prefix(RAW_NOTE_TOKEN_PREFIX)
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            .ok_or_else(|| SerializationError::InvalidTokenFormat {
+                token: token.to_string(),
+            })
+    };
+    let octave_of = |token: &str| -> SerializationResult<u8> {
+        token
+            .strip_prefix(OCTAVE_TOKEN_PREFIX)
+            .and_then(|octave| octave.parse().ok())
+            .ok_or_else(|| SerializationError::InvalidTokenFormat {
+                token: token.to_string(),
+            })
+    };
+    let chord_cluster =
+        |token: &str| -> SerializationResult<Vec<u8>> { token.split('-').map(raw_note).collect() };
+
+    let mut events = Vec::new();
+    let mut beat = 0.0;
+    let mut octave: Option<u8> = None;
+    let mut pending: Option<PendingEvent> = None;
+    // Notes of an open `<NOTES>` group; NaN durations are still to be read
+    let mut cluster: Option<Vec<ClusterNote>> = None;
+    let mut in_chord_group = false;
+    let mut after_raw_note = false;
+    let mut meter_changes = Vec::new();
+    let mut key_changes = Vec::new();
+
+    for (index, token) in tokens.iter().enumerate() {
+        let token = token.as_ref();
+        let follows_raw_note = std::mem::take(&mut after_raw_note);
+
+        if validate_octave_token(token) {
+            let value = octave_of(token)?;
+            octave = Some(value);
+            if follows_raw_note {
+                // The octave of the note just read
+                match (&mut cluster, &mut pending) {
+                    (Some(notes), _) => {
+                        if let Some(note) = notes.last_mut() {
+                            note.octave = value;
+                        }
+                    },
+                    (None, Some(PendingEvent::Event(TokenEventType::Note(note)))) => {
+                        note.octave = value;
+                    },
+                    _ => {},
+                }
+            }
+            continue;
+        }
+
+        if let Some(notes) = &mut cluster {
+            if token == NOTES_GROUP_CLOSE {
+                if notes.iter().any(|note| note.duration.is_nan()) {
+                    return Err(SerializationError::InvalidTokenStream {
+                        index,
+                        token: token.to_string(),
+                        reason: "cluster note has no duration".to_string(),
+                    });
+                }
+                pending = Some(PendingEvent::Event(TokenEventType::Cluster(
+                    std::mem::take(notes),
+                )));
+                cluster = None;
+            } else if validate_raw_note_token(token) {
+                notes.push(ClusterNote {
+                    pitch_class: raw_note(token)?,
+                    octave: octave.unwrap_or(DEFAULT_OCTAVE),
+                    duration: f64::NAN,
+                });
+                after_raw_note = true;
+            } else if validate_duration_token(token) {
+                if let Some(note) = notes.last_mut() {
+                    note.duration = parse_duration_token(token)?;
+                }
+            }
+            continue;
+        }
+
+        match token {
+            NOTES_GROUP_OPEN => cluster = Some(Vec::new()),
+            CHORD_GROUP_OPEN => in_chord_group = true,
+            CHORD_GROUP_CLOSE => in_chord_group = false,
+            REST_NOTE_TOKEN | REST_CHORD_TOKEN => {
+                pending = Some(PendingEvent::Event(TokenEventType::Rest));
+            },
+            _ if validate_duration_token(token) => {
+                let duration = parse_duration_token(token)?;
+                if let Some(event) = pending.take() {
+                    events.push(TokenEvent {
+                        beat,
+                        event_type: event
+                            .into_event_type(duration, octave.unwrap_or(DEFAULT_OCTAVE)),
+                    });
+                }
+                beat += duration;
+            },
+            _ if !in_chord_group && validate_raw_note_token(token) => {
+                pending = Some(PendingEvent::Event(TokenEventType::Note(Note {
+                    scale_degree: raw_note(token)?,
+                    octave: octave.unwrap_or(DEFAULT_OCTAVE),
+                    is_rest: false,
+                })));
+                after_raw_note = true;
+            },
+            _ if validate_chord_cluster_token(token) => {
+                pending = Some(PendingEvent::ChordCluster(chord_cluster(token)?));
+            },
+            _ if validate_chord_token(token) => {
+                let chord = parse_chord_binary_token(token).map_err(|e| {
+                    SerializationError::InvalidTokenStream {
+                        index,
+                        token: token.to_string(),
+                        reason: e.to_string(),
+                    }
+                })?;
+                pending = Some(PendingEvent::Event(TokenEventType::Chord(chord)));
+            },
+            _ if validate_key_token(token) => {
+                let key = parse_key_token(token)?;
+                key_changes.push(KeyChange { beat, key });
+            },
+            _ if validate_time_signature_token(token) => {
+                meter_changes.push(MeterChange {
+                    beat,
+                    time_signature: parse_time_signature_token(token)?,
+                });
+            },
+            _ => {},
+        }
+    }
+
+    // An event without a following duration takes no time
+    if let Some(event) = pending {
+        events.push(TokenEvent {
+            beat,
+            event_type: event.into_event_type(0.0, octave.unwrap_or(DEFAULT_OCTAVE)),
+        });
+    }
+
+    Ok(Timeline {
+        events,
+        total_duration: beat,
+        meter_changes,
+        key_changes,
     })
 }
 
@@ -322,6 +1360,8 @@ pub fn reconstruct_timeline(events: Vec<TokenEvent>) -> Timeline {
     Timeline {
         events: filled_events,
         total_duration,
+        meter_changes: Vec::new(),
+        key_changes: Vec::new(),
     }
 }
 
@@ -420,6 +1460,11 @@ pub fn validate_token(token: &str) -> bool {
         || validate_raw_note_token(token)
         || validate_octave_token(token)
         || validate_chord_cluster_token(token)
+        || validate_metric_token(token)
+        || validate_key_token(token)
+        || validate_time_signature_token(token)
+        || validate_chord_token(token)
+        || token == BAR_TOKEN
 }
 
 pub fn validate_duration_token(token: &str) -> bool {
@@ -446,6 +1491,267 @@ pub fn validate_chord_cluster_token(token: &str) -> bool {
         .unwrap_or(false)
 }
 
+pub fn validate_metric_token(token: &str) -> bool {
+    Regex::new(METRIC_PATTERN)
+        .map(|regex| regex.is_match(token))
+        .unwrap_or(false)
+}
+
+pub fn validate_key_token(token: &str) -> bool {
+    Regex::new(KEY_PATTERN)
+        .map(|regex| regex.is_match(token))
+        .unwrap_or(false)
+}
+
+pub fn validate_time_signature_token(token: &str) -> bool {
+    Regex::new(TIME_SIGNATURE_PATTERN)
+        .map(|regex| regex.is_match(token))
+        .unwrap_or(false)
+}
+
+pub fn validate_chord_token(token: &str) -> bool {
+    Regex::new(CHORD_PATTERN)
+        .map(|regex| regex.is_match(token))
+        .unwrap_or(false)
+}
+
+/// Split a token string into stream tokens
+///
+/// Splits on whitespace and separates the `<CHORD>`, `</CHORD>`, `<NOTES>` and
+/// `</NOTES>` group markers from adjacent tokens.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_serialization::split_token_stream;
+///
+/// assert_eq!(
+///     split_token_stream("<CHORD>R_0-R_4-R_7</CHORD> D_18"),
+///     ["<CHORD>", "R_0-R_4-R_7", "</CHORD>", "D_18"]
+/// );
+/// ```
+pub fn split_token_stream(token_string: &str) -> Vec<String> {
+    const MARKERS: [&str; 4] = [
+        CHORD_GROUP_OPEN,
+        CHORD_GROUP_CLOSE,
+        NOTES_GROUP_OPEN,
+        NOTES_GROUP_CLOSE,
+    ];
+
+    let mut tokens = Vec::new();
+    for word in token_string.split_whitespace() {
+        let mut rest = word;
+        while !rest.is_empty() {
+            let next_marker = MARKERS
+                .iter()
+                .filter_map(|marker| rest.find(marker).map(|at| (at, *marker)))
+                .min();
+            match next_marker {
+                Some((0, marker)) => {
+                    tokens.push(marker.to_string());
+                    rest = &rest[marker.len()..];
+                },
+                Some((at, _)) => {
+                    tokens.push(rest[..at].to_string());
+                    rest = &rest[at..];
+                },
+                None => {
+                    tokens.push(rest.to_string());
+                    rest = "";
+                },
+            }
+        }
+    }
+    tokens
+}
+
+/// Check a whole token stream against the token grammar
+///
+/// Individual `validate_*` functions check the shape of one token; this also
+/// checks how tokens combine, so a stream can be rejected before
+/// detokenization:
+///
+/// - every token is a duration, raw note, octave, chord cluster, chord,
+///   metric position, bar line, key, time signature, rest or group marker
+/// - `<CHORD>` and `<NOTES>` groups are closed, do not nest, and `<CHORD>`
+///   groups hold only chord cluster tokens
+/// - chords, bar lines, keys and time signatures are outside groups
+/// - a duration follows a note, rest, chord, chord cluster or group, once per
+///   event
+/// - a raw note has an octave, set by an earlier octave token or given right
+///   after the note as [`tokenize_note_as_raw`] emits it
+/// - a metric position token is followed by an event
+///
+/// Fails with `SerializationError::InvalidTokenStream` at the first violation.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_serialization::{validate_token_stream, SerializationError};
+///
+/// assert!(validate_token_stream(&["M_DOWN", "O_4", "R_0", "D_18", "<CHORD>", "R_0-R_4-R_7", "</CHORD>", "D_30"]).is_ok());
+///
+/// match validate_token_stream(&["O_4", "D_18"]) {
+///     Err(SerializationError::InvalidTokenStream { index, .. }) => assert_eq!(index, 1),
+///     other => panic!("unexpected {:?}", other),
+/// }
+/// ```
+///
+/// # Related Functions
+///
+/// - [`split_token_stream`] - Split a token string into stream tokens
+/// - [`validate_token`] - Check the shape of a single token
+pub fn validate_token_stream<S: AsRef<str>>(tokens: &[S]) -> SerializationResult<()> {
+    let violation = |index: usize, reason: &str| SerializationError::InvalidTokenStream {
+        index,
+        token: tokens
+            .get(index)
+            .map_or_else(String::new, |token| token.as_ref().to_string()),
+        reason: reason.to_string(),
+    };
+
+    // Open group: (closing marker, index of the opening marker)
+    let mut group: Option<(&str, usize)> = None;
+    let mut octave_set = false;
+    // A duration may follow
+    let mut after_event = false;
+    // Raw note that the next token may give the octave of
+    let mut last_note: Option<usize> = None;
+    // Metric position still waiting for its event
+    let mut pending_metric: Option<usize> = None;
+
+    for (index, token) in tokens.iter().enumerate() {
+        let token = token.as_ref();
+
+        if let Some(note) = last_note.take() {
+            if validate_octave_token(token) {
+                octave_set = true;
+                continue;
+            }
+            if !octave_set {
+                return Err(violation(note, "raw note has no octave"));
+            }
+        }
+
+        match token {
+            CHORD_GROUP_OPEN | NOTES_GROUP_OPEN => {
+                if group.is_some() {
+                    return Err(violation(index, "groups cannot nest"));
+                }
+                let close = if token == CHORD_GROUP_OPEN {
+                    CHORD_GROUP_CLOSE
+                } else {
+                    NOTES_GROUP_CLOSE
+                };
+                group = Some((close, index));
+                pending_metric = None;
+                after_event = false;
+            },
+            CHORD_GROUP_CLOSE | NOTES_GROUP_CLOSE => {
+                if let Some(metric) = pending_metric {
+                    return Err(violation(
+                        metric,
+                        "metric position is not followed by an event",
+                    ));
+                }
+                match group {
+                    Some((close, _)) if close == token => group = None,
+                    _ => return Err(violation(index, "closing marker has no open group")),
+                }
+                after_event = true;
+            },
+            _ if group.is_some_and(|(close, _)| close == CHORD_GROUP_CLOSE) => {
+                if !validate_chord_cluster_token(token) {
+                    return Err(violation(index, "chord groups hold only chord clusters"));
+                }
+            },
+            _ if validate_duration_token(token) => {
+                if let Some(metric) = pending_metric {
+                    return Err(violation(
+                        metric,
+                        "metric position is not followed by an event",
+                    ));
+                }
+                if !after_event {
+                    return Err(violation(
+                        index,
+                        "duration does not follow a note, rest or chord",
+                    ));
+                }
+                after_event = false;
+            },
+            _ if validate_octave_token(token) => {
+                octave_set = true;
+                after_event = false;
+            },
+            _ if token == BAR_TOKEN
+                || validate_key_token(token)
+                || validate_time_signature_token(token) =>
+            {
+                if group.is_some() {
+                    return Err(violation(
+                        index,
+                        "structure tokens cannot be inside a group",
+                    ));
+                }
+                if let Some(metric) = pending_metric {
+                    return Err(violation(
+                        metric,
+                        "metric position is not followed by an event",
+                    ));
+                }
+                after_event = false;
+            },
+            _ if validate_chord_token(token) => {
+                if group.is_some() {
+                    return Err(violation(index, "chords cannot be inside a group"));
+                }
+                pending_metric = None;
+                after_event = true;
+            },
+            _ if validate_metric_token(token) => {
+                if let Some(metric) = pending_metric {
+                    return Err(violation(
+                        metric,
+                        "metric position is not followed by an event",
+                    ));
+                }
+                pending_metric = Some(index);
+                after_event = false;
+            },
+            _ if validate_raw_note_token(token) => {
+                last_note = Some(index);
+                pending_metric = None;
+                after_event = true;
+            },
+            _ if token == REST_NOTE_TOKEN
+                || token == REST_CHORD_TOKEN
+                || validate_chord_cluster_token(token) =>
+            {
+                pending_metric = None;
+                after_event = true;
+            },
+            _ => return Err(violation(index, "unknown token")),
+        }
+    }
+
+    if let Some(note) = last_note {
+        if !octave_set {
+            return Err(violation(note, "raw note has no octave"));
+        }
+    }
+    if let Some(metric) = pending_metric {
+        return Err(violation(
+            metric,
+            "metric position is not followed by an event",
+        ));
+    }
+    if let Some((_, open)) = group {
+        return Err(violation(open, "group is never closed"));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -462,6 +1768,332 @@ mod tests {
         assert_eq!(duration, 1.5);
     }
 
+    #[test]
+    fn test_validate_token_stream() {
+        let violation = |tokens: &[&str]| match validate_token_stream(tokens) {
+            Err(SerializationError::InvalidTokenStream { index, reason, .. }) => (index, reason),
+            other => panic!("expected a violation, got {:?}", other),
+        };
+
+        let note = tokenize_note_as_raw(
+            &Note {
+                scale_degree: 0,
+                octave: 4,
+                is_rest: false,
+            },
+            &ScaleFingerprint::major_scale(),
+        )
+        .unwrap();
+        let stream = format!(
+            "M_DOWN {} D_18 O_5 R_4 D_c <CHORD>R_0-R_4-R_7</CHORD> D_30 <NOTES> R_2 D_c </NOTES> NOTE-REST D_18",
+            note
+        );
+        assert!(validate_token_stream(&split_token_stream(&stream)).is_ok());
+        assert!(validate_token_stream::<&str>(&[]).is_ok());
+
+        assert_eq!(violation(&["D_18"]).0, 0);
+        assert_eq!(violation(&["O_4", "R_0", "D_18", "D_18"]).0, 3);
+        assert_eq!(violation(&["R_0", "D_18"]).1, "raw note has no octave");
+        assert_eq!(violation(&["O_4", "R_0", "M_OFF", "D_18"]).0, 2);
+        assert_eq!(violation(&["<CHORD>", "R_0-R_4", "D_18", "</CHORD>"]).0, 2);
+        assert_eq!(violation(&["<NOTES>", "<CHORD>"]).0, 1);
+        assert_eq!(violation(&["</NOTES>"]).0, 0);
+        assert_eq!(
+            violation(&["<CHORD>", "R_0-R_4"]),
+            (0, "group is never closed".to_string())
+        );
+        assert_eq!(violation(&["O_4", "X_1"]).1, "unknown token");
+    }
+
+    #[test]
+    fn test_metric_positions() {
+        let positions = |beats: &[f64], time_signature| -> Vec<MetricPosition> {
+            beats
+                .iter()
+                .map(|&beat| metric_position(beat, time_signature).unwrap())
+                .collect()
+        };
+        use MetricPosition::*;
+        assert_eq!(
+            positions(&[0.0, 1.0, 1.5, 2.0, 2.5, 3.5, 4.0, 4.25], (4, 4)),
+            [
+                Downbeat,
+                Beat,
+                Anticipation,
+                MidBar,
+                Offbeat,
+                Anticipation,
+                Downbeat,
+                Offbeat
+            ]
+        );
+        assert_eq!(
+            positions(&[0.0, 1.0, 2.0, 2.5], (3, 4)),
+            [Downbeat, Beat, Beat, Anticipation]
+        );
+        assert_eq!(
+            positions(&[0.5, 1.5, 2.5], (6, 8)),
+            [Offbeat, MidBar, Anticipation]
+        );
+        assert_eq!(
+            positions(&[2.0, 3.0, 4.0], (2, 2)),
+            [Beat, Anticipation, Downbeat]
+        );
+
+        assert!(metric_position(1.0, (4, 3)).is_err());
+        assert!(metric_position(-1.0, (4, 4)).is_err());
+
+        for position in MetricPosition::ALL {
+            assert!(validate_metric_token(&position.token()));
+            assert_eq!(parse_metric_token(&position.token()).unwrap(), position);
+        }
+        assert_eq!(tokenize_metric_position(3.5, (4, 4)).unwrap(), "M_ANT");
+        assert!(parse_metric_token("M_UP").is_err());
+
+        // Bars restart at a change from 4/4 to 7/8 on beat 4
+        let timeline = Timeline {
+            events: [0.0, 4.0, 5.75, 7.5]
+                .iter()
+                .map(|&beat| TokenEvent {
+                    beat,
+                    event_type: TokenEventType::Rest,
+                })
+                .collect(),
+            total_duration: 8.0,
+            meter_changes: vec![MeterChange {
+                beat: 4.0,
+                time_signature: (7, 8),
+            }],
+            ..Default::default()
+        };
+        assert_eq!(timeline.meter_at(2.0), None);
+        assert_eq!(timeline.meter_at(5.0).unwrap().time_signature, (7, 8));
+        assert_eq!(
+            timeline_metric_positions(&timeline, (4, 4)).unwrap(),
+            [Downbeat, Downbeat, Offbeat, Downbeat]
+        );
+    }
+
+    #[test]
+    fn test_token_library_round_trip() {
+        let mut library = TokenLibrary::new();
+        library.add_chord_token("C-MAJ".to_string(), vec![0x10, 0x00, 0x00, 0x00, 0x00]);
+        library.add_chord_token("G-7".to_string(), vec![0x50, 0x08, 0x00, 0x00, 0x00]);
+
+        let bytes = serialize_token_library(&library).unwrap();
+        let restored = deserialize_token_library(&bytes).unwrap();
+        assert_eq!(restored.get_library_size(), 2);
+        assert_eq!(
+            restored.resolve_chord_token("G-7").unwrap(),
+            vec![0x50, 0x08, 0x00, 0x00, 0x00]
+        );
+        assert_eq!(serialize_token_library(&restored).unwrap(), bytes);
+
+        assert!(deserialize_token_library(&bytes[..bytes.len() - 1]).is_err());
+        assert!(deserialize_token_library(b"nope").is_err());
+    }
+
+    #[test]
+    fn test_polyphonic_round_trip() {
+        let scale = ScaleFingerprint::major_scale();
+        let note = |pitch_class, octave, duration| ClusterNote {
+            pitch_class,
+            octave,
+            duration,
+        };
+        let event = |beat, event_type| TokenEvent { beat, event_type };
+        // Left hand holds a bass note across two right-hand clusters
+        let events = vec![
+            event(
+                1.0,
+                TokenEventType::Cluster(vec![note(0, 3, 4.0), note(4, 4, 2.0), note(7, 4, 2.0)]),
+            ),
+            event(
+                3.0,
+                TokenEventType::Cluster(vec![note(5, 4, 1.5), note(9, 4, 1.5)]),
+            ),
+            event(
+                3.0,
+                TokenEventType::Note(Note {
+                    scale_degree: 0,
+                    octave: 5,
+                    is_rest: false,
+                }),
+            ),
+            event(4.5, TokenEventType::Rest),
+        ];
+        let timeline = Timeline {
+            events,
+            total_duration: 5.0,
+            ..Default::default()
+        };
+
+        let tokens = tokenize_polyphonic(&timeline, &scale).unwrap();
+        assert!(validate_token_stream(&tokens).is_ok());
+        assert_eq!(&tokens[..2], ["NOTE-REST", "D_18"]);
+
+        let restored = detokenize_polyphonic(&tokens).unwrap();
+        assert_eq!(restored.total_duration, 5.0);
+        // The leading gap comes back as a rest
+        assert_eq!(restored.events.len(), timeline.events.len() + 1);
+        for (original, restored) in timeline.events.iter().zip(&restored.events[1..]) {
+            assert_eq!(original.beat, restored.beat);
+            match (&original.event_type, &restored.event_type) {
+                (TokenEventType::Cluster(a), TokenEventType::Cluster(b)) => assert_eq!(a, b),
+                (TokenEventType::Note(a), TokenEventType::Note(b)) => {
+                    assert_eq!((a.scale_degree, a.octave), (b.scale_degree, b.octave))
+                },
+                (TokenEventType::Rest, TokenEventType::Rest) => {},
+                other => panic!("event changed: {:?}", other),
+            }
+        }
+
+        // Chord groups become clusters lasting until the next event
+        let chords =
+            detokenize_polyphonic(&["O_3", "<CHORD>", "R_0-R_4-R_7", "</CHORD>", "D_30"]).unwrap();
+        assert!(matches!(
+            &chords.events[0].event_type,
+            TokenEventType::Cluster(notes) if notes == &[note(0, 3, 2.0), note(4, 3, 2.0), note(7, 3, 2.0)]
+        ));
+
+        assert!(matches!(
+            detokenize_polyphonic(&["<NOTES>", "R_0", "O_4", "</NOTES>", "D_18"]),
+            Err(SerializationError::InvalidTokenStream { index: 3, .. })
+        ));
+        let out_of_range = Timeline {
+            events: vec![event(0.0, TokenEventType::Cluster(vec![note(12, 4, 1.0)]))],
+            ..Default::default()
+        };
+        assert!(tokenize_polyphonic(&out_of_range, &scale).is_err());
+    }
+
+    #[test]
+    fn test_timeline_round_trip() {
+        let event = |beat, event_type| TokenEvent { beat, event_type };
+        let seventh = Chord::new(5, 7).unwrap();
+        // Starts after a rest, changes to 3/4 and to A minor on beat 8
+        let timeline = Timeline {
+            events: vec![
+                event(1.0, TokenEventType::Chord(Chord::triad(1).unwrap())),
+                event(4.0, TokenEventType::Chord(seventh.clone())),
+                event(
+                    4.0,
+                    TokenEventType::Note(Note {
+                        scale_degree: 7,
+                        octave: 5,
+                        is_rest: false,
+                    }),
+                ),
+                event(8.0, TokenEventType::Chord(Chord::rest())),
+                event(9.0, TokenEventType::Chord(Chord::triad(6).unwrap())),
+            ],
+            total_duration: 11.0,
+            meter_changes: vec![MeterChange {
+                beat: 8.0,
+                time_signature: (3, 4),
+            }],
+            key_changes: vec![
+                KeyChange {
+                    beat: 0.0,
+                    key: "C".parse().unwrap(),
+                },
+                KeyChange {
+                    beat: 8.0,
+                    key: "Am".parse().unwrap(),
+                },
+            ],
+        };
+
+        let tokens = tokenize_timeline(&timeline).unwrap();
+        assert!(validate_token_stream(&tokens).is_ok());
+        assert_eq!(&tokens[..4], ["BAR", "K_0_MAJ", "NOTE-REST", "D_18"]);
+        let change = tokens.iter().position(|token| token == "K_9_MIN").unwrap();
+        assert_eq!(
+            &tokens[change - 1..=change + 1],
+            ["BAR", "K_9_MIN", "TS_3_4"]
+        );
+        // Two bars of 4/4, then 3/4 bars on beats 8 and 11 - only the first before the end
+        assert_eq!(tokens.iter().filter(|token| *token == BAR_TOKEN).count(), 3);
+
+        let restored = detokenize_tokens(&tokens).unwrap();
+        assert_eq!(restored.total_duration, 11.0);
+        assert_eq!(restored.meter_changes, timeline.meter_changes);
+        assert_eq!(restored.key_changes, timeline.key_changes);
+        assert_eq!(restored.events.len(), timeline.events.len() + 1);
+        assert!(matches!(
+            restored.events[0].event_type,
+            TokenEventType::Rest
+        ));
+        assert!(matches!(
+            &restored.events[2].event_type,
+            TokenEventType::Chord(chord) if *chord == seventh
+        ));
+        assert!(matches!(
+            restored.events[4].event_type,
+            TokenEventType::Rest
+        ));
+        for (original, restored) in timeline.events.iter().zip(&restored.events[1..]) {
+            assert_eq!(original.beat, restored.beat);
+        }
+
+        // Structure tokens and chords stay outside groups
+        assert!(validate_token_stream(&["<NOTES>", "BAR", "</NOTES>", "D_18"]).is_err());
+        assert!(validate_token_stream(&["<CHORD>", &tokens[4], "</CHORD>", "D_18"]).is_err());
+        assert!(validate_token_stream(&["M_DOWN", "BAR", "NOTE-REST", "D_18"]).is_err());
+        assert!(validate_token_stream(&["BAR", "D_18"]).is_err());
+        assert!(detokenize_tokens(&["CH_ffffffffff", "D_18"]).is_err());
+
+        assert_eq!(parse_key_token("K_b_MAJ").unwrap(), "B".parse().unwrap());
+        assert_eq!(parse_key_token("K_a_MIN").unwrap(), "Bbm".parse().unwrap());
+        assert_eq!(tokenize_key(&"Db dorian".parse().unwrap()), "K_1_MIN");
+        assert!(parse_key_token("K_c_MAJ").is_err());
+        assert_eq!(parse_time_signature_token("TS_12_8").unwrap(), (12, 8));
+        assert!(tokenize_time_signature((4, 3)).is_err());
+    }
+
+    #[test]
+    fn test_token_library_upgrade() {
+        let mut old = TokenLibrary::with_schema_version(1).unwrap();
+        old.add_chord_token(
+            "R_0-R_4-R_7".to_string(),
+            vec![0x10, 0x00, 0x00, 0x00, 0x00],
+        );
+        old.add_chord_token(
+            "R_7-R_11-R_2".to_string(),
+            vec![0x50, 0x00, 0x00, 0x00, 0x00],
+        );
+        let bytes = serialize_token_library(&old).unwrap();
+        assert_eq!(bytes[4], 1);
+
+        // Version 1 libraries load in the current vocabulary
+        let loaded = deserialize_token_library(&bytes).unwrap();
+        assert_eq!(loaded.schema_version, TOKEN_LIBRARY_FORMAT_VERSION);
+        assert_eq!(loaded.get_library_size(), 2);
+        assert!(loaded.resolve_chord_token("R_7-R_b-R_2").is_ok());
+        assert!(loaded.resolve_chord_token("R_7-R_11-R_2").is_err());
+
+        assert_eq!(
+            upgrade_token_stream(&["O_4", "R_10", "D_48", "M_DOWN"], 1).unwrap(),
+            ["O_4", "R_a", "D_30", "M_DOWN"]
+        );
+        assert!(upgrade_token("R_0", 0).is_err());
+        assert!(TokenLibrary::with_schema_version(TOKEN_LIBRARY_FORMAT_VERSION + 1).is_err());
+
+        // Two version 1 tokens for different chords cannot share a version 2 token
+        let mut clash = TokenLibrary::with_schema_version(1).unwrap();
+        clash.add_chord_token("D_10".to_string(), vec![0x10, 0x00, 0x00, 0x00, 0x00]);
+        clash.add_chord_token("D_a".to_string(), vec![0x50, 0x00, 0x00, 0x00, 0x00]);
+        assert!(clash.upgrade().is_err());
+        assert_eq!(clash.schema_version, 1);
+
+        let mut bytes = bytes;
+        bytes[4] = TOKEN_LIBRARY_FORMAT_VERSION + 1;
+        assert!(matches!(
+            deserialize_token_library(&bytes),
+            Err(SerializationError::UnsupportedVersion { .. })
+        ));
+    }
+
     #[test]
     fn test_validate_tokens() {
         assert!(validate_duration_token("D_24"));
@@ -1,16 +1,46 @@
 //! Binary trie serialization for chord progression data
-
+//!
+//! Tries are written as a [`TRIE_BINARY_HEADER_LEN`]-byte header (magic `CTRI`,
+//! format version, flags) followed by the root node. Version 1 data has no header;
+//! it is still read, and [`migrate_trie_binary`] rewrites it in the current format.
+//! Either may be wrapped in a zstd or LZ4 frame (see [`crate::compression`]),
+//! which readers detect and unpack first.
+
+use crate::compression::{
+    compress, decompress, decompress_prefix, detect_compression, Compression,
+};
+use crate::constants::{
+    TRIE_BINARY_FORMAT_VERSION, TRIE_BINARY_HEADER_LEN, TRIE_BINARY_MAGIC,
+    TRIE_BINARY_MIN_FORMAT_VERSION, TRIE_FLAG_KEY_TONIC, TRIE_FLAG_REFERENCES,
+};
 use crate::error::{SerializationError, SerializationResult};
+use crate::trie_stream::{decode_slice, decode_slice_with_references, encode_to_vec};
+use composer_config::{MEMORY, PERFORMANCE};
+use composer_core::Key;
 use std::collections::HashMap;
+use std::time::Duration;
 
 /// Trie node structure for serialization
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TrieNode {
     pub node_count: u32,
     pub id_list: Vec<u32>,
     pub children: HashMap<Vec<u8>, TrieNode>, // key is 5-byte chord binary
 }
 
+/// Source of trie patterns, listed in the reference table that
+/// [`serialize_trie_with_references`] writes after the root node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrieReference {
+    /// Source identifier as given when the pattern was added
+    pub id: String,
+    /// Key of the source, if known
+    pub key: Option<Key>,
+}
+
+/// Trie and the reference table its ids index, if it has one
+pub type ReferencedTrie = (TrieNode, Option<Vec<TrieReference>>);
+
 /// Child node entry for binary format
 #[derive(Debug, Clone)]
 pub struct ChildNodeEntry {
@@ -22,6 +52,68 @@ pub struct ChildNodeEntry {
 /// Progress callback type for serialization
 pub type ProgressCallback = Box<dyn Fn(f64) + Send + Sync>;
 
+/// Resource caps applied while deserializing trie data.
+///
+/// Every count read from the input is checked against these limits (and against the
+/// bytes actually remaining) before anything is allocated, so adversarial inputs fail
+/// with [`SerializationError::LimitExceeded`] instead of exhausting memory or the stack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeserializationLimits {
+    /// Maximum total number of nodes
+    pub max_nodes: usize,
+    /// Maximum nesting depth below the root
+    pub max_depth: usize,
+    /// Maximum ids stored on a single node
+    pub max_ids_per_node: usize,
+    /// Maximum children of a single node
+    pub max_children_per_node: usize,
+    /// Approximate upper bound on memory allocated for the decoded trie
+    pub max_allocation_bytes: usize,
+    /// Wall-clock budget; ignored on `wasm32`, which has no monotonic clock in std
+    pub timeout: Option<Duration>,
+    /// Accept bytes after the root node instead of treating them as corruption
+    pub allow_trailing_data: bool,
+    /// Maximum size of compressed input once decompressed
+    pub max_decompressed_bytes: usize,
+}
+
+impl Default for DeserializationLimits {
+    fn default() -> Self {
+        Self {
+            max_nodes: 4_000_000,
+            max_depth: 64,
+            max_ids_per_node: 1_000_000,
+            max_children_per_node: 1 << 20,
+            max_allocation_bytes: MEMORY.trie_memory_max_mb as usize * 1024 * 1024,
+            timeout: Some(Duration::from_millis(
+                PERFORMANCE.asset_loading_max_ms as u64,
+            )),
+            allow_trailing_data: false,
+            max_decompressed_bytes: MEMORY.trie_memory_max_mb as usize * 1024 * 1024,
+        }
+    }
+}
+
+impl DeserializationLimits {
+    /// No caps, as [`deserialize_trie`] reads trusted data: any size, no deadline,
+    /// and bytes after the root node ignored
+    ///
+    /// Counts are still checked against the bytes remaining, so corrupt data
+    /// fails instead of allocating.
+    pub fn unlimited() -> Self {
+        Self {
+            max_nodes: usize::MAX,
+            max_depth: usize::MAX,
+            max_ids_per_node: usize::MAX,
+            max_children_per_node: usize::MAX,
+            max_allocation_bytes: usize::MAX,
+            timeout: None,
+            allow_trailing_data: true,
+            max_decompressed_bytes: usize::MAX,
+        }
+    }
+}
+
 impl TrieNode {
     /// Create a new empty trie node
     pub fn new() -> Self {
@@ -60,6 +152,17 @@ impl TrieNode {
         }
     }
 
+    /// Iterate over every stored pattern and the ids recorded for it
+    ///
+    /// Patterns are yielded depth-first with children visited in key order, so the
+    /// iteration order is deterministic. Only nodes holding at least one id are
+    /// yielded.
+    pub fn patterns(&self) -> TriePatterns<'_> {
+        TriePatterns {
+            stack: vec![(Vec::new(), self)],
+        }
+    }
+
     /// Calculate the rank of this node based on count
     pub fn calculate_rank(&self, total_nodes: u32) -> u32 {
         if total_nodes == 0 {
@@ -70,6 +173,35 @@ impl TrieNode {
     }
 }
 
+/// Sequence of chord keys leading from the root to a node
+type PatternPath = Vec<Vec<u8>>;
+
+/// Depth-first iterator over the patterns stored in a [`TrieNode`]
+pub struct TriePatterns<'a> {
+    stack: Vec<(PatternPath, &'a TrieNode)>,
+}
+
+impl<'a> Iterator for TriePatterns<'a> {
+    type Item = (PatternPath, &'a [u32]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((path, node)) = self.stack.pop() {
+            let mut children: Vec<_> = node.children.iter().collect();
+            children.sort_by(|a, b| b.0.cmp(a.0));
+            for (key, child) in children {
+                let mut child_path = path.clone();
+                child_path.push(key.clone());
+                self.stack.push((child_path, child));
+            }
+
+            if !node.id_list.is_empty() {
+                return Some((path, &node.id_list));
+            }
+        }
+        None
+    }
+}
+
 impl Default for TrieNode {
     fn default() -> Self {
         Self::new()
@@ -86,24 +218,45 @@ pub fn serialize_trie_with_progress(
     trie: &TrieNode,
     progress_callback: Option<&ProgressCallback>,
 ) -> SerializationResult<Vec<u8>> {
-    let mut buffer = Vec::new();
-
-    // Calculate total nodes for progress reporting
-    let total_nodes = count_total_nodes(trie);
-    let mut processed_nodes = 0;
-
-    serialize_node_recursive(
-        trie,
-        &mut buffer,
-        &mut processed_nodes,
-        total_nodes,
-        progress_callback,
-    )?;
+    encode_to_vec(trie, progress_callback)
+}
 
-    Ok(buffer)
+/// Serialize a trie and compress it
+///
+/// Readers detect the compression, so the output loads with
+/// [`deserialize_trie`] like uncompressed data.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_serialization::{
+///     deserialize_trie, serialize_trie_compressed, Compression, SerializationError, TrieNode,
+/// };
+///
+/// let mut trie = TrieNode::new();
+/// for id in 0..100 {
+///     trie.add_pattern(&[vec![0x10, 0, 0, 0, 0], vec![0x50, 0x08, 0, 0, 0]], id);
+/// }
+/// let packed = serialize_trie_compressed(&trie, Compression::Zstd)?;
+/// assert_eq!(deserialize_trie(&packed, false)?, trie);
+/// # Ok::<(), SerializationError>(())
+/// ```
+pub fn serialize_trie_compressed(
+    trie: &TrieNode,
+    compression: Compression,
+) -> SerializationResult<Vec<u8>> {
+    compress(&serialize_trie(trie)?, compression)
 }
 
 /// Deserialize a trie from binary format
+///
+/// Both the current format and headerless version 1 data are accepted,
+/// compressed or not.
+/// `include_key_tonic` gives the id width of version 1 data, which does not
+/// record it; later versions take it from their header.
+///
+/// No resource caps apply and bytes after the trie are ignored; read untrusted
+/// data with [`deserialize_trie_with_limits`] instead.
 pub fn deserialize_trie(data: &[u8], include_key_tonic: bool) -> SerializationResult<TrieNode> {
     deserialize_trie_with_progress(data, include_key_tonic, None)
 }
@@ -114,186 +267,337 @@ pub fn deserialize_trie_with_progress(
     include_key_tonic: bool,
     progress_callback: Option<&ProgressCallback>,
 ) -> SerializationResult<TrieNode> {
-    let mut offset = 0;
-    let total_bytes = data.len();
-
-    deserialize_node_recursive(
+    deserialize_trie_with_limits(
         data,
-        &mut offset,
         include_key_tonic,
-        total_bytes,
+        &DeserializationLimits::unlimited(),
         progress_callback,
     )
 }
 
-/// Validate binary format
-pub fn validate_binary_format(data: &[u8]) -> bool {
-    // Basic validation checks
-    if data.len() < 12 {
-        return false; // Minimum size for header
-    }
+/// Deserializes a trie while enforcing resource caps.
+///
+/// Use this for untrusted input such as user-uploaded assets. Node counts, depth,
+/// per-node id and child counts, total allocation, and elapsed time are all bounded
+/// by `limits`, as is the size of compressed input once decompressed; child
+/// length prefixes must match the bytes actually consumed, and
+/// unless [`DeserializationLimits::allow_trailing_data`] is set the input must contain
+/// exactly one trie with nothing after it.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_serialization::{
+///     deserialize_trie_with_limits, serialize_trie, DeserializationLimits, SerializationError,
+///     TrieNode,
+/// };
+///
+/// let mut trie = TrieNode::new();
+/// trie.add_pattern(&[vec![0x10, 0, 0, 0, 0], vec![0x50, 0x08, 0, 0, 0]], 7);
+/// let bytes = serialize_trie(&trie)?;
+///
+/// let limits = DeserializationLimits { max_depth: 1, ..Default::default() };
+/// let result = deserialize_trie_with_limits(&bytes, false, &limits, None);
+/// assert!(matches!(result, Err(SerializationError::LimitExceeded { .. })));
+/// # Ok::<(), SerializationError>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`deserialize_trie`] - Deserialize trusted data without limits
+/// - [`validate_binary_format`] - Quick structural check
+pub fn deserialize_trie_with_limits(
+    data: &[u8],
+    include_key_tonic: bool,
+    limits: &DeserializationLimits,
+    progress_callback: Option<&ProgressCallback>,
+) -> SerializationResult<TrieNode> {
+    decode_slice(data, include_key_tonic, limits, progress_callback)
+}
 
-    // Check for basic structure integrity
-    let mut offset = 0;
-    if read_u32_be(data, &mut offset).is_err() {
-        return false;
+/// Serializes a trie whose ids index `references`, followed by the reference table.
+///
+/// Unlike [`serialize_trie`], which can only store numeric ids, every source id
+/// and key survives. The header flags the table, so [`deserialize_trie`] and the
+/// streaming readers reject the output instead of returning table indices as ids.
+/// Fails with [`SerializationError::InvalidTrieStructure`] if an id is not an
+/// index into `references`.
+///
+/// Each table entry is the id as a `u32` byte length and UTF-8, then the key
+/// name (as in `"F#m"`) as a `u8` byte length and UTF-8, empty without a key.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_serialization::{
+///     deserialize_trie_with_references, serialize_trie_with_references, DeserializationLimits,
+///     TrieNode, TrieReference,
+/// };
+///
+/// let mut trie = TrieNode::new();
+/// trie.add_pattern(&[vec![0x10, 0, 0, 0, 0], vec![0x50, 0x08, 0, 0, 0]], 0);
+/// let references = vec![TrieReference { id: "song-a".to_string(), key: Some("F#m".parse()?) }];
+/// let bytes = serialize_trie_with_references(&trie, &references)?;
+///
+/// let limits = DeserializationLimits::default();
+/// let (restored, table) = deserialize_trie_with_references(&bytes, false, &limits)?;
+/// assert_eq!(restored, trie);
+/// assert_eq!(table, Some(references));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn serialize_trie_with_references(
+    trie: &TrieNode,
+    references: &[TrieReference],
+) -> SerializationResult<Vec<u8>> {
+    check_reference_ids(trie, references.len())?;
+
+    let mut buffer = serialize_trie(trie)?;
+    buffer[5] |= TRIE_FLAG_REFERENCES;
+    let count = u32::try_from(references.len()).map_err(|_| SerializationError::BufferOverflow)?;
+    buffer.extend_from_slice(&count.to_be_bytes());
+    for reference in references {
+        let id_len =
+            u32::try_from(reference.id.len()).map_err(|_| SerializationError::BufferOverflow)?;
+        buffer.extend_from_slice(&id_len.to_be_bytes());
+        buffer.extend_from_slice(reference.id.as_bytes());
+
+        let key = reference.key.map(|key| key.to_string()).unwrap_or_default();
+        let key_len = u8::try_from(key.len()).map_err(|_| SerializationError::BufferOverflow)?;
+        buffer.push(key_len);
+        buffer.extend_from_slice(key.as_bytes());
     }
-
-    // Additional checks could be added here
-    true
+    Ok(buffer)
 }
 
-// Internal serialization functions
-
-fn serialize_node_recursive(
-    node: &TrieNode,
-    buffer: &mut Vec<u8>,
-    processed_nodes: &mut u32,
-    total_nodes: u32,
-    progress_callback: Option<&ProgressCallback>,
-) -> SerializationResult<()> {
-    // Serialize node count (4 bytes, big-endian)
-    write_u32_be(buffer, node.node_count);
+/// Deserializes a trie and the reference table its ids index, if it has one.
+///
+/// Reads the output of [`serialize_trie_with_references`] under `limits`, and
+/// returns `None` for the table of data without one, such as the output of
+/// [`serialize_trie`], whose ids are the source ids themselves. Compressed data
+/// is accepted, as with [`deserialize_trie_with_limits`].
+pub fn deserialize_trie_with_references(
+    data: &[u8],
+    include_key_tonic: bool,
+    limits: &DeserializationLimits,
+) -> SerializationResult<ReferencedTrie> {
+    let data = decompress(data, limits.max_decompressed_bytes)?;
+    let (trie, has_references, mut rest) =
+        decode_slice_with_references(&data, include_key_tonic, limits)?;
+    if !has_references {
+        if !rest.is_empty() && !limits.allow_trailing_data {
+            return Err(SerializationError::CorruptedBinary {
+                details: format!("{} trailing bytes after trie data", rest.len()),
+            });
+        }
+        return Ok((trie, None));
+    }
 
-    // Serialize ID list
-    write_u32_be(buffer, node.id_list.len() as u32);
-    for &id in &node.id_list {
-        write_u32_be(buffer, id);
+    // Every entry holds at least an id length and a key length
+    let count = take_u32(&mut rest)? as usize;
+    if count.saturating_mul(5) > rest.len() {
+        return Err(SerializationError::UnexpectedEof);
+    }
+    let mut references = Vec::with_capacity(count);
+    for _ in 0..count {
+        let id_len = take_u32(&mut rest)? as usize;
+        let id = take_str(&mut rest, id_len)?.to_string();
+        let key_len = take_bytes(&mut rest, 1)?[0] as usize;
+        let key = match take_str(&mut rest, key_len)? {
+            "" => None,
+            name => Some(
+                name.parse()
+                    .map_err(|_| SerializationError::InvalidBinaryFormat {
+                        reason: format!("invalid reference key {:?}", name),
+                    })?,
+            ),
+        };
+        references.push(TrieReference { id, key });
+    }
+    if !rest.is_empty() && !limits.allow_trailing_data {
+        return Err(SerializationError::CorruptedBinary {
+            details: format!("{} trailing bytes after reference table", rest.len()),
+        });
     }
 
-    // Serialize children
-    write_u32_be(buffer, node.children.len() as u32);
+    check_reference_ids(&trie, references.len())?;
+    Ok((trie, Some(references)))
+}
 
-    for (key, child) in &node.children {
-        // Validate key length
-        if key.len() != 5 {
-            return Err(SerializationError::InvalidFormat {
-                message: "Chord key must be exactly 5 bytes".to_string(),
+/// Check every id in `trie` indexes a reference table of `count` entries
+fn check_reference_ids(trie: &TrieNode, count: usize) -> SerializationResult<()> {
+    let mut stack = vec![trie];
+    while let Some(node) = stack.pop() {
+        if let Some(id) = node.id_list.iter().find(|&&id| id as usize >= count) {
+            return Err(SerializationError::InvalidTrieStructure {
+                reason: format!("id {} is not in the reference table of {}", id, count),
             });
         }
-
-        // Serialize child data to temporary buffer
-        let mut child_buffer = Vec::new();
-        serialize_node_recursive(
-            child,
-            &mut child_buffer,
-            processed_nodes,
-            total_nodes,
-            progress_callback,
-        )?;
-
-        // Write data length, key, and child data
-        write_u32_be(buffer, child_buffer.len() as u32);
-        buffer.extend_from_slice(key);
-        buffer.extend_from_slice(&child_buffer);
+        stack.extend(node.children.values());
     }
-
-    // Update progress
-    *processed_nodes += 1;
-    if let Some(callback) = progress_callback {
-        let progress = *processed_nodes as f64 / total_nodes as f64;
-        callback(progress);
-    }
-
     Ok(())
 }
 
-fn deserialize_node_recursive(
-    data: &[u8],
-    offset: &mut usize,
-    include_key_tonic: bool,
-    total_bytes: usize,
-    progress_callback: Option<&ProgressCallback>,
-) -> SerializationResult<TrieNode> {
-    // Read node count
-    let node_count = read_u32_be(data, offset)?;
-
-    // Read ID list
-    let id_list_length = read_u32_be(data, offset)?;
-    let mut id_list = Vec::with_capacity(id_list_length as usize);
-
-    for _ in 0..id_list_length {
-        let id = if include_key_tonic {
-            // Read 6 bytes for key tonic support
-            let mut id_bytes = [0u8; 6];
-            read_bytes(data, offset, &mut id_bytes)?;
-            u32::from_be_bytes([id_bytes[0], id_bytes[1], id_bytes[2], id_bytes[3]])
-        } else {
-            read_u32_be(data, offset)?
-        };
-        id_list.push(id);
+fn take_bytes<'d>(data: &mut &'d [u8], len: usize) -> SerializationResult<&'d [u8]> {
+    if data.len() < len {
+        return Err(SerializationError::UnexpectedEof);
     }
+    let (bytes, rest) = data.split_at(len);
+    *data = rest;
+    Ok(bytes)
+}
 
-    // Read children
-    let children_count = read_u32_be(data, offset)?;
-    let mut children = HashMap::with_capacity(children_count as usize);
-
-    for _ in 0..children_count {
-        // Read child data length and key
-        let _data_length = read_u32_be(data, offset)?;
+fn take_u32(data: &mut &[u8]) -> SerializationResult<u32> {
+    let bytes = take_bytes(data, 4)?;
+    Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
 
-        let mut key = vec![0u8; 5];
-        read_bytes(data, offset, &mut key)?;
+fn take_str<'d>(data: &mut &'d [u8], len: usize) -> SerializationResult<&'d str> {
+    std::str::from_utf8(take_bytes(data, len)?).map_err(|_| {
+        SerializationError::InvalidBinaryFormat {
+            reason: "reference is not UTF-8".to_string(),
+        }
+    })
+}
 
-        // Recursively deserialize child
-        let child = deserialize_node_recursive(
-            data,
-            offset,
-            include_key_tonic,
-            total_bytes,
-            progress_callback,
-        )?;
-        children.insert(key, child);
+/// Validate binary format
+pub fn validate_binary_format(data: &[u8]) -> bool {
+    // A supported header, if any, followed by at least a root node header
+    let Ok(prefix) = decompress_prefix(data, TRIE_BINARY_HEADER_LEN + 12) else {
+        return false;
+    };
+    match trie_body(&prefix, false) {
+        Ok((body, _)) => body.len() >= 12,
+        Err(_) => false,
     }
+}
 
-    // Update progress
-    if let Some(callback) = progress_callback {
-        let progress = *offset as f64 / total_bytes as f64;
-        callback(progress);
+/// Detects the format version of serialized trie data.
+///
+/// Data starting with the `CTRI` magic reports the version in its header; any
+/// other data is headerless version 1. Only the header is read, and only the
+/// header is decompressed from compressed data, so the trie itself may still
+/// fail to decode.
+///
+/// Fails with [`SerializationError::UnsupportedVersion`] for header versions
+/// this build cannot read, such as data written by a newer release.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_serialization::{
+///     detect_version, migrate_trie_binary, serialize_trie, SerializationError, TrieNode,
+///     TRIE_BINARY_FORMAT_VERSION, TRIE_BINARY_HEADER_LEN,
+/// };
+///
+/// let bytes = serialize_trie(&TrieNode::new())?;
+/// assert_eq!(detect_version(&bytes)?, TRIE_BINARY_FORMAT_VERSION);
+///
+/// // Version 1 data is the bare root node
+/// let legacy = &bytes[TRIE_BINARY_HEADER_LEN..];
+/// assert_eq!(detect_version(legacy)?, 1);
+/// assert_eq!(migrate_trie_binary(legacy, false)?, bytes);
+///
+/// let mut future = bytes.clone();
+/// future[4] = TRIE_BINARY_FORMAT_VERSION + 1;
+/// assert!(matches!(
+///     detect_version(&future),
+///     Err(SerializationError::UnsupportedVersion { .. })
+/// ));
+/// # Ok::<(), SerializationError>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`migrate_trie_binary`] - Rewrite older data in the current format
+pub fn detect_version(data: &[u8]) -> SerializationResult<u8> {
+    let data = decompress_prefix(data, TRIE_BINARY_HEADER_LEN)?;
+    if data.len() < TRIE_BINARY_MAGIC.len() || data[..4] != TRIE_BINARY_MAGIC {
+        return Ok(1);
     }
-
-    Ok(TrieNode {
-        node_count,
-        id_list,
-        children,
-    })
+    if data.len() < TRIE_BINARY_HEADER_LEN {
+        return Err(SerializationError::UnexpectedEof);
+    }
+    check_trie_format_version(data[4])?;
+    Ok(data[4])
 }
 
-// Utility functions
+/// Rewrites serialized trie data in the current format version.
+///
+/// Applies each format change since the version [`detect_version`] reports in
+/// turn; data already in the current format is returned unchanged. Version 2
+/// prefixes version 1 data with a header recording `include_key_tonic`, which
+/// is ignored for every later version. Nodes are copied rather than decoded, so
+/// migrating does not validate the trie. Compressed data is recompressed with
+/// the same codec.
+pub fn migrate_trie_binary(data: &[u8], include_key_tonic: bool) -> SerializationResult<Vec<u8>> {
+    let from_version = detect_version(data)?;
+    let compression = detect_compression(data);
+    if from_version == TRIE_BINARY_FORMAT_VERSION {
+        return Ok(data.to_vec());
+    }
 
-fn count_total_nodes(node: &TrieNode) -> u32 {
-    let mut count = 1; // Count this node
-    for child in node.children.values() {
-        count += count_total_nodes(child);
+    let limits = DeserializationLimits::default();
+    let mut data = decompress(data, limits.max_decompressed_bytes)?.into_owned();
+    for version in from_version..TRIE_BINARY_FORMAT_VERSION {
+        data = match version {
+            1 => migrate_trie_v1(&data, include_key_tonic),
+            _ => data,
+        };
     }
-    count
+    compress(&data, compression)
 }
 
-fn write_u32_be(buffer: &mut Vec<u8>, value: u32) {
-    buffer.extend_from_slice(&value.to_be_bytes());
+/// Version 1 to 2: add the header
+fn migrate_trie_v1(data: &[u8], include_key_tonic: bool) -> Vec<u8> {
+    let mut migrated = Vec::with_capacity(TRIE_BINARY_HEADER_LEN + data.len());
+    migrated.extend_from_slice(&trie_header(include_key_tonic));
+    migrated.extend_from_slice(data);
+    migrated
 }
 
-fn read_u32_be(data: &[u8], offset: &mut usize) -> SerializationResult<u32> {
-    if *offset + 4 > data.len() {
-        return Err(SerializationError::UnexpectedEof);
+pub(crate) fn check_trie_format_version(version: u8) -> SerializationResult<()> {
+    // Version 1 predates the header, so no header may claim it
+    if version > TRIE_BINARY_MIN_FORMAT_VERSION && version <= TRIE_BINARY_FORMAT_VERSION {
+        Ok(())
+    } else {
+        Err(SerializationError::UnsupportedVersion {
+            version: format!(
+                "trie binary format {} (this build reads {} to {})",
+                version, TRIE_BINARY_MIN_FORMAT_VERSION, TRIE_BINARY_FORMAT_VERSION
+            ),
+        })
     }
+}
 
-    let mut bytes = [0u8; 4];
-    bytes.copy_from_slice(&data[*offset..*offset + 4]);
-    *offset += 4;
-
-    Ok(u32::from_be_bytes(bytes))
+pub(crate) fn trie_header(include_key_tonic: bool) -> [u8; TRIE_BINARY_HEADER_LEN] {
+    let flags = if include_key_tonic {
+        TRIE_FLAG_KEY_TONIC
+    } else {
+        0
+    };
+    let [a, b, c, d] = TRIE_BINARY_MAGIC;
+    [a, b, c, d, TRIE_BINARY_FORMAT_VERSION, flags]
 }
 
-fn read_bytes(data: &[u8], offset: &mut usize, buffer: &mut [u8]) -> SerializationResult<()> {
-    if *offset + buffer.len() > data.len() {
-        return Err(SerializationError::UnexpectedEof);
-    }
+/// Root node bytes, and whether their ids carry key tonics
+type TrieBody<'a> = (&'a [u8], bool);
 
-    buffer.copy_from_slice(&data[*offset..*offset + buffer.len()]);
-    *offset += buffer.len();
+/// Split off the header, returning the root node bytes and their id width
+fn trie_body(data: &[u8], include_key_tonic: bool) -> SerializationResult<TrieBody<'_>> {
+    if detect_version(data)? == 1 {
+        return Ok((data, include_key_tonic));
+    }
 
-    Ok(())
+    let flags = data[5];
+    if flags & !(TRIE_FLAG_KEY_TONIC | TRIE_FLAG_REFERENCES) != 0 {
+        return Err(SerializationError::InvalidBinaryFormat {
+            reason: format!("unknown trie header flags {:#04x}", flags),
+        });
+    }
+    Ok((
+        &data[TRIE_BINARY_HEADER_LEN..],
+        flags & TRIE_FLAG_KEY_TONIC != 0,
+    ))
 }
 
 /// Token vocabulary management
@@ -309,8 +613,8 @@ pub fn reduce_chord_vocab(
     }
 
     // Sort by frequency (descending)
-    let mut sorted_chords: Vec<(Vec<u8>, u32)> = chord_counts.into_iter().collect();
-    sorted_chords.sort_by(|a, b| b.1.cmp(&a.1));
+    let mut sorted_chords: Vec<_> = chord_counts.into_iter().collect();
+    sorted_chords.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
 
     // Take top maxVocab chords
     let top_chords: Vec<Vec<u8>> = sorted_chords
@@ -371,6 +675,7 @@ pub fn augment_with_repeated<T: Clone>(sequence: Vec<T>, min_tokens: usize) -> V
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::trie_stream::{deserialize_trie_from_reader, NODE_ALLOCATION_BYTES};
 
     #[test]
     fn test_trie_node_creation() {
@@ -391,6 +696,20 @@ mod tests {
         assert!(root.children.contains_key(&vec![1, 2, 3, 4, 5]));
     }
 
+    #[test]
+    fn test_patterns_iterates_in_key_order() {
+        let mut root = sample_trie();
+        root.add_pattern(&[vec![0x10, 0, 0, 0, 0]], 3);
+
+        let patterns: Vec<_> = root.patterns().collect();
+        assert_eq!(patterns.len(), 3);
+        assert_eq!(patterns[0], (vec![vec![0x10, 0, 0, 0, 0]], &[3u32][..]));
+        assert_eq!(patterns[1].0[1], vec![0x40, 0, 0, 0, 0]);
+        assert_eq!(patterns[1].1, &[2]);
+        assert_eq!(patterns[2].1, &[1]);
+        assert_eq!(TrieNode::new().patterns().count(), 0);
+    }
+
     #[test]
     fn test_serialize_deserialize_roundtrip() {
         let mut root = TrieNode::new();
@@ -404,6 +723,237 @@ mod tests {
         assert_eq!(root.id_list, deserialized.id_list);
     }
 
+    fn sample_trie() -> TrieNode {
+        let mut root = TrieNode::new();
+        root.add_pattern(&[vec![0x10, 0, 0, 0, 0], vec![0x50, 0x08, 0, 0, 0]], 1);
+        root.add_pattern(&[vec![0x10, 0, 0, 0, 0], vec![0x40, 0, 0, 0, 0]], 2);
+        root
+    }
+
+    #[test]
+    fn test_reference_tables() {
+        let references = vec![
+            TrieReference {
+                id: "unused".to_string(),
+                key: None,
+            },
+            TrieReference {
+                id: "pop_1".to_string(),
+                key: Some("Bb dorian".parse().unwrap()),
+            },
+            TrieReference {
+                id: "42".to_string(),
+                key: None,
+            },
+        ];
+        let limits = DeserializationLimits::default();
+        let bytes = serialize_trie_with_references(&sample_trie(), &references).unwrap();
+        let (trie, table) = deserialize_trie_with_references(&bytes, false, &limits).unwrap();
+        assert_eq!(trie, sample_trie());
+        assert_eq!(table.as_deref(), Some(references.as_slice()));
+        assert!(validate_binary_format(&bytes));
+
+        let packed = compress(&bytes, Compression::Zstd).unwrap();
+        let (_, table) = deserialize_trie_with_references(&packed, false, &limits).unwrap();
+        assert_eq!(table, Some(references.clone()));
+
+        // Plain readers would return table indices as ids
+        assert!(matches!(
+            deserialize_trie(&bytes, false),
+            Err(SerializationError::InvalidBinaryFormat { .. })
+        ));
+        assert!(deserialize_trie_from_reader(bytes.as_slice(), false, &limits).is_err());
+
+        // Ids must index the table, which must be complete
+        assert!(matches!(
+            serialize_trie_with_references(&sample_trie(), &references[..2]),
+            Err(SerializationError::InvalidTrieStructure { .. })
+        ));
+        assert!(
+            deserialize_trie_with_references(&bytes[..bytes.len() - 1], false, &limits).is_err()
+        );
+        let mut padded = bytes.clone();
+        padded.push(0);
+        assert!(deserialize_trie_with_references(&padded, false, &limits).is_err());
+
+        // Data without a table keeps its ids
+        let plain = serialize_trie(&sample_trie()).unwrap();
+        let (trie, table) = deserialize_trie_with_references(&plain, false, &limits).unwrap();
+        assert_eq!((trie, table), (sample_trie(), None));
+    }
+
+    #[test]
+    fn test_limits_reject_oversized_tries() {
+        let bytes = serialize_trie(&sample_trie()).unwrap();
+        assert!(deserialize_trie(&bytes, false).is_ok());
+
+        let too_deep = DeserializationLimits {
+            max_depth: 1,
+            ..Default::default()
+        };
+        let too_many_nodes = DeserializationLimits {
+            max_nodes: 3,
+            ..Default::default()
+        };
+        let tiny_budget = DeserializationLimits {
+            max_allocation_bytes: NODE_ALLOCATION_BYTES * 2,
+            ..Default::default()
+        };
+
+        for limits in [too_deep, too_many_nodes, tiny_budget] {
+            assert!(matches!(
+                deserialize_trie_with_limits(&bytes, false, &limits, None),
+                Err(SerializationError::LimitExceeded { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_hostile_counts_fail_without_allocating() {
+        // Root claiming u32::MAX ids and children in a 12-byte buffer
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&0u32.to_be_bytes());
+        bytes.extend_from_slice(&u32::MAX.to_be_bytes());
+        bytes.extend_from_slice(&0u32.to_be_bytes());
+        assert!(deserialize_trie(&bytes, false).is_err());
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&0u32.to_be_bytes());
+        bytes.extend_from_slice(&0u32.to_be_bytes());
+        bytes.extend_from_slice(&u32::MAX.to_be_bytes());
+        assert!(deserialize_trie(&bytes, false).is_err());
+    }
+
+    #[test]
+    fn test_deeply_nested_input_is_rejected() {
+        // A chain of 10,000 single-child nodes would overflow the stack if unbounded
+        let mut bytes = Vec::new();
+        for _ in 0..10_000 {
+            bytes.extend_from_slice(&0u32.to_be_bytes());
+            bytes.extend_from_slice(&0u32.to_be_bytes());
+            bytes.extend_from_slice(&1u32.to_be_bytes());
+            bytes.extend_from_slice(&0u32.to_be_bytes());
+            bytes.extend_from_slice(&[0x10, 0, 0, 0, 0]);
+        }
+        let limits = DeserializationLimits::default();
+        assert!(matches!(
+            deserialize_trie_with_limits(&bytes, false, &limits, None),
+            Err(SerializationError::LimitExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn test_corrupted_lengths_and_trailing_data() {
+        let mut bytes = serialize_trie(&sample_trie()).unwrap();
+
+        // Trusted data may carry padding; untrusted data may not
+        let mut padded = bytes.clone();
+        padded.push(0);
+        assert_eq!(deserialize_trie(&padded, false).unwrap(), sample_trie());
+        let limits = DeserializationLimits::default();
+        assert!(matches!(
+            deserialize_trie_with_limits(&padded, false, &limits, None),
+            Err(SerializationError::CorruptedBinary { .. })
+        ));
+
+        // First child's length prefix sits right after the root node header
+        let child = TRIE_BINARY_HEADER_LEN + 12;
+        bytes[child..child + 4].copy_from_slice(&1u32.to_be_bytes());
+        assert!(deserialize_trie(&bytes, false).is_err());
+    }
+
+    #[test]
+    fn test_fuzzed_inputs_never_panic() {
+        let bytes = serialize_trie(&sample_trie()).unwrap();
+
+        // Every truncation and single-byte corruption must return cleanly
+        for len in 0..bytes.len() {
+            let _ = deserialize_trie(&bytes[..len], false);
+        }
+        for i in 0..bytes.len() {
+            for value in [0x00, 0x01, 0x7F, 0xFF] {
+                let mut corrupted = bytes.clone();
+                corrupted[i] = value;
+                let _ = deserialize_trie(&corrupted, false);
+                let _ = deserialize_trie(&corrupted, true);
+            }
+        }
+    }
+
+    #[test]
+    fn test_version_1_data_migrates() {
+        let bytes = serialize_trie(&sample_trie()).unwrap();
+        let legacy = &bytes[TRIE_BINARY_HEADER_LEN..];
+        assert_eq!(detect_version(legacy).unwrap(), 1);
+        assert_eq!(deserialize_trie(legacy, false).unwrap(), sample_trie());
+
+        let migrated = migrate_trie_binary(legacy, false).unwrap();
+        assert_eq!(migrated, bytes);
+        assert_eq!(migrate_trie_binary(&migrated, true).unwrap(), bytes);
+
+        // Key tonic ids keep their width once the header records it
+        let mut tonic_legacy = Vec::new();
+        tonic_legacy.extend_from_slice(&0u32.to_be_bytes());
+        tonic_legacy.extend_from_slice(&1u32.to_be_bytes());
+        tonic_legacy.extend_from_slice(&[0, 0, 0, 9, 0, 4]);
+        tonic_legacy.extend_from_slice(&0u32.to_be_bytes());
+        let migrated = migrate_trie_binary(&tonic_legacy, true).unwrap();
+        assert_eq!(migrated[5], TRIE_FLAG_KEY_TONIC);
+        assert_eq!(deserialize_trie(&migrated, false).unwrap().id_list, vec![9]);
+    }
+
+    #[test]
+    fn test_compressed_tries() {
+        let bytes = serialize_trie(&sample_trie()).unwrap();
+        for compression in [Compression::Zstd, Compression::Lz4] {
+            let packed = serialize_trie_compressed(&sample_trie(), compression).unwrap();
+            assert_eq!(deserialize_trie(&packed, false).unwrap(), sample_trie());
+            assert_eq!(detect_version(&packed).unwrap(), TRIE_BINARY_FORMAT_VERSION);
+            assert!(validate_binary_format(&packed));
+
+            let legacy = compress(&bytes[TRIE_BINARY_HEADER_LEN..], compression).unwrap();
+            assert_eq!(detect_version(&legacy).unwrap(), 1);
+            let migrated = migrate_trie_binary(&legacy, false).unwrap();
+            assert_eq!(detect_compression(&migrated), compression);
+            assert_eq!(decompress(&migrated, usize::MAX).unwrap(), bytes);
+
+            let tiny = DeserializationLimits {
+                max_decompressed_bytes: bytes.len() - 1,
+                ..Default::default()
+            };
+            assert!(matches!(
+                deserialize_trie_with_limits(&packed, false, &tiny, None),
+                Err(SerializationError::LimitExceeded { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_unsupported_versions_are_rejected() {
+        let bytes = serialize_trie(&TrieNode::new()).unwrap();
+        for version in [0, 1, TRIE_BINARY_FORMAT_VERSION + 1] {
+            let mut versioned = bytes.clone();
+            versioned[4] = version;
+            assert!(matches!(
+                deserialize_trie(&versioned, false),
+                Err(SerializationError::UnsupportedVersion { .. })
+            ));
+            assert!(migrate_trie_binary(&versioned, false).is_err());
+            assert!(!validate_binary_format(&versioned));
+        }
+
+        let mut flagged = bytes.clone();
+        flagged[5] = 0x80;
+        assert!(matches!(
+            deserialize_trie(&flagged, false),
+            Err(SerializationError::InvalidBinaryFormat { .. })
+        ));
+        assert!(matches!(
+            detect_version(&bytes[..5]),
+            Err(SerializationError::UnexpectedEof)
+        ));
+    }
+
     #[test]
     fn test_validate_binary_format() {
         let mut root = TrieNode::new();
@@ -414,6 +964,12 @@ mod tests {
 
         let invalid_data = vec![1, 2, 3]; // Too short
         assert!(!validate_binary_format(&invalid_data));
+        assert!(validate_binary_format(
+            &valid_data[TRIE_BINARY_HEADER_LEN..]
+        ));
+        assert!(!validate_binary_format(
+            &valid_data[..TRIE_BINARY_HEADER_LEN + 11]
+        ));
     }
 
     #[test]
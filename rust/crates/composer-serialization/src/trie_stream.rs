@@ -0,0 +1,954 @@
+//! Streaming trie serialization
+//!
+//! [`serialize_trie_to_writer`] and [`deserialize_trie_from_reader`] move a trie
+//! through any `Write` or `Read` a chunk at a time, so a large trie can go to disk
+//! or over HTTP without a buffer holding all of its bytes. For async code,
+//! [`serialize_trie_chunks`] yields the encoding as owned chunks on demand and
+//! [`TrieDecoder`] is fed chunks as they arrive; neither blocks.
+//!
+//! The bytes are exactly those of [`serialize_trie`](crate::serialize_trie), and
+//! every reader applies the same [`DeserializationLimits`].
+
+use crate::compression::{
+    decompress, detect_compression, stream_decoder, CappedReader, Compression,
+};
+use crate::constants::{
+    TRIE_BINARY_HEADER_LEN, TRIE_BINARY_MAGIC, TRIE_FLAG_KEY_TONIC, TRIE_FLAG_REFERENCES,
+};
+use crate::error::{SerializationError, SerializationResult};
+use crate::trie_binary::{
+    check_trie_format_version, trie_header, DeserializationLimits, ProgressCallback, TrieNode,
+};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+/// Bytes moved per read or write by the streaming functions
+pub const TRIE_STREAM_CHUNK_LEN: usize = 64 * 1024;
+
+/// Estimated heap cost of one decoded node, excluding its ids
+pub(crate) const NODE_ALLOCATION_BYTES: usize =
+    std::mem::size_of::<TrieNode>() + std::mem::size_of::<Vec<u8>>() + 5 + 16;
+
+/// Nodes decoded between timeout checks
+const TIMEOUT_CHECK_INTERVAL: usize = 1024;
+
+/// Most ids or children reserved up front when the input length is unknown;
+/// larger counts grow as their entries arrive
+const STREAM_PREALLOCATION: usize = 1024;
+
+/// Serializes a trie to a writer, a chunk at a time.
+///
+/// Only the encoded size of each subtree is held in memory (four bytes per node);
+/// the output goes to `writer` in chunks of about [`TRIE_STREAM_CHUNK_LEN`] bytes.
+/// Returns the number of bytes written.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_serialization::{
+///     deserialize_trie_from_reader, serialize_trie, serialize_trie_to_writer,
+///     DeserializationLimits, SerializationError, TrieNode,
+/// };
+///
+/// let mut trie = TrieNode::new();
+/// trie.add_pattern(&[vec![0x10, 0, 0, 0, 0], vec![0x50, 0x08, 0, 0, 0]], 7);
+///
+/// let mut file = Vec::new();
+/// let written = serialize_trie_to_writer(&trie, &mut file)?;
+/// assert_eq!(written, file.len() as u64);
+/// assert_eq!(file, serialize_trie(&trie)?);
+///
+/// let restored = deserialize_trie_from_reader(file.as_slice(), false, &DeserializationLimits::default())?;
+/// assert_eq!(restored, trie);
+/// # Ok::<(), SerializationError>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`deserialize_trie_from_reader`] - Read the trie back
+/// - [`serialize_trie_chunks`] - Pull the encoding chunk by chunk instead
+pub fn serialize_trie_to_writer<W: Write>(
+    trie: &TrieNode,
+    mut writer: W,
+) -> SerializationResult<u64> {
+    let mut encoder = TrieEncoder::new(trie, None)?;
+    let mut buffer = Vec::with_capacity(TRIE_STREAM_CHUNK_LEN + 64);
+    loop {
+        buffer.clear();
+        let done = encoder.fill(&mut buffer, TRIE_STREAM_CHUNK_LEN);
+        writer.write_all(&buffer)?;
+        if done {
+            break;
+        }
+    }
+    writer.flush()?;
+    Ok(encoder.encoded_len())
+}
+
+/// Serializes a trie as an iterator of chunks of about `chunk_len` bytes.
+///
+/// Each chunk is encoded when it is requested, which suits streaming response
+/// bodies. The trie is checked before the first chunk, so iteration itself
+/// cannot fail; [`TrieChunks::encoded_len`] gives the total size up front.
+pub fn serialize_trie_chunks(
+    trie: &TrieNode,
+    chunk_len: usize,
+) -> SerializationResult<TrieChunks<'_>> {
+    let encoder = TrieEncoder::new(trie, None)?;
+    Ok(TrieChunks {
+        encoded_len: encoder.encoded_len(),
+        encoder: Some(encoder),
+        chunk_len: chunk_len.max(1),
+    })
+}
+
+/// Chunks of a serialized trie, from [`serialize_trie_chunks`]
+pub struct TrieChunks<'a> {
+    encoder: Option<TrieEncoder<'a>>,
+    chunk_len: usize,
+    encoded_len: u64,
+}
+
+impl TrieChunks<'_> {
+    /// Total size of the chunks in bytes
+    pub fn encoded_len(&self) -> u64 {
+        self.encoded_len
+    }
+}
+
+impl Iterator for TrieChunks<'_> {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let encoder = self.encoder.as_mut()?;
+        let mut chunk = Vec::with_capacity(self.chunk_len + 32);
+        if encoder.fill(&mut chunk, self.chunk_len) {
+            self.encoder = None;
+        }
+        (!chunk.is_empty()).then_some(chunk)
+    }
+}
+
+/// Deserializes a trie from a reader, a chunk at a time.
+///
+/// Accepts everything [`deserialize_trie`](crate::deserialize_trie) does,
+/// including compressed data, which is decompressed as it is read. The reader
+/// is read to its end; unless [`DeserializationLimits::allow_trailing_data`] is
+/// set, nothing may follow the trie.
+pub fn deserialize_trie_from_reader<R: Read>(
+    mut reader: R,
+    include_key_tonic: bool,
+    limits: &DeserializationLimits,
+) -> SerializationResult<TrieNode> {
+    decode_reader(&mut reader, include_key_tonic, limits, true)
+}
+
+/// Reader decoding without generics, so compressed streams can recurse once
+fn decode_reader(
+    reader: &mut dyn Read,
+    include_key_tonic: bool,
+    limits: &DeserializationLimits,
+    accept_compressed: bool,
+) -> SerializationResult<TrieNode> {
+    let mut decoder = TrieDecoder::new(include_key_tonic, limits.clone());
+    decoder.accept_compressed = accept_compressed;
+    let mut buffer = vec![0u8; TRIE_STREAM_CHUNK_LEN];
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        decoder.feed(&buffer[..read])?;
+
+        // Decompress the rest of the stream as it is read rather than buffering it
+        if let DecodeStep::Compressed(compression) = decoder.state {
+            let source =
+                std::io::Cursor::new(std::mem::take(&mut decoder.compressed)).chain(reader);
+            let mut inner = CappedReader::new(
+                stream_decoder(source, compression, limits.max_decompressed_bytes)?,
+                limits.max_decompressed_bytes,
+            );
+            return decode_reader(&mut inner, include_key_tonic, limits, false);
+        }
+    }
+    decoder.finish()
+}
+
+/// Deserialize a complete trie held in memory
+pub(crate) fn decode_slice(
+    data: &[u8],
+    include_key_tonic: bool,
+    limits: &DeserializationLimits,
+    progress_callback: Option<&ProgressCallback>,
+) -> SerializationResult<TrieNode> {
+    let data = decompress(data, limits.max_decompressed_bytes)?;
+    let mut decoder = TrieDecoder::new(include_key_tonic, limits.clone());
+    decoder.accept_compressed = false;
+    decoder.total_len = Some(data.len());
+    decoder.progress_callback = progress_callback;
+    decoder.feed(&data)?;
+    decoder.finish()
+}
+
+/// Trie, whether its header flags a reference table, and the bytes after its root
+type TrieWithTrailer<'d> = (TrieNode, bool, &'d [u8]);
+
+/// Decode uncompressed trie data that may carry a reference table, returning the
+/// trie, whether the header flags a table, and the bytes after the root node
+pub(crate) fn decode_slice_with_references<'d>(
+    data: &'d [u8],
+    include_key_tonic: bool,
+    limits: &DeserializationLimits,
+) -> SerializationResult<TrieWithTrailer<'d>> {
+    let mut decoder = TrieDecoder::new(
+        include_key_tonic,
+        DeserializationLimits {
+            allow_trailing_data: true,
+            ..limits.clone()
+        },
+    );
+    decoder.accept_compressed = false;
+    decoder.accept_references = true;
+    decoder.total_len = Some(data.len());
+    decoder.feed(data)?;
+    let (end, has_references) = (decoder.offset, decoder.has_references);
+    Ok((decoder.finish()?, has_references, &data[end..]))
+}
+
+/// Serialize a complete trie into memory
+pub(crate) fn encode_to_vec(
+    trie: &TrieNode,
+    progress_callback: Option<&ProgressCallback>,
+) -> SerializationResult<Vec<u8>> {
+    let mut encoder = TrieEncoder::new(trie, progress_callback)?;
+    let mut buffer = Vec::with_capacity(usize::try_from(encoder.encoded_len()).unwrap_or(0));
+    encoder.fill(&mut buffer, usize::MAX);
+    Ok(buffer)
+}
+
+// Encoder
+
+/// Resumable depth-first trie encoder
+///
+/// Child length prefixes precede the child, so the encoded size of every subtree
+/// is computed up front, in the order nodes are written, instead of encoding each
+/// subtree to a temporary buffer.
+struct TrieEncoder<'a> {
+    root: &'a TrieNode,
+    /// Encoded size of each node, in the order nodes are written
+    sizes: Vec<u32>,
+    encoded_len: u64,
+    started: bool,
+    entered: usize,
+    stack: Vec<EncodeFrame<'a>>,
+    progress_callback: Option<&'a ProgressCallback>,
+}
+
+/// Child key and node, borrowed from the trie being encoded
+type EncodeChild<'a> = (&'a Vec<u8>, &'a TrieNode);
+
+struct EncodeFrame<'a> {
+    node: &'a TrieNode,
+    children: Vec<EncodeChild<'a>>,
+    ids_written: usize,
+    children_started: bool,
+    next_child: usize,
+}
+
+impl<'a> TrieEncoder<'a> {
+    fn new(
+        root: &'a TrieNode,
+        progress_callback: Option<&'a ProgressCallback>,
+    ) -> SerializationResult<Self> {
+        let mut sizes = Vec::new();
+        let root_len = encoded_sizes(root, &mut sizes)?;
+        Ok(Self {
+            root,
+            sizes,
+            encoded_len: TRIE_BINARY_HEADER_LEN as u64 + root_len,
+            started: false,
+            entered: 0,
+            stack: Vec::new(),
+            progress_callback,
+        })
+    }
+
+    fn encoded_len(&self) -> u64 {
+        self.encoded_len
+    }
+
+    /// Append output until `out` holds at least `target` bytes or the trie is
+    /// written; returns whether the trie is written
+    fn fill(&mut self, out: &mut Vec<u8>, target: usize) -> bool {
+        if !self.started {
+            self.started = true;
+            out.extend_from_slice(&trie_header(false));
+            self.enter(self.root, out);
+        }
+
+        while out.len() < target {
+            let Some(frame) = self.stack.last_mut() else {
+                break;
+            };
+
+            let ids = &frame.node.id_list;
+            if frame.ids_written < ids.len() {
+                let room = ((target - out.len()) / 4).max(1);
+                let end = ids.len().min(frame.ids_written.saturating_add(room));
+                for &id in &ids[frame.ids_written..end] {
+                    write_u32_be(out, id);
+                }
+                frame.ids_written = end;
+            } else if !frame.children_started {
+                frame.children_started = true;
+                write_u32_be(out, frame.children.len() as u32);
+            } else if let Some(&(key, child)) = frame.children.get(frame.next_child) {
+                frame.next_child += 1;
+                write_u32_be(out, self.sizes[self.entered]);
+                out.extend_from_slice(key);
+                self.enter(child, out);
+            } else {
+                self.stack.pop();
+            }
+        }
+
+        self.stack.is_empty()
+    }
+
+    fn enter(&mut self, node: &'a TrieNode, out: &mut Vec<u8>) {
+        write_u32_be(out, node.node_count);
+        write_u32_be(out, node.id_list.len() as u32);
+
+        let mut children: Vec<_> = node.children.iter().collect();
+        children.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        self.stack.push(EncodeFrame {
+            node,
+            children,
+            ids_written: 0,
+            children_started: false,
+            next_child: 0,
+        });
+
+        self.entered += 1;
+        if let Some(callback) = self.progress_callback {
+            callback(self.entered as f64 / self.sizes.len() as f64);
+        }
+    }
+}
+
+/// Record the encoded size of `node` and its descendants in write order, checking
+/// keys and sizes before anything is written; returns the size of `node`
+fn encoded_sizes(node: &TrieNode, sizes: &mut Vec<u32>) -> SerializationResult<u64> {
+    sizes.push(0);
+    let mut size = 12 + 4 * node.id_list.len() as u64;
+
+    let mut children: Vec<_> = node.children.iter().collect();
+    children.sort_unstable_by(|a, b| a.0.cmp(b.0));
+    for (key, child) in children {
+        if key.len() != 5 {
+            return Err(SerializationError::InvalidFormat {
+                message: "Chord key must be exactly 5 bytes".to_string(),
+            });
+        }
+        let index = sizes.len();
+        let child_size = encoded_sizes(child, sizes)?;
+        sizes[index] = u32::try_from(child_size).map_err(|_| SerializationError::BufferOverflow)?;
+        size += 4 + 5 + child_size;
+    }
+
+    Ok(size)
+}
+
+fn write_u32_be(buffer: &mut Vec<u8>, value: u32) {
+    buffer.extend_from_slice(&value.to_be_bytes());
+}
+
+// Decoder
+
+/// Incremental trie decoder fed with chunks of input as they arrive.
+///
+/// Chunks may split the input anywhere. Everything
+/// [`deserialize_trie`](crate::deserialize_trie) accepts is accepted, and every
+/// count is checked against the [`DeserializationLimits`] as soon as it is read,
+/// so hostile input fails before its allocations are made. Compressed input is
+/// buffered and decompressed by [`TrieDecoder::finish`]; prefer
+/// [`deserialize_trie_from_reader`], which decompresses while reading, when the
+/// input is available as a reader.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_serialization::{
+///     serialize_trie_chunks, DeserializationLimits, SerializationError, TrieDecoder, TrieNode,
+/// };
+///
+/// let mut trie = TrieNode::new();
+/// for id in 0..50 {
+///     trie.add_pattern(&[vec![0x10, 0, 0, 0, 0], vec![0x50, 0x08, 0, 0, 0]], id);
+/// }
+///
+/// // Chunks of 7 bytes split every field of the format at some point
+/// let mut decoder = TrieDecoder::new(false, DeserializationLimits::default());
+/// for chunk in serialize_trie_chunks(&trie, 7)? {
+///     decoder.feed(&chunk)?;
+/// }
+/// assert_eq!(decoder.finish()?, trie);
+/// # Ok::<(), SerializationError>(())
+/// ```
+pub struct TrieDecoder<'a> {
+    include_key_tonic: bool,
+    limits: DeserializationLimits,
+    /// Whether input may open with a compressed frame; it may not once decompressed
+    accept_compressed: bool,
+    /// Whether the header may flag a reference table after the root node
+    accept_references: bool,
+    has_references: bool,
+    progress_callback: Option<&'a ProgressCallback>,
+    /// Length of the whole input, when known before decoding
+    total_len: Option<usize>,
+    state: DecodeStep,
+    /// Start of a field split across chunks
+    pending: Vec<u8>,
+    compressed: Vec<u8>,
+    stack: Vec<DecodeFrame>,
+    root: Option<TrieNode>,
+    offset: usize,
+    trailing: usize,
+    nodes: usize,
+    allocated: usize,
+    #[cfg(not(target_arch = "wasm32"))]
+    start_time: Instant,
+}
+
+/// Next field the decoder expects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DecodeStep {
+    /// Header magic, compression magic, or the start of a version 1 root node
+    Magic,
+    /// Header version and flags
+    Header,
+    /// Node count and id count
+    NodeHeader,
+    /// Remaining ids of the current node
+    Ids(usize),
+    /// Child count of the current node
+    ChildCount,
+    /// Length prefix and key of the next child
+    ChildPrefix,
+    /// Compressed input, buffered until the end
+    Compressed(Compression),
+    /// Root node complete
+    Done,
+}
+
+struct DecodeFrame {
+    node: TrieNode,
+    depth: usize,
+    key: Vec<u8>,
+    /// Offset of the node's first byte, and its length from the parent's prefix
+    start: usize,
+    expected_len: usize,
+    children_left: usize,
+}
+
+impl<'a> TrieDecoder<'a> {
+    /// Create a decoder; `include_key_tonic` gives the id width of headerless
+    /// version 1 input
+    pub fn new(include_key_tonic: bool, limits: DeserializationLimits) -> Self {
+        Self {
+            include_key_tonic,
+            limits,
+            accept_compressed: true,
+            accept_references: false,
+            has_references: false,
+            progress_callback: None,
+            total_len: None,
+            state: DecodeStep::Magic,
+            pending: Vec::new(),
+            compressed: Vec::new(),
+            stack: Vec::new(),
+            root: None,
+            offset: 0,
+            trailing: 0,
+            nodes: 0,
+            allocated: 0,
+            #[cfg(not(target_arch = "wasm32"))]
+            start_time: Instant::now(),
+        }
+    }
+}
+
+impl TrieDecoder<'_> {
+    /// Decode the next chunk of input
+    pub fn feed(&mut self, mut input: &[u8]) -> SerializationResult<()> {
+        while !input.is_empty() {
+            match self.state {
+                DecodeStep::Done => {
+                    self.trailing += input.len();
+                    return Ok(());
+                },
+                DecodeStep::Compressed(_) => {
+                    self.compressed.extend_from_slice(input);
+                    return Ok(());
+                },
+                _ => {},
+            }
+
+            let need = self.field_len();
+            if self.pending.is_empty() && input.len() >= need {
+                let consumed = self.step(&input[..need])?;
+                input = &input[consumed..];
+                continue;
+            }
+
+            // Gather a field split across chunks
+            let take = (need - self.pending.len()).min(input.len());
+            self.pending.extend_from_slice(&input[..take]);
+            input = &input[take..];
+            if self.pending.len() == need {
+                let field = std::mem::take(&mut self.pending);
+                let consumed = self.step(&field)?;
+                self.pending.extend_from_slice(&field[consumed..]);
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the input is complete and return the decoded trie
+    pub fn finish(mut self) -> SerializationResult<TrieNode> {
+        match self.state {
+            DecodeStep::Compressed(_) => {
+                let compressed = std::mem::take(&mut self.compressed);
+                decode_reader(
+                    &mut compressed.as_slice(),
+                    self.include_key_tonic,
+                    &self.limits,
+                    true,
+                )
+            },
+            DecodeStep::Done => {
+                if self.trailing > 0 && !self.limits.allow_trailing_data {
+                    return Err(SerializationError::CorruptedBinary {
+                        details: format!("{} trailing bytes after trie data", self.trailing),
+                    });
+                }
+                self.root.ok_or(SerializationError::UnexpectedEof)
+            },
+            _ => Err(SerializationError::UnexpectedEof),
+        }
+    }
+
+    fn id_size(&self) -> usize {
+        if self.include_key_tonic {
+            6
+        } else {
+            4
+        }
+    }
+
+    fn field_len(&self) -> usize {
+        match self.state {
+            DecodeStep::Magic | DecodeStep::ChildCount => 4,
+            DecodeStep::Header => TRIE_BINARY_HEADER_LEN - TRIE_BINARY_MAGIC.len(),
+            DecodeStep::NodeHeader => 8,
+            DecodeStep::Ids(_) => self.id_size(),
+            DecodeStep::ChildPrefix => 4 + 5,
+            DecodeStep::Compressed(_) | DecodeStep::Done => 0,
+        }
+    }
+
+    /// Bytes of the input not yet decoded, when its length is known
+    fn remaining(&self) -> Option<usize> {
+        self.total_len.map(|len| len.saturating_sub(self.offset))
+    }
+
+    /// Decode one complete field, returning how many of its bytes were used
+    fn step(&mut self, field: &[u8]) -> SerializationResult<usize> {
+        match self.state {
+            DecodeStep::Magic => {
+                let compression = detect_compression(field);
+                if field == TRIE_BINARY_MAGIC {
+                    self.offset += field.len();
+                    self.state = DecodeStep::Header;
+                    Ok(field.len())
+                } else if compression != Compression::None && self.accept_compressed {
+                    self.compressed.extend_from_slice(field);
+                    self.state = DecodeStep::Compressed(compression);
+                    Ok(field.len())
+                } else {
+                    // Version 1: these bytes open the root node
+                    self.enter_root()?;
+                    Ok(0)
+                }
+            },
+            DecodeStep::Header => {
+                self.offset += field.len();
+                check_trie_format_version(field[0])?;
+                let flags = field[1];
+                if flags & !(TRIE_FLAG_KEY_TONIC | TRIE_FLAG_REFERENCES) != 0 {
+                    return Err(SerializationError::InvalidBinaryFormat {
+                        reason: format!("unknown trie header flags {:#04x}", flags),
+                    });
+                }
+                self.has_references = flags & TRIE_FLAG_REFERENCES != 0;
+                if self.has_references && !self.accept_references {
+                    return Err(SerializationError::InvalidBinaryFormat {
+                        reason: "trie ids index a reference table; read it with \
+                                 deserialize_trie_with_references"
+                            .to_string(),
+                    });
+                }
+                self.include_key_tonic = flags & TRIE_FLAG_KEY_TONIC != 0;
+                self.enter_root()?;
+                Ok(field.len())
+            },
+            DecodeStep::NodeHeader => {
+                self.offset += field.len();
+                let node_count = read_u32_be(&field[..4]);
+                let id_count = read_u32_be(&field[4..]) as usize;
+                check_limit("ids per node", id_count, self.limits.max_ids_per_node)?;
+                if self
+                    .remaining()
+                    .is_some_and(|remaining| id_count.saturating_mul(self.id_size()) > remaining)
+                {
+                    return Err(SerializationError::UnexpectedEof);
+                }
+                self.allocate(id_count * std::mem::size_of::<u32>())?;
+
+                let capacity = match self.total_len {
+                    Some(_) => id_count,
+                    None => id_count.min(STREAM_PREALLOCATION),
+                };
+                let frame = self.frame()?;
+                frame.node.node_count = node_count;
+                frame.node.id_list = Vec::with_capacity(capacity);
+                self.state = if id_count == 0 {
+                    DecodeStep::ChildCount
+                } else {
+                    DecodeStep::Ids(id_count)
+                };
+                Ok(field.len())
+            },
+            DecodeStep::Ids(left) => {
+                self.offset += field.len();
+                // Key tonic ids carry two more bytes after the id itself
+                let id = read_u32_be(&field[..4]);
+                self.frame()?.node.id_list.push(id);
+                self.state = if left == 1 {
+                    DecodeStep::ChildCount
+                } else {
+                    DecodeStep::Ids(left - 1)
+                };
+                Ok(field.len())
+            },
+            DecodeStep::ChildCount => {
+                self.offset += field.len();
+                // Each child needs at least a length prefix, key, and empty node header
+                const MIN_CHILD_BYTES: usize = 4 + 5 + 12;
+                let child_count = read_u32_be(field) as usize;
+                check_limit(
+                    "children per node",
+                    child_count,
+                    self.limits.max_children_per_node,
+                )?;
+                if self.remaining().is_some_and(|remaining| {
+                    child_count.saturating_mul(MIN_CHILD_BYTES) > remaining
+                }) {
+                    return Err(SerializationError::UnexpectedEof);
+                }
+
+                let capacity = match self.total_len {
+                    Some(_) => child_count,
+                    None => child_count.min(STREAM_PREALLOCATION),
+                };
+                let frame = self.frame()?;
+                frame.node.children = HashMap::with_capacity(capacity);
+                frame.children_left = child_count;
+                if child_count == 0 {
+                    self.finish_node()?;
+                } else {
+                    self.state = DecodeStep::ChildPrefix;
+                }
+                Ok(field.len())
+            },
+            DecodeStep::ChildPrefix => {
+                self.offset += field.len();
+                let expected_len = read_u32_be(&field[..4]) as usize;
+                if self
+                    .remaining()
+                    .is_some_and(|remaining| expected_len > remaining)
+                {
+                    return Err(SerializationError::UnexpectedEof);
+                }
+
+                let parent = self.frame()?;
+                parent.children_left -= 1;
+                let depth = parent.depth + 1;
+                self.enter_node(depth)?;
+                self.stack.push(DecodeFrame {
+                    node: TrieNode::new(),
+                    depth,
+                    key: field[4..].to_vec(),
+                    start: self.offset,
+                    expected_len,
+                    children_left: 0,
+                });
+                self.state = DecodeStep::NodeHeader;
+                Ok(field.len())
+            },
+            DecodeStep::Compressed(_) | DecodeStep::Done => Ok(0),
+        }
+    }
+
+    fn frame(&mut self) -> SerializationResult<&mut DecodeFrame> {
+        self.stack
+            .last_mut()
+            .ok_or_else(|| SerializationError::InvalidTrieStructure {
+                reason: "no node is being decoded".to_string(),
+            })
+    }
+
+    fn enter_root(&mut self) -> SerializationResult<()> {
+        self.enter_node(0)?;
+        self.stack.push(DecodeFrame {
+            node: TrieNode::new(),
+            depth: 0,
+            key: Vec::new(),
+            start: self.offset,
+            expected_len: 0,
+            children_left: 0,
+        });
+        self.state = DecodeStep::NodeHeader;
+        Ok(())
+    }
+
+    /// Attach completed nodes to their parents, up to the first parent with
+    /// children still to read
+    fn finish_node(&mut self) -> SerializationResult<()> {
+        while let Some(frame) = self.stack.pop() {
+            if let (Some(callback), Some(len)) = (self.progress_callback, self.total_len) {
+                callback(self.offset as f64 / len as f64);
+            }
+
+            let Some(parent) = self.stack.last_mut() else {
+                self.root = Some(frame.node);
+                self.state = DecodeStep::Done;
+                return Ok(());
+            };
+
+            let decoded = self.offset - frame.start;
+            if decoded != frame.expected_len {
+                return Err(SerializationError::CorruptedBinary {
+                    details: format!(
+                        "child length prefix {} does not match {} bytes decoded",
+                        frame.expected_len, decoded
+                    ),
+                });
+            }
+            if parent.node.children.insert(frame.key, frame.node).is_some() {
+                return Err(SerializationError::InvalidTrieStructure {
+                    reason: "duplicate child key".to_string(),
+                });
+            }
+            if parent.children_left > 0 {
+                self.state = DecodeStep::ChildPrefix;
+                return Ok(());
+            }
+        }
+        Ok(())
+    }
+
+    /// Charge an allocation against the budget before making it
+    fn allocate(&mut self, bytes: usize) -> SerializationResult<()> {
+        self.allocated = self.allocated.saturating_add(bytes);
+        check_limit(
+            "allocation bytes",
+            self.allocated,
+            self.limits.max_allocation_bytes,
+        )
+    }
+
+    fn enter_node(&mut self, depth: usize) -> SerializationResult<()> {
+        check_limit("depth", depth, self.limits.max_depth)?;
+
+        self.nodes += 1;
+        check_limit("nodes", self.nodes, self.limits.max_nodes)?;
+        self.allocate(NODE_ALLOCATION_BYTES)?;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.nodes % TIMEOUT_CHECK_INTERVAL == 0 {
+            if let Some(timeout) = self.limits.timeout {
+                let elapsed = self.start_time.elapsed();
+                if elapsed > timeout {
+                    return Err(SerializationError::Timeout {
+                        elapsed_ms: elapsed.as_millis() as u64,
+                        limit_ms: timeout.as_millis() as u64,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn check_limit(resource: &str, actual: usize, limit: usize) -> SerializationResult<()> {
+    if actual > limit {
+        return Err(SerializationError::LimitExceeded {
+            resource: resource.to_string(),
+            limit: limit as u64,
+            actual: actual as u64,
+        });
+    }
+    Ok(())
+}
+
+fn read_u32_be(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compression::compress;
+    use crate::trie_binary::{deserialize_trie, serialize_trie};
+
+    fn sample_trie() -> TrieNode {
+        let mut root = TrieNode::new();
+        for id in 0..40u32 {
+            let first = vec![0x10 + (id % 3) as u8 * 0x10, 0, 0, 0, 0];
+            let second = vec![0x50, 0x08, (id % 5) as u8, 0, 0];
+            root.add_pattern(&[first.clone(), second], id);
+            root.add_pattern(&[first], id + 100);
+        }
+        root.node_count = 3;
+        root
+    }
+
+    #[test]
+    fn test_streams_match_serialize_trie() {
+        let trie = sample_trie();
+        let bytes = serialize_trie(&trie).unwrap();
+
+        let mut written = Vec::new();
+        assert_eq!(
+            serialize_trie_to_writer(&trie, &mut written).unwrap(),
+            bytes.len() as u64
+        );
+        assert_eq!(written, bytes);
+
+        for chunk_len in [1, 5, 64, 1 << 20] {
+            let chunks = serialize_trie_chunks(&trie, chunk_len).unwrap();
+            assert_eq!(chunks.encoded_len(), bytes.len() as u64);
+            let chunks: Vec<_> = chunks.collect();
+            assert!(chunks[..chunks.len() - 1]
+                .iter()
+                .all(|chunk| chunk.len() >= chunk_len));
+            assert_eq!(chunks.concat(), bytes);
+        }
+    }
+
+    #[test]
+    fn test_decoder_accepts_any_chunking() {
+        let trie = sample_trie();
+        let bytes = serialize_trie(&trie).unwrap();
+        let limits = DeserializationLimits::default();
+
+        for chunk_len in [1, 2, 3, 7, 13, bytes.len()] {
+            let mut decoder = TrieDecoder::new(false, limits.clone());
+            for chunk in bytes.chunks(chunk_len) {
+                decoder.feed(chunk).unwrap();
+            }
+            assert_eq!(decoder.finish().unwrap(), trie);
+        }
+
+        // Version 1 input, compressed input and readers
+        let legacy = &bytes[TRIE_BINARY_HEADER_LEN..];
+        let mut decoder = TrieDecoder::new(false, limits.clone());
+        for chunk in legacy.chunks(3) {
+            decoder.feed(chunk).unwrap();
+        }
+        assert_eq!(decoder.finish().unwrap(), trie);
+
+        for compression in [Compression::Zstd, Compression::Lz4] {
+            let packed = compress(&bytes, compression).unwrap();
+            let mut decoder = TrieDecoder::new(false, limits.clone());
+            for chunk in packed.chunks(5) {
+                decoder.feed(chunk).unwrap();
+            }
+            assert_eq!(decoder.finish().unwrap(), trie);
+            assert_eq!(
+                deserialize_trie_from_reader(packed.as_slice(), false, &limits).unwrap(),
+                trie
+            );
+        }
+    }
+
+    #[test]
+    fn test_stream_errors_match_slice_errors() {
+        let bytes = serialize_trie(&sample_trie()).unwrap();
+        let limits = DeserializationLimits::default();
+
+        for len in 0..bytes.len() {
+            let truncated = &bytes[..len];
+            assert!(deserialize_trie_from_reader(truncated, false, &limits).is_err());
+            assert!(deserialize_trie(truncated, false).is_err());
+        }
+
+        let mut padded = bytes.clone();
+        padded.extend_from_slice(&[0, 0]);
+        assert!(matches!(
+            deserialize_trie_from_reader(padded.as_slice(), false, &limits),
+            Err(SerializationError::CorruptedBinary { .. })
+        ));
+        let lenient = DeserializationLimits {
+            allow_trailing_data: true,
+            ..Default::default()
+        };
+        assert!(deserialize_trie_from_reader(padded.as_slice(), false, &lenient).is_ok());
+
+        // Hostile counts fail on the limits, not on allocation, without a known length
+        let mut hostile = trie_header(false).to_vec();
+        hostile.extend_from_slice(&0u32.to_be_bytes());
+        hostile.extend_from_slice(&u32::MAX.to_be_bytes());
+        assert!(matches!(
+            deserialize_trie_from_reader(hostile.as_slice(), false, &limits),
+            Err(SerializationError::LimitExceeded { .. })
+        ));
+
+        let packed = compress(&bytes, Compression::Lz4).unwrap();
+        let tiny = DeserializationLimits {
+            max_decompressed_bytes: bytes.len() / 2,
+            ..Default::default()
+        };
+        assert!(matches!(
+            deserialize_trie_from_reader(packed.as_slice(), false, &tiny),
+            Err(SerializationError::LimitExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn test_writer_errors_are_reported() {
+        struct FailingWriter;
+        impl Write for FailingWriter {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::new(std::io::ErrorKind::Other, "disk full"))
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        assert!(matches!(
+            serialize_trie_to_writer(&sample_trie(), FailingWriter),
+            Err(SerializationError::IoError { .. })
+        ));
+
+        let mut bad_key = TrieNode::new();
+        bad_key.add_pattern(&[vec![1, 2, 3]], 1);
+        assert!(serialize_trie_chunks(&bad_key, 16).is_err());
+    }
+}
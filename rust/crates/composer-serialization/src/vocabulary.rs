@@ -0,0 +1,548 @@
+//! Token vocabularies with stable integer ids for ML training
+//!
+//! Models consume token ids, not token strings, so a training pipeline and the
+//! detokenizer have to agree on the id of every token. A [`TokenVocabulary`]
+//! fixes that mapping: the special, structural, metric, octave, raw note,
+//! duration, bar and key tokens always take the same leading ids, and chord and
+//! time signature tokens follow in the order they were first added to the
+//! vocabulary. Growing a vocabulary appends, so ids a model was trained on never
+//! move. Vocabularies are exchanged as JSON for Python pipelines or in a compact
+//! binary form.
+
+use crate::chord_binary::ChordBinary;
+use crate::constants::*;
+use crate::error::{SerializationError, SerializationResult};
+use crate::tokenization::{
+    check_token_schema_version, tokenize_key, upgrade_token, validate_token, MetricPosition,
+    TokenLibrary,
+};
+use composer_core::{Key, Mode};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Tokens with fixed ids at the start of every vocabulary, in id order
+const SPECIAL_TOKENS: [&str; 4] = [PAD_TOKEN, UNKNOWN_TOKEN, BOS_TOKEN, EOS_TOKEN];
+
+/// Token vocabulary with stable integer ids
+///
+/// A token's id is its position in the vocabulary. Ids 0-3 are always
+/// `<PAD>`, `<UNK>`, `<BOS>` and `<EOS>`.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_serialization::{
+///     deserialize_token_vocabulary, serialize_token_vocabulary, TokenLibrary, UNKNOWN_TOKEN,
+/// };
+///
+/// let mut library = TokenLibrary::new();
+/// library.add_chord_token("R_0-R_4-R_7".to_string(), vec![0x10, 0, 0, 0, 0]);
+/// let vocabulary = library.export_vocab()?;
+///
+/// let ids = vocabulary.encode(&["<CHORD>", "R_0-R_4-R_7", "</CHORD>", "D_18", "X"]);
+/// assert_eq!(ids[4], vocabulary.id(UNKNOWN_TOKEN).unwrap());
+/// assert_eq!(vocabulary.decode(&ids[..4])?, ["<CHORD>", "R_0-R_4-R_7", "</CHORD>", "D_18"]);
+///
+/// // The binary form restores the same ids and the library's chords
+/// let restored = deserialize_token_vocabulary(&serialize_token_vocabulary(&vocabulary)?)?;
+/// assert_eq!(restored, vocabulary);
+/// let library = TokenLibrary::import_vocab(&restored);
+/// assert_eq!(library.resolve_chord_token("R_0-R_4-R_7")?, vec![0x10, 0, 0, 0, 0]);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Related Functions
+///
+/// - [`TokenLibrary::export_vocab`] - Vocabulary of a token library
+/// - [`token_vocabulary_to_json`] - Write a vocabulary for Python pipelines
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenVocabulary {
+    schema_version: u8,
+    tokens: Vec<String>,
+    chords: BTreeMap<String, Vec<u8>>,
+    ids: HashMap<String, u32>,
+}
+
+/// JSON form of a [`TokenVocabulary`]
+#[derive(Serialize, Deserialize)]
+struct VocabularyFile {
+    schema_version: u8,
+    tokens: Vec<String>,
+    chords: BTreeMap<String, Vec<u8>>,
+}
+
+impl TokenVocabulary {
+    /// Vocabulary of every token that is not a chord or time signature, in the
+    /// current token vocabulary version
+    ///
+    /// Durations have tokens from one tick up to
+    /// [`VOCABULARY_MAX_DURATION_TICKS`]; longer ones encode as `<UNK>`. Chord
+    /// and time signature tokens are added with
+    /// [`TokenVocabulary::extend_from_tokens`].
+    pub fn new() -> Self {
+        let mut tokens: Vec<String> = SPECIAL_TOKENS.iter().map(|t| t.to_string()).collect();
+        tokens.extend(
+            [
+                CHORD_GROUP_OPEN,
+                CHORD_GROUP_CLOSE,
+                NOTES_GROUP_OPEN,
+                NOTES_GROUP_CLOSE,
+                REST_NOTE_TOKEN,
+                REST_CHORD_TOKEN,
+            ]
+            .iter()
+            .map(|t| t.to_string()),
+        );
+        tokens.extend(MetricPosition::ALL.iter().map(|position| position.token()));
+        tokens.extend(
+            (OCTAVE_RANGE_MIN..=OCTAVE_RANGE_MAX)
+                .map(|octave| format!("{}{}", OCTAVE_TOKEN_PREFIX, octave)),
+        );
+        tokens.extend(
+            (0..CHROMATIC_RANGE)
+                .map(|pitch_class| format!("{}{:x}", RAW_NOTE_TOKEN_PREFIX, pitch_class)),
+        );
+        tokens.extend(
+            (1..=VOCABULARY_MAX_DURATION_TICKS)
+                .map(|ticks| format!("{}{:x}", DURATION_TOKEN_PREFIX, ticks)),
+        );
+        tokens.push(BAR_TOKEN.to_string());
+        for mode in [Mode::Major, Mode::Minor] {
+            tokens.extend(
+                (0..CHROMATIC_RANGE).map(|tonic| tokenize_key(&Key::from_chromatic(tonic, mode))),
+            );
+        }
+
+        let ids = Self::index(&tokens);
+        Self {
+            schema_version: TOKEN_LIBRARY_FORMAT_VERSION,
+            tokens,
+            chords: BTreeMap::new(),
+            ids,
+        }
+    }
+
+    /// Token vocabulary version the tokens are written in
+    pub fn schema_version(&self) -> u8 {
+        self.schema_version
+    }
+
+    /// Number of tokens
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    /// Whether the vocabulary has no tokens; never true for a valid vocabulary
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Every token, indexed by id
+    pub fn tokens(&self) -> &[String] {
+        &self.tokens
+    }
+
+    /// Id of `token`, if it is in the vocabulary
+    pub fn id(&self, token: &str) -> Option<u32> {
+        self.ids.get(token).copied()
+    }
+
+    /// Token with id `id`, if any
+    pub fn token(&self, id: u32) -> Option<&str> {
+        self.tokens.get(id as usize).map(String::as_str)
+    }
+
+    /// Ids of a token stream; tokens outside the vocabulary become `<UNK>`
+    pub fn encode<S: AsRef<str>>(&self, tokens: &[S]) -> Vec<u32> {
+        let unknown = self.ids[UNKNOWN_TOKEN];
+        tokens
+            .iter()
+            .map(|token| self.id(token.as_ref()).unwrap_or(unknown))
+            .collect()
+    }
+
+    /// Token stream of `ids`
+    ///
+    /// Fails with `SerializationError::InvalidFormat` for an id outside the
+    /// vocabulary.
+    pub fn decode(&self, ids: &[u32]) -> SerializationResult<Vec<String>> {
+        ids.iter()
+            .map(|&id| {
+                self.token(id).map(str::to_string).ok_or_else(|| {
+                    SerializationError::InvalidFormat {
+                        message: format!("Token id {} is outside the vocabulary", id),
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Append the chord tokens of `library` that are not in the vocabulary yet
+    ///
+    /// New tokens are appended in token order, so existing ids never change.
+    /// Returns how many were added. Fails with `SerializationError::InvalidFormat`
+    /// if the library is written in another token vocabulary version or maps a
+    /// token already in the vocabulary to a different chord.
+    pub fn extend_from_library(&mut self, library: &TokenLibrary) -> SerializationResult<usize> {
+        if library.schema_version != self.schema_version {
+            return Err(SerializationError::InvalidFormat {
+                message: format!(
+                    "Token library version {} does not match vocabulary version {}",
+                    library.schema_version, self.schema_version
+                ),
+            });
+        }
+
+        let mut entries: Vec<_> = library.chord_tokens.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        for (token, binary) in &entries {
+            if let Some(existing) = self.chords.get(*token) {
+                if existing != *binary {
+                    return Err(SerializationError::InvalidFormat {
+                        message: format!("Token {} maps to a different chord", token),
+                    });
+                }
+            }
+        }
+
+        let mut added = 0;
+        for (token, binary) in entries {
+            if !self.ids.contains_key(token) {
+                self.ids.insert(token.clone(), self.tokens.len() as u32);
+                self.tokens.push(token.clone());
+                added += 1;
+            }
+            self.chords.insert(token.clone(), binary.clone());
+        }
+        Ok(added)
+    }
+
+    /// Append the tokens of a token stream that are not in the vocabulary yet
+    ///
+    /// Collects the chord and time signature tokens a corpus tokenized with
+    /// [`crate::tokenize_timeline`] uses. New tokens are appended in the order
+    /// they first appear, so existing ids never change. Returns how many were
+    /// added. Fails with `SerializationError::InvalidTokenFormat` for a token
+    /// that is not in the token grammar.
+    pub fn extend_from_tokens<S: AsRef<str>>(
+        &mut self,
+        tokens: &[S],
+    ) -> SerializationResult<usize> {
+        if let Some(token) = tokens
+            .iter()
+            .map(AsRef::as_ref)
+            .find(|token| !validate_token(token) && !self.ids.contains_key(*token))
+        {
+            return Err(SerializationError::InvalidTokenFormat {
+                token: token.to_string(),
+            });
+        }
+
+        let mut added = 0;
+        for token in tokens {
+            let token = token.as_ref();
+            if !self.ids.contains_key(token) {
+                self.ids.insert(token.to_string(), self.tokens.len() as u32);
+                self.tokens.push(token.to_string());
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+
+    /// Validate the parts of a stored vocabulary, upgrading older tokens
+    fn from_parts(
+        schema_version: u8,
+        mut tokens: Vec<String>,
+        mut chords: BTreeMap<String, Vec<u8>>,
+    ) -> SerializationResult<Self> {
+        let invalid = |message: String| SerializationError::InvalidFormat { message };
+        check_token_schema_version(schema_version)?;
+
+        // Upgrading renames tokens in place, so their ids survive
+        if schema_version != TOKEN_LIBRARY_FORMAT_VERSION {
+            for token in &mut tokens {
+                *token = upgrade_token(token, schema_version)?;
+            }
+            chords = chords
+                .into_iter()
+                .map(|(token, binary)| Ok((upgrade_token(&token, schema_version)?, binary)))
+                .collect::<SerializationResult<_>>()?;
+        }
+
+        if !tokens.starts_with(&SPECIAL_TOKENS.map(String::from)) {
+            return Err(invalid(format!(
+                "Vocabulary must start with {}",
+                SPECIAL_TOKENS.join(", ")
+            )));
+        }
+        let ids = Self::index(&tokens);
+        if ids.len() != tokens.len() {
+            return Err(invalid("Vocabulary has duplicate tokens".to_string()));
+        }
+        for (token, binary) in &chords {
+            if !ids.contains_key(token) {
+                return Err(invalid(format!("Chord token {} has no id", token)));
+            }
+            if binary.len() != std::mem::size_of::<ChordBinary>() {
+                return Err(invalid(format!(
+                    "Chord token {} has a {}-byte chord binary",
+                    token,
+                    binary.len()
+                )));
+            }
+        }
+
+        Ok(Self {
+            schema_version: TOKEN_LIBRARY_FORMAT_VERSION,
+            tokens,
+            chords,
+            ids,
+        })
+    }
+
+    fn index(tokens: &[String]) -> HashMap<String, u32> {
+        tokens
+            .iter()
+            .enumerate()
+            .map(|(id, token)| (token.clone(), id as u32))
+            .collect()
+    }
+}
+
+impl Default for TokenVocabulary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TokenLibrary {
+    /// Vocabulary of every token with this library's chord tokens, for ML training
+    ///
+    /// Libraries in an older token vocabulary are upgraded first; fails if that
+    /// upgrade does. To keep the ids of an earlier export as the library grows,
+    /// extend that vocabulary with [`TokenVocabulary::extend_from_library`]
+    /// instead.
+    pub fn export_vocab(&self) -> SerializationResult<TokenVocabulary> {
+        let mut library = self.clone();
+        library.upgrade()?;
+        let mut vocabulary = TokenVocabulary::new();
+        vocabulary.extend_from_library(&library)?;
+        Ok(vocabulary)
+    }
+
+    /// Library of the chord tokens of a vocabulary
+    pub fn import_vocab(vocabulary: &TokenVocabulary) -> TokenLibrary {
+        let mut library = TokenLibrary::new();
+        for (token, binary) in &vocabulary.chords {
+            library.add_chord_token(token.clone(), binary.clone());
+        }
+        library
+    }
+}
+
+/// Write a vocabulary as JSON
+///
+/// The object has the token vocabulary version, every token in id order, and
+/// the serialized chord of each chord token as a byte array:
+/// `{"schema_version": 2, "tokens": ["<PAD>", ...], "chords": {"R_0-R_4-R_7": [16, 0, 0, 0, 0]}}`.
+pub fn token_vocabulary_to_json(vocabulary: &TokenVocabulary) -> SerializationResult<String> {
+    let file = VocabularyFile {
+        schema_version: vocabulary.schema_version,
+        tokens: vocabulary.tokens.clone(),
+        chords: vocabulary.chords.clone(),
+    };
+    serde_json::to_string(&file).map_err(|e| SerializationError::InvalidFormat {
+        message: format!("Vocabulary JSON encoding failed: {}", e),
+    })
+}
+
+/// Read a vocabulary written by [`token_vocabulary_to_json`]
+///
+/// Vocabularies written with an older token vocabulary are upgraded to the
+/// current one, keeping every token's id.
+pub fn token_vocabulary_from_json(json: &str) -> SerializationResult<TokenVocabulary> {
+    let file: VocabularyFile =
+        serde_json::from_str(json).map_err(|e| SerializationError::DeserializationFailed {
+            reason: format!("Invalid vocabulary JSON: {}", e),
+        })?;
+    TokenVocabulary::from_parts(file.schema_version, file.tokens, file.chords)
+}
+
+/// Serialize a vocabulary to binary format
+///
+/// Layout (big-endian): magic `CVOC` (4), schema version (1), token count (4),
+/// then per token in id order: token length (2), UTF-8 token, chord binary
+/// length (1, 0 for tokens that are not chords), chord binary.
+pub fn serialize_token_vocabulary(vocabulary: &TokenVocabulary) -> SerializationResult<Vec<u8>> {
+    let mut buffer = Vec::new();
+    buffer.extend_from_slice(&TOKEN_VOCABULARY_MAGIC);
+    buffer.push(vocabulary.schema_version);
+    buffer.extend_from_slice(&(vocabulary.tokens.len() as u32).to_be_bytes());
+
+    for token in &vocabulary.tokens {
+        let binary = vocabulary.chords.get(token).map_or(&[][..], Vec::as_slice);
+        let token_len =
+            u16::try_from(token.len()).map_err(|_| SerializationError::BufferOverflow)?;
+        let binary_len =
+            u8::try_from(binary.len()).map_err(|_| SerializationError::BufferOverflow)?;
+
+        buffer.extend_from_slice(&token_len.to_be_bytes());
+        buffer.extend_from_slice(token.as_bytes());
+        buffer.push(binary_len);
+        buffer.extend_from_slice(binary);
+    }
+
+    Ok(buffer)
+}
+
+/// Deserialize a vocabulary from binary format
+///
+/// Vocabularies written with an older token vocabulary are upgraded to the
+/// current one, keeping every token's id.
+pub fn deserialize_token_vocabulary(data: &[u8]) -> SerializationResult<TokenVocabulary> {
+    if data.len() < TOKEN_VOCABULARY_HEADER_LEN || data[0..4] != TOKEN_VOCABULARY_MAGIC {
+        return Err(SerializationError::InvalidBinaryFormat {
+            reason: "Missing token vocabulary header".to_string(),
+        });
+    }
+
+    let count = u32::from_be_bytes([data[5], data[6], data[7], data[8]]) as usize;
+    let mut offset = TOKEN_VOCABULARY_HEADER_LEN;
+    let mut take = |len: usize| -> SerializationResult<&[u8]> {
+        if offset + len > data.len() {
+            return Err(SerializationError::UnexpectedEof);
+        }
+        let slice = &data[offset..offset + len];
+        offset += len;
+        Ok(slice)
+    };
+
+    let mut tokens = Vec::new();
+    let mut chords = BTreeMap::new();
+    for _ in 0..count {
+        let token_len = take(2)?;
+        let token_len = u16::from_be_bytes([token_len[0], token_len[1]]) as usize;
+        let token = std::str::from_utf8(take(token_len)?)
+            .map_err(|e| SerializationError::CorruptedBinary {
+                details: format!("token is not valid UTF-8: {}", e),
+            })?
+            .to_string();
+        let binary_len = take(1)?[0] as usize;
+        let binary = take(binary_len)?;
+        if !binary.is_empty() {
+            chords.insert(token.clone(), binary.to_vec());
+        }
+        tokens.push(token);
+    }
+
+    if offset != data.len() {
+        return Err(SerializationError::CorruptedBinary {
+            details: format!(
+                "{} trailing bytes after token vocabulary",
+                data.len() - offset
+            ),
+        });
+    }
+
+    TokenVocabulary::from_parts(data[4], tokens, chords)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenization::tokenize_duration;
+
+    fn library() -> TokenLibrary {
+        let mut library = TokenLibrary::new();
+        library.add_chord_token("R_0-R_4-R_7".to_string(), vec![0x10, 0, 0, 0, 0]);
+        library.add_chord_token("R_2-R_5-R_9".to_string(), vec![0x20, 0, 0, 0, 0]);
+        library
+    }
+
+    #[test]
+    fn test_base_ids_are_fixed() {
+        let vocabulary = TokenVocabulary::new();
+        assert_eq!(vocabulary.id(PAD_TOKEN), Some(0));
+        assert_eq!(vocabulary.id(EOS_TOKEN), Some(3));
+        assert_eq!(vocabulary.id(CHORD_GROUP_OPEN), Some(4));
+        assert_eq!(vocabulary.id("M_DOWN"), Some(10));
+        assert_eq!(vocabulary.id("O_2"), Some(15));
+        assert_eq!(vocabulary.id("R_b"), Some(32));
+        assert_eq!(vocabulary.id(&tokenize_duration(1.0)), Some(32 + 24));
+        assert_eq!(
+            vocabulary.len(),
+            33 + VOCABULARY_MAX_DURATION_TICKS as usize + 25
+        );
+        assert_eq!(vocabulary.id("K_9_MIN"), Some(vocabulary.len() as u32 - 3));
+        assert!(vocabulary.decode(&[vocabulary.len() as u32]).is_err());
+    }
+
+    #[test]
+    fn test_extending_keeps_ids() {
+        let mut vocabulary = library().export_vocab().unwrap();
+        let base = TokenVocabulary::new().len() as u32;
+        assert_eq!(vocabulary.id("R_0-R_4-R_7"), Some(base));
+        assert_eq!(vocabulary.id("R_2-R_5-R_9"), Some(base + 1));
+
+        // A token sorting first still goes to the end
+        let mut grown = library();
+        grown.add_chord_token("R_0-R_3-R_7".to_string(), vec![0x18, 0, 0, 0, 0]);
+        assert_eq!(vocabulary.extend_from_library(&grown).unwrap(), 1);
+        assert_eq!(vocabulary.id("R_0-R_4-R_7"), Some(base));
+        assert_eq!(vocabulary.id("R_0-R_3-R_7"), Some(base + 2));
+
+        grown.add_chord_token("R_0-R_4-R_7".to_string(), vec![0x11, 0, 0, 0, 0]);
+        assert!(vocabulary.extend_from_library(&grown).is_err());
+        let old = TokenLibrary::with_schema_version(1).unwrap();
+        assert!(vocabulary.extend_from_library(&old).is_err());
+
+        // Stream tokens outside the base section are appended in order
+        let stream = ["TS_7_8", "BAR", "CH_1000000000", "D_18", "TS_7_8"];
+        assert_eq!(vocabulary.extend_from_tokens(&stream).unwrap(), 2);
+        assert_eq!(vocabulary.id("TS_7_8"), Some(base + 3));
+        assert_eq!(vocabulary.id("CH_1000000000"), Some(base + 4));
+        assert!(vocabulary.extend_from_tokens(&["TS_0_4"]).is_err());
+    }
+
+    #[test]
+    fn test_json_round_trip_and_upgrade() {
+        let vocabulary = library().export_vocab().unwrap();
+        let json = token_vocabulary_to_json(&vocabulary).unwrap();
+        assert_eq!(token_vocabulary_from_json(&json).unwrap(), vocabulary);
+
+        // Version 1 tokens are upgraded in place
+        let tokens = [&SPECIAL_TOKENS[..], &["D_24", "R_2-R_5-R_11"]].concat();
+        let chords = BTreeMap::from([("R_2-R_5-R_11".to_string(), vec![0x50, 8, 0, 0, 0])]);
+        let old =
+            TokenVocabulary::from_parts(1, tokens.iter().map(|t| t.to_string()).collect(), chords)
+                .unwrap();
+        assert_eq!(old.schema_version(), TOKEN_LIBRARY_FORMAT_VERSION);
+        assert_eq!(old.id("D_18"), Some(4));
+        assert_eq!(old.id("R_2-R_5-R_b"), Some(5));
+        assert!(TokenLibrary::import_vocab(&old)
+            .resolve_chord_token("R_2-R_5-R_b")
+            .is_ok());
+
+        assert!(token_vocabulary_from_json("{}").is_err());
+        let missing_specials = r#"{"schema_version": 2, "tokens": ["D_1"], "chords": {}}"#;
+        assert!(token_vocabulary_from_json(missing_specials).is_err());
+        let duplicate = r#"{"schema_version": 2, "tokens": ["<PAD>", "<UNK>", "<BOS>", "<EOS>", "D_1", "D_1"], "chords": {}}"#;
+        assert!(token_vocabulary_from_json(duplicate).is_err());
+    }
+
+    #[test]
+    fn test_binary_rejects_corruption() {
+        let bytes = serialize_token_vocabulary(&library().export_vocab().unwrap()).unwrap();
+        assert!(deserialize_token_vocabulary(&bytes[..bytes.len() - 1]).is_err());
+        assert!(deserialize_token_vocabulary(&[bytes.clone(), vec![0]].concat()).is_err());
+        assert!(deserialize_token_vocabulary(b"CTOK").is_err());
+
+        let mut future = bytes;
+        future[4] = TOKEN_LIBRARY_FORMAT_VERSION + 1;
+        assert!(matches!(
+            deserialize_token_vocabulary(&future),
+            Err(SerializationError::UnsupportedVersion { .. })
+        ));
+    }
+}
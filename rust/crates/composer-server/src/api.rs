@@ -0,0 +1,447 @@
+//! JSON API routes and wire types
+//!
+//! | Method | Path            | Body               | Response                |
+//! |--------|-----------------|--------------------|-------------------------|
+//! | GET    | `/v1/health`    | -                  | [`HealthResponse`]      |
+//! | GET    | `/v1/metrics`   | -                  | `EngineMetrics`         |
+//! | POST   | `/v1/suggest`   | [`SuggestRequest`] | [`SuggestResponse`]     |
+//! | POST   | `/v1/assess`    | [`AssessRequest`]  | `DifficultyAssessment`  |
+//! | POST   | `/v1/analyze`   | [`AnalyzeRequest`] | `ProgressionAnalysis`   |
+//! | POST   | `/v1/harmonize` | [`HarmonizeRequest`] | `BassHarmonization`   |
+//!
+//! Chords are sent either as their 10-character hex encoding (`"0a00000000"`) or
+//! as an object `{"root": 5, "chord_type": 7, "inversion": 0}`; responses always
+//! include both forms.
+
+use crate::error::{ServerError, ServerResult};
+use crate::http::{Request, Response};
+use composer_ai::{
+    AiEngine, BassHarmonizationOptions, BassStyle, ChordSuggestion, SuggestionConfig,
+    SuggestionContext,
+};
+use composer_core::Chord;
+use composer_serialization::{
+    chord_binary_to_hex, deserialize_chord, hex_to_chord_binary, serialize_chord,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Chord as accepted in request bodies
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum WireChord {
+    /// Hex encoding of the 5-byte chord binary
+    Hex(String),
+    /// Scale-degree root, chord type and optional inversion
+    Spec {
+        root: u8,
+        chord_type: u8,
+        #[serde(default)]
+        inversion: u8,
+    },
+}
+
+impl WireChord {
+    /// Decode into a chord, validating its fields
+    pub fn to_chord(&self) -> ServerResult<Chord> {
+        match self {
+            WireChord::Hex(hex) => Ok(deserialize_chord(&hex_to_chord_binary(hex)?)?),
+            WireChord::Spec {
+                root,
+                chord_type,
+                inversion,
+            } => {
+                let mut chord = Chord::new(*root, *chord_type)?;
+                if *inversion > 0 {
+                    chord = chord.with_inversion(*inversion)?;
+                }
+                Ok(chord)
+            },
+        }
+    }
+}
+
+/// Chord as returned in response bodies
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChordView {
+    /// Hex encoding of the chord binary
+    pub hex: String,
+    /// Display symbol
+    pub symbol: String,
+    /// Scale-degree root
+    pub root: u8,
+    /// Chord type
+    pub chord_type: u8,
+    /// Inversion
+    pub inversion: u8,
+}
+
+impl ChordView {
+    /// Describe a chord for a response
+    pub fn new(chord: &Chord) -> ServerResult<Self> {
+        Ok(Self {
+            hex: chord_binary_to_hex(&serialize_chord(chord)?),
+            symbol: chord.to_string(),
+            root: chord.root,
+            chord_type: chord.chord_type,
+            inversion: chord.inversion,
+        })
+    }
+}
+
+/// Body of `POST /v1/suggest`; unset options use the engine defaults
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SuggestRequest {
+    /// Progression so far
+    pub pattern: Vec<WireChord>,
+    /// Maximum number of suggestions
+    pub max_suggestions: Option<usize>,
+    /// Minimum confidence threshold (0.0-1.0)
+    pub min_confidence: Option<f64>,
+    /// Sample instead of ranking deterministically
+    pub use_probabilistic: Option<bool>,
+    /// Sampling temperature
+    pub temperature: Option<f64>,
+    /// Position in the progression (0.0-1.0)
+    pub position_in_progression: Option<f64>,
+    /// Target emotional valence (-1.0 to 1.0)
+    pub target_valence: Option<f64>,
+    /// Complexity preference (0.0-1.0)
+    pub complexity_preference: Option<f64>,
+}
+
+/// One suggestion in a [`SuggestResponse`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SuggestionView {
+    /// Suggested chord
+    pub chord: ChordView,
+    /// Confidence (0.0-1.0)
+    pub confidence: f64,
+    /// Final ranking score (0.0-1.0)
+    pub weighted_score: f64,
+    /// Explanation of the ranking
+    pub reasoning: String,
+}
+
+/// Response of `POST /v1/suggest`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SuggestResponse {
+    /// Suggestions, best first
+    pub suggestions: Vec<SuggestionView>,
+}
+
+/// Body of `POST /v1/assess`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssessRequest {
+    /// Progression to assess
+    pub progression: Vec<WireChord>,
+    /// Tempo in beats per minute
+    #[serde(default)]
+    pub tempo_bpm: Option<f64>,
+    /// Time signature as `[beats_per_bar, beat_unit]`
+    #[serde(default)]
+    pub time_signature: Option<(u8, u8)>,
+}
+
+/// Body of `POST /v1/analyze`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalyzeRequest {
+    /// Progression to analyze
+    pub progression: Vec<WireChord>,
+}
+
+/// Body of `POST /v1/harmonize`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarmonizeRequest {
+    /// Progression to harmonize
+    pub progression: Vec<WireChord>,
+    /// Bass style, e.g. `"Walking"`
+    #[serde(default)]
+    pub style: BassStyle,
+    /// Complexity preference (0.0-1.0)
+    #[serde(default = "default_complexity")]
+    pub complexity: f64,
+    /// Allow walking bass lines
+    #[serde(default)]
+    pub enable_walking: bool,
+}
+
+fn default_complexity() -> f64 {
+    BassHarmonizationOptions::default().complexity
+}
+
+/// Response of `GET /v1/health`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthResponse {
+    /// Always `"ok"` when the service answers
+    pub status: String,
+    /// Whether the engine has patterns loaded
+    pub initialized: bool,
+    /// Library version
+    pub version: String,
+}
+
+/// Routes API requests to a shared [`AiEngine`].
+///
+/// The service is transport-agnostic: [`crate::Server`] feeds it requests read
+/// from sockets, and tests or other transports can call [`Self::handle`] directly.
+///
+/// # Examples
+///
+/// ```rust
+/// use composer_ai::{AiEngine, AiEngineConfig};
+/// use composer_server::{ComposerService, Request};
+/// use std::sync::Arc;
+///
+/// let engine = AiEngine::new(AiEngineConfig::default());
+/// engine.initialize(vec![])?;
+/// let service = ComposerService::new(Arc::new(engine));
+///
+/// let body = r#"{"progression": [{"root": 2, "chord_type": 7}, {"root": 5, "chord_type": 7}]}"#;
+/// let response = service.handle(&Request::new("POST", "/v1/assess", body));
+/// assert_eq!(response.status, 200);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone)]
+pub struct ComposerService {
+    engine: Arc<AiEngine>,
+}
+
+impl ComposerService {
+    /// Serve requests from `engine`
+    pub fn new(engine: Arc<AiEngine>) -> Self {
+        Self { engine }
+    }
+
+    /// Engine behind the service
+    pub fn engine(&self) -> &Arc<AiEngine> {
+        &self.engine
+    }
+
+    /// Handle one request, converting failures into JSON error responses
+    pub fn handle(&self, request: &Request) -> Response {
+        match self.route(request) {
+            Ok(response) => response,
+            Err(error) => Response::error(&error),
+        }
+    }
+
+    fn route(&self, request: &Request) -> ServerResult<Response> {
+        let path = request.path.trim_end_matches('/');
+        let expected = match path {
+            "/v1/health" | "/v1/metrics" => "GET",
+            "/v1/suggest" | "/v1/assess" | "/v1/analyze" | "/v1/harmonize" => "POST",
+            _ => {
+                return Err(ServerError::NotFound {
+                    method: request.method.clone(),
+                    path: request.path.clone(),
+                })
+            },
+        };
+        if request.method != expected {
+            return Err(ServerError::MethodNotAllowed {
+                method: request.method.clone(),
+                path: request.path.clone(),
+            });
+        }
+
+        match path {
+            "/v1/health" => ok(&HealthResponse {
+                status: "ok".to_string(),
+                initialized: self.engine.is_initialized(),
+                version: composer_config::APPLICATION.version.to_string(),
+            }),
+            "/v1/metrics" => ok(&self.engine.get_metrics()),
+            "/v1/suggest" => ok(&self.suggest(&parse(request)?)?),
+            "/v1/assess" => {
+                let body: AssessRequest = parse(request)?;
+                ok(&self.engine.assess_difficulty(
+                    &chords(&body.progression)?,
+                    body.tempo_bpm,
+                    body.time_signature,
+                )?)
+            },
+            "/v1/analyze" => {
+                let body: AnalyzeRequest = parse(request)?;
+                ok(&self
+                    .engine
+                    .analyze_progression(&chords(&body.progression)?)?)
+            },
+            _ => {
+                let body: HarmonizeRequest = parse(request)?;
+                let options = BassHarmonizationOptions {
+                    style: body.style,
+                    complexity: body.complexity,
+                    enable_walking: body.enable_walking,
+                    rhythm_pattern: None,
+                    ..Default::default()
+                };
+                ok(&self
+                    .engine
+                    .harmonize_bass_line(&chords(&body.progression)?, &options)?)
+            },
+        }
+    }
+
+    fn suggest(&self, body: &SuggestRequest) -> ServerResult<SuggestResponse> {
+        let pattern = chords(&body.pattern)?;
+
+        let mut config = SuggestionConfig::default();
+        if let Some(max_suggestions) = body.max_suggestions {
+            config.max_suggestions = max_suggestions;
+        }
+        if let Some(min_confidence) = body.min_confidence {
+            config.min_confidence = min_confidence;
+        }
+        if let Some(use_probabilistic) = body.use_probabilistic {
+            config.use_probabilistic = use_probabilistic;
+        }
+        if let Some(temperature) = body.temperature {
+            config.temperature = temperature;
+        }
+
+        let mut context = SuggestionContext::default();
+        if let Some(position) = body.position_in_progression {
+            context.position_in_progression = position;
+        }
+        if let Some(valence) = body.target_valence {
+            context.target_valence = valence;
+        }
+        if let Some(complexity) = body.complexity_preference {
+            context.complexity_preference = complexity;
+        }
+
+        let suggestions = self
+            .engine
+            .get_chord_suggestions(&pattern, &context, &config)?;
+        Ok(SuggestResponse {
+            suggestions: suggestions
+                .iter()
+                .map(suggestion_view)
+                .collect::<ServerResult<_>>()?,
+        })
+    }
+}
+
+fn suggestion_view(suggestion: &ChordSuggestion) -> ServerResult<SuggestionView> {
+    Ok(SuggestionView {
+        chord: ChordView::new(&suggestion.chord)?,
+        confidence: suggestion.confidence,
+        weighted_score: suggestion.weighted_score,
+        reasoning: suggestion.reasoning.clone(),
+    })
+}
+
+fn parse<T: DeserializeOwned>(request: &Request) -> ServerResult<T> {
+    Ok(serde_json::from_slice(&request.body)?)
+}
+
+fn chords(wire: &[WireChord]) -> ServerResult<Vec<Chord>> {
+    wire.iter().map(WireChord::to_chord).collect()
+}
+
+fn ok(value: &impl Serialize) -> ServerResult<Response> {
+    Ok(Response::json(200, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use composer_ai::AiEngineConfig;
+
+    fn service() -> ComposerService {
+        let engine = AiEngine::new(AiEngineConfig::default());
+        let cadence = vec![
+            Chord::new(2, 7).unwrap(),
+            Chord::new(5, 7).unwrap(),
+            Chord::new(1, 7).unwrap(),
+        ];
+        engine
+            .initialize(vec![
+                (cadence.clone(), "ii-V-I".to_string(), None),
+                (cadence, "ii-V-I again".to_string(), None),
+            ])
+            .unwrap();
+        ComposerService::new(Arc::new(engine))
+    }
+
+    #[test]
+    fn test_wire_chords() {
+        let spec: WireChord =
+            serde_json::from_str(r#"{"root": 5, "chord_type": 7, "inversion": 1}"#).unwrap();
+        let chord = spec.to_chord().unwrap();
+        assert_eq!((chord.root, chord.chord_type, chord.inversion), (5, 7, 1));
+
+        let view = ChordView::new(&chord).unwrap();
+        let hex = WireChord::Hex(view.hex.clone());
+        assert_eq!(hex.to_chord().unwrap(), chord);
+
+        let invalid = WireChord::Spec {
+            root: 9,
+            chord_type: 5,
+            inversion: 0,
+        };
+        assert!(matches!(invalid.to_chord(), Err(ServerError::Theory(_))));
+    }
+
+    #[test]
+    fn test_suggest_route() {
+        let service = service();
+        let body = r#"{"pattern": [{"root": 2, "chord_type": 7}, {"root": 5, "chord_type": 7}],
+                       "min_confidence": 0.0, "max_suggestions": 3}"#;
+        let response = service.handle(&Request::new("POST", "/v1/suggest", body));
+        assert_eq!(response.status, 200);
+
+        let parsed: SuggestResponse = serde_json::from_slice(&response.body).unwrap();
+        assert!(!parsed.suggestions.is_empty());
+        assert!(parsed.suggestions.len() <= 3);
+        assert_eq!(parsed.suggestions[0].chord.root, 1);
+    }
+
+    #[test]
+    fn test_analysis_routes() {
+        let service = service();
+        let progression = r#"{"progression": ["0a00000000", {"root": 5, "chord_type": 7}],
+                              "style": "Walking"}"#;
+
+        for path in ["/v1/assess", "/v1/analyze", "/v1/harmonize"] {
+            let response = service.handle(&Request::new("POST", path, progression));
+            assert_eq!(response.status, 200, "{}: {:?}", path, response.json_body());
+        }
+
+        let health = service.handle(&Request::new("GET", "/v1/health", ""));
+        assert_eq!(health.json_body().unwrap()["initialized"], true);
+        let metrics = service.handle(&Request::new("GET", "/v1/metrics/", ""));
+        assert_eq!(metrics.status, 200);
+    }
+
+    #[test]
+    fn test_error_responses() {
+        let service = service();
+        let cases = [
+            ("GET", "/v1/missing", "", 404),
+            ("GET", "/v1/suggest", "", 405),
+            ("POST", "/v1/assess", "not json", 400),
+            ("POST", "/v1/assess", r#"{"progression": []}"#, 422),
+            (
+                "POST",
+                "/v1/assess",
+                r#"{"progression": [{"root": 8, "chord_type": 5}]}"#,
+                400,
+            ),
+        ];
+        for (method, path, body, status) in cases {
+            let response = service.handle(&Request::new(method, path, body));
+            assert_eq!(response.status, status, "{} {} {}", method, path, body);
+            assert!(response.json_body().unwrap()["error"].is_string());
+        }
+
+        let uninitialized =
+            ComposerService::new(Arc::new(AiEngine::new(AiEngineConfig::default())));
+        let response =
+            uninitialized.handle(&Request::new("POST", "/v1/suggest", r#"{"pattern": []}"#));
+        assert_eq!(response.status, 503);
+    }
+}
@@ -0,0 +1,64 @@
+//! Error types for the suggestion service
+
+use composer_ai::AiError;
+use composer_core::ChordTheoryError;
+use composer_serialization::SerializationError;
+use thiserror::Error;
+
+/// Errors raised while serving a request
+#[derive(Debug, Error)]
+pub enum ServerError {
+    #[error("Malformed request: {0}")]
+    BadRequest(String),
+
+    #[error("No route for {method} {path}")]
+    NotFound { method: String, path: String },
+
+    #[error("Method {method} not allowed for {path}")]
+    MethodNotAllowed { method: String, path: String },
+
+    #[error("Request body exceeds {limit} bytes")]
+    PayloadTooLarge { limit: usize },
+
+    #[error("Server is at its limit of {limit} concurrent requests")]
+    Busy { limit: usize },
+
+    #[error("Invalid chord: {0}")]
+    Theory(#[from] ChordTheoryError),
+
+    #[error("Invalid chord encoding: {0}")]
+    Serialization(#[from] SerializationError),
+
+    #[error("AI engine error: {0}")]
+    Ai(#[from] AiError),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl ServerError {
+    /// HTTP status code reported for this error
+    pub fn status_code(&self) -> u16 {
+        match self {
+            ServerError::BadRequest(_) | ServerError::Theory(_) | ServerError::Serialization(_) => {
+                400
+            },
+            ServerError::NotFound { .. } => 404,
+            ServerError::MethodNotAllowed { .. } => 405,
+            ServerError::PayloadTooLarge { .. } => 413,
+            ServerError::Busy { .. } | ServerError::Ai(AiError::EngineNotInitialized) => 503,
+            ServerError::Ai(AiError::InvalidPattern { .. })
+            | ServerError::Ai(AiError::AnalysisFailed { .. }) => 422,
+            ServerError::Ai(_) | ServerError::Io(_) => 500,
+        }
+    }
+}
+
+impl From<serde_json::Error> for ServerError {
+    fn from(error: serde_json::Error) -> Self {
+        ServerError::BadRequest(error.to_string())
+    }
+}
+
+/// Result type for the suggestion service
+pub type ServerResult<T> = Result<T, ServerError>;
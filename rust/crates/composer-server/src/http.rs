@@ -0,0 +1,248 @@
+//! Minimal HTTP/1.1 request parsing and response writing
+//!
+//! The service speaks a deliberately small subset of HTTP: one request per
+//! connection, bodies sized by `Content-Length`, and JSON responses. That is enough
+//! for load balancers, health checks and the JSON API without pulling an async
+//! runtime into the workspace.
+
+use crate::error::{ServerError, ServerResult};
+use serde::Serialize;
+use std::io::{BufRead, Read, Write};
+
+/// Longest accepted request or header line
+const MAX_LINE_BYTES: usize = 8 * 1024;
+
+/// Most headers accepted on one request
+const MAX_HEADERS: usize = 64;
+
+/// Parsed HTTP request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    /// Request method, e.g. `POST`
+    pub method: String,
+    /// Request path without the query string
+    pub path: String,
+    /// Headers with lower-cased names
+    pub headers: Vec<(String, String)>,
+    /// Raw request body
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// Build a request in memory, e.g. to call the service without a socket
+    pub fn new(method: &str, path: &str, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            method: method.to_string(),
+            path: path.to_string(),
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    /// Value of a header, matched case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let name = name.to_ascii_lowercase();
+        self.headers
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// HTTP response with a JSON body
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    /// Status code
+    pub status: u16,
+    /// Extra headers besides `Content-Type`, `Content-Length` and `Connection`
+    pub headers: Vec<(String, String)>,
+    /// JSON body
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// Serialize `value` as a JSON response
+    pub fn json(status: u16, value: &impl Serialize) -> Self {
+        let body = serde_json::to_vec(value)
+            .unwrap_or_else(|_| br#"{"error":"Failed to encode response"}"#.to_vec());
+        Self {
+            status,
+            headers: Vec::new(),
+            body,
+        }
+    }
+
+    /// JSON error response for `error`
+    pub fn error(error: &ServerError) -> Self {
+        let status = error.status_code();
+        let mut response = Self::json(
+            status,
+            &serde_json::json!({ "error": error.to_string(), "status": status }),
+        );
+        if status == 503 {
+            response
+                .headers
+                .push(("Retry-After".to_string(), "1".to_string()));
+        }
+        response
+    }
+
+    /// Decode the body as JSON
+    pub fn json_body(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::from_slice(&self.body)
+    }
+}
+
+/// Read one request, rejecting bodies larger than `max_body_bytes`
+pub fn read_request(reader: &mut impl BufRead, max_body_bytes: usize) -> ServerResult<Request> {
+    let request_line = read_line(reader)?;
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(method), Some(target), Some(version)) if version.starts_with("HTTP/1.") => {
+            (method.to_string(), target)
+        },
+        _ => {
+            return Err(ServerError::BadRequest(format!(
+                "Invalid request line `{}`",
+                request_line
+            )))
+        },
+    };
+    let path = target.split('?').next().unwrap_or(target).to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let line = read_line(reader)?;
+        if line.is_empty() {
+            break;
+        }
+        if headers.len() == MAX_HEADERS {
+            return Err(ServerError::BadRequest("Too many headers".to_string()));
+        }
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| ServerError::BadRequest(format!("Invalid header `{}`", line)))?;
+        headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+    }
+
+    let mut request = Request {
+        method,
+        path,
+        headers,
+        body: Vec::new(),
+    };
+    let length = match request.header("content-length") {
+        Some(value) => value
+            .parse::<usize>()
+            .map_err(|_| ServerError::BadRequest(format!("Invalid Content-Length `{}`", value)))?,
+        None => 0,
+    };
+    if length > max_body_bytes {
+        return Err(ServerError::PayloadTooLarge {
+            limit: max_body_bytes,
+        });
+    }
+    request.body = vec![0; length];
+    reader.read_exact(&mut request.body)?;
+    Ok(request)
+}
+
+/// Write a response and mark the connection for closing
+pub fn write_response(writer: &mut impl Write, response: &Response) -> std::io::Result<()> {
+    write!(
+        writer,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        reason_phrase(response.status),
+        response.body.len()
+    )?;
+    for (name, value) in &response.headers {
+        write!(writer, "{}: {}\r\n", name, value)?;
+    }
+    writer.write_all(b"\r\n")?;
+    writer.write_all(&response.body)?;
+    writer.flush()
+}
+
+fn read_line(reader: &mut impl BufRead) -> ServerResult<String> {
+    let mut line = Vec::new();
+    let read = reader
+        .by_ref()
+        .take(MAX_LINE_BYTES as u64 + 1)
+        .read_until(b'\n', &mut line)?;
+    if read == 0 {
+        return Err(ServerError::BadRequest(
+            "Connection closed before the request was complete".to_string(),
+        ));
+    }
+    if line.len() > MAX_LINE_BYTES {
+        return Err(ServerError::BadRequest("Request line too long".to_string()));
+    }
+    let line = String::from_utf8(line)
+        .map_err(|_| ServerError::BadRequest("Request head is not UTF-8".to_string()))?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        422 => "Unprocessable Entity",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_request() {
+        let raw =
+            b"POST /v1/assess?debug=1 HTTP/1.1\r\nHost: x\r\nContent-Length: 2\r\n\r\n{}extra";
+        let request = read_request(&mut &raw[..], 1024).unwrap();
+
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/v1/assess");
+        assert_eq!(request.header("HOST"), Some("x"));
+        assert_eq!(request.body, b"{}");
+    }
+
+    #[test]
+    fn test_read_request_rejects_bad_input() {
+        let too_large = b"POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\n0123456789";
+        assert!(matches!(
+            read_request(&mut &too_large[..], 4),
+            Err(ServerError::PayloadTooLarge { limit: 4 })
+        ));
+
+        for raw in [
+            &b"GARBAGE\r\n\r\n"[..],
+            b"GET / HTTP/1.1\r\nNoColon\r\n\r\n",
+            b"",
+        ] {
+            assert!(matches!(
+                read_request(&mut &raw[..], 1024),
+                Err(ServerError::BadRequest(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_write_response() {
+        let mut response = Response::error(&ServerError::Busy { limit: 2 });
+        response.body = b"{}".to_vec();
+        let mut out = Vec::new();
+        write_response(&mut out, &response).unwrap();
+
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+        assert!(text.contains("Content-Length: 2\r\n"));
+        assert!(text.contains("Retry-After: 1\r\n"));
+        assert!(text.ends_with("\r\n\r\n{}"));
+    }
+}
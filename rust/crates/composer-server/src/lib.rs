@@ -0,0 +1,28 @@
+//! HTTP suggestion service for the Composer AI engine
+//!
+//! This crate wraps a shared [`composer_ai::AiEngine`] behind a small JSON-over-HTTP
+//! API so that teams can deploy Composer as one service instead of loading the
+//! pattern trie into every process. The routes are listed in [`api`].
+//!
+//! The transport is a blocking HTTP/1.1 listener built on `std::net`; the number of
+//! requests processed at once is capped at `MAX_CONCURRENT_REQUESTS` (see
+//! [`ServerConfig`]) and additional requests are rejected with `503` so load
+//! balancers can retry elsewhere.
+//!
+//! The `composer-server` binary serves an engine loaded from a trie binary:
+//!
+//! ```text
+//! composer-server --listen 0.0.0.0:8080 --trie patterns.bin
+//! ```
+
+pub mod api;
+pub mod error;
+pub mod http;
+pub mod limiter;
+pub mod server;
+
+pub use api::*;
+pub use error::*;
+pub use http::*;
+pub use limiter::*;
+pub use server::*;
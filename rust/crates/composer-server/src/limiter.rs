@@ -0,0 +1,78 @@
+//! Concurrency limiting for in-flight requests
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Non-blocking counting semaphore that caps requests in flight.
+///
+/// Requests beyond the limit are rejected immediately with `503 Service
+/// Unavailable` instead of queueing, so a saturated instance sheds load to its
+/// peers behind a load balancer rather than building latency.
+#[derive(Debug)]
+pub struct ConcurrencyLimiter {
+    limit: usize,
+    in_flight: AtomicUsize,
+}
+
+impl ConcurrencyLimiter {
+    /// Create a limiter admitting at most `limit` concurrent requests (at least one)
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit: limit.max(1),
+            in_flight: AtomicUsize::new(0),
+        }
+    }
+
+    /// Reserve a slot, or return `None` when the limit is reached
+    pub fn try_acquire(self: &Arc<Self>) -> Option<Permit> {
+        self.in_flight
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |current| {
+                (current < self.limit).then_some(current + 1)
+            })
+            .ok()
+            .map(|_| Permit {
+                limiter: Arc::clone(self),
+            })
+    }
+
+    /// Maximum number of concurrent requests
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Requests currently holding a permit
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Acquire)
+    }
+}
+
+/// Slot held for the duration of one request; released on drop
+#[derive(Debug)]
+pub struct Permit {
+    limiter: Arc<ConcurrencyLimiter>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.limiter.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permits_are_bounded_and_released() {
+        let limiter = Arc::new(ConcurrencyLimiter::new(2));
+        let first = limiter.try_acquire().unwrap();
+        let _second = limiter.try_acquire().unwrap();
+        assert!(limiter.try_acquire().is_none());
+        assert_eq!(limiter.in_flight(), 2);
+
+        drop(first);
+        assert_eq!(limiter.in_flight(), 1);
+        assert!(limiter.try_acquire().is_some());
+        assert_eq!(ConcurrencyLimiter::new(0).limit(), 1);
+    }
+}
@@ -0,0 +1,110 @@
+//! `composer-server` binary: serve the Composer JSON API over HTTP
+
+use composer_ai::{AiEngine, AiEngineConfig};
+use composer_config::ComposerConfig;
+use composer_server::{ComposerService, Server, ServerConfig};
+use std::process::ExitCode;
+use std::sync::Arc;
+
+const USAGE: &str = "\
+Usage: composer-server [OPTIONS]
+
+Options:
+  --listen <ADDR>          Address to listen on [default: 127.0.0.1:8080]
+  --trie <PATH>            Trie binary to load patterns from
+  --config <PATH>          TOML file overriding engine thresholds and limits
+  --max-concurrent <N>     Requests processed at once [default: MAX_CONCURRENT_REQUESTS]
+  --max-body-bytes <N>     Largest accepted request body [default: 1048576]
+  -h, --help               Print this help
+
+Environment:
+  COMPOSER__<SECTION>__<FIELD>  Override one configuration field, after --config";
+
+struct Options {
+    listen: String,
+    trie: Option<String>,
+    engine_config: Option<String>,
+    config: ServerConfig,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        listen: "127.0.0.1:8080".to_string(),
+        trie: None,
+        engine_config: None,
+        config: ServerConfig::default(),
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("{} requires a value", arg))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--listen" => options.listen = value()?,
+            "--trie" => options.trie = Some(value()?),
+            "--config" => options.engine_config = Some(value()?),
+            "--max-concurrent" => {
+                options.config.max_concurrent_requests = value()?
+                    .parse()
+                    .map_err(|e| format!("--max-concurrent: {}", e))?
+            },
+            "--max-body-bytes" => {
+                options.config.max_body_bytes = value()?
+                    .parse()
+                    .map_err(|e| format!("--max-body-bytes: {}", e))?
+            },
+            _ => return Err(format!("Unknown argument `{}`", arg)),
+        }
+    }
+    Ok(Some(options))
+}
+
+fn run(options: Options) -> Result<(), String> {
+    let mut runtime = ComposerConfig::builder().with_env();
+    if let Some(path) = &options.engine_config {
+        runtime = runtime.with_toml_file(path);
+    }
+    let runtime = runtime.build().map_err(|e| e.to_string())?;
+    let engine = AiEngine::new(AiEngineConfig::from_runtime(runtime));
+    match &options.trie {
+        Some(path) => {
+            let bytes = std::fs::read(path).map_err(|e| format!("Reading {}: {}", path, e))?;
+            engine
+                .initialize_from_binary(&bytes)
+                .map_err(|e| format!("Loading {}: {}", path, e))?;
+        },
+        None => engine.initialize(vec![]).map_err(|e| e.to_string())?,
+    }
+
+    let service = ComposerService::new(Arc::new(engine));
+    let server = Server::bind(&options.listen, service, options.config)
+        .map_err(|e| format!("Binding {}: {}", options.listen, e))?;
+    eprintln!(
+        "composer-server {} listening on {} (max {} concurrent requests)",
+        composer_config::APPLICATION.version,
+        server.local_addr().map_err(|e| e.to_string())?,
+        server.limiter().limit()
+    );
+    server.serve().map_err(|e| e.to_string())
+}
+
+fn main() -> ExitCode {
+    match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => match run(options) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(message) => {
+                eprintln!("error: {}", message);
+                ExitCode::FAILURE
+            },
+        },
+        Ok(None) => {
+            println!("{}", USAGE);
+            ExitCode::SUCCESS
+        },
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            ExitCode::from(2)
+        },
+    }
+}
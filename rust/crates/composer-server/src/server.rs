@@ -0,0 +1,270 @@
+//! TCP listener that feeds HTTP requests to a [`ComposerService`]
+
+use crate::api::ComposerService;
+use crate::error::ServerError;
+use crate::http::{read_request, write_response, Response};
+use crate::limiter::ConcurrencyLimiter;
+use composer_config::PROCESSING;
+use std::io::{BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How long a rejected connection may take to deliver its request head
+const REJECT_DRAIN_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// First pause after a transient `accept` failure; doubles up to [`ACCEPT_BACKOFF_MAX`]
+const ACCEPT_BACKOFF_MIN: Duration = Duration::from_millis(5);
+
+/// Longest pause between `accept` retries
+const ACCEPT_BACKOFF_MAX: Duration = Duration::from_secs(1);
+
+/// Server configuration
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// Maximum requests processed at once; further requests get `503`
+    pub max_concurrent_requests: usize,
+
+    /// Largest accepted request body in bytes
+    pub max_body_bytes: usize,
+
+    /// Socket read and write timeout
+    pub io_timeout: Duration,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_requests: PROCESSING.max_concurrent_requests as usize,
+            max_body_bytes: 1024 * 1024,
+            io_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Blocking HTTP server with one worker thread per admitted request.
+///
+/// Concurrency is capped by [`ServerConfig::max_concurrent_requests`], which
+/// defaults to `MAX_CONCURRENT_REQUESTS` from the shared configuration. A
+/// connection arriving while the server is saturated is answered with
+/// `503 Service Unavailable` and `Retry-After` on the accept thread, without
+/// spawning a worker.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use composer_ai::{AiEngine, AiEngineConfig};
+/// use composer_server::{ComposerService, Server, ServerConfig};
+/// use std::sync::Arc;
+///
+/// let engine = AiEngine::new(AiEngineConfig::default());
+/// engine.initialize(vec![])?;
+///
+/// let service = ComposerService::new(Arc::new(engine));
+/// let server = Server::bind("127.0.0.1:8080", service, ServerConfig::default())?;
+/// server.serve()?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct Server {
+    listener: TcpListener,
+    service: Arc<ComposerService>,
+    limiter: Arc<ConcurrencyLimiter>,
+    config: ServerConfig,
+}
+
+impl Server {
+    /// Bind a listener on `addr`
+    pub fn bind(
+        addr: impl ToSocketAddrs,
+        service: ComposerService,
+        config: ServerConfig,
+    ) -> std::io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            service: Arc::new(service),
+            limiter: Arc::new(ConcurrencyLimiter::new(config.max_concurrent_requests)),
+            config,
+        })
+    }
+
+    /// Address the server is listening on
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Limiter shared by all connections
+    pub fn limiter(&self) -> &Arc<ConcurrencyLimiter> {
+        &self.limiter
+    }
+
+    /// Accept connections until the listener fails.
+    ///
+    /// Transient `accept` errors (a client resetting before the handshake
+    /// completes, an interrupted call) are logged and retried after a short
+    /// backoff; anything else, including running out of file descriptors, is
+    /// returned.
+    pub fn serve(&self) -> std::io::Result<()> {
+        let mut backoff = ACCEPT_BACKOFF_MIN;
+        for stream in self.listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => {
+                    backoff = ACCEPT_BACKOFF_MIN;
+                    stream
+                },
+                Err(error) if is_transient_accept_error(&error) => {
+                    eprintln!("accept failed: {}; retrying in {:?}", error, backoff);
+                    std::thread::sleep(backoff);
+                    backoff = (backoff * 2).min(ACCEPT_BACKOFF_MAX);
+                    continue;
+                },
+                Err(error) => return Err(error),
+            };
+            let _ = stream.set_read_timeout(Some(self.config.io_timeout));
+            let _ = stream.set_write_timeout(Some(self.config.io_timeout));
+
+            let Some(permit) = self.limiter.try_acquire() else {
+                let busy = ServerError::Busy {
+                    limit: self.limiter.limit(),
+                };
+                reject(stream, &Response::error(&busy));
+                continue;
+            };
+
+            let service = Arc::clone(&self.service);
+            let max_body_bytes = self.config.max_body_bytes;
+            std::thread::spawn(move || {
+                let _permit = permit;
+                handle_connection(stream, &service, max_body_bytes);
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Whether an `accept` error concerns only the connection being accepted
+fn is_transient_accept_error(error: &std::io::Error) -> bool {
+    use std::io::ErrorKind;
+    matches!(
+        error.kind(),
+        ErrorKind::ConnectionAborted
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionRefused
+            | ErrorKind::Interrupted
+            | ErrorKind::WouldBlock
+            | ErrorKind::TimedOut
+    )
+}
+
+/// Answer a connection without a worker thread.
+///
+/// Whatever part of the request has already arrived is drained first (waiting at
+/// most briefly), since closing a socket with unread data resets the connection
+/// before the client sees the response.
+fn reject(mut stream: TcpStream, response: &Response) {
+    let _ = stream.set_read_timeout(Some(REJECT_DRAIN_TIMEOUT));
+    let mut scratch = [0u8; 4096];
+    let _ = stream.read(&mut scratch);
+    let _ = write_response(&mut stream, response);
+    let _ = stream.shutdown(Shutdown::Write);
+}
+
+fn handle_connection(stream: TcpStream, service: &ComposerService, max_body_bytes: usize) {
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(_) => return,
+    };
+    let response = match read_request(&mut BufReader::new(stream), max_body_bytes) {
+        Ok(request) => service.handle(&request),
+        Err(error) => Response::error(&error),
+    };
+    let _ = write_response(&mut writer, &response);
+    let _ = writer.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use composer_ai::{AiEngine, AiEngineConfig};
+
+    fn send(addr: SocketAddr, raw: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(raw.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    fn start(config: ServerConfig) -> (SocketAddr, Arc<ConcurrencyLimiter>) {
+        let engine = AiEngine::new(AiEngineConfig::default());
+        engine.initialize(vec![]).unwrap();
+        let server = Server::bind(
+            "127.0.0.1:0",
+            ComposerService::new(Arc::new(engine)),
+            config,
+        )
+        .unwrap();
+        let addr = server.local_addr().unwrap();
+        let limiter = Arc::clone(server.limiter());
+        std::thread::spawn(move || server.serve());
+        (addr, limiter)
+    }
+
+    #[test]
+    fn test_serves_requests_over_tcp() {
+        let (addr, _) = start(ServerConfig::default());
+
+        let health = send(addr, "GET /v1/health HTTP/1.1\r\nHost: test\r\n\r\n");
+        assert!(health.starts_with("HTTP/1.1 200 OK"));
+        assert!(health.contains(r#""status":"ok""#));
+
+        let body = r#"{"progression": [{"root": 1, "chord_type": 5}]}"#;
+        let assess = send(
+            addr,
+            &format!(
+                "POST /v1/assess HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            ),
+        );
+        assert!(assess.starts_with("HTTP/1.1 200 OK"));
+        assert!(assess.contains("overall_score"));
+
+        let malformed = send(addr, "NONSENSE\r\n\r\n");
+        assert!(malformed.starts_with("HTTP/1.1 400 Bad Request"));
+    }
+
+    #[test]
+    fn test_rejects_requests_over_the_limit() {
+        let (addr, limiter) = start(ServerConfig {
+            max_concurrent_requests: 1,
+            ..Default::default()
+        });
+
+        // Hold the only slot; the server must shed the next connection
+        let held = limiter.try_acquire().unwrap();
+        let response = send(addr, "GET /v1/health HTTP/1.1\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 503 Service Unavailable"));
+        assert!(response.contains("Retry-After: 1"));
+
+        drop(held);
+        let response = send(addr, "GET /v1/health HTTP/1.1\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+    }
+
+    #[test]
+    fn test_accept_errors_are_classified() {
+        use std::io::{Error, ErrorKind};
+
+        assert!(is_transient_accept_error(&Error::from(
+            ErrorKind::ConnectionAborted
+        )));
+        assert!(is_transient_accept_error(&Error::from(
+            ErrorKind::Interrupted
+        )));
+        // EMFILE: the process is out of file descriptors
+        assert!(!is_transient_accept_error(&Error::from_raw_os_error(24)));
+        assert!(!is_transient_accept_error(&Error::from(
+            ErrorKind::InvalidInput
+        )));
+    }
+}
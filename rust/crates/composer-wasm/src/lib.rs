@@ -3,18 +3,407 @@
 //! This crate provides WASM bindings that allow the Composer library to be used
 //! from JavaScript in web browsers and Node.js environments.
 
-use composer_ai::{AiEngine, AiEngineConfig, ChordProgressionTrie, ChordSuggestion};
+use composer_ai::{
+    AiEngine, AiEngineConfig, BudgetMode, ChordProgressionTrie, ChordSuggestion, ComposerError,
+    PerformanceBudget,
+};
 use composer_core::{
-    get_chord_complexity, get_relative_chord_graphic, get_stable_scale_degrees, Chord,
-    ScaleFingerprint,
+    get_chord_complexity, get_relative_chord_graphic, get_stable_scale_degrees,
+    validate_modification, Chord, ChordModification, ChordTheoryResult, Key, Mode,
+    ModificationConflict, ScaleFingerprint,
 };
 use composer_serialization::{
-    chord_binary_to_hex, deserialize_chord, hex_to_chord_binary, parse_duration_token,
-    serialize_chord, tokenize_duration, Note, Timeline, TokenEvent, TokenEventType,
+    chord_binary_to_hex, chord_from_json, chord_to_json, deserialize_chord, hex_to_chord_binary,
+    parse_duration_token, scale_fingerprint_from_json, scale_fingerprint_to_json, serialize_chord,
+    timeline_from_json, timeline_to_json, tokenize_duration, validate_chord_hex, MeterChange, Note,
+    Timeline, TokenEvent, TokenEventType, ValidationMode,
 };
-use console_error_panic_hook;
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
+#[cfg(feature = "tracing")]
+mod trace;
+mod views;
+
+/// wasm-bindgen-rayon's `initThreadPool(numThreads)`, for pages that start the
+/// worker pool themselves; follow it with `setParallelScoring(true)`, or use
+/// `configureThreadPool`, which does both
+#[cfg(feature = "threads")]
+pub use wasm_bindgen_rayon::init_thread_pool;
+
+use views::{
+    error_details_view, ChordBinaryReportView, CompactionStatsView, ConflictView,
+    DifficultyAssessmentView, EngineMetricsView, PerformanceBudgetView, SuggestionView,
+    TrieStatisticsView,
+};
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_RESULT_TYPES: &'static str = r#"
+/** Error thrown for every failure raised by the Rust core */
+export interface ComposerError extends Error {
+  name: "ComposerError";
+  /** Stable error code, such as "invalid-chord-root" */
+  code: string;
+  domain: "theory" | "serialization" | "ai";
+  /** Fields of the error, such as { root: 9 } for an invalid chord root */
+  details: Record<string, unknown>;
+}
+
+export type ChordInput =
+  | WasmChord
+  | string
+  | {
+      root: number;
+      chordType: number;
+      inversion?: number;
+      applied?: number;
+      alterations?: string[];
+      suspensions?: number[];
+      adds?: number[];
+      omits?: number[];
+    };
+
+export interface TrainingPattern {
+  chords: ChordInput[];
+  name: string;
+  key?: string;
+}
+
+export interface Reference {
+  id: string;
+  keyTonic?: string | null;
+}
+
+export interface PatternResult {
+  serializedChord: number[];
+  count: number;
+  durationWeight: number;
+  meanDuration: number | null;
+  rank: number;
+  relativeCount: number;
+  idList: Reference[];
+  weight: number;
+  branch: string | null;
+}
+
+export interface ChordSuggestion {
+  /** The chord in its JSON form, as from WasmChord.toJson() */
+  chord: Record<string, unknown>;
+  chordHex: string;
+  confidence: number;
+  frequencyScore: number;
+  contextScore: number;
+  theoryScore: number;
+  weightedScore: number;
+  patternInfo: PatternResult;
+  reasoning: string;
+  recommendedInversion: number | null;
+  suggestedDuration: number | null;
+  patternCount: number;
+  rank: number;
+  relativeCount: number;
+  matchedContextLength: number;
+  sources: Reference[];
+}
+
+export interface ComplexityFactors {
+  uniqueChords: number;
+  avgChordComplexity: number;
+  keyChanges: number;
+  uncommonProgressions: number;
+  timeSignatureChanges: number;
+  extendedHarmonies: number;
+  voiceLeadingComplexity: number;
+  tempoVariations: number;
+}
+
+export type SkillLevel = "Beginner" | "Intermediate" | "Advanced" | "Expert";
+
+export interface DifficultyAssessment {
+  overallScore: number;
+  harmonicComplexity: number;
+  rhythmicComplexity: number;
+  technicalComplexity: number;
+  melodicComplexity: number;
+  factors: ComplexityFactors;
+  confidence: number;
+  scoreInterval: ConfidenceInterval;
+  evidence: number;
+  insufficientData: boolean;
+  skillLevel: SkillLevel;
+  skillBand: string;
+  explanation: DifficultyExplanation;
+  instrument: DifficultyProfile | null;
+  percentile: number | null;
+  perChordBreakdown: ChordDifficultyContribution[];
+  melody: MelodicAnalysis | null;
+}
+
+export interface MelodicAnalysis {
+  noteCount: number;
+  lowestPitch: number;
+  highestPitch: number;
+  range: number;
+  tessitura: [number, number];
+  meanPitch: number;
+  intervalHistogram: number[];
+  contourEntropy: number;
+  rhythmDensity: number;
+  complexity: number;
+}
+
+export interface ChordDifficultyContribution {
+  index: number;
+  complexity: number;
+  voiceLeadingCost: number;
+  novelty: number;
+}
+
+export type DifficultyProfile = "Piano" | "Guitar" | "Ukulele" | "Voice";
+
+export interface ConfidenceInterval {
+  lower: number;
+  upper: number;
+}
+
+export interface FactorContribution {
+  feature: string;
+  value: number;
+  weight: number;
+  share: number;
+}
+
+export interface FeatureComparison {
+  feature: string;
+  value: number;
+  referenceMean: number;
+  difference: number;
+}
+
+export interface ReferenceComparison {
+  skillLevel: SkillLevel;
+  references: string[];
+  referenceScore: number;
+  scoreDifference: number;
+  features: FeatureComparison[];
+}
+
+export interface DifficultyExplanation {
+  topFactors: FactorContribution[];
+  reference: ReferenceComparison;
+}
+
+export interface EngineMetrics {
+  totalRequests: number;
+  avgResponseTimeMs: number;
+  memoryUsageBytes: number;
+  cacheHitRate: number;
+  totalPatterns: number;
+  uptimeSeconds: number;
+  /** Calls over their performance budget, by camelCased operation name */
+  budgetViolations: Record<string, number>;
+}
+
+export interface PerformanceBudget {
+  chordLookupMaxMs: number;
+  assetLoadingMaxMs: number;
+  /** Limit for a suggestion call, thread pool included, as seen by the page */
+  uiResponseMaxMs: number;
+  /** "Warn" returns results from slow calls, "Fail" throws */
+  mode: "Warn" | "Fail";
+}
+
+export interface TrieStatistics {
+  totalPatterns: number;
+  totalNodes: number;
+  memoryUsageBytes: number;
+  scaleBranches: number;
+  maxDepth: number;
+  avgBranchingFactor: number;
+  duplicatePatterns: number;
+}
+
+export interface CompactionStats {
+  nodes: number;
+  bytesBefore: number;
+  bytesAfter: number;
+}
+
+export interface ChordBinaryField {
+  name: string;
+  byte: number;
+  mask: number;
+  raw: number;
+  value: string;
+  status: "valid" | "warning" | "invalid";
+  message: string;
+}
+
+export interface ChordBinaryReport {
+  valid: boolean;
+  hex: string;
+  mode: "strict" | "lenient";
+  fields: ChordBinaryField[];
+  chordError: string | null;
+}
+
+export interface ModificationConflict {
+  reason: "InvalidValue" | "Incompatible";
+  existing: string | null;
+}
+"#;
+
+/// Convert a serializable Rust value, usually one of the [`views`], into a
+/// plain JavaScript object
+fn to_js_value<T: Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Milliseconds on a monotonic-enough clock, for timing calls made from the page
+fn now_ms() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        js_sys::Date::now()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        static START: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+        START
+            .get_or_init(std::time::Instant::now)
+            .elapsed()
+            .as_secs_f64()
+            * 1000.0
+    }
+}
+
+/// Convert a Rust error into a JavaScript `Error` with its stable code attached.
+///
+/// The thrown object matches the `ComposerError` TypeScript interface, so callers
+/// can branch on `error.code` instead of parsing the message.
+fn to_js_error(error: impl Into<ComposerError>) -> JsValue {
+    let error = error.into();
+    let js_error = js_sys::Error::new(&error.to_string());
+    js_error.set_name("ComposerError");
+    let details = to_js_value(&error_details_view(error.details())).unwrap_or(JsValue::UNDEFINED);
+    for (key, value) in [
+        ("code", JsValue::from_str(error.code())),
+        ("domain", JsValue::from_str(error.domain())),
+        ("details", details),
+    ] {
+        let _ = js_sys::Reflect::set(&js_error, &JsValue::from_str(key), &value);
+    }
+    js_error.into()
+}
+
+/// Performance budget fields accepted from JavaScript; missing fields keep
+/// their current values
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BudgetSpec {
+    chord_lookup_max_ms: Option<u64>,
+    asset_loading_max_ms: Option<u64>,
+    ui_response_max_ms: Option<u64>,
+    mode: Option<BudgetMode>,
+}
+
+/// Plain-object chord description accepted from JavaScript
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChordSpec {
+    root: u8,
+    chord_type: u8,
+    #[serde(default)]
+    inversion: u8,
+    #[serde(default)]
+    applied: u8,
+    #[serde(default)]
+    alterations: Vec<String>,
+    #[serde(default)]
+    suspensions: Vec<u8>,
+    #[serde(default)]
+    adds: Vec<u8>,
+    #[serde(default)]
+    omits: Vec<u8>,
+}
+
+impl ChordSpec {
+    /// Build a validated chord from the description
+    fn into_chord(self) -> ChordTheoryResult<Chord> {
+        let mut chord = Chord::new(self.root, self.chord_type)?.with_inversion(self.inversion)?;
+        if self.applied > 0 {
+            chord = chord.with_applied(self.applied)?;
+        }
+        for alteration in &self.alterations {
+            chord = chord.with_alteration(alteration)?;
+        }
+        for suspension in self.suspensions {
+            chord = chord.with_suspension(suspension)?;
+        }
+        for add in self.adds {
+            chord = chord.with_add(add)?;
+        }
+        for omit in self.omits {
+            chord = chord.with_omit(omit)?;
+        }
+        Ok(chord)
+    }
+}
+
+/// Decode a chord from its hex encoding
+fn chord_from_hex(hex: &str) -> Result<Chord, JsValue> {
+    let binary = hex_to_chord_binary(hex).map_err(to_js_error)?;
+    deserialize_chord(&binary).map_err(to_js_error)
+}
+
+/// Convert a JavaScript chord value into a Rust chord.
+///
+/// Accepts `WasmChord` instances, plain `{root, chordType, ...}` objects, and
+/// hex strings produced by `WasmChord.toHex()`.
+fn chord_from_js(value: &JsValue) -> Result<Chord, JsValue> {
+    if let Some(hex) = value.as_string() {
+        return chord_from_hex(&hex);
+    }
+
+    if value.is_object() {
+        // WasmChord instances are opaque handles; round-trip through their hex form
+        // so every modification (alterations, suspensions, ...) is preserved.
+        let to_hex = js_sys::Reflect::get(value, &JsValue::from_str("toHex"))?;
+        if let Some(to_hex) = to_hex.dyn_ref::<js_sys::Function>() {
+            let hex = to_hex
+                .call0(value)?
+                .as_string()
+                .ok_or_else(|| JsValue::from_str("WasmChord.toHex() did not return a string"))?;
+            return chord_from_hex(&hex);
+        }
+
+        let spec: ChordSpec = serde_wasm_bindgen::from_value(value.clone())
+            .map_err(|e| JsValue::from_str(&format!("Invalid chord object: {}", e)))?;
+        return spec.into_chord().map_err(to_js_error);
+    }
+
+    Err(JsValue::from_str(
+        "Expected a WasmChord, a {root, chordType} object, or a chord hex string",
+    ))
+}
+
+/// Convert a list of JavaScript chord values into Rust chords
+fn chords_from_js(values: &[JsValue]) -> Result<Vec<Chord>, JsValue> {
+    values.iter().map(chord_from_js).collect()
+}
+
+/// Parse an optional key name such as "F#m" from JavaScript
+fn key_from_js(key: Option<String>) -> Result<Option<Key>, JsValue> {
+    key.map(|key| key.parse()).transpose().map_err(to_js_error)
+}
+
+/// Parse a mode or scale name such as "minor" from JavaScript
+fn mode_from_js(name: &str) -> Result<Mode, JsValue> {
+    name.parse().map_err(to_js_error)
+}
+
 // Set up console error reporting
 #[wasm_bindgen(start)]
 pub fn main() {
@@ -84,7 +473,7 @@ impl WasmChord {
     /// - `getChordComplexity()` - Calculate harmonic complexity
     #[wasm_bindgen(constructor)]
     pub fn new(root: u8, chord_type: u8) -> Result<WasmChord, JsValue> {
-        let chord = Chord::new(root, chord_type).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let chord = Chord::new(root, chord_type).map_err(to_js_error)?;
         Ok(WasmChord { inner: chord })
     }
 
@@ -99,14 +488,14 @@ impl WasmChord {
     /// Create a triad
     #[wasm_bindgen(js_name = "triad")]
     pub fn triad(root: u8) -> Result<WasmChord, JsValue> {
-        let chord = Chord::triad(root).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let chord = Chord::triad(root).map_err(to_js_error)?;
         Ok(WasmChord { inner: chord })
     }
 
     /// Create a seventh chord
     #[wasm_bindgen(js_name = "seventh")]
     pub fn seventh(root: u8) -> Result<WasmChord, JsValue> {
-        let chord = Chord::seventh(root).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let chord = Chord::seventh(root).map_err(to_js_error)?;
         Ok(WasmChord { inner: chord })
     }
 
@@ -159,10 +548,20 @@ impl WasmChord {
             .inner
             .clone()
             .with_alteration(alteration)
-            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+            .map_err(to_js_error)?;
         Ok(WasmChord { inner: chord })
     }
 
+    /// Alterations that can be added without a conflict, including present ones
+    #[wasm_bindgen(js_name = "compatibleAlterations")]
+    pub fn compatible_alterations(&self) -> Vec<String> {
+        self.inner
+            .compatible_alterations()
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+
     /// Set inversion level
     #[wasm_bindgen(js_name = "withInversion")]
     pub fn with_inversion(&self, inversion: u8) -> Result<WasmChord, JsValue> {
@@ -170,12 +569,13 @@ impl WasmChord {
             .inner
             .clone()
             .with_inversion(inversion)
-            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+            .map_err(to_js_error)?;
         Ok(WasmChord { inner: chord })
     }
 
     /// Convert to string representation
     #[wasm_bindgen(js_name = "toString")]
+    #[allow(clippy::inherent_to_string)]
     pub fn to_string(&self) -> String {
         format!("{}", self.inner)
     }
@@ -183,15 +583,28 @@ impl WasmChord {
     /// Serialize to hex string
     #[wasm_bindgen(js_name = "toHex")]
     pub fn to_hex(&self) -> Result<String, JsValue> {
-        let binary = serialize_chord(&self.inner).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let binary = serialize_chord(&self.inner).map_err(to_js_error)?;
         Ok(chord_binary_to_hex(&binary))
     }
 
     /// Deserialize from hex string
     #[wasm_bindgen(js_name = "fromHex")]
     pub fn from_hex(hex: &str) -> Result<WasmChord, JsValue> {
-        let binary = hex_to_chord_binary(hex).map_err(|e| JsValue::from_str(&e.to_string()))?;
-        let chord = deserialize_chord(&binary).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let binary = hex_to_chord_binary(hex).map_err(to_js_error)?;
+        let chord = deserialize_chord(&binary).map_err(to_js_error)?;
+        Ok(WasmChord { inner: chord })
+    }
+
+    /// Serialize to human-readable JSON, keeping every field
+    #[wasm_bindgen(js_name = "toJson")]
+    pub fn to_json(&self) -> Result<String, JsValue> {
+        chord_to_json(&self.inner).map_err(to_js_error)
+    }
+
+    /// Deserialize from JSON; fields left out take their defaults
+    #[wasm_bindgen(js_name = "fromJson")]
+    pub fn from_json(json: &str) -> Result<WasmChord, JsValue> {
+        let chord = chord_from_json(json).map_err(to_js_error)?;
         Ok(WasmChord { inner: chord })
     }
 }
@@ -248,6 +661,7 @@ impl WasmNote {
 
     /// Convert to string representation
     #[wasm_bindgen(js_name = "toString")]
+    #[allow(clippy::inherent_to_string)]
     pub fn to_string(&self) -> String {
         if self.inner.is_rest {
             "REST".to_string()
@@ -263,6 +677,12 @@ pub struct WasmTimeline {
     inner: Timeline,
 }
 
+impl Default for WasmTimeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[wasm_bindgen]
 impl WasmTimeline {
     /// Create new empty timeline
@@ -272,6 +692,8 @@ impl WasmTimeline {
             inner: Timeline {
                 events: Vec::new(),
                 total_duration: 0.0,
+                meter_changes: Vec::new(),
+                key_changes: Vec::new(),
             },
         }
     }
@@ -317,6 +739,37 @@ impl WasmTimeline {
         });
         self.inner.total_duration = self.inner.total_duration.max(beat);
     }
+
+    /// Change the time signature from a beat on
+    #[wasm_bindgen(js_name = "addMeterChange")]
+    pub fn add_meter_change(&mut self, beat: f64, numerator: u8, denominator: u8) {
+        self.inner.meter_changes.push(MeterChange {
+            beat,
+            time_signature: (numerator, denominator),
+        });
+        self.inner
+            .meter_changes
+            .sort_by(|a, b| a.beat.total_cmp(&b.beat));
+    }
+
+    /// Get number of meter changes
+    #[wasm_bindgen(getter, js_name = "meterChangeCount")]
+    pub fn meter_change_count(&self) -> usize {
+        self.inner.meter_changes.len()
+    }
+
+    /// Serialize to human-readable JSON
+    #[wasm_bindgen(js_name = "toJson")]
+    pub fn to_json(&self) -> Result<String, JsValue> {
+        timeline_to_json(&self.inner).map_err(to_js_error)
+    }
+
+    /// Deserialize from JSON
+    #[wasm_bindgen(js_name = "fromJson")]
+    pub fn from_json(json: &str) -> Result<WasmTimeline, JsValue> {
+        let timeline = timeline_from_json(json).map_err(to_js_error)?;
+        Ok(WasmTimeline { inner: timeline })
+    }
 }
 
 /// WASM wrapper for ScaleFingerprint
@@ -354,8 +807,7 @@ impl WasmScaleFingerprint {
     /// Create from array
     #[wasm_bindgen(js_name = "fromArray")]
     pub fn from_array(semitones: &[u8]) -> Result<WasmScaleFingerprint, JsValue> {
-        let fingerprint = ScaleFingerprint::from_slice(semitones)
-            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let fingerprint = ScaleFingerprint::from_slice(semitones).map_err(to_js_error)?;
         Ok(WasmScaleFingerprint { inner: fingerprint })
     }
 
@@ -371,8 +823,54 @@ impl WasmScaleFingerprint {
         self.inner.is_diatonic()
     }
 
+    /// Mode starting on the note `n` steps up the scale, re-rooted on 0
+    pub fn rotate(&self, n: usize) -> WasmScaleFingerprint {
+        WasmScaleFingerprint {
+            inner: self.inner.rotate(n),
+        }
+    }
+
+    /// Notes in both scales, or undefined if they share no notes
+    pub fn intersection(&self, other: &WasmScaleFingerprint) -> Option<WasmScaleFingerprint> {
+        self.inner
+            .intersection(&other.inner)
+            .map(|inner| WasmScaleFingerprint { inner })
+    }
+
+    /// Notes in this scale but not in `other`, or undefined if there are none
+    pub fn difference(&self, other: &WasmScaleFingerprint) -> Option<WasmScaleFingerprint> {
+        self.inner
+            .difference(&other.inner)
+            .map(|inner| WasmScaleFingerprint { inner })
+    }
+
+    /// Check if every tone of a chord, read in this scale, is in the scale
+    #[wasm_bindgen(js_name = "containsChord")]
+    pub fn contains_chord(&self, chord: &WasmChord) -> bool {
+        self.inner.contains_chord(&chord.inner)
+    }
+
+    /// Number of semitones that are in one scale but not the other
+    pub fn distance(&self, other: &WasmScaleFingerprint) -> usize {
+        self.inner.distance(&other.inner)
+    }
+
+    /// Serialize to a JSON array of 12 semitone flags
+    #[wasm_bindgen(js_name = "toJson")]
+    pub fn to_json(&self) -> Result<String, JsValue> {
+        scale_fingerprint_to_json(&self.inner).map_err(to_js_error)
+    }
+
+    /// Deserialize from a JSON array of 12 semitone flags
+    #[wasm_bindgen(js_name = "fromJson")]
+    pub fn from_json(json: &str) -> Result<WasmScaleFingerprint, JsValue> {
+        let fingerprint = scale_fingerprint_from_json(json).map_err(to_js_error)?;
+        Ok(WasmScaleFingerprint { inner: fingerprint })
+    }
+
     /// Convert to string representation
     #[wasm_bindgen(js_name = "toString")]
+    #[allow(clippy::inherent_to_string)]
     pub fn to_string(&self) -> String {
         format!("{}", self.inner)
     }
@@ -468,14 +966,13 @@ pub fn get_stable_scale_degrees_wasm(
     chord: &WasmChord,
     scale: &WasmScaleFingerprint,
 ) -> Result<Vec<String>, JsValue> {
-    get_stable_scale_degrees(&chord.inner, &scale.inner)
-        .map_err(|e| JsValue::from_str(&e.to_string()))
+    get_stable_scale_degrees(&chord.inner, &scale.inner).map_err(to_js_error)
 }
 
 /// Calculate chord complexity
 #[wasm_bindgen(js_name = "getChordComplexity")]
 pub fn get_chord_complexity_wasm(chord: &WasmChord, scale_name: &str) -> Result<f64, JsValue> {
-    get_chord_complexity(&chord.inner, scale_name).map_err(|e| JsValue::from_str(&e.to_string()))
+    get_chord_complexity(&chord.inner, mode_from_js(scale_name)?).map_err(to_js_error)
 }
 
 /// Get Roman numeral representation
@@ -484,17 +981,146 @@ pub fn get_roman_numeral_wasm(
     chord: &WasmChord,
     scale: &WasmScaleFingerprint,
 ) -> Result<String, JsValue> {
-    let graphic = get_relative_chord_graphic(&chord.inner, &scale.inner)
-        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let graphic = get_relative_chord_graphic(&chord.inner, &scale.inner).map_err(to_js_error)?;
     Ok(graphic.full_symbol())
 }
 
+/// Reasons a modification ("b5", "sus4", "add9", "omit5", "/1" or "/V5")
+/// cannot be made to a chord; empty when it can
+#[wasm_bindgen(
+    js_name = "validateModification",
+    unchecked_return_type = "ModificationConflict[]"
+)]
+pub fn validate_modification_wasm(
+    chord: &WasmChord,
+    modification: &str,
+) -> Result<JsValue, JsValue> {
+    let modification = modification
+        .parse::<ChordModification>()
+        .map_err(to_js_error)?;
+    let conflicts: Vec<ConflictView> = validate_modification(&chord.inner, &modification)
+        .into_iter()
+        .map(|conflict| match conflict {
+            ModificationConflict::InvalidValue => ConflictView {
+                reason: "InvalidValue",
+                existing: None,
+            },
+            ModificationConflict::Incompatible { existing } => ConflictView {
+                reason: "Incompatible",
+                existing: Some(existing.to_string()),
+            },
+        })
+        .collect();
+    to_js_value(&conflicts)
+}
+
+/// Field-by-field validation of a chord hex string; `mode` is "strict"
+/// (default) or "lenient", which reports set reserved bits as warnings
+#[wasm_bindgen(
+    js_name = "validateChordHex",
+    unchecked_return_type = "ChordBinaryReport"
+)]
+pub fn validate_chord_hex_wasm(hex: &str, mode: Option<String>) -> Result<JsValue, JsValue> {
+    let mode = match mode.as_deref().unwrap_or("strict") {
+        "strict" => ValidationMode::Strict,
+        "lenient" => ValidationMode::Lenient,
+        other => {
+            return Err(JsValue::from_str(&format!(
+                "Invalid validation mode: {}",
+                other
+            )))
+        },
+    };
+    let report = validate_chord_hex(hex, mode).map_err(to_js_error)?;
+    to_js_value(&ChordBinaryReportView {
+        valid: report.is_valid(),
+        hex: &report.hex,
+        mode: report.mode,
+        fields: &report.fields,
+        chord_error: report.chord_error.as_deref(),
+    })
+}
+
 /// Get library version
 #[wasm_bindgen(js_name = "getVersion")]
 pub fn get_version() -> String {
     composer_config::APPLICATION.version.to_string()
 }
 
+/// Log spans for trie search, suggestion scoring and analysis to the console.
+///
+/// Each span is logged when it closes, with its fields and duration in
+/// milliseconds. `level` is one of "error", "warn", "info", "debug" (the default)
+/// or "trace"; the spans are at "debug". Returns `false` for builds without the
+/// `tracing` feature and throws if a subscriber is already installed.
+#[wasm_bindgen(js_name = "enableTracing")]
+pub fn enable_tracing(level: Option<String>) -> Result<bool, JsValue> {
+    #[cfg(feature = "tracing")]
+    {
+        let level = level.as_deref().unwrap_or("debug");
+        let filter = level
+            .parse()
+            .map_err(|_| JsValue::from_str(&format!("Unknown tracing level: {}", level)))?;
+        trace::install(filter).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(true)
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = level;
+        Ok(false)
+    }
+}
+
+/// Configure the Web Worker thread pool used for suggestion scoring.
+///
+/// Resolves to `true` once `numThreads` workers are running and parallel scoring is
+/// enabled. Builds without the `threads` feature resolve to `false` and keep scoring
+/// sequentially on the calling thread. The page must be cross-origin isolated
+/// (COOP/COEP headers) for `SharedArrayBuffer` to be available.
+#[wasm_bindgen(
+    js_name = "configureThreadPool",
+    unchecked_return_type = "Promise<boolean>"
+)]
+pub fn configure_thread_pool(num_threads: usize) -> js_sys::Promise {
+    #[cfg(feature = "threads")]
+    {
+        wasm_bindgen_futures::future_to_promise(async move {
+            if num_threads == 0 {
+                return Err(JsValue::from_str("thread pool size must be at least 1"));
+            }
+            wasm_bindgen_futures::JsFuture::from(wasm_bindgen_rayon::init_thread_pool(num_threads))
+                .await?;
+            composer_ai::set_parallel_enabled(true);
+            Ok(JsValue::TRUE)
+        })
+    }
+
+    #[cfg(not(feature = "threads"))]
+    {
+        let _ = num_threads;
+        js_sys::Promise::resolve(&JsValue::FALSE)
+    }
+}
+
+/// Enable or disable parallel scoring once a thread pool is running
+#[wasm_bindgen(js_name = "setParallelScoring")]
+pub fn set_parallel_scoring(enabled: bool) {
+    composer_ai::set_parallel_enabled(enabled);
+}
+
+/// Check if suggestion scoring runs on the thread pool
+#[wasm_bindgen(js_name = "isParallelScoringEnabled")]
+pub fn is_parallel_scoring_enabled() -> bool {
+    composer_ai::is_parallel_enabled()
+}
+
+/// Number of threads used for scoring (1 when sequential)
+#[wasm_bindgen(js_name = "getThreadPoolSize")]
+pub fn get_thread_pool_size() -> usize {
+    composer_ai::current_num_threads()
+}
+
 /// Tokenize duration for ML applications
 #[wasm_bindgen(js_name = "tokenizeDuration")]
 pub fn tokenize_duration_wasm(duration: f64) -> String {
@@ -504,7 +1130,7 @@ pub fn tokenize_duration_wasm(duration: f64) -> String {
 /// Parse duration token
 #[wasm_bindgen(js_name = "parseDurationToken")]
 pub fn parse_duration_token_wasm(token: &str) -> Result<f64, JsValue> {
-    parse_duration_token(token).map_err(|e| JsValue::from_str(&e.to_string()))
+    parse_duration_token(token).map_err(to_js_error)
 }
 
 /// WASM wrapper for AiEngine
@@ -513,6 +1139,12 @@ pub struct WasmAiEngine {
     inner: AiEngine,
 }
 
+impl Default for WasmAiEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[wasm_bindgen]
 impl WasmAiEngine {
     /// Create a new AI engine with default configuration
@@ -527,8 +1159,10 @@ impl WasmAiEngine {
     /// Create AI engine with custom memory limit
     #[wasm_bindgen(js_name = "withMemoryLimit")]
     pub fn with_memory_limit(max_memory_mb: u32) -> WasmAiEngine {
-        let mut config = AiEngineConfig::default();
-        config.max_memory_mb = max_memory_mb;
+        let config = AiEngineConfig {
+            max_memory_mb,
+            ..Default::default()
+        };
         WasmAiEngine {
             inner: AiEngine::new(config),
         }
@@ -536,7 +1170,10 @@ impl WasmAiEngine {
 
     /// Initialize the engine with training patterns
     #[wasm_bindgen(js_name = "initialize")]
-    pub fn initialize(&self, training_patterns: Vec<JsValue>) -> Result<(), JsValue> {
+    pub fn initialize(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "TrainingPattern[]")] training_patterns: Vec<JsValue>,
+    ) -> Result<(), JsValue> {
         // Convert JS training patterns to Rust format
         let mut patterns = Vec::new();
 
@@ -548,32 +1185,35 @@ impl WasmAiEngine {
             let key_val = js_sys::Reflect::get(&pattern_obj, &JsValue::from_str("key")).ok();
 
             let chords_array = chords_val.dyn_into::<js_sys::Array>()?;
-            let mut chord_pattern = Vec::new();
-
-            for i in 0..chords_array.length() {
-                let chord_js = chords_array.get(i);
-                // For now, we'll need to handle chord conversion
-                // This is a simplified approach - in practice, we'd want better serialization
-                if let Some(chord_hex) = chord_js.as_string() {
-                    let binary = hex_to_chord_binary(&chord_hex)
-                        .map_err(|e| JsValue::from_str(&e.to_string()))?;
-                    let chord = deserialize_chord(&binary)
-                        .map_err(|e| JsValue::from_str(&e.to_string()))?;
-                    chord_pattern.push(chord);
-                }
-            }
+            let chord_pattern = chords_from_js(&chords_array.to_vec())?;
 
             let name = name_val
                 .as_string()
                 .unwrap_or_else(|| "unknown".to_string());
-            let key = key_val.and_then(|v| v.as_string());
+            let key = key_from_js(key_val.and_then(|v| v.as_string()))?;
 
             patterns.push((chord_pattern, name, key));
         }
 
+        self.inner.initialize(patterns).map_err(to_js_error)
+    }
+
+    /// Initialize the engine from a pre-serialized trie asset in one call.
+    ///
+    /// Accepts the output of `exportTrieAsset()` or of composer-serialization's
+    /// `serialize_trie`. Fails, leaving the engine uninitialized, if the asset's model
+    /// version is incompatible or loading exceeds ASSET_LOADING_MAX_MS.
+    #[wasm_bindgen(js_name = "initializeFromBinary")]
+    pub fn initialize_from_binary(&self, bytes: &[u8]) -> Result<(), JsValue> {
         self.inner
-            .initialize(patterns)
-            .map_err(|e| JsValue::from_str(&format!("AI Engine initialization failed: {}", e)))
+            .initialize_from_binary(bytes)
+            .map_err(to_js_error)
+    }
+
+    /// Serialize the engine's patterns as a trie asset for `initializeFromBinary`
+    #[wasm_bindgen(js_name = "exportTrieAsset")]
+    pub fn export_trie_asset(&self) -> Result<Vec<u8>, JsValue> {
+        self.inner.export_trie_asset().map_err(to_js_error)
     }
 
     /// Check if the engine is initialized
@@ -583,86 +1223,101 @@ impl WasmAiEngine {
     }
 
     /// Get magic chord solutions
-    #[wasm_bindgen(js_name = "getMagicChordSolutions")]
+    ///
+    /// Counted against the performance budget's `uiResponseMaxMs`.
+    #[wasm_bindgen(
+        js_name = "getMagicChordSolutions",
+        unchecked_return_type = "ChordSuggestion[]"
+    )]
     pub fn get_magic_chord_solutions(
         &self,
-        previous_chords: Vec<JsValue>,
-        following_chords: Vec<JsValue>,
+        #[wasm_bindgen(unchecked_param_type = "ChordInput[]")] previous_chords: Vec<JsValue>,
+        #[wasm_bindgen(unchecked_param_type = "ChordInput[]")] following_chords: Vec<JsValue>,
         scale: &str,
         limit: usize,
-    ) -> Result<Vec<JsValue>, JsValue> {
-        // Convert JS chords to Rust chords
-        let prev_chords = self.convert_js_chords_to_rust(previous_chords)?;
-        let follow_chords = self.convert_js_chords_to_rust(following_chords)?;
-
-        let suggestions = self
-            .inner
-            .get_magic_chord_solutions(&prev_chords, &follow_chords, scale, limit)
-            .map_err(|e| JsValue::from_str(&e.to_string()))?;
-
-        // Convert suggestions to JS objects
-        let mut js_suggestions = Vec::new();
-        for suggestion in suggestions {
-            let js_suggestion = self.suggestion_to_js(&suggestion)?;
-            js_suggestions.push(js_suggestion);
-        }
-
-        Ok(js_suggestions)
+    ) -> Result<JsValue, JsValue> {
+        self.within_ui_budget(|| {
+            // Convert JS chords to Rust chords
+            let prev_chords = chords_from_js(&previous_chords)?;
+            let follow_chords = chords_from_js(&following_chords)?;
+
+            let suggestions = self
+                .inner
+                .get_magic_chord_solutions(
+                    &prev_chords,
+                    &follow_chords,
+                    mode_from_js(scale)?,
+                    limit,
+                )
+                .map_err(to_js_error)?;
+
+            self.suggestions_to_js(&suggestions, prev_chords.len() + follow_chords.len())
+        })
     }
 
     /// Get bass harmonization solutions
-    #[wasm_bindgen(js_name = "getMagicBassSolutions")]
+    ///
+    /// Counted against the performance budget's `uiResponseMaxMs`.
+    #[wasm_bindgen(
+        js_name = "getMagicBassSolutions",
+        unchecked_return_type = "ChordSuggestion[]"
+    )]
     pub fn get_magic_bass_solutions(
         &self,
         bass_note: &str,
         scale: &str,
         limit: usize,
-    ) -> Result<Vec<JsValue>, JsValue> {
-        let suggestions = self
-            .inner
-            .get_magic_bass_solutions(bass_note, scale, limit)
-            .map_err(|e| JsValue::from_str(&e.to_string()))?;
-
-        let mut js_suggestions = Vec::new();
-        for suggestion in suggestions {
-            let js_suggestion = self.suggestion_to_js(&suggestion)?;
-            js_suggestions.push(js_suggestion);
-        }
-
-        Ok(js_suggestions)
+    ) -> Result<JsValue, JsValue> {
+        self.within_ui_budget(|| {
+            let suggestions = self
+                .inner
+                .get_magic_bass_solutions(
+                    bass_note.parse().map_err(to_js_error)?,
+                    mode_from_js(scale)?,
+                    limit,
+                )
+                .map_err(to_js_error)?;
+
+            // Bass lookups match no chord context
+            self.suggestions_to_js(&suggestions, 0)
+        })
     }
 
     /// Get scale degree harmonization solutions
-    #[wasm_bindgen(js_name = "getHarmonizeBySdSolutions")]
+    ///
+    /// Counted against the performance budget's `uiResponseMaxMs`.
+    #[wasm_bindgen(
+        js_name = "getHarmonizeBySdSolutions",
+        unchecked_return_type = "ChordSuggestion[]"
+    )]
     pub fn get_harmonize_by_sd_solutions(
         &self,
         scale_degree_bits: u32,
         scale: &str,
         limit: usize,
-    ) -> Result<Vec<JsValue>, JsValue> {
-        let suggestions = self
-            .inner
-            .get_harmonize_by_sd_solutions(scale_degree_bits, scale, limit)
-            .map_err(|e| JsValue::from_str(&e.to_string()))?;
-
-        let mut js_suggestions = Vec::new();
-        for suggestion in suggestions {
-            let js_suggestion = self.suggestion_to_js(&suggestion)?;
-            js_suggestions.push(js_suggestion);
-        }
-
-        Ok(js_suggestions)
+    ) -> Result<JsValue, JsValue> {
+        self.within_ui_budget(|| {
+            let suggestions = self
+                .inner
+                .get_harmonize_by_sd_solutions(scale_degree_bits, mode_from_js(scale)?, limit)
+                .map_err(to_js_error)?;
+
+            self.suggestions_to_js(&suggestions, 0)
+        })
     }
 
     /// Assess difficulty of a chord progression
-    #[wasm_bindgen(js_name = "assessDifficulty")]
+    #[wasm_bindgen(
+        js_name = "assessDifficulty",
+        unchecked_return_type = "DifficultyAssessment"
+    )]
     pub fn assess_difficulty(
         &self,
-        progression: Vec<JsValue>,
+        #[wasm_bindgen(unchecked_param_type = "ChordInput[]")] progression: Vec<JsValue>,
         tempo_bpm: Option<f64>,
         time_signature: Option<Vec<u8>>,
     ) -> Result<JsValue, JsValue> {
-        let chords = self.convert_js_chords_to_rust(progression)?;
+        let chords = chords_from_js(&progression)?;
 
         let time_sig = time_signature.and_then(|v| {
             if v.len() >= 2 {
@@ -675,73 +1330,81 @@ impl WasmAiEngine {
         let assessment = self
             .inner
             .assess_difficulty(&chords, tempo_bpm, time_sig)
-            .map_err(|e| JsValue::from_str(&e.to_string()))?;
-
-        // Convert assessment to JS object
-        let js_obj = js_sys::Object::new();
-        js_sys::Reflect::set(
-            &js_obj,
-            &JsValue::from_str("overallScore"),
-            &JsValue::from_f64(assessment.overall_score),
-        )?;
-        js_sys::Reflect::set(
-            &js_obj,
-            &JsValue::from_str("skillLevel"),
-            &JsValue::from_str(&format!("{:?}", assessment.skill_level)),
-        )?;
-        js_sys::Reflect::set(
-            &js_obj,
-            &JsValue::from_str("confidence"),
-            &JsValue::from_f64(assessment.confidence),
-        )?;
-
-        Ok(js_obj.into())
+            .map_err(to_js_error)?;
+
+        to_js_value(&DifficultyAssessmentView::from(&assessment))
+    }
+
+    /// Assess difficulty of the chords of a timeline, following its meter changes
+    #[wasm_bindgen(
+        js_name = "assessTimelineDifficulty",
+        unchecked_return_type = "DifficultyAssessment"
+    )]
+    pub fn assess_timeline_difficulty(
+        &self,
+        timeline: &WasmTimeline,
+        tempo_bpm: Option<f64>,
+        time_signature: Option<Vec<u8>>,
+    ) -> Result<JsValue, JsValue> {
+        let time_sig = time_signature.and_then(|v| {
+            if v.len() >= 2 {
+                Some((v[0], v[1]))
+            } else {
+                None
+            }
+        });
+
+        let assessment = self
+            .inner
+            .assess_timeline_difficulty(&timeline.inner, tempo_bpm, time_sig)
+            .map_err(to_js_error)?;
+
+        to_js_value(&DifficultyAssessmentView::from(&assessment))
     }
 
     /// Get engine performance metrics
-    #[wasm_bindgen(js_name = "getMetrics")]
-    pub fn get_metrics(&self) -> JsValue {
-        let metrics = self.inner.get_metrics();
-
-        let js_obj = js_sys::Object::new();
-        js_sys::Reflect::set(
-            &js_obj,
-            &JsValue::from_str("totalRequests"),
-            &JsValue::from_f64(metrics.total_requests as f64),
-        )
-        .unwrap();
-        js_sys::Reflect::set(
-            &js_obj,
-            &JsValue::from_str("avgResponseTimeMs"),
-            &JsValue::from_f64(metrics.avg_response_time_ms),
-        )
-        .unwrap();
-        js_sys::Reflect::set(
-            &js_obj,
-            &JsValue::from_str("memoryUsageBytes"),
-            &JsValue::from_f64(metrics.memory_usage_bytes as f64),
-        )
-        .unwrap();
-        js_sys::Reflect::set(
-            &js_obj,
-            &JsValue::from_str("cacheHitRate"),
-            &JsValue::from_f64(metrics.cache_hit_rate),
-        )
-        .unwrap();
-        js_sys::Reflect::set(
-            &js_obj,
-            &JsValue::from_str("totalPatterns"),
-            &JsValue::from_f64(metrics.total_patterns as f64),
-        )
-        .unwrap();
-        js_sys::Reflect::set(
-            &js_obj,
-            &JsValue::from_str("uptimeSeconds"),
-            &JsValue::from_f64(metrics.uptime_seconds as f64),
-        )
-        .unwrap();
-
-        js_obj.into()
+    #[wasm_bindgen(js_name = "getMetrics", unchecked_return_type = "EngineMetrics")]
+    pub fn get_metrics(&self) -> Result<JsValue, JsValue> {
+        to_js_value(&EngineMetricsView::from(&self.inner.get_metrics()))
+    }
+
+    /// Set the time limits for suggestion and asset loading calls.
+    ///
+    /// Fields left out keep their current values. In "Warn" mode (the default)
+    /// slow calls return their results and are counted in the metrics'
+    /// `budgetViolations`; in "Fail" mode they throw.
+    #[wasm_bindgen(js_name = "setPerformanceBudget")]
+    pub fn set_performance_budget(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "Partial<PerformanceBudget>")] budget: JsValue,
+    ) -> Result<(), JsValue> {
+        let spec: BudgetSpec = serde_wasm_bindgen::from_value(budget)
+            .map_err(|e| JsValue::from_str(&format!("Invalid performance budget: {}", e)))?;
+        let current = self.inner.get_config().performance_budget;
+        self.inner.set_performance_budget(PerformanceBudget {
+            chord_lookup_max_ms: spec
+                .chord_lookup_max_ms
+                .unwrap_or(current.chord_lookup_max_ms),
+            asset_loading_max_ms: spec
+                .asset_loading_max_ms
+                .unwrap_or(current.asset_loading_max_ms),
+            ui_response_max_ms: spec
+                .ui_response_max_ms
+                .unwrap_or(current.ui_response_max_ms),
+            mode: spec.mode.unwrap_or(current.mode),
+        });
+        Ok(())
+    }
+
+    /// Time limits for suggestion and asset loading calls, and the budget mode
+    #[wasm_bindgen(
+        js_name = "getPerformanceBudget",
+        unchecked_return_type = "PerformanceBudget"
+    )]
+    pub fn get_performance_budget(&self) -> Result<JsValue, JsValue> {
+        to_js_value(&PerformanceBudgetView::from(
+            &self.inner.get_config().performance_budget,
+        ))
     }
 
     /// Clear all caches
@@ -750,61 +1413,53 @@ impl WasmAiEngine {
         self.inner.clear_caches();
     }
 
+    /// Compact the pattern trie, releasing unused node capacity
+    #[wasm_bindgen(js_name = "compactTrie", unchecked_return_type = "CompactionStats")]
+    pub fn compact_trie(&self) -> Result<JsValue, JsValue> {
+        to_js_value(&CompactionStatsView::from(&self.inner.compact_trie()))
+    }
+
     /// Shutdown the engine
     #[wasm_bindgen(js_name = "shutdown")]
     pub fn shutdown(&self) -> Result<(), JsValue> {
-        self.inner
-            .shutdown()
-            .map_err(|e| JsValue::from_str(&e.to_string()))
+        self.inner.shutdown().map_err(to_js_error)
     }
 
     // Helper methods for converting between JS and Rust types
-    fn convert_js_chords_to_rust(&self, js_chords: Vec<JsValue>) -> Result<Vec<Chord>, JsValue> {
-        let mut chords = Vec::new();
-
-        for js_chord in js_chords {
-            if let Some(chord_hex) = js_chord.as_string() {
-                let binary = hex_to_chord_binary(&chord_hex)
-                    .map_err(|e| JsValue::from_str(&e.to_string()))?;
-                let chord =
-                    deserialize_chord(&binary).map_err(|e| JsValue::from_str(&e.to_string()))?;
-                chords.push(chord);
-            }
-        }
-
-        Ok(chords)
+    fn suggestions_to_js(
+        &self,
+        suggestions: &[ChordSuggestion],
+        matched_context_length: usize,
+    ) -> Result<JsValue, JsValue> {
+        let views = suggestions
+            .iter()
+            .map(|suggestion| {
+                let chord_binary = serialize_chord(&suggestion.chord).map_err(to_js_error)?;
+                Ok(SuggestionView::new(
+                    suggestion,
+                    chord_binary_to_hex(&chord_binary),
+                    matched_context_length,
+                ))
+            })
+            .collect::<Result<Vec<_>, JsValue>>()?;
+
+        to_js_value(&views)
     }
+}
 
-    fn suggestion_to_js(&self, suggestion: &ChordSuggestion) -> Result<JsValue, JsValue> {
-        let js_obj = js_sys::Object::new();
-
-        // Serialize chord to hex for JS consumption
-        let chord_binary =
-            serialize_chord(&suggestion.chord).map_err(|e| JsValue::from_str(&e.to_string()))?;
-        let chord_hex = chord_binary_to_hex(&chord_binary);
-
-        js_sys::Reflect::set(
-            &js_obj,
-            &JsValue::from_str("chordHex"),
-            &JsValue::from_str(&chord_hex),
-        )?;
-        js_sys::Reflect::set(
-            &js_obj,
-            &JsValue::from_str("confidence"),
-            &JsValue::from_f64(suggestion.confidence),
-        )?;
-        js_sys::Reflect::set(
-            &js_obj,
-            &JsValue::from_str("weightedScore"),
-            &JsValue::from_f64(suggestion.weighted_score),
-        )?;
-        js_sys::Reflect::set(
-            &js_obj,
-            &JsValue::from_str("reasoning"),
-            &JsValue::from_str(&suggestion.reasoning),
-        )?;
-
-        Ok(js_obj.into())
+impl WasmAiEngine {
+    /// Run a suggestion call made from the page, thread pool included, and
+    /// check how long it took against the budget's `uiResponseMaxMs`
+    fn within_ui_budget(
+        &self,
+        call: impl FnOnce() -> Result<JsValue, JsValue>,
+    ) -> Result<JsValue, JsValue> {
+        let start = now_ms();
+        let result = call()?;
+        self.inner
+            .check_ui_response_budget(now_ms() - start)
+            .map_err(to_js_error)?;
+        Ok(result)
     }
 }
 
@@ -814,6 +1469,12 @@ pub struct WasmTrieNode {
     inner: ChordProgressionTrie,
 }
 
+impl Default for WasmTrieNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[wasm_bindgen]
 impl WasmTrieNode {
     /// Create a new empty trie
@@ -828,26 +1489,19 @@ impl WasmTrieNode {
     #[wasm_bindgen(js_name = "addPattern")]
     pub fn add_pattern(
         &self,
-        pattern: Vec<JsValue>,
+        #[wasm_bindgen(unchecked_param_type = "ChordInput[]")] pattern: Vec<JsValue>,
         source_id: &str,
         key_tonic: Option<String>,
     ) -> Result<(), JsValue> {
-        // Convert JS chord pattern to Rust chords
-        let mut chord_pattern = Vec::new();
-
-        for js_chord in pattern {
-            if let Some(chord_hex) = js_chord.as_string() {
-                let binary = hex_to_chord_binary(&chord_hex)
-                    .map_err(|e| JsValue::from_str(&e.to_string()))?;
-                let chord =
-                    deserialize_chord(&binary).map_err(|e| JsValue::from_str(&e.to_string()))?;
-                chord_pattern.push(chord);
-            }
-        }
+        let chord_pattern = chords_from_js(&pattern)?;
 
         self.inner
-            .add_pattern(&chord_pattern, source_id.to_string(), key_tonic)
-            .map_err(|e| JsValue::from_str(&e.to_string()))
+            .add_pattern(
+                &chord_pattern,
+                source_id.to_string(),
+                key_from_js(key_tonic)?,
+            )
+            .map_err(to_js_error)
     }
 
     /// Calculate ranks for all patterns
@@ -857,47 +1511,23 @@ impl WasmTrieNode {
     }
 
     /// Get trie statistics
-    #[wasm_bindgen(js_name = "getStatistics")]
-    pub fn get_statistics(&self) -> JsValue {
-        let stats = self.inner.statistics();
-        let js_obj = js_sys::Object::new();
-        js_sys::Reflect::set(
-            &js_obj,
-            &JsValue::from_str("totalPatterns"),
-            &JsValue::from_f64(stats.total_patterns as f64),
-        )
-        .unwrap();
-        js_sys::Reflect::set(
-            &js_obj,
-            &JsValue::from_str("totalNodes"),
-            &JsValue::from_f64(stats.total_nodes as f64),
-        )
-        .unwrap();
-        js_sys::Reflect::set(
-            &js_obj,
-            &JsValue::from_str("memoryUsageBytes"),
-            &JsValue::from_f64(stats.memory_usage_bytes as f64),
-        )
-        .unwrap();
-        js_sys::Reflect::set(
-            &js_obj,
-            &JsValue::from_str("scaleBranches"),
-            &JsValue::from_f64(stats.scale_branches as f64),
-        )
-        .unwrap();
-        js_sys::Reflect::set(
-            &js_obj,
-            &JsValue::from_str("maxDepth"),
-            &JsValue::from_f64(stats.max_depth as f64),
-        )
-        .unwrap();
-        js_sys::Reflect::set(
-            &js_obj,
-            &JsValue::from_str("avgBranchingFactor"),
-            &JsValue::from_f64(stats.avg_branching_factor),
-        )
-        .unwrap();
-        js_obj.into()
+    #[wasm_bindgen(js_name = "getStatistics", unchecked_return_type = "TrieStatistics")]
+    pub fn get_statistics(&self) -> Result<JsValue, JsValue> {
+        to_js_value(&TrieStatisticsView::from(&self.inner.statistics()))
+    }
+
+    /// Compact node storage, releasing unused capacity
+    #[wasm_bindgen(js_name = "compact", unchecked_return_type = "CompactionStats")]
+    pub fn compact(&self) -> Result<JsValue, JsValue> {
+        to_js_value(&CompactionStatsView::from(&self.inner.compact()))
+    }
+
+    /// Copy of the trie sharing its storage until either side adds patterns
+    #[wasm_bindgen(js_name = "snapshot")]
+    pub fn snapshot(&self) -> WasmTrieNode {
+        WasmTrieNode {
+            inner: self.inner.snapshot(),
+        }
     }
 
     /// Get total patterns stored
@@ -945,7 +1575,7 @@ pub fn parse_chord_symbol(symbol: &str, _scale_name: &str) -> Result<Vec<JsValue
         let chord_type = if symbol.contains("7") { 7 } else { 5 };
 
         if let Ok(chord) = Chord::new(root, chord_type) {
-            let binary = serialize_chord(&chord).map_err(|e| JsValue::from_str(&e.to_string()))?;
+            let binary = serialize_chord(&chord).map_err(to_js_error)?;
             let hex = chord_binary_to_hex(&binary);
             candidate_chords.push(JsValue::from_str(&hex));
         }
@@ -954,7 +1584,8 @@ pub fn parse_chord_symbol(symbol: &str, _scale_name: &str) -> Result<Vec<JsValue
     Ok(candidate_chords)
 }
 
-/// Check if two chords are isotonal (harmonically equivalent)
+/// Check if two chords sound the same pitch classes (isotonal), whatever
+/// their spelling, root or inversion
 #[wasm_bindgen(js_name = "isIsotonal")]
 pub fn is_isotonal(
     chord1_hex: &str,
@@ -962,20 +1593,13 @@ pub fn is_isotonal(
     scale: &WasmScaleFingerprint,
 ) -> Result<bool, JsValue> {
     // Deserialize chords
-    let binary1 = hex_to_chord_binary(chord1_hex).map_err(|e| JsValue::from_str(&e.to_string()))?;
-    let chord1 = deserialize_chord(&binary1).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let binary1 = hex_to_chord_binary(chord1_hex).map_err(to_js_error)?;
+    let chord1 = deserialize_chord(&binary1).map_err(to_js_error)?;
 
-    let binary2 = hex_to_chord_binary(chord2_hex).map_err(|e| JsValue::from_str(&e.to_string()))?;
-    let chord2 = deserialize_chord(&binary2).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let binary2 = hex_to_chord_binary(chord2_hex).map_err(to_js_error)?;
+    let chord2 = deserialize_chord(&binary2).map_err(to_js_error)?;
 
-    // Get scale degrees for both chords
-    let degrees1 = get_stable_scale_degrees(&chord1, &scale.inner)
-        .map_err(|e| JsValue::from_str(&e.to_string()))?;
-    let degrees2 = get_stable_scale_degrees(&chord2, &scale.inner)
-        .map_err(|e| JsValue::from_str(&e.to_string()))?;
-
-    // Simple isotonal check: same scale degrees
-    Ok(degrees1 == degrees2)
+    composer_core::is_isotonal(&chord1, &chord2, &scale.inner).map_err(to_js_error)
 }
 
 /// Utility function for debugging - log to console
@@ -988,6 +1612,7 @@ extern "C" {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use composer_ai::Reference;
     use wasm_bindgen_test::*;
 
     #[wasm_bindgen_test]
@@ -1018,6 +1643,18 @@ mod tests {
         assert_eq!(major.note_count(), 7);
     }
 
+    #[wasm_bindgen_test]
+    #[test]
+    fn test_scale_arithmetic() {
+        let major = WasmScaleFingerprint::major();
+        let minor = WasmScaleFingerprint::minor();
+        assert_eq!(major.rotate(5).inner, minor.inner);
+        assert_eq!(major.intersection(&minor).unwrap().note_count(), 4);
+        assert_eq!(major.difference(&minor).unwrap().note_count(), 3);
+        assert_eq!(major.distance(&minor), 6);
+        assert!(major.contains_chord(&WasmChord::new(5, 7).unwrap()));
+    }
+
     #[wasm_bindgen_test]
     #[test]
     fn test_stable_scale_degrees() {
@@ -1031,6 +1668,17 @@ mod tests {
         assert_eq!(degrees[2], "5");
     }
 
+    #[wasm_bindgen_test]
+    #[test]
+    fn test_is_isotonal() {
+        let hex = |chord: Chord| chord_binary_to_hex(&serialize_chord(&chord).unwrap());
+        let tonic = hex(Chord::triad(1).unwrap());
+        let first_inversion = hex(Chord::triad(1).unwrap().with_inversion(1).unwrap());
+        let scale = WasmScaleFingerprint::major();
+        assert!(is_isotonal(&tonic, &first_inversion, &scale).unwrap());
+        assert!(!is_isotonal(&tonic, &hex(Chord::triad(6).unwrap()), &scale).unwrap());
+    }
+
     #[wasm_bindgen_test]
     #[test]
     fn test_chord_complexity() {
@@ -1052,6 +1700,144 @@ mod tests {
         assert_eq!(roman, "I");
     }
 
+    #[test]
+    fn test_snake_to_camel() {
+        use crate::views::snake_to_camel;
+
+        assert_eq!(snake_to_camel("overall_score"), "overallScore");
+        assert_eq!(snake_to_camel("avg_response_time_ms"), "avgResponseTimeMs");
+        assert_eq!(snake_to_camel("confidence"), "confidence");
+    }
+
+    #[test]
+    fn test_error_details_view_renames_only_fields() {
+        let details = serde_json::json!({
+            "max_depth": 20,
+            "counts": { "song_1": 1 }
+        });
+        let converted = error_details_view(details);
+        assert_eq!(converted["maxDepth"], 20);
+        assert_eq!(converted["counts"]["song_1"], 1);
+    }
+
+    #[test]
+    fn test_assessment_view_nested_fields() {
+        let mut assessment = composer_ai::DifficultyAssessment::default();
+        assessment.explanation.reference.features = vec![composer_ai::FeatureComparison {
+            feature: "unique_chords".to_string(),
+            value: 4.0,
+            reference_mean: 3.0,
+            difference: 1.0,
+        }];
+        let converted = serde_json::to_value(DifficultyAssessmentView::from(&assessment)).unwrap();
+        let feature = &converted["explanation"]["reference"]["features"][0];
+        assert_eq!(feature["referenceMean"], 3.0);
+        assert_eq!(feature["feature"], "unique_chords");
+        assert_eq!(converted["factors"]["uniqueChords"], 0);
+        assert_eq!(converted["insufficientData"], false);
+    }
+
+    #[test]
+    fn test_suggestion_view_provenance() {
+        let suggestion = ChordSuggestion {
+            chord: Chord::new(5, 7).unwrap(),
+            confidence: 0.8,
+            frequency_score: 0.5,
+            context_score: 0.5,
+            theory_score: 0.5,
+            weighted_score: 0.6,
+            pattern_info: composer_ai::PatternResult {
+                serialized_chord: [0; 5],
+                count: 12,
+                duration_weight: 12.0,
+                rank: 2,
+                relative_count: 0.25,
+                id_list: vec![Reference {
+                    id: "song_1".to_string(),
+                    key_tonic: Some("C".parse().unwrap()),
+                }],
+                weight: 1.0,
+                branch: None,
+                mean_duration: None,
+            },
+            reasoning: String::new(),
+            recommended_inversion: None,
+            suggested_duration: None,
+        };
+        let view = SuggestionView::new(&suggestion, String::new(), 3);
+        let converted = serde_json::to_value(&view).unwrap();
+
+        assert_eq!(converted["patternCount"], 12);
+        assert_eq!(converted["rank"], 2);
+        assert_eq!(converted["relativeCount"], 0.25);
+        assert_eq!(converted["matchedContextLength"], 3);
+        assert_eq!(converted["sources"][0]["id"], "song_1");
+        assert_eq!(converted["sources"][0]["keyTonic"], "C");
+        assert_eq!(converted["weightedScore"], 0.6);
+    }
+
+    #[test]
+    fn test_chord_spec_into_chord() {
+        let spec = ChordSpec {
+            root: 5,
+            chord_type: 7,
+            inversion: 1,
+            applied: 0,
+            alterations: vec!["b9".to_string()],
+            suspensions: vec![4],
+            adds: vec![],
+            omits: vec![],
+        };
+        let chord = spec.into_chord().unwrap();
+        assert_eq!(chord.root, 5);
+        assert_eq!(chord.inversion, 1);
+        assert!(chord.has_alterations());
+        assert!(chord.has_suspensions());
+    }
+
+    #[test]
+    fn test_chord_spec_rejects_invalid() {
+        let spec = ChordSpec {
+            root: 9,
+            chord_type: 5,
+            inversion: 0,
+            applied: 0,
+            alterations: vec![],
+            suspensions: vec![],
+            adds: vec![],
+            omits: vec![],
+        };
+        assert!(spec.into_chord().is_err());
+    }
+
+    #[wasm_bindgen_test]
+    #[test]
+    fn test_initialize_from_binary() {
+        let trainer = AiEngine::new(AiEngineConfig::default());
+        trainer
+            .initialize(vec![(
+                vec![Chord::new(1, 5).unwrap(), Chord::new(5, 7).unwrap()],
+                "1".to_string(),
+                None,
+            )])
+            .unwrap();
+        let asset = trainer.export_trie_asset().unwrap();
+
+        let engine = WasmAiEngine::new();
+        engine.initialize_from_binary(&asset).unwrap();
+        assert!(engine.is_initialized());
+        assert_eq!(engine.export_trie_asset().unwrap(), asset);
+    }
+
+    #[test]
+    fn test_sequential_scoring_fallback() {
+        let was_enabled = is_parallel_scoring_enabled();
+        set_parallel_scoring(false);
+        assert!(!is_parallel_scoring_enabled());
+        assert_eq!(get_thread_pool_size(), 1);
+        set_parallel_scoring(was_enabled);
+    }
+
     #[wasm_bindgen_test]
     #[test]
     fn test_version() {
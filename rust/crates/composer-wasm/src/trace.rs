@@ -0,0 +1,122 @@
+//! Console subscriber for the trace spans emitted by composer-ai
+//!
+//! `tracing-subscriber`'s formatter times spans with `std::time::Instant`, which
+//! is unavailable on `wasm32-unknown-unknown`, so spans are timed here with
+//! `performance.now()` and logged to the console when they close.
+
+use std::fmt::Write;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::{SubscriberInitExt, TryInitError};
+use tracing_subscriber::Layer;
+use wasm_bindgen::{JsCast, JsValue};
+
+/// Fields and start time of an open span
+struct SpanTiming {
+    fields: String,
+    start_ms: f64,
+}
+
+/// Formats fields as space-separated `name=value` pairs
+#[derive(Default)]
+struct FieldWriter(String);
+
+impl Visit for FieldWriter {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if !self.0.is_empty() {
+            self.0.push(' ');
+        }
+        if field.name() == "message" {
+            let _ = write!(self.0, "{:?}", value);
+        } else {
+            let _ = write!(self.0, "{}={:?}", field.name(), value);
+        }
+    }
+}
+
+/// Logs events, and spans with their durations as they close, to the console
+struct ConsoleLayer;
+
+impl<S> Layer<S> for ConsoleLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = FieldWriter::default();
+        attrs.record(&mut fields);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanTiming {
+                fields: fields.0,
+                start_ms: now_ms(),
+            });
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = FieldWriter::default();
+        event.record(&mut fields);
+        let scope = ctx
+            .event_span(event)
+            .map_or(event.metadata().target(), |span| span.name());
+        log(
+            *event.metadata().level(),
+            &format!("{}: {}", scope, fields.0),
+        );
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let extensions = span.extensions();
+        let Some(timing) = extensions.get::<SpanTiming>() else {
+            return;
+        };
+        log(
+            *span.metadata().level(),
+            &format!(
+                "{} {:.3}ms {}",
+                span.name(),
+                now_ms() - timing.start_ms,
+                timing.fields
+            ),
+        );
+    }
+}
+
+/// High-resolution time in milliseconds, falling back to `Date.now()` where
+/// `performance` is missing
+fn now_ms() -> f64 {
+    js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("performance"))
+        .ok()
+        .and_then(|performance| {
+            let now = js_sys::Reflect::get(&performance, &JsValue::from_str("now")).ok()?;
+            now.dyn_into::<js_sys::Function>()
+                .ok()?
+                .call0(&performance)
+                .ok()?
+                .as_f64()
+        })
+        .unwrap_or_else(js_sys::Date::now)
+}
+
+fn log(level: Level, message: &str) {
+    let message = JsValue::from_str(message);
+    match level {
+        Level::ERROR => web_sys::console::error_1(&message),
+        Level::WARN => web_sys::console::warn_1(&message),
+        Level::INFO => web_sys::console::info_1(&message),
+        _ => web_sys::console::debug_1(&message),
+    }
+}
+
+/// Install the console subscriber for spans and events at `level` or above
+pub(crate) fn install(level: LevelFilter) -> Result<(), TryInitError> {
+    tracing_subscriber::registry()
+        .with(ConsoleLayer.with_filter(level))
+        .try_init()
+}
@@ -0,0 +1,466 @@
+//! JavaScript views of Rust results
+//!
+//! Each view borrows a Rust result and serializes it with the camelCase field
+//! names of its TypeScript interface in `TS_RESULT_TYPES`. Map keys are passed
+//! through as they are, so data such as source ids is never renamed.
+
+use composer_ai::{
+    BudgetMode, ChordDifficultyContribution, ChordSuggestion, CompactionStats, ComplexityFactors,
+    ConfidenceInterval, DifficultyAssessment, DifficultyExplanation, DifficultyProfile,
+    EngineMetrics, FactorContribution, FeatureComparison, MelodicAnalysis, PatternResult,
+    PerformanceBudget, Reference, ReferenceComparison, SkillLevel, TrieStatistics,
+};
+use composer_core::{Chord, Key};
+use composer_serialization::{ChordBinary, ChordBinaryField, ValidationMode};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Convert a snake_case identifier to camelCase
+pub(crate) fn snake_to_camel(key: &str) -> String {
+    let mut result = String::with_capacity(key.len());
+    let mut upper_next = false;
+
+    for c in key.chars() {
+        if c == '_' {
+            upper_next = true;
+        } else if upper_next {
+            result.extend(c.to_uppercase());
+            upper_next = false;
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+/// Error fields with camelCase names; the fields are those of the error
+/// variant, so only the top-level keys are renamed
+pub(crate) fn error_details_view(details: serde_json::Value) -> serde_json::Value {
+    match details {
+        serde_json::Value::Object(fields) => serde_json::Value::Object(
+            fields
+                .into_iter()
+                .map(|(name, value)| (snake_to_camel(&name), value))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// Reason a chord modification is rejected
+#[derive(Serialize)]
+pub(crate) struct ConflictView {
+    pub reason: &'static str,
+    pub existing: Option<String>,
+}
+
+/// Chord binary validation report; a valid `hex` decodes with `WasmChord.fromHex`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ChordBinaryReportView<'a> {
+    pub valid: bool,
+    pub hex: &'a str,
+    pub mode: ValidationMode,
+    pub fields: &'a [ChordBinaryField],
+    pub chord_error: Option<&'a str>,
+}
+
+/// Source of a stored pattern
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReferenceView<'a> {
+    id: &'a str,
+    key_tonic: Option<&'a Key>,
+}
+
+impl<'a> From<&'a Reference> for ReferenceView<'a> {
+    fn from(reference: &'a Reference) -> Self {
+        Self {
+            id: &reference.id,
+            key_tonic: reference.key_tonic.as_ref(),
+        }
+    }
+}
+
+/// Trie evidence behind a suggestion
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PatternResultView<'a> {
+    serialized_chord: &'a ChordBinary,
+    count: u32,
+    duration_weight: f64,
+    mean_duration: Option<f64>,
+    rank: u32,
+    relative_count: f64,
+    id_list: Vec<ReferenceView<'a>>,
+    weight: f64,
+    branch: Option<&'a str>,
+}
+
+impl<'a> From<&'a PatternResult> for PatternResultView<'a> {
+    fn from(pattern: &'a PatternResult) -> Self {
+        Self {
+            serialized_chord: &pattern.serialized_chord,
+            count: pattern.count,
+            duration_weight: pattern.duration_weight,
+            mean_duration: pattern.mean_duration,
+            rank: pattern.rank,
+            relative_count: pattern.relative_count,
+            id_list: pattern.id_list.iter().map(ReferenceView::from).collect(),
+            weight: pattern.weight,
+            branch: pattern.branch.as_deref(),
+        }
+    }
+}
+
+/// Chord suggestion with the chord's hex encoding and the evidence behind it
+/// lifted to the top level
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SuggestionView<'a> {
+    /// The chord in its JSON form, as from `WasmChord.toJson`
+    chord: &'a Chord,
+    chord_hex: String,
+    confidence: f64,
+    frequency_score: f64,
+    context_score: f64,
+    theory_score: f64,
+    weighted_score: f64,
+    pattern_info: PatternResultView<'a>,
+    reasoning: &'a str,
+    recommended_inversion: Option<u8>,
+    suggested_duration: Option<f64>,
+    pattern_count: u32,
+    rank: u32,
+    relative_count: f64,
+    /// Number of context chords the matched pattern shares with the query
+    matched_context_length: usize,
+    sources: Vec<ReferenceView<'a>>,
+}
+
+impl<'a> SuggestionView<'a> {
+    pub(crate) fn new(
+        suggestion: &'a ChordSuggestion,
+        chord_hex: String,
+        matched_context_length: usize,
+    ) -> Self {
+        let pattern = &suggestion.pattern_info;
+        Self {
+            chord: &suggestion.chord,
+            chord_hex,
+            confidence: suggestion.confidence,
+            frequency_score: suggestion.frequency_score,
+            context_score: suggestion.context_score,
+            theory_score: suggestion.theory_score,
+            weighted_score: suggestion.weighted_score,
+            pattern_info: PatternResultView::from(pattern),
+            reasoning: &suggestion.reasoning,
+            recommended_inversion: suggestion.recommended_inversion,
+            suggested_duration: suggestion.suggested_duration,
+            pattern_count: pattern.count,
+            rank: pattern.rank,
+            relative_count: pattern.relative_count,
+            matched_context_length,
+            sources: pattern.id_list.iter().map(ReferenceView::from).collect(),
+        }
+    }
+}
+
+/// Complexity factors of a difficulty assessment
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ComplexityFactorsView {
+    unique_chords: usize,
+    avg_chord_complexity: f64,
+    key_changes: usize,
+    uncommon_progressions: usize,
+    time_signature_changes: usize,
+    extended_harmonies: usize,
+    voice_leading_complexity: f64,
+    tempo_variations: f64,
+}
+
+impl From<&ComplexityFactors> for ComplexityFactorsView {
+    fn from(factors: &ComplexityFactors) -> Self {
+        Self {
+            unique_chords: factors.unique_chords,
+            avg_chord_complexity: factors.avg_chord_complexity,
+            key_changes: factors.key_changes,
+            uncommon_progressions: factors.uncommon_progressions,
+            time_signature_changes: factors.time_signature_changes,
+            extended_harmonies: factors.extended_harmonies,
+            voice_leading_complexity: factors.voice_leading_complexity,
+            tempo_variations: factors.tempo_variations,
+        }
+    }
+}
+
+/// Feature measured against the reference progressions of a skill level
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FeatureComparisonView<'a> {
+    feature: &'a str,
+    value: f64,
+    reference_mean: f64,
+    difference: f64,
+}
+
+impl<'a> From<&'a FeatureComparison> for FeatureComparisonView<'a> {
+    fn from(comparison: &'a FeatureComparison) -> Self {
+        Self {
+            feature: &comparison.feature,
+            value: comparison.value,
+            reference_mean: comparison.reference_mean,
+            difference: comparison.difference,
+        }
+    }
+}
+
+/// Comparison with the reference progressions of the assessed skill level
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReferenceComparisonView<'a> {
+    skill_level: SkillLevel,
+    references: &'a [String],
+    reference_score: f64,
+    score_difference: f64,
+    features: Vec<FeatureComparisonView<'a>>,
+}
+
+impl<'a> From<&'a ReferenceComparison> for ReferenceComparisonView<'a> {
+    fn from(comparison: &'a ReferenceComparison) -> Self {
+        Self {
+            skill_level: comparison.skill_level,
+            references: &comparison.references,
+            reference_score: comparison.reference_score,
+            score_difference: comparison.score_difference,
+            features: comparison
+                .features
+                .iter()
+                .map(FeatureComparisonView::from)
+                .collect(),
+        }
+    }
+}
+
+/// Why a progression received its difficulty score
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DifficultyExplanationView<'a> {
+    top_factors: &'a [FactorContribution],
+    reference: ReferenceComparisonView<'a>,
+}
+
+impl<'a> From<&'a DifficultyExplanation> for DifficultyExplanationView<'a> {
+    fn from(explanation: &'a DifficultyExplanation) -> Self {
+        Self {
+            top_factors: &explanation.top_factors,
+            reference: ReferenceComparisonView::from(&explanation.reference),
+        }
+    }
+}
+
+/// One chord's contribution to the difficulty of its progression
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ChordDifficultyContributionView {
+    index: usize,
+    complexity: f64,
+    voice_leading_cost: f64,
+    novelty: f64,
+}
+
+impl From<&ChordDifficultyContribution> for ChordDifficultyContributionView {
+    fn from(contribution: &ChordDifficultyContribution) -> Self {
+        Self {
+            index: contribution.index,
+            complexity: contribution.complexity,
+            voice_leading_cost: contribution.voice_leading_cost,
+            novelty: contribution.novelty,
+        }
+    }
+}
+
+/// Melody the melodic complexity was measured from
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MelodicAnalysisView<'a> {
+    note_count: usize,
+    lowest_pitch: u8,
+    highest_pitch: u8,
+    range: u8,
+    tessitura: (u8, u8),
+    mean_pitch: f64,
+    interval_histogram: &'a [u32],
+    contour_entropy: f64,
+    rhythm_density: f64,
+    complexity: f64,
+}
+
+impl<'a> From<&'a MelodicAnalysis> for MelodicAnalysisView<'a> {
+    fn from(melody: &'a MelodicAnalysis) -> Self {
+        Self {
+            note_count: melody.note_count,
+            lowest_pitch: melody.lowest_pitch,
+            highest_pitch: melody.highest_pitch,
+            range: melody.range,
+            tessitura: melody.tessitura,
+            mean_pitch: melody.mean_pitch,
+            interval_histogram: &melody.interval_histogram,
+            contour_entropy: melody.contour_entropy,
+            rhythm_density: melody.rhythm_density,
+            complexity: melody.complexity,
+        }
+    }
+}
+
+/// Difficulty assessment of a progression
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DifficultyAssessmentView<'a> {
+    overall_score: f64,
+    harmonic_complexity: f64,
+    rhythmic_complexity: f64,
+    technical_complexity: f64,
+    melodic_complexity: f64,
+    factors: ComplexityFactorsView,
+    confidence: f64,
+    score_interval: ConfidenceInterval,
+    evidence: f64,
+    insufficient_data: bool,
+    skill_level: SkillLevel,
+    skill_band: &'a str,
+    explanation: DifficultyExplanationView<'a>,
+    instrument: Option<DifficultyProfile>,
+    percentile: Option<u8>,
+    per_chord_breakdown: Vec<ChordDifficultyContributionView>,
+    melody: Option<MelodicAnalysisView<'a>>,
+}
+
+impl<'a> From<&'a DifficultyAssessment> for DifficultyAssessmentView<'a> {
+    fn from(assessment: &'a DifficultyAssessment) -> Self {
+        Self {
+            overall_score: assessment.overall_score,
+            harmonic_complexity: assessment.harmonic_complexity,
+            rhythmic_complexity: assessment.rhythmic_complexity,
+            technical_complexity: assessment.technical_complexity,
+            melodic_complexity: assessment.melodic_complexity,
+            factors: ComplexityFactorsView::from(&assessment.factors),
+            confidence: assessment.confidence,
+            score_interval: assessment.score_interval,
+            evidence: assessment.evidence,
+            insufficient_data: assessment.insufficient_data,
+            skill_level: assessment.skill_level,
+            skill_band: &assessment.skill_band,
+            explanation: DifficultyExplanationView::from(&assessment.explanation),
+            instrument: assessment.instrument,
+            percentile: assessment.percentile,
+            per_chord_breakdown: assessment
+                .per_chord_breakdown
+                .iter()
+                .map(ChordDifficultyContributionView::from)
+                .collect(),
+            melody: assessment.melody.as_ref().map(MelodicAnalysisView::from),
+        }
+    }
+}
+
+/// Engine performance metrics
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EngineMetricsView {
+    total_requests: u64,
+    avg_response_time_ms: f64,
+    memory_usage_bytes: u64,
+    cache_hit_rate: f64,
+    total_patterns: u64,
+    uptime_seconds: u64,
+    /// Violation counts keyed by camelCased operation name
+    budget_violations: BTreeMap<String, u64>,
+}
+
+impl From<&EngineMetrics> for EngineMetricsView {
+    fn from(metrics: &EngineMetrics) -> Self {
+        Self {
+            total_requests: metrics.total_requests,
+            avg_response_time_ms: metrics.avg_response_time_ms,
+            memory_usage_bytes: metrics.memory_usage_bytes,
+            cache_hit_rate: metrics.cache_hit_rate,
+            total_patterns: metrics.total_patterns,
+            uptime_seconds: metrics.uptime_seconds,
+            budget_violations: metrics
+                .budget_violations
+                .iter()
+                .map(|(operation, count)| (snake_to_camel(operation), *count))
+                .collect(),
+        }
+    }
+}
+
+/// Time limits for suggestion and asset loading calls
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PerformanceBudgetView {
+    chord_lookup_max_ms: u64,
+    asset_loading_max_ms: u64,
+    ui_response_max_ms: u64,
+    mode: BudgetMode,
+}
+
+impl From<&PerformanceBudget> for PerformanceBudgetView {
+    fn from(budget: &PerformanceBudget) -> Self {
+        Self {
+            chord_lookup_max_ms: budget.chord_lookup_max_ms,
+            asset_loading_max_ms: budget.asset_loading_max_ms,
+            ui_response_max_ms: budget.ui_response_max_ms,
+            mode: budget.mode,
+        }
+    }
+}
+
+/// Size and shape of a pattern trie
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TrieStatisticsView {
+    total_patterns: u64,
+    total_nodes: u64,
+    memory_usage_bytes: u64,
+    scale_branches: usize,
+    max_depth: u32,
+    avg_branching_factor: f64,
+    duplicate_patterns: u64,
+}
+
+impl From<&TrieStatistics> for TrieStatisticsView {
+    fn from(statistics: &TrieStatistics) -> Self {
+        Self {
+            total_patterns: statistics.total_patterns,
+            total_nodes: statistics.total_nodes,
+            memory_usage_bytes: statistics.memory_usage_bytes,
+            scale_branches: statistics.scale_branches,
+            max_depth: statistics.max_depth,
+            avg_branching_factor: statistics.avg_branching_factor,
+            duplicate_patterns: statistics.duplicate_patterns,
+        }
+    }
+}
+
+/// Effect of compacting a pattern trie
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CompactionStatsView {
+    nodes: u64,
+    bytes_before: u64,
+    bytes_after: u64,
+}
+
+impl From<&CompactionStats> for CompactionStatsView {
+    fn from(stats: &CompactionStats) -> Self {
+        Self {
+            nodes: stats.nodes,
+            bytes_before: stats.bytes_before,
+            bytes_after: stats.bytes_after,
+        }
+    }
+}